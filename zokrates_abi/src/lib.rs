@@ -15,6 +15,7 @@ impl<T: Field> Encode<T> for Inputs<T> {
 }
 
 use std::fmt;
+use zokrates_ast::typed::abi::AbiInput;
 use zokrates_ast::typed::types::{ConcreteType, UBitwidth};
 
 use zokrates_field::Field;
@@ -24,6 +25,7 @@ pub enum Error {
     Json(String),
     Conversion(String),
     Type(String),
+    Path(String),
 }
 
 impl fmt::Display for Error {
@@ -32,16 +34,20 @@ impl fmt::Display for Error {
             Error::Json(e) => write!(f, "Invalid JSON: {}", e),
             Error::Conversion(e) => write!(f, "Invalid ZoKrates values: {}", e),
             Error::Type(e) => write!(f, "Type error: {}", e),
+            Error::Path(e) => write!(f, "Invalid path: {}", e),
         }
     }
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum Value<T> {
     U8(u8),
     U16(u16),
     U32(u32),
     U64(u64),
+    I8(i8),
+    I16(i16),
+    I32(i32),
     Field(T),
     Boolean(bool),
     Array(Vec<Value<T>>),
@@ -60,6 +66,9 @@ impl<T: Field> fmt::Display for Value<T> {
             Value::U16(v) => write!(f, "{:#06x}", v),
             Value::U32(v) => write!(f, "{:#010x}", v),
             Value::U64(v) => write!(f, "{:#018x}", v),
+            Value::I8(v) => write!(f, "{}", v),
+            Value::I16(v) => write!(f, "{}", v),
+            Value::I32(v) => write!(f, "{}", v),
             Value::Boolean(v) => write!(f, "{}", v),
             Value::Array(v) => write!(
                 f,
@@ -115,6 +124,10 @@ impl<T: Field> Encode<T> for Value<T> {
             Value::U16(v) => vec![T::from(v)],
             Value::U32(v) => vec![T::from(v)],
             Value::U64(v) => vec![T::from(v)],
+            // signed integers are encoded in two's complement
+            Value::I8(v) => vec![T::from(v as u8)],
+            Value::I16(v) => vec![T::from(v as u16)],
+            Value::I32(v) => vec![T::from(v as u32)],
             Value::Boolean(b) => vec![T::from(b)],
             Value::Array(a) => a.into_iter().flat_map(|v| v.encode()).collect(),
             Value::Tuple(t) => t.into_iter().flat_map(|v| v.encode()).collect(),
@@ -162,6 +175,15 @@ impl<T: Field> Decode<T> for Value<T> {
             ConcreteType::Uint(UBitwidth::B64) => {
                 Value::U64(raw.pop().unwrap().to_dec_string().parse().unwrap())
             }
+            ConcreteType::Uint(UBitwidth::I8) => {
+                Value::I8(raw.pop().unwrap().to_dec_string().parse::<u8>().unwrap() as i8)
+            }
+            ConcreteType::Uint(UBitwidth::I16) => {
+                Value::I16(raw.pop().unwrap().to_dec_string().parse::<u16>().unwrap() as i16)
+            }
+            ConcreteType::Uint(UBitwidth::I32) => {
+                Value::I32(raw.pop().unwrap().to_dec_string().parse::<u32>().unwrap() as i32)
+            }
             ConcreteType::Boolean => {
                 let v = raw.pop().unwrap();
                 Value::Boolean(if v == 0.into() {
@@ -218,6 +240,9 @@ impl<T: Field> Value<T> {
             Value::U16(u) => serde_json::Value::String(format!("{:#06x}", u)),
             Value::U32(u) => serde_json::Value::String(format!("{:#010x}", u)),
             Value::U64(u) => serde_json::Value::String(format!("{:#018x}", u)),
+            Value::I8(i) => serde_json::Value::String(i.to_string()),
+            Value::I16(i) => serde_json::Value::String(i.to_string()),
+            Value::I32(i) => serde_json::Value::String(i.to_string()),
             Value::Boolean(b) => serde_json::Value::Bool(b),
             Value::Array(a) => {
                 serde_json::Value::Array(a.into_iter().map(|e| e.into_serde_json()).collect())
@@ -275,6 +300,31 @@ fn parse_value<T: Field>(
             .or_else(|_| u64::from_str_radix(s.as_str().trim_start_matches("0x"), 16))
             .map(Value::U64)
             .map_err(|_| Error::Type(format!("Could not parse `{}` to u64 type", s))),
+        // signed integers are written in decimal, or in hexadecimal as their two's complement representation
+        (ConcreteType::Uint(UBitwidth::I8), serde_json::Value::String(s)) => s
+            .as_str()
+            .parse::<i8>()
+            .or_else(|_| {
+                u8::from_str_radix(s.as_str().trim_start_matches("0x"), 16).map(|u| u as i8)
+            })
+            .map(Value::I8)
+            .map_err(|_| Error::Type(format!("Could not parse `{}` to i8 type", s))),
+        (ConcreteType::Uint(UBitwidth::I16), serde_json::Value::String(s)) => s
+            .as_str()
+            .parse::<i16>()
+            .or_else(|_| {
+                u16::from_str_radix(s.as_str().trim_start_matches("0x"), 16).map(|u| u as i16)
+            })
+            .map(Value::I16)
+            .map_err(|_| Error::Type(format!("Could not parse `{}` to i16 type", s))),
+        (ConcreteType::Uint(UBitwidth::I32), serde_json::Value::String(s)) => s
+            .as_str()
+            .parse::<i32>()
+            .or_else(|_| {
+                u32::from_str_radix(s.as_str().trim_start_matches("0x"), 16).map(|u| u as i32)
+            })
+            .map(Value::I32)
+            .map_err(|_| Error::Type(format!("Could not parse `{}` to i32 type", s))),
         (ConcreteType::Boolean, serde_json::Value::Bool(b)) => Ok(Value::Boolean(b)),
         (ConcreteType::Array(array_type), serde_json::Value::Array(a)) => {
             let size = *array_type.size;
@@ -377,6 +427,128 @@ pub fn parse_strict_json<T: Field>(
     ))
 }
 
+impl<T: Field> Value<T> {
+    /// The default value of type `ty`: zero for numbers, `false` for booleans, and the default values of the
+    /// elements of arrays, structs and tuples
+    pub fn default(ty: &ConcreteType) -> Self {
+        match ty {
+            ConcreteType::Int => unreachable!(),
+            ConcreteType::FieldElement => Value::Field(T::zero()),
+            ConcreteType::Uint(UBitwidth::B8) => Value::U8(0),
+            ConcreteType::Uint(UBitwidth::B16) => Value::U16(0),
+            ConcreteType::Uint(UBitwidth::B32) => Value::U32(0),
+            ConcreteType::Uint(UBitwidth::B64) => Value::U64(0),
+            ConcreteType::Uint(UBitwidth::I8) => Value::I8(0),
+            ConcreteType::Uint(UBitwidth::I16) => Value::I16(0),
+            ConcreteType::Uint(UBitwidth::I32) => Value::I32(0),
+            ConcreteType::Boolean => Value::Boolean(false),
+            ConcreteType::Array(array_type) => Value::Array(vec![
+                Value::default(&array_type.ty);
+                *array_type.size as usize
+            ]),
+            ConcreteType::Struct(struct_type) => Value::Struct(
+                struct_type
+                    .members
+                    .iter()
+                    .map(|m| (m.id.clone(), Value::default(&m.ty)))
+                    .collect(),
+            ),
+            ConcreteType::Tuple(tuple_type) => {
+                Value::Tuple(tuple_type.elements.iter().map(Value::default).collect())
+            }
+        }
+    }
+}
+
+/// The default values of `inputs`
+pub fn defaults<T: Field>(inputs: &[AbiInput]) -> Values<T> {
+    Values(inputs.iter().map(|i| Value::default(&i.ty)).collect())
+}
+
+impl<T: Field> Values<T> {
+    /// Replace the value at `path` in the values of `inputs` by `value`, given in the ABI input format. Quotes can be
+    /// omitted around numbers.
+    ///
+    /// A path starts with the name of an input, followed by the names of struct members and the indices of array or
+    /// tuple elements, separated by dots, for example `foo.b.a` or `bar.1`
+    pub fn set(&mut self, inputs: &[AbiInput], path: &str, value: &str) -> Result<(), Error> {
+        let mut segments = path.split('.');
+        let name = segments.next().unwrap();
+
+        let index = inputs.iter().position(|i| i.name == name).ok_or_else(|| {
+            Error::Path(format!("`{}`: the program has no input `{}`", path, name))
+        })?;
+
+        let (target, ty) = segments.try_fold(
+            (&mut self.0[index], inputs[index].ty.clone()),
+            |(target, ty), segment| member(target, ty, segment, path),
+        )?;
+
+        let value = match serde_json::from_str(value) {
+            Ok(serde_json::Value::Number(_)) | Err(_) => serde_json::Value::String(value.into()),
+            Ok(value) => value,
+        };
+
+        *target = parse_value(value, ty).map_err(|e| match e {
+            Error::Type(e) => Error::Type(format!("{} at `{}`", e, path)),
+            e => e,
+        })?;
+
+        Ok(())
+    }
+}
+
+/// The element `segment` of `value` of type `ty`, along with its type
+fn member<'a, T>(
+    value: &'a mut Value<T>,
+    ty: ConcreteType,
+    segment: &str,
+    path: &str,
+) -> Result<(&'a mut Value<T>, ConcreteType), Error> {
+    let index = |len: usize| {
+        segment
+            .parse::<usize>()
+            .ok()
+            .filter(|i| *i < len)
+            .ok_or_else(|| {
+                Error::Path(format!(
+                    "`{}`: expected an index lower than {}, found `{}`",
+                    path, len, segment
+                ))
+            })
+    };
+
+    match (ty, value) {
+        (ConcreteType::Array(array_type), Value::Array(elements)) => {
+            let i = index(elements.len())?;
+            Ok((&mut elements[i], *array_type.ty))
+        }
+        (ConcreteType::Tuple(tuple_type), Value::Tuple(elements)) => {
+            let i = index(elements.len())?;
+            Ok((&mut elements[i], tuple_type.elements[i].clone()))
+        }
+        (ConcreteType::Struct(struct_type), Value::Struct(members)) => {
+            let i = struct_type
+                .members
+                .iter()
+                .position(|m| m.id == segment)
+                .ok_or_else(|| {
+                    Error::Path(format!(
+                        "`{}`: struct `{}` has no member `{}`",
+                        path,
+                        struct_type.name(),
+                        segment
+                    ))
+                })?;
+            Ok((&mut members[i].1, *struct_type.members[i].ty.clone()))
+        }
+        (ty, _) => Err(Error::Path(format!(
+            "`{}`: a value of type `{}` has no element `{}`",
+            path, ty, segment
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -411,6 +583,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn fields_out_of_range() {
+        // the modulus of bn128, in decimal and in hexadecimal
+        for s in [
+            r#"["21888242871839275222246405745257275088548364400416034343698204186575808495617"]"#,
+            r#"["0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001"]"#,
+        ] {
+            assert!(matches!(
+                parse_strict::<Bn128Field>(s, vec![ConcreteType::FieldElement]).unwrap_err(),
+                Error::Type(_)
+            ));
+        }
+
+        let s =
+            r#"["21888242871839275222246405745257275088548364400416034343698204186575808495616"]"#;
+        assert_eq!(
+            parse_strict::<Bn128Field>(s, vec![ConcreteType::FieldElement]).unwrap(),
+            Values(vec![Value::Field(Bn128Field::max_value())])
+        );
+    }
+
     #[test]
     fn uints() {
         let s = r#"["0x12", "0x1234", "0x12345678", "0x1234567812345678"]"#;
@@ -446,6 +639,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn signed() {
+        let s = r#"["-128", "0x8000", "2147483647"]"#;
+        let types = vec![
+            ConcreteType::Uint(UBitwidth::I8),
+            ConcreteType::Uint(UBitwidth::I16),
+            ConcreteType::Uint(UBitwidth::I32),
+        ];
+        let values = parse_strict::<Bn128Field>(s, types.clone()).unwrap();
+        assert_eq!(
+            values,
+            Values(vec![
+                Value::I8(-128),
+                Value::I16(-32768),
+                Value::I32(2147483647)
+            ])
+        );
+
+        // signed integers are encoded in two's complement
+        let encoded = values.encode();
+        assert_eq!(
+            encoded,
+            vec![
+                Bn128Field::from(128),
+                Bn128Field::from(32768),
+                Bn128Field::from(2147483647)
+            ]
+        );
+        assert_eq!(
+            Values::decode(encoded, types).into_serde_json().to_string(),
+            r#"["-128","-32768","2147483647"]"#
+        );
+
+        let s = r#"["128"]"#;
+        assert_eq!(
+            parse_strict::<Bn128Field>(s, vec![ConcreteType::Uint(UBitwidth::I8)]).unwrap_err(),
+            Error::Type("Could not parse `128` to i8 type".into())
+        );
+    }
+
     #[test]
     fn bools() {
         let s = "[true, false]";
@@ -584,6 +817,122 @@ mod tests {
         )
     }
 
+    mod defaults {
+        use super::*;
+        use zokrates_ast::typed::types::ConcreteTupleType;
+
+        // def main(Foo foo, (bool, u32[2]) bar) where struct Foo { u8 a; Bar b; } and struct Bar { field a; }
+        fn inputs() -> Vec<AbiInput> {
+            let bar = ConcreteType::Struct(ConcreteStructType::new(
+                "".into(),
+                "Bar".into(),
+                vec![],
+                vec![ConcreteStructMember::new(
+                    "a".into(),
+                    ConcreteType::FieldElement,
+                )],
+            ));
+            let foo = ConcreteType::Struct(ConcreteStructType::new(
+                "".into(),
+                "Foo".into(),
+                vec![],
+                vec![
+                    ConcreteStructMember::new("a".into(), ConcreteType::Uint(UBitwidth::B8)),
+                    ConcreteStructMember::new("b".into(), bar),
+                ],
+            ));
+
+            vec![
+                AbiInput {
+                    name: "foo".into(),
+                    public: true,
+                    ty: foo,
+                },
+                AbiInput {
+                    name: "bar".into(),
+                    public: false,
+                    ty: ConcreteType::Tuple(ConcreteTupleType::new(vec![
+                        ConcreteType::Boolean,
+                        ConcreteType::array((ConcreteType::Uint(UBitwidth::B32), 2u32)),
+                    ])),
+                },
+            ]
+        }
+
+        #[test]
+        fn zeros() {
+            let values = defaults::<Bn128Field>(&inputs());
+
+            assert_eq!(
+                values.into_serde_json().to_string(),
+                r#"[{"a":"0x00","b":{"a":"0"}},[false,["0x00000000","0x00000000"]]]"#
+            );
+        }
+
+        #[test]
+        fn nested_overrides() {
+            let inputs = inputs();
+            let mut values = defaults::<Bn128Field>(&inputs);
+
+            values.set(&inputs, "foo.b.a", "42").unwrap();
+            values.set(&inputs, "foo.a", "0x2a").unwrap();
+            values.set(&inputs, "bar.0", "true").unwrap();
+            values.set(&inputs, "bar.1.1", "7").unwrap();
+            // composite values are given as JSON
+            values.set(&inputs, "foo.b", r#"{"a": "3"}"#).unwrap();
+
+            assert_eq!(
+                values.into_serde_json().to_string(),
+                r#"[{"a":"0x2a","b":{"a":"3"}},[true,["0x00000000","0x00000007"]]]"#
+            );
+        }
+
+        #[test]
+        fn type_mismatch() {
+            let inputs = inputs();
+            let mut values = defaults::<Bn128Field>(&inputs);
+
+            assert_eq!(
+                values.set(&inputs, "foo.a", "256").unwrap_err(),
+                Error::Type("Could not parse `256` to u8 type at `foo.a`".into())
+            );
+            assert_eq!(
+                values.set(&inputs, "bar.0", "1").unwrap_err(),
+                Error::Type("Value `\"1\"` doesn't match expected type `bool` at `bar.0`".into())
+            );
+            assert_eq!(
+                values.set(&inputs, "foo.b", "[]").unwrap_err(),
+                Error::Type("Value `[]` doesn't match expected type `Bar` at `foo.b`".into())
+            );
+
+            // the values are left unchanged
+            assert_eq!(values, defaults(&inputs));
+        }
+
+        #[test]
+        fn bad_paths() {
+            let inputs = inputs();
+            let mut values = defaults::<Bn128Field>(&inputs);
+
+            assert_eq!(
+                values.set(&inputs, "baz", "1").unwrap_err(),
+                Error::Path("`baz`: the program has no input `baz`".into())
+            );
+            assert_eq!(
+                values.set(&inputs, "foo.c", "1").unwrap_err(),
+                Error::Path("`foo.c`: struct `Foo` has no member `c`".into())
+            );
+            assert_eq!(
+                values.set(&inputs, "bar.1.2", "1").unwrap_err(),
+                Error::Path("`bar.1.2`: expected an index lower than 2, found `2`".into())
+            );
+            assert_eq!(
+                values.set(&inputs, "foo.a.b", "1").unwrap_err(),
+                Error::Path("`foo.a.b`: a value of type `u8` has no element `b`".into())
+            );
+        }
+    }
+
     mod encode {
         use super::*;
 
@@ -33,7 +33,9 @@ impl<'ast, T: Field> ResultFolder<'ast, T> for AssemblyTransformer {
         s: ZirAssemblyStatement<'ast, T>,
     ) -> Result<Vec<ZirAssemblyStatement<'ast, T>>, Self::Error> {
         match s {
-            ZirAssemblyStatement::Assignment(_, _) => Ok(vec![s]),
+            ZirAssemblyStatement::Assignment(..) | ZirAssemblyStatement::UnsafeWitness(..) => {
+                Ok(vec![s])
+            }
             ZirAssemblyStatement::Constraint(lhs, rhs, metadata) => {
                 let lhs = self.fold_field_expression(lhs)?;
                 let rhs = self.fold_field_expression(rhs)?;
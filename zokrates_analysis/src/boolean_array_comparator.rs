@@ -1,7 +1,7 @@
 use zokrates_ast::typed::{
     folder::*, ArrayExpressionInner, ArrayValue, BooleanExpression, ConditionalExpression,
-    ConditionalKind, EqExpression, FieldElementExpression, SelectExpression, Type, TypedExpression,
-    TypedProgram, UExpressionInner,
+    ConditionalKind, EqExpression, FieldElementExpression, RuntimeError, SelectExpression, Type,
+    TypedExpression, TypedProgram, TypedStatement, UExpressionInner,
 };
 use zokrates_field::Field;
 
@@ -15,6 +15,22 @@ impl BooleanArrayComparator {
 }
 
 impl<'ast, T: Field> Folder<'ast, T> for BooleanArrayComparator {
+    fn fold_statement(&mut self, s: TypedStatement<'ast, T>) -> Vec<TypedStatement<'ast, T>> {
+        let s = match s {
+            // boolean arrays are compared by chunks, so a failure cannot point at an element of the source
+            TypedStatement::Assertion(
+                BooleanExpression::ArrayEq(e),
+                RuntimeError::SourceAssertion(metadata),
+            ) if *e.left.inner_type() == Type::Boolean => TypedStatement::Assertion(
+                BooleanExpression::ArrayEq(e),
+                RuntimeError::SourceAssertion(metadata.element(None)),
+            ),
+            s => s,
+        };
+
+        fold_statement(self, s)
+    }
+
     fn fold_boolean_expression(
         &mut self,
         e: BooleanExpression<'ast, T>,
@@ -0,0 +1,197 @@
+//! Module containing the call graph of a typed program, used to remove the functions which cannot be reached from
+//! `main` before the program is reduced
+//!
+//! Calls are recorded per function declaration rather than per instantiation: a call to a generic function makes
+//! the function reachable for any value of its generic parameters, which is conservative.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use zokrates_ast::typed::{
+    folder::*, DeclarationFunctionKey, Expr, FunctionCall, FunctionCallExpression,
+    FunctionCallOrExpression, Id, TypedExpression, TypedFunctionSymbol,
+    TypedFunctionSymbolDeclaration, TypedModule, TypedProgram, TypedStatement,
+    TypedSymbolDeclaration,
+};
+use zokrates_field::Field;
+
+pub struct CallGraph<'ast, T> {
+    /// The number of statements of each function, including the statements nested in loops
+    statements: BTreeMap<DeclarationFunctionKey<'ast, T>, usize>,
+    /// The functions called by each function. An imported function calls the function it refers to
+    calls: BTreeMap<DeclarationFunctionKey<'ast, T>, BTreeSet<DeclarationFunctionKey<'ast, T>>>,
+    /// The functions which are always reachable: `main`, and the functions called to compute constants
+    roots: BTreeSet<DeclarationFunctionKey<'ast, T>>,
+}
+
+impl<'ast, T: Field> CallGraph<'ast, T> {
+    /// Build the call graph of `p`, which is returned unchanged
+    pub fn build(p: TypedProgram<'ast, T>) -> (TypedProgram<'ast, T>, Self) {
+        let mut builder = CallGraphBuilder {
+            graph: CallGraph {
+                statements: BTreeMap::new(),
+                calls: BTreeMap::new(),
+                roots: BTreeSet::new(),
+            },
+            current: None,
+        };
+
+        let p = builder.fold_program(p);
+
+        let mut graph = builder.graph;
+        let main = graph
+            .statements
+            .keys()
+            .filter(|key| key.module == p.main && key.id == "main")
+            .cloned()
+            .collect::<Vec<_>>();
+        graph.roots.extend(main);
+
+        (p, graph)
+    }
+
+    /// The functions reachable from the roots of the graph
+    pub fn reachable(&self) -> BTreeSet<&DeclarationFunctionKey<'ast, T>> {
+        let mut reachable = BTreeSet::new();
+        let mut to_visit: Vec<_> = self.roots.iter().collect();
+
+        while let Some(key) = to_visit.pop() {
+            if reachable.insert(key) {
+                to_visit.extend(self.calls.get(key).into_iter().flatten());
+            }
+        }
+
+        reachable
+    }
+
+    /// Remove the functions of `p` which are not reachable
+    pub fn prune(&self, p: TypedProgram<'ast, T>) -> TypedProgram<'ast, T> {
+        let reachable = self.reachable();
+
+        TypedProgram {
+            modules: p
+                .modules
+                .into_iter()
+                .map(|(id, module)| {
+                    (
+                        id,
+                        TypedModule {
+                            symbols: module
+                                .symbols
+                                .into_iter()
+                                .filter(|s| match s {
+                                    TypedSymbolDeclaration::Function(d) => {
+                                        reachable.contains(&d.key)
+                                    }
+                                    TypedSymbolDeclaration::Constant(..) => true,
+                                })
+                                .collect(),
+                        },
+                    )
+                })
+                .collect(),
+            ..p
+        }
+    }
+
+    /// Render the graph in the Graphviz DOT format. Each function is labelled with its number of statements, and the
+    /// functions which are not reachable are dashed
+    pub fn to_dot(&self) -> String {
+        let reachable = self.reachable();
+        let escape = |s: String| s.replace('\\', "\\\\").replace('"', "\\\"");
+
+        let mut res = String::from("digraph calls {\n");
+        for (key, count) in &self.statements {
+            writeln!(
+                res,
+                "    \"{}\" [label=\"{}/{}\\n{} statement{}\"{}];",
+                escape(key.to_string()),
+                escape(key.module.display().to_string()),
+                key.id,
+                count,
+                if *count == 1 { "" } else { "s" },
+                if reachable.contains(key) {
+                    ""
+                } else {
+                    ", style=dashed"
+                }
+            )
+            .unwrap();
+        }
+        for (caller, callees) in &self.calls {
+            for callee in callees {
+                writeln!(
+                    res,
+                    "    \"{}\" -> \"{}\";",
+                    escape(caller.to_string()),
+                    escape(callee.to_string())
+                )
+                .unwrap();
+            }
+        }
+        res.push_str("}\n");
+        res
+    }
+}
+
+struct CallGraphBuilder<'ast, T> {
+    graph: CallGraph<'ast, T>,
+    /// The function being visited, if any
+    current: Option<DeclarationFunctionKey<'ast, T>>,
+}
+
+impl<'ast, T: Field> Folder<'ast, T> for CallGraphBuilder<'ast, T> {
+    fn fold_function_symbol_declaration(
+        &mut self,
+        s: TypedFunctionSymbolDeclaration<'ast, T>,
+    ) -> TypedFunctionSymbolDeclaration<'ast, T> {
+        self.graph.statements.insert(s.key.clone(), 0);
+        if let TypedFunctionSymbol::There(key) = &s.symbol {
+            self.graph
+                .calls
+                .entry(s.key.clone())
+                .or_default()
+                .insert(key.clone());
+        }
+
+        self.current = Some(s.key.clone());
+        let s = fold_function_symbol_declaration(self, s);
+        self.current = None;
+        s
+    }
+
+    fn fold_statement(&mut self, s: TypedStatement<'ast, T>) -> Vec<TypedStatement<'ast, T>> {
+        if let Some(count) = self
+            .current
+            .as_ref()
+            .and_then(|key| self.graph.statements.get_mut(key))
+        {
+            *count += 1;
+        }
+
+        fold_statement(self, s)
+    }
+
+    fn fold_function_call_expression<
+        E: Id<'ast, T> + From<TypedExpression<'ast, T>> + Expr<'ast, T> + FunctionCall<'ast, T>,
+    >(
+        &mut self,
+        ty: &E::Ty,
+        e: FunctionCallExpression<'ast, T, E>,
+    ) -> FunctionCallOrExpression<'ast, T, E> {
+        let callee = e.function_key.clone();
+        match &self.current {
+            Some(caller) => {
+                self.graph
+                    .calls
+                    .entry(caller.clone())
+                    .or_default()
+                    .insert(callee);
+            }
+            None => {
+                self.graph.roots.insert(callee);
+            }
+        }
+
+        fold_function_call_expression(self, ty, e)
+    }
+}
@@ -102,6 +102,7 @@ impl<'ast, T: Field> Folder<'ast, T> for ConstantResolver<'ast, T> {
         TypedConstantSymbolDeclaration {
             id,
             symbol: TypedConstantSymbol::Here(constant),
+            public: c.public,
         }
     }
 }
@@ -33,7 +33,9 @@ impl<'ast, T: Field> ResultFolder<'ast, T> for ExpressionValidator {
     ) -> Result<Vec<TypedAssemblyStatement<'ast, T>>, Self::Error> {
         match s {
             // we allow more dynamic expressions in witness generation
-            TypedAssemblyStatement::Assignment(_, _) => Ok(vec![s]),
+            TypedAssemblyStatement::Assignment(..) | TypedAssemblyStatement::UnsafeWitness(..) => {
+                Ok(vec![s])
+            }
             s => fold_assembly_statement(self, s),
         }
     }
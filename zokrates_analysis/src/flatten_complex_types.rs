@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use std::convert::{TryFrom, TryInto};
 use std::marker::PhantomData;
+use zokrates_ast::common::stack::maybe_grow;
+use zokrates_ast::common::SourceMetadata;
 use zokrates_ast::typed::types::{ConcreteArrayType, IntoType, UBitwidth};
 use zokrates_ast::typed::{self, Expr, Typed};
 use zokrates_ast::zir::IntoType as ZirIntoType;
@@ -8,8 +10,14 @@ use zokrates_ast::zir::{self, Folder, Id, Select};
 use zokrates_field::Field;
 
 #[derive(Default)]
-pub struct Flattener<T: Field> {
-    phantom: PhantomData<T>,
+pub struct Flattener<'ast, T: Field> {
+    /// The flattened elements of immutable arrays built from spreads, which are wired to their sources instead of copied
+    aliases: HashMap<typed::Identifier<'ast>, Vec<zir::ZirExpression<'ast, T>>>,
+    /// Whether to keep the logs marking the bodies of the inlined functions
+    call_logs: bool,
+    /// The location of the source statement being flattened in each function being inlined, innermost last, if the
+    /// program is checked with span logs
+    spans: Vec<Option<SourceMetadata>>,
 }
 
 fn flatten_identifier_rec<'ast>(
@@ -58,6 +66,27 @@ fn flatten_identifier_rec<'ast>(
     }
 }
 
+// whether each of the variables `flatten_identifier_rec` flattens a value of type `ty` to is a signed integer
+fn flatten_signedness_rec(ty: &typed::types::ConcreteType) -> Vec<bool> {
+    match ty {
+        typed::ConcreteType::Int => unreachable!(),
+        typed::ConcreteType::FieldElement | typed::ConcreteType::Boolean => vec![false],
+        typed::ConcreteType::Uint(bitwidth) => vec![bitwidth.is_signed()],
+        typed::ConcreteType::Array(array_type) => (0..*array_type.size)
+            .flat_map(|_| flatten_signedness_rec(&array_type.ty))
+            .collect(),
+        typed::ConcreteType::Struct(members) => members
+            .iter()
+            .flat_map(|struct_member| flatten_signedness_rec(&struct_member.ty))
+            .collect(),
+        typed::ConcreteType::Tuple(tuple_ty) => tuple_ty
+            .elements
+            .iter()
+            .flat_map(flatten_signedness_rec)
+            .collect(),
+    }
+}
+
 fn flatten_identifier_to_expression_rec<'ast, T: Field>(
     id: zir::SourceIdentifier<'ast>,
     ty: &typed::types::ConcreteType,
@@ -109,7 +138,7 @@ fn flatten_identifier_to_expression_rec<'ast, T: Field>(
 trait Flatten<'ast, T: Field> {
     fn flatten(
         self,
-        f: &mut Flattener<T>,
+        f: &mut Flattener<'ast, T>,
         statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     ) -> Vec<zir::ZirExpression<'ast, T>>;
 }
@@ -117,7 +146,7 @@ trait Flatten<'ast, T: Field> {
 impl<'ast, T: Field> Flatten<'ast, T> for typed::FieldElementExpression<'ast, T> {
     fn flatten(
         self,
-        f: &mut Flattener<T>,
+        f: &mut Flattener<'ast, T>,
         statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     ) -> Vec<zir::ZirExpression<'ast, T>> {
         vec![f.fold_field_expression(statements_buffer, self).into()]
@@ -127,7 +156,7 @@ impl<'ast, T: Field> Flatten<'ast, T> for typed::FieldElementExpression<'ast, T>
 impl<'ast, T: Field> Flatten<'ast, T> for typed::BooleanExpression<'ast, T> {
     fn flatten(
         self,
-        f: &mut Flattener<T>,
+        f: &mut Flattener<'ast, T>,
         statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     ) -> Vec<zir::ZirExpression<'ast, T>> {
         vec![f.fold_boolean_expression(statements_buffer, self).into()]
@@ -137,7 +166,7 @@ impl<'ast, T: Field> Flatten<'ast, T> for typed::BooleanExpression<'ast, T> {
 impl<'ast, T: Field> Flatten<'ast, T> for typed::UExpression<'ast, T> {
     fn flatten(
         self,
-        f: &mut Flattener<T>,
+        f: &mut Flattener<'ast, T>,
         statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     ) -> Vec<zir::ZirExpression<'ast, T>> {
         vec![f.fold_uint_expression(statements_buffer, self).into()]
@@ -147,7 +176,7 @@ impl<'ast, T: Field> Flatten<'ast, T> for typed::UExpression<'ast, T> {
 impl<'ast, T: Field> Flatten<'ast, T> for typed::ArrayExpression<'ast, T> {
     fn flatten(
         self,
-        f: &mut Flattener<T>,
+        f: &mut Flattener<'ast, T>,
         statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     ) -> Vec<zir::ZirExpression<'ast, T>> {
         f.fold_array_expression(statements_buffer, self)
@@ -157,7 +186,7 @@ impl<'ast, T: Field> Flatten<'ast, T> for typed::ArrayExpression<'ast, T> {
 impl<'ast, T: Field> Flatten<'ast, T> for typed::StructExpression<'ast, T> {
     fn flatten(
         self,
-        f: &mut Flattener<T>,
+        f: &mut Flattener<'ast, T>,
         statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     ) -> Vec<zir::ZirExpression<'ast, T>> {
         f.fold_struct_expression(statements_buffer, self)
@@ -167,19 +196,27 @@ impl<'ast, T: Field> Flatten<'ast, T> for typed::StructExpression<'ast, T> {
 impl<'ast, T: Field> Flatten<'ast, T> for typed::TupleExpression<'ast, T> {
     fn flatten(
         self,
-        f: &mut Flattener<T>,
+        f: &mut Flattener<'ast, T>,
         statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     ) -> Vec<zir::ZirExpression<'ast, T>> {
         f.fold_tuple_expression(statements_buffer, self)
     }
 }
 
-impl<'ast, T: Field> Flattener<T> {
-    pub fn flatten(p: typed::TypedProgram<T>) -> zir::ZirProgram<T> {
-        let mut f = Flattener::default();
+impl<'ast, T: Field> Flattener<'ast, T> {
+    pub fn flatten(p: typed::TypedProgram<T>, call_logs: bool) -> zir::ZirProgram<T> {
+        let mut f = Flattener {
+            call_logs,
+            spans: vec![None],
+            ..Flattener::default()
+        };
         f.fold_program(p)
     }
 
+    pub fn flatten_function(f: typed::TypedFunction<T>) -> zir::ZirFunction<T> {
+        Flattener::default().fold_function(f)
+    }
+
     fn fold_program(&mut self, p: typed::TypedProgram<'ast, T>) -> zir::ZirProgram<'ast, T> {
         fold_program(self, p)
     }
@@ -193,9 +230,21 @@ impl<'ast, T: Field> Flattener<T> {
         p: typed::DeclarationParameter<'ast, T>,
     ) -> Vec<zir::Parameter<'ast>> {
         let private = p.private;
-        self.fold_variable(zokrates_ast::typed::variable::try_from_g_variable(p.id).unwrap())
+        let variable = zokrates_ast::typed::variable::try_from_g_variable(p.id).unwrap();
+        let ty = typed::types::ConcreteType::try_from(variable.get_type()).unwrap();
+        let signed = flatten_signedness_rec(&ty);
+        // the bound of the parameter applies to its elements, and the bounds of struct members to their values
+        let bounds = ty.get_primitive_bounds(p.bound);
+        self.fold_variable(variable)
             .into_iter()
-            .map(|v| zir::Parameter { id: v, private })
+            .zip(signed)
+            .zip(bounds)
+            .map(|((v, signed), bound)| zir::Parameter {
+                id: v,
+                private,
+                bound,
+                signed,
+            })
             .collect()
     }
 
@@ -521,7 +570,7 @@ impl<'ast, T: Field> Folder<'ast, T> for ArgumentFinder<'ast, T> {
 }
 
 fn fold_assembly_statement<'ast, T: Field>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     s: typed::TypedAssemblyStatement<'ast, T>,
 ) -> zir::ZirAssemblyStatement<'ast, T> {
@@ -550,6 +599,8 @@ fn fold_assembly_statement<'ast, T: Field>(
                     .map(|(id, ty)| zir::Parameter {
                         id: zir::Variable::with_id_and_type(id, ty),
                         private: true,
+                        bound: None,
+                        signed: false,
                     })
                     .collect(),
                 statements: statements_buffer,
@@ -562,11 +613,48 @@ fn fold_assembly_statement<'ast, T: Field>(
             let rhs = f.fold_field_expression(statements_buffer, rhs);
             zir::ZirAssemblyStatement::Constraint(lhs, rhs, metadata)
         }
+        typed::TypedAssemblyStatement::UnsafeWitness(a, plugin, arguments) => {
+            let a = f.fold_assignee(a);
+            let arguments = arguments
+                .into_iter()
+                .flat_map(|e| f.fold_expression(statements_buffer, e))
+                .map(|e| match e {
+                    zir::ZirExpression::FieldElement(e) => e,
+                    _ => unreachable!("the arguments of a plugin are made of field elements"),
+                })
+                .collect();
+            zir::ZirAssemblyStatement::UnsafeWitness(a, plugin, arguments)
+        }
+    }
+}
+
+fn is_spread<T: Field>(e: &typed::ArrayExpression<T>) -> bool {
+    match e.as_inner() {
+        typed::ArrayExpressionInner::Value(values) => values
+            .iter()
+            .any(|v| matches!(v, typed::TypedExpressionOrSpread::Spread(..))),
+        _ => false,
+    }
+}
+
+/// Whether `e` can be referred to without being copied into a new variable
+fn is_wire<T: Field>(e: &zir::ZirExpression<T>) -> bool {
+    use zokrates_ast::zir::Constant;
+
+    match e {
+        zir::ZirExpression::FieldElement(zir::FieldElementExpression::Identifier(_))
+        | zir::ZirExpression::Boolean(zir::BooleanExpression::Identifier(_)) => true,
+        zir::ZirExpression::Uint(e)
+            if matches!(e.as_inner(), zir::UExpressionInner::Identifier(_)) =>
+        {
+            true
+        }
+        e => e.is_constant(),
     }
 }
 
 fn fold_statement<'ast, T: Field>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     s: typed::TypedStatement<'ast, T>,
 ) {
@@ -581,6 +669,26 @@ fn fold_statement<'ast, T: Field>(
         typed::TypedStatement::Return(expression) => vec![zir::ZirStatement::Return(
             f.fold_expression(statements_buffer, expression),
         )],
+        // an immutable array built from spreads over wires is not copied: its uses refer to the sources directly
+        typed::TypedStatement::Definition(
+            typed::TypedAssignee::Identifier(v),
+            typed::DefinitionRhs::Expression(typed::TypedExpression::Array(e)),
+        ) if !v.is_mutable && is_spread(&e) => {
+            let e = f.fold_array_expression(statements_buffer, e);
+
+            match e.iter().all(is_wire) {
+                true => {
+                    f.aliases.insert(v.id, e);
+                    vec![]
+                }
+                false => f
+                    .fold_variable(v)
+                    .into_iter()
+                    .zip(e.into_iter())
+                    .map(|(a, e)| zir::ZirStatement::Definition(a, e))
+                    .collect(),
+            }
+        }
         typed::TypedStatement::Definition(a, typed::DefinitionRhs::Expression(e)) => {
             let a = f.fold_assignee(a);
             let e = f.fold_expression(statements_buffer, e);
@@ -590,17 +698,28 @@ fn fold_statement<'ast, T: Field>(
                 .map(|(a, e)| zir::ZirStatement::Definition(a, e))
                 .collect()
         }
-        typed::TypedStatement::Assertion(e, error) => {
-            let e = f.fold_boolean_expression(statements_buffer, e);
-            let error = match error {
-                typed::RuntimeError::SourceAssertion(metadata) => {
-                    zir::RuntimeError::SourceAssertion(metadata)
-                }
-                typed::RuntimeError::SelectRangeCheck => zir::RuntimeError::SelectRangeCheck,
-                typed::RuntimeError::DivisionByZero => zir::RuntimeError::DivisionByZero,
-            };
-            vec![zir::ZirStatement::Assertion(e, error)]
-        }
+        typed::TypedStatement::Assertion(e, error) => match (e, error) {
+            (typed::BooleanExpression::ArrayEq(e), typed::RuntimeError::SourceAssertion(m)) => {
+                fold_eq_assertion(f, statements_buffer, e, m)
+            }
+            (typed::BooleanExpression::StructEq(e), typed::RuntimeError::SourceAssertion(m)) => {
+                fold_eq_assertion(f, statements_buffer, e, m)
+            }
+            (typed::BooleanExpression::TupleEq(e), typed::RuntimeError::SourceAssertion(m)) => {
+                fold_eq_assertion(f, statements_buffer, e, m)
+            }
+            (e, error) => {
+                let e = f.fold_boolean_expression(statements_buffer, e);
+                let error = match error {
+                    typed::RuntimeError::SourceAssertion(metadata) => {
+                        zir::RuntimeError::SourceAssertion(metadata)
+                    }
+                    typed::RuntimeError::SelectRangeCheck => zir::RuntimeError::SelectRangeCheck,
+                    typed::RuntimeError::DivisionByZero => zir::RuntimeError::DivisionByZero(None),
+                };
+                vec![zir::ZirStatement::Assertion(e, error)]
+            }
+        },
         typed::TypedStatement::Definition(
             assignee,
             typed::DefinitionRhs::EmbedCall(embed_call),
@@ -629,8 +748,53 @@ fn fold_statement<'ast, T: Field>(
                 })
                 .collect(),
         )],
-        typed::TypedStatement::PushCallLog(..) => vec![],
-        typed::TypedStatement::PopCallLog => vec![],
+        typed::TypedStatement::PushCallLog(key, _, site) if f.call_logs => {
+            // in a program checked with span logs, the body of the function is attributed to the call itself rather
+            // than to the whole statement it appears in
+            let call_span = match f.spans.last() {
+                Some(Some(_)) => site.span.map(zir::ZirStatement::SpanLog),
+                _ => None,
+            };
+            f.spans.push(None);
+
+            call_span
+                .into_iter()
+                .chain(std::iter::once(zir::ZirStatement::PushCallLog(format!(
+                    "{}/{}",
+                    key.module.display(),
+                    key.id
+                ))))
+                .collect()
+        }
+        typed::TypedStatement::PopCallLog if f.call_logs => {
+            f.spans.pop();
+
+            // the statements which follow the call are attributed to the statement again
+            std::iter::once(zir::ZirStatement::PopCallLog)
+                .chain(
+                    f.spans
+                        .last()
+                        .cloned()
+                        .flatten()
+                        .map(zir::ZirStatement::SpanLog),
+                )
+                .collect()
+        }
+        typed::TypedStatement::PushCallLog(..) | typed::TypedStatement::PopCallLog => vec![],
+        typed::TypedStatement::PushIterationLog(label, index) => match index.into_inner() {
+            typed::UExpressionInner::Value(index) => vec![zir::ZirStatement::PushIterationLog(
+                label.to_string(),
+                index as u32,
+            )],
+            _ => unreachable!("loop indices should be known after reduction"),
+        },
+        typed::TypedStatement::PopIterationLog => vec![zir::ZirStatement::PopIterationLog],
+        typed::TypedStatement::SpanLog(span) => {
+            if let Some(current) = f.spans.last_mut() {
+                *current = Some(span.clone());
+            }
+            vec![zir::ZirStatement::SpanLog(span)]
+        }
         typed::TypedStatement::For(..) => unreachable!(),
     };
 
@@ -638,7 +802,7 @@ fn fold_statement<'ast, T: Field>(
 }
 
 fn fold_array_expression_inner<'ast, T: Field>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     ty: typed::types::ConcreteType,
     size: u32,
@@ -708,7 +872,7 @@ fn fold_array_expression_inner<'ast, T: Field>(
 }
 
 fn fold_struct_expression_inner<'ast, T: Field>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     ty: typed::types::ConcreteStructType,
     struc: typed::StructExpressionInner<'ast, T>,
@@ -741,7 +905,7 @@ fn fold_struct_expression_inner<'ast, T: Field>(
 }
 
 fn fold_tuple_expression_inner<'ast, T: Field>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     ty: typed::types::ConcreteTupleType,
     tuple: typed::TupleExpressionInner<'ast, T>,
@@ -774,7 +938,7 @@ fn fold_tuple_expression_inner<'ast, T: Field>(
 }
 
 fn fold_member_expression<'ast, T: Field, E>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     m: typed::MemberExpression<'ast, T, E>,
 ) -> Vec<zir::ZirExpression<'ast, T>> {
@@ -810,7 +974,7 @@ fn fold_member_expression<'ast, T: Field, E>(
 }
 
 fn fold_element_expression<'ast, T: Field, E>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     e: typed::ElementExpression<'ast, T, E>,
 ) -> Vec<zir::ZirExpression<'ast, T>> {
@@ -849,7 +1013,7 @@ fn fold_element_expression<'ast, T: Field, E>(
 }
 
 fn fold_select_expression<'ast, T: Field, E>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     select: typed::SelectExpression<'ast, T, E>,
 ) -> Vec<zir::ZirExpression<'ast, T>> {
@@ -923,7 +1087,7 @@ fn fold_select_expression<'ast, T: Field, E>(
 }
 
 fn fold_conditional_expression<'ast, T: Field, E: Flatten<'ast, T>>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     c: typed::ConditionalExpression<'ast, T, E>,
 ) -> Vec<zir::ZirExpression<'ast, T>> {
@@ -965,19 +1129,22 @@ fn fold_conditional_expression<'ast, T: Field, E: Flatten<'ast, T>>(
 }
 
 fn fold_identifier_expression<'ast, T: Field, E: Expr<'ast, T>>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     ty: E::ConcreteTy,
     e: typed::IdentifierExpression<'ast, E>,
 ) -> Vec<zir::ZirExpression<'ast, T>> {
-    flatten_identifier_to_expression_rec(f.fold_name(e.id), &ty.into_type())
+    match f.aliases.get(&e.id) {
+        Some(elements) => elements.clone(),
+        None => flatten_identifier_to_expression_rec(f.fold_name(e.id), &ty.into_type()),
+    }
 }
 
 fn fold_field_expression<'ast, T: Field>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     e: typed::FieldElementExpression<'ast, T>,
 ) -> zir::FieldElementExpression<'ast, T> {
-    match e {
+    maybe_grow(|| match e {
         typed::FieldElementExpression::Number(n) => zir::FieldElementExpression::Number(n),
         typed::FieldElementExpression::Identifier(id) => f
             .fold_identifier_expression(typed::ConcreteType::FieldElement, id)
@@ -1000,10 +1167,10 @@ fn fold_field_expression<'ast, T: Field>(
             let e2 = f.fold_field_expression(statements_buffer, e2);
             zir::FieldElementExpression::Mult(box e1, box e2)
         }
-        typed::FieldElementExpression::Div(box e1, box e2) => {
+        typed::FieldElementExpression::Div(box e1, box e2, span) => {
             let e1 = f.fold_field_expression(statements_buffer, e1);
             let e2 = f.fold_field_expression(statements_buffer, e2);
-            zir::FieldElementExpression::Div(box e1, box e2)
+            zir::FieldElementExpression::Div(box e1, box e2, span)
         }
         typed::FieldElementExpression::Pow(box e1, box e2) => {
             let e1 = f.fold_field_expression(statements_buffer, e1);
@@ -1081,7 +1248,7 @@ fn fold_field_expression<'ast, T: Field>(
                 .for_each(|s| f.fold_statement(statements_buffer, s));
             f.fold_field_expression(statements_buffer, *block.value)
         }
-    }
+    })
 }
 
 // util function to output a boolean expression representing the equality of two lists of ZirExpression.
@@ -1115,8 +1282,75 @@ fn conjunction_tree<'ast, T: Field>(
     }
 }
 
+// the paths to the leaves of a value of type `ty` in the order they are flattened in, such as `[3].x`
+fn element_paths(ty: &typed::types::ConcreteType) -> Vec<String> {
+    match ty {
+        typed::ConcreteType::Int => unreachable!(),
+        typed::ConcreteType::FieldElement
+        | typed::ConcreteType::Boolean
+        | typed::ConcreteType::Uint(_) => vec![String::new()],
+        typed::ConcreteType::Array(array_type) => {
+            let paths = element_paths(&array_type.ty);
+            (0..*array_type.size)
+                .flat_map(|i| paths.iter().map(move |path| format!("[{}]{}", i, path)))
+                .collect()
+        }
+        typed::ConcreteType::Struct(members) => members
+            .iter()
+            .flat_map(|member| {
+                element_paths(&member.ty)
+                    .into_iter()
+                    .map(move |path| format!(".{}{}", member.id, path))
+            })
+            .collect(),
+        typed::ConcreteType::Tuple(tuple_ty) => tuple_ty
+            .elements
+            .iter()
+            .enumerate()
+            .flat_map(|(i, ty)| {
+                element_paths(ty)
+                    .into_iter()
+                    .map(move |path| format!(".{}{}", i, path))
+            })
+            .collect(),
+    }
+}
+
+// an assertion on the equality of complex values is split into one assertion per leaf, so that a failure points at
+// the element which differs
+fn fold_eq_assertion<'ast, T: Field, E: Flatten<'ast, T> + Typed<'ast, T>>(
+    f: &mut Flattener<'ast, T>,
+    statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
+    e: typed::EqExpression<E>,
+    metadata: SourceMetadata,
+) -> Vec<zir::ZirStatement<'ast, T>> {
+    let ty = typed::ConcreteType::try_from(e.left.get_type()).unwrap();
+    // the compared value is unknown when the elements do not match the source, see `BooleanArrayComparator`
+    let compared = metadata.element.clone();
+
+    let left = e.left.flatten(f, statements_buffer);
+    let right = e.right.flatten(f, statements_buffer);
+    let paths = element_paths(&ty);
+    assert_eq!(left.len(), paths.len());
+
+    left.into_iter()
+        .zip(right)
+        .zip(paths)
+        .map(|((left, right), path)| {
+            zir::ZirStatement::Assertion(
+                conjunction_tree(&[left], &[right]),
+                zir::RuntimeError::SourceAssertion(
+                    metadata
+                        .clone()
+                        .element(compared.as_ref().map(|c| format!("{}{}", c, path))),
+                ),
+            )
+        })
+        .collect()
+}
+
 fn fold_eq_expression<'ast, T: Field, E: Flatten<'ast, T>>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     e: typed::EqExpression<E>,
 ) -> zir::BooleanExpression<'ast, T> {
@@ -1126,11 +1360,11 @@ fn fold_eq_expression<'ast, T: Field, E: Flatten<'ast, T>>(
 }
 
 fn fold_boolean_expression<'ast, T: Field>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     e: typed::BooleanExpression<'ast, T>,
 ) -> zir::BooleanExpression<'ast, T> {
-    match e {
+    maybe_grow(|| match e {
         typed::BooleanExpression::Block(block) => {
             block
                 .statements
@@ -1172,23 +1406,27 @@ fn fold_boolean_expression<'ast, T: Field>(
             zir::BooleanExpression::FieldLe(box e2, box e1)
         }
         typed::BooleanExpression::UintLt(box e1, box e2) => {
-            let e1 = f.fold_uint_expression(statements_buffer, e1);
-            let e2 = f.fold_uint_expression(statements_buffer, e2);
+            let bitwidth = e1.bitwidth;
+            let e1 = order_key(f.fold_uint_expression(statements_buffer, e1), bitwidth);
+            let e2 = order_key(f.fold_uint_expression(statements_buffer, e2), bitwidth);
             zir::BooleanExpression::UintLt(box e1, box e2)
         }
         typed::BooleanExpression::UintLe(box e1, box e2) => {
-            let e1 = f.fold_uint_expression(statements_buffer, e1);
-            let e2 = f.fold_uint_expression(statements_buffer, e2);
+            let bitwidth = e1.bitwidth;
+            let e1 = order_key(f.fold_uint_expression(statements_buffer, e1), bitwidth);
+            let e2 = order_key(f.fold_uint_expression(statements_buffer, e2), bitwidth);
             zir::BooleanExpression::UintLe(box e1, box e2)
         }
         typed::BooleanExpression::UintGt(box e1, box e2) => {
-            let e1 = f.fold_uint_expression(statements_buffer, e1);
-            let e2 = f.fold_uint_expression(statements_buffer, e2);
+            let bitwidth = e1.bitwidth;
+            let e1 = order_key(f.fold_uint_expression(statements_buffer, e1), bitwidth);
+            let e2 = order_key(f.fold_uint_expression(statements_buffer, e2), bitwidth);
             zir::BooleanExpression::UintLt(box e2, box e1)
         }
         typed::BooleanExpression::UintGe(box e1, box e2) => {
-            let e1 = f.fold_uint_expression(statements_buffer, e1);
-            let e2 = f.fold_uint_expression(statements_buffer, e2);
+            let bitwidth = e1.bitwidth;
+            let e1 = order_key(f.fold_uint_expression(statements_buffer, e1), bitwidth);
+            let e2 = order_key(f.fold_uint_expression(statements_buffer, e2), bitwidth);
             zir::BooleanExpression::UintLe(box e2, box e1)
         }
         typed::BooleanExpression::Or(box e1, box e2) => {
@@ -1230,11 +1468,23 @@ fn fold_boolean_expression<'ast, T: Field>(
             .unwrap()
             .try_into()
             .unwrap(),
+    })
+}
+
+// signed integers are compared by adding the value of the sign bit to both sides, which maps their two's complement
+// representations to unsigned integers in the same order
+fn order_key<'ast, T: Field>(
+    e: zir::UExpression<'ast, T>,
+    bitwidth: UBitwidth,
+) -> zir::UExpression<'ast, T> {
+    match bitwidth.sign_bit() {
+        Some(sign) => e.add(zir::UExpressionInner::Value(sign).annotate(bitwidth.to_usize())),
+        None => e,
     }
 }
 
 fn fold_uint_expression<'ast, T: Field>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     e: typed::UExpression<'ast, T>,
 ) -> zir::UExpression<'ast, T> {
@@ -1243,12 +1493,12 @@ fn fold_uint_expression<'ast, T: Field>(
 }
 
 fn fold_uint_expression_inner<'ast, T: Field>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     bitwidth: UBitwidth,
     e: typed::UExpressionInner<'ast, T>,
 ) -> zir::UExpressionInner<'ast, T> {
-    match e {
+    maybe_grow(|| match e {
         typed::UExpressionInner::Block(block) => {
             block
                 .statements
@@ -1383,11 +1633,11 @@ fn fold_uint_expression_inner<'ast, T: Field>(
         )
         .unwrap()
         .into_inner(),
-    }
+    })
 }
 
 fn fold_function<'ast, T: Field>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     fun: typed::TypedFunction<'ast, T>,
 ) -> zir::ZirFunction<'ast, T> {
     let mut main_statements_buffer = vec![];
@@ -1416,7 +1666,7 @@ fn fold_function<'ast, T: Field>(
 }
 
 fn fold_array_expression<'ast, T: Field>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     e: typed::ArrayExpression<'ast, T>,
 ) -> Vec<zir::ZirExpression<'ast, T>> {
@@ -1430,7 +1680,7 @@ fn fold_array_expression<'ast, T: Field>(
 }
 
 fn fold_struct_expression<'ast, T: Field>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     e: typed::StructExpression<'ast, T>,
 ) -> Vec<zir::ZirExpression<'ast, T>> {
@@ -1442,7 +1692,7 @@ fn fold_struct_expression<'ast, T: Field>(
 }
 
 fn fold_tuple_expression<'ast, T: Field>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     statements_buffer: &mut Vec<zir::ZirStatement<'ast, T>>,
     e: typed::TupleExpression<'ast, T>,
 ) -> Vec<zir::ZirExpression<'ast, T>> {
@@ -1454,7 +1704,7 @@ fn fold_tuple_expression<'ast, T: Field>(
 }
 
 fn fold_program<'ast, T: Field>(
-    f: &mut Flattener<T>,
+    f: &mut Flattener<'ast, T>,
     mut p: typed::TypedProgram<'ast, T>,
 ) -> zir::ZirProgram<'ast, T> {
     let main_module = p.modules.remove(&p.main).unwrap();
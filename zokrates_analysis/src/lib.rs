@@ -9,6 +9,7 @@
 mod assembly_transformer;
 mod boolean_array_comparator;
 mod branch_isolator;
+mod call_graph;
 mod condition_redefiner;
 mod constant_argument_checker;
 mod constant_resolver;
@@ -18,13 +19,18 @@ mod flat_propagation;
 mod flatten_complex_types;
 mod log_ignorer;
 mod out_of_bounds;
+mod output_dropper;
 mod panic_extractor;
+mod pipeline;
 mod propagation;
 mod reducer;
 mod struct_concretizer;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 mod uint_optimizer;
 mod variable_write_remover;
 mod zir_propagation;
+mod zir_validator;
 
 use self::boolean_array_comparator::BooleanArrayComparator;
 use self::branch_isolator::Isolator;
@@ -33,20 +39,24 @@ use self::constant_argument_checker::ConstantArgumentChecker;
 use self::flatten_complex_types::Flattener;
 use self::log_ignorer::LogIgnorer;
 use self::out_of_bounds::OutOfBoundsChecker;
-use self::propagation::Propagator;
-use self::reducer::reduce_program;
+use self::output_dropper::OutputDropper;
+use self::propagation::{Abandoned, Budget, Propagator};
+use self::reducer::{reduce_constants, reduce_main, MAX_CALL_DEPTH};
 use self::struct_concretizer::StructConcretizer;
-use self::uint_optimizer::UintOptimizer;
 use self::variable_write_remover::VariableWriteRemover;
 use crate::assembly_transformer::AssemblyTransformer;
+pub use crate::call_graph::CallGraph;
 use crate::constant_resolver::ConstantResolver;
-use crate::dead_code::DeadCodeEliminator;
 use crate::expression_validator::ExpressionValidator;
-use crate::panic_extractor::PanicExtractor;
+pub use crate::pipeline::{zir_pass, ZirPass};
 pub use crate::zir_propagation::ZirPropagator;
+use crate::zir_validator::ZirValidator;
+use std::collections::BTreeSet;
 use std::fmt;
-use zokrates_ast::typed::{abi::Abi, TypedProgram};
+use zokrates_ast::common::{Diagnostic, SourceMetadata};
+use zokrates_ast::typed::{abi::Abi, OwnedTypedModuleId, TypedProgram};
 use zokrates_ast::zir::ZirProgram;
+use zokrates_common::pipeline::{Pass, Stage};
 use zokrates_common::CompileConfig;
 use zokrates_field::Field;
 
@@ -60,6 +70,9 @@ pub enum Error {
     Assembly(self::assembly_transformer::Error),
     VariableIndex(self::variable_write_remover::Error),
     InvalidExpression(self::expression_validator::Error),
+    UintOptimization(self::uint_optimizer::Error),
+    TrivialAssertions(Vec<SourceMetadata>),
+    InvalidZir(String, self::zir_validator::Error),
 }
 
 impl From<reducer::Error> for Error {
@@ -104,6 +117,12 @@ impl From<variable_write_remover::Error> for Error {
     }
 }
 
+impl From<uint_optimizer::Error> for Error {
+    fn from(e: uint_optimizer::Error) -> Self {
+        Error::UintOptimization(e)
+    }
+}
+
 impl From<expression_validator::Error> for Error {
     fn from(e: expression_validator::Error) -> Self {
         Error::InvalidExpression(e)
@@ -121,17 +140,43 @@ impl fmt::Display for Error {
             Error::Assembly(e) => write!(f, "{}", e),
             Error::VariableIndex(e) => write!(f, "{}", e),
             Error::InvalidExpression(e) => write!(f, "{}", e),
+            Error::UintOptimization(e) => write!(f, "{}", e),
+            Error::TrivialAssertions(assertions) => write!(
+                f,
+                "Found {} trivially true assertion{}: {}",
+                assertions.len(),
+                if assertions.len() == 1 { "" } else { "s" },
+                assertions
+                    .iter()
+                    .map(|a| format!("`{}`", a))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Error::InvalidZir(pass, e) => write!(f, "After {}: {}", pass, e),
         }
     }
 }
 
+/// Reduces a checked program to a single function in zir, keeping the logs which mark the bodies of the inlined
+/// functions if `call_logs` is set. The warnings found along the way are returned as diagnostics
+///
+/// Trivially true assertions are only reported in the `user_modules`, as the generic functions of libraries contain
+/// assertions which hold for some of their instances
 pub fn analyse<'ast, T: Field>(
     p: TypedProgram<'ast, T>,
     config: &CompileConfig,
-) -> Result<(ZirProgram<'ast, T>, Abi), Error> {
+    call_logs: bool,
+    user_modules: &BTreeSet<OwnedTypedModuleId>,
+) -> Result<(ZirProgram<'ast, T>, Abi, Vec<Diagnostic>), Error> {
+    // remove the functions which cannot be reached from main
+    log::debug!("Static analyser: Prune unreachable functions");
+    let (p, call_graph) = CallGraph::build(p);
+    let r = call_graph.prune(p);
+    log::trace!("\n{}", r);
+
     // inline user-defined constants
     log::debug!("Static analyser: Inline constants");
-    let r = ConstantResolver::inline(p);
+    let r = ConstantResolver::inline(r);
     log::trace!("\n{}", r);
 
     // isolate branches
@@ -156,13 +201,26 @@ pub fn analyse<'ast, T: Field>(
         r
     };
 
+    let max_call_depth = config.max_call_depth.unwrap_or(MAX_CALL_DEPTH);
+
+    // reduce constants to literals
+    log::debug!("Static analyser: Reduce constants");
+    let r = reduce_constants(r, max_call_depth).map_err(Error::from)?;
+    log::trace!("\n{}", r);
+
+    // export the public constants of the main module before the other symbols are inlined
+    let constants = r.public_constants();
+
+    // the propagation of constant arrays, structs and tuples stops once the budget is spent
+    let mut budget = Budget::new(config.propagation_budget);
+
     // reduce the program to a single function
     log::debug!("Static analyser: Reduce program");
-    let r = reduce_program(r).map_err(Error::from)?;
+    let r = reduce_main(r, max_call_depth, &mut budget).map_err(Error::from)?;
     log::trace!("\n{}", r);
 
     log::debug!("Static analyser: Propagate");
-    let r = Propagator::propagate(r)?;
+    let r = Propagator::propagate(r, &mut budget)?;
     log::trace!("\n{}", r);
 
     log::debug!("Static analyser: Concretize structs");
@@ -175,13 +233,47 @@ pub fn analyse<'ast, T: Field>(
 
     // generate abi
     log::debug!("Static analyser: Generate abi");
-    let abi = r.abi();
+    let abi = Abi {
+        constants,
+        ..r.abi()
+    };
+    let abi = match config.no_outputs {
+        true => abi.without_outputs(),
+        false => abi,
+    };
 
     // propagate
     log::debug!("Static analyser: Propagate");
-    let r = Propagator::propagate(r).map_err(Error::from)?;
+    let r = Propagator::propagate(r, &mut budget).map_err(Error::from)?;
     log::trace!("\n{}", r);
 
+    let mut diagnostics: Vec<_> = budget
+        .abandoned()
+        .iter()
+        .map(Abandoned::diagnostic)
+        .collect();
+
+    // equality assertions which always hold waste constraints and overstate what the program checks
+    let user_files: BTreeSet<_> = user_modules
+        .iter()
+        .map(|m| m.display().to_string())
+        .collect();
+    let trivial: Vec<_> = budget
+        .trivial_assertions()
+        .iter()
+        .filter(|metadata| user_files.contains(&metadata.file))
+        .cloned()
+        .collect();
+    if !trivial.is_empty() && config.deny_trivial_asserts {
+        return Err(Error::TrivialAssertions(trivial));
+    }
+    diagnostics.extend(trivial.into_iter().map(|metadata| {
+        Diagnostic::new(
+            "Assertion always holds and was removed, delete it or use `--deny-trivial-asserts` to reject it",
+        )
+        .span(Some(metadata))
+    }));
+
     // simplify boolean array comparisons
     log::debug!("Static analyser: Simplify boolean array comparisons");
     let r = BooleanArrayComparator::simplify(r);
@@ -209,30 +301,45 @@ pub fn analyse<'ast, T: Field>(
 
     // convert to zir, removing complex types
     log::debug!("Static analyser: Convert to zir");
-    let zir = Flattener::flatten(r);
+    let mut zir = Flattener::flatten(r, call_logs);
     log::trace!("\n{}", zir);
 
-    // apply propagation in zir
-    log::debug!("Static analyser: Apply propagation in zir");
-    let zir = ZirPropagator::propagate(zir).map_err(Error::from)?;
-    log::trace!("\n{}", zir);
+    // check the single-assignment form the zir passes rely on, each time it is produced
+    let validate = cfg!(debug_assertions) || config.validate_ir;
+    let check = |zir: &ZirProgram<'ast, T>, pass: &str| match validate {
+        true => {
+            log::debug!("Static analyser: Validate zir after {}", pass);
+            ZirValidator::validate(zir).map_err(|e| Error::InvalidZir(pass.to_string(), e))
+        }
+        false => Ok(()),
+    };
 
-    log::debug!("Static analyser: Extract panics");
-    let zir = PanicExtractor::extract(zir);
-    log::trace!("\n{}", zir);
+    check(&zir, "conversion to zir")?;
 
-    log::debug!("Static analyser: Remove dead code");
-    let zir = DeadCodeEliminator::eliminate(zir);
-    log::trace!("\n{}", zir);
+    // drop the return values, so that the zir passes remove the computations which only feed them
+    if config.no_outputs {
+        log::debug!("Static analyser: Drop outputs");
+        zir = OutputDropper::drop_outputs(zir);
+        log::trace!("\n{}", zir);
+        check(&zir, "dropping the outputs")?;
+    }
 
-    // optimize uint expressions
-    log::debug!("Static analyser: Optimize uints");
-    let zir = UintOptimizer::optimize(zir);
-    log::trace!("\n{}", zir);
+    // run the zir passes of the pipeline
+    for id in config
+        .pipeline
+        .clone()
+        .unwrap_or_default()
+        .stage(Stage::Zir)
+    {
+        zir = zir_pass(id, config).apply(zir)?;
+        log::trace!("\n{}", zir);
+        check(&zir, &format!("pass `{}`", id))?;
+    }
 
     log::debug!("Static analyser: Apply constraint transformations in assembly");
     let zir = AssemblyTransformer::transform(zir).map_err(Error::from)?;
     log::trace!("\n{}", zir);
+    check(&zir, "assembly transformations")?;
 
-    Ok((zir, abi))
+    Ok((zir, abi, diagnostics))
 }
@@ -0,0 +1,31 @@
+//! Module dropping the return values of `main`, for programs which only check properties of their inputs.
+//!
+//! The returned expressions are removed, so that the computations which only feed them become dead code and are
+//! removed by the propagation and dead code passes. Assertions are kept, along with what they depend on.
+
+use zokrates_ast::zir::{folder::*, ZirFunction, ZirProgram, ZirStatement};
+use zokrates_field::Field;
+
+#[derive(Default)]
+pub struct OutputDropper;
+
+impl OutputDropper {
+    pub fn drop_outputs<T: Field>(p: ZirProgram<T>) -> ZirProgram<T> {
+        Self::default().fold_program(p)
+    }
+}
+
+impl<'ast, T: Field> Folder<'ast, T> for OutputDropper {
+    fn fold_function(&mut self, f: ZirFunction<'ast, T>) -> ZirFunction<'ast, T> {
+        let mut f = fold_function(self, f);
+        f.signature.outputs = vec![];
+        f
+    }
+
+    fn fold_statement(&mut self, s: ZirStatement<'ast, T>) -> Vec<ZirStatement<'ast, T>> {
+        match s {
+            ZirStatement::Return(..) => vec![ZirStatement::Return(vec![])],
+            s => fold_statement(self, s),
+        }
+    }
+}
@@ -2,18 +2,33 @@ use zokrates_ast::zir::{
     folder::*, BooleanExpression, Conditional, ConditionalExpression, ConditionalOrExpression,
     FieldElementExpression, RuntimeError, UBitwidth, UExpressionInner, ZirProgram, ZirStatement,
 };
+use zokrates_common::DivByZero;
 use zokrates_field::Field;
 
 // a static analyser pass to extract the failure modes into separate assert statements, so that a statement can panic iff it's an assertion
+// field divisions by zero only panic under `DivByZero::Error`, as they are otherwise defined
 
 #[derive(Default)]
 pub struct PanicExtractor<'ast, T> {
     panic_buffer: Vec<ZirStatement<'ast, T>>,
+    div_by_zero: DivByZero,
 }
 
 impl<'ast, T: Field> PanicExtractor<'ast, T> {
-    pub fn extract(p: ZirProgram<'ast, T>) -> ZirProgram<'ast, T> {
-        Self::default().fold_program(p)
+    pub fn extract(p: ZirProgram<'ast, T>, div_by_zero: DivByZero) -> ZirProgram<'ast, T> {
+        PanicExtractor {
+            panic_buffer: vec![],
+            div_by_zero,
+        }
+        .fold_program(p)
+    }
+
+    // an extractor for the statements of a branch, which are extracted separately
+    fn branch(&self) -> Self {
+        PanicExtractor {
+            panic_buffer: vec![],
+            div_by_zero: self.div_by_zero,
+        }
     }
 }
 
@@ -22,13 +37,13 @@ impl<'ast, T: Field> Folder<'ast, T> for PanicExtractor<'ast, T> {
         match s {
             ZirStatement::IfElse(condition, consequence, alternative) => {
                 let condition = self.fold_boolean_expression(condition);
-                let mut consequence_extractor = Self::default();
+                let mut consequence_extractor = self.branch();
                 let consequence = consequence
                     .into_iter()
                     .flat_map(|s| consequence_extractor.fold_statement(s))
                     .collect();
                 assert!(consequence_extractor.panic_buffer.is_empty());
-                let mut alternative_extractor = Self::default();
+                let mut alternative_extractor = self.branch();
                 let alternative = alternative
                     .into_iter()
                     .flat_map(|s| alternative_extractor.fold_statement(s))
@@ -56,17 +71,19 @@ impl<'ast, T: Field> Folder<'ast, T> for PanicExtractor<'ast, T> {
         e: FieldElementExpression<'ast, T>,
     ) -> FieldElementExpression<'ast, T> {
         match e {
-            FieldElementExpression::Div(box n, box d) => {
+            FieldElementExpression::Div(box n, box d, span) => {
                 let n = self.fold_field_expression(n);
                 let d = self.fold_field_expression(d);
-                self.panic_buffer.push(ZirStatement::Assertion(
-                    BooleanExpression::Not(box BooleanExpression::FieldEq(
-                        box d.clone(),
-                        box FieldElementExpression::Number(T::zero()),
-                    )),
-                    RuntimeError::DivisionByZero,
-                ));
-                FieldElementExpression::Div(box n, box d)
+                if self.div_by_zero == DivByZero::Error {
+                    self.panic_buffer.push(ZirStatement::Assertion(
+                        BooleanExpression::Not(box BooleanExpression::FieldEq(
+                            box d.clone(),
+                            box FieldElementExpression::Number(T::zero()),
+                        )),
+                        RuntimeError::DivisionByZero(span.clone()),
+                    ));
+                }
+                FieldElementExpression::Div(box n, box d, span)
             }
             e => fold_field_expression(self, e),
         }
@@ -80,9 +97,9 @@ impl<'ast, T: Field> Folder<'ast, T> for PanicExtractor<'ast, T> {
         e: ConditionalExpression<'ast, T, E>,
     ) -> ConditionalOrExpression<'ast, T, E> {
         let condition = self.fold_boolean_expression(*e.condition);
-        let mut consequence_extractor = Self::default();
+        let mut consequence_extractor = self.branch();
         let consequence = e.consequence.fold(&mut consequence_extractor);
-        let mut alternative_extractor = Self::default();
+        let mut alternative_extractor = self.branch();
         let alternative = e.alternative.fold(&mut alternative_extractor);
 
         let consequence_panics: Vec<_> = consequence_extractor.panic_buffer.drain(..).collect();
@@ -117,7 +134,7 @@ impl<'ast, T: Field> Folder<'ast, T> for PanicExtractor<'ast, T> {
                         box d.clone(),
                         box UExpressionInner::Value(0).annotate(b),
                     )),
-                    RuntimeError::DivisionByZero,
+                    RuntimeError::DivisionByZero(None),
                 ));
                 UExpressionInner::Div(box n, box d)
             }
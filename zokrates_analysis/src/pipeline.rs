@@ -0,0 +1,77 @@
+//! The zir passes which can be ordered in a pipeline, see `zokrates_common::pipeline`
+
+use crate::dead_code::DeadCodeEliminator;
+use crate::panic_extractor::PanicExtractor;
+use crate::uint_optimizer::UintOptimizer;
+use crate::{Error, ZirPropagator};
+use zokrates_ast::zir::ZirProgram;
+use zokrates_common::pipeline::{Pass, PassId};
+use zokrates_common::{CompileConfig, DivByZero, ReductionPolicy};
+use zokrates_field::Field;
+
+pub type ZirPass<'ast, T> = Box<dyn Pass<ZirProgram<'ast, T>, Error = Error>>;
+
+struct Propagate;
+struct Panics(DivByZero);
+struct DeadCode;
+struct Prune;
+struct Uint(ReductionPolicy);
+
+impl<'ast, T: Field> Pass<ZirProgram<'ast, T>> for Propagate {
+    type Error = Error;
+
+    fn apply(&self, p: ZirProgram<'ast, T>) -> Result<ZirProgram<'ast, T>, Error> {
+        log::debug!("Static analyser: Apply propagation in zir");
+        ZirPropagator::propagate(p).map_err(Error::from)
+    }
+}
+
+impl<'ast, T: Field> Pass<ZirProgram<'ast, T>> for Panics {
+    type Error = Error;
+
+    fn apply(&self, p: ZirProgram<'ast, T>) -> Result<ZirProgram<'ast, T>, Error> {
+        log::debug!("Static analyser: Extract panics");
+        Ok(PanicExtractor::extract(p, self.0))
+    }
+}
+
+impl<'ast, T: Field> Pass<ZirProgram<'ast, T>> for DeadCode {
+    type Error = Error;
+
+    fn apply(&self, p: ZirProgram<'ast, T>) -> Result<ZirProgram<'ast, T>, Error> {
+        log::debug!("Static analyser: Remove dead code");
+        Ok(DeadCodeEliminator::eliminate(p))
+    }
+}
+
+impl<'ast, T: Field> Pass<ZirProgram<'ast, T>> for Prune {
+    type Error = Error;
+
+    fn apply(&self, p: ZirProgram<'ast, T>) -> Result<ZirProgram<'ast, T>, Error> {
+        // the passes after propagation can introduce constant conditions, for example when extracting panics,
+        // so propagate again to prune the branches and assertions they guard
+        log::debug!("Static analyser: Prune constant conditions");
+        ZirPropagator::propagate(p).map_err(Error::from)
+    }
+}
+
+impl<'ast, T: Field> Pass<ZirProgram<'ast, T>> for Uint {
+    type Error = Error;
+
+    fn apply(&self, p: ZirProgram<'ast, T>) -> Result<ZirProgram<'ast, T>, Error> {
+        log::debug!("Static analyser: Optimize uints");
+        UintOptimizer::optimize(p, self.0).map_err(Error::from)
+    }
+}
+
+/// The zir pass registered as `id`, set up with the policies of `config`
+pub fn zir_pass<'ast, T: Field>(id: PassId, config: &CompileConfig) -> ZirPass<'ast, T> {
+    match id {
+        PassId::Propagate => Box::new(Propagate),
+        PassId::Panics => Box::new(Panics(config.div_by_zero)),
+        PassId::DeadCode => Box::new(DeadCode),
+        PassId::Prune => Box::new(Prune),
+        PassId::Uint => Box::new(Uint(config.reduction_policy)),
+        id => unreachable!("`{}` is not a zir pass", id),
+    }
+}
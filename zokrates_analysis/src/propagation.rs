@@ -13,7 +13,8 @@ use std::collections::HashMap;
 use std::convert::{TryFrom, TryInto};
 use std::fmt;
 use std::ops::{BitAnd, BitOr, BitXor, Shl, Shr, Sub};
-use zokrates_ast::common::FlatEmbed;
+use zokrates_ast::common::stack::maybe_grow;
+use zokrates_ast::common::{AssertionError, Diagnostic, Endianness, FlatEmbed, SourceMetadata};
 use zokrates_ast::typed::result_folder::*;
 use zokrates_ast::typed::types::Type;
 use zokrates_ast::typed::*;
@@ -44,25 +45,220 @@ impl fmt::Display for Error {
     }
 }
 
+/// A limit on the work done by the propagation, counted in statements and in values of arrays, structs and tuples
+/// copied from the constants
+///
+/// Once the budget is spent, the constant arrays, structs and tuples are defined in the program and left symbolic,
+/// which is always sound. Other constants are still propagated, as the reduction of the program relies on them.
+///
+/// As it is shared by all the propagations of a compilation, the budget also collects the trivially true assertions
+/// they removed.
+#[derive(Debug, Default)]
+pub struct Budget {
+    limit: Option<usize>,
+    spent: usize,
+    abandoned: Vec<Abandoned>,
+    trivial_assertions: Vec<SourceMetadata>,
+}
+
+impl Budget {
+    pub fn new(limit: Option<usize>) -> Self {
+        Budget {
+            limit,
+            ..Self::default()
+        }
+    }
+
+    pub fn spent(&self) -> usize {
+        self.spent
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.limit.map(|limit| self.spent > limit).unwrap_or(false)
+    }
+
+    /// The variables which were not propagated because the budget was spent
+    pub fn abandoned(&self) -> &[Abandoned] {
+        &self.abandoned
+    }
+
+    /// The equality assertions which were removed because both sides are identical or fold to the same constant
+    pub fn trivial_assertions(&self) -> &[SourceMetadata] {
+        &self.trivial_assertions
+    }
+}
+
+/// Constants which were left symbolic, along with the last source location reached before, if any
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Abandoned {
+    pub location: Option<SourceMetadata>,
+    pub variables: Vec<String>,
+}
+
+impl Abandoned {
+    fn stopped(&self) -> String {
+        format!(
+            "stopped propagating {}",
+            self.variables
+                .iter()
+                .map(|v| format!("`{}`", v))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+
+    /// The warning reported to the user, located at the last source location reached
+    pub fn diagnostic(&self) -> Diagnostic {
+        Diagnostic::new(format!("Propagation budget exceeded: {}", self.stopped()))
+            .span(self.location.clone())
+    }
+}
+
+impl fmt::Display for Abandoned {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.stopped())?;
+        match &self.location {
+            Some(location) => write!(f, " after {}", location),
+            None => write!(f, " at the start of `main`"),
+        }
+    }
+}
+
+/// The number of values in the constant `e`
+fn size<T>(e: &TypedExpression<'_, T>) -> usize {
+    match e {
+        TypedExpression::Array(a) => match a.as_inner() {
+            ArrayExpressionInner::Value(v) => {
+                v.0.iter()
+                    .map(|e| match e {
+                        TypedExpressionOrSpread::Expression(e) => size(e),
+                        TypedExpressionOrSpread::Spread(_) => 1,
+                    })
+                    .sum()
+            }
+            _ => 1,
+        },
+        TypedExpression::Struct(s) => match s.as_inner() {
+            StructExpressionInner::Value(v) => v.iter().map(size).sum(),
+            _ => 1,
+        },
+        TypedExpression::Tuple(t) => match t.as_inner() {
+            TupleExpressionInner::Value(v) => v.iter().map(size).sum(),
+            _ => 1,
+        },
+        _ => 1,
+    }
+}
+
+fn is_composite<T>(e: &TypedExpression<'_, T>) -> bool {
+    matches!(
+        e,
+        TypedExpression::Array(_) | TypedExpression::Struct(_) | TypedExpression::Tuple(_)
+    )
+}
+
 #[derive(Debug)]
 pub struct Propagator<'ast, 'a, T: Field> {
     // constants keeps track of constant expressions
     // we currently do not support partially constant expressions: `field [x, 1][1]` is not considered constant, `field [0, 1][1]` is
     constants: &'a mut Constants<'ast, T>,
+    budget: Option<&'a mut Budget>,
+    // the last source location reached, used to report where the propagation was abandoned
+    location: Option<SourceMetadata>,
 }
 
 impl<'ast, 'a, T: Field> Propagator<'ast, 'a, T> {
     pub fn with_constants(constants: &'a mut Constants<'ast, T>) -> Self {
-        Propagator { constants }
+        Propagator {
+            constants,
+            budget: None,
+            location: None,
+        }
+    }
+
+    pub fn with_budget(mut self, budget: &'a mut Budget) -> Self {
+        self.budget = Some(budget);
+        self
     }
 
-    pub fn propagate(p: TypedProgram<'ast, T>) -> Result<TypedProgram<'ast, T>, Error> {
+    pub fn propagate(
+        p: TypedProgram<'ast, T>,
+        budget: &mut Budget,
+    ) -> Result<TypedProgram<'ast, T>, Error> {
         let mut constants = Constants::new();
 
-        Propagator {
-            constants: &mut constants,
+        Propagator::with_constants(&mut constants)
+            .with_budget(budget)
+            .fold_program(p)
+    }
+
+    fn spend(&mut self, amount: usize) {
+        if let Some(budget) = self.budget.as_deref_mut() {
+            budget.spent += amount;
+        }
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.budget
+            .as_ref()
+            .map(|budget| budget.is_exhausted())
+            .unwrap_or(false)
+    }
+
+    // record the variables left symbolic, once even if they are visited again by a later propagation
+    fn record(&mut self, mut variables: Vec<String>) {
+        if let Some(budget) = self.budget.as_deref_mut() {
+            variables.retain(|v| !budget.abandoned.iter().any(|a| a.variables.contains(v)));
+            if !variables.is_empty() {
+                variables.sort();
+                budget.abandoned.push(Abandoned {
+                    location: self.location.clone(),
+                    variables,
+                });
+            }
+        }
+    }
+
+    // record a trivially true assertion, once even if its function is inlined several times
+    fn record_trivial(&mut self, metadata: SourceMetadata) {
+        if let Some(budget) = self.budget.as_deref_mut() {
+            if !budget.trivial_assertions.contains(&metadata) {
+                budget.trivial_assertions.push(metadata);
+            }
+        }
+    }
+
+    // once the budget is spent, define the composite constants in the program instead of propagating them
+    fn abandon(&mut self) -> Vec<TypedStatement<'ast, T>> {
+        if !self.is_exhausted() {
+            return vec![];
+        }
+
+        let mut ids: Vec<_> = self
+            .constants
+            .iter()
+            .filter(|(_, e)| is_composite(e))
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.sort();
+
+        self.record(ids.iter().map(|id| id.to_string()).collect());
+
+        ids.into_iter()
+            .map(|id| {
+                let e = self.constants.remove(&id).unwrap();
+                TypedStatement::definition(Variable::new(id, e.get_type(), true).into(), e)
+            })
+            .collect()
+    }
+
+    // whether the constant `e` assigned to `var` should be left symbolic because the budget is spent
+    fn is_abandoned(&mut self, var: &Variable<'ast, T>, e: &TypedExpression<'ast, T>) -> bool {
+        let abandoned = self.is_exhausted() && is_composite(e);
+        if abandoned {
+            self.record(vec![var.id.to_string()]);
         }
-        .fold_program(p)
+        abandoned
     }
 
     // get a mutable reference to the constant corresponding to a given assignee if any, otherwise
@@ -257,6 +453,29 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                     }
                 }
             }
+            TypedAssemblyStatement::UnsafeWitness(assignee, plugin, arguments) => {
+                let assignee = self.fold_assignee(assignee)?;
+                let arguments = arguments
+                    .into_iter()
+                    .map(|e| self.fold_expression(e))
+                    .collect::<Result<_, _>>()?;
+
+                // the outputs of a plugin are only known at execution time, invalidate the cache
+                let v = self
+                    .try_get_constant_mut(&assignee)
+                    .map(|(v, _)| v)
+                    .unwrap_or_else(|v| v);
+
+                match self.constants.remove(&v.id) {
+                    Some(c) => Ok(vec![
+                        TypedAssemblyStatement::Assignment(v.clone().into(), c),
+                        TypedAssemblyStatement::UnsafeWitness(assignee, plugin, arguments),
+                    ]),
+                    None => Ok(vec![TypedAssemblyStatement::UnsafeWitness(
+                        assignee, plugin, arguments,
+                    )]),
+                }
+            }
             TypedAssemblyStatement::Constraint(left, right, metadata) => {
                 let left = self.fold_field_expression(left)?;
                 let right = self.fold_field_expression(right)?;
@@ -267,7 +486,10 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                 let is_equal = self.fold_boolean_expression(is_equal)?;
 
                 match is_equal {
-                    BooleanExpression::Value(true) => Ok(vec![]),
+                    BooleanExpression::Value(true) => {
+                        self.record_trivial(metadata);
+                        Ok(vec![])
+                    }
                     BooleanExpression::Value(false) => {
                         Err(Error::AssertionFailed(RuntimeError::SourceAssertion(
                             metadata
@@ -286,6 +508,29 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
         &mut self,
         s: TypedStatement<'ast, T>,
     ) -> Result<Vec<TypedStatement<'ast, T>>, Error> {
+        let abandoned = self.abandon();
+        if !abandoned.is_empty() {
+            return Ok(abandoned
+                .into_iter()
+                .chain(self.fold_statement(s)?)
+                .collect());
+        }
+
+        self.spend(1);
+        match &s {
+            TypedStatement::PushCallLog(
+                _,
+                _,
+                CallSite {
+                    span: Some(span), ..
+                },
+            )
+            | TypedStatement::Assertion(_, RuntimeError::SourceAssertion(span)) => {
+                self.location = Some(SourceMetadata::new(span.file.clone(), span.position));
+            }
+            _ => {}
+        }
+
         match s {
             TypedStatement::Assembly(statements) => {
                 let statements: Vec<_> = statements
@@ -322,6 +567,10 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                         TypedAssignee::Identifier(var) => {
                             let expr = expr.into_canonical_constant();
 
+                            if self.is_abandoned(&var, &expr) {
+                                return Ok(vec![TypedStatement::definition(var.into(), expr)]);
+                            }
+
                             assert!(self.constants.insert(var.id, expr).is_none());
 
                             Ok(vec![])
@@ -369,9 +618,23 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                 let assignee = self.fold_assignee(assignee)?;
                 let embed_call = self.fold_embed_call(embed_call)?;
 
+                // packing more bits than the field holds would silently wrap around the modulus
+                if let FlatEmbed::Pack | FlatEmbed::UnpackStrict = embed_call.embed {
+                    let bit_width = embed_call.generics[0] as usize;
+                    if bit_width > T::get_required_bits() {
+                        return Err(Error::InvalidValue(format!(
+                            "Cannot call `{}` on {} bits: at most {} bits fit in a field element",
+                            embed_call.embed.id(),
+                            bit_width,
+                            T::get_required_bits()
+                        )));
+                    }
+                }
+
                 fn process_u_from_bits<'ast, T: Field>(
                     arguments: &[TypedExpression<'ast, T>],
                     bitwidth: UBitwidth,
+                    bit_order: Endianness,
                 ) -> TypedExpression<'ast, T> {
                     assert_eq!(arguments.len(), 1);
 
@@ -395,12 +658,12 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                             })
                             .enumerate()
                             .fold(0, |acc, (i, v)| {
+                                let position = match bit_order {
+                                    Endianness::Big => bitwidth.to_usize() - i - 1,
+                                    Endianness::Little => i,
+                                };
                                 if v {
-                                    acc + 2u128.pow(
-                                        (bitwidth.to_usize() - i - 1)
-                                            .try_into()
-                                            .unwrap(),
-                                    )
+                                    acc + 2u128.pow(position.try_into().unwrap())
                                 } else {
                                     acc
                                 }
@@ -415,6 +678,7 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                 fn process_u_to_bits<'ast, T: Field>(
                     arguments: &[TypedExpression<'ast, T>],
                     bitwidth: UBitwidth,
+                    bit_order: Endianness,
                 ) -> TypedExpression<'ast, T> {
                     assert_eq!(arguments.len(), 1);
 
@@ -426,7 +690,7 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                             let mut num = v;
                             let mut res = vec![];
 
-                            for i in (0..bitwidth as u32).rev() {
+                            for i in (0..bitwidth.to_usize() as u32).rev() {
                                 if 2u128.pow(i) <= num {
                                     num -= 2u128.pow(i);
                                     res.push(true);
@@ -436,6 +700,10 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                             }
                             assert_eq!(num, 0);
 
+                            if bit_order == Endianness::Little {
+                                res.reverse();
+                            }
+
                             ArrayExpressionInner::Value(
                                 res.into_iter()
                                     .map(|v| BooleanExpression::Value(v).into())
@@ -453,6 +721,8 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                     true => {
                         let r: Option<TypedExpression<'ast, T>> = match embed_call.embed {
                             FlatEmbed::BitArrayLe => Ok(None), // todo
+                            // the value is chosen by the prover, even without arguments
+                            FlatEmbed::UnconstrainedWitness => Ok(None),
                             FlatEmbed::FieldToBoolUnsafe => {
                                 match FieldElementExpression::try_from_typed(
                                     embed_call.arguments[0].clone(),
@@ -473,39 +743,113 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                                     _ => Ok(None),
                                 }
                             }
-                            FlatEmbed::U64FromBits => Ok(Some(process_u_from_bits(
-                                &embed_call.arguments,
-                                UBitwidth::B64,
-                            ))),
-                            FlatEmbed::U32FromBits => Ok(Some(process_u_from_bits(
-                                &embed_call.arguments,
-                                UBitwidth::B32,
-                            ))),
-                            FlatEmbed::U16FromBits => Ok(Some(process_u_from_bits(
-                                &embed_call.arguments,
-                                UBitwidth::B16,
-                            ))),
-                            FlatEmbed::U8FromBits => Ok(Some(process_u_from_bits(
-                                &embed_call.arguments,
-                                UBitwidth::B8,
-                            ))),
-                            FlatEmbed::U64ToBits => Ok(Some(process_u_to_bits(
-                                &embed_call.arguments,
-                                UBitwidth::B64,
-                            ))),
-                            FlatEmbed::U32ToBits => Ok(Some(process_u_to_bits(
-                                &embed_call.arguments,
-                                UBitwidth::B32,
-                            ))),
-                            FlatEmbed::U16ToBits => Ok(Some(process_u_to_bits(
+                            embed @ (FlatEmbed::FieldToU8
+                            | FlatEmbed::FieldToU16
+                            | FlatEmbed::FieldToU32
+                            | FlatEmbed::FieldToU64
+                            | FlatEmbed::FieldToU8Unchecked
+                            | FlatEmbed::FieldToU16Unchecked
+                            | FlatEmbed::FieldToU32Unchecked
+                            | FlatEmbed::FieldToU64Unchecked) => {
+                                let bitwidth = embed.field_to_uint_bitwidth().unwrap();
+
+                                match FieldElementExpression::try_from_typed(
+                                    embed_call.arguments[0].clone(),
+                                ) {
+                                    Ok(FieldElementExpression::Number(n))
+                                        if n.bits() as usize <= bitwidth.to_usize() =>
+                                    {
+                                        Ok(Some(
+                                            UExpressionInner::Value(
+                                                n.to_dec_string().parse().unwrap(),
+                                            )
+                                            .annotate(bitwidth)
+                                            .into(),
+                                        ))
+                                    }
+                                    // an unchecked conversion of a value which does not fit is left to the prover
+                                    Ok(FieldElementExpression::Number(_))
+                                        if embed.is_unchecked() =>
+                                    {
+                                        Ok(None)
+                                    }
+                                    Ok(FieldElementExpression::Number(n)) => {
+                                        Err(Error::InvalidValue(format!(
+                                            "Cannot convert `{}` to `{}`: value is too large",
+                                            n,
+                                            bitwidth.type_name()
+                                        )))
+                                    }
+                                    _ => Ok(None),
+                                }
+                            }
+                            embed @ (FlatEmbed::U64FromBits
+                            | FlatEmbed::U32FromBits
+                            | FlatEmbed::U16FromBits
+                            | FlatEmbed::U8FromBits
+                            | FlatEmbed::U64FromLeBits
+                            | FlatEmbed::U32FromLeBits
+                            | FlatEmbed::U16FromLeBits
+                            | FlatEmbed::U8FromLeBits
+                            | FlatEmbed::I32FromBits
+                            | FlatEmbed::I16FromBits
+                            | FlatEmbed::I8FromBits) => Ok(Some(process_u_from_bits(
                                 &embed_call.arguments,
-                                UBitwidth::B16,
+                                embed.from_bits_bitwidth().unwrap(),
+                                embed.bit_order(),
                             ))),
-                            FlatEmbed::U8ToBits => Ok(Some(process_u_to_bits(
+                            embed @ (FlatEmbed::U64ToBits
+                            | FlatEmbed::U32ToBits
+                            | FlatEmbed::U16ToBits
+                            | FlatEmbed::U8ToBits
+                            | FlatEmbed::U64ToLeBits
+                            | FlatEmbed::U32ToLeBits
+                            | FlatEmbed::U16ToLeBits
+                            | FlatEmbed::U8ToLeBits
+                            | FlatEmbed::I32ToBits
+                            | FlatEmbed::I16ToBits
+                            | FlatEmbed::I8ToBits) => Ok(Some(process_u_to_bits(
                                 &embed_call.arguments,
-                                UBitwidth::B8,
+                                embed.to_bits_bitwidth().unwrap(),
+                                embed.bit_order(),
                             ))),
-                            FlatEmbed::Unpack => {
+                            FlatEmbed::Pack => {
+                                assert_eq!(embed_call.arguments.len(), 1);
+
+                                let argument =
+                                    embed_call.arguments[0].clone().into_canonical_constant();
+
+                                let bits =
+                                    match ArrayExpression::try_from(argument).unwrap().into_inner()
+                                    {
+                                        ArrayExpressionInner::Value(v) => v
+                                            .into_iter()
+                                            .map(|v| match v {
+                                                TypedExpressionOrSpread::Expression(
+                                                    TypedExpression::Boolean(
+                                                        BooleanExpression::Value(v),
+                                                    ),
+                                                ) => v,
+                                                _ => unreachable!(
+                                                    "should be a constant boolean expression"
+                                                ),
+                                            })
+                                            .collect::<Vec<_>>(),
+                                        _ => unreachable!("should be an array value"),
+                                    };
+
+                                Ok(Some(
+                                    FieldElementExpression::Number(bits.into_iter().fold(
+                                        T::zero(),
+                                        |acc, bit| match bit {
+                                            true => acc * T::from(2) + T::one(),
+                                            false => acc * T::from(2),
+                                        },
+                                    ))
+                                    .into(),
+                                ))
+                            }
+                            FlatEmbed::Unpack | FlatEmbed::UnpackStrict => {
                                 assert_eq!(embed_call.arguments.len(), 1);
                                 assert_eq!(embed_call.generics.len(), 1);
 
@@ -551,8 +895,12 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                                     _ => unreachable!("should be a field value"),
                                 }
                             }
+                            FlatEmbed::BatchEq => Ok(None),
+                            FlatEmbed::MerkleVerifyPoseidon => Ok(None),
                             #[cfg(feature = "bellman")]
                             FlatEmbed::Sha256Round => Ok(None),
+                            #[cfg(feature = "bellman")]
+                            FlatEmbed::MerkleVerifySha256 => Ok(None),
                             #[cfg(feature = "ark")]
                             FlatEmbed::SnarkVerifyBls12377 => Ok(None),
                         }?;
@@ -560,6 +908,11 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                         Ok(match r {
                             // if the function call returns a constant
                             Some(expr) => match assignee {
+                                TypedAssignee::Identifier(var)
+                                    if self.is_abandoned(&var, &expr) =>
+                                {
+                                    vec![TypedStatement::definition(var.into(), expr)]
+                                }
                                 TypedAssignee::Identifier(var) => {
                                     self.constants.insert(var.id, expr);
                                     vec![]
@@ -622,10 +975,32 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                 }
             }
             TypedStatement::Assertion(e, err) => {
+                let is_equality = matches!(
+                    e,
+                    BooleanExpression::FieldEq(_)
+                        | BooleanExpression::BoolEq(_)
+                        | BooleanExpression::ArrayEq(_)
+                        | BooleanExpression::StructEq(_)
+                        | BooleanExpression::TupleEq(_)
+                        | BooleanExpression::UintEq(_)
+                );
                 let expr = self.fold_boolean_expression(e)?;
                 match expr {
                     BooleanExpression::Value(false) => Err(Error::AssertionFailed(err)),
-                    BooleanExpression::Value(true) => Ok(vec![]),
+                    // critical assertions are kept even when they are known to hold
+                    BooleanExpression::Value(true) if !err.is_critical() => {
+                        // equalities written in the source which always hold are reported, except for the elements
+                        // of a comparison of arrays, structs or tuples which only partially holds
+                        match err {
+                            RuntimeError::SourceAssertion(metadata)
+                                if is_equality && metadata.element.is_none() =>
+                            {
+                                self.record_trivial(metadata)
+                            }
+                            _ => {}
+                        }
+                        Ok(vec![])
+                    }
                     _ => Ok(vec![TypedStatement::Assertion(expr, err)]),
                 }
             }
@@ -640,7 +1015,7 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
         bitwidth: UBitwidth,
         e: UExpressionInner<'ast, T>,
     ) -> Result<UExpressionInner<'ast, T>, Error> {
-        match e {
+        maybe_grow(|| match e {
             UExpressionInner::Add(box e1, box e2) => match (
                 self.fold_uint_expression(e1)?.into_inner(),
                 self.fold_uint_expression(e2)?.into_inner(),
@@ -785,9 +1160,11 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                 let e = self.fold_uint_expression(e)?;
                 let by = self.fold_uint_expression(by)?;
                 match (e.into_inner(), by.into_inner()) {
-                    (UExpressionInner::Value(v), UExpressionInner::Value(by)) => Ok(
-                        UExpressionInner::Value((v << by) & (2_u128.pow(bitwidth as u32) - 1)),
-                    ),
+                    (UExpressionInner::Value(v), UExpressionInner::Value(by)) => {
+                        Ok(UExpressionInner::Value(
+                            (v << by) & (2_u128.pow(bitwidth.to_usize() as u32) - 1),
+                        ))
+                    }
                     (e, by) => Ok(UExpressionInner::LeftShift(
                         box e.annotate(bitwidth),
                         box by.annotate(UBitwidth::B32),
@@ -833,7 +1210,7 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                 let e = self.fold_uint_expression(e)?.into_inner();
                 match e {
                     UExpressionInner::Value(v) => Ok(UExpressionInner::Value(
-                        (!v) & (2_u128.pow(bitwidth as u32) - 1),
+                        (!v) & (2_u128.pow(bitwidth.to_usize() as u32) - 1),
                     )),
                     e => Ok(UExpressionInner::Not(box e.annotate(bitwidth))),
                 }
@@ -856,14 +1233,14 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                 }
             }
             e => fold_uint_expression_inner(self, bitwidth, e),
-        }
+        })
     }
 
     fn fold_field_expression(
         &mut self,
         e: FieldElementExpression<'ast, T>,
     ) -> Result<FieldElementExpression<'ast, T>, Error> {
-        match e {
+        maybe_grow(|| match e {
             FieldElementExpression::Add(box e1, box e2) => match (
                 self.fold_field_expression(e1)?,
                 self.fold_field_expression(e2)?,
@@ -891,14 +1268,14 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                 }
                 (e1, e2) => Ok(FieldElementExpression::Mult(box e1, box e2)),
             },
-            FieldElementExpression::Div(box e1, box e2) => match (
+            FieldElementExpression::Div(box e1, box e2, span) => match (
                 self.fold_field_expression(e1)?,
                 self.fold_field_expression(e2)?,
             ) {
                 (FieldElementExpression::Number(n1), FieldElementExpression::Number(n2)) => {
                     Ok(FieldElementExpression::Number(n1 / n2))
                 }
-                (e1, e2) => Ok(FieldElementExpression::Div(box e1, box e2)),
+                (e1, e2) => Ok(FieldElementExpression::Div(box e1, box e2, span)),
             },
             FieldElementExpression::Neg(box e) => match self.fold_field_expression(e)? {
                 FieldElementExpression::Number(n) => {
@@ -1059,7 +1436,7 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                 }
             }
             e => fold_field_expression(self, e),
-        }
+        })
     }
 
     fn fold_member_expression<
@@ -1261,7 +1638,10 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
         id: IdentifierExpression<'ast, E>,
     ) -> Result<IdentifierOrExpression<'ast, T, E>, Self::Error> {
         match self.constants.get(&id.id).cloned() {
-            Some(e) => Ok(IdentifierOrExpression::Expression(E::from(e).into_inner())),
+            Some(e) => {
+                self.spend(size(&e));
+                Ok(IdentifierOrExpression::Expression(E::from(e).into_inner()))
+            }
             None => Ok(IdentifierOrExpression::Identifier(id)),
         }
     }
@@ -1340,7 +1720,7 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
         // For example, `2 * a` is equivalent to `a + a`, but our notion of equality would not detect that here
         // These kind of reduction rules are easier to apply later in the process, when we have canonical representations
         // of expressions, ie `a + a` would always be written `2 * a`
-        match e {
+        maybe_grow(|| match e {
             BooleanExpression::FieldLt(box e1, box e2) => {
                 let e1 = self.fold_field_expression(e1)?;
                 let e2 = self.fold_field_expression(e2)?;
@@ -1389,10 +1769,12 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                 let e1 = self.fold_uint_expression(e1)?;
                 let e2 = self.fold_uint_expression(e2)?;
 
+                let bitwidth = e1.bitwidth();
+
                 match (e1.as_inner(), e2.as_inner()) {
-                    (UExpressionInner::Value(n1), UExpressionInner::Value(n2)) => {
-                        Ok(BooleanExpression::Value(n1 < n2))
-                    }
+                    (UExpressionInner::Value(n1), UExpressionInner::Value(n2)) => Ok(
+                        BooleanExpression::Value(bitwidth.order_key(*n1) < bitwidth.order_key(*n2)),
+                    ),
                     _ => Ok(BooleanExpression::UintLt(box e1, box e2)),
                 }
             }
@@ -1400,9 +1782,13 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                 let e1 = self.fold_uint_expression(e1)?;
                 let e2 = self.fold_uint_expression(e2)?;
 
+                let bitwidth = e1.bitwidth();
+
                 match (e1.as_inner(), e2.as_inner()) {
                     (UExpressionInner::Value(n1), UExpressionInner::Value(n2)) => {
-                        Ok(BooleanExpression::Value(n1 <= n2))
+                        Ok(BooleanExpression::Value(
+                            bitwidth.order_key(*n1) <= bitwidth.order_key(*n2),
+                        ))
                     }
                     _ => Ok(BooleanExpression::UintLe(box e1, box e2)),
                 }
@@ -1411,10 +1797,12 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                 let e1 = self.fold_uint_expression(e1)?;
                 let e2 = self.fold_uint_expression(e2)?;
 
+                let bitwidth = e1.bitwidth();
+
                 match (e1.as_inner(), e2.as_inner()) {
-                    (UExpressionInner::Value(n1), UExpressionInner::Value(n2)) => {
-                        Ok(BooleanExpression::Value(n1 > n2))
-                    }
+                    (UExpressionInner::Value(n1), UExpressionInner::Value(n2)) => Ok(
+                        BooleanExpression::Value(bitwidth.order_key(*n1) > bitwidth.order_key(*n2)),
+                    ),
                     _ => Ok(BooleanExpression::UintGt(box e1, box e2)),
                 }
             }
@@ -1422,9 +1810,13 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                 let e1 = self.fold_uint_expression(e1)?;
                 let e2 = self.fold_uint_expression(e2)?;
 
+                let bitwidth = e1.bitwidth();
+
                 match (e1.as_inner(), e2.as_inner()) {
                     (UExpressionInner::Value(n1), UExpressionInner::Value(n2)) => {
-                        Ok(BooleanExpression::Value(n1 >= n2))
+                        Ok(BooleanExpression::Value(
+                            bitwidth.order_key(*n1) >= bitwidth.order_key(*n2),
+                        ))
                     }
                     _ => Ok(BooleanExpression::UintGe(box e1, box e2)),
                 }
@@ -1477,7 +1869,7 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Propagator<'ast, 'a, T> {
                 }
             }
             e => fold_boolean_expression(self, e),
-        }
+        })
     }
 }
 
@@ -1538,6 +1930,7 @@ mod tests {
                 let e = FieldElementExpression::Div(
                     box FieldElementExpression::Number(Bn128Field::from(6)),
                     box FieldElementExpression::Number(Bn128Field::from(2)),
+                    None,
                 );
 
                 assert_eq!(
@@ -2201,4 +2594,82 @@ mod tests {
             }
         }
     }
+
+    mod budget {
+        use super::*;
+
+        #[test]
+        fn abandon_arrays() {
+            let a = Variable::new("a", Type::array((Type::FieldElement, 3u32)), true);
+            let a_expression = ArrayExpression::try_from(TypedExpression::from(a.clone())).unwrap();
+            let value: TypedExpression<Bn128Field> = ArrayExpressionInner::Value(
+                vec![
+                    FieldElementExpression::Number(Bn128Field::from(1)).into(),
+                    FieldElementExpression::Number(Bn128Field::from(2)).into(),
+                    FieldElementExpression::Number(Bn128Field::from(3)).into(),
+                ]
+                .into(),
+            )
+            .annotate(Type::FieldElement, 3u32)
+            .into();
+            let select = |index: u32| -> TypedExpression<Bn128Field> {
+                FieldElementExpression::select(a_expression.clone(), index).into()
+            };
+
+            let f = TypedFunction {
+                arguments: vec![],
+                statements: vec![
+                    TypedStatement::definition(a.clone().into(), value.clone()),
+                    TypedStatement::definition(Variable::field_element("b").into(), select(0)),
+                    TypedStatement::definition(Variable::field_element("c").into(), select(1)),
+                    TypedStatement::Return(select(2)),
+                ],
+                signature: DeclarationSignature::new().output(DeclarationType::FieldElement),
+            };
+
+            // without a budget, everything is propagated
+            let mut budget = Budget::default();
+            let propagated = Propagator::with_constants(&mut Constants::new())
+                .with_budget(&mut budget)
+                .fold_function(f.clone())
+                .unwrap();
+            assert_eq!(
+                propagated.statements,
+                vec![TypedStatement::Return(
+                    FieldElementExpression::Number(Bn128Field::from(3)).into()
+                )]
+            );
+            assert!(budget.abandoned().is_empty());
+
+            // reading `a` to define `b` spends the budget, so `a` is defined before `c`
+            let mut budget = Budget::new(Some(4));
+            let propagated = Propagator::with_constants(&mut Constants::new())
+                .with_budget(&mut budget)
+                .fold_function(f)
+                .unwrap();
+            assert_eq!(
+                propagated.statements,
+                vec![
+                    TypedStatement::definition(a.into(), value),
+                    TypedStatement::definition(Variable::field_element("c").into(), select(1)),
+                    TypedStatement::Return(select(2)),
+                ]
+            );
+            assert_eq!(
+                budget.abandoned(),
+                &[Abandoned {
+                    location: None,
+                    variables: vec!["a".into()]
+                }]
+            );
+            assert_eq!(
+                budget.abandoned()[0].to_string(),
+                "stopped propagating `a` at the start of `main`"
+            );
+            assert_eq!(
+                budget.abandoned()[0].diagnostic(),
+                Diagnostic::new("Propagation budget exceeded: stopped propagating `a`")
+            );
+        }
+    }
 }
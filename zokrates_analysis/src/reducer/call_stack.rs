@@ -0,0 +1,225 @@
+// The call stack keeps track of the calls being inlined around the statement currently reduced, so that recursion
+// which does not terminate is reported instead of inlining forever.
+
+// The stack is rebuilt on each reduction pass from the `PushCallLog` and `PopCallLog` statements left by the inliner.
+// Recursion is rejected when:
+// - the stack gets deeper than a given limit
+// - a function is called with the same generic values and the same constant arguments as one of its callers, as such
+//   a call can only expand to itself again
+
+use std::fmt;
+use zokrates_ast::common::SourceMetadata;
+use zokrates_ast::typed::types::ConcreteGenericsAssignment;
+use zokrates_ast::typed::{CallSite, Constant, DeclarationFunctionKey, TypedExpression};
+use zokrates_field::Field;
+
+/// The maximum depth of nested calls when none is configured
+pub const MAX_CALL_DEPTH: usize = 1000;
+
+/// A call in the chain leading to a recursion error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallFrame {
+    pub function: String,
+    pub generics: String,
+    pub span: Option<SourceMetadata>,
+}
+
+impl fmt::Display for CallFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.function)?;
+        if !self.generics.is_empty() {
+            write!(f, "::<{}>", self.generics)?;
+        }
+        match &self.span {
+            Some(span) => write!(f, " called at {}", span),
+            None => write!(f, " called at an unknown location"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecursionError {
+    /// The calls are nested deeper than the limit
+    TooDeep(usize, Vec<CallFrame>),
+    /// The last call repeats one of its callers
+    Cycle(Vec<CallFrame>),
+}
+
+impl fmt::Display for RecursionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let chain = match self {
+            RecursionError::TooDeep(max_depth, chain) => {
+                write!(f, "Maximum call depth of {} exceeded, check that recursive functions have a base case. Call chain:", max_depth)?;
+                chain
+            }
+            RecursionError::Cycle(chain) => {
+                write!(f, "Function calls itself with identical generic values and arguments, which never terminates. Call chain:")?;
+                chain
+            }
+        };
+
+        for (depth, frame) in chain.iter().enumerate() {
+            write!(f, "\n\t{}: {}", depth, frame)?;
+        }
+
+        Ok(())
+    }
+}
+
+type Frame<'ast, T> = (
+    DeclarationFunctionKey<'ast, T>,
+    ConcreteGenericsAssignment<'ast>,
+    CallSite<'ast, T>,
+);
+
+#[derive(Debug)]
+pub struct CallStack<'ast, T> {
+    frames: Vec<Frame<'ast, T>>,
+    max_depth: usize,
+}
+
+impl<'ast, T: Field> CallStack<'ast, T> {
+    pub fn new(max_depth: usize) -> Self {
+        CallStack {
+            frames: vec![],
+            max_depth,
+        }
+    }
+
+    pub fn push(
+        &mut self,
+        key: DeclarationFunctionKey<'ast, T>,
+        generics: ConcreteGenericsAssignment<'ast>,
+        site: CallSite<'ast, T>,
+    ) {
+        self.frames.push((key, generics, site));
+    }
+
+    pub fn pop(&mut self) {
+        self.frames.pop();
+    }
+
+    /// Check that calling `key` with `generics` from `site` on top of this stack may terminate
+    pub fn check(
+        &self,
+        key: &DeclarationFunctionKey<'ast, T>,
+        generics: &ConcreteGenericsAssignment<'ast>,
+        site: &CallSite<'ast, T>,
+    ) -> Result<(), RecursionError> {
+        let chain = || {
+            self.frames
+                .iter()
+                .map(|(key, generics, site)| (key, generics, site))
+                .chain(std::iter::once((key, generics, site)))
+                .map(|(key, generics, site)| CallFrame {
+                    function: format!("{}/{}", key.module.display(), key.id),
+                    generics: generics.to_string(),
+                    span: site.span.clone(),
+                })
+                .collect()
+        };
+
+        if self.frames.len() >= self.max_depth {
+            return Err(RecursionError::TooDeep(self.max_depth, chain()));
+        }
+
+        let is_cycle = site.arguments.is_some()
+            && self
+                .frames
+                .iter()
+                .any(|(k, g, s)| k == key && g == generics && s.arguments == site.arguments);
+
+        match is_cycle {
+            true => Err(RecursionError::Cycle(chain())),
+            false => Ok(()),
+        }
+    }
+}
+
+/// The call site of a call to be inlined, keeping its arguments only if they are all constant
+pub fn call_site<'ast, T: Field>(
+    span: Option<SourceMetadata>,
+    arguments: &[TypedExpression<'ast, T>],
+) -> CallSite<'ast, T> {
+    CallSite {
+        span,
+        arguments: arguments
+            .iter()
+            .all(|a| a.is_constant())
+            .then(|| arguments.to_vec()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_ast::typed::types::{
+        DeclarationSignature, GGenericsAssignment, GenericIdentifier,
+    };
+    use zokrates_ast::untyped::Position;
+    use zokrates_field::Bn128Field;
+
+    fn key() -> DeclarationFunctionKey<'static, Bn128Field> {
+        DeclarationFunctionKey::with_location("main", "foo").signature(
+            DeclarationSignature::new().generics(vec![Some(
+                GenericIdentifier::with_name("N").with_index(0).into(),
+            )]),
+        )
+    }
+
+    fn generics(value: u32) -> ConcreteGenericsAssignment<'static> {
+        GGenericsAssignment(
+            vec![(GenericIdentifier::with_name("N").with_index(0), value)]
+                .into_iter()
+                .collect(),
+        )
+    }
+
+    fn site(line: usize) -> CallSite<'static, Bn128Field> {
+        call_site(
+            Some(SourceMetadata::new(
+                "main.zok".into(),
+                Position { line, col: 12 },
+            )),
+            &[],
+        )
+    }
+
+    #[test]
+    fn too_deep() {
+        let mut stack = CallStack::new(2);
+        stack.push(key(), generics(2), site(6));
+        assert_eq!(stack.check(&key(), &generics(1), &site(2)), Ok(()));
+        stack.push(key(), generics(1), site(2));
+
+        let error = stack.check(&key(), &generics(0), &site(2)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Maximum call depth of 2 exceeded, check that recursive functions have a base case. Call chain:\
+            \n\t0: main/foo::<N: 2> called at main.zok:6:12\
+            \n\t1: main/foo::<N: 1> called at main.zok:2:12\
+            \n\t2: main/foo::<N: 0> called at main.zok:2:12"
+        );
+    }
+
+    #[test]
+    fn cycle() {
+        let mut stack = CallStack::new(MAX_CALL_DEPTH);
+        stack.push(key(), generics(2), site(6));
+
+        assert!(matches!(
+            stack.check(&key(), &generics(2), &site(2)),
+            Err(RecursionError::Cycle(chain)) if chain.len() == 2
+        ));
+
+        // different generic values may terminate
+        assert_eq!(stack.check(&key(), &generics(1), &site(2)), Ok(()));
+
+        // so may non-constant arguments
+        let non_constant = call_site(
+            None,
+            &[zokrates_ast::typed::FieldElementExpression::identifier("a".into()).into()],
+        );
+        assert_eq!(stack.check(&key(), &generics(2), &non_constant), Ok(()));
+    }
+}
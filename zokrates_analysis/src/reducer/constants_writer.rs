@@ -1,5 +1,6 @@
 // A folder to inline all constant definitions down to a single literal and register them in the state for later use.
 
+use crate::propagation::Budget;
 use crate::reducer::{
     constants_reader::ConstantsReader, reduce_function, ConstantDefinitions, Error,
 };
@@ -16,15 +17,17 @@ pub struct ConstantsWriter<'ast, T> {
     constants: ConstantDefinitions<'ast, T>,
     location: OwnedTypedModuleId,
     program: TypedProgram<'ast, T>,
+    max_call_depth: usize,
 }
 
 impl<'ast, T: Field> ConstantsWriter<'ast, T> {
-    pub fn with_program(program: TypedProgram<'ast, T>) -> Self {
+    pub fn with_program(program: TypedProgram<'ast, T>, max_call_depth: usize) -> Self {
         ConstantsWriter {
             constants: ConstantDefinitions::default(),
             location: program.main.clone(),
             treated: HashSet::default(),
             program,
+            max_call_depth,
         }
     }
 
@@ -122,6 +125,9 @@ impl<'ast, T: Field> ResultFolder<'ast, T> for ConstantsWriter<'ast, T> {
                     wrapper,
                     ConcreteGenericsAssignment::default(),
                     &self.program,
+                    self.max_call_depth,
+                    // constants must be reduced to literals, so their propagation is not limited
+                    &mut Budget::default(),
                 )?;
 
                 if let TypedStatement::Return(expression) =
@@ -149,6 +155,7 @@ impl<'ast, T: Field> ResultFolder<'ast, T> for ConstantsWriter<'ast, T> {
                                 expression,
                                 ty: c.ty,
                             }),
+                            public: d.public,
                         })
                     } else {
                         Err(Error::Type(format!("Expression of type `{}` cannot be assigned to constant `{}` of type `{}`", expression.get_type(), id, c.ty)))
@@ -26,6 +26,7 @@
 // - The body of the function is in SSA form
 // - The return value(s) are assigned to internal variables
 
+use crate::reducer::call_stack::{call_site, CallStack, RecursionError};
 use crate::reducer::Output;
 use crate::reducer::ShallowTransformer;
 use crate::reducer::Versions;
@@ -36,8 +37,9 @@ use zokrates_ast::typed::CoreIdentifier;
 use zokrates_ast::typed::Identifier;
 use zokrates_ast::typed::{
     ConcreteFunctionKey, ConcreteSignature, ConcreteVariable, DeclarationFunctionKey, Expr,
-    Signature, Type, TypedExpression, TypedFunctionSymbol, TypedFunctionSymbolDeclaration,
-    TypedProgram, TypedStatement, UExpression, UExpressionInner, Variable,
+    FunctionCallExpression, Signature, Type, TypedExpression, TypedFunctionSymbol,
+    TypedFunctionSymbolDeclaration, TypedProgram, TypedStatement, UExpression, UExpressionInner,
+    Variable,
 };
 use zokrates_field::Field;
 
@@ -55,6 +57,7 @@ pub enum InlineError<'ast, T> {
         Vec<TypedExpression<'ast, T>>,
         Type<'ast, T>,
     ),
+    Recursion(RecursionError),
 }
 
 fn get_canonical_function<'ast, T: Field>(
@@ -81,14 +84,20 @@ type InlineResult<'ast, T> = Result<
 >;
 
 pub fn inline_call<'a, 'ast, T: Field, E: Expr<'ast, T>>(
-    k: DeclarationFunctionKey<'ast, T>,
-    generics: Vec<Option<UExpression<'ast, T>>>,
-    arguments: Vec<TypedExpression<'ast, T>>,
+    call: FunctionCallExpression<'ast, T, E>,
     output: &E::Ty,
     program: &TypedProgram<'ast, T>,
     versions: &'a mut Versions<'ast>,
+    call_stack: &CallStack<'ast, T>,
 ) -> InlineResult<'ast, T> {
     use zokrates_ast::typed::Typed;
+    let FunctionCallExpression {
+        function_key: k,
+        generics,
+        arguments,
+        span,
+        ..
+    } = call;
     let output_type = output.clone().into_type();
 
     // we try to get concrete values for explicit generics
@@ -162,12 +171,18 @@ pub fn inline_call<'a, 'ast, T: Field, E: Expr<'ast, T>>(
 
     assert_eq!(f.arguments.len(), arguments.len());
 
+    let site = call_site(span, &arguments);
+
+    call_stack
+        .check(&decl.key, &assignment, &site)
+        .map_err(InlineError::Recursion)?;
+
     let (ssa_f, incomplete_data) = match ShallowTransformer::transform(f, &assignment, versions) {
         Output::Complete(v) => (v, None),
         Output::Incomplete(statements, for_loop_versions) => (statements, Some(for_loop_versions)),
     };
 
-    let call_log = TypedStatement::PushCallLog(decl.key.clone(), assignment.clone());
+    let call_log = TypedStatement::PushCallLog(decl.key.clone(), assignment.clone(), site);
 
     let input_bindings: Vec<TypedStatement<'ast, T>> = ssa_f
         .arguments
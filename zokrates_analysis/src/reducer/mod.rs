@@ -11,11 +11,13 @@
 // - unroll loops
 // - inline function calls. This includes applying shallow-ssa on the target function
 
+mod call_stack;
 mod constants_reader;
 mod constants_writer;
 mod inline;
 mod shallow_ssa;
 
+use self::call_stack::{CallStack, RecursionError};
 use self::inline::{inline_call, InlineError};
 use std::collections::HashMap;
 use zokrates_ast::typed::result_folder::*;
@@ -36,10 +38,12 @@ use zokrates_field::Field;
 use self::constants_writer::ConstantsWriter;
 use self::shallow_ssa::ShallowTransformer;
 
-use crate::propagation::{Constants, Propagator};
+use crate::propagation::{Budget, Constants, Propagator};
 
 use std::fmt;
 
+pub use self::call_stack::MAX_CALL_DEPTH;
+
 const MAX_FOR_LOOP_SIZE: u128 = 2u128.pow(20);
 
 // A map to register the canonical value of all constants. The values must be literals.
@@ -65,6 +69,7 @@ pub enum Error {
     LoopTooLarge(u128),
     ConstantReduction(String, OwnedTypedModuleId),
     Type(String),
+    Recursion(RecursionError),
 }
 
 impl fmt::Display for Error {
@@ -80,6 +85,7 @@ impl fmt::Display for Error {
             Error::LoopTooLarge(size) => write!(f, "Found a loop of size {}, which is larger than the maximum allowed of {}. Check the loop bounds, especially for underflows", size, MAX_FOR_LOOP_SIZE),
             Error::ConstantReduction(name, module) => write!(f, "Failed to reduce constant `{}` in module `{}` to a literal, try simplifying its declaration", name, module.display()),
             Error::Type(message) => write!(f, "{}", message),
+            Error::Recursion(e) => write!(f, "{}", e),
         }
     }
 }
@@ -179,6 +185,7 @@ struct Reducer<'ast, 'a, T> {
     program: &'a TypedProgram<'ast, T>,
     versions: &'a mut Versions<'ast>,
     substitutions: &'a mut Substitutions<'ast>,
+    call_stack: CallStack<'ast, T>,
     complete: bool,
 }
 
@@ -188,6 +195,7 @@ impl<'ast, 'a, T: Field> Reducer<'ast, 'a, T> {
         versions: &'a mut Versions<'ast>,
         substitutions: &'a mut Substitutions<'ast>,
         for_loop_versions: Vec<Versions<'ast>>,
+        max_call_depth: usize,
     ) -> Self {
         // we reverse the vector as it's cheaper to `pop` than to take from
         // the head
@@ -202,6 +210,7 @@ impl<'ast, 'a, T: Field> Reducer<'ast, 'a, T> {
             substitutions,
             program,
             versions,
+            call_stack: CallStack::new(max_call_depth),
             complete: true,
         }
     }
@@ -229,13 +238,14 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Reducer<'ast, 'a, T> {
             .map(|e| self.fold_expression(e))
             .collect::<Result<_, _>>()?;
 
+        let span = e.span;
+
         let res = inline_call::<_, E>(
-            e.function_key.clone(),
-            generics,
-            arguments,
+            FunctionCallExpression::new(e.function_key, generics, arguments).span(span.clone()),
             ty,
             self.program,
             self.versions,
+            &self.call_stack,
         );
 
         match res {
@@ -261,10 +271,11 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Reducer<'ast, 'a, T> {
             Err(InlineError::NonConstant(key, generics, arguments, _)) => {
                 self.complete = false;
 
-                Ok(FunctionCallOrExpression::Expression(E::function_call(
-                    key, generics, arguments,
-                )))
+                Ok(FunctionCallOrExpression::FunctionCall(
+                    FunctionCallExpression::new(key, generics, arguments).span(span),
+                ))
             }
+            Err(InlineError::Recursion(e)) => Err(Error::Recursion(e)),
             Err(InlineError::Flat(embed, generics, arguments, output_type)) => {
                 let identifier = Identifier::from(CoreIdentifier::Call(0)).version(
                     *self
@@ -323,6 +334,16 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Reducer<'ast, 'a, T> {
         &mut self,
         s: TypedStatement<'ast, T>,
     ) -> Result<Vec<TypedStatement<'ast, T>>, Self::Error> {
+        // keep track of the calls inlined in previous passes
+        match &s {
+            TypedStatement::PushCallLog(key, generics, site) => {
+                self.call_stack
+                    .push(key.clone(), generics.clone(), site.clone())
+            }
+            TypedStatement::PopCallLog => self.call_stack.pop(),
+            _ => {}
+        };
+
         let res = match s {
             TypedStatement::For(v, from, to, statements) => {
                 let versions_before = self.for_loop_versions.pop().unwrap();
@@ -421,13 +442,31 @@ impl<'ast, 'a, T: Field> ResultFolder<'ast, T> for Reducer<'ast, 'a, T> {
     }
 }
 
-pub fn reduce_program<T: Field>(p: TypedProgram<T>) -> Result<TypedProgram<T>, Error> {
-    // inline all constants and replace them in the program
+#[cfg(test)]
+fn reduce_program<T: Field>(
+    p: TypedProgram<T>,
+    max_call_depth: usize,
+) -> Result<TypedProgram<T>, Error> {
+    let p = reduce_constants(p, max_call_depth)?;
+    reduce_main(p, max_call_depth, &mut Budget::default())
+}
 
-    let mut constants_writer = ConstantsWriter::with_program(p.clone());
+/// Inline all constants down to literals and replace them in the program
+pub fn reduce_constants<T: Field>(
+    p: TypedProgram<T>,
+    max_call_depth: usize,
+) -> Result<TypedProgram<T>, Error> {
+    let mut constants_writer = ConstantsWriter::with_program(p.clone(), max_call_depth);
 
-    let p = constants_writer.fold_program(p)?;
+    constants_writer.fold_program(p)
+}
 
+/// Reduce a program whose constants were reduced to a single `main` function, within the propagation `budget`
+pub fn reduce_main<T: Field>(
+    p: TypedProgram<T>,
+    max_call_depth: usize,
+    budget: &mut Budget,
+) -> Result<TypedProgram<T>, Error> {
     // inline starting from main
     let main_module = p.modules.get(&p.main).unwrap().clone();
 
@@ -443,7 +482,13 @@ pub fn reduce_program<T: Field>(p: TypedProgram<T>) -> Result<TypedProgram<T>, E
 
     match main_function.signature.generics.len() {
         0 => {
-            let main_function = reduce_function(main_function, GGenericsAssignment::default(), &p)?;
+            let main_function = reduce_function(
+                main_function,
+                GGenericsAssignment::default(),
+                &p,
+                max_call_depth,
+                budget,
+            )?;
 
             Ok(TypedProgram {
                 main: p.main.clone(),
@@ -469,6 +514,8 @@ fn reduce_function<'ast, T: Field>(
     f: TypedFunction<'ast, T>,
     generics: ConcreteGenericsAssignment<'ast>,
     program: &TypedProgram<'ast, T>,
+    max_call_depth: usize,
+    budget: &mut Budget,
 ) -> Result<TypedFunction<'ast, T>, Error> {
     let mut versions = Versions::default();
 
@@ -491,6 +538,7 @@ fn reduce_function<'ast, T: Field>(
                     &mut versions,
                     &mut substitutions,
                     for_loop_versions,
+                    max_call_depth,
                 );
 
                 let new_f = TypedFunction {
@@ -514,6 +562,7 @@ fn reduce_function<'ast, T: Field>(
                         let new_f = Sub::new(&substitutions).fold_function(new_f);
 
                         let new_f = Propagator::with_constants(&mut constants)
+                            .with_budget(budget)
                             .fold_function(new_f)
                             .map_err(|e| Error::Incompatible(format!("{}", e)))?;
 
@@ -525,6 +574,7 @@ fn reduce_function<'ast, T: Field>(
                         let new_f = Sub::new(&substitutions).fold_function(new_f);
 
                         f = Propagator::with_constants(&mut constants)
+                            .with_budget(budget)
                             .fold_function(new_f)
                             .map_err(|e| Error::Incompatible(format!("{}", e)))?;
 
@@ -542,6 +592,7 @@ fn reduce_function<'ast, T: Field>(
     }?;
 
     Propagator::with_constants(&mut constants)
+        .with_budget(budget)
         .fold_function(f)
         .map_err(|e| Error::Incompatible(format!("{}", e)))
 }
@@ -560,7 +611,7 @@ mod tests {
     use zokrates_ast::typed::types::DeclarationSignature;
     use zokrates_ast::typed::types::{DeclarationConstant, GTupleType};
     use zokrates_ast::typed::{
-        ArrayExpression, ArrayExpressionInner, DeclarationFunctionKey, DeclarationType,
+        ArrayExpression, ArrayExpressionInner, CallSite, DeclarationFunctionKey, DeclarationType,
         DeclarationVariable, FieldElementExpression, GenericIdentifier, Identifier,
         OwnedTypedModuleId, Select, TupleExpressionInner, TupleType, Type, TypedExpression,
         TypedExpressionOrSpread, UBitwidth, UExpressionInner, Variable,
@@ -682,7 +733,7 @@ mod tests {
             .collect(),
         };
 
-        let reduced = reduce_program(p);
+        let reduced = reduce_program(p, MAX_CALL_DEPTH);
 
         let expected_main = TypedFunction {
             arguments: vec![DeclarationVariable::field_element("a").into()],
@@ -698,6 +749,7 @@ mod tests {
                             .output(DeclarationType::FieldElement),
                     ),
                     GGenericsAssignment::default(),
+                    CallSite::default(),
                 ),
                 TypedStatement::definition(
                     Variable::field_element(Identifier::from("a").version(3)).into(),
@@ -884,7 +936,7 @@ mod tests {
             .collect(),
         };
 
-        let reduced = reduce_program(p);
+        let reduced = reduce_program(p, MAX_CALL_DEPTH);
 
         let expected_main = TypedFunction {
             arguments: vec![DeclarationVariable::field_element("a").into()],
@@ -905,6 +957,7 @@ mod tests {
                             .into_iter()
                             .collect(),
                     ),
+                    CallSite::default(),
                 ),
                 TypedStatement::definition(
                     Variable::array(Identifier::from("a").version(1), Type::FieldElement, 1u32)
@@ -1117,7 +1170,7 @@ mod tests {
             .collect(),
         };
 
-        let reduced = reduce_program(p);
+        let reduced = reduce_program(p, MAX_CALL_DEPTH);
 
         let expected_main = TypedFunction {
             arguments: vec![DeclarationVariable::field_element("a").into()],
@@ -1138,6 +1191,7 @@ mod tests {
                             .into_iter()
                             .collect(),
                     ),
+                    CallSite::default(),
                 ),
                 TypedStatement::definition(
                     Variable::array(Identifier::from("a").version(1), Type::FieldElement, 1u32)
@@ -1387,7 +1441,7 @@ mod tests {
             .collect(),
         };
 
-        let reduced = reduce_program(p);
+        let reduced = reduce_program(p, MAX_CALL_DEPTH);
 
         let expected_main = TypedFunction {
             arguments: vec![],
@@ -1400,6 +1454,7 @@ mod tests {
                             .into_iter()
                             .collect(),
                     ),
+                    CallSite::default(),
                 ),
                 TypedStatement::PushCallLog(
                     DeclarationFunctionKey::with_location("main", "bar")
@@ -1409,6 +1464,7 @@ mod tests {
                             .into_iter()
                             .collect(),
                     ),
+                    CallSite::default(),
                 ),
                 TypedStatement::PopCallLog,
                 TypedStatement::PopCallLog,
@@ -1537,7 +1593,7 @@ mod tests {
             .collect(),
         };
 
-        let reduced = reduce_program(p);
+        let reduced = reduce_program(p, MAX_CALL_DEPTH);
 
         assert_eq!(
             reduced,
@@ -66,6 +66,7 @@ impl<'ast, T: Field> Folder<'ast, T> for StructConcretizer<'ast, T> {
                         member.id,
                         internal_concretizer.fold_declaration_type(*member.ty),
                     )
+                    .bound(member.bound)
                 })
                 .collect(),
             generics: concrete_generics
@@ -0,0 +1,25 @@
+//! Module exposing the lowering of single functions to zir, so that the constraints an expression lowers to can be
+//! tested without compiling a whole program.
+//!
+//! Only available with the `testing` feature.
+
+use crate::flatten_complex_types::Flattener;
+use crate::uint_optimizer::UintOptimizer;
+use crate::Error;
+use zokrates_ast::typed::TypedFunction;
+use zokrates_ast::zir::{ZirFunction, ZirProgram};
+use zokrates_common::ReductionPolicy;
+use zokrates_field::Field;
+
+/// Lower `f` to zir and set the metadata of its integers, without running the other passes of the analysis
+///
+/// `f` must already be reduced: it cannot contain function calls, loops or generic parameters.
+pub fn lower<'ast, T: Field>(f: TypedFunction<'ast, T>) -> Result<ZirFunction<'ast, T>, Error> {
+    let p = ZirProgram {
+        main: Flattener::flatten_function(f),
+    };
+
+    UintOptimizer::optimize(p, ReductionPolicy::default())
+        .map(|p| p.main)
+        .map_err(Error::from)
+}
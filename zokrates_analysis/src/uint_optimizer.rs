@@ -1,398 +1,185 @@
 use std::collections::HashMap;
+use std::fmt;
 use std::ops::{BitAnd, Shl, Shr};
-use zokrates_ast::common::FlatEmbed;
-use zokrates_ast::zir::folder::*;
+use zokrates_ast::common::stack::maybe_grow;
+use zokrates_ast::zir::folder::{self, Folder};
+use zokrates_ast::zir::result_folder::*;
 use zokrates_ast::zir::*;
+use zokrates_common::ReductionPolicy;
 use zokrates_field::Field;
 
+// the number of choices explored per statement when minimizing decompositions, the plans for later choices are greedy
+const MAX_LOOKAHEAD: usize = 8;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// An identifier was used before being defined
+    UndefinedIdentifier(String),
+    /// Unsigned integers of this bitwidth do not fit in the field
+    UnsupportedBitwidth {
+        bitwidth: usize,
+        field: &'static str,
+        field_bits: usize,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::UndefinedIdentifier(id) => {
+                write!(f, "Identifier `{}` is used before being defined", id)
+            }
+            Error::UnsupportedBitwidth {
+                bitwidth,
+                field,
+                field_bits,
+            } => write!(
+                f,
+                "u{} is not supported on {}, whose field elements have {} bits: computing on {}-bit integers requires at least {} bits",
+                bitwidth,
+                field,
+                field_bits,
+                bitwidth,
+                bitwidth + bitwidth / 2 + 2
+            ),
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct UintOptimizer<'ast, T: Field> {
     ids: HashMap<ZirAssignee<'ast>, UMetadata<T>>,
+    // the number of bits field variables are known to fit in, starting from the `field<N>` parameters
+    field_bounds: HashMap<ZirAssignee<'ast>, usize>,
+    policy: ReductionPolicy,
+    // when folding a statement under a given plan, the operand to reduce at each choice: `true` for the right one
+    plan: Option<Vec<bool>>,
+    // the number of choices encountered while folding the current statement
+    choice_count: usize,
 }
 
 impl<'ast, T: Field> UintOptimizer<'ast, T> {
     pub fn new() -> Self {
+        Self::with_policy(ReductionPolicy::default())
+    }
+
+    pub fn with_policy(policy: ReductionPolicy) -> Self {
         UintOptimizer {
             ids: HashMap::new(),
+            field_bounds: HashMap::new(),
+            policy,
+            plan: None,
+            choice_count: 0,
         }
     }
 
-    pub fn optimize(p: ZirProgram<'ast, T>) -> ZirProgram<'ast, T> {
-        UintOptimizer::new().fold_program(p)
+    pub fn optimize(
+        p: ZirProgram<'ast, T>,
+        policy: ReductionPolicy,
+    ) -> Result<ZirProgram<'ast, T>, Error> {
+        UintOptimizer::with_policy(policy).fold_program(p)
     }
 
     fn register(&mut self, a: ZirAssignee<'ast>, m: UMetadata<T>) {
         self.ids.insert(a, m);
     }
-}
 
-fn force_reduce<T: Field>(e: UExpression<T>) -> UExpression<T> {
-    let metadata = e.metadata.unwrap();
-
-    let should_reduce = metadata.should_reduce.make_true();
+    // the number of bits `e` is known to fit in, if any. Bounds which leave no room in the field are dropped, as the
+    // value may wrap around
+    fn field_bound(&self, e: &FieldElementExpression<'ast, T>) -> Option<usize> {
+        let bound = match e {
+            FieldElementExpression::Number(n) => n.bits() as usize,
+            FieldElementExpression::Identifier(id) => *self
+                .field_bounds
+                .get(&Variable::field_element(id.id.clone()))?,
+            FieldElementExpression::Add(box left, box right) => {
+                std::cmp::max(self.field_bound(left)?, self.field_bound(right)?) + 1
+            }
+            FieldElementExpression::Mult(box left, box right) => {
+                self.field_bound(left)? + self.field_bound(right)?
+            }
+            FieldElementExpression::Conditional(c) => std::cmp::max(
+                self.field_bound(&c.consequence)?,
+                self.field_bound(&c.alternative)?,
+            ),
+            _ => return None,
+        };
 
-    UExpression {
-        metadata: Some(UMetadata {
-            should_reduce,
-            ..metadata
-        }),
-        ..e
+        (bound <= T::get_required_bits() - 2).then_some(bound)
     }
-}
-
-fn force_no_reduce<T: Field>(e: UExpression<T>) -> UExpression<T> {
-    let metadata = e.metadata.unwrap();
-
-    let should_reduce = metadata.should_reduce.make_false();
 
-    UExpression {
-        metadata: Some(UMetadata {
-            should_reduce,
-            ..metadata
-        }),
-        ..e
+    // when reducing either operand alone is enough, decide whether to reduce the right one
+    fn reduce_right(&mut self) -> bool {
+        let choice = self
+            .plan
+            .as_ref()
+            .and_then(|plan| plan.get(self.choice_count).cloned())
+            .unwrap_or(false);
+        self.choice_count += 1;
+        choice
     }
-}
 
-impl<'ast, T: Field> Folder<'ast, T> for UintOptimizer<'ast, T> {
-    fn fold_select_expression<E: Expr<'ast, T> + Fold<'ast, T> + Select<'ast, T>>(
+    fn fold_statement_with_plan(
         &mut self,
-        _: &E::Ty,
-        e: SelectExpression<'ast, T, E>,
-    ) -> SelectOrExpression<'ast, T, E> {
-        let array = e.array.into_iter().map(|e| e.fold(self)).collect();
-        let index = e.index.fold(self);
-
-        SelectOrExpression::Select(SelectExpression::new(array, force_reduce(index)))
+        s: ZirStatement<'ast, T>,
+        plan: Vec<bool>,
+    ) -> Result<Vec<ZirStatement<'ast, T>>, Error> {
+        self.plan = Some(plan);
+        self.choice_count = 0;
+        let res = self.fold_statement_inner(s);
+        self.plan = None;
+        res
     }
 
-    fn fold_boolean_expression(
+    // fold the statement with every combination of the first choices it contains, and keep the one which
+    // decomposes the fewest bits. Ties are resolved in favor of the greedy plan, which is tried first.
+    fn fold_statement_minimizing(
         &mut self,
-        e: BooleanExpression<'ast, T>,
-    ) -> BooleanExpression<'ast, T> {
-        match e {
-            BooleanExpression::UintEq(box left, box right) => {
-                let left = self.fold_uint_expression(left);
-                let right = self.fold_uint_expression(right);
-
-                let left = force_reduce(left);
-                let right = force_reduce(right);
-
-                BooleanExpression::UintEq(box left, box right)
-            }
-            BooleanExpression::UintLt(box left, box right) => {
-                let left = self.fold_uint_expression(left);
-                let right = self.fold_uint_expression(right);
+        s: ZirStatement<'ast, T>,
+    ) -> Result<Vec<ZirStatement<'ast, T>>, Error> {
+        let greedy = self.fold_statement_with_plan(s.clone(), vec![])?;
 
-                let left = force_reduce(left);
-                let right = force_reduce(right);
+        let choices = std::cmp::min(self.choice_count, MAX_LOOKAHEAD);
 
-                BooleanExpression::UintLt(box left, box right)
-            }
-            BooleanExpression::UintLe(box left, box right) => {
-                let left = self.fold_uint_expression(left);
-                let right = self.fold_uint_expression(right);
-
-                let left = force_reduce(left);
-                let right = force_reduce(right);
-
-                BooleanExpression::UintLe(box left, box right)
-            }
-            e => fold_boolean_expression(self, e),
+        if choices == 0 {
+            return Ok(greedy);
         }
-    }
-
-    fn fold_uint_expression(&mut self, e: UExpression<'ast, T>) -> UExpression<'ast, T> {
-        if e.metadata.is_some() {
-            return e;
-        }
-
-        let max_bitwidth = T::get_required_bits() - 1;
-
-        let range = e.bitwidth.to_usize();
-
-        let range_max: T = (2_u128.pow(range as u32) - 1).into();
-
-        assert!(range < max_bitwidth / 2);
-
-        let inner = e.inner;
-
-        use self::UExpressionInner::*;
-
-        let res = match inner {
-            Value(v) => Value(v).annotate(range).with_max(v),
-            Identifier(id) => Identifier(id.clone()).annotate(range).metadata(
-                self.ids
-                    .get(&Variable::uint(id.id.clone(), range))
-                    .cloned()
-                    .unwrap_or_else(|| panic!("identifier should have been defined: {}", id)),
-            ),
-            Select(e) => {
-                let index = *e.index;
-                let array = e.array;
-
-                let index = self.fold_uint_expression(index);
-
-                let index = force_reduce(index);
-
-                let values: Vec<_> = array
-                    .into_iter()
-                    .map(|v| force_no_reduce(self.fold_uint_expression(v)))
-                    .collect();
-
-                let max_value = T::try_from(
-                    values
-                        .iter()
-                        .map(|v| v.metadata.as_ref().unwrap().max.to_biguint())
-                        .max()
-                        .unwrap(),
-                )
-                .unwrap();
-
-                UExpression::select(values, index).with_max(max_value)
-            }
-            Add(box left, box right) => {
-                // reduce the two terms
-                let left = self.fold_uint_expression(left);
-                let right = self.fold_uint_expression(right);
-
-                let left_max = left.metadata.clone().unwrap().max;
-                let right_max = right.metadata.clone().unwrap().max;
-
-                let (should_reduce_left, should_reduce_right, max) = left_max
-                    .checked_add(&right_max)
-                    .map(|max| (false, false, max))
-                    .unwrap_or_else(|| {
-                        range_max
-                            .checked_add(&right_max)
-                            .map(|max| (true, false, max))
-                            .unwrap_or_else(|| {
-                                left_max
-                                    .checked_add(&range_max.clone())
-                                    .map(|max| (false, true, max))
-                                    .unwrap_or_else(|| (true, true, range_max.clone() + range_max))
-                            })
-                    });
-
-                let left = if should_reduce_left {
-                    force_reduce(left)
-                } else {
-                    force_no_reduce(left)
-                };
-                let right = if should_reduce_right {
-                    force_reduce(right)
-                } else {
-                    force_no_reduce(right)
-                };
-
-                UExpression::add(left, right).with_max(max)
-            }
-            Sub(box left, box right) => {
-                // let `target` the target bitwidth of `left` and `right`
-                // `0 <= left <= max_left`
-                // `0 <= right <= max_right`
-                // `- max_right <= left - right <= max_right`
-                // let `n_bits_left` the number of bits needed to represent `max_left`
-                // let `n = max(n_bits_left, target)`
-                // let offset = 2**n`
-
-                // `2**n - max_left <= a - b + 2 ** n <= bound  where  bound = max_left + offset`
-
-                // If ´bound < N´, we return `bound` as the max of ´left - right`
-                // Else we start again, reducing `left`. In this case `max_left` becomes `2**target - 1`
-                // Else we start again, reducing `right`. In this case `offset` becomes `2**target`
-                // Else we start again reducing both. In this case `bound` becomes `2**(target+1) - 1` which is always
-                // smaller or equal to N for target in {8, 16, 32}
-
-                // reduce the two terms
-                let left = self.fold_uint_expression(left);
-                let right = self.fold_uint_expression(right);
-
-                let left_max = left.metadata.clone().unwrap().max;
-                let right_bitwidth = right.metadata.clone().unwrap().bitwidth();
-
-                let offset =
-                    T::from(2u32).pow(std::cmp::max(right_bitwidth, range as u32) as usize);
-
-                let target_offset = T::from(2u32).pow(range);
-
-                let (should_reduce_left, should_reduce_right, max) =
-                    if right_bitwidth as usize == T::get_required_bits() - 1 {
-                        // if and only if `right_bitwidth` is `T::get_required_bits() - 1`, then `offset` is out of the interval
-                        // [0, 2**(max_bitwidth)[, therefore we need to reduce `right`
-                        left_max
-                            .checked_add(&target_offset)
-                            .map(|max| (false, true, max))
-                            .unwrap_or_else(|| (true, true, range_max.clone() + target_offset))
-                    } else {
-                        left_max
-                            .checked_add(&offset)
-                            .map(|max| (false, false, max))
-                            .unwrap_or_else(|| {
-                                range_max
-                                    .clone()
-                                    .checked_add(&offset)
-                                    .map(|max| (true, false, max))
-                                    .unwrap_or_else(
-                                        // this is unreachable because the max value for `range_max + offset` is
-                                        // 2**32 + 2**(T::get_required_bits() - 2) < 2**(T::get_required_bits() - 1)
-                                        || unreachable!(),
-                                    )
-                            })
-                    };
-
-                let left = if should_reduce_left {
-                    force_reduce(left)
-                } else {
-                    force_no_reduce(left)
-                };
-                let right = if should_reduce_right {
-                    force_reduce(right)
-                } else {
-                    force_no_reduce(right)
-                };
-
-                UExpression::sub(left, right).with_max(max)
-            }
-            Xor(box left, box right) => {
-                // reduce the two terms
-                let left = self.fold_uint_expression(left);
-                let right = self.fold_uint_expression(right);
-
-                UExpression::xor(force_reduce(left), force_reduce(right)).with_max(range_max)
-            }
-            And(box left, box right) => {
-                // reduce the two terms
-                let left = self.fold_uint_expression(left);
-                let right = self.fold_uint_expression(right);
-
-                UExpression::and(force_reduce(left), force_reduce(right)).with_max(range_max)
-            }
-            Or(box left, box right) => {
-                // reduce the two terms
-                let left = self.fold_uint_expression(left);
-                let right = self.fold_uint_expression(right);
-
-                UExpression::or(force_reduce(left), force_reduce(right)).with_max(range_max)
-            }
-            Mult(box left, box right) => {
-                // reduce the two terms
-                let left = self.fold_uint_expression(left);
-                let right = self.fold_uint_expression(right);
-
-                let left_max = left.metadata.clone().unwrap().max;
-                let right_max = right.metadata.clone().unwrap().max;
-
-                let (should_reduce_left, should_reduce_right, max) = left_max
-                    .checked_mul(&right_max)
-                    .map(|max| (false, false, max))
-                    .unwrap_or_else(|| {
-                        range_max
-                            .checked_mul(&right_max)
-                            .map(|max| (true, false, max))
-                            .unwrap_or_else(|| {
-                                left_max
-                                    .checked_mul(&range_max.clone())
-                                    .map(|max| (false, true, max))
-                                    .unwrap_or_else(|| (true, true, range_max.clone() * range_max))
-                            })
-                    });
-
-                let left = if should_reduce_left {
-                    force_reduce(left)
-                } else {
-                    force_no_reduce(left)
-                };
-                let right = if should_reduce_right {
-                    force_reduce(right)
-                } else {
-                    force_no_reduce(right)
-                };
-
-                UExpression::mult(left, right).with_max(max)
-            }
-            Div(box left, box right) => {
-                // reduce the two terms
-                let left = self.fold_uint_expression(left);
-                let right = self.fold_uint_expression(right);
-
-                UExpression::div(force_reduce(left), force_reduce(right)).with_max(range_max)
-            }
-            Rem(box left, box right) => {
-                // reduce the two terms
-                let left = self.fold_uint_expression(left);
-                let right = self.fold_uint_expression(right);
-
-                UExpression::rem(force_reduce(left), force_reduce(right)).with_max(range_max)
-            }
-            Not(box e) => {
-                let e = self.fold_uint_expression(e);
-
-                UExpressionInner::Not(box force_reduce(e))
-                    .annotate(range)
-                    .with_max(range_max)
-            }
-            LeftShift(box e, by) => {
-                // reduce both terms
-                let e = self.fold_uint_expression(e);
-
-                let e_max: num_bigint::BigUint = e.metadata.as_ref().unwrap().max.to_biguint();
-                let max = e_max
-                    .shl(by as usize)
-                    .bitand(&(2_u128.pow(range as u32) - 1).into());
-
-                let max = T::try_from(max).unwrap();
-
-                UExpression::left_shift(force_reduce(e), by).with_max(max)
-            }
-            RightShift(box e, by) => {
-                // reduce both terms
-                let e = self.fold_uint_expression(e);
-
-                let e_max: num_bigint::BigUint = e.metadata.as_ref().unwrap().max.to_biguint();
-                let max = e_max
-                    .bitand(&(2_u128.pow(range as u32) - 1).into())
-                    .shr(by as usize);
-
-                let max = T::try_from(max).unwrap();
-
-                UExpression::right_shift(force_reduce(e), by).with_max(max)
-            }
-            Conditional(e) => {
-                let condition = self.fold_boolean_expression(*e.condition);
-                let consequence = e.consequence.fold(self);
-                let alternative = e.alternative.fold(self);
-
-                let consequence_max = consequence.metadata.clone().unwrap().max;
-                let alternative_max = alternative.metadata.clone().unwrap().max;
 
-                let max = std::cmp::max(consequence_max.to_biguint(), alternative_max.to_biguint());
+        let mut best = (decomposed_bits(&greedy), vec![]);
 
-                UExpression::conditional(
-                    condition,
-                    force_no_reduce(consequence),
-                    force_no_reduce(alternative),
-                )
-                .with_max(T::try_from(max).unwrap())
+        for index in 1..(1usize << choices) {
+            let plan: Vec<_> = (0..choices).map(|i| index & (1 << i) != 0).collect();
+            let cost = decomposed_bits(&self.fold_statement_with_plan(s.clone(), plan.clone())?);
+            if cost < best.0 {
+                best = (cost, plan);
             }
-        };
-
-        assert!(res.metadata.is_some());
+        }
 
-        res
+        // fold again with the best plan, so that the identifiers are registered with the right metadata
+        self.fold_statement_with_plan(s, best.1)
     }
 
-    fn fold_statement(&mut self, s: ZirStatement<'ast, T>) -> Vec<ZirStatement<'ast, T>> {
-        match s {
+    fn fold_statement_inner(
+        &mut self,
+        s: ZirStatement<'ast, T>,
+    ) -> Result<Vec<ZirStatement<'ast, T>>, Error> {
+        Ok(match s {
             ZirStatement::Definition(a, e) => {
-                let e = self.fold_expression(e);
+                let e = self.fold_expression(e)?;
 
                 let e = match e {
                     ZirExpression::Uint(i) => {
                         let i = force_no_reduce(i);
+
                         self.register(a.clone(), i.metadata.clone().unwrap());
                         ZirExpression::Uint(i)
                     }
+                    ZirExpression::FieldElement(f) => {
+                        if let Some(bound) = self.field_bound(&f) {
+                            self.field_bounds.insert(a.clone(), bound);
+                        }
+                        ZirExpression::FieldElement(f)
+                    }
                     e => e,
                 };
                 vec![ZirStatement::Definition(a, e)]
@@ -403,69 +190,45 @@ impl<'ast, T: Field> Folder<'ast, T> for UintOptimizer<'ast, T> {
                     .into_iter()
                     .map(|e| match e {
                         ZirExpression::Uint(e) => {
-                            let e = self.fold_uint_expression(e);
+                            let e = self.fold_uint_expression(e)?;
 
                             let e = force_reduce(e);
 
-                            ZirExpression::Uint(e)
+                            Ok(ZirExpression::Uint(e))
                         }
                         e => self.fold_expression(e),
                     })
-                    .collect(),
+                    .collect::<Result<_, _>>()?,
             )],
             ZirStatement::MultipleDefinition(
                 lhs,
                 ZirExpressionList::EmbedCall(embed, generics, arguments),
             ) => {
-                match embed {
-                    FlatEmbed::U64FromBits => {
-                        assert_eq!(lhs.len(), 1);
-                        self.register(
-                            lhs[0].clone(),
-                            UMetadata {
-                                max: T::from(2).pow(64) - T::from(1),
-                                should_reduce: ShouldReduce::False,
-                            },
-                        );
-                    }
-                    FlatEmbed::U32FromBits => {
-                        assert_eq!(lhs.len(), 1);
-                        self.register(
-                            lhs[0].clone(),
-                            UMetadata {
-                                max: T::from(2).pow(32) - T::from(1),
-                                should_reduce: ShouldReduce::False,
-                            },
-                        );
-                    }
-                    FlatEmbed::U16FromBits => {
-                        assert_eq!(lhs.len(), 1);
-                        self.register(
-                            lhs[0].clone(),
-                            UMetadata {
-                                max: T::from(2).pow(16) - T::from(1),
-                                should_reduce: ShouldReduce::False,
-                            },
-                        );
-                    }
-                    FlatEmbed::U8FromBits => {
-                        assert_eq!(lhs.len(), 1);
-                        self.register(
-                            lhs[0].clone(),
-                            UMetadata {
-                                max: T::from(2).pow(8) - T::from(1),
-                                should_reduce: ShouldReduce::False,
-                            },
-                        );
-                    }
-                    _ => {}
-                };
+                // the result of a field to integer conversion is in range, either proven or assumed when unchecked. It
+                // is smaller when the field element is known to fit in fewer bits
+                if let Some(bitwidth) = embed
+                    .from_bits_bitwidth()
+                    .or_else(|| embed.field_to_uint_bitwidth())
+                {
+                    assert_eq!(lhs.len(), 1);
+                    let bits = match embed.field_to_uint_bitwidth().and(arguments.first()) {
+                        Some(ZirExpression::FieldElement(e)) => self
+                            .field_bound(e)
+                            .map_or(bitwidth.to_usize(), |b| b.min(bitwidth.to_usize())),
+                        _ => bitwidth.to_usize(),
+                    };
+                    self.register(
+                        lhs[0].clone(),
+                        UMetadata {
+                            max: T::from(2).pow(bits) - T::from(1),
+                            should_reduce: ShouldReduce::False,
+                            signed: bitwidth.is_signed(),
+                        },
+                    );
+                }
 
-                match embed {
-                    FlatEmbed::U8ToBits
-                    | FlatEmbed::U16ToBits
-                    | FlatEmbed::U32ToBits
-                    | FlatEmbed::U64ToBits => {
+                match embed.to_bits_bitwidth() {
+                    Some(_) => {
                         vec![ZirStatement::MultipleDefinition(
                             lhs,
                             ZirExpressionList::EmbedCall(
@@ -475,17 +238,17 @@ impl<'ast, T: Field> Folder<'ast, T> for UintOptimizer<'ast, T> {
                                     .into_iter()
                                     .map(|e| match e {
                                         ZirExpression::Uint(e) => {
-                                            let e = self.fold_uint_expression(e);
+                                            let e = self.fold_uint_expression(e)?;
                                             let e = force_reduce(e);
-                                            ZirExpression::Uint(e)
+                                            Ok(ZirExpression::Uint(e))
                                         }
                                         e => self.fold_expression(e),
                                     })
-                                    .collect(),
+                                    .collect::<Result<_, _>>()?,
                             ),
                         )]
                     }
-                    _ => {
+                    None => {
                         vec![ZirStatement::MultipleDefinition(
                             lhs,
                             ZirExpressionList::EmbedCall(
@@ -494,15 +257,15 @@ impl<'ast, T: Field> Folder<'ast, T> for UintOptimizer<'ast, T> {
                                 arguments
                                     .into_iter()
                                     .map(|e| self.fold_expression(e))
-                                    .collect(),
+                                    .collect::<Result<_, _>>()?,
                             ),
                         )]
                     }
                 }
             }
             ZirStatement::Assertion(BooleanExpression::UintEq(box left, box right), metadata) => {
-                let left = self.fold_uint_expression(left);
-                let right = self.fold_uint_expression(right);
+                let left = self.fold_uint_expression(left)?;
+                let right = self.fold_uint_expression(right)?;
 
                 // we can only compare two unsigned integers if they are in range
                 let left = force_reduce(left);
@@ -517,44 +280,618 @@ impl<'ast, T: Field> Folder<'ast, T> for UintOptimizer<'ast, T> {
                 l,
                 e.into_iter()
                     .map(|(t, e)| {
-                        (
-                            t,
-                            e.into_iter()
-                                .map(|e| match e {
-                                    ZirExpression::Uint(e) => {
-                                        force_reduce(self.fold_uint_expression(e)).into()
-                                    }
-                                    e => self.fold_expression(e),
-                                })
-                                .collect(),
-                        )
+                        e.into_iter()
+                            .map(|e| match e {
+                                ZirExpression::Uint(e) => {
+                                    Ok(force_reduce(self.fold_uint_expression(e)?).into())
+                                }
+                                e => self.fold_expression(e),
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                            .map(|e| (t, e))
                     })
-                    .collect(),
+                    .collect::<Result<_, _>>()?,
             )],
-            s => fold_statement(self, s),
+            s => fold_statement(self, s)?,
+        })
+    }
+}
+
+// the number of bits decomposed to reduce the uint expressions of some statements
+fn decomposed_bits<T: Field>(statements: &[ZirStatement<T>]) -> usize {
+    let mut counter = DecompositionCounter::default();
+    for s in statements {
+        counter.fold_statement(s.clone());
+    }
+    counter.bits
+}
+
+#[derive(Default)]
+struct DecompositionCounter {
+    bits: usize,
+}
+
+impl<'ast, T: Field> Folder<'ast, T> for DecompositionCounter {
+    fn fold_uint_expression(&mut self, e: UExpression<'ast, T>) -> UExpression<'ast, T> {
+        if let Some(metadata) = &e.metadata {
+            // constants are decomposed at compile time
+            if metadata.should_reduce.is_true() && !matches!(e.inner, UExpressionInner::Value(_)) {
+                self.bits += std::cmp::max(metadata.bitwidth() as usize, 1);
+            }
+        }
+        folder::fold_uint_expression(self, e)
+    }
+}
+
+fn force_reduce<T: Field>(mut e: UExpression<T>) -> UExpression<T> {
+    let metadata = e.metadata.take().unwrap();
+
+    let should_reduce = metadata.should_reduce.make_true();
+
+    e.metadata = Some(UMetadata {
+        should_reduce,
+        ..metadata
+    });
+    e
+}
+
+fn force_no_reduce<T: Field>(mut e: UExpression<T>) -> UExpression<T> {
+    let metadata = e.metadata.take().unwrap();
+
+    let should_reduce = metadata.should_reduce.make_false();
+
+    e.metadata = Some(UMetadata {
+        should_reduce,
+        ..metadata
+    });
+    e
+}
+
+// the value of a constant operand
+fn constant<T>(e: &UExpression<T>) -> Option<u128> {
+    match e.inner {
+        UExpressionInner::Value(v) => Some(v),
+        _ => None,
+    }
+}
+
+// an operation on signed integers yields the two's complement representation of its signed result, as arithmetic
+// modulo `2**bitwidth` does not depend on the sign. Constants carry no sign, so an operation is signed if any of its
+// operands is
+fn has_signed_operand<T>(e: &UExpressionInner<T>) -> bool {
+    let signed = |e: &UExpression<T>| e.metadata.as_ref().map_or(false, |m| m.signed);
+
+    use self::UExpressionInner::*;
+
+    match e {
+        Value(_) | Identifier(_) => false,
+        Select(e) => e.array.iter().any(signed),
+        Add(left, right)
+        | Sub(left, right)
+        | Mult(left, right)
+        | Div(left, right)
+        | Rem(left, right)
+        | Xor(left, right)
+        | And(left, right)
+        | Or(left, right) => signed(left) || signed(right),
+        LeftShift(e, _) | RightShift(e, _) | Not(e) => signed(e),
+        Conditional(e) => signed(&e.consequence) || signed(&e.alternative),
+    }
+}
+
+// reduce an operand of a bitwise operation, unless it is a constant: the bits of a constant are known at compile time
+fn reduce_operand<T: Field>(e: UExpression<T>) -> UExpression<T> {
+    match e.inner {
+        UExpressionInner::Value(_) if !e.metadata.as_ref().unwrap().should_reduce.is_true() => {
+            force_no_reduce(e)
+        }
+        _ => force_reduce(e),
+    }
+}
+
+// fail if integers of `bitwidth` do not fit in the field, as their sums and products would wrap around the modulus
+fn check_supported<T: Field>(bitwidth: UBitwidth) -> Result<(), Error> {
+    match bitwidth.is_supported::<T>() {
+        true => Ok(()),
+        false => Err(Error::UnsupportedBitwidth {
+            bitwidth: bitwidth.to_usize(),
+            field: T::name(),
+            field_bits: T::get_required_bits(),
+        }),
+    }
+}
+
+// the max of the product of two reduced operands. When it does not fit in the field, the product is computed on
+// limbs: `left_low * right + ((left_high * right) % 2**(bitwidth - limb)) * 2**limb`
+fn reduced_product_max<T: Field>(bitwidth: UBitwidth, range_max: &T) -> T {
+    match bitwidth.splits_products::<T>() {
+        true => {
+            let limb = bitwidth.limb_bitwidth();
+            let low_max = T::from(2).pow(limb) - T::one();
+            let high_max = T::from(2).pow(bitwidth.to_usize() - limb) - T::one();
+            low_max * range_max + high_max * T::from(2).pow(limb)
         }
+        false => range_max.clone() * range_max,
+    }
+}
+
+// operands of binary operators must have the bitwidth of the operator, even when one of them was
+// obtained by casting a smaller integer
+fn check_bitwidths<T: Field>(range: usize, left: &UExpression<T>, right: &UExpression<T>) {
+    assert_eq!(
+        left.bitwidth.to_usize(),
+        range,
+        "left operand bitwidth does not match the operation"
+    );
+    assert_eq!(
+        right.bitwidth.to_usize(),
+        range,
+        "right operand bitwidth does not match the operation"
+    );
+}
+
+// the max of an expression once reduced: reduction cannot increase a value which is already in range
+fn reduced_max<T: Field>(e: &UExpression<T>, range_max: &T) -> T {
+    let max = e.metadata.as_ref().unwrap().max.clone();
+    T::try_from(std::cmp::min(max.to_biguint(), range_max.to_biguint())).unwrap()
+}
+
+// the result of `Or` and `Xor` cannot set bits higher than the highest bit of its operands
+fn bitwise_or_max<T: Field>(left: &UExpression<T>, right: &UExpression<T>, range_max: &T) -> T {
+    let max = std::cmp::max(
+        reduced_max(left, range_max).to_biguint(),
+        reduced_max(right, range_max).to_biguint(),
+    );
+    let bound = (num_bigint::BigUint::from(1u32) << max.bits()) - 1u32;
+    T::try_from(std::cmp::min(bound, range_max.to_biguint())).unwrap()
+}
+
+impl<'ast, T: Field> ResultFolder<'ast, T> for UintOptimizer<'ast, T> {
+    type Error = Error;
+
+    fn fold_select_expression<E: Clone + Expr<'ast, T> + ResultFold<'ast, T> + Select<'ast, T>>(
+        &mut self,
+        _: &E::Ty,
+        e: SelectExpression<'ast, T, E>,
+    ) -> Result<SelectOrExpression<'ast, T, E>, Self::Error> {
+        let array = e
+            .array
+            .into_iter()
+            .map(|e| e.fold(self))
+            .collect::<Result<_, _>>()?;
+        let index = e.index.fold(self)?;
+
+        Ok(SelectOrExpression::Select(SelectExpression::new(
+            array,
+            force_reduce(index),
+        )))
     }
 
-    fn fold_parameter(&mut self, p: Parameter<'ast>) -> Parameter<'ast> {
+    fn fold_boolean_expression(
+        &mut self,
+        e: BooleanExpression<'ast, T>,
+    ) -> Result<BooleanExpression<'ast, T>, Self::Error> {
+        Ok(match e {
+            BooleanExpression::UintEq(box left, box right) => {
+                let left = self.fold_uint_expression(left)?;
+                let right = self.fold_uint_expression(right)?;
+
+                let left = force_reduce(left);
+                let right = force_reduce(right);
+
+                BooleanExpression::UintEq(box left, box right)
+            }
+            BooleanExpression::UintLt(box left, box right) => {
+                // the comparison decomposes the shifted difference of the operands, see `lt_check` in codegen
+                check_supported::<T>(left.bitwidth)?;
+
+                let left = self.fold_uint_expression(left)?;
+                let right = self.fold_uint_expression(right)?;
+
+                let left = force_reduce(left);
+                let right = force_reduce(right);
+
+                BooleanExpression::UintLt(box left, box right)
+            }
+            BooleanExpression::UintLe(box left, box right) => {
+                // the comparison decomposes the shifted difference of the operands, see `lt_check` in codegen
+                check_supported::<T>(left.bitwidth)?;
+
+                let left = self.fold_uint_expression(left)?;
+                let right = self.fold_uint_expression(right)?;
+
+                let left = force_reduce(left);
+                let right = force_reduce(right);
+
+                BooleanExpression::UintLe(box left, box right)
+            }
+            e => fold_boolean_expression(self, e)?,
+        })
+    }
+
+    fn fold_uint_expression(
+        &mut self,
+        e: UExpression<'ast, T>,
+    ) -> Result<UExpression<'ast, T>, Self::Error> {
+        maybe_grow(|| {
+            if e.metadata.is_some() {
+                return Ok(e);
+            }
+
+            check_supported::<T>(e.bitwidth)?;
+
+            let bitwidth = e.bitwidth;
+
+            let range = bitwidth.to_usize();
+
+            let range_max: T = (2_u128.pow(range as u32) - 1).into();
+
+            let inner = e.into_inner();
+
+            use self::UExpressionInner::*;
+
+            let mut res = match inner {
+                Value(v) => Value(v).annotate(range).with_max(v),
+                Identifier(id) => Identifier(id.clone()).annotate(range).metadata(
+                    self.ids
+                        .get(&Variable::uint(id.id.clone(), range))
+                        .cloned()
+                        .ok_or_else(|| Error::UndefinedIdentifier(id.to_string()))?,
+                ),
+                Select(e) => {
+                    let index = *e.index;
+                    let array = e.array;
+
+                    let index = self.fold_uint_expression(index)?;
+
+                    let index = force_reduce(index);
+
+                    let values: Vec<_> = array
+                        .into_iter()
+                        .map(|v| self.fold_uint_expression(v).map(force_no_reduce))
+                        .collect::<Result<_, _>>()?;
+
+                    let max_value = T::try_from(
+                        values
+                            .iter()
+                            .map(|v| v.metadata.as_ref().unwrap().max.to_biguint())
+                            .max()
+                            .unwrap(),
+                    )
+                    .unwrap();
+
+                    UExpression::select(values, index).with_max(max_value)
+                }
+                Add(box left, box right) => {
+                    // reduce the two terms
+                    let left = self.fold_uint_expression(left)?;
+                    let right = self.fold_uint_expression(right)?;
+
+                    let left_max = left.metadata.clone().unwrap().max;
+                    let right_max = right.metadata.clone().unwrap().max;
+
+                    let (should_reduce_left, should_reduce_right, max) =
+                        match left_max.checked_add(&right_max) {
+                            Some(max) => (false, false, max),
+                            None => match (
+                                range_max.checked_add(&right_max),
+                                left_max.checked_add(&range_max),
+                            ) {
+                                (Some(left_reduced), Some(right_reduced)) => {
+                                    if self.reduce_right() {
+                                        (false, true, right_reduced)
+                                    } else {
+                                        (true, false, left_reduced)
+                                    }
+                                }
+                                (Some(max), None) => (true, false, max),
+                                (None, Some(max)) => (false, true, max),
+                                (None, None) => (true, true, range_max.clone() + range_max),
+                            },
+                        };
+
+                    let left = if should_reduce_left {
+                        force_reduce(left)
+                    } else {
+                        force_no_reduce(left)
+                    };
+                    let right = if should_reduce_right {
+                        force_reduce(right)
+                    } else {
+                        force_no_reduce(right)
+                    };
+
+                    UExpression::add(left, right).with_max(max)
+                }
+                Sub(box left, box right) => {
+                    // let `target` the target bitwidth of `left` and `right`
+                    // `0 <= left <= max_left`
+                    // `0 <= right <= max_right`
+                    // `- max_right <= left - right <= max_right`
+                    // let `n_bits_left` the number of bits needed to represent `max_left`
+                    // let `n = max(n_bits_left, target)`
+                    // let offset = 2**n`
+
+                    // `2**n - max_left <= a - b + 2 ** n <= bound  where  bound = max_left + offset`
+
+                    // If ´bound < N´, we return `bound` as the max of ´left - right`
+                    // Else we start again, reducing `left`. In this case `max_left` becomes `2**target - 1`
+                    // Else we start again, reducing `right`. In this case `offset` becomes `2**target`
+                    // Else we start again reducing both. In this case `bound` becomes `2**(target+1) - 1` which is always
+                    // smaller or equal to N for target in {8, 16, 32}
+
+                    // reduce the two terms
+                    let left = self.fold_uint_expression(left)?;
+                    let right = self.fold_uint_expression(right)?;
+
+                    let left_max = left.metadata.clone().unwrap().max;
+                    let right_bitwidth = right.metadata.clone().unwrap().bitwidth();
+
+                    let offset =
+                        T::from(2u32).pow(std::cmp::max(right_bitwidth, range as u32) as usize);
+
+                    let target_offset = T::from(2u32).pow(range);
+
+                    let (should_reduce_left, should_reduce_right, max) =
+                        if right_bitwidth as usize == T::get_required_bits() - 1 {
+                            // if and only if `right_bitwidth` is `T::get_required_bits() - 1`, then `offset` is out of the interval
+                            // [0, 2**(max_bitwidth)[, therefore we need to reduce `right`
+                            left_max
+                                .checked_add(&target_offset)
+                                .map(|max| (false, true, max))
+                                .unwrap_or_else(|| (true, true, range_max.clone() + target_offset))
+                        } else {
+                            left_max
+                                .checked_add(&offset)
+                                .map(|max| (false, false, max))
+                                .unwrap_or_else(|| {
+                                    range_max
+                                        .clone()
+                                        .checked_add(&offset)
+                                        .map(|max| (true, false, max))
+                                        .unwrap_or_else(
+                                            // this is unreachable because the max value for `range_max + offset` is
+                                            // 2**32 + 2**(T::get_required_bits() - 2) < 2**(T::get_required_bits() - 1)
+                                            || unreachable!(),
+                                        )
+                                })
+                        };
+
+                    let left = if should_reduce_left {
+                        force_reduce(left)
+                    } else {
+                        force_no_reduce(left)
+                    };
+                    let right = if should_reduce_right {
+                        force_reduce(right)
+                    } else {
+                        force_no_reduce(right)
+                    };
+
+                    UExpression::sub(left, right).with_max(max)
+                }
+                Xor(box left, box right) => {
+                    // reduce the two terms
+                    let left = self.fold_uint_expression(left)?;
+                    let right = self.fold_uint_expression(right)?;
+
+                    check_bitwidths(range, &left, &right);
+
+                    match (constant(&left), constant(&right)) {
+                        (Some(left), Some(right)) => {
+                            Value(left ^ right).annotate(range).with_max(left ^ right)
+                        }
+                        _ => {
+                            let max = bitwise_or_max(&left, &right, &range_max);
+
+                            UExpression::xor(reduce_operand(left), reduce_operand(right))
+                                .with_max(max)
+                        }
+                    }
+                }
+                And(box left, box right) => {
+                    // reduce the two terms
+                    let left = self.fold_uint_expression(left)?;
+                    let right = self.fold_uint_expression(right)?;
+
+                    check_bitwidths(range, &left, &right);
+
+                    match (constant(&left), constant(&right)) {
+                        (Some(left), Some(right)) => {
+                            Value(left & right).annotate(range).with_max(left & right)
+                        }
+                        _ => {
+                            // each bit of the result is set only if it is set in both operands, so masking with a
+                            // constant bounds the result by the constant
+                            let max = std::cmp::min(
+                                reduced_max(&left, &range_max).to_biguint(),
+                                reduced_max(&right, &range_max).to_biguint(),
+                            );
+
+                            UExpression::and(reduce_operand(left), reduce_operand(right))
+                                .with_max(T::try_from(max).unwrap())
+                        }
+                    }
+                }
+                Or(box left, box right) => {
+                    // reduce the two terms
+                    let left = self.fold_uint_expression(left)?;
+                    let right = self.fold_uint_expression(right)?;
+
+                    check_bitwidths(range, &left, &right);
+
+                    match (constant(&left), constant(&right)) {
+                        (Some(left), Some(right)) => {
+                            Value(left | right).annotate(range).with_max(left | right)
+                        }
+                        _ => {
+                            let max = bitwise_or_max(&left, &right, &range_max);
+
+                            UExpression::or(reduce_operand(left), reduce_operand(right))
+                                .with_max(max)
+                        }
+                    }
+                }
+                Mult(box left, box right) => {
+                    // reduce the two terms
+                    let left = self.fold_uint_expression(left)?;
+                    let right = self.fold_uint_expression(right)?;
+
+                    let left_max = left.metadata.clone().unwrap().max;
+                    let right_max = right.metadata.clone().unwrap().max;
+
+                    let (should_reduce_left, should_reduce_right, max) = match left_max
+                        .checked_mul(&right_max)
+                    {
+                        Some(max) => (false, false, max),
+                        None => match (
+                            range_max.checked_mul(&right_max),
+                            left_max.checked_mul(&range_max),
+                        ) {
+                            (Some(left_reduced), Some(right_reduced)) => {
+                                if self.reduce_right() {
+                                    (false, true, right_reduced)
+                                } else {
+                                    (true, false, left_reduced)
+                                }
+                            }
+                            (Some(max), None) => (true, false, max),
+                            (None, Some(max)) => (false, true, max),
+                            (None, None) => (true, true, reduced_product_max(bitwidth, &range_max)),
+                        },
+                    };
+
+                    let left = if should_reduce_left {
+                        force_reduce(left)
+                    } else {
+                        force_no_reduce(left)
+                    };
+                    let right = if should_reduce_right {
+                        force_reduce(right)
+                    } else {
+                        force_no_reduce(right)
+                    };
+
+                    UExpression::mult(left, right).with_max(max)
+                }
+                Div(box left, box right) => {
+                    // reduce the two terms
+                    let left = self.fold_uint_expression(left)?;
+                    let right = self.fold_uint_expression(right)?;
+
+                    UExpression::div(force_reduce(left), force_reduce(right)).with_max(range_max)
+                }
+                Rem(box left, box right) => {
+                    // reduce the two terms
+                    let left = self.fold_uint_expression(left)?;
+                    let right = self.fold_uint_expression(right)?;
+
+                    UExpression::rem(force_reduce(left), force_reduce(right)).with_max(range_max)
+                }
+                Not(box e) => {
+                    let e = self.fold_uint_expression(e)?;
+
+                    UExpressionInner::Not(box force_reduce(e))
+                        .annotate(range)
+                        .with_max(range_max)
+                }
+                LeftShift(box e, by) => {
+                    // reduce both terms
+                    let e = self.fold_uint_expression(e)?;
+
+                    let e_max: num_bigint::BigUint = e.metadata.as_ref().unwrap().max.to_biguint();
+                    let max = e_max
+                        .shl(by as usize)
+                        .bitand(&(2_u128.pow(range as u32) - 1).into());
+
+                    let max = T::try_from(max).unwrap();
+
+                    UExpression::left_shift(force_reduce(e), by).with_max(max)
+                }
+                RightShift(box e, by) => {
+                    // reduce both terms
+                    let e = self.fold_uint_expression(e)?;
+
+                    let e_max: num_bigint::BigUint = e.metadata.as_ref().unwrap().max.to_biguint();
+                    let max = e_max
+                        .bitand(&(2_u128.pow(range as u32) - 1).into())
+                        .shr(by as usize);
+
+                    let max = T::try_from(max).unwrap();
+
+                    UExpression::right_shift(force_reduce(e), by).with_max(max)
+                }
+                Conditional(e) => {
+                    let condition = self.fold_boolean_expression(*e.condition)?;
+                    let consequence = e.consequence.fold(self)?;
+                    let alternative = e.alternative.fold(self)?;
+
+                    let consequence_max = consequence.metadata.clone().unwrap().max;
+                    let alternative_max = alternative.metadata.clone().unwrap().max;
+
+                    let max =
+                        std::cmp::max(consequence_max.to_biguint(), alternative_max.to_biguint());
+
+                    UExpression::conditional(
+                        condition,
+                        force_no_reduce(consequence),
+                        force_no_reduce(alternative),
+                    )
+                    .with_max(T::try_from(max).unwrap())
+                }
+            };
+
+            // the sign of an identifier is the one it was registered with
+            if !matches!(res.inner, UExpressionInner::Identifier(_)) {
+                let signed = has_signed_operand(&res.inner);
+                res.metadata = res.metadata.take().map(|m| m.into_signed(signed));
+            }
+
+            assert!(res.metadata.is_some());
+
+            Ok(res)
+        })
+    }
+
+    fn fold_statement(
+        &mut self,
+        s: ZirStatement<'ast, T>,
+    ) -> Result<Vec<ZirStatement<'ast, T>>, Self::Error> {
+        // statements nested in a statement being planned follow the same plan
+        match (self.policy, &self.plan) {
+            (ReductionPolicy::MinimizeDecompositions, None) => self.fold_statement_minimizing(s),
+            _ => self.fold_statement_inner(s),
+        }
+    }
+
+    fn fold_parameter(&mut self, p: Parameter<'ast>) -> Result<Parameter<'ast>, Self::Error> {
+        if let (Type::FieldElement, Some(bound)) = (p.id.get_type(), p.bound) {
+            self.field_bounds.insert(p.id.clone(), bound as usize);
+        }
+
         let id = match p.id.get_type() {
             Type::Uint(bitwidth) => {
-                self.register(p.id.clone(), UMetadata::parameter(bitwidth));
+                check_supported::<T>(bitwidth)?;
+                self.register(
+                    p.id.clone(),
+                    UMetadata::parameter(bitwidth).into_signed(p.signed),
+                );
                 p.id
             }
             _ => p.id,
         };
 
-        Parameter {
-            id: self.fold_variable(id),
+        Ok(Parameter {
+            id: self.fold_variable(id)?,
             ..p
-        }
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use zokrates_field::Bn128Field;
+    use zokrates_field::{Bn128Field, GoldilocksField, Pow};
 
     use pretty_assertions::assert_eq;
 
@@ -588,7 +925,7 @@ mod tests {
             assert_eq!(
                 UintOptimizer::new()
                     .fold_uint_expression(UExpression::$op(left.clone(), right.clone())),
-                UExpression::$op(left_expected, right_expected).with_max($res_max)
+                Ok(UExpression::$op(left_expected, right_expected).with_max($res_max))
             );
         }};
     }
@@ -698,14 +1035,119 @@ mod tests {
         );
     }
 
+    #[test]
+    fn mult_on_limbs() {
+        // on a 64-bit field, the product of two reduced u32 values is computed on limbs, and keeps 49 bits
+        let e = |id| {
+            UExpression::<GoldilocksField>::identifier(id)
+                .annotate(32)
+                .metadata(UMetadata::with_max(GoldilocksField::max_unique_value()))
+        };
+
+        assert_eq!(
+            UintOptimizer::new()
+                .fold_uint_expression(UExpression::mult(e("a".into()), e("b".into()))),
+            Ok(
+                UExpression::mult(force_reduce(e("a".into())), force_reduce(e("b".into())))
+                    .with_max(0xfffffffe0001_u128)
+            )
+        );
+    }
+
+    #[test]
+    fn unsupported_bitwidth() {
+        // u64 values do not fit in a 64-bit field
+        let e = UExpression::<GoldilocksField>::identifier("a".into())
+            .annotate(64)
+            .metadata(UMetadata::with_max(42u32));
+
+        let error = UintOptimizer::new()
+            .fold_uint_expression(UExpression::add(e.clone(), e.clone()))
+            .unwrap_err();
+
+        assert_eq!(
+            error,
+            Error::UnsupportedBitwidth {
+                bitwidth: 64,
+                field: "goldilocks",
+                field_bits: 64
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "u64 is not supported on goldilocks, whose field elements have 64 bits: computing on 64-bit integers requires at least 98 bits"
+        );
+
+        // comparisons are rejected even if their operands were already optimized
+        assert_eq!(
+            UintOptimizer::new()
+                .fold_boolean_expression(BooleanExpression::UintLt(box e.clone(), box e)),
+            Err(Error::UnsupportedBitwidth {
+                bitwidth: 64,
+                field: "goldilocks",
+                field_bits: 64
+            })
+        );
+
+        // u32 values fit
+        assert!(UBitwidth::B32.is_supported::<GoldilocksField>());
+        assert!(UBitwidth::B32.splits_products::<GoldilocksField>());
+        assert!(!UBitwidth::B16.splits_products::<GoldilocksField>());
+        assert!(!UBitwidth::B64.splits_products::<Bn128Field>());
+    }
+
+    #[test]
+    fn minimize_decompositions() {
+        // `(x * y) * z` where `x`, `y` and `z` have 64, 192 and 32 bits. Reducing `x` forces reducing the product
+        // again, while reducing `y` does not
+        let max = |bits| Bn128Field::from(2).pow(bits) - Bn128Field::from(1);
+
+        let statement = ZirStatement::Return(vec![ZirExpression::Uint(UExpression::mult(
+            UExpression::mult(e_with_max(max(64)), e_with_max(max(192))),
+            e_with_max(max(32)),
+        ))]);
+
+        let reductions = |policy| match &UintOptimizer::with_policy(policy)
+            .fold_statement(statement.clone())
+            .unwrap()[..]
+        {
+            [ZirStatement::Return(expressions)] => match &expressions[..] {
+                [ZirExpression::Uint(UExpression {
+                    inner:
+                        UExpressionInner::Mult(
+                            box UExpression {
+                                inner: UExpressionInner::Mult(box x, box y),
+                                metadata: Some(product),
+                                ..
+                            },
+                            _,
+                        ),
+                    ..
+                })] => (
+                    x.metadata.as_ref().unwrap().should_reduce.is_true(),
+                    y.metadata.as_ref().unwrap().should_reduce.is_true(),
+                    product.should_reduce.is_true(),
+                ),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+
+        assert_eq!(reductions(ReductionPolicy::Greedy), (true, false, true));
+        assert_eq!(
+            reductions(ReductionPolicy::MinimizeDecompositions),
+            (false, true, false)
+        );
+    }
+
     #[test]
     fn bitwise() {
         // xor
-        uint_test!(42, true, 33, true, xor, 0xffffffff_u32);
+        uint_test!(42, true, 33, true, xor, 63);
         // or
-        uint_test!(42, true, 33, true, or, 0xffffffff_u32);
+        uint_test!(42, true, 33, true, or, 63);
         // and
-        uint_test!(42, true, 33, true, and, 0xffffffff_u32);
+        uint_test!(42, true, 33, true, and, 33);
         // not
         let e = e_with_max(255);
 
@@ -713,10 +1155,129 @@ mod tests {
 
         assert_eq!(
             UintOptimizer::new().fold_uint_expression(UExpression::not(e)),
-            UExpression::not(e_expected).with_max(0xffffffff_u32)
+            Ok(UExpression::not(e_expected).with_max(0xffffffff_u32))
+        );
+    }
+
+    #[test]
+    fn bitwise_mixed_bitwidths() {
+        // `u8_as_u32(x) & y`: the result fits in the smaller operand
+        uint_test!(0xff_u32, true, 0xffffffff_u32, true, and, 0xff_u32);
+        // `u8_as_u32(x) | u16_as_u32(y)`: the result fits in the larger operand
+        uint_test!(0xff_u32, true, 0xffff_u32, true, or, 0xffff_u32);
+        uint_test!(0x100_u32, true, 0x3_u32, true, xor, 0x1ff_u32);
+        // operands which exceed the range are reduced first
+        uint_test!(
+            Bn128Field::max_unique_value(),
+            true,
+            0xff_u32,
+            true,
+            and,
+            0xff_u32
+        );
+        uint_test!(
+            Bn128Field::max_unique_value(),
+            true,
+            0xff_u32,
+            true,
+            or,
+            0xffffffff_u32
         );
     }
 
+    #[test]
+    fn bitwise_constant_operands() {
+        let x = e_with_max(0xffffffff_u32);
+        let constant = |v: u128| UExpressionInner::Value(v).annotate(32);
+
+        // a constant operand is not reduced, and bounds the result of `and`
+        assert_eq!(
+            UintOptimizer::new().fold_uint_expression(UExpression::and(x.clone(), constant(0x0f))),
+            Ok(UExpression::and(
+                force_reduce(x.clone()),
+                force_no_reduce(constant(0x0f).with_max(0x0f_u32))
+            )
+            .with_max(0x0f_u32))
+        );
+
+        // the result of `or` holds on the bits of the largest operand
+        let y = e_with_max(0xff_u32);
+        assert_eq!(
+            UintOptimizer::new().fold_uint_expression(UExpression::or(constant(0x100), y.clone())),
+            Ok(UExpression::or(
+                force_no_reduce(constant(0x100).with_max(0x100_u32)),
+                force_reduce(y.clone())
+            )
+            .with_max(0x1ff_u32))
+        );
+        assert_eq!(
+            UintOptimizer::new().fold_uint_expression(UExpression::xor(y.clone(), constant(0x3))),
+            Ok(UExpression::xor(
+                force_reduce(y),
+                force_no_reduce(constant(0x3).with_max(0x3_u32))
+            )
+            .with_max(0xff_u32))
+        );
+
+        // constant operands are folded
+        assert_eq!(
+            UintOptimizer::new()
+                .fold_uint_expression(UExpression::and(constant(0xf0), constant(0x3c))),
+            Ok(constant(0x30).with_max(0x30_u32))
+        );
+        assert_eq!(
+            UintOptimizer::new()
+                .fold_uint_expression(UExpression::or(constant(0xf0), constant(0x3c))),
+            Ok(constant(0xfc).with_max(0xfc_u32))
+        );
+        assert_eq!(
+            UintOptimizer::new()
+                .fold_uint_expression(UExpression::xor(constant(0xf0), constant(0x3c))),
+            Ok(constant(0xcc).with_max(0xcc_u32))
+        );
+    }
+
+    #[test]
+    fn masked_addition() {
+        // `(x & 0x0f) + z` where `z` is close to the capacity of the field: thanks to the bound of the mask, the sum
+        // does not overflow and neither operand is reduced
+        let max = Bn128Field::max_unique_value() - Bn128Field::from(0x0f);
+        let masked = UExpression::and(
+            e_with_max(0xffffffff_u32),
+            UExpressionInner::Value(0x0f).annotate(32),
+        );
+
+        let res = UintOptimizer::new()
+            .fold_uint_expression(UExpression::add(masked, e_with_max(max.clone())))
+            .unwrap();
+
+        match &res.inner {
+            UExpressionInner::Add(left, right) => {
+                assert_eq!(left.metadata.as_ref().unwrap().max, 0x0f_u32.into());
+                assert!(!left.metadata.as_ref().unwrap().should_reduce.is_true());
+                assert!(!right.metadata.as_ref().unwrap().should_reduce.is_true());
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(
+            res.metadata.as_ref().unwrap().max,
+            Bn128Field::max_unique_value()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn bitwise_bitwidth_mismatch() {
+        let left = e_with_max(0xff_u32);
+        let right: UExpression<Bn128Field> = UExpression::identifier("bar".into())
+            .annotate(8)
+            .metadata(UMetadata::with_max(0xff_u32));
+
+        UintOptimizer::new()
+            .fold_uint_expression(UExpression::and(left, right))
+            .unwrap();
+    }
+
     #[test]
     fn right_shift() {
         fn right_shift_test<U: Into<Bn128Field>>(e_max: U, by: u32, output_max: u32) {
@@ -731,7 +1292,7 @@ mod tests {
             assert_eq!(
                 UintOptimizer::new()
                     .fold_uint_expression(UExpression::right_shift(left.clone(), right)),
-                UExpression::right_shift(left_expected, right_expected).with_max(output_max)
+                Ok(UExpression::right_shift(left_expected, right_expected).with_max(output_max))
             );
         }
 
@@ -754,7 +1315,7 @@ mod tests {
             assert_eq!(
                 UintOptimizer::new()
                     .fold_uint_expression(UExpression::left_shift(left.clone(), right)),
-                UExpression::left_shift(left_expected, right_expected).with_max(output_max)
+                Ok(UExpression::left_shift(left_expected, right_expected).with_max(output_max))
             );
         }
 
@@ -781,10 +1342,222 @@ mod tests {
                     consequence,
                     alternative
                 ))
+                .unwrap()
                 .metadata
+                .as_ref()
                 .unwrap()
                 .max,
             Bn128Field::from(42)
         );
     }
+
+    #[test]
+    fn from_bits_metadata() {
+        use zokrates_ast::common::FlatEmbed;
+
+        for embed in [
+            FlatEmbed::U8FromBits,
+            FlatEmbed::U16FromBits,
+            FlatEmbed::U32FromBits,
+            FlatEmbed::U64FromBits,
+            FlatEmbed::U8FromLeBits,
+            FlatEmbed::U16FromLeBits,
+            FlatEmbed::U32FromLeBits,
+            FlatEmbed::U64FromLeBits,
+            FlatEmbed::I8FromBits,
+            FlatEmbed::I16FromBits,
+            FlatEmbed::I32FromBits,
+        ] {
+            let bitwidth = embed.from_bits_bitwidth().unwrap();
+            let a = ZirAssignee::uint("a".into(), bitwidth.to_usize());
+
+            let mut optimizer = UintOptimizer::<Bn128Field>::new();
+            optimizer
+                .fold_statement(ZirStatement::MultipleDefinition(
+                    vec![a.clone()],
+                    ZirExpressionList::EmbedCall(
+                        embed,
+                        vec![],
+                        vec![BooleanExpression::Value(true).into(); bitwidth.to_usize()],
+                    ),
+                ))
+                .unwrap();
+
+            // the result of the embed is in range
+            assert_eq!(
+                optimizer.ids.get(&a),
+                Some(&UMetadata {
+                    max: Bn128Field::from(2).pow(bitwidth.to_usize()) - Bn128Field::from(1),
+                    should_reduce: ShouldReduce::False,
+                    signed: bitwidth.is_signed(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn bounded_field_to_uint() {
+        use zokrates_ast::common::FlatEmbed;
+
+        // def main(field<8> a) { field b = a + 1; u32 c = u32_from_field(b); u32 d = u32_from_field(a * a); }
+        let mut optimizer = UintOptimizer::<Bn128Field>::new();
+
+        optimizer
+            .fold_parameter(Parameter {
+                bound: Some(8),
+                ..Parameter::private(Variable::field_element("a"))
+            })
+            .unwrap();
+
+        let a = FieldElementExpression::identifier("a".into());
+
+        optimizer
+            .fold_statement(ZirStatement::Definition(
+                Variable::field_element("b"),
+                FieldElementExpression::Add(
+                    box a.clone(),
+                    box FieldElementExpression::Number(1.into()),
+                )
+                .into(),
+            ))
+            .unwrap();
+
+        let mut convert = |id: &'static str, e: FieldElementExpression<'static, Bn128Field>| {
+            let lhs = ZirAssignee::uint(id.into(), 32);
+            optimizer
+                .fold_statement(ZirStatement::MultipleDefinition(
+                    vec![lhs.clone()],
+                    ZirExpressionList::EmbedCall(FlatEmbed::FieldToU32, vec![], vec![e.into()]),
+                ))
+                .unwrap();
+            optimizer.ids.get(&lhs).unwrap().max
+        };
+
+        // the bound of the parameter is propagated through the definition, so the result fits in 9 bits
+        assert_eq!(
+            convert("c", FieldElementExpression::identifier("b".into())),
+            Bn128Field::from(511)
+        );
+        assert_eq!(
+            convert("d", FieldElementExpression::Mult(box a.clone(), box a)),
+            Bn128Field::from(65535)
+        );
+    }
+
+    #[test]
+    fn signed_metadata() {
+        // def main(i8 a, u8 b) { i8 c = a + 1; u8 d = b + 1; }
+        let mut optimizer = UintOptimizer::<Bn128Field>::new();
+
+        for (id, signed) in [("a", true), ("b", false)] {
+            optimizer
+                .fold_parameter(Parameter {
+                    signed,
+                    ..Parameter::private(Variable::uint(id.into(), UBitwidth::B8))
+                })
+                .unwrap();
+        }
+
+        let increment = |optimizer: &mut UintOptimizer<Bn128Field>, id: &'static str| {
+            optimizer
+                .fold_uint_expression(UExpression::add(
+                    UExpression::identifier(id.into()).annotate(UBitwidth::B8),
+                    UExpressionInner::Value(1).annotate(UBitwidth::B8),
+                ))
+                .unwrap()
+                .into_parts()
+                .1
+                .unwrap()
+        };
+
+        // the sign of the parameter is propagated through the operations on it
+        let signed = increment(&mut optimizer, "a");
+        assert!(signed.signed);
+        let unsigned = increment(&mut optimizer, "b");
+        assert!(!unsigned.signed);
+
+        // the sum is not reduced, so it may reach the sign bit and is only known to be an `i8`
+        assert_eq!(signed.signed_bounds(UBitwidth::B8), (-128, 127));
+        assert_eq!(unsigned.signed_bounds(UBitwidth::B8), (0, 255));
+
+        // a signed value below the sign bit is non-negative
+        assert_eq!(
+            UMetadata::<Bn128Field>::with_max(42u32)
+                .into_signed(true)
+                .signed_bounds(UBitwidth::B8),
+            (0, 42)
+        );
+    }
+
+    #[test]
+    fn to_bits_reduces_argument() {
+        use zokrates_ast::common::FlatEmbed;
+
+        let argument = UExpression::identifier("a".into())
+            .annotate(32)
+            .metadata(UMetadata::with_max(Bn128Field::max_unique_value()));
+
+        let mut optimizer = UintOptimizer::<Bn128Field>::new();
+        optimizer.register(
+            ZirAssignee::uint("a".into(), UBitwidth::B32),
+            UMetadata::with_max(Bn128Field::max_unique_value()),
+        );
+
+        let statements = optimizer
+            .fold_statement(ZirStatement::MultipleDefinition(
+                vec![ZirAssignee::boolean("b".into())],
+                ZirExpressionList::EmbedCall(
+                    FlatEmbed::U32ToBits,
+                    vec![],
+                    vec![argument.clone().into()],
+                ),
+            ))
+            .unwrap();
+
+        assert_eq!(
+            statements,
+            vec![ZirStatement::MultipleDefinition(
+                vec![ZirAssignee::boolean("b".into())],
+                ZirExpressionList::EmbedCall(
+                    FlatEmbed::U32ToBits,
+                    vec![],
+                    vec![force_reduce(argument).into()],
+                ),
+            )]
+        );
+    }
+
+    #[test]
+    fn undefined_identifier() {
+        let defined = |id| {
+            UExpression::<Bn128Field>::identifier(id)
+                .annotate(32)
+                .metadata(UMetadata::with_max(42u32))
+        };
+        let undefined = |id| UExpression::<Bn128Field>::identifier(id).annotate(32);
+
+        // the error is propagated from an identifier nested in an assertion
+        assert_eq!(
+            UintOptimizer::new().fold_statement(ZirStatement::Assertion(
+                BooleanExpression::UintLt(
+                    box defined("a".into()),
+                    box UExpression::not(UExpression::add(
+                        defined("b".into()),
+                        undefined("c".into())
+                    )),
+                ),
+                RuntimeError::mock(),
+            )),
+            Err(Error::UndefinedIdentifier("c".into()))
+        );
+
+        // folding stops at the first undefined identifier
+        assert_eq!(
+            UintOptimizer::new().fold_statement(ZirStatement::Return(vec![
+                undefined("d".into()).into(),
+                undefined("e".into()).into(),
+            ])),
+            Err(Error::UndefinedIdentifier("d".into()))
+        );
+    }
 }
@@ -478,6 +478,9 @@ impl<'ast, T: Field> ResultFolder<'ast, T> for VariableWriteRemover {
                 "Cannot assign to an assignee with a variable index `{}`",
                 a
             ))),
+            TypedAssemblyStatement::UnsafeWitness(a, ..) if !is_constant(&a) => Err(Error(
+                format!("Cannot assign to an assignee with a variable index `{}`", a),
+            )),
             s => Ok(vec![s]),
         }
     }
@@ -3,6 +3,8 @@ use num_bigint::BigUint;
 use std::collections::HashMap;
 use std::fmt;
 use std::ops::{BitAnd, BitOr, BitXor, Shl, Shr, Sub};
+use zokrates_ast::common::stack::maybe_grow;
+use zokrates_ast::common::AssertionError;
 use zokrates_ast::zir::types::UBitwidth;
 use zokrates_ast::zir::{
     result_folder::*, Conditional, ConditionalExpression, ConditionalOrExpression, Constant, Expr,
@@ -117,6 +119,23 @@ impl<'ast, T: Field> ResultFolder<'ast, T> for ZirPropagator<'ast, T> {
                     }
                 }
             }
+            ZirAssemblyStatement::UnsafeWitness(assignees, plugin, arguments) => {
+                let assignees: Vec<_> = assignees
+                    .into_iter()
+                    .map(|a| self.fold_assignee(a))
+                    .collect::<Result<_, _>>()?;
+                let arguments = arguments
+                    .into_iter()
+                    .map(|e| self.fold_field_expression(e))
+                    .collect::<Result<_, _>>()?;
+
+                assignees.iter().for_each(|a| {
+                    self.constants.remove(&a.id);
+                });
+                Ok(vec![ZirAssemblyStatement::UnsafeWitness(
+                    assignees, plugin, arguments,
+                )])
+            }
             ZirAssemblyStatement::Constraint(left, right, metadata) => {
                 let left = self.fold_field_expression(left)?;
                 let right = self.fold_field_expression(right)?;
@@ -147,7 +166,7 @@ impl<'ast, T: Field> ResultFolder<'ast, T> for ZirPropagator<'ast, T> {
     ) -> Result<Vec<ZirStatement<'ast, T>>, Self::Error> {
         match s {
             ZirStatement::Assertion(e, error) => match self.fold_boolean_expression(e)? {
-                BooleanExpression::Value(true) => Ok(vec![]),
+                BooleanExpression::Value(true) if !error.is_critical() => Ok(vec![]),
                 BooleanExpression::Value(false) => Err(Error::AssertionFailed(error)),
                 e => Ok(vec![ZirStatement::Assertion(e, error)]),
             },
@@ -245,7 +264,7 @@ impl<'ast, T: Field> ResultFolder<'ast, T> for ZirPropagator<'ast, T> {
         &mut self,
         e: FieldElementExpression<'ast, T>,
     ) -> Result<FieldElementExpression<'ast, T>, Self::Error> {
-        match e {
+        maybe_grow(|| match e {
             FieldElementExpression::Number(n) => Ok(FieldElementExpression::Number(n)),
             FieldElementExpression::Add(box e1, box e2) => {
                 match (
@@ -299,7 +318,7 @@ impl<'ast, T: Field> ResultFolder<'ast, T> for ZirPropagator<'ast, T> {
                     (e1, e2) => Ok(FieldElementExpression::Mult(box e1, box e2)),
                 }
             }
-            FieldElementExpression::Div(box e1, box e2) => {
+            FieldElementExpression::Div(box e1, box e2, span) => {
                 match (
                     self.fold_field_expression(e1)?,
                     self.fold_field_expression(e2)?,
@@ -311,7 +330,7 @@ impl<'ast, T: Field> ResultFolder<'ast, T> for ZirPropagator<'ast, T> {
                     (FieldElementExpression::Number(n1), FieldElementExpression::Number(n2)) => {
                         Ok(FieldElementExpression::Number(n1 / n2))
                     }
-                    (e1, e2) => Ok(FieldElementExpression::Div(box e1, box e2)),
+                    (e1, e2) => Ok(FieldElementExpression::Div(box e1, box e2, span)),
                 }
             }
             FieldElementExpression::Pow(box e, box exponent) => {
@@ -452,14 +471,14 @@ impl<'ast, T: Field> ResultFolder<'ast, T> for ZirPropagator<'ast, T> {
                 }
             }
             e => fold_field_expression(self, e),
-        }
+        })
     }
 
     fn fold_boolean_expression(
         &mut self,
         e: BooleanExpression<'ast, T>,
     ) -> Result<BooleanExpression<'ast, T>, Error> {
-        match e {
+        maybe_grow(|| match e {
             BooleanExpression::Value(v) => Ok(BooleanExpression::Value(v)),
             BooleanExpression::FieldLt(box e1, box e2) => {
                 match (
@@ -598,7 +617,7 @@ impl<'ast, T: Field> ResultFolder<'ast, T> for ZirPropagator<'ast, T> {
                 e => Ok(BooleanExpression::Not(box e)),
             },
             e => fold_boolean_expression(self, e),
-        }
+        })
     }
 
     fn fold_select_expression<
@@ -632,7 +651,7 @@ impl<'ast, T: Field> ResultFolder<'ast, T> for ZirPropagator<'ast, T> {
         bitwidth: UBitwidth,
         e: UExpressionInner<'ast, T>,
     ) -> Result<UExpressionInner<'ast, T>, Self::Error> {
-        match e {
+        maybe_grow(|| match e {
             UExpressionInner::Value(v) => Ok(UExpressionInner::Value(v)),
             UExpressionInner::Add(box e1, box e2) => {
                 let e1 = self.fold_uint_expression(e1)?;
@@ -801,7 +820,7 @@ impl<'ast, T: Field> ResultFolder<'ast, T> for ZirPropagator<'ast, T> {
                 }
             }
             e => fold_uint_expression_inner(self, bitwidth, e),
-        }
+        })
     }
 
     fn fold_conditional_expression<
@@ -841,7 +860,7 @@ impl<'ast, T: Field> ResultFolder<'ast, T> for ZirPropagator<'ast, T> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use zokrates_ast::zir::{Id, RuntimeError};
+    use zokrates_ast::zir::{Id, RuntimeError, ZirAssignee};
     use zokrates_field::Bn128Field;
 
     #[test]
@@ -883,6 +902,77 @@ mod tests {
         );
     }
 
+    #[test]
+    fn keep_critical_assertion() {
+        // #[critical] assert(1 == 1)
+        let error = RuntimeError::SourceAssertion(
+            zokrates_ast::common::SourceMetadata::default().critical(true),
+        );
+
+        let statement = ZirStatement::Assertion(
+            BooleanExpression::FieldEq(
+                box FieldElementExpression::Number(Bn128Field::from(1)),
+                box FieldElementExpression::Number(Bn128Field::from(1)),
+            ),
+            error.clone(),
+        );
+
+        let mut propagator = ZirPropagator::<Bn128Field>::default();
+
+        assert_eq!(
+            propagator.fold_statement(statement),
+            Ok(vec![ZirStatement::Assertion(
+                BooleanExpression::Value(true),
+                error
+            )])
+        );
+
+        // the same assertion is removed when it is not critical
+        assert_eq!(
+            propagator.fold_statement(ZirStatement::Assertion(
+                BooleanExpression::Value(true),
+                RuntimeError::mock()
+            )),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn select_constant_branch() {
+        // if 2u32 < 3u32 && !(1 == 2) { a = x } else { a = 2 }
+        let condition = BooleanExpression::And(
+            box BooleanExpression::UintLt(
+                box UExpressionInner::Value(2).annotate(UBitwidth::B32),
+                box UExpressionInner::Value(3).annotate(UBitwidth::B32),
+            ),
+            box BooleanExpression::Not(box BooleanExpression::FieldEq(
+                box FieldElementExpression::Number(Bn128Field::from(1)),
+                box FieldElementExpression::Number(Bn128Field::from(2)),
+            )),
+        );
+
+        let define = |e: ZirExpression<'static, Bn128Field>| {
+            ZirStatement::Definition(ZirAssignee::field_element("a"), e)
+        };
+
+        let mut propagator = ZirPropagator::<Bn128Field>::default();
+
+        assert_eq!(
+            propagator.fold_statement(ZirStatement::IfElse(
+                condition,
+                vec![define(
+                    FieldElementExpression::identifier("x".into()).into()
+                )],
+                vec![define(
+                    FieldElementExpression::Number(Bn128Field::from(2)).into()
+                )],
+            )),
+            Ok(vec![define(
+                FieldElementExpression::identifier("x".into()).into()
+            )])
+        );
+    }
+
     #[cfg(test)]
     mod field {
         use zokrates_ast::zir::Conditional;
@@ -1000,6 +1090,7 @@ mod tests {
                 propagator.fold_field_expression(FieldElementExpression::Div(
                     box FieldElementExpression::Number(Bn128Field::from(6)),
                     box FieldElementExpression::Number(Bn128Field::from(2)),
+                    None,
                 )),
                 Ok(FieldElementExpression::Number(Bn128Field::from(3)))
             );
@@ -1008,6 +1099,7 @@ mod tests {
                 propagator.fold_field_expression(FieldElementExpression::Div(
                     box FieldElementExpression::identifier("a".into()),
                     box FieldElementExpression::Number(Bn128Field::from(1)),
+                    None,
                 )),
                 Ok(FieldElementExpression::identifier("a".into()))
             );
@@ -1016,6 +1108,7 @@ mod tests {
                 propagator.fold_field_expression(FieldElementExpression::Div(
                     box FieldElementExpression::identifier("a".into()),
                     box FieldElementExpression::Number(Bn128Field::from(0)),
+                    None,
                 )),
                 Err(Error::DivisionByZero)
             );
@@ -0,0 +1,315 @@
+//! Check that a zir program is in single-assignment form: each variable is defined once, before it is used, and with
+//! the type it is used with.
+//!
+//! The zir passes and the code generation rely on this, for example the uint optimizer records the metadata of each
+//! variable when it is defined, and would silently overwrite it on a second definition. The check is run after each
+//! pass producing zir in debug builds, and when `validate_ir` is set in the configuration otherwise.
+
+use std::collections::HashMap;
+use std::fmt;
+use zokrates_ast::zir::result_visitor::*;
+use zokrates_ast::zir::*;
+use zokrates_field::Field;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// The variable is defined more than once
+    DuplicateDefinition(String),
+    /// The variable is used before it is defined, or is never defined
+    Undefined(String),
+    /// The variable is used with another type than the one it is defined with
+    TypeMismatch {
+        variable: String,
+        defined: Type,
+        used: Type,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    /// The function the statement was inlined from, `main` for the statements of `main` itself
+    pub function: String,
+    /// The index of the statement, counting nested statements in the order they appear
+    pub statement: usize,
+    pub violation: Violation,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Invalid zir in `{}` at statement #{}: ",
+            self.function, self.statement
+        )?;
+        match &self.violation {
+            Violation::DuplicateDefinition(v) => write!(f, "`{}` is defined more than once", v),
+            Violation::Undefined(v) => write!(f, "`{}` is used before it is defined", v),
+            Violation::TypeMismatch {
+                variable,
+                defined,
+                used,
+            } => write!(
+                f,
+                "`{}` is defined as `{}` but used as `{}`",
+                variable, defined, used
+            ),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ZirValidator<'ast> {
+    /// The type of each variable defined so far
+    defined: HashMap<Identifier<'ast>, Type>,
+    /// The functions the statements visited were inlined from, innermost last
+    calls: Vec<String>,
+    /// The index of the statement being checked
+    current: usize,
+    /// The number of statements visited so far
+    count: usize,
+}
+
+impl<'ast> ZirValidator<'ast> {
+    pub fn validate<T: Field>(p: &ZirProgram<'ast, T>) -> Result<(), Error> {
+        ZirValidator::default().visit_program(p)
+    }
+
+    fn error(&self, violation: Violation) -> Error {
+        Error {
+            function: self
+                .calls
+                .last()
+                .cloned()
+                .unwrap_or_else(|| String::from("main")),
+            statement: self.current,
+            violation,
+        }
+    }
+
+    fn define(&mut self, v: &Variable<'ast>) -> Result<(), Error> {
+        match self.defined.insert(v.id.clone(), v.get_type()) {
+            Some(_) => Err(self.error(Violation::DuplicateDefinition(v.id.to_string()))),
+            None => Ok(()),
+        }
+    }
+
+    fn use_variable(&self, id: &Identifier<'ast>, used: Type) -> Result<(), Error> {
+        match self.defined.get(id) {
+            None => Err(self.error(Violation::Undefined(id.to_string()))),
+            Some(defined) if *defined != used => Err(self.error(Violation::TypeMismatch {
+                variable: id.to_string(),
+                defined: defined.clone(),
+                used,
+            })),
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+impl<'ast, T: Field> ResultVisitor<'ast, T> for ZirValidator<'ast> {
+    type Error = Error;
+
+    fn visit_parameter(&mut self, p: &Parameter<'ast>) -> Result<(), Self::Error> {
+        self.define(&p.id)
+    }
+
+    fn visit_identifier(&mut self, id: &Identifier<'ast>, ty: Type) -> Result<(), Self::Error> {
+        self.use_variable(id, ty)
+    }
+
+    fn visit_statement(&mut self, s: &ZirStatement<'ast, T>) -> Result<(), Self::Error> {
+        self.current = self.count;
+        self.count += 1;
+
+        match s {
+            // the variables are defined once the expression is checked, so that they cannot be used in it
+            ZirStatement::Definition(a, e) => {
+                self.visit_expression(e)?;
+                self.define(a)
+            }
+            ZirStatement::MultipleDefinition(assignees, list) => {
+                self.visit_expression_list(list)?;
+                assignees.iter().try_for_each(|a| self.define(a))
+            }
+            ZirStatement::PushCallLog(function) => {
+                self.calls.push(function.clone());
+                Ok(())
+            }
+            ZirStatement::PopCallLog => {
+                self.calls.pop();
+                Ok(())
+            }
+            s => visit_statement(self, s),
+        }
+    }
+
+    fn visit_assembly_statement(
+        &mut self,
+        s: &ZirAssemblyStatement<'ast, T>,
+    ) -> Result<(), Self::Error> {
+        match s {
+            // the witness function is checked in its own scope, its arguments being variables of the enclosing one
+            ZirAssemblyStatement::Assignment(assignees, function) => {
+                for a in &function.arguments {
+                    self.use_variable(&a.id.id, a.id.get_type())?;
+                }
+
+                let current = self.current;
+                let defined = std::mem::take(&mut self.defined);
+                let res = self.visit_function(function);
+                self.defined = defined;
+                self.current = current;
+                res?;
+
+                assignees.iter().try_for_each(|a| self.define(a))
+            }
+            ZirAssemblyStatement::UnsafeWitness(assignees, _, arguments) => {
+                for e in arguments {
+                    self.visit_field_expression(e)?;
+                }
+                assignees.iter().try_for_each(|a| self.define(a))
+            }
+            s => visit_assembly_statement(self, s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_ast::zir::types::{Signature, UBitwidth};
+    use zokrates_field::Bn128Field;
+
+    fn validate(statements: Vec<ZirStatement<Bn128Field>>) -> Result<(), Error> {
+        ZirValidator::validate(&ZirProgram {
+            main: ZirFunction {
+                arguments: vec![Parameter::private(Variable::field_element("x"))],
+                statements,
+                signature: Signature::default(),
+            },
+        })
+    }
+
+    fn x() -> FieldElementExpression<'static, Bn128Field> {
+        FieldElementExpression::identifier("x".into())
+    }
+
+    #[test]
+    fn valid() {
+        assert_eq!(
+            validate(vec![
+                ZirStatement::Definition(Variable::field_element("a"), x().into()),
+                ZirStatement::Return(vec![FieldElementExpression::identifier("a".into()).into()]),
+            ]),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn duplicate_definition() {
+        assert_eq!(
+            validate(vec![
+                ZirStatement::PushCallLog("main.zok/foo".into()),
+                ZirStatement::Definition(Variable::field_element("a"), x().into()),
+                ZirStatement::Definition(Variable::field_element("a"), x().into()),
+                ZirStatement::PopCallLog,
+            ]),
+            Err(Error {
+                function: "main.zok/foo".into(),
+                statement: 2,
+                violation: Violation::DuplicateDefinition("a".into()),
+            })
+        );
+
+        // arguments count as definitions
+        assert_eq!(
+            validate(vec![ZirStatement::Definition(
+                Variable::field_element("x"),
+                FieldElementExpression::Number(Bn128Field::from(1)).into(),
+            )]),
+            Err(Error {
+                function: "main".into(),
+                statement: 0,
+                violation: Violation::DuplicateDefinition("x".into()),
+            })
+        );
+    }
+
+    #[test]
+    fn undefined() {
+        // a variable cannot be used in its own definition
+        assert_eq!(
+            validate(vec![ZirStatement::Definition(
+                Variable::field_element("a"),
+                FieldElementExpression::Add(
+                    box FieldElementExpression::identifier("a".into()),
+                    box x(),
+                )
+                .into(),
+            )]),
+            Err(Error {
+                function: "main".into(),
+                statement: 0,
+                violation: Violation::Undefined("a".into()),
+            })
+        );
+
+        // nor before it
+        assert_eq!(
+            validate(vec![
+                ZirStatement::Assertion(
+                    BooleanExpression::FieldEq(
+                        box FieldElementExpression::identifier("a".into()),
+                        box x(),
+                    ),
+                    RuntimeError::mock(),
+                ),
+                ZirStatement::Definition(Variable::field_element("a"), x().into()),
+            ]),
+            Err(Error {
+                function: "main".into(),
+                statement: 0,
+                violation: Violation::Undefined("a".into()),
+            })
+        );
+    }
+
+    #[test]
+    fn type_mismatch() {
+        assert_eq!(
+            validate(vec![
+                ZirStatement::Definition(
+                    Variable::uint("a".into(), UBitwidth::B32),
+                    UExpression::from(42u32).into(),
+                ),
+                ZirStatement::Return(vec![UExpression::identifier("a".into())
+                    .annotate(UBitwidth::B8)
+                    .into()]),
+            ]),
+            Err(Error {
+                function: "main".into(),
+                statement: 1,
+                violation: Violation::TypeMismatch {
+                    variable: "a".into(),
+                    defined: Type::Uint(UBitwidth::B32),
+                    used: Type::Uint(UBitwidth::B8),
+                },
+            })
+        );
+
+        assert_eq!(
+            validate(vec![ZirStatement::Return(vec![
+                BooleanExpression::identifier("x".into()).into()
+            ])]),
+            Err(Error {
+                function: "main".into(),
+                statement: 0,
+                violation: Violation::TypeMismatch {
+                    variable: "x".into(),
+                    defined: Type::FieldElement,
+                    used: Type::Boolean,
+                },
+            })
+        );
+    }
+}
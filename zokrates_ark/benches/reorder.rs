@@ -0,0 +1,73 @@
+//! Prove a long chain of squares whose intermediate variables are numbered out of order, to measure the effect of
+//! `zokrates compile --reorder-variables` on proving.
+//!
+//! Run with `cargo bench -p zokrates_ark --bench reorder`. `prove_scattered` proves the program as numbered by the
+//! compiler and `prove_reordered` proves it after the reverse Cuthill-McKee reordering.
+
+#![feature(test)]
+
+extern crate test;
+
+use test::Bencher;
+use zokrates_ark::Ark;
+use zokrates_ast::ir::ordering::reorder;
+use zokrates_ast::ir::{Parameter, Prog, QuadComb, Statement, Variable, Witness};
+use zokrates_field::Bn128Field;
+use zokrates_interpreter::Interpreter;
+use zokrates_proof_systems::groth16::G16;
+use zokrates_proof_systems::rng::get_rng_from_entropy;
+use zokrates_proof_systems::{Backend, NonUniversalBackend};
+
+const LENGTH: usize = 1 << 12;
+
+// the variable computed by the `i`-th square, spread over the whole range of indices
+fn scattered(i: usize) -> Variable {
+    Variable::new(1 + (i * 2731) % LENGTH)
+}
+
+// def main(private _0) -> ~out_0, squaring _0 `LENGTH` times
+fn program() -> Prog<'static, Bn128Field> {
+    let square = |a: Variable, b: Variable| {
+        Statement::constraint(QuadComb::from_linear_combinations(a.into(), a.into()), b)
+    };
+
+    let mut statements: Vec<_> = (0..LENGTH)
+        .map(|i| match i {
+            0 => square(Variable::new(0), scattered(0)),
+            i => square(scattered(i - 1), scattered(i)),
+        })
+        .collect();
+    statements.push(Statement::definition(
+        Variable::public(0),
+        scattered(LENGTH - 1),
+    ));
+
+    Prog::new(vec![Parameter::private(Variable::new(0))], statements, 1)
+}
+
+fn prove(b: &mut Bencher, program: Prog<'static, Bn128Field>) {
+    let rng = &mut get_rng_from_entropy("0");
+    let keypair = <Ark as NonUniversalBackend<Bn128Field, G16>>::setup(program.clone(), rng);
+    let witness: Witness<Bn128Field> = Interpreter::default()
+        .execute(program.clone(), &[Bn128Field::from(3)])
+        .unwrap();
+
+    b.iter(|| {
+        <Ark as Backend<Bn128Field, G16>>::generate_proof(
+            program.clone(),
+            witness.clone(),
+            keypair.pk.clone(),
+            rng,
+        )
+    });
+}
+
+#[bench]
+fn prove_scattered(b: &mut Bencher) {
+    prove(b, program());
+}
+
+#[bench]
+fn prove_reordered(b: &mut Bencher) {
+    prove(b, reorder(program()));
+}
@@ -40,10 +40,20 @@ impl<T: Field + ArkFieldExtensions> NonUniversalBackend<T, GM17> for Ark {
 }
 
 impl<T: Field + ArkFieldExtensions> Backend<T, GM17> for Ark {
-    fn generate_proof<'a, I: IntoIterator<Item = Statement<'a, T>>, R: RngCore + CryptoRng>(
+    type ProvingKey = ProvingKey<T::ArkEngine>;
+
+    fn read_proving_key(proving_key: &[u8]) -> Result<Self::ProvingKey, String> {
+        ProvingKey::<T::ArkEngine>::deserialize_unchecked(proving_key).map_err(|e| e.to_string())
+    }
+
+    fn generate_proof_with_key<
+        'a,
+        I: IntoIterator<Item = Statement<'a, T>>,
+        R: RngCore + CryptoRng,
+    >(
         program: ProgIterator<'a, T, I>,
         witness: Witness<T>,
-        proving_key: Vec<u8>,
+        pk: &Self::ProvingKey,
         rng: &mut R,
     ) -> Proof<T, GM17> {
         let computation = Computation::with_witness(program, witness);
@@ -54,12 +64,7 @@ impl<T: Field + ArkFieldExtensions> Backend<T, GM17> for Ark {
             .map(parse_fr::<T>)
             .collect::<Vec<_>>();
 
-        let pk = ProvingKey::<<T as ArkFieldExtensions>::ArkEngine>::deserialize_unchecked(
-            &mut proving_key.as_slice(),
-        )
-        .unwrap();
-
-        let proof = ArkGM17::<T::ArkEngine>::prove(&pk, computation, rng).unwrap();
+        let proof = ArkGM17::<T::ArkEngine>::prove(pk, computation, rng).unwrap();
         let proof_points = ProofPoints {
             a: parse_g1::<T>(&proof.a),
             b: parse_g2::<T>(&proof.b),
@@ -113,15 +118,18 @@ mod tests {
     use zokrates_ast::flat::{Parameter, Variable};
     use zokrates_ast::ir::{Prog, Statement};
     use zokrates_interpreter::Interpreter;
+    use zokrates_proof_systems::testing::check_deterministic_proofs;
 
     use super::*;
-    use zokrates_field::{Bls12_377Field, Bw6_761Field};
+    use zokrates_field::{Bls12_377Field, Bn128Field, Bw6_761Field};
 
     #[test]
     fn verify_bls12_377_field() {
         let program: Prog<Bls12_377Field> = Prog {
             arguments: vec![Parameter::public(Variable::new(0))],
             return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
             statements: vec![Statement::constraint(Variable::new(0), Variable::public(0))],
         };
 
@@ -142,11 +150,34 @@ mod tests {
         assert!(ans);
     }
 
+    #[test]
+    fn deterministic_proofs() {
+        let program: Prog<Bn128Field> = Prog {
+            arguments: vec![Parameter::private(Variable::new(0))],
+            return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
+            statements: vec![Statement::constraint(Variable::new(0), Variable::public(0))],
+        };
+
+        let keypair = <Ark as NonUniversalBackend<Bn128Field, GM17>>::setup(
+            program.clone(),
+            &mut StdRng::from_entropy(),
+        );
+        let witness = Interpreter::default()
+            .execute(program.clone(), &[Bn128Field::from(42)])
+            .unwrap();
+
+        check_deterministic_proofs::<_, GM17, Ark>(program, witness, keypair.pk);
+    }
+
     #[test]
     fn verify_bw6_761_field() {
         let program: Prog<Bw6_761Field> = Prog {
             arguments: vec![Parameter::public(Variable::new(0))],
             return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
             statements: vec![Statement::constraint(Variable::new(0), Variable::public(0))],
         };
 
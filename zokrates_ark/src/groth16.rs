@@ -6,7 +6,9 @@ use ark_groth16::{
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use zokrates_field::ArkFieldExtensions;
 use zokrates_field::Field;
-use zokrates_proof_systems::{Backend, NonUniversalBackend, Proof, SetupKeypair};
+use zokrates_proof_systems::{
+    Backend, NonUniversalBackend, Proof, RerandomizationBackend, SetupKeypair,
+};
 
 use crate::Computation;
 use crate::{parse_fr, serialization, Ark};
@@ -17,10 +19,20 @@ use zokrates_proof_systems::groth16::{ProofPoints, VerificationKey, G16};
 use zokrates_proof_systems::Scheme;
 
 impl<T: Field + ArkFieldExtensions> Backend<T, G16> for Ark {
-    fn generate_proof<'a, I: IntoIterator<Item = Statement<'a, T>>, R: RngCore + CryptoRng>(
+    type ProvingKey = ProvingKey<T::ArkEngine>;
+
+    fn read_proving_key(proving_key: &[u8]) -> Result<Self::ProvingKey, String> {
+        ProvingKey::<T::ArkEngine>::deserialize_unchecked(proving_key).map_err(|e| e.to_string())
+    }
+
+    fn generate_proof_with_key<
+        'a,
+        I: IntoIterator<Item = Statement<'a, T>>,
+        R: RngCore + CryptoRng,
+    >(
         program: ProgIterator<'a, T, I>,
         witness: Witness<T>,
-        proving_key: Vec<u8>,
+        pk: &Self::ProvingKey,
         rng: &mut R,
     ) -> Proof<T, G16> {
         let computation = Computation::with_witness(program, witness);
@@ -31,12 +43,7 @@ impl<T: Field + ArkFieldExtensions> Backend<T, G16> for Ark {
             .map(parse_fr::<T>)
             .collect::<Vec<_>>();
 
-        let pk = ProvingKey::<<T as ArkFieldExtensions>::ArkEngine>::deserialize_unchecked(
-            &mut proving_key.as_slice(),
-        )
-        .unwrap();
-
-        let proof = Groth16::<T::ArkEngine>::prove(&pk, computation, rng).unwrap();
+        let proof = Groth16::<T::ArkEngine>::prove(pk, computation, rng).unwrap();
         let proof_points = ProofPoints {
             a: parse_g1::<T>(&proof.a),
             b: parse_g2::<T>(&proof.b),
@@ -80,6 +87,34 @@ impl<T: Field + ArkFieldExtensions> Backend<T, G16> for Ark {
     }
 }
 
+impl<T: Field + ArkFieldExtensions> RerandomizationBackend<T, G16> for Ark {
+    fn rerandomize<R: RngCore + CryptoRng>(
+        vk: &<G16 as Scheme<T>>::VerificationKey,
+        proof: &Proof<T, G16>,
+        rng: &mut R,
+    ) -> Proof<T, G16> {
+        use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+        use ark_ff::{Field as ArkField, UniformRand};
+
+        let delta = serialization::to_g2::<T>(vk.delta.clone());
+        let a = serialization::to_g1::<T>(proof.proof.a.clone());
+        let b = serialization::to_g2::<T>(proof.proof.b.clone());
+        let c = serialization::to_g1::<T>(proof.proof.c.clone());
+
+        let r = <T::ArkEngine as PairingEngine>::Fr::rand(rng);
+        let s = <T::ArkEngine as PairingEngine>::Fr::rand(rng);
+
+        // (A, B, C) becomes (A / r, r * B + r * s * delta, C + s * A), which satisfies the same pairing equation
+        let proof_points = ProofPoints {
+            a: parse_g1::<T>(&a.mul(r.inverse().unwrap()).into_affine()),
+            b: parse_g2::<T>(&(b.mul(r) + delta.mul(r * s)).into_affine()),
+            c: parse_g1::<T>(&(c.into_projective() + a.mul(s)).into_affine()),
+        };
+
+        Proof::new(proof_points, proof.inputs.clone())
+    }
+}
+
 impl<T: Field + ArkFieldExtensions> NonUniversalBackend<T, G16> for Ark {
     fn setup<'a, I: IntoIterator<Item = Statement<'a, T>>, R: RngCore + CryptoRng>(
         program: ProgIterator<'a, T, I>,
@@ -108,17 +143,21 @@ mod tests {
     use rand_0_8::rngs::StdRng;
     use rand_0_8::SeedableRng;
     use zokrates_ast::flat::{Parameter, Variable};
-    use zokrates_ast::ir::{Prog, Statement};
+    use zokrates_ast::ir::ordering::reorder;
+    use zokrates_ast::ir::{LinComb, Prog, QuadComb, Statement};
     use zokrates_interpreter::Interpreter;
+    use zokrates_proof_systems::testing::check_deterministic_proofs;
 
     use super::*;
-    use zokrates_field::{Bls12_377Field, Bw6_761Field};
+    use zokrates_field::{Bls12_377Field, Bn128Field, Bw6_761Field};
 
     #[test]
     fn verify_bls12_377_field() {
         let program: Prog<Bls12_377Field> = Prog {
             arguments: vec![Parameter::public(Variable::new(0))],
             return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
             statements: vec![Statement::constraint(Variable::new(0), Variable::public(0))],
         };
 
@@ -144,6 +183,8 @@ mod tests {
         let program: Prog<Bw6_761Field> = Prog {
             arguments: vec![Parameter::public(Variable::new(0))],
             return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
             statements: vec![Statement::constraint(Variable::new(0), Variable::public(0))],
         };
 
@@ -161,4 +202,306 @@ mod tests {
 
         assert!(ans);
     }
+
+    #[test]
+    fn deterministic_proofs() {
+        let program: Prog<Bn128Field> = Prog {
+            arguments: vec![Parameter::private(Variable::new(0))],
+            return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
+            statements: vec![Statement::constraint(Variable::new(0), Variable::public(0))],
+        };
+
+        let keypair = <Ark as NonUniversalBackend<Bn128Field, G16>>::setup(
+            program.clone(),
+            &mut StdRng::from_entropy(),
+        );
+        let witness = Interpreter::default()
+            .execute(program.clone(), &[Bn128Field::from(42)])
+            .unwrap();
+
+        check_deterministic_proofs::<_, G16, Ark>(program, witness, keypair.pk);
+    }
+
+    #[test]
+    fn verify_reordered() {
+        // ~out_0 = x^16 + x, with the intermediate variables numbered out of order
+        let square = |a: usize, b: usize| {
+            Statement::constraint(
+                QuadComb::from_linear_combinations(
+                    Variable::new(a).into(),
+                    Variable::new(a).into(),
+                ),
+                Variable::new(b),
+            )
+        };
+
+        let program: Prog<Bn128Field> = Prog {
+            arguments: vec![Parameter::private(Variable::new(0))],
+            return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
+            statements: vec![
+                square(0, 4),
+                square(4, 1),
+                square(1, 3),
+                square(3, 2),
+                Statement::definition(
+                    Variable::public(0),
+                    LinComb::from(Variable::new(2)) + LinComb::from(Variable::new(0)),
+                ),
+            ],
+        };
+
+        let reordered = reorder(program.clone());
+        let ordering = reordered.variable_ordering.clone().unwrap();
+        assert_ne!(reordered, program);
+
+        let interpreter = Interpreter::default();
+        let inputs = [Bn128Field::from(2)];
+
+        // a witness of the original program maps to a witness of the reordered one
+        let witness = interpreter.execute(reordered.clone(), &inputs).unwrap();
+        assert_eq!(
+            ordering.apply_to_witness(interpreter.execute(program, &inputs).unwrap()),
+            witness
+        );
+
+        let rng = &mut StdRng::from_entropy();
+        let keypair = <Ark as NonUniversalBackend<Bn128Field, G16>>::setup(reordered.clone(), rng);
+
+        let proof =
+            <Ark as Backend<Bn128Field, G16>>::generate_proof(reordered, witness, keypair.pk, rng);
+        assert!(<Ark as Backend<Bn128Field, G16>>::verify(keypair.vk, proof));
+    }
+
+    #[test]
+    fn rerandomize() {
+        let program: Prog<Bn128Field> = Prog {
+            arguments: vec![Parameter::public(Variable::new(0))],
+            return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
+            statements: vec![Statement::constraint(Variable::new(0), Variable::public(0))],
+        };
+
+        let rng = &mut StdRng::from_entropy();
+        let keypair = <Ark as NonUniversalBackend<Bn128Field, G16>>::setup(program.clone(), rng);
+        let other_keypair =
+            <Ark as NonUniversalBackend<Bn128Field, G16>>::setup(program.clone(), rng);
+
+        let witness = Interpreter::default()
+            .execute(program.clone(), &[Bn128Field::from(42)])
+            .unwrap();
+
+        let proof =
+            <Ark as Backend<Bn128Field, G16>>::generate_proof(program, witness, keypair.pk, rng);
+
+        let rerandomized = proof.rerandomize::<Ark, _>(&keypair.vk, rng);
+
+        // the proof points change, the public inputs do not
+        assert_ne!(proof.proof.a.to_string(), rerandomized.proof.a.to_string());
+        assert_ne!(proof.proof.b.to_string(), rerandomized.proof.b.to_string());
+        assert_ne!(proof.proof.c.to_string(), rerandomized.proof.c.to_string());
+        assert_eq!(proof.inputs, rerandomized.inputs);
+
+        assert!(<Ark as Backend<Bn128Field, G16>>::verify(
+            keypair.vk.clone(),
+            rerandomized
+        ));
+
+        // a re-randomized proof is only valid against the key it was generated for
+        let rerandomized = proof.rerandomize::<Ark, _>(&keypair.vk, rng);
+        assert!(!<Ark as Backend<Bn128Field, G16>>::verify(
+            other_keypair.vk,
+            rerandomized
+        ));
+    }
+
+    // a verification key and a proof of `~out_0 = _0` for `_0 = 42`, serialized like the CLI does
+    fn serialized_keys_and_proof() -> (Vec<u8>, Vec<u8>, Vec<String>) {
+        use zokrates_proof_systems::{TaggedProof, TaggedVerificationKey};
+
+        let program: Prog<Bn128Field> = Prog {
+            arguments: vec![Parameter::public(Variable::new(0))],
+            return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
+            statements: vec![Statement::constraint(Variable::new(0), Variable::public(0))],
+        };
+
+        let rng = &mut StdRng::from_entropy();
+        let keypair = <Ark as NonUniversalBackend<Bn128Field, G16>>::setup(program.clone(), rng);
+
+        let witness = Interpreter::default()
+            .execute(program.clone(), &[Bn128Field::from(42)])
+            .unwrap();
+
+        let proof =
+            <Ark as Backend<Bn128Field, G16>>::generate_proof(program, witness, keypair.pk, rng);
+
+        (
+            serde_json::to_vec(&TaggedVerificationKey::<Bn128Field, G16>::new(keypair.vk)).unwrap(),
+            serde_json::to_vec(&TaggedProof::<Bn128Field, G16>::new(
+                proof.proof,
+                proof.inputs.clone(),
+            ))
+            .unwrap(),
+            proof.inputs,
+        )
+    }
+
+    // apply `f` to the JSON value serialized in `bytes`
+    fn edit(bytes: &[u8], f: impl FnOnce(&mut serde_json::Value)) -> Vec<u8> {
+        let mut value: serde_json::Value = serde_json::from_slice(bytes).unwrap();
+        f(&mut value);
+        serde_json::to_vec(&value).unwrap()
+    }
+
+    mod verify_bytes {
+        use super::*;
+        use zokrates_proof_systems::{verify_bytes, VerifyError};
+
+        #[test]
+        fn valid_proof() {
+            let (vk, proof, inputs) = serialized_keys_and_proof();
+
+            assert_eq!(
+                verify_bytes::<Ark>("g16", "bn128", &vk, &proof, &inputs),
+                Ok(true)
+            );
+        }
+
+        #[test]
+        fn scheme_and_curve_from_artifact() {
+            let (vk, proof, inputs) = serialized_keys_and_proof();
+
+            let tags: serde_json::Value = serde_json::from_slice(&vk).unwrap();
+            let (scheme, curve) = (
+                tags["scheme"].as_str().unwrap(),
+                tags["curve"].as_str().unwrap(),
+            );
+            assert_eq!(
+                verify_bytes::<Ark>(scheme, curve, &vk, &proof, &inputs),
+                Ok(true)
+            );
+
+            assert_eq!(
+                verify_bytes::<Ark>("g16", "pallas", &vk, &proof, &inputs),
+                Err(VerifyError::Unsupported {
+                    scheme: "g16".to_string(),
+                    curve: "pallas".to_string()
+                })
+            );
+        }
+
+        #[test]
+        fn wrong_public_input() {
+            let (vk, proof, _) = serialized_keys_and_proof();
+
+            assert_eq!(
+                verify_bytes::<Ark>(
+                    "g16",
+                    "bn128",
+                    &vk,
+                    &proof,
+                    &["0x2a".to_string(), "0x2b".to_string()]
+                ),
+                Ok(false)
+            );
+
+            // values which are not reduced are rejected rather than taken modulo the size of the field
+            let p = Bn128Field::max_value().to_biguint() + 1u32;
+            assert_eq!(
+                verify_bytes::<Ark>(
+                    "g16",
+                    "bn128",
+                    &vk,
+                    &proof,
+                    &["0x2a".to_string(), format!("0x{:x}", p)]
+                ),
+                Err(VerifyError::NonCanonicalInput { index: 1 })
+            );
+
+            assert_eq!(
+                verify_bytes::<Ark>("g16", "bn128", &vk, &proof, &["0x2a".to_string()]),
+                Err(VerifyError::InputCount {
+                    expected: 2,
+                    found: 1
+                })
+            );
+        }
+
+        #[test]
+        fn malformed_point() {
+            let (vk, proof, inputs) = serialized_keys_and_proof();
+
+            // a coordinate larger than the modulus of the base field
+            let proof_not_reduced = edit(&proof, |p| {
+                p["proof"]["a"][0] = format!("0x{}", "ff".repeat(32)).into()
+            });
+            assert_eq!(
+                verify_bytes::<Ark>("g16", "bn128", &vk, &proof_not_reduced, &inputs),
+                Err(VerifyError::NonCanonicalPoint {
+                    name: "a".to_string()
+                })
+            );
+
+            // not hexadecimal
+            let vk_not_hex = edit(&vk, |vk| vk["gamma_abc"][1][1] = "0xzz".into());
+            assert_eq!(
+                verify_bytes::<Ark>("g16", "bn128", &vk_not_hex, &proof, &inputs),
+                Err(VerifyError::NonCanonicalPoint {
+                    name: "gamma_abc[1]".to_string()
+                })
+            );
+
+            // `(1, 3)` is not on `y^2 = x^3 + 3`
+            let proof_off_curve = edit(&proof, |p| {
+                p["proof"]["c"] = serde_json::json!(["0x01", "0x03"])
+            });
+            assert_eq!(
+                verify_bytes::<Ark>("g16", "bn128", &vk, &proof_off_curve, &inputs),
+                Err(VerifyError::NotOnCurve {
+                    name: "c".to_string()
+                })
+            );
+        }
+
+        #[test]
+        fn scheme_and_curve_mismatch() {
+            let (vk, proof, inputs) = serialized_keys_and_proof();
+
+            let vk_gm17 = edit(&vk, |vk| vk["scheme"] = "gm17".into());
+            assert_eq!(
+                verify_bytes::<Ark>("g16", "bn128", &vk_gm17, &proof, &inputs),
+                Err(VerifyError::SchemeMismatch {
+                    what: "verification key",
+                    expected: "g16".to_string(),
+                    found: "gm17".to_string()
+                })
+            );
+
+            let proof_bls12_381 = edit(&proof, |p| p["curve"] = "bls12_381".into());
+            assert_eq!(
+                verify_bytes::<Ark>("g16", "bn128", &vk, &proof_bls12_381, &inputs),
+                Err(VerifyError::CurveMismatch {
+                    what: "proof",
+                    expected: "bn128".to_string(),
+                    found: "bls12_381".to_string()
+                })
+            );
+
+            // a verification key for another curve is rejected before any point is read
+            assert_eq!(
+                verify_bytes::<Ark>("g16", "bls12_377", &vk, &proof, &inputs),
+                Err(VerifyError::CurveMismatch {
+                    what: "verification key",
+                    expected: "bls12_377".to_string(),
+                    found: "bn128".to_string()
+                })
+            );
+        }
+    }
 }
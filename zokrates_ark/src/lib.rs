@@ -1,6 +1,7 @@
 pub mod gm17;
 pub mod groth16;
 pub mod marlin;
+pub mod sharding;
 
 use ark_ec::PairingEngine;
 use ark_relations::r1cs::{
@@ -10,12 +11,79 @@ use ark_relations::r1cs::{
 use std::collections::BTreeMap;
 use zokrates_ast::common::Variable;
 use zokrates_ast::ir::{CanonicalLinComb, ProgIterator, Statement, Witness};
-use zokrates_field::{ArkFieldExtensions, Field};
+use zokrates_field::{
+    ArkFieldExtensions, Bls12_377Field, Bls12_381Field, Bn128Field, Bw6_761Field, Field,
+};
+use zokrates_proof_systems::{
+    verify_bytes_with, BytesVerifier, Fr, G1Affine, G2Affine, Marlin, PointError, PointValidation,
+    VerifyError, G16, GM17,
+};
 
 pub use self::parse::*;
 
 pub struct Ark;
 
+impl<T: Field + ArkFieldExtensions> PointValidation<T> for Ark {
+    fn check_g1(point: &G1Affine) -> Result<(), PointError> {
+        serialization::try_to_g1::<T>(point).map(|_| ())
+    }
+
+    fn check_g2(point: &G2Affine) -> Result<(), PointError> {
+        serialization::try_to_g2::<T>(point).map(|_| ())
+    }
+}
+
+impl BytesVerifier for Ark {
+    fn verify_bytes(
+        scheme: &str,
+        curve: &str,
+        vk: &[u8],
+        proof: &[u8],
+        public_inputs: &[Fr],
+    ) -> Result<bool, VerifyError> {
+        match (scheme, curve) {
+            ("g16", "bn128") => verify_bytes_with::<Bn128Field, G16, Ark>(vk, proof, public_inputs),
+            ("g16", "bls12_381") => {
+                verify_bytes_with::<Bls12_381Field, G16, Ark>(vk, proof, public_inputs)
+            }
+            ("g16", "bls12_377") => {
+                verify_bytes_with::<Bls12_377Field, G16, Ark>(vk, proof, public_inputs)
+            }
+            ("g16", "bw6_761") => {
+                verify_bytes_with::<Bw6_761Field, G16, Ark>(vk, proof, public_inputs)
+            }
+            ("gm17", "bn128") => {
+                verify_bytes_with::<Bn128Field, GM17, Ark>(vk, proof, public_inputs)
+            }
+            ("gm17", "bls12_381") => {
+                verify_bytes_with::<Bls12_381Field, GM17, Ark>(vk, proof, public_inputs)
+            }
+            ("gm17", "bls12_377") => {
+                verify_bytes_with::<Bls12_377Field, GM17, Ark>(vk, proof, public_inputs)
+            }
+            ("gm17", "bw6_761") => {
+                verify_bytes_with::<Bw6_761Field, GM17, Ark>(vk, proof, public_inputs)
+            }
+            ("marlin", "bn128") => {
+                verify_bytes_with::<Bn128Field, Marlin, Ark>(vk, proof, public_inputs)
+            }
+            ("marlin", "bls12_381") => {
+                verify_bytes_with::<Bls12_381Field, Marlin, Ark>(vk, proof, public_inputs)
+            }
+            ("marlin", "bls12_377") => {
+                verify_bytes_with::<Bls12_377Field, Marlin, Ark>(vk, proof, public_inputs)
+            }
+            ("marlin", "bw6_761") => {
+                verify_bytes_with::<Bw6_761Field, Marlin, Ark>(vk, proof, public_inputs)
+            }
+            _ => Err(VerifyError::Unsupported {
+                scheme: scheme.to_string(),
+                curve: curve.to_string(),
+            }),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Computation<'a, T, I: IntoIterator<Item = Statement<'a, T>>> {
     program: ProgIterator<'a, T, I>,
@@ -43,13 +111,14 @@ fn ark_combination<T: Field + ArkFieldExtensions>(
     cs: &mut ConstraintSystem<<<T as ArkFieldExtensions>::ArkEngine as PairingEngine>::Fr>,
     symbols: &mut BTreeMap<Variable, ArkVariable>,
     witness: &mut Witness<T>,
+    return_count: usize,
 ) -> LinearCombination<<<T as ArkFieldExtensions>::ArkEngine as PairingEngine>::Fr> {
     l.0.into_iter()
         .map(|(k, v)| {
             (
                 v.into_ark(),
                 *symbols.entry(k).or_insert_with(|| {
-                    match k.is_output() {
+                    match k.is_public_output(return_count) {
                         true => cs.new_input_variable(|| {
                             Ok(witness
                                 .0
@@ -90,27 +159,31 @@ impl<'a, T: Field + ArkFieldExtensions, I: IntoIterator<Item = Statement<'a, T>>
         match cs {
             ConstraintSystemRef::CS(rc) => {
                 let mut cs = rc.borrow_mut();
-                symbols.extend(self.program.arguments.iter().enumerate().map(|(_, p)| {
-                    let wire = match p.private {
-                        true => cs.new_witness_variable(|| {
-                            Ok(witness
-                                .0
-                                .remove(&p.id)
-                                .ok_or(SynthesisError::AssignmentMissing)?
-                                .into_ark())
-                        }),
-                        false => cs.new_input_variable(|| {
-                            Ok(witness
-                                .0
-                                .remove(&p.id)
-                                .ok_or(SynthesisError::AssignmentMissing)?
-                                .into_ark())
-                        }),
-                    }
-                    .unwrap();
-                    (p.id, wire)
-                }));
 
+                // the public values are allocated first, in the order of the public layout of the program
+                for v in self.program.public_variables() {
+                    let wire = cs.new_input_variable(|| {
+                        Ok(witness
+                            .0
+                            .remove(&v)
+                            .ok_or(SynthesisError::AssignmentMissing)?
+                            .into_ark())
+                    })?;
+                    symbols.insert(v, wire);
+                }
+
+                for p in self.program.arguments.iter().filter(|p| p.private) {
+                    let wire = cs.new_witness_variable(|| {
+                        Ok(witness
+                            .0
+                            .remove(&p.id)
+                            .ok_or(SynthesisError::AssignmentMissing)?
+                            .into_ark())
+                    })?;
+                    symbols.insert(p.id, wire);
+                }
+
+                let return_count = self.program.return_count;
                 for statement in self.program.statements {
                     if let Statement::Constraint(quad, lin, _) = statement {
                         let a = ark_combination(
@@ -118,18 +191,21 @@ impl<'a, T: Field + ArkFieldExtensions, I: IntoIterator<Item = Statement<'a, T>>
                             &mut cs,
                             &mut symbols,
                             &mut witness,
+                            return_count,
                         );
                         let b = ark_combination(
                             quad.right.clone().into_canonical(),
                             &mut cs,
                             &mut symbols,
                             &mut witness,
+                            return_count,
                         );
                         let c = ark_combination(
                             lin.into_canonical(),
                             &mut cs,
                             &mut symbols,
                             &mut witness,
+                            return_count,
                         );
 
                         cs.enforce_constraint(a, b, c)?;
@@ -241,10 +317,11 @@ mod parse {
 }
 
 pub mod serialization {
-    use ark_ec::PairingEngine;
-    use ark_ff::FromBytes;
+    use ark_ec::{AffineCurve, PairingEngine};
+    use ark_ff::{FromBytes, ToBytes};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
     use zokrates_field::ArkFieldExtensions;
-    use zokrates_proof_systems::{G1Affine, G2Affine};
+    use zokrates_proof_systems::{G1Affine, G2Affine, PointError};
 
     #[inline]
     fn decode_hex(value: String) -> Vec<u8> {
@@ -253,6 +330,61 @@ pub mod serialization {
         bytes
     }
 
+    // decode a big endian coordinate of `length` bytes at most, returning it little endian and padded to `length`
+    fn try_decode_hex(value: &str, length: usize) -> Result<Vec<u8>, PointError> {
+        let bytes = value
+            .strip_prefix("0x")
+            .and_then(|v| hex::decode(v).ok())
+            .filter(|bytes| bytes.len() <= length)
+            .ok_or(PointError::NonCanonical)?;
+
+        let mut padded = vec![0u8; length - bytes.len()];
+        padded.extend(bytes);
+        padded.reverse();
+        Ok(padded)
+    }
+
+    // read a point from its coordinates, checking that they are reduced and that the point is in the prime order
+    // subgroup of the curve
+    fn read_checked<G: AffineCurve>(coordinates: &[&String]) -> Result<G, PointError> {
+        // the uncompressed encoding of a point is its coordinates followed by the infinity flag
+        let mut generator = vec![];
+        G::prime_subgroup_generator().write(&mut generator).unwrap();
+        let length = (generator.len() - 1) / coordinates.len();
+
+        let mut bytes = vec![];
+        for c in coordinates {
+            bytes.append(&mut try_decode_hex(c, length)?);
+        }
+        bytes.push(0u8); // infinity flag
+
+        let point = G::read(&*bytes).map_err(|_| PointError::NonCanonical)?;
+
+        // decompressing a point recomputes its `y` coordinate from `x` with the equation of the curve and checks the
+        // subgroup, so the round trip only gives the same point back for valid points
+        let mut compressed = vec![];
+        point.serialize(&mut compressed).unwrap();
+        match G::deserialize(&*compressed) {
+            Ok(p) if p == point => Ok(point),
+            _ => Err(PointError::NotOnCurve),
+        }
+    }
+
+    pub fn try_to_g1<T: ArkFieldExtensions>(
+        g1: &G1Affine,
+    ) -> Result<<T::ArkEngine as PairingEngine>::G1Affine, PointError> {
+        read_checked(&[&g1.0, &g1.1])
+    }
+
+    pub fn try_to_g2<T: ArkFieldExtensions>(
+        g2: &G2Affine,
+    ) -> Result<<T::ArkEngine as PairingEngine>::G2Affine, PointError> {
+        match g2 {
+            G2Affine::Fq(g2) => read_checked(&[&g2.0, &g2.1]),
+            G2Affine::Fq2(g2) => read_checked(&[&(g2.0).0, &(g2.0).1, &(g2.1).0, &(g2.1).1]),
+        }
+    }
+
     pub fn to_g1<T: ArkFieldExtensions>(g1: G1Affine) -> <T::ArkEngine as PairingEngine>::G1Affine {
         let mut bytes = vec![];
         bytes.append(&mut decode_hex(g1.0));
@@ -283,3 +415,73 @@ pub mod serialization {
         <T::ArkEngine as PairingEngine>::G2Affine::read(&*bytes).unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_ast::ir::arkworks::ProgSynthesizer;
+    use zokrates_ast::ir::{LinComb, Parameter, Prog, QuadComb};
+
+    type Scalar = <<Bn128Field as ArkFieldExtensions>::ArkEngine as PairingEngine>::Fr;
+
+    // def main(public x, private y, public z) -> ~out_0
+    //     ~out_0 + (-1) * z == x * y
+    // the same program as the one synthesized by `ProgSynthesizer` in `zokrates_ast`
+    fn fixture() -> (Prog<'static, Bn128Field>, Witness<Bn128Field>) {
+        let x = Variable::new(0);
+        let y = Variable::new(1);
+        let z = Variable::new(2);
+        let out = Variable::public(0);
+
+        let prog = Prog::new(
+            vec![
+                Parameter::public(x),
+                Parameter::private(y),
+                Parameter::public(z),
+            ],
+            vec![Statement::constraint(
+                QuadComb::from_linear_combinations(x.into(), y.into()),
+                LinComb::from(out) - LinComb::from(z),
+            )],
+            1,
+        );
+
+        let witness = Witness(
+            vec![
+                (Variable::one(), Bn128Field::from(1)),
+                (x, Bn128Field::from(3)),
+                (y, Bn128Field::from(4)),
+                (z, Bn128Field::from(5)),
+                (out, Bn128Field::from(17)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        (prog, witness)
+    }
+
+    // the values of the public inputs allocated by `synthesizer`, without `~one`
+    fn instance<C: ConstraintSynthesizer<Scalar>>(synthesizer: C) -> Vec<Scalar> {
+        let cs = ConstraintSystem::new_ref();
+        synthesizer.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        let cs = cs.borrow().unwrap();
+        cs.instance_assignment[1..].to_vec()
+    }
+
+    #[test]
+    fn public_input_order() {
+        let (prog, witness) = fixture();
+
+        // the default layout, and `~out_0` moved before the arguments
+        for prog in [prog.clone(), prog.with_public_layout(vec![2, 0, 1])] {
+            let expected = instance(ProgSynthesizer::with_witness(&prog, &witness));
+
+            let computation = Computation::with_witness(prog.clone(), witness.clone());
+            assert_eq!(computation.public_inputs_values(), expected);
+            assert_eq!(instance(computation), expected);
+        }
+    }
+}
@@ -206,27 +206,35 @@ impl<T: Field + ArkFieldExtensions> UniversalBackend<T, marlin::Marlin> for Ark
 }
 
 impl<T: Field + ArkFieldExtensions> Backend<T, marlin::Marlin> for Ark {
-    fn generate_proof<'a, I: IntoIterator<Item = Statement<'a, T>>, R: RngCore + CryptoRng>(
+    type ProvingKey = IndexProverKey<
+        <<T as ArkFieldExtensions>::ArkEngine as PairingEngine>::Fr,
+        MarlinKZG10<
+            T::ArkEngine,
+            DensePolynomial<<<T as ArkFieldExtensions>::ArkEngine as PairingEngine>::Fr>,
+        >,
+    >;
+
+    fn read_proving_key(proving_key: &[u8]) -> Result<Self::ProvingKey, String> {
+        <Self::ProvingKey as CanonicalDeserialize>::deserialize_unchecked(proving_key)
+            .map_err(|e| e.to_string())
+    }
+
+    fn generate_proof_with_key<
+        'a,
+        I: IntoIterator<Item = Statement<'a, T>>,
+        R: RngCore + CryptoRng,
+    >(
         program: ProgIterator<'a, T, I>,
         witness: Witness<T>,
-        proving_key: Vec<u8>,
+        pk: &Self::ProvingKey,
         rng: &mut R,
     ) -> Proof<T, marlin::Marlin> {
         let computation = Computation::with_witness(program, witness);
 
-        let pk = IndexProverKey::<
-            <<T as ArkFieldExtensions>::ArkEngine as PairingEngine>::Fr,
-            MarlinKZG10<
-                T::ArkEngine,
-                DensePolynomial<<<T as ArkFieldExtensions>::ArkEngine as PairingEngine>::Fr>,
-            >,
-        >::deserialize_unchecked(&mut proving_key.as_slice())
-        .unwrap();
-
         let public_inputs = computation.public_inputs_values();
         let inputs = public_inputs.iter().map(parse_fr::<T>).collect::<Vec<_>>();
 
-        let proof = MarlinInst::<T>::prove(&pk, computation, rng).unwrap();
+        let proof = MarlinInst::<T>::prove(pk, computation, rng).unwrap();
 
         assert!(proof.pc_proof.evals.is_none());
 
@@ -386,7 +394,8 @@ mod tests {
     use zokrates_interpreter::Interpreter;
 
     use super::*;
-    use zokrates_field::{Bls12_377Field, Bw6_761Field};
+    use zokrates_field::{Bls12_377Field, Bn128Field, Bw6_761Field};
+    use zokrates_proof_systems::testing::check_deterministic_proofs;
     use zokrates_proof_systems::Marlin;
 
     #[test]
@@ -394,6 +403,8 @@ mod tests {
         let program: Prog<Bls12_377Field> = Prog {
             arguments: vec![Parameter::private(Variable::new(0))],
             return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
             statements: vec![
                 Statement::constraint(
                     QuadComb::from_linear_combinations(
@@ -424,11 +435,43 @@ mod tests {
         assert!(ans);
     }
 
+    #[test]
+    fn deterministic_proofs() {
+        let program: Prog<Bn128Field> = Prog {
+            arguments: vec![Parameter::private(Variable::new(0))],
+            return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
+            statements: vec![
+                Statement::constraint(
+                    QuadComb::from_linear_combinations(
+                        Variable::new(0).into(),
+                        Variable::new(0).into(),
+                    ),
+                    Variable::new(1),
+                ),
+                Statement::constraint(Variable::new(1), Variable::public(0)),
+            ],
+        };
+
+        let rng = &mut StdRng::from_entropy();
+        let srs = <Ark as UniversalBackend<Bn128Field, Marlin>>::universal_setup(5, rng);
+        let keypair =
+            <Ark as UniversalBackend<Bn128Field, Marlin>>::setup(srs, program.clone()).unwrap();
+        let witness = Interpreter::default()
+            .execute(program.clone(), &[Bn128Field::from(42)])
+            .unwrap();
+
+        check_deterministic_proofs::<_, Marlin, Ark>(program, witness, keypair.pk);
+    }
+
     #[test]
     fn verify_bw6_761_field() {
         let program: Prog<Bw6_761Field> = Prog {
             arguments: vec![Parameter::private(Variable::new(0))],
             return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
             statements: vec![
                 Statement::constraint(
                     QuadComb::from_linear_combinations(
@@ -0,0 +1,545 @@
+//! Distributed Groth16 proof generation.
+//!
+//! Most of the work in a Groth16 prover goes into multi-scalar multiplications of the proving key
+//! queries with the assignment, which are linear: the sum over all variables is the sum of the sums
+//! over any partition of the variables. We use this to split proving into shards:
+//!
+//! 1. `split_proving_key` partitions the queries of a proving key into `n` shards, once per key,
+//!    along with a `ShardManifest` holding the ranges and the few key elements needed to combine.
+//! 2. `split_witness` synthesizes the constraint system for a given witness, computes the QAP
+//!    witness map, and slices the assignment along the ranges of the manifest.
+//! 3. `prove_shard` computes the partial sums of a shard, typically on a separate machine which
+//!    only needs that shard of the proving key in memory.
+//! 4. `combine` adds up the partial sums and applies the zero-knowledge randomization.
+//!
+//! Only the queries are sharded: the witness map requires the full constraint system and is
+//! computed by the planner.
+
+use crate::{parse_fr, parse_g1, parse_g2, Computation};
+use ark_ec::msm::VariableBaseMSM;
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{One, PrimeField, UniformRand, Zero};
+use ark_groth16::ProvingKey;
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_relations::r1cs::{
+    ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, OptimizationGoal,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand_0_8::{CryptoRng, RngCore};
+use zokrates_ast::ir::{ProgIterator, Statement, Witness};
+use zokrates_field::{ArkFieldExtensions, Field};
+use zokrates_proof_systems::groth16::{ProofPoints, G16};
+use zokrates_proof_systems::{Proof, Scheme};
+
+type Engine<T> = <T as ArkFieldExtensions>::ArkEngine;
+type Fr<T> = <Engine<T> as PairingEngine>::Fr;
+
+/// A contiguous range of indices, `start` included and `end` excluded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Chunk {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Chunk {
+    fn new(len: usize, count: usize, index: usize) -> Self {
+        Chunk {
+            start: len * index / count,
+            end: len * (index + 1) / count,
+        }
+    }
+}
+
+/// The ranges covered by a shard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ShardRanges {
+    /// Range of the assignment (public inputs then witness, without `~one`) for the `A` and `B` queries
+    pub assignment: Chunk,
+    /// Range of the witness for the `L` query
+    pub witness: Chunk,
+    /// Range of the QAP coefficients for the `H` query
+    pub h: Chunk,
+}
+
+/// Describes how a proving key was split, and holds what is needed to combine partial proofs
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ShardManifest<E: PairingEngine> {
+    pub shards: Vec<ShardRanges>,
+    pub assignment_count: usize,
+    pub witness_count: usize,
+    alpha_g1: E::G1Affine,
+    beta_g1: E::G1Affine,
+    beta_g2: E::G2Affine,
+    delta_g1: E::G1Affine,
+    delta_g2: E::G2Affine,
+    a_one: E::G1Affine,
+    b_g1_one: E::G1Affine,
+    b_g2_one: E::G2Affine,
+}
+
+/// The part of a proving key needed to prove a shard
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProvingKeyShard<E: PairingEngine> {
+    pub index: usize,
+    a_query: Vec<E::G1Affine>,
+    b_g1_query: Vec<E::G1Affine>,
+    b_g2_query: Vec<E::G2Affine>,
+    l_query: Vec<E::G1Affine>,
+    h_query: Vec<E::G1Affine>,
+}
+
+/// The part of the assignment needed to prove a shard
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct WitnessShard<E: PairingEngine> {
+    pub index: usize,
+    assignment: Vec<E::Fr>,
+    witness: Vec<E::Fr>,
+    h: Vec<E::Fr>,
+}
+
+/// The public inputs of the proof along with a witness shard for each shard of the manifest
+pub struct ShardedWitness<E: PairingEngine> {
+    pub inputs: Vec<E::Fr>,
+    pub shards: Vec<WitnessShard<E>>,
+}
+
+/// The partial sums computed for a shard
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PartialProof<E: PairingEngine> {
+    pub index: usize,
+    a: E::G1Affine,
+    b_g1: E::G1Affine,
+    b_g2: E::G2Affine,
+    c: E::G1Affine,
+}
+
+/// Split a proving key of scheme `S` into `shard_count` shards
+///
+/// Only Groth16 is supported: other schemes are not linear in the proving key in a way which lets
+/// the partial results be combined.
+#[allow(clippy::type_complexity)]
+pub fn split_proving_key<T: Field + ArkFieldExtensions, S: Scheme<T>>(
+    proving_key: &[u8],
+    shard_count: usize,
+) -> Result<(ShardManifest<Engine<T>>, Vec<ProvingKeyShard<Engine<T>>>), String> {
+    if S::NAME != <G16 as Scheme<T>>::NAME {
+        return Err(format!(
+            "Sharded proving is not supported for scheme `{}`, only `{}` is supported",
+            S::NAME,
+            <G16 as Scheme<T>>::NAME
+        ));
+    }
+
+    if shard_count == 0 {
+        return Err(String::from("The number of shards should be at least 1"));
+    }
+
+    let pk = ProvingKey::<Engine<T>>::deserialize_unchecked(proving_key)
+        .map_err(|e| format!("Could not deserialize proving key: {}", e))?;
+
+    // the first element of the `A` and `B` queries corresponds to `~one`
+    let assignment_count = pk.a_query.len() - 1;
+    let witness_count = pk.l_query.len();
+    let h_count = pk.h_query.len();
+
+    let ranges: Vec<_> = (0..shard_count)
+        .map(|index| ShardRanges {
+            assignment: Chunk::new(assignment_count, shard_count, index),
+            witness: Chunk::new(witness_count, shard_count, index),
+            h: Chunk::new(h_count, shard_count, index),
+        })
+        .collect();
+
+    let shards = ranges
+        .iter()
+        .enumerate()
+        .map(|(index, r)| {
+            let queried = 1 + r.assignment.start..1 + r.assignment.end;
+            ProvingKeyShard {
+                index,
+                a_query: pk.a_query[queried.clone()].to_vec(),
+                b_g1_query: pk.b_g1_query[queried.clone()].to_vec(),
+                b_g2_query: pk.b_g2_query[queried].to_vec(),
+                l_query: pk.l_query[r.witness.start..r.witness.end].to_vec(),
+                h_query: pk.h_query[r.h.start..r.h.end].to_vec(),
+            }
+        })
+        .collect();
+
+    let manifest = ShardManifest {
+        shards: ranges,
+        assignment_count,
+        witness_count,
+        alpha_g1: pk.vk.alpha_g1,
+        beta_g1: pk.beta_g1,
+        beta_g2: pk.vk.beta_g2,
+        delta_g1: pk.delta_g1,
+        delta_g2: pk.vk.delta_g2,
+        a_one: pk.a_query[0],
+        b_g1_one: pk.b_g1_query[0],
+        b_g2_one: pk.b_g2_query[0],
+    };
+
+    Ok((manifest, shards))
+}
+
+/// Compute the assignment for `witness` and slice it along the shards of `manifest`
+pub fn split_witness<
+    'a,
+    T: Field + ArkFieldExtensions,
+    I: IntoIterator<Item = Statement<'a, T>>,
+>(
+    manifest: &ShardManifest<Engine<T>>,
+    program: ProgIterator<'a, T, I>,
+    witness: Witness<T>,
+) -> Result<ShardedWitness<Engine<T>>, String> {
+    let cs = ConstraintSystem::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+
+    Computation::with_witness(program, witness)
+        .generate_constraints(cs.clone())
+        .map_err(|e| format!("Could not synthesize constraints: {}", e))?;
+    cs.finalize();
+
+    let matrices = cs.to_matrices().unwrap();
+    let cs = cs.into_inner().unwrap();
+
+    if cs.num_instance_variables + cs.num_witness_variables != manifest.assignment_count + 1
+        || cs.num_witness_variables != manifest.witness_count
+    {
+        return Err(String::from(
+            "The proving key does not match the program: variable counts differ",
+        ));
+    }
+
+    let h = witness_map(&matrices, &cs.instance_assignment, &cs.witness_assignment)?;
+
+    let inputs = cs.instance_assignment[1..].to_vec();
+    let assignment = [&inputs[..], &cs.witness_assignment[..]].concat();
+
+    let shards = manifest
+        .shards
+        .iter()
+        .enumerate()
+        .map(|(index, r)| WitnessShard {
+            index,
+            assignment: assignment[r.assignment.start..r.assignment.end].to_vec(),
+            witness: cs.witness_assignment[r.witness.start..r.witness.end].to_vec(),
+            h: h[r.h.start..r.h.end].to_vec(),
+        })
+        .collect();
+
+    Ok(ShardedWitness { inputs, shards })
+}
+
+/// Compute the partial sums of a shard
+pub fn prove_shard<E: PairingEngine>(
+    key: &ProvingKeyShard<E>,
+    witness: &WitnessShard<E>,
+) -> Result<PartialProof<E>, String> {
+    if key.index != witness.index {
+        return Err(format!(
+            "Proving key shard {} cannot be used with witness shard {}",
+            key.index, witness.index
+        ));
+    }
+
+    if key.a_query.len() != witness.assignment.len()
+        || key.l_query.len() != witness.witness.len()
+        || key.h_query.len() != witness.h.len()
+    {
+        return Err(format!(
+            "Proving key shard {} does not match the witness shard",
+            key.index
+        ));
+    }
+
+    let assignment: Vec<_> = witness.assignment.iter().map(|s| s.into_repr()).collect();
+    let aux: Vec<_> = witness.witness.iter().map(|s| s.into_repr()).collect();
+    let h: Vec<_> = witness.h.iter().map(|s| s.into_repr()).collect();
+
+    let a = VariableBaseMSM::multi_scalar_mul(&key.a_query, &assignment);
+    let b_g1 = VariableBaseMSM::multi_scalar_mul(&key.b_g1_query, &assignment);
+    let b_g2 = VariableBaseMSM::multi_scalar_mul(&key.b_g2_query, &assignment);
+    let c = VariableBaseMSM::multi_scalar_mul(&key.l_query, &aux)
+        + VariableBaseMSM::multi_scalar_mul(&key.h_query, &h);
+
+    Ok(PartialProof {
+        index: key.index,
+        a: a.into_affine(),
+        b_g1: b_g1.into_affine(),
+        b_g2: b_g2.into_affine(),
+        c: c.into_affine(),
+    })
+}
+
+/// Combine the partial proofs of all shards into a proof for `inputs`
+///
+/// Randomness is drawn from `rng` in the same way as the single-machine prover, so that both produce
+/// the same proof for the same randomness.
+pub fn combine<T: Field + ArkFieldExtensions, R: RngCore + CryptoRng>(
+    manifest: &ShardManifest<Engine<T>>,
+    inputs: &[Fr<T>],
+    partials: &[PartialProof<Engine<T>>],
+    rng: &mut R,
+) -> Result<Proof<T, G16>, String> {
+    let mut indices: Vec<_> = partials.iter().map(|p| p.index).collect();
+    indices.sort_unstable();
+
+    if indices != (0..manifest.shards.len()).collect::<Vec<_>>() {
+        return Err(format!(
+            "Expected exactly one partial proof for each of the {} shards, found shards {:?}",
+            manifest.shards.len(),
+            indices
+        ));
+    }
+
+    let r = Fr::<T>::rand(rng);
+    let s = Fr::<T>::rand(rng);
+
+    let mut g_a = manifest.delta_g1.mul(r);
+    g_a.add_assign_mixed(&manifest.alpha_g1);
+    g_a.add_assign_mixed(&manifest.a_one);
+
+    let mut g1_b = manifest.delta_g1.mul(s);
+    g1_b.add_assign_mixed(&manifest.beta_g1);
+    g1_b.add_assign_mixed(&manifest.b_g1_one);
+
+    let mut g2_b = manifest.delta_g2.mul(s);
+    g2_b.add_assign_mixed(&manifest.beta_g2);
+    g2_b.add_assign_mixed(&manifest.b_g2_one);
+
+    let mut g_c = <Engine<T> as PairingEngine>::G1Projective::zero();
+
+    for p in partials {
+        g_a.add_assign_mixed(&p.a);
+        g1_b.add_assign_mixed(&p.b_g1);
+        g2_b.add_assign_mixed(&p.b_g2);
+        g_c.add_assign_mixed(&p.c);
+    }
+
+    g_c += &g_a.mul(&s.into_repr());
+    g_c += &g1_b.mul(&r.into_repr());
+    g_c -= &manifest.delta_g1.mul(r * s);
+
+    let proof_points = ProofPoints {
+        a: parse_g1::<T>(&g_a.into_affine()),
+        b: parse_g2::<T>(&g2_b.into_affine()),
+        c: parse_g1::<T>(&g_c.into_affine()),
+    };
+
+    Ok(Proof::new(
+        proof_points,
+        inputs.iter().map(parse_fr::<T>).collect(),
+    ))
+}
+
+// Adapted from the QAP reduction of `ark-groth16`, which is not exposed
+fn witness_map<F: PrimeField>(
+    matrices: &ConstraintMatrices<F>,
+    instance_assignment: &[F],
+    witness_assignment: &[F],
+) -> Result<Vec<F>, String> {
+    let num_inputs = matrices.num_instance_variables;
+    let num_constraints = matrices.num_constraints;
+
+    let full_assignment = [instance_assignment, witness_assignment].concat();
+
+    let evaluate = |terms: &[(F, usize)]| -> F {
+        terms.iter().fold(F::zero(), |acc, (coeff, index)| {
+            if coeff.is_one() {
+                acc + full_assignment[*index]
+            } else {
+                acc + full_assignment[*index] * coeff
+            }
+        })
+    };
+
+    let domain = GeneralEvaluationDomain::<F>::new(num_constraints + num_inputs)
+        .ok_or_else(|| String::from("The program is too large for the evaluation domain"))?;
+    let domain_size = domain.size();
+
+    let mut a = vec![F::zero(); domain_size];
+    let mut b = vec![F::zero(); domain_size];
+    let mut c = vec![F::zero(); domain_size];
+
+    for i in 0..num_constraints {
+        a[i] = evaluate(&matrices.a[i]);
+        b[i] = evaluate(&matrices.b[i]);
+        c[i] = evaluate(&matrices.c[i]);
+    }
+
+    a[num_constraints..num_constraints + num_inputs]
+        .clone_from_slice(&full_assignment[..num_inputs]);
+
+    domain.ifft_in_place(&mut a);
+    domain.ifft_in_place(&mut b);
+    domain.ifft_in_place(&mut c);
+
+    domain.coset_fft_in_place(&mut a);
+    domain.coset_fft_in_place(&mut b);
+    domain.coset_fft_in_place(&mut c);
+
+    let mut ab = domain.mul_polynomials_in_evaluation_domain(&a, &b);
+    ab.iter_mut().zip(c).for_each(|(ab_i, c_i)| *ab_i -= &c_i);
+
+    domain.divide_by_vanishing_poly_on_coset_in_place(&mut ab);
+    domain.coset_ifft_in_place(&mut ab);
+
+    Ok(ab)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Ark;
+    use rand_0_8::rngs::StdRng;
+    use rand_0_8::SeedableRng;
+    use zokrates_ast::flat::{Parameter, Variable};
+    use zokrates_ast::ir::{LinComb, Prog, QuadComb};
+    use zokrates_field::Bn128Field;
+    use zokrates_interpreter::Interpreter;
+    use zokrates_proof_systems::gm17::GM17;
+    use zokrates_proof_systems::{Backend, NonUniversalBackend};
+
+    // def main(public _0, private _1) -> ~out_0
+    // computes _0 ** (2 ** N) + _1 with a chain of N squarings
+    fn fixture() -> Prog<'static, Bn128Field> {
+        const N: usize = 200;
+
+        let mut statements: Vec<_> = (2..N + 2)
+            .map(|i| {
+                let previous = if i == 2 { 0 } else { i - 1 };
+                Statement::definition(
+                    Variable::new(i),
+                    QuadComb::from_linear_combinations(
+                        Variable::new(previous).into(),
+                        Variable::new(previous).into(),
+                    ),
+                )
+            })
+            .collect();
+
+        statements.push(Statement::definition(
+            Variable::public(0),
+            LinComb::from(Variable::new(N + 1)) + LinComb::from(Variable::new(1)),
+        ));
+
+        Prog {
+            arguments: vec![
+                Parameter::public(Variable::new(0)),
+                Parameter::private(Variable::new(1)),
+            ],
+            return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
+            statements,
+        }
+    }
+
+    #[test]
+    fn combined_proof_matches_single_machine_proof() {
+        let program = fixture();
+
+        let keypair = <Ark as NonUniversalBackend<Bn128Field, G16>>::setup(
+            program.clone(),
+            &mut StdRng::from_seed([0; 32]),
+        );
+
+        let witness = Interpreter::default()
+            .execute(
+                program.clone(),
+                &[Bn128Field::from(3), Bn128Field::from(42)],
+            )
+            .unwrap();
+
+        let expected = <Ark as Backend<Bn128Field, G16>>::generate_proof(
+            program.clone(),
+            witness.clone(),
+            keypair.pk.clone(),
+            &mut StdRng::from_seed([1; 32]),
+        );
+
+        let proofs: Vec<_> = [1, 2, 3]
+            .into_iter()
+            .map(|shard_count| {
+                let (manifest, key_shards) =
+                    split_proving_key::<Bn128Field, G16>(&keypair.pk, shard_count).unwrap();
+
+                // shards travel to other machines serialized
+                let key_shards: Vec<ProvingKeyShard<_>> = key_shards
+                    .into_iter()
+                    .map(|s| {
+                        let mut buffer = vec![];
+                        s.serialize(&mut buffer).unwrap();
+                        ProvingKeyShard::deserialize(&buffer[..]).unwrap()
+                    })
+                    .collect();
+
+                let sharded = split_witness(&manifest, program.clone(), witness.clone()).unwrap();
+
+                let partials: Vec<_> = key_shards
+                    .iter()
+                    .zip(sharded.shards.iter())
+                    .map(|(k, w)| prove_shard(k, w).unwrap())
+                    .collect();
+
+                combine::<Bn128Field, _>(
+                    &manifest,
+                    &sharded.inputs,
+                    &partials,
+                    &mut StdRng::from_seed([1; 32]),
+                )
+                .unwrap()
+            })
+            .collect();
+
+        for proof in &proofs {
+            assert_eq!(proof.inputs, expected.inputs);
+            assert_eq!(proof.proof.a.to_string(), expected.proof.a.to_string());
+            assert_eq!(proof.proof.b.to_string(), expected.proof.b.to_string());
+            assert_eq!(proof.proof.c.to_string(), expected.proof.c.to_string());
+        }
+
+        assert!(<Ark as Backend<Bn128Field, G16>>::verify(
+            keypair.vk,
+            proofs.into_iter().last().unwrap()
+        ));
+    }
+
+    #[test]
+    fn missing_shard() {
+        let program = fixture();
+        let keypair = <Ark as NonUniversalBackend<Bn128Field, G16>>::setup(
+            program.clone(),
+            &mut StdRng::from_seed([0; 32]),
+        );
+        let witness = Interpreter::default()
+            .execute(
+                program.clone(),
+                &[Bn128Field::from(3), Bn128Field::from(42)],
+            )
+            .unwrap();
+
+        let (manifest, key_shards) = split_proving_key::<Bn128Field, G16>(&keypair.pk, 2).unwrap();
+        let sharded = split_witness(&manifest, program, witness).unwrap();
+
+        let partial = prove_shard(&key_shards[0], &sharded.shards[0]).unwrap();
+        assert!(prove_shard(&key_shards[0], &sharded.shards[1]).is_err());
+        assert!(combine::<Bn128Field, _>(
+            &manifest,
+            &sharded.inputs,
+            &[partial.clone(), partial],
+            &mut StdRng::from_seed([1; 32])
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn unsupported_scheme() {
+        assert_eq!(
+            split_proving_key::<Bn128Field, GM17>(&[], 2).err().unwrap(),
+            "Sharded proving is not supported for scheme `gm17`, only `g16` is supported"
+        );
+    }
+}
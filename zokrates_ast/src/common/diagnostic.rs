@@ -0,0 +1,34 @@
+use crate::common::SourceMetadata;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A warning about a program which compiled nonetheless, located in the source if possible
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<SourceMetadata>,
+}
+
+impl Diagnostic {
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        Diagnostic {
+            message: message.into(),
+            span: None,
+        }
+    }
+
+    pub fn span(mut self, span: Option<SourceMetadata>) -> Self {
+        self.span = span;
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.span {
+            Some(span) => write!(f, "{}:{}: {}", span.file, span.position, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
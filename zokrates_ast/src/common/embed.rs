@@ -3,7 +3,7 @@ use crate::flat::{flat_expression_from_bits, flat_expression_from_variable_summa
 use crate::flat::{FlatDirective, FlatExpression, FlatFunctionIterator, FlatStatement};
 use crate::typed::types::{
     ConcreteGenericsAssignment, DeclarationConstant, DeclarationSignature, DeclarationType,
-    GenericIdentifier,
+    GenericIdentifier, UBitwidth,
 };
 use crate::untyped::{
     types::{UnresolvedSignature, UnresolvedType},
@@ -27,6 +27,15 @@ cfg_if::cfg_if! {
     }
 }
 
+/// The order of the bits of an unsigned integer in a bit array
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    /// The most significant bit comes first
+    Big,
+    /// The least significant bit comes first
+    Little,
+}
+
 /// A low level function that contains non-deterministic introduction of variables. It is carried out as is until
 /// the flattening step when it can be inlined.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Copy, PartialOrd, Ord, Serialize, Deserialize)]
@@ -34,6 +43,10 @@ pub enum FlatEmbed {
     FieldToBoolUnsafe,
     BitArrayLe,
     Unpack,
+    /// Packs `N` booleans, most significant first, into a field element
+    Pack,
+    /// Unpacks a field element to `N` booleans, most significant first, rejecting values which do not fit
+    UnpackStrict,
     U8ToBits,
     U16ToBits,
     U32ToBits,
@@ -42,8 +55,42 @@ pub enum FlatEmbed {
     U16FromBits,
     U32FromBits,
     U64FromBits,
+    U8ToLeBits,
+    U16ToLeBits,
+    U32ToLeBits,
+    U64ToLeBits,
+    U8FromLeBits,
+    U16FromLeBits,
+    U32FromLeBits,
+    U64FromLeBits,
+    I8ToBits,
+    I16ToBits,
+    I32ToBits,
+    I8FromBits,
+    I16FromBits,
+    I32FromBits,
+    /// Converts a field element to an unsigned integer, proving that it fits unless its bound is known
+    FieldToU8,
+    FieldToU16,
+    FieldToU32,
+    FieldToU64,
+    /// Converts a field element to an unsigned integer without proving that it fits
+    FieldToU8Unchecked,
+    FieldToU16Unchecked,
+    FieldToU32Unchecked,
+    FieldToU64Unchecked,
+    UnconstrainedWitness,
+    /// Checks that two arrays of `N` field elements are equal, with a single constraint on a random linear
+    /// combination of their differences
+    BatchEq,
+    /// Checks a Merkle path of depth `DEPTH` from a leaf to a root, the nodes being Poseidon hashes of their children
+    MerkleVerifyPoseidon,
     #[cfg(feature = "bellman")]
     Sha256Round,
+    /// Checks a Merkle path of depth `DEPTH` from a leaf to a root, the nodes being SHA-256 hashes of their children
+    /// truncated to 31 bytes
+    #[cfg(feature = "bellman")]
+    MerkleVerifySha256,
     #[cfg(feature = "ark")]
     SnarkVerifyBls12377,
 }
@@ -79,7 +126,25 @@ impl FlatEmbed {
                     )
                     .into(),
                 ),
-            FlatEmbed::U8ToBits => UnresolvedSignature::new()
+            FlatEmbed::Pack => UnresolvedSignature::new()
+                .generics(vec!["N".into()])
+                .inputs(vec![UnresolvedType::array(
+                    UnresolvedType::Boolean.into(),
+                    Expression::Identifier("N").into(),
+                )
+                .into()])
+                .output(UnresolvedType::FieldElement.into()),
+            FlatEmbed::UnpackStrict => UnresolvedSignature::new()
+                .generics(vec!["N".into()])
+                .inputs(vec![UnresolvedType::FieldElement.into()])
+                .output(
+                    UnresolvedType::array(
+                        UnresolvedType::Boolean.into(),
+                        Expression::Identifier("N").into(),
+                    )
+                    .into(),
+                ),
+            FlatEmbed::U8ToBits | FlatEmbed::U8ToLeBits => UnresolvedSignature::new()
                 .inputs(vec![UnresolvedType::Uint(8).into()])
                 .output(
                     UnresolvedType::array(
@@ -88,7 +153,7 @@ impl FlatEmbed {
                     )
                     .into(),
                 ),
-            FlatEmbed::U16ToBits => UnresolvedSignature::new()
+            FlatEmbed::U16ToBits | FlatEmbed::U16ToLeBits => UnresolvedSignature::new()
                 .inputs(vec![UnresolvedType::Uint(16).into()])
                 .output(
                     UnresolvedType::array(
@@ -97,7 +162,7 @@ impl FlatEmbed {
                     )
                     .into(),
                 ),
-            FlatEmbed::U32ToBits => UnresolvedSignature::new()
+            FlatEmbed::U32ToBits | FlatEmbed::U32ToLeBits => UnresolvedSignature::new()
                 .inputs(vec![UnresolvedType::Uint(32).into()])
                 .output(
                     UnresolvedType::array(
@@ -106,7 +171,7 @@ impl FlatEmbed {
                     )
                     .into(),
                 ),
-            FlatEmbed::U64ToBits => UnresolvedSignature::new()
+            FlatEmbed::U64ToBits | FlatEmbed::U64ToLeBits => UnresolvedSignature::new()
                 .inputs(vec![UnresolvedType::Uint(64).into()])
                 .output(
                     UnresolvedType::array(
@@ -115,34 +180,116 @@ impl FlatEmbed {
                     )
                     .into(),
                 ),
-            FlatEmbed::U8FromBits => UnresolvedSignature::new()
+            FlatEmbed::U8FromBits | FlatEmbed::U8FromLeBits => UnresolvedSignature::new()
                 .output(UnresolvedType::Uint(8).into())
                 .inputs(vec![UnresolvedType::array(
                     UnresolvedType::Boolean.into(),
                     Expression::U32Constant(8).into(),
                 )
                 .into()]),
-            FlatEmbed::U16FromBits => UnresolvedSignature::new()
+            FlatEmbed::U16FromBits | FlatEmbed::U16FromLeBits => UnresolvedSignature::new()
                 .output(UnresolvedType::Uint(16).into())
                 .inputs(vec![UnresolvedType::array(
                     UnresolvedType::Boolean.into(),
                     Expression::U32Constant(16).into(),
                 )
                 .into()]),
-            FlatEmbed::U32FromBits => UnresolvedSignature::new()
+            FlatEmbed::U32FromBits | FlatEmbed::U32FromLeBits => UnresolvedSignature::new()
                 .output(UnresolvedType::Uint(32).into())
                 .inputs(vec![UnresolvedType::array(
                     UnresolvedType::Boolean.into(),
                     Expression::U32Constant(32).into(),
                 )
                 .into()]),
-            FlatEmbed::U64FromBits => UnresolvedSignature::new()
+            FlatEmbed::U64FromBits | FlatEmbed::U64FromLeBits => UnresolvedSignature::new()
                 .output(UnresolvedType::Uint(64).into())
                 .inputs(vec![UnresolvedType::array(
                     UnresolvedType::Boolean.into(),
                     Expression::U32Constant(64).into(),
                 )
                 .into()]),
+            FlatEmbed::FieldToU8
+            | FlatEmbed::FieldToU16
+            | FlatEmbed::FieldToU32
+            | FlatEmbed::FieldToU64
+            | FlatEmbed::FieldToU8Unchecked
+            | FlatEmbed::FieldToU16Unchecked
+            | FlatEmbed::FieldToU32Unchecked
+            | FlatEmbed::FieldToU64Unchecked => UnresolvedSignature::new()
+                .inputs(vec![UnresolvedType::FieldElement.into()])
+                .output(
+                    UnresolvedType::Uint(self.field_to_uint_bitwidth().unwrap().to_usize()).into(),
+                ),
+            FlatEmbed::I8ToBits => UnresolvedSignature::new()
+                .inputs(vec![UnresolvedType::Signed(8).into()])
+                .output(
+                    UnresolvedType::array(
+                        UnresolvedType::Boolean.into(),
+                        Expression::U32Constant(8).into(),
+                    )
+                    .into(),
+                ),
+            FlatEmbed::I16ToBits => UnresolvedSignature::new()
+                .inputs(vec![UnresolvedType::Signed(16).into()])
+                .output(
+                    UnresolvedType::array(
+                        UnresolvedType::Boolean.into(),
+                        Expression::U32Constant(16).into(),
+                    )
+                    .into(),
+                ),
+            FlatEmbed::I32ToBits => UnresolvedSignature::new()
+                .inputs(vec![UnresolvedType::Signed(32).into()])
+                .output(
+                    UnresolvedType::array(
+                        UnresolvedType::Boolean.into(),
+                        Expression::U32Constant(32).into(),
+                    )
+                    .into(),
+                ),
+            FlatEmbed::I8FromBits => UnresolvedSignature::new()
+                .output(UnresolvedType::Signed(8).into())
+                .inputs(vec![UnresolvedType::array(
+                    UnresolvedType::Boolean.into(),
+                    Expression::U32Constant(8).into(),
+                )
+                .into()]),
+            FlatEmbed::I16FromBits => UnresolvedSignature::new()
+                .output(UnresolvedType::Signed(16).into())
+                .inputs(vec![UnresolvedType::array(
+                    UnresolvedType::Boolean.into(),
+                    Expression::U32Constant(16).into(),
+                )
+                .into()]),
+            FlatEmbed::I32FromBits => UnresolvedSignature::new()
+                .output(UnresolvedType::Signed(32).into())
+                .inputs(vec![UnresolvedType::array(
+                    UnresolvedType::Boolean.into(),
+                    Expression::U32Constant(32).into(),
+                )
+                .into()]),
+            FlatEmbed::UnconstrainedWitness => {
+                UnresolvedSignature::new().output(UnresolvedType::FieldElement.into())
+            }
+            FlatEmbed::BatchEq => UnresolvedSignature::new()
+                .generics(vec!["N".into()])
+                .inputs(vec![
+                    UnresolvedType::array(
+                        UnresolvedType::FieldElement.into(),
+                        Expression::Identifier("N").into(),
+                    )
+                    .into(),
+                    UnresolvedType::array(
+                        UnresolvedType::FieldElement.into(),
+                        Expression::Identifier("N").into(),
+                    )
+                    .into(),
+                    UnresolvedType::FieldElement.into(),
+                ])
+                .output(UnresolvedType::Boolean.into()),
+            FlatEmbed::MerkleVerifyPoseidon => merkle_verify_signature(),
+            #[cfg(feature = "bellman")]
+            FlatEmbed::MerkleVerifySha256 => merkle_verify_signature(),
             #[cfg(feature = "bellman")]
             FlatEmbed::Sha256Round => UnresolvedSignature::new()
                 .inputs(vec![
@@ -208,7 +355,7 @@ impl FlatEmbed {
                     )),
                 ])
                 .output(DeclarationType::Boolean),
-            FlatEmbed::Unpack => DeclarationSignature::new()
+            FlatEmbed::Unpack | FlatEmbed::UnpackStrict => DeclarationSignature::new()
                 .generics(vec![Some(DeclarationConstant::Generic(
                     GenericIdentifier::with_name("N").with_index(0),
                 ))])
@@ -217,42 +364,112 @@ impl FlatEmbed {
                     DeclarationType::Boolean,
                     GenericIdentifier::with_name("N").with_index(0),
                 ))),
-            FlatEmbed::U8ToBits => DeclarationSignature::new()
+            FlatEmbed::Pack => DeclarationSignature::new()
+                .generics(vec![Some(DeclarationConstant::Generic(
+                    GenericIdentifier::with_name("N").with_index(0),
+                ))])
+                .inputs(vec![DeclarationType::array((
+                    DeclarationType::Boolean,
+                    GenericIdentifier::with_name("N").with_index(0),
+                ))])
+                .output(DeclarationType::FieldElement),
+            FlatEmbed::U8ToBits | FlatEmbed::U8ToLeBits => DeclarationSignature::new()
                 .inputs(vec![DeclarationType::uint(8)])
                 .output(DeclarationType::array((DeclarationType::Boolean, 8u32))),
-            FlatEmbed::U16ToBits => DeclarationSignature::new()
+            FlatEmbed::U16ToBits | FlatEmbed::U16ToLeBits => DeclarationSignature::new()
                 .inputs(vec![DeclarationType::uint(16)])
                 .output(DeclarationType::array((DeclarationType::Boolean, 16u32))),
-            FlatEmbed::U32ToBits => DeclarationSignature::new()
+            FlatEmbed::U32ToBits | FlatEmbed::U32ToLeBits => DeclarationSignature::new()
                 .inputs(vec![DeclarationType::uint(32)])
                 .output(DeclarationType::array((DeclarationType::Boolean, 32u32))),
-            FlatEmbed::U64ToBits => DeclarationSignature::new()
+            FlatEmbed::U64ToBits | FlatEmbed::U64ToLeBits => DeclarationSignature::new()
                 .inputs(vec![DeclarationType::uint(64)])
                 .output(DeclarationType::array((DeclarationType::Boolean, 64u32))),
-            FlatEmbed::U8FromBits => DeclarationSignature::new()
+            FlatEmbed::U8FromBits | FlatEmbed::U8FromLeBits => DeclarationSignature::new()
                 .output(DeclarationType::uint(8))
                 .inputs(vec![DeclarationType::array((
                     DeclarationType::Boolean,
                     8u32,
                 ))]),
-            FlatEmbed::U16FromBits => DeclarationSignature::new()
+            FlatEmbed::U16FromBits | FlatEmbed::U16FromLeBits => DeclarationSignature::new()
                 .output(DeclarationType::uint(16))
                 .inputs(vec![DeclarationType::array((
                     DeclarationType::Boolean,
                     16u32,
                 ))]),
-            FlatEmbed::U32FromBits => DeclarationSignature::new()
+            FlatEmbed::U32FromBits | FlatEmbed::U32FromLeBits => DeclarationSignature::new()
                 .output(DeclarationType::uint(32))
                 .inputs(vec![DeclarationType::array((
                     DeclarationType::Boolean,
                     32u32,
                 ))]),
-            FlatEmbed::U64FromBits => DeclarationSignature::new()
+            FlatEmbed::U64FromBits | FlatEmbed::U64FromLeBits => DeclarationSignature::new()
                 .output(DeclarationType::uint(64))
                 .inputs(vec![DeclarationType::array((
                     DeclarationType::Boolean,
                     64u32,
                 ))]),
+            FlatEmbed::FieldToU8
+            | FlatEmbed::FieldToU16
+            | FlatEmbed::FieldToU32
+            | FlatEmbed::FieldToU64
+            | FlatEmbed::FieldToU8Unchecked
+            | FlatEmbed::FieldToU16Unchecked
+            | FlatEmbed::FieldToU32Unchecked
+            | FlatEmbed::FieldToU64Unchecked => DeclarationSignature::new()
+                .inputs(vec![DeclarationType::FieldElement])
+                .output(DeclarationType::Uint(
+                    self.field_to_uint_bitwidth().unwrap(),
+                )),
+            FlatEmbed::I8ToBits => DeclarationSignature::new()
+                .inputs(vec![DeclarationType::Uint(UBitwidth::I8)])
+                .output(DeclarationType::array((DeclarationType::Boolean, 8u32))),
+            FlatEmbed::I16ToBits => DeclarationSignature::new()
+                .inputs(vec![DeclarationType::Uint(UBitwidth::I16)])
+                .output(DeclarationType::array((DeclarationType::Boolean, 16u32))),
+            FlatEmbed::I32ToBits => DeclarationSignature::new()
+                .inputs(vec![DeclarationType::Uint(UBitwidth::I32)])
+                .output(DeclarationType::array((DeclarationType::Boolean, 32u32))),
+            FlatEmbed::I8FromBits => DeclarationSignature::new()
+                .output(DeclarationType::Uint(UBitwidth::I8))
+                .inputs(vec![DeclarationType::array((
+                    DeclarationType::Boolean,
+                    8u32,
+                ))]),
+            FlatEmbed::I16FromBits => DeclarationSignature::new()
+                .output(DeclarationType::Uint(UBitwidth::I16))
+                .inputs(vec![DeclarationType::array((
+                    DeclarationType::Boolean,
+                    16u32,
+                ))]),
+            FlatEmbed::I32FromBits => DeclarationSignature::new()
+                .output(DeclarationType::Uint(UBitwidth::I32))
+                .inputs(vec![DeclarationType::array((
+                    DeclarationType::Boolean,
+                    32u32,
+                ))]),
+            FlatEmbed::UnconstrainedWitness => {
+                DeclarationSignature::new().output(DeclarationType::FieldElement)
+            }
+            FlatEmbed::BatchEq => DeclarationSignature::new()
+                .generics(vec![Some(DeclarationConstant::Generic(
+                    GenericIdentifier::with_name("N").with_index(0),
+                ))])
+                .inputs(vec![
+                    DeclarationType::array((
+                        DeclarationType::FieldElement,
+                        GenericIdentifier::with_name("N").with_index(0),
+                    )),
+                    DeclarationType::array((
+                        DeclarationType::FieldElement,
+                        GenericIdentifier::with_name("N").with_index(0),
+                    )),
+                    DeclarationType::FieldElement,
+                ])
+                .output(DeclarationType::Boolean),
+            FlatEmbed::MerkleVerifyPoseidon => merkle_verify_typed_signature(),
+            #[cfg(feature = "bellman")]
+            FlatEmbed::MerkleVerifySha256 => merkle_verify_typed_signature(),
             #[cfg(feature = "bellman")]
             FlatEmbed::Sha256Round => DeclarationSignature::new()
                 .inputs(vec![
@@ -302,20 +519,171 @@ impl FlatEmbed {
             FlatEmbed::FieldToBoolUnsafe => "_FIELD_TO_BOOL_UNSAFE",
             FlatEmbed::BitArrayLe => "_BIT_ARRAY_LT",
             FlatEmbed::Unpack => "_UNPACK",
+            FlatEmbed::Pack => "_PACK",
+            FlatEmbed::UnpackStrict => "_UNPACK_STRICT",
             FlatEmbed::U8ToBits => "_U8_TO_BITS",
+            FlatEmbed::U8ToLeBits => "_U8_TO_LE_BITS",
             FlatEmbed::U16ToBits => "_U16_TO_BITS",
+            FlatEmbed::U16ToLeBits => "_U16_TO_LE_BITS",
             FlatEmbed::U32ToBits => "_U32_TO_BITS",
+            FlatEmbed::U32ToLeBits => "_U32_TO_LE_BITS",
             FlatEmbed::U64ToBits => "_U64_TO_BITS",
+            FlatEmbed::U64ToLeBits => "_U64_TO_LE_BITS",
             FlatEmbed::U8FromBits => "_U8_FROM_BITS",
+            FlatEmbed::U8FromLeBits => "_U8_FROM_LE_BITS",
             FlatEmbed::U16FromBits => "_U16_FROM_BITS",
+            FlatEmbed::U16FromLeBits => "_U16_FROM_LE_BITS",
             FlatEmbed::U32FromBits => "_U32_FROM_BITS",
+            FlatEmbed::U32FromLeBits => "_U32_FROM_LE_BITS",
             FlatEmbed::U64FromBits => "_U64_FROM_BITS",
+            FlatEmbed::U64FromLeBits => "_U64_FROM_LE_BITS",
+            FlatEmbed::I8ToBits => "_I8_TO_BITS",
+            FlatEmbed::I16ToBits => "_I16_TO_BITS",
+            FlatEmbed::I32ToBits => "_I32_TO_BITS",
+            FlatEmbed::I8FromBits => "_I8_FROM_BITS",
+            FlatEmbed::I16FromBits => "_I16_FROM_BITS",
+            FlatEmbed::I32FromBits => "_I32_FROM_BITS",
+            FlatEmbed::FieldToU8 => "_FIELD_TO_U8",
+            FlatEmbed::FieldToU16 => "_FIELD_TO_U16",
+            FlatEmbed::FieldToU32 => "_FIELD_TO_U32",
+            FlatEmbed::FieldToU64 => "_FIELD_TO_U64",
+            FlatEmbed::FieldToU8Unchecked => "_FIELD_TO_U8_UNCHECKED",
+            FlatEmbed::FieldToU16Unchecked => "_FIELD_TO_U16_UNCHECKED",
+            FlatEmbed::FieldToU32Unchecked => "_FIELD_TO_U32_UNCHECKED",
+            FlatEmbed::FieldToU64Unchecked => "_FIELD_TO_U64_UNCHECKED",
+            FlatEmbed::UnconstrainedWitness => "_UNCONSTRAINED_WITNESS",
+            FlatEmbed::BatchEq => "_BATCH_EQ",
+            FlatEmbed::MerkleVerifyPoseidon => "_MERKLE_VERIFY_POSEIDON",
             #[cfg(feature = "bellman")]
             FlatEmbed::Sha256Round => "_SHA256_ROUND",
+            #[cfg(feature = "bellman")]
+            FlatEmbed::MerkleVerifySha256 => "_MERKLE_VERIFY_SHA256",
             #[cfg(feature = "ark")]
             FlatEmbed::SnarkVerifyBls12377 => "_SNARK_VERIFY_BLS12_377",
         }
     }
+
+    /// The bitwidth of the integer returned by a `_UX_FROM_BITS`, `_UX_FROM_LE_BITS` or `_IX_FROM_BITS` embed
+    pub fn from_bits_bitwidth(&self) -> Option<UBitwidth> {
+        match self {
+            FlatEmbed::U8FromBits | FlatEmbed::U8FromLeBits => Some(UBitwidth::B8),
+            FlatEmbed::U16FromBits | FlatEmbed::U16FromLeBits => Some(UBitwidth::B16),
+            FlatEmbed::U32FromBits | FlatEmbed::U32FromLeBits => Some(UBitwidth::B32),
+            FlatEmbed::U64FromBits | FlatEmbed::U64FromLeBits => Some(UBitwidth::B64),
+            FlatEmbed::I8FromBits => Some(UBitwidth::I8),
+            FlatEmbed::I16FromBits => Some(UBitwidth::I16),
+            FlatEmbed::I32FromBits => Some(UBitwidth::I32),
+            _ => None,
+        }
+    }
+
+    /// The order of the bits taken or returned by a bit decomposition embed.
+    /// Both orders use the same constraints: only the order of the bits in the array differs
+    pub fn bit_order(&self) -> Endianness {
+        match self {
+            FlatEmbed::U8ToLeBits
+            | FlatEmbed::U16ToLeBits
+            | FlatEmbed::U32ToLeBits
+            | FlatEmbed::U64ToLeBits
+            | FlatEmbed::U8FromLeBits
+            | FlatEmbed::U16FromLeBits
+            | FlatEmbed::U32FromLeBits
+            | FlatEmbed::U64FromLeBits => Endianness::Little,
+            _ => Endianness::Big,
+        }
+    }
+
+    /// The bitwidth of the integer taken by a `_UX_TO_BITS`, `_UX_TO_LE_BITS` or `_IX_TO_BITS` embed
+    pub fn to_bits_bitwidth(&self) -> Option<UBitwidth> {
+        match self {
+            FlatEmbed::U8ToBits | FlatEmbed::U8ToLeBits => Some(UBitwidth::B8),
+            FlatEmbed::U16ToBits | FlatEmbed::U16ToLeBits => Some(UBitwidth::B16),
+            FlatEmbed::U32ToBits | FlatEmbed::U32ToLeBits => Some(UBitwidth::B32),
+            FlatEmbed::U64ToBits | FlatEmbed::U64ToLeBits => Some(UBitwidth::B64),
+            FlatEmbed::I8ToBits => Some(UBitwidth::I8),
+            FlatEmbed::I16ToBits => Some(UBitwidth::I16),
+            FlatEmbed::I32ToBits => Some(UBitwidth::I32),
+            _ => None,
+        }
+    }
+
+    /// The bitwidth of the integer returned by a field to integer conversion embed
+    pub fn field_to_uint_bitwidth(&self) -> Option<UBitwidth> {
+        match self {
+            FlatEmbed::FieldToU8 | FlatEmbed::FieldToU8Unchecked => Some(UBitwidth::B8),
+            FlatEmbed::FieldToU16 | FlatEmbed::FieldToU16Unchecked => Some(UBitwidth::B16),
+            FlatEmbed::FieldToU32 | FlatEmbed::FieldToU32Unchecked => Some(UBitwidth::B32),
+            FlatEmbed::FieldToU64 | FlatEmbed::FieldToU64Unchecked => Some(UBitwidth::B64),
+            _ => None,
+        }
+    }
+
+    /// Whether this embed converts a field element to an integer without proving that it fits
+    pub fn is_unchecked(&self) -> bool {
+        matches!(
+            self,
+            FlatEmbed::FieldToU8Unchecked
+                | FlatEmbed::FieldToU16Unchecked
+                | FlatEmbed::FieldToU32Unchecked
+                | FlatEmbed::FieldToU64Unchecked
+        )
+    }
+
+    /// The conversion embed called by a cast such as `u32(x)` or `u32::unchecked(x)`, given the name of the cast
+    pub fn cast(id: &str) -> Option<FlatEmbed> {
+        match id {
+            "u8" => Some(FlatEmbed::FieldToU8),
+            "u16" => Some(FlatEmbed::FieldToU16),
+            "u32" => Some(FlatEmbed::FieldToU32),
+            "u64" => Some(FlatEmbed::FieldToU64),
+            "u8::unchecked" => Some(FlatEmbed::FieldToU8Unchecked),
+            "u16::unchecked" => Some(FlatEmbed::FieldToU16Unchecked),
+            "u32::unchecked" => Some(FlatEmbed::FieldToU32Unchecked),
+            "u64::unchecked" => Some(FlatEmbed::FieldToU64Unchecked),
+            _ => None,
+        }
+    }
+}
+
+// merkle_verify<DEPTH>(field leaf, field root, field[DEPTH] path, bool[DEPTH] directions) -> bool
+fn merkle_verify_signature() -> UnresolvedSignature<'static> {
+    UnresolvedSignature::new()
+        .generics(vec!["DEPTH".into()])
+        .inputs(vec![
+            UnresolvedType::FieldElement.into(), // leaf
+            UnresolvedType::FieldElement.into(), // root
+            UnresolvedType::array(
+                UnresolvedType::FieldElement.into(),
+                Expression::Identifier("DEPTH").into(),
+            )
+            .into(), // path
+            UnresolvedType::array(
+                UnresolvedType::Boolean.into(),
+                Expression::Identifier("DEPTH").into(),
+            )
+            .into(), // directions
+        ])
+        .output(UnresolvedType::Boolean.into())
+}
+
+fn merkle_verify_typed_signature<T>() -> DeclarationSignature<'static, T> {
+    DeclarationSignature::new()
+        .generics(vec![Some(DeclarationConstant::Generic(
+            GenericIdentifier::with_name("DEPTH").with_index(0),
+        ))])
+        .inputs(vec![
+            DeclarationType::FieldElement, // leaf
+            DeclarationType::FieldElement, // root
+            DeclarationType::array((
+                DeclarationType::FieldElement,
+                GenericIdentifier::with_name("DEPTH").with_index(0),
+            )), // path
+            DeclarationType::array((
+                DeclarationType::Boolean,
+                GenericIdentifier::with_name("DEPTH").with_index(0),
+            )), // directions
+        ])
+        .output(DeclarationType::Boolean)
 }
 
 /// Returns a flat function which computes a sha256 round
@@ -644,6 +1012,129 @@ mod tests {
     use super::*;
     use zokrates_field::Bn128Field;
 
+    #[test]
+    fn uint_bitwidths() {
+        let from_bits = [
+            (FlatEmbed::U8FromBits, UBitwidth::B8),
+            (FlatEmbed::U16FromBits, UBitwidth::B16),
+            (FlatEmbed::U32FromBits, UBitwidth::B32),
+            (FlatEmbed::U64FromBits, UBitwidth::B64),
+        ];
+
+        let to_bits = [
+            (FlatEmbed::U8ToBits, UBitwidth::B8),
+            (FlatEmbed::U16ToBits, UBitwidth::B16),
+            (FlatEmbed::U32ToBits, UBitwidth::B32),
+            (FlatEmbed::U64ToBits, UBitwidth::B64),
+        ];
+
+        for (embed, bitwidth) in from_bits {
+            assert_eq!(embed.from_bits_bitwidth(), Some(bitwidth));
+            assert_eq!(embed.to_bits_bitwidth(), None);
+            assert_eq!(embed.id(), format!("_U{}_FROM_BITS", bitwidth.to_usize()));
+        }
+
+        for (embed, bitwidth) in to_bits {
+            assert_eq!(embed.to_bits_bitwidth(), Some(bitwidth));
+            assert_eq!(embed.from_bits_bitwidth(), None);
+            assert_eq!(embed.id(), format!("_U{}_TO_BITS", bitwidth.to_usize()));
+        }
+
+        assert_eq!(FlatEmbed::Unpack.from_bits_bitwidth(), None);
+        assert_eq!(FlatEmbed::Unpack.to_bits_bitwidth(), None);
+        assert_eq!(FlatEmbed::Pack.from_bits_bitwidth(), None);
+        assert_eq!(FlatEmbed::UnpackStrict.to_bits_bitwidth(), None);
+    }
+
+    #[test]
+    fn signed_bitwidths() {
+        for bitwidth in [UBitwidth::I8, UBitwidth::I16, UBitwidth::I32] {
+            let (from_bits, to_bits) = match bitwidth {
+                UBitwidth::I8 => (FlatEmbed::I8FromBits, FlatEmbed::I8ToBits),
+                UBitwidth::I16 => (FlatEmbed::I16FromBits, FlatEmbed::I16ToBits),
+                _ => (FlatEmbed::I32FromBits, FlatEmbed::I32ToBits),
+            };
+
+            assert_eq!(from_bits.from_bits_bitwidth(), Some(bitwidth));
+            assert_eq!(to_bits.to_bits_bitwidth(), Some(bitwidth));
+            assert_eq!(from_bits.bit_order(), Endianness::Big);
+            assert_eq!(
+                from_bits.id(),
+                format!("_I{}_FROM_BITS", bitwidth.to_usize())
+            );
+            assert_eq!(to_bits.id(), format!("_I{}_TO_BITS", bitwidth.to_usize()));
+        }
+    }
+
+    #[test]
+    fn casts() {
+        for bitwidth in [
+            UBitwidth::B8,
+            UBitwidth::B16,
+            UBitwidth::B32,
+            UBitwidth::B64,
+        ] {
+            let checked = FlatEmbed::cast(&bitwidth.type_name()).unwrap();
+            let unchecked =
+                FlatEmbed::cast(&format!("{}::unchecked", bitwidth.type_name())).unwrap();
+
+            assert_eq!(checked.field_to_uint_bitwidth(), Some(bitwidth));
+            assert_eq!(unchecked.field_to_uint_bitwidth(), Some(bitwidth));
+            assert!(!checked.is_unchecked());
+            assert!(unchecked.is_unchecked());
+            assert_eq!(
+                checked.typed_signature::<Bn128Field>(),
+                unchecked.typed_signature::<Bn128Field>()
+            );
+            assert_eq!(
+                unchecked.id(),
+                format!("_FIELD_TO_U{}_UNCHECKED", bitwidth.to_usize())
+            );
+        }
+
+        assert_eq!(FlatEmbed::cast("i32"), None);
+        assert_eq!(FlatEmbed::cast("field"), None);
+    }
+
+    #[test]
+    fn bit_orders() {
+        let little_endian = [
+            (FlatEmbed::U8ToLeBits, FlatEmbed::U8ToBits),
+            (FlatEmbed::U16ToLeBits, FlatEmbed::U16ToBits),
+            (FlatEmbed::U32ToLeBits, FlatEmbed::U32ToBits),
+            (FlatEmbed::U64ToLeBits, FlatEmbed::U64ToBits),
+            (FlatEmbed::U8FromLeBits, FlatEmbed::U8FromBits),
+            (FlatEmbed::U16FromLeBits, FlatEmbed::U16FromBits),
+            (FlatEmbed::U32FromLeBits, FlatEmbed::U32FromBits),
+            (FlatEmbed::U64FromLeBits, FlatEmbed::U64FromBits),
+        ];
+
+        // both orders have the same signature and bitwidth
+        for (little, big) in little_endian {
+            assert_eq!(little.bit_order(), Endianness::Little);
+            assert_eq!(big.bit_order(), Endianness::Big);
+            assert_eq!(little.to_bits_bitwidth(), big.to_bits_bitwidth());
+            assert_eq!(little.from_bits_bitwidth(), big.from_bits_bitwidth());
+            assert_eq!(
+                little.typed_signature::<Bn128Field>(),
+                big.typed_signature::<Bn128Field>()
+            );
+        }
+    }
+
+    #[test]
+    fn serialized_variant_names() {
+        // the embed is serialized by variant name, so programs serialized before the bitwidth accessors still load
+        assert_eq!(
+            serde_json::from_str::<FlatEmbed>("\"U32FromBits\"").unwrap(),
+            FlatEmbed::U32FromBits
+        );
+        assert_eq!(
+            serde_json::to_string(&FlatEmbed::U8ToBits).unwrap(),
+            "\"U8ToBits\""
+        );
+    }
+
     #[cfg(test)]
     mod split {
         use super::*;
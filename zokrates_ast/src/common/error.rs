@@ -30,7 +30,13 @@ pub enum RuntimeError {
     SourceAssertion(SourceMetadata),
     SourceAssemblyConstraint(SourceMetadata),
     ArgumentBitness,
+    ArgumentRange(String, u32),
     SelectRangeCheck,
+    DivisionByZero(SourceMetadata),
+    /// A field element converted to an unsigned integer of the given bitwidth does not fit
+    CastRange(u32),
+    /// The arrays compared by `batch_eq` differ
+    BatchEq,
 }
 
 impl From<crate::zir::RuntimeError> for RuntimeError {
@@ -40,7 +46,10 @@ impl From<crate::zir::RuntimeError> for RuntimeError {
                 RuntimeError::SourceAssertion(metadata)
             }
             crate::zir::RuntimeError::SelectRangeCheck => RuntimeError::SelectRangeCheck,
-            crate::zir::RuntimeError::DivisionByZero => RuntimeError::Inverse,
+            crate::zir::RuntimeError::DivisionByZero(Some(metadata)) => {
+                RuntimeError::DivisionByZero(metadata)
+            }
+            crate::zir::RuntimeError::DivisionByZero(None) => RuntimeError::Inverse,
             crate::zir::RuntimeError::IncompleteDynamicRange => {
                 RuntimeError::IncompleteDynamicRange
             }
@@ -48,6 +57,27 @@ impl From<crate::zir::RuntimeError> for RuntimeError {
     }
 }
 
+/// The runtime errors of the typed, zir and flat/ir programs, which all record the assertions of the source
+pub trait AssertionError {
+    /// The metadata of the source assertion this error comes from, if any
+    fn source_assertion(&self) -> Option<&SourceMetadata>;
+
+    /// Whether this error comes from an assertion marked `#[critical]`
+    fn is_critical(&self) -> bool {
+        self.source_assertion()
+            .map_or(false, |metadata| metadata.critical)
+    }
+}
+
+impl AssertionError for RuntimeError {
+    fn source_assertion(&self) -> Option<&SourceMetadata> {
+        match self {
+            RuntimeError::SourceAssertion(metadata) => Some(metadata),
+            _ => None,
+        }
+    }
+}
+
 impl RuntimeError {
     pub fn is_malicious(&self) -> bool {
         use RuntimeError::*;
@@ -57,8 +87,12 @@ impl RuntimeError {
             SourceAssemblyConstraint(_)
                 | SourceAssertion(_)
                 | Inverse
+                | DivisionByZero(_)
                 | SelectRangeCheck
                 | ArgumentBitness
+                | ArgumentRange(..)
+                | CastRange(_)
+                | BatchEq
                 | IncompleteDynamicRange
         )
     }
@@ -103,7 +137,30 @@ impl fmt::Display for RuntimeError {
                 buf.as_str()
             }
             ArgumentBitness => "Argument bitness check failed",
+            ArgumentRange(name, bits) => {
+                write!(
+                    &mut buf,
+                    "Argument `{}` does not fit in {} bits",
+                    name, bits
+                )
+                .unwrap();
+                buf.as_str()
+            }
             SelectRangeCheck => "Out of bounds array access",
+            DivisionByZero(m) => {
+                write!(&mut buf, "Division by zero at {}", m).unwrap();
+                buf.as_str()
+            }
+            BatchEq => "Batched equality check failed: the arrays differ",
+            CastRange(bits) => {
+                write!(
+                    &mut buf,
+                    "Field element does not fit in {} bits when converted to `u{}`",
+                    bits, bits
+                )
+                .unwrap();
+                buf.as_str()
+            }
         };
 
         write!(f, "{}", msg)
@@ -2,14 +2,41 @@ use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
+/// How a value is rendered in place of a placeholder
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Hash, Serialize, Deserialize)]
+pub enum FormatSpec {
+    /// `{}`
+    Display,
+    /// `{:x}`, only valid for unsigned integers
+    Hex,
+}
+
+impl FormatSpec {
+    fn placeholder(&self) -> &'static str {
+        match self {
+            FormatSpec::Display => "{}",
+            FormatSpec::Hex => "{:x}",
+        }
+    }
+}
+
 #[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Hash, Serialize, Deserialize)]
 pub struct FormatString {
     pub parts: Vec<String>,
+    /// The spec of each placeholder, between two consecutive parts
+    // programs compiled before specs existed only use `{}`
+    #[serde(default)]
+    pub specs: Vec<FormatSpec>,
 }
 
 impl fmt::Display for FormatString {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.parts.join("{}"))
+        let mut parts = self.parts.iter();
+        write!(f, "{}", parts.next().unwrap())?;
+        for (index, part) in parts.enumerate() {
+            write!(f, "{}{}", self.spec(index).placeholder(), part)?;
+        }
+        Ok(())
     }
 }
 
@@ -21,13 +48,68 @@ impl FormatString {
     pub fn is_empty(&self) -> bool {
         self.parts.len() == 1
     }
+
+    /// The spec of the placeholder at `index`
+    pub fn spec(&self, index: usize) -> FormatSpec {
+        self.specs
+            .get(index)
+            .cloned()
+            .unwrap_or(FormatSpec::Display)
+    }
 }
 
 impl From<&str> for FormatString {
     fn from(s: &str) -> Self {
-        let parts = s.split("{}").map(|p| p.to_string());
-        FormatString {
-            parts: parts.collect(),
+        let mut parts = vec![];
+        let mut specs = vec![];
+
+        let mut rest = s;
+        let mut part = String::new();
+
+        while !rest.is_empty() {
+            if let Some(r) = rest.strip_prefix("{}") {
+                parts.push(std::mem::take(&mut part));
+                specs.push(FormatSpec::Display);
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix("{:x}") {
+                parts.push(std::mem::take(&mut part));
+                specs.push(FormatSpec::Hex);
+                rest = r;
+            } else {
+                let c = rest.chars().next().unwrap();
+                part.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
         }
+
+        parts.push(part);
+
+        FormatString { parts, specs }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let s = FormatString::from("a = {}, b = {:x}{}");
+
+        assert_eq!(s.parts, vec!["a = ", ", b = ", "", ""]);
+        assert_eq!(
+            s.specs,
+            vec![FormatSpec::Display, FormatSpec::Hex, FormatSpec::Display]
+        );
+        assert_eq!(s.len(), 3);
+        assert_eq!(s.to_string(), "a = {}, b = {:x}{}");
+    }
+
+    #[test]
+    fn no_placeholder() {
+        let s = FormatString::from("{:?}");
+
+        assert!(s.is_empty());
+        assert_eq!(s.to_string(), "{:?}");
     }
 }
@@ -7,6 +7,13 @@ pub struct SourceMetadata {
     pub file: String,
     pub position: Position,
     pub message: Option<String>,
+    /// Whether the assertion was marked `#[critical]`, in which case it must survive optimization
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub critical: bool,
+    /// The element of the compared values which the assertion is about, when asserting the equality of arrays,
+    /// structs or tuples
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub element: Option<String>,
 }
 
 impl SourceMetadata {
@@ -15,17 +22,30 @@ impl SourceMetadata {
             file,
             position,
             message: None,
+            critical: false,
+            element: None,
         }
     }
     pub fn message(mut self, message: Option<String>) -> Self {
         self.message = message;
         self
     }
+    pub fn critical(mut self, critical: bool) -> Self {
+        self.critical = critical;
+        self
+    }
+    pub fn element(mut self, element: Option<String>) -> Self {
+        self.element = element;
+        self
+    }
 }
 
 impl fmt::Display for SourceMetadata {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}:{}", self.file, self.position)?;
+        if let Some(element) = &self.element {
+            write!(f, " on `{}`", element)?;
+        }
         match &self.message {
             Some(m) => write!(f, ": \"{}\"", m),
             None => write!(f, ""),
@@ -1,15 +1,18 @@
+mod diagnostic;
 pub mod embed;
 mod error;
 mod format_string;
 mod metadata;
 mod parameter;
 mod solvers;
+pub mod stack;
 mod variable;
 
-pub use self::embed::FlatEmbed;
-pub use self::error::RuntimeError;
+pub use self::diagnostic::Diagnostic;
+pub use self::embed::{Endianness, FlatEmbed};
+pub use self::error::{AssertionError, RuntimeError};
 pub use self::metadata::SourceMetadata;
 pub use self::parameter::Parameter;
 pub use self::solvers::Solver;
 pub use self::variable::Variable;
-pub use format_string::FormatString;
+pub use format_string::{FormatSpec, FormatString};
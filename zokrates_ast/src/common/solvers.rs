@@ -1,5 +1,6 @@
 use crate::zir::ZirFunction;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::fmt;
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Hash, Eq)]
@@ -12,8 +13,12 @@ pub enum Solver<'ast, T> {
     ShaAndXorAndXorAnd,
     ShaCh,
     EuclideanDiv,
+    /// A value chosen by the prover, derived from the inputs and the index of the sample
+    Sample(usize, usize),
     #[serde(borrow)]
     Zir(ZirFunction<'ast, T>),
+    /// A solver provided at execution time under a name, with its number of inputs and outputs
+    Plugin(Cow<'ast, str>, usize, usize),
     #[cfg(feature = "bellman")]
     Sha256Round,
     #[cfg(feature = "ark")]
@@ -31,7 +36,11 @@ impl<'ast, T> fmt::Display for Solver<'ast, T> {
             Solver::ShaAndXorAndXorAnd => write!(f, "ShaAndXorAndXorAnd"),
             Solver::ShaCh => write!(f, "ShaCh"),
             Solver::EuclideanDiv => write!(f, "EuclideanDiv"),
+            Solver::Sample(n, index) => write!(f, "Sample({}, {})", n, index),
             Solver::Zir(_) => write!(f, "Zir(..)"),
+            Solver::Plugin(name, inputs, outputs) => {
+                write!(f, "Plugin(\"{}\", {}, {})", name, inputs, outputs)
+            }
             #[cfg(feature = "bellman")]
             Solver::Sha256Round => write!(f, "Sha256Round"),
             #[cfg(feature = "ark")]
@@ -51,17 +60,77 @@ impl<'ast, T> Solver<'ast, T> {
             Solver::ShaAndXorAndXorAnd => (3, 1),
             Solver::ShaCh => (3, 1),
             Solver::EuclideanDiv => (2, 2),
+            Solver::Sample(n, _) => (*n, 1),
             Solver::Zir(f) => (f.arguments.len(), 1),
+            Solver::Plugin(_, inputs, outputs) => (*inputs, *outputs),
             #[cfg(feature = "bellman")]
             Solver::Sha256Round => (768, 26935),
             #[cfg(feature = "ark")]
             Solver::SnarkVerifyBls12377(n) => (26 + 3 * n, 41991 + 4972 * n),
         }
     }
+
+    /// The kind of this solver, ignoring its parameters
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Solver::ConditionEq => "ConditionEq",
+            Solver::Bits(..) => "Bits",
+            Solver::Div => "Div",
+            Solver::Xor => "Xor",
+            Solver::Or => "Or",
+            Solver::ShaAndXorAndXorAnd => "ShaAndXorAndXorAnd",
+            Solver::ShaCh => "ShaCh",
+            Solver::EuclideanDiv => "EuclideanDiv",
+            Solver::Sample(..) => "Sample",
+            Solver::Zir(..) => "Zir",
+            Solver::Plugin(..) => "Plugin",
+            #[cfg(feature = "bellman")]
+            Solver::Sha256Round => "Sha256Round",
+            #[cfg(feature = "ark")]
+            Solver::SnarkVerifyBls12377(..) => "SnarkVerifyBls12377",
+        }
+    }
 }
 
 impl<'ast, T> Solver<'ast, T> {
     pub fn bits(width: usize) -> Self {
         Solver::Bits(width)
     }
+
+    /// The solvers which take no parameters, so that they are fully described by their kind
+    pub fn without_parameters() -> Vec<Self> {
+        #[allow(unused_mut)]
+        let mut solvers = vec![
+            Solver::ConditionEq,
+            Solver::Div,
+            Solver::Xor,
+            Solver::Or,
+            Solver::ShaAndXorAndXorAnd,
+            Solver::ShaCh,
+            Solver::EuclideanDiv,
+        ];
+        #[cfg(feature = "bellman")]
+        solvers.push(Solver::Sha256Round);
+
+        debug_assert!(solvers.iter().all(|s| !s.has_parameters()));
+        solvers
+    }
+
+    /// Whether this solver takes parameters, which follow its kind in parentheses
+    pub fn has_parameters(&self) -> bool {
+        match self {
+            Solver::ConditionEq
+            | Solver::Div
+            | Solver::Xor
+            | Solver::Or
+            | Solver::ShaAndXorAndXorAnd
+            | Solver::ShaCh
+            | Solver::EuclideanDiv => false,
+            #[cfg(feature = "bellman")]
+            Solver::Sha256Round => false,
+            Solver::Bits(..) | Solver::Sample(..) | Solver::Zir(..) | Solver::Plugin(..) => true,
+            #[cfg(feature = "ark")]
+            Solver::SnarkVerifyBls12377(..) => true,
+        }
+    }
 }
@@ -0,0 +1,21 @@
+//! Growing the stack of the recursive passes over expressions.
+//!
+//! The folders, the visitors and the flattening recurse on the operands of expressions, so that a long chain such as
+//! `a + a + ... + a` can exhaust the stack of the thread. The recursive steps go through `maybe_grow`, which runs them
+//! on a new segment allocated on the heap when the current one is close to being exhausted. Checking the remaining
+//! space is a comparison with the stack pointer, so that programs of a usual depth are not slowed down.
+//!
+//! The unsigned integer expressions of zir, which the uint optimizer and the flattening keep nesting, also grow the
+//! stack when they are dropped, cloned, compared, hashed or formatted.
+
+/// The space left on the stack below which a new segment is allocated, larger than the frames between two checks
+const RED_ZONE: usize = 512 * 1024;
+
+/// The size of the segments allocated when the stack runs low
+const SEGMENT_SIZE: usize = 8 * 1024 * 1024;
+
+/// Run `f`, on a new stack segment if less than `RED_ZONE` is left on the current one
+#[inline]
+pub fn maybe_grow<R, F: FnOnce() -> R>(f: F) -> R {
+    stacker::maybe_grow(RED_ZONE, SEGMENT_SIZE, f)
+}
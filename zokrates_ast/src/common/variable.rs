@@ -86,6 +86,11 @@ impl Variable {
     pub fn is_output(&self) -> bool {
         self.id < 0
     }
+
+    /// Whether this variable is one of the first `return_count` outputs, the following ones being private
+    pub fn is_public_output(&self, return_count: usize) -> bool {
+        self.is_output() && (-self.id) as usize <= return_count
+    }
 }
 
 #[cfg(test)]
@@ -265,24 +265,30 @@ impl<T: Field> FlatExpression<T> {
     }
 
     pub fn is_linear(&self) -> bool {
-        match *self {
-            FlatExpression::Number(_) | FlatExpression::Identifier(_) => true,
-            FlatExpression::Add(ref x, ref y) | FlatExpression::Sub(ref x, ref y) => {
-                x.is_linear() && y.is_linear()
+        // the operands of sums and differences are walked with a stack, as they can be nested deeply
+        let mut stack = vec![self];
+
+        while let Some(e) = stack.pop() {
+            match e {
+                FlatExpression::Number(_) | FlatExpression::Identifier(_) => {}
+                FlatExpression::Add(x, y) | FlatExpression::Sub(x, y) => {
+                    stack.push(y);
+                    stack.push(x);
+                }
+                FlatExpression::Mult(x, y) => {
+                    if !matches!(
+                        (x.as_ref(), y.as_ref()),
+                        (FlatExpression::Number(_), FlatExpression::Number(_))
+                            | (FlatExpression::Number(_), FlatExpression::Identifier(_))
+                            | (FlatExpression::Identifier(_), FlatExpression::Number(_))
+                    ) {
+                        return false;
+                    }
+                }
             }
-            FlatExpression::Mult(ref x, ref y) => matches!(
-                (x.clone(), y.clone()),
-                (box FlatExpression::Number(_), box FlatExpression::Number(_))
-                    | (
-                        box FlatExpression::Number(_),
-                        box FlatExpression::Identifier(_)
-                    )
-                    | (
-                        box FlatExpression::Identifier(_),
-                        box FlatExpression::Number(_)
-                    )
-            ),
         }
+
+        true
     }
 }
 
@@ -0,0 +1,237 @@
+//! Expose compiled programs to arkworks proving systems.
+//!
+//! `ProgSynthesizer` implements `ConstraintSynthesizer` for the scalar field of the pairing engine
+//! associated with `T`, so that any arkworks backend can be used directly on a `Prog`.
+//!
+//! Public inputs are allocated in the order of `ProgIterator::public_variables`: by default the public
+//! arguments in declaration order, followed by the return values `~out_0`, `~out_1`, ...
+
+use super::{LinComb, Prog, Statement, Variable, Witness};
+use ark_ec::PairingEngine;
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, SynthesisError,
+    Variable as ArkVariable,
+};
+use std::collections::BTreeMap;
+use zokrates_field::{ArkFieldExtensions, Field};
+
+type Fr<T> = <<T as ArkFieldExtensions>::ArkEngine as PairingEngine>::Fr;
+
+pub struct ProgSynthesizer<'a, T> {
+    program: &'a Prog<'a, T>,
+    witness: Option<&'a Witness<T>>,
+}
+
+impl<'a, T> ProgSynthesizer<'a, T> {
+    /// Create a synthesizer without assignments, suitable for key generation
+    pub fn without_witness(program: &'a Prog<'a, T>) -> Self {
+        ProgSynthesizer {
+            program,
+            witness: None,
+        }
+    }
+
+    /// Create a synthesizer which assigns variables from `witness`, suitable for proving
+    pub fn with_witness(program: &'a Prog<'a, T>, witness: &'a Witness<T>) -> Self {
+        ProgSynthesizer {
+            program,
+            witness: Some(witness),
+        }
+    }
+}
+
+impl<'a, T: Field + ArkFieldExtensions> ProgSynthesizer<'a, T> {
+    /// The public inputs expected by a verifier, in allocation order
+    ///
+    /// Panics if no witness was provided.
+    pub fn public_inputs(&self) -> Vec<Fr<T>> {
+        self.program
+            .public_inputs_values(self.witness.unwrap())
+            .into_iter()
+            .map(|v| v.into_ark())
+            .collect()
+    }
+
+    fn value(&self, variable: &Variable) -> Result<Fr<T>, SynthesisError> {
+        self.witness
+            .and_then(|w| w.0.get(variable))
+            .map(|v| v.clone().into_ark())
+            .ok_or(SynthesisError::AssignmentMissing)
+    }
+
+    fn combination(
+        &self,
+        l: &LinComb<T>,
+        cs: &ConstraintSystemRef<Fr<T>>,
+        symbols: &mut BTreeMap<Variable, ArkVariable>,
+    ) -> Result<LinearCombination<Fr<T>>, SynthesisError> {
+        l.clone().into_canonical().0.into_iter().try_fold(
+            LinearCombination::zero(),
+            |acc, (k, v)| {
+                let variable = match symbols.get(&k) {
+                    Some(variable) => *variable,
+                    None => {
+                        let variable = cs.new_witness_variable(|| self.value(&k))?;
+                        symbols.insert(k, variable);
+                        variable
+                    }
+                };
+                Ok(acc + (v.into_ark(), variable))
+            },
+        )
+    }
+
+    fn enforce(
+        &self,
+        statements: &[Statement<'a, T>],
+        cs: &ConstraintSystemRef<Fr<T>>,
+        symbols: &mut BTreeMap<Variable, ArkVariable>,
+    ) -> Result<(), SynthesisError> {
+        for statement in statements {
+            match statement {
+                Statement::Block(statements) => self.enforce(statements, cs, symbols)?,
+                Statement::Constraint(quad, lin, _) => {
+                    let a = self.combination(&quad.left, cs, symbols)?;
+                    let b = self.combination(&quad.right, cs, symbols)?;
+                    let c = self.combination(lin, cs, symbols)?;
+
+                    cs.enforce_constraint(a, b, c)?;
+                }
+                Statement::Directive(..) | Statement::Log(..) => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, T: Field + ArkFieldExtensions> ConstraintSynthesizer<Fr<T>> for ProgSynthesizer<'a, T> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr<T>>) -> Result<(), SynthesisError> {
+        let mut symbols = BTreeMap::new();
+        symbols.insert(Variable::one(), ArkVariable::One);
+
+        for variable in self.program.public_variables() {
+            let v = cs.new_input_variable(|| self.value(&variable))?;
+            symbols.insert(variable, v);
+        }
+
+        for variable in self
+            .program
+            .arguments
+            .iter()
+            .filter(|p| p.private)
+            .map(|p| p.id)
+        {
+            let v = cs.new_witness_variable(|| self.value(&variable))?;
+            symbols.insert(variable, v);
+        }
+
+        self.enforce(&self.program.statements, &cs, &mut symbols)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{Parameter, QuadComb};
+    use ark_groth16::{
+        create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof,
+    };
+    use ark_relations::r1cs::ConstraintSystem;
+    use rand_0_8::{rngs::StdRng, SeedableRng};
+    use zokrates_field::Bn128Field;
+
+    type Engine = <Bn128Field as ArkFieldExtensions>::ArkEngine;
+
+    // def main(public x, private y, public z) -> ~out_0
+    //     ~out_0 + (-1) * z == x * y
+    fn fixture() -> (Prog<'static, Bn128Field>, Witness<Bn128Field>) {
+        let x = Variable::new(0);
+        let y = Variable::new(1);
+        let z = Variable::new(2);
+        let out = Variable::public(0);
+
+        let prog = Prog::new(
+            vec![
+                Parameter::public(x),
+                Parameter::private(y),
+                Parameter::public(z),
+            ],
+            vec![Statement::constraint(
+                QuadComb::from_linear_combinations(x.into(), y.into()),
+                LinComb::from(out) - LinComb::from(z),
+            )],
+            1,
+        );
+
+        let witness = Witness(
+            vec![
+                (Variable::one(), Bn128Field::from(1)),
+                (x, Bn128Field::from(3)),
+                (y, Bn128Field::from(4)),
+                (z, Bn128Field::from(5)),
+                (out, Bn128Field::from(17)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        (prog, witness)
+    }
+
+    #[test]
+    fn prove_and_verify() {
+        let (prog, witness) = fixture();
+        let rng = &mut StdRng::from_seed([0; 32]);
+
+        let params = generate_random_parameters::<Engine, _, _>(
+            ProgSynthesizer::without_witness(&prog),
+            rng,
+        )
+        .unwrap();
+
+        let synthesizer = ProgSynthesizer::with_witness(&prog, &witness);
+        let inputs = synthesizer.public_inputs();
+        let proof = create_random_proof(synthesizer, &params, rng).unwrap();
+
+        let pvk = prepare_verifying_key(&params.vk);
+        assert!(verify_proof(&pvk, &proof, &inputs).unwrap());
+
+        let mut wrong_inputs = inputs;
+        wrong_inputs.swap(0, 1);
+        assert!(!verify_proof(&pvk, &proof, &wrong_inputs).unwrap());
+    }
+
+    #[test]
+    fn public_input_order() {
+        let (prog, witness) = fixture();
+
+        let cs = ConstraintSystem::new_ref();
+        ProgSynthesizer::with_witness(&prog, &witness)
+            .generate_constraints(cs.clone())
+            .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        let cs = cs.borrow().unwrap();
+        // the first instance variable is `~one`
+        assert_eq!(
+            cs.instance_assignment[1..].to_vec(),
+            prog.public_inputs_values(&witness)
+                .into_iter()
+                .map(|v| v.into_ark())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn missing_assignment() {
+        let (prog, mut witness) = fixture();
+        witness.0.remove(&Variable::new(1));
+
+        let cs = ConstraintSystem::<Fr<Bn128Field>>::new_ref();
+        assert_eq!(
+            ProgSynthesizer::with_witness(&prog, &witness).generate_constraints(cs),
+            Err(SynthesisError::AssignmentMissing)
+        );
+    }
+}
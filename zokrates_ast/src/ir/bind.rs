@@ -0,0 +1,118 @@
+use super::folder::Folder;
+use super::{LinComb, ProgIterator, Statement};
+use crate::common::Variable;
+use std::collections::HashMap;
+use zokrates_field::Field;
+
+/// Substitutes constant values for some arguments of a program
+pub struct Binder<T> {
+    values: HashMap<Variable, T>,
+}
+
+impl<'ast, T: Field, I: IntoIterator<Item = Statement<'ast, T>>> ProgIterator<'ast, T, I> {
+    /// Fix the values of some arguments of the program, removing them from its arguments. The linear combinations
+    /// using them are folded to constants
+    pub fn bind(
+        self,
+        values: HashMap<Variable, T>,
+    ) -> ProgIterator<'ast, T, impl IntoIterator<Item = Statement<'ast, T>>> {
+        let mut binder = Binder { values };
+
+        ProgIterator {
+            arguments: self
+                .arguments
+                .into_iter()
+                .filter(|a| !binder.values.contains_key(&a.id))
+                .collect(),
+            return_count: self.return_count,
+            public_layout: self.public_layout,
+            variable_ordering: self.variable_ordering,
+            statements: self
+                .statements
+                .into_iter()
+                .flat_map(move |s| binder.fold_statement(s)),
+        }
+    }
+}
+
+impl<'ast, T: Field> Folder<'ast, T> for Binder<T> {
+    fn fold_linear_combination(&mut self, e: LinComb<T>) -> LinComb<T> {
+        if !e.0.iter().any(|(v, _)| self.values.contains_key(v)) {
+            return e;
+        }
+
+        LinComb(
+            e.0.into_iter()
+                .map(|(v, c)| match self.values.get(&v) {
+                    Some(value) => (Variable::one(), c * value),
+                    None => (v, c),
+                })
+                .collect(),
+        )
+        .reduce()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{Parameter, Prog, QuadComb};
+    use zokrates_field::Bn128Field;
+
+    #[test]
+    fn bind_argument() {
+        // def main(private field a, private field b) -> field { return a * b + a; }
+        let program: Prog<Bn128Field> = Prog {
+            arguments: vec![
+                Parameter::private(Variable::new(0)),
+                Parameter::private(Variable::new(1)),
+            ],
+            return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
+            statements: vec![
+                Statement::definition(
+                    Variable::new(2),
+                    QuadComb::from_linear_combinations(
+                        LinComb::from(Variable::new(0)),
+                        LinComb::from(Variable::new(1)),
+                    ),
+                ),
+                Statement::definition(
+                    Variable::public(0),
+                    LinComb::from(Variable::new(2)) + LinComb::from(Variable::new(0)),
+                ),
+            ],
+        };
+
+        let bound: Prog<Bn128Field> = program
+            .bind(
+                vec![(Variable::new(0), Bn128Field::from(3))]
+                    .into_iter()
+                    .collect(),
+            )
+            .collect();
+
+        let expected: Prog<Bn128Field> = Prog {
+            arguments: vec![Parameter::private(Variable::new(1))],
+            return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
+            statements: vec![
+                Statement::definition(
+                    Variable::new(2),
+                    QuadComb::from_linear_combinations(
+                        LinComb::summand(3, Variable::one()),
+                        LinComb::from(Variable::new(1)),
+                    ),
+                ),
+                Statement::definition(
+                    Variable::public(0),
+                    LinComb::summand(3, Variable::one()) + LinComb::from(Variable::new(2)),
+                ),
+            ],
+        };
+
+        assert_eq!(bound, expected);
+    }
+}
@@ -1,12 +1,26 @@
-use crate::ir::folder::Folder;
+use crate::ir::result_visitor::ResultVisitor;
 use crate::ir::Directive;
 use crate::ir::Parameter;
 use crate::ir::ProgIterator;
+use crate::ir::Solver;
 use crate::ir::Statement;
 use crate::ir::Variable;
 use std::collections::HashSet;
+use std::fmt;
 use zokrates_field::Field;
 
+/// Some private inputs or directive outputs are never used in a constraint
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnconstrainedVariables(pub usize);
+
+impl fmt::Display for UnconstrainedVariables {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Found {} unconstrained variable(s)", self.0)
+    }
+}
+
+impl std::error::Error for UnconstrainedVariables {}
+
 #[derive(Debug)]
 pub struct UnconstrainedVariableDetector {
     pub(self) variables: HashSet<Variable>,
@@ -26,24 +40,64 @@ impl UnconstrainedVariableDetector {
         }
     }
 
-    pub fn finalize(self) -> Result<(), usize> {
+    pub fn finalize(self) -> Result<(), UnconstrainedVariables> {
         if self.variables.is_empty() {
             return Ok(());
         }
-        Err(self.variables.len())
+        Err(UnconstrainedVariables(self.variables.len()))
     }
 }
 
-impl<'ast, T: Field> Folder<'ast, T> for UnconstrainedVariableDetector {
-    fn fold_argument(&mut self, p: Parameter) -> Parameter {
-        p
+impl<T: Field> ResultVisitor<T> for UnconstrainedVariableDetector {
+    // whether a variable is constrained is only known once all statements were visited, see `finalize`
+    type Error = UnconstrainedVariables;
+
+    fn visit_argument(&mut self, _: &Parameter) -> Result<(), Self::Error> {
+        Ok(())
     }
-    fn fold_variable(&mut self, v: Variable) -> Variable {
-        self.variables.remove(&v);
-        v
+
+    fn visit_variable(&mut self, v: &Variable) -> Result<(), Self::Error> {
+        self.variables.remove(v);
+        Ok(())
     }
-    fn fold_directive(&mut self, d: Directive<'ast, T>) -> Directive<'ast, T> {
-        self.variables.extend(d.outputs.iter());
-        d
+
+    fn visit_directive(&mut self, d: &Directive<T>) -> Result<(), Self::Error> {
+        // prover-chosen values are unconstrained on purpose
+        if !matches!(d.solver, Solver::Sample(..)) {
+            self.variables.extend(d.outputs.iter());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::Prog;
+    use zokrates_field::Bn128Field;
+
+    fn detect(solver: Solver<'static, Bn128Field>) -> Result<(), UnconstrainedVariables> {
+        // a directive whose output is never used in a constraint
+        let program: Prog<Bn128Field> = Prog::new(
+            vec![],
+            vec![Statement::Directive(Directive {
+                inputs: vec![],
+                outputs: vec![Variable::new(0)],
+                solver,
+            })],
+            0,
+        );
+
+        let mut detector = UnconstrainedVariableDetector::new(&program);
+        for s in &program.statements {
+            detector.visit_statement(s)?;
+        }
+        detector.finalize()
+    }
+
+    #[test]
+    fn sample_is_allowed() {
+        assert_eq!(detect(Solver::Sample(0, 0)), Ok(()));
+        assert_eq!(detect(Solver::Bits(1)), Err(UnconstrainedVariables(1)));
     }
 }
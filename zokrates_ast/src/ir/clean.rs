@@ -10,6 +10,8 @@ impl<'ast, T: Field, I: IntoIterator<Item = Statement<'ast, T>>> ProgIterator<'a
         ProgIterator {
             arguments: self.arguments,
             return_count: self.return_count,
+            public_layout: self.public_layout,
+            variable_ordering: self.variable_ordering,
             statements: self
                 .statements
                 .into_iter()
@@ -0,0 +1,458 @@
+//! A container of several independent programs compiled for the same curve, indexed by name.
+//!
+//! The field constants and the runtime errors of all programs are stored once, in pools shared by the programs, and
+//! the statements refer to them by index. Programs compiled from the same sources use the same constants (`1`, `-1`,
+//! powers of two...) and error messages, so the container is smaller than the separate programs.
+
+use super::check::UnconstrainedVariableDetector;
+use super::result_visitor::ResultVisitor;
+use super::{LinComb, ProgIterator, QuadComb, Statement};
+use crate::common::{FormatString, Parameter, RuntimeError, Solver, Variable};
+use crate::typed::ConcreteType;
+use serde::{Deserialize, Serialize};
+use serde_cbor::de::IoRead;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io::{Cursor, Read, Write};
+use zokrates_field::*;
+
+type DynamicError = Box<dyn std::error::Error>;
+
+pub const ZOKRATES_CONTAINER_MAGIC: &[u8; 4] = &[0x5a, 0x4f, 0x4b, 0x43];
+const ZOKRATES_CONTAINER_VERSION_1: &[u8; 4] = &[0, 0, 0, 1];
+
+#[derive(Serialize, Deserialize)]
+struct Pools<T> {
+    constants: Vec<T>,
+    errors: Vec<RuntimeError>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EntryHeader {
+    name: String,
+    arguments: Vec<Parameter>,
+    return_count: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    public_layout: Vec<usize>,
+}
+
+/// A statement whose constants and runtime error are indices in the pools of the container
+#[derive(Serialize, Deserialize)]
+enum PooledStatement<'ast, T> {
+    Constraint(QuadComb<u32>, LinComb<u32>, Option<u32>),
+    Directive(
+        Vec<QuadComb<u32>>,
+        Vec<Variable>,
+        #[serde(borrow)] Solver<'ast, T>,
+    ),
+    Log(FormatString, Vec<(ConcreteType, Vec<LinComb<u32>>)>),
+}
+
+/// Interns values, returning the index of each value in the pool
+struct Pool<V> {
+    values: Vec<V>,
+    indices: HashMap<V, u32>,
+}
+
+impl<V: Clone + Hash + Eq> Pool<V> {
+    fn new() -> Self {
+        Pool {
+            values: vec![],
+            indices: HashMap::new(),
+        }
+    }
+
+    fn intern(&mut self, value: V) -> u32 {
+        let values = &mut self.values;
+        *self.indices.entry(value).or_insert_with_key(|value| {
+            values.push(value.clone());
+            values.len() as u32 - 1
+        })
+    }
+}
+
+struct Encoder<T> {
+    constants: Pool<T>,
+    errors: Pool<RuntimeError>,
+}
+
+impl<T: Field> Encoder<T> {
+    fn linear_combination(&mut self, l: LinComb<T>) -> LinComb<u32> {
+        LinComb(
+            l.0.into_iter()
+                .map(|(v, c)| (v, self.constants.intern(c)))
+                .collect(),
+        )
+    }
+
+    fn quadratic_combination(&mut self, q: QuadComb<T>) -> QuadComb<u32> {
+        QuadComb {
+            left: self.linear_combination(q.left),
+            right: self.linear_combination(q.right),
+        }
+    }
+
+    fn statement<'ast>(
+        &mut self,
+        s: Statement<'ast, T>,
+        pooled: &mut Vec<PooledStatement<'ast, T>>,
+    ) {
+        match s {
+            // blocks are not serialized, their statements are inlined
+            Statement::Block(statements) => {
+                for s in statements {
+                    self.statement(s, pooled);
+                }
+            }
+            Statement::Constraint(quad, lin, error) => pooled.push(PooledStatement::Constraint(
+                self.quadratic_combination(quad),
+                self.linear_combination(lin),
+                error.map(|e| self.errors.intern(e)),
+            )),
+            Statement::Directive(d) => pooled.push(PooledStatement::Directive(
+                d.inputs
+                    .into_iter()
+                    .map(|q| self.quadratic_combination(q))
+                    .collect(),
+                d.outputs,
+                d.solver,
+            )),
+            Statement::Log(format, expressions) => pooled.push(PooledStatement::Log(
+                format,
+                expressions
+                    .into_iter()
+                    .map(|(t, e)| {
+                        (
+                            t,
+                            e.into_iter().map(|l| self.linear_combination(l)).collect(),
+                        )
+                    })
+                    .collect(),
+            )),
+        }
+    }
+}
+
+impl<T: Field> Pools<T> {
+    fn constant(&self, index: u32) -> Result<T, String> {
+        self.constants
+            .get(index as usize)
+            .cloned()
+            .ok_or_else(|| format!("Constant {} not found in the container", index))
+    }
+
+    fn linear_combination(&self, l: LinComb<u32>) -> Result<LinComb<T>, String> {
+        l.0.into_iter()
+            .map(|(v, c)| self.constant(c).map(|c| (v, c)))
+            .collect::<Result<_, _>>()
+            .map(LinComb)
+    }
+
+    fn quadratic_combination(&self, q: QuadComb<u32>) -> Result<QuadComb<T>, String> {
+        Ok(QuadComb {
+            left: self.linear_combination(q.left)?,
+            right: self.linear_combination(q.right)?,
+        })
+    }
+
+    fn statement<'ast>(&self, s: PooledStatement<'ast, T>) -> Result<Statement<'ast, T>, String> {
+        Ok(match s {
+            PooledStatement::Constraint(quad, lin, error) => Statement::Constraint(
+                self.quadratic_combination(quad)?,
+                self.linear_combination(lin)?,
+                error
+                    .map(|index| {
+                        self.errors.get(index as usize).cloned().ok_or_else(|| {
+                            format!("Runtime error {} not found in the container", index)
+                        })
+                    })
+                    .transpose()?,
+            ),
+            PooledStatement::Directive(inputs, outputs, solver) => {
+                Statement::Directive(super::Directive {
+                    inputs: inputs
+                        .into_iter()
+                        .map(|q| self.quadratic_combination(q))
+                        .collect::<Result<_, _>>()?,
+                    outputs,
+                    solver,
+                })
+            }
+            PooledStatement::Log(format, expressions) => Statement::Log(
+                format,
+                expressions
+                    .into_iter()
+                    .map(|(t, e)| {
+                        e.into_iter()
+                            .map(|l| self.linear_combination(l))
+                            .collect::<Result<Vec<_>, _>>()
+                            .map(|e| (t, e))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+        })
+    }
+}
+
+/// Serialize named programs into a container, returning the number of constraints of each program
+pub fn serialize_container<'ast, T: Field, I: IntoIterator<Item = Statement<'ast, T>>, W: Write>(
+    programs: Vec<(String, ProgIterator<'ast, T, I>)>,
+    mut w: W,
+) -> Result<Vec<usize>, DynamicError> {
+    let mut encoder = Encoder {
+        constants: Pool::new(),
+        errors: Pool::new(),
+    };
+
+    let mut headers: Vec<EntryHeader> = vec![];
+    let mut bodies = vec![];
+    let mut counts = vec![];
+
+    for (name, program) in programs {
+        if headers.iter().any(|h| h.name == name) {
+            return Err(format!("Duplicate entry `{}` in the container", name).into());
+        }
+
+        let mut unconstrained_variable_detector = UnconstrainedVariableDetector::new(&program);
+
+        let mut count = 0;
+        let mut pooled = vec![];
+        for s in program.statements {
+            if matches!(s, Statement::Constraint(..)) {
+                count += 1;
+            }
+            unconstrained_variable_detector.visit_statement(&s)?;
+            encoder.statement(s, &mut pooled);
+        }
+
+        unconstrained_variable_detector
+            .finalize()
+            .map_err(|e| format!("Error in entry `{}`: {}", name, e))?;
+
+        headers.push(EntryHeader {
+            name,
+            arguments: program.arguments,
+            return_count: program.return_count,
+            public_layout: program.public_layout,
+        });
+        bodies.push(pooled);
+        counts.push(count);
+    }
+
+    w.write_all(ZOKRATES_CONTAINER_MAGIC)?;
+    w.write_all(ZOKRATES_CONTAINER_VERSION_1)?;
+    w.write_all(&T::id())?;
+
+    serde_cbor::to_writer(
+        &mut w,
+        &Pools {
+            constants: encoder.constants.values,
+            errors: encoder.errors.values,
+        },
+    )?;
+    serde_cbor::to_writer(&mut w, &headers)?;
+    for body in bodies {
+        serde_cbor::to_writer(&mut w, &body)?;
+    }
+
+    Ok(counts)
+}
+
+/// Extract the program called `entry` from a container, returning it in the format of a single serialized program
+pub fn extract_entry<R: Read>(mut r: R, entry: &str) -> Result<Vec<u8>, String> {
+    let mut magic = [0; 4];
+    r.read_exact(&mut magic)
+        .map_err(|_| String::from("Cannot read magic number"))?;
+
+    if &magic != ZOKRATES_CONTAINER_MAGIC {
+        return Err(String::from(
+            "The file is not a container of several programs",
+        ));
+    }
+
+    let mut version = [0; 4];
+    r.read_exact(&mut version)
+        .map_err(|_| String::from("Cannot read version"))?;
+
+    if &version != ZOKRATES_CONTAINER_VERSION_1 {
+        return Err(String::from("Unknown version"));
+    }
+
+    let mut curve = [0; 4];
+    r.read_exact(&mut curve)
+        .map_err(|_| String::from("Cannot read curve identifier"))?;
+
+    let de = serde_cbor::Deserializer::from_reader(r);
+
+    match curve {
+        m if m == Bls12_381Field::id() => extract::<Bls12_381Field, _>(de, entry),
+        m if m == Bn128Field::id() => extract::<Bn128Field, _>(de, entry),
+        m if m == Bls12_377Field::id() => extract::<Bls12_377Field, _>(de, entry),
+        m if m == Bw6_761Field::id() => extract::<Bw6_761Field, _>(de, entry),
+        m if m == PallasField::id() => extract::<PallasField, _>(de, entry),
+        m if m == VestaField::id() => extract::<VestaField, _>(de, entry),
+        _ => Err(String::from("Unknown curve identifier")),
+    }
+}
+
+fn extract<T: Field, R: Read>(
+    mut de: serde_cbor::Deserializer<IoRead<R>>,
+    entry: &str,
+) -> Result<Vec<u8>, String> {
+    let corrupted = |e: serde_cbor::Error| format!("Could not read the container: {}", e);
+
+    let pools = Pools::<T>::deserialize(&mut de).map_err(corrupted)?;
+    let headers = Vec::<EntryHeader>::deserialize(&mut de).map_err(corrupted)?;
+
+    let index = headers
+        .iter()
+        .position(|h| h.name == entry)
+        .ok_or_else(|| {
+            format!(
+                "Entry `{}` not found, the available entries are: {}",
+                entry,
+                headers
+                    .iter()
+                    .map(|h| h.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })?;
+
+    // skip the bodies of the previous entries
+    for _ in 0..index {
+        serde::de::IgnoredAny::deserialize(&mut de).map_err(corrupted)?;
+    }
+
+    let statements = Vec::<PooledStatement<T>>::deserialize(&mut de)
+        .map_err(corrupted)?
+        .into_iter()
+        .map(|s| pools.statement(s))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let header = &headers[index];
+
+    let mut buffer = Cursor::new(vec![]);
+    ProgIterator::new(header.arguments.clone(), statements, header.return_count)
+        .with_public_layout(header.public_layout.clone())
+        .serialize(&mut buffer)
+        .map_err(|e| e.to_string())?;
+
+    Ok(buffer.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{Prog, ProgEnum};
+    use zokrates_field::Bn128Field;
+
+    fn program(factor: usize) -> Prog<'static, Bn128Field> {
+        // `~out_0 = factor * _0`, using the same constants as the other programs
+        Prog {
+            arguments: vec![Parameter::private(Variable::new(0))],
+            return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
+            statements: vec![
+                Statement::constraint(
+                    LinComb::summand(factor, Variable::new(0)),
+                    Variable::public(0),
+                ),
+                Statement::constraint(
+                    QuadComb::from_linear_combinations(
+                        LinComb::from(Variable::new(0)),
+                        LinComb::from(Variable::new(0)) - LinComb::one(),
+                    ),
+                    LinComb::zero(),
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut buffer = vec![];
+        let counts = serialize_container(
+            vec![
+                ("double".to_string(), program(2)),
+                ("triple".to_string(), program(3)),
+            ],
+            &mut buffer,
+        )
+        .unwrap();
+
+        assert_eq!(counts, vec![2, 2]);
+
+        for (name, factor) in [("double", 2), ("triple", 3)] {
+            let extracted = extract_entry(&buffer[..], name).unwrap();
+            assert_eq!(
+                ProgEnum::deserialize(&extracted[..]).unwrap().collect(),
+                ProgEnum::Bn128Program(program(factor))
+            );
+        }
+    }
+
+    #[test]
+    fn smaller_than_separate_programs() {
+        let mut container = vec![];
+        serialize_container(
+            vec![
+                ("double".to_string(), program(2)),
+                ("triple".to_string(), program(3)),
+            ],
+            &mut container,
+        )
+        .unwrap();
+
+        let separate: usize = [program(2), program(3)]
+            .into_iter()
+            .map(|p| {
+                let mut buffer = Cursor::new(vec![]);
+                p.serialize(&mut buffer).unwrap();
+                buffer.into_inner().len()
+            })
+            .sum();
+
+        assert!(container.len() < separate);
+    }
+
+    #[test]
+    fn missing_entry() {
+        let mut buffer = vec![];
+        serialize_container(vec![("double".to_string(), program(2))], &mut buffer).unwrap();
+
+        assert_eq!(
+            extract_entry(&buffer[..], "triple").unwrap_err(),
+            "Entry `triple` not found, the available entries are: double"
+        );
+    }
+
+    #[test]
+    fn duplicate_entry() {
+        let mut buffer = vec![];
+        assert_eq!(
+            serialize_container(
+                vec![
+                    ("double".to_string(), program(2)),
+                    ("double".to_string(), program(2)),
+                ],
+                &mut buffer,
+            )
+            .unwrap_err()
+            .to_string(),
+            "Duplicate entry `double` in the container"
+        );
+    }
+
+    #[test]
+    fn container_is_not_a_program() {
+        let mut buffer = vec![];
+        serialize_container(vec![("double".to_string(), program(2))], &mut buffer).unwrap();
+
+        assert_eq!(
+            ProgEnum::deserialize(&buffer[..]).unwrap_err(),
+            "The file contains several programs, one of them must be selected"
+        );
+    }
+}
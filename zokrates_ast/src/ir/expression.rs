@@ -1,6 +1,7 @@
 use super::Witness;
 use crate::common::Variable;
 use serde::{Deserialize, Serialize};
+use smallvec::{smallvec, SmallVec};
 use std::collections::btree_map::{BTreeMap, Entry};
 use std::fmt;
 use std::hash::Hash;
@@ -18,6 +19,11 @@ impl<T: Field> QuadComb<T> {
         QuadComb { left, right }
     }
 
+    /// Whether the combination is written `(~one) * (lincomb)`, the form of the linear constraints
+    pub fn is_linear(&self) -> bool {
+        self.left == LinComb::one()
+    }
+
     pub fn try_linear(self) -> Result<LinComb<T>, Self> {
         // identify `(k * ~ONE) * (lincomb)` and `(lincomb) * (k * ~ONE)` and return (k * lincomb)
         // if not, error out with the input
@@ -54,8 +60,14 @@ impl<T: Field> fmt::Display for QuadComb<T> {
     }
 }
 
+/// The number of terms a `LinComb` holds without allocating. Most linear combinations have one to three terms.
+pub const INLINE_TERMS: usize = 4;
+
+/// The terms of a `LinComb`. They are serialized as a sequence, like a `Vec`.
+pub type Terms<T> = SmallVec<[(Variable, T); INLINE_TERMS]>;
+
 #[derive(Clone, Debug, Serialize, Deserialize, Hash, PartialEq, Eq)]
-pub struct LinComb<T>(pub Vec<(Variable, T)>);
+pub struct LinComb<T>(pub Terms<T>);
 
 #[derive(PartialEq, PartialOrd, Clone, Eq, Ord, Hash, Debug, Serialize, Deserialize)]
 pub struct CanonicalLinComb<T>(pub BTreeMap<Variable, T>);
@@ -75,6 +87,12 @@ impl<T> From<CanonicalQuadComb<T>> for QuadComb<T> {
     }
 }
 
+impl<T> From<Vec<(Variable, T)>> for LinComb<T> {
+    fn from(terms: Vec<(Variable, T)>) -> Self {
+        LinComb(terms.into())
+    }
+}
+
 impl<T> From<CanonicalLinComb<T>> for LinComb<T> {
     fn from(l: CanonicalLinComb<T>) -> Self {
         LinComb(l.0.into_iter().collect())
@@ -83,13 +101,13 @@ impl<T> From<CanonicalLinComb<T>> for LinComb<T> {
 
 impl<T> LinComb<T> {
     pub fn summand<U: Into<T>>(mult: U, var: Variable) -> LinComb<T> {
-        let res = vec![(var, mult.into())];
+        let res = smallvec![(var, mult.into())];
 
         LinComb(res)
     }
 
     pub fn zero() -> LinComb<T> {
-        LinComb(Vec::new())
+        LinComb(SmallVec::new())
     }
 
     pub fn is_zero(&self) -> bool {
@@ -221,7 +239,7 @@ impl<T: Field> fmt::Display for LinComb<T> {
 
 impl<T: Field> From<Variable> for LinComb<T> {
     fn from(v: Variable) -> LinComb<T> {
-        let r = vec![(v, T::one())];
+        let r = smallvec![(v, T::one())];
         LinComb(r)
     }
 }
@@ -299,7 +317,7 @@ mod tests {
                 (Variable::new(42), Bn128Field::from(1)),
             ];
 
-            assert_eq!(c, LinComb(expected_vec));
+            assert_eq!(c, LinComb::from(expected_vec));
         }
         #[test]
         fn sub() {
@@ -312,7 +330,39 @@ mod tests {
                 (Variable::new(42), Bn128Field::from(-1)),
             ];
 
-            assert_eq!(c, LinComb(expected_vec));
+            assert_eq!(c, LinComb::from(expected_vec));
+        }
+
+        #[test]
+        fn inline_terms() {
+            let a: LinComb<Bn128Field> = LinComb::from(Variable::new(42))
+                + LinComb::summand(3, Variable::new(21))
+                - LinComb::one();
+            assert!(!a.0.spilled());
+
+            let b = a.clone() + a;
+            assert!(b.0.spilled());
+            assert_eq!(b.0.len(), 6);
+        }
+
+        #[test]
+        fn serialize_as_sequence() {
+            let a: LinComb<Bn128Field> =
+                LinComb::from(Variable::new(42)) + LinComb::summand(3, Variable::new(21));
+            let terms: Vec<_> = a.0.to_vec();
+
+            assert_eq!(
+                serde_json::to_string(&a).unwrap(),
+                serde_json::to_string(&terms).unwrap()
+            );
+            assert_eq!(
+                serde_cbor::to_vec(&a).unwrap(),
+                serde_cbor::to_vec(&terms).unwrap()
+            );
+            assert_eq!(
+                serde_cbor::from_slice::<LinComb<_>>(&serde_cbor::to_vec(&terms).unwrap()).unwrap(),
+                a
+            );
         }
 
         #[test]
@@ -369,7 +419,7 @@ mod tests {
 
         #[test]
         fn try_summand() {
-            let summand = LinComb(vec![
+            let summand = LinComb(smallvec![
                 (Variable::new(42), Bn128Field::from(1)),
                 (Variable::new(42), Bn128Field::from(2)),
                 (Variable::new(42), Bn128Field::from(3)),
@@ -379,14 +429,14 @@ mod tests {
                 Ok((Variable::new(42), Bn128Field::from(6)))
             );
 
-            let not_summand = LinComb(vec![
+            let not_summand = LinComb(smallvec![
                 (Variable::new(41), Bn128Field::from(1)),
                 (Variable::new(42), Bn128Field::from(2)),
                 (Variable::new(42), Bn128Field::from(3)),
             ]);
             assert!(not_summand.try_summand().is_err());
 
-            let empty: LinComb<Bn128Field> = LinComb(vec![]);
+            let empty: LinComb<Bn128Field> = LinComb(smallvec![]);
             assert!(empty.try_summand().is_err());
         }
     }
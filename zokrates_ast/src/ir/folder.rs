@@ -50,6 +50,8 @@ pub fn fold_program<'ast, T: Field, F: Folder<'ast, T>>(
             .flat_map(|s| f.fold_statement(s))
             .collect(),
         return_count: p.return_count,
+        public_layout: p.public_layout,
+        variable_ordering: p.variable_ordering,
     }
 }
 
@@ -24,31 +24,55 @@ pub fn from_flat<'ast, T: Field, I: IntoIterator<Item = FlatStatement<'ast, T>>>
         statements: flat_prog_iterator.statements.into_iter().map(Into::into),
         arguments: flat_prog_iterator.arguments,
         return_count: flat_prog_iterator.return_count,
+        public_layout: vec![],
+        variable_ordering: None,
     }
 }
 
 impl<T: Field> From<FlatExpression<T>> for LinComb<T> {
     fn from(flat_expression: FlatExpression<T>) -> LinComb<T> {
-        match flat_expression {
-            FlatExpression::Number(ref n) if *n == T::from(0) => LinComb::zero(),
-            FlatExpression::Number(n) => LinComb::summand(n, Variable::one()),
-            FlatExpression::Identifier(id) => LinComb::from(id),
-            FlatExpression::Add(box e1, box e2) => LinComb::from(e1) + LinComb::from(e2),
-            FlatExpression::Sub(box e1, box e2) => LinComb::from(e1) - LinComb::from(e2),
-            FlatExpression::Mult(
-                box FlatExpression::Number(n1),
-                box FlatExpression::Identifier(v1),
-            )
-            | FlatExpression::Mult(
-                box FlatExpression::Identifier(v1),
-                box FlatExpression::Number(n1),
-            ) => LinComb::summand(n1, v1),
-            FlatExpression::Mult(
-                box FlatExpression::Number(n1),
-                box FlatExpression::Number(n2),
-            ) => LinComb::summand(n1 * n2, Variable::one()),
-            e => unreachable!("{}", e),
+        // the operands of sums and differences are walked with a stack rather than recursively, as they can be nested
+        // deeply. A summand is negated if it is on the right of an odd number of differences
+        let mut res = LinComb::zero();
+        let mut stack = vec![(flat_expression, false)];
+
+        while let Some((e, negated)) = stack.pop() {
+            let (variable, coefficient) = match e {
+                FlatExpression::Number(ref n) if *n == T::from(0) => continue,
+                FlatExpression::Number(n) => (Variable::one(), n),
+                FlatExpression::Identifier(id) => (id, T::one()),
+                FlatExpression::Add(box e1, box e2) => {
+                    stack.push((e2, negated));
+                    stack.push((e1, negated));
+                    continue;
+                }
+                FlatExpression::Sub(box e1, box e2) => {
+                    stack.push((e2, !negated));
+                    stack.push((e1, negated));
+                    continue;
+                }
+                FlatExpression::Mult(
+                    box FlatExpression::Number(n1),
+                    box FlatExpression::Identifier(v1),
+                )
+                | FlatExpression::Mult(
+                    box FlatExpression::Identifier(v1),
+                    box FlatExpression::Number(n1),
+                ) => (v1, n1),
+                FlatExpression::Mult(
+                    box FlatExpression::Number(n1),
+                    box FlatExpression::Number(n2),
+                ) => (Variable::one(), n1 * n2),
+                e => unreachable!("{}", e),
+            };
+
+            res.0.push(match negated {
+                true => (variable, T::zero() - coefficient),
+                false => (variable, coefficient),
+            });
         }
+
+        res
     }
 }
 
@@ -176,4 +200,22 @@ mod tests {
             LinComb::summand(42, Variable::new(42)) + LinComb::summand(21, Variable::new(21));
         assert_eq!(LinComb::from(add), expected);
     }
+
+    #[test]
+    fn nested_differences() {
+        // x - (2 - (y - 0))
+        let sub = FlatExpression::Sub(
+            box FlatExpression::Identifier(Variable::new(42)),
+            box FlatExpression::Sub(
+                box FlatExpression::Number(Bn128Field::from(2)),
+                box FlatExpression::Sub(
+                    box FlatExpression::Identifier(Variable::new(21)),
+                    box FlatExpression::Number(Bn128Field::from(0)),
+                ),
+            ),
+        );
+        let expected: LinComb<Bn128Field> = LinComb::summand(1, Variable::new(42))
+            - (LinComb::summand(2, Variable::one()) - LinComb::summand(1, Variable::new(21)));
+        assert_eq!(LinComb::from(sub), expected);
+    }
 }
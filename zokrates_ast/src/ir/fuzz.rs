@@ -0,0 +1,316 @@
+//! Look for under-constrained variables by perturbing a valid witness.
+//!
+//! Each non-input variable of the witness is changed, one at a time, and the constraints it
+//! appears in are checked again. If they still hold, a malicious prover could pick that value
+//! too, so the variable is reported as likely unconstrained.
+
+use super::visitor::{self, Visitor};
+use super::{Directive, LinComb, Prog, QuadComb, Statement, Variable, Witness};
+use rand_0_8::rngs::StdRng;
+use rand_0_8::{Rng, SeedableRng};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use zokrates_field::Field;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FuzzReport {
+    /// The variables for which a perturbed value satisfied all constraints
+    pub unconstrained: Vec<Variable>,
+    /// The variables which appear in no constraint, and were therefore not perturbed
+    pub free: Vec<Variable>,
+    /// The number of perturbed witnesses which were checked
+    pub checked: usize,
+    /// The seed of the random perturbations, to reproduce the run
+    pub seed: u64,
+}
+
+/// The witness given as a reference does not satisfy the constraint at this index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidWitness(pub usize);
+
+impl fmt::Display for InvalidWitness {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The witness does not satisfy constraint #{} and cannot be perturbed",
+            self.0
+        )
+    }
+}
+
+/// Perturb each non-input variable of `witness` up to `n` times, and report those whose
+/// perturbation was not rejected by the constraints of `prog`
+///
+/// Directive outputs are tried first, as they are the values the unconstrained variable check
+/// is concerned with. The first perturbations are `1 - v` and `-v`, which expose unchecked
+/// booleans and square roots, the following ones are drawn from a generator seeded with `seed`.
+pub fn check_rejects_perturbations<T: Field>(
+    prog: &Prog<T>,
+    witness: &Witness<T>,
+    n: usize,
+    seed: u64,
+) -> Result<FuzzReport, InvalidWitness> {
+    let rng = &mut StdRng::seed_from_u64(seed);
+
+    let mut collector = Collector::default();
+    collector.visit_module(prog);
+
+    let mut values = witness.clone();
+    values.0.entry(Variable::one()).or_insert_with(T::one);
+
+    if let Some(index) = (0..collector.constraints.len())
+        .find(|index| !is_satisfied(&collector.constraints[*index], &values))
+    {
+        return Err(InvalidWitness(index));
+    }
+
+    let inputs: BTreeSet<_> = prog.arguments.iter().map(|p| p.id).collect();
+
+    let mut occurrences: BTreeMap<Variable, Vec<usize>> = BTreeMap::new();
+    for (index, constraint) in collector.constraints.iter().enumerate() {
+        let mut variables = VariableCollector::default();
+        variables.visit_quadratic_combination(&constraint.0);
+        variables.visit_linear_combination(&constraint.1);
+        for v in variables.0 {
+            occurrences.entry(v).or_default().push(index);
+        }
+    }
+
+    let candidates = collector.directive_outputs.iter().chain(
+        values
+            .0
+            .keys()
+            .filter(|v| !collector.directive_outputs.contains(v)),
+    );
+
+    let mut report = FuzzReport {
+        seed,
+        ..FuzzReport::default()
+    };
+
+    for v in candidates {
+        if *v == Variable::one() || inputs.contains(v) || !values.0.contains_key(v) {
+            continue;
+        }
+
+        let constraints = match occurrences.get(v) {
+            Some(constraints) => constraints,
+            None => {
+                report.free.push(*v);
+                continue;
+            }
+        };
+
+        let original = values.0[v].clone();
+
+        let perturbations = [T::one() - &original, T::zero() - &original]
+            .into_iter()
+            .chain(std::iter::repeat_with(|| random_field(rng)))
+            .filter(|p| *p != original)
+            .take(n);
+
+        let mut perturbed = values.clone();
+        for p in perturbations {
+            perturbed.0.insert(*v, p);
+            report.checked += 1;
+
+            if constraints
+                .iter()
+                .all(|index| is_satisfied(&collector.constraints[*index], &perturbed))
+            {
+                report.unconstrained.push(*v);
+                break;
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+fn random_field<T: Field, R: Rng>(rng: &mut R) -> T {
+    // accumulate more bits than the modulus has, so that the reduction is close to uniform
+    (0..T::get_required_bits() / 64 + 2).fold(T::zero(), |acc, _| {
+        acc * T::from(1u128 << 64) + T::from(rng.gen::<u64>())
+    })
+}
+
+fn evaluate<T: Field>(l: &LinComb<T>, witness: &Witness<T>) -> Option<T> {
+    l.0.iter().try_fold(T::zero(), |acc, (v, coefficient)| {
+        witness
+            .0
+            .get(v)
+            .map(|value| acc + value.clone() * coefficient)
+    })
+}
+
+fn is_satisfied<T: Field>((quad, lin): &(QuadComb<T>, LinComb<T>), witness: &Witness<T>) -> bool {
+    match (
+        evaluate(&quad.left, witness),
+        evaluate(&quad.right, witness),
+        evaluate(lin, witness),
+    ) {
+        (Some(left), Some(right), Some(lin)) => left * right == lin,
+        _ => false,
+    }
+}
+
+/// Collect the constraints of a program, flattening blocks, and the outputs of its directives
+#[derive(Default)]
+struct Collector<T> {
+    constraints: Vec<(QuadComb<T>, LinComb<T>)>,
+    directive_outputs: Vec<Variable>,
+}
+
+impl<T: Field> Visitor<T> for Collector<T> {
+    fn visit_statement(&mut self, s: &Statement<T>) {
+        match s {
+            Statement::Constraint(quad, lin, _) => {
+                self.constraints.push((quad.clone(), lin.clone()))
+            }
+            _ => visitor::visit_statement(self, s),
+        }
+    }
+
+    fn visit_directive(&mut self, d: &Directive<T>) {
+        self.directive_outputs.extend(d.outputs.iter().copied());
+    }
+}
+
+#[derive(Default)]
+struct VariableCollector(BTreeSet<Variable>);
+
+impl<T: Field> Visitor<T> for VariableCollector {
+    fn visit_variable(&mut self, v: &Variable) {
+        if *v != Variable::one() {
+            self.0.insert(*v);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{Parameter, Solver};
+    use zokrates_field::Bn128Field;
+
+    fn witness(values: Vec<(Variable, u32)>) -> Witness<Bn128Field> {
+        Witness(
+            values
+                .into_iter()
+                .map(|(v, value)| (v, Bn128Field::from(value)))
+                .collect(),
+        )
+    }
+
+    // def main(private x) -> ~out_0
+    //     # _1 = Bits(x)
+    //     _1 * _1 == _1
+    //     ~out_0 == _1
+    //     # _2 = Bits(x)
+    fn program(check_bit: bool) -> Prog<'static, Bn128Field> {
+        let x = Variable::new(0);
+        let bit = Variable::new(1);
+
+        let mut statements = vec![Statement::Directive(Directive {
+            inputs: vec![x.into()],
+            outputs: vec![bit],
+            solver: Solver::Bits(1),
+        })];
+
+        if check_bit {
+            statements.push(Statement::constraint(
+                QuadComb::from_linear_combinations(bit.into(), bit.into()),
+                bit,
+            ));
+        }
+
+        statements.push(Statement::definition(Variable::public(0), bit));
+        statements.push(Statement::Directive(Directive {
+            inputs: vec![x.into()],
+            outputs: vec![Variable::new(2)],
+            solver: Solver::Bits(1),
+        }));
+
+        Prog::new(vec![Parameter::private(x)], statements, 1)
+    }
+
+    fn honest_witness() -> Witness<Bn128Field> {
+        witness(vec![
+            (Variable::one(), 1),
+            (Variable::new(0), 1),
+            (Variable::new(1), 1),
+            (Variable::public(0), 1),
+            (Variable::new(2), 1),
+        ])
+    }
+
+    #[test]
+    fn skips_free_variables() {
+        let report =
+            check_rejects_perturbations(&program(false), &honest_witness(), 8, 42).unwrap();
+
+        // `_1` and `~out_0` are bound to each other by `~out_0 == _1`
+        assert!(report.unconstrained.is_empty());
+        assert_eq!(report.free, vec![Variable::new(2)]);
+    }
+
+    #[test]
+    fn flags_free_square_root() {
+        // def main(private y) -> ~out_0
+        //     # _1 = sqrt(y)
+        //     _1 * _1 == y
+        //     ~out_0 == _1
+        let y = Variable::new(0);
+        let root = Variable::new(1);
+
+        let prog = Prog::new(
+            vec![Parameter::private(y)],
+            vec![
+                Statement::Directive(Directive {
+                    inputs: vec![y.into()],
+                    outputs: vec![root],
+                    solver: Solver::Bits(1),
+                }),
+                Statement::constraint(
+                    QuadComb::from_linear_combinations(root.into(), root.into()),
+                    y,
+                ),
+            ],
+            0,
+        );
+
+        let report =
+            check_rejects_perturbations(&prog, &witness(vec![(y, 9), (root, 3)]), 8, 42).unwrap();
+
+        assert_eq!(report.unconstrained, vec![root]);
+    }
+
+    #[test]
+    fn rejects_checked_variables() {
+        let report = check_rejects_perturbations(&program(true), &honest_witness(), 8, 42).unwrap();
+
+        assert!(report.unconstrained.is_empty());
+        assert_eq!(report.checked, 16);
+    }
+
+    #[test]
+    fn invalid_witness() {
+        let mut witness = honest_witness();
+        witness.insert(Variable::public(0), Bn128Field::from(0));
+
+        assert_eq!(
+            check_rejects_perturbations(&program(true), &witness, 8, 42),
+            Err(InvalidWitness(1))
+        );
+    }
+
+    #[test]
+    fn same_seed_same_report() {
+        let run = |seed| check_rejects_perturbations(&program(true), &honest_witness(), 8, seed);
+
+        let report = run(7).unwrap();
+
+        assert_eq!(report.seed, 7);
+        assert_eq!(run(7), Ok(report));
+    }
+}
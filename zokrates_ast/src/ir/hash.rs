@@ -0,0 +1,233 @@
+use super::{LinComb, Prog, QuadComb, Statement};
+use crate::common::{Parameter, Variable};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use zokrates_field::Field;
+
+const DOMAIN: &[u8] = b"zokrates-constraint-system-v1";
+
+/// The SHA-256 hash of the constraint system of a program
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProgramHash(pub [u8; 32]);
+
+impl fmt::Display for ProgramHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ProgramHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ProgramHash({})", self)
+    }
+}
+
+impl FromStr for ProgramHash {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid program hash `{}`", s);
+
+        if s.len() != 64 || !s.is_ascii() {
+            return Err(invalid());
+        }
+
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).map_err(|_| invalid())?;
+        }
+
+        Ok(ProgramHash(bytes))
+    }
+}
+
+impl Serialize for ProgramHash {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for ProgramHash {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Hashes the constraint system of a program, one statement at a time.
+///
+/// Only the arguments, the number of outputs, the public layout, the constraints and the inputs and outputs of the
+/// directives are hashed: logs, runtime errors and solvers do not change the constraint system. Each statement is
+/// encoded independently of the serialization format of the program, and linear combinations are canonicalized first,
+/// so that the order of their terms does not matter.
+pub struct ConstraintSystemHasher<T> {
+    hasher: Sha256,
+    _t: PhantomData<T>,
+}
+
+impl<T: Field> ConstraintSystemHasher<T> {
+    pub fn new(arguments: &[Parameter], return_count: usize, public_layout: &[usize]) -> Self {
+        let mut hasher = ConstraintSystemHasher {
+            hasher: Sha256::new(),
+            _t: PhantomData,
+        };
+
+        hasher.hasher.update(DOMAIN);
+        hasher.hasher.update(T::id());
+        hasher.length(arguments.len());
+        for argument in arguments {
+            hasher.variable(&argument.id);
+            hasher.hasher.update([argument.private as u8]);
+        }
+        hasher.length(return_count);
+        // the default layout is not hashed, so that the hash of programs compiled without a layout does not change
+        if !public_layout.is_empty() {
+            hasher.length(public_layout.len());
+            for position in public_layout {
+                hasher.length(*position);
+            }
+        }
+
+        hasher
+    }
+
+    pub fn update(&mut self, statement: &Statement<T>) {
+        match statement {
+            Statement::Block(statements) => {
+                for s in statements {
+                    self.update(s);
+                }
+            }
+            Statement::Constraint(quad, lin, _) => {
+                self.hasher.update([0]);
+                self.quadratic_combination(quad);
+                self.linear_combination(lin);
+            }
+            Statement::Directive(d) => {
+                self.hasher.update([1]);
+                self.length(d.inputs.len());
+                for input in &d.inputs {
+                    self.quadratic_combination(input);
+                }
+                self.length(d.outputs.len());
+                for output in &d.outputs {
+                    self.variable(output);
+                }
+            }
+            Statement::Log(..) => {}
+        }
+    }
+
+    pub fn finalize(self) -> ProgramHash {
+        ProgramHash(self.hasher.finalize().into())
+    }
+
+    fn length(&mut self, length: usize) {
+        self.hasher.update((length as u64).to_le_bytes());
+    }
+
+    fn variable(&mut self, v: &Variable) {
+        self.hasher.update((v.id as i64).to_le_bytes());
+    }
+
+    fn linear_combination(&mut self, l: &LinComb<T>) {
+        let canonical = l.clone().into_canonical();
+        self.length(canonical.0.len());
+        for (variable, coefficient) in &canonical.0 {
+            self.variable(variable);
+            self.hasher.update(coefficient.to_byte_vector());
+        }
+    }
+
+    fn quadratic_combination(&mut self, q: &QuadComb<T>) {
+        self.linear_combination(&q.left);
+        self.linear_combination(&q.right);
+    }
+}
+
+impl<'ast, T: Field> Prog<'ast, T> {
+    /// The hash of the constraint system of this program
+    pub fn hash(&self) -> ProgramHash {
+        let mut hasher =
+            ConstraintSystemHasher::new(&self.arguments, self.return_count, &self.public_layout);
+        for s in &self.statements {
+            hasher.update(s);
+        }
+        hasher.finalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::RuntimeError;
+    use zokrates_field::Bn128Field;
+
+    fn program(statements: Vec<Statement<'static, Bn128Field>>) -> Prog<'static, Bn128Field> {
+        Prog {
+            arguments: vec![Parameter::private(Variable::new(0))],
+            return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
+            statements,
+        }
+    }
+
+    #[test]
+    fn canonical() {
+        // the order of the terms, logs and runtime errors do not change the hash
+        let left = program(vec![Statement::Constraint(
+            (LinComb::from(Variable::new(0)) + LinComb::summand(2, Variable::new(1))).into(),
+            Variable::public(0).into(),
+            None,
+        )]);
+        let right = program(vec![
+            Statement::Constraint(
+                (LinComb::summand(2, Variable::new(1)) + LinComb::from(Variable::new(0))).into(),
+                Variable::public(0).into(),
+                Some(RuntimeError::Sum),
+            ),
+            Statement::Log("debug".into(), vec![]),
+        ]);
+
+        assert_eq!(left.hash(), right.hash());
+    }
+
+    #[test]
+    fn constraints_change_the_hash() {
+        let left = program(vec![Statement::constraint(
+            Variable::new(0),
+            Variable::public(0),
+        )]);
+        let right = program(vec![Statement::constraint(
+            LinComb::summand(2, Variable::new(0)),
+            Variable::public(0),
+        )]);
+
+        assert_ne!(left.hash(), right.hash());
+    }
+
+    #[test]
+    fn public_layout_changes_the_hash() {
+        let statements = vec![Statement::constraint(Variable::new(0), Variable::public(0))];
+        let mut left = program(statements.clone());
+        left.arguments[0] = Parameter::public(Variable::new(0));
+        let right = left.clone().with_public_layout(vec![1, 0]);
+
+        assert_ne!(left.hash(), right.hash());
+    }
+
+    #[test]
+    fn display_and_parse() {
+        let hash = program(vec![]).hash();
+        assert_eq!(hash.to_string().parse::<ProgramHash>().unwrap(), hash);
+        assert!("00".parse::<ProgramHash>().is_err());
+    }
+}
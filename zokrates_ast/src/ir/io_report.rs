@@ -0,0 +1,338 @@
+//! Report the public values of a program, with the number of bits of information each of them can hold.
+//!
+//! The capacity of a value is the bitwidth of unsigned integers, the bound of the arguments declared `field<N>`, and
+//! the number of bits of the field otherwise, composite values adding up the capacities of their elements. A
+//! commitment or a nullifier accidentally truncated to a small type shows up as a small capacity.
+//!
+//! Each public output also lists the private arguments which influence it, according to the taint analysis. The
+//! analysis over-approximates the influence, so an output constrained together with a value depending on a private
+//! argument is reported as influenced by it.
+
+use super::{taint::taint, Prog, Variable};
+use crate::typed::abi::Abi;
+use crate::typed::{ConcreteType, GType};
+use serde::Serialize;
+use std::fmt;
+use zokrates_field::Field;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Input,
+    Output,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Direction::Input => write!(f, "input"),
+            Direction::Output => write!(f, "output"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PublicValue {
+    /// The name of the argument, or `out` and `out_<index>` for the outputs like in the exported verifier
+    pub name: String,
+    pub direction: Direction,
+    #[serde(flatten)]
+    pub ty: ConcreteType,
+    /// The number of bits of information the value can hold
+    pub capacity: usize,
+    /// The private arguments which influence the value, always empty for inputs
+    pub influenced_by: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Default)]
+pub struct IoReport {
+    pub values: Vec<PublicValue>,
+}
+
+impl fmt::Display for IoReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows: Vec<[String; 5]> = self
+            .values
+            .iter()
+            .map(|v| {
+                [
+                    v.direction.to_string(),
+                    v.name.clone(),
+                    v.ty.to_string(),
+                    v.capacity.to_string(),
+                    v.influenced_by.join(", "),
+                ]
+            })
+            .collect();
+
+        let header = ["direction", "name", "type", "capacity", "influenced by"].map(String::from);
+
+        let widths: Vec<usize> = (0..header.len())
+            .map(|i| {
+                std::iter::once(&header)
+                    .chain(rows.iter())
+                    .map(|row| row[i].len())
+                    .max()
+                    .unwrap()
+            })
+            .collect();
+
+        for row in std::iter::once(&header).chain(rows.iter()) {
+            let line = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join("  ");
+            writeln!(f, "{}", line.trim_end())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The number of bits of information held by a value of type `ty`, whose field elements fit in `bound` bits if any
+fn capacity<T: Field>(ty: &ConcreteType, bound: Option<u32>) -> usize {
+    match ty {
+        GType::FieldElement => bound
+            .map(|bound| bound as usize)
+            .unwrap_or_else(T::get_required_bits),
+        GType::Boolean => 1,
+        GType::Uint(bitwidth) => bitwidth.to_usize(),
+        GType::Array(array_type) => {
+            *array_type.size as usize * capacity::<T>(&array_type.ty, bound)
+        }
+        GType::Tuple(tuple_type) => tuple_type
+            .elements
+            .iter()
+            .map(|e| capacity::<T>(e, None))
+            .sum(),
+        GType::Struct(struct_type) => struct_type
+            .iter()
+            .map(|member| capacity::<T>(&member.ty, None))
+            .sum(),
+        GType::Int => unreachable!(),
+    }
+}
+
+/// List the public inputs and outputs of `program`, described by `abi`, in the order a verifier expects them
+pub fn io_report<T: Field>(program: &Prog<T>, abi: &Abi) -> IoReport {
+    // each input is flattened to consecutive arguments of the program
+    let mut offset = 0;
+    let mut private_inputs = vec![];
+    for input in &abi.inputs {
+        let count = input.ty.get_primitive_count();
+        if !input.public {
+            let variables: Vec<Variable> = program
+                .arguments
+                .get(offset..offset + count)
+                .unwrap_or_default()
+                .iter()
+                .map(|p| p.id)
+                .collect();
+            private_inputs.push((input.name.clone(), taint(program, variables).variables));
+        }
+        offset += count;
+    }
+
+    let inputs = abi.public_inputs().into_iter().map(|i| PublicValue {
+        capacity: capacity::<T>(&i.ty, abi.bounds.get(&i.name).cloned()),
+        name: i.name,
+        direction: Direction::Input,
+        ty: i.ty,
+        influenced_by: vec![],
+    });
+
+    let outputs: Vec<_> = match &abi.output {
+        ConcreteType::Tuple(tuple) => tuple
+            .elements
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !abi.private_outputs.contains(index))
+            .map(|(index, ty)| (format!("out_{}", index), ty.clone()))
+            .collect(),
+        ty => vec![("out".to_string(), ty.clone())],
+    };
+
+    // the public outputs are the first outputs of the program, in order
+    let mut offset = 0;
+    let outputs = outputs.into_iter().map(|(name, ty)| {
+        let count = ty.get_primitive_count();
+        let variables: Vec<_> = (offset..offset + count).map(Variable::public).collect();
+        offset += count;
+
+        PublicValue {
+            influenced_by: private_inputs
+                .iter()
+                .filter(|(_, tainted)| variables.iter().any(|v| tainted.contains(v)))
+                .map(|(name, _)| name.clone())
+                .collect(),
+            capacity: capacity::<T>(&ty, None),
+            name,
+            direction: Direction::Output,
+            ty,
+        }
+    });
+
+    let mut values: Vec<_> = inputs.chain(outputs).collect();
+
+    // the public layout keeps the elements of each value together, so values are ordered by their first element
+    if !program.public_layout.is_empty() {
+        let mut offset = 0;
+        let mut positions = Vec::with_capacity(values.len());
+        for value in &values {
+            positions.push(
+                program
+                    .public_layout
+                    .iter()
+                    .position(|i| *i == offset)
+                    .unwrap_or(offset),
+            );
+            offset += value.ty.get_primitive_count();
+        }
+
+        let mut ordered: Vec<_> = positions.into_iter().zip(values).collect();
+        ordered.sort_by_key(|(position, _)| *position);
+        values = ordered.into_iter().map(|(_, value)| value).collect();
+    }
+
+    IoReport { values }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{LinComb, Parameter, QuadComb, Statement};
+    use crate::typed::abi::AbiInput;
+    use crate::typed::{ConcreteTupleType, UBitwidth};
+    use zokrates_field::Bn128Field;
+
+    #[test]
+    fn truncated_output() {
+        // def main(private u32[8] secret, field<128> root) -> (field, u32)
+        let secret: Vec<_> = (0..8).map(Variable::new).collect();
+        let root = Variable::new(8);
+        let sum = Variable::new(9);
+
+        let program: Prog<Bn128Field> = Prog {
+            arguments: secret
+                .iter()
+                .map(|v| Parameter::private(*v))
+                .chain(std::iter::once(Parameter::public(root)))
+                .collect(),
+            return_count: 2,
+            public_layout: vec![],
+            variable_ordering: None,
+            statements: vec![
+                // a 32-bit digest of the 256-bit secret, here the sum of its words
+                Statement::definition(
+                    sum,
+                    secret
+                        .iter()
+                        .fold(LinComb::zero(), |acc, v| acc + LinComb::from(*v)),
+                ),
+                Statement::definition(Variable::public(0), root),
+                Statement::constraint(
+                    QuadComb::from_linear_combinations(sum.into(), LinComb::one()),
+                    Variable::public(1),
+                ),
+            ],
+        };
+
+        let abi = Abi {
+            inputs: vec![
+                AbiInput {
+                    name: "secret".into(),
+                    public: false,
+                    ty: ConcreteType::array((ConcreteType::Uint(UBitwidth::B32), 8u32)),
+                },
+                AbiInput {
+                    name: "root".into(),
+                    public: true,
+                    ty: ConcreteType::FieldElement,
+                },
+            ],
+            output: ConcreteType::Tuple(ConcreteTupleType::new(vec![
+                ConcreteType::FieldElement,
+                ConcreteType::Uint(UBitwidth::B32),
+            ])),
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: vec![("root".to_string(), 128)].into_iter().collect(),
+            commitment: None,
+            constants: vec![],
+        };
+
+        let report = io_report(&program, &abi);
+
+        assert_eq!(
+            report
+                .values
+                .iter()
+                .map(|v| (v.name.as_str(), v.capacity, v.influenced_by.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("root", 128, vec![]),
+                ("out_0", 254, vec![]),
+                ("out_1", 32, vec!["secret".to_string()]),
+            ]
+        );
+
+        assert_eq!(
+            report.to_string(),
+            "direction  name   type   capacity  influenced by
+input      root   field  128
+output     out_0  field  254
+output     out_1  u32    32        secret
+"
+        );
+    }
+
+    #[test]
+    fn public_layout() {
+        // def main(public field a, public u32[2] b) -> field, with the layout `out,b,a`
+        let program: Prog<Bn128Field> = Prog {
+            arguments: (0..3)
+                .map(|i| Parameter::public(Variable::new(i)))
+                .collect(),
+            return_count: 1,
+            public_layout: vec![3, 1, 2, 0],
+            variable_ordering: None,
+            statements: vec![Statement::definition(Variable::public(0), Variable::new(0))],
+        };
+
+        let abi = Abi {
+            inputs: vec![
+                AbiInput {
+                    name: "a".into(),
+                    public: true,
+                    ty: ConcreteType::FieldElement,
+                },
+                AbiInput {
+                    name: "b".into(),
+                    public: true,
+                    ty: ConcreteType::array((ConcreteType::Uint(UBitwidth::B32), 2u32)),
+                },
+            ],
+            output: ConcreteType::FieldElement,
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
+        };
+
+        assert_eq!(
+            io_report(&program, &abi)
+                .values
+                .iter()
+                .map(|v| v.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["out", "b", "a"]
+        );
+    }
+}
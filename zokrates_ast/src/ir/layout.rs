@@ -0,0 +1,339 @@
+//! Choose the order of the public values of a proof.
+//!
+//! By default, a verifier expects the public arguments of `main` in declaration order, followed by its public outputs.
+//! A `PublicLayout` reorders them, for verifiers which expect the outputs first or a specific order. Each argument
+//! and output keeps its field elements together, in the order of its type.
+
+use super::{ProgIterator, Statement};
+use crate::typed::abi::Abi;
+use crate::typed::ConcreteType;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PublicLayout {
+    /// The public arguments, followed by the public outputs
+    InputsFirst,
+    /// The public outputs, followed by the public arguments
+    OutputsFirst,
+    /// The public arguments and outputs in the given order, the outputs being named `out` or `out_<index>` like in
+    /// the exported verifier
+    Explicit(Vec<String>),
+}
+
+impl Default for PublicLayout {
+    fn default() -> Self {
+        PublicLayout::InputsFirst
+    }
+}
+
+impl FromStr for PublicLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "inputs-first" => Ok(PublicLayout::InputsFirst),
+            "outputs-first" => Ok(PublicLayout::OutputsFirst),
+            s => {
+                let names: Vec<_> = s.split(',').map(|n| n.trim().to_string()).collect();
+                match names.iter().any(|n| n.is_empty()) {
+                    true => Err(format!("Invalid public layout `{}`", s)),
+                    false => Ok(PublicLayout::Explicit(names)),
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for PublicLayout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PublicLayout::InputsFirst => write!(f, "inputs-first"),
+            PublicLayout::OutputsFirst => write!(f, "outputs-first"),
+            PublicLayout::Explicit(names) => write!(f, "{}", names.join(",")),
+        }
+    }
+}
+
+/// The names of the public arguments and of the public outputs described by `abi`, with their number of field
+/// elements, in the default order
+fn public_values(abi: &Abi) -> (Vec<(String, usize)>, Vec<(String, usize)>) {
+    let inputs = abi
+        .public_inputs()
+        .into_iter()
+        .map(|i| (i.name, i.ty.get_primitive_count()))
+        .collect();
+
+    let outputs = match &abi.output {
+        ConcreteType::Tuple(tuple) => tuple
+            .elements
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !abi.private_outputs.contains(index))
+            .map(|(index, ty)| (format!("out_{}", index), ty.get_primitive_count()))
+            .collect(),
+        ty => vec![("out".to_string(), ty.get_primitive_count())],
+    };
+
+    (inputs, outputs)
+}
+
+impl PublicLayout {
+    /// The position of each public value among the public arguments followed by the public outputs described by
+    /// `abi`. The result is empty if the layout is the default one
+    pub fn resolve(&self, abi: &Abi) -> Result<Vec<usize>, String> {
+        let (inputs, outputs) = public_values(abi);
+
+        let mut offset = 0;
+        let mut groups = Vec::with_capacity(inputs.len() + outputs.len());
+        for (name, size) in inputs.iter().chain(outputs.iter()) {
+            groups.push((name.as_str(), offset..offset + size));
+            offset += size;
+        }
+
+        let order: Vec<_> = match self {
+            PublicLayout::InputsFirst => return Ok(vec![]),
+            PublicLayout::OutputsFirst => groups[inputs.len()..]
+                .iter()
+                .chain(groups[..inputs.len()].iter())
+                .collect(),
+            PublicLayout::Explicit(names) => {
+                let mut placed = BTreeSet::new();
+                let order = names
+                    .iter()
+                    .map(|name| {
+                        if !placed.insert(name.as_str()) {
+                            return Err(format!(
+                                "`{}` appears several times in the public layout",
+                                name
+                            ));
+                        }
+                        groups.iter().find(|(n, _)| n == name).ok_or_else(|| {
+                            format!(
+                                "`{}` is not a public input or output of the program, expected one of {}",
+                                name,
+                                groups
+                                    .iter()
+                                    .map(|(n, _)| format!("`{}`", n))
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                            )
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if let Some((missing, _)) = groups.iter().find(|(n, _)| !placed.contains(n)) {
+                    return Err(format!("The public layout does not place `{}`", missing));
+                }
+
+                order
+            }
+        };
+
+        let layout: Vec<_> = order
+            .into_iter()
+            .flat_map(|(_, range)| range.clone())
+            .collect();
+
+        match layout.iter().enumerate().all(|(i, p)| i == *p) {
+            true => Ok(vec![]),
+            false => Ok(layout),
+        }
+    }
+}
+
+impl<'ast, T, I: IntoIterator<Item = Statement<'ast, T>>> ProgIterator<'ast, T, I> {
+    /// Order the public values of the program according to `layout`, the public arguments and outputs being
+    /// described by `abi`
+    pub fn apply_public_layout(self, layout: &PublicLayout, abi: &Abi) -> Result<Self, String> {
+        let public_layout = layout.resolve(abi)?;
+
+        let (inputs, outputs) = public_values(abi);
+        let abi_count = inputs.iter().chain(outputs.iter()).map(|(_, s)| s).sum();
+
+        if abi_count != self.public_count() {
+            return Err(format!(
+                "The ABI describes {} public values, the program has {}",
+                abi_count,
+                self.public_count()
+            ));
+        }
+
+        Ok(self.with_public_layout(public_layout))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{Parameter, Variable};
+    use crate::ir::{Prog, Witness};
+    use crate::typed::abi::AbiInput;
+    use crate::typed::types::{ConcreteTupleType, UBitwidth};
+    use zokrates_field::Bn128Field;
+
+    // def main(public field a, private field b, public u8[2] c) -> (field, bool)
+    fn abi() -> Abi {
+        Abi {
+            inputs: vec![
+                AbiInput {
+                    name: "a".into(),
+                    public: true,
+                    ty: ConcreteType::FieldElement,
+                },
+                AbiInput {
+                    name: "b".into(),
+                    public: false,
+                    ty: ConcreteType::FieldElement,
+                },
+                AbiInput {
+                    name: "c".into(),
+                    public: true,
+                    ty: ConcreteType::array((ConcreteType::Uint(UBitwidth::B8), 2u32)),
+                },
+            ],
+            output: ConcreteType::Tuple(ConcreteTupleType::new(vec![
+                ConcreteType::FieldElement,
+                ConcreteType::Boolean,
+            ])),
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
+        }
+    }
+
+    fn prog() -> Prog<'static, Bn128Field> {
+        Prog::new(
+            vec![
+                Parameter::public(Variable::new(0)),
+                Parameter::private(Variable::new(1)),
+                Parameter::public(Variable::new(2)),
+                Parameter::public(Variable::new(3)),
+            ],
+            vec![],
+            2,
+        )
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            "inputs-first".parse::<PublicLayout>(),
+            Ok(PublicLayout::InputsFirst)
+        );
+        assert_eq!(
+            "outputs-first".parse::<PublicLayout>(),
+            Ok(PublicLayout::OutputsFirst)
+        );
+        assert_eq!(
+            "c, out_1,a".parse::<PublicLayout>(),
+            Ok(PublicLayout::Explicit(vec![
+                "c".into(),
+                "out_1".into(),
+                "a".into()
+            ]))
+        );
+        assert!("a,,b".parse::<PublicLayout>().is_err());
+    }
+
+    #[test]
+    fn resolve() {
+        let abi = abi();
+
+        assert_eq!(PublicLayout::InputsFirst.resolve(&abi), Ok(vec![]));
+        assert_eq!(
+            PublicLayout::OutputsFirst.resolve(&abi),
+            Ok(vec![3, 4, 0, 1, 2])
+        );
+        assert_eq!(
+            "out_1,c,a,out_0"
+                .parse::<PublicLayout>()
+                .unwrap()
+                .resolve(&abi),
+            Ok(vec![4, 1, 2, 0, 3])
+        );
+        // the default order is not recorded
+        assert_eq!(
+            "a,c,out_0,out_1"
+                .parse::<PublicLayout>()
+                .unwrap()
+                .resolve(&abi),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn resolve_errors() {
+        let abi = abi();
+
+        assert!("a,c,out_0"
+            .parse::<PublicLayout>()
+            .unwrap()
+            .resolve(&abi)
+            .is_err());
+        assert!("a,c,out_0,out_1,a"
+            .parse::<PublicLayout>()
+            .unwrap()
+            .resolve(&abi)
+            .is_err());
+        assert!("a,b,c,out_0,out_1"
+            .parse::<PublicLayout>()
+            .unwrap()
+            .resolve(&abi)
+            .is_err());
+    }
+
+    #[test]
+    fn public_values_follow_the_layout() {
+        let prog = prog()
+            .apply_public_layout(&PublicLayout::OutputsFirst, &abi())
+            .unwrap();
+
+        assert_eq!(
+            prog.public_variables(),
+            vec![
+                Variable::public(0),
+                Variable::public(1),
+                Variable::new(0),
+                Variable::new(2),
+                Variable::new(3)
+            ]
+        );
+
+        let witness = Witness(
+            vec![
+                (Variable::new(0), Bn128Field::from(1)),
+                (Variable::new(1), Bn128Field::from(2)),
+                (Variable::new(2), Bn128Field::from(3)),
+                (Variable::new(3), Bn128Field::from(4)),
+                (Variable::public(0), Bn128Field::from(5)),
+                (Variable::public(1), Bn128Field::from(6)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        assert_eq!(
+            prog.public_inputs_values(&witness),
+            vec![5, 6, 1, 3, 4]
+                .into_iter()
+                .map(Bn128Field::from)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn abi_mismatch() {
+        let mut abi = abi();
+        abi.inputs.pop();
+
+        assert!(prog()
+            .apply_public_layout(&PublicLayout::OutputsFirst, &abi)
+            .is_err());
+    }
+}
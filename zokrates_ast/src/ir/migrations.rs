@@ -0,0 +1,176 @@
+//! Readers for the versions of the program format.
+//!
+//! Each version of the format only adds to the header of the previous one, so the reader of a version is the reader of
+//! the previous version followed by whatever was added. Every reader upgrades what it reads to the current header, so
+//! that the rest of the compiler only ever sees programs in the current format.
+//!
+//! Supporting a new version of the format is done by bumping `CURRENT_VERSION`, writing its reader on top of the one
+//! of the previous version, and registering it in `reader`.
+
+use super::{Parameter, ProgramHash, VariableOrdering};
+use serde::Deserialize;
+use serde_cbor::de::IoRead;
+use std::io::Read;
+
+/// The oldest version of the format this compiler reads
+pub const OLDEST_VERSION: u32 = 2;
+/// The latest version of the format, which this compiler writes
+pub const CURRENT_VERSION: u32 = 5;
+
+pub type Deserializer<R> = serde_cbor::Deserializer<IoRead<R>>;
+
+/// The header of a program in the current format
+pub struct Header {
+    /// The hash of the constraint system, if the program was compiled with it
+    pub hash: Option<ProgramHash>,
+    pub arguments: Vec<Parameter>,
+    pub return_count: usize,
+    pub public_layout: Vec<usize>,
+    pub variable_ordering: Option<VariableOrdering>,
+}
+
+/// Reads the header of a program which follows the curve identifier, leaving the statements in the deserializer
+pub type Reader<R> = fn(R) -> Result<(Header, Deserializer<R>), String>;
+
+/// The reader for the version `version` of the format
+pub fn reader<R: Read>(version: [u8; 4]) -> Result<Reader<R>, String> {
+    match u32::from_be_bytes(version) {
+        2 => Ok(read_v2),
+        3 => Ok(read_v3),
+        4 => Ok(read_v4),
+        5 => Ok(read_v5),
+        v if v > CURRENT_VERSION => Err(format!(
+            "This program was compiled to version {} of the format, which is newer than version {} read by this version of ZoKrates. Please upgrade ZoKrates",
+            v, CURRENT_VERSION
+        )),
+        v => Err(format!(
+            "This program was compiled to version {} of the format, which is no longer supported: this version of ZoKrates reads versions {} to {}. Please recompile it",
+            v, OLDEST_VERSION, CURRENT_VERSION
+        )),
+    }
+}
+
+/// Version 2: the arguments and the number of outputs
+fn read_v2<R: Read>(r: R) -> Result<(Header, Deserializer<R>), String> {
+    use serde::de::Deserializer as _;
+
+    let mut p = serde_cbor::Deserializer::from_reader(r);
+
+    struct ArgumentsVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for ArgumentsVisitor {
+        type Value = Vec<Parameter>;
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("seq of flat param")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::SeqAccess<'de>,
+        {
+            let mut res = vec![];
+            while let Some(e) = seq.next_element()? {
+                res.push(e);
+            }
+            Ok(res)
+        }
+    }
+
+    let arguments = p
+        .deserialize_seq(ArgumentsVisitor)
+        .map_err(|_| String::from("Cannot read arguments"))?;
+
+    struct ReturnCountVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for ReturnCountVisitor {
+        type Value = usize;
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("usize")
+        }
+
+        fn visit_u32<E>(self, v: u32) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(v as usize)
+        }
+
+        fn visit_u8<E>(self, v: u8) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(v as usize)
+        }
+
+        fn visit_u16<E>(self, v: u16) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(v as usize)
+        }
+    }
+
+    let return_count = p
+        .deserialize_u32(ReturnCountVisitor)
+        .map_err(|_| String::from("Cannot read number of outputs"))?;
+
+    Ok((
+        Header {
+            hash: None,
+            arguments,
+            return_count,
+            public_layout: vec![],
+            variable_ordering: None,
+        },
+        p,
+    ))
+}
+
+/// Version 3: the hash of the constraint system, before the rest of the header of version 2
+fn read_v3<R: Read>(mut r: R) -> Result<(Header, Deserializer<R>), String> {
+    let mut hash = [0; 32];
+    r.read_exact(&mut hash)
+        .map_err(|_| String::from("Cannot read program hash"))?;
+
+    let (header, p) = read_v2(r)?;
+
+    Ok((
+        Header {
+            hash: Some(ProgramHash(hash)),
+            ..header
+        },
+        p,
+    ))
+}
+
+/// Version 4: the public layout, after the header of version 3
+fn read_v4<R: Read>(r: R) -> Result<(Header, Deserializer<R>), String> {
+    let (header, mut p) = read_v3(r)?;
+
+    let public_layout =
+        Vec::deserialize(&mut p).map_err(|_| String::from("Cannot read public layout"))?;
+
+    Ok((
+        Header {
+            public_layout,
+            ..header
+        },
+        p,
+    ))
+}
+
+/// Version 5: the variable ordering, after the header of version 4
+fn read_v5<R: Read>(r: R) -> Result<(Header, Deserializer<R>), String> {
+    let (header, mut p) = read_v4(r)?;
+
+    let variable_ordering = VariableOrdering::deserialize(&mut p)
+        .map_err(|_| String::from("Cannot read variable ordering"))?;
+
+    Ok((
+        Header {
+            variable_ordering: Some(variable_ordering),
+            ..header
+        },
+        p,
+    ))
+}
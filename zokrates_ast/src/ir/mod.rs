@@ -1,4 +1,4 @@
-use crate::common::FormatString;
+use crate::common::{AssertionError, FormatString};
 use crate::typed::ConcreteType;
 use derivative::Derivative;
 use serde::{Deserialize, Serialize};
@@ -7,25 +7,46 @@ use std::fmt;
 use std::hash::Hash;
 use zokrates_field::Field;
 
+#[cfg(feature = "arkworks")]
+pub mod arkworks;
+mod bind;
 mod check;
 mod clean;
+pub mod container;
 mod expression;
 pub mod folder;
 pub mod from_flat;
+pub mod fuzz;
+mod hash;
+pub mod io_report;
+pub mod layout;
+mod migrations;
+pub mod ordering;
+pub mod result_visitor;
 mod serialize;
+pub mod smt;
 pub mod smtlib2;
+pub mod stats;
+pub mod taint;
+pub mod text;
 pub mod visitor;
 mod witness;
+pub mod zkinterface;
 
 pub use self::expression::QuadComb;
 pub use self::expression::{CanonicalLinComb, LinComb};
+pub use self::hash::{ConstraintSystemHasher, ProgramHash};
+pub use self::layout::PublicLayout;
+pub use self::ordering::VariableOrdering;
 pub use self::serialize::ProgEnum;
 pub use crate::common::Parameter;
 pub use crate::common::RuntimeError;
 pub use crate::common::Solver;
 pub use crate::common::Variable;
 
-pub use self::witness::Witness;
+pub use self::witness::{
+    MergeError, Substitution, Witness, WitnessKey, ENCRYPTED_WITNESS_MAGIC, WITNESS_NONCE_SIZE,
+};
 
 #[derive(Debug, Serialize, Deserialize, Clone, Derivative)]
 #[derivative(Hash, PartialEq, Eq)]
@@ -52,6 +73,12 @@ impl<'ast, T: Field> Statement<'ast, T> {
     pub fn constraint<U: Into<QuadComb<T>>, V: Into<LinComb<T>>>(quad: U, lin: V) -> Self {
         Statement::Constraint(quad.into(), lin.into(), None)
     }
+
+    /// Whether this statement is a constraint coming from an assertion marked `#[critical]`, which optimizations must
+    /// not remove
+    pub fn is_critical(&self) -> bool {
+        matches!(self, Statement::Constraint(_, _, Some(error)) if error.is_critical())
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Hash, PartialEq, Eq)]
@@ -129,6 +156,14 @@ pub type Prog<'ast, T> = ProgIterator<'ast, T, Vec<Statement<'ast, T>>>;
 pub struct ProgIterator<'ast, T, I: IntoIterator<Item = Statement<'ast, T>>> {
     pub arguments: Vec<Parameter>,
     pub return_count: usize,
+    /// The position of each public value of the proof among the public arguments followed by the public outputs, see
+    /// `PublicLayout`. Empty when the public values follow this default order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub public_layout: Vec<usize>,
+    /// The permutation applied to the intermediate variables by `ordering::reorder`, if any, to map values computed
+    /// for the program before it was reordered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variable_ordering: Option<VariableOrdering>,
     pub statements: I,
 }
 
@@ -137,18 +172,38 @@ impl<'ast, T, I: IntoIterator<Item = Statement<'ast, T>>> ProgIterator<'ast, T,
         Self {
             arguments,
             return_count,
+            public_layout: vec![],
+            variable_ordering: None,
             statements,
         }
     }
 
+    pub fn with_public_layout(self, public_layout: Vec<usize>) -> Self {
+        Self {
+            public_layout,
+            ..self
+        }
+    }
+
+    pub fn with_variable_ordering(self, variable_ordering: Option<VariableOrdering>) -> Self {
+        Self {
+            variable_ordering,
+            ..self
+        }
+    }
+
     pub fn collect(self) -> ProgIterator<'ast, T, Vec<Statement<'ast, T>>> {
         ProgIterator {
             statements: self.statements.into_iter().collect::<Vec<_>>(),
             arguments: self.arguments,
             return_count: self.return_count,
+            public_layout: self.public_layout,
+            variable_ordering: self.variable_ordering,
         }
     }
 
+    /// The public outputs of the program. The outputs which follow them, if any, are private return values: they
+    /// are part of the witness, but not of the public inputs
     pub fn returns(&self) -> Vec<Variable> {
         (0..self.return_count).map(Variable::public).collect()
     }
@@ -164,15 +219,29 @@ impl<'ast, T, I: IntoIterator<Item = Statement<'ast, T>>> ProgIterator<'ast, T,
             .map(|a| a.id)
             .collect()
     }
+
+    /// The public values of the proof, in the order a verifier expects them
+    pub fn public_variables(&self) -> Vec<Variable> {
+        let variables = self
+            .arguments
+            .iter()
+            .filter(|a| !a.private)
+            .map(|a| a.id)
+            .chain(self.returns())
+            .collect::<Vec<_>>();
+
+        match self.public_layout.is_empty() {
+            true => variables,
+            false => self.public_layout.iter().map(|i| variables[*i]).collect(),
+        }
+    }
 }
 
 impl<'ast, T: Field, I: IntoIterator<Item = Statement<'ast, T>>> ProgIterator<'ast, T, I> {
     pub fn public_inputs_values(&self, witness: &Witness<T>) -> Vec<T> {
-        self.arguments
+        self.public_variables()
             .iter()
-            .filter(|p| !p.private)
-            .map(|p| witness.0.get(&p.id).unwrap().clone())
-            .chain(witness.return_values())
+            .map(|v| witness.0.get(v).unwrap().clone())
             .collect()
     }
 }
@@ -192,6 +261,21 @@ impl<'ast, T> Prog<'ast, T> {
             statements: self.statements.into_iter(),
             arguments: self.arguments,
             return_count: self.return_count,
+            public_layout: self.public_layout,
+            variable_ordering: self.variable_ordering,
+        }
+    }
+}
+
+impl<'ast, T: Clone> Prog<'ast, T> {
+    /// Iterate on the program, copying its statements one at a time rather than the whole program upfront
+    pub fn iter(&self) -> ProgIterator<'ast, T, impl IntoIterator<Item = Statement<'ast, T>> + '_> {
+        ProgIterator {
+            statements: self.statements.iter().cloned(),
+            arguments: self.arguments.clone(),
+            return_count: self.return_count,
+            public_layout: self.public_layout.clone(),
+            variable_ordering: self.variable_ordering.clone(),
         }
     }
 }
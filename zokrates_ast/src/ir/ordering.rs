@@ -0,0 +1,313 @@
+//! Renumber the variables of a program so that variables used by the same constraints get close indices.
+//!
+//! Variables are ordered by a reverse Cuthill-McKee traversal of the incidence graph between constraints and the
+//! variables they use. `~one`, the arguments and the return values keep their indices, and the renumbered variables
+//! take the indices they occupied before, so the layout of the program stays as dense as it was.
+
+use super::folder::Folder;
+use super::{LinComb, Prog, Statement, Witness};
+use crate::common::Variable;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use zokrates_field::Field;
+
+/// A permutation of the intermediate variables of a program
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct VariableOrdering {
+    /// The new index of each moved variable, by its index in the original program
+    permutation: BTreeMap<usize, usize>,
+}
+
+impl VariableOrdering {
+    /// Compute a reverse Cuthill-McKee ordering of the intermediate variables of `program`
+    pub fn reverse_cuthill_mckee<T: Field>(program: &Prog<T>) -> Self {
+        let mut graph = IncidenceGraph {
+            arguments: program.arguments.iter().map(|a| a.id).collect(),
+            ..IncidenceGraph::default()
+        };
+
+        graph.add_statements(&program.statements);
+
+        let order = graph.cuthill_mckee();
+
+        // the renumbered variables take the indices they had, in the reverse order of the traversal
+        let slots = graph.incidence.keys().map(|v| v.id());
+
+        VariableOrdering {
+            permutation: order
+                .into_iter()
+                .rev()
+                .zip(slots)
+                .filter(|(v, slot)| v.id() != *slot)
+                .map(|(v, slot)| (v.id(), slot))
+                .collect(),
+        }
+    }
+
+    /// The variable `v` is renumbered to
+    pub fn get(&self, v: Variable) -> Variable {
+        match v.id > 0 {
+            true => self
+                .permutation
+                .get(&v.id())
+                .map(|id| Variable::new(*id))
+                .unwrap_or(v),
+            false => v,
+        }
+    }
+
+    /// The ordering which maps renumbered variables back to the original ones
+    pub fn inverse(&self) -> Self {
+        VariableOrdering {
+            permutation: self.permutation.iter().map(|(a, b)| (*b, *a)).collect(),
+        }
+    }
+
+    /// Renumber the variables of `program`
+    pub fn apply<'ast, T: Field>(&self, program: Prog<'ast, T>) -> Prog<'ast, T> {
+        Renumberer { ordering: self }.fold_program(program)
+    }
+
+    /// Map a witness of the original program to a witness of the renumbered one
+    pub fn apply_to_witness<T>(&self, witness: Witness<T>) -> Witness<T> {
+        Witness(
+            witness
+                .0
+                .into_iter()
+                .map(|(v, value)| (self.get(v), value))
+                .collect(),
+        )
+    }
+}
+
+/// Renumber the variables of `program` for cache-friendly proving, recording the ordering which was applied in the
+/// program
+pub fn reorder<T: Field>(program: Prog<T>) -> Prog<T> {
+    let ordering = VariableOrdering::reverse_cuthill_mckee(&program);
+    ordering
+        .apply(program)
+        .with_variable_ordering(Some(ordering))
+}
+
+struct Renumberer<'a> {
+    ordering: &'a VariableOrdering,
+}
+
+impl<'a, 'ast, T: Field> Folder<'ast, T> for Renumberer<'a> {
+    fn fold_variable(&mut self, v: Variable) -> Variable {
+        self.ordering.get(v)
+    }
+}
+
+#[derive(Default)]
+struct IncidenceGraph {
+    /// The arguments of the program, which are not renumbered
+    arguments: BTreeSet<Variable>,
+    /// The intermediate variables of each constraint
+    constraints: Vec<Vec<Variable>>,
+    /// The constraints of each intermediate variable
+    incidence: BTreeMap<Variable, Vec<usize>>,
+}
+
+impl IncidenceGraph {
+    fn is_intermediate(&self, v: &Variable) -> bool {
+        v.id > 0 && !self.arguments.contains(v)
+    }
+
+    fn add_variable(&mut self, v: Variable) {
+        if self.is_intermediate(&v) {
+            self.incidence.entry(v).or_default();
+        }
+    }
+
+    fn add_statements<T>(&mut self, statements: &[Statement<T>]) {
+        for statement in statements {
+            match statement {
+                Statement::Block(statements) => self.add_statements(statements),
+                Statement::Constraint(quad, lin, _) => {
+                    let variables: BTreeSet<_> = [&quad.left, &quad.right, lin]
+                        .into_iter()
+                        .flat_map(|l: &LinComb<T>| l.0.iter().map(|(v, _)| *v))
+                        .filter(|v| self.is_intermediate(v))
+                        .collect();
+
+                    let index = self.constraints.len();
+                    for v in &variables {
+                        self.incidence.entry(*v).or_default().push(index);
+                    }
+                    self.constraints.push(variables.into_iter().collect());
+                }
+                // variables which only appear outside constraints are renumbered too
+                Statement::Directive(d) => {
+                    for v in d
+                        .inputs
+                        .iter()
+                        .flat_map(|q| q.left.0.iter().chain(q.right.0.iter()))
+                        .map(|(v, _)| *v)
+                        .chain(d.outputs.iter().cloned())
+                    {
+                        self.add_variable(v);
+                    }
+                }
+                Statement::Log(_, expressions) => {
+                    for v in expressions
+                        .iter()
+                        .flat_map(|(_, e)| e.iter())
+                        .flat_map(|l| l.0.iter().map(|(v, _)| *v))
+                    {
+                        self.add_variable(v);
+                    }
+                }
+            }
+        }
+    }
+
+    fn degree(&self, v: &Variable) -> usize {
+        self.incidence[v].len()
+    }
+
+    /// Visit the variables breadth first, starting each connected component from a variable of lowest degree and
+    /// enqueuing the neighbours of a variable by increasing degree
+    fn cuthill_mckee(&self) -> Vec<Variable> {
+        let mut starts: Vec<_> = self.incidence.keys().cloned().collect();
+        starts.sort_by_key(|v| (self.degree(v), *v));
+
+        let mut order = Vec::with_capacity(starts.len());
+        let mut visited = BTreeSet::new();
+        let mut expanded = vec![false; self.constraints.len()];
+
+        for start in starts {
+            if !visited.insert(start) {
+                continue;
+            }
+
+            let mut queue = VecDeque::from([start]);
+
+            while let Some(v) = queue.pop_front() {
+                order.push(v);
+
+                // each constraint is only expanded once, so the traversal is linear in the size of the program
+                for c in &self.incidence[&v] {
+                    if std::mem::replace(&mut expanded[*c], true) {
+                        continue;
+                    }
+
+                    let mut neighbours: Vec<_> = self.constraints[*c]
+                        .iter()
+                        .filter(|w| !visited.contains(*w))
+                        .cloned()
+                        .collect();
+                    neighbours.sort_by_key(|w| (self.degree(w), *w));
+
+                    for w in neighbours {
+                        visited.insert(w);
+                        queue.push_back(w);
+                    }
+                }
+            }
+        }
+
+        order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::Parameter;
+    use crate::ir::{Directive, QuadComb, Solver};
+    use zokrates_field::Bn128Field;
+
+    // def main(private _0) -> ~out_0
+    //   _9 * _9 == _5
+    //   _5 * _5 == _1
+    //   _1 * _1 == _7
+    //   _7 * _0 == ~out_0
+    fn chain() -> Prog<'static, Bn128Field> {
+        let square = |a: usize, b: usize| {
+            Statement::constraint(
+                QuadComb::from_linear_combinations(
+                    Variable::new(a).into(),
+                    Variable::new(a).into(),
+                ),
+                Variable::new(b),
+            )
+        };
+
+        Prog::new(
+            vec![Parameter::private(Variable::new(0))],
+            vec![
+                Statement::Directive(Directive {
+                    inputs: vec![Variable::new(0).into()],
+                    outputs: vec![Variable::new(9)],
+                    solver: Solver::Bits(1),
+                }),
+                square(9, 5),
+                square(5, 1),
+                square(1, 7),
+                Statement::constraint(
+                    QuadComb::from_linear_combinations(
+                        Variable::new(7).into(),
+                        Variable::new(0).into(),
+                    ),
+                    Variable::public(0),
+                ),
+            ],
+            1,
+        )
+    }
+
+    #[test]
+    fn chain_is_contiguous() {
+        let program = reorder(chain());
+        let ordering = program.variable_ordering.clone().unwrap();
+
+        // the variables of the chain take the same indices, in reverse traversal order
+        assert_eq!(ordering.get(Variable::new(9)), Variable::new(9));
+        assert_eq!(ordering.get(Variable::new(5)), Variable::new(7));
+        assert_eq!(ordering.get(Variable::new(1)), Variable::new(5));
+        assert_eq!(ordering.get(Variable::new(7)), Variable::new(1));
+
+        // arguments, outputs and ~one are untouched
+        assert_eq!(program.arguments, chain().arguments);
+        assert_eq!(ordering.get(Variable::new(0)), Variable::new(0));
+        assert_eq!(ordering.get(Variable::public(0)), Variable::public(0));
+        assert_eq!(ordering.get(Variable::one()), Variable::one());
+
+        assert_eq!(
+            program.statements[1],
+            Statement::constraint(
+                QuadComb::from_linear_combinations(
+                    Variable::new(9).into(),
+                    Variable::new(9).into()
+                ),
+                Variable::new(7)
+            )
+        );
+    }
+
+    #[test]
+    fn inverse() {
+        let ordering = VariableOrdering::reverse_cuthill_mckee(&chain());
+        let inverse = ordering.inverse();
+
+        for id in [0, 1, 5, 7, 9] {
+            let v = Variable::new(id);
+            assert_eq!(inverse.get(ordering.get(v)), v);
+        }
+
+        assert_eq!(inverse.apply(ordering.apply(chain())), chain());
+    }
+
+    #[test]
+    fn serialize() {
+        let ordering = VariableOrdering::reverse_cuthill_mckee(&chain());
+
+        let json = serde_json::to_string(&ordering).unwrap();
+        assert_eq!(json, r#"{"permutation":{"1":5,"5":7,"7":1}}"#);
+        assert_eq!(
+            serde_json::from_str::<VariableOrdering>(&json).unwrap(),
+            ordering
+        );
+    }
+}
@@ -0,0 +1,195 @@
+// Generic walk through an IR AST which can fail. Not mutating in place
+
+use super::*;
+use crate::common::Variable;
+use zokrates_field::Field;
+
+pub trait ResultVisitor<T: Field>: Sized {
+    type Error;
+
+    fn visit_module(&mut self, p: &Prog<T>) -> Result<(), Self::Error> {
+        visit_module(self, p)
+    }
+
+    fn visit_argument(&mut self, p: &Parameter) -> Result<(), Self::Error> {
+        visit_argument(self, p)
+    }
+
+    fn visit_variable(&mut self, v: &Variable) -> Result<(), Self::Error> {
+        visit_variable(self, v)
+    }
+
+    fn visit_value(&mut self, v: &T) -> Result<(), Self::Error> {
+        visit_value(self, v)
+    }
+
+    fn visit_statement(&mut self, s: &Statement<T>) -> Result<(), Self::Error> {
+        visit_statement(self, s)
+    }
+
+    fn visit_linear_combination(&mut self, e: &LinComb<T>) -> Result<(), Self::Error> {
+        visit_linear_combination(self, e)
+    }
+
+    fn visit_quadratic_combination(&mut self, es: &QuadComb<T>) -> Result<(), Self::Error> {
+        visit_quadratic_combination(self, es)
+    }
+
+    fn visit_directive(&mut self, d: &Directive<T>) -> Result<(), Self::Error> {
+        visit_directive(self, d)
+    }
+
+    fn visit_runtime_error(&mut self, e: &RuntimeError) -> Result<(), Self::Error> {
+        visit_runtime_error(self, e)
+    }
+}
+
+pub fn visit_module<T: Field, F: ResultVisitor<T>>(f: &mut F, p: &Prog<T>) -> Result<(), F::Error> {
+    for expr in p.arguments.iter() {
+        f.visit_argument(expr)?;
+    }
+    for expr in p.statements.iter() {
+        f.visit_statement(expr)?;
+    }
+    Ok(())
+}
+
+pub fn visit_statement<T: Field, F: ResultVisitor<T>>(
+    f: &mut F,
+    s: &Statement<T>,
+) -> Result<(), F::Error> {
+    match s {
+        Statement::Block(statements) => {
+            for s in statements {
+                f.visit_statement(s)?;
+            }
+        }
+        Statement::Constraint(quad, lin, error) => {
+            f.visit_quadratic_combination(quad)?;
+            f.visit_linear_combination(lin)?;
+            if let Some(error) = error.as_ref() {
+                f.visit_runtime_error(error)?;
+            }
+        }
+        Statement::Directive(dir) => f.visit_directive(dir)?,
+        Statement::Log(_, expressions) => {
+            for (_, e) in expressions {
+                for e in e {
+                    f.visit_linear_combination(e)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn visit_linear_combination<T: Field, F: ResultVisitor<T>>(
+    f: &mut F,
+    e: &LinComb<T>,
+) -> Result<(), F::Error> {
+    for expr in e.0.iter() {
+        f.visit_variable(&expr.0)?;
+        f.visit_value(&expr.1)?;
+    }
+    Ok(())
+}
+
+pub fn visit_quadratic_combination<T: Field, F: ResultVisitor<T>>(
+    f: &mut F,
+    e: &QuadComb<T>,
+) -> Result<(), F::Error> {
+    f.visit_linear_combination(&e.left)?;
+    f.visit_linear_combination(&e.right)
+}
+
+pub fn visit_directive<T: Field, F: ResultVisitor<T>>(
+    f: &mut F,
+    ds: &Directive<T>,
+) -> Result<(), F::Error> {
+    for expr in ds.inputs.iter() {
+        f.visit_quadratic_combination(expr)?;
+    }
+    for expr in ds.outputs.iter() {
+        f.visit_variable(expr)?;
+    }
+    Ok(())
+}
+
+pub fn visit_argument<T: Field, F: ResultVisitor<T>>(
+    f: &mut F,
+    a: &Parameter,
+) -> Result<(), F::Error> {
+    f.visit_variable(&a.id)
+}
+
+pub fn visit_variable<T: Field, F: ResultVisitor<T>>(
+    _f: &mut F,
+    _v: &Variable,
+) -> Result<(), F::Error> {
+    Ok(())
+}
+
+pub fn visit_value<T: Field, F: ResultVisitor<T>>(_f: &mut F, _v: &T) -> Result<(), F::Error> {
+    Ok(())
+}
+
+fn visit_runtime_error<T: Field, F: ResultVisitor<T>>(
+    _f: &mut F,
+    _: &RuntimeError,
+) -> Result<(), F::Error> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_field::Bn128Field;
+
+    // fails on the first use of a given variable, counting the variables visited
+    struct Forbid {
+        forbidden: Variable,
+        visited: usize,
+    }
+
+    impl<T: Field> ResultVisitor<T> for Forbid {
+        type Error = Variable;
+
+        fn visit_variable(&mut self, v: &Variable) -> Result<(), Self::Error> {
+            self.visited += 1;
+            if *v == self.forbidden {
+                Err(*v)
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[test]
+    fn stop_on_first_error() {
+        let statements: Vec<Statement<Bn128Field>> = vec![
+            Statement::constraint(Variable::new(0), Variable::new(1)),
+            // the forbidden variable is nested in a block and a quadratic combination
+            Statement::Block(vec![Statement::constraint(
+                QuadComb::from_linear_combinations(
+                    LinComb::from(Variable::new(2)),
+                    LinComb::from(Variable::new(3)),
+                ),
+                Variable::new(4),
+            )]),
+            Statement::constraint(Variable::new(3), Variable::new(5)),
+        ];
+
+        let mut visitor = Forbid {
+            forbidden: Variable::new(3),
+            visited: 0,
+        };
+
+        let res = statements
+            .iter()
+            .try_for_each(|s| ResultVisitor::<Bn128Field>::visit_statement(&mut visitor, s));
+
+        assert_eq!(res, Err(Variable::new(3)));
+        // `_0` and `_1` on the first constraint, then `_2` and `_3`
+        assert_eq!(visitor.visited, 4);
+    }
+}
@@ -1,14 +1,18 @@
 use crate::ir::check::UnconstrainedVariableDetector;
+use crate::ir::container::ZOKRATES_CONTAINER_MAGIC;
 
-use super::{ProgIterator, Statement};
+use super::migrations::{self, Header};
+use super::{ConstraintSystemHasher, ProgIterator, ProgramHash, Statement};
 use serde_cbor::{self, StreamDeserializer};
-use std::io::{Read, Write};
+use std::io::{Read, Seek, SeekFrom, Write};
 use zokrates_field::*;
 
 type DynamicError = Box<dyn std::error::Error>;
 
 const ZOKRATES_MAGIC: &[u8; 4] = &[0x5a, 0x4f, 0x4b, 0];
-const ZOKRATES_VERSION_2: &[u8; 4] = &[0, 0, 0, 2];
+const ZOKRATES_VERSION_3: &[u8; 4] = &[0, 0, 0, 3];
+const ZOKRATES_VERSION_4: &[u8; 4] = &[0, 0, 0, 4];
+const ZOKRATES_VERSION_5: &[u8; 4] = &[0, 0, 0, 5];
 
 #[derive(PartialEq, Eq, Debug)]
 pub enum ProgEnum<
@@ -17,11 +21,15 @@ pub enum ProgEnum<
     Bn128I: IntoIterator<Item = Statement<'ast, Bn128Field>>,
     Bls12_377I: IntoIterator<Item = Statement<'ast, Bls12_377Field>>,
     Bw6_761I: IntoIterator<Item = Statement<'ast, Bw6_761Field>>,
+    PallasI: IntoIterator<Item = Statement<'ast, PallasField>>,
+    VestaI: IntoIterator<Item = Statement<'ast, VestaField>>,
 > {
     Bls12_381Program(ProgIterator<'ast, Bls12_381Field, Bls12_381I>),
     Bn128Program(ProgIterator<'ast, Bn128Field, Bn128I>),
     Bls12_377Program(ProgIterator<'ast, Bls12_377Field, Bls12_377I>),
     Bw6_761Program(ProgIterator<'ast, Bw6_761Field, Bw6_761I>),
+    PallasProgram(ProgIterator<'ast, PallasField, PallasI>),
+    VestaProgram(ProgIterator<'ast, VestaField, VestaI>),
 }
 
 type MemoryProgEnum<'ast> = ProgEnum<
@@ -30,6 +38,8 @@ type MemoryProgEnum<'ast> = ProgEnum<
     Vec<Statement<'ast, Bn128Field>>,
     Vec<Statement<'ast, Bls12_377Field>>,
     Vec<Statement<'ast, Bw6_761Field>>,
+    Vec<Statement<'ast, PallasField>>,
+    Vec<Statement<'ast, VestaField>>,
 >;
 
 impl<
@@ -38,7 +48,9 @@ impl<
         Bn128I: IntoIterator<Item = Statement<'ast, Bn128Field>>,
         Bls12_377I: IntoIterator<Item = Statement<'ast, Bls12_377Field>>,
         Bw6_761I: IntoIterator<Item = Statement<'ast, Bw6_761Field>>,
-    > ProgEnum<'ast, Bls12_381I, Bn128I, Bls12_377I, Bw6_761I>
+        PallasI: IntoIterator<Item = Statement<'ast, PallasField>>,
+        VestaI: IntoIterator<Item = Statement<'ast, VestaField>>,
+    > ProgEnum<'ast, Bls12_381I, Bn128I, Bls12_377I, Bw6_761I, PallasI, VestaI>
 {
     pub fn collect(self) -> MemoryProgEnum<'ast> {
         match self {
@@ -46,6 +58,8 @@ impl<
             ProgEnum::Bn128Program(p) => ProgEnum::Bn128Program(p.collect()),
             ProgEnum::Bls12_377Program(p) => ProgEnum::Bls12_377Program(p.collect()),
             ProgEnum::Bw6_761Program(p) => ProgEnum::Bw6_761Program(p.collect()),
+            ProgEnum::PallasProgram(p) => ProgEnum::PallasProgram(p.collect()),
+            ProgEnum::VestaProgram(p) => ProgEnum::VestaProgram(p.collect()),
         }
     }
     pub fn curve(&self) -> &'static str {
@@ -54,6 +68,8 @@ impl<
             ProgEnum::Bls12_381Program(_) => Bls12_381Field::name(),
             ProgEnum::Bls12_377Program(_) => Bls12_377Field::name(),
             ProgEnum::Bw6_761Program(_) => Bw6_761Field::name(),
+            ProgEnum::PallasProgram(_) => PallasField::name(),
+            ProgEnum::VestaProgram(_) => VestaField::name(),
         }
     }
 }
@@ -61,17 +77,41 @@ impl<
 impl<'ast, T: Field, I: IntoIterator<Item = Statement<'ast, T>>> ProgIterator<'ast, T, I> {
     /// serialize a program iterator, returning the number of constraints serialized
     /// Note that we only return constraints, not other statements such as directives
-    pub fn serialize<W: Write>(self, mut w: W) -> Result<usize, DynamicError> {
-        use super::folder::Folder;
+    ///
+    /// The hash of the constraint system is written in the header once all statements were streamed, by seeking back
+    ///
+    /// Programs with a public layout are written in version 4, which stores the layout after the number of outputs.
+    /// Reordered programs are written in version 5, which stores the variable ordering after the layout
+    pub fn serialize<W: Write + Seek>(self, mut w: W) -> Result<usize, DynamicError> {
+        use super::result_visitor::ResultVisitor;
+
+        let mut unconstrained_variable_detector = UnconstrainedVariableDetector::new(&self);
+        let mut hasher = ConstraintSystemHasher::<T>::new(
+            &self.arguments,
+            self.return_count,
+            &self.public_layout,
+        );
 
         w.write_all(ZOKRATES_MAGIC)?;
-        w.write_all(ZOKRATES_VERSION_2)?;
+        match (self.public_layout.is_empty(), &self.variable_ordering) {
+            (_, Some(_)) => w.write_all(ZOKRATES_VERSION_5)?,
+            (false, None) => w.write_all(ZOKRATES_VERSION_4)?,
+            (true, None) => w.write_all(ZOKRATES_VERSION_3)?,
+        }
         w.write_all(&T::id())?;
 
+        // leave room for the hash, which is only known after the last statement
+        let hash_position = w.stream_position()?;
+        w.write_all(&[0; 32])?;
+
         serde_cbor::to_writer(&mut w, &self.arguments)?;
         serde_cbor::to_writer(&mut w, &self.return_count)?;
-
-        let mut unconstrained_variable_detector = UnconstrainedVariableDetector::new(&self);
+        if !self.public_layout.is_empty() || self.variable_ordering.is_some() {
+            serde_cbor::to_writer(&mut w, &self.public_layout)?;
+        }
+        if let Some(variable_ordering) = &self.variable_ordering {
+            serde_cbor::to_writer(&mut w, variable_ordering)?;
+        }
 
         let statements = self.statements.into_iter();
 
@@ -80,16 +120,21 @@ impl<'ast, T: Field, I: IntoIterator<Item = Statement<'ast, T>>> ProgIterator<'a
             if matches!(s, Statement::Constraint(..)) {
                 count += 1;
             }
-            let s = unconstrained_variable_detector.fold_statement(s);
-            for s in s {
-                serde_cbor::to_writer(&mut w, &s)?;
-            }
+            unconstrained_variable_detector.visit_statement(&s)?;
+            hasher.update(&s);
+            serde_cbor::to_writer(&mut w, &s)?;
         }
 
         unconstrained_variable_detector
             .finalize()
-            .map(|_| count)
-            .map_err(|count| format!("Error: Found {} unconstrained variable(s)", count).into())
+            .map_err(|e| format!("Error: {}", e))?;
+
+        let end = w.stream_position()?;
+        w.seek(SeekFrom::Start(hash_position))?;
+        w.write_all(&hasher.finalize().0)?;
+        w.seek(SeekFrom::Start(end))?;
+
+        Ok(count)
     }
 }
 
@@ -114,125 +159,148 @@ impl<'de, R: Read>
         UnwrappedStreamDeserializer<'de, serde_cbor::de::IoRead<R>, Statement<'de, Bn128Field>>,
         UnwrappedStreamDeserializer<'de, serde_cbor::de::IoRead<R>, Statement<'de, Bls12_377Field>>,
         UnwrappedStreamDeserializer<'de, serde_cbor::de::IoRead<R>, Statement<'de, Bw6_761Field>>,
+        UnwrappedStreamDeserializer<'de, serde_cbor::de::IoRead<R>, Statement<'de, PallasField>>,
+        UnwrappedStreamDeserializer<'de, serde_cbor::de::IoRead<R>, Statement<'de, VestaField>>,
     >
 {
-    pub fn deserialize(mut r: R) -> Result<Self, String> {
+    pub fn deserialize(r: R) -> Result<Self, String> {
+        Self::deserialize_with_hash(r).map(|(p, _)| p)
+    }
+
+    /// Deserialize a program, returning the hash of its constraint system if the program was compiled with it
+    pub fn deserialize_with_hash(mut r: R) -> Result<(Self, Option<ProgramHash>), String> {
         // Check the magic number, `ZOK`
         let mut magic = [0; 4];
         r.read_exact(&mut magic)
             .map_err(|_| String::from("Cannot read magic number"))?;
 
         if &magic == ZOKRATES_MAGIC {
-            // Check the version, 2
             let mut version = [0; 4];
             r.read_exact(&mut version)
                 .map_err(|_| String::from("Cannot read version"))?;
 
-            if &version == ZOKRATES_VERSION_2 {
-                // Check the curve identifier, deserializing accordingly
-                let mut curve = [0; 4];
-                r.read_exact(&mut curve)
-                    .map_err(|_| String::from("Cannot read curve identifier"))?;
-
-                use serde::de::Deserializer;
-                let mut p = serde_cbor::Deserializer::from_reader(r);
-
-                struct ArgumentsVisitor;
-
-                impl<'de> serde::de::Visitor<'de> for ArgumentsVisitor {
-                    type Value = Vec<super::Parameter>;
-                    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                        formatter.write_str("seq of flat param")
-                    }
-
-                    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-                    where
-                        A: serde::de::SeqAccess<'de>,
-                    {
-                        let mut res = vec![];
-                        while let Some(e) = seq.next_element().unwrap() {
-                            res.push(e);
-                        }
-                        Ok(res)
-                    }
+            // Fail early on unsupported versions, before reading anything else
+            let reader = migrations::reader(version)?;
+
+            // Check the curve identifier, deserializing accordingly
+            let mut curve = [0; 4];
+            r.read_exact(&mut curve)
+                .map_err(|_| String::from("Cannot read curve identifier"))?;
+
+            let (
+                Header {
+                    hash,
+                    arguments,
+                    return_count,
+                    public_layout,
+                    variable_ordering,
+                },
+                p,
+            ) = reader(r)?;
+
+            match curve {
+                m if m == Bls12_381Field::id() => {
+                    let s = p.into_iter::<Statement<Bls12_381Field>>();
+
+                    Ok((
+                        ProgEnum::Bls12_381Program(
+                            ProgIterator::new(
+                                arguments,
+                                UnwrappedStreamDeserializer { s },
+                                return_count,
+                            )
+                            .with_public_layout(public_layout)
+                            .with_variable_ordering(variable_ordering),
+                        ),
+                        hash,
+                    ))
                 }
-
-                let arguments = p.deserialize_seq(ArgumentsVisitor).unwrap();
-
-                struct ReturnCountVisitor;
-
-                impl<'de> serde::de::Visitor<'de> for ReturnCountVisitor {
-                    type Value = usize;
-                    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                        formatter.write_str("usize")
-                    }
-
-                    fn visit_u32<E>(self, v: u32) -> Result<Self::Value, E>
-                    where
-                        E: serde::de::Error,
-                    {
-                        Ok(v as usize)
-                    }
-
-                    fn visit_u8<E>(self, v: u8) -> Result<Self::Value, E>
-                    where
-                        E: serde::de::Error,
-                    {
-                        Ok(v as usize)
-                    }
-
-                    fn visit_u16<E>(self, v: u16) -> Result<Self::Value, E>
-                    where
-                        E: serde::de::Error,
-                    {
-                        Ok(v as usize)
-                    }
+                m if m == Bn128Field::id() => {
+                    let s = p.into_iter::<Statement<Bn128Field>>();
+
+                    Ok((
+                        ProgEnum::Bn128Program(
+                            ProgIterator::new(
+                                arguments,
+                                UnwrappedStreamDeserializer { s },
+                                return_count,
+                            )
+                            .with_public_layout(public_layout)
+                            .with_variable_ordering(variable_ordering),
+                        ),
+                        hash,
+                    ))
                 }
-
-                let return_count = p.deserialize_u32(ReturnCountVisitor).unwrap();
-
-                match curve {
-                    m if m == Bls12_381Field::id() => {
-                        let s = p.into_iter::<Statement<Bls12_381Field>>();
-
-                        Ok(ProgEnum::Bls12_381Program(ProgIterator::new(
-                            arguments,
-                            UnwrappedStreamDeserializer { s },
-                            return_count,
-                        )))
-                    }
-                    m if m == Bn128Field::id() => {
-                        let s = p.into_iter::<Statement<Bn128Field>>();
-
-                        Ok(ProgEnum::Bn128Program(ProgIterator::new(
-                            arguments,
-                            UnwrappedStreamDeserializer { s },
-                            return_count,
-                        )))
-                    }
-                    m if m == Bls12_377Field::id() => {
-                        let s = p.into_iter::<Statement<Bls12_377Field>>();
-
-                        Ok(ProgEnum::Bls12_377Program(ProgIterator::new(
-                            arguments,
-                            UnwrappedStreamDeserializer { s },
-                            return_count,
-                        )))
-                    }
-                    m if m == Bw6_761Field::id() => {
-                        let s = p.into_iter::<Statement<Bw6_761Field>>();
-
-                        Ok(ProgEnum::Bw6_761Program(ProgIterator::new(
-                            arguments,
-                            UnwrappedStreamDeserializer { s },
-                            return_count,
-                        )))
-                    }
-                    _ => Err(String::from("Unknown curve identifier")),
+                m if m == Bls12_377Field::id() => {
+                    let s = p.into_iter::<Statement<Bls12_377Field>>();
+
+                    Ok((
+                        ProgEnum::Bls12_377Program(
+                            ProgIterator::new(
+                                arguments,
+                                UnwrappedStreamDeserializer { s },
+                                return_count,
+                            )
+                            .with_public_layout(public_layout)
+                            .with_variable_ordering(variable_ordering),
+                        ),
+                        hash,
+                    ))
+                }
+                m if m == Bw6_761Field::id() => {
+                    let s = p.into_iter::<Statement<Bw6_761Field>>();
+
+                    Ok((
+                        ProgEnum::Bw6_761Program(
+                            ProgIterator::new(
+                                arguments,
+                                UnwrappedStreamDeserializer { s },
+                                return_count,
+                            )
+                            .with_public_layout(public_layout)
+                            .with_variable_ordering(variable_ordering),
+                        ),
+                        hash,
+                    ))
+                }
+                m if m == PallasField::id() => {
+                    let s = p.into_iter::<Statement<PallasField>>();
+
+                    Ok((
+                        ProgEnum::PallasProgram(
+                            ProgIterator::new(
+                                arguments,
+                                UnwrappedStreamDeserializer { s },
+                                return_count,
+                            )
+                            .with_public_layout(public_layout)
+                            .with_variable_ordering(variable_ordering),
+                        ),
+                        hash,
+                    ))
+                }
+                m if m == VestaField::id() => {
+                    let s = p.into_iter::<Statement<VestaField>>();
+
+                    Ok((
+                        ProgEnum::VestaProgram(
+                            ProgIterator::new(
+                                arguments,
+                                UnwrappedStreamDeserializer { s },
+                                return_count,
+                            )
+                            .with_public_layout(public_layout)
+                            .with_variable_ordering(variable_ordering),
+                        ),
+                        hash,
+                    ))
                 }
-            } else {
-                Err(String::from("Unknown version"))
+                _ => Err(String::from("Unknown curve identifier")),
             }
+        } else if &magic == ZOKRATES_CONTAINER_MAGIC {
+            Err(String::from(
+                "The file contains several programs, one of them must be selected",
+            ))
         } else {
             Err(String::from("Wrong magic number"))
         }
@@ -243,8 +311,176 @@ impl<'de, R: Read>
 mod tests {
     use super::*;
     use crate::ir::Prog;
-    use std::io::{Cursor, Seek, SeekFrom};
-    use zokrates_field::{Bls12_381Field, Bn128Field};
+    use std::io::Cursor;
+    use zokrates_field::{Bls12_381Field, Bn128Field, PallasField, VestaField};
+
+    #[test]
+    fn ser_deser_v3_hash() {
+        let p: Prog<Bn128Field> = Prog::default();
+
+        let mut buffer = Cursor::new(vec![]);
+        p.clone().serialize(&mut buffer).unwrap();
+        buffer.seek(SeekFrom::Start(0)).unwrap();
+
+        let (deserialized_p, hash) = ProgEnum::deserialize_with_hash(buffer).unwrap();
+
+        assert_eq!(ProgEnum::Bn128Program(p.clone()), deserialized_p.collect());
+        assert_eq!(hash, Some(p.hash()));
+    }
+
+    #[test]
+    fn ser_deser_v4_public_layout() {
+        let p: Prog<Bn128Field> = Prog::new(
+            vec![
+                crate::ir::Parameter::public(crate::ir::Variable::new(0)),
+                crate::ir::Parameter::public(crate::ir::Variable::new(1)),
+            ],
+            vec![],
+            0,
+        )
+        .with_public_layout(vec![1, 0]);
+
+        let mut buffer = Cursor::new(vec![]);
+        p.clone().serialize(&mut buffer).unwrap();
+        assert_eq!(&buffer.get_ref()[4..8], ZOKRATES_VERSION_4);
+        buffer.seek(SeekFrom::Start(0)).unwrap();
+
+        let (deserialized_p, hash) = ProgEnum::deserialize_with_hash(buffer).unwrap();
+
+        assert_eq!(ProgEnum::Bn128Program(p.clone()), deserialized_p.collect());
+        assert_eq!(hash, Some(p.hash()));
+    }
+
+    #[test]
+    fn ser_deser_v5_variable_ordering() {
+        let p: Prog<Bn128Field> = Prog::new(
+            vec![crate::ir::Parameter::private(crate::ir::Variable::new(0))],
+            vec![
+                crate::ir::Statement::definition(
+                    crate::ir::Variable::new(2),
+                    crate::ir::Variable::new(0),
+                ),
+                crate::ir::Statement::definition(
+                    crate::ir::Variable::new(1),
+                    crate::ir::Variable::new(2),
+                ),
+                crate::ir::Statement::definition(
+                    crate::ir::Variable::public(0),
+                    crate::ir::Variable::new(1),
+                ),
+            ],
+            1,
+        );
+        let p = crate::ir::ordering::reorder(p);
+        assert!(p.variable_ordering.is_some());
+
+        let mut buffer = Cursor::new(vec![]);
+        p.clone().serialize(&mut buffer).unwrap();
+        assert_eq!(&buffer.get_ref()[4..8], ZOKRATES_VERSION_5);
+        buffer.seek(SeekFrom::Start(0)).unwrap();
+
+        let (deserialized_p, hash) = ProgEnum::deserialize_with_hash(buffer).unwrap();
+
+        assert_eq!(ProgEnum::Bn128Program(p.clone()), deserialized_p.collect());
+        assert_eq!(hash, Some(p.hash()));
+    }
+
+    #[test]
+    fn ser_deser_plugin_file() {
+        use crate::ir::{Directive, LinComb, Parameter, QuadComb, Solver, Statement, Variable};
+
+        // the name of the plugin is read back from a file, not borrowed from a buffer
+        let p: Prog<Bn128Field> = Prog::new(
+            vec![Parameter::private(Variable::new(0))],
+            vec![
+                Statement::Directive(Directive {
+                    inputs: vec![Variable::new(0).into()],
+                    outputs: vec![Variable::public(0)],
+                    solver: Solver::Plugin("isqrt".into(), 1, 1),
+                }),
+                Statement::Constraint(
+                    QuadComb::from_linear_combinations(
+                        LinComb::from(Variable::public(0)),
+                        LinComb::from(Variable::public(0)),
+                    ),
+                    LinComb::from(Variable::new(0)),
+                    None,
+                ),
+            ],
+            1,
+        );
+
+        let mut file = tempfile::tempfile().unwrap();
+        p.clone().serialize(&mut file).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let deserialized_p = ProgEnum::deserialize(file).unwrap();
+
+        assert_eq!(ProgEnum::Bn128Program(p), deserialized_p.collect());
+    }
+
+    #[test]
+    fn deser_v2() {
+        // programs compiled before the hash was introduced do not have one
+        let p: Prog<Bn128Field> = Prog::default();
+
+        let mut buffer = Cursor::new(vec![]);
+        buffer.write_all(ZOKRATES_MAGIC).unwrap();
+        buffer.write_all(&[0, 0, 0, 2]).unwrap();
+        buffer.write_all(&Bn128Field::id()).unwrap();
+        serde_cbor::to_writer(&mut buffer, &p.arguments).unwrap();
+        serde_cbor::to_writer(&mut buffer, &p.return_count).unwrap();
+        buffer.seek(SeekFrom::Start(0)).unwrap();
+
+        let (deserialized_p, hash) = ProgEnum::deserialize_with_hash(buffer).unwrap();
+
+        assert_eq!(ProgEnum::Bn128Program(p), deserialized_p.collect());
+        assert_eq!(hash, None);
+    }
+
+    #[test]
+    fn deser_v2_fixture() {
+        // `def main(private field a) -> field { return a; }`, as written by a compiler using version 2 of the format
+        let fixture = include_bytes!("../../tests/fixtures/identity.v2.out");
+
+        let (deserialized_p, hash) = ProgEnum::deserialize_with_hash(&fixture[..]).unwrap();
+
+        let expected: Prog<Bn128Field> = Prog::new(
+            vec![crate::ir::Parameter::private(crate::ir::Variable::new(0))],
+            vec![crate::ir::Statement::definition(
+                crate::ir::Variable::public(0),
+                crate::ir::LinComb::from(crate::ir::Variable::new(0)),
+            )],
+            1,
+        );
+
+        assert_eq!(ProgEnum::Bn128Program(expected), deserialized_p.collect());
+        assert_eq!(hash, None);
+    }
+
+    #[test]
+    fn deser_unsupported_version() {
+        let fixture = include_bytes!("../../tests/fixtures/unsupported.v1.out");
+
+        assert_eq!(
+            ProgEnum::deserialize(&fixture[..]).unwrap_err(),
+            "This program was compiled to version 1 of the format, which is no longer supported: this version of ZoKrates reads versions 2 to 5. Please recompile it"
+        );
+    }
+
+    #[test]
+    fn deser_newer_version() {
+        let mut buffer = Cursor::new(vec![]);
+        buffer.write_all(ZOKRATES_MAGIC).unwrap();
+        buffer.write_all(&[0, 0, 0, 6]).unwrap();
+        buffer.write_all(&Bn128Field::id()).unwrap();
+        buffer.seek(SeekFrom::Start(0)).unwrap();
+
+        assert_eq!(
+            ProgEnum::deserialize(buffer).unwrap_err(),
+            "This program was compiled to version 6 of the format, which is newer than version 5 read by this version of ZoKrates. Please upgrade ZoKrates"
+        );
+    }
 
     #[test]
     fn ser_deser_v2() {
@@ -273,5 +509,25 @@ mod tests {
         let deserialized_p = ProgEnum::deserialize(buffer).unwrap();
 
         assert_eq!(ProgEnum::Bls12_381Program(p), deserialized_p.collect());
+
+        let p: Prog<PallasField> = Prog::default();
+
+        let mut buffer = Cursor::new(vec![]);
+        p.clone().serialize(&mut buffer).unwrap();
+        buffer.seek(SeekFrom::Start(0)).unwrap();
+
+        let deserialized_p = ProgEnum::deserialize(buffer).unwrap();
+
+        assert_eq!(ProgEnum::PallasProgram(p), deserialized_p.collect());
+
+        let p: Prog<VestaField> = Prog::default();
+
+        let mut buffer = Cursor::new(vec![]);
+        p.clone().serialize(&mut buffer).unwrap();
+        buffer.seek(SeekFrom::Start(0)).unwrap();
+
+        let deserialized_p = ProgEnum::deserialize(buffer).unwrap();
+
+        assert_eq!(ProgEnum::VestaProgram(p), deserialized_p.collect());
     }
 }
@@ -0,0 +1,574 @@
+//! Check whether two compiled programs accept the same inputs and outputs, using an SMT solver.
+//!
+//! The arguments and return values of both programs are shared, and every other variable is renamed
+//! apart. The solver is then asked for inputs and outputs accepted by one program but not by the
+//! other, once in each direction. Rejection is expressed by quantifying over the variables of the
+//! rejecting program: variables which the program assigns with a definition are pinned to their
+//! value, which most solvers eliminate, while the outputs of directives are quantified over.
+
+use super::{LinComb, Prog, QuadComb, Statement, Variable};
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::process::{Command, Stdio};
+use zokrates_field::Field;
+
+/// Which of the two compared programs accepts the counterexample
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counterexample<T> {
+    /// The arguments, in the order of the program parameters
+    pub inputs: Vec<T>,
+    /// The return values
+    pub outputs: Vec<T>,
+    /// The only program accepting these inputs and outputs
+    pub accepted_by: Side,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EquivalenceResult<T> {
+    Equivalent,
+    Counterexample(Counterexample<T>),
+    /// The solver could not decide
+    Unknown(String),
+}
+
+/// A solver accepting SMT-LIB2 scripts
+pub trait SmtSolver {
+    /// Run `script` and return the output of the solver
+    fn run(&self, script: &str) -> Result<String, String>;
+}
+
+/// The z3 solver, run as an external process
+#[derive(Debug, Clone)]
+pub struct Z3 {
+    path: String,
+}
+
+impl Default for Z3 {
+    fn default() -> Self {
+        Z3 {
+            path: String::from("z3"),
+        }
+    }
+}
+
+impl Z3 {
+    pub fn with_path<S: Into<String>>(path: S) -> Self {
+        Z3 { path: path.into() }
+    }
+}
+
+impl SmtSolver for Z3 {
+    fn run(&self, script: &str) -> Result<String, String> {
+        let mut child = Command::new(&self.path)
+            .arg("-in")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Could not run `{}`: {}", self.path, e))?;
+
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(script.as_bytes())
+            .map_err(|e| e.to_string())?;
+
+        let output = child.wait_with_output().map_err(|e| e.to_string())?;
+
+        String::from_utf8(output.stdout).map_err(|e| e.to_string())
+    }
+}
+
+/// Check the equivalence of two programs using z3
+pub fn check_equivalence<T: Field>(
+    a: &Prog<T>,
+    b: &Prog<T>,
+) -> Result<EquivalenceResult<T>, String> {
+    check_equivalence_with(a, b, &Z3::default())
+}
+
+pub fn check_equivalence_with<T: Field, S: SmtSolver>(
+    a: &Prog<T>,
+    b: &Prog<T>,
+    solver: &S,
+) -> Result<EquivalenceResult<T>, String> {
+    if a.arguments.len() != b.arguments.len() || a.return_count != b.return_count {
+        return Err(format!(
+            "Programs have different signatures: {} argument(s) and {} return value(s) against {} argument(s) and {} return value(s)",
+            a.arguments.len(),
+            a.return_count,
+            b.arguments.len(),
+            b.return_count
+        ));
+    }
+
+    if let Some(index) = a
+        .arguments
+        .iter()
+        .zip(&b.arguments)
+        .position(|(a, b)| a.private != b.private)
+    {
+        return Err(format!(
+            "Argument {} is public in one program and private in the other",
+            index
+        ));
+    }
+
+    let mut unknown = vec![];
+
+    for (accepting, rejecting, side) in [(a, b, Side::Left), (b, a, Side::Right)] {
+        let script = query(accepting, rejecting);
+        let output = solver.run(&script)?;
+
+        match output.lines().next().map(str::trim) {
+            Some("unsat") => {}
+            Some("sat") => {
+                let shared = Shared::new(accepting);
+                let value = |name: &str| model_value::<T>(&output, name);
+
+                return Ok(EquivalenceResult::Counterexample(Counterexample {
+                    inputs: shared
+                        .inputs
+                        .iter()
+                        .map(|n| value(n))
+                        .collect::<Result<_, _>>()?,
+                    outputs: shared
+                        .outputs
+                        .iter()
+                        .map(|n| value(n))
+                        .collect::<Result<_, _>>()?,
+                    accepted_by: side,
+                }));
+            }
+            _ => unknown.push(output.trim().to_string()),
+        }
+    }
+
+    Ok(match unknown.is_empty() {
+        true => EquivalenceResult::Equivalent,
+        false => EquivalenceResult::Unknown(unknown.join("\n")),
+    })
+}
+
+/// The names of the variables shared by both programs
+struct Shared {
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+}
+
+impl Shared {
+    fn new<T>(p: &Prog<T>) -> Self {
+        Shared {
+            inputs: (0..p.arguments.len())
+                .map(|i| format!("in_{}", i))
+                .collect(),
+            outputs: (0..p.return_count).map(|i| format!("out_{}", i)).collect(),
+        }
+    }
+}
+
+/// Names the variables of one program, keeping the shared ones apart from the renamed ones
+struct Namer<'a, T> {
+    program: &'a Prog<'a, T>,
+    prefix: &'static str,
+}
+
+impl<'a, T: Field> Namer<'a, T> {
+    fn name(&self, v: &Variable) -> String {
+        if let Some(index) = self.program.arguments.iter().position(|p| p.id == *v) {
+            format!("|in_{}|", index)
+        } else if let Some(index) = self.program.returns().iter().position(|r| r == v) {
+            format!("|out_{}|", index)
+        } else {
+            format!("|{}.{}|", self.prefix, v)
+        }
+    }
+
+    fn is_shared(&self, v: &Variable) -> bool {
+        *v == Variable::one()
+            || self.program.arguments.iter().any(|p| p.id == *v)
+            || self.program.returns().contains(v)
+    }
+
+    fn lin(&self, l: &LinComb<T>) -> String {
+        let terms: Vec<_> =
+            l.0.iter()
+                .map(|(v, c)| match *v == Variable::one() {
+                    true => c.to_biguint().to_string(),
+                    false => format!("(* {} {})", self.name(v), c.to_biguint()),
+                })
+                .collect();
+
+        match terms.len() {
+            0 => "0".to_string(),
+            1 => terms[0].clone(),
+            _ => format!("(+ {})", terms.join(" ")),
+        }
+    }
+
+    fn quad(&self, q: &QuadComb<T>) -> String {
+        format!("(* {} {})", self.lin(&q.left), self.lin(&q.right))
+    }
+
+    fn constraint(&self, q: &QuadComb<T>, l: &LinComb<T>) -> String {
+        format!(
+            "(= (mod {} |~prime|) (mod {} |~prime|))",
+            self.quad(q),
+            self.lin(l)
+        )
+    }
+}
+
+struct Encoding {
+    /// Variables which are not shared
+    variables: Vec<String>,
+    /// Equalities pinning a variable to its value
+    definitions: Vec<String>,
+    /// All other constraints
+    checks: Vec<String>,
+}
+
+fn flatten<'a, 'ast, T>(
+    statements: &'a [Statement<'ast, T>],
+    res: &mut Vec<&'a Statement<'ast, T>>,
+) {
+    for s in statements {
+        match s {
+            Statement::Block(statements) => flatten(statements, res),
+            s => res.push(s),
+        }
+    }
+}
+
+fn encode<T: Field>(namer: &Namer<T>) -> Encoding {
+    let mut statements = vec![];
+    flatten(&namer.program.statements, &mut statements);
+
+    let mut encoding = Encoding {
+        variables: vec![],
+        definitions: vec![],
+        checks: vec![],
+    };
+
+    let mut known: BTreeSet<Variable> = BTreeSet::new();
+
+    let declare = |v: &Variable, encoding: &mut Encoding, known: &mut BTreeSet<Variable>| {
+        if !namer.is_shared(v) && known.insert(*v) {
+            encoding.variables.push(namer.name(v));
+        }
+    };
+
+    for s in statements {
+        match s {
+            Statement::Constraint(quad, lin, _) => {
+                // mirror the interpreter: a constraint with a single unassigned variable with
+                // coefficient one on its linear side assigns that variable
+                let assignee = match lin.0.as_slice() {
+                    [(v, c)] if *c == T::one() && !namer.is_shared(v) && !known.contains(v) => {
+                        Some(*v)
+                    }
+                    _ => None,
+                };
+
+                for v in quad.left.0.iter().chain(&quad.right.0).chain(&lin.0) {
+                    declare(&v.0, &mut encoding, &mut known);
+                }
+
+                match assignee {
+                    Some(v) => encoding.definitions.push(format!(
+                        "(= {} (mod {} |~prime|))",
+                        namer.name(&v),
+                        namer.quad(quad)
+                    )),
+                    None => encoding.checks.push(namer.constraint(quad, lin)),
+                }
+            }
+            Statement::Directive(d) => {
+                for v in &d.outputs {
+                    declare(v, &mut encoding, &mut known);
+                    let name = namer.name(v);
+                    encoding
+                        .checks
+                        .push(format!("(and (<= 0 {}) (< {} |~prime|))", name, name));
+                }
+            }
+            Statement::Log(..) | Statement::Block(..) => {}
+        }
+    }
+
+    encoding
+}
+
+fn conjunction(terms: &[String]) -> String {
+    match terms.len() {
+        0 => "true".to_string(),
+        _ => format!("(and {})", terms.join(" ")),
+    }
+}
+
+/// Build a script asking for inputs and outputs accepted by `accepting` but not by `rejecting`
+fn query<T: Field>(accepting: &Prog<T>, rejecting: &Prog<T>) -> String {
+    let shared = Shared::new(accepting);
+
+    let accepting = encode(&Namer {
+        program: accepting,
+        prefix: "a",
+    });
+    let rejecting = encode(&Namer {
+        program: rejecting,
+        prefix: "b",
+    });
+
+    let mut script = String::new();
+
+    writeln!(script, "; Auto generated by ZoKrates").unwrap();
+    writeln!(script, "(declare-const |~prime| Int)").unwrap();
+    writeln!(
+        script,
+        "(assert (= |~prime| {}))",
+        T::max_value().to_biguint() + 1usize
+    )
+    .unwrap();
+
+    for v in shared.inputs.iter().chain(&shared.outputs) {
+        writeln!(script, "(declare-const |{}| Int)", v).unwrap();
+        writeln!(script, "(assert (and (<= 0 |{}|) (< |{}| |~prime|)))", v, v).unwrap();
+    }
+
+    for v in &accepting.variables {
+        writeln!(script, "(declare-const {} Int)", v).unwrap();
+    }
+
+    for c in accepting.definitions.iter().chain(&accepting.checks) {
+        writeln!(script, "(assert {})", c).unwrap();
+    }
+
+    let rejected = format!(
+        "(=> {} (not {}))",
+        conjunction(&rejecting.definitions),
+        conjunction(&rejecting.checks)
+    );
+
+    match rejecting.variables.is_empty() {
+        true => writeln!(script, "(assert {})", rejected).unwrap(),
+        false => writeln!(
+            script,
+            "(assert (forall ({}) {}))",
+            rejecting
+                .variables
+                .iter()
+                .map(|v| format!("({} Int)", v))
+                .collect::<Vec<_>>()
+                .join(" "),
+            rejected
+        )
+        .unwrap(),
+    }
+
+    writeln!(script, "(check-sat)").unwrap();
+
+    if !shared.inputs.is_empty() || !shared.outputs.is_empty() {
+        writeln!(
+            script,
+            "(get-value ({}))",
+            shared
+                .inputs
+                .iter()
+                .chain(&shared.outputs)
+                .map(|v| format!("|{}|", v))
+                .collect::<Vec<_>>()
+                .join(" ")
+        )
+        .unwrap();
+    }
+
+    script
+}
+
+/// Read the value of `name` in the output of `get-value`, for example `((|in_0| 3) (|out_0| 9))`
+fn model_value<T: Field>(output: &str, name: &str) -> Result<T, String> {
+    let pattern = format!("|{}|", name);
+    let start = output
+        .find(&pattern)
+        .ok_or_else(|| format!("No value for `{}` in the solver model", name))?
+        + pattern.len();
+
+    let value: String = output[start..]
+        .trim_start()
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+
+    T::try_from_dec_str(&value)
+        .map_err(|_| format!("Invalid value for `{}` in the solver model", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::Parameter;
+    use std::cell::RefCell;
+    use zokrates_field::Bn128Field;
+
+    /// A solver returning predefined outputs, recording the scripts it receives
+    struct Scripted {
+        outputs: RefCell<Vec<String>>,
+        scripts: RefCell<Vec<String>>,
+    }
+
+    impl Scripted {
+        fn new(outputs: &[&str]) -> Self {
+            Scripted {
+                outputs: RefCell::new(outputs.iter().rev().map(|o| o.to_string()).collect()),
+                scripts: RefCell::new(vec![]),
+            }
+        }
+    }
+
+    impl SmtSolver for Scripted {
+        fn run(&self, script: &str) -> Result<String, String> {
+            self.scripts.borrow_mut().push(script.to_string());
+            Ok(self.outputs.borrow_mut().pop().unwrap())
+        }
+    }
+
+    // def main(private x, private y) -> ~out_0
+    //     ~out_0 = x * y
+    fn product() -> Prog<'static, Bn128Field> {
+        let x = Variable::new(0);
+        let y = Variable::new(1);
+
+        Prog::new(
+            vec![Parameter::private(x), Parameter::private(y)],
+            vec![Statement::definition(
+                Variable::public(0),
+                QuadComb::from_linear_combinations(x.into(), y.into()),
+            )],
+            1,
+        )
+    }
+
+    // def main(private x, private y) -> ~out_0
+    //     _5 = x * y
+    //     ~out_0 = _5
+    fn product_with_intermediate() -> Prog<'static, Bn128Field> {
+        let x = Variable::new(0);
+        let y = Variable::new(1);
+
+        Prog::new(
+            vec![Parameter::private(x), Parameter::private(y)],
+            vec![
+                Statement::definition(
+                    Variable::new(5),
+                    QuadComb::from_linear_combinations(x.into(), y.into()),
+                ),
+                Statement::definition(Variable::public(0), LinComb::from(Variable::new(5))),
+            ],
+            1,
+        )
+    }
+
+    // def main(private x, private y) -> ~out_0
+    //     ~out_0 = x * x
+    fn square() -> Prog<'static, Bn128Field> {
+        let x = Variable::new(0);
+        let y = Variable::new(1);
+
+        Prog::new(
+            vec![Parameter::private(x), Parameter::private(y)],
+            vec![Statement::definition(
+                Variable::public(0),
+                QuadComb::from_linear_combinations(x.into(), x.into()),
+            )],
+            1,
+        )
+    }
+
+    #[test]
+    fn query_renames_apart() {
+        let solver = Scripted::new(&["unsat", "unsat"]);
+
+        assert_eq!(
+            check_equivalence_with(&product(), &product_with_intermediate(), &solver),
+            Ok(EquivalenceResult::Equivalent)
+        );
+
+        let scripts = solver.scripts.borrow();
+        // the intermediate variable is universally quantified when rejecting...
+        assert!(scripts[0].contains("(forall ((|b._5| Int))"));
+        assert!(scripts[0].contains("(= |b._5| (mod (* (* |in_0| 1) (* |in_1| 1)) |~prime|))"));
+        // ...and existentially quantified when accepting
+        assert!(scripts[1].contains("(declare-const |a._5| Int)"));
+        assert!(scripts[1].contains("(get-value (|in_0| |in_1| |out_0|))"));
+    }
+
+    #[test]
+    fn counterexample_from_model() {
+        let solver = Scripted::new(&["unsat", "sat\n((|in_0| 2)\n (|in_1| 3)\n (|out_0| 4))"]);
+
+        assert_eq!(
+            check_equivalence_with(&product(), &square(), &solver),
+            Ok(EquivalenceResult::Counterexample(Counterexample {
+                inputs: vec![Bn128Field::from(2), Bn128Field::from(3)],
+                outputs: vec![Bn128Field::from(4)],
+                accepted_by: Side::Right
+            }))
+        );
+    }
+
+    #[test]
+    fn unknown() {
+        let solver = Scripted::new(&["unknown", "unsat"]);
+
+        assert_eq!(
+            check_equivalence_with(&product(), &square(), &solver),
+            Ok(EquivalenceResult::Unknown("unknown".into()))
+        );
+    }
+
+    #[test]
+    fn different_signatures() {
+        let mut other = square();
+        other.arguments[1] = Parameter::public(Variable::new(1));
+
+        assert!(check_equivalence_with(&product(), &other, &Scripted::new(&[])).is_err());
+    }
+
+    #[test]
+    #[ignore]
+    fn z3_equivalent() {
+        assert_eq!(
+            check_equivalence(&product(), &product_with_intermediate()),
+            Ok(EquivalenceResult::Equivalent)
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn z3_counterexample() {
+        let res = check_equivalence(&product(), &square()).unwrap();
+
+        match res {
+            EquivalenceResult::Counterexample(c) => {
+                let (x, y, out) = (
+                    c.inputs[0].clone(),
+                    c.inputs[1].clone(),
+                    c.outputs[0].clone(),
+                );
+                assert_ne!(x, y);
+                match c.accepted_by {
+                    Side::Left => assert!(out == x.clone() * y && out != x.clone() * x),
+                    Side::Right => assert!(out == x.clone() * x && out != x * y),
+                }
+            }
+            r => panic!("expected a counterexample, found {:?}", r),
+        }
+    }
+}
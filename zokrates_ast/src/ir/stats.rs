@@ -0,0 +1,212 @@
+//! Statistics on the constraint system of a program, collected one statement at a time so that the program does not
+//! have to be kept in memory.
+
+use super::{LinComb, Statement};
+use crate::common::{Parameter, Variable};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::ops::Add;
+
+/// The ranges of numbers of terms the linear combinations of the constraints are counted by
+pub const DENSITY_BUCKETS: [&str; 7] = ["0", "1", "2", "3-4", "5-8", "9-16", "17+"];
+
+fn density_bucket(terms: usize) -> usize {
+    match terms {
+        0..=2 => terms,
+        3..=4 => 3,
+        5..=8 => 4,
+        9..=16 => 5,
+        _ => 6,
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConstraintSystemStats {
+    pub constraints: usize,
+    pub directives: usize,
+    pub logs: usize,
+    /// The number of distinct variables, `~one` excluded
+    pub variables: usize,
+    /// The number of linear combinations in the constraints, three per constraint, by number of terms, following
+    /// `DENSITY_BUCKETS`
+    pub density: [usize; 7],
+}
+
+/// The difference between two `ConstraintSystemStats`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatsDelta {
+    pub constraints: isize,
+    pub directives: isize,
+    pub logs: isize,
+    pub variables: isize,
+    pub density: [isize; 7],
+}
+
+impl ConstraintSystemStats {
+    /// The change from `self` to `after`
+    pub fn delta(&self, after: &Self) -> StatsDelta {
+        let diff = |before: usize, after: usize| after as isize - before as isize;
+
+        let mut density = [0; 7];
+        for (i, d) in density.iter_mut().enumerate() {
+            *d = diff(self.density[i], after.density[i]);
+        }
+
+        StatsDelta {
+            constraints: diff(self.constraints, after.constraints),
+            directives: diff(self.directives, after.directives),
+            logs: diff(self.logs, after.logs),
+            variables: diff(self.variables, after.variables),
+            density,
+        }
+    }
+}
+
+impl StatsDelta {
+    pub fn is_zero(&self) -> bool {
+        *self == StatsDelta::default()
+    }
+}
+
+impl Add for StatsDelta {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self.constraints += other.constraints;
+        self.directives += other.directives;
+        self.logs += other.logs;
+        self.variables += other.variables;
+        for (d, o) in self.density.iter_mut().zip(other.density) {
+            *d += o;
+        }
+        self
+    }
+}
+
+/// Collects the statistics of a program as its statements are visited
+///
+/// Only the set of variables seen so far is kept, the statements themselves are not.
+#[derive(Debug, Clone, Default)]
+pub struct StatsCollector {
+    stats: ConstraintSystemStats,
+    variables: BTreeSet<Variable>,
+}
+
+impl StatsCollector {
+    pub fn new(arguments: &[Parameter]) -> Self {
+        StatsCollector {
+            stats: ConstraintSystemStats::default(),
+            variables: arguments.iter().map(|a| a.id).collect(),
+        }
+    }
+
+    pub fn update<T>(&mut self, statement: &Statement<T>) {
+        match statement {
+            Statement::Block(statements) => {
+                for s in statements {
+                    self.update(s);
+                }
+            }
+            Statement::Constraint(quad, lin, _) => {
+                self.stats.constraints += 1;
+                for l in [&quad.left, &quad.right, lin] {
+                    self.stats.density[density_bucket(l.0.len())] += 1;
+                    self.linear_combination(l);
+                }
+            }
+            Statement::Directive(d) => {
+                self.stats.directives += 1;
+                for input in &d.inputs {
+                    self.linear_combination(&input.left);
+                    self.linear_combination(&input.right);
+                }
+                self.variables.extend(d.outputs.iter().cloned());
+            }
+            Statement::Log(..) => {
+                self.stats.logs += 1;
+            }
+        }
+    }
+
+    /// The statistics of the statements visited so far
+    pub fn stats(&self) -> ConstraintSystemStats {
+        ConstraintSystemStats {
+            variables: self.variables.len(),
+            ..self.stats
+        }
+    }
+
+    fn linear_combination<T>(&mut self, l: &LinComb<T>) {
+        self.variables.extend(
+            l.0.iter()
+                .map(|(v, _)| *v)
+                .filter(|v| *v != Variable::one()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::QuadComb;
+    use zokrates_field::Bn128Field;
+
+    #[test]
+    fn collect() {
+        let mut collector = StatsCollector::new(&[Parameter::private(Variable::new(0))]);
+
+        let statements: Vec<Statement<Bn128Field>> = vec![
+            Statement::Block(vec![Statement::constraint(
+                Variable::new(0),
+                Variable::new(1),
+            )]),
+            Statement::constraint(
+                QuadComb::from_linear_combinations(
+                    LinComb::from(Variable::new(0)) + LinComb::from(Variable::new(1)),
+                    LinComb::one(),
+                ),
+                Variable::public(0),
+            ),
+            Statement::Log("{}".into(), vec![]),
+        ];
+
+        for s in &statements {
+            collector.update(s);
+        }
+
+        let stats = collector.stats();
+        assert_eq!(stats.constraints, 2);
+        assert_eq!(stats.logs, 1);
+        // `~one` is not counted
+        assert_eq!(stats.variables, 3);
+        assert_eq!(stats.density, [0, 5, 1, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn deltas() {
+        let before = ConstraintSystemStats {
+            constraints: 3,
+            variables: 4,
+            density: [1, 6, 2, 0, 0, 0, 0],
+            ..ConstraintSystemStats::default()
+        };
+        let middle = ConstraintSystemStats {
+            constraints: 2,
+            variables: 3,
+            density: [1, 4, 1, 0, 0, 0, 0],
+            ..ConstraintSystemStats::default()
+        };
+        let after = ConstraintSystemStats {
+            constraints: 2,
+            variables: 2,
+            density: [0, 4, 1, 1, 0, 0, 0],
+            ..ConstraintSystemStats::default()
+        };
+
+        assert_eq!(
+            before.delta(&middle) + middle.delta(&after),
+            before.delta(&after)
+        );
+        assert!(after.delta(&after).is_zero());
+    }
+}
@@ -0,0 +1,244 @@
+//! Compute which constraints of a program depend on a set of variables, typically secret inputs.
+//!
+//! Taint flows from the inputs to the outputs of directives, and between all variables appearing
+//! in the same constraint. The analysis is run to a fixpoint, so a constraint reported as
+//! independent shares no variable, other than `~one`, with anything the sources can influence.
+
+use super::visitor::{self, Visitor};
+use super::{Directive, Prog, RuntimeError, Statement, Variable};
+use crate::common::SourceMetadata;
+use std::collections::BTreeSet;
+use zokrates_field::Field;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaintedConstraint {
+    /// The index of the constraint, in the order of `Prog::constraint_count`
+    pub index: usize,
+    pub span: Option<SourceMetadata>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TaintReport {
+    /// The variables which depend on the sources, including the sources themselves
+    pub variables: BTreeSet<Variable>,
+    /// The constraints involving at least one tainted variable
+    pub dependent: Vec<TaintedConstraint>,
+    /// The indices of the constraints which are provably independent of the sources
+    pub independent: Vec<usize>,
+}
+
+pub fn taint<T: Field, I: IntoIterator<Item = Variable>>(
+    program: &Prog<T>,
+    sources: I,
+) -> TaintReport {
+    let mut propagator = Propagator {
+        tainted: sources.into_iter().collect(),
+        changed: true,
+    };
+
+    while propagator.changed {
+        propagator.changed = false;
+        propagator.visit_module(program);
+    }
+
+    let mut reporter = Reporter {
+        report: TaintReport {
+            variables: propagator.tainted,
+            ..TaintReport::default()
+        },
+        index: 0,
+    };
+
+    reporter.visit_module(program);
+
+    reporter.report
+}
+
+/// Collect the variables used in a statement or a program, except `~one`
+#[derive(Default)]
+pub(crate) struct VariableCollector(pub(crate) BTreeSet<Variable>);
+
+impl<T: Field> Visitor<T> for VariableCollector {
+    fn visit_variable(&mut self, v: &Variable) {
+        if *v != Variable::one() {
+            self.0.insert(*v);
+        }
+    }
+}
+
+fn variables<T: Field>(s: &Statement<T>) -> BTreeSet<Variable> {
+    let mut collector = VariableCollector::default();
+    collector.visit_statement(s);
+    collector.0
+}
+
+fn span(error: &Option<RuntimeError>) -> Option<SourceMetadata> {
+    match error {
+        Some(RuntimeError::SourceAssertion(metadata))
+        | Some(RuntimeError::SourceAssemblyConstraint(metadata))
+        | Some(RuntimeError::DivisionByZero(metadata)) => Some(metadata.clone()),
+        _ => None,
+    }
+}
+
+struct Propagator {
+    tainted: BTreeSet<Variable>,
+    changed: bool,
+}
+
+impl Propagator {
+    fn taint_all(&mut self, variables: BTreeSet<Variable>) {
+        for v in variables {
+            self.changed |= self.tainted.insert(v);
+        }
+    }
+}
+
+impl<T: Field> Visitor<T> for Propagator {
+    fn visit_statement(&mut self, s: &Statement<T>) {
+        match s {
+            Statement::Constraint(..) => {
+                let variables = variables(s);
+                if !variables.is_disjoint(&self.tainted) {
+                    self.taint_all(variables);
+                }
+            }
+            Statement::Log(..) => {}
+            _ => visitor::visit_statement(self, s),
+        }
+    }
+
+    fn visit_directive(&mut self, d: &Directive<T>) {
+        let mut inputs = VariableCollector::default();
+        for i in &d.inputs {
+            inputs.visit_quadratic_combination(i);
+        }
+
+        if !inputs.0.is_disjoint(&self.tainted) {
+            self.taint_all(d.outputs.iter().copied().collect());
+        }
+    }
+}
+
+struct Reporter {
+    report: TaintReport,
+    index: usize,
+}
+
+impl<T: Field> Visitor<T> for Reporter {
+    fn visit_statement(&mut self, s: &Statement<T>) {
+        match s {
+            Statement::Constraint(_, _, error) => {
+                if variables(s).is_disjoint(&self.report.variables) {
+                    self.report.independent.push(self.index);
+                } else {
+                    self.report.dependent.push(TaintedConstraint {
+                        index: self.index,
+                        span: span(error),
+                    });
+                }
+                self.index += 1;
+            }
+            _ => visitor::visit_statement(self, s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{LinComb, Parameter, QuadComb, Solver};
+    use crate::untyped::Position;
+    use zokrates_field::Bn128Field;
+
+    // def main(private x, private y) -> (~out_0, ~out_1)
+    //     _2 = x * x
+    //     _3 = y * y
+    //     ~out_0 = _2
+    //     ~out_1 = _3
+    //     # _4 = Bits(x)
+    //     _4 * _4 == _4 (assertion at main.zok:5:5)
+    fn program() -> Prog<'static, Bn128Field> {
+        let x = Variable::new(0);
+        let y = Variable::new(1);
+
+        Prog::new(
+            vec![Parameter::private(x), Parameter::private(y)],
+            vec![
+                Statement::definition(
+                    Variable::new(2),
+                    QuadComb::from_linear_combinations(x.into(), x.into()),
+                ),
+                Statement::definition(
+                    Variable::new(3),
+                    QuadComb::from_linear_combinations(y.into(), y.into()),
+                ),
+                Statement::definition(Variable::public(0), LinComb::from(Variable::new(2))),
+                Statement::definition(Variable::public(1), LinComb::from(Variable::new(3))),
+                Statement::Directive(Directive {
+                    inputs: vec![x.into()],
+                    outputs: vec![Variable::new(4)],
+                    solver: Solver::Bits(1),
+                }),
+                Statement::Constraint(
+                    QuadComb::from_linear_combinations(
+                        Variable::new(4).into(),
+                        Variable::new(4).into(),
+                    ),
+                    Variable::new(4).into(),
+                    Some(RuntimeError::SourceAssertion(SourceMetadata::new(
+                        "main.zok".into(),
+                        Position { line: 5, col: 5 },
+                    ))),
+                ),
+            ],
+            2,
+        )
+    }
+
+    #[test]
+    fn half_dependent() {
+        let report = taint(&program(), vec![Variable::new(0)]);
+
+        assert_eq!(
+            report.dependent,
+            vec![
+                TaintedConstraint {
+                    index: 0,
+                    span: None
+                },
+                TaintedConstraint {
+                    index: 2,
+                    span: None
+                },
+                TaintedConstraint {
+                    index: 4,
+                    span: Some(SourceMetadata::new(
+                        "main.zok".into(),
+                        Position { line: 5, col: 5 }
+                    ))
+                },
+            ]
+        );
+        assert_eq!(report.independent, vec![1, 3]);
+        assert_eq!(
+            report.variables,
+            vec![
+                Variable::new(0),
+                Variable::new(2),
+                Variable::public(0),
+                Variable::new(4)
+            ]
+            .into_iter()
+            .collect()
+        );
+    }
+
+    #[test]
+    fn no_sources() {
+        let report = taint(&program(), vec![]);
+
+        assert!(report.dependent.is_empty());
+        assert_eq!(report.independent, vec![0, 1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,614 @@
+//! A canonical textual format for programs, which can be printed and parsed back
+//!
+//! ```text
+//! // curve: bn128
+//! def main(_0, private _1) -> (~out_0) {
+//! 	# _2 = Div((1 * ~one) * (1 * _0), (1 * ~one) * (1 * _1))
+//! 	(1 * _1) * (1 * _2) == 1 * _0 // {"SourceAssertion":{...}}
+//! 	log({"parts":["x = ",""],"specs":["Display"]}, {"type":"field"}: [1 * _2])
+//! 	(1 * ~one) * (1 * _2) == 1 * ~out_0
+//! }
+//! ```
+//!
+//! Linear combinations are printed in canonical form, and the metadata of constraints, the format strings and types
+//! of logs and the functions of `Zir` solvers are printed as JSON. Printing a parsed program gives back the same
+//! text, so that programs can be compared line by line.
+
+use super::*;
+use serde::Deserialize;
+use zokrates_field::Field;
+
+/// Displays a program in the textual format
+pub struct TextDisplay<'a, 'ast, T>(pub &'a Prog<'ast, T>);
+
+impl<'ast, T: Field> fmt::Display for TextDisplay<'_, 'ast, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "// curve: {}", T::name())?;
+        writeln!(
+            f,
+            "def main({}) -> ({}) {{",
+            self.0
+                .arguments
+                .iter()
+                .map(|a| a.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.0
+                .returns()
+                .iter()
+                .map(|r| r.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        for s in &self.0.statements {
+            write_statement(f, s)?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+fn json<V: Serialize>(value: &V) -> String {
+    serde_json::to_string(value).unwrap()
+}
+
+fn lin<T: Field>(l: &LinComb<T>) -> String {
+    l.clone().reduce().to_string()
+}
+
+fn quad<T: Field>(q: &QuadComb<T>) -> String {
+    format!("({}) * ({})", lin(&q.left), lin(&q.right))
+}
+
+fn write_statement<T: Field>(f: &mut fmt::Formatter, s: &Statement<T>) -> fmt::Result {
+    match s {
+        Statement::Block(statements) => statements.iter().try_for_each(|s| write_statement(f, s)),
+        Statement::Constraint(q, l, error) => writeln!(
+            f,
+            "\t{} == {}{}",
+            quad(q),
+            lin(l),
+            error
+                .as_ref()
+                .map(|e| format!(" // {}", json(e)))
+                .unwrap_or_default()
+        ),
+        Statement::Directive(d) => writeln!(
+            f,
+            "\t# {} = {}({})",
+            d.outputs
+                .iter()
+                .map(|o| o.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            match &d.solver {
+                Solver::Zir(function) => format!("Zir({})", json(function)),
+                Solver::Plugin(name, inputs, outputs) =>
+                    format!("Plugin({}, {}, {})", json(name), inputs, outputs),
+                solver => solver.to_string(),
+            },
+            d.inputs.iter().map(quad).collect::<Vec<_>>().join(", ")
+        ),
+        Statement::Log(format_string, expressions) => writeln!(
+            f,
+            "\tlog({}{})",
+            json(format_string),
+            expressions
+                .iter()
+                .map(|(ty, e)| format!(
+                    ", {}: [{}]",
+                    json(ty),
+                    e.iter().map(lin).collect::<Vec<_>>().join(", ")
+                ))
+                .collect::<String>()
+        ),
+    }
+}
+
+/// An error found while parsing a program in the textual format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextError {
+    /// The line of the error, starting at 1
+    pub line: usize,
+    /// The column of the error, starting at 1
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TextError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// A position in a line of the text
+struct Cursor<'ast> {
+    line: usize,
+    source: &'ast str,
+    offset: usize,
+}
+
+impl<'ast> Cursor<'ast> {
+    fn new(line: usize, source: &'ast str) -> Self {
+        Cursor {
+            line,
+            source,
+            offset: 0,
+        }
+    }
+
+    fn rest(&self) -> &'ast str {
+        &self.source[self.offset..]
+    }
+
+    fn error<U>(&self, message: String) -> Result<U, TextError> {
+        Err(TextError {
+            line: self.line,
+            column: self.source[..self.offset].chars().count() + 1,
+            message,
+        })
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        match self.rest().starts_with(token) {
+            true => {
+                self.offset += token.len();
+                true
+            }
+            false => false,
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), TextError> {
+        match self.eat(token) {
+            true => Ok(()),
+            false => self.error(format!("Expected `{}`", token)),
+        }
+    }
+
+    fn end(&self) -> Result<(), TextError> {
+        match self.rest() {
+            "" => Ok(()),
+            rest => self.error(format!("Unexpected `{}`", rest)),
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'ast str {
+        let rest = self.rest();
+        let len = rest.find(|c| !predicate(c)).unwrap_or(rest.len());
+        self.offset += len;
+        &rest[..len]
+    }
+
+    fn number(&mut self) -> Result<usize, TextError> {
+        let start = self.offset;
+        match self.take_while(|c| c.is_ascii_digit()).parse() {
+            Ok(n) => Ok(n),
+            Err(_) => {
+                self.offset = start;
+                self.error("Expected a number".to_string())
+            }
+        }
+    }
+
+    fn json<V: Deserialize<'ast>>(&mut self) -> Result<V, TextError> {
+        let mut values = serde_json::Deserializer::from_str(self.rest()).into_iter::<V>();
+        match values.next() {
+            Some(Ok(value)) => {
+                self.offset += values.byte_offset();
+                Ok(value)
+            }
+            Some(Err(e)) => {
+                let rest = self.rest();
+                self.offset += rest
+                    .char_indices()
+                    .nth(e.column().saturating_sub(1))
+                    .map(|(i, _)| i)
+                    .unwrap_or(rest.len());
+                self.error(format!("Invalid JSON: {}", e))
+            }
+            None => self.error("Expected a JSON value".to_string()),
+        }
+    }
+
+    fn variable(&mut self) -> Result<Variable, TextError> {
+        if self.eat("~one") {
+            Ok(Variable::one())
+        } else if self.eat("~out_") {
+            self.number().map(Variable::public)
+        } else if self.eat("_") {
+            self.number().map(Variable::new)
+        } else {
+            self.error("Expected a variable".to_string())
+        }
+    }
+
+    fn coefficient<T: Field>(&mut self) -> Result<T, TextError> {
+        let negative = self.eat("(-");
+        let start = self.offset;
+        let value = match T::try_from_dec_str(self.take_while(|c| c.is_ascii_digit())) {
+            Ok(value) => value,
+            Err(_) => {
+                self.offset = start;
+                return self.error("Expected a field element".to_string());
+            }
+        };
+        match negative {
+            true => {
+                self.expect(")")?;
+                Ok(T::zero() - value)
+            }
+            false => Ok(value),
+        }
+    }
+
+    fn lin<T: Field>(&mut self) -> Result<LinComb<T>, TextError> {
+        let rest = self.rest();
+        if rest.starts_with('0') && !rest[1..].starts_with(|c: char| c.is_ascii_digit() || c == ' ')
+        {
+            self.offset += 1;
+            return Ok(LinComb::zero());
+        }
+
+        let mut terms = vec![];
+        loop {
+            let coefficient = self.coefficient()?;
+            self.expect(" * ")?;
+            terms.push((self.variable()?, coefficient));
+            if !self.eat(" + ") {
+                return Ok(terms.into());
+            }
+        }
+    }
+
+    fn quad<T: Field>(&mut self) -> Result<QuadComb<T>, TextError> {
+        self.expect("(")?;
+        let left = self.lin()?;
+        self.expect(") * (")?;
+        let right = self.lin()?;
+        self.expect(")")?;
+        Ok(QuadComb::from_linear_combinations(left, right))
+    }
+
+    /// A list of `item`s separated by `, `, ending before `end`
+    fn list<U>(
+        &mut self,
+        end: &str,
+        mut item: impl FnMut(&mut Self) -> Result<U, TextError>,
+    ) -> Result<Vec<U>, TextError> {
+        let mut items = vec![];
+        if self.rest().starts_with(end) {
+            return Ok(items);
+        }
+        loop {
+            items.push(item(self)?);
+            if !self.eat(", ") {
+                return Ok(items);
+            }
+        }
+    }
+
+    fn solver<T: Field>(&mut self) -> Result<Solver<'ast, T>, TextError> {
+        let start = self.offset;
+        let kind = self.take_while(|c| c.is_ascii_alphanumeric());
+        if let Some(solver) = Solver::without_parameters()
+            .into_iter()
+            .find(|solver| solver.kind() == kind)
+        {
+            return Ok(solver);
+        }
+        let solver = match kind {
+            "Bits" => {
+                self.expect("(")?;
+                let bit_width = self.number()?;
+                self.expect(")")?;
+                Solver::Bits(bit_width)
+            }
+            "Sample" => {
+                self.expect("(")?;
+                let input_count = self.number()?;
+                self.expect(", ")?;
+                let index = self.number()?;
+                self.expect(")")?;
+                Solver::Sample(input_count, index)
+            }
+            "Zir" => {
+                self.expect("(")?;
+                let function = self.json()?;
+                self.expect(")")?;
+                Solver::Zir(function)
+            }
+            "Plugin" => {
+                self.expect("(")?;
+                let name = self.json()?;
+                self.expect(", ")?;
+                let input_count = self.number()?;
+                self.expect(", ")?;
+                let output_count = self.number()?;
+                self.expect(")")?;
+                Solver::Plugin(name, input_count, output_count)
+            }
+            #[cfg(feature = "ark")]
+            "SnarkVerifyBls12377" => {
+                self.expect("(")?;
+                let n = self.number()?;
+                self.expect(")")?;
+                Solver::SnarkVerifyBls12377(n)
+            }
+            name => {
+                self.offset = start;
+                return self.error(format!("Unknown solver `{}`", name));
+            }
+        };
+        Ok(solver)
+    }
+
+    fn statement<T: Field>(&mut self) -> Result<Statement<'ast, T>, TextError> {
+        if self.eat("# ") {
+            let outputs = self.list(" = ", Self::variable)?;
+            self.expect(" = ")?;
+            let solver = self.solver()?;
+            self.expect("(")?;
+            let inputs = self.list(")", Self::quad::<T>)?;
+            self.expect(")")?;
+            self.end()?;
+            Ok(Statement::Directive(Directive {
+                inputs,
+                outputs,
+                solver,
+            }))
+        } else if self.eat("log(") {
+            let format_string = self.json()?;
+            let mut expressions = vec![];
+            while self.eat(", ") {
+                let ty = self.json()?;
+                self.expect(": [")?;
+                let e = self.list("]", Self::lin::<T>)?;
+                self.expect("]")?;
+                expressions.push((ty, e));
+            }
+            self.expect(")")?;
+            self.end()?;
+            Ok(Statement::Log(format_string, expressions))
+        } else {
+            let quad = self.quad()?;
+            self.expect(" == ")?;
+            let lin = self.lin()?;
+            let error = match self.eat(" // ") {
+                true => Some(self.json()?),
+                false => None,
+            };
+            self.end()?;
+            Ok(Statement::Constraint(quad, lin, error))
+        }
+    }
+}
+
+impl<'ast, T: Field> Prog<'ast, T> {
+    /// Parse a program printed by `TextDisplay`
+    pub fn from_text(text: &'ast str) -> Result<Self, TextError> {
+        let mut lines = text.lines().enumerate().map(|(i, l)| Cursor::new(i + 1, l));
+        let eof = TextError {
+            line: text.lines().count() + 1,
+            column: 1,
+            message: "Unexpected end of input".to_string(),
+        };
+
+        let mut header = lines.next().ok_or_else(|| eof.clone())?;
+        header.expect("// curve: ")?;
+        if header.rest() != T::name() {
+            return header.error(format!(
+                "Expected a program for curve {}, found {}",
+                T::name(),
+                header.rest()
+            ));
+        }
+
+        let mut signature = lines.next().ok_or_else(|| eof.clone())?;
+        signature.expect("def main(")?;
+        let arguments = signature.list(")", |c| {
+            let private = c.eat("private ");
+            c.variable().map(|id| Parameter { id, private })
+        })?;
+        signature.expect(") -> (")?;
+        let mut return_count = 0;
+        signature.list(")", |c| {
+            let expected = Variable::public(return_count);
+            match c.eat(&expected.to_string()) {
+                true => {
+                    return_count += 1;
+                    Ok(())
+                }
+                false => c.error(format!("Expected `{}`", expected)),
+            }
+        })?;
+        signature.expect(") {")?;
+        signature.end()?;
+
+        let mut statements = vec![];
+        loop {
+            let mut line = lines.next().ok_or_else(|| eof.clone())?;
+            if line.eat("}") {
+                line.end()?;
+                break;
+            }
+            line.expect("\t")?;
+            statements.push(line.statement()?);
+        }
+
+        if let Some(line) = lines.next() {
+            return line.error("Unexpected content after the program".to_string());
+        }
+
+        Ok(Prog::new(arguments, statements, return_count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{FormatSpec, SourceMetadata};
+    use crate::typed::ConcreteType;
+    use crate::untyped::Position;
+    use crate::zir::types::Signature;
+    use crate::zir::ZirFunction;
+    use zokrates_field::Bn128Field;
+
+    const FIXTURES: [&str; 2] = [
+        "// curve: bn128
+def main() -> () {
+}
+",
+        r#"// curve: bn128
+def main(_0, private _1) -> (~out_0, ~out_1) {
+	# _2 = Div((1 * ~one) * (1 * _0), (1 * ~one) * (1 * _1))
+	# _3, _4 = Bits(2)((1 * ~one) * ((-3) * _0 + 1 * _2))
+	# _5 = Plugin("isqrt", 1, 1)((1 * ~one) * (1 * _0))
+	(1 * _1) * (1 * _2) == 1 * _0 // {"SourceAssertion":{"file":"main.zok","position":{"line":2,"col":5},"message":null}}
+	(1 * _3) * (1 * _3) == 1 * _3 // "Bitness"
+	(0) * (1 * _4) == 0
+	log({"parts":["x = ",""],"specs":["Hex"]}, {"type":"u8"}: [1 * _2], {"type":"array","components":{"size":2,"type":"field"}}: [1 * _0, (-1) * _1])
+	(1 * ~one) * (1 * _2) == 1 * ~out_0
+	(1 * ~one) * (2 * _3 + 1 * _4) == 1 * ~out_1
+}
+"#,
+    ];
+
+    #[test]
+    fn round_trip_fixtures() {
+        for fixture in FIXTURES {
+            let program = Prog::<Bn128Field>::from_text(fixture).unwrap();
+            assert_eq!(TextDisplay(&program).to_string(), fixture);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let program: Prog<Bn128Field> = Prog::new(
+            vec![Parameter::private(Variable::new(0))],
+            vec![
+                Statement::Directive(Directive {
+                    inputs: vec![Variable::new(0).into()],
+                    outputs: vec![Variable::new(1), Variable::new(2)],
+                    solver: Solver::ConditionEq,
+                }),
+                Statement::Constraint(
+                    QuadComb::from_linear_combinations(
+                        LinComb::from(Variable::new(0)) + LinComb::from(Variable::new(0)),
+                        LinComb::summand(
+                            Bn128Field::from(0) - Bn128Field::from(1),
+                            Variable::new(2),
+                        ),
+                    ),
+                    Variable::new(1).into(),
+                    Some(RuntimeError::SourceAssertion(
+                        SourceMetadata::new("main.zok".to_string(), Position { line: 3, col: 9 })
+                            .message(Some("\"quoted\"".to_string())),
+                    )),
+                ),
+                Statement::Log(
+                    FormatString {
+                        parts: vec!["".to_string(), "".to_string()],
+                        specs: vec![FormatSpec::Display],
+                    },
+                    vec![(ConcreteType::Boolean, vec![Variable::new(1).into()])],
+                ),
+                Statement::definition(Variable::public(0), Variable::new(1)),
+            ],
+            1,
+        );
+
+        let text = TextDisplay(&program).to_string();
+        let parsed = Prog::<Bn128Field>::from_text(&text).unwrap();
+
+        assert_eq!(TextDisplay(&parsed).to_string(), text);
+        // the terms of the linear combinations are merged
+        assert!(text.contains("(2 * _0) * ((-1) * _2) == 1 * _1"));
+        assert_eq!(parsed.statements[0], program.statements[0]);
+        assert_eq!(parsed.statements[2], program.statements[2]);
+    }
+
+    #[test]
+    fn round_trip_solvers() {
+        let zir = ZirFunction {
+            arguments: vec![],
+            statements: vec![],
+            signature: Signature::new(),
+        };
+
+        let mut solvers: Vec<Solver<Bn128Field>> = Solver::without_parameters();
+        solvers.extend([
+            Solver::Bits(2),
+            Solver::Sample(2, 1),
+            Solver::Zir(zir),
+            Solver::Plugin("isqrt".into(), 1, 1),
+        ]);
+        #[cfg(feature = "ark")]
+        solvers.push(Solver::SnarkVerifyBls12377(1));
+
+        for solver in solvers {
+            let program: Prog<Bn128Field> = Prog::new(
+                vec![],
+                vec![Statement::Directive(Directive {
+                    inputs: vec![Variable::new(0).into()],
+                    outputs: vec![Variable::new(1)],
+                    solver: solver.clone(),
+                })],
+                0,
+            );
+
+            let text = TextDisplay(&program).to_string();
+            let parsed = Prog::<Bn128Field>::from_text(&text)
+                .unwrap_or_else(|e| panic!("{} does not parse: {}", solver.kind(), e));
+
+            assert_eq!(parsed.statements[0], program.statements[0]);
+        }
+    }
+
+    #[test]
+    fn parse_error() {
+        let text = "// curve: bn128
+def main(_0) -> (~out_0) {
+	(1 * ~one) * (1 * _0) == 1 * ~out_0
+	(1 * ~one) * (1 _0) == 1 * ~out_0
+}
+";
+
+        assert_eq!(
+            Prog::<Bn128Field>::from_text(text).unwrap_err(),
+            TextError {
+                line: 4,
+                column: 17,
+                message: "Expected ` * `".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn wrong_curve() {
+        let text = "// curve: bls12_381
+def main() -> () {
+}
+";
+
+        assert_eq!(
+            Prog::<Bn128Field>::from_text(text).unwrap_err().to_string(),
+            "1:11: Expected a program for curve bn128, found bls12_381"
+        );
+    }
+
+    #[test]
+    fn missing_end() {
+        let text = "// curve: bn128
+def main() -> () {
+	(1 * ~one) * (1 * ~one) == 1 * ~one";
+
+        assert_eq!(
+            Prog::<Bn128Field>::from_text(text).unwrap_err(),
+            TextError {
+                line: 4,
+                column: 1,
+                message: "Unexpected end of input".to_string()
+            }
+        );
+    }
+}
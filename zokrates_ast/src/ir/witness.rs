@@ -1,14 +1,134 @@
 use crate::common::Variable;
+use crate::ir::taint::VariableCollector;
+use crate::ir::visitor::Visitor;
+use crate::ir::Prog;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
 use std::collections::{BTreeMap, HashMap};
+use std::convert::TryInto;
 use std::fmt;
 use std::io;
 use std::io::{Read, Write};
 use zokrates_field::Field;
 
+/// The bytes a witness with encrypted private values starts with
+pub const ENCRYPTED_WITNESS_MAGIC: &[u8; 8] = b"ZOKWENC1";
+
+/// The size of the nonce used to encrypt a witness
+pub const WITNESS_NONCE_SIZE: usize = 24;
+
+/// A 256-bit key for the encryption of the private values of a witness
+#[derive(Clone, PartialEq, Eq)]
+pub struct WitnessKey([u8; 32]);
+
+impl WitnessKey {
+    pub fn new(bytes: [u8; 32]) -> Self {
+        WitnessKey(bytes)
+    }
+
+    /// Parses a key written as 64 hexadecimal characters, ignoring surrounding whitespace
+    pub fn from_hex(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let s = s.strip_prefix("0x").unwrap_or(s);
+
+        if s.len() != 64 || !s.is_ascii() {
+            return Err(String::from(
+                "Witness key must be 32 bytes written as 64 hexadecimal characters",
+            ));
+        }
+
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16)
+                .map_err(|_| format!("Invalid hexadecimal in witness key: {}", s))?;
+        }
+
+        Ok(WitnessKey(bytes))
+    }
+}
+
+// never print the key itself
+impl fmt::Debug for WitnessKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "WitnessKey(..)")
+    }
+}
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Default)]
 pub struct Witness<T>(pub BTreeMap<Variable, T>);
 
+/// The renaming of the variables of a sub-program into the variables of the program it is linked into. `~one` is
+/// shared by all programs and is never renamed
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Substitution(pub BTreeMap<Variable, Variable>);
+
+impl Substitution {
+    fn apply(&self, v: &Variable) -> Option<Variable> {
+        match *v == Variable::one() {
+            true => Some(*v),
+            false => self.0.get(v).copied(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MergeError {
+    /// A part assigns a variable which its substitution does not rename
+    Unmapped { part: usize, variable: Variable },
+    /// A part assigns a variable which its substitution renames into a variable the linked program does not use
+    Foreign { part: usize, variable: Variable },
+    /// A part does not assign some variables of its sub-program
+    Incomplete {
+        part: usize,
+        variables: Vec<Variable>,
+    },
+    /// Two parts assign different values to the same variable of the linked program
+    Conflict {
+        variable: Variable,
+        parts: (usize, usize),
+    },
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MergeError::Unmapped { part, variable } => write!(
+                f,
+                "Witness {} assigns {}, which is not a variable of its sub-program",
+                part, variable
+            ),
+            MergeError::Foreign { part, variable } => write!(
+                f,
+                "Witness {} assigns {} once renamed, which is not a variable of the linked program",
+                part, variable
+            ),
+            MergeError::Incomplete { part, variables } => write!(
+                f,
+                "Witness {} does not assign {}",
+                part,
+                variables
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            MergeError::Conflict { variable, parts } => write!(
+                f,
+                "Witnesses {} and {} assign different values to {}",
+                parts.0, parts.1, variable
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MergeError {}
+
 impl<T: Field> Witness<T> {
+    /// The values of the outputs, the public ones followed by the private return values if any
     pub fn return_values(&self) -> Vec<T> {
         let out = self
             .0
@@ -41,6 +161,73 @@ impl<T: Field> Witness<T> {
         Witness(BTreeMap::new())
     }
 
+    /// Merges the witnesses of the sub-programs of the linked program `program`, each renamed into the variables of
+    /// `program` by its substitution. Each witness must assign every variable its substitution renames and nothing
+    /// else, the renamed variables must be variables of `program`, and the parts must agree on the variables they
+    /// share.
+    ///
+    /// The result only holds the variables of the sub-programs, and can be completed by the interpreter with
+    /// `execute_with_partial_witness`.
+    pub fn merge(
+        program: &Prog<T>,
+        parts: Vec<(Substitution, Witness<T>)>,
+    ) -> Result<Witness<T>, MergeError> {
+        let mut variables = VariableCollector::default();
+        variables.visit_module(program);
+        let variables = variables.0;
+
+        // the merged values, along with the part each one comes from
+        let mut merged: BTreeMap<Variable, (T, usize)> = BTreeMap::new();
+
+        for (part, (substitution, witness)) in parts.into_iter().enumerate() {
+            let missing: Vec<_> = substitution
+                .0
+                .keys()
+                .filter(|v| !witness.0.contains_key(v))
+                .copied()
+                .collect();
+            if !missing.is_empty() {
+                return Err(MergeError::Incomplete {
+                    part,
+                    variables: missing,
+                });
+            }
+
+            for (variable, value) in witness.0 {
+                let renamed = substitution
+                    .apply(&variable)
+                    .ok_or(MergeError::Unmapped { part, variable })?;
+
+                if renamed != Variable::one() && !variables.contains(&renamed) {
+                    return Err(MergeError::Foreign {
+                        part,
+                        variable: renamed,
+                    });
+                }
+
+                match merged.get(&renamed) {
+                    Some((previous, other)) if *previous != value => {
+                        return Err(MergeError::Conflict {
+                            variable: renamed,
+                            parts: (*other, part),
+                        })
+                    }
+                    Some(_) => {}
+                    None => {
+                        merged.insert(renamed, (value, part));
+                    }
+                }
+            }
+        }
+
+        Ok(Witness(
+            merged
+                .into_iter()
+                .map(|(v, (value, _))| (v, value))
+                .collect(),
+        ))
+    }
+
     pub fn write<W: Write>(&self, writer: W) -> io::Result<()> {
         let mut wtr = csv::WriterBuilder::new()
             .delimiter(b' ')
@@ -56,7 +243,85 @@ impl<T: Field> Witness<T> {
         Ok(())
     }
 
-    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+    /// Writes the witness with the values of all variables except `~one` and `public` encrypted with
+    /// XChaCha20-Poly1305. The cleartext values are authenticated too, so that they cannot be tampered with.
+    ///
+    /// `public` holds the public inputs and the public outputs: private return values are encrypted.
+    /// `nonce` must never be reused with the same key.
+    pub fn write_encrypted<W: Write>(
+        &self,
+        mut writer: W,
+        public: &[Variable],
+        key: &WitnessKey,
+        nonce: [u8; WITNESS_NONCE_SIZE],
+    ) -> io::Result<()> {
+        let (public, private): (BTreeMap<_, _>, BTreeMap<_, _>) = self
+            .0
+            .iter()
+            .map(|(v, e)| (*v, e.clone()))
+            .partition(|(v, _)| *v == Variable::one() || public.contains(v));
+
+        let mut cleartext = vec![];
+        Witness(public).write(&mut cleartext)?;
+
+        let mut plaintext = vec![];
+        Witness(private).write(&mut plaintext)?;
+
+        let mut header = ENCRYPTED_WITNESS_MAGIC.to_vec();
+        header.extend((cleartext.len() as u64).to_le_bytes());
+        header.extend(cleartext);
+
+        let ciphertext = XChaCha20Poly1305::new(Key::from_slice(&key.0))
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &plaintext,
+                    aad: &header,
+                },
+            )
+            .map_err(|_| invalid_data("Could not encrypt the witness"))?;
+
+        writer.write_all(&header)?;
+        writer.write_all(&nonce)?;
+        writer.write_all(&ciphertext)?;
+
+        Ok(())
+    }
+
+    /// Reads a witness, failing if it was written with `write_encrypted`
+    pub fn read<R: Read>(reader: R) -> io::Result<Self> {
+        Self::read_with_key(reader, None)
+    }
+
+    /// Reads a witness, decrypting its private values with `key` if it was written with `write_encrypted`
+    pub fn read_with_key<R: Read>(mut reader: R, key: Option<&WitnessKey>) -> io::Result<Self> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+
+        match EncryptedWitness::parse(&bytes)? {
+            Some(encrypted) => match key {
+                Some(key) => encrypted.decrypt(key),
+                None => Err(invalid_data(
+                    "The witness is encrypted, a key is required to read it",
+                )),
+            },
+            None => Self::read_plain(&bytes[..]),
+        }
+    }
+
+    /// Reads the values of `~one`, the outputs and the public inputs of a witness, which are not encrypted by
+    /// `write_encrypted`
+    pub fn read_public<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+
+        match EncryptedWitness::parse(&bytes)? {
+            Some(encrypted) => Self::read_plain(encrypted.cleartext()),
+            None => Err(invalid_data("The witness is not encrypted")),
+        }
+    }
+
+    fn read_plain<R: Read>(mut reader: R) -> io::Result<Self> {
         let mut rdr = csv::ReaderBuilder::new()
             .delimiter(b' ')
             .flexible(true)
@@ -92,6 +357,74 @@ impl<T: Field> Witness<T> {
     }
 }
 
+// the layout of an encrypted witness:
+// magic | cleartext length (u64, little endian) | cleartext | nonce | ciphertext
+struct EncryptedWitness<'a> {
+    header: &'a [u8],
+    nonce: &'a [u8],
+    ciphertext: &'a [u8],
+}
+
+impl<'a> EncryptedWitness<'a> {
+    fn parse(bytes: &'a [u8]) -> io::Result<Option<Self>> {
+        if !bytes.starts_with(ENCRYPTED_WITNESS_MAGIC) {
+            return Ok(None);
+        }
+
+        let truncated = || invalid_data("Encrypted witness is truncated");
+
+        let length_start = ENCRYPTED_WITNESS_MAGIC.len();
+        let cleartext_start = length_start + 8;
+
+        let length = bytes
+            .get(length_start..cleartext_start)
+            .ok_or_else(truncated)?;
+        let length = u64::from_le_bytes(length.try_into().unwrap()) as usize;
+
+        let nonce_start = cleartext_start.checked_add(length).ok_or_else(truncated)?;
+        let ciphertext_start = nonce_start
+            .checked_add(WITNESS_NONCE_SIZE)
+            .ok_or_else(truncated)?;
+
+        if bytes.len() < ciphertext_start {
+            return Err(truncated());
+        }
+
+        Ok(Some(EncryptedWitness {
+            header: &bytes[..nonce_start],
+            nonce: &bytes[nonce_start..ciphertext_start],
+            ciphertext: &bytes[ciphertext_start..],
+        }))
+    }
+
+    fn cleartext(&self) -> &'a [u8] {
+        &self.header[ENCRYPTED_WITNESS_MAGIC.len() + 8..]
+    }
+
+    fn decrypt<T: Field>(&self, key: &WitnessKey) -> io::Result<Witness<T>> {
+        let plaintext = XChaCha20Poly1305::new(Key::from_slice(&key.0))
+            .decrypt(
+                XNonce::from_slice(self.nonce),
+                Payload {
+                    msg: self.ciphertext,
+                    aad: self.header,
+                },
+            )
+            .map_err(|_| {
+                invalid_data(
+                    "Could not decrypt the witness: the key is wrong or the witness was modified",
+                )
+            })?;
+
+        let mut witness = Witness::read_plain(self.cleartext())?;
+        witness
+            .0
+            .extend(Witness::<T>::read_plain(&plaintext[..])?.0);
+
+        Ok(witness)
+    }
+}
+
 impl<T: Field> fmt::Display for Witness<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -166,4 +499,265 @@ mod tests {
             assert!(Witness::<Bn128Field>::read(buff).is_err());
         }
     }
+
+    mod merge {
+        use super::*;
+        use crate::ir::{Parameter, QuadComb, Statement};
+
+        // the linked program, `_0 * _1 == _2` and `_2 * _3 == _4`
+        fn linked() -> Prog<'static, Bn128Field> {
+            let product = |x: usize, y: usize, z: usize| {
+                Statement::constraint(
+                    QuadComb::from_linear_combinations(
+                        Variable::new(x).into(),
+                        Variable::new(y).into(),
+                    ),
+                    Variable::new(z),
+                )
+            };
+
+            Prog::new(
+                [0, 1, 3]
+                    .iter()
+                    .map(|i| Parameter::private(Variable::new(*i)))
+                    .collect(),
+                vec![product(0, 1, 2), product(2, 3, 4)],
+                0,
+            )
+        }
+
+        // `x * y == z`, linked twice so that the output of the first copy is the input of the second one
+        fn part(
+            x: u32,
+            y: u32,
+            z: u32,
+            renamed: [usize; 3],
+        ) -> (Substitution, Witness<Bn128Field>) {
+            (
+                Substitution(
+                    (0..3)
+                        .map(|i| (Variable::new(i), Variable::new(renamed[i])))
+                        .collect(),
+                ),
+                Witness(
+                    vec![
+                        (Variable::one(), Bn128Field::from(1)),
+                        (Variable::new(0), Bn128Field::from(x)),
+                        (Variable::new(1), Bn128Field::from(y)),
+                        (Variable::new(2), Bn128Field::from(z)),
+                    ]
+                    .into_iter()
+                    .collect(),
+                ),
+            )
+        }
+
+        #[test]
+        fn consistent() {
+            // `_2` is shared: it is the output of the first part and the input of the second one
+            let merged = Witness::merge(
+                &linked(),
+                vec![part(2, 3, 6, [0, 1, 2]), part(6, 7, 42, [2, 3, 4])],
+            )
+            .unwrap();
+
+            assert_eq!(
+                merged,
+                Witness(
+                    vec![
+                        (Variable::one(), Bn128Field::from(1)),
+                        (Variable::new(0), Bn128Field::from(2)),
+                        (Variable::new(1), Bn128Field::from(3)),
+                        (Variable::new(2), Bn128Field::from(6)),
+                        (Variable::new(3), Bn128Field::from(7)),
+                        (Variable::new(4), Bn128Field::from(42)),
+                    ]
+                    .into_iter()
+                    .collect()
+                )
+            );
+        }
+
+        #[test]
+        fn conflicting() {
+            let res = Witness::merge(
+                &linked(),
+                vec![part(2, 3, 6, [0, 1, 2]), part(5, 7, 35, [2, 3, 4])],
+            );
+
+            assert_eq!(
+                res,
+                Err(MergeError::Conflict {
+                    variable: Variable::new(2),
+                    parts: (0, 1)
+                })
+            );
+        }
+
+        #[test]
+        fn incomplete() {
+            let (substitution, mut witness) = part(2, 3, 6, [0, 1, 2]);
+            witness.0.remove(&Variable::new(1));
+
+            assert_eq!(
+                Witness::merge(&linked(), vec![(substitution, witness)]),
+                Err(MergeError::Incomplete {
+                    part: 0,
+                    variables: vec![Variable::new(1)]
+                })
+            );
+        }
+
+        #[test]
+        fn foreign() {
+            // `_5` is not a variable of the linked program
+            let res = Witness::merge(&linked(), vec![part(2, 3, 6, [0, 1, 5])]);
+
+            assert_eq!(
+                res,
+                Err(MergeError::Foreign {
+                    part: 0,
+                    variable: Variable::new(5)
+                })
+            );
+        }
+
+        #[test]
+        fn unmapped() {
+            let (substitution, mut witness) = part(2, 3, 6, [0, 1, 2]);
+            witness.insert(Variable::new(3), Bn128Field::from(1));
+
+            assert_eq!(
+                Witness::merge(&linked(), vec![(substitution, witness)]),
+                Err(MergeError::Unmapped {
+                    part: 0,
+                    variable: Variable::new(3)
+                })
+            );
+        }
+    }
+
+    mod encryption {
+        use super::*;
+
+        fn witness() -> Witness<Bn128Field> {
+            Witness(
+                vec![
+                    (Variable::one(), Bn128Field::from(1)),
+                    (Variable::new(0), Bn128Field::from(42)),
+                    (Variable::new(1), Bn128Field::from(1337)),
+                    (Variable::new(7), Bn128Field::from(3)),
+                    (Variable::public(0), Bn128Field::from(8)),
+                ]
+                .into_iter()
+                .collect(),
+            )
+        }
+
+        fn encrypt(w: &Witness<Bn128Field>, key: &WitnessKey) -> Vec<u8> {
+            let mut buff = vec![];
+            // `_0` is a public input, `_1` a private input and `_7` an intermediate variable
+            w.write_encrypted(
+                &mut buff,
+                &[Variable::new(0), Variable::public(0)],
+                key,
+                [7; WITNESS_NONCE_SIZE],
+            )
+            .unwrap();
+            buff
+        }
+
+        #[test]
+        fn round_trip() {
+            let key = WitnessKey::new([1; 32]);
+            let encrypted = encrypt(&witness(), &key);
+
+            assert!(encrypted.starts_with(ENCRYPTED_WITNESS_MAGIC));
+            assert_eq!(
+                Witness::read_with_key(&encrypted[..], Some(&key)).unwrap(),
+                witness()
+            );
+        }
+
+        #[test]
+        fn public_values_in_cleartext() {
+            let encrypted = encrypt(&witness(), &WitnessKey::new([1; 32]));
+
+            let public = Witness::<Bn128Field>::read_public(&encrypted[..]).unwrap();
+
+            assert_eq!(
+                public,
+                Witness(
+                    vec![
+                        (Variable::one(), Bn128Field::from(1)),
+                        (Variable::new(0), Bn128Field::from(42)),
+                        (Variable::public(0), Bn128Field::from(8)),
+                    ]
+                    .into_iter()
+                    .collect()
+                )
+            );
+
+            // the private values do not appear in cleartext
+            let text = String::from_utf8_lossy(&encrypted);
+            assert!(!text.contains("1337"));
+            assert!(!text.contains("_7"));
+        }
+
+        #[test]
+        fn wrong_key() {
+            let encrypted = encrypt(&witness(), &WitnessKey::new([1; 32]));
+
+            let e = Witness::<Bn128Field>::read_with_key(
+                &encrypted[..],
+                Some(&WitnessKey::new([2; 32])),
+            )
+            .unwrap_err();
+            assert!(e.to_string().contains("the key is wrong"));
+        }
+
+        #[test]
+        fn missing_key() {
+            let encrypted = encrypt(&witness(), &WitnessKey::new([1; 32]));
+
+            let e = Witness::<Bn128Field>::read(&encrypted[..]).unwrap_err();
+            assert_eq!(
+                e.to_string(),
+                "The witness is encrypted, a key is required to read it"
+            );
+        }
+
+        #[test]
+        fn tampered_public_value() {
+            let key = WitnessKey::new([1; 32]);
+            let mut encrypted = encrypt(&witness(), &key);
+
+            // change the value of `~out_0` from 8 to 9
+            let position = encrypted.iter().position(|b| *b == b'8').unwrap();
+            encrypted[position] = b'9';
+
+            assert!(Witness::<Bn128Field>::read_with_key(&encrypted[..], Some(&key)).is_err());
+        }
+
+        #[test]
+        fn plain_witness_with_key() {
+            let mut buff = vec![];
+            witness().write(&mut buff).unwrap();
+
+            assert_eq!(
+                Witness::read_with_key(&buff[..], Some(&WitnessKey::new([1; 32]))).unwrap(),
+                witness()
+            );
+        }
+
+        #[test]
+        fn key_from_hex() {
+            assert_eq!(
+                WitnessKey::from_hex(&format!("{}\n", "01".repeat(32))).unwrap(),
+                WitnessKey::new([1; 32])
+            );
+            assert!(WitnessKey::from_hex("0102").is_err());
+            assert!(WitnessKey::from_hex(&"zz".repeat(32)).is_err());
+        }
+    }
 }
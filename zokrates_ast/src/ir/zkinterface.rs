@@ -0,0 +1,295 @@
+//! Module containing the export of programs to zkInterface, see https://github.com/QED-it/zkinterface
+//!
+//! A program is written as a sequence of messages encoded by the `zkinterface` crate: a `CircuitHeader` declaring the
+//! instance variables and the field, a `ConstraintSystem` holding the constraints and, if the values of the variables
+//! are known, a `Witness` assigning the other variables.
+//!
+//! zkInterface identifies variables by integers, `0` being the constant one. The public inputs of the program
+//! followed by its public outputs, or the order of its public layout, are the instance variables, with the ids `1..`.
+//! The other variables follow, the private inputs first, then the variables of the constraints in the order they
+//! appear.
+
+use super::{LinComb, Prog, Statement, Variable, Witness};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use zkinterface::{BilinearConstraint, CircuitHeader, ConstraintSystem, Variables};
+use zokrates_field::Field;
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+// the errors of the `zkinterface` crate are not `Send`, so only their message is kept
+fn write_error(e: Box<dyn std::error::Error>) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e.to_string())
+}
+
+/// The ids of the variables of a program in zkInterface
+struct VariableIds {
+    ids: HashMap<Variable, u64>,
+    /// The variables in the order of their ids, without the constant one
+    variables: Vec<Variable>,
+}
+
+impl VariableIds {
+    fn insert(&mut self, v: Variable) {
+        if !self.ids.contains_key(&v) {
+            self.variables.push(v);
+            self.ids.insert(v, self.variables.len() as u64);
+        }
+    }
+
+    fn new<T>(prog: &Prog<T>) -> Self {
+        let mut ids = VariableIds {
+            ids: HashMap::from([(Variable::one(), 0)]),
+            variables: vec![],
+        };
+
+        for v in prog.public_variables() {
+            ids.insert(v);
+        }
+
+        for a in prog.arguments.iter().filter(|a| a.private) {
+            ids.insert(a.id);
+        }
+
+        for s in &prog.statements {
+            if let Statement::Constraint(quad, lin, _) = s {
+                for (v, _) in quad.left.0.iter().chain(&quad.right.0).chain(&lin.0) {
+                    ids.insert(*v);
+                }
+            }
+        }
+
+        ids
+    }
+}
+
+/// The size of the encoding of the elements of `T`, the size of its largest element
+fn element_size<T: Field>() -> usize {
+    T::max_value().to_biguint().to_bytes_le().len()
+}
+
+fn encode_value<T: Field>(value: &T, values: &mut Vec<u8>) {
+    let mut bytes = value.to_biguint().to_bytes_le();
+    bytes.resize(element_size::<T>(), 0);
+    values.extend(bytes);
+}
+
+fn linear_combination<T: Field>(l: &LinComb<T>, ids: &VariableIds) -> Variables {
+    let mut values = vec![];
+    for (_, coefficient) in &l.0 {
+        encode_value(coefficient, &mut values);
+    }
+
+    Variables {
+        variable_ids: l.0.iter().map(|(v, _)| ids.ids[v]).collect(),
+        values: Some(values),
+    }
+}
+
+fn assignment<T: Field>(
+    variables: &[Variable],
+    ids: &VariableIds,
+    witness: &Witness<T>,
+) -> io::Result<Variables> {
+    let mut values = vec![];
+    for v in variables {
+        let value = witness
+            .0
+            .get(v)
+            .ok_or_else(|| invalid_data(format!("Witness is missing the value of {}", v)))?;
+        encode_value(value, &mut values);
+    }
+
+    Ok(Variables {
+        variable_ids: variables.iter().map(|v| ids.ids[v]).collect(),
+        values: Some(values),
+    })
+}
+
+/// The zkInterface messages describing `prog`: its header, its constraint system and, if a `witness` is given, the
+/// values of the variables which are not instance variables
+pub fn messages<T: Field>(
+    prog: &Prog<T>,
+    witness: Option<&Witness<T>>,
+) -> io::Result<(
+    CircuitHeader,
+    ConstraintSystem,
+    Option<zkinterface::Witness>,
+)> {
+    let ids = VariableIds::new(prog);
+    let (instance, private) = ids.variables.split_at(prog.public_count());
+
+    let header = CircuitHeader {
+        instance_variables: match witness {
+            Some(witness) => assignment(instance, &ids, witness)?,
+            None => Variables {
+                variable_ids: instance.iter().map(|v| ids.ids[v]).collect(),
+                values: None,
+            },
+        },
+        free_variable_id: ids.variables.len() as u64 + 1,
+        field_maximum: Some(T::max_value().to_biguint().to_bytes_le()),
+        ..CircuitHeader::default()
+    };
+
+    let constraints = ConstraintSystem {
+        constraints: prog
+            .statements
+            .iter()
+            .filter_map(|s| match s {
+                Statement::Constraint(quad, lin, _) => Some(BilinearConstraint {
+                    linear_combination_a: linear_combination(&quad.left, &ids),
+                    linear_combination_b: linear_combination(&quad.right, &ids),
+                    linear_combination_c: linear_combination(lin, &ids),
+                }),
+                _ => None,
+            })
+            .collect(),
+    };
+
+    let witness = witness
+        .map(|witness| {
+            assignment(private, &ids, witness)
+                .map(|assigned_variables| zkinterface::Witness { assigned_variables })
+        })
+        .transpose()?;
+
+    Ok((header, constraints, witness))
+}
+
+/// Write `prog` to `writer` in zkInterface, along with the values of its variables if a `witness` is given
+pub fn write_zkinterface<T: Field, W: Write>(
+    mut writer: W,
+    prog: &Prog<T>,
+    witness: Option<&Witness<T>>,
+) -> io::Result<()> {
+    let (header, constraints, witness) = messages(prog, witness)?;
+
+    header.write_into(&mut writer).map_err(write_error)?;
+    constraints.write_into(&mut writer).map_err(write_error)?;
+    if let Some(witness) = witness {
+        witness.write_into(&mut writer).map_err(write_error)?;
+    }
+
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{Parameter, QuadComb};
+    use zkinterface::{Messages, Reader};
+    use zokrates_field::Bn128Field;
+
+    // _1 = _0 * _0
+    // ~out_0 = _1 * a + 2
+    fn program() -> Prog<'static, Bn128Field> {
+        let a = Variable::new(2);
+        Prog::new(
+            vec![Parameter::private(Variable::new(0)), Parameter::public(a)],
+            vec![
+                Statement::constraint(
+                    QuadComb::from_linear_combinations(
+                        Variable::new(0).into(),
+                        Variable::new(0).into(),
+                    ),
+                    Variable::new(1),
+                ),
+                Statement::constraint(
+                    QuadComb::from_linear_combinations(
+                        Variable::new(1).into(),
+                        LinComb::from(a) + LinComb::summand(2, Variable::one()),
+                    ),
+                    Variable::public(0),
+                ),
+            ],
+            1,
+        )
+    }
+
+    fn witness() -> Witness<Bn128Field> {
+        Witness(
+            vec![
+                (Variable::one(), 1),
+                (Variable::new(0), 3),
+                (Variable::new(1), 9),
+                (Variable::new(2), 5),
+                (Variable::public(0), 63),
+            ]
+            .into_iter()
+            .map(|(v, value)| (v, Bn128Field::from(value)))
+            .collect(),
+        )
+    }
+
+    // decode the messages written to `buf` with the reader of the `zkinterface` crate
+    fn read(buf: Vec<u8>) -> Messages {
+        let mut reader = Reader::new();
+        reader.push_message(buf).unwrap();
+        Messages::from(&reader)
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut buf = vec![];
+        write_zkinterface(&mut buf, &program(), None).unwrap();
+
+        let messages = read(buf);
+        let (header, constraints, _) = super::messages(&program(), None).unwrap();
+        assert_eq!(messages.circuit_headers, vec![header.clone()]);
+        assert_eq!(messages.constraint_systems, vec![constraints]);
+        assert!(messages.witnesses.is_empty());
+
+        // `a` and `~out_0` are the instance variables, followed by `_0` and `_1`
+        assert_eq!(header.instance_variables.variable_ids, vec![1, 2]);
+        assert_eq!(header.instance_variables.values, None);
+        assert_eq!(header.free_variable_id, 5);
+        assert_eq!(
+            header.field_maximum,
+            Some(Bn128Field::max_value().to_biguint().to_bytes_le())
+        );
+
+        let constraints = &messages.constraint_systems[0].constraints;
+        assert_eq!(constraints.len(), 2);
+        assert_eq!(constraints[1].linear_combination_a.variable_ids, vec![4]);
+        assert_eq!(constraints[1].linear_combination_b.variable_ids, vec![1, 0]);
+        assert_eq!(
+            constraints[1].linear_combination_b.values.as_ref().unwrap()[32..],
+            encoded(2)[..]
+        );
+        assert_eq!(constraints[1].linear_combination_c.variable_ids, vec![2]);
+    }
+
+    fn encoded(value: u32) -> Vec<u8> {
+        let mut values = vec![];
+        encode_value(&Bn128Field::from(value), &mut values);
+        values
+    }
+
+    #[test]
+    fn witness_values() {
+        let mut buf = vec![];
+        write_zkinterface(&mut buf, &program(), Some(&witness())).unwrap();
+
+        let messages = read(buf);
+        assert_eq!(messages.circuit_headers.len(), 1);
+        assert_eq!(messages.constraint_systems.len(), 1);
+        assert_eq!(messages.witnesses.len(), 1);
+
+        assert_eq!(
+            messages.circuit_headers[0].instance_variables.values,
+            Some([encoded(5), encoded(63)].concat())
+        );
+
+        let assigned = &messages.witnesses[0].assigned_variables;
+        assert_eq!(assigned.variable_ids, vec![3, 4]);
+        assert_eq!(assigned.values, Some([encoded(3), encoded(9)].concat()));
+
+        // a witness without the values of some variables is rejected
+        let mut partial = witness();
+        partial.0.remove(&Variable::new(1));
+        assert!(write_zkinterface(vec![], &program(), Some(&partial)).is_err());
+    }
+}
@@ -1,5 +1,15 @@
-use crate::typed::types::{ConcreteSignature, ConcreteType};
+use crate::typed::types::{ConcreteSignature, ConcreteTupleType, ConcreteType};
+use crate::typed::{
+    ArrayExpressionInner, BooleanExpression, Constant, FieldElementExpression,
+    StructExpressionInner, TupleExpressionInner, Typed, TypedExpression, UExpressionInner,
+};
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use zokrates_field::Field;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct AbiInput {
@@ -11,13 +21,163 @@ pub struct AbiInput {
 
 pub type AbiOutput = ConcreteType;
 
+/// A public input of the compiled program which commits to the values of some inputs of the source program
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AbiCommitment {
+    /// The name of the commitment input
+    pub name: String,
+    /// The names of the inputs the commitment is computed from, in order
+    pub inputs: Vec<String>,
+}
+
+/// A constant of the main module marked `pub`, exported so that clients can reuse its value
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AbiConstant {
+    pub name: String,
+    #[serde(flatten)]
+    pub ty: ConcreteType,
+    /// The value of the constant, encoded like the arguments of the program
+    pub value: serde_json::Value,
+}
+
+impl AbiConstant {
+    /// Export the constant `name` of value `e`, which must have been reduced to a literal
+    pub fn new<T: Field>(name: String, e: TypedExpression<'_, T>) -> Self {
+        AbiConstant {
+            name,
+            ty: ConcreteType::try_from(e.get_type()).unwrap(),
+            value: constant_value(e),
+        }
+    }
+}
+
+fn constant_value<T: Field>(e: TypedExpression<'_, T>) -> serde_json::Value {
+    match e {
+        TypedExpression::FieldElement(FieldElementExpression::Number(v)) => {
+            serde_json::Value::String(v.to_dec_string())
+        }
+        TypedExpression::Boolean(BooleanExpression::Value(v)) => serde_json::Value::Bool(v),
+        TypedExpression::Uint(e) => {
+            let bitwidth = e.bitwidth();
+            match e.into_inner() {
+                UExpressionInner::Value(v) => {
+                    serde_json::Value::String(match bitwidth.is_signed() {
+                        true => bitwidth.signed_value(v).to_string(),
+                        false => format!("{:#0width$x}", v, width = bitwidth.to_usize() / 4 + 2),
+                    })
+                }
+                _ => unreachable!("constants are reduced to literals"),
+            }
+        }
+        TypedExpression::Array(e) => match e.into_canonical_constant().into_inner() {
+            ArrayExpressionInner::Value(v) => {
+                serde_json::Value::Array(v.into_iter().map(|e| constant_value(e.into())).collect())
+            }
+            _ => unreachable!("constants are reduced to literals"),
+        },
+        TypedExpression::Struct(e) => {
+            let members = e.ty().members.clone();
+            match e.into_inner() {
+                StructExpressionInner::Value(v) => serde_json::Value::Object(
+                    members
+                        .into_iter()
+                        .zip(v)
+                        .map(|(member, e)| (member.id, constant_value(e)))
+                        .collect(),
+                ),
+                _ => unreachable!("constants are reduced to literals"),
+            }
+        }
+        TypedExpression::Tuple(e) => match e.into_inner() {
+            TupleExpressionInner::Value(v) => {
+                serde_json::Value::Array(v.into_iter().map(constant_value).collect())
+            }
+            _ => unreachable!("constants are reduced to literals"),
+        },
+        _ => unreachable!("constants are reduced to literals"),
+    }
+}
+
+/// A 256-bit key for the derivation of the inputs marked `#[derive_nonce]`
+#[derive(Clone, PartialEq, Eq)]
+pub struct NonceKey([u8; 32]);
+
+impl NonceKey {
+    pub fn new(bytes: [u8; 32]) -> Self {
+        NonceKey(bytes)
+    }
+
+    /// Parses a key written as 64 hexadecimal characters, ignoring surrounding whitespace
+    pub fn from_hex(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let s = s.strip_prefix("0x").unwrap_or(s);
+
+        if s.len() != 64 || !s.is_ascii() {
+            return Err(String::from(
+                "Nonce key must be 32 bytes written as 64 hexadecimal characters",
+            ));
+        }
+
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16)
+                .map_err(|_| format!("Invalid hexadecimal in nonce key: {}", s))?;
+        }
+
+        Ok(NonceKey(bytes))
+    }
+
+    // HMAC-SHA256 of `message` under this key
+    fn mac(&self, message: &[u8]) -> [u8; 32] {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.0).expect("HMAC accepts keys of any size");
+        mac.update(message);
+
+        let mut res = [0u8; 32];
+        res.copy_from_slice(&mac.finalize().into_bytes());
+        res
+    }
+}
+
+// never print the key itself
+impl fmt::Debug for NonceKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NonceKey(..)")
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub struct Abi {
     pub inputs: Vec<AbiInput>,
     pub output: AbiOutput,
+    /// The indices of the elements of the output tuple which are private, and therefore not public inputs of the proof
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub private_outputs: Vec<usize>,
+    /// Whether the program was compiled with its return values dropped, in which case it has no outputs
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_outputs: bool,
+    /// The names of the private inputs marked `#[derive_nonce]`, which are derived from the other inputs when
+    /// computing the witness
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub derived_nonces: Vec<String>,
+    /// The names of the private inputs marked `#[bind_at_setup]`, whose values are fixed when the keys are generated
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bound_at_setup: Vec<String>,
+    /// The number of bits the values of the inputs declared `field<N>` fit in, by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub bounds: BTreeMap<String, u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commitment: Option<AbiCommitment>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub constants: Vec<AbiConstant>,
 }
 
 impl Abi {
+    /// The constants of the main module marked `pub`, in the order they are declared
+    pub fn constants(&self) -> &[AbiConstant] {
+        &self.constants
+    }
+
     pub fn signature(&self) -> ConcreteSignature {
         ConcreteSignature {
             generics: vec![],
@@ -25,6 +185,691 @@ impl Abi {
             output: box self.output.clone(),
         }
     }
+
+    /// The public inputs of the compiled program, including the commitment if any
+    pub fn public_inputs(&self) -> Vec<AbiInput> {
+        self.inputs
+            .iter()
+            .filter(|i| i.public)
+            .cloned()
+            .chain(self.commitment.iter().map(|c| AbiInput {
+                name: c.name.clone(),
+                public: true,
+                ty: ConcreteType::FieldElement,
+            }))
+            .collect()
+    }
+
+    /// Drop the output of the program, recording that it was compiled without outputs
+    pub fn without_outputs(self) -> Self {
+        Abi {
+            output: ConcreteType::Tuple(ConcreteTupleType::new(vec![])),
+            private_outputs: vec![],
+            no_outputs: true,
+            ..self
+        }
+    }
+
+    /// The output seen by a verifier, without the private elements of the output tuple
+    pub fn public_output(&self) -> AbiOutput {
+        match &self.output {
+            ConcreteType::Tuple(tuple) if !self.private_outputs.is_empty() => {
+                ConcreteType::Tuple(ConcreteTupleType::new(
+                    tuple
+                        .elements
+                        .iter()
+                        .enumerate()
+                        .filter(|(index, _)| !self.private_outputs.contains(index))
+                        .map(|(_, ty)| ty.clone())
+                        .collect(),
+                ))
+            }
+            output => output.clone(),
+        }
+    }
+
+    /// Whether each value of the output is private, in the order of the output type
+    pub fn private_output_values(&self) -> Vec<bool> {
+        match &self.output {
+            ConcreteType::Tuple(tuple) => tuple
+                .elements
+                .iter()
+                .enumerate()
+                .flat_map(|(index, ty)| {
+                    vec![self.private_outputs.contains(&index); ty.get_primitive_count()]
+                })
+                .collect(),
+            output => vec![false; output.get_primitive_count()],
+        }
+    }
+
+    /// Arrange the return values of the compiled program, where the private values follow the public ones, in the
+    /// order of the output type
+    pub fn arrange_outputs<T>(&self, values: Vec<T>) -> Vec<T> {
+        let private = self.private_output_values();
+        let public_count = private.iter().filter(|p| !**p).count();
+
+        let mut values = values.into_iter();
+        let mut public = values
+            .by_ref()
+            .take(public_count)
+            .collect::<Vec<_>>()
+            .into_iter();
+
+        private
+            .into_iter()
+            .map(|p| match p {
+                true => values.next(),
+                false => public.next(),
+            })
+            .collect::<Option<_>>()
+            .expect("the number of return values should match the output type")
+    }
+
+    /// Append the commitment to the encoded `arguments` of the program, if the ABI has one
+    ///
+    /// The commitment is the SHA-256 hash of the 32-byte big-endian encodings of the values of the committed inputs,
+    /// truncated to its last 31 bytes.
+    pub fn commit<T: Field>(&self, mut arguments: Vec<T>) -> Vec<T> {
+        let commitment = match &self.commitment {
+            Some(commitment) => commitment,
+            None => return arguments,
+        };
+
+        let mut hasher = Sha256::new();
+
+        let mut offset = 0;
+        for input in &self.inputs {
+            let count = input.ty.get_primitive_count();
+            if commitment.inputs.contains(&input.name) {
+                for value in &arguments[offset..offset + count] {
+                    let bytes = value.to_biguint().to_bytes_be();
+                    hasher.update(&[0u8; 32][bytes.len()..]);
+                    hasher.update(&bytes);
+                }
+            }
+            offset += count;
+        }
+
+        let value = hasher.finalize()[1..]
+            .iter()
+            .fold(T::from(0u32), |acc, byte| {
+                acc * T::from(256u32) + T::from(*byte)
+            });
+
+        arguments.push(value);
+        arguments
+    }
+
+    /// Whether the input `name` is derived with `#[derive_nonce]` rather than given by the user
+    pub fn is_derived(&self, name: &str) -> bool {
+        self.derived_nonces.iter().any(|n| n == name)
+    }
+
+    /// Whether the input `name` is bound at setup with `#[bind_at_setup]` rather than given by the user
+    pub fn is_bound(&self, name: &str) -> bool {
+        self.bound_at_setup.iter().any(|n| n == name)
+    }
+
+    /// The inputs given by the user, without the derived nonces and the inputs bound at setup
+    pub fn provided_inputs(&self) -> Vec<AbiInput> {
+        self.inputs
+            .iter()
+            .filter(|i| !self.is_derived(&i.name) && !self.is_bound(&i.name))
+            .cloned()
+            .collect()
+    }
+
+    /// The inputs bound at setup, in the order of the inputs
+    pub fn bound_inputs(&self) -> Vec<AbiInput> {
+        self.inputs
+            .iter()
+            .filter(|i| self.is_bound(&i.name))
+            .cloned()
+            .collect()
+    }
+
+    /// Check that the encoded `arguments` of the program fit in the bounds of the inputs declared `field<N>`, so that
+    /// out of range values are reported before the witness is computed
+    pub fn check_bounds<T: Field>(&self, arguments: &[T]) -> Result<(), String> {
+        let mut offset = 0;
+
+        for input in &self.inputs {
+            let count = input.ty.get_primitive_count();
+            let bounds = input
+                .ty
+                .get_primitive_bounds(self.bounds.get(&input.name).cloned());
+
+            if let Some((index, bound)) = arguments
+                .iter()
+                .skip(offset)
+                .take(count)
+                .zip(bounds)
+                .enumerate()
+                .find_map(|(index, (v, bound))| bound.filter(|b| v.bits() > *b).map(|b| (index, b)))
+            {
+                return Err(match count {
+                    1 => format!(
+                        "Value of input `{}` does not fit in {} bits",
+                        input.name, bound
+                    ),
+                    _ => format!(
+                        "Value of input `{}` at index {} does not fit in {} bits",
+                        input.name, index, bound
+                    ),
+                });
+            }
+            offset += count;
+        }
+
+        Ok(())
+    }
+
+    /// Check that the input set at `path`, such as `a.b`, is neither a derived nonce nor bound at setup
+    pub fn check_settable(&self, path: &str) -> Result<(), String> {
+        match path.split('.').next() {
+            Some(name) if self.is_derived(name) => Err(format!(
+                "`{}` is derived with `#[derive_nonce]` and cannot be provided",
+                name
+            )),
+            Some(name) if self.is_bound(name) => Err(format!(
+                "`{}` is bound at setup with `#[bind_at_setup]` and cannot be provided",
+                name
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that the `arguments` given by the user as a JSON array leave out the derived nonces and the inputs bound
+    /// at setup. The values are matched against the types of the inputs, and arguments which match neither the
+    /// provided inputs nor the inputs including the derived or bound ones are left to the parser to report
+    pub fn check_provided(&self, arguments: &serde_json::Value) -> Result<(), String> {
+        let values = match arguments {
+            serde_json::Value::Array(values) => values,
+            _ => return Ok(()),
+        };
+
+        let provided = |keep: &dyn Fn(&str) -> bool| {
+            self.inputs
+                .iter()
+                .filter(|i| {
+                    (!self.is_derived(&i.name) && !self.is_bound(&i.name)) || keep(i.name.as_str())
+                })
+                .map(|i| &i.ty)
+                .collect::<Vec<_>>()
+        };
+
+        let matches = |types: Vec<&ConcreteType>| {
+            values.len() == types.len() && values.iter().zip(types).all(|(v, ty)| fits(v, ty))
+        };
+
+        if matches(provided(&|_| false)) {
+            return Ok(());
+        }
+
+        if !self.derived_nonces.is_empty()
+            && (matches(provided(&|name| self.is_derived(name))) || matches(provided(&|_| true)))
+        {
+            return Err(format!(
+                "`{}` is derived with `#[derive_nonce]` and cannot be provided",
+                self.derived_nonces.join("`, `")
+            ));
+        }
+
+        if !self.bound_at_setup.is_empty() && matches(provided(&|name| self.is_bound(name))) {
+            return Err(format!(
+                "`{}` is bound at setup with `#[bind_at_setup]` and cannot be provided",
+                self.bound_at_setup.join("`, `")
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Insert the encoded values of the inputs bound at setup, in the order of `bound_inputs`, in the encoded
+    /// `arguments` given by the user. The derived nonces are left out
+    pub fn insert_bound<T>(&self, bound: Vec<T>, arguments: Vec<T>) -> Vec<T> {
+        let mut bound = bound.into_iter();
+        let mut provided = arguments.into_iter();
+
+        self.inputs
+            .iter()
+            .filter(|input| !self.is_derived(&input.name))
+            .flat_map(|input| {
+                let count = input.ty.get_primitive_count();
+                match self.is_bound(&input.name) {
+                    true => bound.by_ref().take(count).collect::<Vec<_>>(),
+                    false => provided.by_ref().take(count).collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// The indices in the arguments of the compiled program of the encoded values of the inputs bound at setup, in
+    /// the order of `bound_inputs`
+    pub fn bound_indices(&self) -> Vec<usize> {
+        let mut offset = 0;
+        let mut indices = vec![];
+
+        for input in &self.inputs {
+            let count = input.ty.get_primitive_count();
+            if self.is_bound(&input.name) {
+                indices.extend(offset..offset + count);
+            }
+            offset += count;
+        }
+
+        indices
+    }
+
+    /// Insert the derived nonces in the encoded `arguments` given by the user along with the values bound at setup,
+    /// returning all the arguments of the
+    /// program along with the derived values, by name
+    ///
+    /// Each nonce is the HMAC-SHA256 under `key` of its name followed by the 32-byte big-endian encodings of the
+    /// provided values, truncated to its last 31 bytes. The same key and inputs always derive the same nonces.
+    pub fn derive_nonces<T: Field>(
+        &self,
+        key: &NonceKey,
+        arguments: Vec<T>,
+    ) -> (Vec<T>, Vec<(String, T)>) {
+        let mut message = vec![];
+        for value in &arguments {
+            let bytes = value.to_biguint().to_bytes_be();
+            message.extend(&[0u8; 32][bytes.len()..]);
+            message.extend(bytes);
+        }
+
+        let derived: Vec<_> = self
+            .derived_nonces
+            .iter()
+            .map(|name| {
+                let mut input = name.as_bytes().to_vec();
+                input.push(0);
+                input.extend(&message);
+
+                let value = key.mac(&input)[1..]
+                    .iter()
+                    .fold(T::from(0u32), |acc, byte| {
+                        acc * T::from(256u32) + T::from(*byte)
+                    });
+
+                (name.clone(), value)
+            })
+            .collect();
+
+        let mut provided = arguments.into_iter();
+        let arguments = self
+            .inputs
+            .iter()
+            .flat_map(
+                |input| match derived.iter().find(|(name, _)| *name == input.name) {
+                    Some((_, value)) => vec![value.clone()],
+                    None => provided
+                        .by_ref()
+                        .take(input.ty.get_primitive_count())
+                        .collect(),
+                },
+            )
+            .collect();
+
+        (arguments, derived)
+    }
+}
+
+// whether the JSON `value` has the shape of a value of type `ty`, as the arguments of the ABI are written
+fn fits(value: &serde_json::Value, ty: &ConcreteType) -> bool {
+    use serde_json::Value;
+
+    match (ty, value) {
+        (ConcreteType::FieldElement | ConcreteType::Uint(_), Value::String(_)) => true,
+        (ConcreteType::Boolean, Value::Bool(_)) => true,
+        (ConcreteType::Array(array_type), Value::Array(values)) => {
+            values.len() == *array_type.size as usize
+                && values.iter().all(|v| fits(v, &array_type.ty))
+        }
+        (ConcreteType::Tuple(tuple_type), Value::Array(values)) => {
+            values.len() == tuple_type.elements.len()
+                && values
+                    .iter()
+                    .zip(&tuple_type.elements)
+                    .all(|(v, ty)| fits(v, ty))
+        }
+        (ConcreteType::Struct(struct_type), Value::Object(members)) => {
+            members.len() == struct_type.members.len()
+                && struct_type
+                    .members
+                    .iter()
+                    .all(|m| members.get(&m.id).map_or(false, |v| fits(v, &m.ty)))
+        }
+        _ => false,
+    }
+}
+
+/// A change between two ABIs, located by a path such as `a.b[].0`, outputs being found under `return`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum AbiChange {
+    Added {
+        path: String,
+        ty: ConcreteType,
+    },
+    Removed {
+        path: String,
+        ty: ConcreteType,
+    },
+    Retyped {
+        path: String,
+        old: ConcreteType,
+        new: ConcreteType,
+    },
+    /// An input or a struct member kept its name but not its position
+    Moved {
+        path: String,
+        from: usize,
+        to: usize,
+    },
+    Visibility {
+        path: String,
+        public: bool,
+    },
+}
+
+impl fmt::Display for AbiChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AbiChange::Added { path, ty } => write!(f, "`{}` of type `{}` was added", path, ty),
+            AbiChange::Removed { path, ty } => {
+                write!(f, "`{}` of type `{}` was removed", path, ty)
+            }
+            AbiChange::Retyped { path, old, new } => {
+                write!(f, "`{}` changed type from `{}` to `{}`", path, old, new)
+            }
+            AbiChange::Moved { path, from, to } => {
+                write!(f, "`{}` moved from position {} to {}", path, from, to)
+            }
+            AbiChange::Visibility { path, public } => write!(
+                f,
+                "`{}` is now {}",
+                path,
+                if *public { "public" } else { "private" }
+            ),
+        }
+    }
+}
+
+/// An input or a struct member which was renamed, keeping its type and its position
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AbiRename {
+    pub from: String,
+    pub to: String,
+}
+
+impl fmt::Display for AbiRename {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "`{}` was renamed to `{}`", self.from, self.to)
+    }
+}
+
+/// The differences between two ABIs, where the renames are reported separately from the other changes
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct AbiDiff {
+    pub renamed: Vec<AbiRename>,
+    pub changes: Vec<AbiChange>,
+}
+
+impl AbiDiff {
+    pub fn is_empty(&self) -> bool {
+        self.renamed.is_empty() && self.changes.is_empty()
+    }
+
+    /// Whether the arguments of the old interface may not be valid for the new one. Inputs are given by position, so
+    /// renaming them is compatible, but struct members are given by name, so renaming them is not
+    pub fn is_breaking(&self) -> bool {
+        !self.changes.is_empty() || self.renamed.iter().any(|r| r.from.contains('.'))
+    }
+}
+
+impl fmt::Display for AbiDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines = self
+            .renamed
+            .iter()
+            .map(|r| r.to_string())
+            .chain(self.changes.iter().map(|c| c.to_string()))
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Abi {
+    /// The differences from `self` to `other`. An input or a struct member which was removed is considered renamed
+    /// if a member of the same type, and of the same visibility for inputs, was added at the same position
+    pub fn diff(&self, other: &Abi) -> AbiDiff {
+        let mut changes = self.changes(other);
+        let mut renamed = vec![];
+
+        let mut index = 0;
+        while index < changes.len() {
+            let rename = match &changes[index] {
+                AbiChange::Removed { path, ty } => changes.iter().position(|c| match c {
+                    AbiChange::Added {
+                        path: added,
+                        ty: added_ty,
+                    } => {
+                        added_ty == ty
+                            && parent(added) == parent(path)
+                            && locate(other, added).is_some()
+                            && locate(other, added) == locate(self, path)
+                    }
+                    _ => false,
+                }),
+                _ => None,
+            };
+
+            match rename {
+                Some(added) => {
+                    let to = match changes.remove(added) {
+                        AbiChange::Added { path, .. } => path,
+                        _ => unreachable!(),
+                    };
+                    // the added change always follows the removed one
+                    let from = match changes.remove(index) {
+                        AbiChange::Removed { path, .. } => path,
+                        _ => unreachable!(),
+                    };
+                    renamed.push(AbiRename { from, to });
+                }
+                None => index += 1,
+            }
+        }
+
+        AbiDiff { renamed, changes }
+    }
+
+    /// The changes from `self` to `other`, inputs and struct members being matched by name
+    ///
+    /// The commitment, if any, is compared as a public field input, while constants are ignored.
+    pub fn changes(&self, other: &Abi) -> Vec<AbiChange> {
+        let inputs = |abi: &Abi| {
+            abi.inputs
+                .iter()
+                .cloned()
+                .chain(abi.commitment.iter().map(|c| AbiInput {
+                    name: c.name.clone(),
+                    public: true,
+                    ty: ConcreteType::FieldElement,
+                }))
+                .collect::<Vec<_>>()
+        };
+
+        let (old, new) = (inputs(self), inputs(other));
+
+        let mut changes = vec![];
+
+        for (from, input) in old.iter().enumerate() {
+            match new.iter().position(|i| i.name == input.name) {
+                Some(to) => {
+                    if from != to {
+                        changes.push(AbiChange::Moved {
+                            path: input.name.clone(),
+                            from,
+                            to,
+                        });
+                    }
+                    if input.public != new[to].public {
+                        changes.push(AbiChange::Visibility {
+                            path: input.name.clone(),
+                            public: new[to].public,
+                        });
+                    }
+                    type_changes(&input.name, &input.ty, &new[to].ty, &mut changes);
+                }
+                None => changes.push(AbiChange::Removed {
+                    path: input.name.clone(),
+                    ty: input.ty.clone(),
+                }),
+            }
+        }
+
+        changes.extend(
+            new.iter()
+                .filter(|i| !old.iter().any(|o| o.name == i.name))
+                .map(|i| AbiChange::Added {
+                    path: i.name.clone(),
+                    ty: i.ty.clone(),
+                }),
+        );
+
+        type_changes("return", &self.output, &other.output, &mut changes);
+
+        if let (ConcreteType::Tuple(old), ConcreteType::Tuple(new)) = (&self.output, &other.output)
+        {
+            for index in 0..old.elements.len().min(new.elements.len()) {
+                let public = !other.private_outputs.contains(&index);
+                if public == self.private_outputs.contains(&index) {
+                    changes.push(AbiChange::Visibility {
+                        path: format!("return.{}", index),
+                        public,
+                    });
+                }
+            }
+        }
+
+        changes
+    }
+}
+
+/// The path of the element containing the element at `path`
+fn parent(path: &str) -> &str {
+    path.rsplit_once('.')
+        .map(|(parent, _)| parent)
+        .unwrap_or("")
+}
+
+/// The position of the input or struct member at `path` in `abi`, along with its visibility if it is an input
+fn locate(abi: &Abi, path: &str) -> Option<(usize, Option<bool>)> {
+    match path.rsplit_once('.') {
+        None => {
+            let position = abi.inputs.iter().position(|i| i.name == path)?;
+            Some((position, Some(abi.inputs[position].public)))
+        }
+        Some((parent, id)) => match resolve(abi, parent)? {
+            ConcreteType::Struct(struct_type) => struct_type
+                .members
+                .iter()
+                .position(|m| m.id == id)
+                .map(|position| (position, None)),
+            _ => None,
+        },
+    }
+}
+
+/// The type of the element at `path` in `abi`
+fn resolve(abi: &Abi, path: &str) -> Option<ConcreteType> {
+    let mut segments = path.split('.');
+
+    let first = segments.next()?;
+    let ty = match first.trim_end_matches("[]") {
+        "return" => abi.output.clone(),
+        name => abi.inputs.iter().find(|i| i.name == name)?.ty.clone(),
+    };
+
+    segments.try_fold(elements(ty, first)?, |ty, segment| {
+        let id = segment.trim_end_matches("[]");
+        let ty = match ty {
+            ConcreteType::Tuple(tuple_type) => {
+                tuple_type.elements.get(id.parse::<usize>().ok()?)?.clone()
+            }
+            ConcreteType::Struct(struct_type) => {
+                *struct_type.members.iter().find(|m| m.id == id)?.ty.clone()
+            }
+            _ => return None,
+        };
+        elements(ty, segment)
+    })
+}
+
+/// The type of the elements of `ty`, one array dimension down for each `[]` at the end of `segment`
+fn elements(ty: ConcreteType, segment: &str) -> Option<ConcreteType> {
+    let dimensions = (segment.len() - segment.trim_end_matches("[]").len()) / 2;
+
+    (0..dimensions).try_fold(ty, |ty, _| match ty {
+        ConcreteType::Array(array_type) => Some(*array_type.ty),
+        _ => None,
+    })
+}
+
+fn type_changes(path: &str, old: &ConcreteType, new: &ConcreteType, changes: &mut Vec<AbiChange>) {
+    match (old, new) {
+        (ConcreteType::Array(old), ConcreteType::Array(new)) if old.size == new.size => {
+            type_changes(&format!("{}[]", path), &old.ty, &new.ty, changes)
+        }
+        (ConcreteType::Tuple(old), ConcreteType::Tuple(new))
+            if old.elements.len() == new.elements.len() =>
+        {
+            for (i, (old, new)) in old.elements.iter().zip(&new.elements).enumerate() {
+                type_changes(&format!("{}.{}", path, i), old, new, changes);
+            }
+        }
+        // structs are compared member by member, so that renaming a struct type is not a change
+        (ConcreteType::Struct(old), ConcreteType::Struct(new)) => {
+            for (from, member) in old.members.iter().enumerate() {
+                let member_path = format!("{}.{}", path, member.id);
+                match new.members.iter().position(|m| m.id == member.id) {
+                    Some(to) => {
+                        if from != to {
+                            changes.push(AbiChange::Moved {
+                                path: member_path.clone(),
+                                from,
+                                to,
+                            });
+                        }
+                        type_changes(&member_path, &member.ty, &new.members[to].ty, changes);
+                    }
+                    None => changes.push(AbiChange::Removed {
+                        path: member_path,
+                        ty: *member.ty.clone(),
+                    }),
+                }
+            }
+            changes.extend(
+                new.members
+                    .iter()
+                    .filter(|m| !old.members.iter().any(|o| o.id == m.id))
+                    .map(|m| AbiChange::Added {
+                        path: format!("{}.{}", path, m.id),
+                        ty: *m.ty.clone(),
+                    }),
+            );
+        }
+        (old, new) if old != new => changes.push(AbiChange::Retyped {
+            path: path.to_string(),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => {}
+    }
 }
 
 #[cfg(test)]
@@ -52,10 +897,16 @@ mod tests {
                     DeclarationParameter {
                         id: DeclarationVariable::new("a", DeclarationType::FieldElement, true),
                         private: true,
+                        derive_nonce: false,
+                        bind_at_setup: false,
+                        bound: None,
                     },
                     DeclarationParameter {
                         id: DeclarationVariable::new("b", DeclarationType::Boolean, false),
                         private: false,
+                        derive_nonce: false,
+                        bind_at_setup: false,
+                        bound: None,
                     },
                 ],
                 statements: vec![],
@@ -90,6 +941,13 @@ mod tests {
                 },
             ],
             output: ConcreteType::FieldElement,
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
         };
 
         assert_eq!(expected_abi, abi);
@@ -100,6 +958,13 @@ mod tests {
         let abi: Abi = Abi {
             inputs: vec![],
             output: ConcreteType::Tuple(GTupleType::new(vec![])),
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
         };
 
         let json = serde_json::to_string(&abi).unwrap();
@@ -111,6 +976,243 @@ mod tests {
         assert_eq!(de_abi, abi);
     }
 
+    #[test]
+    fn hmac_sha256() {
+        // RFC 4231, test case 2: keys shorter than a block are padded with zeros
+        let mut key = [0u8; 32];
+        key[..4].copy_from_slice(b"Jefe");
+
+        let mac = NonceKey::new(key).mac(b"what do ya want for nothing?");
+        assert_eq!(
+            mac.iter().map(|b| format!("{:02x}", b)).collect::<String>(),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    fn nonce_abi() -> Abi {
+        Abi {
+            inputs: vec![
+                AbiInput {
+                    name: String::from("a"),
+                    public: true,
+                    ty: ConcreteType::FieldElement,
+                },
+                AbiInput {
+                    name: String::from("nonce"),
+                    public: false,
+                    ty: ConcreteType::FieldElement,
+                },
+                AbiInput {
+                    name: String::from("b"),
+                    public: false,
+                    ty: ConcreteType::Array(ConcreteArrayType::new(
+                        ConcreteType::FieldElement,
+                        2u32,
+                    )),
+                },
+            ],
+            output: ConcreteType::FieldElement,
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![String::from("nonce")],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
+        }
+    }
+
+    #[test]
+    fn derive_nonces() {
+        let abi = nonce_abi();
+        let key = NonceKey::from_hex(&"42".repeat(32)).unwrap();
+        let provided = vec![
+            Bn128Field::from(1),
+            Bn128Field::from(2),
+            Bn128Field::from(3),
+        ];
+
+        let (arguments, derived) = abi.derive_nonces(&key, provided.clone());
+
+        // the nonce is inserted at its position
+        assert_eq!(derived.len(), 1);
+        assert_eq!(derived[0].0, "nonce");
+        assert_eq!(
+            arguments,
+            vec![
+                provided[0].clone(),
+                derived[0].1.clone(),
+                provided[1].clone(),
+                provided[2].clone()
+            ]
+        );
+
+        // the same key and inputs derive the same nonce
+        assert_eq!(
+            abi.derive_nonces(&key, provided.clone()),
+            (arguments, derived.clone())
+        );
+
+        // another key or other inputs derive another nonce
+        let other_key = NonceKey::from_hex(&"43".repeat(32)).unwrap();
+        assert_ne!(abi.derive_nonces(&other_key, provided.clone()).1, derived);
+        let other_inputs = vec![
+            Bn128Field::from(1),
+            Bn128Field::from(2),
+            Bn128Field::from(4),
+        ];
+        assert_ne!(abi.derive_nonces(&key, other_inputs).1, derived);
+    }
+
+    #[test]
+    fn reject_provided_nonces() {
+        let abi = nonce_abi();
+
+        assert!(abi.is_derived("nonce"));
+        assert_eq!(
+            abi.provided_inputs()
+                .into_iter()
+                .map(|i| i.name)
+                .collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+
+        assert!(abi
+            .check_provided(&serde_json::json!(["1", ["2", "3"]]))
+            .is_ok());
+        assert_eq!(
+            abi.check_provided(&serde_json::json!(["1", "42", ["2", "3"]])),
+            Err(String::from(
+                "`nonce` is derived with `#[derive_nonce]` and cannot be provided"
+            ))
+        );
+
+        // the values are checked, not only their count: a wrong value is left to the parser to report
+        assert!(abi
+            .check_provided(&serde_json::json!(["1", true, ["2", "3"]]))
+            .is_ok());
+        assert!(abi
+            .check_provided(&serde_json::json!(["1", "42", ["2"]]))
+            .is_ok());
+
+        // with an input bound at setup, the nonce is found among the values given for the other inputs
+        let abi = Abi {
+            bound_at_setup: vec![String::from("b")],
+            ..nonce_abi()
+        };
+        assert!(abi.check_provided(&serde_json::json!(["1"])).is_ok());
+        assert_eq!(
+            abi.check_provided(&serde_json::json!(["1", "42"])),
+            Err(String::from(
+                "`nonce` is derived with `#[derive_nonce]` and cannot be provided"
+            ))
+        );
+        assert_eq!(
+            abi.check_provided(&serde_json::json!(["1", ["2", "3"]])),
+            Err(String::from(
+                "`b` is bound at setup with `#[bind_at_setup]` and cannot be provided"
+            ))
+        );
+    }
+
+    #[test]
+    fn insert_bound_inputs() {
+        let abi = Abi {
+            bound_at_setup: vec![String::from("b")],
+            ..nonce_abi()
+        };
+
+        assert_eq!(
+            abi.provided_inputs()
+                .into_iter()
+                .map(|i| i.name)
+                .collect::<Vec<_>>(),
+            vec!["a"]
+        );
+        assert_eq!(abi.bound_indices(), vec![2, 3]);
+
+        // the bound values follow the provided ones, the nonce being derived later
+        assert_eq!(abi.insert_bound(vec![2, 3], vec![1]), vec![1, 2, 3]);
+
+        assert_eq!(
+            abi.check_settable("b"),
+            Err(String::from(
+                "`b` is bound at setup with `#[bind_at_setup]` and cannot be provided"
+            ))
+        );
+    }
+
+    #[test]
+    fn check_bounds() {
+        let abi = Abi {
+            derived_nonces: vec![],
+            bounds: vec![(String::from("a"), 8), (String::from("b"), 4)]
+                .into_iter()
+                .collect(),
+            ..nonce_abi()
+        };
+
+        let values = |v: [u32; 4]| v.map(Bn128Field::from);
+
+        assert_eq!(abi.check_bounds(&values([255, 42, 15, 0])), Ok(()));
+        assert_eq!(
+            abi.check_bounds(&values([256, 42, 15, 0])),
+            Err(String::from("Value of input `a` does not fit in 8 bits"))
+        );
+        assert_eq!(
+            abi.check_bounds(&values([0, 42, 15, 16])),
+            Err(String::from(
+                "Value of input `b` at index 1 does not fit in 4 bits"
+            ))
+        );
+
+        // the bounds appear in the abi
+        let json = serde_json::to_value(&abi).unwrap();
+        assert_eq!(json["bounds"], serde_json::json!({ "a": 8, "b": 4 }));
+    }
+
+    #[test]
+    fn check_member_bounds() {
+        let abi = Abi {
+            inputs: vec![AbiInput {
+                name: String::from("s"),
+                public: true,
+                ty: ConcreteType::Struct(ConcreteStructType::new(
+                    "".into(),
+                    "Foo".into(),
+                    vec![],
+                    vec![
+                        ConcreteStructMember::new(String::from("a"), ConcreteType::FieldElement)
+                            .bound(Some(8)),
+                        ConcreteStructMember::new(String::from("b"), ConcreteType::FieldElement),
+                    ],
+                )),
+            }],
+            derived_nonces: vec![],
+            ..nonce_abi()
+        };
+
+        let values = |v: [u32; 2]| v.map(Bn128Field::from);
+
+        assert_eq!(abi.check_bounds(&values([255, 1 << 20])), Ok(()));
+        assert_eq!(
+            abi.check_bounds(&values([256, 0])),
+            Err(String::from(
+                "Value of input `s` at index 0 does not fit in 8 bits"
+            ))
+        );
+
+        // the bounds of the members appear in their type
+        let json = serde_json::to_value(&abi).unwrap();
+        assert_eq!(
+            json["inputs"][0]["components"]["members"],
+            serde_json::json!([
+                { "name": "a", "type": "field", "bound": 8 },
+                { "name": "b", "type": "field" }
+            ])
+        );
+    }
+
     #[test]
     #[should_panic]
     fn serialize_integer() {
@@ -119,6 +1221,13 @@ mod tests {
         let abi: Abi = Abi {
             inputs: vec![],
             output: ConcreteType::Int,
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
         };
 
         let _ = serde_json::to_string_pretty(&abi).unwrap();
@@ -140,6 +1249,13 @@ mod tests {
                 },
             ],
             output: ConcreteType::FieldElement,
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
         };
 
         let json = serde_json::to_string_pretty(&abi).unwrap();
@@ -189,6 +1305,13 @@ mod tests {
                 },
             ],
             output: ConcreteType::Tuple(GTupleType::new(vec![])),
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
         };
 
         let json = serde_json::to_string_pretty(&abi).unwrap();
@@ -253,6 +1376,13 @@ mod tests {
                     ConcreteStructMember::new(String::from("b"), ConcreteType::Boolean),
                 ],
             )),
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
         };
 
         let json = serde_json::to_string_pretty(&abi).unwrap();
@@ -337,6 +1467,13 @@ mod tests {
                 )),
             }],
             output: ConcreteType::Tuple(GTupleType::new(vec![])),
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
         };
 
         let json = serde_json::to_string_pretty(&abi).unwrap();
@@ -410,6 +1547,13 @@ mod tests {
                 )),
             }],
             output: ConcreteType::Boolean,
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
         };
 
         let json = serde_json::to_string_pretty(&abi).unwrap();
@@ -463,6 +1607,13 @@ mod tests {
                 )),
             }],
             output: ConcreteType::FieldElement,
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
         };
 
         let json = serde_json::to_string_pretty(&abi).unwrap();
@@ -506,6 +1657,13 @@ mod tests {
                 ])),
             }],
             output: ConcreteType::Tuple(ConcreteTupleType::new(vec![ConcreteType::FieldElement])),
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
         };
 
         let json = serde_json::to_string_pretty(&abi).unwrap();
@@ -545,4 +1703,412 @@ mod tests {
         let de_abi: Abi = serde_json::from_str(json.as_ref()).unwrap();
         assert_eq!(de_abi, abi);
     }
+
+    fn committed_abi() -> Abi {
+        Abi {
+            inputs: vec![
+                AbiInput {
+                    name: String::from("a"),
+                    public: false,
+                    ty: ConcreteType::Array(ConcreteArrayType::new(
+                        ConcreteType::FieldElement,
+                        3u32,
+                    )),
+                },
+                AbiInput {
+                    name: String::from("b"),
+                    public: false,
+                    ty: ConcreteType::FieldElement,
+                },
+            ],
+            output: ConcreteType::FieldElement,
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: Some(AbiCommitment {
+                name: String::from("public_commitment"),
+                inputs: vec![String::from("a")],
+            }),
+            constants: vec![],
+        }
+    }
+
+    #[test]
+    fn serialize_commitment() {
+        let abi = committed_abi();
+
+        let json = serde_json::to_string(&abi).unwrap();
+        let de_abi: Abi = serde_json::from_str(json.as_ref()).unwrap();
+        assert_eq!(de_abi, abi);
+
+        // the user still provides the inputs of the source program
+        assert_eq!(
+            de_abi.signature().inputs,
+            vec![
+                ConcreteType::Array(ConcreteArrayType::new(ConcreteType::FieldElement, 3u32)),
+                ConcreteType::FieldElement
+            ]
+        );
+
+        // the commitment is the only public input of the compiled program
+        assert_eq!(
+            de_abi.public_inputs(),
+            vec![AbiInput {
+                name: String::from("public_commitment"),
+                public: true,
+                ty: ConcreteType::FieldElement,
+            }]
+        );
+    }
+
+    #[test]
+    fn commit() {
+        let abi = committed_abi();
+
+        let arguments: Vec<Bn128Field> = vec![1.into(), 2.into(), 3.into(), 4.into()];
+
+        // last 31 bytes of the SHA-256 hash of 1, 2 and 3 encoded on 32 bytes each
+        assert_eq!(
+            abi.commit(arguments.clone()),
+            arguments
+                .into_iter()
+                .chain(std::iter::once(
+                    Bn128Field::try_from_dec_str(
+                        "3446033955913018254391050256942293153325553851905978002802227984220846142"
+                    )
+                    .unwrap()
+                ))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn private_outputs() {
+        // def main() -> (private field[2], bool, private u32)
+        let abi = Abi {
+            inputs: vec![],
+            output: ConcreteType::Tuple(ConcreteTupleType::new(vec![
+                ConcreteType::Array(ConcreteArrayType::new(ConcreteType::FieldElement, 2u32)),
+                ConcreteType::Boolean,
+                ConcreteType::Uint(UBitwidth::B32),
+            ])),
+            private_outputs: vec![0, 2],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
+        };
+
+        assert_eq!(
+            abi.public_output(),
+            ConcreteType::Tuple(ConcreteTupleType::new(vec![ConcreteType::Boolean]))
+        );
+        assert_eq!(abi.private_output_values(), vec![true, true, false, true]);
+
+        // the compiled program returns the public value first
+        assert_eq!(
+            abi.arrange_outputs(vec!["bool", "a[0]", "a[1]", "u32"]),
+            vec!["a[0]", "a[1]", "bool", "u32"]
+        );
+
+        let serialized = serde_json::to_string(&abi).unwrap();
+        let deserialized: Abi = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, abi);
+    }
+
+    #[test]
+    fn changes() {
+        let point = |members: Vec<(&str, ConcreteType)>| {
+            ConcreteType::Struct(ConcreteStructType::new(
+                "".into(),
+                "Point".into(),
+                vec![],
+                members
+                    .into_iter()
+                    .map(|(id, ty)| ConcreteStructMember::new(id.into(), ty))
+                    .collect(),
+            ))
+        };
+        let input = |name: &str, public: bool, ty: ConcreteType| AbiInput {
+            name: name.into(),
+            public,
+            ty,
+        };
+
+        let old = Abi {
+            inputs: vec![
+                input(
+                    "p",
+                    true,
+                    point(vec![
+                        ("x", ConcreteType::FieldElement),
+                        ("y", ConcreteType::FieldElement),
+                    ]),
+                ),
+                input("a", false, ConcreteType::Boolean),
+                input("b", false, ConcreteType::FieldElement),
+            ],
+            output: ConcreteType::FieldElement,
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
+        };
+
+        assert_eq!(old.changes(&old), vec![]);
+
+        let new = Abi {
+            inputs: vec![
+                input(
+                    "p",
+                    true,
+                    point(vec![
+                        ("x", ConcreteType::FieldElement),
+                        ("y", ConcreteType::Uint(UBitwidth::B32)),
+                    ]),
+                ),
+                input("c", false, ConcreteType::FieldElement),
+                input("a", true, ConcreteType::Boolean),
+            ],
+            output: ConcreteType::Array(ConcreteArrayType::new(ConcreteType::FieldElement, 2u32)),
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
+        };
+
+        let changes = old.changes(&new);
+        assert_eq!(
+            changes,
+            vec![
+                AbiChange::Retyped {
+                    path: "p.y".into(),
+                    old: ConcreteType::FieldElement,
+                    new: ConcreteType::Uint(UBitwidth::B32)
+                },
+                AbiChange::Moved {
+                    path: "a".into(),
+                    from: 1,
+                    to: 2
+                },
+                AbiChange::Visibility {
+                    path: "a".into(),
+                    public: true
+                },
+                AbiChange::Removed {
+                    path: "b".into(),
+                    ty: ConcreteType::FieldElement
+                },
+                AbiChange::Added {
+                    path: "c".into(),
+                    ty: ConcreteType::FieldElement
+                },
+                AbiChange::Retyped {
+                    path: "return".into(),
+                    old: ConcreteType::FieldElement,
+                    new: ConcreteType::Array(ConcreteArrayType::new(
+                        ConcreteType::FieldElement,
+                        2u32
+                    ))
+                },
+            ]
+        );
+        assert_eq!(
+            changes[0].to_string(),
+            "`p.y` changed type from `field` to `u32`"
+        );
+    }
+
+    mod diff {
+        use super::*;
+
+        fn structure(name: &str, members: Vec<(&str, ConcreteType)>) -> ConcreteType {
+            ConcreteType::Struct(ConcreteStructType::new(
+                "".into(),
+                name.into(),
+                vec![],
+                members
+                    .into_iter()
+                    .map(|(id, ty)| ConcreteStructMember::new(id.into(), ty))
+                    .collect(),
+            ))
+        }
+
+        fn abi(inputs: Vec<(&str, bool, ConcreteType)>) -> Abi {
+            Abi {
+                inputs: inputs
+                    .into_iter()
+                    .map(|(name, public, ty)| AbiInput {
+                        name: name.into(),
+                        public,
+                        ty,
+                    })
+                    .collect(),
+                output: ConcreteType::FieldElement,
+                private_outputs: vec![],
+                no_outputs: false,
+                derived_nonces: vec![],
+                bound_at_setup: vec![],
+                bounds: Default::default(),
+                commitment: None,
+                constants: vec![],
+            }
+        }
+
+        #[test]
+        fn rename() {
+            let point = |x: &str| {
+                structure(
+                    "Point",
+                    vec![
+                        (x, ConcreteType::FieldElement),
+                        ("y", ConcreteType::FieldElement),
+                    ],
+                )
+            };
+
+            let old = abi(vec![
+                ("a", true, ConcreteType::FieldElement),
+                ("p", false, point("x")),
+            ]);
+            let new = abi(vec![
+                ("b", true, ConcreteType::FieldElement),
+                ("p", false, point("z")),
+            ]);
+
+            let diff = old.diff(&new);
+            assert_eq!(
+                diff,
+                AbiDiff {
+                    renamed: vec![
+                        AbiRename {
+                            from: "a".into(),
+                            to: "b".into()
+                        },
+                        AbiRename {
+                            from: "p.x".into(),
+                            to: "p.z".into()
+                        }
+                    ],
+                    changes: vec![]
+                }
+            );
+            assert_eq!(
+                diff.to_string(),
+                "`a` was renamed to `b`\n`p.x` was renamed to `p.z`"
+            );
+            assert!(diff.is_breaking());
+            assert!(old.diff(&old).is_empty());
+            assert!(!old.diff(&old).is_breaking());
+
+            // renaming an input alone is compatible
+            let renamed_input = abi(vec![
+                ("b", true, ConcreteType::FieldElement),
+                ("p", false, point("x")),
+            ]);
+            assert!(!old.diff(&renamed_input).is_breaking());
+
+            // a member of another type, or an input of another visibility, is not a rename
+            let new = abi(vec![
+                ("b", false, ConcreteType::FieldElement),
+                ("p", false, point("x")),
+            ]);
+            assert_eq!(
+                old.diff(&new),
+                AbiDiff {
+                    renamed: vec![],
+                    changes: vec![
+                        AbiChange::Removed {
+                            path: "a".into(),
+                            ty: ConcreteType::FieldElement
+                        },
+                        AbiChange::Added {
+                            path: "b".into(),
+                            ty: ConcreteType::FieldElement
+                        }
+                    ]
+                }
+            );
+            assert!(old.diff(&new).is_breaking());
+        }
+
+        #[test]
+        fn nested_retype() {
+            let inner = |ty: ConcreteType| structure("Inner", vec![("value", ty)]);
+            let outer = |ty: ConcreteType| {
+                structure(
+                    "Outer",
+                    vec![(
+                        "inner",
+                        ConcreteType::Array(ConcreteArrayType::new(inner(ty), 2u32)),
+                    )],
+                )
+            };
+
+            let old = abi(vec![("o", true, outer(ConcreteType::FieldElement))]);
+            let new = abi(vec![("o", true, outer(ConcreteType::Uint(UBitwidth::B64)))]);
+
+            let diff = old.diff(&new);
+            assert_eq!(
+                diff,
+                AbiDiff {
+                    renamed: vec![],
+                    changes: vec![AbiChange::Retyped {
+                        path: "o.inner[].value".into(),
+                        old: ConcreteType::FieldElement,
+                        new: ConcreteType::Uint(UBitwidth::B64)
+                    }]
+                }
+            );
+
+            let serialized = serde_json::to_string(&diff).unwrap();
+            assert_eq!(
+                serialized,
+                r#"{"renamed":[],"changes":[{"change":"retyped","path":"o.inner[].value","old":{"type":"field"},"new":{"type":"u64"}}]}"#
+            );
+            assert_eq!(serde_json::from_str::<AbiDiff>(&serialized).unwrap(), diff);
+        }
+
+        #[test]
+        fn added_dimension() {
+            let old = abi(vec![(
+                "a",
+                true,
+                ConcreteType::Array(ConcreteArrayType::new(ConcreteType::FieldElement, 2u32)),
+            )]);
+            let new = abi(vec![(
+                "a",
+                true,
+                ConcreteType::Array(ConcreteArrayType::new(
+                    ConcreteType::Array(ConcreteArrayType::new(ConcreteType::FieldElement, 3u32)),
+                    2u32,
+                )),
+            )]);
+
+            assert_eq!(
+                old.diff(&new),
+                AbiDiff {
+                    renamed: vec![],
+                    changes: vec![AbiChange::Retyped {
+                        path: "a[]".into(),
+                        old: ConcreteType::FieldElement,
+                        new: ConcreteType::Array(ConcreteArrayType::new(
+                            ConcreteType::FieldElement,
+                            3u32
+                        ))
+                    }]
+                }
+            );
+        }
+    }
 }
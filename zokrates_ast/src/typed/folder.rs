@@ -1,5 +1,6 @@
 // Generic walk through a typed AST. Not mutating in place
 
+use crate::common::stack::maybe_grow;
 use crate::typed::types::*;
 use crate::typed::*;
 use zokrates_field::Field;
@@ -509,6 +510,7 @@ pub fn fold_constant_symbol_declaration<'ast, T: Field, F: Folder<'ast, T>>(
     TypedConstantSymbolDeclaration {
         id: f.fold_canonical_constant_identifier(d.id),
         symbol: f.fold_constant_symbol(d.symbol),
+        public: d.public,
     }
 }
 
@@ -540,6 +542,17 @@ pub fn fold_assembly_statement<'ast, T: Field, F: Folder<'ast, T>>(
                 metadata,
             )]
         }
+        TypedAssemblyStatement::UnsafeWitness(a, plugin, arguments) => {
+            let arguments = arguments
+                .into_iter()
+                .map(|e| f.fold_expression(e))
+                .collect();
+            vec![TypedAssemblyStatement::UnsafeWitness(
+                f.fold_assignee(a),
+                plugin,
+                arguments,
+            )]
+        }
     }
 }
 
@@ -567,6 +580,9 @@ pub fn fold_statement<'ast, T: Field, F: Folder<'ast, T>>(
         TypedStatement::Log(s, e) => {
             TypedStatement::Log(s, e.into_iter().map(|e| f.fold_expression(e)).collect())
         }
+        TypedStatement::PushIterationLog(label, index) => {
+            TypedStatement::PushIterationLog(label, f.fold_uint_expression(index))
+        }
         TypedStatement::Assembly(statements) => TypedStatement::Assembly(
             statements
                 .into_iter()
@@ -753,7 +769,7 @@ pub fn fold_field_expression<'ast, T: Field, F: Folder<'ast, T>>(
 ) -> FieldElementExpression<'ast, T> {
     use FieldElementExpression::*;
 
-    match e {
+    maybe_grow(|| match e {
         Identifier(id) => match f.fold_identifier_expression(&Type::FieldElement, id) {
             IdentifierOrExpression::Identifier(i) => Identifier(i),
             IdentifierOrExpression::Expression(u) => u,
@@ -775,10 +791,10 @@ pub fn fold_field_expression<'ast, T: Field, F: Folder<'ast, T>>(
             let e2 = f.fold_field_expression(e2);
             Mult(box e1, box e2)
         }
-        Div(box e1, box e2) => {
+        Div(box e1, box e2, span) => {
             let e1 = f.fold_field_expression(e1);
             let e2 = f.fold_field_expression(e2);
-            Div(box e1, box e2)
+            Div(box e1, box e2, span)
         }
         Pow(box e1, box e2) => {
             let e1 = f.fold_field_expression(e1);
@@ -849,7 +865,7 @@ pub fn fold_field_expression<'ast, T: Field, F: Folder<'ast, T>>(
             ElementOrExpression::Element(m) => Element(m),
             ElementOrExpression::Expression(u) => u,
         },
-    }
+    })
 }
 
 pub fn fold_conditional_expression<
@@ -938,7 +954,7 @@ pub fn fold_boolean_expression<'ast, T: Field, F: Folder<'ast, T>>(
 ) -> BooleanExpression<'ast, T> {
     use BooleanExpression::*;
 
-    match e {
+    maybe_grow(|| match e {
         Identifier(id) => match f.fold_identifier_expression(&Type::Boolean, id) {
             IdentifierOrExpression::Identifier(i) => Identifier(i),
             IdentifierOrExpression::Expression(u) => u,
@@ -1047,7 +1063,7 @@ pub fn fold_boolean_expression<'ast, T: Field, F: Folder<'ast, T>>(
             ElementOrExpression::Element(m) => Element(m),
             ElementOrExpression::Expression(u) => u,
         },
-    }
+    })
 }
 
 pub fn fold_uint_expression<'ast, T: Field, F: Folder<'ast, T>>(
@@ -1067,7 +1083,7 @@ pub fn fold_uint_expression_inner<'ast, T: Field, F: Folder<'ast, T>>(
 ) -> UExpressionInner<'ast, T> {
     use UExpressionInner::*;
 
-    match e {
+    maybe_grow(|| match e {
         Identifier(id) => match f.fold_identifier_expression(&ty, id) {
             IdentifierOrExpression::Identifier(i) => Identifier(i),
             IdentifierOrExpression::Expression(u) => u,
@@ -1175,7 +1191,7 @@ pub fn fold_uint_expression_inner<'ast, T: Field, F: Folder<'ast, T>>(
             ElementOrExpression::Element(m) => Element(m),
             ElementOrExpression::Expression(u) => u,
         },
-    }
+    })
 }
 
 pub fn fold_block_expression<'ast, T: Field, E: Fold<'ast, T>, F: Folder<'ast, T>>(
@@ -1213,17 +1229,20 @@ pub fn fold_function_call_expression<
     _: &E::Ty,
     e: FunctionCallExpression<'ast, T, E>,
 ) -> FunctionCallOrExpression<'ast, T, E> {
-    FunctionCallOrExpression::FunctionCall(FunctionCallExpression::new(
-        f.fold_declaration_function_key(e.function_key),
-        e.generics
-            .into_iter()
-            .map(|g| g.map(|g| f.fold_uint_expression(g)))
-            .collect(),
-        e.arguments
-            .into_iter()
-            .map(|e| f.fold_expression(e))
-            .collect(),
-    ))
+    FunctionCallOrExpression::FunctionCall(
+        FunctionCallExpression::new(
+            f.fold_declaration_function_key(e.function_key),
+            e.generics
+                .into_iter()
+                .map(|g| g.map(|g| f.fold_uint_expression(g)))
+                .collect(),
+            e.arguments
+                .into_iter()
+                .map(|e| f.fold_expression(e))
+                .collect(),
+        )
+        .span(e.span),
+    )
 }
 
 pub fn fold_function<'ast, T: Field, F: Folder<'ast, T>>(
@@ -11,6 +11,7 @@ use crate::typed::{
     TypedExpressionOrSpread, TypedSpread, UExpression, UExpressionInner,
 };
 use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 use std::fmt;
 use std::ops::{Add, Div, Mul, Neg, Not, Rem, Sub};
@@ -105,6 +106,7 @@ impl<'ast, T: Clone> IntegerInference for StructType<'ast, T> {
                     Ok(ty) => DeclarationStructMember {
                         ty: box ty,
                         id: m_t.id,
+                        bound: m_t.bound,
                     },
                     Err(..) => unreachable!(
                         "struct instances of the same struct should always have a common type"
@@ -272,7 +274,7 @@ impl<'ast, T: Field> TypedExpression<'ast, T> {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum IntExpression<'ast, T> {
     Value(BigUint),
     Pos(Box<IntExpression<'ast, T>>),
@@ -283,6 +285,7 @@ pub enum IntExpression<'ast, T> {
     Div(Box<IntExpression<'ast, T>>, Box<IntExpression<'ast, T>>),
     Rem(Box<IntExpression<'ast, T>>, Box<IntExpression<'ast, T>>),
     Pow(Box<IntExpression<'ast, T>>, Box<IntExpression<'ast, T>>),
+    #[serde(borrow)]
     Conditional(ConditionalExpression<'ast, T, IntExpression<'ast, T>>),
     Select(SelectExpression<'ast, T, IntExpression<'ast, T>>),
     Xor(Box<IntExpression<'ast, T>>, Box<IntExpression<'ast, T>>),
@@ -377,6 +380,68 @@ impl<'ast, T> IntExpression<'ast, T> {
     pub fn pos(self) -> Self {
         IntExpression::Pos(box self)
     }
+
+    /// Evaluate this expression if it only involves constants, returning `None` if it does not or if an
+    /// intermediate result is not a natural number
+    pub fn try_evaluate(&self) -> Option<BigUint> {
+        // bound exponents and shifts so that evaluating a constant cannot exhaust memory
+        const MAX_EXPONENT: usize = 1024;
+
+        fn small(n: BigUint) -> Option<usize> {
+            match n.bits() <= 16 {
+                true => n.to_str_radix(10).parse().ok(),
+                false => None,
+            }
+        }
+
+        fn shift<'ast, T>(by: &UExpression<'ast, T>) -> Option<usize> {
+            match by.as_inner() {
+                UExpressionInner::Value(v) if *v <= MAX_EXPONENT as u128 => Some(*v as usize),
+                _ => None,
+            }
+        }
+
+        use IntExpression::*;
+
+        match self {
+            Value(v) => Some(v.clone()),
+            Pos(e) => e.try_evaluate(),
+            Add(left, right) => Some(left.try_evaluate()? + right.try_evaluate()?),
+            Sub(left, right) => {
+                let (left, right) = (left.try_evaluate()?, right.try_evaluate()?);
+                match left >= right {
+                    true => Some(left - right),
+                    false => None,
+                }
+            }
+            Mult(left, right) => Some(left.try_evaluate()? * right.try_evaluate()?),
+            Div(left, right) => {
+                let (left, right) = (left.try_evaluate()?, right.try_evaluate()?);
+                match right == BigUint::from(0u32) {
+                    true => None,
+                    false => Some(left / right),
+                }
+            }
+            Rem(left, right) => {
+                let (left, right) = (left.try_evaluate()?, right.try_evaluate()?);
+                match right == BigUint::from(0u32) {
+                    true => None,
+                    false => Some(left % right),
+                }
+            }
+            Pow(base, exponent) => {
+                let base = base.try_evaluate()?;
+                let exponent = small(exponent.try_evaluate()?).filter(|e| *e <= MAX_EXPONENT)?;
+                Some((0..exponent).fold(BigUint::from(1u32), |acc, _| acc * &base))
+            }
+            And(left, right) => Some(left.try_evaluate()? & right.try_evaluate()?),
+            Or(left, right) => Some(left.try_evaluate()? | right.try_evaluate()?),
+            Xor(left, right) => Some(left.try_evaluate()? ^ right.try_evaluate()?),
+            LeftShift(e, by) => Some(e.try_evaluate()? << shift(by)?),
+            RightShift(e, by) => Some(e.try_evaluate()? >> shift(by)?),
+            Neg(..) | Not(..) | Conditional(..) | Select(..) => None,
+        }
+    }
 }
 
 impl<'ast, T: fmt::Display> fmt::Display for IntExpression<'ast, T> {
@@ -445,6 +510,7 @@ impl<'ast, T: Field> FieldElementExpression<'ast, T> {
             IntExpression::Div(box e1, box e2) => Ok(Self::Div(
                 box Self::try_from_int(e1)?,
                 box Self::try_from_int(e2)?,
+                None,
             )),
             IntExpression::And(box e1, box e2) => Ok(Self::And(
                 box Self::try_from_int(e1)?,
@@ -539,7 +605,9 @@ impl<'ast, T: Field> UExpression<'ast, T> {
 
         match i {
             Value(i) => {
-                if i <= BigUint::from(2u128.pow(bitwidth.to_usize() as u32) - 1) {
+                // the largest positive value of a signed integer only uses the bits below the sign bit
+                let bits = bitwidth.to_usize() - bitwidth.is_signed() as usize;
+                if i <= BigUint::from(2u128.pow(bits as u32) - 1) {
                     Ok(UExpressionInner::Value(
                         u128::from_str_radix(&i.to_str_radix(16), 16).unwrap(),
                     )
@@ -548,6 +616,13 @@ impl<'ast, T: Field> UExpression<'ast, T> {
                     Err(Value(i))
                 }
             }
+            // negative literals of signed integers are stored in two's complement
+            Neg(box Value(i)) if matches!(bitwidth.sign_bit(), Some(sign) if i <= BigUint::from(sign)) =>
+            {
+                let modulus = 2u128.pow(bitwidth.to_usize() as u32);
+                let i = u128::from_str_radix(&i.to_str_radix(16), 16).unwrap();
+                Ok(UExpressionInner::Value((modulus - i) % modulus).annotate(*bitwidth))
+            }
             Add(box e1, box e2) => {
                 Ok(Self::try_from_int(e1, bitwidth)? + Self::try_from_int(e2, bitwidth)?)
             }
@@ -559,10 +634,10 @@ impl<'ast, T: Field> UExpression<'ast, T> {
             Mult(box e1, box e2) => {
                 Ok(Self::try_from_int(e1, bitwidth)? * Self::try_from_int(e2, bitwidth)?)
             }
-            Div(box e1, box e2) => {
+            Div(box e1, box e2) if !bitwidth.is_signed() => {
                 Ok(Self::try_from_int(e1, bitwidth)? / Self::try_from_int(e2, bitwidth)?)
             }
-            Rem(box e1, box e2) => {
+            Rem(box e1, box e2) if !bitwidth.is_signed() => {
                 Ok(Self::try_from_int(e1, bitwidth)? % Self::try_from_int(e2, bitwidth)?)
             }
             And(box e1, box e2) => Ok(UExpression::and(
@@ -578,7 +653,7 @@ impl<'ast, T: Field> UExpression<'ast, T> {
                 Self::try_from_int(e1, bitwidth)?,
                 Self::try_from_int(e2, bitwidth)?,
             )),
-            RightShift(box e1, box e2) => Ok(UExpression::right_shift(
+            RightShift(box e1, box e2) if !bitwidth.is_signed() => Ok(UExpression::right_shift(
                 Self::try_from_int(e1, bitwidth)?,
                 e2,
             )),
@@ -938,4 +1013,38 @@ mod tests {
             assert!(e.is_err());
         }
     }
+
+    #[test]
+    fn evaluate() {
+        let n = |v: u32| -> IntExpression<Bn128Field> { BigUint::from(v).into() };
+
+        // 200 + 100
+        assert_eq!(
+            (n(200) + n(100)).try_evaluate(),
+            Some(BigUint::from(300u32))
+        );
+        // (2 ** 8) - 1
+        assert_eq!(
+            (n(2).pow(n(8)) - n(1)).try_evaluate(),
+            Some(BigUint::from(255u32))
+        );
+        // 1 << 9
+        assert_eq!(
+            n(1).left_shift(UExpression::from(9u32)).try_evaluate(),
+            Some(BigUint::from(512u32))
+        );
+        // negative intermediate results are not evaluated
+        assert_eq!((n(1) - n(2)).try_evaluate(), None);
+        // neither are divisions by zero
+        assert_eq!((n(1) / n(0)).try_evaluate(), None);
+        // nor expressions which are not constant
+        assert_eq!(
+            IntExpression::<Bn128Field>::select(
+                ArrayExpressionInner::Value(vec![n(1).into()].into()).annotate(Type::Int, 1u32),
+                UExpression::identifier("i".into()).annotate(UBitwidth::B32)
+            )
+            .try_evaluate(),
+            None
+        );
+    }
 }
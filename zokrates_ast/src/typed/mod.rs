@@ -36,7 +36,7 @@ use std::path::{Path, PathBuf};
 pub use crate::typed::integer::IntExpression;
 pub use crate::typed::uint::{bitwidth, UExpression, UExpressionInner, UMetadata};
 
-use crate::common::{FlatEmbed, FormatString, SourceMetadata};
+use crate::common::{AssertionError, FlatEmbed, FormatString, SourceMetadata};
 
 use std::collections::BTreeMap;
 use std::convert::{TryFrom, TryInto};
@@ -44,10 +44,11 @@ use std::fmt;
 
 pub use crate::typed::types::{ArrayType, FunctionKey, MemberId};
 
+use serde::{Deserialize, Serialize};
 use zokrates_field::Field;
 
 pub use self::folder::Folder;
-use crate::typed::abi::{Abi, AbiInput};
+use crate::typed::abi::{Abi, AbiConstant, AbiInput};
 use std::ops::{Add, Div, Mul, Sub};
 
 pub use self::identifier::Identifier;
@@ -66,8 +67,9 @@ pub type TypedModules<'ast, T> = BTreeMap<OwnedTypedModuleId, TypedModule<'ast,
 pub type TypedFunctionSymbols<'ast, T> =
     BTreeMap<DeclarationFunctionKey<'ast, T>, TypedFunctionSymbol<'ast, T>>;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TypedConstantSymbol<'ast, T> {
+    #[serde(borrow)]
     Here(TypedConstant<'ast, T>),
     There(CanonicalConstantIdentifier<'ast>),
 }
@@ -125,8 +127,45 @@ impl<'ast, T: Field> TypedProgram<'ast, T> {
                 .unwrap(),
             )
             .unwrap(),
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: main
+                .arguments
+                .iter()
+                .filter(|p| p.derive_nonce)
+                .map(|p| p.id.id.to_string())
+                .collect(),
+            bound_at_setup: main
+                .arguments
+                .iter()
+                .filter(|p| p.bind_at_setup)
+                .map(|p| p.id.id.to_string())
+                .collect(),
+            bounds: main
+                .arguments
+                .iter()
+                .filter_map(|p| p.bound.map(|bound| (p.id.id.to_string(), bound)))
+                .collect(),
+            commitment: None,
+            constants: vec![],
         }
     }
+
+    /// The constants of the main module marked `pub`, which must have been reduced to literals
+    pub fn public_constants(&self) -> Vec<AbiConstant> {
+        self.modules[&self.main]
+            .symbols
+            .iter()
+            .filter_map(|s| match s {
+                TypedSymbolDeclaration::Constant(TypedConstantSymbolDeclaration {
+                    id,
+                    symbol: TypedConstantSymbol::Here(c),
+                    public: true,
+                }) => Some(AbiConstant::new(id.id.to_string(), c.expression.clone())),
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 impl<'ast, T: fmt::Display> fmt::Display for TypedProgram<'ast, T> {
@@ -151,8 +190,9 @@ impl<'ast, T: fmt::Display> fmt::Display for TypedProgram<'ast, T> {
     }
 }
 
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
 pub struct TypedFunctionSymbolDeclaration<'ast, T> {
+    #[serde(borrow)]
     pub key: DeclarationFunctionKey<'ast, T>,
     pub symbol: TypedFunctionSymbol<'ast, T>,
 }
@@ -163,10 +203,13 @@ impl<'ast, T> TypedFunctionSymbolDeclaration<'ast, T> {
     }
 }
 
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
 pub struct TypedConstantSymbolDeclaration<'ast, T> {
+    #[serde(borrow)]
     pub id: CanonicalConstantIdentifier<'ast>,
     pub symbol: TypedConstantSymbol<'ast, T>,
+    /// Whether the constant is marked `pub`
+    pub public: bool,
 }
 
 impl<'ast, T> TypedConstantSymbolDeclaration<'ast, T> {
@@ -174,13 +217,23 @@ impl<'ast, T> TypedConstantSymbolDeclaration<'ast, T> {
         id: CanonicalConstantIdentifier<'ast>,
         symbol: TypedConstantSymbol<'ast, T>,
     ) -> Self {
-        TypedConstantSymbolDeclaration { id, symbol }
+        TypedConstantSymbolDeclaration {
+            id,
+            symbol,
+            public: false,
+        }
+    }
+
+    pub fn public(mut self, public: bool) -> Self {
+        self.public = public;
+        self
     }
 }
 
 #[allow(clippy::large_enum_variant)]
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
 pub enum TypedSymbolDeclaration<'ast, T> {
+    #[serde(borrow)]
     Function(TypedFunctionSymbolDeclaration<'ast, T>),
     Constant(TypedConstantSymbolDeclaration<'ast, T>),
 }
@@ -209,8 +262,9 @@ impl<'ast, T: fmt::Display> fmt::Display for TypedSymbolDeclaration<'ast, T> {
 pub type TypedSymbolDeclarations<'ast, T> = Vec<TypedSymbolDeclaration<'ast, T>>;
 
 /// A typed module as a collection of functions. Types have been resolved during semantic checking.
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
 pub struct TypedModule<'ast, T> {
+    #[serde(borrow)]
     pub symbols: TypedSymbolDeclarations<'ast, T>,
 }
 
@@ -232,8 +286,9 @@ impl<'ast, T> TypedModule<'ast, T> {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum TypedFunctionSymbol<'ast, T> {
+    #[serde(borrow)]
     Here(TypedFunction<'ast, T>),
     There(DeclarationFunctionKey<'ast, T>),
     Flat(FlatEmbed),
@@ -263,6 +318,9 @@ impl<'ast, T: fmt::Display> fmt::Display for TypedConstantSymbolDeclaration<'ast
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.symbol {
             TypedConstantSymbol::Here(ref tc) => {
+                if self.public {
+                    write!(f, "pub ")?;
+                }
                 write!(f, "const {} {} = {};", tc.ty, self.id, tc.expression)
             }
             TypedConstantSymbol::There(ref imported_id) => {
@@ -315,9 +373,10 @@ impl<'ast, T: fmt::Display> fmt::Display for TypedModule<'ast, T> {
 }
 
 /// A typed function
-#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+#[derive(Clone, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 pub struct TypedFunction<'ast, T> {
     /// Arguments of the function
+    #[serde(borrow)]
     pub arguments: Vec<DeclarationParameter<'ast, T>>,
     /// Vector of statements that are executed when running the function
     pub statements: Vec<TypedStatement<'ast, T>>,
@@ -374,8 +433,9 @@ impl<'ast, T: fmt::Display> fmt::Display for TypedFunction<'ast, T> {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct TypedConstant<'ast, T> {
+    #[serde(borrow)]
     pub expression: TypedExpression<'ast, T>,
     pub ty: DeclarationType<'ast, T>,
 }
@@ -400,16 +460,18 @@ impl<'ast, T: Field> Typed<'ast, T> for TypedConstant<'ast, T> {
 
 /// Something we can assign to.
 #[allow(clippy::large_enum_variant)]
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum TypedAssignee<'ast, T> {
+    #[serde(borrow)]
     Identifier(Variable<'ast, T>),
     Select(Box<TypedAssignee<'ast, T>>, Box<UExpression<'ast, T>>),
     Member(Box<TypedAssignee<'ast, T>>, MemberId),
     Element(Box<TypedAssignee<'ast, T>>, u32),
 }
 
-#[derive(Clone, PartialEq, Hash, Eq, Debug, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Hash, Eq, Debug, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct TypedSpread<'ast, T> {
+    #[serde(borrow)]
     pub array: ArrayExpression<'ast, T>,
 }
 
@@ -425,8 +487,9 @@ impl<'ast, T: fmt::Display> fmt::Display for TypedSpread<'ast, T> {
     }
 }
 
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum TypedExpressionOrSpread<'ast, T> {
+    #[serde(borrow)]
     Expression(TypedExpression<'ast, T>),
     Spread(TypedSpread<'ast, T>),
 }
@@ -567,7 +630,7 @@ impl<'ast, T: fmt::Display> fmt::Display for TypedAssignee<'ast, T> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Hash, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum RuntimeError {
     SourceAssertion(SourceMetadata),
     SelectRangeCheck,
@@ -584,10 +647,20 @@ impl fmt::Display for RuntimeError {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Hash, Eq, PartialOrd, Ord)]
+impl AssertionError for RuntimeError {
+    fn source_assertion(&self) -> Option<&SourceMetadata> {
+        match self {
+            RuntimeError::SourceAssertion(metadata) => Some(metadata),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct EmbedCall<'ast, T> {
     pub embed: FlatEmbed,
     pub generics: Vec<u32>,
+    #[serde(borrow)]
     pub arguments: Vec<TypedExpression<'ast, T>>,
 }
 
@@ -631,8 +704,9 @@ impl<'ast, T: fmt::Display> fmt::Display for EmbedCall<'ast, T> {
     }
 }
 
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum DefinitionRhs<'ast, T> {
+    #[serde(borrow)]
     Expression(TypedExpression<'ast, T>),
     EmbedCall(EmbedCall<'ast, T>),
 }
@@ -658,14 +732,23 @@ impl<'ast, T: fmt::Display> fmt::Display for DefinitionRhs<'ast, T> {
     }
 }
 
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum TypedAssemblyStatement<'ast, T> {
-    Assignment(TypedAssignee<'ast, T>, TypedExpression<'ast, T>),
+    Assignment(
+        #[serde(borrow)] TypedAssignee<'ast, T>,
+        TypedExpression<'ast, T>,
+    ),
     Constraint(
         FieldElementExpression<'ast, T>,
         FieldElementExpression<'ast, T>,
         SourceMetadata,
     ),
+    /// An assignment of the outputs of a plugin, given its name and its arguments
+    UnsafeWitness(
+        TypedAssignee<'ast, T>,
+        &'ast str,
+        Vec<TypedExpression<'ast, T>>,
+    ),
 }
 
 impl<'ast, T: fmt::Display> fmt::Display for TypedAssemblyStatement<'ast, T> {
@@ -677,14 +760,43 @@ impl<'ast, T: fmt::Display> fmt::Display for TypedAssemblyStatement<'ast, T> {
             TypedAssemblyStatement::Constraint(ref lhs, ref rhs, _) => {
                 write!(f, "{} === {};", lhs, rhs)
             }
+            TypedAssemblyStatement::UnsafeWitness(ref lhs, plugin, ref arguments) => {
+                write!(f, "{} <-- unsafe_witness(\"{}\"", lhs, plugin)?;
+                for a in arguments {
+                    write!(f, ", {}", a)?;
+                }
+                write!(f, ");")
+            }
+        }
+    }
+}
+
+/// What is known about a call when it is inlined
+///
+/// The arguments are kept to detect recursion which does not terminate.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct CallSite<'ast, T> {
+    /// The location of the call in the source, if known
+    pub span: Option<SourceMetadata>,
+    /// The arguments of the call, if they are all constant
+    #[serde(borrow)]
+    pub arguments: Option<Vec<TypedExpression<'ast, T>>>,
+}
+
+impl<'ast, T> Default for CallSite<'ast, T> {
+    fn default() -> Self {
+        CallSite {
+            span: None,
+            arguments: None,
         }
     }
 }
 
 /// A statement in a `TypedFunction`
 #[allow(clippy::large_enum_variant)]
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum TypedStatement<'ast, T> {
+    #[serde(borrow)]
     Return(TypedExpression<'ast, T>),
     Definition(TypedAssignee<'ast, T>, DefinitionRhs<'ast, T>),
     Assertion(BooleanExpression<'ast, T>, RuntimeError),
@@ -699,8 +811,14 @@ pub enum TypedStatement<'ast, T> {
     PushCallLog(
         DeclarationFunctionKey<'ast, T>,
         ConcreteGenericsAssignment<'ast>,
+        CallSite<'ast, T>,
     ),
     PopCallLog,
+    // marks the statements unrolled from an iteration of a labeled loop, with the value of its index
+    PushIterationLog(&'ast str, UExpression<'ast, T>),
+    PopIterationLog,
+    // marks the statements checked from the source statement at the given location, until the next one
+    SpanLog(SourceMetadata),
     Assembly(Vec<TypedAssemblyStatement<'ast, T>>),
 }
 
@@ -747,6 +865,9 @@ impl<'ast, T: fmt::Display> fmt::Display for TypedStatement<'ast, T> {
             }
             TypedStatement::Definition(ref lhs, ref rhs) => write!(f, "{} = {};", lhs, rhs),
             TypedStatement::Assertion(ref e, ref error) => {
+                if error.is_critical() {
+                    write!(f, "#[critical] ")?;
+                }
                 write!(f, "assert({}", e)?;
                 match error {
                     RuntimeError::SourceAssertion(metadata) => match &metadata.message {
@@ -773,7 +894,7 @@ impl<'ast, T: fmt::Display> fmt::Display for TypedStatement<'ast, T> {
                     .collect::<Vec<_>>()
                     .join(", ")
             ),
-            TypedStatement::PushCallLog(ref key, ref generics) => write!(
+            TypedStatement::PushCallLog(ref key, ref generics, _) => write!(
                 f,
                 "// PUSH CALL TO {}/{}::<{}>",
                 key.module.display(),
@@ -781,6 +902,11 @@ impl<'ast, T: fmt::Display> fmt::Display for TypedStatement<'ast, T> {
                 generics,
             ),
             TypedStatement::PopCallLog => write!(f, "// POP CALL",),
+            TypedStatement::PushIterationLog(label, ref index) => {
+                write!(f, "// PUSH ITERATION {} OF '{}", index, label)
+            }
+            TypedStatement::PopIterationLog => write!(f, "// POP ITERATION"),
+            TypedStatement::SpanLog(ref span) => write!(f, "// SPAN {}", span),
             TypedStatement::Assembly(ref statements) => {
                 writeln!(f, "asm {{")?;
                 for s in statements {
@@ -798,8 +924,9 @@ pub trait Typed<'ast, T> {
 
 /// A typed expression
 #[allow(clippy::large_enum_variant)]
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum TypedExpression<'ast, T> {
+    #[serde(borrow)]
     Boolean(BooleanExpression<'ast, T>),
     FieldElement(FieldElementExpression<'ast, T>),
     Uint(UExpression<'ast, T>),
@@ -955,7 +1082,7 @@ impl<'ast, T: Clone> Typed<'ast, T> for BooleanExpression<'ast, T> {
     }
 }
 
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct EqExpression<E> {
     pub left: Box<E>,
     pub right: Box<E>,
@@ -976,8 +1103,9 @@ impl<E: fmt::Display> fmt::Display for EqExpression<E> {
     }
 }
 
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct BlockExpression<'ast, T, E> {
+    #[serde(borrow)]
     pub statements: Vec<TypedStatement<'ast, T>>,
     pub value: Box<E>,
 }
@@ -991,8 +1119,9 @@ impl<'ast, T, E> BlockExpression<'ast, T, E> {
     }
 }
 
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct IdentifierExpression<'ast, E> {
+    #[serde(borrow)]
     pub id: Identifier<'ast>,
     ty: PhantomData<E>,
 }
@@ -1012,8 +1141,9 @@ impl<'ast, E> fmt::Display for IdentifierExpression<'ast, E> {
     }
 }
 
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct MemberExpression<'ast, T, E> {
+    #[serde(borrow)]
     pub struc: Box<StructExpression<'ast, T>>,
     pub id: MemberId,
     ty: PhantomData<E>,
@@ -1035,8 +1165,9 @@ impl<'ast, T: fmt::Display, E> fmt::Display for MemberExpression<'ast, T, E> {
     }
 }
 
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct SelectExpression<'ast, T, E> {
+    #[serde(borrow)]
     pub array: Box<ArrayExpression<'ast, T>>,
     pub index: Box<UExpression<'ast, T>>,
     ty: PhantomData<E>,
@@ -1058,8 +1189,9 @@ impl<'ast, T: fmt::Display, E> fmt::Display for SelectExpression<'ast, T, E> {
     }
 }
 
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct ElementExpression<'ast, T, E> {
+    #[serde(borrow)]
     pub tuple: Box<TupleExpression<'ast, T>>,
     pub index: u32,
     ty: PhantomData<E>,
@@ -1081,14 +1213,15 @@ impl<'ast, T: fmt::Display, E> fmt::Display for ElementExpression<'ast, T, E> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Hash, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ConditionalKind {
     IfElse,
     Ternary,
 }
 
-#[derive(Debug, Clone, PartialEq, Hash, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct ConditionalExpression<'ast, T, E> {
+    #[serde(borrow)]
     pub condition: Box<BooleanExpression<'ast, T>>,
     pub consequence: Box<E>,
     pub alternative: Box<E>,
@@ -1130,11 +1263,14 @@ impl<'ast, T: fmt::Display, E: fmt::Display> fmt::Display for ConditionalExpress
     }
 }
 
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct FunctionCallExpression<'ast, T, E> {
+    #[serde(borrow)]
     pub function_key: DeclarationFunctionKey<'ast, T>,
     pub generics: Vec<Option<UExpression<'ast, T>>>,
     pub arguments: Vec<TypedExpression<'ast, T>>,
+    /// The location of the call in the source, if known
+    pub span: Option<SourceMetadata>,
     ty: PhantomData<E>,
 }
 
@@ -1148,9 +1284,15 @@ impl<'ast, T, E> FunctionCallExpression<'ast, T, E> {
             function_key,
             generics,
             arguments,
+            span: None,
             ty: PhantomData,
         }
     }
+
+    pub fn span(mut self, span: Option<SourceMetadata>) -> Self {
+        self.span = span;
+        self
+    }
 }
 
 impl<'ast, T: fmt::Display, E> fmt::Display for FunctionCallExpression<'ast, T, E> {
@@ -1183,8 +1325,9 @@ impl<'ast, T: fmt::Display, E> fmt::Display for FunctionCallExpression<'ast, T,
 }
 
 /// An expression of type `field`
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum FieldElementExpression<'ast, T> {
+    #[serde(borrow)]
     Block(BlockExpression<'ast, T, Self>),
     Number(T),
     Identifier(IdentifierExpression<'ast, Self>),
@@ -1200,9 +1343,11 @@ pub enum FieldElementExpression<'ast, T> {
         Box<FieldElementExpression<'ast, T>>,
         Box<FieldElementExpression<'ast, T>>,
     ),
+    /// The location of the division in the source is kept to report divisions by zero
     Div(
         Box<FieldElementExpression<'ast, T>>,
         Box<FieldElementExpression<'ast, T>>,
+        Option<SourceMetadata>,
     ),
     Pow(
         Box<FieldElementExpression<'ast, T>>,
@@ -1331,7 +1476,7 @@ impl<'ast, T> Div for FieldElementExpression<'ast, T> {
     type Output = Self;
 
     fn div(self, other: Self) -> Self {
-        FieldElementExpression::Div(box self, box other)
+        FieldElementExpression::Div(box self, box other, None)
     }
 }
 
@@ -1348,8 +1493,9 @@ impl<'ast, T> From<T> for FieldElementExpression<'ast, T> {
 }
 
 /// An expression of type `bool`
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum BooleanExpression<'ast, T> {
+    #[serde(borrow)]
     Block(BlockExpression<'ast, T, Self>),
     Identifier(IdentifierExpression<'ast, Self>),
     Value(bool),
@@ -1406,14 +1552,15 @@ impl<'ast, T> From<bool> for BooleanExpression<'ast, T> {
 /// * Contrary to basic types which are represented as enums, we wrap an enum `ArrayExpressionInner` in a struct in order to keep track of the type (content and size)
 /// of the array. Only using an enum would require generics, which would propagate up to TypedExpression which we want to keep simple, hence this "runtime"
 /// type checking
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct ArrayExpression<'ast, T> {
+    #[serde(borrow)]
     pub ty: Box<ArrayType<'ast, T>>,
     pub inner: ArrayExpressionInner<'ast, T>,
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
-pub struct ArrayValue<'ast, T>(pub Vec<TypedExpressionOrSpread<'ast, T>>);
+#[derive(Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ArrayValue<'ast, T>(#[serde(borrow)] pub Vec<TypedExpressionOrSpread<'ast, T>>);
 
 impl<'ast, T> From<Vec<TypedExpressionOrSpread<'ast, T>>> for ArrayValue<'ast, T> {
     fn from(array: Vec<TypedExpressionOrSpread<'ast, T>>) -> Self {
@@ -1489,8 +1636,9 @@ impl<'ast, T> std::iter::FromIterator<TypedExpressionOrSpread<'ast, T>> for Arra
     }
 }
 
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ArrayExpressionInner<'ast, T> {
+    #[serde(borrow)]
     Block(BlockExpression<'ast, T, ArrayExpression<'ast, T>>),
     Identifier(IdentifierExpression<'ast, ArrayExpression<'ast, T>>),
     Value(ArrayValue<'ast, T>),
@@ -1530,8 +1678,9 @@ impl<'ast, T: Clone> ArrayExpression<'ast, T> {
     }
 }
 
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct StructExpression<'ast, T> {
+    #[serde(borrow)]
     ty: StructType<'ast, T>,
     inner: StructExpressionInner<'ast, T>,
 }
@@ -1554,8 +1703,9 @@ impl<'ast, T> StructExpression<'ast, T> {
     }
 }
 
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum StructExpressionInner<'ast, T> {
+    #[serde(borrow)]
     Block(BlockExpression<'ast, T, StructExpression<'ast, T>>),
     Identifier(IdentifierExpression<'ast, StructExpression<'ast, T>>),
     Value(Vec<TypedExpression<'ast, T>>),
@@ -1572,8 +1722,9 @@ impl<'ast, T> StructExpressionInner<'ast, T> {
     }
 }
 
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct TupleExpression<'ast, T> {
+    #[serde(borrow)]
     ty: TupleType<'ast, T>,
     inner: TupleExpressionInner<'ast, T>,
 }
@@ -1596,8 +1747,9 @@ impl<'ast, T> TupleExpression<'ast, T> {
     }
 }
 
-#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Debug, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum TupleExpressionInner<'ast, T> {
+    #[serde(borrow)]
     Block(BlockExpression<'ast, T, TupleExpression<'ast, T>>),
     Identifier(IdentifierExpression<'ast, TupleExpression<'ast, T>>),
     Value(Vec<TypedExpression<'ast, T>>),
@@ -1779,7 +1931,7 @@ impl<'ast, T: fmt::Display> fmt::Display for FieldElementExpression<'ast, T> {
             FieldElementExpression::Add(ref lhs, ref rhs) => write!(f, "({} + {})", lhs, rhs),
             FieldElementExpression::Sub(ref lhs, ref rhs) => write!(f, "({} - {})", lhs, rhs),
             FieldElementExpression::Mult(ref lhs, ref rhs) => write!(f, "({} * {})", lhs, rhs),
-            FieldElementExpression::Div(ref lhs, ref rhs) => write!(f, "({} / {})", lhs, rhs),
+            FieldElementExpression::Div(ref lhs, ref rhs, _) => write!(f, "({} / {})", lhs, rhs),
             FieldElementExpression::Pow(ref lhs, ref rhs) => write!(f, "{}**{}", lhs, rhs),
             FieldElementExpression::Neg(ref e) => write!(f, "(-{})", e),
             FieldElementExpression::Pos(ref e) => write!(f, "(+{})", e),
@@ -1803,7 +1955,7 @@ impl<'ast, T: fmt::Display> fmt::Display for UExpression<'ast, T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.inner {
             UExpressionInner::Block(ref block) => write!(f, "{}", block,),
-            UExpressionInner::Value(ref v) => write!(f, "{}", v),
+            UExpressionInner::Value(ref v) => write!(f, "{}", self.bitwidth.signed_value(*v)),
             UExpressionInner::Identifier(ref var) => write!(f, "{}", var),
             UExpressionInner::Add(ref lhs, ref rhs) => write!(f, "({} + {})", lhs, rhs),
             UExpressionInner::And(ref lhs, ref rhs) => write!(f, "({} & {})", lhs, rhs),
@@ -1,11 +1,20 @@
 use crate::typed::types::DeclarationConstant;
 use crate::typed::GVariable;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Clone, PartialEq, Eq, Hash)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct GParameter<'ast, S> {
+    #[serde(borrow)]
     pub id: GVariable<'ast, S>,
     pub private: bool,
+    /// Whether the value of the parameter is derived from the other arguments when computing the witness
+    pub derive_nonce: bool,
+    /// Whether the value of the parameter is fixed when the keys are generated
+    pub bind_at_setup: bool,
+    /// The number of bits the value of the parameter is asserted to fit in, for `field<N>` parameters and arrays of
+    /// them
+    pub bound: Option<u32>,
 }
 
 impl<'ast, S> From<GVariable<'ast, S>> for GParameter<'ast, S> {
@@ -13,6 +22,9 @@ impl<'ast, S> From<GVariable<'ast, S>> for GParameter<'ast, S> {
         GParameter {
             id: v,
             private: true,
+            derive_nonce: false,
+            bind_at_setup: false,
+            bound: None,
         }
     }
 }
@@ -21,8 +33,20 @@ pub type DeclarationParameter<'ast, T> = GParameter<'ast, DeclarationConstant<'a
 
 impl<'ast, S: fmt::Display> fmt::Display for GParameter<'ast, S> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let attribute = match (self.derive_nonce, self.bind_at_setup) {
+            (true, _) => "#[derive_nonce] ",
+            (false, true) => "#[bind_at_setup] ",
+            (false, false) => "",
+        };
         let visibility = if self.private { "private " } else { "" };
-        write!(f, "{}{} {}", visibility, self.id._type, self.id.id)
+        write!(
+            f,
+            "{}{}{} {}",
+            attribute,
+            visibility,
+            self.id._type.with_bound(self.bound),
+            self.id.id
+        )
     }
 }
 
@@ -1,5 +1,6 @@
 // Generic walk through a typed AST. Not mutating in place
 
+use crate::common::stack::maybe_grow;
 use crate::typed::types::*;
 use crate::typed::*;
 use zokrates_field::Field;
@@ -311,8 +312,12 @@ pub trait ResultFolder<'ast, T: Field>: Sized {
                 .into_iter()
                 .map(|m| {
                     let id = m.id;
-                    self.fold_type(*m.ty)
-                        .map(|ty| StructMember { ty: box ty, id })
+                    let bound = m.bound;
+                    self.fold_type(*m.ty).map(|ty| StructMember {
+                        ty: box ty,
+                        id,
+                        bound,
+                    })
                 })
                 .collect::<Result<_, _>>()?,
             ..t
@@ -371,8 +376,13 @@ pub trait ResultFolder<'ast, T: Field>: Sized {
                 .into_iter()
                 .map(|m| {
                     let id = m.id;
+                    let bound = m.bound;
                     self.fold_declaration_type(*m.ty)
-                        .map(|ty| DeclarationStructMember { ty: box ty, id })
+                        .map(|ty| DeclarationStructMember {
+                            ty: box ty,
+                            id,
+                            bound,
+                        })
                 })
                 .collect::<Result<_, _>>()?,
             ..t
@@ -541,6 +551,17 @@ pub fn fold_assembly_statement<'ast, T: Field, F: ResultFolder<'ast, T>>(
                 metadata,
             )]
         }
+        TypedAssemblyStatement::UnsafeWitness(a, plugin, arguments) => {
+            let arguments = arguments
+                .into_iter()
+                .map(|e| f.fold_expression(e))
+                .collect::<Result<_, _>>()?;
+            vec![TypedAssemblyStatement::UnsafeWitness(
+                f.fold_assignee(a)?,
+                plugin,
+                arguments,
+            )]
+        }
     })
 }
 
@@ -574,6 +595,9 @@ pub fn fold_statement<'ast, T: Field, F: ResultFolder<'ast, T>>(
                 .map(|e| f.fold_expression(e))
                 .collect::<Result<Vec<_>, _>>()?,
         ),
+        TypedStatement::PushIterationLog(label, index) => {
+            TypedStatement::PushIterationLog(label, f.fold_uint_expression(index)?)
+        }
         TypedStatement::Assembly(statements) => TypedStatement::Assembly(
             statements
                 .into_iter()
@@ -775,102 +799,104 @@ pub fn fold_field_expression<'ast, T: Field, F: ResultFolder<'ast, T>>(
 ) -> Result<FieldElementExpression<'ast, T>, F::Error> {
     use FieldElementExpression::*;
 
-    let e = match e {
-        Identifier(id) => match f.fold_identifier_expression(&Type::FieldElement, id)? {
-            IdentifierOrExpression::Identifier(i) => Identifier(i),
-            IdentifierOrExpression::Expression(u) => u,
-        },
-        Block(block) => Block(f.fold_block_expression(block)?),
-        Number(n) => Number(n),
-        Add(box e1, box e2) => {
-            let e1 = f.fold_field_expression(e1)?;
-            let e2 = f.fold_field_expression(e2)?;
-            Add(box e1, box e2)
-        }
-        Sub(box e1, box e2) => {
-            let e1 = f.fold_field_expression(e1)?;
-            let e2 = f.fold_field_expression(e2)?;
-            Sub(box e1, box e2)
-        }
-        Mult(box e1, box e2) => {
-            let e1 = f.fold_field_expression(e1)?;
-            let e2 = f.fold_field_expression(e2)?;
-            Mult(box e1, box e2)
-        }
-        Div(box e1, box e2) => {
-            let e1 = f.fold_field_expression(e1)?;
-            let e2 = f.fold_field_expression(e2)?;
-            Div(box e1, box e2)
-        }
-        Pow(box e1, box e2) => {
-            let e1 = f.fold_field_expression(e1)?;
-            let e2 = f.fold_uint_expression(e2)?;
-            Pow(box e1, box e2)
-        }
-        Neg(box e) => {
-            let e = f.fold_field_expression(e)?;
+    maybe_grow(|| {
+        let e = match e {
+            Identifier(id) => match f.fold_identifier_expression(&Type::FieldElement, id)? {
+                IdentifierOrExpression::Identifier(i) => Identifier(i),
+                IdentifierOrExpression::Expression(u) => u,
+            },
+            Block(block) => Block(f.fold_block_expression(block)?),
+            Number(n) => Number(n),
+            Add(box e1, box e2) => {
+                let e1 = f.fold_field_expression(e1)?;
+                let e2 = f.fold_field_expression(e2)?;
+                Add(box e1, box e2)
+            }
+            Sub(box e1, box e2) => {
+                let e1 = f.fold_field_expression(e1)?;
+                let e2 = f.fold_field_expression(e2)?;
+                Sub(box e1, box e2)
+            }
+            Mult(box e1, box e2) => {
+                let e1 = f.fold_field_expression(e1)?;
+                let e2 = f.fold_field_expression(e2)?;
+                Mult(box e1, box e2)
+            }
+            Div(box e1, box e2, span) => {
+                let e1 = f.fold_field_expression(e1)?;
+                let e2 = f.fold_field_expression(e2)?;
+                Div(box e1, box e2, span)
+            }
+            Pow(box e1, box e2) => {
+                let e1 = f.fold_field_expression(e1)?;
+                let e2 = f.fold_uint_expression(e2)?;
+                Pow(box e1, box e2)
+            }
+            Neg(box e) => {
+                let e = f.fold_field_expression(e)?;
 
-            Neg(box e)
-        }
-        Pos(box e) => {
-            let e = f.fold_field_expression(e)?;
+                Neg(box e)
+            }
+            Pos(box e) => {
+                let e = f.fold_field_expression(e)?;
 
-            Pos(box e)
-        }
-        And(box left, box right) => {
-            let left = f.fold_field_expression(left)?;
-            let right = f.fold_field_expression(right)?;
+                Pos(box e)
+            }
+            And(box left, box right) => {
+                let left = f.fold_field_expression(left)?;
+                let right = f.fold_field_expression(right)?;
 
-            And(box left, box right)
-        }
-        Or(box left, box right) => {
-            let left = f.fold_field_expression(left)?;
-            let right = f.fold_field_expression(right)?;
+                And(box left, box right)
+            }
+            Or(box left, box right) => {
+                let left = f.fold_field_expression(left)?;
+                let right = f.fold_field_expression(right)?;
 
-            Or(box left, box right)
-        }
-        Xor(box left, box right) => {
-            let left = f.fold_field_expression(left)?;
-            let right = f.fold_field_expression(right)?;
+                Or(box left, box right)
+            }
+            Xor(box left, box right) => {
+                let left = f.fold_field_expression(left)?;
+                let right = f.fold_field_expression(right)?;
 
-            Xor(box left, box right)
-        }
-        LeftShift(box e, box by) => {
-            let e = f.fold_field_expression(e)?;
-            let by = f.fold_uint_expression(by)?;
+                Xor(box left, box right)
+            }
+            LeftShift(box e, box by) => {
+                let e = f.fold_field_expression(e)?;
+                let by = f.fold_uint_expression(by)?;
 
-            LeftShift(box e, box by)
-        }
-        RightShift(box e, box by) => {
-            let e = f.fold_field_expression(e)?;
-            let by = f.fold_uint_expression(by)?;
+                LeftShift(box e, box by)
+            }
+            RightShift(box e, box by) => {
+                let e = f.fold_field_expression(e)?;
+                let by = f.fold_uint_expression(by)?;
 
-            RightShift(box e, box by)
-        }
-        Conditional(c) => match f.fold_conditional_expression(&Type::FieldElement, c)? {
-            ConditionalOrExpression::Conditional(c) => Conditional(c),
-            ConditionalOrExpression::Expression(u) => u,
-        },
-        FunctionCall(function_call) => {
-            match f.fold_function_call_expression(&Type::FieldElement, function_call)? {
-                FunctionCallOrExpression::FunctionCall(c) => FunctionCall(c),
-                FunctionCallOrExpression::Expression(u) => u,
+                RightShift(box e, box by)
             }
-        }
-        Member(m) => match f.fold_member_expression(&Type::FieldElement, m)? {
-            MemberOrExpression::Member(m) => Member(m),
-            MemberOrExpression::Expression(u) => u,
-        },
-        Select(select) => match f.fold_select_expression(&Type::FieldElement, select)? {
-            SelectOrExpression::Select(s) => Select(s),
-            SelectOrExpression::Expression(u) => u,
-        },
-        Element(element) => match f.fold_element_expression(&Type::FieldElement, element)? {
-            ElementOrExpression::Element(m) => Element(m),
-            ElementOrExpression::Expression(u) => u,
-        },
-    };
-    Ok(e)
+            Conditional(c) => match f.fold_conditional_expression(&Type::FieldElement, c)? {
+                ConditionalOrExpression::Conditional(c) => Conditional(c),
+                ConditionalOrExpression::Expression(u) => u,
+            },
+            FunctionCall(function_call) => {
+                match f.fold_function_call_expression(&Type::FieldElement, function_call)? {
+                    FunctionCallOrExpression::FunctionCall(c) => FunctionCall(c),
+                    FunctionCallOrExpression::Expression(u) => u,
+                }
+            }
+            Member(m) => match f.fold_member_expression(&Type::FieldElement, m)? {
+                MemberOrExpression::Member(m) => Member(m),
+                MemberOrExpression::Expression(u) => u,
+            },
+            Select(select) => match f.fold_select_expression(&Type::FieldElement, select)? {
+                SelectOrExpression::Select(s) => Select(s),
+                SelectOrExpression::Expression(u) => u,
+            },
+            Element(element) => match f.fold_element_expression(&Type::FieldElement, element)? {
+                ElementOrExpression::Element(m) => Element(m),
+                ElementOrExpression::Expression(u) => u,
+            },
+        };
+        Ok(e)
+    })
 }
 
 pub fn fold_int_expression<'ast, T: Field, F: ResultFolder<'ast, T>>(
@@ -1007,17 +1033,20 @@ pub fn fold_function_call_expression<
     _: &E::Ty,
     e: FunctionCallExpression<'ast, T, E>,
 ) -> Result<FunctionCallOrExpression<'ast, T, E>, F::Error> {
-    Ok(FunctionCallOrExpression::Expression(E::function_call(
-        f.fold_declaration_function_key(e.function_key)?,
-        e.generics
-            .into_iter()
-            .map(|g| g.map(|g| f.fold_uint_expression(g)).transpose())
-            .collect::<Result<_, _>>()?,
-        e.arguments
-            .into_iter()
-            .map(|e| f.fold_expression(e))
-            .collect::<Result<_, _>>()?,
-    )))
+    Ok(FunctionCallOrExpression::FunctionCall(
+        FunctionCallExpression::new(
+            f.fold_declaration_function_key(e.function_key)?,
+            e.generics
+                .into_iter()
+                .map(|g| g.map(|g| f.fold_uint_expression(g)).transpose())
+                .collect::<Result<_, _>>()?,
+            e.arguments
+                .into_iter()
+                .map(|e| f.fold_expression(e))
+                .collect::<Result<_, _>>()?,
+        )
+        .span(e.span),
+    ))
 }
 
 pub fn fold_boolean_expression<'ast, T: Field, F: ResultFolder<'ast, T>>(
@@ -1026,115 +1055,117 @@ pub fn fold_boolean_expression<'ast, T: Field, F: ResultFolder<'ast, T>>(
 ) -> Result<BooleanExpression<'ast, T>, F::Error> {
     use BooleanExpression::*;
 
-    let e = match e {
-        Identifier(id) => match f.fold_identifier_expression(&Type::Boolean, id)? {
-            IdentifierOrExpression::Identifier(i) => Identifier(i),
-            IdentifierOrExpression::Expression(u) => u,
-        },
-        Block(block) => Block(f.fold_block_expression(block)?),
-        Value(v) => Value(v),
-        FieldEq(e) => match f.fold_eq_expression(e)? {
-            EqOrBoolean::Eq(e) => FieldEq(e),
-            EqOrBoolean::Boolean(u) => u,
-        },
-        BoolEq(e) => match f.fold_eq_expression(e)? {
-            EqOrBoolean::Eq(e) => BoolEq(e),
-            EqOrBoolean::Boolean(u) => u,
-        },
-        ArrayEq(e) => match f.fold_eq_expression(e)? {
-            EqOrBoolean::Eq(e) => ArrayEq(e),
-            EqOrBoolean::Boolean(u) => u,
-        },
-        StructEq(e) => match f.fold_eq_expression(e)? {
-            EqOrBoolean::Eq(e) => StructEq(e),
-            EqOrBoolean::Boolean(u) => u,
-        },
-        TupleEq(e) => match f.fold_eq_expression(e)? {
-            EqOrBoolean::Eq(e) => TupleEq(e),
-            EqOrBoolean::Boolean(u) => u,
-        },
-        UintEq(e) => match f.fold_eq_expression(e)? {
-            EqOrBoolean::Eq(e) => UintEq(e),
-            EqOrBoolean::Boolean(u) => u,
-        },
-        FieldLt(box e1, box e2) => {
-            let e1 = f.fold_field_expression(e1)?;
-            let e2 = f.fold_field_expression(e2)?;
-            FieldLt(box e1, box e2)
-        }
-        FieldLe(box e1, box e2) => {
-            let e1 = f.fold_field_expression(e1)?;
-            let e2 = f.fold_field_expression(e2)?;
-            FieldLe(box e1, box e2)
-        }
-        FieldGt(box e1, box e2) => {
-            let e1 = f.fold_field_expression(e1)?;
-            let e2 = f.fold_field_expression(e2)?;
-            FieldGt(box e1, box e2)
-        }
-        FieldGe(box e1, box e2) => {
-            let e1 = f.fold_field_expression(e1)?;
-            let e2 = f.fold_field_expression(e2)?;
-            FieldGe(box e1, box e2)
-        }
-        UintLt(box e1, box e2) => {
-            let e1 = f.fold_uint_expression(e1)?;
-            let e2 = f.fold_uint_expression(e2)?;
-            UintLt(box e1, box e2)
-        }
-        UintLe(box e1, box e2) => {
-            let e1 = f.fold_uint_expression(e1)?;
-            let e2 = f.fold_uint_expression(e2)?;
-            UintLe(box e1, box e2)
-        }
-        UintGt(box e1, box e2) => {
-            let e1 = f.fold_uint_expression(e1)?;
-            let e2 = f.fold_uint_expression(e2)?;
-            UintGt(box e1, box e2)
-        }
-        UintGe(box e1, box e2) => {
-            let e1 = f.fold_uint_expression(e1)?;
-            let e2 = f.fold_uint_expression(e2)?;
-            UintGe(box e1, box e2)
-        }
-        Or(box e1, box e2) => {
-            let e1 = f.fold_boolean_expression(e1)?;
-            let e2 = f.fold_boolean_expression(e2)?;
-            Or(box e1, box e2)
-        }
-        And(box e1, box e2) => {
-            let e1 = f.fold_boolean_expression(e1)?;
-            let e2 = f.fold_boolean_expression(e2)?;
-            And(box e1, box e2)
-        }
-        Not(box e) => {
-            let e = f.fold_boolean_expression(e)?;
-            Not(box e)
-        }
-        FunctionCall(function_call) => {
-            match f.fold_function_call_expression(&Type::Boolean, function_call)? {
-                FunctionCallOrExpression::FunctionCall(c) => FunctionCall(c),
-                FunctionCallOrExpression::Expression(u) => u,
+    maybe_grow(|| {
+        let e = match e {
+            Identifier(id) => match f.fold_identifier_expression(&Type::Boolean, id)? {
+                IdentifierOrExpression::Identifier(i) => Identifier(i),
+                IdentifierOrExpression::Expression(u) => u,
+            },
+            Block(block) => Block(f.fold_block_expression(block)?),
+            Value(v) => Value(v),
+            FieldEq(e) => match f.fold_eq_expression(e)? {
+                EqOrBoolean::Eq(e) => FieldEq(e),
+                EqOrBoolean::Boolean(u) => u,
+            },
+            BoolEq(e) => match f.fold_eq_expression(e)? {
+                EqOrBoolean::Eq(e) => BoolEq(e),
+                EqOrBoolean::Boolean(u) => u,
+            },
+            ArrayEq(e) => match f.fold_eq_expression(e)? {
+                EqOrBoolean::Eq(e) => ArrayEq(e),
+                EqOrBoolean::Boolean(u) => u,
+            },
+            StructEq(e) => match f.fold_eq_expression(e)? {
+                EqOrBoolean::Eq(e) => StructEq(e),
+                EqOrBoolean::Boolean(u) => u,
+            },
+            TupleEq(e) => match f.fold_eq_expression(e)? {
+                EqOrBoolean::Eq(e) => TupleEq(e),
+                EqOrBoolean::Boolean(u) => u,
+            },
+            UintEq(e) => match f.fold_eq_expression(e)? {
+                EqOrBoolean::Eq(e) => UintEq(e),
+                EqOrBoolean::Boolean(u) => u,
+            },
+            FieldLt(box e1, box e2) => {
+                let e1 = f.fold_field_expression(e1)?;
+                let e2 = f.fold_field_expression(e2)?;
+                FieldLt(box e1, box e2)
             }
-        }
-        Conditional(c) => match f.fold_conditional_expression(&Type::Boolean, c)? {
-            ConditionalOrExpression::Conditional(c) => Conditional(c),
-            ConditionalOrExpression::Expression(u) => u,
-        },
-        Select(select) => match f.fold_select_expression(&Type::Boolean, select)? {
-            SelectOrExpression::Select(s) => Select(s),
-            SelectOrExpression::Expression(u) => u,
-        },
-        Member(m) => match f.fold_member_expression(&Type::Boolean, m)? {
-            MemberOrExpression::Member(m) => Member(m),
-            MemberOrExpression::Expression(u) => u,
-        },
-        Element(element) => match f.fold_element_expression(&Type::Boolean, element)? {
-            ElementOrExpression::Element(m) => Element(m),
-            ElementOrExpression::Expression(u) => u,
-        },
-    };
-    Ok(e)
+            FieldLe(box e1, box e2) => {
+                let e1 = f.fold_field_expression(e1)?;
+                let e2 = f.fold_field_expression(e2)?;
+                FieldLe(box e1, box e2)
+            }
+            FieldGt(box e1, box e2) => {
+                let e1 = f.fold_field_expression(e1)?;
+                let e2 = f.fold_field_expression(e2)?;
+                FieldGt(box e1, box e2)
+            }
+            FieldGe(box e1, box e2) => {
+                let e1 = f.fold_field_expression(e1)?;
+                let e2 = f.fold_field_expression(e2)?;
+                FieldGe(box e1, box e2)
+            }
+            UintLt(box e1, box e2) => {
+                let e1 = f.fold_uint_expression(e1)?;
+                let e2 = f.fold_uint_expression(e2)?;
+                UintLt(box e1, box e2)
+            }
+            UintLe(box e1, box e2) => {
+                let e1 = f.fold_uint_expression(e1)?;
+                let e2 = f.fold_uint_expression(e2)?;
+                UintLe(box e1, box e2)
+            }
+            UintGt(box e1, box e2) => {
+                let e1 = f.fold_uint_expression(e1)?;
+                let e2 = f.fold_uint_expression(e2)?;
+                UintGt(box e1, box e2)
+            }
+            UintGe(box e1, box e2) => {
+                let e1 = f.fold_uint_expression(e1)?;
+                let e2 = f.fold_uint_expression(e2)?;
+                UintGe(box e1, box e2)
+            }
+            Or(box e1, box e2) => {
+                let e1 = f.fold_boolean_expression(e1)?;
+                let e2 = f.fold_boolean_expression(e2)?;
+                Or(box e1, box e2)
+            }
+            And(box e1, box e2) => {
+                let e1 = f.fold_boolean_expression(e1)?;
+                let e2 = f.fold_boolean_expression(e2)?;
+                And(box e1, box e2)
+            }
+            Not(box e) => {
+                let e = f.fold_boolean_expression(e)?;
+                Not(box e)
+            }
+            FunctionCall(function_call) => {
+                match f.fold_function_call_expression(&Type::Boolean, function_call)? {
+                    FunctionCallOrExpression::FunctionCall(c) => FunctionCall(c),
+                    FunctionCallOrExpression::Expression(u) => u,
+                }
+            }
+            Conditional(c) => match f.fold_conditional_expression(&Type::Boolean, c)? {
+                ConditionalOrExpression::Conditional(c) => Conditional(c),
+                ConditionalOrExpression::Expression(u) => u,
+            },
+            Select(select) => match f.fold_select_expression(&Type::Boolean, select)? {
+                SelectOrExpression::Select(s) => Select(s),
+                SelectOrExpression::Expression(u) => u,
+            },
+            Member(m) => match f.fold_member_expression(&Type::Boolean, m)? {
+                MemberOrExpression::Member(m) => Member(m),
+                MemberOrExpression::Expression(u) => u,
+            },
+            Element(element) => match f.fold_element_expression(&Type::Boolean, element)? {
+                ElementOrExpression::Element(m) => Element(m),
+                ElementOrExpression::Expression(u) => u,
+            },
+        };
+        Ok(e)
+    })
 }
 
 pub fn fold_uint_expression<'ast, T: Field, F: ResultFolder<'ast, T>>(
@@ -1154,118 +1185,120 @@ pub fn fold_uint_expression_inner<'ast, T: Field, F: ResultFolder<'ast, T>>(
 ) -> Result<UExpressionInner<'ast, T>, F::Error> {
     use UExpressionInner::*;
 
-    let e = match e {
-        Identifier(id) => match f.fold_identifier_expression(&ty, id)? {
-            IdentifierOrExpression::Identifier(i) => Identifier(i),
-            IdentifierOrExpression::Expression(u) => u,
-        },
-        Block(block) => Block(f.fold_block_expression(block)?),
-        Value(v) => Value(v),
-        Add(box left, box right) => {
-            let left = f.fold_uint_expression(left)?;
-            let right = f.fold_uint_expression(right)?;
-
-            Add(box left, box right)
-        }
-        Sub(box left, box right) => {
-            let left = f.fold_uint_expression(left)?;
-            let right = f.fold_uint_expression(right)?;
+    maybe_grow(|| {
+        let e = match e {
+            Identifier(id) => match f.fold_identifier_expression(&ty, id)? {
+                IdentifierOrExpression::Identifier(i) => Identifier(i),
+                IdentifierOrExpression::Expression(u) => u,
+            },
+            Block(block) => Block(f.fold_block_expression(block)?),
+            Value(v) => Value(v),
+            Add(box left, box right) => {
+                let left = f.fold_uint_expression(left)?;
+                let right = f.fold_uint_expression(right)?;
+
+                Add(box left, box right)
+            }
+            Sub(box left, box right) => {
+                let left = f.fold_uint_expression(left)?;
+                let right = f.fold_uint_expression(right)?;
 
-            Sub(box left, box right)
-        }
-        FloorSub(box left, box right) => {
-            let left = f.fold_uint_expression(left)?;
-            let right = f.fold_uint_expression(right)?;
+                Sub(box left, box right)
+            }
+            FloorSub(box left, box right) => {
+                let left = f.fold_uint_expression(left)?;
+                let right = f.fold_uint_expression(right)?;
 
-            FloorSub(box left, box right)
-        }
-        Mult(box left, box right) => {
-            let left = f.fold_uint_expression(left)?;
-            let right = f.fold_uint_expression(right)?;
+                FloorSub(box left, box right)
+            }
+            Mult(box left, box right) => {
+                let left = f.fold_uint_expression(left)?;
+                let right = f.fold_uint_expression(right)?;
 
-            Mult(box left, box right)
-        }
-        Div(box left, box right) => {
-            let left = f.fold_uint_expression(left)?;
-            let right = f.fold_uint_expression(right)?;
+                Mult(box left, box right)
+            }
+            Div(box left, box right) => {
+                let left = f.fold_uint_expression(left)?;
+                let right = f.fold_uint_expression(right)?;
 
-            Div(box left, box right)
-        }
-        Rem(box left, box right) => {
-            let left = f.fold_uint_expression(left)?;
-            let right = f.fold_uint_expression(right)?;
+                Div(box left, box right)
+            }
+            Rem(box left, box right) => {
+                let left = f.fold_uint_expression(left)?;
+                let right = f.fold_uint_expression(right)?;
 
-            Rem(box left, box right)
-        }
-        Xor(box left, box right) => {
-            let left = f.fold_uint_expression(left)?;
-            let right = f.fold_uint_expression(right)?;
+                Rem(box left, box right)
+            }
+            Xor(box left, box right) => {
+                let left = f.fold_uint_expression(left)?;
+                let right = f.fold_uint_expression(right)?;
 
-            Xor(box left, box right)
-        }
-        And(box left, box right) => {
-            let left = f.fold_uint_expression(left)?;
-            let right = f.fold_uint_expression(right)?;
+                Xor(box left, box right)
+            }
+            And(box left, box right) => {
+                let left = f.fold_uint_expression(left)?;
+                let right = f.fold_uint_expression(right)?;
 
-            And(box left, box right)
-        }
-        Or(box left, box right) => {
-            let left = f.fold_uint_expression(left)?;
-            let right = f.fold_uint_expression(right)?;
+                And(box left, box right)
+            }
+            Or(box left, box right) => {
+                let left = f.fold_uint_expression(left)?;
+                let right = f.fold_uint_expression(right)?;
 
-            Or(box left, box right)
-        }
-        LeftShift(box e, box by) => {
-            let e = f.fold_uint_expression(e)?;
-            let by = f.fold_uint_expression(by)?;
+                Or(box left, box right)
+            }
+            LeftShift(box e, box by) => {
+                let e = f.fold_uint_expression(e)?;
+                let by = f.fold_uint_expression(by)?;
 
-            LeftShift(box e, box by)
-        }
-        RightShift(box e, box by) => {
-            let e = f.fold_uint_expression(e)?;
-            let by = f.fold_uint_expression(by)?;
+                LeftShift(box e, box by)
+            }
+            RightShift(box e, box by) => {
+                let e = f.fold_uint_expression(e)?;
+                let by = f.fold_uint_expression(by)?;
 
-            RightShift(box e, box by)
-        }
-        Not(box e) => {
-            let e = f.fold_uint_expression(e)?;
+                RightShift(box e, box by)
+            }
+            Not(box e) => {
+                let e = f.fold_uint_expression(e)?;
 
-            Not(box e)
-        }
-        Neg(box e) => {
-            let e = f.fold_uint_expression(e)?;
+                Not(box e)
+            }
+            Neg(box e) => {
+                let e = f.fold_uint_expression(e)?;
 
-            Neg(box e)
-        }
-        Pos(box e) => {
-            let e = f.fold_uint_expression(e)?;
+                Neg(box e)
+            }
+            Pos(box e) => {
+                let e = f.fold_uint_expression(e)?;
 
-            Pos(box e)
-        }
-        FunctionCall(function_call) => {
-            match f.fold_function_call_expression(&ty, function_call)? {
-                FunctionCallOrExpression::FunctionCall(c) => FunctionCall(c),
-                FunctionCallOrExpression::Expression(u) => u,
+                Pos(box e)
             }
-        }
-        Select(select) => match f.fold_select_expression(&ty, select)? {
-            SelectOrExpression::Select(s) => Select(s),
-            SelectOrExpression::Expression(u) => u,
-        },
-        Conditional(c) => match f.fold_conditional_expression(&ty, c)? {
-            ConditionalOrExpression::Conditional(c) => Conditional(c),
-            ConditionalOrExpression::Expression(u) => u,
-        },
-        Member(m) => match f.fold_member_expression(&ty, m)? {
-            MemberOrExpression::Member(m) => Member(m),
-            MemberOrExpression::Expression(u) => u,
-        },
-        Element(element) => match f.fold_element_expression(&ty, element)? {
-            ElementOrExpression::Element(m) => Element(m),
-            ElementOrExpression::Expression(u) => u,
-        },
-    };
-    Ok(e)
+            FunctionCall(function_call) => {
+                match f.fold_function_call_expression(&ty, function_call)? {
+                    FunctionCallOrExpression::FunctionCall(c) => FunctionCall(c),
+                    FunctionCallOrExpression::Expression(u) => u,
+                }
+            }
+            Select(select) => match f.fold_select_expression(&ty, select)? {
+                SelectOrExpression::Select(s) => Select(s),
+                SelectOrExpression::Expression(u) => u,
+            },
+            Conditional(c) => match f.fold_conditional_expression(&ty, c)? {
+                ConditionalOrExpression::Conditional(c) => Conditional(c),
+                ConditionalOrExpression::Expression(u) => u,
+            },
+            Member(m) => match f.fold_member_expression(&ty, m)? {
+                MemberOrExpression::Member(m) => Member(m),
+                MemberOrExpression::Expression(u) => u,
+            },
+            Element(element) => match f.fold_element_expression(&ty, element)? {
+                ElementOrExpression::Element(m) => Element(m),
+                ElementOrExpression::Expression(u) => u,
+            },
+        };
+        Ok(e)
+    })
 }
 
 pub fn fold_declaration_function_key<'ast, T: Field, F: ResultFolder<'ast, T>>(
@@ -1450,6 +1483,7 @@ pub fn fold_constant_symbol_declaration<'ast, T: Field, F: ResultFolder<'ast, T>
     Ok(TypedConstantSymbolDeclaration {
         id: f.fold_canonical_constant_identifier(d.id)?,
         symbol: f.fold_constant_symbol(d.symbol)?,
+        public: d.public,
     })
 }
 
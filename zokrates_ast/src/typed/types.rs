@@ -43,8 +43,9 @@ impl<S> IntoType<S> for UBitwidth {
     }
 }
 
-#[derive(Debug, Clone, Eq)]
+#[derive(Debug, Clone, Eq, Serialize, Deserialize)]
 pub struct GenericIdentifier<'ast> {
+    #[serde(borrow)]
     name: Option<&'ast str>,
     index: usize,
 }
@@ -142,8 +143,9 @@ impl<'ast> CanonicalConstantIdentifier<'ast> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum DeclarationConstant<'ast, T> {
+    #[serde(borrow)]
     Generic(GenericIdentifier<'ast>),
     Concrete(u32),
     Constant(CanonicalConstantIdentifier<'ast>),
@@ -287,6 +289,10 @@ pub struct GStructMember<S> {
     pub id: MemberId,
     #[serde(flatten)]
     pub ty: Box<GType<S>>,
+    /// The number of bits the value of the member is asserted to fit in, for `field<N>` members and arrays of them.
+    /// It is checked when the struct is an input of the program
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bound: Option<u32>,
 }
 
 pub type DeclarationStructMember<'ast, T> = GStructMember<DeclarationConstant<'ast, T>>;
@@ -295,7 +301,7 @@ pub type StructMember<'ast, T> = GStructMember<UExpression<'ast, T>>;
 
 impl<S, R: PartialEq<S>> PartialEq<GStructMember<S>> for GStructMember<R> {
     fn eq(&self, other: &GStructMember<S>) -> bool {
-        self.id == other.id && *self.ty == *other.ty
+        self.id == other.id && *self.ty == *other.ty && self.bound == other.bound
     }
 }
 
@@ -305,6 +311,7 @@ fn try_from_g_struct_member<T: TryInto<U>, U>(
     Ok(GStructMember {
         id: t.id,
         ty: box try_from_g_type(*t.ty)?,
+        bound: t.bound,
     })
 }
 
@@ -470,7 +477,7 @@ impl<S> TryFrom<GType<S>> for GTupleType<S> {
 
 #[derive(Debug, Clone, Hash, Serialize, Deserialize, PartialOrd, Ord, Eq, PartialEq)]
 pub struct StructLocation {
-    #[serde(skip)]
+    #[serde(default, skip_serializing_if = "in_abi")]
     pub module: PathBuf,
     pub name: String,
 }
@@ -481,11 +488,11 @@ impl<'ast, T> From<ConcreteArrayType> for DeclarationArrayType<'ast, T> {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialOrd, Ord)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GStructType<S> {
     #[serde(flatten)]
     pub canonical_location: StructLocation,
-    #[serde(skip)]
+    #[serde(default, skip_serializing_if = "in_abi")]
     pub location: Option<StructLocation>,
     pub generics: Vec<Option<S>>,
     pub members: Vec<GStructMember<S>>,
@@ -509,6 +516,21 @@ impl<S, R: PartialEq<S>> PartialEq<GStructType<S>> for GStructType<R> {
     }
 }
 
+// struct types are nominal: a type imported in several modules under different names is the same type
+impl<S: PartialOrd> PartialOrd for GStructType<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        (&self.canonical_location, &self.generics)
+            .partial_cmp(&(&other.canonical_location, &other.generics))
+    }
+}
+
+impl<S: Ord> Ord for GStructType<S> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (&self.canonical_location, &self.generics)
+            .cmp(&(&other.canonical_location, &other.generics))
+    }
+}
+
 impl<S: Hash> Hash for GStructType<S> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.canonical_location.hash(state);
@@ -605,21 +627,77 @@ impl<S> IntoIterator for GStructType<S> {
     }
 }
 
+/// The bitwidth of an integer type. Signed integers are represented as unsigned integers of the same bitwidth in
+/// two's complement, so they share the uint machinery and only differ where the sign matters
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Copy)]
 pub enum UBitwidth {
     #[serde(rename = "8")]
-    B8 = 8,
+    B8,
     #[serde(rename = "16")]
-    B16 = 16,
+    B16,
     #[serde(rename = "32")]
-    B32 = 32,
+    B32,
     #[serde(rename = "64")]
-    B64 = 64,
+    B64,
+    #[serde(rename = "i8")]
+    I8,
+    #[serde(rename = "i16")]
+    I16,
+    #[serde(rename = "i32")]
+    I32,
 }
 
 impl UBitwidth {
     pub fn to_usize(self) -> usize {
-        self as usize
+        match self {
+            UBitwidth::B8 | UBitwidth::I8 => 8,
+            UBitwidth::B16 | UBitwidth::I16 => 16,
+            UBitwidth::B32 | UBitwidth::I32 => 32,
+            UBitwidth::B64 => 64,
+        }
+    }
+
+    pub fn signed(b: usize) -> Self {
+        match b {
+            8 => UBitwidth::I8,
+            16 => UBitwidth::I16,
+            32 => UBitwidth::I32,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn is_signed(self) -> bool {
+        matches!(self, UBitwidth::I8 | UBitwidth::I16 | UBitwidth::I32)
+    }
+
+    /// The value of the sign bit of a signed integer, if any
+    pub fn sign_bit(self) -> Option<u128> {
+        match self.is_signed() {
+            true => Some(1 << (self.to_usize() - 1)),
+            false => None,
+        }
+    }
+
+    /// Interpret the representation `v` of an integer of this bitwidth, as a negative number if its sign bit is set
+    pub fn signed_value(self, v: u128) -> i128 {
+        match self.sign_bit() {
+            Some(sign) if v & sign != 0 => v as i128 - (sign << 1) as i128,
+            _ => v as i128,
+        }
+    }
+
+    /// Map the two's complement representation `v` to a value with the same order in the unsigned integers,
+    /// by flipping the sign bit of signed integers
+    pub fn order_key(self, v: u128) -> u128 {
+        v ^ self.sign_bit().unwrap_or(0)
+    }
+
+    /// The name of the integer type of this bitwidth, for example `u8` or `i8`
+    pub fn type_name(self) -> String {
+        match self.is_signed() {
+            true => format!("i{}", self.to_usize()),
+            false => format!("u{}", self.to_usize()),
+        }
     }
 }
 
@@ -641,6 +719,25 @@ impl fmt::Display for UBitwidth {
     }
 }
 
+thread_local! {
+    static IN_FULL: std::cell::Cell<bool> = std::cell::Cell::new(false);
+}
+
+/// Run `f` with types serialized in full. By default, types are serialized as in the ABI, which leaves out where
+/// struct types are declared and cannot represent the `int` type, while snapshots of checked modules need both
+pub fn in_full<R, F: FnOnce() -> R>(f: F) -> R {
+    IN_FULL.with(|in_full| {
+        let previous = in_full.replace(true);
+        let res = f();
+        in_full.set(previous);
+        res
+    })
+}
+
+fn in_abi<U>(_: &U) -> bool {
+    !IN_FULL.with(|in_full| in_full.get())
+}
+
 #[allow(clippy::derive_hash_xor_eq)]
 #[derive(Clone, Eq, Hash, PartialOrd, Ord, Debug)]
 pub enum GType<S> {
@@ -681,12 +778,10 @@ impl<Z: Serialize> Serialize for GType<Z> {
                 map.serialize_entry("components", tuple_type)?;
                 map.end()
             }
-            GType::Uint(width) => s.serialize_newtype_variant(
-                "Type",
-                4,
-                "type",
-                format!("u{}", width.to_usize()).as_str(),
-            ),
+            GType::Uint(width) => {
+                s.serialize_newtype_variant("Type", 4, "type", width.type_name().as_str())
+            }
+            GType::Int if !in_abi(self) => s.serialize_newtype_variant("Type", 5, "type", "int"),
             GType::Int => Err(S::Error::custom(
                 "Cannot serialize Int type as it's not allowed in function signatures".to_string(),
             )),
@@ -760,6 +855,10 @@ impl<'de, S: Deserialize<'de>> Deserialize<'de> for GType<S> {
             "u16" => strict_type(mapping, GType::Uint(UBitwidth::B16)),
             "u32" => strict_type(mapping, GType::Uint(UBitwidth::B32)),
             "u64" => strict_type(mapping, GType::Uint(UBitwidth::B64)),
+            "i8" => strict_type(mapping, GType::Uint(UBitwidth::I8)),
+            "i16" => strict_type(mapping, GType::Uint(UBitwidth::I16)),
+            "i32" => strict_type(mapping, GType::Uint(UBitwidth::I32)),
+            "int" if !in_abi(&mapping) => strict_type(mapping, GType::Int),
             t => Err(D::Error::custom(format!("invalid type `{}`", t))),
         }
     }
@@ -839,8 +938,13 @@ impl<S> GStructMember<S> {
         GStructMember {
             id,
             ty: Box::new(ty),
+            bound: None,
         }
     }
+
+    pub fn bound(self, bound: Option<u32>) -> Self {
+        GStructMember { bound, ..self }
+    }
 }
 
 impl<S: fmt::Display> fmt::Display for GType<S> {
@@ -848,7 +952,7 @@ impl<S: fmt::Display> fmt::Display for GType<S> {
         match self {
             GType::FieldElement => write!(f, "field"),
             GType::Boolean => write!(f, "bool"),
-            GType::Uint(ref bitwidth) => write!(f, "u{}", bitwidth),
+            GType::Uint(ref bitwidth) => write!(f, "{}", bitwidth.type_name()),
             GType::Int => write!(f, "{{integer}}"),
             GType::Array(ref array_type) => write!(f, "{}", array_type),
             GType::Struct(ref struct_type) => write!(f, "{}", struct_type),
@@ -902,6 +1006,39 @@ impl<S> GType<S> {
     pub fn is_empty_tuple(&self) -> bool {
         matches!(self, GType::Tuple(ty) if ty.elements.is_empty())
     }
+
+    /// This type with its `field` elements bounded to `bound` bits, for display
+    pub fn with_bound(&self, bound: Option<u32>) -> BoundedType<S> {
+        BoundedType { ty: self, bound }
+    }
+}
+
+/// A type whose `field` elements are declared `field<N>`, displayed as in the source, such as `field<8>[2]`
+pub struct BoundedType<'a, S> {
+    ty: &'a GType<S>,
+    bound: Option<u32>,
+}
+
+impl<'a, S: fmt::Display> fmt::Display for BoundedType<'a, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.ty, self.bound) {
+            (GType::FieldElement, Some(bound)) => write!(f, "field<{}>", bound),
+            (GType::Array(_), Some(_)) => {
+                let mut sizes = vec![];
+                let mut ty = self.ty;
+                while let GType::Array(array_type) = ty {
+                    sizes.push(&array_type.size);
+                    ty = &array_type.ty;
+                }
+                write!(f, "{}", ty.with_bound(self.bound))?;
+                for size in sizes {
+                    write!(f, "[{}]", size)?;
+                }
+                Ok(())
+            }
+            (ty, _) => write!(f, "{}", ty),
+        }
+    }
 }
 
 impl<'ast, T: fmt::Display + PartialEq + fmt::Debug> Type<'ast, T> {
@@ -961,13 +1098,36 @@ impl ConcreteType {
                 .sum(),
         }
     }
+
+    /// The number of bits each of the field elements the type maps to is asserted to fit in, given the bound declared
+    /// for the type itself: the bounds of `field<N>` values, and of the `field<N>` members of structs
+    pub fn get_primitive_bounds(&self, bound: Option<u32>) -> Vec<Option<u32>> {
+        match self {
+            GType::FieldElement => vec![bound],
+            GType::Boolean | GType::Uint(_) => vec![None],
+            GType::Array(array_type) => (0..*array_type.size)
+                .flat_map(|_| array_type.ty.get_primitive_bounds(bound))
+                .collect(),
+            GType::Tuple(tuple_type) => tuple_type
+                .elements
+                .iter()
+                .flat_map(|e| e.get_primitive_bounds(None))
+                .collect(),
+            GType::Int => unreachable!(),
+            GType::Struct(struct_type) => struct_type
+                .iter()
+                .flat_map(|member| member.ty.get_primitive_bounds(member.bound))
+                .collect(),
+        }
+    }
 }
 
 pub type FunctionIdentifier<'ast> = &'ast str;
 
-#[derive(PartialEq, Eq, Hash, Debug, Clone, PartialOrd, Ord)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct GFunctionKey<'ast, S> {
     pub module: OwnedTypedModuleId,
+    #[serde(borrow)]
     pub id: FunctionIdentifier<'ast>,
     pub signature: GSignature<S>,
 }
@@ -988,6 +1148,20 @@ pub struct GGenericsAssignment<'ast, S>(pub BTreeMap<GenericIdentifier<'ast>, S>
 pub type ConcreteGenericsAssignment<'ast> = GGenericsAssignment<'ast, u32>;
 pub type GenericsAssignment<'ast, T> = GGenericsAssignment<'ast, UExpression<'ast, T>>;
 
+// serialized as a list of pairs, as generic identifiers cannot be used as keys in every format
+impl<'ast, S: Serialize> Serialize for GGenericsAssignment<'ast, S> {
+    fn serialize<Z: Serializer>(&self, s: Z) -> Result<Z::Ok, Z::Error> {
+        s.collect_seq(self.0.iter())
+    }
+}
+
+impl<'de: 'ast, 'ast, S: Deserialize<'de>> Deserialize<'de> for GGenericsAssignment<'ast, S> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        Vec::<(GenericIdentifier<'ast>, S)>::deserialize(d)
+            .map(|assignment| GGenericsAssignment(assignment.into_iter().collect()))
+    }
+}
+
 impl<'ast, S> Default for GGenericsAssignment<'ast, S> {
     fn default() -> Self {
         GGenericsAssignment(BTreeMap::new())
@@ -1207,8 +1381,14 @@ pub fn specialize_declaration_type<
                     .into_iter()
                     .map(|m| {
                         let id = m.id;
-                        specialize_declaration_type(*m.ty, &inside_generics)
-                            .map(|ty| GStructMember { ty: box ty, id })
+                        let bound = m.bound;
+                        specialize_declaration_type(*m.ty, &inside_generics).map(|ty| {
+                            GStructMember {
+                                ty: box ty,
+                                id,
+                                bound,
+                            }
+                        })
                     })
                     .collect::<Result<_, _>>()?,
                 generics: s0
@@ -1548,4 +1728,84 @@ mod tests {
         ));
         assert_eq!(format!("{}", t), "field[1][2]");
     }
+
+    #[test]
+    fn bounded_display() {
+        // field<8>[1][2]
+        let t = ConcreteType::Array(ConcreteArrayType::new(
+            ConcreteType::Array(ConcreteArrayType::new(ConcreteType::FieldElement, 2u32)),
+            1u32,
+        ));
+        assert_eq!(t.with_bound(Some(8)).to_string(), "field<8>[1][2]");
+        assert_eq!(t.with_bound(None).to_string(), "field[1][2]");
+        assert_eq!(
+            ConcreteType::FieldElement.with_bound(Some(8)).to_string(),
+            "field<8>"
+        );
+    }
+
+    #[test]
+    fn primitive_bounds() {
+        let s = ConcreteType::Struct(ConcreteStructType::new(
+            "".into(),
+            "Foo".into(),
+            vec![],
+            vec![
+                ConcreteStructMember::new("a".into(), ConcreteType::Boolean),
+                ConcreteStructMember::new(
+                    "b".into(),
+                    ConcreteType::Array(ConcreteArrayType::new(ConcreteType::FieldElement, 2u32)),
+                )
+                .bound(Some(8)),
+            ],
+        ));
+        let t = ConcreteType::Array(ConcreteArrayType::new(s, 2u32));
+
+        assert_eq!(
+            t.get_primitive_bounds(None),
+            vec![None, Some(8), Some(8), None, Some(8), Some(8)]
+        );
+    }
+
+    #[test]
+    fn nominal_struct_types() {
+        let point = ConcreteStructType::new(
+            "types".into(),
+            "Point".into(),
+            vec![],
+            vec![ConcreteStructMember::new(
+                "x".into(),
+                ConcreteType::FieldElement,
+            )],
+        );
+
+        // `Point` imported as `P` in another module
+        let imported = ConcreteStructType {
+            location: Some(StructLocation {
+                module: "main".into(),
+                name: "P".into(),
+            }),
+            ..point.clone()
+        };
+
+        assert_eq!(point, imported);
+        assert_eq!(point.cmp(&imported), std::cmp::Ordering::Equal);
+        assert_eq!(
+            vec![point.clone(), imported]
+                .into_iter()
+                .collect::<std::collections::BTreeSet<_>>()
+                .len(),
+            1
+        );
+
+        // a struct declared in another module is a different type, even with the same name and members
+        let other = ConcreteStructType::new(
+            "other".into(),
+            "Point".into(),
+            vec![],
+            point.members.clone(),
+        );
+        assert_ne!(point, other);
+        assert_ne!(point.cmp(&other), std::cmp::Ordering::Equal);
+    }
 }
@@ -122,6 +122,7 @@ impl<'ast> From<pest::ConstantDefinition<'ast>> for untyped::SymbolDeclarationNo
         let id = definition.id.identifier.span.as_str();
 
         let ty = untyped::ConstantDefinition {
+            public: definition.public.is_some(),
             ty: definition.id.ty.into(),
             expression: definition.expression.into(),
         }
@@ -179,13 +180,39 @@ impl<'ast> From<pest::FunctionDefinition<'ast>> for untyped::SymbolDeclarationNo
                     .parameters
                     .clone()
                     .into_iter()
-                    .map(|p| untyped::UnresolvedTypeNode::from(p.ty))
+                    .map(|p| {
+                        // the bounds of `field<N>` arguments are recorded on the arguments
+                        untyped::UnresolvedType::strip_bound(untyped::UnresolvedTypeNode::from(
+                            p.ty,
+                        ))
+                        .0
+                    })
                     .collect(),
             );
 
-        let signature = match function.return_type {
-            Some(ret_ty) => signature.output(untyped::UnresolvedTypeNode::from(ret_ty)),
-            None => signature,
+        let signature = match (function.return_type, function.return_tuple) {
+            (Some(ret_ty), _) => signature.output(untyped::UnresolvedTypeNode::from(ret_ty)),
+            (None, Some(ret_tuple)) => signature
+                .private_outputs(
+                    ret_tuple
+                        .elements
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, e)| matches!(e.visibility, Some(pest::Visibility::Private(_))))
+                        .map(|(index, _)| index)
+                        .collect(),
+                )
+                .output(
+                    untyped::types::UnresolvedType::Tuple(
+                        ret_tuple
+                            .elements
+                            .into_iter()
+                            .map(|e| untyped::UnresolvedTypeNode::from(e.ty))
+                            .collect(),
+                    )
+                    .span(ret_tuple.span),
+                ),
+            (None, None) => signature,
         };
 
         let id = function.id.span.as_str();
@@ -230,14 +257,25 @@ impl<'ast> From<pest::Parameter<'ast>> for untyped::ParameterNode<'ast> {
 
         let is_mutable = param.mutable.is_some();
 
-        let variable = untyped::Variable::new(
-            param.id.span.as_str(),
-            untyped::UnresolvedTypeNode::from(param.ty),
-            is_mutable,
-        )
-        .span(param.id.span);
-
-        untyped::Parameter::new(variable, is_private).span(param.span)
+        let (ty, bound) =
+            untyped::UnresolvedType::strip_bound(untyped::UnresolvedTypeNode::from(param.ty));
+
+        let variable =
+            untyped::Variable::new(param.id.span.as_str(), ty, is_mutable).span(param.id.span);
+
+        untyped::Parameter {
+            derive_nonce: param
+                .attributes
+                .iter()
+                .any(|a| matches!(a, pest::ParameterAttribute::DeriveNonce(_))),
+            bind_at_setup: param
+                .attributes
+                .iter()
+                .any(|a| matches!(a, pest::ParameterAttribute::BindAtSetup(_))),
+            bound,
+            ..untyped::Parameter::new(variable, is_private)
+        }
+        .span(param.span)
     }
 }
 
@@ -323,6 +361,7 @@ impl<'ast> From<pest::AssertionStatement<'ast>> for untyped::StatementNode<'ast>
         untyped::Statement::Assertion(
             untyped::ExpressionNode::from(statement.expression),
             statement.message.map(|m| m.raw.value),
+            statement.critical.is_some(),
         )
         .span(statement.span)
     }
@@ -336,8 +375,9 @@ impl<'ast> From<pest::IterationStatement<'ast>> for untyped::StatementNode<'ast>
         let to = untyped::ExpressionNode::from(statement.to);
         let statements: Vec<untyped::StatementNode<'ast>> =
             statement.statements.into_iter().map(|s| s.into()).collect();
+        let label = statement.label.map(|label| label.id.span.as_str());
 
-        untyped::Statement::For(index, from, to, statements).span(statement.span)
+        untyped::Statement::For(index, from, to, statements, label).span(statement.span)
     }
 }
 
@@ -357,6 +397,14 @@ impl<'ast> From<pest::AssemblyStatement<'ast>> for untyped::StatementNode<'ast>
                     )
                     .span(a.span)
                 }
+                pest::AssemblyStatementInner::UnsafeWitness(w) => {
+                    untyped::AssemblyStatement::UnsafeWitness(
+                        w.assignee.into(),
+                        w.plugin.raw.span.as_str(),
+                        w.arguments.into_iter().map(|a| a.into()).collect(),
+                    )
+                    .span(w.span)
+                }
                 pest::AssemblyStatementInner::Constraint(c) => {
                     untyped::AssemblyStatement::Constraint(c.lhs.into(), c.rhs.into()).span(c.span)
                 }
@@ -695,33 +743,41 @@ impl<'ast> From<pest::DecimalLiteralExpression<'ast>> for untyped::ExpressionNod
     fn from(expression: pest::DecimalLiteralExpression<'ast>) -> untyped::ExpressionNode<'ast> {
         use crate::untyped::NodeValue;
 
+        let value = BigUint::parse_bytes(expression.value.span.as_str().as_bytes(), 10).unwrap();
+
         match expression.suffix {
             Some(suffix) => match suffix {
-                pest::DecimalSuffix::Field(_) => untyped::Expression::FieldConstant(
-                    BigUint::parse_bytes(expression.value.span.as_str().as_bytes(), 10).unwrap(),
-                ),
-                pest::DecimalSuffix::U64(_) => untyped::Expression::U64Constant(
-                    expression.value.span.as_str().parse().unwrap(),
-                ),
-                pest::DecimalSuffix::U32(_) => untyped::Expression::U32Constant(
-                    expression.value.span.as_str().parse().unwrap(),
-                ),
-                pest::DecimalSuffix::U16(_) => untyped::Expression::U16Constant(
-                    expression.value.span.as_str().parse().unwrap(),
-                ),
-                pest::DecimalSuffix::U8(_) => {
-                    untyped::Expression::U8Constant(expression.value.span.as_str().parse().unwrap())
+                pest::DecimalSuffix::Field(_) => untyped::Expression::FieldConstant(value),
+                pest::DecimalSuffix::U64(_) => uint_constant(value, 64),
+                pest::DecimalSuffix::U32(_) => uint_constant(value, 32),
+                pest::DecimalSuffix::U16(_) => uint_constant(value, 16),
+                pest::DecimalSuffix::U8(_) => uint_constant(value, 8),
+                pest::DecimalSuffix::Wrapping(suffix) => {
+                    // explicitly truncate the value to the bitwidth of the suffix
+                    let bitwidth = suffix.span.as_str()[1..].parse().unwrap();
+                    uint_constant(value % (BigUint::from(1u32) << bitwidth), bitwidth)
                 }
             }
             .span(expression.span),
-            None => untyped::Expression::IntConstant(
-                BigUint::parse_bytes(expression.value.span.as_str().as_bytes(), 10).unwrap(),
-            )
-            .span(expression.span),
+            None => untyped::Expression::IntConstant(value).span(expression.span),
         }
     }
 }
 
+/// Build the constant for a literal of type `u{bitwidth}`, keeping values which do not fit for the semantic checker to report
+fn uint_constant<'ast>(value: BigUint, bitwidth: usize) -> untyped::Expression<'ast> {
+    let fits = value.bits() <= bitwidth;
+
+    match (bitwidth, fits) {
+        (_, false) => untyped::Expression::OutOfRangeUintConstant(value, bitwidth),
+        (8, true) => untyped::Expression::U8Constant(value.to_str_radix(10).parse().unwrap()),
+        (16, true) => untyped::Expression::U16Constant(value.to_str_radix(10).parse().unwrap()),
+        (32, true) => untyped::Expression::U32Constant(value.to_str_radix(10).parse().unwrap()),
+        (64, true) => untyped::Expression::U64Constant(value.to_str_radix(10).parse().unwrap()),
+        _ => unreachable!(),
+    }
+}
+
 impl<'ast> From<pest::HexLiteralExpression<'ast>> for untyped::ExpressionNode<'ast> {
     fn from(expression: pest::HexLiteralExpression<'ast>) -> untyped::ExpressionNode<'ast> {
         use crate::untyped::NodeValue;
@@ -800,6 +856,20 @@ impl<'ast> From<pest::Assignee<'ast>> for untyped::AssigneeNode<'ast> {
     }
 }
 
+fn field_type(t: pest::FieldType) -> untyped::UnresolvedTypeNode {
+    use crate::untyped::types::UnresolvedType;
+    use crate::untyped::NodeValue;
+
+    match t.bound {
+        // bounds which do not fit in a `u32` are larger than any field, and rejected as such by the checker
+        Some(bound) => {
+            UnresolvedType::BoundedField(bound.value.span.as_str().parse().unwrap_or(u32::MAX))
+        }
+        None => UnresolvedType::FieldElement,
+    }
+    .span(t.span)
+}
+
 impl<'ast> From<pest::Type<'ast>> for untyped::UnresolvedTypeNode<'ast> {
     fn from(t: pest::Type<'ast>) -> untyped::UnresolvedTypeNode<'ast> {
         use crate::untyped::types::UnresolvedType;
@@ -807,22 +877,28 @@ impl<'ast> From<pest::Type<'ast>> for untyped::UnresolvedTypeNode<'ast> {
 
         match t {
             pest::Type::Basic(t) => match t {
-                pest::BasicType::Field(t) => UnresolvedType::FieldElement.span(t.span),
+                pest::BasicType::Field(t) => field_type(t),
                 pest::BasicType::Boolean(t) => UnresolvedType::Boolean.span(t.span),
                 pest::BasicType::U8(t) => UnresolvedType::Uint(8).span(t.span),
                 pest::BasicType::U16(t) => UnresolvedType::Uint(16).span(t.span),
                 pest::BasicType::U32(t) => UnresolvedType::Uint(32).span(t.span),
                 pest::BasicType::U64(t) => UnresolvedType::Uint(64).span(t.span),
+                pest::BasicType::I8(t) => UnresolvedType::Signed(8).span(t.span),
+                pest::BasicType::I16(t) => UnresolvedType::Signed(16).span(t.span),
+                pest::BasicType::I32(t) => UnresolvedType::Signed(32).span(t.span),
             },
             pest::Type::Array(t) => {
                 let inner_type = match t.ty {
                     pest::BasicOrStructOrTupleType::Basic(t) => match t {
-                        pest::BasicType::Field(t) => UnresolvedType::FieldElement.span(t.span),
+                        pest::BasicType::Field(t) => field_type(t),
                         pest::BasicType::Boolean(t) => UnresolvedType::Boolean.span(t.span),
                         pest::BasicType::U8(t) => UnresolvedType::Uint(8).span(t.span),
                         pest::BasicType::U16(t) => UnresolvedType::Uint(16).span(t.span),
                         pest::BasicType::U32(t) => UnresolvedType::Uint(32).span(t.span),
                         pest::BasicType::U64(t) => UnresolvedType::Uint(64).span(t.span),
+                        pest::BasicType::I8(t) => UnresolvedType::Signed(8).span(t.span),
+                        pest::BasicType::I16(t) => UnresolvedType::Signed(16).span(t.span),
+                        pest::BasicType::I32(t) => UnresolvedType::Signed(32).span(t.span),
                     },
                     pest::BasicOrStructOrTupleType::Struct(t) => UnresolvedType::User(
                         t.id.span.as_str().to_string(),
@@ -1206,6 +1282,54 @@ mod tests {
             );
         }
     }
+    mod literals {
+        use super::*;
+
+        fn wrap(expression: untyped::Expression<'static>) -> untyped::Module {
+            untyped::Module {
+                symbols: vec![untyped::SymbolDeclaration {
+                    id: "main",
+                    symbol: untyped::Symbol::Here(untyped::SymbolDefinition::Function(
+                        untyped::Function {
+                            arguments: vec![],
+                            statements: vec![
+                                untyped::Statement::Return(Some(expression.into())).into()
+                            ],
+                            signature: UnresolvedSignature::new(),
+                        }
+                        .into(),
+                    )),
+                }
+                .into()],
+            }
+        }
+
+        #[test]
+        fn uint_suffixes() {
+            let vectors = vec![
+                ("255u8", untyped::Expression::U8Constant(255)),
+                (
+                    "256u8",
+                    untyped::Expression::OutOfRangeUintConstant(256u32.into(), 8),
+                ),
+                ("300w8", untyped::Expression::U8Constant(44)),
+                ("65536w16", untyped::Expression::U16Constant(0)),
+                ("42_w32", untyped::Expression::U32Constant(42)),
+                (
+                    "18446744073709551617w64",
+                    untyped::Expression::U64Constant(1),
+                ),
+            ];
+
+            for (source, expected) in vectors {
+                let source = format!("def main() {{ return {}; }}", source);
+                let expected = wrap(expected);
+                let ast = pest::generate_ast(&source).unwrap();
+                assert_eq!(untyped::Module::from(ast), expected);
+            }
+        }
+    }
+
     #[test]
     fn declarations() {
         use self::pest::Span;
@@ -1251,6 +1375,7 @@ mod tests {
         let definition = pest::DefinitionStatement {
             lhs: pest::TypedIdentifierOrAssignee::TypedIdentifier(pest::TypedIdentifier {
                 ty: pest::Type::Basic(pest::BasicType::Field(pest::FieldType {
+                    bound: None,
                     span: span.clone(),
                 })),
                 identifier: pest::IdentifierExpression {
@@ -23,7 +23,7 @@ use std::path::{Path, PathBuf};
 use std::fmt;
 
 use num_bigint::BigUint;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 /// An identifier of a function or a variable
 pub type Identifier<'ast> = &'ast str;
@@ -44,6 +44,32 @@ pub struct Program<'ast> {
     pub main: OwnedModuleId,
 }
 
+impl<'ast> Program<'ast> {
+    /// The modules written by the user: the main module and the modules it imports with relative paths, transitively.
+    /// The others, such as the modules of the standard library, are libraries
+    pub fn user_modules(&self) -> BTreeSet<OwnedModuleId> {
+        let mut user_modules = BTreeSet::new();
+        let mut pending = vec![self.main.clone()];
+
+        while let Some(module_id) = pending.pop() {
+            if !user_modules.insert(module_id.clone()) {
+                continue;
+            }
+
+            if let Some(module) = self.modules.get(&module_id) {
+                pending.extend(module.symbols.iter().filter_map(|s| match &s.value.symbol {
+                    Symbol::There(import) if import.value.relative => {
+                        Some(import.value.module_id.clone())
+                    }
+                    _ => None,
+                }));
+            }
+        }
+
+        user_modules
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct SymbolIdentifier<'ast> {
     pub id: Identifier<'ast>,
@@ -95,6 +121,9 @@ impl<'ast> fmt::Display for CanonicalImport<'ast> {
 pub struct SymbolImport<'ast> {
     pub module_id: OwnedModuleId,
     pub symbol_id: Identifier<'ast>,
+    /// Whether the module was imported with a path relative to the importing module, such as `./foo`, rather than
+    /// from a library
+    pub relative: bool,
 }
 
 pub type SymbolImportNode<'ast> = Node<SymbolImport<'ast>>;
@@ -107,8 +136,14 @@ impl<'ast> SymbolImport<'ast> {
         SymbolImport {
             symbol_id: symbol_id.into(),
             module_id: module_id.into(),
+            relative: false,
         }
     }
+
+    pub fn relative(mut self, relative: bool) -> Self {
+        self.relative = relative;
+        self
+    }
 }
 
 impl<'ast> fmt::Display for SymbolImport<'ast> {
@@ -263,6 +298,8 @@ type StructDefinitionFieldNode<'ast> = Node<StructDefinitionField<'ast>>;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ConstantDefinition<'ast> {
+    /// Whether the constant is marked `pub`, in which case it is exported in the ABI if it is defined in the main module
+    pub public: bool,
     pub ty: UnresolvedTypeNode<'ast>,
     pub expression: ExpressionNode<'ast>,
 }
@@ -271,6 +308,9 @@ pub type ConstantDefinitionNode<'ast> = Node<ConstantDefinition<'ast>>;
 
 impl<'ast> fmt::Display for ConstantDefinition<'ast> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.public {
+            write!(f, "pub ")?;
+        }
         write!(f, "const {} _ = {}", self.ty, self.expression)
     }
 }
@@ -386,6 +426,8 @@ impl<'ast> fmt::Display for Assignee<'ast> {
 pub enum AssemblyStatement<'ast> {
     Assignment(AssigneeNode<'ast>, ExpressionNode<'ast>, bool),
     Constraint(ExpressionNode<'ast>, ExpressionNode<'ast>),
+    /// An assignment of the outputs of the plugin of the given name
+    UnsafeWitness(AssigneeNode<'ast>, &'ast str, Vec<ExpressionNode<'ast>>),
 }
 
 pub type AssemblyStatementNode<'ast> = Node<AssemblyStatement<'ast>>;
@@ -405,6 +447,13 @@ impl<'ast> fmt::Display for AssemblyStatement<'ast> {
             AssemblyStatement::Constraint(ref lhs, ref rhs) => {
                 write!(f, "{} === {}", lhs, rhs)
             }
+            AssemblyStatement::UnsafeWitness(ref lhs, plugin, ref arguments) => {
+                write!(f, "{} <-- unsafe_witness(\"{}\"", lhs, plugin)?;
+                for a in arguments {
+                    write!(f, ", {}", a)?;
+                }
+                write!(f, ")")
+            }
         }
     }
 }
@@ -416,12 +465,13 @@ pub enum Statement<'ast> {
     Return(Option<ExpressionNode<'ast>>),
     Definition(VariableNode<'ast>, ExpressionNode<'ast>),
     Assignment(AssigneeNode<'ast>, ExpressionNode<'ast>),
-    Assertion(ExpressionNode<'ast>, Option<String>),
+    Assertion(ExpressionNode<'ast>, Option<String>, bool),
     For(
         VariableNode<'ast>,
         ExpressionNode<'ast>,
         ExpressionNode<'ast>,
         Vec<StatementNode<'ast>>,
+        Option<Identifier<'ast>>,
     ),
     Log(&'ast str, Vec<ExpressionNode<'ast>>),
     Assembly(Vec<AssemblyStatementNode<'ast>>),
@@ -443,15 +493,22 @@ impl<'ast> fmt::Display for Statement<'ast> {
                 write!(f, "{} = {};", var, rhs)
             }
             Statement::Assignment(ref lhs, ref rhs) => write!(f, "{} = {};", lhs, rhs),
-            Statement::Assertion(ref e, ref message) => {
+            Statement::Assertion(ref e, ref message, critical) => {
+                if critical {
+                    write!(f, "#[critical] ")?;
+                }
                 write!(f, "assert({}", e)?;
                 match message {
                     Some(m) => write!(f, ", \"{}\");", m),
                     None => write!(f, ");"),
                 }
             }
-            Statement::For(ref var, ref start, ref stop, ref list) => {
-                writeln!(f, "for {} in {}..{} {{", var, start, stop)?;
+            Statement::For(ref var, ref start, ref stop, ref list, ref label) => {
+                write!(f, "for {} in {}..{}", var, start, stop)?;
+                if let Some(label) = label {
+                    write!(f, " as '{}:", label)?;
+                }
+                writeln!(f, " {{")?;
                 for l in list {
                     writeln!(f, "\t\t{}", l)?;
                 }
@@ -609,6 +666,8 @@ pub enum Expression<'ast> {
     U16Constant(u16),
     U32Constant(u32),
     U64Constant(u64),
+    /// A suffixed literal which does not fit in its unsigned integer type, rejected by the semantic checker
+    OutOfRangeUintConstant(BigUint, usize),
     Identifier(Identifier<'ast>),
     Add(Box<ExpressionNode<'ast>>, Box<ExpressionNode<'ast>>),
     Sub(Box<ExpressionNode<'ast>>, Box<ExpressionNode<'ast>>),
@@ -656,6 +715,7 @@ impl<'ast> fmt::Display for Expression<'ast> {
             Expression::U16Constant(ref i) => write!(f, "{}", i),
             Expression::U32Constant(ref i) => write!(f, "{}", i),
             Expression::U64Constant(ref i) => write!(f, "{}", i),
+            Expression::OutOfRangeUintConstant(ref i, bitwidth) => write!(f, "{}u{}", i, bitwidth),
             Expression::IntConstant(ref i) => write!(f, "{}", i),
             Expression::Identifier(ref var) => write!(f, "{}", var),
             Expression::Add(ref lhs, ref rhs) => write!(f, "({} + {})", lhs, rhs),
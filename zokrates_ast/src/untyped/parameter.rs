@@ -5,11 +5,24 @@ use std::fmt;
 pub struct Parameter<'ast> {
     pub id: VariableNode<'ast>,
     pub is_private: Option<bool>,
+    /// Whether the value of the parameter is derived from the other arguments when computing the witness
+    pub derive_nonce: bool,
+    /// Whether the value of the parameter is fixed when the keys are generated
+    pub bind_at_setup: bool,
+    /// The number of bits the value of the parameter is asserted to fit in, for `field<N>` parameters and arrays of
+    /// them
+    pub bound: Option<u32>,
 }
 
 impl<'ast> Parameter<'ast> {
     pub fn new(v: VariableNode<'ast>, is_private: Option<bool>) -> Self {
-        Parameter { id: v, is_private }
+        Parameter {
+            id: v,
+            is_private,
+            derive_nonce: false,
+            bind_at_setup: false,
+            bound: None,
+        }
     }
 
     pub fn private(v: VariableNode<'ast>) -> Self {
@@ -25,6 +38,14 @@ pub type ParameterNode<'ast> = Node<Parameter<'ast>>;
 
 impl<'ast> fmt::Display for Parameter<'ast> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.derive_nonce {
+            write!(f, "#[derive_nonce] ")?;
+        }
+
+        if self.bind_at_setup {
+            write!(f, "#[bind_at_setup] ")?;
+        }
+
         let visibility = if let Some(true) = self.is_private {
             "private "
         } else {
@@ -45,8 +66,8 @@ impl<'ast> fmt::Debug for Parameter<'ast> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "Parameter(variable: {:?}, private: {:?})",
-            self.id, self.is_private
+            "Parameter(variable: {:?}, private: {:?}, derive_nonce: {:?}, bind_at_setup: {:?}, bound: {:?})",
+            self.id, self.is_private, self.derive_nonce, self.bind_at_setup, self.bound
         )
     }
 }
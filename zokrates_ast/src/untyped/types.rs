@@ -11,6 +11,9 @@ pub enum UnresolvedType<'ast> {
     FieldElement,
     Boolean,
     Uint(usize),
+    Signed(usize),
+    /// A field element asserted to fit in a number of bits, written `field<N>`
+    BoundedField(u32),
     Array(Box<UnresolvedTypeNode<'ast>>, ExpressionNode<'ast>),
     User(UserTypeId, Option<Vec<Option<ExpressionNode<'ast>>>>),
     Tuple(Vec<UnresolvedTypeNode<'ast>>),
@@ -22,6 +25,8 @@ impl<'ast> fmt::Display for UnresolvedType<'ast> {
             UnresolvedType::FieldElement => write!(f, "field"),
             UnresolvedType::Boolean => write!(f, "bool"),
             UnresolvedType::Uint(bitwidth) => write!(f, "u{}", bitwidth),
+            UnresolvedType::Signed(bitwidth) => write!(f, "i{}", bitwidth),
+            UnresolvedType::BoundedField(bound) => write!(f, "field<{}>", bound),
             UnresolvedType::Array(ref ty, ref size) => write!(f, "{}[{}]", ty, size),
             UnresolvedType::Tuple(ref elements) => {
                 write!(f, "(")?;
@@ -71,6 +76,31 @@ impl<'ast> UnresolvedType<'ast> {
     pub fn array(ty: UnresolvedTypeNode<'ast>, size: ExpressionNode<'ast>) -> Self {
         UnresolvedType::Array(box ty, size)
     }
+
+    /// Split a `field<N>` type, or an array of them, into the corresponding unbounded type and the bound `N`. Other
+    /// types are returned unchanged, along with no bound
+    pub fn strip_bound(ty: UnresolvedTypeNode<'ast>) -> (UnresolvedTypeNode<'ast>, Option<u32>) {
+        match ty.value {
+            UnresolvedType::BoundedField(bound) => (
+                UnresolvedTypeNode {
+                    value: UnresolvedType::FieldElement,
+                    ..ty
+                },
+                Some(bound),
+            ),
+            UnresolvedType::Array(box inner, size) => {
+                let (inner, bound) = Self::strip_bound(inner);
+                (
+                    UnresolvedTypeNode {
+                        value: UnresolvedType::array(inner, size),
+                        ..ty
+                    },
+                    bound,
+                )
+            }
+            value => (UnresolvedTypeNode { value, ..ty }, None),
+        }
+    }
 }
 
 pub use self::signature::UnresolvedSignature;
@@ -86,6 +116,8 @@ mod signature {
         pub generics: Vec<ConstantGenericNode<'ast>>,
         pub inputs: Vec<UnresolvedTypeNode<'ast>>,
         pub output: Option<UnresolvedTypeNode<'ast>>,
+        /// The indices of the elements of the output tuple marked `private`
+        pub private_outputs: Vec<usize>,
     }
 
     impl<'ast> fmt::Debug for UnresolvedSignature<'ast> {
@@ -133,5 +165,10 @@ mod signature {
             self.output = Some(output);
             self
         }
+
+        pub fn private_outputs(mut self, private_outputs: Vec<usize>) -> Self {
+            self.private_outputs = private_outputs;
+            self
+        }
     }
 }
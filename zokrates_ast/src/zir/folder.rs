@@ -1,5 +1,6 @@
 // Generic walk through ZIR. Not mutating in place
 
+use crate::common::stack::maybe_grow;
 use crate::zir::types::UBitwidth;
 use crate::zir::*;
 use zokrates_field::Field;
@@ -157,6 +158,16 @@ pub fn fold_assembly_statement<'ast, T: Field, F: Folder<'ast, T>>(
             let rhs = f.fold_field_expression(rhs);
             vec![ZirAssemblyStatement::Constraint(lhs, rhs, metadata)]
         }
+        ZirAssemblyStatement::UnsafeWitness(assignees, plugin, arguments) => {
+            let arguments = arguments
+                .into_iter()
+                .map(|e| f.fold_field_expression(e))
+                .collect();
+            let assignees = assignees.into_iter().map(|a| f.fold_assignee(a)).collect();
+            vec![ZirAssemblyStatement::UnsafeWitness(
+                assignees, plugin, arguments,
+            )]
+        }
     }
 }
 
@@ -204,6 +215,11 @@ pub fn fold_statement<'ast, T: Field, F: Folder<'ast, T>>(
                 .flat_map(|s| f.fold_assembly_statement(s))
                 .collect(),
         ),
+        s @ ZirStatement::PushCallLog(..)
+        | s @ ZirStatement::PopCallLog
+        | s @ ZirStatement::PushIterationLog(..)
+        | s @ ZirStatement::PopIterationLog
+        | s @ ZirStatement::SpanLog(..) => s,
     };
     vec![res]
 }
@@ -225,7 +241,7 @@ pub fn fold_field_expression<'ast, T: Field, F: Folder<'ast, T>>(
     f: &mut F,
     e: FieldElementExpression<'ast, T>,
 ) -> FieldElementExpression<'ast, T> {
-    match e {
+    maybe_grow(|| match e {
         FieldElementExpression::Number(n) => FieldElementExpression::Number(n),
         FieldElementExpression::Identifier(id) => {
             match f.fold_identifier_expression(&Type::FieldElement, id) {
@@ -254,10 +270,10 @@ pub fn fold_field_expression<'ast, T: Field, F: Folder<'ast, T>>(
             let e2 = f.fold_field_expression(e2);
             FieldElementExpression::Mult(box e1, box e2)
         }
-        FieldElementExpression::Div(box e1, box e2) => {
+        FieldElementExpression::Div(box e1, box e2, span) => {
             let e1 = f.fold_field_expression(e1);
             let e2 = f.fold_field_expression(e2);
-            FieldElementExpression::Div(box e1, box e2)
+            FieldElementExpression::Div(box e1, box e2, span)
         }
         FieldElementExpression::Pow(box e1, box e2) => {
             let e1 = f.fold_field_expression(e1);
@@ -300,14 +316,14 @@ pub fn fold_field_expression<'ast, T: Field, F: Folder<'ast, T>>(
                 ConditionalOrExpression::Expression(u) => u,
             }
         }
-    }
+    })
 }
 
 pub fn fold_boolean_expression<'ast, T: Field, F: Folder<'ast, T>>(
     f: &mut F,
     e: BooleanExpression<'ast, T>,
 ) -> BooleanExpression<'ast, T> {
-    match e {
+    maybe_grow(|| match e {
         BooleanExpression::Value(v) => BooleanExpression::Value(v),
         BooleanExpression::Identifier(id) => match f.fold_identifier_expression(&Type::Boolean, id)
         {
@@ -372,16 +388,19 @@ pub fn fold_boolean_expression<'ast, T: Field, F: Folder<'ast, T>>(
             ConditionalOrExpression::Conditional(s) => BooleanExpression::Conditional(s),
             ConditionalOrExpression::Expression(u) => u,
         },
-    }
+    })
 }
 
 pub fn fold_uint_expression<'ast, T: Field, F: Folder<'ast, T>>(
     f: &mut F,
     e: UExpression<'ast, T>,
 ) -> UExpression<'ast, T> {
+    let (bitwidth, metadata, inner) = e.into_parts();
+
     UExpression {
-        inner: f.fold_uint_expression_inner(e.bitwidth, e.inner),
-        ..e
+        inner: f.fold_uint_expression_inner(bitwidth, inner),
+        bitwidth,
+        metadata,
     }
 }
 
@@ -390,7 +409,7 @@ pub fn fold_uint_expression_inner<'ast, T: Field, F: Folder<'ast, T>>(
     ty: UBitwidth,
     e: UExpressionInner<'ast, T>,
 ) -> UExpressionInner<'ast, T> {
-    match e {
+    maybe_grow(|| match e {
         UExpressionInner::Value(v) => UExpressionInner::Value(v),
         UExpressionInner::Identifier(id) => match f.fold_identifier_expression(&ty, id) {
             IdentifierOrExpression::Identifier(i) => UExpressionInner::Identifier(i),
@@ -467,7 +486,7 @@ pub fn fold_uint_expression_inner<'ast, T: Field, F: Folder<'ast, T>>(
             ConditionalOrExpression::Conditional(s) => UExpressionInner::Conditional(s),
             ConditionalOrExpression::Expression(u) => u,
         },
-    }
+    })
 }
 
 pub fn fold_function<'ast, T: Field, F: Folder<'ast, T>>(
@@ -4,6 +4,7 @@ mod identifier;
 pub mod lqc;
 mod parameter;
 pub mod result_folder;
+pub mod result_visitor;
 pub mod types;
 mod uint;
 mod variable;
@@ -11,7 +12,8 @@ mod variable;
 pub use self::parameter::Parameter;
 pub use self::types::{Type, UBitwidth};
 pub use self::variable::Variable;
-use crate::common::{FlatEmbed, FormatString, SourceMetadata};
+use crate::common::stack::maybe_grow;
+use crate::common::{AssertionError, FlatEmbed, FormatString, SourceMetadata};
 use crate::typed::ConcreteType;
 pub use crate::zir::uint::{ShouldReduce, UExpression, UExpressionInner, UMetadata};
 
@@ -25,8 +27,9 @@ pub use self::identifier::{Identifier, SourceIdentifier};
 use serde::{Deserialize, Serialize};
 
 /// A typed program as a collection of modules, one of them being the main
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
 pub struct ZirProgram<'ast, T> {
+    #[serde(borrow)]
     pub main: ZirFunction<'ast, T>,
 }
 
@@ -96,7 +99,8 @@ pub type ZirAssignee<'ast> = Variable<'ast>;
 pub enum RuntimeError {
     SourceAssertion(SourceMetadata),
     SelectRangeCheck,
-    DivisionByZero,
+    /// The location of the division is known for field divisions
+    DivisionByZero(Option<SourceMetadata>),
     IncompleteDynamicRange,
 }
 
@@ -105,7 +109,10 @@ impl fmt::Display for RuntimeError {
         match self {
             RuntimeError::SourceAssertion(metadata) => write!(f, "{}", metadata),
             RuntimeError::SelectRangeCheck => write!(f, "Range check on array access"),
-            RuntimeError::DivisionByZero => write!(f, "Division by zero"),
+            RuntimeError::DivisionByZero(Some(metadata)) => {
+                write!(f, "Division by zero at {}", metadata)
+            }
+            RuntimeError::DivisionByZero(None) => write!(f, "Division by zero"),
             RuntimeError::IncompleteDynamicRange => write!(f, "Dynamic comparison is incomplete"),
         }
     }
@@ -117,6 +124,15 @@ impl RuntimeError {
     }
 }
 
+impl AssertionError for RuntimeError {
+    fn source_assertion(&self) -> Option<&SourceMetadata> {
+        match self {
+            RuntimeError::SourceAssertion(metadata) => Some(metadata),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Hash, Eq, Debug, Serialize, Deserialize)]
 pub enum ZirAssemblyStatement<'ast, T> {
     Assignment(
@@ -128,6 +144,12 @@ pub enum ZirAssemblyStatement<'ast, T> {
         FieldElementExpression<'ast, T>,
         SourceMetadata,
     ),
+    /// An assignment of the outputs of a plugin, given its name and its arguments
+    UnsafeWitness(
+        Vec<ZirAssignee<'ast>>,
+        &'ast str,
+        Vec<FieldElementExpression<'ast, T>>,
+    ),
 }
 
 impl<'ast, T: fmt::Display> fmt::Display for ZirAssemblyStatement<'ast, T> {
@@ -147,6 +169,21 @@ impl<'ast, T: fmt::Display> fmt::Display for ZirAssemblyStatement<'ast, T> {
             ZirAssemblyStatement::Constraint(ref lhs, ref rhs, _) => {
                 write!(f, "{} === {};", lhs, rhs)
             }
+            ZirAssemblyStatement::UnsafeWitness(ref lhs, plugin, ref arguments) => {
+                write!(
+                    f,
+                    "{} <-- unsafe_witness(\"{}\"",
+                    lhs.iter()
+                        .map(|a| a.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    plugin
+                )?;
+                for a in arguments {
+                    write!(f, ", {}", a)?;
+                }
+                write!(f, ");")
+            }
         }
     }
 }
@@ -169,6 +206,15 @@ pub enum ZirStatement<'ast, T> {
     ),
     #[serde(borrow)]
     Assembly(Vec<ZirAssemblyStatement<'ast, T>>),
+    // Aux, marking the statements inlined from a call to the named function
+    PushCallLog(String),
+    PopCallLog,
+    // Aux, marking the statements unrolled from the iteration of the labeled loop with the given index
+    PushIterationLog(String, u32),
+    PopIterationLog,
+    // Aux, marking the statements produced from the source statement at the given location, until the next one in
+    // the same function
+    SpanLog(SourceMetadata),
 }
 
 impl<'ast, T: fmt::Display> fmt::Display for ZirStatement<'ast, T> {
@@ -213,6 +259,9 @@ impl<'ast, T: fmt::Display> ZirStatement<'ast, T> {
                 write!(f, "{}}}", "\t".repeat(depth))
             }
             ZirStatement::Assertion(ref e, ref error) => {
+                if error.is_critical() {
+                    write!(f, "#[critical] ")?;
+                }
                 write!(f, "assert({}", e)?;
                 match error {
                     RuntimeError::SourceAssertion(message) => write!(f, ", \"{}\");", message),
@@ -251,6 +300,13 @@ impl<'ast, T: fmt::Display> ZirStatement<'ast, T> {
                 }
                 write!(f, "{}}}", "\t".repeat(depth))
             }
+            ZirStatement::PushCallLog(ref function) => write!(f, "// PUSH CALL TO {}", function),
+            ZirStatement::PopCallLog => write!(f, "// POP CALL"),
+            ZirStatement::PushIterationLog(ref label, index) => {
+                write!(f, "// PUSH ITERATION {} OF '{}", index, label)
+            }
+            ZirStatement::PopIterationLog => write!(f, "// POP ITERATION"),
+            ZirStatement::SpanLog(ref span) => write!(f, "// SPAN {}", span),
         }
     }
 }
@@ -446,9 +502,11 @@ pub enum FieldElementExpression<'ast, T> {
         Box<FieldElementExpression<'ast, T>>,
         Box<FieldElementExpression<'ast, T>>,
     ),
+    /// The location of the division in the source is kept to report divisions by zero
     Div(
         Box<FieldElementExpression<'ast, T>>,
         Box<FieldElementExpression<'ast, T>>,
+        Option<SourceMetadata>,
     ),
     Pow(
         Box<FieldElementExpression<'ast, T>>,
@@ -599,7 +657,7 @@ impl<'ast, T: fmt::Display> fmt::Display for FieldElementExpression<'ast, T> {
             FieldElementExpression::Add(ref lhs, ref rhs) => write!(f, "({} + {})", lhs, rhs),
             FieldElementExpression::Sub(ref lhs, ref rhs) => write!(f, "({} - {})", lhs, rhs),
             FieldElementExpression::Mult(ref lhs, ref rhs) => write!(f, "({} * {})", lhs, rhs),
-            FieldElementExpression::Div(ref lhs, ref rhs) => write!(f, "({} / {})", lhs, rhs),
+            FieldElementExpression::Div(ref lhs, ref rhs, _) => write!(f, "({} / {})", lhs, rhs),
             FieldElementExpression::Pow(ref lhs, ref rhs) => write!(f, "{}**{}", lhs, rhs),
             FieldElementExpression::And(ref lhs, ref rhs) => write!(f, "({} & {})", lhs, rhs),
             FieldElementExpression::Or(ref lhs, ref rhs) => write!(f, "({} | {})", lhs, rhs),
@@ -619,7 +677,7 @@ impl<'ast, T: fmt::Display> fmt::Display for FieldElementExpression<'ast, T> {
 
 impl<'ast, T: fmt::Display> fmt::Display for UExpression<'ast, T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.inner {
+        maybe_grow(|| match self.inner {
             UExpressionInner::Value(ref v) => write!(f, "{}", v),
             UExpressionInner::Identifier(ref var) => write!(f, "{}", var),
             UExpressionInner::Select(ref e) => write!(f, "{}", e),
@@ -637,7 +695,7 @@ impl<'ast, T: fmt::Display> fmt::Display for UExpression<'ast, T> {
             UExpressionInner::Conditional(ref c) => {
                 write!(f, "{}", c)
             }
-        }
+        })
     }
 }
 
@@ -774,7 +832,7 @@ impl<'ast, T: Field> Expr<'ast, T> for UExpression<'ast, T> {
     }
 
     fn into_inner(self) -> Self::Inner {
-        self.inner
+        self.into_parts().2
     }
 
     fn as_inner(&self) -> &Self::Inner {
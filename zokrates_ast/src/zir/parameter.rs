@@ -7,14 +7,21 @@ pub struct Parameter<'ast> {
     #[serde(borrow)]
     pub id: Variable<'ast>,
     pub private: bool,
+    /// The number of bits the value of the parameter is asserted to fit in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bound: Option<u32>,
+    /// Whether the parameter is a signed integer, represented in two's complement
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub signed: bool,
 }
 
 impl<'ast> Parameter<'ast> {
-    #[cfg(test)]
     pub fn private(v: Variable<'ast>) -> Self {
         Parameter {
             id: v,
             private: true,
+            bound: None,
+            signed: false,
         }
     }
 }
@@ -22,7 +29,10 @@ impl<'ast> Parameter<'ast> {
 impl<'ast> fmt::Display for Parameter<'ast> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let visibility = if self.private { "private " } else { "" };
-        write!(f, "{}{} {}", visibility, self.id.get_type(), self.id.id)
+        match self.bound {
+            Some(bound) => write!(f, "{}field<{}> {}", visibility, bound, self.id.id),
+            None => write!(f, "{}{} {}", visibility, self.id.get_type(), self.id.id),
+        }
     }
 }
 
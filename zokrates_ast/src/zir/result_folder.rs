@@ -1,5 +1,6 @@
 // Generic walk through ZIR. Not mutating in place
 
+use crate::common::stack::maybe_grow;
 use crate::zir::types::UBitwidth;
 use crate::zir::*;
 use zokrates_field::Field;
@@ -177,6 +178,19 @@ pub fn fold_assembly_statement<'ast, T: Field, F: ResultFolder<'ast, T>>(
             let rhs = f.fold_field_expression(rhs)?;
             vec![ZirAssemblyStatement::Constraint(lhs, rhs, metadata)]
         }
+        ZirAssemblyStatement::UnsafeWitness(assignees, plugin, arguments) => {
+            let arguments = arguments
+                .into_iter()
+                .map(|e| f.fold_field_expression(e))
+                .collect::<Result<_, _>>()?;
+            let assignees = assignees
+                .into_iter()
+                .map(|a| f.fold_assignee(a))
+                .collect::<Result<_, _>>()?;
+            vec![ZirAssemblyStatement::UnsafeWitness(
+                assignees, plugin, arguments,
+            )]
+        }
     })
 }
 
@@ -244,6 +258,11 @@ pub fn fold_statement<'ast, T: Field, F: ResultFolder<'ast, T>>(
                 .collect();
             ZirStatement::Assembly(statements)
         }
+        s @ ZirStatement::PushCallLog(..)
+        | s @ ZirStatement::PopCallLog
+        | s @ ZirStatement::PushIterationLog(..)
+        | s @ ZirStatement::PopIterationLog
+        | s @ ZirStatement::SpanLog(..) => s,
     };
     Ok(vec![res])
 }
@@ -252,81 +271,85 @@ pub fn fold_field_expression<'ast, T: Field, F: ResultFolder<'ast, T>>(
     f: &mut F,
     e: FieldElementExpression<'ast, T>,
 ) -> Result<FieldElementExpression<'ast, T>, F::Error> {
-    Ok(match e {
-        FieldElementExpression::Number(n) => FieldElementExpression::Number(n),
-        FieldElementExpression::Identifier(id) => {
-            match f.fold_identifier_expression(&Type::FieldElement, id)? {
-                IdentifierOrExpression::Identifier(i) => FieldElementExpression::Identifier(i),
-                IdentifierOrExpression::Expression(e) => e,
+    maybe_grow(|| {
+        Ok(match e {
+            FieldElementExpression::Number(n) => FieldElementExpression::Number(n),
+            FieldElementExpression::Identifier(id) => {
+                match f.fold_identifier_expression(&Type::FieldElement, id)? {
+                    IdentifierOrExpression::Identifier(i) => FieldElementExpression::Identifier(i),
+                    IdentifierOrExpression::Expression(e) => e,
+                }
             }
-        }
-        FieldElementExpression::Select(e) => {
-            match f.fold_select_expression(&Type::FieldElement, e)? {
-                SelectOrExpression::Select(s) => FieldElementExpression::Select(s),
-                SelectOrExpression::Expression(u) => u,
+            FieldElementExpression::Select(e) => {
+                match f.fold_select_expression(&Type::FieldElement, e)? {
+                    SelectOrExpression::Select(s) => FieldElementExpression::Select(s),
+                    SelectOrExpression::Expression(u) => u,
+                }
             }
-        }
-        FieldElementExpression::Add(box e1, box e2) => {
-            let e1 = f.fold_field_expression(e1)?;
-            let e2 = f.fold_field_expression(e2)?;
-            FieldElementExpression::Add(box e1, box e2)
-        }
-        FieldElementExpression::Sub(box e1, box e2) => {
-            let e1 = f.fold_field_expression(e1)?;
-            let e2 = f.fold_field_expression(e2)?;
-            FieldElementExpression::Sub(box e1, box e2)
-        }
-        FieldElementExpression::Mult(box e1, box e2) => {
-            let e1 = f.fold_field_expression(e1)?;
-            let e2 = f.fold_field_expression(e2)?;
-            FieldElementExpression::Mult(box e1, box e2)
-        }
-        FieldElementExpression::Div(box e1, box e2) => {
-            let e1 = f.fold_field_expression(e1)?;
-            let e2 = f.fold_field_expression(e2)?;
-            FieldElementExpression::Div(box e1, box e2)
-        }
-        FieldElementExpression::Pow(box e1, box e2) => {
-            let e1 = f.fold_field_expression(e1)?;
-            let e2 = f.fold_uint_expression(e2)?;
-            FieldElementExpression::Pow(box e1, box e2)
-        }
-        FieldElementExpression::Xor(box left, box right) => {
-            let left = f.fold_field_expression(left)?;
-            let right = f.fold_field_expression(right)?;
+            FieldElementExpression::Add(box e1, box e2) => {
+                let e1 = f.fold_field_expression(e1)?;
+                let e2 = f.fold_field_expression(e2)?;
+                FieldElementExpression::Add(box e1, box e2)
+            }
+            FieldElementExpression::Sub(box e1, box e2) => {
+                let e1 = f.fold_field_expression(e1)?;
+                let e2 = f.fold_field_expression(e2)?;
+                FieldElementExpression::Sub(box e1, box e2)
+            }
+            FieldElementExpression::Mult(box e1, box e2) => {
+                let e1 = f.fold_field_expression(e1)?;
+                let e2 = f.fold_field_expression(e2)?;
+                FieldElementExpression::Mult(box e1, box e2)
+            }
+            FieldElementExpression::Div(box e1, box e2, span) => {
+                let e1 = f.fold_field_expression(e1)?;
+                let e2 = f.fold_field_expression(e2)?;
+                FieldElementExpression::Div(box e1, box e2, span)
+            }
+            FieldElementExpression::Pow(box e1, box e2) => {
+                let e1 = f.fold_field_expression(e1)?;
+                let e2 = f.fold_uint_expression(e2)?;
+                FieldElementExpression::Pow(box e1, box e2)
+            }
+            FieldElementExpression::Xor(box left, box right) => {
+                let left = f.fold_field_expression(left)?;
+                let right = f.fold_field_expression(right)?;
 
-            FieldElementExpression::Xor(box left, box right)
-        }
-        FieldElementExpression::And(box left, box right) => {
-            let left = f.fold_field_expression(left)?;
-            let right = f.fold_field_expression(right)?;
+                FieldElementExpression::Xor(box left, box right)
+            }
+            FieldElementExpression::And(box left, box right) => {
+                let left = f.fold_field_expression(left)?;
+                let right = f.fold_field_expression(right)?;
 
-            FieldElementExpression::And(box left, box right)
-        }
-        FieldElementExpression::Or(box left, box right) => {
-            let left = f.fold_field_expression(left)?;
-            let right = f.fold_field_expression(right)?;
+                FieldElementExpression::And(box left, box right)
+            }
+            FieldElementExpression::Or(box left, box right) => {
+                let left = f.fold_field_expression(left)?;
+                let right = f.fold_field_expression(right)?;
 
-            FieldElementExpression::Or(box left, box right)
-        }
-        FieldElementExpression::LeftShift(box e, box by) => {
-            let e = f.fold_field_expression(e)?;
-            let by = f.fold_uint_expression(by)?;
+                FieldElementExpression::Or(box left, box right)
+            }
+            FieldElementExpression::LeftShift(box e, box by) => {
+                let e = f.fold_field_expression(e)?;
+                let by = f.fold_uint_expression(by)?;
 
-            FieldElementExpression::LeftShift(box e, box by)
-        }
-        FieldElementExpression::RightShift(box e, box by) => {
-            let e = f.fold_field_expression(e)?;
-            let by = f.fold_uint_expression(by)?;
+                FieldElementExpression::LeftShift(box e, box by)
+            }
+            FieldElementExpression::RightShift(box e, box by) => {
+                let e = f.fold_field_expression(e)?;
+                let by = f.fold_uint_expression(by)?;
 
-            FieldElementExpression::RightShift(box e, box by)
-        }
-        FieldElementExpression::Conditional(c) => {
-            match f.fold_conditional_expression(&Type::FieldElement, c)? {
-                ConditionalOrExpression::Conditional(s) => FieldElementExpression::Conditional(s),
-                ConditionalOrExpression::Expression(u) => u,
+                FieldElementExpression::RightShift(box e, box by)
             }
-        }
+            FieldElementExpression::Conditional(c) => {
+                match f.fold_conditional_expression(&Type::FieldElement, c)? {
+                    ConditionalOrExpression::Conditional(s) => {
+                        FieldElementExpression::Conditional(s)
+                    }
+                    ConditionalOrExpression::Expression(u) => u,
+                }
+            }
+        })
     })
 }
 
@@ -334,73 +357,75 @@ pub fn fold_boolean_expression<'ast, T: Field, F: ResultFolder<'ast, T>>(
     f: &mut F,
     e: BooleanExpression<'ast, T>,
 ) -> Result<BooleanExpression<'ast, T>, F::Error> {
-    Ok(match e {
-        BooleanExpression::Value(v) => BooleanExpression::Value(v),
-        BooleanExpression::Identifier(id) => {
-            match f.fold_identifier_expression(&Type::Boolean, id)? {
-                IdentifierOrExpression::Identifier(i) => BooleanExpression::Identifier(i),
-                IdentifierOrExpression::Expression(e) => e,
+    maybe_grow(|| {
+        Ok(match e {
+            BooleanExpression::Value(v) => BooleanExpression::Value(v),
+            BooleanExpression::Identifier(id) => {
+                match f.fold_identifier_expression(&Type::Boolean, id)? {
+                    IdentifierOrExpression::Identifier(i) => BooleanExpression::Identifier(i),
+                    IdentifierOrExpression::Expression(e) => e,
+                }
             }
-        }
-        BooleanExpression::Select(e) => match f.fold_select_expression(&Type::Boolean, e)? {
-            SelectOrExpression::Select(s) => BooleanExpression::Select(s),
-            SelectOrExpression::Expression(u) => u,
-        },
-        BooleanExpression::FieldEq(box e1, box e2) => {
-            let e1 = f.fold_field_expression(e1)?;
-            let e2 = f.fold_field_expression(e2)?;
-            BooleanExpression::FieldEq(box e1, box e2)
-        }
-        BooleanExpression::BoolEq(box e1, box e2) => {
-            let e1 = f.fold_boolean_expression(e1)?;
-            let e2 = f.fold_boolean_expression(e2)?;
-            BooleanExpression::BoolEq(box e1, box e2)
-        }
-        BooleanExpression::UintEq(box e1, box e2) => {
-            let e1 = f.fold_uint_expression(e1)?;
-            let e2 = f.fold_uint_expression(e2)?;
-            BooleanExpression::UintEq(box e1, box e2)
-        }
-        BooleanExpression::FieldLt(box e1, box e2) => {
-            let e1 = f.fold_field_expression(e1)?;
-            let e2 = f.fold_field_expression(e2)?;
-            BooleanExpression::FieldLt(box e1, box e2)
-        }
-        BooleanExpression::UintLt(box e1, box e2) => {
-            let e1 = f.fold_uint_expression(e1)?;
-            let e2 = f.fold_uint_expression(e2)?;
-            BooleanExpression::UintLt(box e1, box e2)
-        }
-        BooleanExpression::FieldLe(box e1, box e2) => {
-            let e1 = f.fold_field_expression(e1)?;
-            let e2 = f.fold_field_expression(e2)?;
-            BooleanExpression::FieldLe(box e1, box e2)
-        }
-        BooleanExpression::UintLe(box e1, box e2) => {
-            let e1 = f.fold_uint_expression(e1)?;
-            let e2 = f.fold_uint_expression(e2)?;
-            BooleanExpression::UintLe(box e1, box e2)
-        }
-        BooleanExpression::Or(box e1, box e2) => {
-            let e1 = f.fold_boolean_expression(e1)?;
-            let e2 = f.fold_boolean_expression(e2)?;
-            BooleanExpression::Or(box e1, box e2)
-        }
-        BooleanExpression::And(box e1, box e2) => {
-            let e1 = f.fold_boolean_expression(e1)?;
-            let e2 = f.fold_boolean_expression(e2)?;
-            BooleanExpression::And(box e1, box e2)
-        }
-        BooleanExpression::Not(box e) => {
-            let e = f.fold_boolean_expression(e)?;
-            BooleanExpression::Not(box e)
-        }
-        BooleanExpression::Conditional(c) => {
-            match f.fold_conditional_expression(&Type::Boolean, c)? {
-                ConditionalOrExpression::Conditional(s) => BooleanExpression::Conditional(s),
-                ConditionalOrExpression::Expression(u) => u,
+            BooleanExpression::Select(e) => match f.fold_select_expression(&Type::Boolean, e)? {
+                SelectOrExpression::Select(s) => BooleanExpression::Select(s),
+                SelectOrExpression::Expression(u) => u,
+            },
+            BooleanExpression::FieldEq(box e1, box e2) => {
+                let e1 = f.fold_field_expression(e1)?;
+                let e2 = f.fold_field_expression(e2)?;
+                BooleanExpression::FieldEq(box e1, box e2)
             }
-        }
+            BooleanExpression::BoolEq(box e1, box e2) => {
+                let e1 = f.fold_boolean_expression(e1)?;
+                let e2 = f.fold_boolean_expression(e2)?;
+                BooleanExpression::BoolEq(box e1, box e2)
+            }
+            BooleanExpression::UintEq(box e1, box e2) => {
+                let e1 = f.fold_uint_expression(e1)?;
+                let e2 = f.fold_uint_expression(e2)?;
+                BooleanExpression::UintEq(box e1, box e2)
+            }
+            BooleanExpression::FieldLt(box e1, box e2) => {
+                let e1 = f.fold_field_expression(e1)?;
+                let e2 = f.fold_field_expression(e2)?;
+                BooleanExpression::FieldLt(box e1, box e2)
+            }
+            BooleanExpression::UintLt(box e1, box e2) => {
+                let e1 = f.fold_uint_expression(e1)?;
+                let e2 = f.fold_uint_expression(e2)?;
+                BooleanExpression::UintLt(box e1, box e2)
+            }
+            BooleanExpression::FieldLe(box e1, box e2) => {
+                let e1 = f.fold_field_expression(e1)?;
+                let e2 = f.fold_field_expression(e2)?;
+                BooleanExpression::FieldLe(box e1, box e2)
+            }
+            BooleanExpression::UintLe(box e1, box e2) => {
+                let e1 = f.fold_uint_expression(e1)?;
+                let e2 = f.fold_uint_expression(e2)?;
+                BooleanExpression::UintLe(box e1, box e2)
+            }
+            BooleanExpression::Or(box e1, box e2) => {
+                let e1 = f.fold_boolean_expression(e1)?;
+                let e2 = f.fold_boolean_expression(e2)?;
+                BooleanExpression::Or(box e1, box e2)
+            }
+            BooleanExpression::And(box e1, box e2) => {
+                let e1 = f.fold_boolean_expression(e1)?;
+                let e2 = f.fold_boolean_expression(e2)?;
+                BooleanExpression::And(box e1, box e2)
+            }
+            BooleanExpression::Not(box e) => {
+                let e = f.fold_boolean_expression(e)?;
+                BooleanExpression::Not(box e)
+            }
+            BooleanExpression::Conditional(c) => {
+                match f.fold_conditional_expression(&Type::Boolean, c)? {
+                    ConditionalOrExpression::Conditional(s) => BooleanExpression::Conditional(s),
+                    ConditionalOrExpression::Expression(u) => u,
+                }
+            }
+        })
     })
 }
 
@@ -408,9 +433,12 @@ pub fn fold_uint_expression<'ast, T: Field, F: ResultFolder<'ast, T>>(
     f: &mut F,
     e: UExpression<'ast, T>,
 ) -> Result<UExpression<'ast, T>, F::Error> {
+    let (bitwidth, metadata, inner) = e.into_parts();
+
     Ok(UExpression {
-        inner: f.fold_uint_expression_inner(e.bitwidth, e.inner)?,
-        ..e
+        inner: f.fold_uint_expression_inner(bitwidth, inner)?,
+        bitwidth,
+        metadata,
     })
 }
 
@@ -419,83 +447,85 @@ pub fn fold_uint_expression_inner<'ast, T: Field, F: ResultFolder<'ast, T>>(
     ty: UBitwidth,
     e: UExpressionInner<'ast, T>,
 ) -> Result<UExpressionInner<'ast, T>, F::Error> {
-    Ok(match e {
-        UExpressionInner::Value(v) => UExpressionInner::Value(v),
-        UExpressionInner::Identifier(id) => match f.fold_identifier_expression(&ty, id)? {
-            IdentifierOrExpression::Identifier(i) => UExpressionInner::Identifier(i),
-            IdentifierOrExpression::Expression(e) => e,
-        },
-        UExpressionInner::Select(e) => match f.fold_select_expression(&ty, e)? {
-            SelectOrExpression::Select(s) => UExpressionInner::Select(s),
-            SelectOrExpression::Expression(u) => u,
-        },
-        UExpressionInner::Add(box left, box right) => {
-            let left = f.fold_uint_expression(left)?;
-            let right = f.fold_uint_expression(right)?;
-
-            UExpressionInner::Add(box left, box right)
-        }
-        UExpressionInner::Sub(box left, box right) => {
-            let left = f.fold_uint_expression(left)?;
-            let right = f.fold_uint_expression(right)?;
+    maybe_grow(|| {
+        Ok(match e {
+            UExpressionInner::Value(v) => UExpressionInner::Value(v),
+            UExpressionInner::Identifier(id) => match f.fold_identifier_expression(&ty, id)? {
+                IdentifierOrExpression::Identifier(i) => UExpressionInner::Identifier(i),
+                IdentifierOrExpression::Expression(e) => e,
+            },
+            UExpressionInner::Select(e) => match f.fold_select_expression(&ty, e)? {
+                SelectOrExpression::Select(s) => UExpressionInner::Select(s),
+                SelectOrExpression::Expression(u) => u,
+            },
+            UExpressionInner::Add(box left, box right) => {
+                let left = f.fold_uint_expression(left)?;
+                let right = f.fold_uint_expression(right)?;
 
-            UExpressionInner::Sub(box left, box right)
-        }
-        UExpressionInner::Mult(box left, box right) => {
-            let left = f.fold_uint_expression(left)?;
-            let right = f.fold_uint_expression(right)?;
+                UExpressionInner::Add(box left, box right)
+            }
+            UExpressionInner::Sub(box left, box right) => {
+                let left = f.fold_uint_expression(left)?;
+                let right = f.fold_uint_expression(right)?;
 
-            UExpressionInner::Mult(box left, box right)
-        }
-        UExpressionInner::Div(box left, box right) => {
-            let left = f.fold_uint_expression(left)?;
-            let right = f.fold_uint_expression(right)?;
+                UExpressionInner::Sub(box left, box right)
+            }
+            UExpressionInner::Mult(box left, box right) => {
+                let left = f.fold_uint_expression(left)?;
+                let right = f.fold_uint_expression(right)?;
 
-            UExpressionInner::Div(box left, box right)
-        }
-        UExpressionInner::Rem(box left, box right) => {
-            let left = f.fold_uint_expression(left)?;
-            let right = f.fold_uint_expression(right)?;
+                UExpressionInner::Mult(box left, box right)
+            }
+            UExpressionInner::Div(box left, box right) => {
+                let left = f.fold_uint_expression(left)?;
+                let right = f.fold_uint_expression(right)?;
 
-            UExpressionInner::Rem(box left, box right)
-        }
-        UExpressionInner::Xor(box left, box right) => {
-            let left = f.fold_uint_expression(left)?;
-            let right = f.fold_uint_expression(right)?;
+                UExpressionInner::Div(box left, box right)
+            }
+            UExpressionInner::Rem(box left, box right) => {
+                let left = f.fold_uint_expression(left)?;
+                let right = f.fold_uint_expression(right)?;
 
-            UExpressionInner::Xor(box left, box right)
-        }
-        UExpressionInner::And(box left, box right) => {
-            let left = f.fold_uint_expression(left)?;
-            let right = f.fold_uint_expression(right)?;
+                UExpressionInner::Rem(box left, box right)
+            }
+            UExpressionInner::Xor(box left, box right) => {
+                let left = f.fold_uint_expression(left)?;
+                let right = f.fold_uint_expression(right)?;
 
-            UExpressionInner::And(box left, box right)
-        }
-        UExpressionInner::Or(box left, box right) => {
-            let left = f.fold_uint_expression(left)?;
-            let right = f.fold_uint_expression(right)?;
+                UExpressionInner::Xor(box left, box right)
+            }
+            UExpressionInner::And(box left, box right) => {
+                let left = f.fold_uint_expression(left)?;
+                let right = f.fold_uint_expression(right)?;
 
-            UExpressionInner::Or(box left, box right)
-        }
-        UExpressionInner::LeftShift(box e, by) => {
-            let e = f.fold_uint_expression(e)?;
+                UExpressionInner::And(box left, box right)
+            }
+            UExpressionInner::Or(box left, box right) => {
+                let left = f.fold_uint_expression(left)?;
+                let right = f.fold_uint_expression(right)?;
 
-            UExpressionInner::LeftShift(box e, by)
-        }
-        UExpressionInner::RightShift(box e, by) => {
-            let e = f.fold_uint_expression(e)?;
+                UExpressionInner::Or(box left, box right)
+            }
+            UExpressionInner::LeftShift(box e, by) => {
+                let e = f.fold_uint_expression(e)?;
 
-            UExpressionInner::RightShift(box e, by)
-        }
-        UExpressionInner::Not(box e) => {
-            let e = f.fold_uint_expression(e)?;
+                UExpressionInner::LeftShift(box e, by)
+            }
+            UExpressionInner::RightShift(box e, by) => {
+                let e = f.fold_uint_expression(e)?;
 
-            UExpressionInner::Not(box e)
-        }
-        UExpressionInner::Conditional(c) => match f.fold_conditional_expression(&ty, c)? {
-            ConditionalOrExpression::Conditional(s) => UExpressionInner::Conditional(s),
-            ConditionalOrExpression::Expression(u) => u,
-        },
+                UExpressionInner::RightShift(box e, by)
+            }
+            UExpressionInner::Not(box e) => {
+                let e = f.fold_uint_expression(e)?;
+
+                UExpressionInner::Not(box e)
+            }
+            UExpressionInner::Conditional(c) => match f.fold_conditional_expression(&ty, c)? {
+                ConditionalOrExpression::Conditional(s) => UExpressionInner::Conditional(s),
+                ConditionalOrExpression::Expression(u) => u,
+            },
+        })
     })
 }
 
@@ -0,0 +1,327 @@
+// Generic walk through ZIR which can fail. Not mutating in place
+
+use crate::common::stack::maybe_grow;
+use crate::zir::types::UBitwidth;
+use crate::zir::*;
+use zokrates_field::Field;
+
+pub trait ResultVisit<'ast, T: Field> {
+    fn visit<F: ResultVisitor<'ast, T>>(&self, f: &mut F) -> Result<(), F::Error>;
+}
+
+impl<'ast, T: Field> ResultVisit<'ast, T> for FieldElementExpression<'ast, T> {
+    fn visit<F: ResultVisitor<'ast, T>>(&self, f: &mut F) -> Result<(), F::Error> {
+        f.visit_field_expression(self)
+    }
+}
+
+impl<'ast, T: Field> ResultVisit<'ast, T> for BooleanExpression<'ast, T> {
+    fn visit<F: ResultVisitor<'ast, T>>(&self, f: &mut F) -> Result<(), F::Error> {
+        f.visit_boolean_expression(self)
+    }
+}
+
+impl<'ast, T: Field> ResultVisit<'ast, T> for UExpression<'ast, T> {
+    fn visit<F: ResultVisitor<'ast, T>>(&self, f: &mut F) -> Result<(), F::Error> {
+        f.visit_uint_expression(self)
+    }
+}
+
+pub trait ResultVisitor<'ast, T: Field>: Sized {
+    type Error;
+
+    fn visit_program(&mut self, p: &ZirProgram<'ast, T>) -> Result<(), Self::Error> {
+        visit_program(self, p)
+    }
+
+    fn visit_function(&mut self, f: &ZirFunction<'ast, T>) -> Result<(), Self::Error> {
+        visit_function(self, f)
+    }
+
+    fn visit_parameter(&mut self, p: &Parameter<'ast>) -> Result<(), Self::Error> {
+        self.visit_variable(&p.id)
+    }
+
+    fn visit_variable(&mut self, _v: &Variable<'ast>) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn visit_assignee(&mut self, a: &ZirAssignee<'ast>) -> Result<(), Self::Error> {
+        self.visit_variable(a)
+    }
+
+    /// Visit the use of the variable `id` with type `ty`
+    fn visit_identifier(&mut self, _id: &Identifier<'ast>, _ty: Type) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn visit_assembly_statement(
+        &mut self,
+        s: &ZirAssemblyStatement<'ast, T>,
+    ) -> Result<(), Self::Error> {
+        visit_assembly_statement(self, s)
+    }
+
+    fn visit_statement(&mut self, s: &ZirStatement<'ast, T>) -> Result<(), Self::Error> {
+        visit_statement(self, s)
+    }
+
+    fn visit_expression(&mut self, e: &ZirExpression<'ast, T>) -> Result<(), Self::Error> {
+        match e {
+            ZirExpression::FieldElement(e) => self.visit_field_expression(e),
+            ZirExpression::Boolean(e) => self.visit_boolean_expression(e),
+            ZirExpression::Uint(e) => self.visit_uint_expression(e),
+        }
+    }
+
+    fn visit_expression_list(
+        &mut self,
+        es: &ZirExpressionList<'ast, T>,
+    ) -> Result<(), Self::Error> {
+        match es {
+            ZirExpressionList::EmbedCall(_, _, arguments) => {
+                arguments.iter().try_for_each(|a| self.visit_expression(a))
+            }
+        }
+    }
+
+    fn visit_conditional_expression<E: ResultVisit<'ast, T>>(
+        &mut self,
+        e: &ConditionalExpression<'ast, T, E>,
+    ) -> Result<(), Self::Error> {
+        visit_conditional_expression(self, e)
+    }
+
+    fn visit_select_expression<E: ResultVisit<'ast, T>>(
+        &mut self,
+        e: &SelectExpression<'ast, T, E>,
+    ) -> Result<(), Self::Error> {
+        visit_select_expression(self, e)
+    }
+
+    fn visit_field_expression(
+        &mut self,
+        e: &FieldElementExpression<'ast, T>,
+    ) -> Result<(), Self::Error> {
+        visit_field_expression(self, e)
+    }
+
+    fn visit_boolean_expression(
+        &mut self,
+        e: &BooleanExpression<'ast, T>,
+    ) -> Result<(), Self::Error> {
+        visit_boolean_expression(self, e)
+    }
+
+    fn visit_uint_expression(&mut self, e: &UExpression<'ast, T>) -> Result<(), Self::Error> {
+        self.visit_uint_expression_inner(e.bitwidth, &e.inner)
+    }
+
+    fn visit_uint_expression_inner(
+        &mut self,
+        bitwidth: UBitwidth,
+        e: &UExpressionInner<'ast, T>,
+    ) -> Result<(), Self::Error> {
+        visit_uint_expression_inner(self, bitwidth, e)
+    }
+}
+
+pub fn visit_assembly_statement<'ast, T: Field, F: ResultVisitor<'ast, T>>(
+    f: &mut F,
+    s: &ZirAssemblyStatement<'ast, T>,
+) -> Result<(), F::Error> {
+    match s {
+        ZirAssemblyStatement::Assignment(assignees, function) => {
+            for a in assignees {
+                f.visit_assignee(a)?;
+            }
+            f.visit_function(function)
+        }
+        ZirAssemblyStatement::Constraint(lhs, rhs, _) => {
+            f.visit_field_expression(lhs)?;
+            f.visit_field_expression(rhs)
+        }
+        ZirAssemblyStatement::UnsafeWitness(assignees, _, arguments) => {
+            for e in arguments {
+                f.visit_field_expression(e)?;
+            }
+            for a in assignees {
+                f.visit_assignee(a)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+pub fn visit_statement<'ast, T: Field, F: ResultVisitor<'ast, T>>(
+    f: &mut F,
+    s: &ZirStatement<'ast, T>,
+) -> Result<(), F::Error> {
+    match s {
+        ZirStatement::Return(expressions) => {
+            expressions.iter().try_for_each(|e| f.visit_expression(e))
+        }
+        ZirStatement::Definition(a, e) => {
+            f.visit_assignee(a)?;
+            f.visit_expression(e)
+        }
+        ZirStatement::IfElse(condition, consequence, alternative) => {
+            f.visit_boolean_expression(condition)?;
+            consequence
+                .iter()
+                .chain(alternative.iter())
+                .try_for_each(|s| f.visit_statement(s))
+        }
+        ZirStatement::Assertion(e, _) => f.visit_boolean_expression(e),
+        ZirStatement::MultipleDefinition(variables, elist) => {
+            for v in variables {
+                f.visit_assignee(v)?;
+            }
+            f.visit_expression_list(elist)
+        }
+        ZirStatement::Log(_, e) => e
+            .iter()
+            .flat_map(|(_, e)| e)
+            .try_for_each(|e| f.visit_expression(e)),
+        ZirStatement::Assembly(statements) => statements
+            .iter()
+            .try_for_each(|s| f.visit_assembly_statement(s)),
+        ZirStatement::PushCallLog(..)
+        | ZirStatement::PopCallLog
+        | ZirStatement::PushIterationLog(..)
+        | ZirStatement::PopIterationLog
+        | ZirStatement::SpanLog(..) => Ok(()),
+    }
+}
+
+pub fn visit_field_expression<'ast, T: Field, F: ResultVisitor<'ast, T>>(
+    f: &mut F,
+    e: &FieldElementExpression<'ast, T>,
+) -> Result<(), F::Error> {
+    maybe_grow(|| match e {
+        FieldElementExpression::Number(_) => Ok(()),
+        FieldElementExpression::Identifier(id) => f.visit_identifier(&id.id, Type::FieldElement),
+        FieldElementExpression::Select(e) => f.visit_select_expression(e),
+        FieldElementExpression::Add(e1, e2)
+        | FieldElementExpression::Sub(e1, e2)
+        | FieldElementExpression::Mult(e1, e2)
+        | FieldElementExpression::Div(e1, e2, _)
+        | FieldElementExpression::Xor(e1, e2)
+        | FieldElementExpression::And(e1, e2)
+        | FieldElementExpression::Or(e1, e2) => {
+            f.visit_field_expression(e1)?;
+            f.visit_field_expression(e2)
+        }
+        FieldElementExpression::Pow(e, by)
+        | FieldElementExpression::LeftShift(e, by)
+        | FieldElementExpression::RightShift(e, by) => {
+            f.visit_field_expression(e)?;
+            f.visit_uint_expression(by)
+        }
+        FieldElementExpression::Conditional(c) => f.visit_conditional_expression(c),
+    })
+}
+
+pub fn visit_boolean_expression<'ast, T: Field, F: ResultVisitor<'ast, T>>(
+    f: &mut F,
+    e: &BooleanExpression<'ast, T>,
+) -> Result<(), F::Error> {
+    maybe_grow(|| match e {
+        BooleanExpression::Value(_) => Ok(()),
+        BooleanExpression::Identifier(id) => f.visit_identifier(&id.id, Type::Boolean),
+        BooleanExpression::Select(e) => f.visit_select_expression(e),
+        BooleanExpression::FieldEq(e1, e2)
+        | BooleanExpression::FieldLt(e1, e2)
+        | BooleanExpression::FieldLe(e1, e2) => {
+            f.visit_field_expression(e1)?;
+            f.visit_field_expression(e2)
+        }
+        BooleanExpression::UintEq(e1, e2)
+        | BooleanExpression::UintLt(e1, e2)
+        | BooleanExpression::UintLe(e1, e2) => {
+            f.visit_uint_expression(e1)?;
+            f.visit_uint_expression(e2)
+        }
+        BooleanExpression::BoolEq(e1, e2)
+        | BooleanExpression::Or(e1, e2)
+        | BooleanExpression::And(e1, e2) => {
+            f.visit_boolean_expression(e1)?;
+            f.visit_boolean_expression(e2)
+        }
+        BooleanExpression::Not(e) => f.visit_boolean_expression(e),
+        BooleanExpression::Conditional(c) => f.visit_conditional_expression(c),
+    })
+}
+
+pub fn visit_uint_expression_inner<'ast, T: Field, F: ResultVisitor<'ast, T>>(
+    f: &mut F,
+    bitwidth: UBitwidth,
+    e: &UExpressionInner<'ast, T>,
+) -> Result<(), F::Error> {
+    maybe_grow(|| match e {
+        UExpressionInner::Value(_) => Ok(()),
+        UExpressionInner::Identifier(id) => f.visit_identifier(&id.id, Type::Uint(bitwidth)),
+        UExpressionInner::Select(e) => f.visit_select_expression(e),
+        UExpressionInner::Add(left, right)
+        | UExpressionInner::Sub(left, right)
+        | UExpressionInner::Mult(left, right)
+        | UExpressionInner::Div(left, right)
+        | UExpressionInner::Rem(left, right)
+        | UExpressionInner::Xor(left, right)
+        | UExpressionInner::And(left, right)
+        | UExpressionInner::Or(left, right) => {
+            f.visit_uint_expression(left)?;
+            f.visit_uint_expression(right)
+        }
+        UExpressionInner::LeftShift(e, _)
+        | UExpressionInner::RightShift(e, _)
+        | UExpressionInner::Not(e) => f.visit_uint_expression(e),
+        UExpressionInner::Conditional(c) => f.visit_conditional_expression(c),
+    })
+}
+
+pub fn visit_function<'ast, T: Field, F: ResultVisitor<'ast, T>>(
+    f: &mut F,
+    fun: &ZirFunction<'ast, T>,
+) -> Result<(), F::Error> {
+    for a in &fun.arguments {
+        f.visit_parameter(a)?;
+    }
+    fun.statements.iter().try_for_each(|s| f.visit_statement(s))
+}
+
+pub fn visit_program<'ast, T: Field, F: ResultVisitor<'ast, T>>(
+    f: &mut F,
+    p: &ZirProgram<'ast, T>,
+) -> Result<(), F::Error> {
+    f.visit_function(&p.main)
+}
+
+pub fn visit_conditional_expression<
+    'ast,
+    T: Field,
+    E: ResultVisit<'ast, T>,
+    F: ResultVisitor<'ast, T>,
+>(
+    f: &mut F,
+    e: &ConditionalExpression<'ast, T, E>,
+) -> Result<(), F::Error> {
+    f.visit_boolean_expression(&e.condition)?;
+    e.consequence.visit(f)?;
+    e.alternative.visit(f)
+}
+
+pub fn visit_select_expression<
+    'ast,
+    T: Field,
+    E: ResultVisit<'ast, T>,
+    F: ResultVisitor<'ast, T>,
+>(
+    f: &mut F,
+    e: &SelectExpression<'ast, T, E>,
+) -> Result<(), F::Error> {
+    for e in &e.array {
+        e.visit(f)?;
+    }
+    e.index.visit(f)
+}
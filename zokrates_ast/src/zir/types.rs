@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use zokrates_field::Field;
 
 pub type MemberId = String;
 
@@ -31,6 +32,23 @@ impl UBitwidth {
             UBitwidth::B64 => 64,
         }
     }
+
+    /// The bitwidth of the low limb when a product is computed on limbs, see `splits_products`
+    pub fn limb_bitwidth(self) -> usize {
+        self.to_usize() / 2
+    }
+
+    /// Whether unsigned integers of this bitwidth can be computed on in the field `T`: the product of two of them,
+    /// computed on limbs if needed, must have a unique bit decomposition
+    pub fn is_supported<T: Field>(self) -> bool {
+        self.to_usize() + self.limb_bitwidth() + 1 < T::get_required_bits()
+    }
+
+    /// Whether the product of two unsigned integers of this bitwidth may not have a unique bit decomposition in the
+    /// field `T`, in which case it is computed on limbs
+    pub fn splits_products<T: Field>(self) -> bool {
+        2 * self.to_usize() >= T::get_required_bits()
+    }
 }
 
 impl From<usize> for UBitwidth {
@@ -1,6 +1,9 @@
+use crate::common::stack::maybe_grow;
 use crate::zir::types::UBitwidth;
 use crate::zir::IdentifierExpression;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use zokrates_field::Field;
 
 use super::{ConditionalExpression, SelectExpression};
@@ -140,6 +143,9 @@ impl ShouldReduce {
 pub struct UMetadata<T> {
     pub max: T,
     pub should_reduce: ShouldReduce,
+    /// Whether the value is a signed integer in two's complement, whose sign is its most significant bit
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub signed: bool,
 }
 
 impl<T: Field> UMetadata<T> {
@@ -147,9 +153,14 @@ impl<T: Field> UMetadata<T> {
         UMetadata {
             max: max.into(),
             should_reduce: ShouldReduce::Unknown,
+            signed: false,
         }
     }
 
+    pub fn into_signed(self, signed: bool) -> Self {
+        UMetadata { signed, ..self }
+    }
+
     pub fn bitwidth(&self) -> u32 {
         self.max.bits() as u32
     }
@@ -159,11 +170,38 @@ impl<T: Field> UMetadata<T> {
         Self {
             should_reduce: ShouldReduce::False,
             max: T::from(2_u32).pow(bitwidth.into().to_usize()) - T::from(1),
+            signed: false,
+        }
+    }
+
+    /// The bounds of the value once reduced to `bitwidth`, sign-extended if it is signed: a signed value whose max
+    /// does not reach the sign bit is non-negative
+    pub fn signed_bounds<W: Into<UBitwidth>>(&self, bitwidth: W) -> (i128, i128) {
+        let bitwidth = bitwidth.into().to_usize();
+        let range_max = (1i128 << bitwidth) - 1;
+        let max = match self.max.bits() as usize > bitwidth {
+            true => range_max,
+            false => self
+                .max
+                .to_biguint()
+                .to_bytes_le()
+                .iter()
+                .rev()
+                .fold(0, |acc, byte| acc << 8 | *byte as i128),
+        };
+
+        match self.signed && max >> (bitwidth - 1) == 1 {
+            true => (-(1 << (bitwidth - 1)), range_max >> 1),
+            false => (0, max),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// An unsigned integer expression
+///
+/// A chain of operations such as `a + a + ... + a` nests as deep as it is long, so the traits which recurse on the
+/// operands grow the stack like the folders do, see `common::stack`.
+#[derive(Eq, Serialize, Deserialize)]
 pub struct UExpression<'ast, T> {
     pub bitwidth: UBitwidth,
     pub metadata: Option<UMetadata<T>>,
@@ -171,6 +209,55 @@ pub struct UExpression<'ast, T> {
     pub inner: UExpressionInner<'ast, T>,
 }
 
+impl<'ast, T> Drop for UExpression<'ast, T> {
+    fn drop(&mut self) {
+        let inner = std::mem::replace(&mut self.inner, UExpressionInner::Value(0));
+        maybe_grow(|| drop(inner));
+    }
+}
+
+impl<'ast, T: Clone> Clone for UExpression<'ast, T> {
+    fn clone(&self) -> Self {
+        maybe_grow(|| UExpression {
+            bitwidth: self.bitwidth,
+            metadata: self.metadata.clone(),
+            inner: self.inner.clone(),
+        })
+    }
+}
+
+impl<'ast, T: PartialEq> PartialEq for UExpression<'ast, T> {
+    fn eq(&self, other: &Self) -> bool {
+        maybe_grow(|| {
+            self.bitwidth == other.bitwidth
+                && self.metadata == other.metadata
+                && self.inner == other.inner
+        })
+    }
+}
+
+impl<'ast, T: Hash> Hash for UExpression<'ast, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        maybe_grow(|| {
+            self.bitwidth.hash(state);
+            self.metadata.hash(state);
+            self.inner.hash(state);
+        })
+    }
+}
+
+impl<'ast, T: fmt::Debug> fmt::Debug for UExpression<'ast, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        maybe_grow(|| {
+            f.debug_struct("UExpression")
+                .field("bitwidth", &self.bitwidth)
+                .field("metadata", &self.metadata)
+                .field("inner", &self.inner)
+                .finish()
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum UExpressionInner<'ast, T> {
     Value(u128),
@@ -202,18 +289,14 @@ impl<'ast, T> UExpressionInner<'ast, T> {
 }
 
 impl<'ast, T: Field> UExpression<'ast, T> {
-    pub fn metadata(self, metadata: UMetadata<T>) -> UExpression<'ast, T> {
-        UExpression {
-            metadata: Some(metadata),
-            ..self
-        }
+    pub fn metadata(mut self, metadata: UMetadata<T>) -> UExpression<'ast, T> {
+        self.metadata = Some(metadata);
+        self
     }
 
-    pub fn with_max<U: Into<T>>(self, max: U) -> Self {
-        UExpression {
-            metadata: Some(UMetadata::with_max(max)),
-            ..self
-        }
+    pub fn with_max<U: Into<T>>(mut self, max: U) -> Self {
+        self.metadata = Some(UMetadata::with_max(max));
+        self
     }
 }
 
@@ -227,6 +310,13 @@ impl<'ast, T> UExpression<'ast, T> {
     }
 
     pub fn into_inner(self) -> UExpressionInner<'ast, T> {
-        self.inner
+        self.into_parts().2
+    }
+
+    /// The bitwidth, the metadata and the inner expression, which cannot be moved out of the expression as it
+    /// implements `Drop`
+    pub fn into_parts(mut self) -> (UBitwidth, Option<UMetadata<T>>, UExpressionInner<'ast, T>) {
+        let inner = std::mem::replace(&mut self.inner, UExpressionInner::Value(0));
+        (self.bitwidth, self.metadata.take(), inner)
     }
 }
@@ -6,7 +6,9 @@ use pairing::{ff::to_hex, CurveAffine, Engine};
 
 use zokrates_field::BellmanFieldExtensions;
 use zokrates_field::Field;
-use zokrates_proof_systems::{Backend, MpcBackend, NonUniversalBackend, Proof, SetupKeypair};
+use zokrates_proof_systems::{
+    Backend, MpcBackend, NonUniversalBackend, Proof, RerandomizationBackend, SetupKeypair,
+};
 
 use crate::Computation;
 use crate::{get_random_seed, Bellman};
@@ -20,14 +22,23 @@ use zokrates_proof_systems::groth16::{ProofPoints, VerificationKey, G16};
 use zokrates_proof_systems::Scheme;
 
 impl<T: Field + BellmanFieldExtensions> Backend<T, G16> for Bellman {
-    fn generate_proof<'a, I: IntoIterator<Item = Statement<'a, T>>, R: RngCore + CryptoRng>(
+    type ProvingKey = Parameters<T::BellmanEngine>;
+
+    fn read_proving_key(proving_key: &[u8]) -> Result<Self::ProvingKey, String> {
+        Parameters::read(proving_key, true).map_err(|e| e.to_string())
+    }
+
+    fn generate_proof_with_key<
+        'a,
+        I: IntoIterator<Item = Statement<'a, T>>,
+        R: RngCore + CryptoRng,
+    >(
         program: ProgIterator<'a, T, I>,
         witness: Witness<T>,
-        proving_key: Vec<u8>,
+        params: &Self::ProvingKey,
         rng: &mut R,
     ) -> Proof<T, G16> {
         let computation = Computation::with_witness(program, witness);
-        let params = Parameters::read(proving_key.as_slice(), true).unwrap();
 
         let public_inputs: Vec<String> = computation
             .public_inputs_values()
@@ -35,7 +46,7 @@ impl<T: Field + BellmanFieldExtensions> Backend<T, G16> for Bellman {
             .map(|e| format!("0x{}", to_hex(e)))
             .collect();
 
-        let proof = computation.prove(&params, rng);
+        let proof = computation.prove(params, rng);
         let proof_points = ProofPoints {
             a: parse_g1::<T>(&proof.a),
             b: parse_g2::<T>(&proof.b),
@@ -81,6 +92,48 @@ impl<T: Field + BellmanFieldExtensions> Backend<T, G16> for Bellman {
     }
 }
 
+impl<T: Field + BellmanFieldExtensions> RerandomizationBackend<T, G16> for Bellman {
+    fn rerandomize<R: RngCore + CryptoRng>(
+        vk: &<G16 as Scheme<T>>::VerificationKey,
+        proof: &Proof<T, G16>,
+        rng: &mut R,
+    ) -> Proof<T, G16> {
+        use pairing::ff::{Field as BellmanField, PrimeField, ScalarEngine};
+        use pairing::CurveProjective;
+        use rand_0_4::Rand;
+
+        let seed = get_random_seed(rng);
+        let rng = &mut ChaChaRng::from_seed(seed.as_ref());
+
+        let delta = serialization::to_g2::<T>(vk.delta.clone());
+        let a = serialization::to_g1::<T>(proof.proof.a.clone());
+        let b = serialization::to_g2::<T>(proof.proof.b.clone());
+        let c = serialization::to_g1::<T>(proof.proof.c.clone());
+
+        let r = <T::BellmanEngine as ScalarEngine>::Fr::rand(rng);
+        let s = <T::BellmanEngine as ScalarEngine>::Fr::rand(rng);
+        let mut rs = r;
+        rs.mul_assign(&s);
+
+        // (A, B, C) becomes (A / r, r * B + r * s * delta, C + s * A), which satisfies the same pairing equation
+        let a_prime = a.mul(r.inverse().unwrap().into_repr());
+
+        let mut b_prime = b.mul(r.into_repr());
+        b_prime.add_assign(&delta.mul(rs.into_repr()));
+
+        let mut c_prime = a.mul(s.into_repr());
+        c_prime.add_assign_mixed(&c);
+
+        let proof_points = ProofPoints {
+            a: parse_g1::<T>(&a_prime.into_affine()),
+            b: parse_g2::<T>(&b_prime.into_affine()),
+            c: parse_g1::<T>(&c_prime.into_affine()),
+        };
+
+        Proof::new(proof_points, proof.inputs.clone())
+    }
+}
+
 impl<T: Field + BellmanFieldExtensions> NonUniversalBackend<T, G16> for Bellman {
     fn setup<'a, I: IntoIterator<Item = Statement<'a, T>>, R: RngCore + CryptoRng>(
         program: ProgIterator<'a, T, I>,
@@ -156,7 +209,7 @@ impl<T: Field + BellmanFieldExtensions> MpcBackend<T, G16> for Bellman {
 pub mod serialization {
     use super::*;
     use pairing::from_hex;
-    use zokrates_proof_systems::{G1Affine, G2Affine};
+    use zokrates_proof_systems::{G1Affine, G2Affine, PointError};
 
     pub fn parameters_to_verification_key<T: Field + BellmanFieldExtensions>(
         parameters: &Parameters<T::BellmanEngine>,
@@ -175,6 +228,35 @@ pub mod serialization {
         }
     }
 
+    pub fn try_to_g1<T: BellmanFieldExtensions>(
+        g1: &G1Affine,
+    ) -> Result<<T::BellmanEngine as Engine>::G1Affine, PointError> {
+        let x = from_hex(&g1.0).map_err(|_| PointError::NonCanonical)?;
+        let y = from_hex(&g1.1).map_err(|_| PointError::NonCanonical)?;
+        <T::BellmanEngine as Engine>::G1Affine::from_xy_checked(x, y)
+            .map_err(|_| PointError::NotOnCurve)
+    }
+
+    pub fn try_to_g2<T: BellmanFieldExtensions>(
+        g2: &G2Affine,
+    ) -> Result<<T::BellmanEngine as Engine>::G2Affine, PointError> {
+        match g2 {
+            G2Affine::Fq2(g2) => {
+                // `new_fq2` expects valid coordinates
+                for c in [&(g2.0).0, &(g2.0).1, &(g2.1).0, &(g2.1).1] {
+                    from_hex::<<T::BellmanEngine as Engine>::Fq>(c)
+                        .map_err(|_| PointError::NonCanonical)?;
+                }
+                let x = T::new_fq2(&(g2.0).0, &(g2.0).1);
+                let y = T::new_fq2(&(g2.1).0, &(g2.1).1);
+                <T::BellmanEngine as Engine>::G2Affine::from_xy_checked(x, y)
+                    .map_err(|_| PointError::NotOnCurve)
+            }
+            // the curves supported by bellman all have their G2 defined on Fq2
+            G2Affine::Fq(_) => Err(PointError::NonCanonical),
+        }
+    }
+
     pub fn to_g1<T: BellmanFieldExtensions>(
         g1: G1Affine,
     ) -> <T::BellmanEngine as Engine>::G1Affine {
@@ -203,6 +285,7 @@ mod tests {
     use rand_0_8::SeedableRng;
     use zokrates_field::Bn128Field;
     use zokrates_interpreter::Interpreter;
+    use zokrates_proof_systems::testing::check_deterministic_proofs;
 
     use super::*;
     use zokrates_ast::common::{Parameter, Variable};
@@ -213,6 +296,8 @@ mod tests {
         let program: Prog<Bn128Field> = Prog {
             arguments: vec![Parameter::public(Variable::new(0))],
             return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
             statements: vec![Statement::constraint(Variable::new(0), Variable::public(0))],
         };
 
@@ -232,4 +317,70 @@ mod tests {
 
         assert!(ans);
     }
+
+    #[test]
+    fn deterministic_proofs() {
+        let program: Prog<Bn128Field> = Prog {
+            arguments: vec![Parameter::private(Variable::new(0))],
+            return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
+            statements: vec![Statement::constraint(Variable::new(0), Variable::public(0))],
+        };
+
+        let keypair = <Bellman as NonUniversalBackend<Bn128Field, G16>>::setup(
+            program.clone(),
+            &mut StdRng::from_entropy(),
+        );
+        let witness = Interpreter::default()
+            .execute(program.clone(), &[Bn128Field::from(42)])
+            .unwrap();
+
+        check_deterministic_proofs::<_, G16, Bellman>(program, witness, keypair.pk);
+    }
+
+    #[test]
+    fn rerandomize() {
+        let program: Prog<Bn128Field> = Prog {
+            arguments: vec![Parameter::public(Variable::new(0))],
+            return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
+            statements: vec![Statement::constraint(Variable::new(0), Variable::public(0))],
+        };
+
+        let rng = &mut StdRng::from_entropy();
+        let keypair =
+            <Bellman as NonUniversalBackend<Bn128Field, G16>>::setup(program.clone(), rng);
+        let other_keypair =
+            <Bellman as NonUniversalBackend<Bn128Field, G16>>::setup(program.clone(), rng);
+
+        let witness = Interpreter::default()
+            .execute(program.clone(), &[Bn128Field::from(42)])
+            .unwrap();
+
+        let proof = <Bellman as Backend<Bn128Field, G16>>::generate_proof(
+            program, witness, keypair.pk, rng,
+        );
+
+        let rerandomized = proof.rerandomize::<Bellman, _>(&keypair.vk, rng);
+
+        // the proof points change, the public inputs do not
+        assert_ne!(proof.proof.a.to_string(), rerandomized.proof.a.to_string());
+        assert_ne!(proof.proof.b.to_string(), rerandomized.proof.b.to_string());
+        assert_ne!(proof.proof.c.to_string(), rerandomized.proof.c.to_string());
+        assert_eq!(proof.inputs, rerandomized.inputs);
+
+        assert!(<Bellman as Backend<Bn128Field, G16>>::verify(
+            keypair.vk.clone(),
+            rerandomized
+        ));
+
+        // a re-randomized proof is only valid against the key it was generated for
+        let rerandomized = proof.rerandomize::<Bellman, _>(&keypair.vk, rng);
+        assert!(!<Bellman as Backend<Bn128Field, G16>>::verify(
+            other_keypair.vk,
+            rerandomized
+        ));
+    }
 }
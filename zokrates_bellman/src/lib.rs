@@ -13,7 +13,11 @@ use std::collections::BTreeMap;
 use zokrates_ast::common::Variable;
 use zokrates_ast::ir::{CanonicalLinComb, ProgIterator, Statement, Witness};
 use zokrates_field::BellmanFieldExtensions;
-use zokrates_field::Field;
+use zokrates_field::{Bls12_381Field, Bn128Field, Field};
+use zokrates_proof_systems::{
+    verify_bytes_with, BytesVerifier, Fr, G1Affine, G2Affine, PointError, PointValidation,
+    VerifyError, G16,
+};
 
 use rand_0_4::ChaChaRng;
 use rand_0_8::{CryptoRng, RngCore};
@@ -22,6 +26,39 @@ pub use self::parse::*;
 
 pub struct Bellman;
 
+impl<T: Field + BellmanFieldExtensions> PointValidation<T> for Bellman {
+    fn check_g1(point: &G1Affine) -> Result<(), PointError> {
+        groth16::serialization::try_to_g1::<T>(point).map(|_| ())
+    }
+
+    fn check_g2(point: &G2Affine) -> Result<(), PointError> {
+        groth16::serialization::try_to_g2::<T>(point).map(|_| ())
+    }
+}
+
+impl BytesVerifier for Bellman {
+    fn verify_bytes(
+        scheme: &str,
+        curve: &str,
+        vk: &[u8],
+        proof: &[u8],
+        public_inputs: &[Fr],
+    ) -> Result<bool, VerifyError> {
+        match (scheme, curve) {
+            ("g16", "bn128") => {
+                verify_bytes_with::<Bn128Field, G16, Bellman>(vk, proof, public_inputs)
+            }
+            ("g16", "bls12_381") => {
+                verify_bytes_with::<Bls12_381Field, G16, Bellman>(vk, proof, public_inputs)
+            }
+            _ => Err(VerifyError::Unsupported {
+                scheme: scheme.to_string(),
+                curve: curve.to_string(),
+            }),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Computation<'a, T, I: IntoIterator<Item = Statement<'a, T>>> {
     program: ProgIterator<'a, T, I>,
@@ -49,13 +86,14 @@ fn bellman_combination<T: BellmanFieldExtensions, CS: ConstraintSystem<T::Bellma
     cs: &mut CS,
     symbols: &mut BTreeMap<Variable, BellmanVariable>,
     witness: &mut Witness<T>,
+    return_count: usize,
 ) -> LinearCombination<T::BellmanEngine> {
     l.0.into_iter()
         .map(|(k, v)| {
             (
                 v.into_bellman(),
                 *symbols.entry(k).or_insert_with(|| {
-                    match k.is_output() {
+                    match k.is_public_output(return_count) {
                         true => cs.alloc_input(
                             || format!("{}", k),
                             || {
@@ -98,9 +136,24 @@ impl<'a, T: BellmanFieldExtensions + Field, I: IntoIterator<Item = Statement<'a,
 
         assert!(symbols.insert(Variable::one(), CS::one()).is_none());
 
-        symbols.extend(self.program.arguments.iter().enumerate().map(|(index, p)| {
-            let wire = match p.private {
-                true => cs.alloc(
+        // the public values are allocated first, in the order of the public layout of the program
+        for (index, v) in self.program.public_variables().into_iter().enumerate() {
+            let wire = cs.alloc_input(
+                || format!("PUBLIC_INPUT_{}", index),
+                || {
+                    Ok(witness
+                        .0
+                        .remove(&v)
+                        .ok_or(SynthesisError::AssignmentMissing)?
+                        .into_bellman())
+                },
+            )?;
+            symbols.insert(v, wire);
+        }
+
+        for (index, p) in self.program.arguments.iter().enumerate() {
+            if p.private {
+                let wire = cs.alloc(
                     || format!("PRIVATE_INPUT_{}", index),
                     || {
                         Ok(witness
@@ -109,22 +162,12 @@ impl<'a, T: BellmanFieldExtensions + Field, I: IntoIterator<Item = Statement<'a,
                             .ok_or(SynthesisError::AssignmentMissing)?
                             .into_bellman())
                     },
-                ),
-                false => cs.alloc_input(
-                    || format!("PUBLIC_INPUT_{}", index),
-                    || {
-                        Ok(witness
-                            .0
-                            .remove(&p.id)
-                            .ok_or(SynthesisError::AssignmentMissing)?
-                            .into_bellman())
-                    },
-                ),
+                )?;
+                symbols.insert(p.id, wire);
             }
-            .unwrap();
-            (p.id, wire)
-        }));
+        }
 
+        let return_count = self.program.return_count;
         for statement in self.program.statements {
             if let Statement::Constraint(quad, lin, _) = statement {
                 let a = &bellman_combination(
@@ -132,14 +175,22 @@ impl<'a, T: BellmanFieldExtensions + Field, I: IntoIterator<Item = Statement<'a,
                     cs,
                     &mut symbols,
                     &mut witness,
+                    return_count,
                 );
                 let b = &bellman_combination(
                     quad.right.into_canonical(),
                     cs,
                     &mut symbols,
                     &mut witness,
+                    return_count,
+                );
+                let c = &bellman_combination(
+                    lin.into_canonical(),
+                    cs,
+                    &mut symbols,
+                    &mut witness,
+                    return_count,
                 );
-                let c = &bellman_combination(lin.into_canonical(), cs, &mut symbols, &mut witness);
 
                 cs.enforce(|| "Constraint", |lc| lc + a, |lc| lc + b, |lc| lc + c);
             }
@@ -275,6 +326,8 @@ mod tests {
             let program: Prog<Bn128Field> = Prog {
                 arguments: vec![Parameter::private(Variable::new(0))],
                 return_count: 1,
+                public_layout: vec![],
+                variable_ordering: None,
                 statements: vec![Statement::constraint(Variable::new(0), Variable::public(0))],
             };
 
@@ -296,6 +349,8 @@ mod tests {
             let program: Prog<Bn128Field> = Prog {
                 arguments: vec![Parameter::public(Variable::new(0))],
                 return_count: 1,
+                public_layout: vec![],
+                variable_ordering: None,
                 statements: vec![Statement::constraint(Variable::new(0), Variable::public(0))],
             };
 
@@ -317,6 +372,8 @@ mod tests {
             let program: Prog<Bn128Field> = Prog {
                 arguments: vec![],
                 return_count: 1,
+                public_layout: vec![],
+                variable_ordering: None,
                 statements: vec![Statement::constraint(Variable::one(), Variable::public(0))],
             };
 
@@ -340,6 +397,8 @@ mod tests {
                     Parameter::public(Variable::new(51)),
                 ],
                 return_count: 2,
+                public_layout: vec![],
+                variable_ordering: None,
                 statements: vec![
                     Statement::constraint(
                         LinComb::from(Variable::new(42)) + LinComb::from(Variable::new(51)),
@@ -369,6 +428,8 @@ mod tests {
             let program: Prog<Bn128Field> = Prog {
                 arguments: vec![Parameter::public(Variable::new(42))],
                 return_count: 1,
+                public_layout: vec![],
+                variable_ordering: None,
                 statements: vec![Statement::constraint(
                     LinComb::from(Variable::new(42)) + LinComb::one(),
                     Variable::public(0),
@@ -396,6 +457,8 @@ mod tests {
                     Parameter::public(Variable::new(51)),
                 ],
                 return_count: 1,
+                public_layout: vec![],
+                variable_ordering: None,
                 statements: vec![Statement::constraint(
                     LinComb::from(Variable::new(42)) + LinComb::from(Variable::new(51)),
                     Variable::public(0),
@@ -0,0 +1,61 @@
+//! Measure the lowering and flattening of single expressions, nested to the depth of usual programs.
+//!
+//! Run with `cargo bench -p zokrates_bench --bench expressions`. The passes check the space left on the stack at each
+//! level of an expression, see `zokrates_ast::common::stack`, so that these sums measure the cost of the checks where
+//! they are most frequent. Compare two versions with `--save-baseline` and `--baseline`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use zokrates_ast::typed::{
+    DeclarationVariable, FieldElementExpression, TypedExpression, UBitwidth, UExpression,
+};
+use zokrates_core::testing::flatten_expression;
+use zokrates_field::Bn128Field;
+
+const DEPTHS: &[usize] = &[8, 64, 512];
+
+// a + a + ... + a
+fn field_sum(depth: usize) -> TypedExpression<'static, Bn128Field> {
+    let a = || FieldElementExpression::identifier("a".into());
+    (1..depth).fold(a(), |sum, _| sum + a()).into()
+}
+
+// a + a + ... + a, on 32 bits
+fn uint_sum(depth: usize) -> TypedExpression<'static, Bn128Field> {
+    let a = || UExpression::identifier("a".into()).annotate(UBitwidth::B32);
+    (1..depth).fold(a(), |sum, _| sum + a()).into()
+}
+
+fn sums(c: &mut Criterion) {
+    let cases: [(&str, fn(usize) -> TypedExpression<'static, Bn128Field>, _); 2] = [
+        (
+            "field_sum",
+            field_sum,
+            DeclarationVariable::field_element("a"),
+        ),
+        (
+            "uint_sum",
+            uint_sum,
+            DeclarationVariable::uint("a", UBitwidth::B32),
+        ),
+    ];
+
+    for (name, sum, variable) in cases {
+        let mut group = c.benchmark_group(name);
+        let context = [variable];
+
+        for depth in DEPTHS {
+            group.bench_with_input(BenchmarkId::from_parameter(depth), depth, |b, depth| {
+                b.iter_batched(
+                    || sum(*depth),
+                    |e| flatten_expression(e, &context),
+                    BatchSize::SmallInput,
+                )
+            });
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, sums);
+criterion_main!(benches);
@@ -0,0 +1,63 @@
+//! Measure each stage of the compilation of the fixtures separately.
+//!
+//! Run with `cargo bench -p zokrates_bench`. `<fixture>/typed` parses and checks the program, `<fixture>/zir` runs the
+//! static analysis from the checked program and `<fixture>/ir` flattens and optimizes the program in zir.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use typed_arena::Arena;
+use zokrates_bench::{location, resolver, FIXTURES};
+use zokrates_common::CompileConfig;
+use zokrates_core::compile::{to_ir, to_typed, to_zir};
+use zokrates_field::Bn128Field;
+
+fn stages(c: &mut Criterion) {
+    let resolver = resolver();
+    let config = CompileConfig::default();
+
+    for fixture in FIXTURES {
+        let mut group = c.benchmark_group(fixture.name);
+        // the typed programs borrow their sources from the arena, so it lives as long as the group
+        let arena = Arena::new();
+
+        let typed = || {
+            to_typed::<Bn128Field, _>(
+                fixture.source.to_string(),
+                location(fixture),
+                Some(&resolver),
+                &config,
+                &arena,
+            )
+            .unwrap()
+        };
+
+        group.bench_function("typed", |b| b.iter(typed));
+
+        group.bench_function("zir", |b| {
+            b.iter_batched(
+                typed,
+                |typed| to_zir(typed, &config).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+
+        let zir = to_zir(typed(), &config).unwrap();
+
+        group.bench_function("ir", |b| {
+            b.iter_batched(
+                || zir.clone(),
+                |zir| to_ir(zir, config.clone()).unwrap().prog().collect(),
+                BatchSize::SmallInput,
+            )
+        });
+
+        group.finish();
+    }
+}
+
+criterion_group! {
+    name = benches;
+    // compiling the fixtures takes up to seconds, so fewer samples are taken than by default
+    config = Criterion::default().sample_size(10);
+    targets = stages
+}
+criterion_main!(benches);
@@ -0,0 +1,309 @@
+//! Representative programs to measure the compilation pipeline, and baselines of the size of each stage.
+//!
+//! The fixtures cover the main shapes of programs: a hash made of uint operations, a Merkle path made of field
+//! operations, and a state machine made of branches. `cargo bench -p zokrates_bench` measures the time of each stage
+//! of their compilation. `cargo run -p zokrates_bench` checks the size of the output of each stage against the
+//! baselines recorded in `baselines.json`, and `cargo run -p zokrates_bench -- --update` records them again.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use typed_arena::Arena;
+use zokrates_ast::typed::TypedFunctionSymbol;
+use zokrates_common::CompileConfig;
+use zokrates_core::compile::{to_ir, to_typed, to_zir, CompileErrors};
+use zokrates_field::Bn128Field;
+use zokrates_fs_resolver::FileSystemResolver;
+
+pub struct Fixture {
+    pub name: &'static str,
+    pub source: &'static str,
+}
+
+pub const FIXTURES: &[Fixture] = &[
+    Fixture {
+        name: "hash",
+        source: include_str!("../fixtures/hash.zok"),
+    },
+    Fixture {
+        name: "merkle",
+        source: include_str!("../fixtures/merkle.zok"),
+    },
+    Fixture {
+        name: "state_machine",
+        source: include_str!("../fixtures/state_machine.zok"),
+    },
+];
+
+/// The path of the recorded baselines
+pub const BASELINES_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/baselines.json");
+
+/// The path of the standard library the fixtures import from
+pub const STDLIB_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../zokrates_stdlib/stdlib");
+
+/// A stage of the compilation, see `zokrates_core::compile`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    Typed,
+    Zir,
+    Ir,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stage::Typed => write!(f, "typed"),
+            Stage::Zir => write!(f, "zir"),
+            Stage::Ir => write!(f, "ir"),
+        }
+    }
+}
+
+/// The size of the output of each stage: the number of statements in typed and zir, of constraints in ir
+pub type Sizes = BTreeMap<Stage, usize>;
+
+/// The sizes of each fixture, by name
+pub type Baselines = BTreeMap<String, Sizes>;
+
+pub fn resolver() -> FileSystemResolver<'static> {
+    FileSystemResolver::with_stdlib_root(STDLIB_PATH)
+}
+
+/// The location a fixture is compiled from, which its errors refer to
+pub fn location(fixture: &Fixture) -> PathBuf {
+    PathBuf::from(format!("{}.zok", fixture.name))
+}
+
+fn fmt_errors(fixture: &Fixture, errors: CompileErrors) -> String {
+    format!(
+        "Compilation of `{}` failed:\n\n{}",
+        fixture.name,
+        errors
+            .0
+            .iter()
+            .map(|e| format!("{}:{}", e.file().display(), e.value()))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    )
+}
+
+/// Compile a fixture, returning the size of the output of each stage
+pub fn measure(fixture: &Fixture) -> Result<Sizes, String> {
+    let arena = Arena::new();
+    let config = CompileConfig::default();
+    let resolver = resolver();
+
+    let typed = to_typed::<Bn128Field, _>(
+        fixture.source.to_string(),
+        location(fixture),
+        Some(&resolver),
+        &config,
+        &arena,
+    )
+    .map_err(|e| fmt_errors(fixture, e))?;
+
+    let typed_size = typed
+        .program()
+        .modules
+        .values()
+        .flat_map(|module| module.functions_iter())
+        .map(|declaration| match &declaration.symbol {
+            TypedFunctionSymbol::Here(function) => function.statements.len(),
+            _ => 0,
+        })
+        .sum();
+
+    let zir = to_zir(typed, &config).map_err(|e| fmt_errors(fixture, e))?;
+    let zir_size = zir.program.main.statements.len();
+
+    let ir = to_ir(zir, config)
+        .map_err(|e| fmt_errors(fixture, e))?
+        .prog()
+        .collect();
+
+    Ok(Sizes::from([
+        (Stage::Typed, typed_size),
+        (Stage::Zir, zir_size),
+        (Stage::Ir, ir.constraint_count()),
+    ]))
+}
+
+pub fn read_baselines(path: &Path) -> Result<Baselines, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|why| format!("Could not read {}: {}", path.display(), why))?;
+    serde_json::from_str(&contents)
+        .map_err(|why| format!("Could not parse {}: {}", path.display(), why))
+}
+
+pub fn write_baselines(path: &Path, baselines: &Baselines) -> Result<(), String> {
+    let contents = serde_json::to_string_pretty(baselines).unwrap();
+    std::fs::write(path, contents + "\n")
+        .map_err(|why| format!("Could not write {}: {}", path.display(), why))
+}
+
+/// A size which differs from its baseline, or which has no baseline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delta {
+    pub fixture: String,
+    pub stage: Stage,
+    pub expected: Option<usize>,
+    pub actual: usize,
+}
+
+impl fmt::Display for Delta {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.expected {
+            Some(expected) => write!(
+                f,
+                "{}/{}: expected {}, found {} ({:+})",
+                self.fixture,
+                self.stage,
+                expected,
+                self.actual,
+                self.actual as i64 - expected as i64
+            ),
+            None => write!(
+                f,
+                "{}/{}: found {}, no baseline recorded",
+                self.fixture, self.stage, self.actual
+            ),
+        }
+    }
+}
+
+/// Compare measured sizes to the baselines, in the order of the fixtures and stages. A size without a baseline is a
+/// delta like one which differs from its baseline
+pub fn compare(baselines: &Baselines, measured: &Baselines) -> Vec<Delta> {
+    measured
+        .iter()
+        .flat_map(|(fixture, sizes)| {
+            sizes.iter().filter_map(move |(stage, actual)| {
+                let expected = baselines.get(fixture).and_then(|b| b.get(stage)).copied();
+                match expected == Some(*actual) {
+                    true => None,
+                    false => Some(Delta {
+                        fixture: fixture.clone(),
+                        stage: *stage,
+                        expected,
+                        actual: *actual,
+                    }),
+                }
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sizes(typed: usize, zir: usize, ir: usize) -> Sizes {
+        Sizes::from([(Stage::Typed, typed), (Stage::Zir, zir), (Stage::Ir, ir)])
+    }
+
+    #[test]
+    fn matching_baselines() {
+        let baselines = Baselines::from([("hash".into(), sizes(10, 200, 30000))]);
+
+        assert!(compare(&baselines, &baselines).is_empty());
+    }
+
+    #[test]
+    fn report_mismatches() {
+        let baselines = Baselines::from([
+            ("hash".into(), sizes(10, 200, 30000)),
+            ("merkle".into(), sizes(20, 300, 4000)),
+        ]);
+        let measured = Baselines::from([
+            ("hash".into(), sizes(10, 180, 30016)),
+            ("merkle".into(), sizes(20, 300, 4000)),
+        ]);
+
+        let deltas = compare(&baselines, &measured);
+
+        assert_eq!(
+            deltas,
+            vec![
+                Delta {
+                    fixture: "hash".into(),
+                    stage: Stage::Zir,
+                    expected: Some(200),
+                    actual: 180,
+                },
+                Delta {
+                    fixture: "hash".into(),
+                    stage: Stage::Ir,
+                    expected: Some(30000),
+                    actual: 30016,
+                },
+            ]
+        );
+        assert_eq!(
+            deltas[0].to_string(),
+            "hash/zir: expected 200, found 180 (-20)"
+        );
+        assert_eq!(
+            deltas[1].to_string(),
+            "hash/ir: expected 30000, found 30016 (+16)"
+        );
+    }
+
+    #[test]
+    fn report_missing_baselines() {
+        let baselines = Baselines::from([("hash".into(), Sizes::from([(Stage::Ir, 30000)]))]);
+        let measured = Baselines::from([
+            ("hash".into(), sizes(10, 200, 30000)),
+            ("merkle".into(), Sizes::from([(Stage::Ir, 4000)])),
+        ]);
+
+        let deltas = compare(&baselines, &measured);
+
+        assert_eq!(deltas.len(), 3);
+        assert!(deltas.iter().all(|d| d.expected.is_none()));
+        assert_eq!(
+            deltas[2].to_string(),
+            "merkle/ir: found 4000, no baseline recorded"
+        );
+    }
+
+    #[test]
+    fn baselines_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baselines.json");
+
+        let baselines = Baselines::from([("hash".into(), sizes(10, 200, 30000))]);
+        write_baselines(&path, &baselines).unwrap();
+
+        assert_eq!(read_baselines(&path).unwrap(), baselines);
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("\"typed\": 10"));
+
+        std::fs::write(&path, "{\"hash\": {\"flat\": 1}}").unwrap();
+        assert!(read_baselines(&path)
+            .unwrap_err()
+            .starts_with(&format!("Could not parse {}", path.display())));
+    }
+
+    #[test]
+    fn recorded_baselines() {
+        // the recorded baselines can be loaded, only refer to existing fixtures, and cover every stage of each of them
+        let baselines = read_baselines(Path::new(BASELINES_PATH)).unwrap();
+
+        assert!(baselines
+            .keys()
+            .all(|name| FIXTURES.iter().any(|f| f.name == name)));
+        for fixture in FIXTURES {
+            let sizes = baselines.get(fixture.name);
+            assert!(
+                [Stage::Typed, Stage::Zir, Stage::Ir]
+                    .iter()
+                    .all(|stage| sizes.map_or(false, |sizes| sizes.contains_key(stage))),
+                "no baseline recorded for every stage of `{}`, run `cargo run -p zokrates_bench -- --update`",
+                fixture.name
+            );
+        }
+    }
+}
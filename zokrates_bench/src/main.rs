@@ -0,0 +1,48 @@
+//! Check the sizes of the compilation stages of the fixtures against the recorded baselines.
+//!
+//! Run with `cargo run -p zokrates_bench`, which fails if a size differs from its baseline or has none, or with
+//! `--update` to record the current sizes as the baselines.
+
+use std::path::Path;
+use zokrates_bench::{compare, measure, read_baselines, write_baselines, Baselines, FIXTURES};
+
+fn run(update: bool) -> Result<(), String> {
+    let path = Path::new(zokrates_bench::BASELINES_PATH);
+
+    let measured = FIXTURES
+        .iter()
+        .map(|fixture| measure(fixture).map(|sizes| (fixture.name.to_string(), sizes)))
+        .collect::<Result<Baselines, _>>()?;
+
+    if update {
+        write_baselines(path, &measured)?;
+        println!("Recorded the baselines in {}", path.display());
+        return Ok(());
+    }
+
+    let deltas = compare(&read_baselines(path)?, &measured);
+
+    for delta in &deltas {
+        println!("{}", delta);
+    }
+
+    match deltas.len() {
+        0 => {
+            println!("All sizes match the baselines");
+            Ok(())
+        }
+        count => Err(format!(
+            "{} sizes differ from the baselines or have none, run with `--update` to record them if the change is expected",
+            count
+        )),
+    }
+}
+
+fn main() {
+    let update = std::env::args().skip(1).any(|a| a == "--update");
+
+    if let Err(e) = run(update) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
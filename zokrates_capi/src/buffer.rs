@@ -0,0 +1,86 @@
+use crate::error::Error;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+
+/// A byte buffer allocated by the library. Ownership is transferred to the caller, who must release it
+/// with `zokrates_buffer_free`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ZokratesBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl From<Vec<u8>> for ZokratesBuffer {
+    fn from(v: Vec<u8>) -> Self {
+        let boxed = v.into_boxed_slice();
+        let len = boxed.len();
+        ZokratesBuffer {
+            data: Box::into_raw(boxed) as *mut u8,
+            len,
+        }
+    }
+}
+
+/// Release a buffer returned by the library
+///
+/// # Safety
+///
+/// `buffer` must have been returned by this library and must not have been freed already
+#[no_mangle]
+pub unsafe extern "C" fn zokrates_buffer_free(buffer: ZokratesBuffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+            buffer.data,
+            buffer.len,
+        )));
+    }
+}
+
+pub(crate) unsafe fn read_str<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, Error> {
+    if ptr.is_null() {
+        return Err(Error::invalid_argument(format!("`{}` is null", name)));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| Error::invalid_argument(format!("`{}` is not valid UTF-8", name)))
+}
+
+pub(crate) unsafe fn read_optional_str<'a>(
+    ptr: *const c_char,
+    name: &str,
+) -> Result<Option<&'a str>, Error> {
+    match ptr.is_null() {
+        true => Ok(None),
+        false => read_str(ptr, name).map(Some),
+    }
+}
+
+pub(crate) unsafe fn read_bytes<'a>(
+    ptr: *const u8,
+    len: usize,
+    name: &str,
+) -> Result<&'a [u8], Error> {
+    if ptr.is_null() {
+        return Err(Error::invalid_argument(format!("`{}` is null", name)));
+    }
+    Ok(std::slice::from_raw_parts(ptr, len))
+}
+
+pub(crate) unsafe fn read_optional_bytes<'a>(
+    ptr: *const u8,
+    len: usize,
+    name: &str,
+) -> Result<Option<&'a [u8]>, Error> {
+    match ptr.is_null() {
+        true => Ok(None),
+        false => read_bytes(ptr, len, name).map(Some),
+    }
+}
+
+pub(crate) fn check_output<T>(ptr: *mut T, name: &str) -> Result<(), Error> {
+    match ptr.is_null() {
+        true => Err(Error::invalid_argument(format!("`{}` is null", name))),
+        false => Ok(()),
+    }
+}
@@ -0,0 +1,95 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Status code returned by every function of the C API
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZokratesStatus {
+    Ok = 0,
+    /// An argument is null, malformed or out of range
+    InvalidArgument = 1,
+    /// The source does not compile
+    CompileError = 2,
+    /// The witness cannot be computed from the arguments
+    ComputationError = 3,
+    /// The program cannot be set up, as its curve is not supported by the backend
+    SetupError = 4,
+    /// The proof cannot be generated, as the proving key cannot be read or does not match the program, or its curve is
+    /// not supported
+    ProofError = 5,
+    /// The proof cannot be checked against the verification key, as their curves or schemes differ or the curve
+    /// is not supported
+    VerificationError = 6,
+    Panic = 7,
+}
+
+#[derive(Debug)]
+pub struct Error {
+    status: ZokratesStatus,
+    message: String,
+}
+
+impl Error {
+    pub fn new<S: Into<String>>(status: ZokratesStatus, message: S) -> Self {
+        Error {
+            status,
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid_argument<S: Into<String>>(message: S) -> Self {
+        Error::new(ZokratesStatus::InvalidArgument, message)
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+}
+
+fn set_last_error(message: String) {
+    // interior nul bytes cannot be represented in a C string
+    let message = CString::new(message.replace('\0', "")).unwrap();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(s) => *s,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(s) => s.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    }
+}
+
+/// Run `f`, converting its result to a status code and making sure no panic unwinds across the FFI boundary
+pub fn guard<F: FnOnce() -> Result<(), Error>>(f: F) -> ZokratesStatus {
+    LAST_ERROR.with(|e| *e.borrow_mut() = None);
+
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => ZokratesStatus::Ok,
+        Ok(Err(e)) => {
+            set_last_error(e.message);
+            e.status
+        }
+        Err(payload) => {
+            set_last_error(format!("Internal error: {}", panic_message(payload)));
+            ZokratesStatus::Panic
+        }
+    }
+}
+
+/// Returns a description of the last error which occurred on the calling thread, or null if the last call succeeded.
+/// The returned string is owned by the library and remains valid until the next call to this API on the same thread.
+#[no_mangle]
+pub extern "C" fn zokrates_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|e| {
+        e.borrow()
+            .as_ref()
+            .map(|m| m.as_ptr())
+            .unwrap_or(std::ptr::null())
+    })
+}
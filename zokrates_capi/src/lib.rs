@@ -0,0 +1,859 @@
+//! C bindings to embed the ZoKrates compiler and prover without going through the CLI.
+//!
+//! Every function returns a `ZokratesStatus`. When it is not `ZOKRATES_STATUS_OK`, a description of
+//! the error can be retrieved with `zokrates_last_error_message`. Buffers written to output parameters
+//! are owned by the caller and must be released with `zokrates_buffer_free`. Panics are caught and
+//! reported as `ZOKRATES_STATUS_PANIC`: they never unwind across the FFI boundary.
+
+mod buffer;
+mod error;
+mod resolver;
+
+pub use crate::buffer::{zokrates_buffer_free, ZokratesBuffer};
+pub use crate::error::{zokrates_last_error_message, ZokratesStatus};
+pub use crate::resolver::ZokratesResolveCallback;
+
+use crate::buffer::{check_output, read_bytes, read_optional_bytes, read_optional_str, read_str};
+use crate::error::{guard, Error};
+use crate::resolver::CallbackResolver;
+use rand_0_8::{rngs::StdRng, SeedableRng};
+use std::convert::TryFrom;
+use std::ffi::c_void;
+use std::os::raw::c_char;
+use zokrates_ast::ir::ProgEnum;
+use zokrates_common::helpers::{CurveParameter, SchemeParameter};
+use zokrates_field::{
+    Bls12_377Field, Bls12_381Field, Bn128Field, Bw6_761Field, PallasField, VestaField,
+};
+use zokrates_proof_systems::groth16::G16;
+use zokrates_proof_systems::rng::get_rng_from_seed;
+use zokrates_proof_systems::GM17;
+
+mod internal {
+    use super::*;
+    use serde::Serialize;
+    use std::io::Cursor;
+    use std::path::PathBuf;
+    use typed_arena::Arena;
+    use zokrates_abi::{parse_strict, Decode, Encode, Inputs};
+    use zokrates_ark::Ark;
+    use zokrates_ast::ir;
+    use zokrates_ast::typed::abi::Abi;
+    use zokrates_ast::typed::types::{ConcreteSignature, ConcreteType, GTupleType};
+    use zokrates_common::CompileConfig;
+    use zokrates_core::compile::{compile as core_compile, CompilationArtifacts};
+    use zokrates_field::Field;
+    use zokrates_proof_systems::{
+        read_proving_key_for, write_proving_key, Backend, NonUniversalBackend, NonUniversalScheme,
+        Proof, Scheme, TaggedProof, TaggedVerificationKey,
+    };
+
+    pub fn compile<T: Field>(
+        source: &str,
+        location: &str,
+        resolver: &CallbackResolver,
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        let arena = Arena::new();
+        let artifacts: CompilationArtifacts<T, _> = core_compile(
+            source.to_string(),
+            PathBuf::from(location),
+            Some(resolver),
+            CompileConfig::default(),
+            &arena,
+        )
+        .map_err(|errors| {
+            Error::new(
+                ZokratesStatus::CompileError,
+                errors
+                    .0
+                    .iter()
+                    .map(|e| format!("{}:{}", e.file().display(), e.value()))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )
+        })?;
+
+        let (program, abi) = artifacts.into_inner();
+
+        let mut buffer = Cursor::new(vec![]);
+        program
+            .serialize(&mut buffer)
+            .map_err(|e| Error::new(ZokratesStatus::CompileError, e.to_string()))?;
+
+        Ok((buffer.into_inner(), serde_json::to_vec(&abi).unwrap()))
+    }
+
+    pub fn compute<T: Field>(
+        program: ir::Prog<T>,
+        abi: Option<&str>,
+        arguments: &str,
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        let signature = match abi {
+            Some(abi) => {
+                let abi: Abi = serde_json::from_str(abi).map_err(|e| {
+                    Error::invalid_argument(format!("Could not deserialize `abi`: {}", e))
+                })?;
+                abi.signature()
+            }
+            None => ConcreteSignature::new()
+                .inputs(vec![ConcreteType::FieldElement; program.arguments.len()])
+                .output(ConcreteType::Tuple(GTupleType::new(
+                    vec![ConcreteType::FieldElement; program.return_count],
+                ))),
+        };
+
+        let inputs = parse_strict(arguments, signature.inputs.clone())
+            .map(Inputs::Abi)
+            .map_err(|e| Error::invalid_argument(e.to_string()))?;
+
+        let interpreter = zokrates_interpreter::Interpreter::default();
+
+        let witness = interpreter
+            .execute(program, &inputs.encode())
+            .map_err(|e| {
+                Error::new(
+                    ZokratesStatus::ComputationError,
+                    format!("Execution failed: {}", e),
+                )
+            })?;
+
+        let output = zokrates_abi::Value::decode(witness.return_values(), *signature.output)
+            .into_serde_json();
+
+        let mut buffer = vec![];
+        witness.write(&mut buffer).map_err(|e| {
+            Error::new(
+                ZokratesStatus::ComputationError,
+                format!("Could not write witness: {}", e),
+            )
+        })?;
+
+        Ok((buffer, serde_json::to_vec(&output).unwrap()))
+    }
+
+    pub fn setup<T: Field, S: NonUniversalScheme<T> + Serialize>(
+        program: ir::Prog<T>,
+        rng: &mut StdRng,
+    ) -> (Vec<u8>, Vec<u8>)
+    where
+        Ark: NonUniversalBackend<T, S>,
+    {
+        // the proving key is written with the hash of its constraint system, like the one of the cli
+        let hash = program.hash();
+        let keypair = <Ark as NonUniversalBackend<T, S>>::setup(program, rng);
+        let vk = serde_json::to_vec(&TaggedVerificationKey::<T, S>::new(keypair.vk)).unwrap();
+        let mut pk = vec![];
+        write_proving_key(&mut pk, &keypair.pk, &hash).unwrap();
+        (pk, vk)
+    }
+
+    pub fn generate_proof<T: Field, S: Scheme<T>>(
+        program: ir::Prog<T>,
+        witness: &[u8],
+        proving_key: &[u8],
+        rng: &mut StdRng,
+    ) -> Result<Vec<u8>, Error>
+    where
+        Ark: Backend<T, S>,
+    {
+        let witness = ir::Witness::read(witness)
+            .map_err(|e| Error::invalid_argument(format!("Could not read witness: {}", e)))?;
+
+        let proving_key = read_proving_key_for(proving_key, &program.hash())
+            .map_err(|e| Error::new(ZokratesStatus::ProofError, e))?;
+
+        let proof = <Ark as Backend<T, S>>::generate_proof(program, witness, proving_key, rng);
+
+        Ok(serde_json::to_vec(&TaggedProof::<T, S>::new(proof.proof, proof.inputs)).unwrap())
+    }
+
+    pub fn verify<T: Field, S: Scheme<T>>(
+        vk: serde_json::Value,
+        proof: serde_json::Value,
+    ) -> Result<bool, Error>
+    where
+        Ark: Backend<T, S>,
+    {
+        let vk: S::VerificationKey = serde_json::from_value(vk)
+            .map_err(|e| Error::invalid_argument(format!("Invalid verification key: {}", e)))?;
+        let proof: Proof<T, S> = serde_json::from_value(proof)
+            .map_err(|e| Error::invalid_argument(format!("Invalid proof: {}", e)))?;
+
+        Ok(<Ark as Backend<T, S>>::verify(vk, proof))
+    }
+}
+
+fn get_rng(entropy: Option<&[u8]>) -> Result<StdRng, Error> {
+    match entropy {
+        Some(seed) => get_rng_from_seed(seed).map_err(Error::invalid_argument),
+        None => Ok(StdRng::from_entropy()),
+    }
+}
+
+fn non_universal_scheme(scheme: &str) -> Result<SchemeParameter, Error> {
+    match SchemeParameter::try_from(scheme).map_err(Error::invalid_argument)? {
+        SchemeParameter::MARLIN => Err(Error::invalid_argument(
+            "Universal schemes are not supported",
+        )),
+        scheme => Ok(scheme),
+    }
+}
+
+fn tag<'a>(value: &'a serde_json::Value, field: &str, name: &str) -> Result<&'a str, Error> {
+    value[field].as_str().ok_or_else(|| {
+        Error::invalid_argument(format!("Invalid {}: missing field `{}`", name, field))
+    })
+}
+
+/// Compile a program for the given curve (`bn128`, `bls12_381`, `bls12_377` or `bw6_761`).
+///
+/// On success, `program` receives the serialized program and `abi` its ABI encoded as JSON.
+///
+/// # Safety
+///
+/// `source`, `location` and `curve` must be valid nul-terminated strings. `program` and `abi` must
+/// point to writable `ZokratesBuffer`s. `resolve_callback` may be null, in which case imports fail.
+#[no_mangle]
+pub unsafe extern "C" fn zokrates_compile(
+    source: *const c_char,
+    location: *const c_char,
+    curve: *const c_char,
+    resolve_callback: ZokratesResolveCallback,
+    user_data: *mut c_void,
+    program: *mut ZokratesBuffer,
+    abi: *mut ZokratesBuffer,
+) -> ZokratesStatus {
+    guard(|| {
+        let source = read_str(source, "source")?;
+        let location = read_str(location, "location")?;
+        let curve =
+            CurveParameter::try_from(read_str(curve, "curve")?).map_err(Error::invalid_argument)?;
+        check_output(program, "program")?;
+        check_output(abi, "abi")?;
+
+        let resolver = CallbackResolver::new(resolve_callback, user_data);
+
+        let (compiled, compiled_abi) = match curve {
+            CurveParameter::Bn128 => internal::compile::<Bn128Field>(source, location, &resolver),
+            CurveParameter::Bls12_381 => {
+                internal::compile::<Bls12_381Field>(source, location, &resolver)
+            }
+            CurveParameter::Bls12_377 => {
+                internal::compile::<Bls12_377Field>(source, location, &resolver)
+            }
+            CurveParameter::Bw6_761 => {
+                internal::compile::<Bw6_761Field>(source, location, &resolver)
+            }
+            CurveParameter::Pallas => internal::compile::<PallasField>(source, location, &resolver),
+            CurveParameter::Vesta => internal::compile::<VestaField>(source, location, &resolver),
+        }?;
+
+        *program = compiled.into();
+        *abi = compiled_abi.into();
+        Ok(())
+    })
+}
+
+/// Compute a witness for a compiled program.
+///
+/// `arguments` is a JSON array of arguments. If `abi` is not null, arguments are checked against
+/// it, otherwise they are all expected to be field elements. On success, `witness` receives the
+/// witness and `output` the return value of the program encoded as JSON.
+///
+/// # Safety
+///
+/// `program` must point to `program_len` readable bytes. `abi` must be null or a valid nul-terminated
+/// string, `arguments` must be a valid nul-terminated string. `witness` and `output` must point to
+/// writable `ZokratesBuffer`s.
+#[no_mangle]
+pub unsafe extern "C" fn zokrates_compute_witness(
+    program: *const u8,
+    program_len: usize,
+    abi: *const c_char,
+    arguments: *const c_char,
+    witness: *mut ZokratesBuffer,
+    output: *mut ZokratesBuffer,
+) -> ZokratesStatus {
+    guard(|| {
+        let program = read_bytes(program, program_len, "program")?;
+        let abi = read_optional_str(abi, "abi")?;
+        let arguments = read_str(arguments, "arguments")?;
+        check_output(witness, "witness")?;
+        check_output(output, "output")?;
+
+        let (computed_witness, computed_output) = match ProgEnum::deserialize(program)
+            .map_err(Error::invalid_argument)?
+            .collect()
+        {
+            ProgEnum::Bn128Program(p) => internal::compute(p, abi, arguments),
+            ProgEnum::Bls12_381Program(p) => internal::compute(p, abi, arguments),
+            ProgEnum::Bls12_377Program(p) => internal::compute(p, abi, arguments),
+            ProgEnum::Bw6_761Program(p) => internal::compute(p, abi, arguments),
+            ProgEnum::PallasProgram(p) => internal::compute(p, abi, arguments),
+            ProgEnum::VestaProgram(p) => internal::compute(p, abi, arguments),
+        }?;
+
+        *witness = computed_witness.into();
+        *output = computed_output.into();
+        Ok(())
+    })
+}
+
+/// Run a trusted setup for a compiled program using the `ark` backend and a non-universal scheme
+/// (`g16` or `gm17`).
+///
+/// If `entropy` is not null, the `entropy_len` bytes it points to seed the random number generator,
+/// in which case at least 32 bytes are required. On success, `proving_key` receives the proving key
+/// and `verification_key` the verification key encoded as JSON.
+///
+/// # Safety
+///
+/// `program` must point to `program_len` readable bytes, `entropy` must be null or point to
+/// `entropy_len` readable bytes. `scheme` must be a valid nul-terminated string. `proving_key` and
+/// `verification_key` must point to writable `ZokratesBuffer`s.
+#[no_mangle]
+pub unsafe extern "C" fn zokrates_setup(
+    program: *const u8,
+    program_len: usize,
+    scheme: *const c_char,
+    entropy: *const u8,
+    entropy_len: usize,
+    proving_key: *mut ZokratesBuffer,
+    verification_key: *mut ZokratesBuffer,
+) -> ZokratesStatus {
+    guard(|| {
+        let program = read_bytes(program, program_len, "program")?;
+        let scheme = non_universal_scheme(read_str(scheme, "scheme")?)?;
+        let mut rng = get_rng(read_optional_bytes(entropy, entropy_len, "entropy")?)?;
+        check_output(proving_key, "proving_key")?;
+        check_output(verification_key, "verification_key")?;
+
+        let prog = ProgEnum::deserialize(program)
+            .map_err(Error::invalid_argument)?
+            .collect();
+
+        let (pk, vk) = match (scheme, prog) {
+            (SchemeParameter::G16, ProgEnum::Bn128Program(p)) => {
+                internal::setup::<_, G16>(p, &mut rng)
+            }
+            (SchemeParameter::G16, ProgEnum::Bls12_381Program(p)) => {
+                internal::setup::<_, G16>(p, &mut rng)
+            }
+            (SchemeParameter::G16, ProgEnum::Bls12_377Program(p)) => {
+                internal::setup::<_, G16>(p, &mut rng)
+            }
+            (SchemeParameter::G16, ProgEnum::Bw6_761Program(p)) => {
+                internal::setup::<_, G16>(p, &mut rng)
+            }
+            (SchemeParameter::GM17, ProgEnum::Bn128Program(p)) => {
+                internal::setup::<_, GM17>(p, &mut rng)
+            }
+            (SchemeParameter::GM17, ProgEnum::Bls12_381Program(p)) => {
+                internal::setup::<_, GM17>(p, &mut rng)
+            }
+            (SchemeParameter::GM17, ProgEnum::Bls12_377Program(p)) => {
+                internal::setup::<_, GM17>(p, &mut rng)
+            }
+            (SchemeParameter::GM17, ProgEnum::Bw6_761Program(p)) => {
+                internal::setup::<_, GM17>(p, &mut rng)
+            }
+            (SchemeParameter::MARLIN, _) => unreachable!(),
+            (_, prog) => {
+                return Err(Error::new(
+                    ZokratesStatus::SetupError,
+                    format!(
+                        "Curve `{}` is not supported by the ark backend",
+                        prog.curve()
+                    ),
+                ))
+            }
+        };
+
+        *proving_key = pk.into();
+        *verification_key = vk.into();
+        Ok(())
+    })
+}
+
+/// Generate a proof for a compiled program and a witness using the `ark` backend.
+///
+/// If `entropy` is not null, the `entropy_len` bytes it points to seed the random number generator,
+/// in which case at least 32 bytes are required. On success, `proof` receives the proof encoded as
+/// JSON.
+///
+/// # Safety
+///
+/// `program`, `witness` and `proving_key` must point to `program_len`, `witness_len` and
+/// `proving_key_len` readable bytes, `entropy` must be null or point to `entropy_len` readable bytes.
+/// `scheme` must be a valid nul-terminated string. `proof` must point to a writable `ZokratesBuffer`.
+#[no_mangle]
+pub unsafe extern "C" fn zokrates_generate_proof(
+    program: *const u8,
+    program_len: usize,
+    witness: *const u8,
+    witness_len: usize,
+    proving_key: *const u8,
+    proving_key_len: usize,
+    scheme: *const c_char,
+    entropy: *const u8,
+    entropy_len: usize,
+    proof: *mut ZokratesBuffer,
+) -> ZokratesStatus {
+    guard(|| {
+        let program = read_bytes(program, program_len, "program")?;
+        let witness = read_bytes(witness, witness_len, "witness")?;
+        let pk = read_bytes(proving_key, proving_key_len, "proving_key")?;
+        let scheme = non_universal_scheme(read_str(scheme, "scheme")?)?;
+        let mut rng = get_rng(read_optional_bytes(entropy, entropy_len, "entropy")?)?;
+        check_output(proof, "proof")?;
+
+        let prog = ProgEnum::deserialize(program)
+            .map_err(Error::invalid_argument)?
+            .collect();
+
+        let generated = match (scheme, prog) {
+            (SchemeParameter::G16, ProgEnum::Bn128Program(p)) => {
+                internal::generate_proof::<_, G16>(p, witness, pk, &mut rng)
+            }
+            (SchemeParameter::G16, ProgEnum::Bls12_381Program(p)) => {
+                internal::generate_proof::<_, G16>(p, witness, pk, &mut rng)
+            }
+            (SchemeParameter::G16, ProgEnum::Bls12_377Program(p)) => {
+                internal::generate_proof::<_, G16>(p, witness, pk, &mut rng)
+            }
+            (SchemeParameter::G16, ProgEnum::Bw6_761Program(p)) => {
+                internal::generate_proof::<_, G16>(p, witness, pk, &mut rng)
+            }
+            (SchemeParameter::GM17, ProgEnum::Bn128Program(p)) => {
+                internal::generate_proof::<_, GM17>(p, witness, pk, &mut rng)
+            }
+            (SchemeParameter::GM17, ProgEnum::Bls12_381Program(p)) => {
+                internal::generate_proof::<_, GM17>(p, witness, pk, &mut rng)
+            }
+            (SchemeParameter::GM17, ProgEnum::Bls12_377Program(p)) => {
+                internal::generate_proof::<_, GM17>(p, witness, pk, &mut rng)
+            }
+            (SchemeParameter::GM17, ProgEnum::Bw6_761Program(p)) => {
+                internal::generate_proof::<_, GM17>(p, witness, pk, &mut rng)
+            }
+            (SchemeParameter::MARLIN, _) => unreachable!(),
+            (_, prog) => Err(Error::new(
+                ZokratesStatus::ProofError,
+                format!(
+                    "Curve `{}` is not supported by the ark backend",
+                    prog.curve()
+                ),
+            )),
+        }?;
+
+        *proof = generated.into();
+        Ok(())
+    })
+}
+
+/// Verify a proof against a verification key, both encoded as JSON, using the `ark` backend.
+///
+/// On success, `verified` is set to whether the proof is valid.
+///
+/// # Safety
+///
+/// `verification_key` and `proof` must be valid nul-terminated strings. `verified` must point to a
+/// writable `bool`.
+#[no_mangle]
+pub unsafe extern "C" fn zokrates_verify(
+    verification_key: *const c_char,
+    proof: *const c_char,
+    verified: *mut bool,
+) -> ZokratesStatus {
+    guard(|| {
+        let vk: serde_json::Value =
+            serde_json::from_str(read_str(verification_key, "verification_key")?)
+                .map_err(|e| Error::invalid_argument(format!("Invalid verification key: {}", e)))?;
+        let proof: serde_json::Value = serde_json::from_str(read_str(proof, "proof")?)
+            .map_err(|e| Error::invalid_argument(format!("Invalid proof: {}", e)))?;
+        check_output(verified, "verified")?;
+
+        let curve = CurveParameter::try_from(tag(&vk, "curve", "verification key")?)
+            .map_err(Error::invalid_argument)?;
+        let scheme = non_universal_scheme(tag(&vk, "scheme", "verification key")?)?;
+
+        if tag(&proof, "curve", "proof")? != tag(&vk, "curve", "verification key")? {
+            return Err(Error::new(
+                ZokratesStatus::VerificationError,
+                "Proof and verification key should have the same curve",
+            ));
+        }
+
+        if tag(&proof, "scheme", "proof")? != tag(&vk, "scheme", "verification key")? {
+            return Err(Error::new(
+                ZokratesStatus::VerificationError,
+                "Proof and verification key should have the same scheme",
+            ));
+        }
+
+        *verified = match (scheme, curve) {
+            (SchemeParameter::G16, CurveParameter::Bn128) => {
+                internal::verify::<Bn128Field, G16>(vk, proof)
+            }
+            (SchemeParameter::G16, CurveParameter::Bls12_381) => {
+                internal::verify::<Bls12_381Field, G16>(vk, proof)
+            }
+            (SchemeParameter::G16, CurveParameter::Bls12_377) => {
+                internal::verify::<Bls12_377Field, G16>(vk, proof)
+            }
+            (SchemeParameter::G16, CurveParameter::Bw6_761) => {
+                internal::verify::<Bw6_761Field, G16>(vk, proof)
+            }
+            (SchemeParameter::GM17, CurveParameter::Bn128) => {
+                internal::verify::<Bn128Field, GM17>(vk, proof)
+            }
+            (SchemeParameter::GM17, CurveParameter::Bls12_381) => {
+                internal::verify::<Bls12_381Field, GM17>(vk, proof)
+            }
+            (SchemeParameter::GM17, CurveParameter::Bls12_377) => {
+                internal::verify::<Bls12_377Field, GM17>(vk, proof)
+            }
+            (SchemeParameter::GM17, CurveParameter::Bw6_761) => {
+                internal::verify::<Bw6_761Field, GM17>(vk, proof)
+            }
+            (SchemeParameter::MARLIN, _) => unreachable!(),
+            (_, curve) => Err(Error::new(
+                ZokratesStatus::VerificationError,
+                format!("Curve `{}` is not supported by the ark backend", curve),
+            )),
+        }?;
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::{CStr, CString};
+    use std::ptr;
+
+    fn empty() -> ZokratesBuffer {
+        ZokratesBuffer {
+            data: ptr::null_mut(),
+            len: 0,
+        }
+    }
+
+    unsafe fn into_vec(buffer: ZokratesBuffer) -> Vec<u8> {
+        let v = std::slice::from_raw_parts(buffer.data, buffer.len).to_vec();
+        zokrates_buffer_free(buffer);
+        v
+    }
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(zokrates_last_error_message()) }
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    unsafe extern "C" fn resolve(
+        _: *mut c_void,
+        _: *const c_char,
+        import_location: *const c_char,
+        source: *mut *const c_char,
+        location: *mut *const c_char,
+    ) -> bool {
+        static SOURCE: &[u8] = b"def main() -> field { return 2; }\0";
+        *source = SOURCE.as_ptr() as *const c_char;
+        *location = import_location;
+        true
+    }
+
+    unsafe fn compile(
+        source: &str,
+        callback: ZokratesResolveCallback,
+    ) -> (ZokratesStatus, Vec<u8>, Vec<u8>) {
+        compile_for(source, "bn128", callback)
+    }
+
+    unsafe fn compile_for(
+        source: &str,
+        curve: &str,
+        callback: ZokratesResolveCallback,
+    ) -> (ZokratesStatus, Vec<u8>, Vec<u8>) {
+        let source = CString::new(source).unwrap();
+        let location = CString::new("main.zok").unwrap();
+        let curve = CString::new(curve).unwrap();
+
+        let mut program = empty();
+        let mut abi = empty();
+
+        let status = zokrates_compile(
+            source.as_ptr(),
+            location.as_ptr(),
+            curve.as_ptr(),
+            callback,
+            ptr::null_mut(),
+            &mut program,
+            &mut abi,
+        );
+
+        match status {
+            ZokratesStatus::Ok => (status, into_vec(program), into_vec(abi)),
+            _ => (status, vec![], vec![]),
+        }
+    }
+
+    #[test]
+    fn compile_prove_verify() {
+        unsafe {
+            let (status, program, abi) = compile(
+                "import \"./two\" as two;\ndef main(field a) -> field { return a * two(); }",
+                Some(resolve),
+            );
+            assert_eq!(status, ZokratesStatus::Ok);
+
+            let abi = CString::new(abi).unwrap();
+            let arguments = CString::new("[\"21\"]").unwrap();
+            let mut witness = empty();
+            let mut output = empty();
+
+            assert_eq!(
+                zokrates_compute_witness(
+                    program.as_ptr(),
+                    program.len(),
+                    abi.as_ptr(),
+                    arguments.as_ptr(),
+                    &mut witness,
+                    &mut output,
+                ),
+                ZokratesStatus::Ok
+            );
+
+            let witness = into_vec(witness);
+            let output: serde_json::Value = serde_json::from_slice(&into_vec(output)).unwrap();
+            assert_eq!(output, serde_json::json!("42"));
+
+            let scheme = CString::new("g16").unwrap();
+            let entropy = [42u8; 32];
+            let mut pk = empty();
+            let mut vk = empty();
+
+            assert_eq!(
+                zokrates_setup(
+                    program.as_ptr(),
+                    program.len(),
+                    scheme.as_ptr(),
+                    entropy.as_ptr(),
+                    entropy.len(),
+                    &mut pk,
+                    &mut vk,
+                ),
+                ZokratesStatus::Ok
+            );
+
+            let pk = into_vec(pk);
+            let vk = CString::new(into_vec(vk)).unwrap();
+            let mut proof = empty();
+
+            assert_eq!(
+                zokrates_generate_proof(
+                    program.as_ptr(),
+                    program.len(),
+                    witness.as_ptr(),
+                    witness.len(),
+                    pk.as_ptr(),
+                    pk.len(),
+                    scheme.as_ptr(),
+                    ptr::null(),
+                    0,
+                    &mut proof,
+                ),
+                ZokratesStatus::Ok
+            );
+
+            let proof = CString::new(into_vec(proof)).unwrap();
+            let mut verified = false;
+
+            assert_eq!(
+                zokrates_verify(vk.as_ptr(), proof.as_ptr(), &mut verified),
+                ZokratesStatus::Ok
+            );
+            assert!(verified);
+            assert!(zokrates_last_error_message().is_null());
+        }
+    }
+
+    #[test]
+    fn compile_error() {
+        unsafe {
+            let (status, _, _) = compile("def main() -> field { return true; }", None);
+            assert_eq!(status, ZokratesStatus::CompileError);
+            assert!(last_error().contains("main.zok"));
+        }
+    }
+
+    #[test]
+    fn unresolved_import() {
+        unsafe {
+            let (status, _, _) = compile("import \"./two\" as two;\ndef main() {}", None);
+            assert_eq!(status, ZokratesStatus::CompileError);
+            assert!(last_error().contains("could not resolve module `./two`"));
+        }
+    }
+
+    #[test]
+    fn null_argument() {
+        unsafe {
+            let mut program = empty();
+            let mut abi = empty();
+            let status = zokrates_compile(
+                ptr::null(),
+                ptr::null(),
+                ptr::null(),
+                None,
+                ptr::null_mut(),
+                &mut program,
+                &mut abi,
+            );
+            assert_eq!(status, ZokratesStatus::InvalidArgument);
+            assert_eq!(last_error(), "`source` is null");
+        }
+    }
+
+    #[test]
+    fn short_entropy() {
+        unsafe {
+            let (_, program, _) = compile("def main() {}", None);
+            let scheme = CString::new("g16").unwrap();
+            let entropy = [42u8; 31];
+            let mut pk = empty();
+            let mut vk = empty();
+
+            assert_eq!(
+                zokrates_setup(
+                    program.as_ptr(),
+                    program.len(),
+                    scheme.as_ptr(),
+                    entropy.as_ptr(),
+                    entropy.len(),
+                    &mut pk,
+                    &mut vk,
+                ),
+                ZokratesStatus::InvalidArgument
+            );
+        }
+    }
+
+    // the proving and verification keys of `program`, set up with g16
+    unsafe fn setup(program: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let scheme = CString::new("g16").unwrap();
+        let mut pk = empty();
+        let mut vk = empty();
+
+        assert_eq!(
+            zokrates_setup(
+                program.as_ptr(),
+                program.len(),
+                scheme.as_ptr(),
+                ptr::null(),
+                0,
+                &mut pk,
+                &mut vk,
+            ),
+            ZokratesStatus::Ok
+        );
+
+        (into_vec(pk), into_vec(vk))
+    }
+
+    #[test]
+    fn setup_error() {
+        unsafe {
+            let (_, program, _) = compile_for("def main() {}", "pallas", None);
+            let scheme = CString::new("g16").unwrap();
+            let mut pk = empty();
+            let mut vk = empty();
+
+            assert_eq!(
+                zokrates_setup(
+                    program.as_ptr(),
+                    program.len(),
+                    scheme.as_ptr(),
+                    ptr::null(),
+                    0,
+                    &mut pk,
+                    &mut vk,
+                ),
+                ZokratesStatus::SetupError
+            );
+            assert!(last_error().contains("Curve `pallas` is not supported"));
+        }
+    }
+
+    #[test]
+    fn proof_error() {
+        unsafe {
+            let (_, other, _) = compile("def main() {}", None);
+            let (pk, _) = setup(&other);
+
+            let (_, program, _) = compile("def main(field a) { assert(a == 1); return; }", None);
+            let arguments = CString::new("[\"1\"]").unwrap();
+            let mut witness = empty();
+            let mut output = empty();
+
+            assert_eq!(
+                zokrates_compute_witness(
+                    program.as_ptr(),
+                    program.len(),
+                    ptr::null(),
+                    arguments.as_ptr(),
+                    &mut witness,
+                    &mut output,
+                ),
+                ZokratesStatus::Ok
+            );
+            zokrates_buffer_free(output);
+            let witness = into_vec(witness);
+
+            // the proving key was set up for another program
+            let scheme = CString::new("g16").unwrap();
+            let mut proof = empty();
+
+            assert_eq!(
+                zokrates_generate_proof(
+                    program.as_ptr(),
+                    program.len(),
+                    witness.as_ptr(),
+                    witness.len(),
+                    pk.as_ptr(),
+                    pk.len(),
+                    scheme.as_ptr(),
+                    ptr::null(),
+                    0,
+                    &mut proof,
+                ),
+                ZokratesStatus::ProofError
+            );
+            assert!(last_error().contains("Constraint system hash mismatch"));
+        }
+    }
+
+    #[test]
+    fn verification_error() {
+        unsafe {
+            let (_, program, _) = compile("def main() {}", None);
+            let (_, vk) = setup(&program);
+
+            let vk = CString::new(vk).unwrap();
+            let proof = CString::new("{\"scheme\": \"g16\", \"curve\": \"bls12_381\"}").unwrap();
+            let mut verified = false;
+
+            assert_eq!(
+                zokrates_verify(vk.as_ptr(), proof.as_ptr(), &mut verified),
+                ZokratesStatus::VerificationError
+            );
+            assert_eq!(
+                last_error(),
+                "Proof and verification key should have the same curve"
+            );
+        }
+    }
+}
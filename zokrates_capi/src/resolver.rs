@@ -0,0 +1,82 @@
+use std::ffi::{c_void, CStr, CString};
+use std::os::raw::c_char;
+use std::path::PathBuf;
+use zokrates_common::Resolver;
+use zokrates_core::imports::Error;
+
+/// Callback used to resolve imports during compilation, including imports from the standard library.
+///
+/// It receives the location of the importing module and the import path. On success, it sets `source`
+/// and `location` to nul-terminated strings, which must remain valid until the compilation returns,
+/// and returns `true`.
+pub type ZokratesResolveCallback = Option<
+    unsafe extern "C" fn(
+        user_data: *mut c_void,
+        current_location: *const c_char,
+        import_location: *const c_char,
+        source: *mut *const c_char,
+        location: *mut *const c_char,
+    ) -> bool,
+>;
+
+pub struct CallbackResolver {
+    callback: ZokratesResolveCallback,
+    user_data: *mut c_void,
+}
+
+impl CallbackResolver {
+    pub fn new(callback: ZokratesResolveCallback, user_data: *mut c_void) -> Self {
+        CallbackResolver {
+            callback,
+            user_data,
+        }
+    }
+}
+
+impl Resolver<Error> for CallbackResolver {
+    fn resolve(
+        &self,
+        current_location: PathBuf,
+        import_location: PathBuf,
+    ) -> Result<(String, PathBuf), Error> {
+        let not_found = || {
+            Error::new(format!(
+                "could not resolve module `{}`",
+                import_location.display()
+            ))
+        };
+
+        let callback = self.callback.ok_or_else(not_found)?;
+
+        let current = CString::new(current_location.to_string_lossy().into_owned())
+            .map_err(|_| not_found())?;
+        let import = CString::new(import_location.to_string_lossy().into_owned())
+            .map_err(|_| not_found())?;
+
+        let mut source: *const c_char = std::ptr::null();
+        let mut location: *const c_char = std::ptr::null();
+
+        let resolved = unsafe {
+            callback(
+                self.user_data,
+                current.as_ptr(),
+                import.as_ptr(),
+                &mut source,
+                &mut location,
+            )
+        };
+
+        if !resolved || source.is_null() || location.is_null() {
+            return Err(not_found());
+        }
+
+        let source = unsafe { CStr::from_ptr(source) }
+            .to_str()
+            .map_err(|_| Error::new("resolved source is not valid UTF-8"))?;
+        let location = unsafe { CStr::from_ptr(location) }
+            .to_str()
+            .map_err(|_| Error::new("resolved location is not valid UTF-8"))?;
+
+        Ok((source.to_owned(), PathBuf::from(location)))
+    }
+}
@@ -4,6 +4,26 @@ mod witness;
 pub use r1cs::write_r1cs;
 pub use witness::write_witness;
 
+use zokrates_ast::{
+    flat::Variable,
+    ir::{ProgIterator, Statement},
+};
+
+/// The public wires of `prog`, which follow the constant wire `one`: the outputs then the public inputs as circom
+/// orders them, or the public values in the order of the proof if the program has a public layout
+pub fn public_wires<'ast, T, I: IntoIterator<Item = Statement<'ast, T>>>(
+    prog: &ProgIterator<'ast, T, I>,
+) -> Vec<Variable> {
+    match prog.public_layout.is_empty() {
+        true => prog
+            .returns()
+            .into_iter()
+            .chain(prog.arguments.iter().filter(|a| !a.private).map(|a| a.id))
+            .collect(),
+        false => prog.public_variables(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::io::Cursor;
@@ -15,7 +35,7 @@ mod tests {
     };
     use zokrates_ast::{
         flat::{Parameter, Variable},
-        ir::{LinComb, Prog, PublicInputs, QuadComb, Statement, Witness},
+        ir::{LinComb, Prog, QuadComb, Statement, Witness},
     };
     use zokrates_field::Bn128Field;
 
@@ -29,6 +49,8 @@ mod tests {
                 Parameter::public(Variable::new(1)),
             ],
             return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
             statements: vec![
                 Statement::Constraint(
                     QuadComb::from_linear_combinations(
@@ -46,12 +68,12 @@ mod tests {
             ],
         };
 
+        let public_wires = public_wires(&prog);
+
         let mut r1cs = vec![];
 
         write_r1cs(&mut r1cs, prog).unwrap();
 
-        let public_inputs: PublicInputs = vec![Variable::new(1)].into_iter().collect();
-
         let witness: Witness<Bn128Field> = Witness(
             vec![
                 (Variable::new(0), Bn128Field::from(1u32)),
@@ -65,7 +87,7 @@ mod tests {
 
         let mut wtns = vec![];
 
-        write_witness(&mut wtns, witness, public_inputs).unwrap();
+        write_witness(&mut wtns, witness, public_wires).unwrap();
 
         let (r1cs, mapping) = r1cs_from_bin(Cursor::new(r1cs)).unwrap();
         let wtns = witness_from_bin::<Bn256, _>(Cursor::new(wtns)).unwrap();
@@ -87,4 +109,43 @@ mod tests {
         let rng = create_rng();
         assert!(prove(circuit, &params, rng).is_ok());
     }
+
+    #[test]
+    fn public_wires_follow_layout() {
+        let prog: Prog<Bn128Field> = Prog {
+            arguments: vec![
+                Parameter::private(Variable::new(0)),
+                Parameter::public(Variable::new(1)),
+            ],
+            return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
+            statements: vec![Statement::Constraint(
+                (LinComb::from(Variable::new(0)) + LinComb::from(Variable::new(1))).into(),
+                Variable::public(0).into(),
+                None,
+            )],
+        };
+
+        // circom puts the outputs first by default
+        assert_eq!(
+            public_wires(&prog),
+            vec![Variable::public(0), Variable::new(1)]
+        );
+
+        // the layout of the proof keeps the public input first
+        let prog = prog.with_public_layout(vec![0, 1]);
+
+        assert_eq!(
+            public_wires(&prog),
+            vec![Variable::new(1), Variable::public(0)]
+        );
+
+        let (variables, private_offset, _) = r1cs::r1cs_program(prog);
+        assert_eq!(private_offset, 3);
+        assert_eq!(
+            &variables[..3],
+            &[Variable::one(), Variable::new(1), Variable::public(0)]
+        );
+    }
 }
@@ -5,6 +5,9 @@ use std::{io::Write, ops::Add};
 use zokrates_ast::flat::Variable;
 use zokrates_ast::ir::{Prog, Statement};
 use zokrates_field::Field;
+
+use crate::public_wires;
+
 struct Header {
     pub field_size: u32,
     pub prime_size: Vec<u8>,
@@ -54,12 +57,8 @@ pub fn r1cs_program<T: Field>(prog: Prog<T>) -> (Vec<Variable>, usize, Vec<Const
     let mut variables: HashMap<Variable, usize> = HashMap::new();
     provide_variable_idx(&mut variables, &Variable::one());
 
-    for i in 0..prog.return_count {
-        provide_variable_idx(&mut variables, &Variable::public(i));
-    }
-
-    for x in prog.arguments.iter().filter(|p| !p.private) {
-        provide_variable_idx(&mut variables, &x.id);
+    for v in public_wires(&prog) {
+        provide_variable_idx(&mut variables, &v);
     }
 
     // position where private part of witness starts
@@ -100,6 +99,22 @@ pub fn r1cs_program<T: Field>(prog: Prog<T>) -> (Vec<Variable>, usize, Vec<Const
         Statement::Directive(..) => None,
         Statement::Log(..) => None,
     }) {
+        // a linear constraint `(~one) * (lin') == lin` is written `0 * 0 == lin - lin'`, as circom does, so that
+        // it does not take a multiplication
+        if quad.is_linear() {
+            constraints.push((
+                vec![],
+                vec![],
+                (lin - quad.right)
+                    .into_canonical()
+                    .0
+                    .into_iter()
+                    .map(|(k, v)| (*variables.get(&k).unwrap(), v))
+                    .collect(),
+            ));
+            continue;
+        }
+
         constraints.push((
             quad.left
                 .0
@@ -209,11 +224,7 @@ fn write_lincomb<T: Field, W: Write>(writer: &mut W, l: LinComb<T>) -> Result<()
     writer.write_u32::<LittleEndian>(l.len() as u32)?;
     for (var, coeff) in l {
         writer.write_u32::<LittleEndian>(var as u32)?;
-        let mut res = vec![0u8; 32];
-        for (value, padded) in coeff.to_biguint().to_bytes_le().iter().zip(res.iter_mut()) {
-            *padded = *value;
-        }
-        writer.write_all(&res)?;
+        writer.write_all(&coeff.to_bytes_le())?;
     }
     Ok(())
 }
@@ -291,6 +302,8 @@ mod tests {
         let prog: Prog<Bn128Field> = Prog {
             arguments: vec![],
             return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
             statements: vec![Statement::Constraint(
                 LinComb::one().into(),
                 Variable::public(0).into(),
@@ -305,16 +318,14 @@ mod tests {
             0x72, 0x31, 0x63, 0x73,
             0x01, 0x00, 0x00, 0x00,
             0x03, 0x00, 0x00, 0x00,
-            0x02, 0x00, 0x00, 0x00, 0x78, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // size = 1 constraint = sum(4 /* write term_count_i */ + term_count_i * (4 + 32)) = 120
-            0x01, 0x00, 0x00, 0x00, // 1 element in this lc
-            0x00, 0x00, 0x00, 0x00, // variable 0
-            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // coeff 1
-            0x01, 0x00, 0x00, 0x00, // 1 element in this lc
-            0x00, 0x00, 0x00, 0x00, // variable 0
-            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // coeff 1
-            0x01, 0x00, 0x00, 0x00, // 1 element in this lc
+            0x02, 0x00, 0x00, 0x00, 0x54, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // size = 1 constraint = sum(4 /* write term_count_i */ + term_count_i * (4 + 32)) = 84
+            0x00, 0x00, 0x00, 0x00, // 0 element in this lc
+            0x00, 0x00, 0x00, 0x00, // 0 element in this lc
+            0x02, 0x00, 0x00, 0x00, // 2 element in this lc
             0x01, 0x00, 0x00, 0x00, // variable 1
             0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // coeff 1
+            0x00, 0x00, 0x00, 0x00, // variable 0
+            0x00, 0x00, 0x00, 0xf0, 0x93, 0xf5, 0xe1, 0x43, 0x91, 0x70, 0xb9, 0x79, 0x48, 0xe8, 0x33, 0x28, 0x5d, 0x58, 0x81, 0x81, 0xb6, 0x45, 0x50, 0xb8, 0x29, 0xa0, 0x31, 0xe1, 0x72, 0x4e, 0x64, 0x30, // coeff -1
             // header
             0x01, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             // modulo size 
@@ -342,6 +353,41 @@ mod tests {
         assert!(r1cs_reader::read(c).is_ok());
     }
 
+    #[test]
+    fn linear_constraints() {
+        let x = Variable::new(0);
+        let y = Variable::new(1);
+
+        let constraint = |quad: QuadComb<Bn128Field>| {
+            let prog = Prog {
+                arguments: vec![Parameter::private(x)],
+                return_count: 0,
+                public_layout: vec![],
+                variable_ordering: None,
+                statements: vec![Statement::Constraint(quad, y.into(), None)],
+            };
+            r1cs_program(prog).2.pop().unwrap()
+        };
+
+        // `(2 * ~one) * (x) == y` takes a multiplication
+        let (a, b, c) = constraint(QuadComb::from_linear_combinations(
+            LinComb::summand(2, Variable::one()),
+            x.into(),
+        ));
+        assert_eq!(a, vec![(0, Bn128Field::from(2))]);
+        assert_eq!(b, vec![(1, Bn128Field::from(1))]);
+        assert_eq!(c, vec![(2, Bn128Field::from(1))]);
+
+        // once linearized to `(~one) * (2 * x) == y`, it does not
+        let (a, b, c) = constraint(QuadComb::from_linear_combinations(
+            LinComb::one(),
+            LinComb::summand(2, x),
+        ));
+        assert!(a.is_empty());
+        assert!(b.is_empty());
+        assert_eq!(c, vec![(1, Bn128Field::from(-2)), (2, Bn128Field::from(1))]);
+    }
+
     #[test]
     fn with_inputs() {
         let prog: Prog<Bn128Field> = Prog {
@@ -350,6 +396,8 @@ mod tests {
                 Parameter::public(Variable::new(1)),
             ],
             return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
             statements: vec![
                 Statement::Constraint(
                     QuadComb::from_linear_combinations(
@@ -374,7 +422,7 @@ mod tests {
             0x72, 0x31, 0x63, 0x73,
             0x01, 0x00, 0x00, 0x00,
             0x03, 0x00, 0x00, 0x00,
-            0x02, 0x00, 0x00, 0x00, 0x14, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x02, 0x00, 0x00, 0x00, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             // first constraint
             0x01, 0x00, 0x00, 0x00, // 1 element in this lc
             0x03, 0x00, 0x00, 0x00, // variable 3
@@ -385,18 +433,16 @@ mod tests {
             0x01, 0x00, 0x00, 0x00, // 1 element in this lc
             0x03, 0x00, 0x00, 0x00, // variable 3
             0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // coeff 1
-            // second constraint
-            0x01, 0x00, 0x00, 0x00, // 1 element in this lc
-            0x00, 0x00, 0x00, 0x00, // variable 0
+            // second constraint, linear
+            0x00, 0x00, 0x00, 0x00, // 0 element in this lc
+            0x00, 0x00, 0x00, 0x00, // 0 element in this lc
+            0x03, 0x00, 0x00, 0x00, // 3 element in this lc
+            0x01, 0x00, 0x00, 0x00, // variable 1
             0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // coeff 1
-            0x02, 0x00, 0x00, 0x00, // 2 element in this lc
             0x03, 0x00, 0x00, 0x00, // variable 3
-            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // coeff 1
+            0x00, 0x00, 0x00, 0xf0, 0x93, 0xf5, 0xe1, 0x43, 0x91, 0x70, 0xb9, 0x79, 0x48, 0xe8, 0x33, 0x28, 0x5d, 0x58, 0x81, 0x81, 0xb6, 0x45, 0x50, 0xb8, 0x29, 0xa0, 0x31, 0xe1, 0x72, 0x4e, 0x64, 0x30, // coeff -1
             0x02, 0x00, 0x00, 0x00, // variable 2
-            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // coeff 1
-            0x01, 0x00, 0x00, 0x00, // 1 element in this lc
-            0x01, 0x00, 0x00, 0x00, // variable 1
-            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // coeff 1
+            0x00, 0x00, 0x00, 0xf0, 0x93, 0xf5, 0xe1, 0x43, 0x91, 0x70, 0xb9, 0x79, 0x48, 0xe8, 0x33, 0x28, 0x5d, 0x58, 0x81, 0x81, 0xb6, 0x45, 0x50, 0xb8, 0x29, 0xa0, 0x31, 0xe1, 0x72, 0x4e, 0x64, 0x30, // coeff -1
             // header
             0x01, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             // modulo size 
@@ -4,10 +4,7 @@ use std::{
 };
 
 use byteorder::{LittleEndian, WriteBytesExt};
-use zokrates_ast::{
-    flat::Variable,
-    ir::{PublicInputs, Witness},
-};
+use zokrates_ast::{flat::Variable, ir::Witness};
 use zokrates_field::Field;
 
 pub struct Header {
@@ -24,10 +21,12 @@ fn write_header<W: Write>(writer: &mut W, header: Header) -> Result<()> {
     Ok(())
 }
 
+/// Write the witness `w` of a program in the `.wtns` format, where `public_wires` are the public wires of the program
+/// as returned by `crate::public_wires`
 pub fn write_witness<T: Field, W: Write>(
     writer: &mut W,
     w: Witness<T>,
-    public_inputs: PublicInputs,
+    public_wires: Vec<Variable>,
 ) -> Result<()> {
     let modulo_byte_count = T::max_value().to_biguint().add(1u32).to_bytes_le().len() as u32;
     let witness_size = w.0.len() as u32;
@@ -61,43 +60,32 @@ pub fn write_witness<T: Field, W: Write>(
     let size = witness_size as u64 * modulo_byte_count as u64;
     writer.write_u64::<LittleEndian>(size)?;
 
-    write_witness_values(writer, w, public_inputs)?;
+    write_witness_values(writer, w, public_wires)?;
 
     Ok(())
 }
 
-fn write_val<T: Field, W: Write>(writer: &mut W, v: &T, modulo_byte_count: usize) -> Result<()> {
-    let mut res = vec![0u8; modulo_byte_count];
-    for (value, padded) in v.to_biguint().to_bytes_le().iter().zip(res.iter_mut()) {
-        *padded = *value;
-    }
-    writer.write_all(&res)?;
+fn write_val<T: Field, W: Write>(writer: &mut W, v: &T) -> Result<()> {
+    writer.write_all(&v.to_bytes_le())?;
     Ok(())
 }
 
 fn write_witness_values<T: Field, W: Write>(
     writer: &mut W,
     mut w: Witness<T>,
-    public_inputs: PublicInputs,
+    public_wires: Vec<Variable>,
 ) -> Result<()> {
-    let modulo_byte_count = T::max_value().to_biguint().add(1u32).to_bytes_le().len();
-
     if let Some(value) = w.0.remove(&Variable::one()) {
-        write_val(writer, &value, modulo_byte_count)?;
-    }
-
-    let output_count = w.0.iter().filter(|(var, _)| var.is_output()).count();
-
-    for value in (0..output_count).map(|id| w.0.remove(&Variable::public(id)).unwrap()) {
-        write_val(writer, &value, modulo_byte_count)?;
+        write_val(writer, &value)?;
     }
 
-    for value in public_inputs.iter().map(|var| w.0.remove(var).unwrap()) {
-        write_val(writer, &value, modulo_byte_count)?;
+    // the private return values, if any, are written with the other private values
+    for value in public_wires.iter().map(|var| w.0.remove(var).unwrap()) {
+        write_val(writer, &value)?;
     }
 
     for (_, val) in w.0.iter() {
-        write_val(writer, val, modulo_byte_count)?;
+        write_val(writer, val)?;
     }
 
     Ok(())
@@ -107,13 +95,12 @@ fn write_witness_values<T: Field, W: Write>(
 mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
-    use zokrates_ast::{flat::Variable, ir::PublicInputs};
+    use zokrates_ast::flat::Variable;
     use zokrates_field::Bn128Field;
 
     #[test]
     fn empty() {
         let w: Witness<Bn128Field> = Witness::default();
-        let public_inputs: PublicInputs = Default::default();
         let mut buf = Vec::new();
 
         #[rustfmt::skip]
@@ -136,7 +123,7 @@ mod tests {
             0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 
         ];
 
-        write_witness(&mut buf, w, public_inputs).unwrap();
+        write_witness(&mut buf, w, vec![]).unwrap();
 
         assert_eq!(buf, expected);
     }
@@ -144,7 +131,6 @@ mod tests {
     #[test]
     fn one_value() {
         let mut w: Witness<Bn128Field> = Witness::default();
-        let public_inputs: PublicInputs = Default::default();
         w.0.insert(Variable::public(0), 1.into());
         let mut buf = Vec::new();
 
@@ -170,7 +156,7 @@ mod tests {
             0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
         ];
 
-        write_witness(&mut buf, w, public_inputs).unwrap();
+        write_witness(&mut buf, w, vec![Variable::public(0)]).unwrap();
 
         assert_eq!(buf, expected);
     }
@@ -178,7 +164,6 @@ mod tests {
     #[test]
     fn one_and_pub_and_priv() {
         let mut w: Witness<Bn128Field> = Witness::default();
-        let public_inputs: PublicInputs = vec![Variable::new(1)].into_iter().collect();
         w.0.extend(vec![
             (Variable::public(0), 42.into()),
             (Variable::one(), 1.into()),
@@ -212,7 +197,7 @@ mod tests {
             0x2b, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
         ];
 
-        write_witness(&mut buf, w, public_inputs).unwrap();
+        write_witness(&mut buf, w, vec![Variable::public(0), Variable::new(1)]).unwrap();
 
         assert_eq!(buf, expected);
     }
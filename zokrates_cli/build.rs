@@ -0,0 +1,85 @@
+//! Build the template of `zokrates export-witness-calculator`: the `zokrates_witness_calculator` crate compiled to
+//! WebAssembly, which is embedded in the binary. A prebuilt template can be given in the variable below, which is
+//! required when the sources of the crate are not next to the CLI, or the `wasm32-unknown-unknown` target is not
+//! installed. Otherwise, an empty template is embedded and the command reports it is unavailable.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const TEMPLATE_ENV: &str = "ZOKRATES_WITNESS_CALCULATOR_TEMPLATE";
+const WASM_TARGET: &str = "wasm32-unknown-unknown";
+const CRATE_NAME: &str = "zokrates_witness_calculator";
+
+fn build_template(out_dir: &Path) -> Result<PathBuf, String> {
+    let crate_path = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap())
+        .join("..")
+        .join(CRATE_NAME);
+
+    if !crate_path.join("Cargo.toml").exists() {
+        return Err(format!("{} not found", crate_path.display()));
+    }
+
+    println!(
+        "cargo:rerun-if-changed={}",
+        crate_path.join("src").display()
+    );
+    println!(
+        "cargo:rerun-if-changed={}",
+        crate_path.join("Cargo.toml").display()
+    );
+
+    // the template is built in its own target directory, so that it does not wait for the lock held by this build
+    let target_dir = out_dir.join("target");
+
+    let status = Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()))
+        .args(["build", "--release", "--lib", "--target", WASM_TARGET])
+        .arg("--manifest-path")
+        .arg(crate_path.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .env_remove("CARGO_ENCODED_RUSTFLAGS")
+        .status()
+        .map_err(|e| format!("could not run cargo: {}", e))?;
+
+    match status.success() {
+        true => Ok(target_dir
+            .join(WASM_TARGET)
+            .join("release")
+            .join(format!("{}.wasm", CRATE_NAME))),
+        false => Err(format!(
+            "the build failed, make sure the `{}` target is installed",
+            WASM_TARGET
+        )),
+    }
+}
+
+fn main() {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let template = out_dir.join("witness_calculator.wasm");
+
+    println!("cargo:rerun-if-env-changed={}", TEMPLATE_ENV);
+
+    let source = match env::var(TEMPLATE_ENV) {
+        Ok(path) => {
+            println!("cargo:rerun-if-changed={}", path);
+            Ok(PathBuf::from(path))
+        }
+        Err(_) => build_template(&out_dir),
+    };
+
+    match source {
+        Ok(source) => {
+            fs::copy(&source, &template)
+                .unwrap_or_else(|e| panic!("Could not embed {}: {}", source.display(), e));
+        }
+        Err(e) => {
+            println!(
+                "cargo:warning=The witness calculator template was not built: {}",
+                e
+            );
+            fs::write(&template, []).unwrap();
+        }
+    }
+}
@@ -11,6 +11,9 @@ extern crate lazy_static;
 
 mod cli_constants;
 mod ops;
+mod output;
+mod program;
+mod project;
 
 use clap::{App, AppSettings, Arg};
 use ops::*;
@@ -22,7 +25,10 @@ fn main() {
     env_logger::init();
 
     cli().unwrap_or_else(|e| {
-        println!("{}", e);
+        // an empty error was already reported, for example in the JSON output of the command
+        if !e.is_empty() {
+            println!("{}", e);
+        }
         std::process::exit(1);
     })
 }
@@ -41,43 +47,79 @@ fn cli() -> Result<(), String> {
             .global(true)
         )
         .subcommands(vec![
+            abi_diff::subcommand(),
+            annotate::subcommand(),
+            #[cfg(any(feature = "bellman", feature = "ark"))]
+            build::subcommand(),
             compile::subcommand(),
             inspect::subcommand(),
             check::subcommand(),
+            check_equivalence::subcommand(),
             compute_witness::subcommand(),
+            coverage::subcommand(),
+            explain::subcommand(),
             #[cfg(feature = "ark")]
             universal_setup::subcommand(),
             #[cfg(feature = "bellman")]
             mpc::subcommand(),
             #[cfg(any(feature = "bellman", feature = "ark"))]
             setup::subcommand(),
+            export::subcommand(),
             export_verifier::subcommand(),
+            export_witness_calculator::subcommand(),
             #[cfg(any(feature = "bellman", feature = "ark"))]
             generate_proof::subcommand(),
             generate_smtlib2::subcommand(),
+            init::subcommand(),
+            pin::subcommand(),
             print_proof::subcommand(),
+            repl::subcommand(),
+            #[cfg(any(feature = "bellman", feature = "ark"))]
+            rerandomize_proof::subcommand(),
+            #[cfg(any(feature = "bellman", feature = "ark"))]
+            serve::subcommand(),
             #[cfg(any(feature = "bellman", feature = "ark"))]
-            verify::subcommand()])
+            verify::subcommand(),
+            verify_build::subcommand()])
         .get_matches();
 
     match matches.subcommand() {
+        ("abi-diff", Some(sub_matches)) => abi_diff::exec(sub_matches),
+        ("annotate", Some(sub_matches)) => annotate::exec(sub_matches),
+        #[cfg(any(feature = "bellman", feature = "ark"))]
+        ("build", Some(sub_matches)) => build::exec(sub_matches),
         ("compile", Some(sub_matches)) => compile::exec(sub_matches),
         ("inspect", Some(sub_matches)) => inspect::exec(sub_matches),
         ("check", Some(sub_matches)) => check::exec(sub_matches),
+        ("check-equivalence", Some(sub_matches)) => check_equivalence::exec(sub_matches),
         ("compute-witness", Some(sub_matches)) => compute_witness::exec(sub_matches),
+        ("coverage", Some(sub_matches)) => coverage::exec(sub_matches),
+        ("explain", Some(sub_matches)) => explain::exec(sub_matches),
         #[cfg(feature = "ark")]
         ("universal-setup", Some(sub_matches)) => universal_setup::exec(sub_matches),
         #[cfg(feature = "bellman")]
         ("mpc", Some(sub_matches)) => mpc::exec(sub_matches),
         #[cfg(any(feature = "bellman", feature = "ark"))]
         ("setup", Some(sub_matches)) => setup::exec(sub_matches),
+        ("export", Some(sub_matches)) => export::exec(sub_matches),
         ("export-verifier", Some(sub_matches)) => export_verifier::exec(sub_matches),
+        ("export-witness-calculator", Some(sub_matches)) => {
+            export_witness_calculator::exec(sub_matches)
+        }
         #[cfg(any(feature = "bellman", feature = "ark"))]
         ("generate-proof", Some(sub_matches)) => generate_proof::exec(sub_matches),
         ("generate-smtlib2", Some(sub_matches)) => generate_smtlib2::exec(sub_matches),
+        ("init", Some(sub_matches)) => init::exec(sub_matches),
+        ("pin", Some(sub_matches)) => pin::exec(sub_matches),
         ("print-proof", Some(sub_matches)) => print_proof::exec(sub_matches),
+        ("repl", Some(sub_matches)) => repl::exec(sub_matches),
+        #[cfg(any(feature = "bellman", feature = "ark"))]
+        ("rerandomize-proof", Some(sub_matches)) => rerandomize_proof::exec(sub_matches),
+        #[cfg(any(feature = "bellman", feature = "ark"))]
+        ("serve", Some(sub_matches)) => serve::exec(sub_matches),
         #[cfg(any(feature = "bellman", feature = "ark"))]
         ("verify", Some(sub_matches)) => verify::exec(sub_matches),
+        ("verify-build", Some(sub_matches)) => verify_build::exec(sub_matches),
         _ => unreachable!(),
     }
 }
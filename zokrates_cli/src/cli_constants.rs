@@ -8,11 +8,22 @@ pub const VERIFICATION_KEY_DEFAULT_PATH: &str = "verification.key";
 pub const PROVING_KEY_DEFAULT_PATH: &str = "proving.key";
 pub const VERIFICATION_CONTRACT_DEFAULT_PATH: &str = "verifier.sol";
 pub const WITNESS_DEFAULT_PATH: &str = "witness";
+pub const WITNESS_CALCULATOR_DEFAULT_PATH: &str = "witness_calculator.wasm";
 pub const JSON_PROOF_PATH: &str = "proof.json";
+pub const RERANDOMIZED_PROOF_PATH: &str = "rerandomized_proof.json";
 pub const UNIVERSAL_SETUP_DEFAULT_PATH: &str = "universal_setup.dat";
 pub const UNIVERSAL_SETUP_DEFAULT_SIZE: &str = "10";
 pub const SMTLIB2_DEFAULT_PATH: &str = "out.smt2";
+pub const ZKINTERFACE_DEFAULT_PATH: &str = "out.zkif";
 pub const MPC_DEFAULT_PATH: &str = "mpc.params";
+pub const COVERAGE_DEFAULT_PATH: &str = "coverage.json";
+pub const PIN_DEFAULT_PATH: &str = "pin.json";
+pub const DEBUG_TABLE_DEFAULT_PATH: &str = "out.debug";
+pub const MANIFEST_DEFAULT_PATH: &str = "manifest.json";
+pub const PROJECT_MANIFEST_DEFAULT_PATH: &str = "zokrates.toml";
+pub const WITNESS_KEY_ENV: &str = "ZOKRATES_WITNESS_KEY";
+pub const WITNESS_METADATA_DEFAULT_PATH: &str = "witness.meta.json";
+pub const NONCE_KEY_ENV: &str = "ZOKRATES_NONCE_KEY";
 
 lazy_static! {
     pub static ref DEFAULT_STDLIB_PATH: String = dirs::home_dir()
@@ -36,7 +47,7 @@ pub const BACKENDS: &[&str] = if cfg!(feature = "ark") {
     &[]
 };
 
-pub const CURVES: &[&str] = &[BN128, BLS12_381, BLS12_377, BW6_761];
+pub const CURVES: &[&str] = &[BN128, BLS12_381, BLS12_377, BW6_761, PALLAS, VESTA];
 
 pub const SCHEMES: &[&str] = &[G16, GM17, MARLIN];
 
@@ -0,0 +1,58 @@
+use crate::program::{entry_arg, read_abi};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use zokrates_ast::typed::abi::Abi;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("abi-diff")
+        .about("Compares two ABI specifications, reporting renamed, added, removed and retyped inputs, struct members and outputs, and fails on breaking changes")
+        .arg(
+            Arg::with_name("old")
+                .help("Path of the old ABI specification")
+                .value_name("FILE")
+                .required(true)
+                .index(1),
+        )
+        .arg(
+            Arg::with_name("new")
+                .help("Path of the new ABI specification")
+                .value_name("FILE")
+                .required(true)
+                .index(2),
+        )
+        .arg(entry_arg())
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .help("Write the differences as JSON")
+                .required(false),
+        )
+}
+
+fn read(path: &Path, entry: Option<&str>) -> Result<Abi, String> {
+    let file =
+        File::open(path).map_err(|why| format!("Could not open {}: {}", path.display(), why))?;
+    read_abi(BufReader::new(file), entry)
+        .map_err(|why| format!("Could not deserialize {}: {}", path.display(), why))
+}
+
+pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
+    let entry = sub_matches.value_of("entry");
+    let old = read(Path::new(sub_matches.value_of("old").unwrap()), entry)?;
+    let new = read(Path::new(sub_matches.value_of("new").unwrap()), entry)?;
+
+    let diff = old.diff(&new);
+
+    match (sub_matches.is_present("json"), diff.is_empty()) {
+        (true, _) => println!("{}", serde_json::to_string_pretty(&diff).unwrap()),
+        (false, true) => println!("The interfaces are identical"),
+        (false, false) => println!("{}", diff),
+    }
+
+    match diff.is_breaking() {
+        true => Err("The new interface is not compatible with the old one".to_string()),
+        false => Ok(()),
+    }
+}
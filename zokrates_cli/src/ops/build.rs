@@ -0,0 +1,261 @@
+use crate::cli_constants;
+use crate::ops::compile::{fmt_errors, report};
+use crate::ops::export_verifier;
+use crate::ops::setup::tag_verification_key;
+use crate::project::ProjectManifest;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use rand_0_8::rngs::StdRng;
+use rand_0_8::SeedableRng;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use typed_arena::Arena;
+#[cfg(feature = "ark")]
+use zokrates_ark::Ark;
+use zokrates_ast::ir::{self, ConstraintSystemHasher};
+use zokrates_ast::typed::abi::Abi;
+#[cfg(feature = "bellman")]
+use zokrates_bellman::Bellman;
+use zokrates_common::constants;
+use zokrates_common::helpers::*;
+use zokrates_common::{CompileConfig, Resolver};
+use zokrates_core::compile::{compile, CompilationArtifacts};
+use zokrates_field::{Bls12_377Field, Bls12_381Field, Bn128Field, Bw6_761Field, Field};
+use zokrates_fs_resolver::FileSystemResolver;
+use zokrates_proof_systems::*;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("build")
+        .about("Compiles a project described by a `zokrates.toml` manifest, then runs the setup and exports the verifier")
+        .arg(
+            Arg::with_name("manifest")
+                .long("manifest")
+                .help("Path of the project manifest")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(cli_constants::PROJECT_MANIFEST_DEFAULT_PATH),
+        )
+        .arg(
+            Arg::with_name("stdlib-path")
+                .long("stdlib-path")
+                .help("Path to the standard library")
+                .value_name("PATH")
+                .takes_value(true)
+                .required(false)
+                .env("ZOKRATES_STDLIB")
+                .default_value(cli_constants::DEFAULT_STDLIB_PATH.as_str()),
+        )
+}
+
+// check that the inputs at `path` are valid arguments of a program with the given ABI
+fn check_inputs<T: Field>(path: &Path, abi: &Abi) -> Result<(), String> {
+    let inputs = std::fs::read_to_string(path)
+        .map_err(|why| format!("Could not read {}: {}", path.display(), why))?;
+
+    let types = abi.provided_inputs().into_iter().map(|i| i.ty).collect();
+
+    zokrates_abi::parse_strict::<T>(&inputs, types)
+        .map(|_| ())
+        .map_err(|why| {
+            format!(
+                "The inputs in {} do not match the program: {}",
+                path.display(),
+                why
+            )
+        })
+}
+
+pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
+    let stdlib_path = sub_matches.value_of("stdlib-path").unwrap();
+    match Path::new(stdlib_path).exists() {
+        true => Ok(()),
+        _ => Err(format!(
+            "Invalid standard library source path: {}",
+            stdlib_path
+        )),
+    }?;
+
+    let resolver = FileSystemResolver::with_stdlib_root(stdlib_path);
+
+    let manifest_path = Path::new(sub_matches.value_of("manifest").unwrap());
+    let manifest = ProjectManifest::read(manifest_path)?;
+
+    let root = manifest_path.parent().unwrap_or_else(|| Path::new(""));
+    let out = root.join(&manifest.out);
+    std::fs::create_dir_all(&out)
+        .map_err(|why| format!("Could not create {}: {}", out.display(), why))?;
+
+    let project = Project {
+        entry: root.join(&manifest.entry),
+        inputs: manifest.inputs.as_ref().map(|inputs| root.join(inputs)),
+        out,
+    };
+
+    // the manifest is checked when read, and `build` only runs non-universal setups
+    let parameters = Parameters::try_from((
+        manifest.backend.as_str(),
+        manifest.curve.as_str(),
+        manifest.scheme.as_str(),
+    ))?;
+
+    match parameters {
+        #[cfg(feature = "bellman")]
+        Parameters(BackendParameter::Bellman, CurveParameter::Bn128, SchemeParameter::G16) => {
+            cli_build::<Bn128Field, G16, Bellman>(&project, &resolver)
+        }
+        #[cfg(feature = "bellman")]
+        Parameters(BackendParameter::Bellman, CurveParameter::Bls12_381, SchemeParameter::G16) => {
+            cli_build::<Bls12_381Field, G16, Bellman>(&project, &resolver)
+        }
+        #[cfg(feature = "ark")]
+        Parameters(BackendParameter::Ark, curve, SchemeParameter::G16) => match curve {
+            CurveParameter::Bn128 => cli_build::<Bn128Field, G16, Ark>(&project, &resolver),
+            CurveParameter::Bls12_381 => cli_build::<Bls12_381Field, G16, Ark>(&project, &resolver),
+            CurveParameter::Bls12_377 => cli_build::<Bls12_377Field, G16, Ark>(&project, &resolver),
+            CurveParameter::Bw6_761 => cli_build::<Bw6_761Field, G16, Ark>(&project, &resolver),
+            _ => unreachable!(),
+        },
+        #[cfg(feature = "ark")]
+        Parameters(BackendParameter::Ark, curve, SchemeParameter::GM17) => match curve {
+            CurveParameter::Bn128 => cli_build::<Bn128Field, GM17, Ark>(&project, &resolver),
+            CurveParameter::Bls12_381 => {
+                cli_build::<Bls12_381Field, GM17, Ark>(&project, &resolver)
+            }
+            CurveParameter::Bls12_377 => {
+                cli_build::<Bls12_377Field, GM17, Ark>(&project, &resolver)
+            }
+            CurveParameter::Bw6_761 => cli_build::<Bw6_761Field, GM17, Ark>(&project, &resolver),
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    }?;
+
+    println!(
+        "Built {} in {}",
+        manifest.entry.display(),
+        project.out.display()
+    );
+
+    Ok(())
+}
+
+/// The paths of a project, resolved against the directory of its manifest
+struct Project {
+    entry: PathBuf,
+    inputs: Option<PathBuf>,
+    out: PathBuf,
+}
+
+fn cli_build<T: Field, S: NonUniversalScheme<T>, B: NonUniversalBackend<T, S>>(
+    project: &Project,
+    resolver: &dyn Resolver<io::Error>,
+) -> Result<(), String> {
+    let program_path = project.out.join(cli_constants::FLATTENED_CODE_DEFAULT_PATH);
+    let abi_path = project.out.join(cli_constants::ABI_SPEC_DEFAULT_PATH);
+    let proving_key_path = project.out.join(cli_constants::PROVING_KEY_DEFAULT_PATH);
+    let verification_key_path = project
+        .out
+        .join(cli_constants::VERIFICATION_KEY_DEFAULT_PATH);
+
+    println!("Compiling {}", project.entry.display());
+
+    let source = std::fs::read_to_string(&project.entry)
+        .map_err(|why| format!("Could not open {}: {}", project.entry.display(), why))?;
+
+    let arena = Arena::new();
+    let artifacts: CompilationArtifacts<T, _> = compile::<T, _>(
+        source,
+        project.entry.clone(),
+        Some(resolver),
+        CompileConfig::default(),
+        &arena,
+    )
+    .map_err(fmt_errors)?
+    .collect();
+
+    report(artifacts.diagnostics());
+
+    let (program, abi) = artifacts.into_inner();
+
+    // the program is written as it is, and set up as a stream of its statements
+    let program_file = File::create(&program_path)
+        .map_err(|why| format!("Could not create {}: {}", program_path.display(), why))?;
+    program
+        .clone()
+        .serialize(BufWriter::new(program_file))
+        .map_err(|why| format!("Could not write {}: {}", program_path.display(), why))?;
+
+    let abi_file = File::create(&abi_path)
+        .map_err(|why| format!("Could not create {}: {}", abi_path.display(), why))?;
+    serde_json::to_writer_pretty(BufWriter::new(abi_file), &abi)
+        .map_err(|why| format!("Could not write {}: {}", abi_path.display(), why))?;
+
+    if let Some(inputs) = &project.inputs {
+        check_inputs::<T>(inputs, &abi)?;
+    }
+
+    println!("Running the setup");
+
+    let mut hasher = ConstraintSystemHasher::new(
+        &program.arguments,
+        program.return_count,
+        &program.public_layout,
+    );
+    let public_layout = program.public_layout.clone();
+    let program = ir::ProgIterator::new(
+        program.arguments,
+        program.statements.into_iter().inspect(|s| hasher.update(s)),
+        program.return_count,
+    )
+    .with_public_layout(program.public_layout);
+
+    let keypair = B::setup(program, &mut StdRng::from_entropy());
+    let hash = hasher.finalize();
+
+    let verification_key = tag_verification_key::<T, S>(keypair.vk, Some(abi))
+        .with_hash(hash)
+        .with_public_layout(public_layout);
+
+    let vk_file = File::create(&verification_key_path).map_err(|why| {
+        format!(
+            "Could not create {}: {}",
+            verification_key_path.display(),
+            why
+        )
+    })?;
+    serde_json::to_writer_pretty(BufWriter::new(vk_file), &verification_key).map_err(|why| {
+        format!(
+            "Could not write {}: {}",
+            verification_key_path.display(),
+            why
+        )
+    })?;
+
+    let mut pk_file = File::create(&proving_key_path)
+        .map_err(|why| format!("Could not create {}: {}", proving_key_path.display(), why))?;
+    write_proving_key(&mut pk_file, keypair.pk.as_ref(), &hash)
+        .map_err(|why| format!("Could not write {}: {}", proving_key_path.display(), why))?;
+
+    // solidity verifiers can only be exported for the curve supported by the EVM
+    match T::name() {
+        constants::BN128 => {
+            println!("Exporting the verifier");
+            export_verifier::export(
+                serde_json::to_value(&verification_key).unwrap(),
+                &project
+                    .out
+                    .join(cli_constants::VERIFICATION_CONTRACT_DEFAULT_PATH),
+                false,
+            )
+        }
+        curve => {
+            println!(
+                "Skipping the export of the verifier, which is only supported on bn128, not {}",
+                curve
+            );
+            Ok(())
+        }
+    }
+}
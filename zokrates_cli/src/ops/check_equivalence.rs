@@ -0,0 +1,112 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use zokrates_ast::ir::smt::{check_equivalence_with, EquivalenceResult, Side, Z3};
+use zokrates_ast::ir::{self, ProgEnum};
+use zokrates_field::Field;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("check-equivalence")
+        .about("Checks that two compiled programs accept the same inputs and outputs, using the z3 SMT solver")
+        .arg(
+            Arg::with_name("left")
+                .help("Path of the first binary")
+                .value_name("FILE")
+                .required(true)
+                .index(1),
+        )
+        .arg(
+            Arg::with_name("right")
+                .help("Path of the second binary")
+                .value_name("FILE")
+                .required(true)
+                .index(2),
+        )
+        .arg(
+            Arg::with_name("solver")
+                .long("solver")
+                .help("Path of the z3 executable")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value("z3"),
+        )
+}
+
+fn read(path: &Path) -> Result<BufReader<File>, String> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|why| format!("Could not open {}: {}", path.display(), why))
+}
+
+pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
+    let left = Path::new(sub_matches.value_of("left").unwrap());
+    let right = Path::new(sub_matches.value_of("right").unwrap());
+
+    match (
+        ProgEnum::deserialize(read(left)?)?,
+        ProgEnum::deserialize(read(right)?)?,
+    ) {
+        (ProgEnum::Bn128Program(a), ProgEnum::Bn128Program(b)) => cli_check(a, b, sub_matches),
+        (ProgEnum::Bls12_377Program(a), ProgEnum::Bls12_377Program(b)) => {
+            cli_check(a, b, sub_matches)
+        }
+        (ProgEnum::Bls12_381Program(a), ProgEnum::Bls12_381Program(b)) => {
+            cli_check(a, b, sub_matches)
+        }
+        (ProgEnum::Bw6_761Program(a), ProgEnum::Bw6_761Program(b)) => cli_check(a, b, sub_matches),
+        (ProgEnum::PallasProgram(a), ProgEnum::PallasProgram(b)) => cli_check(a, b, sub_matches),
+        (ProgEnum::VestaProgram(a), ProgEnum::VestaProgram(b)) => cli_check(a, b, sub_matches),
+        _ => Err("Programs were compiled for different curves".into()),
+    }
+}
+
+fn cli_check<
+    'a,
+    'b,
+    T: Field,
+    I: Iterator<Item = ir::Statement<'a, T>>,
+    J: Iterator<Item = ir::Statement<'b, T>>,
+>(
+    left: ir::ProgIterator<'a, T, I>,
+    right: ir::ProgIterator<'b, T, J>,
+    sub_matches: &ArgMatches,
+) -> Result<(), String> {
+    let left: ir::Prog<T> = left.collect();
+    let right: ir::Prog<T> = right.collect();
+
+    println!("Checking equivalence...");
+
+    let solver = Z3::with_path(sub_matches.value_of("solver").unwrap());
+
+    match check_equivalence_with(&left, &right, &solver)? {
+        EquivalenceResult::Equivalent => {
+            println!("The programs are equivalent");
+            Ok(())
+        }
+        EquivalenceResult::Counterexample(c) => {
+            let show = |values: &[T]| {
+                values
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+
+            Err(format!(
+                "The programs are not equivalent: only the {} program accepts arguments [{}] with return values [{}]",
+                match c.accepted_by {
+                    Side::Left => "first",
+                    Side::Right => "second",
+                },
+                show(&c.inputs),
+                show(&c.outputs)
+            ))
+        }
+        EquivalenceResult::Unknown(reason) => Err(format!(
+            "The solver could not decide whether the programs are equivalent:\n{}",
+            reason
+        )),
+    }
+}
@@ -1,16 +1,31 @@
 use crate::cli_constants;
 use clap::{App, Arg, ArgMatches, SubCommand};
 use serde_json::to_writer_pretty;
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::fs::File;
-use std::io::{BufReader, BufWriter, Read};
+use std::io::{self, BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
 use typed_arena::Arena;
+use zokrates_ast::common::Diagnostic;
+use zokrates_ast::ir::{
+    self, container::serialize_container, ordering::reorder, text::TextDisplay, PublicLayout,
+};
+use zokrates_ast::typed::abi::Abi;
 use zokrates_circom::write_r1cs;
 use zokrates_common::constants::BN128;
-use zokrates_common::{helpers::CurveParameter, CompileConfig};
-use zokrates_core::compile::{compile, CompileError};
-use zokrates_field::{Bls12_377Field, Bls12_381Field, Bn128Field, Bw6_761Field, Field};
+use zokrates_common::pipeline::{Pipeline, DEFAULT_PIPELINE};
+use zokrates_common::{
+    helpers::CurveParameter, CompileConfig, DivByZero, ReductionPolicy, Resolver,
+};
+use zokrates_core::cache::{compile_with_cache, BuildCache, SourceCache};
+use zokrates_core::compile::{
+    compile, compile_observed, estimate, CompileError, CompileErrors, Observers,
+};
+use zokrates_core::sink::{ConstraintCounter, ConstraintSink, CsvSink};
+use zokrates_field::{
+    Bls12_377Field, Bls12_381Field, Bn128Field, Bw6_761Field, Field, PallasField, VestaField,
+};
 use zokrates_fs_resolver::FileSystemResolver;
 
 pub fn subcommand() -> App<'static, 'static> {
@@ -22,7 +37,15 @@ pub fn subcommand() -> App<'static, 'static> {
             .help("Path of the source code")
             .value_name("FILE")
             .takes_value(true)
-            .required(true)
+            .required_unless("entry-point")
+        ).arg(Arg::with_name("entry-point")
+            .long("entry-point")
+            .help("Compile several programs into a single binary, each given as `name=path`. The other commands select one of them with `--entry name`")
+            .value_name("NAME=FILE")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .conflicts_with_all(&["input", "report", "reorder-variables", "estimate-only", "debug-table", "manifest", "minimization-report", "stats", "dump-constraints"])
         ).arg(Arg::with_name("stdlib-path")
         .long("stdlib-path")
         .help("Path to the standard library")
@@ -47,6 +70,14 @@ pub fn subcommand() -> App<'static, 'static> {
         .takes_value(true)
         .required(false)
         .default_value(cli_constants::FLATTENED_CODE_DEFAULT_PATH)
+    ).arg(Arg::with_name("output-format")
+        .long("output-format")
+        .help("Format of the output program. The text format can be compared line by line, but cannot be used by the other commands")
+        .takes_value(true)
+        .required(false)
+        .possible_values(&["binary", "text"])
+        .default_value("binary")
+        .conflicts_with("entry-point")
     ).arg(Arg::with_name("r1cs")
     .short("r1cs")
     .long("r1cs")
@@ -58,8 +89,9 @@ pub fn subcommand() -> App<'static, 'static> {
 ).arg(Arg::with_name("curve")
         .short("c")
         .long("curve")
-        .help("Curve to be used in the compilation")
+        .help("Curve to be used in the compilation. Several curves can be given as a comma-separated list, in which case one set of artifacts is written per curve, with the name of the curve inserted in the name of each output file. The imported modules are read once, the program being parsed, checked and compiled for each curve")
         .takes_value(true)
+        .use_delimiter(true)
         .required(false)
         .possible_values(cli_constants::CURVES)
         .default_value(BN128)
@@ -67,49 +99,130 @@ pub fn subcommand() -> App<'static, 'static> {
         .long("isolate-branches")
         .help("Isolate the execution of branches: a panic in a branch only makes the program panic if this branch is being logically executed")
         .required(false)
+    ).arg(Arg::with_name("hash-public-inputs")
+        .long("hash-public-inputs")
+        .help("Replace the public inputs of `main` by a single public SHA-256 commitment to their values, computed from the ABI when computing the witness")
+        .required(false)
+    ).arg(Arg::with_name("no-outputs")
+        .long("no-outputs")
+        .help("Drop the return values of `main` after checking the program, keeping only its assertions, so that the compiled program has no outputs")
+        .required(false)
+    ).arg(Arg::with_name("deny-trivial-asserts")
+        .long("deny-trivial-asserts")
+        .help("Fail compilation on equality assertions which always hold, such as `assert(x == x)`, instead of removing them with a warning")
+        .required(false)
+    ).arg(Arg::with_name("validate-ir")
+        .long("validate-ir")
+        .help("Check that the intermediate representation is in single-assignment form after each pass, which is always done in debug builds")
+        .required(false)
     ).arg(Arg::with_name("debug")
         .long("debug")
         .help("Include logs")
         .required(false)
+    ).arg(Arg::with_name("cache-dir")
+        .long("cache-dir")
+        .help("Directory in which to cache compiled programs, reused as long as no module in the import graph changes")
+        .value_name("PATH")
+        .takes_value(true)
+        .required(false)
+    ).arg(Arg::with_name("reduction-policy")
+        .long("reduction-policy")
+        .help("How to choose which operand of an overflowing unsigned integer operation to reduce")
+        .takes_value(true)
+        .required(false)
+        .possible_values(&["greedy", "minimize-decompositions"])
+        .default_value("greedy")
+    ).arg(Arg::with_name("div-by-zero")
+        .long("div-by-zero")
+        .help("What dividing a field element by zero evaluates to: an error during witness generation, or zero")
+        .takes_value(true)
+        .required(false)
+        .possible_values(&["error", "define-zero"])
+        .default_value("error")
+    ).arg(Arg::with_name("max-constraints")
+        .long("max-constraints")
+        .help("Abort compilation as soon as the program has more constraints than this, before optimization")
+        .value_name("COUNT")
+        .takes_value(true)
+        .required(false)
+    ).arg(Arg::with_name("max-call-depth")
+        .long("max-call-depth")
+        .help("Abort compilation when function calls are nested deeper than this, for example because of a recursion without base case")
+        .value_name("DEPTH")
+        .takes_value(true)
+        .required(false)
+    ).arg(Arg::with_name("propagation-budget")
+        .long("propagation-budget")
+        .help("Stop propagating constant arrays, structs and tuples after this many units of work, leaving them symbolic. Where the propagation stopped is reported as a warning")
+        .value_name("WORK")
+        .takes_value(true)
+        .required(false)
+    ).arg(Arg::with_name("passes")
+        .long("passes")
+        .help("Comma-separated list of the optimization passes to run, in order")
+        .value_name("PASSES")
+        .takes_value(true)
+        .required(false)
+        .default_value(DEFAULT_PIPELINE)
+    ).arg(Arg::with_name("reorder-variables")
+        .long("reorder-variables")
+        .help("Renumber the variables of the compiled program so that variables used by the same constraints are close. The permutation is stored in the program, and can be read with `zokrates inspect --variable-ordering`")
+        .required(false)
+    ).arg(Arg::with_name("public-layout")
+        .long("public-layout")
+        .help("Order of the public values expected by a verifier: `inputs-first`, `outputs-first`, or a comma-separated list of the names of the public inputs and outputs, the outputs being named `out` or `out_<index>`")
+        .value_name("LAYOUT")
+        .takes_value(true)
+        .required(false)
+        .conflicts_with("manifest")
+    ).arg(Arg::with_name("report")
+        .long("report")
+        .help("Path of a JSON report of the directives and embeds used by each source function, which is also printed")
+        .value_name("FILE")
+        .takes_value(true)
+        .required(false)
+    ).arg(Arg::with_name("debug-table")
+        .long("debug-table")
+        .help("Path of a debug table recording the source and the optimization history of each constraint, read by `zokrates explain`")
+        .value_name("FILE")
+        .takes_value(true)
+        .required(false)
+        .conflicts_with_all(&["cache-dir", "reorder-variables"])
+    ).arg(Arg::with_name("manifest")
+        .long("manifest")
+        .help("Path of a manifest recording the compiler version, the curve, the configuration, the hash of every source in the import graph and the hash of the constraint system, checked by `zokrates verify-build`")
+        .value_name("FILE")
+        .takes_value(true)
+        .required(false)
+        .conflicts_with_all(&["cache-dir", "reorder-variables"])
+    ).arg(Arg::with_name("minimization-report")
+        .long("minimization-report")
+        .help("Path of a JSON report comparing the constraint system before and after optimization, with the effect of each optimization pass, which is also printed with `--verbose`")
+        .value_name("FILE")
+        .takes_value(true)
+        .required(false)
+        .conflicts_with("cache-dir")
+    ).arg(Arg::with_name("stats")
+        .long("stats")
+        .help("Print the number of constraints emitted by each source function, before optimization")
+        .required(false)
+        .conflicts_with("cache-dir")
+    ).arg(Arg::with_name("dump-constraints")
+        .long("dump-constraints")
+        .help("Path of a CSV table of the constraints emitted by the flattener, before optimization, with the source function and location of each of them")
+        .value_name("FILE")
+        .takes_value(true)
+        .required(false)
+        .conflicts_with("cache-dir")
+    ).arg(Arg::with_name("estimate-only")
+        .long("estimate-only")
+        .help("Print bounds on the number of constraints and variables of the program and stop before flattening it, without writing any file")
+        .required(false)
 )
 }
 
 pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
-    let curve = CurveParameter::try_from(sub_matches.value_of("curve").unwrap())?;
-    match curve {
-        CurveParameter::Bn128 => cli_compile::<Bn128Field>(sub_matches),
-        CurveParameter::Bls12_377 => cli_compile::<Bls12_377Field>(sub_matches),
-        CurveParameter::Bls12_381 => cli_compile::<Bls12_381Field>(sub_matches),
-        CurveParameter::Bw6_761 => cli_compile::<Bw6_761Field>(sub_matches),
-    }
-}
-
-fn cli_compile<T: Field>(sub_matches: &ArgMatches) -> Result<(), String> {
-    println!("Compiling {}\n", sub_matches.value_of("input").unwrap());
-    let path = PathBuf::from(sub_matches.value_of("input").unwrap());
-    let bin_output_path = Path::new(sub_matches.value_of("output").unwrap());
-    let r1cs_output_path = Path::new(sub_matches.value_of("r1cs").unwrap());
-    let abi_spec_path = Path::new(sub_matches.value_of("abi-spec").unwrap());
-
-    log::debug!("Load entry point file {}", path.display());
-
-    let file = File::open(path.clone())
-        .map_err(|why| format!("Could not open {}: {}", path.display(), why))?;
-
-    let mut reader = BufReader::new(file);
-    let mut source = String::new();
-    reader.read_to_string(&mut source).unwrap();
-
-    let fmt_error = |e: &CompileError| {
-        let file = e.file().canonicalize().unwrap();
-        format!(
-            "{}:{}",
-            file.strip_prefix(std::env::current_dir().unwrap())
-                .unwrap_or(file.as_path())
-                .display(),
-            e.value()
-        )
-    };
+    let curves: Vec<&str> = sub_matches.values_of("curve").unwrap().collect();
 
     let stdlib_path = sub_matches.value_of("stdlib-path").unwrap();
     match Path::new(stdlib_path).exists() {
@@ -120,28 +233,101 @@ fn cli_compile<T: Field>(sub_matches: &ArgMatches) -> Result<(), String> {
         )),
     }?;
 
-    let config = CompileConfig::default()
-        .isolate_branches(sub_matches.is_present("isolate-branches"))
-        .debug(sub_matches.is_present("debug"));
+    let fs_resolver = FileSystemResolver::with_stdlib_root(stdlib_path);
+    // the imported modules are read once, even when compiling for several curves. Parsing depends on the field, so
+    // the program is parsed again for each curve
+    let resolver = SourceCache::new(&fs_resolver as &dyn Resolver<io::Error>);
 
-    let resolver = FileSystemResolver::with_stdlib_root(stdlib_path);
+    match curves.as_slice() {
+        [curve] => compile_for_curve(curve, None, sub_matches, &resolver),
+        curves => {
+            for (i, curve) in curves.iter().enumerate() {
+                if curves[..i].contains(curve) {
+                    return Err(format!("Curve {} is given more than once", curve));
+                }
+            }
 
-    log::debug!("Compile");
+            for curve in curves {
+                println!("Curve {}\n", curve);
+                compile_for_curve(curve, Some(curve), sub_matches, &resolver)?;
+                println!();
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// Compile for `curve`, inserting `suffix` in the names of the output files
+fn compile_for_curve(
+    curve: &str,
+    suffix: Option<&str>,
+    sub_matches: &ArgMatches,
+    resolver: &dyn Resolver<io::Error>,
+) -> Result<(), String> {
+    match CurveParameter::try_from(curve)? {
+        CurveParameter::Bn128 => cli_compile::<Bn128Field>(sub_matches, suffix, resolver),
+        CurveParameter::Bls12_377 => cli_compile::<Bls12_377Field>(sub_matches, suffix, resolver),
+        CurveParameter::Bls12_381 => cli_compile::<Bls12_381Field>(sub_matches, suffix, resolver),
+        CurveParameter::Bw6_761 => cli_compile::<Bw6_761Field>(sub_matches, suffix, resolver),
+        CurveParameter::Pallas => cli_compile::<PallasField>(sub_matches, suffix, resolver),
+        CurveParameter::Vesta => cli_compile::<VestaField>(sub_matches, suffix, resolver),
+    }
+}
+
+/// The path given for the output `name`, with `suffix` inserted before its extension if any, so
+/// that `abi.json` becomes `abi.bn128.json` and `out` becomes `out.bn128`
+fn output_path(sub_matches: &ArgMatches, name: &str, suffix: Option<&str>) -> Option<PathBuf> {
+    let path = Path::new(sub_matches.value_of(name)?);
+
+    Some(match suffix {
+        Some(suffix) => {
+            let mut file_name = path.file_stem().unwrap_or_default().to_os_string();
+            file_name.push(".");
+            file_name.push(suffix);
+            if let Some(extension) = path.extension() {
+                file_name.push(".");
+                file_name.push(extension);
+            }
+            path.with_file_name(file_name)
+        }
+        None => path.to_path_buf(),
+    })
+}
+
+fn cli_compile<T: Field>(
+    sub_matches: &ArgMatches,
+    suffix: Option<&str>,
+    resolver: &dyn Resolver<io::Error>,
+) -> Result<(), String> {
+    if let Some(entry_points) = sub_matches.values_of("entry-point") {
+        return cli_compile_container::<T>(entry_points.collect(), sub_matches, suffix, resolver);
+    }
+
+    if sub_matches.is_present("estimate-only") {
+        return cli_estimate::<T>(sub_matches, resolver);
+    }
+
+    println!("Compiling {}\n", sub_matches.value_of("input").unwrap());
+    let path = PathBuf::from(sub_matches.value_of("input").unwrap());
+    let bin_output_path = output_path(sub_matches, "output", suffix).unwrap();
+    let r1cs_output_path = output_path(sub_matches, "r1cs", suffix).unwrap();
+    let abi_spec_path = output_path(sub_matches, "abi-spec", suffix).unwrap();
 
     let arena = Arena::new();
 
-    let artifacts =
-        compile::<T, _>(source, path, Some(&resolver), config, &arena).map_err(|e| {
-            format!(
-                "Compilation failed:\n\n{}",
-                e.0.iter()
-                    .map(|e| fmt_error(e))
-                    .collect::<Vec<_>>()
-                    .join("\n\n")
-            )
-        })?;
+    let (program_flattened, abi) =
+        compile_program::<T>(path, sub_matches, suffix, resolver, &arena)?;
 
-    let (program_flattened, abi) = artifacts.into_inner();
+    let program_flattened = match sub_matches.is_present("reorder-variables") {
+        true => {
+            log::debug!("Reorder variables");
+            reorder(program_flattened)
+        }
+        false => program_flattened,
+    };
+
+    let program_flattened = apply_public_layout(program_flattened, &abi, sub_matches)?;
 
     // serialize flattened program and write to binary file
     log::debug!("Serialize program");
@@ -154,11 +340,22 @@ fn cli_compile<T: Field>(sub_matches: &ArgMatches) -> Result<(), String> {
     let mut bin_writer = BufWriter::new(bin_output_file);
     let mut r1cs_writer = BufWriter::new(r1cs_output_file);
 
-    let program_flattened = program_flattened.collect();
-
     write_r1cs(&mut r1cs_writer, program_flattened.clone()).unwrap();
 
-    match program_flattened.serialize(&mut bin_writer) {
+    let written = match sub_matches.value_of("output-format").unwrap() {
+        "text" => {
+            let constraint_count = program_flattened.constraint_count();
+            write!(bin_writer, "{}", TextDisplay(&program_flattened))
+                .and_then(|_| bin_writer.flush())
+                .map(|_| constraint_count)
+                .map_err(|e| e.to_string())
+        }
+        _ => program_flattened
+            .serialize(&mut bin_writer)
+            .map_err(|e| e.to_string()),
+    };
+
+    match written {
         Ok(constraint_count) => {
             // serialize ABI spec and write to JSON file
             log::debug!("Serialize ABI");
@@ -177,8 +374,357 @@ fn cli_compile<T: Field>(sub_matches: &ArgMatches) -> Result<(), String> {
         }
         Err(e) => {
             // something wrong happened, clean up
-            std::fs::remove_file(bin_output_path).unwrap();
+            std::fs::remove_file(&bin_output_path).unwrap();
+            Err(e)
+        }
+    }
+}
+
+fn cli_compile_container<T: Field>(
+    entry_points: Vec<&str>,
+    sub_matches: &ArgMatches,
+    suffix: Option<&str>,
+    resolver: &dyn Resolver<io::Error>,
+) -> Result<(), String> {
+    let bin_output_path = output_path(sub_matches, "output", suffix).unwrap();
+    let abi_spec_path = output_path(sub_matches, "abi-spec", suffix).unwrap();
+
+    let arena = Arena::new();
+
+    let mut programs = vec![];
+    let mut abis = BTreeMap::new();
+
+    for entry_point in entry_points {
+        let (name, path) = entry_point.split_once('=').ok_or_else(|| {
+            format!(
+                "Invalid entry point `{}`, expected `name=path`",
+                entry_point
+            )
+        })?;
+
+        println!("Compiling {} as `{}`\n", path, name);
+
+        let (program, abi) =
+            compile_program::<T>(PathBuf::from(path), sub_matches, suffix, resolver, &arena)?;
+        let program = apply_public_layout(program, &abi, sub_matches)?;
+
+        programs.push((name.to_string(), program));
+        abis.insert(name.to_string(), abi);
+    }
+
+    let names: Vec<_> = programs.iter().map(|(name, _)| name.clone()).collect();
+
+    log::debug!("Serialize container");
+    let bin_output_file = File::create(&bin_output_path)
+        .map_err(|why| format!("Could not create {}: {}", bin_output_path.display(), why))?;
+
+    match serialize_container(programs, BufWriter::new(bin_output_file)) {
+        Ok(constraint_counts) => {
+            // the ABI specification maps the name of each entry to its ABI
+            log::debug!("Serialize ABI");
+            let abi_spec_file = File::create(&abi_spec_path)
+                .map_err(|why| format!("Could not create {}: {}", abi_spec_path.display(), why))?;
+
+            to_writer_pretty(BufWriter::new(abi_spec_file), &abis)
+                .map_err(|_| "Unable to write data to file.".to_string())?;
+
+            println!("Compiled code written to '{}'", bin_output_path.display());
+
+            for (name, constraint_count) in names.iter().zip(constraint_counts) {
+                println!("Number of constraints of `{}`: {}", name, constraint_count);
+            }
+
+            Ok(())
+        }
+        Err(e) => {
+            // something wrong happened, clean up
+            std::fs::remove_file(&bin_output_path).unwrap();
             Err(e.to_string())
         }
     }
 }
+
+fn cli_estimate<T: Field>(
+    sub_matches: &ArgMatches,
+    resolver: &dyn Resolver<io::Error>,
+) -> Result<(), String> {
+    println!("Estimating {}\n", sub_matches.value_of("input").unwrap());
+    let path = PathBuf::from(sub_matches.value_of("input").unwrap());
+
+    let (source, config) = prepare(&path, sub_matches)?;
+
+    log::debug!("Estimate");
+
+    let estimate = estimate::<T, _>(source, path, Some(resolver), &config).map_err(fmt_errors)?;
+
+    print!("{}", estimate);
+
+    Ok(())
+}
+
+fn fmt_error(e: &CompileError) -> String {
+    let file = e.file().canonicalize().unwrap();
+    format!(
+        "{}:{}",
+        file.strip_prefix(std::env::current_dir().unwrap())
+            .unwrap_or(file.as_path())
+            .display(),
+        e.value()
+    )
+}
+
+pub(crate) fn fmt_errors(e: CompileErrors) -> String {
+    format!(
+        "Compilation failed:\n\n{}",
+        e.0.iter().map(fmt_error).collect::<Vec<_>>().join("\n\n")
+    )
+}
+
+/// Read the source at `path`, and build the configuration of the compilation from the arguments
+fn prepare(path: &Path, sub_matches: &ArgMatches) -> Result<(String, CompileConfig), String> {
+    log::debug!("Load entry point file {}", path.display());
+
+    let file =
+        File::open(path).map_err(|why| format!("Could not open {}: {}", path.display(), why))?;
+
+    let mut reader = BufReader::new(file);
+    let mut source = String::new();
+    reader.read_to_string(&mut source).unwrap();
+
+    let reduction_policy = match sub_matches.value_of("reduction-policy").unwrap() {
+        "minimize-decompositions" => ReductionPolicy::MinimizeDecompositions,
+        _ => ReductionPolicy::Greedy,
+    };
+
+    let div_by_zero = match sub_matches.value_of("div-by-zero").unwrap() {
+        "define-zero" => DivByZero::DefineZero,
+        _ => DivByZero::Error,
+    };
+
+    let max_constraints = sub_matches
+        .value_of("max-constraints")
+        .map(|count| {
+            count
+                .parse::<usize>()
+                .map_err(|why| format!("Invalid constraint budget {}: {}", count, why))
+        })
+        .transpose()?;
+
+    let max_call_depth = sub_matches
+        .value_of("max-call-depth")
+        .map(|depth| {
+            depth
+                .parse::<usize>()
+                .map_err(|why| format!("Invalid call depth {}: {}", depth, why))
+        })
+        .transpose()?;
+
+    let propagation_budget = sub_matches
+        .value_of("propagation-budget")
+        .map(|work| {
+            work.parse::<usize>()
+                .map_err(|why| format!("Invalid propagation budget {}: {}", work, why))
+        })
+        .transpose()?;
+
+    let pipeline = sub_matches
+        .value_of("passes")
+        .unwrap()
+        .parse::<Pipeline>()
+        .map_err(|why| format!("Invalid pipeline: {}", why))?;
+
+    let config = CompileConfig::default()
+        .isolate_branches(sub_matches.is_present("isolate-branches"))
+        .debug(sub_matches.is_present("debug"))
+        .reduction_policy(reduction_policy)
+        .div_by_zero(div_by_zero)
+        .max_constraints(max_constraints)
+        .max_call_depth(max_call_depth)
+        .propagation_budget(propagation_budget)
+        .hash_public_inputs(sub_matches.is_present("hash-public-inputs"))
+        .no_outputs(sub_matches.is_present("no-outputs"))
+        .deny_trivial_asserts(sub_matches.is_present("deny-trivial-asserts"))
+        .validate_ir(sub_matches.is_present("validate-ir"))
+        // the default pipeline is left implicit so that it does not change the keys of the build cache
+        .pipeline(Some(pipeline).filter(|p| *p != Pipeline::default()));
+
+    Ok((source, config))
+}
+
+/// Order the public values of `program` as requested with `--public-layout`, if at all
+fn apply_public_layout<'ast, T: Field>(
+    program: ir::Prog<'ast, T>,
+    abi: &Abi,
+    sub_matches: &ArgMatches,
+) -> Result<ir::Prog<'ast, T>, String> {
+    match sub_matches.value_of("public-layout") {
+        Some(layout) => {
+            let layout: PublicLayout = layout.parse()?;
+            program
+                .apply_public_layout(&layout, abi)
+                .map_err(|why| format!("Invalid public layout `{}`: {}", layout, why))
+        }
+        None => Ok(program),
+    }
+}
+
+fn compile_program<'ast, T: Field>(
+    path: PathBuf,
+    sub_matches: &ArgMatches,
+    suffix: Option<&str>,
+    resolver: &dyn Resolver<io::Error>,
+    arena: &'ast Arena<String>,
+) -> Result<(ir::Prog<'ast, T>, Abi), String> {
+    let (source, config) = prepare(&path, sub_matches)?;
+
+    let report_path = output_path(sub_matches, "report", suffix);
+    let table_path = output_path(sub_matches, "debug-table", suffix);
+    let manifest_path = output_path(sub_matches, "manifest", suffix);
+    let minimization_path = output_path(sub_matches, "minimization-report", suffix);
+    let stats = sub_matches.is_present("stats");
+    let dump_path = output_path(sub_matches, "dump-constraints", suffix);
+
+    let observed = report_path.is_some()
+        || table_path.is_some()
+        || manifest_path.is_some()
+        || minimization_path.is_some()
+        || stats
+        || dump_path.is_some();
+
+    if observed {
+        log::debug!("Compile with observers");
+
+        let dump = dump_path
+            .as_ref()
+            .map(|dump_path| {
+                File::create(dump_path)
+                    .map(|file| CsvSink::new(BufWriter::new(file)))
+                    .map_err(|why| format!("Could not create {}: {}", dump_path.display(), why))
+            })
+            .transpose()?;
+        let mut sink = (stats.then(ConstraintCounter::default), dump);
+        let observe_constraints = stats || dump_path.is_some();
+
+        let (artifacts, observations) = compile_observed::<T, _>(
+            source,
+            path,
+            Some(resolver),
+            config,
+            arena,
+            Observers {
+                debug_table: table_path.is_some(),
+                manifest: manifest_path.is_some(),
+                minimization_report: minimization_path.is_some(),
+                report: report_path.is_some(),
+                sink: observe_constraints.then(|| &mut sink as &mut dyn ConstraintSink<T>),
+            },
+        )
+        .map_err(fmt_errors)?;
+
+        if let (Some(report), Some(report_path)) = (observations.report, report_path) {
+            let report_file = File::create(&report_path)
+                .map_err(|why| format!("Could not create {}: {}", report_path.display(), why))?;
+            to_writer_pretty(BufWriter::new(report_file), &report).map_err(|e| e.to_string())?;
+
+            println!("{}", report);
+            println!("Report written to '{}'\n", report_path.display());
+        }
+
+        if let (Some(table), Some(table_path)) = (observations.debug_table, table_path) {
+            let table_file = File::create(&table_path)
+                .map_err(|why| format!("Could not create {}: {}", table_path.display(), why))?;
+            serde_cbor::to_writer(BufWriter::new(table_file), &table).map_err(|e| e.to_string())?;
+
+            println!("Debug table written to '{}'", table_path.display());
+        }
+
+        if let (Some(manifest), Some(manifest_path)) = (observations.manifest, manifest_path) {
+            let manifest_file = File::create(&manifest_path)
+                .map_err(|why| format!("Could not create {}: {}", manifest_path.display(), why))?;
+            // the paths are stored relative to the manifest, so that it can be checked from any directory
+            let manifest = manifest
+                .relative_to(&directory(&manifest_path))
+                .map_err(|why| format!("Could not locate the sources of the manifest: {}", why))?;
+            to_writer_pretty(BufWriter::new(manifest_file), &manifest)
+                .map_err(|e| e.to_string())?;
+
+            println!("Manifest written to '{}'", manifest_path.display());
+        }
+
+        if let (Some(minimization), Some(minimization_path)) =
+            (observations.minimization_report, minimization_path)
+        {
+            let report_file = File::create(&minimization_path).map_err(|why| {
+                format!("Could not create {}: {}", minimization_path.display(), why)
+            })?;
+            to_writer_pretty(BufWriter::new(report_file), &minimization)
+                .map_err(|e| e.to_string())?;
+
+            if sub_matches.is_present("verbose") {
+                println!("{}\n", minimization);
+            }
+            println!(
+                "Minimization report written to '{}'",
+                minimization_path.display()
+            );
+        }
+
+        let (counter, dump) = sink;
+
+        if let Some(counter) = counter {
+            println!("Constraints emitted before optimization:\n\n{}", counter);
+        }
+
+        if let (Some(dump), Some(dump_path)) = (dump, dump_path) {
+            dump.finish()
+                .map_err(|why| format!("Could not write {}: {}", dump_path.display(), why))?;
+
+            println!("Emitted constraints written to '{}'", dump_path.display());
+        }
+
+        report(artifacts.diagnostics());
+
+        return Ok(artifacts.into_inner());
+    }
+
+    log::debug!("Compile");
+
+    let artifacts = match sub_matches.value_of("cache-dir") {
+        Some(cache_dir) => {
+            let cache = BuildCache::new(cache_dir);
+            let res =
+                compile_with_cache::<T, _>(source, path, Some(resolver), config, arena, &cache)
+                    .map_err(fmt_errors)?;
+
+            log::debug!(
+                "Cache {}, stage timings: {:?}",
+                if res.cache_hit { "hit" } else { "miss" },
+                res.timings
+            );
+
+            res.artifacts
+        }
+        None => compile::<T, _>(source, path, Some(resolver), config, arena)
+            .map_err(fmt_errors)?
+            .collect(),
+    };
+
+    report(artifacts.diagnostics());
+
+    Ok(artifacts.into_inner())
+}
+
+/// The directory of the file at `path`
+pub fn directory(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// Print the warnings of a compilation
+pub(crate) fn report(diagnostics: &[Diagnostic]) {
+    for diagnostic in diagnostics {
+        println!("Warning: {}", diagnostic);
+    }
+}
@@ -1,17 +1,26 @@
 use crate::cli_constants;
+use crate::output::{output_format_arg, CliOutput};
+use crate::program::{
+    bindings_arg, entry_arg, read_abi, read_bindings, read_program, read_verification_key_bindings,
+};
 use clap::{App, Arg, ArgMatches, SubCommand};
-use serde_json::from_reader;
+use rand_0_8::rngs::StdRng;
+use rand_0_8::{RngCore, SeedableRng};
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::{stdin, BufReader, BufWriter, Read};
+use std::io::{stdin, BufReader, BufWriter, Read, Write};
 use std::path::Path;
+use std::time::{Duration, Instant};
 use zokrates_abi::Encode;
-use zokrates_ast::ir::{self, ProgEnum};
-use zokrates_ast::typed::{
-    abi::Abi,
-    types::{ConcreteSignature, ConcreteType, GTupleType},
-};
-use zokrates_circom::write_witness;
+use zokrates_ast::ir::{self, ProgEnum, ProgramHash, Variable, WitnessKey, WITNESS_NONCE_SIZE};
+use zokrates_ast::typed::abi::{Abi, NonceKey};
+use zokrates_ast::typed::types::{ConcreteSignature, ConcreteType, GTupleType};
+use zokrates_circom::{public_wires, write_witness};
 use zokrates_field::Field;
+use zokrates_interpreter::{
+    Coverage, CoverageCollector, Redaction, TraceWriter, REDACTION_SALT_SIZE,
+};
 
 pub fn subcommand() -> App<'static, 'static> {
     SubCommand::with_name("compute-witness")
@@ -24,6 +33,7 @@ pub fn subcommand() -> App<'static, 'static> {
             .takes_value(true)
             .required(false)
             .default_value(cli_constants::FLATTENED_CODE_DEFAULT_PATH)
+        ).arg(entry_arg()
         ).arg(Arg::with_name("abi-spec")
         .short("s")
         .long("abi-spec")
@@ -66,63 +76,294 @@ pub fn subcommand() -> App<'static, 'static> {
         .help("Read arguments from stdin")
         .conflicts_with("arguments")
         .required(false)
-    )
+    ).arg(Arg::with_name("defaults")
+        .long("defaults")
+        .help("Use the default value of each argument as specified in the ABI: zero for numbers, `false` for booleans")
+        .conflicts_with_all(&["arguments", "stdin"])
+        .required(false)
+    ).arg(Arg::with_name("set")
+        .long("set")
+        .help("Override a default argument, for example `--set foo.b.a=42`. The path is made of the name of the argument followed by the names of struct members and the indices of array or tuple elements")
+        .value_name("PATH=VALUE")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .requires("defaults")
+        .required(false)
+    ).arg(Arg::with_name("trace")
+        .long("trace")
+        .help("Path of the execution trace, written as one JSON object per executed statement")
+        .value_name("FILE")
+        .takes_value(true)
+        .required(false)
+    ).arg(Arg::with_name("trace-filter")
+        .long("trace-filter")
+        .help("Only trace writes to these variables, for example `--trace-filter ~out_0,_42`")
+        .value_name("VARIABLES")
+        .takes_value(true)
+        .use_delimiter(true)
+        .multiple(true)
+        .requires("trace")
+        .required(false)
+    ).arg(Arg::with_name("redact-private")
+        .long("redact-private")
+        .help("Replace the values derived from private arguments with salted hashes in the logs and the trace. The salt changes on every run. Enabled by default with `--trace`")
+        .conflicts_with("no-redact-private")
+        .required(false)
+    ).arg(Arg::with_name("no-redact-private")
+        .long("no-redact-private")
+        .help("Write the values derived from private arguments in cleartext in the trace")
+        .requires("trace")
+        .required(false)
+    ).arg(Arg::with_name("hints")
+        .long("hints")
+        .help("Path of a JSON object mapping variables computed by directives to precomputed values, for example `{\"_42\": \"7\"}`. The directives are skipped and the constraints are still checked")
+        .value_name("FILE")
+        .takes_value(true)
+        .conflicts_with("trace")
+        .required(false)
+    ).arg(Arg::with_name("coverage")
+        .long("coverage")
+        .help("Path of the assertion coverage file. The coverage of this run is added to the file if it exists, and can be rendered with `zokrates coverage report`")
+        .value_name("FILE")
+        .takes_value(true)
+        .conflicts_with_all(&["trace", "hints"])
+        .required(false)
+    ).arg(Arg::with_name("timeout")
+        .long("timeout")
+        .help("Abort the computation of the witness after this many seconds")
+        .value_name("SECONDS")
+        .takes_value(true)
+        .required(false)
+    ).arg(Arg::with_name("parallel")
+        .long("parallel")
+        .help("Solve the directives which do not depend on each other in parallel, one window of statements at a time")
+        .required(false)
+    ).arg(Arg::with_name("encrypt")
+        .long("encrypt")
+        .help("Encrypt the values of the private variables in the witness file with the key given by `--witness-key` or the `ZOKRATES_WITNESS_KEY` environment variable. The circom witness is not written")
+        .required(false)
+    ).arg(Arg::with_name("witness-key")
+        .long("witness-key")
+        .help("Path of the witness encryption key, written as 64 hexadecimal characters")
+        .value_name("FILE")
+        .takes_value(true)
+        .requires("encrypt")
+        .required(false)
+    ).arg(Arg::with_name("nonce-key")
+        .long("nonce-key")
+        .help("Path of the key the inputs marked `#[derive_nonce]` are derived with, written as 64 hexadecimal characters. Defaults to the `ZOKRATES_NONCE_KEY` environment variable")
+        .value_name("FILE")
+        .takes_value(true)
+        .required(false)
+    ).arg(Arg::with_name("metadata")
+        .long("metadata")
+        .help("Path of the witness metadata file, holding the values of the inputs marked `#[derive_nonce]`. Only written if the program has such inputs")
+        .value_name("FILE")
+        .takes_value(true)
+        .required(false)
+        .default_value(cli_constants::WITNESS_METADATA_DEFAULT_PATH)
+    ).arg(bindings_arg()
+    ).arg(Arg::with_name("verification-key-path")
+        .short("v")
+        .long("verification-key-path")
+        .help("Path of the verification key, which records the values of the inputs marked `#[bind_at_setup]` when they are not given by `--bindings`")
+        .value_name("FILE")
+        .takes_value(true)
+        .required(false)
+        .default_value(cli_constants::VERIFICATION_KEY_DEFAULT_PATH)
+    ).arg(output_format_arg())
+}
+
+/// The witness encryption key given by `--witness-key` or the `ZOKRATES_WITNESS_KEY` environment variable, if any
+pub fn witness_key(sub_matches: &ArgMatches) -> Result<Option<WitnessKey>, String> {
+    let hex = match sub_matches.value_of("witness-key") {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|why| format!("Could not read witness key {}: {}", path, why))?,
+        None => match std::env::var(cli_constants::WITNESS_KEY_ENV) {
+            Ok(hex) => hex,
+            Err(_) => return Ok(None),
+        },
+    };
+
+    WitnessKey::from_hex(&hex).map(Some)
+}
+
+// the key the nonces are derived with, given by `--nonce-key` or the `ZOKRATES_NONCE_KEY` environment variable
+fn nonce_key(sub_matches: &ArgMatches) -> Result<NonceKey, String> {
+    let hex = match sub_matches.value_of("nonce-key") {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|why| format!("Could not read nonce key {}: {}", path, why))?,
+        None => std::env::var(cli_constants::NONCE_KEY_ENV).map_err(|_| {
+            format!(
+                "Deriving the inputs marked `#[derive_nonce]` requires a key, given by `--nonce-key` or `{}`",
+                cli_constants::NONCE_KEY_ENV
+            )
+        })?,
+    };
+
+    NonceKey::from_hex(&hex)
+}
+
+/// The metadata of a witness, written next to it
+#[derive(Serialize)]
+struct WitnessMetadata {
+    /// The values of the inputs marked `#[derive_nonce]`, by name
+    derived_nonces: BTreeMap<String, String>,
+}
+
+// read the precomputed values of variables, given as a JSON object mapping variables to decimal values
+fn read_hints<T: Field>(path: &Path) -> Result<BTreeMap<Variable, T>, String> {
+    let file =
+        File::open(path).map_err(|why| format!("Could not open {}: {}", path.display(), why))?;
+
+    let hints: BTreeMap<String, String> = serde_json::from_reader(BufReader::new(file))
+        .map_err(|why| format!("Could not parse {}: {}", path.display(), why))?;
+
+    hints
+        .into_iter()
+        .map(|(variable, value)| {
+            let variable = Variable::try_from_human_readable(&variable)
+                .map_err(|_| format!("Invalid variable `{}` in hints", variable))?;
+            let value = T::try_from_dec_str(&value)
+                .map_err(|_| format!("Invalid value `{}` for {} in hints", value, variable))?;
+            Ok((variable, value))
+        })
+        .collect()
+}
+
+// the default values of the arguments given by the user, with the overrides given by `--set`
+fn default_arguments<T: Field>(
+    abi: &Abi,
+    sub_matches: &ArgMatches,
+) -> Result<zokrates_abi::Inputs<T>, String> {
+    let inputs = &abi.provided_inputs();
+    let mut values = zokrates_abi::defaults(inputs);
+
+    for assignment in sub_matches.values_of("set").into_iter().flatten() {
+        let (path, value) = assignment
+            .split_once('=')
+            .ok_or_else(|| format!("Expected `PATH=VALUE`, found `{}`", assignment))?;
+        abi.check_settable(path)?;
+        values
+            .set(inputs, path, value)
+            .map_err(|why| why.to_string())?;
+    }
+
+    Ok(zokrates_abi::Inputs::Abi(values))
 }
 
 pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
+    let mut output = CliOutput::new("compute-witness", sub_matches);
+    let res = compute_witness(sub_matches, &mut output);
+    output.finish(res)
+}
+
+fn compute_witness(sub_matches: &ArgMatches, output: &mut CliOutput) -> Result<(), String> {
     // read compiled program
     let path = Path::new(sub_matches.value_of("input").unwrap());
-    let file =
-        File::open(&path).map_err(|why| format!("Could not open {}: {}", path.display(), why))?;
+    let mut reader = read_program(path, sub_matches.value_of("entry"))?;
 
-    let mut reader = BufReader::new(file);
+    let (prog, hash) = ProgEnum::deserialize_with_hash(&mut reader)?;
 
-    match ProgEnum::deserialize(&mut reader)? {
-        ProgEnum::Bn128Program(p) => cli_compute(p, sub_matches),
-        ProgEnum::Bls12_377Program(p) => cli_compute(p, sub_matches),
-        ProgEnum::Bls12_381Program(p) => cli_compute(p, sub_matches),
-        ProgEnum::Bw6_761Program(p) => cli_compute(p, sub_matches),
+    match prog {
+        ProgEnum::Bn128Program(p) => cli_compute(p, hash, sub_matches, output),
+        ProgEnum::Bls12_377Program(p) => cli_compute(p, hash, sub_matches, output),
+        ProgEnum::Bls12_381Program(p) => cli_compute(p, hash, sub_matches, output),
+        ProgEnum::Bw6_761Program(p) => cli_compute(p, hash, sub_matches, output),
+        ProgEnum::PallasProgram(p) => cli_compute(p, hash, sub_matches, output),
+        ProgEnum::VestaProgram(p) => cli_compute(p, hash, sub_matches, output),
     }
 }
 
+// add the coverage of a run to the coverage file at `path`, creating it if needed
+fn save_coverage(path: &Path, coverage: Coverage) -> Result<(), String> {
+    let coverage = match path.exists() {
+        true => {
+            let file = File::open(path)
+                .map_err(|why| format!("Could not open {}: {}", path.display(), why))?;
+            let mut previous: Coverage = serde_json::from_reader(BufReader::new(file))
+                .map_err(|why| format!("Could not parse {}: {}", path.display(), why))?;
+            previous.merge(coverage)?;
+            previous
+        }
+        false => coverage,
+    };
+
+    let file = File::create(path)
+        .map_err(|why| format!("Could not create {}: {}", path.display(), why))?;
+
+    serde_json::to_writer_pretty(BufWriter::new(file), &coverage)
+        .map_err(|why| format!("Could not save coverage: {}", why))
+}
+
 fn cli_compute<'a, T: Field, I: Iterator<Item = ir::Statement<'a, T>>>(
     ir_prog: ir::ProgIterator<'a, T, I>,
+    hash: Option<ProgramHash>,
     sub_matches: &ArgMatches,
+    output: &mut CliOutput,
 ) -> Result<(), String> {
-    println!("Computing witness...");
+    output.message("Computing witness...");
 
     let verbose = sub_matches.is_present("verbose");
     let is_stdin = sub_matches.is_present("stdin");
-    let is_abi = sub_matches.is_present("abi");
+    let is_defaults = sub_matches.is_present("defaults");
+    let is_abi = sub_matches.is_present("abi") || is_defaults;
 
-    if !is_stdin && is_abi {
+    if !is_stdin && !is_defaults && is_abi {
         return Err("ABI input as inline argument is not supported. Please use `--stdin`.".into());
     }
 
-    let signature = match is_abi {
+    // raw arguments would include the derived nonces, so the ABI is required whenever the program has some. Without
+    // `--abi`, the ABI is only read to find them, and ignored if it cannot be read
+    if !is_abi {
+        let path = Path::new(sub_matches.value_of("abi-spec").unwrap());
+        let abi = File::open(&path)
+            .ok()
+            .and_then(|file| read_abi(BufReader::new(file), sub_matches.value_of("entry")).ok());
+
+        if let Some(abi) = abi.filter(|abi| !abi.derived_nonces.is_empty()) {
+            return Err(format!(
+                "`{}` is marked `#[derive_nonce]`, the arguments must be given with `--abi` so that it is derived",
+                abi.derived_nonces.join("`, `")
+            ));
+        }
+    }
+
+    let (signature, abi) = match is_abi {
         true => {
             let path = Path::new(sub_matches.value_of("abi-spec").unwrap());
             let file = File::open(&path)
                 .map_err(|why| format!("Could not open {}: {}", path.display(), why))?;
-            let mut reader = BufReader::new(file);
+            let reader = BufReader::new(file);
+
+            let abi = read_abi(reader, sub_matches.value_of("entry"))?;
 
-            let abi: Abi = from_reader(&mut reader).map_err(|why| why.to_string())?;
+            // the derived nonces are not given by the user
+            let signature = ConcreteSignature::new()
+                .inputs(abi.provided_inputs().into_iter().map(|i| i.ty).collect())
+                .output(abi.output.clone());
 
-            abi.signature()
+            (signature, Some(abi))
         }
-        false => ConcreteSignature::new()
-            .inputs(vec![ConcreteType::FieldElement; ir_prog.arguments.len()])
-            .output(ConcreteType::Tuple(GTupleType::new(
-                vec![ConcreteType::FieldElement; ir_prog.return_count],
-            ))),
+        false => (
+            ConcreteSignature::new()
+                .inputs(vec![ConcreteType::FieldElement; ir_prog.arguments.len()])
+                .output(ConcreteType::Tuple(GTupleType::new(
+                    vec![ConcreteType::FieldElement; ir_prog.return_count],
+                ))),
+            None,
+        ),
     };
 
     use zokrates_abi::Inputs;
 
     // get arguments
-    let arguments = match is_stdin {
+    let arguments = match (is_defaults, is_stdin) {
+        // take the default arguments
+        (true, _) => default_arguments(abi.as_ref().unwrap(), sub_matches),
         // take inline arguments
-        false => {
+        (false, false) => {
             let arguments = sub_matches.values_of("arguments");
             arguments
                 .map(|a| {
@@ -133,7 +374,7 @@ fn cli_compute<'a, T: Field, I: Iterator<Item = ir::Statement<'a, T>>>(
                 .map(Inputs::Raw)
         }
         // take stdin arguments
-        true => {
+        (false, true) => {
             let mut stdin = stdin();
             let mut input = String::new();
 
@@ -142,6 +383,10 @@ fn cli_compute<'a, T: Field, I: Iterator<Item = ir::Statement<'a, T>>>(
                     Ok(_) => {
                         use zokrates_abi::parse_strict;
 
+                        if let Ok(json) = serde_json::from_str(&input) {
+                            abi.as_ref().unwrap().check_provided(&json)?;
+                        }
+
                         parse_strict(&input, signature.inputs)
                             .map(Inputs::Abi)
                             .map_err(|why| why.to_string())
@@ -167,21 +412,184 @@ fn cli_compute<'a, T: Field, I: Iterator<Item = ir::Statement<'a, T>>>(
     }
     .map_err(|e| format!("Could not parse argument: {}", e))?;
 
-    let interpreter = zokrates_interpreter::Interpreter::default();
+    // the values bound at setup are given separately from the other arguments
+    let arguments = match &abi {
+        Some(abi) if !abi.bound_at_setup.is_empty() => {
+            // unless given, the values are the ones recorded in the verification key by `setup`
+            let bindings = match sub_matches.value_of("bindings") {
+                Some(path) => read_bindings(Path::new(path), abi)?,
+                None => read_verification_key_bindings(
+                    Path::new(sub_matches.value_of("verification-key-path").unwrap()),
+                    abi,
+                )?,
+            };
+            abi.insert_bound(bindings.values, arguments.encode())
+        }
+        _ => arguments.encode(),
+    };
+
+    // the nonces are derived from the values given by the user
+    let arguments = match &abi {
+        Some(abi) if !abi.derived_nonces.is_empty() => {
+            let (arguments, derived) = abi.derive_nonces(&nonce_key(sub_matches)?, arguments);
 
-    let public_inputs = ir_prog.public_inputs();
+            let metadata = WitnessMetadata {
+                derived_nonces: derived
+                    .into_iter()
+                    .map(|(name, value)| (name, value.to_dec_string()))
+                    .collect(),
+            };
 
-    let witness = interpreter
-        .execute_with_log_stream(ir_prog, &arguments.encode(), &mut std::io::stdout())
-        .map_err(|e| format!("Execution failed: {}", e))?;
+            let metadata_path = Path::new(sub_matches.value_of("metadata").unwrap());
+            let metadata_file = File::create(&metadata_path)
+                .map_err(|why| format!("Could not create {}: {}", metadata_path.display(), why))?;
+            serde_json::to_writer_pretty(BufWriter::new(metadata_file), &metadata)
+                .map_err(|why| format!("Could not save witness metadata: {}", why))?;
+
+            output.written("witness-metadata", "Witness metadata", metadata_path);
+
+            arguments
+        }
+        _ => arguments,
+    };
+
+    // the values of the inputs declared `field<N>` are checked before computing the witness
+    if let Some(abi) = &abi {
+        abi.check_bounds(&arguments)?;
+    }
+
+    // the commitment to hashed public inputs is computed from the values given by the user
+    let arguments = match &abi {
+        Some(abi) => abi.commit(arguments),
+        None => arguments,
+    };
+
+    let redact = sub_matches.is_present("redact-private")
+        || (sub_matches.is_present("trace") && !sub_matches.is_present("no-redact-private"));
+
+    // the values derived from private arguments are followed by the interpreter as the program is streamed
+    let redaction = redact.then(|| {
+        let mut salt = [0u8; REDACTION_SALT_SIZE];
+        StdRng::from_entropy().fill_bytes(&mut salt);
+        Redaction::new(&ir_prog.arguments, salt)
+    });
+
+    let interpreter = match sub_matches.value_of("timeout") {
+        Some(timeout) => zokrates_interpreter::Interpreter::default().with_timeout(
+            timeout
+                .parse::<f64>()
+                .ok()
+                .filter(|t| t.is_finite() && *t >= 0.0)
+                .map(Duration::from_secs_f64)
+                .ok_or_else(|| format!("Invalid timeout `{}`", timeout))?,
+        ),
+        None => zokrates_interpreter::Interpreter::default(),
+    };
+
+    let interpreter = match &redaction {
+        Some(redaction) => interpreter.with_redaction(redaction.clone()),
+        None => interpreter,
+    };
+
+    let interpreter = match sub_matches.is_present("parallel") {
+        true => interpreter.with_parallel_directives(),
+        false => interpreter,
+    };
+
+    let public_wires = public_wires(&ir_prog);
+
+    let mut constraint_count = 0;
+    let ir_prog = ir::ProgIterator::new(
+        ir_prog.arguments,
+        ir_prog.statements.inspect(|s| {
+            if let ir::Statement::Constraint(..) = s {
+                constraint_count += 1;
+            }
+        }),
+        ir_prog.return_count,
+    );
+    let start = Instant::now();
+    let mut log_stream = output.log_stream();
+
+    let witness = match sub_matches.value_of("trace") {
+        Some(trace_path) => {
+            let trace_path = Path::new(trace_path);
+            let trace_file = File::create(&trace_path)
+                .map_err(|why| format!("Could not create {}: {}", trace_path.display(), why))?;
+
+            let mut trace_writer = TraceWriter::new(BufWriter::new(trace_file));
+            if let Some(filter) = sub_matches.values_of("trace-filter") {
+                trace_writer = trace_writer.filter(filter);
+            }
+            if let Some(redaction) = redaction {
+                trace_writer = trace_writer.redact(redaction);
+            }
+
+            let witness = interpreter
+                .execute_with_observer(ir_prog, &arguments, &mut log_stream, &mut trace_writer)
+                .map_err(|e| format!("Execution failed: {}", e))?;
+
+            trace_writer
+                .into_inner()
+                .flush()
+                .map_err(|why| format!("Could not save trace: {}", why))?;
+
+            output.written("trace", "Execution trace", trace_path);
+
+            witness
+        }
+        None => match sub_matches.value_of("coverage") {
+            Some(coverage_path) => {
+                let coverage_path = Path::new(coverage_path);
+                let mut collector = CoverageCollector::new(hash);
+
+                let witness = interpreter.execute_with_observer(
+                    ir_prog,
+                    &arguments,
+                    &mut log_stream,
+                    &mut collector,
+                );
+
+                // failed runs are part of the coverage too
+                save_coverage(coverage_path, collector.into_coverage())?;
+
+                output.written("coverage", "Coverage", coverage_path);
+
+                witness.map_err(|e| format!("Execution failed: {}", e))?
+            }
+            None => match sub_matches.value_of("hints") {
+                Some(hints_path) => interpreter.execute_with_hints_and_log_stream(
+                    ir_prog,
+                    &arguments,
+                    read_hints(Path::new(hints_path))?,
+                    &mut log_stream,
+                ),
+                None => interpreter.execute_with_log_stream(ir_prog, &arguments, &mut log_stream),
+            }
+            .map_err(|e| format!("Execution failed: {}", e))?,
+        },
+    };
+
+    output.timing("execution", start.elapsed());
+    output.constraints(constraint_count);
 
     use zokrates_abi::Decode;
 
+    // the private return values are decoded too, as they are only revealed to the prover
+    let return_values = match &abi {
+        Some(abi) => abi.arrange_outputs(witness.return_values()),
+        None => witness.return_values(),
+    };
+
     let results_json_value: serde_json::Value =
-        zokrates_abi::Value::decode(witness.return_values(), *signature.output).into_serde_json();
+        zokrates_abi::Value::decode(return_values, *signature.output).into_serde_json();
 
     if verbose {
-        println!("\nWitness: \n{}\n", results_json_value);
+        match abi.as_ref().map(|abi| abi.no_outputs).unwrap_or(false) {
+            true => output
+                .message("\nThe program was compiled with `--no-outputs` and returns nothing\n"),
+            false => output.message(format!("\nWitness: \n{}\n", results_json_value)),
+        }
     }
 
     // write witness to file
@@ -191,6 +599,25 @@ fn cli_compute<'a, T: Field, I: Iterator<Item = ir::Statement<'a, T>>>(
 
     let writer = BufWriter::new(output_file);
 
+    if sub_matches.is_present("encrypt") {
+        let key = witness_key(sub_matches)?.ok_or_else(|| {
+            format!(
+                "Encrypting the witness requires a key, given by `--witness-key` or `{}`",
+                cli_constants::WITNESS_KEY_ENV
+            )
+        })?;
+
+        let mut nonce = [0u8; WITNESS_NONCE_SIZE];
+        StdRng::from_entropy().fill_bytes(&mut nonce);
+
+        witness
+            .write_encrypted(writer, &public_wires, &key, nonce)
+            .map_err(|why| format!("Could not save witness: {:?}", why))?;
+
+        output.written("witness", "Encrypted witness file", output_path);
+        return Ok(());
+    }
+
     witness
         .write(writer)
         .map_err(|why| format!("Could not save witness: {:?}", why))?;
@@ -202,9 +629,10 @@ fn cli_compute<'a, T: Field, I: Iterator<Item = ir::Statement<'a, T>>>(
 
     let mut writer = BufWriter::new(wtns_file);
 
-    write_witness(&mut writer, witness, public_inputs)
+    write_witness(&mut writer, witness, public_wires)
         .map_err(|why| format!("Could not save circom witness: {:?}", why))?;
 
-    println!("Witness file written to '{}'", output_path.display());
+    output.file("circom-witness", wtns_path);
+    output.written("witness", "Witness file", output_path);
     Ok(())
 }
@@ -0,0 +1,17 @@
+use clap::{App, AppSettings, ArgMatches, SubCommand};
+
+pub mod report;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("coverage")
+        .about("Assertion coverage collected by `compute-witness --coverage`")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommands(vec![report::subcommand().display_order(1)])
+}
+
+pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
+    match sub_matches.subcommand() {
+        ("report", Some(sub_matches)) => report::exec(sub_matches),
+        _ => unreachable!(),
+    }
+}
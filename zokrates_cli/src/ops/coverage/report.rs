@@ -0,0 +1,79 @@
+use crate::cli_constants::COVERAGE_DEFAULT_PATH;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use zokrates_interpreter::Coverage;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("report")
+        .about("Renders the assertion coverage per source line")
+        .arg(
+            Arg::with_name("input")
+                .short("i")
+                .long("input")
+                .help("Path of the coverage file")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(COVERAGE_DEFAULT_PATH),
+        )
+}
+
+pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
+    let path = Path::new(sub_matches.value_of("input").unwrap());
+    let file =
+        File::open(&path).map_err(|why| format!("Could not open {}: {}", path.display(), why))?;
+
+    let coverage: Coverage = serde_json::from_reader(BufReader::new(file))
+        .map_err(|why| format!("Could not parse {}: {}", path.display(), why))?;
+
+    let lines = coverage.lines();
+
+    println!(
+        "Assertion coverage over {} run{}",
+        coverage.runs,
+        if coverage.runs == 1 { "" } else { "s" }
+    );
+
+    let mut current_file = None;
+    let mut source: Vec<String> = vec![];
+
+    for ((file, line), line_coverage) in &lines {
+        if current_file != Some(file) {
+            println!("\n{}", file);
+            // the source is only shown if the file can still be found
+            source = std::fs::read_to_string(file)
+                .map(|s| s.lines().map(String::from).collect())
+                .unwrap_or_default();
+            current_file = Some(file);
+        }
+
+        println!(
+            "{:>6} | {} evaluated {}/{}, exercised {}/{} | {}",
+            line,
+            match line_coverage.exercised {
+                0 => "!",
+                _ => " ",
+            },
+            line_coverage.evaluated,
+            coverage.runs,
+            line_coverage.exercised,
+            coverage.runs,
+            line.checked_sub(1)
+                .and_then(|index| source.get(index))
+                .map(|s| s.trim())
+                .unwrap_or_default()
+        );
+    }
+
+    let exercised = lines.values().filter(|l| l.exercised > 0).count();
+
+    println!(
+        "\n{}/{} lines with assertions exercised",
+        exercised,
+        lines.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use crate::cli_constants::{DEBUG_TABLE_DEFAULT_PATH, FLATTENED_CODE_DEFAULT_PATH};
+use crate::program::read_program;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use zokrates_ast::common::Variable;
+use zokrates_ast::ir::{self, ProgEnum};
+use zokrates_core::provenance::DebugTable;
+use zokrates_field::Field;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("explain")
+        .about("Explains where a constraint of a compiled program comes from, using the debug table written by `compile --debug-table`")
+        .arg(
+            Arg::with_name("constraint")
+                .long("constraint")
+                .help("Index of the constraint to explain")
+                .value_name("N")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("input")
+                .short("i")
+                .long("input")
+                .help("Path of the binary")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(FLATTENED_CODE_DEFAULT_PATH),
+        )
+        .arg(
+            Arg::with_name("debug-table")
+                .long("debug-table")
+                .help("Path of the debug table")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(DEBUG_TABLE_DEFAULT_PATH),
+        )
+}
+
+pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
+    let index = sub_matches.value_of("constraint").unwrap();
+    let index = index
+        .parse::<usize>()
+        .map_err(|why| format!("Invalid constraint index {}: {}", index, why))?;
+
+    let table_path = Path::new(sub_matches.value_of("debug-table").unwrap());
+    let table_file = File::open(table_path)
+        .map_err(|why| format!("Could not open {}: {}", table_path.display(), why))?;
+    let table: DebugTable = serde_cbor::from_reader(BufReader::new(table_file))
+        .map_err(|why| format!("Could not read {}: {}", table_path.display(), why))?;
+
+    // read compiled program
+    let path = Path::new(sub_matches.value_of("input").unwrap());
+    let mut reader = read_program(path, None)?;
+
+    match ProgEnum::deserialize(&mut reader)? {
+        ProgEnum::Bn128Program(p) => cli_explain(p, table, index),
+        ProgEnum::Bls12_377Program(p) => cli_explain(p, table, index),
+        ProgEnum::Bls12_381Program(p) => cli_explain(p, table, index),
+        ProgEnum::Bw6_761Program(p) => cli_explain(p, table, index),
+        ProgEnum::PallasProgram(p) => cli_explain(p, table, index),
+        ProgEnum::VestaProgram(p) => cli_explain(p, table, index),
+    }
+}
+
+fn cli_explain<'a, T: Field, I: Iterator<Item = ir::Statement<'a, T>>>(
+    ir_prog: ir::ProgIterator<'a, T, I>,
+    table: DebugTable,
+    index: usize,
+) -> Result<(), String> {
+    let constraints: Vec<_> = ir_prog
+        .statements
+        .filter(|s| matches!(s, ir::Statement::Constraint(..)))
+        .collect();
+
+    // a table written for another program would explain the wrong constraints
+    if constraints.len() != table.constraints.len() {
+        return Err(format!(
+            "The debug table does not match the program: it explains {} constraints, the program has {}",
+            table.constraints.len(),
+            constraints.len()
+        ));
+    }
+
+    let (constraint, provenance) = constraints
+        .get(index)
+        .zip(table.constraints.get(index))
+        .ok_or_else(|| {
+            format!(
+                "Constraint #{} not found, the program has {} constraints",
+                index,
+                constraints.len()
+            )
+        })?;
+
+    println!("{:<11} #{}", "constraint:", index);
+    println!("  {}", constraint);
+    println!("{:<11} {}", "function:", provenance.function);
+    match &provenance.span {
+        Some(span) => println!("{:<11} {}", "source:", span),
+        None => println!("{:<11} -", "source:"),
+    }
+    println!("{:<11} #{}", "flattened:", provenance.origin);
+
+    println!("{:<11} {}", "passes:", provenance.history.len());
+    for breadcrumb in &provenance.history {
+        println!("  {}", breadcrumb);
+    }
+
+    let variables: BTreeSet<Variable> = match constraint {
+        ir::Statement::Constraint(quad, lin, _) => quad
+            .left
+            .0
+            .iter()
+            .chain(&quad.right.0)
+            .chain(&lin.0)
+            .map(|(v, _)| *v)
+            .filter(|v| *v != Variable::one())
+            .collect(),
+        _ => unreachable!(),
+    };
+
+    println!("{:<11} {}", "variables:", variables.len());
+    for v in variables {
+        match table.names.get(&v) {
+            Some(names) => println!("  {} {}", v, names.join(", ")),
+            None => println!("  {}", v),
+        }
+    }
+
+    Ok(())
+}
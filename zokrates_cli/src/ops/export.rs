@@ -0,0 +1,115 @@
+use crate::cli_constants::{FLATTENED_CODE_DEFAULT_PATH, ZKINTERFACE_DEFAULT_PATH};
+use crate::program::{entry_arg, read_program};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use zokrates_ast::ir::{self, zkinterface::write_zkinterface, ProgEnum};
+use zokrates_field::Field;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("export")
+        .about("Exports the constraint system, and optionally a witness, to a format understood by other tools")
+        .arg(
+            Arg::with_name("input")
+                .short("i")
+                .long("input")
+                .help("Path of the binary")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(FLATTENED_CODE_DEFAULT_PATH),
+        )
+        .arg(entry_arg())
+        .arg(
+            Arg::with_name("format")
+                .short("f")
+                .long("format")
+                .help("Format of the export")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .possible_values(&["zkinterface"])
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("witness")
+                .short("w")
+                .long("witness")
+                .help("Path of a witness file, whose values are exported along with the constraint system")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false),
+        )
+        .arg(
+            Arg::with_name("witness-key")
+                .long("witness-key")
+                .help("Path of the key of an encrypted witness, written as 64 hexadecimal characters. Defaults to the `ZOKRATES_WITNESS_KEY` environment variable")
+                .value_name("FILE")
+                .takes_value(true)
+                .requires("witness")
+                .required(false),
+        )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .help("Path of the output file")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(ZKINTERFACE_DEFAULT_PATH),
+        )
+}
+
+pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
+    // read compiled program
+    let path = Path::new(sub_matches.value_of("input").unwrap());
+    let mut reader = read_program(path, sub_matches.value_of("entry"))?;
+
+    match ProgEnum::deserialize(&mut reader)? {
+        ProgEnum::Bn128Program(p) => cli_export(p, sub_matches),
+        ProgEnum::Bls12_377Program(p) => cli_export(p, sub_matches),
+        ProgEnum::Bls12_381Program(p) => cli_export(p, sub_matches),
+        ProgEnum::Bw6_761Program(p) => cli_export(p, sub_matches),
+        ProgEnum::PallasProgram(p) => cli_export(p, sub_matches),
+        ProgEnum::VestaProgram(p) => cli_export(p, sub_matches),
+    }
+}
+
+fn cli_export<'a, T: Field, I: Iterator<Item = ir::Statement<'a, T>>>(
+    ir_prog: ir::ProgIterator<'a, T, I>,
+    sub_matches: &ArgMatches,
+) -> Result<(), String> {
+    println!("Exporting to zkInterface...");
+
+    let witness = match sub_matches.value_of("witness") {
+        Some(witness_path) => {
+            let witness_path = Path::new(witness_path);
+            let witness_file = File::open(&witness_path)
+                .map_err(|why| format!("Could not open {}: {}", witness_path.display(), why))?;
+
+            let key = crate::ops::compute_witness::witness_key(sub_matches)?;
+
+            Some(
+                ir::Witness::read_with_key(BufReader::new(witness_file), key.as_ref())
+                    .map_err(|why| format!("Could not load witness: {}", why))?,
+            )
+        }
+        None => None,
+    };
+
+    let ir_prog = ir_prog.collect();
+
+    let output_path = Path::new(sub_matches.value_of("output").unwrap());
+    let output_file = File::create(&output_path)
+        .map_err(|why| format!("Could not create {}: {}", output_path.display(), why))?;
+
+    write_zkinterface(BufWriter::new(output_file), &ir_prog, witness.as_ref())
+        .map_err(|why| format!("Could not export to zkInterface: {}", why))?;
+
+    println!(
+        "zkInterface messages written to '{}'",
+        output_path.display()
+    );
+    Ok(())
+}
@@ -1,9 +1,11 @@
 use crate::cli_constants;
+use crate::ops::verify::read_public_layout;
 use clap::{App, Arg, ArgMatches, SubCommand};
 use std::convert::TryFrom;
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Write};
 use std::path::Path;
+use zokrates_ast::typed::abi::Abi;
 use zokrates_common::helpers::{CurveParameter, SchemeParameter};
 use zokrates_field::Bn128Field;
 use zokrates_proof_systems::*;
@@ -31,6 +33,12 @@ pub fn subcommand() -> App<'static, 'static> {
                 .required(false)
                 .default_value(cli_constants::VERIFICATION_CONTRACT_DEFAULT_PATH),
         )
+        .arg(
+            Arg::with_name("report")
+                .long("report")
+                .help("Print an estimate of the gas spent verifying a proof with the exported contract")
+                .required(false),
+        )
 }
 
 pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
@@ -43,6 +51,16 @@ pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
     let vk: serde_json::Value = serde_json::from_reader(vk_reader)
         .map_err(|why| format!("Could not deserialize verification key: {}", why))?;
 
+    export(
+        vk,
+        Path::new(sub_matches.value_of("output").unwrap()),
+        sub_matches.is_present("report"),
+    )
+}
+
+/// Export the verifier of the verification key `vk` to `output_path`, printing an estimate of its gas costs if `report`
+/// is set. The curve and the scheme are read from the key
+pub fn export(vk: serde_json::Value, output_path: &Path, report: bool) -> Result<(), String> {
     // extract curve and scheme parameters
     let vk_curve = vk
         .get("curve")
@@ -60,30 +78,52 @@ pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
 
     match (curve_parameter, scheme_parameter) {
         (CurveParameter::Bn128, SchemeParameter::G16) => {
-            cli_export_verifier::<Bn128Field, G16>(sub_matches, vk)
+            cli_export_verifier::<Bn128Field, G16>(vk, output_path, report)
         }
         (CurveParameter::Bn128, SchemeParameter::GM17) => {
-            cli_export_verifier::<Bn128Field, GM17>(sub_matches, vk)
+            cli_export_verifier::<Bn128Field, GM17>(vk, output_path, report)
         }
         (CurveParameter::Bn128, SchemeParameter::MARLIN) => {
-            cli_export_verifier::<Bn128Field, Marlin>(sub_matches, vk)
+            cli_export_verifier::<Bn128Field, Marlin>(vk, output_path, report)
         }
         (curve_parameter, scheme_parameter) => Err(format!("Could not export verifier with given parameters (curve: {}, scheme: {}): not supported", curve_parameter, scheme_parameter))
     }
 }
 
 fn cli_export_verifier<T: SolidityCompatibleField, S: SolidityCompatibleScheme<T>>(
-    sub_matches: &ArgMatches,
     vk: serde_json::Value,
+    output_path: &Path,
+    report: bool,
 ) -> Result<(), String> {
     println!("Exporting verifier...");
 
+    // verification keys generated from an ABI specification carry the names of the public values
+    let abi: Option<Abi> = vk
+        .get("abi")
+        .cloned()
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|why| {
+            format!(
+                "Could not deserialize the ABI of the verification key: {}",
+                why
+            )
+        })?;
+
+    let public_layout = read_public_layout(&vk, "verification key")?;
+
     let vk = serde_json::from_value(vk).map_err(|why| format!("{}", why))?;
 
+    let report = report.then(|| VerifierCostReport::new::<T, S>(&vk, abi.as_ref()));
+
     let verifier = S::export_solidity_verifier(vk);
 
+    let verifier = match abi {
+        Some(abi) => add_abi_wrapper(verifier, &abi, &public_layout)?,
+        None => verifier,
+    };
+
     //write output file
-    let output_path = Path::new(sub_matches.value_of("output").unwrap());
     let output_file = File::create(&output_path)
         .map_err(|why| format!("Could not create {}: {}", output_path.display(), why))?;
 
@@ -94,5 +134,10 @@ fn cli_export_verifier<T: SolidityCompatibleField, S: SolidityCompatibleScheme<T
         .map_err(|_| "Failed writing output to file".to_string())?;
 
     println!("Verifier exported to '{}'", output_path.display());
+
+    if let Some(report) = report {
+        println!("{}", report);
+    }
+
     Ok(())
 }
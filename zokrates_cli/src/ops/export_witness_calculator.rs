@@ -0,0 +1,168 @@
+use crate::cli_constants;
+use crate::program::{entry_arg, read_abi, read_program};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::Path;
+use zokrates_ast::ir::ProgEnum;
+
+// the `zokrates_witness_calculator` crate compiled to WebAssembly by the build script, empty if it could not be built
+static TEMPLATE: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/witness_calculator.wasm"));
+
+// the custom section of the module holding the program, read by the loader
+const PROGRAM_SECTION: &str = "zokrates_program";
+
+// loads the exported module, which has no imports, and wraps its raw exports
+const LOADER: &str = r#"// Generated by `zokrates export-witness-calculator`
+//
+// const { initialize } = require("./witness_calculator.js");
+// const calculator = await initialize(fs.readFileSync("witness_calculator.wasm"));
+// const witness = calculator.computeWitness(["1", "2"]);
+
+async function initialize(source) {
+  const module = await WebAssembly.compile(source);
+  const instance = await WebAssembly.instantiate(module, {});
+  const { memory, alloc_inputs, load_program, compute_witness, free_result } = instance.exports;
+
+  // pass `bytes` to `f`, and return the data of its result or throw its error
+  const call = (f, bytes) => {
+    const ptr = alloc_inputs(bytes.length);
+    new Uint8Array(memory.buffer, ptr, bytes.length).set(bytes);
+
+    const result = f(ptr, bytes.length);
+    const header = new DataView(memory.buffer, result, 8);
+    const status = header.getUint32(0, true);
+    const length = header.getUint32(4, true);
+    const data = new Uint8Array(memory.buffer, result + 8, length).slice();
+    free_result(result);
+
+    if (status !== 0) {
+      throw new Error(new TextDecoder().decode(data));
+    }
+    return data;
+  };
+
+  const [program] = WebAssembly.Module.customSections(module, "zokrates_program");
+  call(load_program, new Uint8Array(program));
+
+  return {
+    // compute the witness for the ABI-encoded `inputs`, returned in the format of `zokrates compute-witness`
+    computeWitness(inputs) {
+      return call(compute_witness, new TextEncoder().encode(JSON.stringify(inputs)));
+    },
+  };
+}
+
+module.exports = { initialize };
+"#;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("export-witness-calculator")
+        .about("Exports a standalone WebAssembly module computing witnesses for a compiled program")
+        .arg(
+            Arg::with_name("input")
+                .short("i")
+                .long("input")
+                .help("Path of the binary")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(cli_constants::FLATTENED_CODE_DEFAULT_PATH),
+        )
+        .arg(entry_arg())
+        .arg(
+            Arg::with_name("abi-spec")
+                .short("s")
+                .long("abi-spec")
+                .help("Path of the ABI specification")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(cli_constants::ABI_SPEC_DEFAULT_PATH),
+        )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .help("Path of the output module. A JavaScript loader is written next to it, with the `.js` extension")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(cli_constants::WITNESS_CALCULATOR_DEFAULT_PATH),
+        )
+}
+
+// the unsigned LEB128 encoding of `n`, used for sizes in WebAssembly modules
+fn leb128(mut n: usize) -> Vec<u8> {
+    let mut bytes = vec![];
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            bytes.push(byte);
+            return bytes;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+// a custom section named `name`, which can be appended to a module as custom sections may appear anywhere
+fn custom_section(name: &str, payload: &[u8]) -> Vec<u8> {
+    let mut content = leb128(name.len());
+    content.extend(name.as_bytes());
+    content.extend(payload);
+
+    let mut section = vec![0];
+    section.extend(leb128(content.len()));
+    section.extend(content);
+    section
+}
+
+pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
+    let entry = sub_matches.value_of("entry");
+
+    // read compiled program
+    let path = Path::new(sub_matches.value_of("input").unwrap());
+    let mut program = vec![];
+    read_program(path, entry)?
+        .read_to_end(&mut program)
+        .map_err(|why| format!("Could not read {}: {}", path.display(), why))?;
+
+    // make sure the program can be loaded before exporting the module
+    ProgEnum::deserialize(&program[..])?;
+
+    // read the ABI specification, extracting the entry of a container
+    let abi_path = Path::new(sub_matches.value_of("abi-spec").unwrap());
+    let abi_file = File::open(&abi_path)
+        .map_err(|why| format!("Could not open {}: {}", abi_path.display(), why))?;
+    let abi = read_abi(BufReader::new(abi_file), entry)?;
+
+    if TEMPLATE.is_empty() {
+        return Err("This binary was built without the witness calculator template. Build it with the `wasm32-unknown-unknown` target installed, or set `ZOKRATES_WITNESS_CALCULATOR_TEMPLATE` to a prebuilt template".to_string());
+    }
+
+    // the program section holds the length of the program, the program and the ABI specification
+    let abi = serde_json::to_vec(&abi).unwrap();
+    let mut payload = (program.len() as u32).to_le_bytes().to_vec();
+    payload.extend(program);
+    payload.extend(abi);
+
+    let mut module = TEMPLATE.to_vec();
+    module.extend(custom_section(PROGRAM_SECTION, &payload));
+
+    let output_path = Path::new(sub_matches.value_of("output").unwrap());
+    fs::write(output_path, module)
+        .map_err(|why| format!("Could not create {}: {}", output_path.display(), why))?;
+
+    let loader_path = output_path.with_extension("js");
+    fs::write(&loader_path, LOADER)
+        .map_err(|why| format!("Could not create {}: {}", loader_path.display(), why))?;
+
+    println!(
+        "Witness calculator exported to '{}', loader written to '{}'",
+        output_path.display(),
+        loader_path.display()
+    );
+
+    Ok(())
+}
@@ -1,14 +1,20 @@
 use crate::cli_constants;
+use crate::output::{output_format_arg, CliOutput};
+use crate::program::{
+    bindings_arg, check_hash, entry_arg, force_arg, read_abi, read_program, resolve_bindings,
+    witness_bindings,
+};
 use clap::{App, Arg, ArgMatches, SubCommand};
 use rand_0_8::rngs::StdRng;
 use rand_0_8::SeedableRng;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fs::File;
-use std::io::{BufReader, Read, Write};
+use std::io::{BufReader, Write};
 use std::path::Path;
 #[cfg(feature = "ark")]
 use zokrates_ark::Ark;
-use zokrates_ast::ir::{self, ProgEnum};
+use zokrates_ast::ir::{self, ProgEnum, ProgramHash};
 #[cfg(feature = "bellman")]
 use zokrates_bellman::Bellman;
 use zokrates_common::constants;
@@ -21,6 +27,8 @@ use zokrates_proof_systems::*;
 pub fn subcommand() -> App<'static, 'static> {
     SubCommand::with_name("generate-proof")
         .about("Calculates a proof for a given constraint system and witness")
+        .arg(entry_arg())
+        .arg(force_arg())
         .arg(
             Arg::with_name("witness")
                 .short("w")
@@ -31,6 +39,14 @@ pub fn subcommand() -> App<'static, 'static> {
                 .required(false)
                 .default_value(cli_constants::WITNESS_DEFAULT_PATH),
         )
+        .arg(
+            Arg::with_name("witness-key")
+                .long("witness-key")
+                .help("Path of the key of an encrypted witness, written as 64 hexadecimal characters. Defaults to the `ZOKRATES_WITNESS_KEY` environment variable")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false),
+        )
         .arg(
             Arg::with_name("proving-key-path")
                 .short("p")
@@ -86,63 +102,107 @@ pub fn subcommand() -> App<'static, 'static> {
             Arg::with_name("entropy")
                 .short("e")
                 .long("entropy")
-                .help("User provided randomness")
+                .help("User provided randomness. The proof only depends on it, so that proofs can be compared across runs, which is insecure and requires `--insecure`")
                 .takes_value(true)
+                .required(false)
+                .requires("insecure"),
+        )
+        .arg(
+            Arg::with_name("insecure")
+                .long("insecure")
+                .help("Acknowledge that a proof generated with `--entropy` can leak information on the witness")
                 .required(false),
         )
+        .arg(bindings_arg())
+        .arg(
+            Arg::with_name("abi-spec")
+                .long("abi-spec")
+                .help("Path of the ABI specification, used to locate the inputs marked `#[bind_at_setup]`")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(cli_constants::ABI_SPEC_DEFAULT_PATH),
+        )
+        .arg(output_format_arg())
 }
 
 pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
-    let program_path = Path::new(sub_matches.value_of("input").unwrap());
-    let program_file = File::open(&program_path)
-        .map_err(|why| format!("Could not open {}: {}", program_path.display(), why))?;
-
-    let mut reader = BufReader::new(program_file);
-    let prog = ProgEnum::deserialize(&mut reader)?;
-
-    let curve_parameter = CurveParameter::try_from(prog.curve())?;
+    let mut output = CliOutput::new("generate-proof", sub_matches);
+    let res = generate_proof(sub_matches, &mut output);
+    output.finish(res)
+}
 
-    let backend_parameter = BackendParameter::try_from(sub_matches.value_of("backend").unwrap())?;
-    let scheme_parameter =
-        SchemeParameter::try_from(sub_matches.value_of("proving-scheme").unwrap())?;
+fn generate_proof(sub_matches: &ArgMatches, output: &mut CliOutput) -> Result<(), String> {
+    let program_path = Path::new(sub_matches.value_of("input").unwrap());
+    let mut reader = read_program(program_path, sub_matches.value_of("entry"))?;
+    let (prog, hash) = ProgEnum::deserialize_with_hash(&mut reader)?;
 
-    let parameters = Parameters(backend_parameter, curve_parameter, scheme_parameter);
+    let parameters = Parameters::try_from((
+        sub_matches.value_of("backend").unwrap(),
+        prog.curve(),
+        sub_matches.value_of("proving-scheme").unwrap(),
+    ))?;
 
-    match parameters {
+    match &parameters {
         #[cfg(feature = "bellman")]
         Parameters(BackendParameter::Bellman, _, SchemeParameter::G16) => match prog {
-            ProgEnum::Bn128Program(p) => cli_generate_proof::<_, _, G16, Bellman>(p, sub_matches),
+            ProgEnum::Bn128Program(p) => {
+                cli_generate_proof::<_, _, G16, Bellman>(p, hash, sub_matches, output)
+            }
             ProgEnum::Bls12_381Program(p) => {
-                cli_generate_proof::<_, _, G16, Bellman>(p, sub_matches)
+                cli_generate_proof::<_, _, G16, Bellman>(p, hash, sub_matches, output)
             }
-            _ => unreachable!(),
+            _ => Err(parameters.unsupported_curve()),
         },
         #[cfg(feature = "ark")]
         Parameters(BackendParameter::Ark, _, SchemeParameter::G16) => match prog {
-            ProgEnum::Bn128Program(p) => cli_generate_proof::<_, _, G16, Ark>(p, sub_matches),
-            ProgEnum::Bls12_381Program(p) => cli_generate_proof::<_, _, G16, Ark>(p, sub_matches),
-            ProgEnum::Bls12_377Program(p) => cli_generate_proof::<_, _, G16, Ark>(p, sub_matches),
-            ProgEnum::Bw6_761Program(p) => cli_generate_proof::<_, _, G16, Ark>(p, sub_matches),
+            ProgEnum::Bn128Program(p) => {
+                cli_generate_proof::<_, _, G16, Ark>(p, hash, sub_matches, output)
+            }
+            ProgEnum::Bls12_381Program(p) => {
+                cli_generate_proof::<_, _, G16, Ark>(p, hash, sub_matches, output)
+            }
+            ProgEnum::Bls12_377Program(p) => {
+                cli_generate_proof::<_, _, G16, Ark>(p, hash, sub_matches, output)
+            }
+            ProgEnum::Bw6_761Program(p) => {
+                cli_generate_proof::<_, _, G16, Ark>(p, hash, sub_matches, output)
+            }
+            _ => Err(parameters.unsupported_curve()),
         },
         #[cfg(feature = "ark")]
         Parameters(BackendParameter::Ark, _, SchemeParameter::GM17) => match prog {
-            ProgEnum::Bn128Program(p) => cli_generate_proof::<_, _, GM17, Ark>(p, sub_matches),
-            ProgEnum::Bls12_381Program(p) => cli_generate_proof::<_, _, GM17, Ark>(p, sub_matches),
-            ProgEnum::Bls12_377Program(p) => cli_generate_proof::<_, _, GM17, Ark>(p, sub_matches),
-            ProgEnum::Bw6_761Program(p) => cli_generate_proof::<_, _, GM17, Ark>(p, sub_matches),
+            ProgEnum::Bn128Program(p) => {
+                cli_generate_proof::<_, _, GM17, Ark>(p, hash, sub_matches, output)
+            }
+            ProgEnum::Bls12_381Program(p) => {
+                cli_generate_proof::<_, _, GM17, Ark>(p, hash, sub_matches, output)
+            }
+            ProgEnum::Bls12_377Program(p) => {
+                cli_generate_proof::<_, _, GM17, Ark>(p, hash, sub_matches, output)
+            }
+            ProgEnum::Bw6_761Program(p) => {
+                cli_generate_proof::<_, _, GM17, Ark>(p, hash, sub_matches, output)
+            }
+            _ => Err(parameters.unsupported_curve()),
         },
         #[cfg(feature = "ark")]
         Parameters(BackendParameter::Ark, _, SchemeParameter::MARLIN) => match prog {
-            ProgEnum::Bn128Program(p) => cli_generate_proof::<_, _, Marlin, Ark>(p, sub_matches),
+            ProgEnum::Bn128Program(p) => {
+                cli_generate_proof::<_, _, Marlin, Ark>(p, hash, sub_matches, output)
+            }
             ProgEnum::Bls12_381Program(p) => {
-                cli_generate_proof::<_, _, Marlin, Ark>(p, sub_matches)
+                cli_generate_proof::<_, _, Marlin, Ark>(p, hash, sub_matches, output)
             }
             ProgEnum::Bls12_377Program(p) => {
-                cli_generate_proof::<_, _, Marlin, Ark>(p, sub_matches)
+                cli_generate_proof::<_, _, Marlin, Ark>(p, hash, sub_matches, output)
+            }
+            ProgEnum::Bw6_761Program(p) => {
+                cli_generate_proof::<_, _, Marlin, Ark>(p, hash, sub_matches, output)
             }
-            ProgEnum::Bw6_761Program(p) => cli_generate_proof::<_, _, Marlin, Ark>(p, sub_matches),
+            _ => Err(parameters.unsupported_curve()),
         },
-        _ => unreachable!(),
+        _ => Err(parameters.unsupported_curve()),
     }
 }
 
@@ -154,17 +214,21 @@ fn cli_generate_proof<
     B: Backend<T, S>,
 >(
     program: ir::ProgIterator<'a, T, I>,
+    hash: Option<ProgramHash>,
     sub_matches: &ArgMatches,
+    output: &mut CliOutput,
 ) -> Result<(), String> {
-    println!("Generating proof...");
+    output.message("Generating proof...");
 
     // deserialize witness
     let witness_path = Path::new(sub_matches.value_of("witness").unwrap());
     let witness_file = File::open(&witness_path)
         .map_err(|why| format!("Could not open {}: {}", witness_path.display(), why))?;
 
-    let witness = ir::Witness::read(witness_file)
-        .map_err(|why| format!("Could not load witness: {:?}", why))?;
+    let key = crate::ops::compute_witness::witness_key(sub_matches)?;
+
+    let witness = ir::Witness::read_with_key(BufReader::new(witness_file), key.as_ref())
+        .map_err(|why| format!("Could not load witness: {}", why))?;
 
     let pk_path = Path::new(sub_matches.value_of("proving-key-path").unwrap());
     let proof_path = Path::new(sub_matches.value_of("proof-path").unwrap());
@@ -172,30 +236,106 @@ fn cli_generate_proof<
     let pk_file = File::open(&pk_path)
         .map_err(|why| format!("Could not open {}: {}", pk_path.display(), why))?;
 
-    let mut pk: Vec<u8> = Vec::new();
-    let mut pk_reader = BufReader::new(pk_file);
-    pk_reader
-        .read_to_end(&mut pk)
+    let (pk, pk_hash) = read_proving_key(BufReader::new(pk_file))
         .map_err(|why| format!("Could not read {}: {}", pk_path.display(), why))?;
 
-    let mut rng = sub_matches
-        .value_of("entropy")
-        .map(get_rng_from_entropy)
-        .unwrap_or_else(StdRng::from_entropy);
+    // the ABI specification locates the inputs bound at setup, whose values are read from `--bindings` or from the
+    // witness. Without `--bindings`, a missing specification leaves the program unbound
+    let abi_path = Path::new(sub_matches.value_of("abi-spec").unwrap());
+    let abi = match File::open(&abi_path) {
+        Ok(file) => read_abi(BufReader::new(file), sub_matches.value_of("entry")).map(Some),
+        Err(why) => Err(format!("Could not open {}: {}", abi_path.display(), why)),
+    };
+    let abi = match sub_matches.is_present("bindings") {
+        true => abi?,
+        false => abi.ok().flatten(),
+    };
+    let bound = match (sub_matches.value_of("bindings"), &abi) {
+        (Some(path), abi) => resolve_bindings::<T>(Some(path), abi.as_ref(), &program.arguments)?.0,
+        (None, Some(abi)) => witness_bindings(&program.arguments, abi, &witness)?,
+        (None, None) => HashMap::new(),
+    };
+
+    let mut rng = match sub_matches.value_of("entropy") {
+        Some(entropy) => {
+            output.warning(String::from(
+                "The proof is generated from the given entropy only, it must not be used outside of tests",
+            ));
+            get_rng_from_entropy(entropy)
+        }
+        None => StdRng::from_entropy(),
+    };
+
+    // the keys of a program with bound inputs are tagged with the hash of the bound program, which is kept in memory
+    // so that the hash is checked before proving
+    let input = sub_matches.value_of("input").unwrap();
+    let (program, hash, source) = match bound.is_empty() {
+        true => (
+            ir::ProgIterator::new(
+                program.arguments,
+                Box::new(program.statements) as Box<dyn Iterator<Item = _> + '_>,
+                program.return_count,
+            )
+            .with_public_layout(program.public_layout),
+            hash,
+            format!("the program `{}`", input),
+        ),
+        false => {
+            let program = program.bind(bound).collect();
+            let hash = program.hash();
+            (
+                ir::ProgIterator::new(
+                    program.arguments,
+                    Box::new(program.statements.into_iter()) as Box<dyn Iterator<Item = _> + '_>,
+                    program.return_count,
+                )
+                .with_public_layout(program.public_layout),
+                Some(hash),
+                format!("the program `{}` with its inputs bound", input),
+            )
+        }
+    };
+    check_hash(
+        (hash, &source),
+        (pk_hash, &format!("the proving key `{}`", pk_path.display())),
+        sub_matches.is_present("force"),
+    )?;
+
+    let public_layout = program.public_layout.clone();
+    let mut constraint_count = 0;
+    let program = ir::ProgIterator::new(
+        program.arguments,
+        program.statements.inspect(|s| {
+            if let ir::Statement::Constraint(..) = s {
+                constraint_count += 1;
+            }
+        }),
+        program.return_count,
+    )
+    .with_public_layout(program.public_layout);
+
+    let proof = output.time("proving", || {
+        B::generate_proof(program, witness, pk, &mut rng)
+    });
+    output.constraints(constraint_count);
 
-    let proof = B::generate_proof(program, witness, pk, &mut rng);
-    let mut proof_file = File::create(proof_path).unwrap();
+    let mut proof_file = File::create(proof_path)
+        .map_err(|why| format!("Could not create {}: {}", proof_path.display(), why))?;
 
-    let proof =
-        serde_json::to_string_pretty(&TaggedProof::<T, S>::new(proof.proof, proof.inputs)).unwrap();
+    let proof = serde_json::to_string_pretty(
+        &TaggedProof::<T, S>::new(proof.proof, proof.inputs)
+            .with_hash(pk_hash.or(hash))
+            .with_public_layout(public_layout),
+    )
+    .unwrap();
     proof_file
         .write(proof.as_bytes())
         .map_err(|why| format!("Could not write to {}: {}", proof_path.display(), why))?;
 
     if sub_matches.is_present("verbose") {
-        println!("Proof:\n{}", proof);
+        output.message(format!("Proof:\n{}", proof));
     }
 
-    println!("Proof written to '{}'", proof_path.display());
+    output.written("proof", "Proof", proof_path);
     Ok(())
 }
@@ -0,0 +1,105 @@
+use crate::cli_constants;
+use crate::project::ProjectManifest;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::path::{Path, PathBuf};
+
+/// A starting point for a project: a program, with arguments it accepts
+struct Template {
+    name: &'static str,
+    program: &'static str,
+    inputs: &'static str,
+}
+
+const TEMPLATES: &[Template] = &[
+    Template {
+        name: "hash-preimage",
+        program: include_str!("../../templates/hash-preimage/main.zok"),
+        inputs: include_str!("../../templates/hash-preimage/inputs.json"),
+    },
+    Template {
+        name: "merkle-membership",
+        program: include_str!("../../templates/merkle-membership/main.zok"),
+        inputs: include_str!("../../templates/merkle-membership/inputs.json"),
+    },
+    Template {
+        name: "range-proof",
+        program: include_str!("../../templates/range-proof/main.zok"),
+        inputs: include_str!("../../templates/range-proof/inputs.json"),
+    },
+];
+
+const INPUTS_DEFAULT_PATH: &str = "inputs.json";
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("init")
+        .about("Creates a project from a template, with a program, its inputs and a manifest for `zokrates build`")
+        .arg(
+            Arg::with_name("template")
+                .help("Template of the project")
+                .value_name("TEMPLATE")
+                .required(true)
+                .possible_values(&["hash-preimage", "merkle-membership", "range-proof"])
+                .index(1),
+        )
+        .arg(
+            Arg::with_name("path")
+                .long("path")
+                .help("Directory of the project, named after the template by default. It must not exist or be empty")
+                .value_name("DIR")
+                .takes_value(true)
+                .required(false),
+        )
+}
+
+pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
+    let name = sub_matches.value_of("template").unwrap();
+    let template = TEMPLATES.iter().find(|t| t.name == name).unwrap();
+
+    let dir = sub_matches
+        .value_of("path")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(name));
+
+    if dir.exists()
+        && dir
+            .read_dir()
+            .map_err(|why| format!("Could not read {}: {}", dir.display(), why))?
+            .next()
+            .is_some()
+    {
+        return Err(format!(
+            "Could not create the project in {}: the directory is not empty",
+            dir.display()
+        ));
+    }
+
+    let manifest = ProjectManifest {
+        inputs: Some(PathBuf::from(INPUTS_DEFAULT_PATH)),
+        ..ProjectManifest::default()
+    };
+
+    let write = |path: &Path, contents: &str| {
+        std::fs::write(dir.join(path), contents)
+            .map_err(|why| format!("Could not write {}: {}", dir.join(path).display(), why))
+    };
+
+    std::fs::create_dir_all(&dir)
+        .map_err(|why| format!("Could not create {}: {}", dir.display(), why))?;
+    write(&manifest.entry, template.program)?;
+    write(Path::new(INPUTS_DEFAULT_PATH), template.inputs)?;
+    write(
+        Path::new(cli_constants::PROJECT_MANIFEST_DEFAULT_PATH),
+        &manifest.to_toml(),
+    )?;
+
+    println!("Created a `{}` project in {}", name, dir.display());
+    println!(
+        "Build it with `zokrates build --manifest {}`, then compute a witness for its inputs with `zokrates compute-witness -i {} -s {} --abi --stdin < {}`",
+        dir.join(cli_constants::PROJECT_MANIFEST_DEFAULT_PATH).display(),
+        dir.join(&manifest.out).join(cli_constants::FLATTENED_CODE_DEFAULT_PATH).display(),
+        dir.join(&manifest.out).join(cli_constants::ABI_SPEC_DEFAULT_PATH).display(),
+        dir.join(INPUTS_DEFAULT_PATH).display()
+    );
+
+    Ok(())
+}
@@ -1,14 +1,22 @@
-use crate::cli_constants::FLATTENED_CODE_DEFAULT_PATH;
+use crate::cli_constants::{self, ABI_SPEC_DEFAULT_PATH, FLATTENED_CODE_DEFAULT_PATH};
+use crate::program::{entry_arg, read_abi, read_program};
 use clap::{App, Arg, ArgMatches, SubCommand};
+use rand_0_8::random;
 use std::fs::File;
-use std::io::{BufReader, BufWriter, Write};
+use std::io::{BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
-use zokrates_ast::ir::{self, ProgEnum};
-use zokrates_field::Field;
+use zokrates_ast::ir::{
+    self, fuzz::check_rejects_perturbations, io_report::io_report, taint::taint, ProgEnum,
+};
+use zokrates_ast::typed::abi::Abi;
+use zokrates_core::compile::{call_graph, CompileError};
+use zokrates_field::{Bn128Field, Field};
+use zokrates_fs_resolver::FileSystemResolver;
 
 pub fn subcommand() -> App<'static, 'static> {
     SubCommand::with_name("inspect")
         .about("Inspects a compiled program")
+        .arg(entry_arg())
         .arg(
             Arg::with_name("input")
                 .short("i")
@@ -25,30 +33,181 @@ pub fn subcommand() -> App<'static, 'static> {
                 .help("Writes human readable output (ztf) to a file")
                 .required(false),
         )
+        .arg(
+            Arg::with_name("taint")
+                .long("taint")
+                .help("Reports the constraints which depend on the given input")
+                .value_name("INPUT")
+                .takes_value(true)
+                .required(false),
+        )
+        .arg(
+            Arg::with_name("io-report")
+                .long("io-report")
+                .help("Lists the public inputs and outputs with the number of bits they can hold, and the private inputs influencing each output")
+                .required(false),
+        )
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .help("Writes the report of `--io-report` as JSON")
+                .required(false)
+                .requires("io-report"),
+        )
+        .arg(
+            Arg::with_name("variable-ordering")
+                .long("variable-ordering")
+                .help("Writes the permutation applied to the variables by `zokrates compile --reorder-variables` to FILE as JSON")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false),
+        )
+        .arg(
+            Arg::with_name("abi-spec")
+                .short("s")
+                .long("abi-spec")
+                .help(
+                    "Path of the ABI specification, used to resolve the input passed to `--taint` and by `--io-report`",
+                )
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(ABI_SPEC_DEFAULT_PATH),
+        )
+        .arg(
+            Arg::with_name("fuzz-witness")
+                .long("fuzz-witness")
+                .help(
+                    "Perturbs the variables of a valid witness to find under-constrained variables",
+                )
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false),
+        )
+        .arg(
+            Arg::with_name("fuzz-cases")
+                .long("fuzz-cases")
+                .help("Number of perturbations tried for each variable when fuzzing a witness")
+                .value_name("N")
+                .takes_value(true)
+                .required(false)
+                .default_value("16"),
+        )
+        .arg(
+            Arg::with_name("fuzz-seed")
+                .long("fuzz-seed")
+                .help("Seed of the random perturbations, to reproduce a previous run. Defaults to a random seed, which is printed")
+                .value_name("SEED")
+                .takes_value(true)
+                .required(false)
+                .requires("fuzz-witness"),
+        )
+        .arg(
+            Arg::with_name("call-graph")
+                .long("call-graph")
+                .help("Prints the call graph of the source program passed to `--source`, without compiling it")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .required(false)
+                .possible_values(&["dot"])
+                .requires("source"),
+        )
+        .arg(
+            Arg::with_name("source")
+                .long("source")
+                .help("Path of the source code, used by `--call-graph`")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false),
+        )
+        .arg(
+            Arg::with_name("stdlib-path")
+                .long("stdlib-path")
+                .help("Path to the standard library")
+                .value_name("PATH")
+                .takes_value(true)
+                .required(false)
+                .env("ZOKRATES_STDLIB")
+                .default_value(cli_constants::DEFAULT_STDLIB_PATH.as_str()),
+        )
 }
 
 pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
+    if sub_matches.is_present("call-graph") {
+        return cli_call_graph(sub_matches);
+    }
+
     // read compiled program
     let path = Path::new(sub_matches.value_of("input").unwrap());
-    let file =
-        File::open(&path).map_err(|why| format!("Could not open `{}`: {}", path.display(), why))?;
-
-    let mut reader = BufReader::new(file);
+    let mut reader = read_program(path, sub_matches.value_of("entry"))?;
 
     match ProgEnum::deserialize(&mut reader)? {
         ProgEnum::Bn128Program(p) => cli_inspect(p, sub_matches),
         ProgEnum::Bls12_377Program(p) => cli_inspect(p, sub_matches),
         ProgEnum::Bls12_381Program(p) => cli_inspect(p, sub_matches),
         ProgEnum::Bw6_761Program(p) => cli_inspect(p, sub_matches),
+        ProgEnum::PallasProgram(p) => cli_inspect(p, sub_matches),
+        ProgEnum::VestaProgram(p) => cli_inspect(p, sub_matches),
     }
 }
 
+fn cli_call_graph(sub_matches: &ArgMatches) -> Result<(), String> {
+    let path = PathBuf::from(sub_matches.value_of("source").unwrap());
+
+    let mut source = String::new();
+    File::open(&path)
+        .and_then(|file| BufReader::new(file).read_to_string(&mut source))
+        .map_err(|why| format!("Could not open {}: {}", path.display(), why))?;
+
+    let stdlib_path = sub_matches.value_of("stdlib-path").unwrap();
+    match Path::new(stdlib_path).exists() {
+        true => Ok(()),
+        _ => Err(format!(
+            "Invalid standard library source path: {}",
+            stdlib_path
+        )),
+    }?;
+
+    let fmt_error = |e: &CompileError| format!("{}:{}", e.file().display(), e.value());
+
+    // the call graph does not depend on the curve, as the program is only checked
+    let resolver = FileSystemResolver::with_stdlib_root(stdlib_path);
+    let dot = call_graph::<Bn128Field, _>(source, path, Some(&resolver)).map_err(|e| {
+        format!(
+            "Check failed:\n\n{}",
+            e.0.iter()
+                .map(|e| fmt_error(e))
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        )
+    })?;
+
+    print!("{}", dot);
+
+    Ok(())
+}
+
+fn read_abi_spec(sub_matches: &ArgMatches) -> Result<Abi, String> {
+    let path = Path::new(sub_matches.value_of("abi-spec").unwrap());
+    let file =
+        File::open(&path).map_err(|why| format!("Could not open `{}`: {}", path.display(), why))?;
+    read_abi(BufReader::new(file), sub_matches.value_of("entry"))
+        .map_err(|why| format!("Could not read `{}`: {}", path.display(), why))
+}
+
 fn cli_inspect<'a, T: Field, I: Iterator<Item = ir::Statement<'a, T>>>(
     ir_prog: ir::ProgIterator<'a, T, I>,
     sub_matches: &ArgMatches,
 ) -> Result<(), String> {
     let ir_prog: ir::Prog<T> = ir_prog.collect();
 
+    // the JSON report is printed alone so that it can be parsed
+    if sub_matches.is_present("json") {
+        let report = io_report(&ir_prog, &read_abi_spec(sub_matches)?);
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        return Ok(());
+    }
+
     let curve = format!("{:<17} {}", "curve:", T::name());
     let constraint_count = format!("{:<17} {}", "constraint_count:", ir_prog.constraint_count());
 
@@ -73,5 +232,98 @@ fn cli_inspect<'a, T: Field, I: Iterator<Item = ir::Statement<'a, T>>>(
         println!("ztf file written to '{}'", output_path.display());
     }
 
+    if sub_matches.is_present("io-report") {
+        print!("{}", io_report(&ir_prog, &read_abi_spec(sub_matches)?));
+    }
+
+    if let Some(path) = sub_matches.value_of("variable-ordering") {
+        let ordering = ir_prog.variable_ordering.as_ref().ok_or_else(|| {
+            String::from("The program was not compiled with `--reorder-variables`")
+        })?;
+
+        let file =
+            File::create(path).map_err(|why| format!("Could not create `{}`: {}", path, why))?;
+        serde_json::to_writer_pretty(BufWriter::new(file), ordering)
+            .map_err(|why| format!("Could not write to `{}`: {}", path, why))?;
+
+        println!("Variable ordering written to '{}'", path);
+    }
+
+    if let Some(name) = sub_matches.value_of("taint") {
+        let abi = read_abi_spec(sub_matches)?;
+
+        // each input is flattened to consecutive arguments of the program
+        let offset: usize = abi
+            .inputs
+            .iter()
+            .take_while(|i| i.name != name)
+            .map(|i| i.ty.get_primitive_count())
+            .sum();
+        let input = abi
+            .inputs
+            .iter()
+            .find(|i| i.name == name)
+            .ok_or_else(|| format!("Input `{}` not found in the ABI", name))?;
+
+        let sources = ir_prog
+            .arguments
+            .get(offset..offset + input.ty.get_primitive_count())
+            .ok_or_else(|| {
+                format!(
+                    "Input `{}` does not match the arguments of the program, check that the ABI specification belongs to it",
+                    name
+                )
+            })?
+            .iter()
+            .map(|p| p.id);
+
+        let report = taint(&ir_prog, sources);
+
+        println!("{:<17} {}", "taint:", name);
+        println!("{:<17} {}", "dependent:", report.dependent.len());
+        for constraint in &report.dependent {
+            match &constraint.span {
+                Some(span) => println!("  #{} {}", constraint.index, span),
+                None => println!("  #{}", constraint.index),
+            }
+        }
+        println!("{:<17} {}", "independent:", report.independent.len());
+    }
+
+    if let Some(path) = sub_matches.value_of("fuzz-witness") {
+        let path = Path::new(path);
+        let file = File::open(&path)
+            .map_err(|why| format!("Could not open `{}`: {}", path.display(), why))?;
+        let witness =
+            ir::Witness::read(file).map_err(|why| format!("Could not load witness: {:?}", why))?;
+
+        let cases = sub_matches
+            .value_of("fuzz-cases")
+            .unwrap()
+            .parse::<usize>()
+            .map_err(|why| format!("Invalid number of cases: {}", why))?;
+
+        let seed = match sub_matches.value_of("fuzz-seed") {
+            Some(seed) => seed
+                .parse::<u64>()
+                .map_err(|why| format!("Invalid seed: {}", why))?,
+            None => random(),
+        };
+
+        let report = check_rejects_perturbations(&ir_prog, &witness, cases, seed)
+            .map_err(|e| e.to_string())?;
+
+        println!("{:<17} {}", "seed:", report.seed);
+        println!("{:<17} {}", "perturbations:", report.checked);
+        println!("{:<17} {}", "unconstrained:", report.unconstrained.len());
+        for v in &report.unconstrained {
+            println!("  {}", v);
+        }
+        println!("{:<17} {}", "free:", report.free.len());
+        for v in &report.free {
+            println!("  {}", v);
+        }
+    }
+
     Ok(())
 }
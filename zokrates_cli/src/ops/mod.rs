@@ -1,17 +1,34 @@
+pub mod abi_diff;
+pub mod annotate;
+#[cfg(any(feature = "bellman", feature = "ark"))]
+pub mod build;
 pub mod check;
+pub mod check_equivalence;
 pub mod compile;
 pub mod compute_witness;
+pub mod coverage;
+pub mod explain;
+pub mod export;
 pub mod export_verifier;
+pub mod export_witness_calculator;
 #[cfg(any(feature = "bellman", feature = "ark"))]
 pub mod generate_proof;
 pub mod generate_smtlib2;
+pub mod init;
 pub mod inspect;
 #[cfg(feature = "bellman")]
 pub mod mpc;
+pub mod pin;
 pub mod print_proof;
+pub mod repl;
+#[cfg(any(feature = "bellman", feature = "ark"))]
+pub mod rerandomize_proof;
+#[cfg(any(feature = "bellman", feature = "ark"))]
+pub mod serve;
 #[cfg(any(feature = "bellman", feature = "ark"))]
 pub mod setup;
 #[cfg(feature = "ark")]
 pub mod universal_setup;
 #[cfg(any(feature = "bellman", feature = "ark"))]
 pub mod verify;
+pub mod verify_build;
@@ -0,0 +1,211 @@
+use crate::cli_constants;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read};
+use std::path::{Path, PathBuf};
+use typed_arena::Arena;
+use zokrates_common::constants::{BN128, G16};
+use zokrates_common::helpers::CurveParameter;
+use zokrates_common::{CompileConfig, Resolver};
+use zokrates_core::compile::{compile, CompileErrors};
+use zokrates_core::pin::Pin;
+use zokrates_field::{
+    Bls12_377Field, Bls12_381Field, Bn128Field, Bw6_761Field, Field, PallasField, VestaField,
+};
+use zokrates_fs_resolver::FileSystemResolver;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("pin")
+        .about("Records the hashes of the constraint system and of the ABI of a program, or checks that they did not change")
+        .arg(Arg::with_name("input")
+            .short("i")
+            .long("input")
+            .help("Path of the source code")
+            .value_name("FILE")
+            .takes_value(true)
+            .required_unless("check")
+        ).arg(Arg::with_name("output")
+            .short("o")
+            .long("output")
+            .help("Path of the pin to write")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(cli_constants::PIN_DEFAULT_PATH)
+        ).arg(Arg::with_name("check")
+            .long("check")
+            .help("Compile the pinned program again with the pinned configuration, and fail if its constraint system or its ABI changed")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .conflicts_with_all(&["input", "curve", "proving-scheme", "isolate-branches"])
+        ).arg(Arg::with_name("stdlib-path")
+            .long("stdlib-path")
+            .help("Path to the standard library")
+            .value_name("PATH")
+            .takes_value(true)
+            .required(false)
+            .env("ZOKRATES_STDLIB")
+            .default_value(cli_constants::DEFAULT_STDLIB_PATH.as_str())
+        ).arg(Arg::with_name("curve")
+            .short("c")
+            .long("curve")
+            .help("Curve to be used in the compilation")
+            .takes_value(true)
+            .required(false)
+            .possible_values(cli_constants::CURVES)
+            .default_value(BN128)
+        ).arg(Arg::with_name("proving-scheme")
+            .short("s")
+            .long("proving-scheme")
+            .help("Proving scheme the verification key is generated for")
+            .takes_value(true)
+            .required(false)
+            .possible_values(cli_constants::SCHEMES)
+            .default_value(G16)
+        ).arg(Arg::with_name("isolate-branches")
+            .long("isolate-branches")
+            .help("Isolate the execution of branches: a panic in a branch only makes the program panic if this branch is being logically executed")
+            .required(false)
+        )
+}
+
+pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
+    let stdlib_path = sub_matches.value_of("stdlib-path").unwrap();
+    match Path::new(stdlib_path).exists() {
+        true => Ok(()),
+        _ => Err(format!(
+            "Invalid standard library source path: {}",
+            stdlib_path
+        )),
+    }?;
+
+    let resolver = FileSystemResolver::with_stdlib_root(stdlib_path);
+
+    match sub_matches.value_of("check") {
+        Some(pin_path) => {
+            let pin_path = Path::new(pin_path);
+            let pin_file = File::open(pin_path)
+                .map_err(|why| format!("Could not open {}: {}", pin_path.display(), why))?;
+            let pinned: Pin = serde_json::from_reader(BufReader::new(pin_file))
+                .map_err(|why| format!("Could not read {}: {}", pin_path.display(), why))?;
+
+            let current = pin(
+                pinned.source.clone(),
+                &pinned.curve,
+                &pinned.scheme,
+                pinned.config.clone(),
+                &resolver,
+            )?;
+
+            let drift = pinned.drift(&current);
+
+            match drift.is_empty() {
+                true => {
+                    println!("{} is up to date", pinned.source.display());
+                    Ok(())
+                }
+                false => Err(format!(
+                    "{} drifted from {}:\n\n{}",
+                    pinned.source.display(),
+                    pin_path.display(),
+                    drift
+                        .iter()
+                        .map(|d| d.to_string())
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                )),
+            }
+        }
+        None => {
+            let config = CompileConfig::default()
+                .isolate_branches(sub_matches.is_present("isolate-branches"));
+
+            let pin = pin(
+                PathBuf::from(sub_matches.value_of("input").unwrap()),
+                sub_matches.value_of("curve").unwrap(),
+                sub_matches.value_of("proving-scheme").unwrap(),
+                config,
+                &resolver,
+            )?;
+
+            let output_path = Path::new(sub_matches.value_of("output").unwrap());
+            let output_file = File::create(output_path)
+                .map_err(|why| format!("Could not create {}: {}", output_path.display(), why))?;
+            serde_json::to_writer_pretty(BufWriter::new(output_file), &pin)
+                .map_err(|why| format!("Could not write {}: {}", output_path.display(), why))?;
+
+            println!(
+                "Pin of {} written to '{}'",
+                pin.source.display(),
+                output_path.display()
+            );
+            println!("Constraint system: {}", pin.constraint_system);
+            println!("ABI: {}", pin.abi_hash);
+
+            Ok(())
+        }
+    }
+}
+
+/// Compile the program at `path` and pin it
+fn pin(
+    path: PathBuf,
+    curve: &str,
+    scheme: &str,
+    config: CompileConfig,
+    resolver: &dyn Resolver<io::Error>,
+) -> Result<Pin, String> {
+    match CurveParameter::try_from(curve)? {
+        CurveParameter::Bn128 => cli_pin::<Bn128Field>(path, curve, scheme, config, resolver),
+        CurveParameter::Bls12_377 => {
+            cli_pin::<Bls12_377Field>(path, curve, scheme, config, resolver)
+        }
+        CurveParameter::Bls12_381 => {
+            cli_pin::<Bls12_381Field>(path, curve, scheme, config, resolver)
+        }
+        CurveParameter::Bw6_761 => cli_pin::<Bw6_761Field>(path, curve, scheme, config, resolver),
+        CurveParameter::Pallas => cli_pin::<PallasField>(path, curve, scheme, config, resolver),
+        CurveParameter::Vesta => cli_pin::<VestaField>(path, curve, scheme, config, resolver),
+    }
+}
+
+fn cli_pin<T: Field>(
+    path: PathBuf,
+    curve: &str,
+    scheme: &str,
+    config: CompileConfig,
+    resolver: &dyn Resolver<io::Error>,
+) -> Result<Pin, String> {
+    let mut source = String::new();
+    File::open(&path)
+        .and_then(|file| BufReader::new(file).read_to_string(&mut source))
+        .map_err(|why| format!("Could not open {}: {}", path.display(), why))?;
+
+    let fmt_errors = |e: CompileErrors| {
+        format!(
+            "Compilation failed:\n\n{}",
+            e.0.iter()
+                .map(|e| format!("{}:{}", e.file().display(), e.value()))
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        )
+    };
+
+    let arena = Arena::new();
+    let (program, abi) =
+        compile::<T, _>(source, path.clone(), Some(resolver), config.clone(), &arena)
+            .map_err(fmt_errors)?
+            .collect()
+            .into_inner();
+
+    Ok(Pin::new(
+        path,
+        curve.to_string(),
+        scheme.to_string(),
+        config,
+        &program,
+        &abi,
+    ))
+}
@@ -0,0 +1,508 @@
+use crate::cli_constants;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::convert::TryFrom;
+use std::io::{stdin, stdout, BufRead, Write};
+use std::marker::PhantomData;
+use typed_arena::Arena;
+use zokrates_abi::{parse_strict_json, Encode, Inputs};
+use zokrates_ast::ir::{self, Statement};
+use zokrates_ast::typed::ConcreteType;
+use zokrates_common::constants::BN128;
+use zokrates_common::{helpers::CurveParameter, CompileConfig};
+use zokrates_core::compile::{check, compile, CompileErrors};
+use zokrates_field::{
+    Bls12_377Field, Bls12_381Field, Bn128Field, Bw6_761Field, Field, PallasField, VestaField,
+};
+use zokrates_fs_resolver::FileSystemResolver;
+use zokrates_interpreter::Interpreter;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("repl")
+        .about("Starts an interactive session to evaluate expressions")
+        .arg(
+            Arg::with_name("stdlib-path")
+                .long("stdlib-path")
+                .help("Path to the standard library")
+                .value_name("PATH")
+                .takes_value(true)
+                .required(false)
+                .env("ZOKRATES_STDLIB")
+                .default_value(cli_constants::DEFAULT_STDLIB_PATH.as_str()),
+        )
+        .arg(
+            Arg::with_name("curve")
+                .short("c")
+                .long("curve")
+                .help("Curve to be used in the session")
+                .takes_value(true)
+                .required(false)
+                .possible_values(cli_constants::CURVES)
+                .default_value(BN128),
+        )
+}
+
+pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
+    let curve = CurveParameter::try_from(sub_matches.value_of("curve").unwrap())?;
+    match curve {
+        CurveParameter::Bn128 => cli_repl::<Bn128Field>(sub_matches),
+        CurveParameter::Bls12_377 => cli_repl::<Bls12_377Field>(sub_matches),
+        CurveParameter::Bls12_381 => cli_repl::<Bls12_381Field>(sub_matches),
+        CurveParameter::Bw6_761 => cli_repl::<Bw6_761Field>(sub_matches),
+        CurveParameter::Pallas => cli_repl::<PallasField>(sub_matches),
+        CurveParameter::Vesta => cli_repl::<VestaField>(sub_matches),
+    }
+}
+
+fn cli_repl<T: Field>(sub_matches: &ArgMatches) -> Result<(), String> {
+    let mut session = Session::<T>::new(sub_matches.value_of("stdlib-path").unwrap());
+
+    println!("Declare functions, constants, structs and imports, define variables with `<type> <name> = <expression>;`, or enter an expression to evaluate it. Enter `:quit` to exit.");
+
+    let mut prompt = "> ";
+
+    loop {
+        print!("{}", prompt);
+        stdout().flush().map_err(|e| e.to_string())?;
+
+        let mut line = String::new();
+        if stdin()
+            .lock()
+            .read_line(&mut line)
+            .map_err(|e| e.to_string())?
+            == 0
+        {
+            return Ok(());
+        }
+
+        if line.trim() == ":quit" {
+            return Ok(());
+        }
+
+        prompt = "> ";
+
+        match session.feed(&line) {
+            Ok(Response::Incomplete) => prompt = "... ",
+            Ok(Response::Nothing) | Ok(Response::Declared) => {}
+            Ok(Response::Value {
+                name,
+                logs,
+                value,
+                ty,
+            }) => {
+                for log in logs {
+                    println!("{}", log);
+                }
+                match name {
+                    Some(name) => println!("{}: {} = {}", name, ty, value),
+                    None => println!("{}: {}", value, ty),
+                }
+            }
+            Err(e) => println!("{}", e),
+        }
+    }
+}
+
+/// The prefix of the log statement used to extract the value of an expression
+const RESULT_MARKER: &str = "~repl ";
+
+/// The name under which the value of a variable definition is computed
+const RESULT_VARIABLE: &str = "repl_result";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Response {
+    /// The input is not complete yet, for example a function definition spanning multiple lines
+    Incomplete,
+    /// The input was empty
+    Nothing,
+    /// A declaration was added to the session
+    Declared,
+    /// An expression was evaluated, and possibly assigned to a variable of the session
+    Value {
+        name: Option<String>,
+        /// The logs emitted during the evaluation
+        logs: Vec<String>,
+        value: String,
+        ty: String,
+    },
+}
+
+struct Variable {
+    name: String,
+    ty: String,
+    value: serde_json::Value,
+}
+
+/// A session holding the declarations and variables entered so far
+pub struct Session<T> {
+    stdlib_path: String,
+    declarations: Vec<String>,
+    variables: Vec<Variable>,
+    buffer: String,
+    field: PhantomData<T>,
+}
+
+impl<T: Field> Session<T> {
+    pub fn new<S: Into<String>>(stdlib_path: S) -> Self {
+        Session {
+            stdlib_path: stdlib_path.into(),
+            declarations: vec![],
+            variables: vec![],
+            buffer: String::new(),
+            field: PhantomData,
+        }
+    }
+
+    /// Feed a line of input to the session
+    pub fn feed(&mut self, line: &str) -> Result<Response, String> {
+        self.buffer.push_str(line);
+        self.buffer.push('\n');
+
+        if !is_complete(&self.buffer) {
+            return Ok(Response::Incomplete);
+        }
+
+        let input = std::mem::take(&mut self.buffer);
+        let input = input.trim();
+
+        if input.is_empty() {
+            return Ok(Response::Nothing);
+        }
+
+        const DECLARATIONS: &[&str] = &["import ", "from ", "const ", "struct ", "def ", "type "];
+
+        if DECLARATIONS.iter().any(|d| input.starts_with(d)) {
+            return self.declare(input);
+        }
+
+        match definition(input) {
+            Some((ty, name, expression)) => self.define(ty, name, expression),
+            None => self.evaluate(input.trim_end_matches(';'), None),
+        }
+    }
+
+    fn declare(&mut self, declaration: &str) -> Result<Response, String> {
+        let source = format!(
+            "{}\n{}\ndef main() {{\n    return;\n}}\n",
+            self.declarations.join("\n"),
+            declaration
+        );
+
+        let resolver = FileSystemResolver::with_stdlib_root(&self.stdlib_path);
+
+        check::<T, _>(
+            source,
+            "repl".into(),
+            Some(&resolver),
+            &CompileConfig::default(),
+        )
+        .map_err(format_errors)?;
+
+        self.declarations.push(declaration.to_string());
+
+        Ok(Response::Declared)
+    }
+
+    fn define(&mut self, ty: &str, name: &str, expression: &str) -> Result<Response, String> {
+        let body = format!("{} {} = {};", ty, RESULT_VARIABLE, expression);
+        let res = self.evaluate(RESULT_VARIABLE, Some(&body))?;
+
+        if let Response::Value { value, .. } = &res {
+            let value = serde_json::from_str(value)
+                .map_err(|e| format!("Could not read the value of `{}`: {}", name, e))?;
+
+            let variable = Variable {
+                name: name.to_string(),
+                ty: ty.to_string(),
+                value: into_abi_json(value),
+            };
+
+            // a new definition replaces the previous variable with the same name
+            match self.variables.iter_mut().find(|v| v.name == name) {
+                Some(v) => *v = variable,
+                None => self.variables.push(variable),
+            }
+        }
+
+        Ok(match res {
+            Response::Value {
+                logs, value, ty, ..
+            } => Response::Value {
+                name: Some(name.to_string()),
+                logs,
+                value,
+                ty,
+            },
+            r => r,
+        })
+    }
+
+    /// Evaluate `expression` after running `body`, passing the variables of the session as inputs
+    fn evaluate(&self, expression: &str, body: Option<&str>) -> Result<Response, String> {
+        let source = format!(
+            "{}\ndef main({}) {{\n    {}\n    log(\"{}{{}}\", {});\n    return;\n}}\n",
+            self.declarations.join("\n"),
+            self.variables
+                .iter()
+                .map(|v| format!("private {} {}", v.ty, v.name))
+                .collect::<Vec<_>>()
+                .join(", "),
+            body.unwrap_or(""),
+            RESULT_MARKER,
+            expression
+        );
+
+        let resolver = FileSystemResolver::with_stdlib_root(&self.stdlib_path);
+        let arena = Arena::new();
+
+        let (program, abi) = compile::<T, _>(
+            source,
+            "repl".into(),
+            Some(&resolver),
+            CompileConfig::default().debug(true),
+            &arena,
+        )
+        .map_err(format_errors)?
+        .into_inner();
+
+        let program: ir::Prog<T> = program.collect();
+
+        let ty = result_type(&program.statements)
+            .ok_or_else(|| "The evaluated expression was not logged".to_string())?
+            .to_string();
+
+        let inputs = parse_strict_json(
+            self.variables.iter().map(|v| v.value.clone()).collect(),
+            abi.signature().inputs,
+        )
+        .map(Inputs::Abi)
+        .map_err(|e| e.to_string())?;
+
+        let mut output = vec![];
+
+        Interpreter::default()
+            .execute_with_log_stream(program, &inputs.encode(), &mut output)
+            .map_err(|e| format!("Execution failed: {}", e))?;
+
+        let output = String::from_utf8(output).map_err(|e| e.to_string())?;
+
+        let (results, logs): (Vec<_>, Vec<_>) = output
+            .lines()
+            .partition(|line| line.starts_with(RESULT_MARKER));
+
+        Ok(Response::Value {
+            name: None,
+            logs: logs.into_iter().map(String::from).collect(),
+            value: results
+                .last()
+                .ok_or_else(|| "The evaluated expression was not logged".to_string())?
+                .trim_start_matches(RESULT_MARKER)
+                .to_string(),
+            ty,
+        })
+    }
+}
+
+fn format_errors(errors: CompileErrors) -> String {
+    errors
+        .0
+        .iter()
+        .map(|e| e.value().message())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Turn a logged value back into an abi input, where unsigned integers are logged as numbers but expected as strings
+fn into_abi_json(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Number(n) => serde_json::Value::String(n.to_string()),
+        serde_json::Value::Array(a) => {
+            serde_json::Value::Array(a.into_iter().map(into_abi_json).collect())
+        }
+        serde_json::Value::Object(o) => {
+            serde_json::Value::Object(o.into_iter().map(|(k, v)| (k, into_abi_json(v))).collect())
+        }
+        v => v,
+    }
+}
+
+/// Find the type of the value logged by the session
+fn result_type<'a, T>(statements: &'a [Statement<T>]) -> Option<&'a ConcreteType> {
+    statements.iter().rev().find_map(|s| match s {
+        Statement::Log(format, expressions)
+            if format.parts.first().map(String::as_str) == Some(RESULT_MARKER) =>
+        {
+            expressions.first().map(|(ty, _)| ty)
+        }
+        Statement::Block(statements) => result_type(statements),
+        _ => None,
+    })
+}
+
+/// Check that all brackets in `input` are closed, ignoring string literals
+fn is_complete(input: &str) -> bool {
+    let mut depth = 0i32;
+    let mut in_string = false;
+
+    for c in input.chars() {
+        match (c, in_string) {
+            ('"', _) => in_string = !in_string,
+            ('{' | '(' | '[', false) => depth += 1,
+            ('}' | ')' | ']', false) => depth -= 1,
+            _ => {}
+        }
+    }
+
+    depth <= 0
+}
+
+/// Split a variable definition `<type> <name> = <expression>;` into its parts
+fn definition(input: &str) -> Option<(&str, &str, &str)> {
+    let bytes = input.as_bytes();
+
+    // find the first `=` which is not part of a comparison operator
+    let index = (0..bytes.len()).find(|&i| {
+        bytes[i] == b'='
+            && bytes.get(i + 1) != Some(&b'=')
+            && (i == 0 || !b"=!<>".contains(&bytes[i - 1]))
+    })?;
+
+    let (left, expression) = (input[..index].trim(), input[index + 1..].trim());
+    let expression = expression.trim_end_matches(';').trim();
+
+    let (ty, name) = left.rsplit_once(char::is_whitespace)?;
+    let ty = ty.trim().trim_end_matches(" mut").trim();
+
+    let is_identifier = |s: &str| {
+        s.chars().next().map_or(false, |c| c.is_ascii_alphabetic())
+            && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+
+    match !ty.is_empty() && is_identifier(name) && !expression.is_empty() {
+        true => Some((ty, name, expression)),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> Session<Bn128Field> {
+        Session::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../zokrates_stdlib/stdlib"
+        ))
+    }
+
+    fn value(name: Option<&str>, value: &str, ty: &str) -> Response {
+        Response::Value {
+            name: name.map(String::from),
+            logs: vec![],
+            value: value.to_string(),
+            ty: ty.to_string(),
+        }
+    }
+
+    #[test]
+    fn scripted_session() {
+        let mut session = session();
+
+        assert_eq!(
+            session.feed("field a = 3;"),
+            Ok(value(Some("a"), "\"3\"", "field"))
+        );
+        assert_eq!(
+            session.feed("u32 b = 0x0000000a"),
+            Ok(value(Some("b"), "10", "u32"))
+        );
+        assert_eq!(session.feed("a * a"), Ok(value(None, "\"9\"", "field")));
+        assert_eq!(session.feed("b + 1 == 11"), Ok(value(None, "true", "bool")));
+        assert_eq!(session.feed("b + 1"), Ok(value(None, "11", "u32")));
+        assert_eq!(
+            session.feed("[a, a + 1]"),
+            Ok(value(None, "[\"3\",\"4\"]", "field[2]"))
+        );
+
+        // redefining a variable uses its previous value
+        assert_eq!(
+            session.feed("field a = a + 1;"),
+            Ok(value(Some("a"), "\"4\"", "field"))
+        );
+        assert_eq!(session.feed("a"), Ok(value(None, "\"4\"", "field")));
+    }
+
+    #[test]
+    fn multi_line_function() {
+        let mut session = session();
+
+        assert_eq!(
+            session.feed("def cube(field x) -> field {"),
+            Ok(Response::Incomplete)
+        );
+        assert_eq!(
+            session.feed("    field y = x * x;"),
+            Ok(Response::Incomplete)
+        );
+        assert_eq!(session.feed("    return y * x;"), Ok(Response::Incomplete));
+        assert_eq!(session.feed("}"), Ok(Response::Declared));
+        assert_eq!(session.feed("const field TWO = 2;"), Ok(Response::Declared));
+        assert_eq!(session.feed("cube(TWO)"), Ok(value(None, "\"8\"", "field")));
+    }
+
+    #[test]
+    fn stdlib_import() {
+        let mut session = session();
+
+        assert_eq!(
+            session.feed("import \"utils/casts/u32_to_field\" as to_field;"),
+            Ok(Response::Declared)
+        );
+        assert_eq!(
+            session.feed("to_field(42)"),
+            Ok(value(None, "\"42\"", "field"))
+        );
+    }
+
+    #[test]
+    fn logs() {
+        let mut session = session();
+
+        session
+            .feed("def noisy(field x) -> field { log(\"x is {}\", x); return x; }")
+            .unwrap();
+
+        assert_eq!(
+            session.feed("noisy(5)"),
+            Ok(Response::Value {
+                name: None,
+                logs: vec!["x is \"5\"".to_string()],
+                value: "\"5\"".to_string(),
+                ty: "field".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn errors_keep_session() {
+        let mut session = session();
+
+        session.feed("field a = 3;").unwrap();
+
+        assert!(session.feed("a + true").is_err());
+        assert!(session
+            .feed("def broken() -> field { return true; }")
+            .is_err());
+        assert_eq!(session.feed("a"), Ok(value(None, "\"3\"", "field")));
+    }
+
+    #[test]
+    fn failed_definition_keeps_session() {
+        let mut session = session();
+
+        session.feed("field a = 3;").unwrap();
+
+        assert!(session.feed("field b = a / 0;").is_err());
+        assert!(session.feed("b").is_err());
+        assert_eq!(session.feed("a"), Ok(value(None, "\"3\"", "field")));
+    }
+}
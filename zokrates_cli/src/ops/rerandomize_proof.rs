@@ -0,0 +1,208 @@
+use crate::cli_constants;
+use crate::ops::verify::{read_hash, read_public_layout};
+use crate::program::{check_hash, force_arg};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use rand_0_8::rngs::StdRng;
+use rand_0_8::SeedableRng;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{BufReader, Write};
+use std::path::Path;
+#[cfg(feature = "ark")]
+use zokrates_ark::Ark;
+use zokrates_ast::ir::ProgramHash;
+#[cfg(feature = "bellman")]
+use zokrates_bellman::Bellman;
+use zokrates_common::constants;
+use zokrates_common::helpers::*;
+use zokrates_field::{Bls12_377Field, Bls12_381Field, Bn128Field, Bw6_761Field, Field};
+use zokrates_proof_systems::rng::get_rng_from_entropy;
+use zokrates_proof_systems::*;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("rerandomize-proof")
+        .about("Re-randomizes a Groth16 proof, so that it cannot be linked to the original one")
+        .arg(
+            Arg::with_name("proof-path")
+                .short("j")
+                .long("proof-path")
+                .help("Path of the JSON proof file")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(cli_constants::JSON_PROOF_PATH),
+        )
+        .arg(
+            Arg::with_name("verification-key-path")
+                .short("v")
+                .long("verification-key-path")
+                .help("Path of the verification key the proof was generated for")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(cli_constants::VERIFICATION_KEY_DEFAULT_PATH),
+        )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .help("Path of the re-randomized JSON proof file")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(cli_constants::RERANDOMIZED_PROOF_PATH),
+        )
+        .arg(
+            Arg::with_name("backend")
+                .short("b")
+                .long("backend")
+                .help("Backend to use")
+                .takes_value(true)
+                .required(false)
+                .possible_values(cli_constants::BACKENDS)
+                .default_value(constants::ARK),
+        )
+        .arg(
+            Arg::with_name("entropy")
+                .short("e")
+                .long("entropy")
+                .help("User provided randomness")
+                .takes_value(true)
+                .required(false),
+        )
+        .arg(force_arg())
+}
+
+pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
+    let verbose = sub_matches.is_present("verbose");
+
+    let vk_path = Path::new(sub_matches.value_of("verification-key-path").unwrap());
+    let vk_file = File::open(&vk_path)
+        .map_err(|why| format!("Could not open {}: {}", vk_path.display(), why))?;
+    let vk: serde_json::Value = serde_json::from_reader(BufReader::new(vk_file))
+        .map_err(|why| format!("Could not deserialize verification key: {}", why))?;
+
+    let proof_path = Path::new(sub_matches.value_of("proof-path").unwrap());
+    let proof_file = File::open(&proof_path)
+        .map_err(|why| format!("Could not open {}: {}", proof_path.display(), why))?;
+    let proof: serde_json::Value = serde_json::from_reader(BufReader::new(proof_file))
+        .map_err(|why| format!("Could not deserialize proof: {}", why))?;
+
+    let field = |value: &serde_json::Value, key: &str, name: &str| -> Result<String, String> {
+        value
+            .get(key)
+            .ok_or_else(|| format!("Field `{}` not found in {}", key, name))?
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| format!("`{}` should be a string", key))
+    };
+
+    let curve = field(&proof, "curve", "proof")?;
+    let scheme = field(&proof, "scheme", "proof")?;
+
+    if field(&vk, "curve", "verification key")? != curve
+        || field(&vk, "scheme", "verification key")? != scheme
+    {
+        return Err(
+            "Expected the curve and the scheme of the proof and the verification key to be equal"
+                .to_string(),
+        );
+    }
+
+    let vk_hash = read_hash(&vk, "verification key")?;
+    let proof_hash = read_hash(&proof, "proof")?;
+
+    check_hash(
+        (
+            vk_hash,
+            &format!("the verification key `{}`", vk_path.display()),
+        ),
+        (proof_hash, &format!("the proof `{}`", proof_path.display())),
+        sub_matches.is_present("force"),
+    )?;
+
+    let parameters = Parameters::try_from((
+        sub_matches.value_of("backend").unwrap(),
+        curve.as_str(),
+        scheme.as_str(),
+    ))?;
+
+    let hash = proof_hash.or(vk_hash);
+
+    match parameters {
+        #[cfg(feature = "bellman")]
+        Parameters(BackendParameter::Bellman, CurveParameter::Bn128, SchemeParameter::G16) => {
+            cli_rerandomize_proof::<Bn128Field, Bellman>(vk, proof, hash, verbose, sub_matches)
+        }
+        #[cfg(feature = "bellman")]
+        Parameters(BackendParameter::Bellman, CurveParameter::Bls12_381, SchemeParameter::G16) => {
+            cli_rerandomize_proof::<Bls12_381Field, Bellman>(vk, proof, hash, verbose, sub_matches)
+        }
+        #[cfg(feature = "ark")]
+        Parameters(BackendParameter::Ark, CurveParameter::Bn128, SchemeParameter::G16) => {
+            cli_rerandomize_proof::<Bn128Field, Ark>(vk, proof, hash, verbose, sub_matches)
+        }
+        #[cfg(feature = "ark")]
+        Parameters(BackendParameter::Ark, CurveParameter::Bls12_381, SchemeParameter::G16) => {
+            cli_rerandomize_proof::<Bls12_381Field, Ark>(vk, proof, hash, verbose, sub_matches)
+        }
+        #[cfg(feature = "ark")]
+        Parameters(BackendParameter::Ark, CurveParameter::Bls12_377, SchemeParameter::G16) => {
+            cli_rerandomize_proof::<Bls12_377Field, Ark>(vk, proof, hash, verbose, sub_matches)
+        }
+        #[cfg(feature = "ark")]
+        Parameters(BackendParameter::Ark, CurveParameter::Bw6_761, SchemeParameter::G16) => {
+            cli_rerandomize_proof::<Bw6_761Field, Ark>(vk, proof, hash, verbose, sub_matches)
+        }
+        _ => Err(format!(
+            "Re-randomization is only supported for the `{}` scheme",
+            constants::G16
+        )),
+    }
+}
+
+fn cli_rerandomize_proof<T: Field, B: RerandomizationBackend<T, G16>>(
+    vk: serde_json::Value,
+    proof: serde_json::Value,
+    hash: Option<ProgramHash>,
+    verbose: bool,
+    sub_matches: &ArgMatches,
+) -> Result<(), String> {
+    let public_layout = read_public_layout(&proof, "proof")?;
+
+    let vk = serde_json::from_value(vk)
+        .map_err(|why| format!("Could not deserialize verification key: {}", why))?;
+    let proof: Proof<T, G16> = serde_json::from_value(proof)
+        .map_err(|why| format!("Could not deserialize proof: {}", why))?;
+
+    let mut rng = sub_matches
+        .value_of("entropy")
+        .map(get_rng_from_entropy)
+        .unwrap_or_else(StdRng::from_entropy);
+
+    println!("Re-randomizing proof...");
+
+    let proof = proof.rerandomize::<B, _>(&vk, &mut rng);
+
+    let output_path = Path::new(sub_matches.value_of("output").unwrap());
+    let mut output_file = File::create(output_path)
+        .map_err(|why| format!("Could not create {}: {}", output_path.display(), why))?;
+
+    let proof = serde_json::to_string_pretty(
+        &TaggedProof::<T, G16>::new(proof.proof, proof.inputs)
+            .with_hash(hash)
+            .with_public_layout(public_layout),
+    )
+    .unwrap();
+    output_file
+        .write(proof.as_bytes())
+        .map_err(|why| format!("Could not write to {}: {}", output_path.display(), why))?;
+
+    if verbose {
+        println!("Proof:\n{}", proof);
+    }
+
+    println!("Re-randomized proof written to '{}'", output_path.display());
+
+    Ok(())
+}
@@ -0,0 +1,568 @@
+use crate::cli_constants;
+use crate::program::{check_hash, force_arg};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use rand_0_8::rngs::StdRng;
+use rand_0_8::SeedableRng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{stdin, stdout, BufRead, BufReader, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::{mpsc, Mutex};
+use zokrates_abi::{Decode, Encode};
+#[cfg(feature = "ark")]
+use zokrates_ark::Ark;
+use zokrates_ast::ir::{self, ProgEnum, ProgramHash};
+use zokrates_ast::typed::abi::Abi;
+use zokrates_ast::typed::types::ConcreteSignature;
+#[cfg(feature = "bellman")]
+use zokrates_bellman::Bellman;
+use zokrates_common::constants;
+use zokrates_common::helpers::*;
+use zokrates_field::Field;
+use zokrates_proof_systems::*;
+
+/// The version of the request/response schema spoken by `zokrates serve`.
+/// Requests may omit `version`, in which case this version is assumed.
+pub const SERVE_API_VERSION: u32 = 1;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("serve")
+        .about("Loads a program and its proving key once, then computes witnesses and proofs for newline-delimited JSON requests")
+        .arg(
+            Arg::with_name("input")
+                .short("i")
+                .long("input")
+                .help("Path of the binary")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(cli_constants::FLATTENED_CODE_DEFAULT_PATH),
+        )
+        .arg(
+            Arg::with_name("abi-spec")
+                .long("abi-spec")
+                .help("Path of the ABI specification")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(cli_constants::ABI_SPEC_DEFAULT_PATH),
+        )
+        .arg(
+            Arg::with_name("proving-key-path")
+                .short("p")
+                .long("proving-key-path")
+                .help("Path of the proving key file")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(cli_constants::PROVING_KEY_DEFAULT_PATH),
+        )
+        .arg(
+            Arg::with_name("backend")
+                .short("b")
+                .long("backend")
+                .help("Backend to use")
+                .takes_value(true)
+                .required(false)
+                .possible_values(cli_constants::BACKENDS)
+                .default_value(constants::ARK),
+        )
+        .arg(
+            Arg::with_name("proving-scheme")
+                .short("s")
+                .long("proving-scheme")
+                .help("Proving scheme to use to generate proofs")
+                .takes_value(true)
+                .required(false)
+                .possible_values(cli_constants::SCHEMES)
+                .default_value(constants::G16),
+        )
+        .arg(
+            Arg::with_name("socket")
+                .long("socket")
+                .help("Listen on a Unix socket at this path instead of stdin")
+                .value_name("PATH")
+                .takes_value(true)
+                .required(false),
+        )
+        .arg(
+            Arg::with_name("threads")
+                .long("threads")
+                .help("Number of requests to process in parallel")
+                .value_name("N")
+                .takes_value(true)
+                .required(false)
+                .default_value("1"),
+        )
+        .arg(force_arg())
+}
+
+pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
+    let program_path = Path::new(sub_matches.value_of("input").unwrap());
+    let program_file = File::open(&program_path)
+        .map_err(|why| format!("Could not open {}: {}", program_path.display(), why))?;
+
+    let mut reader = BufReader::new(program_file);
+    let (prog, hash) = ProgEnum::deserialize_with_hash(&mut reader)?;
+    let prog = prog.collect();
+
+    let parameters = Parameters::try_from((
+        sub_matches.value_of("backend").unwrap(),
+        prog.curve(),
+        sub_matches.value_of("proving-scheme").unwrap(),
+    ))?;
+
+    match &parameters {
+        #[cfg(feature = "bellman")]
+        Parameters(BackendParameter::Bellman, _, SchemeParameter::G16) => match prog {
+            ProgEnum::Bn128Program(p) => cli_serve::<_, G16, Bellman>(p, hash, sub_matches),
+            ProgEnum::Bls12_381Program(p) => cli_serve::<_, G16, Bellman>(p, hash, sub_matches),
+            _ => Err(parameters.unsupported_curve()),
+        },
+        #[cfg(feature = "ark")]
+        Parameters(BackendParameter::Ark, _, SchemeParameter::G16) => match prog {
+            ProgEnum::Bn128Program(p) => cli_serve::<_, G16, Ark>(p, hash, sub_matches),
+            ProgEnum::Bls12_381Program(p) => cli_serve::<_, G16, Ark>(p, hash, sub_matches),
+            ProgEnum::Bls12_377Program(p) => cli_serve::<_, G16, Ark>(p, hash, sub_matches),
+            ProgEnum::Bw6_761Program(p) => cli_serve::<_, G16, Ark>(p, hash, sub_matches),
+            _ => Err(parameters.unsupported_curve()),
+        },
+        #[cfg(feature = "ark")]
+        Parameters(BackendParameter::Ark, _, SchemeParameter::GM17) => match prog {
+            ProgEnum::Bn128Program(p) => cli_serve::<_, GM17, Ark>(p, hash, sub_matches),
+            ProgEnum::Bls12_381Program(p) => cli_serve::<_, GM17, Ark>(p, hash, sub_matches),
+            ProgEnum::Bls12_377Program(p) => cli_serve::<_, GM17, Ark>(p, hash, sub_matches),
+            ProgEnum::Bw6_761Program(p) => cli_serve::<_, GM17, Ark>(p, hash, sub_matches),
+            _ => Err(parameters.unsupported_curve()),
+        },
+        #[cfg(feature = "ark")]
+        Parameters(BackendParameter::Ark, _, SchemeParameter::MARLIN) => match prog {
+            ProgEnum::Bn128Program(p) => cli_serve::<_, Marlin, Ark>(p, hash, sub_matches),
+            ProgEnum::Bls12_381Program(p) => cli_serve::<_, Marlin, Ark>(p, hash, sub_matches),
+            ProgEnum::Bls12_377Program(p) => cli_serve::<_, Marlin, Ark>(p, hash, sub_matches),
+            ProgEnum::Bw6_761Program(p) => cli_serve::<_, Marlin, Ark>(p, hash, sub_matches),
+            _ => Err(parameters.unsupported_curve()),
+        },
+        _ => Err(parameters.unsupported_curve()),
+    }
+}
+
+fn cli_serve<'a, T: Field, S: Scheme<T>, B: Backend<T, S>>(
+    program: ir::Prog<'a, T>,
+    hash: Option<ProgramHash>,
+    sub_matches: &ArgMatches,
+) -> Result<(), String> {
+    let abi_path = Path::new(sub_matches.value_of("abi-spec").unwrap());
+    let abi_file = File::open(&abi_path)
+        .map_err(|why| format!("Could not open {}: {}", abi_path.display(), why))?;
+    let abi: Abi = serde_json::from_reader(BufReader::new(abi_file))
+        .map_err(|why| format!("Could not parse {}: {}", abi_path.display(), why))?;
+
+    let pk_path = Path::new(sub_matches.value_of("proving-key-path").unwrap());
+    let pk_file = File::open(&pk_path)
+        .map_err(|why| format!("Could not open {}: {}", pk_path.display(), why))?;
+
+    let (pk, pk_hash) = read_proving_key(BufReader::new(pk_file))
+        .map_err(|why| format!("Could not read {}: {}", pk_path.display(), why))?;
+
+    // the proving key is deserialized once and shared by all requests
+    let pk = B::read_proving_key(&pk)
+        .map_err(|why| format!("Could not read {}: {}", pk_path.display(), why))?;
+
+    check_hash(
+        (
+            hash,
+            &format!("the program `{}`", sub_matches.value_of("input").unwrap()),
+        ),
+        (pk_hash, &format!("the proving key `{}`", pk_path.display())),
+        sub_matches.is_present("force"),
+    )?;
+
+    let threads = sub_matches
+        .value_of("threads")
+        .unwrap()
+        .parse::<usize>()
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or_else(|| String::from("`--threads` should be a positive integer"))?;
+
+    // the private return values are not sent back to the requester
+    let server = Server::<T, S, B>::new(program, abi.signature().output(abi.public_output()), pk);
+
+    // stdout is reserved for responses, so status messages go to stderr
+    match sub_matches.value_of("socket") {
+        None => {
+            eprintln!("Serving requests from stdin...");
+            server
+                .serve(stdin().lock(), stdout(), threads)
+                .map_err(|why| format!("Could not serve requests: {}", why))
+        }
+        #[cfg(unix)]
+        Some(path) => {
+            use std::os::unix::net::UnixListener;
+
+            let listener = UnixListener::bind(path)
+                .map_err(|why| format!("Could not bind {}: {}", path, why))?;
+            eprintln!("Serving requests on {}...", path);
+
+            for stream in listener.incoming() {
+                let stream = stream.map_err(|why| format!("Connection failed: {}", why))?;
+                let reader = stream
+                    .try_clone()
+                    .map_err(|why| format!("Connection failed: {}", why))?;
+
+                // errors on one connection should not bring the server down
+                if let Err(why) = server.serve(BufReader::new(reader), stream, threads) {
+                    eprintln!("Connection closed: {}", why);
+                }
+            }
+
+            Ok(())
+        }
+        #[cfg(not(unix))]
+        Some(_) => Err(String::from(
+            "Unix sockets are not supported on this platform",
+        )),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Request {
+    #[serde(default = "default_version")]
+    version: u32,
+    #[serde(default)]
+    id: Option<Value>,
+    inputs: Value,
+}
+
+fn default_version() -> u32 {
+    SERVE_API_VERSION
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<Value>,
+    #[serde(flatten)]
+    result: Outcome,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum Outcome {
+    Success {
+        witness_outputs: Value,
+        proof: Value,
+    },
+    Failure {
+        error: ServeError,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct ServeError {
+    code: &'static str,
+    message: String,
+}
+
+impl ServeError {
+    fn new<M: Into<String>>(code: &'static str, message: M) -> Self {
+        ServeError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Computes witnesses and proofs for a program whose artifacts were loaded once
+pub struct Server<'a, T: Field, S: Scheme<T>, B: Backend<T, S>> {
+    program: ir::Prog<'a, T>,
+    signature: ConcreteSignature,
+    proving_key: B::ProvingKey,
+    hash: ProgramHash,
+    backend: PhantomData<fn() -> (S, B)>,
+}
+
+impl<'a, T: Field, S: Scheme<T>, B: Backend<T, S>> Server<'a, T, S, B> {
+    pub fn new(
+        program: ir::Prog<'a, T>,
+        signature: ConcreteSignature,
+        proving_key: B::ProvingKey,
+    ) -> Self {
+        Server {
+            hash: program.hash(),
+            program,
+            signature,
+            proving_key,
+            backend: PhantomData,
+        }
+    }
+
+    /// Answer each line of `input` with a line on `output`.
+    ///
+    /// With more than one thread, independent requests are processed in parallel, but responses
+    /// are still written in the order of the requests.
+    pub fn serve<R: BufRead, W: Write + Send>(
+        &self,
+        input: R,
+        mut output: W,
+        threads: usize,
+    ) -> std::io::Result<()> {
+        if threads <= 1 {
+            for line in input.lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                writeln!(output, "{}", self.handle(&line))?;
+                output.flush()?;
+            }
+            return Ok(());
+        }
+
+        let (job_sender, job_receiver) = mpsc::channel::<(usize, String)>();
+        let (response_sender, response_receiver) = mpsc::channel::<(usize, String)>();
+        let job_receiver = Mutex::new(job_receiver);
+
+        std::thread::scope(|scope| {
+            for _ in 0..threads {
+                let response_sender = response_sender.clone();
+                let job_receiver = &job_receiver;
+                scope.spawn(move || loop {
+                    let job = job_receiver.lock().unwrap().recv();
+                    match job {
+                        Ok((index, line)) => {
+                            if response_sender.send((index, self.handle(&line))).is_err() {
+                                break;
+                            }
+                        }
+                        Err(_) => break,
+                    }
+                });
+            }
+            drop(response_sender);
+
+            let writer = scope.spawn(move || -> std::io::Result<()> {
+                let mut pending = BTreeMap::new();
+                let mut next = 0;
+                for (index, response) in response_receiver {
+                    pending.insert(index, response);
+                    while let Some(response) = pending.remove(&next) {
+                        writeln!(output, "{}", response)?;
+                        output.flush()?;
+                        next += 1;
+                    }
+                }
+                Ok(())
+            });
+
+            let read = input
+                .lines()
+                .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+                .enumerate()
+                .try_for_each(|(index, line)| -> std::io::Result<()> {
+                    // a send error means the writer stopped, which it reports itself
+                    let _ = job_sender.send((index, line?));
+                    Ok(())
+                });
+            drop(job_sender);
+
+            let written = writer.join().unwrap();
+            read.and(written)
+        })
+    }
+
+    /// Process a single request, returning the serialized response
+    pub fn handle(&self, request: &str) -> String {
+        let response = match serde_json::from_str::<Request>(request) {
+            Ok(request) => Response {
+                version: SERVE_API_VERSION,
+                result: match self.process(&request) {
+                    Ok((witness_outputs, proof)) => Outcome::Success {
+                        witness_outputs,
+                        proof,
+                    },
+                    Err(error) => Outcome::Failure { error },
+                },
+                id: request.id,
+            },
+            Err(why) => Response {
+                version: SERVE_API_VERSION,
+                id: None,
+                result: Outcome::Failure {
+                    error: ServeError::new("malformed_request", why.to_string()),
+                },
+            },
+        };
+
+        serde_json::to_string(&response).unwrap()
+    }
+
+    fn process(&self, request: &Request) -> Result<(Value, Value), ServeError> {
+        if request.version != SERVE_API_VERSION {
+            return Err(ServeError::new(
+                "unsupported_version",
+                format!(
+                    "Unsupported request version {}, expected {}",
+                    request.version, SERVE_API_VERSION
+                ),
+            ));
+        }
+
+        let inputs = match &request.inputs {
+            Value::Array(values) => {
+                zokrates_abi::parse_strict_json(values.clone(), self.signature.inputs.clone())
+                    .map_err(|why| ServeError::new("invalid_inputs", why.to_string()))
+            }
+            v => Err(ServeError::new(
+                "invalid_inputs",
+                format!("Expected an array of values, found `{}`", v),
+            )),
+        }?;
+
+        let interpreter = zokrates_interpreter::Interpreter::default();
+
+        let witness = interpreter
+            .execute(self.program.iter(), &inputs.encode())
+            .map_err(|why| ServeError::new("execution_failed", why.to_string()))?;
+
+        let witness_outputs =
+            zokrates_abi::Value::decode(witness.return_values(), *self.signature.output.clone())
+                .into_serde_json();
+
+        let proof = B::generate_proof_with_key(
+            self.program.iter(),
+            witness,
+            &self.proving_key,
+            &mut StdRng::from_entropy(),
+        );
+
+        let proof = serde_json::to_value(
+            &TaggedProof::<T, S>::new(proof.proof, proof.inputs)
+                .with_hash(Some(self.hash))
+                .with_public_layout(self.program.public_layout.clone()),
+        )
+        .unwrap();
+
+        Ok((witness_outputs, proof))
+    }
+}
+
+#[cfg(all(test, feature = "ark"))]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use typed_arena::Arena;
+    use zokrates_common::CompileConfig;
+    use zokrates_core::compile::compile;
+    use zokrates_field::Bn128Field;
+
+    fn with_server<F: FnOnce(&Server<Bn128Field, G16, Ark>)>(f: F) {
+        let source = r#"
+            def main(field a, private field b) -> field {
+                assert(a != 0);
+                return a * b;
+            }
+        "#
+        .to_string();
+
+        let arena = Arena::new();
+        let artifacts = compile::<Bn128Field, std::io::Error>(
+            source,
+            "main.zok".into(),
+            None,
+            CompileConfig::default(),
+            &arena,
+        )
+        .unwrap()
+        .collect();
+
+        let signature = artifacts
+            .abi()
+            .signature()
+            .output(artifacts.abi().public_output());
+        let program = artifacts.prog();
+        let keypair = <Ark as NonUniversalBackend<Bn128Field, G16>>::setup(
+            program.clone(),
+            &mut StdRng::from_seed([0; 32]),
+        );
+
+        let proving_key = <Ark as Backend<Bn128Field, G16>>::read_proving_key(&keypair.pk).unwrap();
+
+        f(&Server::new(program, signature, proving_key))
+    }
+
+    fn responses(
+        server: &Server<Bn128Field, G16, Ark>,
+        requests: &str,
+        threads: usize,
+    ) -> Vec<Value> {
+        let mut output = vec![];
+        server
+            .serve(Cursor::new(requests), &mut output, threads)
+            .unwrap();
+
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    const REQUESTS: &str = r#"{"id": 1, "inputs": ["2", "3"]}
+{"id": 2, "inputs": ["0", "3"]}
+{"id": 3, "inputs": [true, "3"]}
+{"id": 4, "inputs": ["2", "3"
+{"version": 42, "inputs": []}
+
+{"version": 1, "id": "six", "inputs": ["4", "5"]}
+"#;
+
+    fn check(responses: Vec<Value>) {
+        assert_eq!(responses.len(), 6);
+
+        for r in &responses {
+            assert_eq!(r["version"], SERVE_API_VERSION);
+        }
+
+        // valid request
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[0]["witness_outputs"], "6");
+        assert_eq!(responses[0]["proof"]["inputs"].as_array().unwrap().len(), 2);
+        assert!(responses[0].get("error").is_none());
+
+        // failing assertion
+        assert_eq!(responses[1]["id"], 2);
+        assert_eq!(responses[1]["error"]["code"], "execution_failed");
+
+        // wrongly typed input
+        assert_eq!(responses[2]["id"], 3);
+        assert_eq!(responses[2]["error"]["code"], "invalid_inputs");
+
+        // malformed json
+        assert!(responses[3].get("id").is_none());
+        assert_eq!(responses[3]["error"]["code"], "malformed_request");
+
+        // unknown version
+        assert_eq!(responses[4]["error"]["code"], "unsupported_version");
+
+        assert_eq!(responses[5]["id"], "six");
+        assert_eq!(responses[5]["witness_outputs"], "20");
+    }
+
+    #[test]
+    fn sequential() {
+        with_server(|server| check(responses(server, REQUESTS, 1)));
+    }
+
+    #[test]
+    fn parallel() {
+        with_server(|server| check(responses(server, REQUESTS, 3)));
+    }
+}
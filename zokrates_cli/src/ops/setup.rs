@@ -1,4 +1,8 @@
 use crate::cli_constants;
+use crate::output::{output_format_arg, CliOutput};
+use crate::program::{
+    bindings_arg, check_hash, entry_arg, force_arg, read_abi, read_program, resolve_bindings,
+};
 use clap::{App, Arg, ArgMatches, SubCommand};
 use rand_0_8::rngs::StdRng;
 use rand_0_8::SeedableRng;
@@ -8,7 +12,8 @@ use std::io::{BufReader, Write};
 use std::path::Path;
 #[cfg(feature = "ark")]
 use zokrates_ark::Ark;
-use zokrates_ast::ir::{self, ProgEnum};
+use zokrates_ast::ir::{self, ConstraintSystemHasher, ProgEnum, ProgramHash};
+use zokrates_ast::typed::abi::Abi;
 #[cfg(feature = "bellman")]
 use zokrates_bellman::Bellman;
 use zokrates_common::constants;
@@ -51,6 +56,17 @@ pub fn subcommand() -> App<'static, 'static> {
                 .required(false)
                 .default_value(cli_constants::VERIFICATION_KEY_DEFAULT_PATH),
         )
+        .arg(entry_arg())
+        .arg(force_arg())
+        .arg(
+            Arg::with_name("abi-spec")
+                .long("abi-spec")
+                .help("Path of the ABI specification, whose public part is added to the verification key")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+                .default_value(cli_constants::ABI_SPEC_DEFAULT_PATH),
+        )
         .arg(
             Arg::with_name("backend")
                 .short("b")
@@ -89,16 +105,21 @@ pub fn subcommand() -> App<'static, 'static> {
                 .takes_value(true)
                 .required(false),
         )
+        .arg(bindings_arg())
+        .arg(output_format_arg())
 }
 
 pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
+    let mut output = CliOutput::new("setup", sub_matches);
+    let res = setup(sub_matches, &mut output);
+    output.finish(res)
+}
+
+fn setup(sub_matches: &ArgMatches, output: &mut CliOutput) -> Result<(), String> {
     // read compiled program
     let path = Path::new(sub_matches.value_of("input").unwrap());
-    let file =
-        File::open(&path).map_err(|why| format!("Couldn't open {}: {}", path.display(), why))?;
-
-    let mut reader = BufReader::new(file);
-    let prog = ProgEnum::deserialize(&mut reader)?;
+    let mut reader = read_program(path, sub_matches.value_of("entry"))?;
+    let (prog, hash) = ProgEnum::deserialize_with_hash(&mut reader)?;
 
     let parameters = Parameters::try_from((
         sub_matches.value_of("backend").unwrap(),
@@ -110,38 +131,44 @@ pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
         #[cfg(feature = "bellman")]
         Parameters(BackendParameter::Bellman, _, SchemeParameter::G16) => match prog {
             ProgEnum::Bn128Program(p) => {
-                cli_setup_non_universal::<_, _, G16, Bellman>(p, sub_matches)
+                cli_setup_non_universal::<_, _, G16, Bellman>(p, hash, sub_matches, output)
             }
             ProgEnum::Bls12_381Program(p) => {
-                cli_setup_non_universal::<_, _, G16, Bellman>(p, sub_matches)
+                cli_setup_non_universal::<_, _, G16, Bellman>(p, hash, sub_matches, output)
             }
             _ => unreachable!(),
         },
         #[cfg(feature = "ark")]
         Parameters(BackendParameter::Ark, _, SchemeParameter::G16) => match prog {
-            ProgEnum::Bn128Program(p) => cli_setup_non_universal::<_, _, G16, Ark>(p, sub_matches),
+            ProgEnum::Bn128Program(p) => {
+                cli_setup_non_universal::<_, _, G16, Ark>(p, hash, sub_matches, output)
+            }
             ProgEnum::Bls12_381Program(p) => {
-                cli_setup_non_universal::<_, _, G16, Ark>(p, sub_matches)
+                cli_setup_non_universal::<_, _, G16, Ark>(p, hash, sub_matches, output)
             }
             ProgEnum::Bls12_377Program(p) => {
-                cli_setup_non_universal::<_, _, G16, Ark>(p, sub_matches)
+                cli_setup_non_universal::<_, _, G16, Ark>(p, hash, sub_matches, output)
             }
             ProgEnum::Bw6_761Program(p) => {
-                cli_setup_non_universal::<_, _, G16, Ark>(p, sub_matches)
+                cli_setup_non_universal::<_, _, G16, Ark>(p, hash, sub_matches, output)
             }
+            _ => unreachable!(),
         },
         #[cfg(feature = "ark")]
         Parameters(BackendParameter::Ark, _, SchemeParameter::GM17) => match prog {
-            ProgEnum::Bn128Program(p) => cli_setup_non_universal::<_, _, GM17, Ark>(p, sub_matches),
+            ProgEnum::Bn128Program(p) => {
+                cli_setup_non_universal::<_, _, GM17, Ark>(p, hash, sub_matches, output)
+            }
             ProgEnum::Bls12_381Program(p) => {
-                cli_setup_non_universal::<_, _, GM17, Ark>(p, sub_matches)
+                cli_setup_non_universal::<_, _, GM17, Ark>(p, hash, sub_matches, output)
             }
             ProgEnum::Bls12_377Program(p) => {
-                cli_setup_non_universal::<_, _, GM17, Ark>(p, sub_matches)
+                cli_setup_non_universal::<_, _, GM17, Ark>(p, hash, sub_matches, output)
             }
             ProgEnum::Bw6_761Program(p) => {
-                cli_setup_non_universal::<_, _, GM17, Ark>(p, sub_matches)
+                cli_setup_non_universal::<_, _, GM17, Ark>(p, hash, sub_matches, output)
             }
+            _ => unreachable!(),
         },
         #[cfg(feature = "ark")]
         Parameters(BackendParameter::Ark, _, SchemeParameter::MARLIN) => {
@@ -160,17 +187,18 @@ pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
 
             match prog {
                 ProgEnum::Bn128Program(p) => {
-                    cli_setup_universal::<_, _, Marlin, Ark>(p, setup, sub_matches)
+                    cli_setup_universal::<_, _, Marlin, Ark>(p, hash, setup, sub_matches, output)
                 }
                 ProgEnum::Bls12_381Program(p) => {
-                    cli_setup_universal::<_, _, Marlin, Ark>(p, setup, sub_matches)
+                    cli_setup_universal::<_, _, Marlin, Ark>(p, hash, setup, sub_matches, output)
                 }
                 ProgEnum::Bls12_377Program(p) => {
-                    cli_setup_universal::<_, _, Marlin, Ark>(p, setup, sub_matches)
+                    cli_setup_universal::<_, _, Marlin, Ark>(p, hash, setup, sub_matches, output)
                 }
                 ProgEnum::Bw6_761Program(p) => {
-                    cli_setup_universal::<_, _, Marlin, Ark>(p, setup, sub_matches)
+                    cli_setup_universal::<_, _, Marlin, Ark>(p, hash, setup, sub_matches, output)
                 }
+                _ => unreachable!(),
             }
         }
         _ => unreachable!(),
@@ -185,9 +213,11 @@ fn cli_setup_non_universal<
     B: NonUniversalBackend<T, S>,
 >(
     program: ir::ProgIterator<'a, T, I>,
+    hash: Option<ProgramHash>,
     sub_matches: &ArgMatches,
+    output: &mut CliOutput,
 ) -> Result<(), String> {
-    println!("Performing setup...");
+    output.message("Performing setup...");
 
     // get paths for proving and verification keys
     let pk_path = Path::new(sub_matches.value_of("proving-key-path").unwrap());
@@ -198,31 +228,87 @@ fn cli_setup_non_universal<
         .map(get_rng_from_entropy)
         .unwrap_or_else(StdRng::from_entropy);
 
+    let abi = read_public_abi(sub_matches, program.public_count(), output)?;
+    let (bound, bindings) = resolve_bindings::<T>(
+        sub_matches.value_of("bindings"),
+        abi.as_ref(),
+        &program.arguments,
+    )?;
+
+    // hash the program to check its integrity, and the program with its inputs bound to tag the keys
+    let mut hasher = ConstraintSystemHasher::new(
+        &program.arguments,
+        program.return_count,
+        &program.public_layout,
+    );
+    let public_layout = program.public_layout.clone();
+    let program = ir::ProgIterator::new(
+        program.arguments,
+        program.statements.inspect(|s| hasher.update(s)),
+        program.return_count,
+    )
+    .with_public_layout(program.public_layout)
+    .bind(bound);
+
+    // count the constraints while the setup consumes them
+    let mut bound_hasher = (!bindings.is_empty()).then(|| {
+        ConstraintSystemHasher::new(
+            &program.arguments,
+            program.return_count,
+            &program.public_layout,
+        )
+    });
+    let mut constraint_count = 0;
+    let program = ir::ProgIterator::new(
+        program.arguments,
+        program.statements.into_iter().inspect(|s| {
+            if let Some(hasher) = bound_hasher.as_mut() {
+                hasher.update(s);
+            }
+            if let ir::Statement::Constraint(..) = s {
+                constraint_count += 1;
+            }
+        }),
+        program.return_count,
+    )
+    .with_public_layout(program.public_layout);
+
     // run setup phase
-    let keypair = B::setup(program, &mut rng);
+    let keypair = output.time("setup", || B::setup(program, &mut rng));
+
+    let computed_hash = hasher.finalize();
+    output.constraints(constraint_count);
+    check_integrity(hash, computed_hash, sub_matches)?;
+    let computed_hash = bound_hasher
+        .map(|hasher| hasher.finalize())
+        .unwrap_or(computed_hash);
 
     // write verification key
     let mut vk_file = File::create(vk_path)
         .map_err(|why| format!("Could not create {}: {}", vk_path.display(), why))?;
     vk_file
         .write_all(
-            serde_json::to_string_pretty(&TaggedVerificationKey::<T, S>::new(keypair.vk))
-                .unwrap()
-                .as_bytes(),
+            serde_json::to_string_pretty(
+                &tag_verification_key::<T, S>(keypair.vk, abi)
+                    .with_hash(computed_hash)
+                    .with_public_layout(public_layout)
+                    .with_bindings(bindings),
+            )
+            .unwrap()
+            .as_bytes(),
         )
         .map_err(|why| format!("Could not write to {}: {}", vk_path.display(), why))?;
 
-    println!("Verification key written to '{}'", vk_path.display());
+    output.written("verification-key", "Verification key", vk_path);
 
     // write proving key
     let mut pk_file = File::create(pk_path)
         .map_err(|why| format!("Could not create {}: {}", pk_path.display(), why))?;
-    pk_file
-        .write_all(keypair.pk.as_ref())
+    write_proving_key(&mut pk_file, keypair.pk.as_ref(), &computed_hash)
         .map_err(|why| format!("Could not write to {}: {}", pk_path.display(), why))?;
 
-    println!("Proving key written to '{}'", pk_path.display());
-    println!("Setup completed");
+    output.written("proving-key", "Proving key", pk_path);
+    output.message("Setup completed");
 
     Ok(())
 }
@@ -235,40 +321,176 @@ fn cli_setup_universal<
     B: UniversalBackend<T, S>,
 >(
     program: ir::ProgIterator<'a, T, I>,
+    hash: Option<ProgramHash>,
     srs: Vec<u8>,
     sub_matches: &ArgMatches,
+    output: &mut CliOutput,
 ) -> Result<(), String> {
-    println!("Performing setup...");
+    output.message("Performing setup...");
 
     // get paths for proving and verification keys
     let pk_path = Path::new(sub_matches.value_of("proving-key-path").unwrap());
     let vk_path = Path::new(sub_matches.value_of("verification-key-path").unwrap());
 
+    let abi = read_public_abi(sub_matches, program.public_count(), output)?;
+    let (bound, bindings) = resolve_bindings::<T>(
+        sub_matches.value_of("bindings"),
+        abi.as_ref(),
+        &program.arguments,
+    )?;
+
+    // hash the program to check its integrity, and the program with its inputs bound to tag the keys
+    let mut hasher = ConstraintSystemHasher::new(
+        &program.arguments,
+        program.return_count,
+        &program.public_layout,
+    );
+    let public_layout = program.public_layout.clone();
+    let program = ir::ProgIterator::new(
+        program.arguments,
+        program.statements.inspect(|s| hasher.update(s)),
+        program.return_count,
+    )
+    .with_public_layout(program.public_layout)
+    .bind(bound);
+
+    // count the constraints while the setup consumes them
+    let mut bound_hasher = (!bindings.is_empty()).then(|| {
+        ConstraintSystemHasher::new(
+            &program.arguments,
+            program.return_count,
+            &program.public_layout,
+        )
+    });
+    let mut constraint_count = 0;
+    let program = ir::ProgIterator::new(
+        program.arguments,
+        program.statements.into_iter().inspect(|s| {
+            if let Some(hasher) = bound_hasher.as_mut() {
+                hasher.update(s);
+            }
+            if let ir::Statement::Constraint(..) = s {
+                constraint_count += 1;
+            }
+        }),
+        program.return_count,
+    )
+    .with_public_layout(program.public_layout);
+
     // run setup phase
-    let keypair = B::setup(srs, program)?;
+    let keypair = output.time("setup", || B::setup(srs, program))?;
+
+    let computed_hash = hasher.finalize();
+    output.constraints(constraint_count);
+    check_integrity(hash, computed_hash, sub_matches)?;
+    let computed_hash = bound_hasher
+        .map(|hasher| hasher.finalize())
+        .unwrap_or(computed_hash);
 
     // write verification key
     let mut vk_file = File::create(vk_path)
         .map_err(|why| format!("Could not create {}: {}", vk_path.display(), why))?;
     vk_file
         .write_all(
-            serde_json::to_string_pretty(&TaggedVerificationKey::<T, S>::new(keypair.vk))
-                .unwrap()
-                .as_bytes(),
+            serde_json::to_string_pretty(
+                &tag_verification_key::<T, S>(keypair.vk, abi)
+                    .with_hash(computed_hash)
+                    .with_public_layout(public_layout)
+                    .with_bindings(bindings),
+            )
+            .unwrap()
+            .as_bytes(),
         )
         .map_err(|why| format!("Could not write to {}: {}", vk_path.display(), why))?;
 
-    println!("Verification key written to '{}'", vk_path.display());
+    output.written("verification-key", "Verification key", vk_path);
 
     // write proving key
     let mut pk_file = File::create(pk_path)
         .map_err(|why| format!("Could not create {}: {}", pk_path.display(), why))?;
-    pk_file
-        .write_all(keypair.pk.as_ref())
+    write_proving_key(&mut pk_file, keypair.pk.as_ref(), &computed_hash)
         .map_err(|why| format!("Could not write to {}: {}", pk_path.display(), why))?;
 
-    println!("Proving key written to '{}'", pk_path.display());
-    println!("Setup completed");
+    output.written("proving-key", "Proving key", pk_path);
+    output.message("Setup completed");
 
     Ok(())
 }
+
+// check that the constraint system of the program matches the hash computed when it was compiled
+fn check_integrity(
+    stored: Option<ProgramHash>,
+    computed: ProgramHash,
+    sub_matches: &ArgMatches,
+) -> Result<(), String> {
+    let path = sub_matches.value_of("input").unwrap();
+    check_hash(
+        (stored, &format!("the header of the program `{}`", path)),
+        (
+            Some(computed),
+            &format!("the constraint system of `{}`", path),
+        ),
+        sub_matches.is_present("force"),
+    )
+}
+
+pub(crate) fn tag_verification_key<T: Field, S: Scheme<T>>(
+    vk: S::VerificationKey,
+    abi: Option<Abi>,
+) -> TaggedVerificationKey<T, S> {
+    let vk = TaggedVerificationKey::new(vk);
+    match abi {
+        Some(abi) => vk.with_abi(abi),
+        None => vk,
+    }
+}
+
+// read the ABI specification if it describes `public_count` public values. A missing or stale specification at the
+// default path is ignored, as it may come from another program
+fn read_public_abi(
+    sub_matches: &ArgMatches,
+    public_count: usize,
+    output: &mut CliOutput,
+) -> Result<Option<Abi>, String> {
+    let path = Path::new(sub_matches.value_of("abi-spec").unwrap());
+    let explicit = sub_matches.occurrences_of("abi-spec") > 0;
+
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(_) if !explicit => return Ok(None),
+        Err(why) => return Err(format!("Could not open {}: {}", path.display(), why)),
+    };
+
+    let abi = read_abi(BufReader::new(file), sub_matches.value_of("entry"))
+        .map_err(|why| format!("Could not deserialize {}: {}", path.display(), why))
+        .and_then(|abi| {
+            let abi_count = abi
+                .public_inputs()
+                .iter()
+                .map(|i| i.ty.get_primitive_count())
+                .sum::<usize>()
+                + abi.public_output().get_primitive_count();
+
+            match abi_count == public_count {
+                true => Ok(abi),
+                false => Err(format!(
+                    "The ABI specification {} does not match the program: it describes {} public values, the program has {}",
+                    path.display(),
+                    abi_count,
+                    public_count
+                )),
+            }
+        });
+
+    match abi {
+        Ok(abi) => Ok(Some(abi)),
+        Err(message) if !explicit => {
+            output.warning(format!(
+                "{}, it is not added to the verification key",
+                message
+            ));
+            Ok(None)
+        }
+        Err(message) => Err(message),
+    }
+}
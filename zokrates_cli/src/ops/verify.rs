@@ -1,16 +1,16 @@
 use crate::cli_constants;
+use crate::output::{output_format_arg, CliOutput};
+use crate::program::{check_hash, force_arg};
 use clap::{App, Arg, ArgMatches, SubCommand};
 use std::convert::TryFrom;
-use std::fs::File;
-use std::io::BufReader;
 use std::path::Path;
 #[cfg(feature = "ark")]
 use zokrates_ark::Ark;
+use zokrates_ast::ir::ProgramHash;
 #[cfg(feature = "bellman")]
 use zokrates_bellman::Bellman;
 use zokrates_common::constants;
 use zokrates_common::helpers::*;
-use zokrates_field::{Bls12_377Field, Bls12_381Field, Bn128Field, Bw6_761Field, Field};
 #[cfg(any(feature = "bellman", feature = "ark"))]
 use zokrates_proof_systems::*;
 
@@ -47,25 +47,31 @@ pub fn subcommand() -> App<'static, 'static> {
                 .possible_values(cli_constants::BACKENDS)
                 .default_value(constants::ARK),
         )
+        .arg(force_arg())
+        .arg(output_format_arg())
 }
 
 pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
+    let mut output = CliOutput::new("verify", sub_matches);
+    let res = verify(sub_matches, &mut output);
+    output.finish(res)
+}
+
+fn verify(sub_matches: &ArgMatches, output: &mut CliOutput) -> Result<(), String> {
     let vk_path = Path::new(sub_matches.value_of("verification-key-path").unwrap());
-    let vk_file = File::open(&vk_path)
+    let vk_bytes = std::fs::read(&vk_path)
         .map_err(|why| format!("Could not open {}: {}", vk_path.display(), why))?;
 
     // deserialize vk to JSON
-    let vk_reader = BufReader::new(vk_file);
-    let vk: serde_json::Value = serde_json::from_reader(vk_reader)
+    let vk: serde_json::Value = serde_json::from_slice(&vk_bytes)
         .map_err(|why| format!("Could not deserialize verification key: {}", why))?;
 
     let proof_path = Path::new(sub_matches.value_of("proof-path").unwrap());
-    let proof_file = File::open(&proof_path)
+    let proof_bytes = std::fs::read(&proof_path)
         .map_err(|why| format!("Could not open {}: {}", proof_path.display(), why))?;
 
     // deserialize proof to JSON
-    let proof_reader = BufReader::new(proof_file);
-    let proof: serde_json::Value = serde_json::from_reader(proof_reader)
+    let proof: serde_json::Value = serde_json::from_slice(&proof_bytes)
         .map_err(|why| format!("Could not deserialize proof: {}", why))?;
 
     // extract curve and scheme parameters from both
@@ -104,92 +110,125 @@ pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
         ));
     }
 
-    let scheme = vk_scheme;
-    let curve = vk_curve;
+    check_hash(
+        (
+            read_hash(&vk, "verification key")?,
+            &format!("the verification key `{}`", vk_path.display()),
+        ),
+        (
+            read_hash(&proof, "proof")?,
+            &format!("the proof `{}`", proof_path.display()),
+        ),
+        sub_matches.is_present("force"),
+    )?;
 
-    // determine parameters based on that
-    let parameters =
-        Parameters::try_from((sub_matches.value_of("backend").unwrap(), curve, scheme))?;
+    check_public_layout(&vk, &proof)?;
 
-    match parameters {
-        #[cfg(feature = "bellman")]
-        Parameters(BackendParameter::Bellman, CurveParameter::Bn128, SchemeParameter::G16) => {
-            cli_verify::<Bn128Field, G16, Bellman>(vk, proof)
-        }
+    // the proof is verified for the public inputs it holds
+    let inputs: Vec<String> =
+        serde_json::from_value(proof.get("inputs").cloned().unwrap_or_default())
+            .map_err(|why| format!("Could not deserialize proof: {}", why))?;
+    let (vk, proof) = (&vk_bytes[..], &proof_bytes[..]);
+
+    // the scheme and the curve are read from the artifacts, and checked to be supported by the backend
+    let parameters = Parameters::try_from((
+        sub_matches.value_of("backend").unwrap(),
+        vk_curve,
+        vk_scheme,
+    ))?;
+
+    match parameters.0 {
         #[cfg(feature = "bellman")]
-        Parameters(BackendParameter::Bellman, CurveParameter::Bls12_381, SchemeParameter::G16) => {
-            cli_verify::<Bls12_381Field, G16, Bellman>(vk, proof)
-        }
-        #[cfg(feature = "ark")]
-        Parameters(BackendParameter::Ark, CurveParameter::Bn128, SchemeParameter::G16) => {
-            cli_verify::<Bn128Field, G16, Ark>(vk, proof)
-        }
-        #[cfg(feature = "ark")]
-        Parameters(BackendParameter::Ark, CurveParameter::Bls12_381, SchemeParameter::G16) => {
-            cli_verify::<Bls12_381Field, G16, Ark>(vk, proof)
-        }
-        #[cfg(feature = "ark")]
-        Parameters(BackendParameter::Ark, CurveParameter::Bls12_377, SchemeParameter::G16) => {
-            cli_verify::<Bls12_377Field, G16, Ark>(vk, proof)
-        }
-        #[cfg(feature = "ark")]
-        Parameters(BackendParameter::Ark, CurveParameter::Bw6_761, SchemeParameter::G16) => {
-            cli_verify::<Bw6_761Field, G16, Ark>(vk, proof)
-        }
-        #[cfg(feature = "ark")]
-        Parameters(BackendParameter::Ark, CurveParameter::Bn128, SchemeParameter::GM17) => {
-            cli_verify::<Bn128Field, GM17, Ark>(vk, proof)
-        }
-        #[cfg(feature = "ark")]
-        Parameters(BackendParameter::Ark, CurveParameter::Bls12_381, SchemeParameter::GM17) => {
-            cli_verify::<Bls12_381Field, GM17, Ark>(vk, proof)
-        }
-        #[cfg(feature = "ark")]
-        Parameters(BackendParameter::Ark, CurveParameter::Bls12_377, SchemeParameter::GM17) => {
-            cli_verify::<Bls12_377Field, GM17, Ark>(vk, proof)
-        }
-        #[cfg(feature = "ark")]
-        Parameters(BackendParameter::Ark, CurveParameter::Bw6_761, SchemeParameter::GM17) => {
-            cli_verify::<Bw6_761Field, GM17, Ark>(vk, proof)
+        BackendParameter::Bellman => {
+            cli_verify::<Bellman>(vk_scheme, vk_curve, vk, proof, &inputs, output)
         }
         #[cfg(feature = "ark")]
-        Parameters(BackendParameter::Ark, CurveParameter::Bn128, SchemeParameter::MARLIN) => {
-            cli_verify::<Bn128Field, Marlin, Ark>(vk, proof)
-        }
-        #[cfg(feature = "ark")]
-        Parameters(BackendParameter::Ark, CurveParameter::Bls12_381, SchemeParameter::MARLIN) => {
-            cli_verify::<Bls12_381Field, Marlin, Ark>(vk, proof)
-        }
-        #[cfg(feature = "ark")]
-        Parameters(BackendParameter::Ark, CurveParameter::Bls12_377, SchemeParameter::MARLIN) => {
-            cli_verify::<Bls12_377Field, Marlin, Ark>(vk, proof)
-        }
-        #[cfg(feature = "ark")]
-        Parameters(BackendParameter::Ark, CurveParameter::Bw6_761, SchemeParameter::MARLIN) => {
-            cli_verify::<Bw6_761Field, Marlin, Ark>(vk, proof)
-        }
-        _ => unreachable!(),
+        BackendParameter::Ark => cli_verify::<Ark>(vk_scheme, vk_curve, vk, proof, &inputs, output),
+    }
+}
+
+// read the optional hash of the constraint system of a verification key or a proof
+pub(crate) fn read_hash(
+    value: &serde_json::Value,
+    name: &str,
+) -> Result<Option<ProgramHash>, String> {
+    value
+        .get("hash")
+        .map(|hash| {
+            hash.as_str()
+                .ok_or_else(|| "`hash` should be a string".to_string())?
+                .parse()
+                .map_err(|why| format!("Could not deserialize {}: {}", name, why))
+        })
+        .transpose()
+}
+
+// read the optional public layout of a verification key or a proof, which is empty for the default order
+pub(crate) fn read_public_layout(
+    value: &serde_json::Value,
+    name: &str,
+) -> Result<Vec<usize>, String> {
+    value
+        .get("public_layout")
+        .map(|layout| {
+            serde_json::from_value(layout.clone()).map_err(|why| {
+                format!(
+                    "Could not deserialize the public layout of the {}: {}",
+                    name, why
+                )
+            })
+        })
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
+// check that the proof orders its public inputs like the verification key expects
+fn check_public_layout(vk: &serde_json::Value, proof: &serde_json::Value) -> Result<(), String> {
+    let describe = |layout: &[usize]| match layout.is_empty() {
+        true => "the default order".to_string(),
+        false => format!("the order {:?}", layout),
+    };
+
+    let vk_layout = read_public_layout(vk, "verification key")?;
+    let proof_layout = read_public_layout(proof, "proof")?;
+
+    match vk_layout == proof_layout {
+        true => Ok(()),
+        false => Err(format!(
+            "Public layout mismatch: the verification key expects the public inputs in {} but the proof has them in {}",
+            describe(&vk_layout),
+            describe(&proof_layout)
+        )),
     }
 }
 
-fn cli_verify<T: Field, S: Scheme<T>, B: Backend<T, S>>(
-    vk: serde_json::Value,
-    proof: serde_json::Value,
+#[cfg(any(feature = "bellman", feature = "ark"))]
+fn cli_verify<B: BytesVerifier>(
+    scheme: &str,
+    curve: &str,
+    vk: &[u8],
+    proof: &[u8],
+    inputs: &[String],
+    output: &mut CliOutput,
 ) -> Result<(), String> {
-    // convert the JSON vk and proof to the correct types
-    let vk = serde_json::from_value(vk)
-        .map_err(|why| format!("Could not deserialize verification key: {}", why))?;
-    let proof: Proof<T, S> = serde_json::from_value(proof)
-        .map_err(|why| format!("Could not deserialize proof: {}", why))?;
+    output.message("Performing verification...");
+    let verified = output
+        .time("verification", || {
+            verify_bytes::<B>(scheme, curve, vk, proof, inputs)
+        })
+        .map_err(|e| e.to_string())?;
+    output.result("verified", verified);
 
-    println!("Performing verification...");
-    println!(
-        "{}",
-        match B::verify(vk, proof) {
-            true => "PASSED",
-            false => "FAILED",
+    match (verified, output.is_json()) {
+        (true, _) => {
+            output.message("PASSED");
+            Ok(())
         }
-    );
-
-    Ok(())
+        // in JSON, an invalid proof is a failure of the command
+        (false, true) => Err("The proof is not valid".to_string()),
+        (false, false) => {
+            output.message("FAILED");
+            Ok(())
+        }
+    }
 }
@@ -0,0 +1,142 @@
+use crate::cli_constants;
+use crate::ops::compile::directory;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+use typed_arena::Arena;
+use zokrates_common::helpers::CurveParameter;
+use zokrates_common::Resolver;
+use zokrates_core::compile::{compile_observed, CompileErrors, Observers};
+use zokrates_core::manifest::Manifest;
+use zokrates_field::{
+    Bls12_377Field, Bls12_381Field, Bn128Field, Bw6_761Field, Field, PallasField, VestaField,
+};
+use zokrates_fs_resolver::FileSystemResolver;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("verify-build")
+        .about("Compiles a program again as recorded in the manifest written by `compile --manifest`, and fails if a source or the constraint system changed")
+        .arg(
+            Arg::with_name("manifest")
+                .help("Path of the manifest")
+                .value_name("FILE")
+                .required(false)
+                .index(1)
+                .default_value(cli_constants::MANIFEST_DEFAULT_PATH),
+        )
+        .arg(
+            Arg::with_name("stdlib-path")
+                .long("stdlib-path")
+                .help("Path to the standard library")
+                .value_name("PATH")
+                .takes_value(true)
+                .required(false)
+                .env("ZOKRATES_STDLIB")
+                .default_value(cli_constants::DEFAULT_STDLIB_PATH.as_str()),
+        )
+}
+
+pub fn exec(sub_matches: &ArgMatches) -> Result<(), String> {
+    let stdlib_path = sub_matches.value_of("stdlib-path").unwrap();
+    match Path::new(stdlib_path).exists() {
+        true => Ok(()),
+        _ => Err(format!(
+            "Invalid standard library source path: {}",
+            stdlib_path
+        )),
+    }?;
+
+    let resolver = FileSystemResolver::with_stdlib_root(stdlib_path);
+
+    let manifest_path = Path::new(sub_matches.value_of("manifest").unwrap());
+    let manifest_file = File::open(manifest_path)
+        .map_err(|why| format!("Could not open {}: {}", manifest_path.display(), why))?;
+    let recorded: Manifest = serde_json::from_reader(BufReader::new(manifest_file))
+        .map_err(|why| format!("Could not read {}: {}", manifest_path.display(), why))?;
+
+    // the paths of the manifest are relative to its directory
+    let root = directory(manifest_path);
+
+    let current = match CurveParameter::try_from(recorded.curve.as_str())? {
+        CurveParameter::Bn128 => cli_verify_build::<Bn128Field>(&recorded, &root, &resolver),
+        CurveParameter::Bls12_377 => {
+            cli_verify_build::<Bls12_377Field>(&recorded, &root, &resolver)
+        }
+        CurveParameter::Bls12_381 => {
+            cli_verify_build::<Bls12_381Field>(&recorded, &root, &resolver)
+        }
+        CurveParameter::Bw6_761 => cli_verify_build::<Bw6_761Field>(&recorded, &root, &resolver),
+        CurveParameter::Pallas => cli_verify_build::<PallasField>(&recorded, &root, &resolver),
+        CurveParameter::Vesta => cli_verify_build::<VestaField>(&recorded, &root, &resolver),
+    }?;
+
+    let drift = recorded.drift(&current);
+
+    match drift.is_empty() {
+        true => {
+            println!(
+                "{} matches {}: constraint system {}",
+                recorded.source.display(),
+                manifest_path.display(),
+                current.constraint_system
+            );
+            Ok(())
+        }
+        false => Err(format!(
+            "{} drifted from {}:\n\n{}",
+            recorded.source.display(),
+            manifest_path.display(),
+            drift
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        )),
+    }
+}
+
+/// Compile the program of `manifest`, whose paths are relative to `root`, again, and return the manifest of this build
+fn cli_verify_build<T: Field>(
+    manifest: &Manifest,
+    root: &Path,
+    resolver: &dyn Resolver<io::Error>,
+) -> Result<Manifest, String> {
+    let path = root.join(&manifest.source);
+
+    let mut source = String::new();
+    File::open(&path)
+        .and_then(|file| BufReader::new(file).read_to_string(&mut source))
+        .map_err(|why| format!("Could not open {}: {}", path.display(), why))?;
+
+    let fmt_errors = |e: CompileErrors| {
+        format!(
+            "Compilation failed:\n\n{}",
+            e.0.iter()
+                .map(|e| format!("{}:{}", e.file().display(), e.value()))
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        )
+    };
+
+    let arena = Arena::new();
+    let (_, observations) = compile_observed::<T, _>(
+        source,
+        path,
+        Some(resolver),
+        manifest.config.clone(),
+        &arena,
+        Observers {
+            manifest: true,
+            ..Observers::default()
+        },
+    )
+    .map_err(fmt_errors)?;
+
+    observations
+        .manifest
+        .unwrap()
+        .relative_to(root)
+        .map_err(|why| format!("Could not locate the sources of the manifest: {}", why))
+}
@@ -0,0 +1,223 @@
+use clap::{Arg, ArgMatches};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// The version of the JSON object written by `--output-format json`, bumped on breaking changes
+pub const OUTPUT_SCHEMA_VERSION: u32 = 1;
+
+/// The argument selecting the format of the output of a command. It is not named `--output`, which `compute-witness`
+/// already uses for the path of the witness
+pub fn output_format_arg() -> Arg<'static, 'static> {
+    Arg::with_name("output-format")
+        .long("output-format")
+        .help("Format of the output. `json` writes a single JSON object to stdout once the command completes, on success and on failure")
+        .value_name("FORMAT")
+        .takes_value(true)
+        .possible_values(&["text", "json"])
+        .default_value("text")
+        .required(false)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Success,
+    Failure,
+}
+
+#[derive(Serialize)]
+struct WrittenFile {
+    kind: &'static str,
+    path: String,
+}
+
+#[derive(Serialize)]
+struct ErrorDetails {
+    message: String,
+}
+
+#[derive(Serialize)]
+struct Report {
+    version: u32,
+    command: &'static str,
+    status: Status,
+    /// The duration of each phase of the command, in seconds, along with the `total`
+    timings: BTreeMap<&'static str, f64>,
+    files: Vec<WrittenFile>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    constraints: Option<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+    /// Results specific to the command
+    #[serde(skip_serializing_if = "serde_json::Map::is_empty")]
+    result: serde_json::Map<String, serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ErrorDetails>,
+}
+
+/// The output of a command, either printed for humans as the command runs, or collected and written as a single JSON
+/// object when it completes
+pub struct CliOutput {
+    format: OutputFormat,
+    command: &'static str,
+    start: Instant,
+    timings: BTreeMap<&'static str, f64>,
+    files: Vec<WrittenFile>,
+    constraints: Option<usize>,
+    warnings: Vec<String>,
+    result: serde_json::Map<String, serde_json::Value>,
+}
+
+impl CliOutput {
+    pub fn new(command: &'static str, sub_matches: &ArgMatches) -> Self {
+        let format = match sub_matches.value_of("output-format") {
+            Some("json") => OutputFormat::Json,
+            _ => OutputFormat::Text,
+        };
+
+        CliOutput {
+            format,
+            command,
+            start: Instant::now(),
+            timings: BTreeMap::new(),
+            files: vec![],
+            constraints: None,
+            warnings: vec![],
+            result: serde_json::Map::new(),
+        }
+    }
+
+    pub fn is_json(&self) -> bool {
+        self.format == OutputFormat::Json
+    }
+
+    /// Print a message for humans, such as progress. Nothing is printed in JSON
+    pub fn message<S: AsRef<str>>(&self, message: S) {
+        if !self.is_json() {
+            println!("{}", message.as_ref());
+        }
+    }
+
+    pub fn warning(&mut self, warning: String) {
+        match self.format {
+            OutputFormat::Text => println!("Warning: {}", warning),
+            OutputFormat::Json => self.warnings.push(warning),
+        }
+    }
+
+    /// Record that a file of the given kind was written at `path`, and tell humans
+    pub fn written(&mut self, kind: &'static str, description: &str, path: &Path) {
+        self.message(format!("{} written to '{}'", description, path.display()));
+        self.file(kind, path);
+    }
+
+    /// Record that a file of the given kind was written at `path`, without telling humans
+    pub fn file(&mut self, kind: &'static str, path: &Path) {
+        self.files.push(WrittenFile {
+            kind,
+            path: path.display().to_string(),
+        });
+    }
+
+    pub fn constraints(&mut self, count: usize) {
+        self.constraints = Some(count);
+    }
+
+    /// Record a result specific to the command, only written in JSON
+    pub fn result<V: Serialize>(&mut self, key: &str, value: V) {
+        self.result
+            .insert(key.to_string(), serde_json::to_value(value).unwrap());
+    }
+
+    /// Run `f`, recording its duration as the given phase
+    pub fn time<R, F: FnOnce() -> R>(&mut self, phase: &'static str, f: F) -> R {
+        let start = Instant::now();
+        let res = f();
+        self.timing(phase, start.elapsed());
+        res
+    }
+
+    pub fn timing(&mut self, phase: &'static str, duration: Duration) {
+        self.timings.insert(phase, duration.as_secs_f64());
+    }
+
+    /// Where the logs of the program go: stdout for humans, stderr in JSON so that stdout only holds the report
+    pub fn log_stream(&self) -> Box<dyn Write> {
+        match self.format {
+            OutputFormat::Text => Box::new(io::stdout()),
+            OutputFormat::Json => Box::new(io::stderr()),
+        }
+    }
+
+    /// Complete the command with `result`. In JSON, the report is written and a failure is returned as an empty error,
+    /// which `main` does not print again
+    pub fn finish(mut self, result: Result<(), String>) -> Result<(), String> {
+        if !self.is_json() {
+            return result;
+        }
+
+        self.timings
+            .insert("total", self.start.elapsed().as_secs_f64());
+
+        let failed = result.is_err();
+        let report = Report {
+            version: OUTPUT_SCHEMA_VERSION,
+            command: self.command,
+            status: match failed {
+                true => Status::Failure,
+                false => Status::Success,
+            },
+            timings: self.timings,
+            files: self.files,
+            constraints: self.constraints,
+            warnings: self.warnings,
+            result: self.result,
+            error: result.err().map(|message| ErrorDetails { message }),
+        };
+
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+
+        match failed {
+            true => Err(String::new()),
+            false => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::App;
+
+    fn output(format: &str) -> CliOutput {
+        let matches = App::new("test")
+            .arg(output_format_arg())
+            .get_matches_from(vec!["test", "--output-format", format]);
+        CliOutput::new("test", &matches)
+    }
+
+    #[test]
+    fn finish() {
+        assert_eq!(output("text").finish(Ok(())), Ok(()));
+        assert_eq!(
+            output("text").finish(Err("failed".into())),
+            Err("failed".into())
+        );
+
+        // the error is part of the report, and is not printed again
+        assert_eq!(output("json").finish(Ok(())), Ok(()));
+        assert_eq!(
+            output("json").finish(Err("failed".into())),
+            Err(String::new())
+        );
+    }
+}
@@ -0,0 +1,238 @@
+use clap::Arg;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read};
+use std::path::Path;
+use zokrates_abi::Encode;
+use zokrates_ast::ir::container::extract_entry;
+use zokrates_ast::ir::{Parameter, ProgramHash, Variable, Witness};
+use zokrates_ast::typed::abi::Abi;
+use zokrates_field::Field;
+
+/// The argument selecting a program in a container compiled from several entry points
+pub fn entry_arg() -> Arg<'static, 'static> {
+    Arg::with_name("entry")
+        .long("entry")
+        .help("Name of the program to use, if the binary contains several programs")
+        .value_name("NAME")
+        .takes_value(true)
+        .required(false)
+}
+
+/// The argument skipping the comparison of the constraint system hashes of the artifacts
+pub fn force_arg() -> Arg<'static, 'static> {
+    Arg::with_name("force")
+        .long("force")
+        .help("Skip the check that the artifacts were generated for the same constraint system")
+        .required(false)
+}
+
+/// The argument giving the values of the inputs bound at setup
+pub fn bindings_arg() -> Arg<'static, 'static> {
+    Arg::with_name("bindings")
+        .long("bindings")
+        .help("Path of a JSON object mapping the inputs marked `#[bind_at_setup]` to their values, for example `{\"threshold\": \"42\"}`. Required by `setup`, after which `compute-witness` reads the values recorded in the verification key and `generate-proof` reads them from the witness")
+        .value_name("FILE")
+        .takes_value(true)
+        .required(false)
+}
+
+/// Check that two artifacts were generated for the same constraint system.
+/// Artifacts without a hash, for example generated by older versions, are not checked
+pub fn check_hash(
+    (left, left_source): (Option<ProgramHash>, &str),
+    (right, right_source): (Option<ProgramHash>, &str),
+    force: bool,
+) -> Result<(), String> {
+    match (left, right) {
+        (Some(left), Some(right)) if left != right && !force => Err(format!(
+            "Constraint system hash mismatch: {} has hash {} but {} has hash {}. Use `--force` to skip this check",
+            left_source, left, right_source, right
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Open a compiled program, extracting `entry` if the binary contains several programs
+pub fn read_program(path: &Path, entry: Option<&str>) -> Result<Box<dyn Read>, String> {
+    let file =
+        File::open(path).map_err(|why| format!("Could not open {}: {}", path.display(), why))?;
+
+    let reader = BufReader::new(file);
+
+    match entry {
+        Some(entry) => extract_entry(reader, entry)
+            .map(|program| Box::new(Cursor::new(program)) as Box<dyn Read>)
+            .map_err(|why| format!("Could not read {}: {}", path.display(), why)),
+        None => Ok(Box::new(reader)),
+    }
+}
+
+/// Read an ABI specification. The specification of a container maps the name of each entry to its ABI
+pub fn read_abi<R: Read>(reader: R, entry: Option<&str>) -> Result<Abi, String> {
+    match entry {
+        Some(entry) => {
+            let mut abis: BTreeMap<String, Abi> =
+                serde_json::from_reader(reader).map_err(|why| why.to_string())?;
+            abis.remove(entry)
+                .ok_or_else(|| format!("No ABI specification found for entry `{}`", entry))
+        }
+        None => serde_json::from_reader(reader).map_err(|why| why.to_string()),
+    }
+}
+
+/// The values of the inputs bound at setup
+pub struct Bindings<T> {
+    /// The values as given by the user, by name
+    pub json: serde_json::Map<String, serde_json::Value>,
+    /// The encoded values, in the order of the inputs
+    pub values: Vec<T>,
+}
+
+/// Read the values of the inputs of `abi` marked `#[bind_at_setup]` from the JSON object at `path`
+pub fn read_bindings<T: Field>(path: &Path, abi: &Abi) -> Result<Bindings<T>, String> {
+    let file =
+        File::open(path).map_err(|why| format!("Could not open {}: {}", path.display(), why))?;
+
+    let json: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|why| format!("Could not parse {}: {}", path.display(), why))?;
+
+    parse_bindings(json, abi)
+}
+
+/// Read the values of the inputs of `abi` marked `#[bind_at_setup]` recorded by `setup` in the verification key at
+/// `path`
+pub fn read_verification_key_bindings<T: Field>(
+    path: &Path,
+    abi: &Abi,
+) -> Result<Bindings<T>, String> {
+    let file = File::open(path).map_err(|why| {
+        format!(
+            "`{}` is marked `#[bind_at_setup]`, its value must be given by `--bindings` or recorded in the verification key: could not open {}: {}",
+            abi.bound_at_setup.join("`, `"),
+            path.display(),
+            why
+        )
+    })?;
+
+    let vk: serde_json::Value = serde_json::from_reader(BufReader::new(file))
+        .map_err(|why| format!("Could not parse {}: {}", path.display(), why))?;
+
+    let json = vk
+        .get("bindings")
+        .and_then(|bindings| bindings.as_object())
+        .cloned()
+        .unwrap_or_default();
+
+    parse_bindings(json, abi)
+}
+
+// the values of the inputs of `abi` marked `#[bind_at_setup]`, given by name in `json`
+fn parse_bindings<T: Field>(
+    json: serde_json::Map<String, serde_json::Value>,
+    abi: &Abi,
+) -> Result<Bindings<T>, String> {
+    if let Some(name) = json.keys().find(|name| !abi.is_bound(name)) {
+        return Err(format!(
+            "`{}` is not an input marked `#[bind_at_setup]`",
+            name
+        ));
+    }
+
+    let inputs = abi.bound_inputs();
+    let values = inputs
+        .iter()
+        .map(|input| {
+            json.get(&input.name).cloned().ok_or_else(|| {
+                format!(
+                    "Missing value for `{}`, which is marked `#[bind_at_setup]`",
+                    input.name
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let values =
+        zokrates_abi::parse_strict_json(values, inputs.into_iter().map(|i| i.ty).collect())
+            .map_err(|why| format!("Could not parse bindings: {}", why))?
+            .encode();
+
+    Ok(Bindings { json, values })
+}
+
+/// The values of the arguments of a compiled program bound by the bindings file at `path`, along with the values as
+/// given by the user. Binding inputs requires the ABI specification of the program, and the inputs of `abi` marked
+/// `#[bind_at_setup]` must be bound
+pub fn resolve_bindings<T: Field>(
+    path: Option<&str>,
+    abi: Option<&Abi>,
+    arguments: &[Parameter],
+) -> Result<
+    (
+        HashMap<Variable, T>,
+        serde_json::Map<String, serde_json::Value>,
+    ),
+    String,
+> {
+    match (path, abi) {
+        (Some(path), Some(abi)) => {
+            let bindings = read_bindings(Path::new(path), abi)?;
+            let values = bound_arguments(arguments, abi, bindings.values)?;
+            Ok((values, bindings.json))
+        }
+        (Some(_), None) => Err(
+            "Binding inputs requires the ABI specification of the program, given by `--abi-spec`"
+                .into(),
+        ),
+        (None, Some(abi)) if !abi.bound_at_setup.is_empty() => Err(format!(
+            "`{}` is marked `#[bind_at_setup]`, its value must be given by `--bindings`",
+            abi.bound_at_setup.join("`, `")
+        )),
+        (None, _) => Ok((HashMap::new(), serde_json::Map::new())),
+    }
+}
+
+/// The values of the arguments of a compiled program described by `abi` which are marked `#[bind_at_setup]`, as
+/// computed in `witness`
+pub fn witness_bindings<T: Field>(
+    arguments: &[Parameter],
+    abi: &Abi,
+    witness: &Witness<T>,
+) -> Result<HashMap<Variable, T>, String> {
+    abi.bound_indices()
+        .into_iter()
+        .map(|index| {
+            let variable = arguments
+                .get(index)
+                .map(|argument| argument.id)
+                .ok_or_else(|| "The ABI specification does not match the program".to_string())?;
+            let value = witness.0.get(&variable).cloned().ok_or_else(|| {
+                format!(
+                    "The witness has no value for `{}`, which is marked `#[bind_at_setup]`",
+                    abi.bound_at_setup.join("`, `")
+                )
+            })?;
+            Ok((variable, value))
+        })
+        .collect()
+}
+
+// map the arguments of a compiled program described by `abi` to the encoded `values` of the inputs bound at setup
+fn bound_arguments<T>(
+    arguments: &[Parameter],
+    abi: &Abi,
+    values: Vec<T>,
+) -> Result<HashMap<Variable, T>, String> {
+    abi.bound_indices()
+        .into_iter()
+        .map(|index| {
+            arguments
+                .get(index)
+                .map(|argument| argument.id)
+                .ok_or_else(|| "The ABI specification does not match the program".to_string())
+        })
+        .zip(values)
+        .map(|(variable, value)| variable.map(|variable| (variable, value)))
+        .collect()
+}
@@ -0,0 +1,139 @@
+//! The manifest of a project built with `zokrates build`, written as `zokrates.toml` at the root of the project
+
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+use zokrates_common::constants;
+use zokrates_common::helpers::{Parameters, SchemeParameter};
+
+/// The description of a project, read from `zokrates.toml` by `zokrates build`
+///
+/// Paths are relative to the directory of the manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectManifest {
+    /// The source file of the program
+    #[serde(default = "default_entry")]
+    pub entry: PathBuf,
+    #[serde(default = "default_curve")]
+    pub curve: String,
+    #[serde(default = "default_scheme")]
+    pub scheme: String,
+    #[serde(default = "default_backend")]
+    pub backend: String,
+    /// The ABI-encoded arguments the program is run with, checked against its ABI when building
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inputs: Option<PathBuf>,
+    /// The directory the artifacts are written to
+    #[serde(default = "default_out")]
+    pub out: PathBuf,
+}
+
+fn default_entry() -> PathBuf {
+    PathBuf::from("main.zok")
+}
+
+fn default_curve() -> String {
+    constants::BN128.to_string()
+}
+
+fn default_scheme() -> String {
+    constants::G16.to_string()
+}
+
+fn default_backend() -> String {
+    constants::ARK.to_string()
+}
+
+fn default_out() -> PathBuf {
+    PathBuf::from("build")
+}
+
+impl Default for ProjectManifest {
+    fn default() -> Self {
+        ProjectManifest {
+            entry: default_entry(),
+            curve: default_curve(),
+            scheme: default_scheme(),
+            backend: default_backend(),
+            inputs: None,
+            out: default_out(),
+        }
+    }
+}
+
+impl ProjectManifest {
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|why| format!("Could not read {}: {}", path.display(), why))?;
+        let manifest: ProjectManifest = toml::from_str(&source)
+            .map_err(|why| format!("Could not parse {}: {}", path.display(), why))?;
+        manifest.check()?;
+        Ok(manifest)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap()
+    }
+
+    // check that the backend supports the curve and the scheme
+    fn check(&self) -> Result<(), String> {
+        let Parameters(_, _, scheme) = Parameters::try_from((
+            self.backend.as_str(),
+            self.curve.as_str(),
+            self.scheme.as_str(),
+        ))?;
+
+        match scheme {
+            SchemeParameter::MARLIN => Err(format!(
+                "`{}` requires a universal setup, which `build` does not perform: run `universal-setup` and `setup` instead",
+                self.scheme
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest() {
+        let manifest: ProjectManifest = toml::from_str(
+            r#"
+            entry = "src/main.zok"
+            curve = "bls12_381"
+            inputs = "inputs.json"
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest,
+            ProjectManifest {
+                entry: "src/main.zok".into(),
+                curve: "bls12_381".into(),
+                inputs: Some("inputs.json".into()),
+                ..ProjectManifest::default()
+            }
+        );
+        assert_eq!(manifest.check(), Ok(()));
+
+        // the default manifest round-trips
+        let default = ProjectManifest::default();
+        assert_eq!(
+            toml::from_str::<ProjectManifest>(&default.to_toml()).unwrap(),
+            default
+        );
+
+        assert!(toml::from_str::<ProjectManifest>("entrypoint = \"main.zok\"").is_err());
+        assert!(ProjectManifest {
+            scheme: "marlin".into(),
+            ..ProjectManifest::default()
+        }
+        .check()
+        .unwrap_err()
+        .contains("universal setup"));
+    }
+}
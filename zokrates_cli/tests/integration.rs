@@ -614,6 +614,2317 @@ mod integration {
         }
     }
 
+    #[test]
+    #[ignore]
+    fn test_encrypted_witness() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let program_path = tmp_base.join("main.zok");
+        let flattened_path = tmp_base.join("out");
+        let witness_path = tmp_base.join("witness");
+        let key_path = tmp_base.join("witness.key");
+        let wrong_key_path = tmp_base.join("wrong.key");
+        let proving_key_path = tmp_base.join("proving.key");
+        let verification_key_path = tmp_base.join("verification.key");
+        let proof_path = tmp_base.join("proof.json");
+
+        fs::write(
+            &program_path,
+            "def main(field a, private field b) -> field { return a * b; }",
+        )
+        .unwrap();
+        fs::write(&key_path, "2a".repeat(32)).unwrap();
+        fs::write(&wrong_key_path, "2b".repeat(32)).unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compile",
+                "-i",
+                program_path.to_str().unwrap(),
+                "-o",
+                flattened_path.to_str().unwrap(),
+            ])
+            .succeeds()
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "setup",
+                "-i",
+                flattened_path.to_str().unwrap(),
+                "-p",
+                proving_key_path.to_str().unwrap(),
+                "-v",
+                verification_key_path.to_str().unwrap(),
+            ])
+            .succeeds()
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compute-witness",
+                "-i",
+                flattened_path.to_str().unwrap(),
+                "-o",
+                witness_path.to_str().unwrap(),
+                "-a",
+                "3",
+                "1337",
+                "--encrypt",
+                "--witness-key",
+                key_path.to_str().unwrap(),
+            ])
+            .succeeds()
+            .unwrap();
+
+        // the public input and the output are readable, the private input is not
+        let witness = fs::read(&witness_path).unwrap();
+        let witness = String::from_utf8_lossy(&witness);
+        assert!(witness.contains("_0 3"));
+        assert!(witness.contains("~out_0 4011"));
+        assert!(!witness.contains("1337"));
+
+        let generate_proof = |key_path: Option<&Path>| {
+            let mut args = vec![
+                "generate-proof",
+                "-i",
+                flattened_path.to_str().unwrap(),
+                "-w",
+                witness_path.to_str().unwrap(),
+                "-p",
+                proving_key_path.to_str().unwrap(),
+                "-j",
+                proof_path.to_str().unwrap(),
+            ];
+            if let Some(key_path) = key_path {
+                args.extend(["--witness-key", key_path.to_str().unwrap()]);
+            }
+            assert_cli::Assert::main_binary().with_args(&args)
+        };
+
+        generate_proof(None)
+            .fails()
+            .and()
+            .stderr()
+            .contains("The witness is encrypted, a key is required to read it")
+            .unwrap();
+
+        generate_proof(Some(&wrong_key_path))
+            .fails()
+            .and()
+            .stderr()
+            .contains("the key is wrong")
+            .unwrap();
+
+        generate_proof(Some(&key_path)).succeeds().unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "verify",
+                "-v",
+                verification_key_path.to_str().unwrap(),
+                "-j",
+                proof_path.to_str().unwrap(),
+            ])
+            .succeeds()
+            .stdout()
+            .contains("PASSED")
+            .unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rerandomize_proof() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let program_path = tmp_base.join("main.zok");
+        let flattened_path = tmp_base.join("out");
+        let witness_path = tmp_base.join("witness");
+        let proving_key_path = tmp_base.join("proving.key");
+        let verification_key_path = tmp_base.join("verification.key");
+        let proof_path = tmp_base.join("proof.json");
+        let rerandomized_proof_path = tmp_base.join("rerandomized_proof.json");
+
+        fs::write(
+            &program_path,
+            "def main(field a, private field b) -> field { return a * b; }",
+        )
+        .unwrap();
+
+        for args in [
+            vec![
+                "compile",
+                "-i",
+                program_path.to_str().unwrap(),
+                "-o",
+                flattened_path.to_str().unwrap(),
+            ],
+            vec![
+                "setup",
+                "-i",
+                flattened_path.to_str().unwrap(),
+                "-p",
+                proving_key_path.to_str().unwrap(),
+                "-v",
+                verification_key_path.to_str().unwrap(),
+            ],
+            vec![
+                "compute-witness",
+                "-i",
+                flattened_path.to_str().unwrap(),
+                "-o",
+                witness_path.to_str().unwrap(),
+                "-a",
+                "3",
+                "1337",
+            ],
+            vec![
+                "generate-proof",
+                "-i",
+                flattened_path.to_str().unwrap(),
+                "-w",
+                witness_path.to_str().unwrap(),
+                "-p",
+                proving_key_path.to_str().unwrap(),
+                "-j",
+                proof_path.to_str().unwrap(),
+            ],
+            vec![
+                "rerandomize-proof",
+                "-j",
+                proof_path.to_str().unwrap(),
+                "-v",
+                verification_key_path.to_str().unwrap(),
+                "-o",
+                rerandomized_proof_path.to_str().unwrap(),
+                "--verbose",
+            ],
+        ] {
+            assert_cli::Assert::main_binary()
+                .with_args(&args)
+                .succeeds()
+                .unwrap();
+        }
+
+        assert_ne!(
+            fs::read(&proof_path).unwrap(),
+            fs::read(&rerandomized_proof_path).unwrap()
+        );
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "verify",
+                "-v",
+                verification_key_path.to_str().unwrap(),
+                "-j",
+                rerandomized_proof_path.to_str().unwrap(),
+            ])
+            .succeeds()
+            .stdout()
+            .contains("PASSED")
+            .unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_container() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let deposit_path = tmp_base.join("deposit.zok");
+        let withdraw_path = tmp_base.join("withdraw.zok");
+        let container_path = tmp_base.join("circuits");
+        let abi_spec_path = tmp_base.join("abi.json");
+
+        fs::write(
+            &deposit_path,
+            "def main(field balance, private field amount) -> field { return balance + amount; }",
+        )
+        .unwrap();
+        fs::write(
+            &withdraw_path,
+            "def main(field balance, private field amount) -> field { assert(amount != 0); return balance - amount; }",
+        )
+        .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compile",
+                "--entry-point",
+                &format!("deposit={}", deposit_path.to_str().unwrap()),
+                "--entry-point",
+                &format!("withdraw={}", withdraw_path.to_str().unwrap()),
+                "-o",
+                container_path.to_str().unwrap(),
+                "-s",
+                abi_spec_path.to_str().unwrap(),
+            ])
+            .succeeds()
+            .unwrap();
+
+        // the container is smaller than the two programs compiled separately
+        let separate_size: u64 = [&deposit_path, &withdraw_path]
+            .iter()
+            .map(|source_path| {
+                let output_path = source_path.with_extension("out");
+                assert_cli::Assert::main_binary()
+                    .with_args(&[
+                        "compile",
+                        "-i",
+                        source_path.to_str().unwrap(),
+                        "-o",
+                        output_path.to_str().unwrap(),
+                        "-s",
+                        tmp_base.join("separate.json").to_str().unwrap(),
+                        "--r1cs",
+                        tmp_base.join("separate.r1cs").to_str().unwrap(),
+                    ])
+                    .succeeds()
+                    .unwrap();
+                fs::metadata(output_path).unwrap().len()
+            })
+            .sum();
+
+        assert!(fs::metadata(&container_path).unwrap().len() < separate_size);
+
+        // selecting an entry is required
+        assert_cli::Assert::main_binary()
+            .with_args(&["inspect", "-i", container_path.to_str().unwrap()])
+            .fails()
+            .and()
+            .stdout()
+            .contains("The file contains several programs")
+            .unwrap();
+
+        for (entry, expected_output) in [("deposit", "\"7\""), ("withdraw", "\"3\"")] {
+            let witness_path = tmp_base.join(format!("{}.witness", entry));
+            let proving_key_path = tmp_base.join(format!("{}.proving.key", entry));
+            let verification_key_path = tmp_base.join(format!("{}.verification.key", entry));
+            let proof_path = tmp_base.join(format!("{}.proof.json", entry));
+
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "compute-witness",
+                    "-i",
+                    container_path.to_str().unwrap(),
+                    "--entry",
+                    entry,
+                    "-s",
+                    abi_spec_path.to_str().unwrap(),
+                    "-o",
+                    witness_path.to_str().unwrap(),
+                    "--circom-witness",
+                    tmp_base.join("out.wtns").to_str().unwrap(),
+                    "--abi",
+                    "--stdin",
+                ])
+                .stdin("[\"5\", \"2\"]")
+                .succeeds()
+                .and()
+                .stdout()
+                .contains(expected_output)
+                .unwrap();
+
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "setup",
+                    "-i",
+                    container_path.to_str().unwrap(),
+                    "--entry",
+                    entry,
+                    "--abi-spec",
+                    abi_spec_path.to_str().unwrap(),
+                    "-p",
+                    proving_key_path.to_str().unwrap(),
+                    "-v",
+                    verification_key_path.to_str().unwrap(),
+                ])
+                .succeeds()
+                .unwrap();
+
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "generate-proof",
+                    "-i",
+                    container_path.to_str().unwrap(),
+                    "--entry",
+                    entry,
+                    "-w",
+                    witness_path.to_str().unwrap(),
+                    "-p",
+                    proving_key_path.to_str().unwrap(),
+                    "-j",
+                    proof_path.to_str().unwrap(),
+                ])
+                .succeeds()
+                .unwrap();
+
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "verify",
+                    "-v",
+                    verification_key_path.to_str().unwrap(),
+                    "-j",
+                    proof_path.to_str().unwrap(),
+                ])
+                .succeeds()
+                .stdout()
+                .contains("PASSED")
+                .unwrap();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn test_hash_mismatch() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let path = |name: &str| tmp_base.join(name).to_str().unwrap().to_string();
+
+        // two programs with the same interface but different constraint systems
+        for (name, source) in [
+            (
+                "mul",
+                "def main(field a, private field b) -> field { return a * b; }",
+            ),
+            (
+                "add",
+                "def main(field a, private field b) -> field { return a + b; }",
+            ),
+        ] {
+            fs::write(path(&format!("{}.zok", name)), source).unwrap();
+
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "compile",
+                    "-i",
+                    &path(&format!("{}.zok", name)),
+                    "-o",
+                    &path(&format!("{}.out", name)),
+                    "-s",
+                    &path(&format!("{}.json", name)),
+                ])
+                .succeeds()
+                .unwrap();
+
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "setup",
+                    "-i",
+                    &path(&format!("{}.out", name)),
+                    "--abi-spec",
+                    &path(&format!("{}.json", name)),
+                    "-p",
+                    &path(&format!("{}.pk", name)),
+                    "-v",
+                    &path(&format!("{}.vk", name)),
+                ])
+                .succeeds()
+                .unwrap();
+        }
+
+        // setup: the hash in the header of the program does not match its constraint system
+        let mut tampered = fs::read(path("mul.out")).unwrap();
+        let add = fs::read(path("add.out")).unwrap();
+        tampered[12..44].copy_from_slice(&add[12..44]);
+        fs::write(path("tampered.out"), tampered).unwrap();
+
+        let setup = |force: bool| {
+            let mut args = vec![
+                "setup".to_string(),
+                "-i".to_string(),
+                path("tampered.out"),
+                "-p".to_string(),
+                path("tampered.pk"),
+                "-v".to_string(),
+                path("tampered.vk"),
+            ];
+            if force {
+                args.push("--force".to_string());
+            }
+            assert_cli::Assert::main_binary().with_args(&args)
+        };
+
+        setup(false)
+            .fails()
+            .and()
+            .stdout()
+            .contains("Constraint system hash mismatch")
+            .unwrap();
+        setup(true).succeeds().unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compute-witness",
+                "-i",
+                &path("mul.out"),
+                "-s",
+                &path("mul.json"),
+                "-o",
+                &path("mul.witness"),
+                "-a",
+                "3",
+                "4",
+            ])
+            .succeeds()
+            .unwrap();
+
+        // proving: the proving key was generated for another program
+        let generate_proof = |pk: &str, force: bool| {
+            let mut args = vec![
+                "generate-proof".to_string(),
+                "-i".to_string(),
+                path("mul.out"),
+                "-w".to_string(),
+                path("mul.witness"),
+                "-p".to_string(),
+                path(pk),
+                "-j".to_string(),
+                path("mul.proof.json"),
+            ];
+            if force {
+                args.push("--force".to_string());
+            }
+            assert_cli::Assert::main_binary().with_args(&args)
+        };
+
+        generate_proof("add.pk", false)
+            .fails()
+            .and()
+            .stdout()
+            .contains("Constraint system hash mismatch")
+            .and()
+            .stdout()
+            .contains(&path("add.pk"))
+            .unwrap();
+        generate_proof("mul.pk", false).succeeds().unwrap();
+
+        // verification: the verification key was generated for another program
+        let verify = |vk: &str, force: bool| {
+            let mut args = vec![
+                "verify".to_string(),
+                "-v".to_string(),
+                path(vk),
+                "-j".to_string(),
+                path("mul.proof.json"),
+            ];
+            if force {
+                args.push("--force".to_string());
+            }
+            assert_cli::Assert::main_binary().with_args(&args)
+        };
+
+        verify("add.vk", false)
+            .fails()
+            .and()
+            .stdout()
+            .contains("Constraint system hash mismatch")
+            .unwrap();
+        verify("add.vk", true)
+            .succeeds()
+            .and()
+            .stdout()
+            .contains("FAILED")
+            .unwrap();
+        verify("mul.vk", false)
+            .succeeds()
+            .and()
+            .stdout()
+            .contains("PASSED")
+            .unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_coverage() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let program_path = tmp_base.join("main.zok");
+        let flattened_path = tmp_base.join("out");
+        let witness_path = tmp_base.join("witness");
+        let coverage_path = tmp_base.join("coverage.json");
+
+        fs::write(
+            &program_path,
+            "def main(bool b, field x) -> field {\n    field y = if b { assert(x == 1); x } else { 0 };\n    return y;\n}",
+        )
+        .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compile",
+                "-i",
+                program_path.to_str().unwrap(),
+                "-o",
+                flattened_path.to_str().unwrap(),
+                "--isolate-branches",
+            ])
+            .succeeds()
+            .unwrap();
+
+        for arguments in [["1", "1"], ["0", "5"]] {
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "compute-witness",
+                    "-i",
+                    flattened_path.to_str().unwrap(),
+                    "-o",
+                    witness_path.to_str().unwrap(),
+                    "--circom-witness",
+                    tmp_base.join("out.wtns").to_str().unwrap(),
+                    "--coverage",
+                    coverage_path.to_str().unwrap(),
+                    "-a",
+                    arguments[0],
+                    arguments[1],
+                ])
+                .succeeds()
+                .unwrap();
+        }
+
+        assert_cli::Assert::main_binary()
+            .with_args(&["coverage", "report", "-i", coverage_path.to_str().unwrap()])
+            .succeeds()
+            .and()
+            .stdout()
+            .contains("Assertion coverage over 2 runs")
+            .and()
+            .stdout()
+            .contains(
+                "evaluated 2/2, exercised 1/2 | field y = if b { assert(x == 1); x } else { 0 };",
+            )
+            .unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_hash_public_inputs() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let path = |name: &str| tmp_base.join(name).to_str().unwrap().to_string();
+        let stdlib = std::fs::canonicalize("../zokrates_stdlib/stdlib").unwrap();
+
+        fs::write(
+            path("main.zok"),
+            "def main(field[3] a, private field b) -> field { return (a[0] + a[1] + a[2]) * b; }",
+        )
+        .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compile",
+                "-i",
+                &path("main.zok"),
+                "--stdlib-path",
+                stdlib.to_str().unwrap(),
+                "-o",
+                &path("out"),
+                "-s",
+                &path("abi.json"),
+                "--hash-public-inputs",
+            ])
+            .succeeds()
+            .unwrap();
+
+        // the inputs are given with their original shape, the commitment is computed from them
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compute-witness",
+                "-i",
+                &path("out"),
+                "-s",
+                &path("abi.json"),
+                "-o",
+                &path("witness"),
+                "--circom-witness",
+                &path("out.wtns"),
+                "--abi",
+                "--stdin",
+            ])
+            .stdin("[[\"1\", \"2\", \"3\"], \"4\"]")
+            .succeeds()
+            .and()
+            .stdout()
+            .contains("\"24\"")
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "setup",
+                "-i",
+                &path("out"),
+                "--abi-spec",
+                &path("abi.json"),
+                "-p",
+                &path("proving.key"),
+                "-v",
+                &path("verification.key"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "generate-proof",
+                "-i",
+                &path("out"),
+                "-w",
+                &path("witness"),
+                "-p",
+                &path("proving.key"),
+                "-j",
+                &path("proof.json"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        // the commitment and the return value are the only public values
+        let proof: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(path("proof.json")).unwrap()).unwrap();
+        assert_eq!(proof["inputs"].as_array().unwrap().len(), 2);
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "verify",
+                "-v",
+                &path("verification.key"),
+                "-j",
+                &path("proof.json"),
+            ])
+            .succeeds()
+            .stdout()
+            .contains("PASSED")
+            .unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_private_outputs() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let path = |name: &str| tmp_base.join(name).to_str().unwrap().to_string();
+        let stdlib = std::fs::canonicalize("../zokrates_stdlib/stdlib").unwrap();
+
+        fs::write(
+            path("main.zok"),
+            "def main(field a, private field b) -> (field, private field) { return (a + b, a * b); }",
+        )
+        .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compile",
+                "-i",
+                &path("main.zok"),
+                "--stdlib-path",
+                stdlib.to_str().unwrap(),
+                "-o",
+                &path("out"),
+                "-s",
+                &path("abi.json"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        // the private return value is still decoded locally
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compute-witness",
+                "-i",
+                &path("out"),
+                "-s",
+                &path("abi.json"),
+                "-o",
+                &path("witness"),
+                "--abi",
+                "--stdin",
+            ])
+            .stdin("[\"2\", \"3\"]")
+            .succeeds()
+            .and()
+            .stdout()
+            .contains("[\"5\",\"6\"]")
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "setup",
+                "-i",
+                &path("out"),
+                "--abi-spec",
+                &path("abi.json"),
+                "-p",
+                &path("proving.key"),
+                "-v",
+                &path("verification.key"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "generate-proof",
+                "-i",
+                &path("out"),
+                "-w",
+                &path("witness"),
+                "-p",
+                &path("proving.key"),
+                "-j",
+                &path("proof.json"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        // `a` and the sum are the only public values
+        let proof: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(path("proof.json")).unwrap()).unwrap();
+        assert_eq!(proof["inputs"].as_array().unwrap().len(), 2);
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "verify",
+                "-v",
+                &path("verification.key"),
+                "-j",
+                &path("proof.json"),
+            ])
+            .succeeds()
+            .stdout()
+            .contains("PASSED")
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "export-verifier",
+                "-i",
+                &path("verification.key"),
+                "-o",
+                &path("verifier.sol"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        let verifier = fs::read_to_string(path("verifier.sol")).unwrap();
+        assert!(verifier.contains("uint[2] memory input"));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_init_and_build() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let stdlib = std::fs::canonicalize("../zokrates_stdlib/stdlib").unwrap();
+
+        for template in ["hash-preimage", "merkle-membership", "range-proof"] {
+            let dir = tmp_base.join(template);
+            let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+
+            assert_cli::Assert::main_binary()
+                .with_args(&["init", template, "--path", dir.to_str().unwrap()])
+                .succeeds()
+                .unwrap();
+
+            // a project is not created over existing files
+            assert_cli::Assert::main_binary()
+                .with_args(&["init", template, "--path", dir.to_str().unwrap()])
+                .fails()
+                .and()
+                .stderr()
+                .contains("the directory is not empty")
+                .unwrap();
+
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "build",
+                    "--manifest",
+                    &path("zokrates.toml"),
+                    "--stdlib-path",
+                    stdlib.to_str().unwrap(),
+                ])
+                .succeeds()
+                .unwrap();
+
+            for artifact in [
+                "out",
+                "abi.json",
+                "proving.key",
+                "verification.key",
+                "verifier.sol",
+            ] {
+                assert!(dir.join("build").join(artifact).exists());
+            }
+
+            let build = |name: &str| dir.join("build").join(name).to_str().unwrap().to_string();
+
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "compute-witness",
+                    "-i",
+                    &build("out"),
+                    "-s",
+                    &build("abi.json"),
+                    "-o",
+                    &build("witness"),
+                    "--circom-witness",
+                    &build("out.wtns"),
+                    "--abi",
+                    "--stdin",
+                ])
+                .stdin(&fs::read_to_string(path("inputs.json")).unwrap())
+                .succeeds()
+                .unwrap();
+
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "generate-proof",
+                    "-i",
+                    &build("out"),
+                    "-w",
+                    &build("witness"),
+                    "-p",
+                    &build("proving.key"),
+                    "-j",
+                    &build("proof.json"),
+                ])
+                .succeeds()
+                .unwrap();
+
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "verify",
+                    "-v",
+                    &build("verification.key"),
+                    "-j",
+                    &build("proof.json"),
+                ])
+                .succeeds()
+                .stdout()
+                .contains("PASSED")
+                .unwrap();
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn test_default_arguments() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let path = |name: &str| tmp_base.join(name).to_str().unwrap().to_string();
+        let stdlib = std::fs::canonicalize("../zokrates_stdlib/stdlib").unwrap();
+
+        fs::write(
+            path("main.zok"),
+            r#"
+struct Point {
+    field x;
+    field y;
+}
+
+def main(Point p, private u32[2] a, bool b) -> field {
+    assert(p.x * p.y == 0);
+    assert(a[0] + a[1] == a[1] + a[0]);
+    assert(!b || p.y == 0);
+    return p.x + p.y;
+}
+"#,
+        )
+        .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compile",
+                "-i",
+                &path("main.zok"),
+                "--stdlib-path",
+                stdlib.to_str().unwrap(),
+                "-o",
+                &path("out"),
+                "-s",
+                &path("abi.json"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        let compute = |set: &[&str]| {
+            let mut args = vec![
+                "compute-witness".to_string(),
+                "-i".to_string(),
+                path("out"),
+                "-s".to_string(),
+                path("abi.json"),
+                "-o".to_string(),
+                path("witness"),
+                "--circom-witness".to_string(),
+                path("out.wtns"),
+                "--defaults".to_string(),
+            ];
+            for assignment in set {
+                args.push("--set".to_string());
+                args.push(assignment.to_string());
+            }
+            assert_cli::Assert::main_binary().with_args(&args)
+        };
+
+        // the asserts accept zeros
+        compute(&[])
+            .succeeds()
+            .and()
+            .stdout()
+            .contains("\"0\"")
+            .unwrap();
+
+        compute(&["p.x=42", "a.1=0x2a"])
+            .succeeds()
+            .and()
+            .stdout()
+            .contains("\"42\"")
+            .unwrap();
+
+        compute(&["p.x=1", "p.y=1"]).fails().unwrap();
+
+        compute(&["p.z=1"])
+            .fails()
+            .and()
+            .stderr()
+            .contains("struct `Point` has no member `z`")
+            .unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_derived_nonces() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let path = |name: &str| tmp_base.join(name).to_str().unwrap().to_string();
+
+        fs::write(
+            path("main.zok"),
+            "def main(field a, #[derive_nonce] private field nonce) -> field { return a + nonce; }",
+        )
+        .unwrap();
+        fs::write(path("nonce.key"), "2a".repeat(32)).unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compile",
+                "-i",
+                &path("main.zok"),
+                "-o",
+                &path("out"),
+                "-s",
+                &path("abi.json"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        let compute = |input: &str, run: &str| {
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "compute-witness",
+                    "-i",
+                    &path("out"),
+                    "-s",
+                    &path("abi.json"),
+                    "-o",
+                    &path(&format!("witness{}", run)),
+                    "--circom-witness",
+                    &path("out.wtns"),
+                    "--metadata",
+                    &path(&format!("witness{}.meta.json", run)),
+                    "--nonce-key",
+                    &path("nonce.key"),
+                    "--abi",
+                    "--stdin",
+                ])
+                .stdin(input)
+        };
+
+        // the nonce is left out of the arguments, and the same inputs derive the same witness
+        compute("[\"1\"]", "0").succeeds().unwrap();
+        compute("[\"1\"]", "1").succeeds().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(path("witness0")).unwrap(),
+            fs::read_to_string(path("witness1")).unwrap()
+        );
+
+        let metadata: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(path("witness0.meta.json")).unwrap()).unwrap();
+        assert!(metadata["derived_nonces"]["nonce"].is_string());
+        assert_eq!(
+            fs::read_to_string(path("witness1.meta.json")).unwrap(),
+            fs::read_to_string(path("witness0.meta.json")).unwrap()
+        );
+
+        // the nonce cannot be provided
+        compute("[\"1\", \"42\"]", "2")
+            .fails()
+            .and()
+            .stderr()
+            .contains("`nonce` is derived with `#[derive_nonce]` and cannot be provided")
+            .unwrap();
+
+        // raw arguments cannot skip the derivation
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compute-witness",
+                "-i",
+                &path("out"),
+                "-s",
+                &path("abi.json"),
+                "-o",
+                &path("witness3"),
+                "-a",
+                "1",
+                "42",
+            ])
+            .fails()
+            .and()
+            .stdout()
+            .contains(
+                "`nonce` is marked `#[derive_nonce]`, the arguments must be given with `--abi`",
+            )
+            .unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_bind_at_setup() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let path = |name: &str| tmp_base.join(name).to_str().unwrap().to_string();
+
+        fs::write(
+            path("main.zok"),
+            "def main(field a, #[bind_at_setup] private field threshold) { assert(a * threshold == 42); }",
+        )
+        .unwrap();
+        fs::write(path("bindings0.json"), r#"{"threshold": "6"}"#).unwrap();
+        fs::write(path("bindings1.json"), r#"{"threshold": "7"}"#).unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compile",
+                "-i",
+                &path("main.zok"),
+                "-o",
+                &path("out"),
+                "-s",
+                &path("abi.json"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "setup",
+                "-i",
+                &path("out"),
+                "--abi-spec",
+                &path("abi.json"),
+                "-p",
+                &path("proving.key"),
+                "-v",
+                &path("verification.key"),
+                "--bindings",
+                &path("bindings0.json"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        // the bound values are recorded in the verification key
+        let vk: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(path("verification.key")).unwrap()).unwrap();
+        assert_eq!(vk["bindings"]["threshold"], "6");
+
+        let prove = |input: &str, bindings: &str| {
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "compute-witness",
+                    "-i",
+                    &path("out"),
+                    "-s",
+                    &path("abi.json"),
+                    "-o",
+                    &path("witness"),
+                    "--circom-witness",
+                    &path("out.wtns"),
+                    "--bindings",
+                    &path(bindings),
+                    "--abi",
+                    "--stdin",
+                ])
+                .stdin(input)
+                .succeeds()
+                .unwrap();
+
+            assert_cli::Assert::main_binary().with_args(&[
+                "generate-proof",
+                "-i",
+                &path("out"),
+                "--abi-spec",
+                &path("abi.json"),
+                "-w",
+                &path("witness"),
+                "-p",
+                &path("proving.key"),
+                "-j",
+                &path("proof.json"),
+                "--bindings",
+                &path(bindings),
+            ])
+        };
+
+        // the bound input is left out of the arguments
+        prove("[\"7\"]", "bindings0.json").succeeds().unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "verify",
+                "-j",
+                &path("proof.json"),
+                "-v",
+                &path("verification.key"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        // without `--bindings`, the values are read from the verification key and from the witness
+        fs::remove_file(path("proof.json")).unwrap();
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compute-witness",
+                "-i",
+                &path("out"),
+                "-s",
+                &path("abi.json"),
+                "-o",
+                &path("witness"),
+                "--circom-witness",
+                &path("out.wtns"),
+                "-v",
+                &path("verification.key"),
+                "--abi",
+                "--stdin",
+            ])
+            .stdin("[\"7\"]")
+            .succeeds()
+            .unwrap();
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "generate-proof",
+                "-i",
+                &path("out"),
+                "--abi-spec",
+                &path("abi.json"),
+                "-w",
+                &path("witness"),
+                "-p",
+                &path("proving.key"),
+                "-j",
+                &path("proof.json"),
+            ])
+            .succeeds()
+            .unwrap();
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "verify",
+                "-j",
+                &path("proof.json"),
+                "-v",
+                &path("verification.key"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        // changing the bound value requires a new setup, which is reported before proving
+        fs::remove_file(path("proof.json")).unwrap();
+        prove("[\"6\"]", "bindings1.json")
+            .fails()
+            .and()
+            .stderr()
+            .contains("Constraint system hash mismatch")
+            .unwrap();
+        assert!(!Path::new(&path("proof.json")).exists());
+
+        // the bound input cannot be provided
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compute-witness",
+                "-i",
+                &path("out"),
+                "-s",
+                &path("abi.json"),
+                "-o",
+                &path("witness"),
+                "--circom-witness",
+                &path("out.wtns"),
+                "--bindings",
+                &path("bindings0.json"),
+                "--abi",
+                "--stdin",
+            ])
+            .stdin("[\"7\", \"6\"]")
+            .fails()
+            .and()
+            .stderr()
+            .contains(
+                "`threshold` is bound at setup with `#[bind_at_setup]` and cannot be provided",
+            )
+            .unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_export_zkinterface() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let path = |name: &str| tmp_base.join(name).to_str().unwrap().to_string();
+
+        fs::write(
+            path("main.zok"),
+            "def main(field a, private field b) -> field { return a * b; }",
+        )
+        .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&["compile", "-i", &path("main.zok"), "-o", &path("out")])
+            .succeeds()
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compute-witness",
+                "-i",
+                &path("out"),
+                "-o",
+                &path("witness"),
+                "--circom-witness",
+                &path("out.wtns"),
+                "-a",
+                "3",
+                "4",
+            ])
+            .succeeds()
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "export",
+                "-i",
+                &path("out"),
+                "--format",
+                "zkinterface",
+                "-w",
+                &path("witness"),
+                "-o",
+                &path("out.zkif"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        // the file is read with the reader of the `zkinterface` crate
+        let mut reader = zkinterface::Reader::new();
+        reader
+            .push_message(fs::read(path("out.zkif")).unwrap())
+            .unwrap();
+        let messages = zkinterface::Messages::from(&reader);
+
+        // `a` and the output are the instance variables
+        assert_eq!(messages.circuit_headers.len(), 1);
+        let instance = &messages.circuit_headers[0].instance_variables;
+        assert_eq!(instance.variable_ids, vec![1, 2]);
+        assert_eq!(instance.values.as_ref().unwrap()[0], 3);
+        assert_eq!(instance.values.as_ref().unwrap()[32], 12);
+
+        assert!(!messages.constraint_systems[0].constraints.is_empty());
+        assert_eq!(messages.witnesses.len(), 1);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_public_layout() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let path = |name: &str| tmp_base.join(name).to_str().unwrap().to_string();
+
+        fs::write(
+            path("main.zok"),
+            "def main(field a, private field b, field c) -> field { return a * b + c; }",
+        )
+        .unwrap();
+
+        // a = 3, c = 5 and the output is 3 * 4 + 5 = 17
+        for (name, layout, expected, packed) in [
+            (
+                "outputs_first",
+                "outputs-first",
+                vec![17, 3, 5],
+                vec!["outputs.out", "inputs.a", "inputs.c"],
+            ),
+            (
+                "explicit",
+                "c,a,out",
+                vec![5, 3, 17],
+                vec!["inputs.c", "inputs.a", "outputs.out"],
+            ),
+        ] {
+            let file = |file: &str| path(&format!("{}.{}", name, file));
+
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "compile",
+                    "-i",
+                    &path("main.zok"),
+                    "-o",
+                    &file("out"),
+                    "-s",
+                    &file("abi.json"),
+                    "--public-layout",
+                    layout,
+                ])
+                .succeeds()
+                .unwrap();
+
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "compute-witness",
+                    "-i",
+                    &file("out"),
+                    "-s",
+                    &file("abi.json"),
+                    "-o",
+                    &file("witness"),
+                    "-a",
+                    "3",
+                    "4",
+                    "5",
+                ])
+                .succeeds()
+                .unwrap();
+
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "setup",
+                    "-i",
+                    &file("out"),
+                    "--abi-spec",
+                    &file("abi.json"),
+                    "-p",
+                    &file("proving.key"),
+                    "-v",
+                    &file("verification.key"),
+                ])
+                .succeeds()
+                .unwrap();
+
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "generate-proof",
+                    "-i",
+                    &file("out"),
+                    "-w",
+                    &file("witness"),
+                    "-p",
+                    &file("proving.key"),
+                    "-j",
+                    &file("proof.json"),
+                ])
+                .succeeds()
+                .unwrap();
+
+            let proof: serde_json::Value =
+                serde_json::from_str(&fs::read_to_string(file("proof.json")).unwrap()).unwrap();
+            let inputs: Vec<u64> = proof["inputs"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|i| u64::from_str_radix(i.as_str().unwrap().trim_start_matches("0x"), 16))
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(inputs, expected);
+
+            let vk: serde_json::Value =
+                serde_json::from_str(&fs::read_to_string(file("verification.key")).unwrap())
+                    .unwrap();
+            assert_eq!(vk["public_layout"], proof["public_layout"]);
+
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "verify",
+                    "-v",
+                    &file("verification.key"),
+                    "-j",
+                    &file("proof.json"),
+                ])
+                .succeeds()
+                .stdout()
+                .contains("PASSED")
+                .unwrap();
+
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "export-verifier",
+                    "-i",
+                    &file("verification.key"),
+                    "-o",
+                    &file("verifier.sol"),
+                ])
+                .succeeds()
+                .unwrap();
+
+            // the named verification function packs the values in the order of the layout
+            let verifier = fs::read_to_string(file("verifier.sol")).unwrap();
+            for (i, value) in packed.iter().enumerate() {
+                assert!(verifier.contains(&format!("input[{}] = {};", i, value)));
+            }
+        }
+
+        // a proof does not verify against a key generated for another layout
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "verify",
+                "-v",
+                &path("outputs_first.verification.key"),
+                "-j",
+                &path("explicit.proof.json"),
+            ])
+            .fails()
+            .unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_no_outputs() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let path = |name: &str| tmp_base.join(name).to_str().unwrap().to_string();
+
+        fs::write(
+            path("main.zok"),
+            "def main(field a, private field b) -> field { assert(a * b == 12); return a * b * b; }",
+        )
+        .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compile",
+                "-i",
+                &path("main.zok"),
+                "-o",
+                &path("out"),
+                "-s",
+                &path("abi.json"),
+                "--no-outputs",
+            ])
+            .succeeds()
+            .unwrap();
+
+        let abi: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(path("abi.json")).unwrap()).unwrap();
+        assert_eq!(abi["no_outputs"], serde_json::Value::Bool(true));
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compute-witness",
+                "-i",
+                &path("out"),
+                "-s",
+                &path("abi.json"),
+                "-o",
+                &path("witness"),
+                "-a",
+                "3",
+                "4",
+            ])
+            .succeeds()
+            .unwrap();
+
+        // the assertion is still checked
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compute-witness",
+                "-i",
+                &path("out"),
+                "-s",
+                &path("abi.json"),
+                "-o",
+                &path("invalid.witness"),
+                "-a",
+                "3",
+                "5",
+            ])
+            .fails()
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "setup",
+                "-i",
+                &path("out"),
+                "--abi-spec",
+                &path("abi.json"),
+                "-p",
+                &path("proving.key"),
+                "-v",
+                &path("verification.key"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "generate-proof",
+                "-i",
+                &path("out"),
+                "-w",
+                &path("witness"),
+                "-p",
+                &path("proving.key"),
+                "-j",
+                &path("proof.json"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        // only `a` is public
+        let proof: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(path("proof.json")).unwrap()).unwrap();
+        assert_eq!(proof["inputs"].as_array().unwrap().len(), 1);
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "verify",
+                "-v",
+                &path("verification.key"),
+                "-j",
+                &path("proof.json"),
+            ])
+            .succeeds()
+            .stdout()
+            .contains("PASSED")
+            .unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_minimization_report() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let path = |name: &str| tmp_base.join(name).to_str().unwrap().to_string();
+
+        fs::write(
+            path("main.zok"),
+            "def main(field a, private field b) -> field { field c = a * b; return c + a; }",
+        )
+        .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compile",
+                "-i",
+                &path("main.zok"),
+                "-o",
+                &path("out"),
+                "-s",
+                &path("abi.json"),
+                "--minimization-report",
+                &path("report.json"),
+                "--verbose",
+            ])
+            .succeeds()
+            .stdout()
+            .contains("constraints")
+            .unwrap();
+
+        let report: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(path("report.json")).unwrap()).unwrap();
+        let passes = report["passes"].as_array().unwrap();
+        assert_eq!(passes.len(), 6);
+        assert_eq!(
+            passes
+                .iter()
+                .map(|p| p["delta"]["constraints"].as_i64().unwrap())
+                .sum::<i64>(),
+            report["delta"]["constraints"].as_i64().unwrap()
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_reorder_variables() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let path = |name: &str| tmp_base.join(name).to_str().unwrap().to_string();
+
+        fs::write(
+            path("main.zok"),
+            "def main(private field x) -> field { field y = x * x; field z = y * y * x; return z + y; }",
+        )
+        .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compile",
+                "-i",
+                &path("main.zok"),
+                "-o",
+                &path("out"),
+                "--reorder-variables",
+            ])
+            .succeeds()
+            .unwrap();
+
+        // the permutation is stored in the program
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "inspect",
+                "-i",
+                &path("out"),
+                "--variable-ordering",
+                &path("ordering.json"),
+            ])
+            .succeeds()
+            .stdout()
+            .contains("Variable ordering written to")
+            .unwrap();
+
+        let ordering: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(path("ordering.json")).unwrap()).unwrap();
+        assert!(ordering["permutation"].is_object());
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compute-witness",
+                "-i",
+                &path("out"),
+                "-o",
+                &path("witness"),
+                "--circom-witness",
+                &path("out.wtns"),
+                "-a",
+                "3",
+            ])
+            .succeeds()
+            .stdout()
+            .contains("\"252\"")
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "setup",
+                "-i",
+                &path("out"),
+                "-p",
+                &path("proving.key"),
+                "-v",
+                &path("verification.key"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "generate-proof",
+                "-i",
+                &path("out"),
+                "-w",
+                &path("witness"),
+                "-p",
+                &path("proving.key"),
+                "-j",
+                &path("proof.json"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "verify",
+                "-v",
+                &path("verification.key"),
+                "-j",
+                &path("proof.json"),
+            ])
+            .succeeds()
+            .stdout()
+            .contains("PASSED")
+            .unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_explain() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let path = |name: &str| tmp_base.join(name).to_str().unwrap().to_string();
+
+        fs::write(
+            path("main.zok"),
+            "def main(private field x) -> field {\n    assert(x * x == 4);\n    return x;\n}",
+        )
+        .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compile",
+                "-i",
+                &path("main.zok"),
+                "-o",
+                &path("out"),
+                "--debug-table",
+                &path("out.debug"),
+            ])
+            .succeeds()
+            .stdout()
+            .contains("Debug table written to")
+            .unwrap();
+
+        // the assertion is the first constraint
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "explain",
+                "-i",
+                &path("out"),
+                "--debug-table",
+                &path("out.debug"),
+                "--constraint",
+                "0",
+            ])
+            .succeeds()
+            .stdout()
+            .contains("main.zok:2")
+            .stdout()
+            .contains("main.zok/main")
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "explain",
+                "-i",
+                &path("out"),
+                "--debug-table",
+                &path("out.debug"),
+                "--constraint",
+                "42",
+            ])
+            .fails()
+            .and()
+            .stderr()
+            .contains("Constraint #42 not found")
+            .unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_annotate() {
+        // the loop is unrolled 8 times: each call defines the argument of `square`, which multiplies it and defines
+        // the result, which is then assigned to `x`
+        let line = |code: &str, comment: &str| format!("{:<30}  // {}\n", code, comment);
+
+        assert_cli::Assert::main_binary()
+            .with_args(&["annotate", "./examples/book/annotate.zok"])
+            .succeeds()
+            .stdout()
+            .contains(line("    return x * x;", "24 constraints").as_str())
+            .stdout()
+            .contains(line("    field mut x = a;", "1 constraints").as_str())
+            .stdout()
+            .contains(line("        x = square(x);", "16 constraints (40 with calls)").as_str())
+            .stdout()
+            .contains(line("    return x;", "1 constraints").as_str())
+            .unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_io_report() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let path = |name: &str| tmp_base.join(name).to_str().unwrap().to_string();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compile",
+                "-i",
+                "./examples/book/io_report.zok",
+                "-o",
+                &path("out"),
+                "-s",
+                &path("abi.json"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        // the nullifier derived from the 256-bit secret only holds 32 bits
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "inspect",
+                "-i",
+                &path("out"),
+                "-s",
+                &path("abi.json"),
+                "--io-report",
+            ])
+            .succeeds()
+            .stdout()
+            .contains("input      root   field  254")
+            .stdout()
+            .contains("output     out_1  u32    32        secret")
+            .unwrap();
+
+        let output = Command::new(env!("CARGO_BIN_EXE_zokrates"))
+            .args(&[
+                "inspect",
+                "-i",
+                &path("out"),
+                "-s",
+                &path("abi.json"),
+                "--io-report",
+                "--json",
+            ])
+            .output()
+            .unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+        assert_eq!(
+            report["values"][2],
+            serde_json::json!({
+                "name": "out_1",
+                "direction": "output",
+                "type": "u32",
+                "capacity": 32,
+                "influenced_by": ["secret"]
+            })
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_verify_build() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let path = |name: &str| tmp_base.join(name).to_str().unwrap().to_string();
+
+        fs::write(
+            path("main.zok"),
+            "from \"./lib.zok\" import square;\n\ndef main(private field x) -> field {\n    return square(x);\n}",
+        )
+        .unwrap();
+        fs::write(
+            path("lib.zok"),
+            "def square(field x) -> field {\n    return x * x;\n}",
+        )
+        .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compile",
+                "-i",
+                &path("main.zok"),
+                "-o",
+                &path("out"),
+                "--manifest",
+                &path("manifest.json"),
+            ])
+            .succeeds()
+            .stdout()
+            .contains("Manifest written to")
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&["verify-build", &path("manifest.json")])
+            .succeeds()
+            .stdout()
+            .contains("matches")
+            .unwrap();
+
+        // tamper with the imported file only
+        fs::write(
+            path("lib.zok"),
+            "def square(field x) -> field {\n    return x * x * x;\n}",
+        )
+        .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&["verify-build", &path("manifest.json")])
+            .fails()
+            .and()
+            .stderr()
+            .contains("lib.zok changed")
+            .stderr()
+            .contains("constraint system changed")
+            .unwrap();
+    }
+
+    // run the binary with `--output-format json`, check that stdout is a single report following the schema and
+    // return it along with the success of the command
+    fn run_json(args: &[&str]) -> (bool, serde_json::Value) {
+        let output = Command::new(env!("CARGO_BIN_EXE_zokrates"))
+            .args(args)
+            .args(&["--output-format", "json"])
+            .output()
+            .unwrap();
+
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+        assert_eq!(report["version"], 1);
+        assert_eq!(report["command"], args[0]);
+        let status = report["status"].as_str().unwrap();
+        assert!(status == "success" || status == "failure");
+        assert!(report["timings"]["total"].is_f64());
+        assert!(report["timings"]
+            .as_object()
+            .unwrap()
+            .values()
+            .all(|t| t.as_f64().unwrap() >= 0.0));
+        for file in report["files"].as_array().unwrap() {
+            assert!(file["kind"].is_string());
+            assert!(file["path"].is_string());
+        }
+        assert!(report.get("constraints").map_or(true, |c| c.is_u64()));
+        assert_eq!(
+            report.get("error").map(|e| e["message"].is_string()),
+            match status {
+                "success" => None,
+                _ => Some(true),
+            }
+        );
+
+        assert_eq!(output.status.success(), status == "success");
+        (output.status.success(), report)
+    }
+
+    #[test]
+    #[ignore]
+    fn test_json_output() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let path = |name: &str| tmp_base.join(name).to_str().unwrap().to_string();
+
+        fs::write(
+            path("main.zok"),
+            "def main(field a, private field b) -> field {\n    log(\"a is {}\", a);\n    return a * b;\n}",
+        )
+        .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compile",
+                "-i",
+                &path("main.zok"),
+                "-o",
+                &path("out"),
+                "--debug",
+            ])
+            .succeeds()
+            .unwrap();
+
+        // the logs of the program do not end up in the report
+        let (success, report) = run_json(&[
+            "compute-witness",
+            "-i",
+            &path("out"),
+            "-o",
+            &path("witness"),
+            "--circom-witness",
+            &path("out.wtns"),
+            "-a",
+            "2",
+            "3",
+        ]);
+        assert!(success);
+        assert!(report["constraints"].as_u64().unwrap() > 0);
+        assert_eq!(report["files"][1]["kind"], "witness");
+        assert_eq!(report["files"][1]["path"], path("witness"));
+
+        let (success, report) = run_json(&[
+            "setup",
+            "-i",
+            &path("out"),
+            "-p",
+            &path("proving.key"),
+            "-v",
+            &path("verification.key"),
+        ]);
+        assert!(success);
+        assert!(report["timings"]["setup"].is_f64());
+
+        let (success, _) = run_json(&[
+            "generate-proof",
+            "-i",
+            &path("out"),
+            "-w",
+            &path("witness"),
+            "-p",
+            &path("proving.key"),
+            "-j",
+            &path("proof.json"),
+        ]);
+        assert!(success);
+
+        let verify = || {
+            run_json(&[
+                "verify",
+                "-j",
+                &path("proof.json"),
+                "-v",
+                &path("verification.key"),
+            ])
+        };
+
+        let (success, report) = verify();
+        assert!(success);
+        assert_eq!(report["status"], "success");
+        assert_eq!(report["result"]["verified"], true);
+
+        // tamper with the public inputs of the proof
+        let mut proof: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(path("proof.json")).unwrap()).unwrap();
+        proof["inputs"][0] =
+            "0x0000000000000000000000000000000000000000000000000000000000000003".into();
+        fs::write(path("proof.json"), proof.to_string()).unwrap();
+
+        let (success, report) = verify();
+        assert!(!success);
+        assert_eq!(report["status"], "failure");
+        assert_eq!(report["result"]["verified"], false);
+        assert_eq!(report["error"]["message"], "The proof is not valid");
+
+        // errors which occur before verifying are reported too
+        fs::remove_file(path("verification.key")).unwrap();
+
+        let (success, report) = verify();
+        assert!(!success);
+        assert!(report["error"]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Could not open"));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_multiple_curves() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let path = |name: &str| tmp_base.join(name).to_str().unwrap().to_string();
+
+        fs::write(
+            path("main.zok"),
+            "def main(private field x, u32 y) -> field { return x * x + if y * 3 == 9 { 1 } else { 0 }; }",
+        )
+        .unwrap();
+
+        let compile = |curve: &str, output: &str, abi: &str, r1cs: &str| {
+            assert_cli::Assert::main_binary()
+                .with_args(&[
+                    "compile",
+                    "-i",
+                    &path("main.zok"),
+                    "--curve",
+                    curve,
+                    "-o",
+                    &path(output),
+                    "-s",
+                    &path(abi),
+                    "--r1cs",
+                    &path(r1cs),
+                ])
+                .succeeds()
+                .unwrap();
+        };
+
+        compile("bn128,bls12_381", "out", "abi.json", "out.r1cs");
+
+        for curve in &["bn128", "bls12_381"] {
+            compile(curve, "single", "single.json", "single.r1cs");
+
+            assert_eq!(
+                fs::read(path(&format!("out.{}", curve))).unwrap(),
+                fs::read(path("single")).unwrap()
+            );
+            assert_eq!(
+                fs::read(path(&format!("abi.{}.json", curve))).unwrap(),
+                fs::read(path("single.json")).unwrap()
+            );
+            assert_eq!(
+                fs::read(path(&format!("out.{}.r1cs", curve))).unwrap(),
+                fs::read(path("single.r1cs")).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn test_unsupported_curve() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let path = |name: &str| tmp_base.join(name).to_str().unwrap().to_string();
+
+        fs::write(
+            path("main.zok"),
+            "def main(private field a) -> field { return a * a; }",
+        )
+        .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compile",
+                "-i",
+                &path("main.zok"),
+                "--curve",
+                "pallas",
+                "-o",
+                &path("out"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compute-witness",
+                "-i",
+                &path("out"),
+                "-o",
+                &path("witness"),
+                "-a",
+                "2",
+            ])
+            .succeeds()
+            .unwrap();
+
+        // no backend implements the pasta curves, so the parameters are rejected before any key is read
+        for backend in &["ark", "bellman"] {
+            let (success, report) = run_json(&[
+                "generate-proof",
+                "-i",
+                &path("out"),
+                "-w",
+                &path("witness"),
+                "-p",
+                &path("proving.key"),
+                "-j",
+                &path("proof.json"),
+                "-b",
+                backend,
+                "-s",
+                "g16",
+            ]);
+            assert!(!success);
+            assert_eq!(
+                report["error"]["message"],
+                format!(
+                    "Unsupported combination of parameters (backend: {}, curve: pallas, proving scheme: g16)",
+                    backend
+                )
+            );
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn test_export_witness_calculator() {
+        let tmp_dir = TempDir::new(".tmp").unwrap();
+        let tmp_base = tmp_dir.path();
+
+        let path = |name: &str| tmp_base.join(name).to_str().unwrap().to_string();
+
+        fs::write(
+            path("main.zok"),
+            "def main(field a, private field b) -> field { assert(a * b == 6); return a + b; }",
+        )
+        .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compile",
+                "-i",
+                &path("main.zok"),
+                "-o",
+                &path("out"),
+                "-s",
+                &path("abi.json"),
+            ])
+            .succeeds()
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "compute-witness",
+                "-i",
+                &path("out"),
+                "-o",
+                &path("witness"),
+                "--circom-witness",
+                &path("out.wtns"),
+                "-a",
+                "2",
+                "3",
+            ])
+            .succeeds()
+            .unwrap();
+
+        assert_cli::Assert::main_binary()
+            .with_args(&[
+                "export-witness-calculator",
+                "-i",
+                &path("out"),
+                "-s",
+                &path("abi.json"),
+                "-o",
+                &path("witness_calculator.wasm"),
+            ])
+            .succeeds()
+            .stdout()
+            .contains("Witness calculator exported to")
+            .unwrap();
+
+        // run the module under node and compare with the witness of the native interpreter
+        let script = r#"
+            const fs = require("fs");
+            const { initialize } = require("./witness_calculator.js");
+            initialize(fs.readFileSync("witness_calculator.wasm")).then((calculator) => {
+                fs.writeFileSync("wasm_witness", calculator.computeWitness(["2", "3"]));
+                try {
+                    calculator.computeWitness(["2", "4"]);
+                    process.exit(1);
+                } catch (e) {}
+            });
+        "#;
+
+        assert_cli::Assert::command(&["node", "-e", script])
+            .current_dir(tmp_base)
+            .succeeds()
+            .unwrap();
+
+        assert_eq!(
+            fs::read(path("wasm_witness")).unwrap(),
+            fs::read(path("witness")).unwrap()
+        );
+    }
+
     #[test]
     #[ignore]
     fn test_rng_tutorial() {
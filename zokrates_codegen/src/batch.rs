@@ -0,0 +1,81 @@
+//! Flattening of the batched equality embed.
+//!
+//! The differences `d_i = a_i - b_i` of two arrays of length `N` are combined with Horner's rule in a challenge `r`,
+//! `acc = (..(d_0 * r + d_1) * r + ..) * r + d_(N - 1)`, and `acc` is constrained to be zero. Each step but the last
+//! defines a product, and the last product is part of the final check, so that `N - 1` constraints are spent for
+//! `N > 1`.
+//!
+//! If the arrays differ, `acc` is the evaluation at `r` of a non-zero polynomial of degree at most `N - 1`, which is
+//! zero for at most `N - 1` values of `r`. The check is only sound if the prover cannot choose or predict `r` before
+//! committing to the arrays.
+
+use crate::{FlatStatements, FlatUExpression, Flattener};
+use zokrates_ast::common::RuntimeError;
+use zokrates_ast::flat::{FlatExpression, FlatStatement};
+use zokrates_field::Field;
+
+impl<'ast, T: Field> Flattener<'ast, T> {
+    /// Flattens a call to `batch_eq<N>(a, b, r)`, which always returns `true` if it is satisfied
+    pub(crate) fn flatten_batch_eq(
+        &mut self,
+        statements_flattened: &mut FlatStatements<'ast, T>,
+        len: usize,
+        params: Vec<FlatUExpression<T>>,
+    ) -> Vec<FlatUExpression<T>> {
+        assert_eq!(params.len(), 2 * len + 1);
+
+        let mut params = params.into_iter().map(|p| p.get_field_unchecked());
+
+        let left: Vec<_> = params.by_ref().take(len).collect();
+        let right: Vec<_> = params.by_ref().take(len).collect();
+        let r = params.next().unwrap();
+
+        // the prover knows a constant challenge in advance, and can pick arrays which differ but pass the check
+        if let (FlatExpression::Number(_), Some(attribution)) = (&r, self.attribution.as_mut()) {
+            attribution.record_warning("constant_challenge");
+        }
+
+        let differences: Vec<_> = left
+            .into_iter()
+            .zip(right)
+            .map(|(a, b)| FlatExpression::Sub(box a, box b))
+            .collect();
+
+        if let Some((last, init)) = differences.split_last() {
+            let condition = match init.split_first() {
+                // a single difference is checked directly
+                None => FlatStatement::Condition(
+                    FlatExpression::Number(T::zero()),
+                    last.clone(),
+                    RuntimeError::BatchEq,
+                ),
+                Some((first, rest)) => {
+                    let acc = rest.iter().fold(first.clone(), |acc, difference| {
+                        let product = self.define(
+                            FlatExpression::Mult(box acc, box r.clone()),
+                            statements_flattened,
+                        );
+                        FlatExpression::Add(box product.into(), box difference.clone())
+                    });
+
+                    // `acc * r == -d_(N - 1)`, the last step of the accumulation being the final check
+                    FlatStatement::Condition(
+                        FlatExpression::Sub(
+                            box FlatExpression::Number(T::zero()),
+                            box last.clone(),
+                        ),
+                        FlatExpression::Mult(box acc, box r),
+                        RuntimeError::BatchEq,
+                    )
+                }
+            };
+
+            statements_flattened.push_back(condition);
+        }
+
+        // the call fails rather than returning `false`
+        vec![FlatUExpression::with_field(
+            FlatExpression::Number(T::one()),
+        )]
+    }
+}
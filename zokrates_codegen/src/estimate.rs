@@ -0,0 +1,770 @@
+//! Estimation of the size of the constraint system of a program, without flattening it.
+//!
+//! The upper bounds follow the `Flattener`: each operation of the zir program is given the largest number of
+//! constraints and variables it can be flattened to, ignoring the decompositions which are cached and the constraints
+//! removed by the optimizer. The lower bounds only count what the optimizer always keeps: the range checks of the
+//! arguments and the definitions of the outputs. Variables are counted as in a witness, including `~one`.
+//!
+//! The same upper bounds are attributed to the source statements and function calls of a program compiled with span
+//! logs by `annotate`.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter::Sum;
+use std::marker::PhantomData;
+use std::ops::{Add, Mul};
+use zokrates_ast::common::embed::*;
+use zokrates_ast::common::{FlatEmbed, SourceMetadata};
+use zokrates_ast::flat::{FlatFunctionIterator, FlatStatement};
+use zokrates_ast::untyped::Position;
+use zokrates_ast::zir::types::{Type, UBitwidth};
+use zokrates_ast::zir::{
+    BooleanExpression, ConditionalExpression, FieldElementExpression, SelectExpression,
+    ShouldReduce, UExpression, UExpressionInner, ZirAssemblyStatement, ZirExpression,
+    ZirExpressionList, ZirFunction, ZirStatement,
+};
+use zokrates_common::{CompileConfig, DivByZero};
+use zokrates_field::Field;
+
+/// An inclusive range
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Bounds {
+    pub lower: usize,
+    pub upper: usize,
+}
+
+impl Bounds {
+    pub fn contains(&self, value: usize) -> bool {
+        self.lower <= value && value <= self.upper
+    }
+}
+
+/// The estimated size of the constraint system of a program
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Estimate {
+    pub constraints: Bounds,
+    pub variables: Bounds,
+}
+
+impl fmt::Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "constraints: between {} and {}",
+            self.constraints.lower, self.constraints.upper
+        )?;
+        writeln!(
+            f,
+            "variables: between {} and {}",
+            self.variables.lower, self.variables.upper
+        )
+    }
+}
+
+/// The number of constraints and variables of some statements
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Cost {
+    pub constraints: usize,
+    pub variables: usize,
+}
+
+impl Cost {
+    const ZERO: Cost = Cost::new(0, 0);
+
+    const fn new(constraints: usize, variables: usize) -> Self {
+        Cost {
+            constraints,
+            variables,
+        }
+    }
+}
+
+impl Add for Cost {
+    type Output = Cost;
+
+    fn add(self, other: Cost) -> Cost {
+        Cost::new(
+            self.constraints + other.constraints,
+            self.variables + other.variables,
+        )
+    }
+}
+
+impl Mul<usize> for Cost {
+    type Output = Cost;
+
+    fn mul(self, n: usize) -> Cost {
+        Cost::new(self.constraints * n, self.variables * n)
+    }
+}
+
+impl Sum for Cost {
+    fn sum<I: Iterator<Item = Cost>>(iter: I) -> Cost {
+        iter.fold(Cost::ZERO, Add::add)
+    }
+}
+
+/// The estimated cost of a source statement or function call
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SpanCost {
+    /// The position of the statement or of the call in its file
+    pub position: Position,
+    /// The statements compiled from the span itself, once for each time it is unrolled or inlined
+    pub own: Cost,
+    /// The statements compiled from the span and from the functions it calls
+    pub inclusive: Cost,
+}
+
+/// The estimated cost of each statement and function call of a program, by file and in the order of their positions
+pub type Annotations = BTreeMap<String, Vec<SpanCost>>;
+
+// a definition of a new variable
+const DEFINE: Cost = Cost::new(1, 1);
+// an equality check, see `Flattener::eq_check`
+const EQ_CHECK: Cost = Cost::new(4, 4);
+// a two-to-one Poseidon hash: 81 s-boxes of three constraints each, see `Flattener::poseidon`
+const POSEIDON: Cost = Cost::new(243, 243);
+
+/// Estimate the number of constraints and variables `funct` flattens to
+pub fn estimate<T: Field>(funct: &ZirFunction<T>, config: CompileConfig) -> Estimate {
+    let estimator = Estimator::<T> {
+        config,
+        field: PhantomData,
+    };
+
+    // the `~one` variable
+    let one = Cost::new(0, 1);
+
+    let arguments: Cost = funct
+        .arguments
+        .iter()
+        .map(|p| estimator.parameter(&p.id.get_type()))
+        .sum();
+
+    // outputs are never optimized away
+    let outputs = DEFINE * funct.signature.outputs.len();
+
+    let statements: Cost = funct
+        .statements
+        .iter()
+        .map(|s| estimator.statement(s))
+        .sum();
+
+    let lower = one + arguments + outputs;
+    let upper = one + arguments + statements;
+
+    Estimate {
+        constraints: Bounds {
+            lower: lower.constraints,
+            upper: upper.constraints.max(lower.constraints),
+        },
+        variables: Bounds {
+            lower: lower.variables,
+            upper: upper.variables.max(lower.variables),
+        },
+    }
+}
+
+/// Attribute the upper bound of the cost of each statement of `funct` to the source statement or function call it is
+/// compiled from, `funct` being checked with span logs
+///
+/// The own costs of all spans add up to the upper bound of `estimate`, without the range checks of the arguments.
+/// The statements of a function are attributed to the call they are inlined at, and to the statements they come from.
+pub fn annotate<T: Field>(funct: &ZirFunction<T>, config: CompileConfig) -> Annotations {
+    let mut annotator = Annotator {
+        estimator: Estimator {
+            config,
+            field: PhantomData,
+        },
+        frames: vec![None],
+        costs: BTreeMap::new(),
+    };
+
+    annotator.statements(&funct.statements, 0);
+
+    let mut annotations = Annotations::new();
+    for ((file, position), (own, inclusive)) in annotator.costs {
+        annotations
+            .entry(file.to_string())
+            .or_default()
+            .push(SpanCost {
+                position,
+                own,
+                inclusive,
+            });
+    }
+
+    annotations
+}
+
+struct Annotator<'a, T> {
+    estimator: Estimator<T>,
+    // the location of the current statement or call of each function being inlined, innermost last
+    frames: Vec<Option<&'a SourceMetadata>>,
+    // the own and inclusive costs of each span
+    costs: BTreeMap<(&'a str, Position), (Cost, Cost)>,
+}
+
+impl<'a, T: Field> Annotator<'a, T> {
+    // attribute `statements`, which are nested in `depth` branches
+    fn statements<'ast>(&mut self, statements: &'a [ZirStatement<'ast, T>], depth: usize) {
+        for s in statements {
+            match s {
+                ZirStatement::PushCallLog(..) => self.frames.push(None),
+                ZirStatement::PopCallLog => {
+                    self.frames.pop();
+                }
+                ZirStatement::SpanLog(span) => *self.frames.last_mut().unwrap() = Some(span),
+                // the statements of the branches are attributed to the spans they come from
+                ZirStatement::IfElse(condition, consequence, alternative) => {
+                    self.charge(self.estimator.boolean(condition) + DEFINE, depth);
+                    self.statements(consequence, depth + 1);
+                    self.statements(alternative, depth + 1);
+                }
+                s => self.charge(self.estimator.statement(s), depth),
+            }
+        }
+    }
+
+    fn charge(&mut self, cost: Cost, depth: usize) {
+        let cost = (0..depth).fold(cost, |cost, _| self.estimator.isolate(cost));
+
+        let mut spans: Vec<_> = self
+            .frames
+            .iter()
+            .flatten()
+            .map(|span| (span.file.as_str(), span.position))
+            .collect();
+
+        // statements which precede the first statement of a function, such as the definition of its arguments, are
+        // attributed to the call
+        if let Some(span) = spans.last() {
+            let (own, _) = self.costs.entry(*span).or_default();
+            *own = *own + cost;
+        }
+
+        // recursive calls go through the same span several times
+        spans.sort();
+        spans.dedup();
+        for span in spans {
+            let (_, inclusive) = self.costs.entry(span).or_default();
+            *inclusive = *inclusive + cost;
+        }
+    }
+}
+
+struct Estimator<T> {
+    config: CompileConfig,
+    field: PhantomData<T>,
+}
+
+impl<T: Field> Estimator<T> {
+    fn field_bits(&self) -> usize {
+        T::get_required_bits()
+    }
+
+    // the bit decomposition of a value of `width` bits
+    fn bits(&self, width: usize) -> Cost {
+        Cost::new(width + 1, width)
+    }
+
+    // the conditions of `Flattener::constant_le_check` on `len` bits
+    fn constant_le_check(&self, len: usize) -> Cost {
+        Cost::new(2 * len + 1, 3 * len)
+    }
+
+    // `Flattener::enforce_constant_le_check`, with a bound of at most `len` bits
+    fn enforce_constant_le_check(&self, len: usize) -> Cost {
+        self.bits(len) + self.constant_le_check(len) + Cost::new(1, 0)
+    }
+
+    // the two products on limbs of a multiplication or a division of `bitwidth`, if any, with the high product
+    // decomposed on `width` bits
+    fn split_product(&self, bitwidth: UBitwidth, width: usize) -> Cost {
+        match bitwidth.splits_products::<T>() {
+            true => DEFINE * 2 + self.bits(width),
+            false => Cost::ZERO,
+        }
+    }
+
+    // `Flattener::lt_check` on `width` bits, depending on whether one side is a constant
+    fn lt_check(&self, constant: bool, width: usize) -> Cost {
+        match constant {
+            // `Flattener::constant_field_le_check`
+            true => {
+                let n = self.field_bits();
+                self.bits(n) + self.constant_le_check(n) * 2 + Cost::new(1, 0) + EQ_CHECK
+            }
+            false => DEFINE * 2 + self.bits(width + 1),
+        }
+    }
+
+    fn parameter(&self, ty: &Type) -> Cost {
+        let variable = Cost::new(0, 1);
+        match ty {
+            Type::Uint(bitwidth) => variable + self.bits(bitwidth.to_usize()),
+            Type::Boolean => variable + Cost::new(1, 0),
+            Type::FieldElement => variable,
+        }
+    }
+
+    // make the constraints of a branch conditional, see `Flattener::make_conditional`
+    fn isolate(&self, cost: Cost) -> Cost {
+        match self.config.isolate_branches {
+            true => Cost::new(4 * cost.constraints, cost.variables + 3 * cost.constraints),
+            false => cost,
+        }
+    }
+
+    fn statement(&self, s: &ZirStatement<T>) -> Cost {
+        match s {
+            ZirStatement::PushCallLog(..)
+            | ZirStatement::PopCallLog
+            | ZirStatement::PushIterationLog(..)
+            | ZirStatement::PopIterationLog
+            | ZirStatement::SpanLog(..) => Cost::ZERO,
+            ZirStatement::Return(expressions) => expressions
+                .iter()
+                .map(|e| self.expression(e) + DEFINE)
+                .sum(),
+            ZirStatement::Definition(_, e) => self.expression(e) + DEFINE,
+            ZirStatement::IfElse(condition, consequence, alternative) => {
+                let consequence = consequence.iter().map(|s| self.statement(s)).sum();
+                let alternative = alternative.iter().map(|s| self.statement(s)).sum();
+                self.boolean(condition)
+                    + DEFINE
+                    + self.isolate(consequence)
+                    + self.isolate(alternative)
+            }
+            ZirStatement::Assertion(e, _) => self.assertion(e),
+            ZirStatement::MultipleDefinition(
+                variables,
+                ZirExpressionList::EmbedCall(embed, generics, arguments),
+            ) => {
+                let arguments: Cost = arguments.iter().map(|e| self.expression(e)).sum();
+                // the arguments of conversions from bits are flattened again to cache the bits
+                let arguments = match embed.from_bits_bitwidth() {
+                    Some(_) => arguments * 2,
+                    None => arguments,
+                };
+                arguments + self.embed(embed, generics) + DEFINE * variables.len()
+            }
+            ZirStatement::Log(_, expressions) => expressions
+                .iter()
+                .flat_map(|(_, e)| e)
+                .map(|e| self.expression(e))
+                .sum(),
+            ZirStatement::Assembly(statements) => statements
+                .iter()
+                .map(|s| match s {
+                    ZirAssemblyStatement::Assignment(assignees, _) => Cost::new(0, assignees.len()),
+                    ZirAssemblyStatement::Constraint(lhs, rhs, _) => {
+                        self.field(lhs) + self.field(rhs) + Cost::new(1, 0)
+                    }
+                    ZirAssemblyStatement::UnsafeWitness(assignees, _, arguments) => {
+                        arguments.iter().map(|a| self.field(a)).sum::<Cost>()
+                            + Cost::new(0, assignees.len())
+                    }
+                })
+                .sum(),
+        }
+    }
+
+    fn assertion(&self, e: &BooleanExpression<T>) -> Cost {
+        // see `Flattener::flatten_equality_assertion`
+        let equality = Cost::new(4, 3);
+
+        match e {
+            BooleanExpression::And(left, right) => self.assertion(left) + self.assertion(right),
+            BooleanExpression::FieldEq(left, right) => {
+                self.field(left) + self.field(right) + equality
+            }
+            BooleanExpression::UintEq(left, right) => self.uint(left) + self.uint(right) + equality,
+            BooleanExpression::BoolEq(left, right) => {
+                self.boolean(left) + self.boolean(right) + equality
+            }
+            BooleanExpression::FieldLt(left, right) | BooleanExpression::FieldLe(left, right) => {
+                let constant = is_number(left) || is_number(right);
+                self.field(left) + self.field(right) + self.enforced_comparison(constant)
+            }
+            // see `Flattener::flatten_uint_comparison_assertion`: the operands fit in their bitwidth, and a constant
+            // range check is only used if it takes fewer constraints than the decomposition of their difference
+            BooleanExpression::UintLt(left, right) | BooleanExpression::UintLe(left, right) => {
+                let bitwidth = left.bitwidth.to_usize();
+                let comparison = match is_value(left) || is_value(right) {
+                    true => self.enforce_constant_le_check(bitwidth),
+                    false => DEFINE * 2 + self.bits(bitwidth),
+                };
+                self.uint(left) + self.uint(right) + comparison
+            }
+            e => self.boolean(e) + Cost::new(1, 0),
+        }
+    }
+
+    // an assertion of `<` or `<=`, against a constant if `constant` is set
+    fn enforced_comparison(&self, constant: bool) -> Cost {
+        match constant {
+            true => self.enforce_constant_le_check(self.field_bits()),
+            false => EQ_CHECK + self.lt_check(false, self.field_bits() - 2) + Cost::new(1, 0),
+        }
+    }
+
+    fn embed(&self, embed: &FlatEmbed, generics: &[u32]) -> Cost {
+        // every embed is listed, so that a new one cannot be estimated as free by mistake
+        match embed {
+            FlatEmbed::FieldToBoolUnsafe => Cost::ZERO,
+            // the bits of integers are already known
+            FlatEmbed::U8ToBits
+            | FlatEmbed::U16ToBits
+            | FlatEmbed::U32ToBits
+            | FlatEmbed::U64ToBits
+            | FlatEmbed::U8FromBits
+            | FlatEmbed::U16FromBits
+            | FlatEmbed::U32FromBits
+            | FlatEmbed::U64FromBits
+            | FlatEmbed::U8ToLeBits
+            | FlatEmbed::U16ToLeBits
+            | FlatEmbed::U32ToLeBits
+            | FlatEmbed::U64ToLeBits
+            | FlatEmbed::U8FromLeBits
+            | FlatEmbed::U16FromLeBits
+            | FlatEmbed::U32FromLeBits
+            | FlatEmbed::U64FromLeBits
+            | FlatEmbed::I8ToBits
+            | FlatEmbed::I16ToBits
+            | FlatEmbed::I32ToBits
+            | FlatEmbed::I8FromBits
+            | FlatEmbed::I16FromBits
+            | FlatEmbed::I32FromBits => Cost::ZERO,
+            FlatEmbed::FieldToU8Unchecked
+            | FlatEmbed::FieldToU16Unchecked
+            | FlatEmbed::FieldToU32Unchecked
+            | FlatEmbed::FieldToU64Unchecked => Cost::ZERO,
+            // a checked conversion is estimated without the bound of its argument, as if it needed a range check
+            FlatEmbed::FieldToU8
+            | FlatEmbed::FieldToU16
+            | FlatEmbed::FieldToU32
+            | FlatEmbed::FieldToU64 => {
+                self.bits(embed.field_to_uint_bitwidth().unwrap().to_usize())
+            }
+            FlatEmbed::UnconstrainedWitness => Cost::new(0, 1),
+            FlatEmbed::BitArrayLe => {
+                let len = generics[0] as usize;
+                DEFINE * len + self.constant_le_check(len) + EQ_CHECK
+            }
+            FlatEmbed::Unpack => synthesized(unpack_to_bitwidth::<T>(generics[0] as usize)),
+            FlatEmbed::Pack => Cost::ZERO,
+            FlatEmbed::UnpackStrict => {
+                let bit_width = generics[0] as usize;
+                match bit_width == self.field_bits() {
+                    true => {
+                        self.bits(bit_width) + self.constant_le_check(bit_width) + Cost::new(1, 0)
+                    }
+                    false => self.bits(bit_width),
+                }
+            }
+            // a product at each step of the accumulation, the last one being part of the final check
+            FlatEmbed::BatchEq => match generics[0] as usize {
+                0 => Cost::ZERO,
+                len => DEFINE * len.saturating_sub(2) + Cost::new(1, 0),
+            },
+            FlatEmbed::MerkleVerifyPoseidon => {
+                let depth = generics[0] as usize;
+                // the arguments, then a selection and a hash at each level
+                DEFINE * (2 + 2 * depth) + (DEFINE + POSEIDON) * depth + EQ_CHECK
+            }
+            #[cfg(feature = "bellman")]
+            FlatEmbed::Sha256Round => synthesized(sha256_round::<T>()),
+            #[cfg(feature = "bellman")]
+            FlatEmbed::MerkleVerifySha256 => {
+                let depth = generics[0] as usize;
+                let bits = self.enforce_constant_le_check(self.field_bits());
+                // the leaf and each sibling are decomposed, then each level selects the 254 bits which are not
+                // constant and runs two rounds of compression
+                DEFINE * (2 + 2 * depth)
+                    + bits * (depth + 1)
+                    + (DEFINE * 254 + synthesized(sha256_round::<T>()) * 2) * depth
+                    + EQ_CHECK
+            }
+            #[cfg(feature = "ark")]
+            FlatEmbed::SnarkVerifyBls12377 => {
+                synthesized(snark_verify_bls12_377::<T>(generics[0] as usize))
+            }
+        }
+    }
+
+    fn expression(&self, e: &ZirExpression<T>) -> Cost {
+        match e {
+            ZirExpression::FieldElement(e) => self.field(e),
+            ZirExpression::Boolean(e) => self.boolean(e),
+            ZirExpression::Uint(e) => self.uint(e),
+        }
+    }
+
+    fn select<E>(&self, e: &SelectExpression<T, E>, element: impl Fn(&E) -> Cost) -> Cost {
+        // the index is compared to each position, and each element is multiplied by the result
+        let index = self.uint(&e.index);
+        e.array
+            .iter()
+            .map(|e| index + EQ_CHECK + element(e) + DEFINE)
+            .sum::<Cost>()
+            + Cost::new(1, 0)
+    }
+
+    fn conditional<E>(&self, e: &ConditionalExpression<T, E>, branch: impl Fn(&E) -> Cost) -> Cost {
+        self.boolean(&e.condition)
+            + DEFINE
+            + self.isolate(branch(e.consequence.as_ref()))
+            + self.isolate(branch(e.alternative.as_ref()))
+            + DEFINE * 5
+    }
+
+    fn field(&self, e: &FieldElementExpression<T>) -> Cost {
+        match e {
+            FieldElementExpression::Number(_) | FieldElementExpression::Identifier(_) => Cost::ZERO,
+            FieldElementExpression::Select(e) => self.select(e, |e| self.field(e)),
+            FieldElementExpression::Conditional(e) => self.conditional(e, |e| self.field(e)),
+            // non-linear operands are defined
+            FieldElementExpression::Add(left, right)
+            | FieldElementExpression::Sub(left, right)
+            | FieldElementExpression::Mult(left, right) => {
+                self.field(left) + self.field(right) + DEFINE * 2
+            }
+            // the operands are defined, then the quotient under `DivByZero::Error`, or the zero flag, the inverse and
+            // the quotient under `DivByZero::DefineZero`
+            FieldElementExpression::Div(left, right, _) => {
+                self.field(left)
+                    + self.field(right)
+                    + match self.config.div_by_zero {
+                        DivByZero::Error => DEFINE * 3,
+                        DivByZero::DefineZero => DEFINE * 5,
+                    }
+            }
+            FieldElementExpression::Pow(base, exponent) => {
+                let bits = match exponent.as_inner() {
+                    UExpressionInner::Value(e) => (128 - e.leading_zeros()) as usize,
+                    _ => 128,
+                };
+                self.field(base) + DEFINE * (2 * bits)
+            }
+            FieldElementExpression::And(left, right)
+            | FieldElementExpression::Or(left, right)
+            | FieldElementExpression::Xor(left, right) => self.field(left) + self.field(right),
+            FieldElementExpression::LeftShift(e, by)
+            | FieldElementExpression::RightShift(e, by) => self.field(e) + self.uint(by),
+        }
+    }
+
+    fn boolean(&self, e: &BooleanExpression<T>) -> Cost {
+        match e {
+            BooleanExpression::Value(_) | BooleanExpression::Identifier(_) => Cost::ZERO,
+            BooleanExpression::Select(e) => self.select(e, |e| self.boolean(e)),
+            BooleanExpression::Conditional(e) => self.conditional(e, |e| self.boolean(e)),
+            BooleanExpression::FieldLt(left, right) => {
+                let constant = is_number(left) || is_number(right);
+                self.field(left)
+                    + self.field(right)
+                    + self.lt_check(constant, self.field_bits() - 2)
+            }
+            // both sides are flattened twice, once for each comparison
+            BooleanExpression::FieldLe(left, right) => {
+                let constant = is_number(left) || is_number(right);
+                (self.field(left) + self.field(right)) * 2
+                    + self.lt_check(constant, self.field_bits() - 2)
+                    + EQ_CHECK
+            }
+            BooleanExpression::FieldEq(left, right) => {
+                self.field(left) + self.field(right) + EQ_CHECK
+            }
+            BooleanExpression::UintLt(left, right) => {
+                let constant = is_value(left) || is_value(right);
+                self.uint(left)
+                    + self.uint(right)
+                    + self.lt_check(constant, left.bitwidth.to_usize())
+            }
+            BooleanExpression::UintLe(left, right) => {
+                let constant = is_value(left) || is_value(right);
+                (self.uint(left) + self.uint(right)) * 2
+                    + self.lt_check(constant, left.bitwidth.to_usize())
+                    + EQ_CHECK
+            }
+            BooleanExpression::UintEq(left, right) => self.uint(left) + self.uint(right) + EQ_CHECK,
+            BooleanExpression::BoolEq(left, right)
+            | BooleanExpression::And(left, right)
+            | BooleanExpression::Or(left, right) => {
+                self.boolean(left) + self.boolean(right) + DEFINE
+            }
+            BooleanExpression::Not(e) => self.boolean(e),
+        }
+    }
+
+    fn uint(&self, e: &UExpression<T>) -> Cost {
+        let bitwidth = e.bitwidth.to_usize();
+
+        let operation = match e.as_inner() {
+            UExpressionInner::Value(_) | UExpressionInner::Identifier(_) => Cost::ZERO,
+            UExpressionInner::Select(e) => self.select(e, |e| self.uint(e)),
+            UExpressionInner::Conditional(e) => self.conditional(e, |e| self.uint(e)),
+            UExpressionInner::Add(left, right) | UExpressionInner::Sub(left, right) => {
+                self.uint(left) + self.uint(right) + DEFINE * 2
+            }
+            // on limbs, the high product is decomposed
+            UExpressionInner::Mult(left, right) => {
+                self.uint(left)
+                    + self.uint(right)
+                    + DEFINE * 3
+                    + self.split_product(e.bitwidth, 2 * bitwidth - e.bitwidth.limb_bitwidth())
+            }
+            // the quotient, the remainder and their difference to the divisor are decomposed
+            UExpressionInner::Div(left, right) | UExpressionInner::Rem(left, right) => {
+                self.uint(left)
+                    + self.uint(right)
+                    + DEFINE * 2
+                    + Cost::new(1, 2)
+                    + self.bits(bitwidth) * 3
+                    + self.split_product(e.bitwidth, bitwidth - e.bitwidth.limb_bitwidth())
+            }
+            // one constraint per bit, two for the majority function of sha256
+            UExpressionInner::Xor(left, right) => {
+                self.uint(left) + self.uint(right) + DEFINE * (2 * bitwidth)
+            }
+            UExpressionInner::And(left, right) | UExpressionInner::Or(left, right) => {
+                self.uint(left) + self.uint(right) + DEFINE * bitwidth
+            }
+            UExpressionInner::Not(e) => self.uint(e) + DEFINE * bitwidth,
+            UExpressionInner::LeftShift(e, _) | UExpressionInner::RightShift(e, _) => self.uint(e),
+        };
+
+        // without annotations, assume the value is reduced from the full width of the field
+        let (should_reduce, actual_bitwidth) = match &e.metadata {
+            Some(metadata) => (
+                !matches!(metadata.should_reduce, ShouldReduce::False),
+                metadata.bitwidth() as usize,
+            ),
+            None => (true, self.field_bits()),
+        };
+
+        let reduction = match should_reduce {
+            true => match &e.metadata {
+                // values bounded by their metadata are only decomposed to the bits they need
+                Some(_) => self.bits(actual_bitwidth.max(1)),
+                None => self.bits(actual_bitwidth.max(bitwidth)),
+            },
+            false => Cost::ZERO,
+        };
+
+        operation + reduction
+    }
+}
+
+fn is_number<T>(e: &FieldElementExpression<T>) -> bool {
+    matches!(e, FieldElementExpression::Number(_))
+}
+
+fn is_value<T>(e: &UExpression<T>) -> bool {
+    matches!(e.as_inner(), UExpressionInner::Value(_))
+}
+
+// the cost of inlining a synthesized embed, see `Flattener::flatten_embed_call_aux`
+fn synthesized<'ast, T>(
+    funct: FlatFunctionIterator<'ast, T, impl IntoIterator<Item = FlatStatement<'ast, T>>>,
+) -> Cost {
+    let arguments = DEFINE * funct.arguments.len();
+
+    arguments
+        + funct
+            .statements
+            .into_iter()
+            .map(|s| match s {
+                FlatStatement::Definition(..) => DEFINE,
+                FlatStatement::Condition(..) => Cost::new(1, 0),
+                FlatStatement::Directive(d) => Cost::new(0, d.outputs.len()),
+                FlatStatement::Block(..) | FlatStatement::Log(..) => Cost::ZERO,
+            })
+            .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_ast::zir::types::{Signature, UBitwidth};
+    use zokrates_ast::zir::{Id, Parameter, UMetadata, Variable};
+    use zokrates_field::Bn128Field;
+
+    #[test]
+    fn arguments_and_outputs() {
+        // def main(private u8 a, field b) -> field { return b * b; }
+        let funct: ZirFunction<Bn128Field> = ZirFunction {
+            arguments: vec![
+                Parameter::private(Variable::uint("a".into(), UBitwidth::B8)),
+                Parameter {
+                    id: Variable::field_element("b"),
+                    private: false,
+                    bound: None,
+                    signed: false,
+                },
+            ],
+            statements: vec![ZirStatement::Return(vec![FieldElementExpression::Mult(
+                box FieldElementExpression::identifier("b".into()),
+                box FieldElementExpression::identifier("b".into()),
+            )
+            .into()])],
+            signature: Signature::new()
+                .inputs(vec![Type::Uint(UBitwidth::B8), Type::FieldElement])
+                .outputs(vec![Type::FieldElement]),
+        };
+
+        let estimate = estimate(&funct, CompileConfig::default());
+
+        // the bits of `a`, and the output
+        assert_eq!(estimate.constraints.lower, 9 + 1);
+        assert_eq!(estimate.variables.lower, 1 + 2 + 8 + 1);
+        assert_eq!(estimate.constraints.upper, 9 + 2 + 1);
+        assert_eq!(estimate.variables.upper, 1 + 2 + 8 + 2 + 1);
+    }
+
+    #[test]
+    fn reduction() {
+        let estimator = Estimator::<Bn128Field> {
+            config: CompileConfig::default(),
+            field: PhantomData,
+        };
+
+        let sum = UExpressionInner::Add(
+            box UExpression::identifier("a".into())
+                .annotate(UBitwidth::B32)
+                .metadata(UMetadata::parameter(UBitwidth::B32)),
+            box UExpressionInner::Value(1)
+                .annotate(UBitwidth::B32)
+                .metadata(UMetadata {
+                    max: 1u32.into(),
+                    should_reduce: ShouldReduce::False,
+                    signed: false,
+                }),
+        )
+        .annotate(UBitwidth::B32);
+
+        let unreduced = sum.clone().metadata(UMetadata {
+            max: Bn128Field::from(2u64.pow(32)),
+            should_reduce: ShouldReduce::False,
+            signed: false,
+        });
+        let bounded = sum.clone().metadata(UMetadata {
+            max: Bn128Field::from(100),
+            should_reduce: ShouldReduce::True,
+            signed: false,
+        });
+        let reduced = sum.metadata(UMetadata {
+            max: Bn128Field::from(2u64.pow(32)),
+            should_reduce: ShouldReduce::True,
+            signed: false,
+        });
+
+        assert_eq!(estimator.uint(&unreduced), Cost::new(2, 2));
+        // the sum takes 33 bits
+        assert_eq!(estimator.uint(&reduced), Cost::new(2 + 34, 2 + 33));
+        // a sum known to be at most 100 takes 7 bits
+        assert_eq!(estimator.uint(&bounded), Cost::new(2 + 8, 2 + 7));
+    }
+}
@@ -7,22 +7,39 @@
 //! @author Jacob Eberhardt <jacob.eberhardt@tu-berlin.de>
 //! @date 2017
 
+mod batch;
+mod estimate;
+mod merkle;
+mod poseidon;
+mod range;
+mod report;
+mod sink;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 mod utils;
 
+pub use self::estimate::{annotate, estimate, Annotations, Bounds, Cost, Estimate, SpanCost};
+use self::report::Attribution;
+pub use self::report::{FunctionReport, Report};
+pub use self::sink::ConstraintSink;
 use self::utils::flat_expression_from_bits;
 use zokrates_ast::zir::{
-    ConditionalExpression, SelectExpression, ShouldReduce, UMetadata, ZirAssemblyStatement,
+    self, ConditionalExpression, SelectExpression, ShouldReduce, UMetadata, ZirAssemblyStatement,
     ZirExpressionList,
 };
 use zokrates_interpreter::Interpreter;
 
+use serde::{Deserialize, Serialize};
 use std::collections::{
     hash_map::{Entry, HashMap},
-    VecDeque,
+    BTreeMap, VecDeque,
 };
+use std::fmt;
+use std::sync::{Arc, Mutex};
 use zokrates_ast::common::embed::*;
+use zokrates_ast::common::stack::maybe_grow;
 use zokrates_ast::common::FlatEmbed;
-use zokrates_ast::common::{RuntimeError, Variable};
+use zokrates_ast::common::{AssertionError, Diagnostic, RuntimeError, SourceMetadata, Variable};
 use zokrates_ast::flat::*;
 use zokrates_ast::ir::Solver;
 use zokrates_ast::zir::types::{Type, UBitwidth};
@@ -31,11 +48,14 @@ use zokrates_ast::zir::{
     UExpression, UExpressionInner, Variable as ZirVariable, ZirExpression, ZirFunction,
     ZirStatement,
 };
-use zokrates_common::CompileConfig;
+use zokrates_common::{CompileConfig, DivByZero};
 use zokrates_field::Field;
 
 type FlatStatements<'ast, T> = VecDeque<FlatStatement<'ast, T>>;
 
+// the number of bits by which the bitwidth of a reduced value can exceed the bits it needs before a warning is logged
+const BITWIDTH_WARNING_THRESHOLD: usize = 8;
+
 /// Flattens a function
 ///
 /// # Arguments
@@ -59,29 +79,411 @@ pub fn from_function_and_config<T: Field>(
             statements: funct.statements.into(),
             statements_flattened,
             flattener,
+            budget: None,
         },
         return_count: funct.signature.outputs.len(),
     }
 }
 
+/// The program being flattened went over the constraint budget of the configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetExceeded {
+    pub budget: usize,
+    /// The number of constraints emitted when flattening was aborted
+    pub count: usize,
+    /// The source statement whose constraints exceeded the budget, if known. Statements are located when the program
+    /// was checked with span logs, and source assertions always are
+    pub span: Option<SourceMetadata>,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Constraint budget of {} exceeded: {} constraints were emitted",
+            self.budget, self.count
+        )?;
+        match &self.span {
+            Some(span) => write!(f, " when flattening {}", span),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Whether flattening was aborted because the constraint budget was exceeded. This is only known once the flattened
+/// statements were consumed
+#[derive(Debug, Clone, Default)]
+pub struct BudgetCheck(Arc<Mutex<Option<BudgetExceeded>>>);
+
+impl BudgetCheck {
+    pub fn result(&self) -> Result<(), BudgetExceeded> {
+        match self.0.lock().unwrap().clone() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    fn exceed(&self, e: BudgetExceeded) {
+        *self.0.lock().unwrap() = Some(e);
+    }
+}
+
+/// The warnings about the statements being flattened, each reported once. They are only all known once the flattened
+/// statements were consumed
+#[derive(Debug, Clone, Default)]
+pub struct Warnings(Arc<Mutex<Vec<Diagnostic>>>);
+
+impl Warnings {
+    /// Takes the warnings reported so far
+    pub fn take(&self) -> Vec<Diagnostic> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+
+    fn push(&self, d: Diagnostic) {
+        let mut warnings = self.0.lock().unwrap();
+        if !warnings.contains(&d) {
+            warnings.push(d);
+        }
+    }
+}
+
+// the constraint budget of a flattening, charged as the statements are flattened
+#[derive(Debug)]
+struct Budget {
+    limit: usize,
+    count: usize,
+    // the location of the current statement of each function being inlined, innermost last
+    frames: Vec<Option<SourceMetadata>>,
+    check: BudgetCheck,
+}
+
+impl Budget {
+    // track the source statement the following statements are flattened from
+    fn enter<T>(&mut self, s: &ZirStatement<T>) {
+        match s {
+            ZirStatement::PushCallLog(..) => self.frames.push(None),
+            ZirStatement::PopCallLog if self.frames.len() > 1 => {
+                self.frames.pop();
+            }
+            ZirStatement::SpanLog(span) => *self.frames.last_mut().unwrap() = Some(span.clone()),
+            _ => {}
+        }
+    }
+
+    // charge the constraints of the statements flattened from `s`, returning whether the budget still holds
+    fn charge<'a, 'ast: 'a, T: 'a>(
+        &mut self,
+        assertion: Option<SourceMetadata>,
+        statements: impl Iterator<Item = &'a FlatStatement<'ast, T>>,
+    ) -> bool {
+        self.count += statements.map(constraint_count).sum::<usize>();
+
+        if self.count <= self.limit {
+            return true;
+        }
+
+        self.check.exceed(BudgetExceeded {
+            budget: self.limit,
+            count: self.count,
+            span: assertion.or_else(|| self.frames.iter().rev().flatten().next().cloned()),
+        });
+
+        false
+    }
+}
+
+/// Flattens a function, enforcing the `max_constraints` budget of `config` if it is set
+///
+/// Without a budget, this is equivalent to `from_function_and_config`. With a budget, the statements are still
+/// flattened lazily: the iterator ends as soon as the budget is exceeded, which the returned `BudgetCheck` reports once
+/// the statements were consumed.
+pub fn from_function_with_budget<T: Field>(
+    funct: ZirFunction<T>,
+    config: CompileConfig,
+) -> (FlattenerIterator<T>, BudgetCheck) {
+    let max_constraints = config.max_constraints;
+    let mut program = from_function_and_config(funct, config);
+    let check = BudgetCheck::default();
+
+    if let Some(limit) = max_constraints {
+        let inner = &mut program.statements;
+
+        let mut budget = Budget {
+            limit,
+            count: 0,
+            frames: vec![None],
+            check: check.clone(),
+        };
+
+        // the parameters may already have been constrained
+        if !budget.charge(None, inner.statements_flattened.iter()) {
+            inner.statements.clear();
+            inner.statements_flattened.clear();
+        }
+
+        inner.budget = Some(budget);
+    }
+
+    (program, check)
+}
+
+// the number of constraints a flat statement turns into
+fn constraint_count<T>(s: &FlatStatement<T>) -> usize {
+    match s {
+        FlatStatement::Condition(..) | FlatStatement::Definition(..) => 1,
+        FlatStatement::Block(statements) => statements.iter().map(constraint_count).sum(),
+        FlatStatement::Directive(..) | FlatStatement::Log(..) => 0,
+    }
+}
+
+// the error of a constraint checking an inverse, which keeps the metadata of critical assertions so that they can be
+// found in the compiled program, and the location of divisions by zero
+fn inverse_error(error: zir::RuntimeError) -> RuntimeError {
+    match error {
+        error @ zir::RuntimeError::DivisionByZero(Some(_)) => error.into(),
+        error if error.is_critical() => error.into(),
+        _ => RuntimeError::Inverse,
+    }
+}
+
+// whether a field expression is a sum or a difference, which flattens to a linear expression: its operands are kept if
+// they are linear and defined otherwise. This saves walking a long chain of sums again at each step to check it
+fn is_sum<T>(e: &FieldElementExpression<T>) -> bool {
+    matches!(
+        e,
+        FieldElementExpression::Add(..) | FieldElementExpression::Sub(..)
+    )
+}
+
+/// Where a flattened statement comes from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Origin {
+    /// The source function whose body the statement was flattened from
+    pub function: String,
+    /// The source assertion the statement enforces, if any
+    pub span: Option<SourceMetadata>,
+    /// The iterations of the labeled loops the statement was unrolled from, outermost first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub iterations: Vec<Iteration>,
+}
+
+/// An iteration of a labeled loop, `for u32 i in 0..64 as 'rounds: { ... }`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Iteration {
+    /// The function the loop is declared in, as in `Origin::function`. Labels are only unique within a function
+    pub function: String,
+    pub label: String,
+    pub index: u32,
+}
+
+/// The observers of a flattening, see `flatten_observed`
+pub struct Observers<'s, T> {
+    /// The name of the function the statements outside of any inlined call are attributed to
+    pub entry: String,
+    /// Record the origin of each statement and the source names of the variables
+    pub trace: bool,
+    /// Report the directives and embeds used by each source function
+    pub report: bool,
+    /// Hand each constraint to a sink as soon as it is emitted
+    pub sink: Option<&'s mut dyn ConstraintSink<T>>,
+}
+
+/// A flattened function, along with what the observers of the flattening recorded
+#[derive(Debug)]
+pub struct Observed<'ast, T> {
+    pub program: FlatProg<'ast, T>,
+    /// The origin of each statement, if traced
+    pub origins: Vec<Origin>,
+    /// The identifiers each variable was introduced for, if traced. Variables introduced by the flattener itself have
+    /// no name
+    pub names: BTreeMap<Variable, Vec<String>>,
+    /// The directives and embeds used by each source function, if reported
+    pub report: Option<Report>,
+    /// The warnings about the flattened statements
+    pub warnings: Warnings,
+}
+
+/// Flattens a function like `from_function_with_budget`, recording what `observers` ask for
+///
+/// The function is flattened eagerly, so that the sink is only borrowed for the duration of the call. If the budget is
+/// exceeded, the program is truncated and the returned `BudgetCheck` reports it.
+pub fn flatten_observed<'ast, T: Field>(
+    funct: ZirFunction<'ast, T>,
+    config: CompileConfig,
+    observers: Observers<'_, T>,
+) -> (Observed<'ast, T>, BudgetCheck) {
+    let Observers {
+        entry,
+        trace,
+        report,
+        mut sink,
+    } = observers;
+
+    let (mut program, check) = from_function_with_budget(funct, config);
+
+    let inner = &mut program.statements;
+
+    if report {
+        inner.flattener.attribution = Some(Attribution::new(entry.clone()));
+    }
+
+    let mut origins = vec![];
+
+    if trace || sink.is_some() {
+        inner.flatten_with_origins(entry, |s, origin| {
+            if let Some(sink) = sink.as_mut() {
+                sink::notify(&mut **sink, s, &origin);
+            }
+            if trace {
+                origins.push(origin);
+            }
+        });
+    } else {
+        while let Some(statement) = inner.statements.pop_front() {
+            inner.flatten(statement);
+        }
+    }
+
+    let mut names: BTreeMap<Variable, Vec<String>> = BTreeMap::new();
+    if trace {
+        for (id, variable) in &inner.flattener.layout {
+            names.entry(*variable).or_default().push(id.to_string());
+        }
+        for n in names.values_mut() {
+            n.sort();
+        }
+    }
+
+    let report = inner
+        .flattener
+        .attribution
+        .take()
+        .map(|attribution| attribution.report);
+
+    let warnings = inner.flattener.warnings();
+
+    (
+        Observed {
+            program: program.collect(),
+            origins,
+            names,
+            report,
+            warnings,
+        },
+        check,
+    )
+}
+
 pub struct FlattenerIteratorInner<'ast, T> {
     pub statements: VecDeque<ZirStatement<'ast, T>>,
     pub statements_flattened: FlatStatements<'ast, T>,
     pub flattener: Flattener<'ast, T>,
+    budget: Option<Budget>,
 }
 
 pub type FlattenerIterator<'ast, T> = FlatProgIterator<'ast, T, FlattenerIteratorInner<'ast, T>>;
 
+impl<'ast, T: Field> FlattenerIteratorInner<'ast, T> {
+    // flatten `s`, charging its constraints to the budget if any. Once the budget is exceeded, the remaining statements
+    // are dropped
+    fn flatten(&mut self, s: ZirStatement<'ast, T>) {
+        let assertion = match (&mut self.budget, &s) {
+            (Some(_), ZirStatement::Assertion(_, zir::RuntimeError::SourceAssertion(metadata))) => {
+                Some(metadata.clone())
+            }
+            (Some(budget), s) => {
+                budget.enter(s);
+                None
+            }
+            (None, _) => None,
+        };
+
+        let emitted = self.statements_flattened.len();
+
+        self.flattener
+            .flatten_statement(&mut self.statements_flattened, s);
+
+        if let Some(budget) = self.budget.as_mut() {
+            if !budget.charge(assertion, self.statements_flattened.iter().skip(emitted)) {
+                self.statements.clear();
+                self.statements_flattened.clear();
+            }
+        }
+    }
+
+    // flatten all statements, calling `emit` with each flattened statement and its origin in order, the constraints on
+    // the parameters first. Statements outside of any inlined call are attributed to `entry`
+    fn flatten_with_origins(
+        &mut self,
+        entry: String,
+        mut emit: impl FnMut(&FlatStatement<'ast, T>, Origin),
+    ) {
+        for s in &self.statements_flattened {
+            emit(
+                s,
+                Origin {
+                    function: entry.clone(),
+                    span: None,
+                    iterations: vec![],
+                },
+            );
+        }
+        let mut stack = vec![];
+        let mut iterations = vec![];
+
+        while let Some(statement) = self.statements.pop_front() {
+            let span = match &statement {
+                ZirStatement::PushCallLog(function) => {
+                    stack.push(function.clone());
+                    None
+                }
+                ZirStatement::PopCallLog => {
+                    stack.pop();
+                    None
+                }
+                ZirStatement::PushIterationLog(label, index) => {
+                    iterations.push(Iteration {
+                        function: stack.last().unwrap_or(&entry).clone(),
+                        label: label.clone(),
+                        index: *index,
+                    });
+                    None
+                }
+                ZirStatement::PopIterationLog => {
+                    iterations.pop();
+                    None
+                }
+                ZirStatement::Assertion(_, zir::RuntimeError::SourceAssertion(metadata)) => {
+                    Some(metadata.clone())
+                }
+                _ => None,
+            };
+
+            let emitted = self.statements_flattened.len();
+            self.flatten(statement);
+
+            let function = stack.last().unwrap_or(&entry);
+            for s in self.statements_flattened.iter().skip(emitted) {
+                emit(
+                    s,
+                    Origin {
+                        function: function.clone(),
+                        span: span.clone(),
+                        iterations: iterations.clone(),
+                    },
+                );
+            }
+        }
+    }
+}
+
 impl<'ast, T: Field> Iterator for FlattenerIteratorInner<'ast, T> {
     type Item = FlatStatement<'ast, T>;
 
     fn next(&mut self) -> Option<Self::Item> {
         while self.statements_flattened.is_empty() {
             match self.statements.pop_front() {
-                Some(s) => {
-                    self.flattener
-                        .flatten_statement(&mut self.statements_flattened, s);
-                }
+                Some(s) => self.flatten(s),
                 None => {
                     break;
                 }
@@ -101,6 +503,18 @@ pub struct Flattener<'ast, T> {
     layout: HashMap<Identifier<'ast>, Variable>,
     /// Cached bit decompositions to avoid re-generating them
     bits_cache: HashMap<FlatExpression<T>, Vec<FlatExpression<T>>>,
+    /// The directives and embeds used by each source function, if a report was requested
+    attribution: Option<Attribution>,
+    /// The variables of the arguments, from which prover-chosen values are derived
+    arguments: Vec<Variable>,
+    /// The number of prover-chosen values introduced so far
+    sample_count: usize,
+    /// The number of bits the values of bounded field arguments are constrained to fit in
+    bounds: HashMap<Variable, usize>,
+    /// The location of the source statement being flattened, if the statements were located with span logs
+    span: Option<SourceMetadata>,
+    /// The warnings about the statements flattened so far
+    warnings: Warnings,
 }
 
 trait FlattenOutput<T: Field>: Sized {
@@ -220,9 +634,26 @@ impl<'ast, T: Field> Flattener<'ast, T> {
             next_var_idx: 0,
             layout: HashMap::new(),
             bits_cache: HashMap::new(),
+            attribution: None,
+            arguments: vec![],
+            sample_count: 0,
+            bounds: HashMap::new(),
+            span: None,
+            warnings: Warnings::default(),
         }
     }
 
+    /// The warnings about the statements flattened by this flattener
+    pub fn warnings(&self) -> Warnings {
+        self.warnings.clone()
+    }
+
+    // warn about the statement being flattened, located in the source if possible
+    fn warn(&self, message: String) {
+        self.warnings
+            .push(Diagnostic::new(message).span(self.span.clone()));
+    }
+
     /// Flattens a definition, trying to avoid creating redundant variables
     fn define(
         &mut self,
@@ -742,6 +1173,36 @@ impl<'ast, T: Field> Flattener<'ast, T> {
         )
     }
 
+    /// The number of bits both `lhs` and `rhs` fit in, if it is smaller than `safe_width`
+    ///
+    /// Comparing values which are known to be small only requires decomposing their difference in few bits. This is
+    /// the case of expressions of bounded arguments, for which the bound is propagated through additions and
+    /// multiplications. Otherwise, `safe_width` is returned.
+    fn comparison_width(
+        &self,
+        lhs: &FlatExpression<T>,
+        rhs: &FlatExpression<T>,
+        safe_width: usize,
+    ) -> usize {
+        match (self.bound(lhs), self.bound(rhs)) {
+            (Some(l), Some(r)) => std::cmp::max(l, r).min(safe_width),
+            _ => safe_width,
+        }
+    }
+
+    // the number of bits `e` is known to fit in, if any
+    fn bound(&self, e: &FlatExpression<T>) -> Option<usize> {
+        match e {
+            FlatExpression::Number(n) => Some(n.bits() as usize),
+            FlatExpression::Identifier(v) => self.bounds.get(v).cloned(),
+            FlatExpression::Add(box l, box r) => {
+                Some(std::cmp::max(self.bound(l)?, self.bound(r)?) + 1)
+            }
+            FlatExpression::Mult(box l, box r) => Some(self.bound(l)? + self.bound(r)?),
+            FlatExpression::Sub(..) => None,
+        }
+    }
+
     #[must_use]
     fn le_check(
         &mut self,
@@ -784,6 +1245,10 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                 T::max_value() - constant,
             ),
             (lhs_flattened, rhs_flattened) => {
+                // the shifted difference must have a unique decomposition. Field comparisons use a safe width, and
+                // comparisons of integers which do not fit in the field are rejected by the uint optimizer
+                debug_assert!(bit_width + 1 < T::get_required_bits());
+
                 let lhs_id = self.define(lhs_flattened, statements_flattened);
                 let rhs_id = self.define(rhs_flattened, statements_flattened);
 
@@ -830,7 +1295,7 @@ impl<'ast, T: Field> Flattener<'ast, T> {
         statements_flattened: &mut FlatStatements<'ast, T>,
         expression: BooleanExpression<'ast, T>,
     ) -> FlatExpression<T> {
-        match expression {
+        maybe_grow(|| match expression {
             BooleanExpression::Identifier(x) => {
                 FlatExpression::Identifier(*self.layout.get(&x.id).unwrap())
             }
@@ -845,12 +1310,8 @@ impl<'ast, T: Field> Flattener<'ast, T> {
 
                 let lhs_flattened = self.flatten_field_expression(statements_flattened, lhs);
                 let rhs_flattened = self.flatten_field_expression(statements_flattened, rhs);
-                self.lt_check(
-                    statements_flattened,
-                    lhs_flattened,
-                    rhs_flattened,
-                    safe_width,
-                )
+                let width = self.comparison_width(&lhs_flattened, &rhs_flattened, safe_width);
+                self.lt_check(statements_flattened, lhs_flattened, rhs_flattened, width)
             }
             BooleanExpression::BoolEq(box lhs, box rhs) => {
                 // lhs and rhs are booleans, they flatten to 0 or 1
@@ -998,7 +1459,7 @@ impl<'ast, T: Field> Flattener<'ast, T> {
             BooleanExpression::Conditional(e) => self
                 .flatten_conditional_expression(statements_flattened, e)
                 .get_field_unchecked(),
-        }
+        })
     }
 
     fn u_to_bits(
@@ -1023,6 +1484,68 @@ impl<'ast, T: Field> Flattener<'ast, T> {
         FlatUExpression::with_bits(bits)
     }
 
+    // the bits of a uint are stored most significant first, so little endian decompositions only reorder them,
+    // without any additional constraint
+    fn flatten_to_bits(
+        &mut self,
+        embed: FlatEmbed,
+        param: FlatUExpression<T>,
+        bitwidth: UBitwidth,
+    ) -> Vec<FlatUExpression<T>> {
+        let mut bits = self.u_to_bits(param, bitwidth.to_usize().into());
+        if embed.bit_order() == Endianness::Little {
+            bits.reverse();
+        }
+        bits
+    }
+
+    fn flatten_from_bits(
+        &mut self,
+        embed: FlatEmbed,
+        mut params: Vec<FlatUExpression<T>>,
+        bitwidth: UBitwidth,
+    ) -> Vec<FlatUExpression<T>> {
+        if embed.bit_order() == Endianness::Little {
+            params.reverse();
+        }
+        vec![self.bits_to_u(params, bitwidth.to_usize().into())]
+    }
+
+    fn flatten_field_to_uint(
+        &mut self,
+        statements_flattened: &mut FlatStatements<'ast, T>,
+        embed: FlatEmbed,
+        param: FlatUExpression<T>,
+        bitwidth: UBitwidth,
+    ) -> Vec<FlatUExpression<T>> {
+        let bitwidth = bitwidth.to_usize();
+        let field = param.get_field_unchecked();
+
+        // an unchecked conversion, or one of a value known to fit, keeps the field element as it is
+        if embed.is_unchecked() || matches!(self.bound(&field), Some(b) if b <= bitwidth) {
+            return vec![FlatUExpression::with_field(field)];
+        }
+
+        self.warn(format!(
+            "Converting a field element to `u{}` adds {} constraints to prove that it fits: declare it as `field<{}>` or convert it with `u{}::unchecked` if it is known to fit",
+            bitwidth,
+            bitwidth + 1,
+            bitwidth,
+            bitwidth
+        ));
+
+        let e = FlatUExpression::with_field(field);
+        let bits = self.get_bits_unchecked(
+            &e,
+            bitwidth,
+            bitwidth,
+            statements_flattened,
+            RuntimeError::CastRange(bitwidth as u32),
+        );
+
+        vec![e.bits(bits)]
+    }
+
     /// Flattens a function call
     ///
     /// # Arguments
@@ -1038,6 +1561,10 @@ impl<'ast, T: Field> Flattener<'ast, T> {
         generics: Vec<u32>,
         param_expressions: Vec<ZirExpression<'ast, T>>,
     ) -> Vec<FlatUExpression<T>> {
+        if let Some(attribution) = self.attribution.as_mut() {
+            attribution.record_embed(embed.id());
+        }
+
         let mut params: Vec<_> = param_expressions
             .into_iter()
             .map(|p| {
@@ -1050,21 +1577,73 @@ impl<'ast, T: Field> Flattener<'ast, T> {
 
         match embed {
             FlatEmbed::FieldToBoolUnsafe => vec![params.pop().unwrap()],
-            FlatEmbed::U8ToBits => self.u_to_bits(params.pop().unwrap(), 8.into()),
-            FlatEmbed::U16ToBits => self.u_to_bits(params.pop().unwrap(), 16.into()),
-            FlatEmbed::U32ToBits => self.u_to_bits(params.pop().unwrap(), 32.into()),
-            FlatEmbed::U64ToBits => self.u_to_bits(params.pop().unwrap(), 64.into()),
-            FlatEmbed::U8FromBits => {
-                vec![self.bits_to_u(params, 8.into())]
+            // signed integers are decomposed as their two's complement representation, an unsigned integer of the
+            // same bitwidth
+            FlatEmbed::U8ToBits | FlatEmbed::U8ToLeBits | FlatEmbed::I8ToBits => {
+                self.flatten_to_bits(embed, params.pop().unwrap(), UBitwidth::B8)
+            }
+            FlatEmbed::U16ToBits | FlatEmbed::U16ToLeBits | FlatEmbed::I16ToBits => {
+                self.flatten_to_bits(embed, params.pop().unwrap(), UBitwidth::B16)
+            }
+            FlatEmbed::U32ToBits | FlatEmbed::U32ToLeBits | FlatEmbed::I32ToBits => {
+                self.flatten_to_bits(embed, params.pop().unwrap(), UBitwidth::B32)
+            }
+            FlatEmbed::U64ToBits | FlatEmbed::U64ToLeBits => {
+                self.flatten_to_bits(embed, params.pop().unwrap(), UBitwidth::B64)
+            }
+            FlatEmbed::U8FromBits | FlatEmbed::U8FromLeBits | FlatEmbed::I8FromBits => {
+                self.flatten_from_bits(embed, params, UBitwidth::B8)
+            }
+            FlatEmbed::U16FromBits | FlatEmbed::U16FromLeBits | FlatEmbed::I16FromBits => {
+                self.flatten_from_bits(embed, params, UBitwidth::B16)
             }
-            FlatEmbed::U16FromBits => {
-                vec![self.bits_to_u(params, 16.into())]
+            FlatEmbed::U32FromBits | FlatEmbed::U32FromLeBits | FlatEmbed::I32FromBits => {
+                self.flatten_from_bits(embed, params, UBitwidth::B32)
             }
-            FlatEmbed::U32FromBits => {
-                vec![self.bits_to_u(params, 32.into())]
+            FlatEmbed::U64FromBits | FlatEmbed::U64FromLeBits => {
+                self.flatten_from_bits(embed, params, UBitwidth::B64)
             }
-            FlatEmbed::U64FromBits => {
-                vec![self.bits_to_u(params, 64.into())]
+            FlatEmbed::FieldToU8 | FlatEmbed::FieldToU8Unchecked => self.flatten_field_to_uint(
+                statements_flattened,
+                embed,
+                params.pop().unwrap(),
+                UBitwidth::B8,
+            ),
+            FlatEmbed::FieldToU16 | FlatEmbed::FieldToU16Unchecked => self.flatten_field_to_uint(
+                statements_flattened,
+                embed,
+                params.pop().unwrap(),
+                UBitwidth::B16,
+            ),
+            FlatEmbed::FieldToU32 | FlatEmbed::FieldToU32Unchecked => self.flatten_field_to_uint(
+                statements_flattened,
+                embed,
+                params.pop().unwrap(),
+                UBitwidth::B32,
+            ),
+            FlatEmbed::FieldToU64 | FlatEmbed::FieldToU64Unchecked => self.flatten_field_to_uint(
+                statements_flattened,
+                embed,
+                params.pop().unwrap(),
+                UBitwidth::B64,
+            ),
+            FlatEmbed::UnconstrainedWitness => {
+                // the value is derived from all arguments so that witness generation is reproducible, and from the
+                // index of the sample so that successive samples differ
+                let inputs: Vec<_> = self.arguments.iter().map(|v| (*v).into()).collect();
+                let output = self.use_sym();
+
+                statements_flattened.push_back(FlatStatement::Directive(FlatDirective {
+                    solver: Solver::Sample(inputs.len(), self.sample_count),
+                    inputs,
+                    outputs: vec![output],
+                }));
+
+                self.sample_count += 1;
+
+                vec![FlatUExpression::with_field(FlatExpression::Identifier(
+                    output,
+                ))]
             }
             FlatEmbed::BitArrayLe => {
                 // get the length of the bit arrays
@@ -1112,6 +1691,51 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                     ),
                 )]
             }
+            // the arguments are booleans already, so packing them is a linear combination which needs no constraint
+            FlatEmbed::Pack => vec![FlatUExpression::with_field(flat_expression_from_bits(
+                params
+                    .into_iter()
+                    .map(|p| p.get_field_unchecked())
+                    .collect(),
+            ))],
+            FlatEmbed::UnpackStrict => {
+                let bit_width = generics[0] as usize;
+
+                let bits = self.get_bits_unchecked(
+                    &params.pop().unwrap(),
+                    bit_width,
+                    bit_width,
+                    statements_flattened,
+                    RuntimeError::Sum,
+                );
+
+                // a decomposition on as many bits as the field holds can also be one of `e + p`, check that it does
+                // not overflow the field
+                if bit_width == T::get_required_bits() {
+                    self.enforce_constant_le_check_bits(
+                        statements_flattened,
+                        &bits,
+                        &T::max_value().to_bits_be(),
+                        RuntimeError::Le,
+                    );
+                }
+
+                bits.into_iter().map(FlatUExpression::with_field).collect()
+            }
+            FlatEmbed::BatchEq => {
+                self.flatten_batch_eq(statements_flattened, generics[0] as usize, params)
+            }
+            FlatEmbed::MerkleVerifyPoseidon => self.flatten_merkle_verify_poseidon(
+                statements_flattened,
+                generics[0] as usize,
+                params,
+            ),
+            #[cfg(feature = "bellman")]
+            FlatEmbed::MerkleVerifySha256 => self.flatten_merkle_verify_sha256(
+                statements_flattened,
+                generics[0] as usize,
+                params,
+            ),
             funct => match funct {
                 FlatEmbed::Unpack => self.flatten_embed_call_aux(
                     statements_flattened,
@@ -1334,10 +1958,12 @@ impl<'ast, T: Field> Flattener<'ast, T> {
             solver: Solver::EuclideanDiv,
         }));
 
+        let split = target_bitwidth.splits_products::<T>();
+        let limb = target_bitwidth.limb_bitwidth();
         let target_bitwidth = target_bitwidth.to_usize();
 
         // q in range
-        let _ = self.get_bits_unchecked(
+        let q_bits = self.get_bits_unchecked(
             &FlatUExpression::with_field(FlatExpression::from(q)),
             target_bitwidth,
             target_bitwidth,
@@ -1366,12 +1992,53 @@ impl<'ast, T: Field> Flattener<'ast, T> {
             RuntimeError::Sum,
         );
 
-        // q*d == n - r
-        statements_flattened.push_back(FlatStatement::Condition(
-            FlatExpression::Sub(box n, box r.into()),
-            FlatExpression::Mult(box q.into(), box d),
-            RuntimeError::Euclidean,
-        ));
+        match split {
+            // `q * d` may wrap around the modulus, so multiply `d` by the two limbs of `q` separately. As
+            // `q * d <= n < 2**w`, the high product is smaller than `2**(w - limb)`, which we check so that the sum of
+            // the products does not wrap around either
+            true => {
+                let (high, low) = q_bits.split_at(target_bitwidth - limb);
+
+                let low_product = self.define(
+                    FlatExpression::Mult(
+                        box flat_expression_from_bits(low.to_vec()),
+                        box d.clone(),
+                    ),
+                    statements_flattened,
+                );
+                let high_product = self.define(
+                    FlatExpression::Mult(box flat_expression_from_bits(high.to_vec()), box d),
+                    statements_flattened,
+                );
+
+                let _ = self.get_bits_unchecked(
+                    &FlatUExpression::with_field(FlatExpression::from(high_product)),
+                    target_bitwidth - limb,
+                    target_bitwidth - limb,
+                    statements_flattened,
+                    RuntimeError::Sum,
+                );
+
+                // low_product + high_product * 2**limb == n - r
+                statements_flattened.push_back(FlatStatement::Condition(
+                    FlatExpression::Sub(box n, box r.into()),
+                    FlatExpression::Add(
+                        box low_product.into(),
+                        box FlatExpression::Mult(
+                            box FlatExpression::Number(T::from(2).pow(limb)),
+                            box high_product.into(),
+                        ),
+                    ),
+                    RuntimeError::Euclidean,
+                ));
+            }
+            // q*d == n - r
+            false => statements_flattened.push_back(FlatStatement::Condition(
+                FlatExpression::Sub(box n, box r.into()),
+                FlatExpression::Mult(box q.into(), box d),
+                RuntimeError::Euclidean,
+            )),
+        }
 
         (q.into(), r.into())
     }
@@ -1400,10 +2067,25 @@ impl<'ast, T: Field> Flattener<'ast, T> {
 
         let should_reduce = should_reduce.to_bool();
 
-        let res = match expr.into_inner() {
+        let name = match &expr.inner {
+            UExpressionInner::Identifier(x) => Some(x.id.to_string()),
+            _ => None,
+        };
+
+        let res = maybe_grow(|| match expr.into_inner() {
             UExpressionInner::Value(x) => {
-                FlatUExpression::with_field(FlatExpression::Number(T::from(x)))
-            } // force to be a field element
+                // force to be a field element, whose bits are known so that it can be used in a bitwise operation
+                // without being reduced
+                let e = FlatUExpression::with_field(FlatExpression::Number(T::from(x)));
+                let bits = self.get_bits_unchecked(
+                    &e,
+                    target_bitwidth.to_usize(),
+                    target_bitwidth.to_usize(),
+                    statements_flattened,
+                    RuntimeError::Sum,
+                );
+                e.bits(bits)
+            }
             UExpressionInner::Identifier(x) => {
                 let field = FlatExpression::Identifier(*self.layout.get(&x.id).unwrap());
                 let bits = self.bits_cache.get(&field).map(|bits| {
@@ -1527,6 +2209,57 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                         .collect::<Vec<_>>(),
                 )
             }
+            UExpressionInner::Mult(box left, box right)
+                if target_bitwidth.splits_products::<T>()
+                    && left.metadata.as_ref().unwrap().should_reduce.to_bool()
+                    && right.metadata.as_ref().unwrap().should_reduce.to_bool() =>
+            {
+                // the product of the reduced operands may not fit in the field, so multiply `right` by the two limbs
+                // of `left` separately. The high product is shifted by the bitwidth of the low limb, so only its
+                // lowest `bitwidth - limb` bits are kept
+                let bitwidth = target_bitwidth.to_usize();
+                let limb = target_bitwidth.limb_bitwidth();
+
+                let left_bits = self
+                    .flatten_uint_expression(statements_flattened, left)
+                    .bits
+                    .unwrap();
+                let right_flattened = self
+                    .flatten_uint_expression(statements_flattened, right)
+                    .get_field_unchecked();
+                let right =
+                    FlatExpression::from(self.define(right_flattened, statements_flattened));
+
+                let (high, low) = left_bits.split_at(bitwidth - limb);
+
+                let low_product = self.define(
+                    FlatExpression::Mult(
+                        box flat_expression_from_bits(low.to_vec()),
+                        box right.clone(),
+                    ),
+                    statements_flattened,
+                );
+                let high_product = self.define(
+                    FlatExpression::Mult(box flat_expression_from_bits(high.to_vec()), box right),
+                    statements_flattened,
+                );
+
+                let high_product_bits = self.get_bits_unchecked(
+                    &FlatUExpression::with_field(FlatExpression::from(high_product)),
+                    2 * bitwidth - limb,
+                    bitwidth - limb,
+                    statements_flattened,
+                    RuntimeError::Sum,
+                );
+
+                FlatUExpression::with_field(FlatExpression::Add(
+                    box low_product.into(),
+                    box FlatExpression::Mult(
+                        box FlatExpression::Number(T::from(2).pow(limb)),
+                        box flat_expression_from_bits(high_product_bits),
+                    ),
+                ))
+            }
             UExpressionInner::Mult(box left, box right) => {
                 let left_flattened = self
                     .flatten_uint_expression(statements_flattened, left)
@@ -1820,13 +2553,37 @@ impl<'ast, T: Field> Flattener<'ast, T> {
 
                 FlatUExpression::with_bits(or)
             }
-        };
+        });
 
         let res = match should_reduce {
             true => {
+                // a value bounded below `2^target_bitwidth` by its metadata is only decomposed to the bits it needs
+                let needed_bitwidth = std::cmp::max(actual_bitwidth, 1);
+
+                if needed_bitwidth + BITWIDTH_WARNING_THRESHOLD < target_bitwidth.to_usize()
+                    && self.will_decompose(&res)
+                {
+                    let bitwidth = target_bitwidth.to_usize();
+                    match name {
+                        Some(name) => self.warn(format!(
+                            "`{}` is declared as `u{}` but never exceeds {}, which fits in {} bits: consider a smaller type",
+                            name,
+                            bitwidth,
+                            metadata.max,
+                            needed_bitwidth
+                        )),
+                        None => self.warn(format!(
+                            "A `u{}` value never exceeds {}, which fits in {} bits: consider declaring its operands with a smaller type",
+                            bitwidth,
+                            metadata.max,
+                            needed_bitwidth
+                        )),
+                    }
+                }
+
                 let bits = self.get_bits_unchecked(
                     &res,
-                    actual_bitwidth,
+                    needed_bitwidth,
                     target_bitwidth.to_usize(),
                     statements_flattened,
                     RuntimeError::Sum,
@@ -1859,6 +2616,16 @@ impl<'ast, T: Field> Flattener<'ast, T> {
         res
     }
 
+    // whether getting the bits of `e` adds a bit decomposition, as they are not already known
+    fn will_decompose(&self, e: &FlatUExpression<T>) -> bool {
+        match (&e.bits, &e.field) {
+            (None, Some(field)) => {
+                !matches!(field, FlatExpression::Number(_)) && !self.bits_cache.contains_key(field)
+            }
+            _ => false,
+        }
+    }
+
     /// Get the bits for a FlatUExpression
     ///
     /// # Arguments
@@ -1899,11 +2666,8 @@ impl<'ast, T: Field> Flattener<'ast, T> {
         };
 
         e.bits.clone().unwrap_or_else(|| {
-            // we are not reducing a constant, therefore the result should always have a smaller bitwidth:
-            // `to` is the target bitwidth, and `from` cannot be smaller than that unless we're looking at a
-            // constant
-
-            let from = std::cmp::max(from, to);
+            // we are not reducing a constant: `from` is the bitwidth `e` is known to fit in, which is larger than the
+            // target bitwidth `to` if `e` may have overflowed, and smaller if its metadata bounds it below `2^to`
             let res = match self.bits_cache.entry(e.field.clone().unwrap()) {
                 Entry::Occupied(entry) => {
                     let res: Vec<_> = entry.get().clone();
@@ -1950,8 +2714,14 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                         error,
                     ));
 
-                    // truncate to the `to` lowest bits
-                    let bits = bits[from - to..].to_vec();
+                    // truncate to the `to` lowest bits, or pad with zeroes on the left if `e` fits in fewer bits
+                    let bits = match from >= to {
+                        true => bits[from - to..].to_vec(),
+                        false => (0..to - from)
+                            .map(|_| FlatExpression::Number(T::zero()))
+                            .chain(bits)
+                            .collect(),
+                    };
 
                     assert_eq!(bits.len(), to);
 
@@ -1989,6 +2759,7 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                             .metadata(UMetadata {
                                 should_reduce: ShouldReduce::True,
                                 max: T::from(i),
+                                signed: false,
                             }),
                         box index.clone(),
                     ),
@@ -2038,7 +2809,7 @@ impl<'ast, T: Field> Flattener<'ast, T> {
         statements_flattened: &mut FlatStatements<'ast, T>,
         expr: FieldElementExpression<'ast, T>,
     ) -> FlatExpression<T> {
-        match expr {
+        maybe_grow(|| match expr {
             FieldElementExpression::Number(x) => FlatExpression::Number(x), // force to be a field element
             FieldElementExpression::Identifier(x) => FlatExpression::Identifier(
                 *self.layout.get(&x.id).unwrap_or_else(|| panic!("{}", x)),
@@ -2047,16 +2818,17 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                 .flatten_select_expression(statements_flattened, e)
                 .get_field_unchecked(),
             FieldElementExpression::Add(box left, box right) => {
+                let (left_sum, right_sum) = (is_sum(&left), is_sum(&right));
                 let left_flattened = self.flatten_field_expression(statements_flattened, left);
                 let right_flattened = self.flatten_field_expression(statements_flattened, right);
-                let new_left = if left_flattened.is_linear() {
+                let new_left = if left_sum || left_flattened.is_linear() {
                     left_flattened
                 } else {
                     let id = self.use_sym();
                     statements_flattened.push_back(FlatStatement::Definition(id, left_flattened));
                     FlatExpression::Identifier(id)
                 };
-                let new_right = if right_flattened.is_linear() {
+                let new_right = if right_sum || right_flattened.is_linear() {
                     right_flattened
                 } else {
                     let id = self.use_sym();
@@ -2066,17 +2838,18 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                 FlatExpression::Add(box new_left, box new_right)
             }
             FieldElementExpression::Sub(box left, box right) => {
+                let (left_sum, right_sum) = (is_sum(&left), is_sum(&right));
                 let left_flattened = self.flatten_field_expression(statements_flattened, left);
                 let right_flattened = self.flatten_field_expression(statements_flattened, right);
 
-                let new_left = if left_flattened.is_linear() {
+                let new_left = if left_sum || left_flattened.is_linear() {
                     left_flattened
                 } else {
                     let id = self.use_sym();
                     statements_flattened.push_back(FlatStatement::Definition(id, left_flattened));
                     FlatExpression::Identifier(id)
                 };
-                let new_right = if right_flattened.is_linear() {
+                let new_right = if right_sum || right_flattened.is_linear() {
                     right_flattened
                 } else {
                     let id = self.use_sym();
@@ -2105,7 +2878,7 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                 };
                 FlatExpression::Mult(box new_left, box new_right)
             }
-            FieldElementExpression::Div(box left, box right) => {
+            FieldElementExpression::Div(box left, box right, _) => {
                 let left_flattened = self.flatten_field_expression(statements_flattened, left);
                 let right_flattened = self.flatten_field_expression(statements_flattened, right);
                 let new_left: FlatExpression<T> = {
@@ -2119,26 +2892,74 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                     id.into()
                 };
 
-                // `right` is assumed to already be non-zero so this is an unchecked division
-                // TODO: we could save one constraint here by reusing the inverse of `right` computed earlier
+                match self.config.div_by_zero {
+                    DivByZero::DefineZero => {
+                        // `left / 0` is defined as `0`
+                        // # Y = if right == 0 then 0 else 1 fi
+                        // # M = if right == 0 then 1 else 1/right fi
+                        // Y == right * M
+                        // 0 == (1 - Y) * right
+                        // Q == left * M
+                        // res = Q * Y
+                        let is_nonzero = self.use_sym();
+                        let inverse = self.use_sym();
+
+                        statements_flattened.push_back(FlatStatement::Directive(
+                            FlatDirective::new(
+                                vec![is_nonzero, inverse],
+                                Solver::ConditionEq,
+                                vec![new_right.clone()],
+                            ),
+                        ));
+                        statements_flattened.push_back(FlatStatement::Condition(
+                            is_nonzero.into(),
+                            FlatExpression::Mult(box new_right.clone(), box inverse.into()),
+                            RuntimeError::Division,
+                        ));
+                        statements_flattened.push_back(FlatStatement::Condition(
+                            FlatExpression::Number(T::zero()),
+                            FlatExpression::Mult(
+                                box FlatExpression::Sub(
+                                    box FlatExpression::Number(T::one()),
+                                    box is_nonzero.into(),
+                                ),
+                                box new_right,
+                            ),
+                            RuntimeError::Division,
+                        ));
+
+                        let quotient = self.define(
+                            FlatExpression::Mult(box new_left, box inverse.into()),
+                            statements_flattened,
+                        );
 
-                let inverse = self.use_sym();
+                        FlatExpression::Mult(box quotient.into(), box is_nonzero.into())
+                    }
+                    DivByZero::Error => {
+                        // `right` is assumed to already be non-zero so this is an unchecked division
+                        // TODO: we could save one constraint here by reusing the inverse of `right` computed earlier
 
-                // # c = a/b
-                statements_flattened.push_back(FlatStatement::Directive(FlatDirective::new(
-                    vec![inverse],
-                    Solver::Div,
-                    vec![new_left.clone(), new_right.clone()],
-                )));
+                        let inverse = self.use_sym();
 
-                // assert(c * b == a)
-                statements_flattened.push_back(FlatStatement::Condition(
-                    new_left,
-                    FlatExpression::Mult(box new_right, box inverse.into()),
-                    RuntimeError::Division,
-                ));
+                        // # c = a/b
+                        statements_flattened.push_back(FlatStatement::Directive(
+                            FlatDirective::new(
+                                vec![inverse],
+                                Solver::Div,
+                                vec![new_left.clone(), new_right.clone()],
+                            ),
+                        ));
+
+                        // assert(c * b == a)
+                        statements_flattened.push_back(FlatStatement::Condition(
+                            new_left,
+                            FlatExpression::Mult(box new_right, box inverse.into()),
+                            RuntimeError::Division,
+                        ));
 
-                inverse.into()
+                        inverse.into()
+                    }
+                }
             }
             FieldElementExpression::Pow(box base, box exponent) => {
                 match exponent.into_inner() {
@@ -2221,7 +3042,7 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                 .flatten_conditional_expression(statements_flattened, e)
                 .get_field_unchecked(),
             _ => unreachable!(),
-        }
+        })
     }
 
     fn flatten_assembly_statement(
@@ -2244,6 +3065,19 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                 let directive = FlatDirective::new(outputs, Solver::Zir(function), inputs);
                 statements_flattened.push_back(FlatStatement::Directive(directive));
             }
+            ZirAssemblyStatement::UnsafeWitness(assignees, plugin, arguments) => {
+                let inputs: Vec<FlatExpression<T>> = arguments
+                    .into_iter()
+                    .map(|e| self.flatten_field_expression(statements_flattened, e))
+                    .collect();
+                let outputs: Vec<Variable> = assignees
+                    .into_iter()
+                    .map(|assignee| self.use_variable(&assignee))
+                    .collect();
+                let solver = Solver::Plugin(plugin.into(), inputs.len(), outputs.len());
+                let directive = FlatDirective::new(outputs, solver, inputs);
+                statements_flattened.push_back(FlatStatement::Directive(directive));
+            }
             ZirAssemblyStatement::Constraint(lhs, rhs, metadata) => {
                 let lhs = self.flatten_field_expression(statements_flattened, lhs);
                 let rhs = self.flatten_field_expression(statements_flattened, rhs);
@@ -2267,7 +3101,43 @@ impl<'ast, T: Field> Flattener<'ast, T> {
         statements_flattened: &mut FlatStatements<'ast, T>,
         stat: ZirStatement<'ast, T>,
     ) {
+        if self.attribution.is_none() {
+            return self.flatten_statement_inner(statements_flattened, stat);
+        }
+
         match stat {
+            ZirStatement::PushCallLog(function) => {
+                self.attribution.as_mut().unwrap().push(function);
+            }
+            ZirStatement::PopCallLog => {
+                self.attribution.as_mut().unwrap().pop();
+            }
+            // the branches may contain calls, so their statements are attributed one by one
+            stat @ ZirStatement::IfElse(..) => {
+                self.flatten_statement_inner(statements_flattened, stat)
+            }
+            stat => {
+                let emitted = statements_flattened.len();
+                self.flatten_statement_inner(statements_flattened, stat);
+                self.attribution
+                    .as_mut()
+                    .unwrap()
+                    .record_statements(statements_flattened.iter().skip(emitted));
+            }
+        }
+    }
+
+    fn flatten_statement_inner(
+        &mut self,
+        statements_flattened: &mut FlatStatements<'ast, T>,
+        stat: ZirStatement<'ast, T>,
+    ) {
+        match stat {
+            ZirStatement::PushCallLog(..)
+            | ZirStatement::PopCallLog
+            | ZirStatement::PushIterationLog(..)
+            | ZirStatement::PopIterationLog => {}
+            ZirStatement::SpanLog(span) => self.span = Some(span),
             ZirStatement::Assembly(statements) => {
                 let mut block_statements = VecDeque::new();
                 for s in statements {
@@ -2293,8 +3163,12 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                 );
             }
             ZirStatement::IfElse(condition, consequence, alternative) => {
+                let emitted = statements_flattened.len();
                 let condition_flat =
                     self.flatten_boolean_expression(statements_flattened, condition.clone());
+                if let Some(attribution) = self.attribution.as_mut() {
+                    attribution.record_statements(statements_flattened.iter().skip(emitted));
+                }
 
                 let condition_id = self.use_sym();
                 statements_flattened
@@ -2348,6 +3222,13 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                     e => {
                         let var = self.use_variable(&assignee);
 
+                        // values defined from bounded arguments are bounded too, as long as they cannot wrap around
+                        if let Some(bound) =
+                            self.bound(&e).filter(|b| *b <= T::get_required_bits() - 2)
+                        {
+                            self.bounds.insert(var, bound);
+                        }
+
                         // handle return of function call
                         statements_flattened.push_back(FlatStatement::Definition(var, e));
 
@@ -2365,7 +3246,7 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                 match e {
                     BooleanExpression::And(..) => {
                         for boolean in e.into_conjunction_iterator() {
-                            self.flatten_statement(
+                            self.flatten_statement_inner(
                                 statements_flattened,
                                 ZirStatement::Assertion(boolean, error.clone()),
                             )
@@ -2382,100 +3263,38 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                             error.into(),
                         )
                     }
-                    BooleanExpression::FieldLt(box lhs, box rhs) => {
-                        let lhs = self.flatten_field_expression(statements_flattened, lhs);
-                        let rhs = self.flatten_field_expression(statements_flattened, rhs);
-
-                        match (lhs, rhs) {
-                            (e, FlatExpression::Number(c)) => self.enforce_constant_lt_check(
-                                statements_flattened,
-                                e,
-                                c,
-                                error.into(),
-                            ),
-                            // c < e <=> p - 1 - e < p - 1 - c
-                            (FlatExpression::Number(c), e) => self.enforce_constant_lt_check(
-                                statements_flattened,
-                                FlatExpression::Sub(box T::max_value().into(), box e),
-                                T::max_value() - c,
-                                error.into(),
-                            ),
-                            (lhs, rhs) => {
-                                let bit_width = T::get_required_bits();
-                                let safe_width = bit_width - 2; // dynamic comparison is not complete
-                                let e = self.lt_check(statements_flattened, lhs, rhs, safe_width);
-                                statements_flattened.push_back(FlatStatement::Condition(
-                                    e,
-                                    FlatExpression::Number(T::one()),
-                                    error.into(),
-                                ));
-                            }
-                        }
-                    }
-                    BooleanExpression::FieldLe(box lhs, box rhs) => {
-                        let lhs = self.flatten_field_expression(statements_flattened, lhs);
-                        let rhs = self.flatten_field_expression(statements_flattened, rhs);
-
-                        match (lhs, rhs) {
-                            (e, FlatExpression::Number(c)) => self.enforce_constant_le_check(
-                                statements_flattened,
-                                e,
-                                c,
-                                error.into(),
-                            ),
-                            // c <= e <=> p - 1 - e <= p - 1 - c
-                            (FlatExpression::Number(c), e) => self.enforce_constant_le_check(
-                                statements_flattened,
-                                FlatExpression::Sub(box T::max_value().into(), box e),
-                                T::max_value() - c,
-                                error.into(),
-                            ),
-                            (lhs, rhs) => {
-                                let bit_width = T::get_required_bits();
-                                let safe_width = bit_width - 2; // dynamic comparison is not complete
-                                let e = self.le_check(statements_flattened, lhs, rhs, safe_width);
-                                statements_flattened.push_back(FlatStatement::Condition(
-                                    e,
-                                    FlatExpression::Number(T::one()),
-                                    error.into(),
-                                ));
-                            }
-                        }
-                    }
-                    BooleanExpression::UintLe(box lhs, box rhs) => {
-                        let lhs = self
-                            .flatten_uint_expression(statements_flattened, lhs)
-                            .get_field_unchecked();
-                        let rhs = self
-                            .flatten_uint_expression(statements_flattened, rhs)
-                            .get_field_unchecked();
-
-                        match (lhs, rhs) {
-                            (e, FlatExpression::Number(c)) => self.enforce_constant_le_check(
-                                statements_flattened,
-                                e,
-                                c,
-                                error.into(),
-                            ),
-                            // c <= e <=> p - 1 - e <= p - 1 - c
-                            (FlatExpression::Number(c), e) => self.enforce_constant_le_check(
-                                statements_flattened,
-                                FlatExpression::Sub(box T::max_value().into(), box e),
-                                T::max_value() - c,
-                                error.into(),
-                            ),
-                            (lhs, rhs) => {
-                                let bit_width = T::get_required_bits();
-                                let safe_width = bit_width - 2; // dynamic comparison is not complete
-                                let e = self.le_check(statements_flattened, lhs, rhs, safe_width);
-                                statements_flattened.push_back(FlatStatement::Condition(
-                                    e,
-                                    FlatExpression::Number(T::one()),
-                                    error.into(),
-                                ));
-                            }
-                        }
-                    }
+                    BooleanExpression::FieldLt(box lhs, box rhs) => self
+                        .flatten_field_comparison_assertion(
+                            statements_flattened,
+                            lhs,
+                            rhs,
+                            true,
+                            error.into(),
+                        ),
+                    BooleanExpression::FieldLe(box lhs, box rhs) => self
+                        .flatten_field_comparison_assertion(
+                            statements_flattened,
+                            lhs,
+                            rhs,
+                            false,
+                            error.into(),
+                        ),
+                    BooleanExpression::UintLt(box lhs, box rhs) => self
+                        .flatten_uint_comparison_assertion(
+                            statements_flattened,
+                            lhs,
+                            rhs,
+                            true,
+                            error.into(),
+                        ),
+                    BooleanExpression::UintLe(box lhs, box rhs) => self
+                        .flatten_uint_comparison_assertion(
+                            statements_flattened,
+                            lhs,
+                            rhs,
+                            false,
+                            error.into(),
+                        ),
                     BooleanExpression::UintEq(box lhs, box rhs) => {
                         let lhs = self
                             .flatten_uint_expression(statements_flattened, lhs)
@@ -2540,7 +3359,7 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                         statements_flattened.push_back(FlatStatement::Condition(
                             FlatExpression::Number(T::one()),
                             FlatExpression::Mult(box invx.into(), box x_id.into()),
-                            RuntimeError::Inverse,
+                            inverse_error(error),
                         ));
                     }
                     // `!(x == 0)` can be asserted by giving the inverse of `x`
@@ -2573,7 +3392,7 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                         statements_flattened.push_back(FlatStatement::Condition(
                             FlatExpression::Number(T::one()),
                             FlatExpression::Mult(box invx.into(), box x_id.into()),
-                            RuntimeError::Inverse,
+                            inverse_error(error),
                         ));
                     }
                     e => {
@@ -2631,21 +3450,19 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                             })
                             .collect();
 
-                        match embed {
-                            FlatEmbed::U64FromBits
-                            | FlatEmbed::U32FromBits
-                            | FlatEmbed::U16FromBits
-                            | FlatEmbed::U8FromBits => {
-                                let bits = exprs
-                                    .into_iter()
-                                    .map(|e| {
-                                        self.flatten_expression(statements_flattened, e)
-                                            .get_field_unchecked()
-                                    })
-                                    .collect();
-                                self.bits_cache.insert(vars[0].into(), bits);
+                        if embed.from_bits_bitwidth().is_some() {
+                            let mut bits: Vec<_> = exprs
+                                .into_iter()
+                                .map(|e| {
+                                    self.flatten_expression(statements_flattened, e)
+                                        .get_field_unchecked()
+                                })
+                                .collect();
+                            // the cache holds the most significant bit first
+                            if embed.bit_order() == Endianness::Little {
+                                bits.reverse();
                             }
-                            _ => {}
+                            self.bits_cache.insert(vars[0].into(), bits);
                         }
                     }
                 }
@@ -2755,6 +3572,7 @@ impl<'ast, T: Field> Flattener<'ast, T> {
         statements_flattened: &mut FlatStatements<'ast, T>,
     ) -> Parameter {
         let variable = self.use_variable(&parameter.id);
+        self.arguments.push(variable);
 
         match parameter.id.get_type() {
             Type::Uint(bitwidth) => {
@@ -2775,7 +3593,20 @@ impl<'ast, T: Field> Flattener<'ast, T> {
                     RuntimeError::ArgumentBitness,
                 ));
             }
-            Type::FieldElement => {}
+            Type::FieldElement => {
+                if let Some(bound) = parameter.bound {
+                    let bound = bound as usize;
+                    // like unsigned integers, bounded field inputs are constrained by their bit decomposition
+                    self.get_bits_unchecked(
+                        &FlatUExpression::with_field(FlatExpression::Identifier(variable)),
+                        bound,
+                        bound,
+                        statements_flattened,
+                        RuntimeError::ArgumentRange(parameter.id.id.to_string(), bound as u32),
+                    );
+                    self.bounds.insert(variable, bound);
+                }
+            }
         }
 
         Parameter {
@@ -2799,6 +3630,7 @@ impl<'ast, T: Field> Flattener<'ast, T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use zokrates_ast::ir::{self, LinComb, QuadComb};
     use zokrates_ast::zir;
     use zokrates_ast::zir::types::Signature;
     use zokrates_ast::zir::types::Type;
@@ -2809,6 +3641,82 @@ mod tests {
         from_function_and_config(f, CompileConfig::default()).collect()
     }
 
+    #[test]
+    fn constraint_budget() {
+        // def main(field a) -> field {
+        //     assert(a * a == 4);
+        //     return a * a * a;
+        // }
+        let span = SourceMetadata::new(
+            "main.zok".into(),
+            zokrates_ast::untyped::Position { line: 2, col: 5 },
+        );
+        let return_span = SourceMetadata::new(
+            "main.zok".into(),
+            zokrates_ast::untyped::Position { line: 3, col: 5 },
+        );
+
+        // the statements are marked with their location, as when checking with span logs
+        let function = ZirFunction::<Bn128Field> {
+            arguments: vec![zir::Parameter::private(zir::Variable::field_element("a"))],
+            statements: vec![
+                ZirStatement::SpanLog(span.clone()),
+                ZirStatement::Assertion(
+                    BooleanExpression::FieldEq(
+                        box FieldElementExpression::Mult(
+                            box FieldElementExpression::identifier("a".into()),
+                            box FieldElementExpression::identifier("a".into()),
+                        ),
+                        box FieldElementExpression::Number(Bn128Field::from(4)),
+                    ),
+                    zir::RuntimeError::SourceAssertion(span.clone()),
+                ),
+                ZirStatement::SpanLog(return_span.clone()),
+                ZirStatement::Return(vec![FieldElementExpression::Mult(
+                    box FieldElementExpression::Mult(
+                        box FieldElementExpression::identifier("a".into()),
+                        box FieldElementExpression::identifier("a".into()),
+                    ),
+                    box FieldElementExpression::identifier("a".into()),
+                )
+                .into()]),
+            ],
+            signature: Signature {
+                inputs: vec![Type::FieldElement],
+                outputs: vec![Type::FieldElement],
+            },
+        };
+
+        let size: usize = flatten_function(function.clone())
+            .statements
+            .iter()
+            .map(constraint_count)
+            .sum();
+
+        let with_budget = |budget| {
+            let (program, check) = from_function_with_budget(
+                function.clone(),
+                CompileConfig::default().max_constraints(Some(budget)),
+            );
+            // the budget is charged as the statements are consumed
+            let program: FlatProg<_> = program.collect();
+            check.result().map(|_| program)
+        };
+
+        // just enough
+        assert_eq!(with_budget(size), Ok(flatten_function(function.clone())));
+
+        // one constraint short: flattening stops at the return statement, after the assertion
+        let error = with_budget(size - 1).unwrap_err();
+        assert_eq!(error.budget, size - 1);
+        assert_eq!(error.count, size);
+        assert_eq!(error.span, Some(return_span));
+
+        // no constraint allowed: flattening stops at the assertion
+        let error = with_budget(0).unwrap_err();
+        assert_eq!(error.span, Some(span));
+    }
+
     #[test]
     fn assertion_bool_eq() {
         // def main() {
@@ -2965,6 +3873,7 @@ mod tests {
         let metadata = UMetadata {
             max: 0xffffffff_u32.into(),
             should_reduce: ShouldReduce::True,
+            signed: false,
         };
         let function = ZirFunction::<Bn128Field> {
             arguments: vec![],
@@ -3606,6 +4515,56 @@ mod tests {
         flattener.flatten_field_expression(&mut FlatStatements::new(), expression);
     }
 
+    #[test]
+    fn if_else_constraints() {
+        // if c { a } else { b }
+        let flattened = testing::flatten_expression::<Bn128Field>(
+            FieldElementExpression::conditional(
+                BooleanExpression::identifier("c".into()),
+                FieldElementExpression::identifier("a".into()),
+                FieldElementExpression::identifier("b".into()),
+            )
+            .into(),
+            &[
+                zir::Variable::boolean("c".into()),
+                zir::Variable::field_element("a"),
+                zir::Variable::field_element("b"),
+            ],
+        );
+
+        assert_eq!(
+            flattened.variables,
+            vec![Variable::new(0), Variable::new(1), Variable::new(2)]
+        );
+        assert_eq!(
+            flattened.statements,
+            vec![
+                ir::Statement::definition(Variable::new(3), Variable::new(0)),
+                ir::Statement::definition(Variable::new(4), Variable::new(1)),
+                ir::Statement::definition(Variable::new(5), Variable::new(2)),
+                ir::Statement::definition(
+                    Variable::new(6),
+                    QuadComb::from_linear_combinations(
+                        Variable::new(3).into(),
+                        Variable::new(4).into()
+                    )
+                ),
+                ir::Statement::definition(
+                    Variable::new(7),
+                    QuadComb::from_linear_combinations(
+                        LinComb::one() - Variable::new(3).into(),
+                        Variable::new(5).into()
+                    )
+                ),
+                ir::Statement::definition(
+                    Variable::new(8),
+                    LinComb::from(Variable::new(6)) + Variable::new(7).into()
+                ),
+            ]
+        );
+        assert_eq!(flattened.output, Variable::new(8).into());
+    }
+
     #[test]
     fn geq_leq() {
         let config = CompileConfig::default();
@@ -3640,6 +4599,66 @@ mod tests {
         flattener.flatten_field_expression(&mut FlatStatements::new(), expression);
     }
 
+    #[test]
+    fn bool_and_constraints() {
+        // a && b
+        let flattened = testing::flatten_expression::<Bn128Field>(
+            BooleanExpression::And(
+                box BooleanExpression::identifier("a".into()),
+                box BooleanExpression::identifier("b".into()),
+            )
+            .into(),
+            &[
+                zir::Variable::boolean("a".into()),
+                zir::Variable::boolean("b".into()),
+            ],
+        );
+
+        assert_eq!(
+            flattened.variables,
+            vec![Variable::new(0), Variable::new(1)]
+        );
+        assert_eq!(
+            flattened.statements,
+            vec![ir::Statement::definition(
+                Variable::new(2),
+                QuadComb::from_linear_combinations(
+                    Variable::new(0).into(),
+                    Variable::new(1).into()
+                )
+            )]
+        );
+        assert_eq!(flattened.output, Variable::new(2).into());
+    }
+
+    #[test]
+    fn located_warnings() {
+        let mut flattener = Flattener::<Bn128Field>::new(CompileConfig::default());
+        let mut statements_flattened = FlatStatements::new();
+
+        // the warnings are located at the statement being flattened
+        let span = SourceMetadata::new(
+            "main.zok".into(),
+            zokrates_ast::untyped::Position { line: 2, col: 5 },
+        );
+        flattener.flatten_statement(
+            &mut statements_flattened,
+            ZirStatement::SpanLog(span.clone()),
+        );
+
+        assert!(statements_flattened.is_empty());
+        assert_eq!(flattener.span, Some(span.clone()));
+
+        // a warning about the same statement is reported once
+        flattener.warn("warning".into());
+        flattener.warn("warning".into());
+
+        assert_eq!(
+            flattener.warnings().take(),
+            vec![Diagnostic::new("warning").span(Some(span))]
+        );
+    }
+
     #[test]
     fn div() {
         // a = 5 / b / b
@@ -3658,8 +4677,10 @@ mod tests {
                 box FieldElementExpression::Div(
                     box FieldElementExpression::Number(Bn128Field::from(5)),
                     box FieldElementExpression::identifier("b".into()),
+                    None,
                 ),
                 box FieldElementExpression::identifier("b".into()),
+                None,
             )
             .into(),
         );
@@ -0,0 +1,347 @@
+//! Flattening of the Merkle membership embeds.
+//!
+//! A path of depth `DEPTH` is checked bottom-up: at each level, the direction bit selects which of the current node
+//! and its sibling is the left child, and the two children are hashed into their parent. Both selections share a
+//! single product `d * (sibling - node)`, and the parent is handed to the next level as is, the Poseidon chain as a
+//! linear combination and the SHA-256 chain as bits, so that no constraint is spent between levels.
+
+use crate::poseidon;
+use crate::{FlatStatements, FlatUExpression, Flattener};
+use std::collections::BTreeMap;
+use zokrates_ast::common::Variable;
+use zokrates_ast::flat::{flat_expression_from_expression_summands, FlatExpression};
+use zokrates_field::Field;
+
+#[cfg(feature = "bellman")]
+use crate::utils::flat_expression_from_bits;
+#[cfg(feature = "bellman")]
+use zokrates_ast::common::{embed::sha256_round, RuntimeError};
+
+/// A linear combination of variables and a constant, kept canonical so that the state of the permutation does not
+/// grow with the number of rounds
+#[derive(Clone, Debug)]
+struct Affine<T> {
+    terms: BTreeMap<Variable, T>,
+    constant: T,
+}
+
+impl<T: Field> Affine<T> {
+    fn constant(constant: T) -> Self {
+        Affine {
+            terms: BTreeMap::new(),
+            constant,
+        }
+    }
+
+    fn variable(v: Variable) -> Self {
+        Affine {
+            terms: vec![(v, T::one())].into_iter().collect(),
+            constant: T::zero(),
+        }
+    }
+
+    fn add(mut self, other: &Self, coefficient: &T) -> Self {
+        for (v, c) in &other.terms {
+            let entry = self.terms.entry(*v).or_insert_with(T::zero);
+            *entry = entry.clone() + c.clone() * coefficient.clone();
+        }
+        self.terms.retain(|_, c| *c != T::zero());
+        self.constant = self.constant + other.constant.clone() * coefficient.clone();
+        self
+    }
+
+    fn into_expression(self) -> FlatExpression<T> {
+        let terms: Vec<_> = self.terms.into_iter().map(|(v, c)| (c, v)).collect();
+        match (terms.is_empty(), self.constant == T::zero()) {
+            (true, _) => FlatExpression::Number(self.constant),
+            (false, true) => flat_expression_from_expression_summands(&terms),
+            (false, false) => FlatExpression::Add(
+                box flat_expression_from_expression_summands(&terms),
+                box FlatExpression::Number(self.constant),
+            ),
+        }
+    }
+}
+
+impl<'ast, T: Field> Flattener<'ast, T> {
+    /// Flattens a call to `merkle_verify_poseidon<DEPTH>(leaf, root, path, directions)`
+    pub(crate) fn flatten_merkle_verify_poseidon(
+        &mut self,
+        statements_flattened: &mut FlatStatements<'ast, T>,
+        depth: usize,
+        params: Vec<FlatUExpression<T>>,
+    ) -> Vec<FlatUExpression<T>> {
+        let (leaf, root, path, directions) =
+            self.merkle_arguments(statements_flattened, depth, params);
+
+        // the constants are parsed once for all the levels of the tree
+        let parameters = poseidon::Parameters::parse();
+
+        let node = path.into_iter().zip(directions).fold(
+            Affine::variable(leaf),
+            |node, (sibling, direction)| {
+                let sibling = Affine::variable(sibling);
+                let (left, right) =
+                    self.merkle_select(statements_flattened, node, sibling, direction);
+                self.poseidon(statements_flattened, &parameters, left, right)
+            },
+        );
+
+        vec![FlatUExpression::with_field(self.eq_check(
+            statements_flattened,
+            root.into(),
+            node.into_expression(),
+        ))]
+    }
+
+    /// Flattens a call to `merkle_verify_sha256<DEPTH>(leaf, root, path, directions)`
+    ///
+    /// Each node is the SHA-256 hash of the 32-byte big-endian encodings of its children, truncated to its last 31
+    /// bytes so that it fits in a field element.
+    #[cfg(feature = "bellman")]
+    pub(crate) fn flatten_merkle_verify_sha256(
+        &mut self,
+        statements_flattened: &mut FlatStatements<'ast, T>,
+        depth: usize,
+        params: Vec<FlatUExpression<T>>,
+    ) -> Vec<FlatUExpression<T>> {
+        let (leaf, root, path, directions) =
+            self.merkle_arguments(statements_flattened, depth, params);
+
+        let leaf = self.merkle_bits(statements_flattened, leaf);
+
+        let node = path
+            .into_iter()
+            .zip(directions)
+            .fold(leaf, |node, (sibling, direction)| {
+                let sibling = self.merkle_bits(statements_flattened, sibling);
+
+                // select the children bit by bit, the bits of the encodings being booleans already
+                let (left, right): (Vec<_>, Vec<_>) = node
+                    .into_iter()
+                    .zip(sibling)
+                    .map(|(node, sibling)| match (&node, &sibling) {
+                        (FlatExpression::Number(n), FlatExpression::Number(s)) if n == s => {
+                            (node, sibling)
+                        }
+                        _ => {
+                            let t = self.define(
+                                FlatExpression::Mult(
+                                    box direction.into(),
+                                    box FlatExpression::Sub(box sibling.clone(), box node.clone()),
+                                ),
+                                statements_flattened,
+                            );
+                            (
+                                FlatExpression::Add(box node, box t.into()),
+                                FlatExpression::Sub(box sibling, box t.into()),
+                            )
+                        }
+                    })
+                    .unzip();
+
+                let digest = self.sha256(
+                    statements_flattened,
+                    left.into_iter().chain(right).collect(),
+                );
+
+                // keep the last 31 bytes
+                (0..8)
+                    .map(|_| FlatExpression::Number(T::zero()))
+                    .chain(digest.into_iter().skip(8))
+                    .collect()
+            });
+
+        vec![FlatUExpression::with_field(self.eq_check(
+            statements_flattened,
+            root.into(),
+            flat_expression_from_bits(node),
+        ))]
+    }
+
+    // define the leaf, the root, the siblings and the directions of a Merkle path as variables
+    #[allow(clippy::type_complexity)]
+    fn merkle_arguments(
+        &mut self,
+        statements_flattened: &mut FlatStatements<'ast, T>,
+        depth: usize,
+        params: Vec<FlatUExpression<T>>,
+    ) -> (Variable, Variable, Vec<Variable>, Vec<Variable>) {
+        assert_eq!(params.len(), 2 + 2 * depth);
+
+        let mut variables = params
+            .into_iter()
+            .map(|p| self.define(p.get_field_unchecked(), statements_flattened));
+
+        let leaf = variables.next().unwrap();
+        let root = variables.next().unwrap();
+        let path = variables.by_ref().take(depth).collect();
+        let directions = variables.collect();
+
+        (leaf, root, path, directions)
+    }
+
+    // order `node` and `sibling`, `node` being the right child if `direction` is set
+    fn merkle_select(
+        &mut self,
+        statements_flattened: &mut FlatStatements<'ast, T>,
+        node: Affine<T>,
+        sibling: Affine<T>,
+        direction: Variable,
+    ) -> (Affine<T>, Affine<T>) {
+        let difference = sibling.clone().add(&node, &(T::zero() - T::one()));
+        let t = Affine::variable(self.define(
+            FlatExpression::Mult(box direction.into(), box difference.into_expression()),
+            statements_flattened,
+        ));
+
+        (
+            node.add(&t, &T::one()),
+            sibling.add(&t, &(T::zero() - T::one())),
+        )
+    }
+
+    // the Poseidon hash of `left` and `right`, as `hashes/poseidon/poseidon` computes it for two inputs
+    fn poseidon(
+        &mut self,
+        statements_flattened: &mut FlatStatements<'ast, T>,
+        parameters: &poseidon::Parameters<T>,
+        left: Affine<T>,
+        right: Affine<T>,
+    ) -> Affine<T> {
+        use poseidon::{FULL_ROUNDS, PARTIAL_ROUNDS, WIDTH};
+
+        let mut state = vec![Affine::constant(T::zero()), left, right];
+
+        for round in 0..FULL_ROUNDS + PARTIAL_ROUNDS {
+            let full = round < FULL_ROUNDS / 2 || round >= FULL_ROUNDS / 2 + PARTIAL_ROUNDS;
+
+            state = state
+                .into_iter()
+                .enumerate()
+                .map(|(i, s)| {
+                    let s = s.add(
+                        &Affine::constant(parameters.round_constants[round * WIDTH + i].clone()),
+                        &T::one(),
+                    );
+                    match i == 0 || full {
+                        true => self.sbox(statements_flattened, s),
+                        false => s,
+                    }
+                })
+                .collect();
+
+            state = parameters
+                .mds
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .zip(&state)
+                        .fold(Affine::constant(T::zero()), |acc, (m, s)| acc.add(s, m))
+                })
+                .collect();
+        }
+
+        state.swap_remove(0)
+    }
+
+    // `x ** 5`, in three constraints unless `x` is constant
+    fn sbox(
+        &mut self,
+        statements_flattened: &mut FlatStatements<'ast, T>,
+        x: Affine<T>,
+    ) -> Affine<T> {
+        if x.terms.is_empty() {
+            return Affine::constant(x.constant.pow(5));
+        }
+
+        let x = x.into_expression();
+        let x2 = self.define(
+            FlatExpression::Mult(box x.clone(), box x.clone()),
+            statements_flattened,
+        );
+        let x4 = self.define(
+            FlatExpression::Mult(box x2.into(), box x2.into()),
+            statements_flattened,
+        );
+        Affine::variable(self.define(
+            FlatExpression::Mult(box x4.into(), box x),
+            statements_flattened,
+        ))
+    }
+
+    // the 256-bit big-endian encoding of `v`, checking that it is the canonical one
+    #[cfg(feature = "bellman")]
+    fn merkle_bits(
+        &mut self,
+        statements_flattened: &mut FlatStatements<'ast, T>,
+        v: Variable,
+    ) -> Vec<FlatExpression<T>> {
+        let bit_width = T::get_required_bits();
+
+        let bits = self.get_bits_unchecked(
+            &FlatUExpression::with_field(FlatExpression::from(v)),
+            bit_width,
+            bit_width,
+            statements_flattened,
+            RuntimeError::Sum,
+        );
+
+        self.enforce_constant_le_check_bits(
+            statements_flattened,
+            &bits,
+            &T::max_value().to_bits_be(),
+            RuntimeError::Le,
+        );
+
+        (bit_width..256)
+            .map(|_| FlatExpression::Number(T::zero()))
+            .chain(bits)
+            .collect()
+    }
+
+    // the SHA-256 digest of a 512-bit message, as two rounds of compression: the message, then its padding
+    #[cfg(feature = "bellman")]
+    fn sha256(
+        &mut self,
+        statements_flattened: &mut FlatStatements<'ast, T>,
+        message: Vec<FlatExpression<T>>,
+    ) -> Vec<FlatExpression<T>> {
+        const IV: [u32; 8] = [
+            0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+            0x5be0cd19,
+        ];
+        // a single one, zeroes, and the length of the message in bits
+        const PADDING: [u32; 16] = [0x80000000, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x200];
+
+        let bits = |words: &[u32]| -> Vec<FlatExpression<T>> {
+            words
+                .iter()
+                .flat_map(|w| (0..32).rev().map(move |i| (w >> i) & 1))
+                .map(|b| FlatExpression::Number(T::from(b)))
+                .collect()
+        };
+
+        let round = |flattener: &mut Self,
+                     statements_flattened: &mut FlatStatements<'ast, T>,
+                     block: Vec<FlatExpression<T>>,
+                     hash: Vec<FlatExpression<T>>| {
+            flattener
+                .flatten_embed_call_aux(
+                    statements_flattened,
+                    block
+                        .into_iter()
+                        .chain(hash)
+                        .map(FlatUExpression::with_field)
+                        .collect(),
+                    sha256_round(),
+                )
+                .into_iter()
+                .map(|e| e.get_field_unchecked())
+                .collect::<Vec<_>>()
+        };
+
+        let hash = round(self, statements_flattened, message, bits(&IV));
+        round(self, statements_flattened, bits(&PADDING), hash)
+    }
+}
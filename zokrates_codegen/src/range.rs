@@ -0,0 +1,344 @@
+//! Flattening of assertions of comparisons, which enforce the comparison without computing it as a boolean.
+//!
+//! Computing `a < b` decomposes the shifted difference `2^w + a - b` in `w + 1` bits to read its sign, and `a <= b`
+//! adds an equality check. Asserting the result then costs one more constraint. When both sides are known to fit in
+//! `w` bits, an assertion does not need the sign: `a <= b` holds iff `b - a` fits in `w` bits, and `a < b` iff
+//! `b - a - 1` does. If the comparison does not hold, the difference wraps around to a field element larger than
+//! `p - 2^w`, which does not fit in `w` bits as long as `w` is at most the bitwidth of the field minus two.
+//!
+//! The bounds come from the metadata of unsigned integers, and from the bounds of field arguments propagated through
+//! the arithmetic, see `Flattener::bound`. Against a small constant, the constant range check decomposes fewer bits
+//! and is kept, as well as the comparisons of operands whose bounds are unknown.
+
+use crate::utils::flat_expression_from_bits;
+use crate::{FlatStatements, Flattener};
+use zokrates_ast::common::RuntimeError;
+use zokrates_ast::flat::{FlatDirective, FlatExpression, FlatStatement};
+use zokrates_ast::ir::Solver;
+use zokrates_ast::zir::{FieldElementExpression, UExpression};
+use zokrates_field::Field;
+
+// the number of bits a reduced unsigned integer fits in
+fn reduced_width<T: Field>(e: &UExpression<T>) -> usize {
+    let metadata = e.metadata.as_ref().unwrap();
+    assert!(metadata.should_reduce.to_bool());
+
+    std::cmp::min(metadata.bitwidth() as usize, e.bitwidth.to_usize())
+}
+
+impl<'ast, T: Field> Flattener<'ast, T> {
+    /// Flattens an assertion of `lhs <= rhs`, or `lhs < rhs` if `strict`, on field elements
+    pub(crate) fn flatten_field_comparison_assertion(
+        &mut self,
+        statements_flattened: &mut FlatStatements<'ast, T>,
+        lhs: FieldElementExpression<'ast, T>,
+        rhs: FieldElementExpression<'ast, T>,
+        strict: bool,
+        error: RuntimeError,
+    ) {
+        let lhs = self.flatten_field_expression(statements_flattened, lhs);
+        let rhs = self.flatten_field_expression(statements_flattened, rhs);
+
+        let bounds = (self.bound(&lhs), self.bound(&rhs));
+
+        self.enforce_comparison(statements_flattened, lhs, rhs, bounds, strict, error)
+    }
+
+    /// Flattens an assertion of `lhs <= rhs`, or `lhs < rhs` if `strict`, on unsigned integers
+    pub(crate) fn flatten_uint_comparison_assertion(
+        &mut self,
+        statements_flattened: &mut FlatStatements<'ast, T>,
+        lhs: UExpression<'ast, T>,
+        rhs: UExpression<'ast, T>,
+        strict: bool,
+        error: RuntimeError,
+    ) {
+        // both sides are reduced, so they fit in their bitwidth, or in fewer bits if their metadata says so
+        let bounds = (Some(reduced_width(&lhs)), Some(reduced_width(&rhs)));
+
+        let lhs = self
+            .flatten_uint_expression(statements_flattened, lhs)
+            .get_field_unchecked();
+        let rhs = self
+            .flatten_uint_expression(statements_flattened, rhs)
+            .get_field_unchecked();
+
+        self.enforce_comparison(statements_flattened, lhs, rhs, bounds, strict, error)
+    }
+
+    // whether `e <= c` (`e < c` if `strict`) is better enforced by the constant range check than by the decomposition of
+    // the difference in `width` bits, see `Estimator::enforce_constant_le_check`
+    fn prefers_constant_check(
+        &self,
+        e: &FlatExpression<T>,
+        c: &T,
+        strict: bool,
+        width: usize,
+    ) -> bool {
+        let c = match strict {
+            // `e < 0` is a single failing constraint
+            true if *c == T::zero() => return true,
+            true => c.clone() - T::one(),
+            false => c.clone(),
+        };
+        let len = c.bits() as usize;
+
+        // the constant range check decomposes `e` in `len` bits, which cannot reuse a decomposition in more bits
+        let decomposed = self
+            .bits_cache
+            .get(e)
+            .map_or(false, |bits| bits.len() > len);
+
+        !decomposed && 3 * len + 3 < width + 1
+    }
+
+    // enforce `lhs <= rhs`, or `lhs < rhs` if `strict`, given the number of bits each side is known to fit in
+    fn enforce_comparison(
+        &mut self,
+        statements_flattened: &mut FlatStatements<'ast, T>,
+        lhs: FlatExpression<T>,
+        rhs: FlatExpression<T>,
+        bounds: (Option<usize>, Option<usize>),
+        strict: bool,
+        error: RuntimeError,
+    ) {
+        let safe_width = T::get_required_bits() - 2; // dynamic comparison is not complete
+
+        // the width of the difference, if both sides are small enough for it not to wrap around into range
+        let width = match bounds {
+            (Some(l), Some(r)) => Some(std::cmp::max(l, r).max(1)).filter(|w| *w <= safe_width),
+            _ => None,
+        };
+
+        match (lhs, rhs, width) {
+            (e, FlatExpression::Number(c), width)
+                if width.map_or(true, |w| self.prefers_constant_check(&e, &c, strict, w)) =>
+            {
+                match strict {
+                    true => self.enforce_constant_lt_check(statements_flattened, e, c, error),
+                    false => self.enforce_constant_le_check(statements_flattened, e, c, error),
+                }
+            }
+            (lhs, rhs, Some(width)) => {
+                self.enforce_difference_check(statements_flattened, lhs, rhs, width, strict, error)
+            }
+            // c < e <=> p - 1 - e < p - 1 - c
+            (FlatExpression::Number(c), e, None) => {
+                let e = FlatExpression::Sub(box T::max_value().into(), box e);
+                let c = T::max_value() - c;
+                match strict {
+                    true => self.enforce_constant_lt_check(statements_flattened, e, c, error),
+                    false => self.enforce_constant_le_check(statements_flattened, e, c, error),
+                }
+            }
+            (lhs, rhs, None) => {
+                let e = match strict {
+                    true => self.lt_check(statements_flattened, lhs, rhs, safe_width),
+                    false => self.le_check(statements_flattened, lhs, rhs, safe_width),
+                };
+                statements_flattened.push_back(FlatStatement::Condition(
+                    e,
+                    FlatExpression::Number(T::one()),
+                    error,
+                ));
+            }
+        }
+    }
+
+    // enforce `lhs <= rhs`, or `lhs < rhs` if `strict`, both sides fitting in `width` bits, by decomposing their
+    // difference in `width` bits
+    fn enforce_difference_check(
+        &mut self,
+        statements_flattened: &mut FlatStatements<'ast, T>,
+        lhs: FlatExpression<T>,
+        rhs: FlatExpression<T>,
+        width: usize,
+        strict: bool,
+        error: RuntimeError,
+    ) {
+        let lhs = match lhs.is_linear() {
+            true => lhs,
+            false => self.define(lhs, statements_flattened).into(),
+        };
+        let rhs = match rhs.is_linear() {
+            true => rhs,
+            false => self.define(rhs, statements_flattened).into(),
+        };
+
+        let difference = FlatExpression::Sub(box rhs, box lhs);
+        let difference = match strict {
+            true => FlatExpression::Sub(box difference, box FlatExpression::Number(T::one())),
+            false => difference,
+        };
+
+        // decomposed here rather than with `get_bits_unchecked`, which would reuse a cached decomposition in more bits
+        let bits: Vec<_> = (0..width).map(|_| self.use_sym()).collect();
+        statements_flattened.push_back(FlatStatement::Directive(FlatDirective::new(
+            bits.clone(),
+            Solver::Bits(width),
+            vec![difference.clone()],
+        )));
+
+        let bits: Vec<_> = bits.into_iter().map(FlatExpression::Identifier).collect();
+
+        statements_flattened.extend(bits.iter().map(|bit| {
+            FlatStatement::Condition(
+                bit.clone(),
+                FlatExpression::Mult(box bit.clone(), box bit.clone()),
+                RuntimeError::Bitness,
+            )
+        }));
+
+        statements_flattened.push_back(FlatStatement::Condition(
+            difference,
+            flat_expression_from_bits(bits),
+            error,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{constraint_count, from_function_and_config};
+    use zokrates_ast::zir::types::{Signature, UBitwidth};
+    use zokrates_ast::zir::{
+        self, BooleanExpression, FieldElementExpression, ShouldReduce, UExpression, UMetadata,
+        ZirFunction, ZirStatement,
+    };
+    use zokrates_common::CompileConfig;
+    use zokrates_field::Bn128Field;
+
+    // the number of constraints of `def main(..arguments) { ..statements; return; }`
+    fn size(
+        arguments: &[zir::Parameter<'static>],
+        mut statements: Vec<ZirStatement<'static, Bn128Field>>,
+    ) -> usize {
+        statements.push(ZirStatement::Return(vec![]));
+
+        let function = ZirFunction {
+            arguments: arguments.to_vec(),
+            statements,
+            signature: Signature::new()
+                .inputs(arguments.iter().map(|p| p.id.get_type()).collect())
+                .outputs(vec![]),
+        };
+
+        from_function_and_config(function, CompileConfig::default())
+            .collect()
+            .statements
+            .iter()
+            .map(constraint_count)
+            .sum()
+    }
+
+    // the number of constraints of `assert(e)` with the given arguments, and of the same assertion with the
+    // comparison first computed as a boolean: `bool c = e; assert(c);`
+    fn assertion_sizes(
+        arguments: &[zir::Parameter<'static>],
+        e: BooleanExpression<'static, Bn128Field>,
+    ) -> (usize, usize) {
+        let arguments_size = size(arguments, vec![]);
+
+        let direct = size(
+            arguments,
+            vec![ZirStatement::Assertion(
+                e.clone(),
+                zir::RuntimeError::mock(),
+            )],
+        );
+        let materialized = size(
+            arguments,
+            vec![
+                ZirStatement::Definition(zir::Variable::boolean("c".into()), e.into()),
+                ZirStatement::Assertion(
+                    BooleanExpression::identifier("c".into()),
+                    zir::RuntimeError::mock(),
+                ),
+            ],
+        );
+
+        (direct - arguments_size, materialized - arguments_size)
+    }
+
+    fn uint(id: &'static str) -> UExpression<'static, Bn128Field> {
+        UExpression::identifier(id.into())
+            .annotate(UBitwidth::B32)
+            .metadata(UMetadata {
+                max: 0xffffffff_u32.into(),
+                should_reduce: ShouldReduce::True,
+                signed: false,
+            })
+    }
+
+    fn field(id: &'static str) -> FieldElementExpression<'static, Bn128Field> {
+        FieldElementExpression::identifier(id.into())
+    }
+
+    fn field_parameter(id: &'static str, bound: Option<u32>) -> zir::Parameter<'static> {
+        zir::Parameter {
+            id: zir::Variable::field_element(id),
+            private: true,
+            bound,
+            signed: false,
+        }
+    }
+
+    #[test]
+    fn uint_comparisons() {
+        let arguments = [
+            zir::Parameter::private(zir::Variable::uint("a".into(), UBitwidth::B32)),
+            zir::Parameter::private(zir::Variable::uint("b".into(), UBitwidth::B32)),
+        ];
+
+        // the difference is decomposed in 32 bits
+        for e in [
+            BooleanExpression::UintLt(box uint("a"), box uint("b")),
+            BooleanExpression::UintLe(box uint("a"), box uint("b")),
+        ] {
+            let (direct, materialized) = assertion_sizes(&arguments, e);
+            assert_eq!(direct, 32 + 1);
+            assert!(direct < materialized);
+        }
+    }
+
+    #[test]
+    fn bounded_field_comparisons() {
+        let arguments = [
+            field_parameter("a", Some(32)),
+            field_parameter("b", Some(32)),
+        ];
+
+        for e in [
+            BooleanExpression::FieldLt(box field("a"), box field("b")),
+            BooleanExpression::FieldLe(box field("a"), box field("b")),
+        ] {
+            let (direct, materialized) = assertion_sizes(&arguments, e);
+            assert_eq!(direct, 32 + 1);
+            assert!(direct < materialized);
+        }
+
+        // `a + b` fits in 33 bits, and is not decomposed yet: the constant range check on 2 bits is smaller
+        let (direct, _) = assertion_sizes(
+            &arguments,
+            BooleanExpression::FieldLe(
+                box FieldElementExpression::Add(box field("a"), box field("b")),
+                box FieldElementExpression::Number(Bn128Field::from(3)),
+            ),
+        );
+        assert!(direct < 33 + 1);
+    }
+
+    #[test]
+    fn unbounded_field_comparisons() {
+        let arguments = [field_parameter("a", None), field_parameter("b", None)];
+
+        // the sign of the shifted difference is still read, but not assigned to a boolean
+        for e in [
+            BooleanExpression::FieldLt(box field("a"), box field("b")),
+            BooleanExpression::FieldLe(box field("a"), box field("b")),
+        ] {
+            let (direct, materialized) = assertion_sizes(&arguments, e);
+            assert!(direct < materialized);
+        }
+    }
+}
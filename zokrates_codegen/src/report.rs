@@ -0,0 +1,166 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use zokrates_ast::flat::FlatStatement;
+
+/// The directives and embeds used by each source function of a program, and the uses which need the attention of an
+/// auditor
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Report {
+    pub functions: BTreeMap<String, FunctionReport>,
+}
+
+/// The directives and embeds used in the body of a single source function, excluding the functions it calls
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FunctionReport {
+    /// The number of directives of each solver kind
+    pub directives: BTreeMap<String, usize>,
+    /// The number of calls to each embed
+    pub embeds: BTreeMap<String, usize>,
+    /// The number of occurrences of each unsafe use, such as a `batch_eq` whose challenge is a compile-time constant
+    /// (`constant_challenge`)
+    pub warnings: BTreeMap<String, usize>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows: Vec<_> = self
+            .functions
+            .iter()
+            .flat_map(|(function, report)| {
+                report
+                    .directives
+                    .iter()
+                    .map(move |(name, count)| (function, "directive", name, count))
+                    .chain(
+                        report
+                            .embeds
+                            .iter()
+                            .map(move |(name, count)| (function, "embed", name, count)),
+                    )
+                    .chain(
+                        report
+                            .warnings
+                            .iter()
+                            .map(move |(name, count)| (function, "warning", name, count)),
+                    )
+            })
+            .collect();
+
+        let width = rows
+            .iter()
+            .map(|(function, ..)| function.len())
+            .chain(std::iter::once("function".len()))
+            .max()
+            .unwrap();
+
+        writeln!(
+            f,
+            "{:<width$}  {:<9}  {:<20}  count",
+            "function",
+            "kind",
+            "name",
+            width = width
+        )?;
+
+        for (function, kind, name, count) in rows {
+            writeln!(
+                f,
+                "{:<width$}  {:<9}  {:<20}  {}",
+                function,
+                kind,
+                name,
+                count,
+                width = width
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Attributes the directives and embeds emitted while flattening to the source function they come from
+#[derive(Debug)]
+pub(crate) struct Attribution {
+    /// The function statements are attributed to when no call is being flattened
+    entry: String,
+    /// The functions whose inlined bodies are being flattened, innermost last
+    stack: Vec<String>,
+    pub report: Report,
+}
+
+impl Attribution {
+    pub fn new(entry: String) -> Self {
+        Attribution {
+            entry,
+            stack: vec![],
+            report: Report::default(),
+        }
+    }
+
+    pub fn push(&mut self, function: String) {
+        self.stack.push(function);
+    }
+
+    pub fn pop(&mut self) {
+        self.stack.pop();
+    }
+
+    fn current(&mut self) -> &mut FunctionReport {
+        let function = self.stack.last().unwrap_or(&self.entry);
+        self.report.functions.entry(function.clone()).or_default()
+    }
+
+    pub fn record_embed(&mut self, id: &str) {
+        *self.current().embeds.entry(id.to_string()).or_default() += 1;
+    }
+
+    pub fn record_warning(&mut self, name: &str) {
+        *self.current().warnings.entry(name.to_string()).or_default() += 1;
+    }
+
+    pub fn record_statements<'a, 'ast: 'a, T: 'a>(
+        &mut self,
+        statements: impl IntoIterator<Item = &'a FlatStatement<'ast, T>>,
+    ) {
+        for s in statements {
+            match s {
+                FlatStatement::Directive(d) => {
+                    *self
+                        .current()
+                        .directives
+                        .entry(d.solver.kind().to_string())
+                        .or_default() += 1;
+                }
+                FlatStatement::Block(statements) => self.record_statements(statements),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table() {
+        let mut attribution = Attribution::new("main.zok/main".into());
+        attribution.record_embed("_UNPACK");
+        attribution.push("lib.zok/main".into());
+        attribution.record_embed("_SHA256_ROUND");
+        attribution.record_embed("_SHA256_ROUND");
+        attribution.pop();
+        attribution.record_embed("_BATCH_EQ");
+        attribution.record_warning("constant_challenge");
+
+        assert_eq!(
+            attribution.report.to_string(),
+            "function       kind       name                  count\n\
+             lib.zok/main   embed      _SHA256_ROUND         2\n\
+             main.zok/main  embed      _BATCH_EQ             1\n\
+             main.zok/main  embed      _UNPACK               1\n\
+             main.zok/main  warning    constant_challenge    1\n"
+        );
+    }
+}
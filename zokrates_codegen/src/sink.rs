@@ -0,0 +1,53 @@
+use crate::Origin;
+use zokrates_ast::flat::FlatStatement;
+use zokrates_ast::ir::Statement;
+use zokrates_field::Field;
+
+/// An observer of the constraints emitted by the flattener, see `observe`
+///
+/// A sink only gets a shared reference to each constraint, so it cannot change the program. The constraints are the
+/// ones of the flattened program, before the optimizer removes or rewrites some of them.
+pub trait ConstraintSink<T> {
+    /// Called for each constraint, in the order in which the flattener emits them
+    fn on_constraint(&mut self, constraint: &Statement<T>, provenance: &Origin);
+}
+
+impl<T, S: ConstraintSink<T> + ?Sized> ConstraintSink<T> for &mut S {
+    fn on_constraint(&mut self, constraint: &Statement<T>, provenance: &Origin) {
+        (**self).on_constraint(constraint, provenance)
+    }
+}
+
+impl<T, S: ConstraintSink<T>> ConstraintSink<T> for Option<S> {
+    fn on_constraint(&mut self, constraint: &Statement<T>, provenance: &Origin) {
+        if let Some(sink) = self {
+            sink.on_constraint(constraint, provenance)
+        }
+    }
+}
+
+impl<T, A: ConstraintSink<T>, B: ConstraintSink<T>> ConstraintSink<T> for (A, B) {
+    fn on_constraint(&mut self, constraint: &Statement<T>, provenance: &Origin) {
+        self.0.on_constraint(constraint, provenance);
+        self.1.on_constraint(constraint, provenance);
+    }
+}
+
+// hand the constraints of a flattened statement to `sink`, the ones of a block one by one
+pub(crate) fn notify<T: Field>(
+    sink: &mut dyn ConstraintSink<T>,
+    s: &FlatStatement<T>,
+    provenance: &Origin,
+) {
+    match s {
+        FlatStatement::Block(statements) => {
+            for s in statements {
+                notify(sink, s, provenance);
+            }
+        }
+        FlatStatement::Condition(..) | FlatStatement::Definition(..) => {
+            sink.on_constraint(&Statement::from(s.clone()), provenance)
+        }
+        FlatStatement::Directive(..) | FlatStatement::Log(..) => {}
+    }
+}
@@ -0,0 +1,49 @@
+//! Module exposing the flattening of single expressions, so that the constraints an expression lowers to can be
+//! tested without compiling a whole program.
+//!
+//! Only available with the `testing` feature.
+
+use crate::{FlatStatements, Flattener};
+use zokrates_ast::common::Variable;
+use zokrates_ast::ir::{LinComb, Statement};
+use zokrates_ast::zir::{Variable as ZirVariable, ZirExpression};
+use zokrates_common::CompileConfig;
+use zokrates_field::Field;
+
+/// The result of flattening a single expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlattenedExpression<'ast, T> {
+    /// The variables the context was mapped to, in order
+    pub variables: Vec<Variable>,
+    /// The statements emitted to compute the expression
+    pub statements: Vec<Statement<'ast, T>>,
+    /// The value of the expression
+    pub output: LinComb<T>,
+}
+
+/// Flattens `e` alone, with the default configuration
+///
+/// The variables of `context` are mapped to `_0`, `_1`, ... in order, and the variables introduced by the flattening
+/// follow them. No constraint is added on the variables of the context: integers are assumed to be in range, and
+/// booleans to be `0` or `1`. Integer expressions must carry the metadata set by the uint optimizer.
+pub fn flatten_expression<'ast, T: Field>(
+    e: ZirExpression<'ast, T>,
+    context: &[ZirVariable<'ast>],
+) -> FlattenedExpression<'ast, T> {
+    let mut flattener = Flattener::new(CompileConfig::default());
+    let mut statements_flattened = FlatStatements::new();
+
+    let variables = context.iter().map(|v| flattener.use_variable(v)).collect();
+
+    let output = flattener
+        .flatten_expression(&mut statements_flattened, e)
+        .get_field_unchecked();
+    // the output may be quadratic, in which case it is defined so that it can be returned as a linear combination
+    let output = flattener.identify_expression(output, &mut statements_flattened);
+
+    FlattenedExpression {
+        variables,
+        statements: statements_flattened.into_iter().map(Into::into).collect(),
+        output: output.into(),
+    }
+}
@@ -5,6 +5,8 @@ pub const BN128: &str = "bn128";
 pub const BLS12_381: &str = "bls12_381";
 pub const BLS12_377: &str = "bls12_377";
 pub const BW6_761: &str = "bw6_761";
+pub const PALLAS: &str = "pallas";
+pub const VESTA: &str = "vesta";
 
 pub const G16: &str = "g16";
 pub const GM17: &str = "gm17";
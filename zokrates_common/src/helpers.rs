@@ -7,6 +7,8 @@ pub enum CurveParameter {
     Bls12_381,
     Bls12_377,
     Bw6_761,
+    Pallas,
+    Vesta,
 }
 
 impl std::fmt::Display for CurveParameter {
@@ -18,6 +20,8 @@ impl std::fmt::Display for CurveParameter {
             Bls12_381 => write!(f, "bls12_381"),
             Bls12_377 => write!(f, "bls12_377"),
             Bw6_761 => write!(f, "bw6_761"),
+            Pallas => write!(f, "pallas"),
+            Vesta => write!(f, "vesta"),
         }
     }
 }
@@ -72,6 +76,8 @@ impl TryFrom<&str> for CurveParameter {
             BLS12_381 => Ok(CurveParameter::Bls12_381),
             BLS12_377 => Ok(CurveParameter::Bls12_377),
             BW6_761 => Ok(CurveParameter::Bw6_761),
+            PALLAS => Ok(CurveParameter::Pallas),
+            VESTA => Ok(CurveParameter::Vesta),
             _ => Err(format!("Unknown curve {}", s)),
         }
     }
@@ -111,6 +117,16 @@ pub struct Parameters(
     pub SchemeParameter,
 );
 
+impl Parameters {
+    /// The error returned when the curve of a program does not match any backend implementation
+    pub fn unsupported_curve(&self) -> String {
+        format!(
+            "Curve {} is not supported by the {} backend with the {} proving scheme",
+            self.1, self.0, self.2
+        )
+    }
+}
+
 impl TryFrom<(&str, &str, &str)> for Parameters {
     type Error = String;
 
@@ -148,7 +164,6 @@ impl TryFrom<(&str, &str, &str)> for Parameters {
             (BackendParameter::Ark, CurveParameter::Bls12_377, SchemeParameter::MARLIN) => Ok(()),
             #[cfg(feature = "ark")]
             (BackendParameter::Ark, CurveParameter::Bw6_761, SchemeParameter::MARLIN) => Ok(()),
-            #[cfg(feature = "bellman")]
             _ => Err(format!(
                 "Unsupported combination of parameters (backend: {}, curve: {}, proving scheme: {})",
                 s.0, s.1, s.2
@@ -156,3 +171,29 @@ impl TryFrom<(&str, &str, &str)> for Parameters {
         }.map(|_: ()| Parameters(backend, curve, proving_scheme))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pasta_curves() {
+        assert_eq!(
+            CurveParameter::try_from("pallas"),
+            Ok(CurveParameter::Pallas)
+        );
+        assert_eq!(CurveParameter::try_from("vesta"), Ok(CurveParameter::Vesta));
+    }
+
+    #[test]
+    fn reject_unsupported_curves() {
+        for curve in [PALLAS, VESTA] {
+            for scheme in [G16, GM17, MARLIN] {
+                #[cfg(feature = "ark")]
+                assert!(Parameters::try_from((ARK, curve, scheme)).is_err());
+                #[cfg(feature = "bellman")]
+                assert!(Parameters::try_from((BELLMAN, curve, scheme)).is_err());
+            }
+        }
+    }
+}
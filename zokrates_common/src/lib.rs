@@ -1,6 +1,8 @@
 pub mod constants;
 pub mod helpers;
+pub mod pipeline;
 
+pub use pipeline::Pipeline;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -12,12 +14,72 @@ pub trait Resolver<E> {
     ) -> Result<(String, PathBuf), E>;
 }
 
-#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy)]
+/// How the uint optimizer decides which operand of an overflowing operation to reduce
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReductionPolicy {
+    /// Reduce the left operand whenever this is enough
+    Greedy,
+    /// Compare the reductions caused by each choice within a statement and keep the cheapest
+    MinimizeDecompositions,
+}
+
+impl Default for ReductionPolicy {
+    fn default() -> Self {
+        ReductionPolicy::Greedy
+    }
+}
+
+/// What dividing a field element by zero evaluates to
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DivByZero {
+    /// Witness generation fails, reporting the location of the division
+    Error,
+    /// The division evaluates to zero, at the cost of extra constraints
+    DefineZero,
+}
+
+impl Default for DivByZero {
+    fn default() -> Self {
+        DivByZero::Error
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct CompileConfig {
     #[serde(default)]
     pub isolate_branches: bool,
     #[serde(default)]
     pub debug: bool,
+    #[serde(default)]
+    pub reduction_policy: ReductionPolicy,
+    #[serde(default)]
+    pub div_by_zero: DivByZero,
+    /// Abort compilation as soon as the program has more constraints than this
+    #[serde(default)]
+    pub max_constraints: Option<usize>,
+    /// Replace the public inputs of `main` by a single public commitment to their values
+    #[serde(default)]
+    pub hash_public_inputs: bool,
+    /// The optimization passes to run, the default pipeline being used if none is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pipeline: Option<Pipeline>,
+    /// The maximum depth of nested function calls, a default limit being used if none is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_call_depth: Option<usize>,
+    /// The amount of work after which constant arrays, structs and tuples are not propagated anymore
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub propagation_budget: Option<usize>,
+    /// Drop the return values of `main` after semantic checking, keeping only its assertions
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_outputs: bool,
+    /// Fail on equality assertions which always hold instead of removing them with a warning
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deny_trivial_asserts: bool,
+    /// Check that the zir program is in single-assignment form after each pass, which is always done in debug builds
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub validate_ir: bool,
 }
 
 impl CompileConfig {
@@ -30,4 +92,54 @@ impl CompileConfig {
         self.debug = debug;
         self
     }
+
+    pub fn reduction_policy(mut self, policy: ReductionPolicy) -> Self {
+        self.reduction_policy = policy;
+        self
+    }
+
+    pub fn div_by_zero(mut self, policy: DivByZero) -> Self {
+        self.div_by_zero = policy;
+        self
+    }
+
+    pub fn max_constraints(mut self, max_constraints: Option<usize>) -> Self {
+        self.max_constraints = max_constraints;
+        self
+    }
+
+    pub fn hash_public_inputs(mut self, flag: bool) -> Self {
+        self.hash_public_inputs = flag;
+        self
+    }
+
+    pub fn pipeline(mut self, pipeline: Option<Pipeline>) -> Self {
+        self.pipeline = pipeline;
+        self
+    }
+
+    pub fn max_call_depth(mut self, max_call_depth: Option<usize>) -> Self {
+        self.max_call_depth = max_call_depth;
+        self
+    }
+
+    pub fn propagation_budget(mut self, budget: Option<usize>) -> Self {
+        self.propagation_budget = budget;
+        self
+    }
+
+    pub fn no_outputs(mut self, flag: bool) -> Self {
+        self.no_outputs = flag;
+        self
+    }
+
+    pub fn deny_trivial_asserts(mut self, flag: bool) -> Self {
+        self.deny_trivial_asserts = flag;
+        self
+    }
+
+    pub fn validate_ir(mut self, flag: bool) -> Self {
+        self.validate_ir = flag;
+        self
+    }
 }
@@ -0,0 +1,299 @@
+//! Descriptions of the optimization passes run by the compiler, and of their order.
+//!
+//! A pipeline is written as a comma-separated list of pass names, such as `propagate,panics,uint,redef,dedup`. The
+//! passes on the zir program come first, then the passes on the ir program. Each pass can appear several times,
+//! except for `panics` and `uint`, which are required exactly once, `uint` being the last zir pass.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// The pipeline used when none is specified
+pub const DEFAULT_PIPELINE: &str =
+    "propagate,panics,dead_code,prune,uint,redef,linearize,tautology,canonicalize,directive,booleanity,dedup";
+
+/// A compiler pass transforming programs of type `P`
+pub trait Pass<P> {
+    type Error;
+
+    fn apply(&self, program: P) -> Result<P, Self::Error>;
+}
+
+/// The program representation a pass runs on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Zir,
+    Ir,
+}
+
+/// The passes which can be used in a pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PassId {
+    Propagate,
+    Panics,
+    DeadCode,
+    Prune,
+    Uint,
+    Redefinition,
+    Linearize,
+    Tautology,
+    Canonicalize,
+    Directive,
+    Booleanity,
+    Dedup,
+}
+
+impl PassId {
+    /// All registered passes
+    pub const ALL: [PassId; 12] = [
+        PassId::Propagate,
+        PassId::Panics,
+        PassId::DeadCode,
+        PassId::Prune,
+        PassId::Uint,
+        PassId::Redefinition,
+        PassId::Linearize,
+        PassId::Tautology,
+        PassId::Canonicalize,
+        PassId::Directive,
+        PassId::Booleanity,
+        PassId::Dedup,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PassId::Propagate => "propagate",
+            PassId::Panics => "panics",
+            PassId::DeadCode => "dead_code",
+            PassId::Prune => "prune",
+            PassId::Uint => "uint",
+            PassId::Redefinition => "redef",
+            PassId::Linearize => "linearize",
+            PassId::Tautology => "tautology",
+            PassId::Canonicalize => "canonicalize",
+            PassId::Directive => "directive",
+            PassId::Booleanity => "booleanity",
+            PassId::Dedup => "dedup",
+        }
+    }
+
+    pub fn stage(&self) -> Stage {
+        match self {
+            PassId::Propagate
+            | PassId::Panics
+            | PassId::DeadCode
+            | PassId::Prune
+            | PassId::Uint => Stage::Zir,
+            PassId::Redefinition
+            | PassId::Linearize
+            | PassId::Tautology
+            | PassId::Canonicalize
+            | PassId::Directive
+            | PassId::Booleanity
+            | PassId::Dedup => Stage::Ir,
+        }
+    }
+}
+
+impl fmt::Display for PassId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl Serialize for PassId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl FromStr for PassId {
+    type Err = PipelineError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PassId::ALL
+            .iter()
+            .find(|p| p.name() == s)
+            .cloned()
+            .ok_or_else(|| PipelineError::UnknownPass(s.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PipelineError {
+    UnknownPass(String),
+    Misordered(PassId, PassId),
+    Occurrences(PassId, usize),
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PipelineError::UnknownPass(name) => write!(
+                f,
+                "Unknown pass `{}`, expected one of: {}",
+                name,
+                PassId::ALL
+                    .iter()
+                    .map(|p| p.name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            PipelineError::Misordered(before, after) => {
+                write!(f, "Pass `{}` cannot run after `{}`", after, before)
+            }
+            PipelineError::Occurrences(pass, count) => write!(
+                f,
+                "Pass `{}` must run exactly once, found {} occurrence(s)",
+                pass, count
+            ),
+        }
+    }
+}
+
+/// An ordered list of passes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pipeline {
+    passes: Vec<PassId>,
+}
+
+impl Pipeline {
+    pub fn passes(&self) -> &[PassId] {
+        &self.passes
+    }
+
+    /// The passes of the pipeline which run on the given program representation, in order
+    pub fn stage(&self, stage: Stage) -> impl Iterator<Item = PassId> + '_ {
+        self.passes()
+            .iter()
+            .cloned()
+            .filter(move |p| p.stage() == stage)
+    }
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        DEFAULT_PIPELINE.parse().unwrap()
+    }
+}
+
+impl FromStr for Pipeline {
+    type Err = PipelineError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let passes = s
+            .split(',')
+            .map(|name| name.trim().parse())
+            .collect::<Result<Vec<PassId>, _>>()?;
+
+        // zir passes run before the program is lowered to ir
+        if let Some(w) = passes
+            .windows(2)
+            .find(|w| w[0].stage() == Stage::Ir && w[1].stage() == Stage::Zir)
+        {
+            return Err(PipelineError::Misordered(w[0], w[1]));
+        }
+
+        for required in [PassId::Panics, PassId::Uint] {
+            let count = passes.iter().filter(|p| **p == required).count();
+            if count != 1 {
+                return Err(PipelineError::Occurrences(required, count));
+            }
+        }
+
+        // the flattening relies on the annotations of the uint optimizer, which other passes do not maintain
+        let uint = passes.iter().position(|p| *p == PassId::Uint).unwrap();
+        if let Some(p) = passes[uint + 1..].iter().find(|p| p.stage() == Stage::Zir) {
+            return Err(PipelineError::Misordered(PassId::Uint, *p));
+        }
+
+        Ok(Pipeline { passes })
+    }
+}
+
+impl fmt::Display for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.passes()
+                .iter()
+                .map(|p| p.name())
+                .collect::<Vec<_>>()
+                .join(",")
+        )
+    }
+}
+
+impl Serialize for Pipeline {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Pipeline {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default() {
+        assert_eq!(Pipeline::default().to_string(), DEFAULT_PIPELINE);
+        assert_eq!(Pipeline::default().stage(Stage::Zir).count(), 5);
+        assert_eq!(Pipeline::default().stage(Stage::Ir).count(), 7);
+    }
+
+    #[test]
+    fn repeated_passes() {
+        let pipeline: Pipeline = "propagate,panics,uint,dedup,redef,dedup".parse().unwrap();
+        assert_eq!(
+            pipeline.stage(Stage::Ir).collect::<Vec<_>>(),
+            vec![PassId::Dedup, PassId::Redefinition, PassId::Dedup]
+        );
+    }
+
+    #[test]
+    fn long_pipeline() {
+        let pipeline = format!("panics,uint{}", ",dedup".repeat(100));
+        let pipeline: Pipeline = pipeline.parse().unwrap();
+        assert_eq!(pipeline.passes().len(), 102);
+    }
+
+    #[test]
+    fn unknown_pass() {
+        let error = "propagate,panics,uint,inline"
+            .parse::<Pipeline>()
+            .unwrap_err();
+        assert_eq!(error, PipelineError::UnknownPass("inline".into()));
+        assert_eq!(
+            error.to_string(),
+            "Unknown pass `inline`, expected one of: propagate, panics, dead_code, prune, uint, redef, linearize, tautology, canonicalize, directive, booleanity, dedup"
+        );
+    }
+
+    #[test]
+    fn invalid_order() {
+        assert_eq!(
+            "panics,uint,redef,propagate".parse::<Pipeline>(),
+            Err(PipelineError::Misordered(
+                PassId::Redefinition,
+                PassId::Propagate
+            ))
+        );
+        assert_eq!(
+            "panics,uint,prune".parse::<Pipeline>(),
+            Err(PipelineError::Misordered(PassId::Uint, PassId::Prune))
+        );
+        assert_eq!(
+            "propagate,uint".parse::<Pipeline>(),
+            Err(PipelineError::Occurrences(PassId::Panics, 0))
+        );
+    }
+}
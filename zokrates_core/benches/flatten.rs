@@ -0,0 +1,150 @@
+//! Compile a medium program to measure flattening.
+//!
+//! Run with `cargo bench -p zokrates_core --bench flatten`. Besides the time per compilation, the number of heap
+//! allocations of one compilation is printed, as most linear combinations are small enough to be stored inline.
+//! `flatten_medium_observed` compiles the same program with a `ConstraintCounter`, to measure the cost of observing
+//! the emitted constraints. Its baseline is the compilation without observers: it fails if compiling through
+//! `compile_observed` without any observer allocates more, or gives another program.
+
+#![feature(test)]
+
+extern crate test;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use test::Bencher;
+use typed_arena::Arena;
+use zokrates_common::{CompileConfig, Resolver};
+use zokrates_core::compile::{compile, compile_observed, Observers};
+use zokrates_core::sink::ConstraintCounter;
+use zokrates_field::Bn128Field;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const SOURCE: &str = r#"
+    def main(private u32[16] a, private field[16] b) -> (u32, field) {
+        u32 mut acc = 0;
+        field mut sum = 0;
+        for u32 i in 0..16 {
+            acc = acc + (a[i] ^ (a[(i + 1) % 16] >> 3));
+            sum = sum + b[i] * b[(i + 3) % 16];
+            assert(b[i] != 0);
+        }
+        return (acc, sum);
+    }
+"#;
+
+fn compile_medium() -> usize {
+    let arena = Arena::new();
+
+    compile::<Bn128Field, io::Error>(
+        SOURCE.to_string(),
+        "main".into(),
+        None::<&dyn Resolver<io::Error>>,
+        CompileConfig::default(),
+        &arena,
+    )
+    .unwrap()
+    .collect()
+    .into_inner()
+    .0
+    .constraint_count()
+}
+
+/// Run `f`, along with the number of heap allocations it made
+fn allocations<R, F: FnOnce() -> R>(f: F) -> (R, usize) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let res = f();
+    (res, ALLOCATIONS.load(Ordering::Relaxed) - before)
+}
+
+#[bench]
+fn flatten_medium(b: &mut Bencher) {
+    let (constraint_count, allocations) = allocations(compile_medium);
+
+    eprintln!(
+        "{} constraints, {} allocations per compilation",
+        constraint_count, allocations
+    );
+
+    b.iter(compile_medium);
+}
+
+fn compile_medium_unobserved() -> usize {
+    let arena = Arena::new();
+
+    compile_observed::<Bn128Field, io::Error>(
+        SOURCE.to_string(),
+        "main".into(),
+        None::<&dyn Resolver<io::Error>>,
+        CompileConfig::default(),
+        &arena,
+        Observers::default(),
+    )
+    .unwrap()
+    .0
+    .into_inner()
+    .0
+    .constraint_count()
+}
+
+fn compile_medium_observed() -> usize {
+    let arena = Arena::new();
+    let mut counter = ConstraintCounter::default();
+
+    compile_observed::<Bn128Field, io::Error>(
+        SOURCE.to_string(),
+        "main".into(),
+        None::<&dyn Resolver<io::Error>>,
+        CompileConfig::default(),
+        &arena,
+        Observers {
+            sink: Some(&mut counter),
+            ..Observers::default()
+        },
+    )
+    .unwrap();
+
+    counter.total
+}
+
+#[bench]
+fn flatten_medium_observed(b: &mut Bencher) {
+    let (constraint_count, baseline) = allocations(compile_medium);
+    let (unobserved_count, unobserved) = allocations(compile_medium_unobserved);
+
+    assert_eq!(unobserved_count, constraint_count);
+    // allow for the allocations of the collections whose capacity depends on the order of hash maps
+    assert!(
+        unobserved <= baseline + baseline / 100,
+        "compiling without observers allocates {} times, against {} for the baseline",
+        unobserved,
+        baseline
+    );
+
+    let (emitted, observed) = allocations(compile_medium_observed);
+
+    eprintln!(
+        "{} constraints emitted, {} allocations per compilation against {} without observers",
+        emitted, observed, baseline
+    );
+
+    b.iter(compile_medium_observed);
+}
@@ -0,0 +1,67 @@
+//! Compute the witness of a program made of 16 independent sha256 hashes, to measure the speedup of solving the
+//! directives in parallel.
+//!
+//! Run with `cargo bench -p zokrates_core --bench witness`. `witness_serial` solves the directives in order and
+//! `witness_parallel` solves the directives of each level of their dependency graph in parallel.
+
+#![feature(test)]
+
+extern crate test;
+
+use std::path::PathBuf;
+use test::Bencher;
+use typed_arena::Arena;
+use zokrates_ast::ir::Prog;
+use zokrates_common::CompileConfig;
+use zokrates_core::compile::compile;
+use zokrates_field::Bn128Field;
+use zokrates_fs_resolver::FileSystemResolver;
+use zokrates_interpreter::Interpreter;
+
+const SOURCE: &str = include_str!("../../zokrates_core_test/tests/tests/parallel/sha256.zok");
+
+fn program<'ast>(arena: &'ast Arena<String>) -> Prog<'ast, Bn128Field> {
+    let stdlib = std::fs::canonicalize("../zokrates_stdlib/stdlib").unwrap();
+    let resolver = FileSystemResolver::with_stdlib_root(stdlib.to_str().unwrap());
+
+    compile::<Bn128Field, _>(
+        SOURCE.to_string(),
+        PathBuf::from("sha256.zok"),
+        Some(&resolver),
+        CompileConfig::default(),
+        arena,
+    )
+    .unwrap()
+    .collect()
+    .into_inner()
+    .0
+}
+
+fn inputs() -> Vec<Bn128Field> {
+    (0..16u32).map(Bn128Field::from).collect()
+}
+
+#[bench]
+fn witness_serial(b: &mut Bencher) {
+    let arena = Arena::new();
+    let program = program(&arena);
+    let interpreter = Interpreter::default();
+
+    b.iter(|| interpreter.execute(program.clone(), &inputs()).unwrap());
+}
+
+#[bench]
+fn witness_parallel(b: &mut Bencher) {
+    let arena = Arena::new();
+    let program = program(&arena);
+    let interpreter = Interpreter::default().with_parallel_directives();
+
+    assert_eq!(
+        interpreter.execute(program.clone(), &inputs()).unwrap(),
+        Interpreter::default()
+            .execute(program.clone(), &inputs())
+            .unwrap()
+    );
+
+    b.iter(|| interpreter.execute(program.clone(), &inputs()).unwrap());
+}
@@ -0,0 +1,767 @@
+//! Module containing an on-disk cache for compiled programs.
+//!
+//! A snapshot of the compiled program and its ABI is stored under a key derived from the compiler
+//! version, the compilation flags and a fingerprint of the entry module. The fingerprint of a module
+//! covers its source text and the fingerprints of every module it imports, so changing any module
+//! in the import graph invalidates the snapshots of all modules which depend on it.
+//!
+//! When the program changed, the outcome of checking each module is also stored under a key derived
+//! from the fingerprint of that module, so that only the modules which changed, or import a module
+//! which changed, are checked again.
+//!
+//! It also contains a cache for the sources of the modules, shared by several compilations of the
+//! same program in a single run, for example for several curves.
+
+use crate::compile::{
+    check_semantics_with_snapshots, parse_program, to_ir, to_typed_from_program, to_zir,
+    CompilationArtifacts, CompileErrors,
+};
+use crate::imports;
+use crate::semantics::{ModuleSnapshot, ModuleSnapshots};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use typed_arena::Arena;
+use zokrates_ast::common::Diagnostic;
+use zokrates_ast::ir;
+use zokrates_ast::typed::abi::Abi;
+use zokrates_ast::typed::types;
+use zokrates_ast::untyped::{OwnedModuleId, Program, Symbol};
+use zokrates_codegen::Warnings;
+use zokrates_common::{CompileConfig, Resolver};
+use zokrates_field::Field;
+
+/// The version of the snapshot format, to be bumped whenever the layout of `Snapshot` or `ModuleEntry` changes, or what
+/// the checked modules contain
+pub const SNAPSHOT_VERSION: u32 = 4;
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot<'ast, T> {
+    version: u32,
+    key: String,
+    abi: Abi,
+    #[serde(borrow)]
+    program: ir::Prog<'ast, T>,
+    /// The warnings of the compilation, reported again when the snapshot is reused
+    diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ModuleEntry<'ast, T> {
+    version: u32,
+    key: String,
+    #[serde(borrow)]
+    snapshot: ModuleSnapshot<'ast, T>,
+}
+
+/// A directory holding compilation snapshots
+#[derive(Debug, Clone)]
+pub struct BuildCache {
+    dir: PathBuf,
+}
+
+impl BuildCache {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        BuildCache { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.snapshot", key))
+    }
+
+    fn load<'ast, T: Field>(&self, key: &str) -> Option<(ir::Prog<'ast, T>, Abi, Vec<Diagnostic>)> {
+        let file = File::open(self.path(key)).ok()?;
+        let mut deserializer = serde_cbor::Deserializer::from_reader(BufReader::new(file));
+
+        match Snapshot::<T>::deserialize(&mut deserializer) {
+            Ok(snapshot) if snapshot.version == SNAPSHOT_VERSION && snapshot.key == key => {
+                Some((snapshot.program, snapshot.abi, snapshot.diagnostics))
+            }
+            Ok(_) => {
+                log::debug!("Ignore outdated snapshot {}", key);
+                None
+            }
+            Err(e) => {
+                log::debug!("Ignore unreadable snapshot {}: {}", key, e);
+                None
+            }
+        }
+    }
+
+    fn store<'ast, T: Field>(
+        &self,
+        key: &str,
+        artifacts: CompilationArtifacts<'ast, T, Vec<ir::Statement<'ast, T>>>,
+    ) -> (
+        io::Result<()>,
+        CompilationArtifacts<'ast, T, Vec<ir::Statement<'ast, T>>>,
+    ) {
+        let snapshot = Snapshot {
+            version: SNAPSHOT_VERSION,
+            key: key.to_string(),
+            abi: artifacts.abi,
+            program: artifacts.prog,
+            diagnostics: artifacts.diagnostics,
+        };
+
+        let res = std::fs::create_dir_all(&self.dir)
+            .and_then(|_| File::create(self.path(key)))
+            .and_then(|file| {
+                serde_cbor::to_writer(BufWriter::new(file), &snapshot)
+                    .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+            });
+
+        (
+            res,
+            CompilationArtifacts {
+                prog: snapshot.program,
+                abi: snapshot.abi,
+                diagnostics: snapshot.diagnostics,
+                flattening: Warnings::default(),
+            },
+        )
+    }
+
+    fn module_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.module", key))
+    }
+
+    /// Load the snapshot of a checked module. It borrows from its source, which is kept in `arena`
+    fn load_module<'ast, T: Field>(
+        &self,
+        key: &str,
+        arena: &'ast Arena<String>,
+    ) -> Option<ModuleSnapshot<'ast, T>> {
+        let source = arena.alloc(std::fs::read_to_string(self.module_path(key)).ok()?);
+
+        match types::in_full(|| serde_json::from_str::<ModuleEntry<T>>(source)) {
+            Ok(entry) if entry.version == SNAPSHOT_VERSION && entry.key == key => {
+                Some(entry.snapshot)
+            }
+            Ok(_) => {
+                log::debug!("Ignore outdated module snapshot {}", key);
+                None
+            }
+            Err(e) => {
+                log::debug!("Ignore unreadable module snapshot {}: {}", key, e);
+                None
+            }
+        }
+    }
+
+    fn store_module<T: Field>(&self, key: &str, snapshot: ModuleSnapshot<T>) -> io::Result<()> {
+        let entry = ModuleEntry {
+            version: SNAPSHOT_VERSION,
+            key: key.to_string(),
+            snapshot,
+        };
+
+        std::fs::create_dir_all(&self.dir)
+            .and_then(|_| File::create(self.module_path(key)))
+            .and_then(|file| {
+                types::in_full(|| serde_json::to_writer(BufWriter::new(file), &entry))
+                    .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+            })
+    }
+}
+
+/// The time spent in each stage of the compilation. Stages which were skipped thanks to the cache
+/// are `None`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StageTimings {
+    pub parse: Duration,
+    pub semantics: Option<Duration>,
+    pub analysis: Option<Duration>,
+    pub lowering: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct CachedCompilation<'ast, T> {
+    pub artifacts: CompilationArtifacts<'ast, T, Vec<ir::Statement<'ast, T>>>,
+    pub cache_hit: bool,
+    /// The number of modules whose checked snapshot was reused on a cache miss
+    pub reused_modules: usize,
+    pub timings: StageTimings,
+}
+
+type Hash = [u8; 32];
+
+fn digest(source: &str) -> Hash {
+    Sha256::digest(source.as_bytes()).into()
+}
+
+/// A resolver which records a digest of every source it resolves
+struct RecordingResolver<'a, E> {
+    inner: &'a dyn Resolver<E>,
+    sources: RefCell<HashMap<OwnedModuleId, Hash>>,
+}
+
+impl<'a, E> Resolver<E> for RecordingResolver<'a, E> {
+    fn resolve(
+        &self,
+        current_location: PathBuf,
+        import_location: PathBuf,
+    ) -> Result<(String, PathBuf), E> {
+        let (source, location) = self.inner.resolve(current_location, import_location)?;
+        self.sources
+            .borrow_mut()
+            .insert(location.clone(), digest(&source));
+        Ok((source, location))
+    }
+}
+
+/// A resolver which reads each module once, so that a program can be compiled several times in a
+/// single run, for example for several curves, without reading its modules again. Failed
+/// resolutions are not remembered.
+///
+/// Only the reads are shared: parsing a module depends on the field it is compiled for (curve
+/// pragmas and the embeds it can import), and so does checking it (literals and intrinsics). Each
+/// compilation still parses, checks and compiles the sources it is given.
+pub struct SourceCache<'a, E> {
+    inner: &'a dyn Resolver<E>,
+    sources: RefCell<HashMap<(PathBuf, PathBuf), (String, PathBuf)>>,
+}
+
+impl<'a, E> SourceCache<'a, E> {
+    pub fn new(inner: &'a dyn Resolver<E>) -> Self {
+        SourceCache {
+            inner,
+            sources: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+impl<'a, E> Resolver<E> for SourceCache<'a, E> {
+    fn resolve(
+        &self,
+        current_location: PathBuf,
+        import_location: PathBuf,
+    ) -> Result<(String, PathBuf), E> {
+        let key = (current_location, import_location);
+
+        if let Some(res) = self.sources.borrow().get(&key) {
+            return Ok(res.clone());
+        }
+
+        let res = self.inner.resolve(key.0.clone(), key.1.clone())?;
+        self.sources.borrow_mut().insert(key, res.clone());
+        Ok(res)
+    }
+}
+
+fn fingerprint(
+    module_id: &OwnedModuleId,
+    program: &Program,
+    sources: &HashMap<OwnedModuleId, Hash>,
+    fingerprints: &mut HashMap<OwnedModuleId, Hash>,
+) -> Hash {
+    if let Some(f) = fingerprints.get(module_id) {
+        return *f;
+    }
+
+    let dependencies: BTreeSet<_> = program.modules[module_id]
+        .symbols
+        .iter()
+        .filter_map(|s| match &s.value.symbol {
+            Symbol::There(import) => Some(import.value.module_id.clone()),
+            _ => None,
+        })
+        .collect();
+
+    let mut hasher = Sha256::new();
+    hasher.update(sources[module_id]);
+    for dependency in dependencies {
+        hasher.update(fingerprint(&dependency, program, sources, fingerprints));
+    }
+    let res: Hash = hasher.finalize().into();
+
+    fingerprints.insert(module_id.clone(), res);
+    res
+}
+
+fn cache_key<T: Field>(config: &CompileConfig, program: &Program, main: Hash) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION"));
+    hasher.update(SNAPSHOT_VERSION.to_be_bytes());
+    hasher.update(T::name());
+    hasher.update(serde_json::to_string(config).unwrap());
+    hasher.update(program.main.to_string_lossy().as_bytes());
+    hasher.update(main);
+    format!("{:x}", hasher.finalize())
+}
+
+/// The key of the snapshot of a checked module. Checking `main` differs from checking other modules, so the entry
+/// module is part of the key
+fn module_key<T: Field>(
+    config: &CompileConfig,
+    program: &Program,
+    module_id: &OwnedModuleId,
+    fingerprint: Hash,
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION"));
+    hasher.update(SNAPSHOT_VERSION.to_be_bytes());
+    hasher.update(T::name());
+    hasher.update(serde_json::to_string(config).unwrap());
+    hasher.update(program.main.to_string_lossy().as_bytes());
+    hasher.update(module_id.to_string_lossy().as_bytes());
+    hasher.update(fingerprint);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Compile a program, reusing the snapshot in `cache` if none of the modules it depends on changed
+pub fn compile_with_cache<'ast, T: Field, E: Into<imports::Error>>(
+    source: String,
+    location: PathBuf,
+    resolver: Option<&dyn Resolver<E>>,
+    config: CompileConfig,
+    arena: &'ast Arena<String>,
+    cache: &BuildCache,
+) -> Result<CachedCompilation<'ast, T>, CompileErrors> {
+    let mut sources = HashMap::new();
+    sources.insert(location.clone(), digest(&source));
+
+    let source = arena.alloc(source);
+
+    let start = Instant::now();
+
+    let program = match resolver {
+        Some(inner) => {
+            let recording = RecordingResolver {
+                inner,
+                sources: RefCell::new(sources),
+            };
+            let program = parse_program::<T, E>(
+                source,
+                location,
+                Some(&recording as &dyn Resolver<E>),
+                arena,
+            )?;
+            sources = recording.sources.into_inner();
+            program
+        }
+        None => parse_program::<T, E>(source, location, None, arena)?,
+    };
+
+    let mut timings = StageTimings {
+        parse: start.elapsed(),
+        ..StageTimings::default()
+    };
+
+    let mut fingerprints = HashMap::new();
+    let main = fingerprint(&program.main, &program, &sources, &mut fingerprints);
+    let key = cache_key::<T>(&config, &program, main);
+
+    if let Some((prog, abi, diagnostics)) = cache.load(&key) {
+        log::debug!("Reuse snapshot {}", key);
+
+        return Ok(CachedCompilation {
+            artifacts: CompilationArtifacts {
+                prog,
+                abi,
+                diagnostics,
+                flattening: Warnings::default(),
+            },
+            cache_hit: true,
+            reused_modules: 0,
+            timings,
+        });
+    }
+
+    let module_keys: HashMap<_, _> = program
+        .modules
+        .keys()
+        .map(|module_id| {
+            let f = fingerprint(module_id, &program, &sources, &mut fingerprints);
+            (
+                module_id.clone(),
+                module_key::<T>(&config, &program, module_id, f),
+            )
+        })
+        .collect();
+
+    let snapshots: ModuleSnapshots<T> = module_keys
+        .iter()
+        .filter_map(|(module_id, key)| {
+            cache
+                .load_module(key, arena)
+                .map(|snapshot| (module_id.clone(), snapshot))
+        })
+        .collect();
+    let reused_modules = snapshots.len();
+
+    // the stages of `compile`, checking the semantics of the modules which changed only
+    let start = Instant::now();
+    let mut checked = ModuleSnapshots::new();
+    let typed = to_typed_from_program::<T, E, _>(program, resolver, &config, arena, true, |p| {
+        let (typed_ast, modules) = check_semantics_with_snapshots(p, snapshots)?;
+        checked = modules;
+        Ok(typed_ast)
+    })?;
+    timings.semantics = Some(start.elapsed());
+
+    // modules introduced after parsing, for example by the commitment to the public inputs, have no key
+    for (module_id, snapshot) in checked {
+        if let Some(key) = module_keys.get(&module_id) {
+            if let Err(e) = cache.store_module(key, snapshot) {
+                log::warn!(
+                    "Could not write module snapshot to {}: {}",
+                    cache.dir().display(),
+                    e
+                );
+            }
+        }
+    }
+
+    let start = Instant::now();
+    let zir = to_zir(typed, &config)?;
+    timings.analysis = Some(start.elapsed());
+
+    let start = Instant::now();
+    let artifacts = to_ir(zir, config)?.collect();
+    timings.lowering = Some(start.elapsed());
+
+    let (res, artifacts) = cache.store(&key, artifacts);
+
+    if let Err(e) = res {
+        log::warn!(
+            "Could not write snapshot to {}: {}",
+            cache.dir().display(),
+            e
+        );
+    }
+
+    Ok(CachedCompilation {
+        artifacts,
+        cache_hit: false,
+        reused_modules,
+        timings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::compile;
+    use std::cell::Cell;
+    use zokrates_field::{Bls12_381Field, Bn128Field};
+
+    struct MemoryResolver(RefCell<HashMap<PathBuf, String>>);
+
+    impl MemoryResolver {
+        fn new(modules: &[(&str, &str)]) -> Self {
+            MemoryResolver(RefCell::new(
+                modules
+                    .iter()
+                    .map(|(location, source)| (PathBuf::from(location), source.to_string()))
+                    .collect(),
+            ))
+        }
+
+        fn touch(&self, location: &str, source: &str) {
+            self.0
+                .borrow_mut()
+                .insert(PathBuf::from(location), source.to_string());
+        }
+    }
+
+    impl Resolver<io::Error> for MemoryResolver {
+        fn resolve(
+            &self,
+            _: PathBuf,
+            import_location: PathBuf,
+        ) -> Result<(String, PathBuf), io::Error> {
+            self.0
+                .borrow()
+                .get(&import_location)
+                .cloned()
+                .map(|source| (source, import_location))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "not found"))
+        }
+    }
+
+    const MAIN: &str = r#"
+        from "./foo" import foo;
+        def main(field a) -> field {
+            return foo(a);
+        }
+    "#;
+
+    fn resolver() -> MemoryResolver {
+        MemoryResolver::new(&[
+            (
+                "./foo",
+                r#"
+                    from "./bar" import bar;
+                    def foo(field a) -> field {
+                        return bar(a) + 1;
+                    }
+                "#,
+            ),
+            (
+                "./bar",
+                r#"
+                    def bar(field a) -> field {
+                        return a * a;
+                    }
+                "#,
+            ),
+        ])
+    }
+
+    fn cache(name: &str) -> BuildCache {
+        let dir = std::env::temp_dir().join(format!(
+            "zokrates_build_cache_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        BuildCache::new(dir)
+    }
+
+    fn entries(cache: &BuildCache, extension: &str) -> usize {
+        std::fs::read_dir(cache.dir())
+            .unwrap()
+            .filter(|e| e.as_ref().unwrap().path().extension().unwrap() == extension)
+            .count()
+    }
+
+    fn build<'ast>(
+        resolver: &MemoryResolver,
+        config: CompileConfig,
+        cache: &BuildCache,
+        arena: &'ast Arena<String>,
+    ) -> CachedCompilation<'ast, Bn128Field> {
+        build_main(MAIN, resolver, config, cache, arena)
+    }
+
+    fn build_main<'ast>(
+        main: &str,
+        resolver: &MemoryResolver,
+        config: CompileConfig,
+        cache: &BuildCache,
+        arena: &'ast Arena<String>,
+    ) -> CachedCompilation<'ast, Bn128Field> {
+        compile_with_cache(
+            main.to_string(),
+            "main".into(),
+            Some(resolver as &dyn Resolver<io::Error>),
+            config,
+            arena,
+            cache,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn cold_compile_populates_cache() {
+        let cache = cache("cold");
+        let arena = Arena::new();
+
+        let res = build(&resolver(), CompileConfig::default(), &cache, &arena);
+
+        assert!(!res.cache_hit);
+        assert_eq!(res.reused_modules, 0);
+        assert!(res.timings.semantics.is_some());
+        assert_eq!(entries(&cache, "snapshot"), 1);
+        // main, foo and bar
+        assert_eq!(entries(&cache, "module"), 3);
+    }
+
+    #[test]
+    fn unchanged_rebuild_skips_semantics() {
+        let cache = cache("unchanged");
+        let resolver = resolver();
+        let arena = Arena::new();
+
+        let cold = build(&resolver, CompileConfig::default(), &cache, &arena);
+        let warm = build(&resolver, CompileConfig::default(), &cache, &arena);
+
+        assert!(warm.cache_hit);
+        assert_eq!(warm.timings.semantics, None);
+        assert_eq!(warm.timings.analysis, None);
+        assert_eq!(warm.timings.lowering, None);
+        assert_eq!(warm.artifacts.prog, cold.artifacts.prog);
+        assert_eq!(warm.artifacts.abi, cold.artifacts.abi);
+    }
+
+    #[test]
+    fn touching_transitive_import_invalidates() {
+        let cache = cache("transitive");
+        let resolver = resolver();
+        let arena = Arena::new();
+
+        let before = build(&resolver, CompileConfig::default(), &cache, &arena);
+
+        resolver.touch(
+            "./bar",
+            r#"
+                def bar(field a) -> field {
+                    return a * a * a;
+                }
+            "#,
+        );
+
+        let after = build(&resolver, CompileConfig::default(), &cache, &arena);
+
+        assert!(!after.cache_hit);
+        // bar changed and main and foo import it, so nothing is reused
+        assert_eq!(after.reused_modules, 0);
+        assert!(after.timings.semantics.is_some());
+        assert_ne!(after.artifacts.prog, before.artifacts.prog);
+        assert_eq!(entries(&cache, "snapshot"), 2);
+        assert_eq!(entries(&cache, "module"), 6);
+    }
+
+    #[test]
+    fn changing_main_reuses_imported_modules() {
+        let warm_cache = cache("modules");
+        let cold_cache = cache("modules_cold");
+        let resolver = resolver();
+        let arena = Arena::new();
+
+        build(&resolver, CompileConfig::default(), &warm_cache, &arena);
+
+        let main = r#"
+            from "./foo" import foo;
+            def main(field a) -> field {
+                return foo(a) * 2;
+            }
+        "#;
+
+        let warm = build_main(
+            main,
+            &resolver,
+            CompileConfig::default(),
+            &warm_cache,
+            &arena,
+        );
+
+        assert!(!warm.cache_hit);
+        // foo and bar did not change
+        assert_eq!(warm.reused_modules, 2);
+
+        let cold = build_main(
+            main,
+            &resolver,
+            CompileConfig::default(),
+            &cold_cache,
+            &arena,
+        );
+
+        assert_eq!(cold.reused_modules, 0);
+        assert_eq!(warm.artifacts.prog, cold.artifacts.prog);
+        assert_eq!(warm.artifacts.abi, cold.artifacts.abi);
+    }
+
+    #[test]
+    fn changing_flags_invalidates() {
+        let cache = cache("flags");
+        let resolver = resolver();
+        let arena = Arena::new();
+
+        build(&resolver, CompileConfig::default(), &cache, &arena);
+        let res = build(
+            &resolver,
+            CompileConfig::default().isolate_branches(true),
+            &cache,
+            &arena,
+        );
+
+        assert!(!res.cache_hit);
+    }
+
+    struct CountingResolver<'a> {
+        inner: &'a MemoryResolver,
+        reads: Cell<usize>,
+    }
+
+    impl<'a> Resolver<io::Error> for CountingResolver<'a> {
+        fn resolve(
+            &self,
+            current_location: PathBuf,
+            import_location: PathBuf,
+        ) -> Result<(String, PathBuf), io::Error> {
+            self.reads.set(self.reads.get() + 1);
+            self.inner.resolve(current_location, import_location)
+        }
+    }
+
+    fn compile_for<'ast, T: Field>(
+        resolver: &dyn Resolver<io::Error>,
+        arena: &'ast Arena<String>,
+    ) -> CompilationArtifacts<'ast, T, Vec<ir::Statement<'ast, T>>> {
+        compile::<T, io::Error>(
+            MAIN.to_string(),
+            "main".into(),
+            Some(resolver),
+            CompileConfig::default(),
+            arena,
+        )
+        .unwrap()
+        .collect()
+    }
+
+    #[test]
+    fn source_cache_shared_across_curves() {
+        let modules = resolver();
+        let counting = CountingResolver {
+            inner: &modules,
+            reads: Cell::new(0),
+        };
+        let sources = SourceCache::new(&counting as &dyn Resolver<io::Error>);
+        let arena = Arena::new();
+
+        let shared_bn128 = compile_for::<Bn128Field>(&sources, &arena);
+        let shared_bls12_381 = compile_for::<Bls12_381Field>(&sources, &arena);
+
+        // each of the two imported modules is read once
+        assert_eq!(counting.reads.get(), 2);
+
+        let bn128 = compile_for::<Bn128Field>(&modules, &arena);
+        let bls12_381 = compile_for::<Bls12_381Field>(&modules, &arena);
+
+        assert_eq!(shared_bn128.prog, bn128.prog);
+        assert_eq!(shared_bn128.abi, bn128.abi);
+        assert_eq!(shared_bls12_381.prog, bls12_381.prog);
+        assert_eq!(shared_bls12_381.abi, bls12_381.abi);
+    }
+
+    fn serialize(prog: ir::Prog<Bn128Field>) -> Vec<u8> {
+        let mut buffer = io::Cursor::new(vec![]);
+        prog.serialize(&mut buffer).unwrap();
+        buffer.into_inner()
+    }
+
+    #[test]
+    fn cached_output_matches_uncached_output() {
+        let cache = cache("identical");
+        let resolver = resolver();
+        let arena = Arena::new();
+
+        let uncached = compile_for::<Bn128Field>(&resolver, &arena);
+
+        let cold = build(&resolver, CompileConfig::default(), &cache, &arena);
+        let warm = build(&resolver, CompileConfig::default(), &cache, &arena);
+        assert!(!cold.cache_hit);
+        assert!(warm.cache_hit);
+
+        for cached in [cold, warm] {
+            assert_eq!(cached.artifacts.abi, uncached.abi);
+            assert_eq!(cached.artifacts.diagnostics, uncached.diagnostics);
+            assert_eq!(
+                serialize(cached.artifacts.prog),
+                serialize(uncached.prog.clone())
+            );
+        }
+    }
+}
@@ -0,0 +1,402 @@
+//! Module replacing the public inputs of a program by a commitment to their values.
+//!
+//! The `main` function of the entry module is renamed, and a new `main` function is generated. It takes the same
+//! arguments, with the public ones made private, followed by a public field element `public_commitment`. It checks
+//! that the commitment is the SHA-256 hash of the hashed inputs before calling the original function.
+//!
+//! Each hashed field element is encoded on 32 big-endian bytes, and the commitment is made of the last 31 bytes of the
+//! hash, so that it fits in any supported field. See `Abi::commit` for the computation of the commitment.
+
+use crate::compile::{parse_module, CompileErrorInner, CompileErrors};
+use crate::imports;
+use std::fmt;
+use typed_arena::Arena;
+use zokrates_ast::typed::abi::{Abi, AbiCommitment};
+use zokrates_ast::untyped::types::UnresolvedType;
+use zokrates_ast::untyped::{
+    NodeValue, Parameter, ParameterNode, Position, Program, Symbol, SymbolDefinition, Variable,
+};
+use zokrates_common::Resolver;
+use zokrates_field::Field;
+
+/// The name of the public input holding the commitment
+pub const COMMITMENT_NAME: &str = "public_commitment";
+
+// the name of the original `main` function, and the prefix of all generated identifiers
+const PREFIX: &str = "public_commitment";
+
+#[derive(Debug)]
+pub struct Error {
+    pos: Option<(Position, Position)>,
+    message: String,
+}
+
+impl Error {
+    fn new<S: Into<String>>(message: S) -> Self {
+        Error {
+            pos: None,
+            message: message.into(),
+        }
+    }
+
+    fn with_pos(self, pos: (Position, Position)) -> Self {
+        Error {
+            pos: Some(pos),
+            ..self
+        }
+    }
+
+    pub fn pos(&self) -> &Option<(Position, Position)> {
+        &self.pos
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let location = self
+            .pos
+            .map(|p| format!("{}", p.0))
+            .unwrap_or_else(|| "".to_string());
+        write!(f, "{}\n\t{}", location, self.message)
+    }
+}
+
+/// Replace the public inputs of the `main` function of `program` by a commitment to their values.
+///
+/// Returns the commitment to record in the ABI, or `None` if `main` has no public input.
+pub fn commit_public_inputs<'ast, T: Field, E: Into<imports::Error>>(
+    program: &mut Program<'ast>,
+    resolver: Option<&dyn Resolver<E>>,
+    arena: &'ast Arena<String>,
+) -> Result<Option<AbiCommitment>, CompileErrors> {
+    let location = program.main.clone();
+    let in_main = |e: Error| CompileErrors::from(CompileErrorInner::from(e).in_file(&location));
+
+    if T::get_required_bits() > 256 {
+        return Err(in_main(Error::new(format!(
+            "Public inputs cannot be hashed on curve {}, as its field elements do not fit in 256 bits",
+            T::name()
+        ))));
+    }
+
+    let module = program.modules.get_mut(&location).unwrap();
+
+    // a missing `main` is reported by the semantic checker
+    let declaration = match module.symbols.iter_mut().find(|s| s.value.id == "main") {
+        Some(declaration) => declaration,
+        None => return Ok(None),
+    };
+
+    let pos = declaration.pos();
+
+    let function = match &mut declaration.value.symbol {
+        Symbol::Here(SymbolDefinition::Function(f)) => f,
+        _ => {
+            return Err(in_main(
+                Error::new(
+                    "Public inputs can only be hashed if `main` is defined in the entry module",
+                )
+                .with_pos(pos),
+            ))
+        }
+    };
+
+    let arguments = function.value.arguments.clone();
+
+    // the dimensions of each hashed input, from the outermost one
+    let mut hashed = vec![];
+    for argument in arguments
+        .iter()
+        .filter(|a| a.value.is_private != Some(true))
+    {
+        let mut dimensions = vec![];
+        let mut ty = argument.value.id.value.get_type();
+        while let UnresolvedType::Array(inner, size) = ty {
+            dimensions.push(format!("({})", size));
+            ty = &inner.value;
+        }
+
+        if *ty != UnresolvedType::FieldElement {
+            return Err(in_main(
+                Error::new(format!(
+                    "Public input `{}` cannot be hashed: only field elements and arrays of field elements are supported",
+                    argument.value.id.value.id
+                ))
+                .with_pos(argument.pos()),
+            ));
+        }
+
+        hashed.push((argument.value.id.value.id, dimensions));
+    }
+
+    if hashed.is_empty() {
+        return Ok(None);
+    }
+
+    if arguments
+        .iter()
+        .any(|a| a.value.id.value.id == COMMITMENT_NAME)
+    {
+        return Err(in_main(Error::new(format!(
+            "Public inputs cannot be hashed: `{}` is reserved for the commitment",
+            COMMITMENT_NAME
+        ))));
+    }
+
+    // the original function is kept under another name, without visibility modifiers
+    let signature = function.value.signature.clone();
+    for argument in function.value.arguments.iter_mut() {
+        argument.value.is_private = None;
+    }
+    function.value.signature.private_outputs = vec![];
+    declaration.value.id = arena.alloc(format!("{}_main", PREFIX)).as_str();
+
+    let source = generate(
+        &hashed,
+        &arguments
+            .iter()
+            .map(|a| a.value.id.value.id)
+            .collect::<Vec<_>>(),
+    );
+
+    let generated_location = location.with_extension("commitment.zok");
+    let generated = parse_module::<T, E>(
+        arena.alloc(source),
+        generated_location,
+        resolver,
+        &mut program.modules,
+        arena,
+    )?;
+
+    let mut symbols = generated.symbols;
+    for symbol in symbols.iter_mut() {
+        if let Symbol::Here(SymbolDefinition::Function(f)) = &mut symbol.value.symbol {
+            // the generated `main` takes the arguments of the original one, with the hashed inputs made private
+            f.value.arguments = arguments
+                .iter()
+                .cloned()
+                .map(|mut a| {
+                    a.value.is_private = Some(true);
+                    a
+                })
+                .chain(std::iter::once(commitment_parameter()))
+                .collect();
+            f.value.signature = signature.clone().inputs(
+                signature
+                    .inputs
+                    .iter()
+                    .cloned()
+                    .chain(std::iter::once(UnresolvedType::FieldElement.mock()))
+                    .collect(),
+            );
+        }
+    }
+
+    program
+        .modules
+        .get_mut(&location)
+        .unwrap()
+        .symbols
+        .extend(symbols);
+
+    Ok(Some(AbiCommitment {
+        name: COMMITMENT_NAME.to_string(),
+        inputs: hashed.into_iter().map(|(id, _)| id.to_string()).collect(),
+    }))
+}
+
+/// Record `commitment` in the ABI of the transformed program, whose last input is the commitment itself.
+pub(crate) fn record(mut abi: Abi, commitment: Option<AbiCommitment>) -> Abi {
+    if let Some(commitment) = commitment {
+        abi.inputs.pop();
+        abi.commitment = Some(commitment);
+    }
+    abi
+}
+
+fn commitment_parameter<'ast>() -> ParameterNode<'ast> {
+    Parameter::new(
+        Variable::immutable(COMMITMENT_NAME, UnresolvedType::FieldElement.mock()).mock(),
+        Some(false),
+    )
+    .mock()
+}
+
+// generate the module defining the new `main`. Its arguments are set afterwards, as the types of the original ones
+// are not rendered back to source
+fn generate(hashed: &[(&str, Vec<String>)], arguments: &[&str]) -> String {
+    let count = hashed
+        .iter()
+        .map(|(_, dimensions)| match dimensions.is_empty() {
+            true => "1".to_string(),
+            false => dimensions.join(" * "),
+        })
+        .collect::<Vec<_>>()
+        .join(" + ");
+
+    let mut body = vec![
+        format!(
+            "bool[256 * ({})] mut {p}_input = [false; 256 * ({})];",
+            count,
+            count,
+            p = PREFIX
+        ),
+        format!("u32 mut {}_offset = 0;", PREFIX),
+    ];
+
+    for (id, dimensions) in hashed {
+        let indices: String = (0..dimensions.len())
+            .map(|i| format!("[{}_i{}]", PREFIX, i))
+            .collect();
+
+        let mut block = vec![
+            format!(
+                "bool[256] {p}_bits = {p}_unpack({}{});",
+                id,
+                indices,
+                p = PREFIX
+            ),
+            format!(
+                "for u32 {p}_j in 0..256 {{ {p}_input[{p}_offset + {p}_j] = {p}_bits[{p}_j]; }}",
+                p = PREFIX
+            ),
+            format!("{p}_offset = {p}_offset + 256;", p = PREFIX),
+        ];
+
+        // iterate over the elements of arrays from the innermost dimension, so that they are hashed in order
+        for (i, size) in dimensions.iter().enumerate().rev() {
+            block = std::iter::once(format!("for u32 {}_i{} in 0..{} {{", PREFIX, i, size))
+                .chain(block.into_iter().map(|line| format!("    {}", line)))
+                .chain(std::iter::once("}".to_string()))
+                .collect();
+        }
+
+        body.extend(block);
+    }
+
+    body.push(format!(
+        "bool[256] {p}_digest = {p}_digest_bits({p}_sha256({p}_input));",
+        p = PREFIX
+    ));
+    body.push(format!(
+        "assert({p}_pack({p}_digest[8..256]) == {});",
+        COMMITMENT_NAME,
+        p = PREFIX
+    ));
+    body.push(format!("return {}_main({});", PREFIX, arguments.join(", ")));
+
+    format!(
+        r#"from "hashes/sha256/sha256Padded" import sha256Padded as {p}_sha256;
+import "utils/pack/bool/unpack256" as {p}_unpack;
+import "utils/pack/bool/pack" as {p}_pack;
+import "utils/casts/u32_8_to_bool_256" as {p}_digest_bits;
+
+def main() {{
+{}
+}}
+"#,
+        body.into_iter()
+            .map(|line| format!("    {}", line))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        p = PREFIX
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::{compile, CompilationArtifacts};
+    use std::io;
+    use zokrates_common::CompileConfig;
+    use zokrates_field::Bn128Field;
+    use zokrates_fs_resolver::FileSystemResolver;
+    use zokrates_interpreter::Interpreter;
+
+    #[test]
+    fn generate_nested_arrays() {
+        let source = generate(
+            &[("a", vec!["(2)".into(), "(3)".into()]), ("b", vec![])],
+            &["a", "b", "c"],
+        );
+
+        assert!(source.contains("bool[256 * ((2) * (3) + 1)] mut public_commitment_input"));
+        assert!(source.contains("for u32 public_commitment_i1 in 0..(3) {"));
+        assert!(source
+            .contains("public_commitment_unpack(a[public_commitment_i0][public_commitment_i1]);"));
+        assert!(source.contains("return public_commitment_main(a, b, c);"));
+    }
+
+    #[test]
+    fn hash_public_inputs() {
+        let stdlib = std::fs::canonicalize("../zokrates_stdlib/stdlib").unwrap();
+        let resolver = FileSystemResolver::with_stdlib_root(stdlib.to_str().unwrap());
+
+        let arena = Arena::new();
+        let artifacts: CompilationArtifacts<Bn128Field, _> = compile(
+            "def main(field[2] a, private field b, field c) -> field { return a[0] * b + a[1] * c; }"
+                .into(),
+            "main.zok".into(),
+            Some(&resolver),
+            CompileConfig::default().hash_public_inputs(true),
+            &arena,
+        )
+        .unwrap();
+
+        let abi = artifacts.abi().clone();
+        let program = artifacts.prog().collect();
+
+        // the user still gives the original inputs, and the commitment is the only public input
+        assert_eq!(
+            abi.inputs
+                .iter()
+                .map(|i| (i.name.as_str(), i.public))
+                .collect::<Vec<_>>(),
+            vec![("a", false), ("b", false), ("c", false)]
+        );
+        assert_eq!(
+            abi.commitment,
+            Some(AbiCommitment {
+                name: COMMITMENT_NAME.into(),
+                inputs: vec!["a".into(), "c".into()]
+            })
+        );
+        assert_eq!(program.public_count(), 2);
+
+        let arguments: Vec<Bn128Field> = vec![2.into(), 3.into(), 4.into(), 5.into()];
+        let arguments = abi.commit(arguments);
+        assert_eq!(arguments.len(), 5);
+
+        let witness = Interpreter::default()
+            .execute(program.clone(), &arguments)
+            .unwrap();
+        assert_eq!(witness.return_values(), vec![Bn128Field::from(23)]);
+
+        // a wrong commitment is rejected
+        let mut wrong = arguments;
+        wrong[4] = wrong[4].clone() + Bn128Field::from(1);
+        assert!(Interpreter::default().execute(program, &wrong).is_err());
+    }
+
+    #[test]
+    fn unsupported_input() {
+        let arena = Arena::new();
+        let res: Result<CompilationArtifacts<Bn128Field, _>, CompileErrors> = compile(
+            "def main(u32 a) { return; }".into(),
+            "main.zok".into(),
+            None::<&dyn Resolver<io::Error>>,
+            CompileConfig::default().hash_public_inputs(true),
+            &arena,
+        )
+        .map(|res| res.collect());
+
+        assert!(res.unwrap_err().0[0]
+            .value()
+            .to_string()
+            .contains("Public input `a` cannot be hashed"));
+    }
+}
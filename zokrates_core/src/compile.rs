@@ -3,30 +3,50 @@
 //! @file compile.rs
 //! @author Thibaut Schaeffer <thibaut@schaeff.fr>
 //! @date 2018
+use crate::commitment;
+use crate::critical;
 use crate::imports::{self, Importer};
 use crate::macros;
-use crate::optimizer::optimize;
+use crate::manifest::{Manifest, RecordingResolver};
+use crate::optimizer::{optimize, StatsRecorder};
+use crate::private_outputs;
+use crate::provenance::{DebugTable, ProvenanceTracker};
 use crate::semantics::{self, Checker};
 use macros::process_macros;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
 use std::fmt;
 use std::io;
 use std::path::{Path, PathBuf};
 use typed_arena::Arena;
-use zokrates_analysis::{self, analyse};
+use zokrates_analysis::{self, analyse, CallGraph};
+use zokrates_ast::common::Diagnostic;
+use zokrates_ast::flat::{FlatProgIterator, FlatStatement};
 use zokrates_ast::ir::{self, from_flat::from_flat};
-use zokrates_ast::typed::abi::Abi;
-use zokrates_ast::untyped::{Module, OwnedModuleId, Program};
+use zokrates_ast::typed::abi::{Abi, AbiCommitment};
+use zokrates_ast::typed::TypedProgram;
+use zokrates_ast::untyped::{Module, OwnedModuleId, Position, Program};
 use zokrates_ast::zir::ZirProgram;
-use zokrates_codegen::from_function_and_config;
+use zokrates_codegen::{
+    flatten_observed, from_function_with_budget, Annotations, BudgetExceeded, ConstraintSink,
+    Estimate, Observers as FlattenObservers, Report, Warnings,
+};
 use zokrates_common::{CompileConfig, Resolver};
 use zokrates_field::Field;
 use zokrates_pest_ast as pest;
 
+pub use crate::optimizer::{MinimizationReport, PassStats};
+
+/// The maximum number of syntax errors reported for a single module
+pub const MAX_PARSER_ERRORS: usize = 10;
+
 #[derive(Debug)]
 pub struct CompilationArtifacts<'ast, T, I: IntoIterator<Item = ir::Statement<'ast, T>>> {
-    prog: ir::ProgIterator<'ast, T, I>,
-    abi: Abi,
+    pub(crate) prog: ir::ProgIterator<'ast, T, I>,
+    pub(crate) abi: Abi,
+    pub(crate) diagnostics: Vec<Diagnostic>,
+    /// The warnings of the flattening, known once the program is consumed
+    pub(crate) flattening: Warnings,
 }
 
 impl<'ast, T, I: IntoIterator<Item = ir::Statement<'ast, T>>> CompilationArtifacts<'ast, T, I> {
@@ -38,18 +58,71 @@ impl<'ast, T, I: IntoIterator<Item = ir::Statement<'ast, T>>> CompilationArtifac
         &self.abi
     }
 
+    /// The warnings about the program, which compiled nonetheless
+    ///
+    /// The warnings about the flattened statements are only included once the program is collected, as it is flattened
+    /// lazily.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
     pub fn into_inner(self) -> (ir::ProgIterator<'ast, T, I>, Abi) {
         (self.prog, self.abi)
     }
 
     pub fn collect(self) -> CompilationArtifacts<'ast, T, Vec<ir::Statement<'ast, T>>> {
+        let prog = self.prog.collect();
+
+        let mut diagnostics = self.diagnostics;
+        diagnostics.extend(self.flattening.take());
+
         CompilationArtifacts {
-            prog: self.prog.collect(),
+            prog,
             abi: self.abi,
+            diagnostics,
+            flattening: Warnings::default(),
         }
     }
 }
 
+/// A program after the semantic checks, the first stage of the compilation
+///
+/// The typed program borrows from the sources in the arena and cannot be serialized, so this stage can only be
+/// resumed in the same process.
+#[derive(Debug)]
+pub struct TypedArtifacts<'ast, T> {
+    pub(crate) program: TypedProgram<'ast, T>,
+    /// The indices of the outputs declared private
+    pub(crate) private_outputs: Vec<usize>,
+    /// The commitment to the public inputs, if they are hashed
+    pub(crate) commitment: Option<AbiCommitment>,
+    /// Whether the bodies of the inlined functions are marked for the observers of the flattening
+    pub(crate) call_logs: bool,
+    /// The modules written by the user, as opposed to libraries
+    pub(crate) user_modules: BTreeSet<OwnedModuleId>,
+}
+
+impl<'ast, T> TypedArtifacts<'ast, T> {
+    pub fn program(&self) -> &TypedProgram<'ast, T> {
+        &self.program
+    }
+}
+
+/// A program after the static analysis and the zir passes, the second stage of the compilation
+///
+/// It can be serialized, to be stored and lowered later to ir with `to_ir`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ZirArtifacts<'ast, T> {
+    #[serde(borrow)]
+    pub program: ZirProgram<'ast, T>,
+    pub abi: Abi,
+    /// The entry file of the program, where the errors found while lowering are reported by default
+    pub main_module: PathBuf,
+    /// The warnings found by the static analysis
+    #[serde(default)]
+    pub diagnostics: Vec<Diagnostic>,
+}
+
 #[derive(Debug)]
 pub struct CompileErrors(pub Vec<CompileError>);
 
@@ -67,6 +140,9 @@ pub enum CompileErrorInner {
     SemanticError(semantics::ErrorInner),
     ReadError(io::Error),
     AnalysisError(zokrates_analysis::Error),
+    BudgetError(BudgetExceeded),
+    CommitmentError(commitment::Error),
+    CriticalAssertionError(critical::Error),
 }
 
 impl CompileErrorInner {
@@ -76,6 +152,60 @@ impl CompileErrorInner {
             file: context.to_path_buf(),
         }
     }
+
+    /// A stable identifier for the stage which produced this error
+    pub fn code(&self) -> &'static str {
+        match self {
+            CompileErrorInner::ParserError(_) => "parser",
+            CompileErrorInner::ImportError(_) => "import",
+            CompileErrorInner::MacroError(_) => "macro",
+            CompileErrorInner::SemanticError(_) => "semantic",
+            CompileErrorInner::ReadError(_) => "io",
+            CompileErrorInner::AnalysisError(_) => "analysis",
+            CompileErrorInner::BudgetError(_) => "budget",
+            CompileErrorInner::CommitmentError(_) => "commitment",
+            CompileErrorInner::CriticalAssertionError(_) => "critical",
+        }
+    }
+
+    /// The error message, without location information
+    pub fn message(&self) -> String {
+        match self {
+            CompileErrorInner::SemanticError(e) => e.message().to_string(),
+            CompileErrorInner::ImportError(e) => e.message().to_string(),
+            CompileErrorInner::CommitmentError(e) => e.message().to_string(),
+            e => e.to_string().trim_start().to_string(),
+        }
+    }
+
+    /// The span of the source code this error refers to, if known
+    pub fn pos(&self) -> Option<(Position, Position)> {
+        match self {
+            CompileErrorInner::ParserError(e) => {
+                let (start, end) = e.line_col();
+                Some((
+                    Position {
+                        line: start.0,
+                        col: start.1,
+                    },
+                    Position {
+                        line: end.0,
+                        col: end.1,
+                    },
+                ))
+            }
+            CompileErrorInner::SemanticError(e) => *e.pos(),
+            CompileErrorInner::ImportError(e) => *e.pos(),
+            CompileErrorInner::CommitmentError(e) => *e.pos(),
+            CompileErrorInner::BudgetError(e) => {
+                e.span.as_ref().map(|span| (span.position, span.position))
+            }
+            CompileErrorInner::CriticalAssertionError(e) => {
+                e.lost.first().map(|span| (span.position, span.position))
+            }
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -147,6 +277,24 @@ impl From<zokrates_analysis::Error> for CompileErrorInner {
     }
 }
 
+impl From<BudgetExceeded> for CompileErrorInner {
+    fn from(error: BudgetExceeded) -> Self {
+        CompileErrorInner::BudgetError(error)
+    }
+}
+
+impl From<critical::Error> for CompileErrorInner {
+    fn from(error: critical::Error) -> Self {
+        CompileErrorInner::CriticalAssertionError(error)
+    }
+}
+
+impl From<commitment::Error> for CompileErrorInner {
+    fn from(error: commitment::Error) -> Self {
+        CompileErrorInner::CommitmentError(error)
+    }
+}
+
 impl fmt::Display for CompileErrorInner {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -168,6 +316,9 @@ impl fmt::Display for CompileErrorInner {
                 write!(f, "{}\n\t{}", location, e.message())
             }
             CompileErrorInner::AnalysisError(ref e) => write!(f, "\n\t{}", e),
+            CompileErrorInner::BudgetError(ref e) => write!(f, "\n\t{}", e),
+            CompileErrorInner::CommitmentError(ref e) => write!(f, "{}", e),
+            CompileErrorInner::CriticalAssertionError(ref e) => write!(f, "\n\t{}", e),
         }
     }
 }
@@ -184,30 +335,160 @@ pub fn compile<'ast, T: Field, E: Into<imports::Error>>(
     CompilationArtifacts<'ast, T, impl IntoIterator<Item = ir::Statement<'ast, T>> + 'ast>,
     CompileErrors,
 > {
-    let (typed_ast, abi): (zokrates_ast::zir::ZirProgram<'_, T>, _) =
-        check_with_arena(source, location, resolver, &config, arena)?;
-
-    // flatten input program
-    log::debug!("Flatten");
-    let program_flattened = from_function_and_config(typed_ast.main, config);
+    let typed = to_typed::<T, E>(source, location, resolver, &config, arena)?;
+    let zir = to_zir(typed, &config)?;
+    to_ir(zir, config)
+}
 
-    // convert to ir
-    log::debug!("Convert to IR");
-    let ir_prog = from_flat(program_flattened);
+/// Parses a program and checks its semantics, stopping before the static analysis
+///
+/// Each statement is located in the source, so that what it is compiled to can be attributed to it.
+pub fn to_typed<'ast, T: Field, E: Into<imports::Error>>(
+    source: String,
+    location: FilePath,
+    resolver: Option<&dyn Resolver<E>>,
+    config: &CompileConfig,
+    arena: &'ast Arena<String>,
+) -> Result<TypedArtifacts<'ast, T>, CompileErrors> {
+    let logs = Logs::located();
+    to_typed_with_logs(source, location, resolver, config, arena, logs)
+}
 
-    // optimize
-    log::debug!("Optimise IR");
-    let optimized_ir_prog = optimize(ir_prog);
+/// Runs the static analysis and the zir passes on a checked program, stopping before the flattening
+pub fn to_zir<'ast, T: Field>(
+    typed: TypedArtifacts<'ast, T>,
+    config: &CompileConfig,
+) -> Result<ZirArtifacts<'ast, T>, CompileErrors> {
+    let main_module = typed.program.main.clone();
+
+    let (program, abi, diagnostics) =
+        analyse_program(typed.program, config, typed.call_logs, &typed.user_modules)?;
+    let abi = private_outputs::record(abi, typed.private_outputs);
+
+    Ok(ZirArtifacts {
+        program,
+        abi: commitment::record(abi, typed.commitment),
+        main_module,
+        diagnostics,
+    })
+}
 
-    // clean (remove blocks)
-    let clean_ir_prog = optimized_ir_prog.clean();
+/// Lowers a program in zir to ir, the last stage of the compilation
+///
+/// The configuration is expected to be the one `to_zir` was called with.
+pub fn to_ir<'ast, T: Field>(
+    zir: ZirArtifacts<'ast, T>,
+    config: CompileConfig,
+) -> Result<
+    CompilationArtifacts<'ast, T, impl IntoIterator<Item = ir::Statement<'ast, T>> + 'ast>,
+    CompileErrors,
+> {
+    let (prog, flattening) = lower(zir.program, &zir.abi, &zir.main_module, config)?;
 
     Ok(CompilationArtifacts {
-        prog: clean_ir_prog,
-        abi,
+        prog,
+        abi: zir.abi,
+        diagnostics: zir.diagnostics,
+        flattening,
     })
 }
 
+/// The optional observers of a compilation, see `compile_observed`
+pub struct Observers<'a, T> {
+    /// Build the debug table explaining each constraint
+    pub debug_table: bool,
+    /// Record the manifest of the build
+    pub manifest: bool,
+    /// Report the effect of the optimizer on the constraint system
+    pub minimization_report: bool,
+    /// Report the directives and embeds used by each source function
+    pub report: bool,
+    /// Hand each constraint to a sink as soon as the flattener emits it, before optimization
+    pub sink: Option<&'a mut dyn ConstraintSink<T>>,
+}
+
+impl<'a, T> Default for Observers<'a, T> {
+    fn default() -> Self {
+        Observers {
+            debug_table: false,
+            manifest: false,
+            minimization_report: false,
+            report: false,
+            sink: None,
+        }
+    }
+}
+
+/// What the observers of a compilation recorded, each observation being set if it was requested
+#[derive(Debug, Default)]
+pub struct Observations {
+    pub debug_table: Option<DebugTable>,
+    pub manifest: Option<Manifest>,
+    pub minimization_report: Option<MinimizationReport>,
+    pub report: Option<Report>,
+}
+
+/// Compiles a program like `compile`, along with what `observers` recorded
+///
+/// The program goes through the same pipeline as with `compile`, so that its constraints are the same and the
+/// constraint budget of the configuration is enforced. It is collected, so that the observations are complete.
+pub fn compile_observed<'ast, T: Field, E: Into<imports::Error>>(
+    source: String,
+    location: FilePath,
+    resolver: Option<&dyn Resolver<E>>,
+    config: CompileConfig,
+    arena: &'ast Arena<String>,
+    observers: Observers<'_, T>,
+) -> Result<
+    (
+        CompilationArtifacts<'ast, T, Vec<ir::Statement<'ast, T>>>,
+        Observations,
+    ),
+    CompileErrors,
+> {
+    let recording = observers
+        .manifest
+        .then(|| RecordingResolver::new(resolver, &location, &source));
+    let resolver = match &recording {
+        Some(recording) => recording.resolver(),
+        None => resolver,
+    };
+
+    // the statements are located, so that the observers of the flattening attribute them to the function they come from
+    let typed = to_typed::<T, E>(source, location.clone(), resolver, &config, arena)?;
+    let zir = to_zir(typed, &config)?;
+
+    let recorder = observers.minimization_report.then(StatsRecorder::default);
+
+    let (prog, mut observations, flattening) = lower_observed(
+        zir.program,
+        &zir.abi,
+        &zir.main_module,
+        config.clone(),
+        observers,
+        recorder.as_ref(),
+    )?;
+    let prog = prog.collect();
+
+    // the statistics are complete once the optimized program is consumed
+    observations.minimization_report = recorder.map(|recorder| recorder.report());
+    observations.manifest = recording
+        .map(|recording| Manifest::new::<T>(location, config, recording.into_sources(), &prog));
+
+    let mut diagnostics = zir.diagnostics;
+    diagnostics.extend(flattening.take());
+
+    Ok((
+        CompilationArtifacts {
+            prog,
+            abi: zir.abi,
+            diagnostics,
+            flattening: Warnings::default(),
+        },
+        observations,
+    ))
+}
+
 pub fn check<T: Field, E: Into<imports::Error>>(
     source: String,
     location: FilePath,
@@ -219,6 +500,65 @@ pub fn check<T: Field, E: Into<imports::Error>>(
     check_with_arena::<T, _>(source, location, resolver, config, &arena).map(|_| ())
 }
 
+/// Estimates the number of constraints and variables of a program, without flattening it
+///
+/// The program is checked and analysed as when compiling it, and the bounds are derived from its operations
+pub fn estimate<T: Field, E: Into<imports::Error>>(
+    source: String,
+    location: FilePath,
+    resolver: Option<&dyn Resolver<E>>,
+    config: &CompileConfig,
+) -> Result<Estimate, CompileErrors> {
+    let arena = Arena::new();
+
+    let (program, _): (ZirProgram<'_, T>, _) =
+        check_with_arena(source, location, resolver, config, &arena)?;
+
+    log::debug!("Estimate");
+
+    Ok(zokrates_codegen::estimate(&program.main, config.clone()))
+}
+
+/// Estimates the cost of each statement and function call of a program, see `zokrates_codegen::annotate`
+///
+/// The program is checked and analysed as when estimating its size, its statements being marked with their location
+pub fn analyze_costs<T: Field, E: Into<imports::Error>>(
+    source: String,
+    location: FilePath,
+    resolver: Option<&dyn Resolver<E>>,
+    config: &CompileConfig,
+) -> Result<Annotations, CompileErrors> {
+    let arena = Arena::new();
+
+    let (program, _): (ZirProgram<'_, T>, _) =
+        check_with_arena_and_logs(source, location, resolver, config, &arena, Logs::located())?;
+
+    log::debug!("Analyze costs");
+
+    Ok(zokrates_codegen::annotate(&program.main, config.clone()))
+}
+
+/// Builds the call graph of a program and renders it in the Graphviz DOT format
+///
+/// The program is only checked, so that the functions which are not reachable from `main` are still part of the graph
+pub fn call_graph<T: Field, E: Into<imports::Error>>(
+    source: String,
+    location: FilePath,
+    resolver: Option<&dyn Resolver<E>>,
+) -> Result<String, CompileErrors> {
+    let arena = Arena::new();
+    let source = arena.alloc(source);
+
+    let compiled = parse_program::<T, E>(source, location, resolver, &arena)?;
+    let typed_ast = check_semantics::<T>(compiled)?;
+
+    log::debug!("Build call graph");
+
+    let (_, call_graph) = CallGraph::build(typed_ast);
+
+    Ok(call_graph.to_dot())
+}
+
 fn check_with_arena<'ast, T: Field, E: Into<imports::Error>>(
     source: String,
     location: FilePath,
@@ -226,29 +566,334 @@ fn check_with_arena<'ast, T: Field, E: Into<imports::Error>>(
     config: &CompileConfig,
     arena: &'ast Arena<String>,
 ) -> Result<(ZirProgram<'ast, T>, Abi), CompileErrors> {
+    check_with_arena_and_logs(source, location, resolver, config, arena, Logs::default())
+}
+
+// the logs kept in a program for the analyses and the observers of its compilation
+#[derive(Debug, Clone, Copy, Default)]
+struct Logs {
+    /// Mark the statements with their location in the source
+    spans: bool,
+    /// Mark the bodies of the inlined functions
+    calls: bool,
+}
+
+impl Logs {
+    // the logs locating each statement within the function it was inlined from, so that the warnings of the flattening
+    // and the statement exceeding the constraint budget can be reported
+    fn located() -> Self {
+        Logs {
+            spans: true,
+            calls: true,
+        }
+    }
+}
+
+// like `check_with_arena`, keeping the `logs`
+fn check_with_arena_and_logs<'ast, T: Field, E: Into<imports::Error>>(
+    source: String,
+    location: FilePath,
+    resolver: Option<&dyn Resolver<E>>,
+    config: &CompileConfig,
+    arena: &'ast Arena<String>,
+    logs: Logs,
+) -> Result<(ZirProgram<'ast, T>, Abi), CompileErrors> {
+    let typed = to_typed_with_logs(source, location, resolver, config, arena, logs)?;
+    let zir = to_zir(typed, config)?;
+
+    Ok((zir.program, zir.abi))
+}
+
+// like `to_typed`, keeping the `logs`
+fn to_typed_with_logs<'ast, T: Field, E: Into<imports::Error>>(
+    source: String,
+    location: FilePath,
+    resolver: Option<&dyn Resolver<E>>,
+    config: &CompileConfig,
+    arena: &'ast Arena<String>,
+    logs: Logs,
+) -> Result<TypedArtifacts<'ast, T>, CompileErrors> {
     let source = arena.alloc(source);
 
     log::debug!("Parse program with entry file {}", location.display());
 
-    let compiled = parse_program::<T, E>(source, location, resolver, arena)?;
+    let program = parse_program::<T, E>(source, location, resolver, arena)?;
+
+    to_typed_from_program::<T, E, _>(program, resolver, config, arena, logs.calls, |program| {
+        match logs.spans {
+            true => check_semantics_with_span_logs(program),
+            false => check_semantics(program),
+        }
+    })
+}
+
+// like `to_typed`, on a program which was already parsed, its semantics being checked with `check`
+pub(crate) fn to_typed_from_program<'ast, T, E, C>(
+    mut compiled: Program<'ast>,
+    resolver: Option<&dyn Resolver<E>>,
+    config: &CompileConfig,
+    arena: &'ast Arena<String>,
+    call_logs: bool,
+    check: C,
+) -> Result<TypedArtifacts<'ast, T>, CompileErrors>
+where
+    T: Field,
+    E: Into<imports::Error>,
+    C: FnOnce(Program<'ast>) -> Result<TypedProgram<'ast, T>, CompileErrors>,
+{
+    let private_outputs = private_outputs::private_outputs(&compiled);
+
+    // the modules generated for the commitment are not written by the user
+    let user_modules = compiled.user_modules();
+
+    let commitment = match config.hash_public_inputs {
+        true => commitment::commit_public_inputs::<T, E>(&mut compiled, resolver, arena)?,
+        false => None,
+    };
+
+    let program = check(compiled)?;
+
+    Ok(TypedArtifacts {
+        program,
+        private_outputs,
+        commitment,
+        call_logs,
+        user_modules,
+    })
+}
 
+pub(crate) fn check_semantics<T: Field>(
+    program: Program<'_>,
+) -> Result<TypedProgram<'_, T>, CompileErrors> {
     log::debug!("Check semantics");
 
     // check semantics
-    let typed_ast = Checker::check(compiled)
+    let typed_ast = Checker::check(program)
+        .map_err(|errors| CompileErrors(errors.into_iter().map(CompileError::from).collect()))?;
+
+    log::trace!("\n{}", typed_ast);
+
+    Ok(typed_ast)
+}
+
+/// Like `check_semantics`, reusing the snapshots of unchanged modules and returning the snapshots of the others
+pub(crate) fn check_semantics_with_snapshots<'ast, T: Field>(
+    program: Program<'ast>,
+    snapshots: semantics::ModuleSnapshots<'ast, T>,
+) -> Result<(TypedProgram<'ast, T>, semantics::ModuleSnapshots<'ast, T>), CompileErrors> {
+    log::debug!(
+        "Check semantics, reusing {} module snapshots",
+        snapshots.len()
+    );
+
+    let (typed_ast, checked) = Checker::check_with_snapshots(program, snapshots)
+        .map_err(|errors| CompileErrors(errors.into_iter().map(CompileError::from).collect()))?;
+
+    log::trace!("\n{}", typed_ast);
+
+    Ok((typed_ast, checked))
+}
+
+fn check_semantics_with_span_logs<T: Field>(
+    program: Program<'_>,
+) -> Result<TypedProgram<'_, T>, CompileErrors> {
+    log::debug!("Check semantics with span logs");
+
+    let typed_ast = Checker::check_with_span_logs(program)
         .map_err(|errors| CompileErrors(errors.into_iter().map(CompileError::from).collect()))?;
 
     log::trace!("\n{}", typed_ast);
 
+    Ok(typed_ast)
+}
+
+pub(crate) fn analyse_program<'ast, T: Field>(
+    typed_ast: TypedProgram<'ast, T>,
+    config: &CompileConfig,
+    call_logs: bool,
+    user_modules: &BTreeSet<OwnedModuleId>,
+) -> Result<(ZirProgram<'ast, T>, Abi, Vec<Diagnostic>), CompileErrors> {
     let main_module = typed_ast.main.clone();
 
     log::debug!("Run static analysis");
 
     // analyse (unroll and constant propagation)
-    analyse(typed_ast, config)
+    analyse(typed_ast, config, call_logs, user_modules)
         .map_err(|e| CompileErrors(vec![CompileErrorInner::from(e).in_file(&main_module)]))
 }
 
+pub(crate) fn lower<'ast, T: Field>(
+    program: ZirProgram<'ast, T>,
+    abi: &Abi,
+    main_module: &Path,
+    config: CompileConfig,
+) -> Result<
+    (
+        ir::ProgIterator<'ast, T, impl IntoIterator<Item = ir::Statement<'ast, T>> + 'ast>,
+        Warnings,
+    ),
+    CompileErrors,
+> {
+    lower_observed(
+        program,
+        abi,
+        main_module,
+        config,
+        Observers::default(),
+        None,
+    )
+    .map(|(prog, _, warnings)| (prog, warnings))
+}
+
+// lower a program like `lower`, the observers of the flattening and of the optimizer recording what happens along the
+// way. The statistics of the optimizer are recorded in `recorder` as the program is consumed, if any. The manifest and
+// the minimization report are left to the caller, as well as the warnings of the flattening, which are known once the
+// program is consumed
+fn lower_observed<'ast, T: Field>(
+    program: ZirProgram<'ast, T>,
+    abi: &Abi,
+    main_module: &Path,
+    config: CompileConfig,
+    observers: Observers<'_, T>,
+    recorder: Option<&StatsRecorder>,
+) -> Result<
+    (
+        ir::ProgIterator<'ast, T, impl IntoIterator<Item = ir::Statement<'ast, T>> + 'ast>,
+        Observations,
+        Warnings,
+    ),
+    CompileErrors,
+> {
+    let Observers {
+        debug_table,
+        report,
+        sink,
+        ..
+    } = observers;
+
+    let mut observations = Observations::default();
+
+    // the private return values are flattened to the last outputs, which are kept by the optimizer like the others
+    let (program, private_output_count) = private_outputs::reorder(program, abi);
+
+    let critical_assertions = critical::critical_assertions(&program.main.statements);
+
+    // flatten input program, lazily unless the flattening is observed
+    let (program_flattened, budget, warnings, trace) = match debug_table || report || sink.is_some()
+    {
+        false => {
+            log::debug!("Flatten");
+            let (program_flattened, budget) =
+                from_function_with_budget(program.main, config.clone());
+            let warnings = program_flattened.statements.flattener.warnings();
+            (boxed(program_flattened), budget, warnings, None)
+        }
+        true => {
+            log::debug!("Flatten with observers");
+            let (observed, budget) = flatten_observed(
+                program.main,
+                config.clone(),
+                FlattenObservers {
+                    entry: format!("{}/main", main_module.display()),
+                    trace: debug_table,
+                    report,
+                    sink,
+                },
+            );
+            observations.report = observed.report;
+            let trace = debug_table.then(|| (observed.origins, observed.names));
+            (boxed(observed.program), budget, observed.warnings, trace)
+        }
+    };
+
+    // convert to ir
+    log::debug!("Convert to IR");
+    let ir_prog = from_flat(program_flattened);
+
+    // optimize, following the provenance of the statements if a debug table is built
+    log::debug!("Optimise IR");
+    let pipeline = config.pipeline.unwrap_or_default();
+    let (tracker, origins) = match trace {
+        Some((origins, names)) => (Some(ProvenanceTracker::new(names)), origins),
+        None => (None, vec![]),
+    };
+    let optimized_ir_prog = optimize(ir_prog, &pipeline, recorder, tracker.as_ref());
+
+    let arguments = optimized_ir_prog.arguments.clone();
+    let return_count = optimized_ir_prog.return_count;
+
+    // clean (remove blocks)
+    let (mut statements, checked) = match tracker {
+        Some(tracker) => {
+            let (statements, table) = tracker.debug_table(optimized_ir_prog, &origins);
+            observations.debug_table = Some(table);
+            (
+                Box::new(std::iter::empty()) as Box<dyn Iterator<Item = _> + 'ast>,
+                Some(statements),
+            )
+        }
+        None => (
+            Box::new(optimized_ir_prog.clean().statements.into_iter())
+                as Box<dyn Iterator<Item = _> + 'ast>,
+            None,
+        ),
+    };
+
+    // make sure flattening stayed within the budget and no critical assertion was optimized away, which requires going
+    // through the whole program. Only the optimized program is kept in memory, as flattening is lazy
+    let budget = Some(budget).filter(|_| config.max_constraints.is_some());
+    let checked: Vec<_> = match (checked, critical_assertions.is_empty() && budget.is_none()) {
+        (None, true) => vec![],
+        (checked, _) => {
+            let checked = checked.unwrap_or_else(|| statements.by_ref().collect());
+
+            if let Some(budget) = budget {
+                log::debug!("Check constraint budget");
+                budget.result().map_err(|e| {
+                    // report the error in the file of the statement it refers to, if any
+                    let file = e
+                        .span
+                        .as_ref()
+                        .map(|span| PathBuf::from(&span.file))
+                        .unwrap_or_else(|| main_module.to_path_buf());
+                    CompileErrors::from(CompileErrorInner::from(e).in_file(&file))
+                })?;
+            }
+
+            log::debug!("Check critical assertions");
+            critical::check(critical_assertions, &checked).map_err(|e| {
+                let file = PathBuf::from(&e.lost[0].file);
+                CompileErrors::from(CompileErrorInner::from(e).in_file(&file))
+            })?;
+            checked
+        }
+    };
+
+    Ok((
+        ir::ProgIterator::new(
+            arguments,
+            checked.into_iter().chain(statements),
+            return_count - private_output_count,
+        ),
+        observations,
+        warnings,
+    ))
+}
+
+// the flattened program `p`, whose statements are boxed so that eager and lazy flattenings have the same type
+fn boxed<'ast, T: 'ast, I: IntoIterator<Item = FlatStatement<'ast, T>>>(
+    p: FlatProgIterator<'ast, T, I>,
+) -> FlatProgIterator<'ast, T, Box<dyn Iterator<Item = FlatStatement<'ast, T>> + 'ast>>
+where
+    I::IntoIter: 'ast,
+{
+    FlatProgIterator {
+        arguments: p.arguments,
+        statements: Box::new(p.statements.into_iter()),
+        return_count: p.return_count,
+    }
+}
+
 pub fn parse_program<'ast, T: Field, E: Into<imports::Error>>(
     source: &'ast str,
     location: FilePath,
@@ -276,8 +921,15 @@ pub fn parse_module<'ast, T: Field, E: Into<imports::Error>>(
 ) -> Result<Module<'ast>, CompileErrors> {
     log::debug!("Generate pest AST for {}", location.display());
 
-    let ast = pest::generate_ast(source)
-        .map_err(|e| CompileErrors::from(CompileErrorInner::from(e).in_file(&location)))?;
+    // a module with syntax errors is never checked, but we report as many of them as we can
+    let ast = pest::generate_ast_with_recovery(source, MAX_PARSER_ERRORS).map_err(|errors| {
+        CompileErrors(
+            errors
+                .into_iter()
+                .map(|e| CompileErrorInner::from(e).in_file(&location))
+                .collect(),
+        )
+    })?;
 
     log::debug!("Process macros for {}", location.display());
 
@@ -302,8 +954,31 @@ pub fn parse_module<'ast, T: Field, E: Into<imports::Error>>(
 #[cfg(test)]
 mod test {
     use super::*;
+    use zokrates_common::ReductionPolicy;
     use zokrates_field::Bn128Field;
 
+    // compile `source`, located at `main.zok`, without a resolver
+    fn compile_source<'ast, T: Field>(
+        source: &str,
+        config: CompileConfig,
+        arena: &'ast Arena<String>,
+    ) -> Result<CompilationArtifacts<'ast, T, Vec<ir::Statement<'ast, T>>>, CompileErrors> {
+        compile::<T, io::Error>(source.into(), "main.zok".into(), None, config, arena)
+            .map(|artifacts| artifacts.collect())
+    }
+
+    // check `source`, located at `main.zok`, without a resolver, joining the messages of the errors
+    fn check_source(source: &str, config: &CompileConfig) -> Result<(), String> {
+        check::<Bn128Field, io::Error>(source.into(), "main.zok".into(), None, config).map_err(
+            |e| {
+                e.0.iter()
+                    .map(|e| e.value().to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            },
+        )
+    }
+
     #[test]
     fn no_resolver_with_imports() {
         let source = r#"
@@ -351,58 +1026,1240 @@ mod test {
         assert!(res.is_ok());
     }
 
-    mod abi {
-        use super::*;
-        use zokrates_ast::typed::abi::*;
-        use zokrates_ast::typed::types::*;
+    #[test]
+    fn reduction_policy() {
+        // greedily reducing `a * b` forces reducing the product again before multiplying by `i`. Reducing the
+        // longer product instead avoids that decomposition, and the output is smaller to reduce
+        let source = r#"
+            def main(u32 a, u32 b, u32 c, u32 d, u32 e, u32 f, u32 g, u32 h, u32 i) -> u32 {
+                return (a * b) * (c * d * e * f * g * h) * i;
+            }
+        "#;
 
-        #[test]
-        fn use_struct_declaration_types() {
-            // when importing types and renaming them, we use the canonical struct names in the ABI
+        let constraint_count = |policy| {
+            let arena = Arena::new();
+            compile_source::<Bn128Field>(
+                source,
+                CompileConfig::default().reduction_policy(policy),
+                &arena,
+            )
+            .unwrap()
+            .prog()
+            .constraint_count()
+        };
 
-            // // main.zok
-            // from foo import Foo as FooMain;
-            //
-            // // foo.zok
-            // from bar import Bar as BarFoo;
-            // struct Foo { BarFoo b; }
-            //
-            // // bar.zok
-            // struct Bar { field a; }
+        let greedy = constraint_count(ReductionPolicy::Greedy);
+        let minimized = constraint_count(ReductionPolicy::MinimizeDecompositions);
 
-            // Expected resolved type for FooMain:
-            // Foo { Bar b; }
+        // 64 + 224 + 64 bits decomposed, against 192 + 128 bits
+        assert!(greedy >= minimized + 32);
+    }
 
-            let main = r#"
-from "foo" import Foo as FooMain;
-def main(FooMain f) {
-    return;
-}
-"#;
+    #[test]
+    fn bounded_reduction() {
+        // the number of constraints of a counter of `n` steps of `step`, which is reduced when it is returned
+        let constraint_count = |n: usize, step: u32| {
+            let arena = Arena::new();
+            let source = format!(
+                r#"
+                def main(private bool[{n}] flags) -> u32 {{
+                    u32 mut count = 0;
+                    for u32 i in 0..{n} {{
+                        count = count + (flags[i] ? {step} : 0);
+                    }}
+                    return count;
+                }}
+            "#,
+                n = n,
+                step = step
+            );
 
-            struct CustomResolver;
+            compile_source::<Bn128Field>(&source, CompileConfig::default(), &arena)
+                .unwrap()
+                .prog()
+                .constraint_count()
+        };
+
+        // the counter is decomposed to the bits needed by its maximum: 8 bits for 200, 9 bits for 400 and 32 bits
+        // for 4000000000
+        let constraints = constraint_count(20, 10);
+        assert_eq!(constraint_count(20, 20), constraints + 1);
+        assert_eq!(constraint_count(20, 200_000_000), constraints + 24);
+    }
 
-            impl<E> Resolver<E> for CustomResolver {
-                fn resolve(
-                    &self,
-                    _: PathBuf,
-                    import_location: PathBuf,
-                ) -> Result<(String, PathBuf), E> {
-                    let loc = import_location.display().to_string();
-                    if loc == "main" {
-                        Ok((
-                            r#"
-from "foo" import Foo as FooMain;
-def main(FooMain f) {
-    return;
-}
-"#
-                            .into(),
-                            "main".into(),
-                        ))
-                    } else if loc == "foo" {
-                        Ok((
-                            r#"
+    #[test]
+    fn bit_order() {
+        let constraint_count = |source: &str| {
+            let arena = Arena::new();
+            compile_source::<Bn128Field>(
+                &format!(
+                    "from \"EMBED\" import u32_to_le_bits, u32_to_be_bits, u32_from_le_bits, u32_from_be_bits;\n{}",
+                    source
+                ),
+                CompileConfig::default(),
+                &arena,
+            )
+            .unwrap()
+            .prog()
+            .constraint_count()
+        };
+
+        // both orders use the same constraints
+        assert_eq!(
+            constraint_count("def main(u32 a) -> bool[32] { return u32_to_le_bits(a); }"),
+            constraint_count("def main(u32 a) -> bool[32] { return u32_to_be_bits(a); }")
+        );
+        assert_eq!(
+            constraint_count("def main(bool[32] a) -> u32 { return u32_from_le_bits(a); }"),
+            constraint_count("def main(bool[32] a) -> u32 { return u32_from_be_bits(a); }")
+        );
+
+        // a round trip does not add any constraint
+        assert_eq!(
+            constraint_count(
+                "def main(u32 a) -> u32 { return u32_from_le_bits(u32_to_le_bits(a)); }"
+            ),
+            constraint_count("def main(u32 a) -> u32 { return a; }")
+        );
+    }
+
+    #[test]
+    fn assertion_coverage() {
+        use zokrates_interpreter::{Coverage, CoverageCollector, Interpreter};
+
+        let arena = Arena::new();
+        let program = compile_source::<Bn128Field>(
+            "def main(bool b, field x) -> field {\n    field y = if b { assert(x == 1); x } else { 0 };\n    assert(x != 0);\n    return y;\n}",
+            CompileConfig::default().isolate_branches(true),
+            &arena,
+        )
+        .unwrap()
+        .prog();
+
+        let mut coverage = Coverage::default();
+        for inputs in [[1, 1], [0, 5]] {
+            let mut collector = CoverageCollector::new(Some(program.hash()));
+            Interpreter::default()
+                .execute_with_observer(
+                    program.clone(),
+                    &inputs.map(Bn128Field::from),
+                    &mut io::sink(),
+                    &mut collector,
+                )
+                .unwrap();
+            coverage.merge(collector.into_coverage()).unwrap();
+        }
+
+        assert_eq!(coverage.runs, 2);
+
+        let lines = coverage.lines();
+        assert_eq!(lines.len(), 2);
+        // the assertion in the branch is only exercised when the branch is taken
+        let branch = lines[&("main.zok".to_string(), 2)];
+        assert_eq!((branch.evaluated, branch.exercised), (2, 1));
+        let unconditional = lines[&("main.zok".to_string(), 3)];
+        assert_eq!((unconditional.evaluated, unconditional.exercised), (2, 2));
+    }
+
+    #[test]
+    fn div_by_zero() {
+        use zokrates_common::DivByZero;
+
+        let source = "def main(field a, field b) -> field {\n    return a / b;\n}";
+
+        let arena = Arena::new();
+        let compile_with = |policy| {
+            compile_source::<Bn128Field>(
+                source,
+                CompileConfig::default().div_by_zero(policy),
+                &arena,
+            )
+            .unwrap()
+            .prog()
+        };
+
+        let error = compile_with(DivByZero::Error);
+        let define_zero = compile_with(DivByZero::DefineZero);
+
+        // the zero flag costs one more constraint than checking that the denominator has an inverse
+        assert_eq!(define_zero.constraint_count(), error.constraint_count() + 1);
+    }
+
+    #[test]
+    fn critical_assertions() {
+        let source = "def main(field x) {\n    field y = 2;\n    #[critical] assert(y == 2);\n    assert(x == 1);\n    #[critical] assert(x == 1);\n    return;\n}";
+
+        let arena = Arena::new();
+        let program = compile_source::<Bn128Field>(source, CompileConfig::default(), &arena)
+            .unwrap()
+            .prog();
+
+        // the first assertion is known to hold and the last one duplicates another one, but both are kept
+        let lines: Vec<_> = program
+            .statements
+            .iter()
+            .filter(|s| s.is_critical())
+            .map(|s| match s {
+                ir::Statement::Constraint(_, _, Some(ir::RuntimeError::SourceAssertion(m))) => {
+                    m.position.line
+                }
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(lines, vec![3, 5]);
+    }
+
+    #[test]
+    fn constraint_budget() {
+        // the multiplication on line 3 goes over the budget, the assertion on line 2 stays within it
+        let source = "def main(field x) -> field {\n    assert(x * x == 4);\n    field y = x * x * x;\n    return y;\n}";
+
+        let arena = Arena::new();
+        let compile_with_budget = |budget| {
+            compile_source::<Bn128Field>(
+                source,
+                CompileConfig::default().max_constraints(Some(budget)),
+                &arena,
+            )
+        };
+
+        assert!(compile_with_budget(100).is_ok());
+
+        let errors = compile_with_budget(1).unwrap_err();
+        assert_eq!(errors.0.len(), 1);
+        match errors.0[0].value() {
+            CompileErrorInner::BudgetError(e) => {
+                assert_eq!(e.budget, 1);
+                assert_eq!(e.span.as_ref().unwrap().position.line, 3);
+            }
+            e => panic!("unexpected error {}", e),
+        }
+    }
+
+    #[test]
+    fn combined_observers() {
+        use crate::sink::ConstraintCounter;
+
+        let source = r#"
+            def square(field x) -> field {
+                return x * x;
+            }
+
+            def main(private field a) -> field {
+                assert(square(a) == 4);
+                return a * a * a;
+            }
+        "#;
+
+        let arena = Arena::new();
+        let compile_observing = |config, sink: &mut dyn ConstraintSink<Bn128Field>| {
+            compile_observed::<Bn128Field, io::Error>(
+                source.into(),
+                "main.zok".into(),
+                None,
+                config,
+                &arena,
+                Observers {
+                    debug_table: true,
+                    manifest: true,
+                    minimization_report: true,
+                    report: true,
+                    sink: Some(sink),
+                },
+            )
+        };
+
+        let mut counter = ConstraintCounter::default();
+        let (artifacts, observations) =
+            compile_observing(CompileConfig::default(), &mut counter).unwrap();
+
+        // observing the compilation does not change the program
+        let unobserved =
+            compile_source::<Bn128Field>(source, CompileConfig::default(), &arena).unwrap();
+        assert_eq!(artifacts.prog, unobserved.prog);
+
+        // all observers saw the same compilation
+        let constraint_count = artifacts.prog.constraint_count();
+        assert_eq!(
+            observations.debug_table.unwrap().constraints.len(),
+            constraint_count
+        );
+        assert_eq!(
+            observations.minimization_report.unwrap().after.constraints,
+            constraint_count
+        );
+        assert_eq!(
+            observations.manifest.unwrap().constraint_system,
+            artifacts.prog.hash()
+        );
+        assert!(observations.report.is_some());
+        assert!(counter.functions.contains_key("main.zok/square"));
+
+        // the constraint budget is still enforced
+        let errors = compile_observing(
+            CompileConfig::default().max_constraints(Some(1)),
+            &mut ConstraintCounter::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            errors.0[0].value(),
+            CompileErrorInner::BudgetError(e) if e.budget == 1
+        ));
+    }
+
+    #[test]
+    fn sink_within_budget() {
+        use crate::sink::ConstraintCounter;
+
+        let source = "def main(field x) -> field {\n    field y = x * x * x;\n    return y;\n}";
+
+        let arena = Arena::new();
+        let mut counter = ConstraintCounter::default();
+
+        // a sink alone goes through the same pipeline, so the budget is enforced
+        let errors = compile_observed::<Bn128Field, io::Error>(
+            source.into(),
+            "main.zok".into(),
+            None,
+            CompileConfig::default().max_constraints(Some(1)),
+            &arena,
+            Observers {
+                sink: Some(&mut counter),
+                ..Observers::default()
+            },
+        )
+        .unwrap_err();
+
+        match errors.0[0].value() {
+            CompileErrorInner::BudgetError(e) => {
+                assert_eq!(e.budget, 1);
+                assert_eq!(e.span.as_ref().unwrap().position.line, 2);
+            }
+            e => panic!("unexpected error {}", e),
+        }
+    }
+
+    #[test]
+    fn unconstrained_witness() {
+        use zokrates_interpreter::Interpreter;
+
+        let arena = Arena::new();
+        let program = compile_source::<Bn128Field>(
+            r#"
+                from "EMBED" import unconstrained_witness;
+
+                def main(field x) -> field {
+                    // the prover picks two distinct values, both different from `x`
+                    field r = unconstrained_witness();
+                    field s = unconstrained_witness();
+                    assert(r != x);
+                    assert(r != s);
+                    return x;
+                }
+            "#,
+            CompileConfig::default(),
+            &arena,
+        )
+        .unwrap()
+        .prog();
+
+        // the sampled values are not reported as unconstrained
+        program
+            .clone()
+            .serialize(&mut std::io::Cursor::new(vec![]))
+            .unwrap();
+
+        let witness = |x: u32| {
+            Interpreter::default()
+                .execute(program.clone(), &[Bn128Field::from(x)])
+                .unwrap()
+        };
+
+        // witness generation is reproducible, and depends on the inputs
+        assert_eq!(witness(3), witness(3));
+        assert_ne!(witness(3), witness(4));
+    }
+
+    #[test]
+    fn oversized_literals() {
+        let check = |body: &str| {
+            check_source(
+                &format!(
+                    "def foo(u8 x) -> u8 {{ return x; }}\ndef main() {{\n{}\nreturn;\n}}",
+                    body
+                ),
+                &CompileConfig::default(),
+            )
+        };
+
+        // a literal which does not fit
+        assert!(check("u8 x = 300;").unwrap_err().contains(
+            "Literal `300` does not fit in type `u8`, expected a value between 0 and 255"
+        ));
+        assert!(check("u8 y = foo(256);")
+            .unwrap_err()
+            .contains("Literal `256` does not fit"));
+        assert!(check("u16 x = 70000u16;")
+            .unwrap_err()
+            .contains("Literal `70000u16` does not fit in type `u16`"));
+
+        // a constant expression which does not fit once folded
+        assert!(check("u8 x = 200 + 100;").unwrap_err().contains(
+            "Expression `(200 + 100)` evaluates to 300, which does not fit in type `u8`"
+        ));
+
+        // explicit truncation
+        assert_eq!(check("u8 x = 300w8;"), Ok(()));
+        assert_eq!(check("u8 x = 255;"), Ok(()));
+    }
+
+    #[test]
+    fn small_field() {
+        use zokrates_field::GoldilocksField;
+        use zokrates_interpreter::Interpreter;
+
+        let arena = Arena::new();
+        let compile_goldilocks = |source: &str| {
+            compile_source::<GoldilocksField>(source, CompileConfig::default(), &arena)
+        };
+
+        // the product of two u32 values does not fit in the 64-bit goldilocks field, so it is computed on limbs
+        let program = compile_goldilocks("def main(u32 a, u32 b) -> u32 {\n    return a * b;\n}")
+            .unwrap()
+            .prog();
+
+        let product = |a: u32, b: u32| {
+            Interpreter::default()
+                .execute(program.clone(), &[a, b].map(GoldilocksField::from))
+                .unwrap()
+                .return_values()
+        };
+
+        // the product wraps around, even when it overflows the field
+        assert_eq!(product(6, 7), vec![GoldilocksField::from(42)]);
+        assert_eq!(
+            product(0x12345678, 7),
+            vec![GoldilocksField::from(0x7f6e5d48)]
+        );
+        assert_eq!(product(u32::MAX, u32::MAX), vec![GoldilocksField::from(1)]);
+
+        // u64 values do not fit at all
+        let errors = compile_goldilocks("def main(u64 a, u64 b) -> u64 {\n    return a * b;\n}")
+            .unwrap_err();
+        assert!(errors.0[0]
+            .value()
+            .to_string()
+            .contains("u64 is not supported on goldilocks"));
+    }
+
+    #[test]
+    fn bounded_field_arguments() {
+        let arena = Arena::new();
+        let constraint_count = |source: &str| {
+            compile_source::<Bn128Field>(source, CompileConfig::default(), &arena)
+                .unwrap()
+                .prog()
+                .constraint_count()
+        };
+
+        // the comparison of bounded arguments only decomposes their difference in 33 bits, which pays for the range
+        // checks of the arguments
+        assert!(
+            constraint_count("def main(field<32> a, field<32> b) -> bool {\n    return a < b;\n}")
+                < constraint_count("def main(field a, field b) -> bool {\n    return a < b;\n}")
+        );
+
+        // bounds are only allowed on the arguments of `main`, and must leave room for comparisons
+        let error = |source: &str| check_source(source, &CompileConfig::default()).unwrap_err();
+
+        assert!(error(
+            "def foo(field<8> x) -> field {\n    return x;\n}\ndef main() {\n    return;\n}"
+        )
+        .contains("`field<8>` is only allowed for arguments of the entrypoint function"));
+        assert!(error("def main(field<254> x) {\n    return;\n}").contains(
+            "Expected the bound of argument `x` to be between 1 and 252 bits, found 254"
+        ));
+        assert!(error("def main() {\n    field<8> x = 1;\n    return;\n}")
+            .contains("`field<8>` is only allowed for arguments of the entrypoint function"));
+        assert!(
+            error("struct Foo {\n    field<0> x;\n}\ndef main(Foo f) {\n    return;\n}")
+                .contains("Expected the bound of member `x` to be between 1 and 252 bits, found 0")
+        );
+
+        // the values defined from bounded arguments are bounded
+        assert!(
+            constraint_count(
+                "def main(field<32> a, field<32> b) -> bool {\n    field c = a * b;\n    return c < a;\n}"
+            ) < constraint_count(
+                "def main(field a, field b) -> bool {\n    field c = a * b;\n    return c < a;\n}"
+            )
+        );
+    }
+
+    #[test]
+    fn bounded_struct_members() {
+        let arena = Arena::new();
+        let artifacts = compile_source::<Bn128Field>(
+            "struct Foo {\n    field<8>[2] x;\n    field y;\n}\ndef main(Foo f) -> bool {\n    return f.x[0] < f.x[1];\n}",
+            CompileConfig::default(),
+            &arena,
+        )
+        .unwrap();
+
+        // the bounds of the members are exposed in the abi, and the unbounded member is not checked
+        assert_eq!(
+            artifacts
+                .abi()
+                .check_bounds(&[1, 256, 1 << 20].map(Bn128Field::from)),
+            Err(String::from(
+                "Value of input `f` at index 1 does not fit in 8 bits"
+            ))
+        );
+        assert_eq!(
+            artifacts
+                .abi()
+                .check_bounds(&[1, 255, 1 << 20].map(Bn128Field::from)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn field_to_uint_casts() {
+        let arena = Arena::new();
+        let constraint_count = |source: &str| {
+            compile_source::<Bn128Field>(source, CompileConfig::default(), &arena)
+                .unwrap()
+                .prog()
+                .constraint_count()
+        };
+
+        // the bound of the argument proves that it fits, so the cast adds no constraint
+        assert_eq!(
+            constraint_count("def main(field<32> x) -> u32 {\n    return u32(x);\n}"),
+            constraint_count("def main(field<32> x) -> field {\n    return x;\n}")
+        );
+
+        // otherwise the value is decomposed in 32 bits, with a constraint for each bit and one for their sum
+        assert_eq!(
+            constraint_count("def main(field x) -> u32 {\n    return u32(x);\n}"),
+            constraint_count("def main(field x) -> field {\n    return x;\n}") + 33
+        );
+    }
+
+    #[test]
+    fn field_to_uint_cast_warning() {
+        let arena = Arena::new();
+        let artifacts = compile_source::<Bn128Field>(
+            "def cast(field x) -> u32 {\n    return u32(x);\n}\n\ndef main(field x, field<32> y) -> u32 {\n    u32 a = cast(x);\n    return a + u32(y);\n}",
+            CompileConfig::default(),
+            &arena,
+        )
+        .unwrap();
+
+        // only the cast of the unbounded value is reported, at the statement it appears in
+        let diagnostics = artifacts.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0]
+            .message
+            .starts_with("Converting a field element to `u32` adds 33 constraints"));
+
+        let span = diagnostics[0].span.as_ref().unwrap();
+        assert_eq!(span.file, "main.zok");
+        assert_eq!(span.position.line, 2);
+    }
+
+    #[test]
+    fn trivial_assertions() {
+        let check = |body: &str, deny: bool| {
+            check_source(
+                &format!(
+                    "def foo(field x) {{\nassert(x == x);\nreturn;\n}}\ndef main(field x, field y) {{\n{}\nreturn;\n}}",
+                    body
+                ),
+                &CompileConfig::default().deny_trivial_asserts(deny),
+            )
+        };
+
+        // trivially true assertions are removed, and only rejected on demand
+        assert_eq!(check("assert(x == x);", false), Ok(()));
+
+        // both sides are identical
+        assert_eq!(
+            check("assert(x == x);", true),
+            Err("Found 1 trivially true assertion: `main.zok:6:1`".into())
+        );
+
+        // both sides fold to the same constant
+        assert_eq!(
+            check("field a = 2 + 3;\nassert(a == 5);", true),
+            Err("Found 1 trivially true assertion: `main.zok:7:1`".into())
+        );
+
+        // an assertion in a function called twice is reported once
+        assert_eq!(
+            check("foo(x);\nfoo(y);", true),
+            Err("Found 1 trivially true assertion: `main.zok:2:1`".into())
+        );
+
+        // near misses which depend on the values of the arguments are not reported
+        assert_eq!(check("assert(x == y);", true), Ok(()));
+        assert_eq!(check("assert(x * x == x);", true), Ok(()));
+    }
+
+    #[test]
+    fn trivial_assertions_in_libraries() {
+        use zokrates_fs_resolver::FileSystemResolver;
+
+        let stdlib = std::fs::canonicalize("../zokrates_stdlib/stdlib").unwrap();
+        let resolver = FileSystemResolver::with_stdlib_root(stdlib.to_str().unwrap());
+
+        let compile_source = |body: &str, deny: bool| {
+            let arena = Arena::new();
+            compile::<Bn128Field, io::Error>(
+                format!(
+                    "from \"utils/casts\" import cast;\ndef main(bool[16] a, field x) -> u8[2] {{\n{}\nu8[2] r = cast(a);\nreturn r;\n}}",
+                    body
+                ),
+                "main.zok".into(),
+                Some(&resolver),
+                CompileConfig::default().deny_trivial_asserts(deny),
+                &arena,
+            )
+            .map(|artifacts| {
+                artifacts
+                    .diagnostics()
+                    .iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<_>>()
+            })
+            .map_err(|e| e.0[0].value().to_string())
+        };
+
+        // the static assertion of the generic `cast`, `assert(N == 8 * P)`, always holds but is not reported
+        assert_eq!(compile_source("", true), Ok(vec![]));
+
+        // the assertions of the user are reported as diagnostics
+        assert_eq!(
+            compile_source("assert(x == x);", false),
+            Ok(vec![String::from(
+                "main.zok:3:1: Assertion always holds and was removed, delete it or use `--deny-trivial-asserts` to reject it"
+            )])
+        );
+        assert_eq!(
+            compile_source("assert(x == x);", true),
+            Err("Found 1 trivially true assertion: `main.zok:3:1`".into())
+        );
+    }
+
+    #[test]
+    fn multiple_syntax_errors() {
+        let source = r#"def foo() -> field {
+    field a = 1 +;
+    return a;
+}
+
+def bar() -> field {
+    field b = ;
+    return b;
+}
+
+def main( -> field {
+    return foo() + bar();
+}
+"#;
+
+        let errors = check::<Bn128Field, io::Error>(
+            source.to_string(),
+            "./path/to/file".into(),
+            None,
+            &CompileConfig::default(),
+        )
+        .unwrap_err()
+        .0;
+
+        // all errors are reported in one run, and the module does not reach semantic checking
+        assert!(errors.iter().all(|e| e.value().code() == "parser"));
+        assert_eq!(
+            errors
+                .iter()
+                .map(|e| e.value().pos().unwrap().0.line)
+                .collect::<Vec<_>>(),
+            vec![2, 7, 11]
+        );
+    }
+
+    #[test]
+    fn field_uint_mismatch() {
+        let check = |body: &str| {
+            check_source(
+                &format!("def main() {{\n{}\nreturn;\n}}", body),
+                &CompileConfig::default(),
+            )
+        };
+
+        // a literal operand gets the suffix of the other operand
+        assert_eq!(
+            check("u32 a = 1; u32 b = a + 2f;"),
+            Err("2:20\n\tCannot apply `+` to u32, field\
+                \n\tleft operand `a` at 2:20 has type u32\
+                \n\tright operand `2f` at 2:24 has type field\
+                \n\thelp: for the right operand, write the literal as `2u32`"
+                .into())
+        );
+
+        // otherwise the integer is converted to a field element
+        assert_eq!(
+            check("field x = 1; u32 y = 2; field z = x * y;"),
+            Err("2:35\n\tCannot apply `*` to field, u32\
+                \n\tleft operand `x` at 2:35 has type field\
+                \n\tright operand `y` at 2:39 has type u32\
+                \n\thelp: for the right operand, convert it with `u32_to_field(y)`, after `import \"utils/casts/u32_to_field\";`"
+                .into())
+        );
+    }
+
+    #[test]
+    fn signed_constants() {
+        let check = |body: &str| {
+            check_source(
+                &format!("def main() {{\n{}\nreturn;\n}}", body),
+                &CompileConfig::default(),
+            )
+        };
+
+        // the range of a signed type is not symmetric
+        assert_eq!(check("i8 x = -128;"), Ok(()));
+        assert_eq!(check("i8 x = 127;"), Ok(()));
+        assert!(check("i8 x = 128;").unwrap_err().contains(
+            "Literal `128` does not fit in type `i8`, expected a value between -128 and 127"
+        ));
+        assert!(check("i16 x = -32769;")
+            .unwrap_err()
+            .contains("evaluates to -32769, which does not fit in type `i16`"));
+
+        // division, remainder and right shifts depend on the sign and are not defined on signed integers
+        assert!(check("i8 x = 7; i8 y = x / 2;")
+            .unwrap_err()
+            .contains("Cannot apply `/` to i8"));
+        assert!(check("i8 x = 7; i8 y = x % 2;")
+            .unwrap_err()
+            .contains("Cannot apply `%` to i8"));
+        assert!(check("i8 x = 7; i8 y = x >> 1;")
+            .unwrap_err()
+            .contains("Cannot right-shift i8"));
+    }
+
+    // the report of the directives and embeds used by `source`
+    fn report(source: String) -> Report {
+        let arena = Arena::new();
+        let (_, observations) = compile_observed::<Bn128Field, io::Error>(
+            source,
+            "main.zok".into(),
+            None,
+            CompileConfig::default(),
+            &arena,
+            Observers {
+                report: true,
+                ..Observers::default()
+            },
+        )
+        .unwrap();
+
+        observations.report.unwrap()
+    }
+
+    #[test]
+    fn report_by_function() {
+        let source = r#"
+            from "EMBED" import sha256round;
+
+            def hash(bool[256] a, bool[256] b, bool[256] iv) -> bool[256] {
+                return sha256round([...a, ...b], iv);
+            }
+
+            def divide(field x, field y) -> field {
+                return x / y;
+            }
+
+            def main(bool[256] a, bool[256] b, bool[256] iv, field x, field y) -> (field, bool[256]) {
+                return (divide(x, y), hash(a, b, iv));
+            }
+        "#
+        .to_string();
+
+        let report = report(source);
+
+        let hash = &report.functions["main.zok/hash"];
+        assert_eq!(hash.embeds["_SHA256_ROUND"], 1);
+        assert_eq!(hash.directives["Sha256Round"], 1);
+        assert!(!hash.directives.contains_key("Div"));
+
+        let divide = &report.functions["main.zok/divide"];
+        assert_eq!(divide.directives["Div"], 1);
+        assert!(divide.embeds.is_empty());
+
+        assert!(report.functions.get("main.zok/main").map_or(true, |main| {
+            !main.directives.contains_key("Div") && !main.embeds.contains_key("_SHA256_ROUND")
+        }));
+
+        // the table lists each function with the kinds it uses
+        let table = report.to_string();
+        assert!(table.contains("main.zok/hash"));
+        assert!(table.contains("Sha256Round"));
+    }
+
+    #[test]
+    fn report_unchecked_casts() {
+        let source = r#"
+            def main(field x, field y) -> (u32, u32) {
+                return (u32::unchecked(x), u32(y));
+            }
+        "#
+        .to_string();
+
+        let report = report(source);
+
+        let main = &report.functions["main.zok/main"];
+        assert_eq!(main.embeds["_FIELD_TO_U32_UNCHECKED"], 1);
+        assert_eq!(main.embeds["_FIELD_TO_U32"], 1);
+        assert!(report.to_string().contains("_FIELD_TO_U32_UNCHECKED"));
+    }
+
+    #[test]
+    fn report_constant_challenges() {
+        let source = r#"
+            from "EMBED" import batch_eq;
+
+            def main(field[2] a, field[2] b, field r) {
+                field c = 6 * 7;
+                assert(batch_eq(a, b, r));
+                assert(batch_eq(a, b, 42));
+                assert(batch_eq(a, b, c));
+                return;
+            }
+        "#
+        .to_string();
+
+        let report = report(source);
+
+        // the challenges known at compile time are flagged, including the ones propagated from definitions
+        let main = &report.functions["main.zok/main"];
+        assert_eq!(main.embeds["_BATCH_EQ"], 3);
+        assert_eq!(main.warnings["constant_challenge"], 2);
+        assert!(report.to_string().contains("constant_challenge"));
+    }
+
+    #[test]
+    fn spread_without_copies() {
+        let source = r#"
+            def main(field[128] a, field[128] b) -> field[256] {
+                field[256] c = [...a, ...b];
+                return c;
+            }
+        "#;
+
+        let arena = Arena::new();
+        let program =
+            parse_program::<Bn128Field, io::Error>(source, "main.zok".into(), None, &arena)
+                .unwrap();
+        let user_modules = program.user_modules();
+        let typed_ast = check_semantics::<Bn128Field>(program).unwrap();
+        let (zir, _, _) =
+            analyse_program(typed_ast, &CompileConfig::default(), false, &user_modules).unwrap();
+
+        // the concatenation refers to the elements of `a` and `b` instead of defining 256 new variables
+        assert_eq!(zir.main.statements.len(), 1);
+        assert!(matches!(
+            zir.main.statements[0],
+            zokrates_ast::zir::ZirStatement::Return(_)
+        ));
+    }
+
+    #[test]
+    fn call_logs_only_when_observed() {
+        let source = r#"
+            def foo(field a) -> field {
+                return a * a;
+            }
+
+            def main(field a) -> field {
+                return foo(a);
+            }
+        "#;
+
+        let call_logs = |calls| {
+            let arena = Arena::new();
+            let (zir, _) = check_with_arena_and_logs::<Bn128Field, io::Error>(
+                source.to_string(),
+                "main.zok".into(),
+                None,
+                &CompileConfig::default(),
+                &arena,
+                Logs {
+                    spans: false,
+                    calls,
+                },
+            )
+            .unwrap();
+            zir.main
+                .statements
+                .iter()
+                .filter(|s| matches!(s, zokrates_ast::zir::ZirStatement::PushCallLog(_)))
+                .count()
+        };
+
+        // the bodies of the inlined functions are marked only for the observers of the flattening
+        assert_eq!(call_logs(false), 0);
+        assert_eq!(call_logs(true), 1);
+    }
+
+    #[test]
+    fn recursion() {
+        let compile_with = |source: &str, config| {
+            let arena = Arena::new();
+            let program =
+                parse_program::<Bn128Field, io::Error>(source, "main.zok".into(), None, &arena)
+                    .unwrap();
+            let user_modules = program.user_modules();
+            let typed_ast = check_semantics::<Bn128Field>(program).unwrap();
+            analyse_program(typed_ast, &config, false, &user_modules)
+                .map(|_| ())
+                .map_err(|e| e.0[0].value().to_string())
+        };
+
+        // the recursion stops when the generic value reaches the base case
+        let source = r#"
+            def sum<N>() -> field {
+                return if N == 0 { 0 } else { N + sum::<N - 1>() };
+            }
+
+            def main() -> field {
+                return sum::<3>();
+            }
+        "#;
+        assert_eq!(compile_with(source, CompileConfig::default()), Ok(()));
+
+        // without a base case, the call chain is reported once it gets deeper than the limit
+        let source = r#"
+            def foo<N>() -> field {
+                return foo::<N + 1>();
+            }
+
+            def main() -> field {
+                return foo::<0>();
+            }
+        "#;
+        let error =
+            compile_with(source, CompileConfig::default().max_call_depth(Some(3))).unwrap_err();
+        assert!(error.contains("Maximum call depth of 3 exceeded"));
+        assert!(error.contains("0: main.zok/foo::<N: 0> called at main.zok:7:24"));
+        assert!(error.contains("3: main.zok/foo::<N: 3> called at main.zok:3:24"));
+
+        // a call identical to one of its callers is reported right away
+        let source = r#"
+            def foo<N>() -> field {
+                return foo::<N>();
+            }
+
+            def main() -> field {
+                return foo::<0>();
+            }
+        "#;
+        let error = compile_with(source, CompileConfig::default()).unwrap_err();
+        assert!(error.contains("Function calls itself with identical generic values and arguments"));
+    }
+
+    #[test]
+    fn field_intrinsics() {
+        let source = r#"
+            const field D = if FIELD_SIZE_IN_BITS == 254 { 168696 } else { 174325 };
+            const field E = if FIELD_MODULUS == 21888242871839275222246405745257275088548364400416034343698204186575808495617 { 1234567 } else { 7654321 };
+
+            def main(field x) -> (field, field, field) {
+                field[FIELD_SIZE_IN_BITS] bits = [0; FIELD_SIZE_IN_BITS];
+                return (x * D, x * E, bits[0]);
+            }
+        "#;
+
+        fn program<T: Field>(source: &str) -> String {
+            let arena = Arena::new();
+            compile_source::<T>(source, CompileConfig::default(), &arena)
+                .unwrap()
+                .prog()
+                .to_string()
+        }
+
+        let bn128 = program::<Bn128Field>(source);
+        assert!(bn128.contains("168696") && bn128.contains("1234567"));
+        assert!(!bn128.contains("174325") && !bn128.contains("7654321"));
+
+        let bls12_381 = program::<zokrates_field::Bls12_381Field>(source);
+        assert!(bls12_381.contains("174325") && bls12_381.contains("7654321"));
+        assert!(!bls12_381.contains("168696") && !bls12_381.contains("1234567"));
+    }
+
+    #[test]
+    fn estimate_bounds() {
+        use zokrates_interpreter::Interpreter;
+
+        let programs: [(&str, &[u32]); 4] = [
+            (
+                r#"
+                def main(field a, private field b) -> field {
+                    field c = a * b;
+                    assert(c != 0);
+                    return c / (a + 1) + c ** 3;
+                }
+                "#,
+                &[2, 3],
+            ),
+            (
+                r#"
+                def main(u32 a, private u32 b) -> (u32, u32) {
+                    u32 c = (a + b) * a;
+                    return (c ^ (b >> 3), c / (b | 1));
+                }
+                "#,
+                &[1234, 56789],
+            ),
+            (
+                r#"
+                def main(field[4] xs, u32 i, bool f) -> field {
+                    field x = xs[i];
+                    assert(x < 100);
+                    return if f && x <= xs[0] { x } else { xs[3] };
+                }
+                "#,
+                &[1, 2, 3, 4, 2, 1],
+            ),
+            (
+                r#"
+                from "EMBED" import unpack;
+
+                def main(field a) -> bool[8] {
+                    bool[8] bits = unpack(a);
+                    return bits;
+                }
+                "#,
+                &[42],
+            ),
+        ];
+
+        for (source, inputs) in programs {
+            let config = CompileConfig::default();
+
+            let estimate = estimate::<Bn128Field, io::Error>(
+                source.to_string(),
+                "main.zok".into(),
+                None,
+                &config,
+            )
+            .unwrap();
+
+            let arena = Arena::new();
+            let program = compile_source::<Bn128Field>(source, config, &arena)
+                .unwrap()
+                .prog();
+
+            let inputs: Vec<_> = inputs.iter().map(|i| Bn128Field::from(*i)).collect();
+            let witness = Interpreter::default()
+                .execute(program.clone(), &inputs)
+                .unwrap();
+
+            assert!(
+                estimate.constraints.contains(program.constraint_count()),
+                "{} constraints out of {:?}",
+                program.constraint_count(),
+                estimate.constraints
+            );
+            assert!(
+                estimate.variables.contains(witness.0.len()),
+                "{} variables out of {:?}",
+                witness.0.len(),
+                estimate.variables
+            );
+        }
+    }
+
+    // the own and inclusive number of constraints of the span at the first occurrence of `code` in `source`
+    fn span_constraints(
+        source: &str,
+        spans: &[zokrates_codegen::SpanCost],
+        code: &str,
+    ) -> (usize, usize) {
+        let position = source
+            .lines()
+            .enumerate()
+            .find_map(|(index, line)| {
+                line.find(code).map(|col| Position {
+                    line: index + 1,
+                    col: col + 1,
+                })
+            })
+            .unwrap();
+
+        let span = spans.iter().find(|s| s.position == position).unwrap();
+        (span.own.constraints, span.inclusive.constraints)
+    }
+
+    #[test]
+    fn analyze_costs_of_loops() {
+        use zokrates_codegen::Cost;
+
+        let source = r#"
+            def square(field x) -> field {
+                return x * x;
+            }
+
+            def main(field a) -> field {
+                field mut x = a;
+                for u32 i in 0..N {
+                    x = square(x);
+                }
+                return x;
+            }
+        "#;
+
+        let config = CompileConfig::default();
+
+        let analyze = |iterations: u32| {
+            let source = source.replace('N', &iterations.to_string());
+
+            let annotations = analyze_costs::<Bn128Field, io::Error>(
+                source.clone(),
+                "./path/to/file".into(),
+                None,
+                &config,
+            )
+            .unwrap();
+            let estimate =
+                estimate::<Bn128Field, io::Error>(source, "./path/to/file".into(), None, &config)
+                    .unwrap();
+
+            assert_eq!(annotations.len(), 1);
+            (annotations.into_values().next().unwrap(), estimate)
+        };
+
+        let (once, _) = analyze(1);
+        let (spans, estimate) = analyze(4);
+
+        // each call defines the argument of `square`, which multiplies it and defines the result, which is then
+        // assigned to `x`
+        let cost = |spans: &[zokrates_codegen::SpanCost], code: &str| {
+            span_constraints(source, spans, code)
+        };
+        assert_eq!(cost(&once, "square(x)"), (1, 4));
+        assert_eq!(cost(&once, "return x * x"), (3, 3));
+
+        // the body of the loop and the function it calls are unrolled four times
+        assert_eq!(spans.len(), 5);
+        assert_eq!(cost(&spans, "field mut x = a"), (1, 1));
+        assert_eq!(cost(&spans, "x = square(x)"), (4, 4));
+        assert_eq!(cost(&spans, "square(x)"), (4, 16));
+        assert_eq!(cost(&spans, "return x * x"), (12, 12));
+        assert_eq!(cost(&spans, "return x;"), (1, 1));
+
+        // the spans add up to the estimate, but for `~one` and the argument
+        let total: Cost = spans.iter().map(|s| s.own).sum();
+        assert_eq!(total.constraints, estimate.constraints.upper);
+        assert_eq!(total.variables + 2, estimate.variables.upper);
+    }
+
+    #[test]
+    fn analyze_costs_of_branches() {
+        use zokrates_codegen::Cost;
+
+        let source = r#"
+            def square(field x) -> field {
+                return x * x;
+            }
+
+            def main(field a, bool b) -> field {
+                field c = b ? square(a) : a;
+                return c;
+            }
+        "#;
+
+        let config = CompileConfig::default().isolate_branches(true);
+
+        let annotations = analyze_costs::<Bn128Field, io::Error>(
+            source.into(),
+            "./path/to/file".into(),
+            None,
+            &config,
+        )
+        .unwrap();
+        let estimate = estimate::<Bn128Field, io::Error>(
+            source.into(),
+            "./path/to/file".into(),
+            None,
+            &config,
+        )
+        .unwrap();
+
+        let spans = annotations.into_values().next().unwrap();
+
+        // the call in the branch is attributed to the call and to the function, its constraints being made
+        // conditional
+        assert_eq!(span_constraints(source, &spans, "square(a)"), (4, 16));
+        assert_eq!(span_constraints(source, &spans, "return x * x"), (12, 12));
+
+        let total: Cost = spans.iter().map(|s| s.own).sum();
+        assert_eq!(total.constraints, estimate.constraints.upper);
+    }
+
+    mod abi {
+        use super::*;
+        use zokrates_ast::typed::abi::*;
+        use zokrates_ast::typed::types::*;
+
+        #[test]
+        fn use_struct_declaration_types() {
+            // when importing types and renaming them, we use the canonical struct names in the ABI
+
+            // // main.zok
+            // from foo import Foo as FooMain;
+            //
+            // // foo.zok
+            // from bar import Bar as BarFoo;
+            // struct Foo { BarFoo b; }
+            //
+            // // bar.zok
+            // struct Bar { field a; }
+
+            // Expected resolved type for FooMain:
+            // Foo { Bar b; }
+
+            let main = r#"
+from "foo" import Foo as FooMain;
+def main(FooMain f) {
+    return;
+}
+"#;
+
+            struct CustomResolver;
+
+            impl<E> Resolver<E> for CustomResolver {
+                fn resolve(
+                    &self,
+                    _: PathBuf,
+                    import_location: PathBuf,
+                ) -> Result<(String, PathBuf), E> {
+                    let loc = import_location.display().to_string();
+                    if loc == "main" {
+                        Ok((
+                            r#"
+from "foo" import Foo as FooMain;
+def main(FooMain f) {
+    return;
+}
+"#
+                            .into(),
+                            "main".into(),
+                        ))
+                    } else if loc == "foo" {
+                        Ok((
+                            r#"
 from "bar" import Bar as BarFoo;
 struct Foo {
     BarFoo b;
@@ -454,15 +2311,752 @@ struct Bar { field a; }
                                     vec![],
                                     vec![ConcreteStructMember {
                                         id: "a".into(),
-                                        ty: box ConcreteType::FieldElement
+                                        ty: box ConcreteType::FieldElement,
+                                        bound: None,
                                     }]
-                                ))
+                                )),
+                                bound: None,
                             }]
                         ))
                     }],
-                    output: ConcreteType::Tuple(GTupleType::new(vec![]))
+                    output: ConcreteType::Tuple(GTupleType::new(vec![])),
+                    private_outputs: vec![],
+                    no_outputs: false,
+                    derived_nonces: vec![],
+                    bound_at_setup: vec![],
+                    bounds: Default::default(),
+                    commitment: None,
+                    constants: vec![],
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn public_constants() {
+        // constants are exported once reduced, so they may be computed by generic functions
+        let source = r#"
+            def double<N>() -> u32 {
+                return N * 2;
+            }
+
+            pub const u32 DEPTH = double::<4>();
+            pub const field[4] VALUES = [1, ...[2; 3]];
+            const u32 HIDDEN = DEPTH + 1;
+
+            def main(field[DEPTH] a) -> field {
+                return a[HIDDEN - 2] + VALUES[3];
+            }
+        "#;
+
+        let arena = Arena::new();
+        let artifacts =
+            compile_source::<Bn128Field>(source, CompileConfig::default(), &arena).unwrap();
+
+        let constants = artifacts.abi().constants();
+
+        assert_eq!(
+            serde_json::to_value(constants).unwrap(),
+            serde_json::json!([
+                {
+                    "name": "DEPTH",
+                    "type": "u32",
+                    "value": "0x00000008"
+                },
+                {
+                    "name": "VALUES",
+                    "type": "array",
+                    "components": {
+                        "size": 4,
+                        "type": "field"
+                    },
+                    "value": ["1", "2", "2", "2"]
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn private_outputs() {
+        use zokrates_ast::typed::{ConcreteTupleType, ConcreteType};
+        use zokrates_interpreter::Interpreter;
+
+        let arena = Arena::new();
+        let (program, abi) = compile_source::<Bn128Field>(
+            r#"
+                def main(field a, private field b) -> (private field, field) {
+                    return (a * b, a + b);
+                }
+            "#,
+            CompileConfig::default(),
+            &arena,
+        )
+        .unwrap()
+        .into_inner();
+
+        assert_eq!(abi.private_outputs, vec![0]);
+        assert_eq!(
+            abi.public_output(),
+            ConcreteType::Tuple(ConcreteTupleType::new(vec![ConcreteType::FieldElement]))
+        );
+
+        // only `a` and the sum are public
+        assert_eq!(program.return_count, 1);
+        assert_eq!(program.public_count(), 2);
+
+        let witness = Interpreter::default()
+            .execute(program.clone(), &[Bn128Field::from(2), Bn128Field::from(3)])
+            .unwrap();
+
+        // the product is still part of the witness, after the public return value
+        assert_eq!(
+            witness.return_values(),
+            vec![Bn128Field::from(5), Bn128Field::from(6)]
+        );
+        assert_eq!(
+            abi.arrange_outputs(witness.return_values()),
+            vec![Bn128Field::from(6), Bn128Field::from(5)]
+        );
+        assert_eq!(
+            program.public_inputs_values(&witness),
+            vec![Bn128Field::from(2), Bn128Field::from(5)]
+        );
+    }
+
+    #[test]
+    fn private_outputs_outside_of_main() {
+        let res = check_source(
+            r#"
+                def foo() -> (private field, field) {
+                    return (1, 2);
+                }
+
+                def main() -> field {
+                    (field, field) t = foo();
+                    return t.1;
+                }
+            "#,
+            &CompileConfig::default(),
+        );
+
+        assert!(res.unwrap_err().contains("only allowed on the entrypoint"));
+    }
+
+    #[test]
+    fn no_outputs() {
+        use zokrates_ast::typed::{ConcreteTupleType, ConcreteType};
+        use zokrates_interpreter::Interpreter;
+
+        let source = r#"
+            def main(field a, private field b) -> (field, u32) {
+                assert(a * b == 6);
+                field mut c = a;
+                for u32 i in 0..4 {
+                    c = c * b;
+                }
+                return (c, 42);
+            }
+        "#;
+
+        let compile_with = |arena, no_outputs| {
+            compile_source::<Bn128Field>(
+                source,
+                CompileConfig::default().no_outputs(no_outputs),
+                arena,
+            )
+            .unwrap()
+            .into_inner()
+        };
+
+        let arena = Arena::new();
+        let (with_outputs, _) = compile_with(&arena, false);
+        let (program, abi) = compile_with(&arena, true);
+
+        assert!(abi.no_outputs);
+        assert_eq!(
+            abi.output,
+            ConcreteType::Tuple(ConcreteTupleType::new(vec![]))
+        );
+
+        // only the assertion is left, the product feeding the output being removed
+        assert_eq!(program.return_count, 0);
+        assert_eq!(program.public_count(), 1);
+        assert!(program.constraint_count() < with_outputs.constraint_count());
+
+        let witness = Interpreter::default()
+            .execute(program.clone(), &[Bn128Field::from(2), Bn128Field::from(3)])
+            .unwrap();
+
+        assert_eq!(witness.return_values(), vec![]);
+        assert_eq!(
+            program.public_inputs_values(&witness),
+            vec![Bn128Field::from(2)]
+        );
+
+        // the assertion is still checked
+        assert!(Interpreter::default()
+            .execute(program, &[Bn128Field::from(2), Bn128Field::from(4)])
+            .is_err());
+    }
+
+    #[test]
+    fn merkle_depth() {
+        let compile_main = |depth: &str| {
+            let source = format!(
+                r#"
+                from "EMBED" import merkle_verify_poseidon;
+
+                const u32 DEPTH = 2;
+
+                def check<N>(field leaf, field root, field[N] path, bool[N] directions) -> bool {{
+                    return merkle_verify_poseidon::<N>(leaf, root, path, directions);
+                }}
+
+                def main(field leaf, field root, field[2] path, bool[2] directions, u32 depth) -> bool {{
+                    assert(merkle_verify_poseidon(leaf, root, path, directions));
+                    assert(check(leaf, root, path, directions));
+                    return merkle_verify_poseidon::<{}>(leaf, root, path, directions);
+                }}
+            "#,
+                depth
+            );
+
+            let arena = Arena::new();
+            compile_source::<Bn128Field>(&source, CompileConfig::default(), &arena)
+                .map(|_| ())
+                .map_err(|e| e.0[0].value().to_string())
+        };
+
+        // literals, constants and generic parameters are known at compile time
+        assert!(compile_main("2").is_ok());
+        assert!(compile_main("DEPTH").is_ok());
+        assert!(compile_main("DEPTH * 2 - 2").is_ok());
+
+        // a runtime value is not
+        assert!(compile_main("depth")
+            .unwrap_err()
+            .contains("Expected the depth of the Merkle path to be a compile-time constant"));
+    }
+
+    #[test]
+    fn minimization_report() {
+        use zokrates_ast::ir::stats::StatsDelta;
+
+        let source = r#"
+            def main(field a, private field b) -> field {
+                field c = a * b;
+                field d = c;
+                assert(d == c);
+                log("{}", d);
+                return d + 1;
+            }
+        "#;
+
+        let compile_with = |pipeline: &str| {
+            let arena = Arena::new();
+            let (artifacts, observations) = compile_observed::<Bn128Field, io::Error>(
+                source.into(),
+                "main.zok".into(),
+                None,
+                CompileConfig::default()
+                    .debug(true)
+                    .pipeline(Some(pipeline.parse().unwrap())),
+                &arena,
+                Observers {
+                    minimization_report: true,
+                    ..Observers::default()
+                },
+            )
+            .unwrap();
+            (
+                artifacts.prog().constraint_count(),
+                observations.minimization_report.unwrap(),
+            )
+        };
+
+        let (constraint_count, report) = compile_with(zokrates_common::pipeline::DEFAULT_PIPELINE);
+
+        // the deltas of the passes add up to the total delta
+        assert_eq!(report.passes.len(), 7);
+        assert_eq!(
+            report
+                .passes
+                .iter()
+                .fold(StatsDelta::default(), |acc, p| acc + p.delta),
+            report.delta
+        );
+        assert_eq!(report.before.delta(&report.after), report.delta);
+        assert_eq!(report.after, report.passes.last().unwrap().stats);
+        assert_eq!(report.after.constraints, constraint_count);
+        assert_eq!(report.after.logs, 1);
+        assert!(report.after.constraints <= report.before.constraints);
+
+        // without ir passes, nothing changes
+        let (constraint_count, report) = compile_with("propagate,panics,uint");
+
+        assert!(report.passes.is_empty());
+        assert!(report.delta.is_zero());
+        assert_eq!(report.before, report.after);
+        assert_eq!(report.before.constraints, constraint_count);
+    }
+
+    #[test]
+    fn booleanity_constraints() {
+        use crate::optimizer::booleanity;
+        use zokrates_ast::common::Variable;
+
+        // the result of the comparison is used three times, and `x` is constrained to be a bit twice in different
+        // forms, which the deduplication of identical constraints does not catch
+        let source = r#"
+            def main(field a, field b, field x) -> field {
+                bool c = a < b;
+                asm {
+                    x === x * x;
+                    2 * x === 2 * x * x;
+                }
+                field y = c ? x : a;
+                field z = c ? a : b;
+                assert(c || x == 0);
+                return y + z;
+            }
+        "#;
+
+        // the number of booleanity constraints on each variable
+        let booleanity_counts = |pipeline: &str| {
+            let arena = Arena::new();
+            let program = compile_source::<Bn128Field>(
+                source,
+                CompileConfig::default().pipeline(Some(pipeline.parse().unwrap())),
+                &arena,
+            )
+            .unwrap()
+            .prog();
+
+            let mut counts: HashMap<Variable, usize> = HashMap::new();
+            for s in program.statements {
+                if let Some(v) = booleanity(&s) {
+                    *counts.entry(v).or_default() += 1;
+                }
+            }
+            counts
+        };
+
+        let pipeline = zokrates_common::pipeline::DEFAULT_PIPELINE;
+        assert!(pipeline.contains(",booleanity,"));
+
+        let before = booleanity_counts(&pipeline.replace(",booleanity,", ","));
+        let after = booleanity_counts(pipeline);
+
+        // some variable was constrained to be a bit more than once, and the repeated constraints are removed
+        assert!(before.values().any(|c| *c > 1));
+        assert!(after.values().sum::<usize>() < before.values().sum::<usize>());
+
+        // every variable constrained to be a bit still is, exactly once
+        assert_eq!(after.len(), before.len());
+        assert!(after.values().all(|c| *c == 1));
+    }
+
+    #[test]
+    fn linear_constraints() {
+        use zokrates_ast::ir::{QuadComb, Statement};
+        use zokrates_interpreter::{evaluate_quad, Interpreter};
+
+        // the outputs scaled by a constant are products with a constant side once flattened
+        let source = r#"
+            def main(field a, private field b) -> (field, field, field) {
+                field c = a * b;
+                assert(3 * c == c + 2 * c);
+                return (2 * a, c, 5 * (a + b));
+            }
+        "#;
+
+        let arena = Arena::new();
+        let compile_with = |pipeline: &str| {
+            compile_source::<Bn128Field>(
+                source,
+                CompileConfig::default().pipeline(Some(pipeline.parse().unwrap())),
+                &arena,
+            )
+            .unwrap()
+            .prog()
+        };
+
+        let linear_count = |program: &ir::Prog<Bn128Field>| {
+            program
+                .statements
+                .iter()
+                .filter(|s| matches!(s, Statement::Constraint(quad, _, _) if quad.is_linear()))
+                .count()
+        };
+
+        let before = compile_with("propagate,panics,uint");
+        let after = compile_with("propagate,panics,uint,linearize");
+
+        assert_eq!(before.constraint_count(), after.constraint_count());
+        assert!(linear_count(&after) > linear_count(&before));
+
+        // every product with a constant side was downgraded
+        assert!(after.statements.iter().all(|s| match s {
+            Statement::Constraint(quad, _, _) =>
+                quad.is_linear() || quad.clone().try_linear().is_err(),
+            _ => true,
+        }));
+
+        // `A * B == C` holds for the witness of the downgraded constraints
+        let inputs = [Bn128Field::from(3), Bn128Field::from(7)];
+        let witness = Interpreter::default()
+            .execute(after.clone(), &inputs)
+            .unwrap();
+        for s in &after.statements {
+            if let Statement::Constraint(quad, lin, _) = s {
+                assert_eq!(
+                    evaluate_quad(&witness, quad).unwrap(),
+                    evaluate_quad(&witness, &QuadComb::from(lin.clone())).unwrap()
+                );
+            }
+        }
+
+        assert_eq!(
+            witness.return_values(),
+            Interpreter::default()
+                .execute(before, &inputs)
+                .unwrap()
+                .return_values()
+        );
+    }
+
+    #[test]
+    fn call_graph_pruning() {
+        use std::collections::BTreeSet;
+
+        // `lib` declares ten functions, of which `main` only uses `f0` and the generic `f1`
+
+        struct CustomResolver;
+
+        impl<E> Resolver<E> for CustomResolver {
+            fn resolve(
+                &self,
+                _: PathBuf,
+                import_location: PathBuf,
+            ) -> Result<(String, PathBuf), E> {
+                assert_eq!(import_location.display().to_string(), "lib");
+
+                let mut source = String::from(
+                    "def f0(field x) -> field {\n    return x;\n}\ndef f1<N>(field[N] a) -> field {\n    field mut s = 0;\n    for u32 i in 0..N {\n        s = s + a[i];\n    }\n    return s;\n}\n",
+                );
+                for i in 2..10 {
+                    source.push_str(&format!(
+                        "def f{}(field x) -> field {{\n    return x + {};\n}}\n",
+                        i, i
+                    ));
                 }
+
+                Ok((source, "lib".into()))
+            }
+        }
+
+        let main = r#"
+from "lib" import f0, f1;
+def main(field x) -> field {
+    return f0(x) + f1([x, x]);
+}
+"#;
+
+        let arena = Arena::new();
+        let program = parse_program::<Bn128Field, io::Error>(
+            main,
+            "main".into(),
+            Some(&CustomResolver),
+            &arena,
+        )
+        .unwrap();
+        let typed_ast = check_semantics::<Bn128Field>(program).unwrap();
+
+        let (typed_ast, call_graph) = CallGraph::build(typed_ast);
+        let pruned = call_graph.prune(typed_ast);
+
+        let ids = |module: &str| {
+            pruned.modules[&PathBuf::from(module)]
+                .functions_iter()
+                .map(|d| d.key.id)
+                .collect::<BTreeSet<_>>()
+        };
+
+        assert_eq!(ids("lib"), BTreeSet::from(["f0", "f1"]));
+        assert_eq!(ids("main"), BTreeSet::from(["f0", "f1", "main"]));
+
+        let dot = call_graph::<Bn128Field, io::Error>(
+            main.to_string(),
+            "main".into(),
+            Some(&CustomResolver),
+        )
+        .unwrap();
+
+        let edges: Vec<_> = dot.lines().filter(|l| l.contains("\" -> \"")).collect();
+        assert_eq!(edges.len(), 4);
+        assert!(edges.contains(&"    \"main/f0(field) -> field\" -> \"lib/f0(field) -> field\";"));
+        assert!(
+            edges.contains(&"    \"main/main(field) -> field\" -> \"main/f0(field) -> field\";")
+        );
+        assert!(edges
+            .iter()
+            .any(|e| e.starts_with("    \"main/f1<") && e.contains("\" -> \"lib/f1<")));
+        assert!(edges
+            .iter()
+            .any(|e| e.starts_with("    \"main/main(field) -> field\" -> \"main/f1<")));
+
+        // the statement counts include the statements nested in loops, and unused functions are dashed
+        assert!(dot.contains("[label=\"lib/f0\\n1 statement\"];"));
+        assert!(dot.contains("[label=\"lib/f1\\n4 statements\"];"));
+        assert!(dot.contains("[label=\"lib/f9\\n1 statement\", style=dashed];"));
+    }
+
+    #[test]
+    fn unsafe_witness() {
+        use zokrates_interpreter::{Error, Interpreter};
+
+        let arena = Arena::new();
+        let compile_main = |constraint: &str| {
+            let source = format!(
+                r#"
+                def main(field x) -> field {{
+                    field mut y = 0;
+                    field mut y2 = 0;
+                    asm {{
+                        y <-- unsafe_witness("isqrt", x);
+                        {}
+                    }}
+                    return y;
+                }}
+            "#,
+                constraint
             );
+
+            compile_source::<Bn128Field>(&source, CompileConfig::default(), &arena)
+                .map(|artifacts| artifacts.prog())
+                .map_err(|e| e.0[0].value().to_string())
+        };
+
+        // the outputs of a plugin must be constrained
+        assert!(compile_main("")
+            .unwrap_err()
+            .contains("must be checked by a constraint in the same asm block"));
+
+        // constraints which hold for any value of the outputs do not check them
+        for constraint in ["y * 0 === 0;", "0 === 0 * y;", "y === y;"] {
+            assert!(compile_main(constraint)
+                .unwrap_err()
+                .contains("must be checked by a constraint in the same asm block"));
         }
+
+        let program: ir::Prog<Bn128Field> = compile_main("y2 <== y * y;\n y2 * x === 1;").unwrap();
+
+        // the inverse of the square root of small squares
+        let isqrt = |inputs: &[Bn128Field]| {
+            let root = (1..100u32)
+                .map(Bn128Field::from)
+                .find(|r| r.clone() * r.clone() == inputs[0])
+                .unwrap();
+            vec![Bn128Field::from(1) / root]
+        };
+
+        let run = |interpreter: Interpreter, x: u32| {
+            interpreter
+                .execute(program.clone(), &[x.into()])
+                .map(|witness| witness.return_values())
+        };
+
+        let y = run(Interpreter::default().with_plugin("isqrt", 1, 1, isqrt), 16).unwrap();
+        assert_eq!(
+            y[0].clone() * y[0].clone() * Bn128Field::from(16),
+            Bn128Field::from(1)
+        );
+
+        assert_eq!(
+            run(Interpreter::default(), 16).unwrap_err(),
+            Error::MissingPlugin {
+                name: "isqrt".into()
+            }
+        );
+        assert_eq!(
+            run(Interpreter::default().with_plugin("isqrt", 2, 1, isqrt), 16).unwrap_err(),
+            Error::PluginSignature {
+                name: "isqrt".into(),
+                registered: (2, 1),
+                declared: (1, 1)
+            }
+        );
+
+        // a wrong result is caught by the constraints
+        let wrong = Interpreter::default()
+            .with_plugin("isqrt", 1, 1, |inputs: &[Bn128Field]| inputs.to_vec());
+        assert!(matches!(
+            run(wrong, 16).unwrap_err(),
+            Error::UnsatisfiedConstraint { .. }
+        ));
+    }
+
+    #[test]
+    fn bind_at_setup() {
+        use std::collections::HashMap;
+        use zokrates_interpreter::Interpreter;
+
+        let arena = Arena::new();
+        let compile_main = |source: &str| {
+            compile_source::<Bn128Field>(source, CompileConfig::default(), &arena)
+                .map(|artifacts| artifacts.into_inner())
+                .map_err(|e| e.0[0].value().to_string())
+        };
+
+        let (program, abi): (ir::Prog<Bn128Field>, _) = compile_main(
+            r#"
+            def main(private field x, #[bind_at_setup] private field[2] weights) -> field {
+                return weights[0] * x + weights[1];
+            }
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(abi.bound_at_setup, vec!["weights"]);
+        assert_eq!(abi.bound_indices(), vec![1, 2]);
+
+        // the bound program only takes `x`
+        let values: HashMap<_, _> = abi
+            .bound_indices()
+            .into_iter()
+            .map(|index| program.arguments[index].id)
+            .zip(vec![Bn128Field::from(3), Bn128Field::from(4)])
+            .collect();
+        let bound = program.bind(values).collect();
+        assert_eq!(bound.arguments.len(), 1);
+
+        let witness = Interpreter::default()
+            .execute(bound, &[Bn128Field::from(5)])
+            .unwrap();
+        assert_eq!(witness.return_values(), vec![Bn128Field::from(19)]);
+
+        // the bound arguments are private arguments of the entrypoint function
+        assert!(
+            compile_main("def main(#[bind_at_setup] field x) -> field { return x; }")
+                .unwrap_err()
+                .contains("must be private")
+        );
+        assert!(compile_main(
+            "def main(#[derive_nonce] #[bind_at_setup] private field x) -> field { return x; }"
+        )
+        .unwrap_err()
+        .contains("cannot be both derived"));
+        assert!(compile_main(
+            r#"
+            def foo(#[bind_at_setup] field x) -> field { return x; }
+            def main(field x) -> field { return foo(x); }
+        "#
+        )
+        .unwrap_err()
+        .contains("only allowed on arguments of the entrypoint function"));
+    }
+
+    #[test]
+    fn resume_from_zir() {
+        let source = r#"
+            struct Point {
+                field x;
+                u32 y;
+            }
+
+            def main(Point p, private u32[2] a) -> (field, u32) {
+                u32 mut acc = p.y;
+                for u32 i in 0..2 {
+                    acc = acc + (a[i] ^ 0x000000ff);
+                }
+                return (p.x * p.x, acc);
+            }
+        "#;
+
+        let config = CompileConfig::default().isolate_branches(true);
+
+        let arena = Arena::new();
+        let expected = compile_source::<Bn128Field>(source, config.clone(), &arena).unwrap();
+
+        // stop after zir and store the result
+        let arena = Arena::new();
+        let typed = to_typed::<Bn128Field, io::Error>(
+            source.to_string(),
+            "main.zok".into(),
+            None,
+            &config,
+            &arena,
+        )
+        .unwrap();
+        let zir = to_zir(typed, &config).unwrap();
+        let stored = serde_json::to_string(&zir).unwrap();
+
+        // resume from the stored zir
+        let resumed: ZirArtifacts<Bn128Field> = serde_json::from_str(&stored).unwrap();
+        assert_eq!(resumed, zir);
+        let actual = to_ir(resumed, config).unwrap().collect();
+
+        assert_eq!(actual.abi, expected.abi);
+        assert_eq!(actual.prog, expected.prog);
+    }
+
+    #[test]
+    fn shadowed_declarations() {
+        let check = |source: &str| check_source(source, &CompileConfig::default());
+
+        let main = "def main() {\n    return;\n}";
+
+        // duplicate struct member
+        let error = check(&format!(
+            "struct Foo {{\n    field a;\n    u32 a;\n}}\n{}",
+            main
+        ))
+        .unwrap_err();
+        assert!(error.contains("Duplicate key a in struct definition"));
+        assert!(error.contains("first declared at 2:5"));
+
+        // duplicate generic parameter
+        let error = check(&format!(
+            "def foo<N, N>(field[N] a) -> field {{\n    return a[0];\n}}\n{}",
+            main
+        ))
+        .unwrap_err();
+        assert!(error.contains("Generic parameter N is already declared"));
+        assert!(error.contains("first declared at 1:9"));
+
+        // generic parameter shadowing a type
+        let error = check(&format!(
+            "struct N {{\n    field a;\n}}\ndef foo<N>(field[N] a) -> field {{\n    return a[0];\n}}\n{}",
+            main
+        ))
+        .unwrap_err();
+        assert!(error.contains("Generic parameter N shadows the type N"));
+        assert!(error.contains("type declared at 1:1"));
+
+        // generic parameter shadowing a type declared after it
+        let error = check(&format!(
+            "def foo<N>(field[N] a) -> field {{\n    return a[0];\n}}\nstruct N {{\n    field a;\n}}\n{}",
+            main
+        ))
+        .unwrap_err();
+        assert!(error.contains("Generic parameter N shadows the type N"));
+        assert!(error.contains("type declared at 4:1"));
+
+        // argument shadowing a generic parameter
+        let error = check(&format!(
+            "def foo<N>(field[N] N) -> field {{\n    return N[0];\n}}\n{}",
+            main
+        ))
+        .unwrap_err();
+        assert!(error.contains("Argument `N` shadows the generic parameter `N`"));
+        assert!(error.contains("generic parameter declared at 1:9"));
+
+        // the same names in unrelated scopes are accepted
+        assert_eq!(
+            check(
+                "struct Foo<N> {\n    field[N] a;\n}\nstruct Bar {\n    u32 a;\n}\ndef foo<N>(field[N] a) -> field {\n    return a[0];\n}\ndef bar(field N) -> field {\n    return N;\n}\ndef main(Foo<2> f, Bar b) -> field {\n    return foo(f.a) + bar(1);\n}"
+            ),
+            Ok(())
+        );
     }
 }
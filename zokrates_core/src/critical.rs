@@ -0,0 +1,151 @@
+//! Module checking that assertions marked `#[critical]` survive optimization.
+//!
+//! The passes of the compiler keep critical assertions even when they can be proven to hold, and the IR optimizer does
+//! not remove or merge the constraints they produce. This check makes sure that each critical assertion left after
+//! analysis is still enforced by at least one constraint of the compiled program.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use zokrates_ast::common::SourceMetadata;
+use zokrates_ast::ir;
+use zokrates_ast::zir::{RuntimeError, ZirStatement};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Error {
+    /// The critical assertions which are not enforced by any constraint
+    pub lost: Vec<SourceMetadata>,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} critical assertion(s) were removed during compilation:",
+            self.lost.len()
+        )?;
+        for span in &self.lost {
+            write!(f, "\n\t{}", span)?;
+        }
+        Ok(())
+    }
+}
+
+/// Collect the critical assertions of `statements`, including the ones in conditional branches
+pub fn critical_assertions<T>(statements: &[ZirStatement<T>]) -> BTreeSet<SourceMetadata> {
+    let mut res = BTreeSet::new();
+    collect(statements, &mut res);
+    res
+}
+
+fn collect<T>(statements: &[ZirStatement<T>], res: &mut BTreeSet<SourceMetadata>) {
+    for s in statements {
+        match s {
+            ZirStatement::Assertion(_, RuntimeError::SourceAssertion(metadata))
+                if metadata.critical =>
+            {
+                res.insert(metadata.clone());
+            }
+            ZirStatement::IfElse(_, consequence, alternative) => {
+                collect(consequence, res);
+                collect(alternative, res);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Check that each assertion of `expected` is enforced by at least one constraint of `statements`
+pub fn check<'ast, T>(
+    expected: BTreeSet<SourceMetadata>,
+    statements: &[ir::Statement<'ast, T>],
+) -> Result<(), Error> {
+    let found: BTreeSet<_> = statements
+        .iter()
+        .filter_map(|s| match s {
+            ir::Statement::Constraint(_, _, Some(ir::RuntimeError::SourceAssertion(metadata)))
+                if metadata.critical =>
+            {
+                Some(metadata)
+            }
+            _ => None,
+        })
+        .collect();
+
+    log::debug!(
+        "Found {} critical assertion(s) in {} constraint(s)",
+        found.len(),
+        statements.iter().filter(|s| s.is_critical()).count()
+    );
+
+    let lost: Vec<_> = expected
+        .into_iter()
+        .filter(|metadata| !found.contains(metadata))
+        .collect();
+
+    match lost.is_empty() {
+        true => Ok(()),
+        false => Err(Error { lost }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_ast::common::Variable;
+    use zokrates_ast::untyped::Position;
+    use zokrates_ast::zir::BooleanExpression;
+    use zokrates_field::Bn128Field;
+
+    fn metadata(line: usize) -> SourceMetadata {
+        SourceMetadata::new("main.zok".into(), Position { line, col: 5 }).critical(true)
+    }
+
+    #[test]
+    fn collect_in_branches() {
+        let statements: Vec<ZirStatement<Bn128Field>> = vec![
+            ZirStatement::Assertion(
+                BooleanExpression::Value(true),
+                RuntimeError::SourceAssertion(metadata(1)),
+            ),
+            ZirStatement::IfElse(
+                BooleanExpression::Value(true),
+                vec![ZirStatement::Assertion(
+                    BooleanExpression::Value(true),
+                    RuntimeError::SourceAssertion(metadata(2)),
+                )],
+                vec![ZirStatement::Assertion(
+                    BooleanExpression::Value(true),
+                    RuntimeError::mock(),
+                )],
+            ),
+        ];
+
+        assert_eq!(
+            critical_assertions(&statements),
+            vec![metadata(1), metadata(2)].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn lost_assertion() {
+        let statements: Vec<ir::Statement<Bn128Field>> = vec![ir::Statement::Constraint(
+            ir::LinComb::from(Variable::one()).into(),
+            Variable::one().into(),
+            Some(ir::RuntimeError::SourceAssertion(metadata(1))),
+        )];
+
+        assert_eq!(
+            check(vec![metadata(1)].into_iter().collect(), &statements),
+            Ok(())
+        );
+        assert_eq!(
+            check(
+                vec![metadata(1), metadata(2)].into_iter().collect(),
+                &statements
+            ),
+            Err(Error {
+                lost: vec![metadata(2)]
+            })
+        );
+    }
+}
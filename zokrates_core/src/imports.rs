@@ -9,7 +9,7 @@ use crate::compile::{CompileErrorInner, CompileErrors};
 use std::collections::HashMap;
 use std::fmt;
 use std::io;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use zokrates_ast::untyped::*;
 
 use typed_arena::Arena;
@@ -122,6 +122,47 @@ impl Importer {
                         }
                     }
                 }
+                "merkle_verify_poseidon" => {
+                    use zokrates_field::Bn128Field;
+                    if T::id() != Bn128Field::id() {
+                        return Err(CompileErrorInner::ImportError(
+                            Error::new(format!(
+                                "`merkle_verify_poseidon` is expected to be compiled over `{}` curve, but found `{}`",
+                                Bn128Field::name(),
+                                T::name()
+                            ))
+                            .with_pos(Some(pos)),
+                        )
+                        .in_file(location)
+                        .into());
+                    } else {
+                        SymbolDeclaration {
+                            id: symbol.get_alias(),
+                            symbol: Symbol::Flat(FlatEmbed::MerkleVerifyPoseidon),
+                        }
+                    }
+                }
+                #[cfg(feature = "bellman")]
+                "merkle_verify_sha256" => {
+                    use zokrates_field::Bn128Field;
+                    if T::id() != Bn128Field::id() {
+                        return Err(CompileErrorInner::ImportError(
+                            Error::new(format!(
+                                "`merkle_verify_sha256` is expected to be compiled over `{}` curve, but found `{}`",
+                                Bn128Field::name(),
+                                T::name()
+                            ))
+                            .with_pos(Some(pos)),
+                        )
+                        .in_file(location)
+                        .into());
+                    } else {
+                        SymbolDeclaration {
+                            id: symbol.get_alias(),
+                            symbol: Symbol::Flat(FlatEmbed::MerkleVerifySha256),
+                        }
+                    }
+                }
                 #[cfg(feature = "ark")]
                 "snark_verify_bls12_377" => {
                     use zokrates_field::Bw6_761Field;
@@ -147,6 +188,18 @@ impl Importer {
                     id: symbol.get_alias(),
                     symbol: Symbol::Flat(FlatEmbed::Unpack),
                 },
+                "pack" => SymbolDeclaration {
+                    id: symbol.get_alias(),
+                    symbol: Symbol::Flat(FlatEmbed::Pack),
+                },
+                "unpack_strict" => SymbolDeclaration {
+                    id: symbol.get_alias(),
+                    symbol: Symbol::Flat(FlatEmbed::UnpackStrict),
+                },
+                "batch_eq" => SymbolDeclaration {
+                    id: symbol.get_alias(),
+                    symbol: Symbol::Flat(FlatEmbed::BatchEq),
+                },
                 "field_to_bool_unsafe" => SymbolDeclaration {
                     id: symbol.get_alias(),
                     symbol: Symbol::Flat(FlatEmbed::FieldToBoolUnsafe),
@@ -155,42 +208,103 @@ impl Importer {
                     id: symbol.get_alias(),
                     symbol: Symbol::Flat(FlatEmbed::BitArrayLe),
                 },
-                "u64_to_bits" => SymbolDeclaration {
+                "u64_to_bits" | "u64_to_be_bits" => SymbolDeclaration {
                     id: symbol.get_alias(),
                     symbol: Symbol::Flat(FlatEmbed::U64ToBits),
                 },
-                "u32_to_bits" => SymbolDeclaration {
+                "u64_to_le_bits" => SymbolDeclaration {
+                    id: symbol.get_alias(),
+                    symbol: Symbol::Flat(FlatEmbed::U64ToLeBits),
+                },
+                "u32_to_bits" | "u32_to_be_bits" => SymbolDeclaration {
                     id: symbol.get_alias(),
                     symbol: Symbol::Flat(FlatEmbed::U32ToBits),
                 },
-                "u16_to_bits" => SymbolDeclaration {
+                "u32_to_le_bits" => SymbolDeclaration {
+                    id: symbol.get_alias(),
+                    symbol: Symbol::Flat(FlatEmbed::U32ToLeBits),
+                },
+                "u16_to_bits" | "u16_to_be_bits" => SymbolDeclaration {
                     id: symbol.get_alias(),
                     symbol: Symbol::Flat(FlatEmbed::U16ToBits),
                 },
-                "u8_to_bits" => SymbolDeclaration {
+                "u16_to_le_bits" => SymbolDeclaration {
+                    id: symbol.get_alias(),
+                    symbol: Symbol::Flat(FlatEmbed::U16ToLeBits),
+                },
+                "u8_to_bits" | "u8_to_be_bits" => SymbolDeclaration {
                     id: symbol.get_alias(),
                     symbol: Symbol::Flat(FlatEmbed::U8ToBits),
                 },
-                "u64_from_bits" => SymbolDeclaration {
+                "u8_to_le_bits" => SymbolDeclaration {
+                    id: symbol.get_alias(),
+                    symbol: Symbol::Flat(FlatEmbed::U8ToLeBits),
+                },
+                "u64_from_bits" | "u64_from_be_bits" => SymbolDeclaration {
                     id: symbol.get_alias(),
                     symbol: Symbol::Flat(FlatEmbed::U64FromBits),
                 },
-                "u32_from_bits" => SymbolDeclaration {
+                "u64_from_le_bits" => SymbolDeclaration {
+                    id: symbol.get_alias(),
+                    symbol: Symbol::Flat(FlatEmbed::U64FromLeBits),
+                },
+                "u32_from_bits" | "u32_from_be_bits" => SymbolDeclaration {
                     id: symbol.get_alias(),
                     symbol: Symbol::Flat(FlatEmbed::U32FromBits),
                 },
-                "u16_from_bits" => SymbolDeclaration {
+                "u32_from_le_bits" => SymbolDeclaration {
+                    id: symbol.get_alias(),
+                    symbol: Symbol::Flat(FlatEmbed::U32FromLeBits),
+                },
+                "u16_from_bits" | "u16_from_be_bits" => SymbolDeclaration {
                     id: symbol.get_alias(),
                     symbol: Symbol::Flat(FlatEmbed::U16FromBits),
                 },
-                "u8_from_bits" => SymbolDeclaration {
+                "u16_from_le_bits" => SymbolDeclaration {
+                    id: symbol.get_alias(),
+                    symbol: Symbol::Flat(FlatEmbed::U16FromLeBits),
+                },
+                "u8_from_bits" | "u8_from_be_bits" => SymbolDeclaration {
                     id: symbol.get_alias(),
                     symbol: Symbol::Flat(FlatEmbed::U8FromBits),
                 },
+                "u8_from_le_bits" => SymbolDeclaration {
+                    id: symbol.get_alias(),
+                    symbol: Symbol::Flat(FlatEmbed::U8FromLeBits),
+                },
+                "i8_to_bits" => SymbolDeclaration {
+                    id: symbol.get_alias(),
+                    symbol: Symbol::Flat(FlatEmbed::I8ToBits),
+                },
+                "i16_to_bits" => SymbolDeclaration {
+                    id: symbol.get_alias(),
+                    symbol: Symbol::Flat(FlatEmbed::I16ToBits),
+                },
+                "i32_to_bits" => SymbolDeclaration {
+                    id: symbol.get_alias(),
+                    symbol: Symbol::Flat(FlatEmbed::I32ToBits),
+                },
+                "i8_from_bits" => SymbolDeclaration {
+                    id: symbol.get_alias(),
+                    symbol: Symbol::Flat(FlatEmbed::I8FromBits),
+                },
+                "i16_from_bits" => SymbolDeclaration {
+                    id: symbol.get_alias(),
+                    symbol: Symbol::Flat(FlatEmbed::I16FromBits),
+                },
+                "i32_from_bits" => SymbolDeclaration {
+                    id: symbol.get_alias(),
+                    symbol: Symbol::Flat(FlatEmbed::I32FromBits),
+                },
+                "unconstrained_witness" => SymbolDeclaration {
+                    id: symbol.get_alias(),
+                    symbol: Symbol::Flat(FlatEmbed::UnconstrainedWitness),
+                },
                 "FIELD_SIZE_IN_BITS" => SymbolDeclaration {
                     id: symbol.get_alias(),
                     symbol: Symbol::Here(SymbolDefinition::Constant(
                         ConstantDefinition {
+                            public: false,
                             ty: UnresolvedType::Uint(32).into(),
                             expression: Expression::U32Constant(T::get_required_bits() as u32)
                                 .into(),
@@ -245,6 +359,10 @@ impl Importer {
                             id: alias,
                             symbol: Symbol::There(
                                 SymbolImport::with_id_in_module(symbol.id, new_location)
+                                    .relative(matches!(
+                                        module_id.components().next(),
+                                        Some(Component::CurDir | Component::ParentDir)
+                                    ))
                                     .start_end(pos.0, pos.1),
                             ),
                         }
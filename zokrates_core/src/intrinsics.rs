@@ -0,0 +1,85 @@
+//! Compile-time constants describing the field the program is compiled for.
+//!
+//! They let a single source file branch on the target field, for example to pick curve parameters:
+//! `const field D = if FIELD_SIZE_IN_BITS == 254 { 168696 } else { 19257038036680949359750312669786877991949435402254120286184196891950884077233 };`.
+//! As the checker is instantiated for a given field, they are resolved to values right away, and the conditions they
+//! appear in are folded by propagation before loops are unrolled.
+
+use num_bigint::BigUint;
+use zokrates_ast::typed::{
+    FieldElementExpression, IntExpression, TypedExpression, UExpressionInner,
+};
+use zokrates_field::Field;
+
+/// The modulus of the field, as an integer which can be compared to literals. It has no field or uint type, as it does
+/// not fit in a field element, and is the same value as `FIELD_MAX + 1` in arithmetic.
+pub const FIELD_MODULUS: &str = "FIELD_MODULUS";
+/// The largest field element, `p - 1`, as a `field`
+pub const FIELD_MAX: &str = "FIELD_MAX";
+/// The number of bits required to represent any field element, as a `u32`
+pub const FIELD_SIZE_IN_BITS: &str = "FIELD_SIZE_IN_BITS";
+
+pub fn field_modulus<T: Field>() -> BigUint {
+    T::max_value().to_biguint() + 1u32
+}
+
+pub fn field_size_in_bits<T: Field>() -> u32 {
+    T::get_required_bits() as u32
+}
+
+/// The value of the intrinsic called `name` for the field `T`, if any
+pub fn intrinsic<'ast, T: Field>(name: &str) -> Option<TypedExpression<'ast, T>> {
+    match name {
+        FIELD_MODULUS => Some(IntExpression::Value(field_modulus::<T>()).into()),
+        FIELD_MAX => Some(FieldElementExpression::Number(T::max_value()).into()),
+        FIELD_SIZE_IN_BITS => Some(
+            UExpressionInner::Value(field_size_in_bits::<T>() as u128)
+                .annotate(32)
+                .into(),
+        ),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_ast::typed::{
+        types::{Type, UBitwidth},
+        Typed,
+    };
+    use zokrates_field::{Bls12_381Field, Bn128Field};
+
+    #[test]
+    fn depends_on_field() {
+        assert_eq!(field_size_in_bits::<Bn128Field>(), 254);
+        assert_eq!(field_size_in_bits::<Bls12_381Field>(), 255);
+        assert_eq!(
+            field_modulus::<Bn128Field>().to_string(),
+            "21888242871839275222246405745257275088548364400416034343698204186575808495617"
+        );
+    }
+
+    #[test]
+    fn typed() {
+        assert_eq!(
+            intrinsic::<Bn128Field>(FIELD_MAX).unwrap().get_type(),
+            Type::FieldElement
+        );
+        assert_eq!(
+            intrinsic::<Bn128Field>(FIELD_SIZE_IN_BITS)
+                .unwrap()
+                .get_type(),
+            Type::Uint(UBitwidth::B32)
+        );
+        assert_eq!(
+            intrinsic::<Bn128Field>(FIELD_MAX),
+            Some(FieldElementExpression::Number(Bn128Field::from(-1)).into())
+        );
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(intrinsic::<Bn128Field>("FIELD"), None);
+    }
+}
@@ -1,7 +1,19 @@
 #![feature(box_patterns, box_syntax)]
 
+pub mod cache;
+pub mod commitment;
 pub mod compile;
+pub mod critical;
 pub mod imports;
+mod intrinsics;
 mod macros;
+pub mod manifest;
 mod optimizer;
+pub mod pin;
+mod private_outputs;
+pub mod provenance;
 mod semantics;
+pub mod sink;
+mod suggestions;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
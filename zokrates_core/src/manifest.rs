@@ -0,0 +1,442 @@
+//! Module containing build manifests, used to audit that a compiled program was built from given sources.
+//!
+//! A manifest records what a compilation depends on: the version of the compiler, the curve, the configuration and
+//! the SHA-256 hash of every module of the import graph, along with the hash of the resulting constraint system.
+//! Compiling the program again with the recorded curve and configuration, and comparing the two manifests, tells
+//! which source drifted.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use zokrates_ast::ir::{self, ProgramHash};
+use zokrates_common::{CompileConfig, Resolver};
+use zokrates_field::Field;
+
+/// A module of the import graph of a program
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SourceFile {
+    /// The SHA-256 hash of the source of the module
+    pub sha256: String,
+    /// The locations of the modules it imports
+    pub imports: BTreeSet<PathBuf>,
+}
+
+impl SourceFile {
+    fn new(source: &str) -> Self {
+        SourceFile {
+            sha256: Sha256::digest(source.as_bytes())
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect(),
+            imports: BTreeSet::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Manifest {
+    /// The version of the compiler which built the program
+    pub compiler: String,
+    /// The path to the entry point of the program, relative to the directory of the manifest once written
+    pub source: PathBuf,
+    pub curve: String,
+    pub config: CompileConfig,
+    /// The modules of the import graph, by location, the entry point included
+    pub sources: BTreeMap<PathBuf, SourceFile>,
+    pub constraint_system: ProgramHash,
+}
+
+impl Manifest {
+    /// The manifest of the build of `prog` from the entry point at `source`, whose import graph is `sources`
+    pub(crate) fn new<T: Field>(
+        source: PathBuf,
+        config: CompileConfig,
+        sources: BTreeMap<PathBuf, SourceFile>,
+        prog: &ir::Prog<T>,
+    ) -> Self {
+        Manifest {
+            compiler: env!("CARGO_PKG_VERSION").to_string(),
+            source,
+            curve: T::name().to_string(),
+            config,
+            sources,
+            constraint_system: prog.hash(),
+        }
+    }
+
+    /// This manifest with its paths relative to `root`, so that it does not depend on the directory the program was
+    /// compiled from. Paths outside of `root`, such as the ones of the standard library, are canonicalized
+    pub fn relative_to(self, root: &Path) -> io::Result<Self> {
+        let root = root.canonicalize()?;
+        let relative = |path: &Path| -> io::Result<PathBuf> {
+            let path = path.canonicalize()?;
+            Ok(path
+                .strip_prefix(&root)
+                .map(Path::to_path_buf)
+                .unwrap_or(path))
+        };
+
+        let source = relative(&self.source)?;
+        let sources = self
+            .sources
+            .into_iter()
+            .map(|(location, file)| {
+                let imports = file
+                    .imports
+                    .iter()
+                    .map(|import| relative(import))
+                    .collect::<io::Result<_>>()?;
+                Ok((relative(&location)?, SourceFile { imports, ..file }))
+            })
+            .collect::<io::Result<_>>()?;
+
+        Ok(Manifest {
+            source,
+            sources,
+            ..self
+        })
+    }
+
+    /// The differences between this manifest and the manifest of the `current` build of the program
+    pub fn drift(&self, current: &Manifest) -> Vec<Drift> {
+        let mut drift = vec![];
+
+        if self.compiler != current.compiler {
+            drift.push(Drift::Compiler {
+                recorded: self.compiler.clone(),
+                current: current.compiler.clone(),
+            });
+        }
+
+        for (location, recorded) in &self.sources {
+            match current.sources.get(location) {
+                Some(source) if source.sha256 != recorded.sha256 => {
+                    drift.push(Drift::Source(location.clone()))
+                }
+                Some(_) => {}
+                None => drift.push(Drift::Removed(location.clone())),
+            }
+        }
+
+        drift.extend(
+            current
+                .sources
+                .keys()
+                .filter(|location| !self.sources.contains_key(*location))
+                .map(|location| Drift::Added(location.clone())),
+        );
+
+        if self.constraint_system != current.constraint_system {
+            drift.push(Drift::ConstraintSystem {
+                recorded: self.constraint_system,
+                current: current.constraint_system,
+            });
+        }
+
+        drift
+    }
+}
+
+/// A difference between a manifest and the current build of a program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Drift {
+    Compiler {
+        recorded: String,
+        current: String,
+    },
+    /// The source of a module changed
+    Source(PathBuf),
+    /// A module is not part of the import graph anymore
+    Removed(PathBuf),
+    /// A module joined the import graph
+    Added(PathBuf),
+    ConstraintSystem {
+        recorded: ProgramHash,
+        current: ProgramHash,
+    },
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Drift::Compiler { recorded, current } => write!(
+                f,
+                "compiler version changed from `{}` to `{}`",
+                recorded, current
+            ),
+            Drift::Source(location) => write!(f, "source {} changed", location.display()),
+            Drift::Removed(location) => {
+                write!(f, "source {} is not imported anymore", location.display())
+            }
+            Drift::Added(location) => write!(f, "source {} is now imported", location.display()),
+            Drift::ConstraintSystem { recorded, current } => write!(
+                f,
+                "constraint system changed from {} to {}",
+                recorded, current
+            ),
+        }
+    }
+}
+
+/// A resolver which records the modules it resolves, and which modules import them, see `compile_observed`
+pub(crate) struct RecordingResolver<'a, E> {
+    inner: Option<&'a dyn Resolver<E>>,
+    sources: RefCell<BTreeMap<PathBuf, SourceFile>>,
+}
+
+impl<'a, E> RecordingResolver<'a, E> {
+    /// Record the modules resolved by `inner` for the entry point `source` at `location`
+    pub fn new(inner: Option<&'a dyn Resolver<E>>, location: &Path, source: &str) -> Self {
+        RecordingResolver {
+            inner,
+            sources: RefCell::new(BTreeMap::from([(
+                location.to_path_buf(),
+                SourceFile::new(source),
+            )])),
+        }
+    }
+
+    /// The resolver to compile with, which is only set if there is an inner resolver
+    pub fn resolver(&self) -> Option<&dyn Resolver<E>> {
+        self.inner.map(|_| self as &dyn Resolver<E>)
+    }
+
+    /// The modules of the import graph, the entry point included
+    pub fn into_sources(self) -> BTreeMap<PathBuf, SourceFile> {
+        self.sources.into_inner()
+    }
+}
+
+impl<'a, E> Resolver<E> for RecordingResolver<'a, E> {
+    fn resolve(
+        &self,
+        current_location: PathBuf,
+        import_location: PathBuf,
+    ) -> Result<(String, PathBuf), E> {
+        let (source, location) = self
+            .inner
+            .expect("a recording resolver is only used with an inner resolver")
+            .resolve(current_location.clone(), import_location)?;
+
+        let mut sources = self.sources.borrow_mut();
+        if let Some(importer) = sources.get_mut(&current_location) {
+            importer.imports.insert(location.clone());
+        }
+        sources
+            .entry(location.clone())
+            .or_insert_with(|| SourceFile::new(&source));
+
+        Ok((source, location))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::{compile_observed, Observers};
+    use std::io;
+    use typed_arena::Arena;
+    use zokrates_field::Bn128Field;
+
+    struct MemoryResolver(RefCell<BTreeMap<PathBuf, String>>);
+
+    impl MemoryResolver {
+        fn touch(&self, location: &str, source: &str) {
+            self.0
+                .borrow_mut()
+                .insert(PathBuf::from(location), source.to_string());
+        }
+    }
+
+    impl Resolver<io::Error> for MemoryResolver {
+        fn resolve(
+            &self,
+            _: PathBuf,
+            import_location: PathBuf,
+        ) -> Result<(String, PathBuf), io::Error> {
+            self.0
+                .borrow()
+                .get(&import_location)
+                .cloned()
+                .map(|source| (source, import_location))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "not found"))
+        }
+    }
+
+    const MAIN: &str = r#"
+        from "./foo" import foo;
+        def main(field a) -> field {
+            return foo(a);
+        }
+    "#;
+
+    fn resolver() -> MemoryResolver {
+        let resolver = MemoryResolver(RefCell::new(BTreeMap::new()));
+        resolver.touch(
+            "./foo",
+            r#"
+                from "./bar" import bar;
+                def foo(field a) -> field {
+                    return bar(a) + 1;
+                }
+            "#,
+        );
+        resolver.touch(
+            "./bar",
+            r#"
+                def bar(field a) -> field {
+                    return a * a;
+                }
+            "#,
+        );
+        resolver
+    }
+
+    fn manifest(resolver: &MemoryResolver) -> Manifest {
+        let arena = Arena::new();
+        compile_observed::<Bn128Field, io::Error>(
+            MAIN.to_string(),
+            "main".into(),
+            Some(resolver as &dyn Resolver<io::Error>),
+            CompileConfig::default(),
+            &arena,
+            Observers {
+                manifest: true,
+                ..Observers::default()
+            },
+        )
+        .unwrap()
+        .1
+        .manifest
+        .unwrap()
+    }
+
+    #[test]
+    fn record_import_graph() {
+        let recorded = manifest(&resolver());
+
+        assert_eq!(recorded.curve, "bn128");
+        assert_eq!(
+            recorded.sources.keys().collect::<Vec<_>>(),
+            vec![
+                &PathBuf::from("./bar"),
+                &PathBuf::from("./foo"),
+                &PathBuf::from("main")
+            ]
+        );
+        assert_eq!(
+            recorded.sources[&PathBuf::from("main")].imports,
+            BTreeSet::from([PathBuf::from("./foo")])
+        );
+        assert_eq!(
+            recorded.sources[&PathBuf::from("./foo")].imports,
+            BTreeSet::from([PathBuf::from("./bar")])
+        );
+        assert!(recorded.sources[&PathBuf::from("./bar")].imports.is_empty());
+
+        // the manifest survives a round trip
+        let serialized = serde_json::to_string(&recorded).unwrap();
+        let deserialized: Manifest = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.drift(&recorded), vec![]);
+        assert_eq!(serde_json::to_string(&deserialized).unwrap(), serialized);
+    }
+
+    #[test]
+    fn tampered_import_is_named() {
+        let resolver = resolver();
+        let recorded = manifest(&resolver);
+
+        // a change which does not affect the constraints is still reported
+        resolver.touch(
+            "./bar",
+            r#"
+                // squares `a`
+                def bar(field a) -> field {
+                    return a * a;
+                }
+            "#,
+        );
+        let current = manifest(&resolver);
+
+        assert_eq!(
+            recorded.drift(&current),
+            vec![Drift::Source(PathBuf::from("./bar"))]
+        );
+
+        resolver.touch(
+            "./bar",
+            r#"
+                def bar(field a) -> field {
+                    return a * a * a;
+                }
+            "#,
+        );
+        let current = manifest(&resolver);
+
+        let drift = recorded.drift(&current);
+        assert_eq!(
+            drift,
+            vec![
+                Drift::Source(PathBuf::from("./bar")),
+                Drift::ConstraintSystem {
+                    recorded: recorded.constraint_system,
+                    current: current.constraint_system
+                }
+            ]
+        );
+        assert_eq!(drift[0].to_string(), "source ./bar changed");
+    }
+
+    #[test]
+    fn relative_to_root() {
+        use zokrates_fs_resolver::FileSystemResolver;
+
+        let root = std::env::temp_dir().join(format!("zokrates_manifest_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("lib")).unwrap();
+        std::fs::write(root.join("main.zok"), MAIN.replace("./foo", "./lib/foo")).unwrap();
+        std::fs::write(
+            root.join("lib").join("foo.zok"),
+            "def foo(field a) -> field { return a + 1; }",
+        )
+        .unwrap();
+
+        let main = root.join("main.zok");
+        let source = std::fs::read_to_string(&main).unwrap();
+        let resolver = FileSystemResolver::default();
+        let arena = Arena::new();
+        let recorded = compile_observed::<Bn128Field, io::Error>(
+            source,
+            main,
+            Some(&resolver as &dyn Resolver<io::Error>),
+            CompileConfig::default(),
+            &arena,
+            Observers {
+                manifest: true,
+                ..Observers::default()
+            },
+        )
+        .unwrap()
+        .1
+        .manifest
+        .unwrap()
+        .relative_to(&root)
+        .unwrap();
+
+        assert_eq!(recorded.source, PathBuf::from("main.zok"));
+        assert_eq!(
+            recorded.sources.keys().collect::<Vec<_>>(),
+            vec![&PathBuf::from("lib/foo.zok"), &PathBuf::from("main.zok")]
+        );
+        assert_eq!(
+            recorded.sources[&PathBuf::from("main.zok")].imports,
+            BTreeSet::from([PathBuf::from("lib/foo.zok")])
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
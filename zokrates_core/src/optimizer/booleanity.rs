@@ -0,0 +1,179 @@
+//! Module containing the `BooleanityOptimizer` to remove redundant booleanity constraints
+//!
+//! A booleanity constraint forces a variable `x` to be 0 or 1. The flattener writes it `x * x == x`, but the same
+//! constraint can be written in many ways, such as `(2 * x - 2) * x == 0`: a constraint `L * R == C` is recognized as
+//! a booleanity constraint on `x` when `L`, `R` and `C` only involve `x` and `~one`, and `L * R - C` is a non-zero
+//! multiple of `x * x - x`.
+//!
+//! One booleanity constraint on a variable is enough, so the following ones are removed. Directives do not constrain
+//! their outputs: the booleanity constraints following a bit decomposition are what makes its outputs bits, so the
+//! first one on each output is always kept.
+
+use std::collections::HashSet;
+use zokrates_ast::ir::folder::*;
+use zokrates_ast::ir::*;
+use zokrates_field::Field;
+
+// the coefficients `(a, b)` of `a * x + b` if `l` only involves `x` and `~one`, `x` being set to the first variable
+// found if it is not known yet
+fn affine<T: Field>(l: &LinComb<T>, x: &mut Option<Variable>) -> Option<(T, T)> {
+    let mut res = (T::zero(), T::zero());
+
+    for (v, c) in l.clone().into_canonical().0 {
+        if v == Variable::one() {
+            res.1 = c;
+        } else {
+            match x {
+                Some(x) if *x != v => return None,
+                Some(_) => {}
+                None => *x = Some(v),
+            }
+            res.0 = c;
+        }
+    }
+
+    Some(res)
+}
+
+/// The variable constrained to be 0 or 1 by `s`, if `s` is a booleanity constraint
+pub fn booleanity<T: Field>(s: &Statement<T>) -> Option<Variable> {
+    match s {
+        Statement::Constraint(quad, lin, _) => {
+            let mut x = None;
+            let (a, b) = affine(&quad.left, &mut x)?;
+            let (c, d) = affine(&quad.right, &mut x)?;
+            let (e, f) = affine(lin, &mut x)?;
+
+            // (a * x + b) * (c * x + d) - (e * x + f) == k * (x * x - x)
+            let k = a.clone() * c.clone();
+            let linear = a * d.clone() + b.clone() * c - e;
+            let constant = b * d - f;
+
+            match k != T::zero() && linear + k == T::zero() && constant == T::zero() {
+                true => x,
+                false => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct BooleanityOptimizer {
+    constrained: HashSet<Variable>,
+}
+
+impl<'ast, T: Field> Folder<'ast, T> for BooleanityOptimizer {
+    fn fold_statement(&mut self, s: Statement<'ast, T>) -> Vec<Statement<'ast, T>> {
+        match booleanity(&s) {
+            Some(x) => {
+                let first = self.constrained.insert(x);
+                match first || s.is_critical() {
+                    true => vec![s],
+                    // copies of critical constraints are kept so that each assertion can be audited
+                    false => vec![],
+                }
+            }
+            None => fold_statement(self, s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_field::Bn128Field;
+
+    fn count(p: &Prog<Bn128Field>) -> usize {
+        p.statements
+            .iter()
+            .filter(|s| booleanity(s).is_some())
+            .count()
+    }
+
+    #[test]
+    fn shapes() {
+        let x = Variable::new(0);
+        let y = Variable::new(1);
+
+        // x * x == x
+        let s: Statement<Bn128Field> =
+            Statement::constraint(QuadComb::from_linear_combinations(x.into(), x.into()), x);
+        assert_eq!(booleanity(&s), Some(x));
+
+        // (x - 1) * x == 0
+        let s: Statement<Bn128Field> = Statement::constraint(
+            QuadComb::from_linear_combinations(LinComb::from(x) - LinComb::one(), LinComb::from(x)),
+            LinComb::zero(),
+        );
+        assert_eq!(booleanity(&s), Some(x));
+
+        // (2 - 2 * x) * (3 * x) == 0, scaled and with the terms out of order
+        let s: Statement<Bn128Field> = Statement::constraint(
+            QuadComb::from_linear_combinations(
+                LinComb::summand(2, Variable::one()) - LinComb::summand(2, x),
+                LinComb::summand(3, x),
+            ),
+            LinComb::zero(),
+        );
+        assert_eq!(booleanity(&s), Some(x));
+
+        // x * x == 2 * x is not a booleanity constraint
+        let s: Statement<Bn128Field> = Statement::constraint(
+            QuadComb::from_linear_combinations(x.into(), x.into()),
+            LinComb::summand(2, x),
+        );
+        assert_eq!(booleanity(&s), None);
+
+        // neither is x * y == x
+        let s: Statement<Bn128Field> =
+            Statement::constraint(QuadComb::from_linear_combinations(x.into(), y.into()), x);
+        assert_eq!(booleanity(&s), None);
+
+        // nor x * x == 0
+        let s: Statement<Bn128Field> = Statement::constraint(
+            QuadComb::from_linear_combinations(x.into(), x.into()),
+            LinComb::zero(),
+        );
+        assert_eq!(booleanity(&s), None);
+    }
+
+    #[test]
+    fn remove_duplicates() {
+        let x = Variable::new(0);
+        let y = Variable::new(1);
+
+        let p: Prog<Bn128Field> = Prog {
+            arguments: vec![Parameter::private(x), Parameter::private(y)],
+            return_count: 0,
+            public_layout: vec![],
+            variable_ordering: None,
+            statements: vec![
+                // x * x == x
+                Statement::constraint(QuadComb::from_linear_combinations(x.into(), x.into()), x),
+                // y * y == y
+                Statement::constraint(QuadComb::from_linear_combinations(y.into(), y.into()), y),
+                // x * (1 - x) == 0
+                Statement::constraint(
+                    QuadComb::from_linear_combinations(
+                        LinComb::from(x),
+                        LinComb::one() - LinComb::from(x),
+                    ),
+                    LinComb::zero(),
+                ),
+                // (5 * x) * x == 5 * x
+                Statement::constraint(
+                    QuadComb::from_linear_combinations(LinComb::summand(5, x), x.into()),
+                    LinComb::summand(5, x),
+                ),
+            ],
+        };
+
+        assert_eq!(count(&p), 4);
+
+        let optimized = BooleanityOptimizer::default().fold_program(p.clone());
+
+        assert_eq!(count(&optimized), 2);
+        assert_eq!(optimized.statements, p.statements[..2].to_vec());
+    }
+}
@@ -41,8 +41,9 @@ impl<'ast, T: Field> Folder<'ast, T> for DuplicateOptimizer {
     fn fold_statement(&mut self, s: Statement<'ast, T>) -> Vec<Statement<'ast, T>> {
         let hashed = hash(&s);
         let result = match self.seen.get(&hashed) {
-            Some(_) => vec![],
-            None => vec![s],
+            // copies of critical constraints are kept so that each assertion can be audited
+            Some(_) if !s.is_critical() => vec![],
+            _ => vec![s],
         };
 
         self.seen.insert(hashed);
@@ -76,6 +77,8 @@ mod tests {
                 ),
             ],
             return_count: 0,
+            public_layout: vec![],
+            variable_ordering: None,
             arguments: vec![],
         };
 
@@ -112,6 +115,8 @@ mod tests {
                 constraint.clone(),
             ],
             return_count: 0,
+            public_layout: vec![],
+            variable_ordering: None,
             arguments: vec![],
         };
 
@@ -127,6 +132,8 @@ mod tests {
                 ),
             ],
             return_count: 0,
+            public_layout: vec![],
+            variable_ordering: None,
             arguments: vec![],
         };
 
@@ -135,4 +142,32 @@ mod tests {
             expected
         );
     }
+
+    #[test]
+    fn keep_critical_duplicates() {
+        use zokrates_ast::common::SourceMetadata;
+
+        let critical = Statement::Constraint(
+            LinComb::summand(3, Variable::new(3)).into(),
+            LinComb::one(),
+            Some(RuntimeError::SourceAssertion(
+                SourceMetadata::default().critical(true),
+            )),
+        );
+
+        let p: Prog<Bn128Field> = Prog {
+            statements: vec![critical.clone(), critical.clone()],
+            return_count: 0,
+            public_layout: vec![],
+            variable_ordering: None,
+            arguments: vec![],
+        };
+
+        let expected = p.clone();
+
+        assert_eq!(
+            DuplicateOptimizer::default().fold_program(p).collect(),
+            expected
+        );
+    }
 }
@@ -0,0 +1,77 @@
+//! Module containing the `Linearizer` to downgrade the constraints with a constant side to linear constraints
+//!
+//! The flattening often produces constraints such as `(2 * ~one) * (x + y) == z`, which are linear but written as a
+//! product. The constant is folded into the coefficients of the other side, and the constraint is rewritten in the
+//! canonical linear form `(~one) * (2 * x + 2 * y) == z`, which the R1CS export recognizes with `QuadComb::is_linear`
+//! and writes without a multiplication.
+
+use zokrates_ast::ir::folder::*;
+use zokrates_ast::ir::*;
+use zokrates_field::Field;
+
+#[derive(Default)]
+pub struct Linearizer;
+
+impl<'ast, T: Field> Folder<'ast, T> for Linearizer {
+    fn fold_statement(&mut self, s: Statement<'ast, T>) -> Vec<Statement<'ast, T>> {
+        match s {
+            Statement::Constraint(quad, lin, error) => match quad.try_linear() {
+                Ok(l) => vec![Statement::Constraint(l.into(), lin, error)],
+                Err(quad) => vec![Statement::Constraint(quad, lin, error)],
+            },
+            _ => fold_statement(self, s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_field::Bn128Field;
+
+    fn linearize(quad: QuadComb<Bn128Field>) -> QuadComb<Bn128Field> {
+        match Linearizer
+            .fold_statement(Statement::constraint(quad, Variable::public(0)))
+            .pop()
+        {
+            Some(Statement::Constraint(quad, _, _)) => quad,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn constant_side() {
+        let x = LinComb::from(Variable::new(0));
+        let y = LinComb::from(Variable::new(1));
+        let two = LinComb::summand(2, Variable::one());
+
+        // (2 * ~one) * (x + y) == (~one) * (2 * x + 2 * y)
+        let quad = QuadComb::from_linear_combinations(two.clone(), x.clone() + y.clone());
+        assert!(!quad.is_linear());
+        assert_eq!(
+            linearize(quad),
+            QuadComb::from_linear_combinations(
+                LinComb::one(),
+                LinComb::summand(2, Variable::new(0)) + LinComb::summand(2, Variable::new(1))
+            )
+        );
+
+        // (x) * (2 * ~one) == (~one) * (2 * x)
+        let quad = QuadComb::from_linear_combinations(x.clone(), two);
+        assert_eq!(
+            linearize(quad),
+            QuadComb::from_linear_combinations(
+                LinComb::one(),
+                LinComb::summand(2, Variable::new(0))
+            )
+        );
+
+        // a zero side makes the product zero
+        let quad = QuadComb::from_linear_combinations(LinComb::zero(), x.clone());
+        assert_eq!(linearize(quad), QuadComb::from(LinComb::zero()));
+
+        // products of variables are kept
+        let quad = QuadComb::from_linear_combinations(x, y);
+        assert_eq!(linearize(quad.clone()), quad);
+    }
+}
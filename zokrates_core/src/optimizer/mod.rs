@@ -4,58 +4,160 @@
 //! @author Thibaut Schaeffer <thibaut@schaeff.fr>
 //! @date 2018
 
+mod booleanity;
 mod canonicalizer;
 mod directive;
 mod duplicate;
+mod linearizer;
 mod redefinition;
+mod stats;
 mod tautology;
 
+pub use self::booleanity::booleanity;
+use self::booleanity::BooleanityOptimizer;
 use self::canonicalizer::Canonicalizer;
 use self::directive::DirectiveOptimizer;
 use self::duplicate::DuplicateOptimizer;
+use self::linearizer::Linearizer;
 use self::redefinition::RedefinitionOptimizer;
+pub use self::stats::{MinimizationReport, PassStats, StatsRecorder};
 use self::tautology::TautologyOptimizer;
+use crate::provenance::ProvenanceTracker;
 
+use zokrates_ast::ir::folder::Folder;
 use zokrates_ast::ir::{ProgIterator, Statement};
+use zokrates_common::pipeline::{PassId, Pipeline, Stage};
 use zokrates_field::Field;
 
+/// An ir program whose statements are optimized lazily
+pub type IrProgram<'ast, T> =
+    ProgIterator<'ast, T, Box<dyn Iterator<Item = Statement<'ast, T>> + 'ast>>;
+
+// apply `folder` to the arguments, then to the statements as they are consumed
+fn apply_folder<'ast, T: Field, F: Folder<'ast, T> + 'ast>(
+    p: IrProgram<'ast, T>,
+    mut folder: F,
+) -> IrProgram<'ast, T> {
+    let arguments = p
+        .arguments
+        .into_iter()
+        .map(|a| folder.fold_argument(a))
+        .collect();
+
+    ProgIterator {
+        arguments,
+        statements: Box::new(
+            p.statements
+                .into_iter()
+                .flat_map(move |s| folder.fold_statement(s)),
+        ),
+        return_count: p.return_count,
+        public_layout: p.public_layout,
+        variable_ordering: p.variable_ordering,
+    }
+}
+
+// apply `folder` to `p`, following the provenance of its statements with `tracker` if any
+fn apply<'ast, T: Field, F: Folder<'ast, T> + 'ast>(
+    p: IrProgram<'ast, T>,
+    mut folder: F,
+    id: PassId,
+    tracker: Option<&ProvenanceTracker>,
+) -> IrProgram<'ast, T> {
+    match tracker {
+        Some(tracker) => tracker.trace(p, &mut folder, id),
+        None => apply_folder(p, folder),
+    }
+}
+
+// apply the ir pass `id` to `p`
+fn apply_pass<'ast, T: Field>(
+    p: IrProgram<'ast, T>,
+    id: PassId,
+    tracker: Option<&ProvenanceTracker>,
+) -> IrProgram<'ast, T> {
+    match id {
+        PassId::Redefinition => {
+            let mut optimizer = RedefinitionOptimizer::init(&p);
+            match tracker {
+                Some(tracker) => {
+                    let p = tracker.trace(p, &mut optimizer, id);
+                    tracker.record_synonyms(optimizer.substitution());
+                    p
+                }
+                None => apply_folder(p, optimizer),
+            }
+        }
+        PassId::Linearize => apply(p, Linearizer::default(), id, tracker),
+        PassId::Tautology => apply(p, TautologyOptimizer::default(), id, tracker),
+        PassId::Canonicalize => apply(p, Canonicalizer::default(), id, tracker),
+        PassId::Directive => apply(p, DirectiveOptimizer::default(), id, tracker),
+        PassId::Booleanity => apply(p, BooleanityOptimizer::default(), id, tracker),
+        PassId::Dedup => apply(p, DuplicateOptimizer::default(), id, tracker),
+        id => unreachable!("`{}` is not an ir pass", id),
+    }
+}
+
+// record the statements of `p` coming out of `pass` in `recorder`, if any
+fn record<'ast, T: Field>(
+    p: IrProgram<'ast, T>,
+    pass: Option<PassId>,
+    recorder: Option<&StatsRecorder>,
+) -> IrProgram<'ast, T> {
+    match recorder {
+        Some(recorder) => {
+            let mut record = recorder.record(pass, &p.arguments);
+            ProgIterator {
+                statements: Box::new(p.statements.inspect(move |s| record(s))),
+                ..p
+            }
+        }
+        None => p,
+    }
+}
+
+/// Optimize `p` with the ir passes of `pipeline`
+///
+/// The statistics of the program before optimization and after each pass are recorded in `recorder` as its statements
+/// are consumed, if any. The provenance of the statements is followed with `tracker`, if any, in which case each pass
+/// goes through the whole program before the next one starts.
 pub fn optimize<'ast, T: Field, I: IntoIterator<Item = Statement<'ast, T>>>(
     p: ProgIterator<'ast, T, I>,
-) -> ProgIterator<'ast, T, impl IntoIterator<Item = Statement<'ast, T>>> {
-    // remove redefinitions
-    log::debug!("Optimizer: Remove redefinitions and tautologies and directives and duplicates");
-
-    // define all optimizer steps
-    let mut redefinition_optimizer = RedefinitionOptimizer::init(&p);
-    let mut tautologies_optimizer = TautologyOptimizer::default();
-    let mut directive_optimizer = DirectiveOptimizer::default();
-    let mut canonicalizer = Canonicalizer::default();
-    let mut duplicate_optimizer = DuplicateOptimizer::default();
-
-    use zokrates_ast::ir::folder::Folder;
-
-    let r = ProgIterator {
-        arguments: p
-            .arguments
-            .into_iter()
-            .map(|a| redefinition_optimizer.fold_argument(a))
-            .map(|a| {
-                <TautologyOptimizer as Folder<T>>::fold_argument(&mut tautologies_optimizer, a)
-            })
-            .map(|a| directive_optimizer.fold_argument(a))
-            .map(|a| <DuplicateOptimizer as Folder<T>>::fold_argument(&mut duplicate_optimizer, a))
-            .collect(),
-        statements: p
-            .statements
-            .into_iter()
-            .flat_map(move |s| redefinition_optimizer.fold_statement(s))
-            .flat_map(move |s| tautologies_optimizer.fold_statement(s))
-            .flat_map(move |s| canonicalizer.fold_statement(s))
-            .flat_map(move |s| directive_optimizer.fold_statement(s))
-            .flat_map(move |s| duplicate_optimizer.fold_statement(s)),
+    pipeline: &Pipeline,
+    recorder: Option<&StatsRecorder>,
+    tracker: Option<&ProvenanceTracker>,
+) -> IrProgram<'ast, T>
+where
+    I::IntoIter: 'ast,
+{
+    log::debug!(
+        "Optimizer: Run {}{}",
+        pipeline
+            .stage(Stage::Ir)
+            .map(|id| id.name())
+            .collect::<Vec<_>>()
+            .join(", "),
+        if tracker.is_some() {
+            " with provenance"
+        } else {
+            ""
+        }
+    );
+
+    let p = ProgIterator {
+        arguments: p.arguments,
+        statements: Box::new(p.statements.into_iter()) as Box<dyn Iterator<Item = _> + 'ast>,
         return_count: p.return_count,
+        public_layout: p.public_layout,
+        variable_ordering: p.variable_ordering,
     };
 
+    let p = record(p, None, recorder);
+
+    let r = pipeline.stage(Stage::Ir).fold(p, |p, id| {
+        record(apply_pass(p, id, tracker), Some(id), recorder)
+    });
+
     log::debug!("Done");
     r
 }
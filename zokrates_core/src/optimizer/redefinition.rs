@@ -32,11 +32,13 @@
 //        as the output, and insert `(v, o)` into `s` for `(v, o)` in `(d.outputs, res)`
 //      - else, for each variable `v` introduced, insert `v` into `i`
 // - For each constraint `c`, we replace all variables by their value in `s` if any, otherwise leave them unchanged. Let's call `c_0` the resulting constraint. We either return `c_0` or nothing based on the form of `c_0`:
-//     - `~one * lin == k * v if v isn't in i`: insert `(v, lin / k)` into `s` and return nothing
+//     - `~one * lin == k * v if v isn't in i and c isn't critical`: insert `(v, lin / k)` into `s` and return nothing
+//     - `~one * lin == k * v if v isn't in i and c is critical`: insert `v` into `i` and return `c_0`
 //     - `q == k * v if v isn't in i`: insert `v` into `i` and return `c_0`
 //     - otherwise return `c_0`
 
 use std::collections::{HashMap, HashSet};
+use zokrates_ast::common::AssertionError;
 use zokrates_ast::flat::Variable;
 use zokrates_ast::ir::folder::{fold_statement, Folder};
 use zokrates_ast::ir::LinComb;
@@ -67,6 +69,11 @@ impl<T: Field> RedefinitionOptimizer<T> {
         }
     }
 
+    /// The substitutions found so far
+    pub fn substitution(&self) -> &HashMap<Variable, CanonicalLinComb<T>> {
+        &self.substitution
+    }
+
     fn fold_statement<'ast>(
         &mut self,
         s: Statement<'ast, T>,
@@ -81,6 +88,8 @@ impl<T: Field> RedefinitionOptimizer<T> {
                     return vec![Statement::Constraint(quad, lin, message)];
                 }
 
+                let critical = message.as_ref().map(|e| e.is_critical()).unwrap_or(false);
+
                 let (constraint, to_insert, to_ignore) = match self.ignore.contains(&lin.0[0].0)
                     || self.substitution.contains_key(&lin.0[0].0)
                 {
@@ -89,7 +98,17 @@ impl<T: Field> RedefinitionOptimizer<T> {
                         // if the right side is a single variable
                         Ok((variable, coefficient)) => match quad.try_linear() {
                             // if the left side is linear
-                            Ok(l) => (None, Some((variable, l / &coefficient)), None),
+                            Ok(l) if !critical => (None, Some((variable, l / &coefficient)), None),
+                            // critical constraints are kept, so the variable cannot be substituted
+                            Ok(l) => (
+                                Some(Statement::Constraint(
+                                    l.into(),
+                                    LinComb::summand(coefficient, variable),
+                                    message,
+                                )),
+                                None,
+                                Some(variable),
+                            ),
                             // if the left side isn't linear
                             Err(quad) => (
                                 Some(Statement::Constraint(
@@ -141,7 +160,8 @@ impl<T: Field> RedefinitionOptimizer<T> {
                     })
                     .collect::<Vec<Result<T, QuadComb<T>>>>();
 
-                match inputs.iter().all(|i| i.is_ok()) {
+                // plugins are only known to the interpreter, so their directives are kept
+                match inputs.iter().all(|i| i.is_ok()) && !matches!(d.solver, Solver::Plugin(..)) {
                     true => {
                         // unwrap inputs to their constant value
                         let inputs: Vec<_> = inputs.into_iter().map(|i| i.unwrap()).collect();
@@ -256,14 +276,49 @@ mod tests {
                 Statement::definition(out, y),
             ],
             return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
         };
 
         let optimized: Prog<Bn128Field> = Prog {
             arguments: vec![x],
             statements: vec![Statement::definition(out, x.id)],
             return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
+        };
+
+        let mut optimizer = RedefinitionOptimizer::init(&p);
+        assert_eq!(optimizer.fold_program(p), optimized);
+    }
+
+    #[test]
+    fn keep_critical_synonyms() {
+        // def main(x):
+        //    #[critical] assert(x == y)
+        //    return y
+
+        let x = Parameter::public(Variable::new(0));
+        let y = Variable::new(1);
+        let out = Variable::public(0);
+
+        let error = RuntimeError::SourceAssertion(
+            zokrates_ast::common::SourceMetadata::default().critical(true),
+        );
+
+        let p: Prog<Bn128Field> = Prog {
+            arguments: vec![x],
+            statements: vec![
+                Statement::Constraint(x.id.into(), y.into(), Some(error)),
+                Statement::definition(out, y),
+            ],
+            return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
         };
 
+        let optimized = p.clone();
+
         let mut optimizer = RedefinitionOptimizer::init(&p);
         assert_eq!(optimizer.fold_program(p), optimized);
     }
@@ -280,6 +335,8 @@ mod tests {
             arguments: vec![x],
             statements: vec![Statement::definition(one, x.id)],
             return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
         };
 
         let optimized = p.clone();
@@ -316,6 +373,8 @@ mod tests {
                 Statement::definition(out, z),
             ],
             return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
         };
 
         let optimized: Prog<Bn128Field> = Prog {
@@ -325,6 +384,8 @@ mod tests {
                 Statement::definition(out, x.id),
             ],
             return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
         };
 
         let mut optimizer = RedefinitionOptimizer::init(&p);
@@ -365,6 +426,8 @@ mod tests {
                 Statement::definition(out_1, w),
             ],
             return_count: 2,
+            public_layout: vec![],
+            variable_ordering: None,
         };
 
         let optimized: Prog<Bn128Field> = Prog {
@@ -374,6 +437,8 @@ mod tests {
                 Statement::definition(out_1, Bn128Field::from(1)),
             ],
             return_count: 2,
+            public_layout: vec![],
+            variable_ordering: None,
         };
 
         let mut optimizer = RedefinitionOptimizer::init(&p);
@@ -422,6 +487,8 @@ mod tests {
                 Statement::definition(r, LinComb::from(a) + LinComb::from(b) + LinComb::from(c)),
             ],
             return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
         };
 
         let expected: Prog<Bn128Field> = Prog {
@@ -442,6 +509,8 @@ mod tests {
                 ),
             ],
             return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
         };
 
         let mut optimizer = RedefinitionOptimizer::init(&p);
@@ -479,6 +548,8 @@ mod tests {
                 Statement::definition(z, LinComb::from(x.id)),
             ],
             return_count: 0,
+            public_layout: vec![],
+            variable_ordering: None,
         };
 
         let optimized = p.clone();
@@ -507,6 +578,8 @@ mod tests {
                 Statement::constraint(x.id, Bn128Field::from(2)),
             ],
             return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
         };
 
         let optimized = p.clone();
@@ -514,4 +587,47 @@ mod tests {
         let mut optimizer = RedefinitionOptimizer::init(&p);
         assert_eq!(optimizer.fold_program(p), optimized);
     }
+
+    #[test]
+    fn keep_plugin_directive() {
+        // # y = Plugin("isqrt", 1, 1)(4)
+        // y * y * 4 == 1
+        // return y
+
+        // ->
+
+        // unchanged, as the plugin cannot be run at compile time
+
+        let y = Variable::new(0);
+        let out = Variable::public(0);
+
+        let p: Prog<Bn128Field> = Prog {
+            arguments: vec![],
+            statements: vec![
+                Statement::Directive(Directive {
+                    inputs: vec![LinComb::summand(4, Variable::one()).into()],
+                    outputs: vec![y],
+                    solver: Solver::Plugin("isqrt".into(), 1, 1),
+                }),
+                Statement::constraint(
+                    QuadComb::from_linear_combinations(LinComb::from(y), LinComb::summand(4, y)),
+                    Variable::one(),
+                ),
+                Statement::definition(out, y),
+            ],
+            return_count: 1,
+            public_layout: vec![],
+            variable_ordering: None,
+        };
+
+        let mut optimizer = RedefinitionOptimizer::init(&p);
+        let optimized = optimizer.fold_program(p);
+        assert!(matches!(
+            optimized.statements[0],
+            Statement::Directive(Directive {
+                solver: Solver::Plugin("isqrt".into(), 1, 1),
+                ..
+            })
+        ));
+    }
 }
@@ -0,0 +1,157 @@
+//! Record what the ir optimizer does to a program.
+//!
+//! The statistics of the constraint system are collected as the statements flow into the first pass and out of each
+//! pass, so that the program is never cloned. They are only complete once the optimized program has been consumed.
+
+use serde::Serialize;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use zokrates_ast::ir::stats::{ConstraintSystemStats, StatsCollector, StatsDelta, DENSITY_BUCKETS};
+use zokrates_ast::ir::{Parameter, Statement};
+use zokrates_common::pipeline::PassId;
+
+/// The statistics of the constraint system after a pass of the optimizer
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PassStats {
+    pub pass: PassId,
+    pub stats: ConstraintSystemStats,
+    /// The change caused by this pass
+    pub delta: StatsDelta,
+}
+
+/// The statistics of the constraint system before and after optimization, and the effect of each pass
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MinimizationReport {
+    pub before: ConstraintSystemStats,
+    pub passes: Vec<PassStats>,
+    pub after: ConstraintSystemStats,
+    pub delta: StatsDelta,
+}
+
+/// A handle on the statistics collected while a program is optimized
+#[derive(Clone, Default)]
+pub struct StatsRecorder {
+    // the statistics of the program before optimization, then after each pass
+    collectors: Rc<RefCell<Vec<(Option<PassId>, StatsCollector)>>>,
+}
+
+impl StatsRecorder {
+    /// Start recording the program coming out of `pass`, or the unoptimized program if `pass` is `None`, returning
+    /// a function to call on each of its statements
+    pub(crate) fn record<'ast, T>(
+        &self,
+        pass: Option<PassId>,
+        arguments: &[Parameter],
+    ) -> impl FnMut(&Statement<'ast, T>) {
+        let index = {
+            let mut collectors = self.collectors.borrow_mut();
+            collectors.push((pass, StatsCollector::new(arguments)));
+            collectors.len() - 1
+        };
+
+        let collectors = self.collectors.clone();
+        move |s| collectors.borrow_mut()[index].1.update(s)
+    }
+
+    /// The report of the statements recorded so far
+    pub fn report(&self) -> MinimizationReport {
+        let collectors = self.collectors.borrow();
+        let mut snapshots = collectors.iter().map(|(pass, c)| (*pass, c.stats()));
+
+        let before = snapshots.next().map(|(_, stats)| stats).unwrap_or_default();
+
+        let mut after = before;
+        let passes = snapshots
+            .map(|(pass, stats)| {
+                let delta = after.delta(&stats);
+                after = stats;
+                PassStats {
+                    pass: pass.unwrap(),
+                    stats,
+                    delta,
+                }
+            })
+            .collect();
+
+        MinimizationReport {
+            before,
+            passes,
+            after,
+            delta: before.delta(&after),
+        }
+    }
+}
+
+// a signed number with an explicit sign, and `0` for no change
+fn signed(n: isize) -> String {
+    match n {
+        0 => "0".into(),
+        n => format!("{:+}", n),
+    }
+}
+
+impl fmt::Display for MinimizationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows = |s: &ConstraintSystemStats| {
+            let mut rows = vec![s.constraints, s.directives, s.logs, s.variables];
+            rows.extend(s.density);
+            rows
+        };
+        let delta_rows = |d: &StatsDelta| {
+            let mut rows = vec![d.constraints, d.directives, d.logs, d.variables];
+            rows.extend(d.density);
+            rows
+        };
+        let labels: Vec<_> = ["constraints", "directives", "logs", "variables"]
+            .iter()
+            .map(|l| l.to_string())
+            .chain(
+                DENSITY_BUCKETS
+                    .iter()
+                    .map(|b| format!("lincombs with {} terms", b)),
+            )
+            .collect();
+
+        writeln!(
+            f,
+            "{:<24} {:>12} {:>12} {:>12}",
+            "", "before", "after", "delta"
+        )?;
+        for (((label, before), after), delta) in labels
+            .iter()
+            .zip(rows(&self.before))
+            .zip(rows(&self.after))
+            .zip(delta_rows(&self.delta))
+        {
+            writeln!(
+                f,
+                "{:<24} {:>12} {:>12} {:>12}",
+                label,
+                before,
+                after,
+                signed(delta)
+            )?;
+        }
+
+        writeln!(f)?;
+        write!(
+            f,
+            "{:<24} {:>12} {:>12} {:>12} {:>12}",
+            "pass", "constraints", "directives", "logs", "variables"
+        )?;
+        for pass in &self.passes {
+            write!(
+                f,
+                "\n{:<24} {:>12} {:>12} {:>12} {:>12}",
+                pass.pass.name(),
+                signed(pass.delta.constraints),
+                signed(pass.delta.directives),
+                signed(pass.delta.logs),
+                signed(pass.delta.variables)
+            )?;
+        }
+
+        Ok(())
+    }
+}
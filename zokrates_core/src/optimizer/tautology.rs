@@ -4,7 +4,10 @@
 // ```
 //
 // This makes the assumption that ~one has value 1, as should be guaranteed by the verifier
+//
+// Constraints coming from assertions marked `#[critical]` are kept even when they are tautologies
 
+use zokrates_ast::common::AssertionError;
 use zokrates_ast::ir::folder::fold_statement;
 use zokrates_ast::ir::folder::Folder;
 use zokrates_ast::ir::*;
@@ -18,7 +21,8 @@ impl<'ast, T: Field> Folder<'ast, T> for TautologyOptimizer {
         match s {
             Statement::Constraint(quad, lin, message) => match quad.try_linear() {
                 Ok(l) => {
-                    if l == lin {
+                    let critical = message.as_ref().map(|e| e.is_critical()).unwrap_or(false);
+                    if l == lin && !critical {
                         vec![]
                     } else {
                         vec![Statement::Constraint(l.into(), lin, message)]
@@ -0,0 +1,297 @@
+//! Module containing pins of compiled programs, used to detect in continuous integration that a change to the sources
+//! requires a new setup.
+//!
+//! A pin records the hash of the constraint system and of the ABI of a program for a given curve and scheme, along
+//! with the configuration it was compiled with, so that the program can be compiled again and compared to the pin.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::path::PathBuf;
+use zokrates_ast::ir::{self, ProgramHash};
+use zokrates_ast::typed::abi::{Abi, AbiChange};
+use zokrates_common::CompileConfig;
+use zokrates_field::Field;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Pin {
+    /// The path to the entry point of the program
+    pub source: PathBuf,
+    pub curve: String,
+    pub scheme: String,
+    pub config: CompileConfig,
+    pub constraint_count: usize,
+    pub constraint_system: ProgramHash,
+    /// The SHA-256 hash of the ABI, constants excluded
+    pub abi_hash: String,
+    pub abi: Abi,
+}
+
+impl Pin {
+    pub fn new<T: Field>(
+        source: PathBuf,
+        curve: String,
+        scheme: String,
+        config: CompileConfig,
+        program: &ir::Prog<T>,
+        abi: &Abi,
+    ) -> Self {
+        // the values of the public constants are not part of the interface of the program
+        let abi = Abi {
+            constants: vec![],
+            ..abi.clone()
+        };
+
+        let abi_hash = Sha256::digest(serde_json::to_string(&abi).unwrap().as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+
+        Pin {
+            source,
+            curve,
+            scheme,
+            config,
+            constraint_count: program.constraint_count(),
+            constraint_system: program.hash(),
+            abi_hash,
+            abi,
+        }
+    }
+
+    /// The differences between this pin and the pin of the `current` version of the program
+    pub fn drift(&self, current: &Pin) -> Vec<Drift> {
+        let mut drift = vec![];
+
+        if self.curve != current.curve {
+            drift.push(Drift::Curve {
+                pinned: self.curve.clone(),
+                current: current.curve.clone(),
+            });
+        }
+
+        if self.scheme != current.scheme {
+            drift.push(Drift::Scheme {
+                pinned: self.scheme.clone(),
+                current: current.scheme.clone(),
+            });
+        }
+
+        if self.constraint_system != current.constraint_system {
+            drift.push(Drift::ConstraintSystem {
+                pinned: self.constraint_count,
+                current: current.constraint_count,
+            });
+        }
+
+        if self.abi_hash != current.abi_hash {
+            drift.push(Drift::Abi(self.abi.changes(&current.abi)));
+        }
+
+        drift
+    }
+}
+
+/// A difference between a pin and the current version of a program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Drift {
+    Curve {
+        pinned: String,
+        current: String,
+    },
+    Scheme {
+        pinned: String,
+        current: String,
+    },
+    /// The constraint system changed, along with the number of constraints before and after the change
+    ConstraintSystem {
+        pinned: usize,
+        current: usize,
+    },
+    /// The ABI changed. The changes can be empty if only the names of the struct types changed
+    Abi(Vec<AbiChange>),
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Drift::Curve { pinned, current } => {
+                write!(f, "curve changed from `{}` to `{}`", pinned, current)
+            }
+            Drift::Scheme { pinned, current } => {
+                write!(f, "scheme changed from `{}` to `{}`", pinned, current)
+            }
+            Drift::ConstraintSystem { pinned, current } => write!(
+                f,
+                "constraint system changed: {} constraints instead of {} ({:+})",
+                current,
+                pinned,
+                *current as isize - *pinned as isize
+            ),
+            Drift::Abi(changes) => {
+                write!(f, "ABI changed")?;
+                for change in changes {
+                    write!(f, "\n    {}", change)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::compile;
+    use std::io;
+    use typed_arena::Arena;
+    use zokrates_ast::typed::{ConcreteType, UBitwidth};
+    use zokrates_common::Resolver;
+    use zokrates_field::Bn128Field;
+
+    fn pin(source: &str) -> Pin {
+        let arena = Arena::new();
+        let artifacts = compile::<Bn128Field, io::Error>(
+            source.into(),
+            "main.zok".into(),
+            None::<&dyn Resolver<io::Error>>,
+            CompileConfig::default(),
+            &arena,
+        )
+        .unwrap()
+        .collect();
+        let (program, abi) = artifacts.into_inner();
+
+        Pin::new(
+            "main.zok".into(),
+            "bn128".into(),
+            "g16".into(),
+            CompileConfig::default(),
+            &program,
+            &abi,
+        )
+    }
+
+    #[test]
+    fn unchanged() {
+        let source = r#"
+            const u32 N = 2;
+            def main(field[N] a) -> field {
+                return a[0] * a[1];
+            }
+        "#;
+
+        let pinned = pin(source);
+
+        assert_eq!(pinned.drift(&pin(source)), vec![]);
+
+        let serialized = serde_json::to_string(&pinned).unwrap();
+        let deserialized: Pin = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.drift(&pinned), vec![]);
+    }
+
+    #[test]
+    fn changed_constant() {
+        let pinned = pin(r#"
+            const field K = 2;
+            def main(field a) -> field {
+                assert(a * a == K);
+                return a;
+            }
+        "#);
+        let current = pin(r#"
+            const field K = 3;
+            def main(field a) -> field {
+                assert(a * a == K);
+                return a;
+            }
+        "#);
+
+        assert_eq!(
+            pinned.drift(&current),
+            vec![Drift::ConstraintSystem {
+                pinned: pinned.constraint_count,
+                current: pinned.constraint_count
+            }]
+        );
+
+        let pinned = pin(r#"
+            const u32 N = 2;
+            def main(private field[2] a) -> field {
+                field mut res = 1;
+                for u32 i in 0..N {
+                    res = res * a[i];
+                }
+                return res;
+            }
+        "#);
+        let current = pin(r#"
+            const u32 N = 1;
+            def main(private field[2] a) -> field {
+                field mut res = 1;
+                for u32 i in 0..N {
+                    res = res * a[i];
+                }
+                return res;
+            }
+        "#);
+
+        let drift = pinned.drift(&current);
+        assert_eq!(
+            drift,
+            vec![Drift::ConstraintSystem {
+                pinned: pinned.constraint_count,
+                current: current.constraint_count
+            }]
+        );
+        assert!(current.constraint_count < pinned.constraint_count);
+    }
+
+    #[test]
+    fn abi_only() {
+        // renaming an input does not change the constraints, but breaks the callers of the program
+        let pinned = pin(r#"
+            def main(field a, private field b) -> field {
+                return a * b;
+            }
+        "#);
+        let current = pin(r#"
+            def main(field x, private field b) -> field {
+                return x * b;
+            }
+        "#);
+
+        assert_eq!(
+            pinned.drift(&current),
+            vec![Drift::Abi(vec![
+                AbiChange::Removed {
+                    path: "a".into(),
+                    ty: ConcreteType::FieldElement
+                },
+                AbiChange::Added {
+                    path: "x".into(),
+                    ty: ConcreteType::FieldElement
+                }
+            ])]
+        );
+
+        let pinned = pin(r#"
+            def main(field a) -> field {
+                return a;
+            }
+        "#);
+        let current = pin(r#"
+            def main(u32 a) -> field {
+                return 1;
+            }
+        "#);
+
+        assert!(pinned
+            .drift(&current)
+            .contains(&Drift::Abi(vec![AbiChange::Retyped {
+                path: "a".into(),
+                old: ConcreteType::FieldElement,
+                new: ConcreteType::Uint(UBitwidth::B32)
+            }])));
+    }
+}
@@ -0,0 +1,84 @@
+//! Module handling the return values of `main` marked `private`.
+//!
+//! Private return values are computed like the public ones, but are not public inputs of the proof. They are flattened
+//! after the public ones, to the outputs which follow the `return_count` public outputs of the compiled program, so
+//! that the prover can still read them from the witness. `Abi::arrange_outputs` restores the order of the output type.
+
+use zokrates_ast::typed::abi::Abi;
+use zokrates_ast::untyped::{Program, Symbol, SymbolDefinition};
+use zokrates_ast::zir::{folder::*, ZirFunction, ZirProgram, ZirStatement};
+use zokrates_field::Field;
+
+/// The indices of the elements of the output tuple of `main` marked `private`
+pub(crate) fn private_outputs(program: &Program) -> Vec<usize> {
+    program.modules[&program.main]
+        .symbols
+        .iter()
+        .find(|s| s.value.id == "main")
+        .and_then(|s| match &s.value.symbol {
+            Symbol::Here(SymbolDefinition::Function(f)) => {
+                Some(f.value.signature.private_outputs.clone())
+            }
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// Record `private_outputs` in the ABI of the program, unless its outputs were dropped
+pub(crate) fn record(mut abi: Abi, private_outputs: Vec<usize>) -> Abi {
+    if !abi.no_outputs {
+        abi.private_outputs = private_outputs;
+    }
+    abi
+}
+
+/// Move the private return values of `main` after the public ones. Returns the number of private return values
+pub(crate) fn reorder<'ast, T: Field>(
+    program: ZirProgram<'ast, T>,
+    abi: &Abi,
+) -> (ZirProgram<'ast, T>, usize) {
+    let private = abi.private_output_values();
+    let count = private.iter().filter(|p| **p).count();
+
+    match count {
+        0 => (program, 0),
+        count => (Reorderer { private }.fold_program(program), count),
+    }
+}
+
+struct Reorderer {
+    /// Whether each return value is private
+    private: Vec<bool>,
+}
+
+impl Reorderer {
+    fn reorder<U>(&self, values: Vec<U>) -> Vec<U> {
+        let (public, private): (Vec<_>, Vec<_>) = values
+            .into_iter()
+            .zip(&self.private)
+            .partition(|(_, private)| !**private);
+
+        public
+            .into_iter()
+            .chain(private)
+            .map(|(value, _)| value)
+            .collect()
+    }
+}
+
+impl<'ast, T: Field> Folder<'ast, T> for Reorderer {
+    fn fold_function(&mut self, f: ZirFunction<'ast, T>) -> ZirFunction<'ast, T> {
+        let mut f = fold_function(self, f);
+        f.signature.outputs = self.reorder(f.signature.outputs);
+        f
+    }
+
+    fn fold_statement(&mut self, s: ZirStatement<'ast, T>) -> Vec<ZirStatement<'ast, T>> {
+        match s {
+            ZirStatement::Return(expressions) => {
+                vec![ZirStatement::Return(self.reorder(expressions))]
+            }
+            s => fold_statement(self, s),
+        }
+    }
+}
@@ -0,0 +1,462 @@
+//! Module recording where the constraints of a compiled program come from, so that a constraint can be explained.
+//!
+//! When compiling with provenance, each statement of the flattened program is followed through the ir passes. A pass
+//! which rewrites a statement, or merges other statements into it, leaves a breadcrumb in the history of the
+//! statement. The resulting `DebugTable` is a side table of the compiled program: the constraints are the same as
+//! when compiling without it.
+
+use crate::optimizer::IrProgram;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use zokrates_ast::common::{SourceMetadata, Variable};
+use zokrates_ast::ir::folder::Folder;
+use zokrates_ast::ir::visitor::Visitor;
+use zokrates_ast::ir::{CanonicalLinComb, ProgIterator, Statement};
+use zokrates_codegen::Origin;
+use zokrates_common::pipeline::PassId;
+use zokrates_field::Field;
+
+/// The trace a single pass left on a statement
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Breadcrumb {
+    /// The name of the pass
+    pub pass: String,
+    /// Whether the pass rewrote the statement
+    pub rewritten: bool,
+    /// The indices in the flattened program of the statements the pass merged into this one
+    pub merged: Vec<usize>,
+}
+
+impl fmt::Display for Breadcrumb {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:", self.pass)?;
+        if self.rewritten {
+            write!(f, " rewritten")?;
+            if !self.merged.is_empty() {
+                write!(f, ",")?;
+            }
+        }
+        if !self.merged.is_empty() {
+            write!(
+                f,
+                " merged {}",
+                self.merged
+                    .iter()
+                    .map(|index| format!("#{}", index))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The provenance of a statement being optimized
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Provenance {
+    /// The index of the statement in the flattened program
+    pub origin: usize,
+    pub history: Vec<Breadcrumb>,
+}
+
+impl Provenance {
+    pub fn new(origin: usize) -> Self {
+        Provenance {
+            origin,
+            history: vec![],
+        }
+    }
+
+    fn record(&mut self, pass: PassId, rewritten: bool, merged: Vec<usize>) {
+        match self.history.last_mut() {
+            Some(last) if last.pass == pass.name() => {
+                last.rewritten |= rewritten;
+                last.merged.extend(merged);
+            }
+            _ => self.history.push(Breadcrumb {
+                pass: pass.name().to_string(),
+                rewritten,
+                merged,
+            }),
+        }
+    }
+}
+
+/// The provenance of a constraint of a compiled program
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintProvenance {
+    /// The index in the flattened program of the statement the constraint comes from
+    pub origin: usize,
+    /// The source function the constraint was flattened from, named `<module>/<function>`
+    pub function: String,
+    /// The source assertion the constraint enforces, if any
+    pub span: Option<SourceMetadata>,
+    /// The passes which changed the constraint, in order
+    pub history: Vec<Breadcrumb>,
+}
+
+/// The debug side table of a compiled program
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct DebugTable {
+    /// The provenance of each constraint, in the order of the constraints of the program
+    pub constraints: Vec<ConstraintProvenance>,
+    /// The source identifiers of the variables of the program, including the ones the optimizer substituted
+    pub names: BTreeMap<Variable, Vec<String>>,
+}
+
+// the statement `s` without its runtime error, so that copies of a constraint enforcing different source assertions
+// are identified
+fn key<'ast, T: Field>(s: &Statement<'ast, T>) -> Statement<'ast, T> {
+    match s {
+        Statement::Constraint(quad, lin, Some(_)) => {
+            Statement::Constraint(quad.clone(), lin.clone(), None)
+        }
+        s => s.clone(),
+    }
+}
+
+/// Collect the variables used in a statement, except `~one`
+#[derive(Default)]
+struct VariableCollector(BTreeSet<Variable>);
+
+impl<T: Field> Visitor<T> for VariableCollector {
+    fn visit_variable(&mut self, v: &Variable) {
+        if *v != Variable::one() {
+            self.0.insert(*v);
+        }
+    }
+}
+
+fn variables<'a, 'ast: 'a, T: Field + 'a>(
+    statements: impl IntoIterator<Item = &'a Statement<'ast, T>>,
+) -> BTreeSet<Variable> {
+    let mut collector = VariableCollector::default();
+    for s in statements {
+        collector.visit_statement(s);
+    }
+    collector.0
+}
+
+// the variable a statement defines, if it is of the form `quad == 1 * v`
+fn defined_variable<T: Field>(s: &Statement<T>) -> Option<Variable> {
+    match s {
+        Statement::Constraint(_, lin, _) if lin.0.len() == 1 && lin.0[0].1 == T::one() => {
+            Some(lin.0[0].0)
+        }
+        _ => None,
+    }
+}
+
+/// Follows the provenance of the statements of a program through the ir passes, see `optimize`
+pub(crate) struct ProvenanceTracker {
+    /// The provenance of each statement of the program coming out of the last pass, if a pass was applied
+    provenance: RefCell<Option<Vec<Provenance>>>,
+    /// The source identifiers of the variables
+    names: RefCell<BTreeMap<Variable, Vec<String>>>,
+}
+
+impl ProvenanceTracker {
+    /// Start following a flattened program, whose variables were introduced for `names`
+    pub fn new(names: BTreeMap<Variable, Vec<String>>) -> Self {
+        ProvenanceTracker {
+            provenance: RefCell::new(None),
+            names: RefCell::new(names),
+        }
+    }
+
+    // the statements of a program along with their provenance
+    fn take<'ast, T>(
+        &self,
+        statements: impl Iterator<Item = Statement<'ast, T>>,
+    ) -> Vec<(Statement<'ast, T>, Provenance)> {
+        match self.provenance.borrow_mut().take() {
+            Some(provenance) => statements.zip(provenance).collect(),
+            None => statements
+                .enumerate()
+                .map(|(origin, s)| (s, Provenance::new(origin)))
+                .collect(),
+        }
+    }
+
+    /// Apply `folder` to `p` for the pass `pass`, going through the whole program
+    pub fn trace<'ast, T: Field, F: Folder<'ast, T>>(
+        &self,
+        p: IrProgram<'ast, T>,
+        folder: &mut F,
+        pass: PassId,
+    ) -> IrProgram<'ast, T> {
+        let arguments = p
+            .arguments
+            .into_iter()
+            .map(|a| folder.fold_argument(a))
+            .collect();
+
+        let (statements, provenance): (Vec<_>, Vec<_>) =
+            trace_folder(self.take(p.statements), folder, pass)
+                .into_iter()
+                .unzip();
+
+        self.provenance.replace(Some(provenance));
+
+        ProgIterator {
+            arguments,
+            statements: Box::new(statements.into_iter()),
+            return_count: p.return_count,
+            public_layout: p.public_layout,
+            variable_ordering: p.variable_ordering,
+        }
+    }
+
+    /// Carry the names of the variables substituted by a synonym over to the synonym
+    pub fn record_synonyms<T: Field>(&self, substitution: &HashMap<Variable, CanonicalLinComb<T>>) {
+        let mut names = self.names.borrow_mut();
+
+        // a variable substituted with `1 * w` is a synonym of `w`
+        for (v, substitute) in substitution {
+            let mut terms = substitute.0.iter();
+            if let (Some((synonym, coefficient)), None) = (terms.next(), terms.next()) {
+                if *coefficient == T::one() {
+                    if let Some(substituted) = names.get(v).cloned() {
+                        let synonym_names = names.entry(*synonym).or_default();
+                        synonym_names.extend(substituted);
+                        synonym_names.sort();
+                        synonym_names.dedup();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Remove the blocks of the optimized program `p` like `Prog::clean`, and build the debug table of the remaining
+    /// constraints
+    ///
+    /// The statements of a block inherit the provenance of the block. `origins` are the origins of the statements of
+    /// the flattened program.
+    pub fn debug_table<'ast, T>(
+        self,
+        p: IrProgram<'ast, T>,
+        origins: &[Origin],
+    ) -> (Vec<Statement<'ast, T>>, DebugTable) {
+        let statements = self.take(p.statements);
+        let names = self.names.into_inner();
+
+        let mut cleaned = vec![];
+        let mut constraints = vec![];
+
+        for (s, provenance) in statements {
+            for s in expand(s) {
+                if let Statement::Constraint(..) = s {
+                    let origin = &origins[provenance.origin];
+                    constraints.push(ConstraintProvenance {
+                        origin: provenance.origin,
+                        function: origin.function.clone(),
+                        span: origin.span.clone(),
+                        history: provenance.history.clone(),
+                    });
+                }
+                cleaned.push(s);
+            }
+        }
+
+        (cleaned, DebugTable { constraints, names })
+    }
+}
+
+/// Apply `folder` to `statements`, recording how the pass `pass` changes each of them
+///
+/// A statement the pass removed is considered merged into the first statement it is a copy of, or if it defines a
+/// variable, into each later statement in which this variable was substituted.
+fn trace_folder<'ast, T: Field, F: Folder<'ast, T>>(
+    statements: Vec<(Statement<'ast, T>, Provenance)>,
+    folder: &mut F,
+    pass: PassId,
+) -> Vec<(Statement<'ast, T>, Provenance)> {
+    let mut result: Vec<(Statement<'ast, T>, Provenance)> = vec![];
+    // the index in `result` of each statement emitted so far
+    let mut emitted: HashMap<Statement<'ast, T>, usize> = HashMap::new();
+    // the origin of each definition removed so far, by the variable it defined
+    let mut removed: HashMap<Variable, usize> = HashMap::new();
+
+    for (s, mut provenance) in statements {
+        let folded = folder.fold_statement(s.clone());
+
+        if folded.is_empty() {
+            if let Some(index) = emitted.get(&key(&s)) {
+                result[*index]
+                    .1
+                    .record(pass, false, vec![provenance.origin]);
+            } else if let Some(v) = defined_variable(&s) {
+                removed.insert(v, provenance.origin);
+            }
+            continue;
+        }
+
+        let remaining = variables(&folded);
+        let merged: Vec<_> = variables(std::iter::once(&s))
+            .difference(&remaining)
+            .filter_map(|v| removed.get(v).cloned())
+            .collect();
+        let rewritten = folded.len() != 1 || folded[0] != s;
+
+        if rewritten || !merged.is_empty() {
+            provenance.record(pass, rewritten, merged);
+        }
+
+        for s in folded {
+            emitted.entry(key(&s)).or_insert(result.len());
+            result.push((s, provenance.clone()));
+        }
+    }
+
+    result
+}
+
+// the statements of `s` once its blocks are removed
+fn expand<'ast, T>(s: Statement<'ast, T>) -> Vec<Statement<'ast, T>> {
+    match s {
+        Statement::Block(statements) => statements.into_iter().flat_map(expand).collect(),
+        s => vec![s],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizer::optimize;
+    use zokrates_ast::common::Parameter;
+    use zokrates_ast::ir::{LinComb, Prog, QuadComb};
+    use zokrates_field::Bn128Field;
+
+    // optimize `p` with `pipeline`, returning the optimized statements with their provenance, and the names of the
+    // variables
+    fn optimize_traced<'ast>(
+        p: Prog<'ast, Bn128Field>,
+        names: BTreeMap<Variable, Vec<String>>,
+        pipeline: &str,
+    ) -> (
+        Vec<Statement<'ast, Bn128Field>>,
+        Vec<Provenance>,
+        BTreeMap<Variable, Vec<String>>,
+    ) {
+        let tracker = ProvenanceTracker::new(names);
+        let statements: Vec<_> = optimize(p, &pipeline.parse().unwrap(), None, Some(&tracker))
+            .statements
+            .collect();
+
+        (
+            statements,
+            tracker.provenance.into_inner().unwrap(),
+            tracker.names.into_inner(),
+        )
+    }
+
+    #[test]
+    fn follow_through_redefinition_and_duplicates() {
+        // def main(private field x):
+        //     #0 a == x
+        //     #1 b == a
+        //     #2 a * a == x
+        //     #3 b * b == x
+        let x = Variable::new(0);
+        let a = Variable::new(1);
+        let b = Variable::new(2);
+
+        let p: Prog<Bn128Field> = Prog {
+            arguments: vec![Parameter::private(x)],
+            return_count: 0,
+            public_layout: vec![],
+            variable_ordering: None,
+            statements: vec![
+                Statement::definition(a, LinComb::from(x)),
+                Statement::definition(b, LinComb::from(a)),
+                Statement::constraint(
+                    QuadComb::from_linear_combinations(a.into(), a.into()),
+                    LinComb::from(x),
+                ),
+                Statement::constraint(
+                    QuadComb::from_linear_combinations(b.into(), b.into()),
+                    LinComb::from(x),
+                ),
+            ],
+        };
+        let names = BTreeMap::from([
+            (x, vec!["x".to_string()]),
+            (a, vec!["a".to_string()]),
+            (b, vec!["b".to_string()]),
+        ]);
+
+        let (statements, provenance, names) = optimize_traced(p, names, "panics,uint,redef,dedup");
+
+        // `a` and `b` are substituted with `x`, which leaves two copies of `x * x == x`
+        assert_eq!(
+            statements,
+            vec![Statement::constraint(
+                QuadComb::from_linear_combinations(x.into(), x.into()),
+                LinComb::from(x)
+            )]
+        );
+        assert_eq!(
+            provenance,
+            vec![Provenance {
+                origin: 2,
+                history: vec![
+                    Breadcrumb {
+                        pass: "redef".into(),
+                        rewritten: true,
+                        merged: vec![0]
+                    },
+                    Breadcrumb {
+                        pass: "dedup".into(),
+                        rewritten: false,
+                        merged: vec![3]
+                    }
+                ]
+            }]
+        );
+        assert_eq!(
+            provenance[0].history[0].to_string(),
+            "redef: rewritten, merged #0"
+        );
+        assert_eq!(provenance[0].history[1].to_string(), "dedup: merged #3");
+
+        // the names of the substituted variables are not lost
+        assert_eq!(
+            names[&x],
+            vec!["a".to_string(), "b".to_string(), "x".to_string()]
+        );
+    }
+
+    #[test]
+    fn merge_duplicates() {
+        let x = Variable::new(0);
+        let constraint = Statement::constraint(
+            QuadComb::from_linear_combinations(x.into(), x.into()),
+            LinComb::from(x),
+        );
+
+        let p: Prog<Bn128Field> = Prog {
+            arguments: vec![Parameter::private(x)],
+            return_count: 0,
+            public_layout: vec![],
+            variable_ordering: None,
+            statements: vec![constraint.clone(), constraint.clone(), constraint.clone()],
+        };
+        let (statements, provenance, _) = optimize_traced(p, BTreeMap::new(), "panics,uint,dedup");
+
+        assert_eq!(statements, vec![constraint]);
+        assert_eq!(
+            provenance,
+            vec![Provenance {
+                origin: 0,
+                history: vec![Breadcrumb {
+                    pass: "dedup".into(),
+                    rewritten: false,
+                    merged: vec![1, 2]
+                }]
+            }]
+        );
+        assert_eq!(provenance[0].history[0].to_string(), "dedup: merged #1, #2");
+    }
+}
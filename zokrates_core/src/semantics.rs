@@ -5,10 +5,12 @@
 //! @date 2017
 
 use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
 use std::collections::{btree_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt;
 use std::path::PathBuf;
-use zokrates_ast::common::{FormatString, SourceMetadata};
+use zokrates_ast::common::{FlatEmbed, FormatSpec, FormatString, SourceMetadata};
+use zokrates_ast::typed::folder::Folder;
 use zokrates_ast::typed::types::{GGenericsAssignment, GTupleType, GenericsAssignment};
 use zokrates_ast::typed::SourceIdentifier;
 use zokrates_ast::typed::*;
@@ -17,6 +19,9 @@ use zokrates_ast::untyped::Identifier;
 use zokrates_ast::untyped::*;
 use zokrates_field::Field;
 
+use crate::intrinsics::{field_size_in_bits, intrinsic, FIELD_SIZE_IN_BITS};
+use crate::suggestions::{suggest_for_operands, Operand};
+
 use zokrates_ast::untyped::types::{UnresolvedSignature, UnresolvedType, UserTypeId};
 
 use std::hash::Hash;
@@ -56,9 +61,42 @@ impl ErrorInner {
     }
 }
 
+/// The error for a binary operator applied to operands of incompatible types, pointing at both operands and suggesting
+/// a conversion when one is known
+fn binary_operator_error<'ast, T: Field>(
+    operator: &str,
+    pos: (Position, Position),
+    operands: &(Operand, Operand),
+    e1: &TypedExpression<'ast, T>,
+    e2: &TypedExpression<'ast, T>,
+) -> ErrorInner {
+    let (t1, t2) = (e1.get_type(), e2.get_type());
+    let (s1, s2) = (e1.to_string(), e2.to_string());
+
+    let mut message = format!(
+        "Cannot apply `{}` to {}, {}\n\tleft operand `{}` at {} has type {}\n\tright operand `{}` at {} has type {}",
+        operator, t1, t2, s1, operands.0.pos.0, t1, s2, operands.1.pos.0, t2
+    );
+
+    if let Some((side, suggestion)) =
+        suggest_for_operands((&operands.0, &s1, &t1), (&operands.1, &s2, &t2))
+    {
+        message.push_str(&format!(
+            "\n\thelp: for the {} operand, {}",
+            side, suggestion
+        ));
+    }
+
+    ErrorInner {
+        pos: Some(pos),
+        message,
+    }
+}
+
 // a single struct to cover all cases of user-defined types
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct UserDeclarationType<'ast, T> {
+    #[serde(borrow)]
     generics: Vec<DeclarationConstant<'ast, T>>,
     ty: DeclarationType<'ast, T>,
 }
@@ -86,6 +124,21 @@ type TypeMap<'ast, T> = BTreeMap<OwnedModuleId, BTreeMap<UserTypeId, UserDeclara
 type ConstantMap<'ast, T> =
     BTreeMap<OwnedModuleId, BTreeMap<ConstantIdentifier<'ast>, DeclarationType<'ast, T>>>;
 
+/// The outcome of checking a module: the checked module along with the types and constants it declares or imports.
+/// It only depends on the module and on the modules it imports, so it can be reused as long as none of them changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleSnapshot<'ast, T> {
+    #[serde(borrow)]
+    module: TypedModule<'ast, T>,
+    #[serde(borrow)]
+    types: BTreeMap<UserTypeId, UserDeclarationType<'ast, T>>,
+    #[serde(borrow)]
+    constants: BTreeMap<ConstantIdentifier<'ast>, DeclarationType<'ast, T>>,
+    type_spans: BTreeMap<UserTypeId, (Position, Position)>,
+}
+
+pub type ModuleSnapshots<'ast, T> = BTreeMap<OwnedModuleId, ModuleSnapshot<'ast, T>>;
+
 /// The global state of the program during semantic checks
 #[derive(Debug)]
 struct State<'ast, T> {
@@ -98,6 +151,12 @@ struct State<'ast, T> {
     types: TypeMap<'ast, T>,
     // The user-defined constants
     constants: ConstantMap<'ast, T>,
+    /// The location of the declaration of each user-defined type, to point at it when it is shadowed
+    type_spans: BTreeMap<OwnedModuleId, BTreeMap<UserTypeId, (Position, Position)>>,
+    /// The snapshots of the modules which do not need to be checked again
+    snapshots: ModuleSnapshots<'ast, T>,
+    /// The snapshots of the modules we checked, if requested
+    checked: Option<ModuleSnapshots<'ast, T>>,
 }
 
 /// A symbol for a given name: either a type or a group of functions. Not both!
@@ -174,8 +233,27 @@ impl<'ast, T: Field> State<'ast, T> {
             typed_modules: BTreeMap::new(),
             types: BTreeMap::new(),
             constants: BTreeMap::new(),
+            type_spans: BTreeMap::new(),
+            snapshots: BTreeMap::new(),
+            checked: None,
+        }
+    }
+
+    fn with_snapshots(self, snapshots: ModuleSnapshots<'ast, T>) -> Self {
+        State {
+            snapshots,
+            checked: Some(BTreeMap::new()),
+            ..self
         }
     }
+
+    /// Record the location of the declaration of a user-defined type
+    fn record_type_span(&mut self, module_id: &ModuleId, id: &str, pos: (Position, Position)) {
+        self.type_spans
+            .entry(module_id.to_path_buf())
+            .or_default()
+            .insert(id.to_string(), pos);
+    }
 }
 
 /// A function query in the current module.
@@ -327,6 +405,12 @@ pub struct Checker<'ast, T> {
     return_type: Option<DeclarationType<'ast, T>>,
     scope: Scope<'ast, T>,
     functions: HashSet<DeclarationFunctionKey<'ast, T>>,
+    embeds: HashMap<DeclarationFunctionKey<'ast, T>, FlatEmbed>,
+    generics: HashSet<CoreIdentifier<'ast>>,
+    /// The casts to unsigned integers called in the module being checked, declared as embeds at the end of the module
+    casts: BTreeMap<&'ast str, FlatEmbed>,
+    /// Whether each checked statement is preceded by a log of its location
+    span_logs: bool,
 }
 
 impl<'ast, T: Field> Checker<'ast, T> {
@@ -339,13 +423,60 @@ impl<'ast, T: Field> Checker<'ast, T> {
         Checker::default().check_program(prog)
     }
 
+    /// Check a `Program`, marking the statements checked from each source statement with its location, so that what
+    /// they are compiled to can be attributed to the source
+    pub fn check_with_span_logs(prog: Program<'ast>) -> Result<TypedProgram<'ast, T>, Vec<Error>> {
+        Checker {
+            span_logs: true,
+            ..Checker::default()
+        }
+        .check_program(prog)
+    }
+
+    /// Check a `Program`, reusing the snapshots of the modules in `snapshots` instead of checking them again. Along with
+    /// the checked program, return the snapshots of the modules which had to be checked. The statements are located
+    /// like with `check_with_span_logs`
+    pub fn check_with_snapshots(
+        prog: Program<'ast>,
+        snapshots: ModuleSnapshots<'ast, T>,
+    ) -> Result<(TypedProgram<'ast, T>, ModuleSnapshots<'ast, T>), Vec<Error>> {
+        let main_id = prog.main.clone();
+        let state = State::new(prog.modules, main_id.clone()).with_snapshots(snapshots);
+
+        Checker {
+            span_logs: true,
+            ..Checker::default()
+        }
+        .check_state(main_id, state)
+        .map(|(program, checked)| (program, checked.unwrap()))
+    }
+
+    // a log of the location of a statement at `pos` in the module `module_id`, if requested
+    fn span_log(
+        &self,
+        pos: (Position, Position),
+        module_id: &ModuleId,
+    ) -> Option<TypedStatement<'ast, T>> {
+        self.span_logs.then(|| {
+            TypedStatement::SpanLog(SourceMetadata::new(module_id.display().to_string(), pos.0))
+        })
+    }
+
     fn check_program(
         &mut self,
         program: Program<'ast>,
     ) -> Result<TypedProgram<'ast, T>, Vec<Error>> {
         let main_id = program.main.clone();
+        let state = State::new(program.modules, main_id.clone());
+
+        self.check_state(main_id, state).map(|(program, _)| program)
+    }
 
-        let mut state = State::new(program.modules, main_id.clone());
+    fn check_state(
+        &mut self,
+        main_id: OwnedModuleId,
+        mut state: State<'ast, T>,
+    ) -> Result<(TypedProgram<'ast, T>, Option<ModuleSnapshots<'ast, T>>), Vec<Error>> {
         let mut errors = vec![];
 
         // recursively type-check modules starting with `main`
@@ -362,32 +493,35 @@ impl<'ast, T: Field> Checker<'ast, T> {
             |inner| {
                 vec![Error {
                     inner,
-                    module_id: main_id,
+                    module_id: main_id.clone(),
                 }]
             },
         )?;
 
-        Ok(TypedProgram {
-            main: program.main,
-            modules: state.typed_modules,
-        })
+        Ok((
+            TypedProgram {
+                main: main_id,
+                modules: state.typed_modules,
+            },
+            state.checked,
+        ))
     }
 
-    fn check_type_definition(
-        &mut self,
-        ty: TypeDefinitionNode<'ast>,
+    /// Check the generic parameters of a declaration, returning them in order along with their indices by name
+    ///
+    /// A generic parameter cannot be declared twice, nor have the name of a constant in scope or of a type of the module
+    fn check_generic_parameters(
+        generics: &[ConstantGenericNode<'ast>],
         module_id: &ModuleId,
         state: &State<'ast, T>,
-    ) -> Result<UserDeclarationType<'ast, T>, Vec<ErrorInner>> {
-        let pos = ty.pos();
-        let ty = ty.value;
-
-        let mut errors = vec![];
-
-        let mut generics = vec![];
+        errors: &mut Vec<ErrorInner>,
+    ) -> (Vec<GenericIdentifier<'ast>>, BTreeMap<&'ast str, usize>) {
+        let mut declared = vec![];
         let mut generics_map = BTreeMap::new();
+        // the location of the declaration of each generic parameter
+        let mut spans: BTreeMap<&'ast str, (Position, Position)> = BTreeMap::new();
 
-        for (index, g) in ty.generics.iter().enumerate() {
+        for (index, g) in generics.iter().enumerate() {
             if state
                 .constants
                 .get(module_id)
@@ -401,23 +535,53 @@ impl<'ast, T: Field> Checker<'ast, T> {
                         p = g.value
                     ),
                 });
+            } else if let Some(type_pos) =
+                state.type_spans.get(module_id).and_then(|m| m.get(g.value))
+            {
+                errors.push(ErrorInner {
+                    pos: Some(g.pos()),
+                    message: format!(
+                        "Generic parameter {p} shadows the type {p}\n\ttype declared at {}",
+                        type_pos.0,
+                        p = g.value
+                    ),
+                });
+            } else if let Some(first_pos) = spans.get(g.value) {
+                errors.push(ErrorInner {
+                    pos: Some(g.pos()),
+                    message: format!(
+                        "Generic parameter {} is already declared\n\tfirst declared at {}",
+                        g.value, first_pos.0
+                    ),
+                });
             } else {
-                match generics_map.insert(g.value, index).is_none() {
-                    true => {
-                        generics.push(DeclarationConstant::Generic(
-                            GenericIdentifier::with_name(g.value).with_index(index),
-                        ));
-                    }
-                    false => {
-                        errors.push(ErrorInner {
-                            pos: Some(g.pos()),
-                            message: format!("Generic parameter {} is already declared", g.value),
-                        });
-                    }
-                }
+                spans.insert(g.value, g.pos());
+                generics_map.insert(g.value, index);
+                declared.push(GenericIdentifier::with_name(g.value).with_index(index));
             }
         }
 
+        (declared, generics_map)
+    }
+
+    fn check_type_definition(
+        &mut self,
+        ty: TypeDefinitionNode<'ast>,
+        module_id: &ModuleId,
+        state: &State<'ast, T>,
+    ) -> Result<UserDeclarationType<'ast, T>, Vec<ErrorInner>> {
+        let pos = ty.pos();
+        let ty = ty.value;
+
+        let mut errors = vec![];
+
+        let (declared, generics_map) =
+            Self::check_generic_parameters(&ty.generics, module_id, state, &mut errors);
+        let generics: Vec<_> = declared
+            .into_iter()
+            .map(DeclarationConstant::Generic)
+            .collect();
+
         let mut used_generics = HashSet::new();
 
         match self.check_declaration_type(
@@ -478,6 +642,7 @@ impl<'ast, T: Field> Checker<'ast, T> {
                 BooleanExpression::try_from_typed(checked_expr).map(TypedExpression::from)
             }
             DeclarationType::Uint(bitwidth) => {
+                Self::check_int_range(&checked_expr, bitwidth, pos)?;
                 UExpression::try_from_typed(checked_expr, &bitwidth).map(TypedExpression::from)
             }
             DeclarationType::Array(ref array_ty) => {
@@ -515,62 +680,56 @@ impl<'ast, T: Field> Checker<'ast, T> {
         let s = s.value;
 
         let mut errors = vec![];
-        let mut fields: Vec<(_, _)> = vec![];
-        let mut fields_set = HashSet::new();
+        let mut fields: Vec<(_, _, _)> = vec![];
+        // the location of the declaration of each member
+        let mut field_spans = BTreeMap::new();
 
-        let mut generics = vec![];
-        let mut generics_map = BTreeMap::new();
-
-        for (index, g) in s.generics.iter().enumerate() {
-            if state
-                .constants
-                .get(module_id)
-                .and_then(|m| m.get(g.value))
-                .is_some()
-            {
-                errors.push(ErrorInner {
-                    pos: Some(g.pos()),
-                    message: format!(
-                        "Generic parameter {p} conflicts with constant symbol {p}",
-                        p = g.value
-                    ),
-                });
-            } else {
-                match generics_map.insert(g.value, index).is_none() {
-                    true => {
-                        generics.push(Some(DeclarationConstant::Generic(
-                            GenericIdentifier::with_name(g.value).with_index(index),
-                        )));
-                    }
-                    false => {
-                        errors.push(ErrorInner {
-                            pos: Some(g.pos()),
-                            message: format!("Generic parameter {} is already declared", g.value),
-                        });
-                    }
-                }
-            }
-        }
+        let (declared, generics_map) =
+            Self::check_generic_parameters(&s.generics, module_id, state, &mut errors);
+        let generics: Vec<_> = declared
+            .into_iter()
+            .map(|g| Some(DeclarationConstant::Generic(g)))
+            .collect();
 
         let mut used_generics = HashSet::new();
 
         for field in s.fields {
+            let field_pos = field.pos();
             let member_id = field.value.id.to_string();
+
+            // like for the arguments of the entrypoint function, the bound of `field<N>` members leaves room for the
+            // comparisons of field elements
+            let (ty, bound) = UnresolvedType::strip_bound(field.value.ty);
+            if let Some(bound) = bound {
+                let max_bound = T::get_required_bits() - 2;
+
+                if bound == 0 || bound as usize > max_bound {
+                    errors.push(ErrorInner {
+                        pos: Some(field_pos),
+                        message: format!(
+                            "Expected the bound of member `{}` to be between 1 and {} bits, found {}",
+                            member_id, max_bound, bound
+                        ),
+                    });
+                }
+            }
+
             match self
-                .check_declaration_type(
-                    field.value.ty,
-                    module_id,
-                    state,
-                    &generics_map,
-                    &mut used_generics,
-                )
+                .check_declaration_type(ty, module_id, state, &generics_map, &mut used_generics)
                 .map(|t| (member_id, t))
             {
-                Ok(f) => match fields_set.insert(f.0.clone()) {
-                    true => fields.push(f),
-                    false => errors.push(ErrorInner {
-                        pos: Some(pos),
-                        message: format!("Duplicate key {} in struct definition", f.0,),
+                Ok(f) => match field_spans.entry(f.0.clone()) {
+                    Entry::Vacant(e) => {
+                        e.insert(field_pos);
+                        fields.push((f.0, f.1, bound));
+                    }
+                    Entry::Occupied(e) => errors.push(ErrorInner {
+                        pos: Some(field_pos),
+                        message: format!(
+                            "Duplicate key {} in struct definition\n\tfirst declared at {}",
+                            f.0,
+                            e.get().0
+                        ),
                     }),
                 },
                 Err(e) => {
@@ -599,7 +758,7 @@ impl<'ast, T: Field> Checker<'ast, T> {
             generics,
             fields
                 .iter()
-                .map(|f| DeclarationStructMember::new(f.0.clone(), f.1.clone()))
+                .map(|f| DeclarationStructMember::new(f.0.clone(), f.1.clone()).bound(f.2))
                 .collect(),
         ))
     }
@@ -656,6 +815,7 @@ impl<'ast, T: Field> Checker<'ast, T> {
                                         }
                                     )
                                     .is_none());
+                                state.record_type_span(module_id, declaration.id, pos);
                             }
                         };
                     }
@@ -666,6 +826,7 @@ impl<'ast, T: Field> Checker<'ast, T> {
                 }
             }
             Symbol::Here(SymbolDefinition::Constant(c)) => {
+                let public = c.value.public;
                 match self.check_constant_definition(declaration.id, c, module_id, state) {
                     Ok(c) => {
                         match symbol_unifier.insert_constant(declaration.id) {
@@ -688,6 +849,7 @@ impl<'ast, T: Field> Checker<'ast, T> {
                                         ),
                                         TypedConstantSymbol::Here(c.clone()),
                                     )
+                                    .public(public)
                                     .into(),
                                 );
                                 let id = declaration.id;
@@ -737,6 +899,7 @@ impl<'ast, T: Field> Checker<'ast, T> {
                                     .or_default()
                                     .insert(declaration.id.to_string(), ty)
                                     .is_none());
+                                state.record_type_span(module_id, declaration.id, pos);
                             }
                         };
                     }
@@ -788,6 +951,20 @@ impl<'ast, T: Field> Checker<'ast, T> {
                     }
                 }
             }
+            // symbols starting with an underscore are private to the module which declares them
+            Symbol::There(import) if import.value.symbol_id.starts_with('_') => {
+                errors.push(
+                    ErrorInner {
+                        pos: Some(import.pos()),
+                        message: format!(
+                            "Cannot import private symbol `{}` from module `{}`",
+                            import.value.symbol_id,
+                            import.value.module_id.display()
+                        ),
+                    }
+                    .in_file(module_id),
+                );
+            }
             Symbol::There(import) => {
                 let pos = import.pos();
                 let import = import.value;
@@ -864,6 +1041,7 @@ impl<'ast, T: Field> Checker<'ast, T> {
                                     .entry(module_id.to_path_buf())
                                     .or_default()
                                     .insert(declaration.id.to_string(), t);
+                                state.record_type_span(module_id, declaration.id, pos);
                             }
                             (0, None, Some(ty)) => {
                                 match symbol_unifier.insert_constant(declaration.id) {
@@ -969,10 +1147,11 @@ impl<'ast, T: Field> Checker<'ast, T> {
                     true => {}
                 };
 
-                self.functions.insert(
+                let key =
                     DeclarationFunctionKey::with_location(module_id.to_path_buf(), declaration.id)
-                        .signature(funct.typed_signature()),
-                );
+                        .signature(funct.typed_signature());
+                self.functions.insert(key.clone());
+                self.embeds.insert(key, funct);
                 symbols.push(
                     TypedFunctionSymbolDeclaration::new(
                         DeclarationFunctionKey::with_location(
@@ -1001,6 +1180,10 @@ impl<'ast, T: Field> Checker<'ast, T> {
         module_id: &ModuleId,
         state: &mut State<'ast, T>,
     ) -> Result<(), Vec<Error>> {
+        if let Some(snapshot) = state.snapshots.remove(module_id) {
+            return Checker::restore_module(module_id, snapshot, state);
+        }
+
         let mut checked_symbols = TypedSymbolDeclarations::new();
 
         // check if the module was already removed from the untyped ones
@@ -1013,6 +1196,10 @@ impl<'ast, T: Field> Checker<'ast, T> {
                 state.types.entry(module_id.to_path_buf()).or_default();
                 state.constants.entry(module_id.to_path_buf()).or_default();
 
+                // the types of the module are located first, so that a generic parameter is reported when it shadows
+                // a type declared after it too
+                Checker::record_type_spans(&module.symbols, module_id, state)?;
+
                 // we keep track of the introduced symbols to avoid collisions between types and functions
                 let mut symbol_unifier = SymbolUnifier::default();
 
@@ -1027,6 +1214,17 @@ impl<'ast, T: Field> Checker<'ast, T> {
                     )?
                 }
 
+                for (id, embed) in std::mem::take(&mut self.casts) {
+                    checked_symbols.push(
+                        TypedFunctionSymbolDeclaration::new(
+                            DeclarationFunctionKey::with_location(module_id.to_path_buf(), id)
+                                .signature(embed.typed_signature()),
+                            TypedFunctionSymbol::Flat(embed),
+                        )
+                        .into(),
+                    );
+                }
+
                 Some(TypedModule {
                     symbols: checked_symbols,
                 })
@@ -1035,6 +1233,18 @@ impl<'ast, T: Field> Checker<'ast, T> {
 
         // insert into typed_modules if we checked anything
         if let Some(typed_module) = to_insert {
+            if let Some(checked) = state.checked.as_mut() {
+                checked.insert(
+                    module_id.to_path_buf(),
+                    ModuleSnapshot {
+                        module: typed_module.clone(),
+                        types: state.types[module_id].clone(),
+                        constants: state.constants[module_id].clone(),
+                        type_spans: state.type_spans.get(module_id).cloned().unwrap_or_default(),
+                    },
+                );
+            }
+
             // there should be no checked module at that key just yet, if there is we have a collision or we checked something twice
             assert!(state
                 .typed_modules
@@ -1045,6 +1255,71 @@ impl<'ast, T: Field> Checker<'ast, T> {
         Ok(())
     }
 
+    /// Record the location of the types declared in a module, either defined there or imported, before its symbols are
+    /// checked
+    fn record_type_spans(
+        symbols: &[SymbolDeclarationNode<'ast>],
+        module_id: &ModuleId,
+        state: &mut State<'ast, T>,
+    ) -> Result<(), Vec<Error>> {
+        for declaration in symbols {
+            let pos = declaration.pos();
+            let declaration = &declaration.value;
+
+            match &declaration.symbol {
+                Symbol::Here(SymbolDefinition::Struct(_))
+                | Symbol::Here(SymbolDefinition::Type(_)) => {
+                    state.record_type_span(module_id, declaration.id, pos);
+                }
+                Symbol::There(import) => {
+                    // whether the symbol is a type is only known once the module it comes from is checked
+                    Checker::default().check_module(&import.value.module_id, state)?;
+
+                    if state
+                        .types
+                        .get(&import.value.module_id)
+                        .map_or(false, |types| types.contains_key(import.value.symbol_id))
+                    {
+                        state.record_type_span(module_id, declaration.id, import.pos());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Use the snapshot of a module instead of checking it
+    fn restore_module(
+        module_id: &ModuleId,
+        snapshot: ModuleSnapshot<'ast, T>,
+        state: &mut State<'ast, T>,
+    ) -> Result<(), Vec<Error>> {
+        if let Some(module) = state.modules.remove(module_id) {
+            // the modules it imports are part of the program too, and were snapshotted along with it
+            for declaration in module.symbols {
+                if let Symbol::There(import) = declaration.value.symbol {
+                    Checker::default().check_module(&import.value.module_id, state)?;
+                }
+            }
+        }
+
+        state.types.insert(module_id.to_path_buf(), snapshot.types);
+        state
+            .constants
+            .insert(module_id.to_path_buf(), snapshot.constants);
+        state
+            .type_spans
+            .insert(module_id.to_path_buf(), snapshot.type_spans);
+        assert!(state
+            .typed_modules
+            .insert(module_id.to_path_buf(), snapshot.module)
+            .is_none());
+
+        Ok(())
+    }
+
     fn check_single_main(module: &TypedModule<T>) -> Result<(), ErrorInner> {
         match module
             .functions_iter()
@@ -1104,6 +1379,7 @@ impl<'ast, T: Field> Checker<'ast, T> {
     ) -> Result<TypedFunction<'ast, T>, Vec<ErrorInner>> {
         assert!(self.return_type.is_none());
 
+        self.generics.clear();
         self.enter_scope();
 
         let pos = funct_node.pos();
@@ -1111,6 +1387,7 @@ impl<'ast, T: Field> Checker<'ast, T> {
         let mut errors = vec![];
         let funct = funct_node.value;
         let mut signature = None;
+        let mut declared = None;
 
         assert_eq!(funct.arguments.len(), funct.signature.inputs.len());
 
@@ -1118,8 +1395,36 @@ impl<'ast, T: Field> Checker<'ast, T> {
 
         let mut statements_checked = vec![];
 
+        // return values defined on a non-entrypoint function should not have visibility modifiers
+        if (state.main_id != module_id || id != "main")
+            && !funct.signature.private_outputs.is_empty()
+        {
+            errors.push(ErrorInner {
+                pos: Some(pos),
+                message:
+                    "Visibility modifiers on return values are only allowed on the entrypoint function"
+                        .into(),
+            });
+        }
+
+        // the location of the declaration of each generic parameter and argument, to point at the first declaration
+        // of a name declared twice
+        let generic_spans: BTreeMap<_, _> = funct
+            .signature
+            .generics
+            .iter()
+            .map(|g| (g.value, g.pos()))
+            .collect();
+        let mut argument_spans = BTreeMap::new();
+
         match self.check_signature(funct.signature, module_id, state) {
             Ok(s) => {
+                // declare the function while checking its body, so that it can call itself
+                let key = DeclarationFunctionKey::with_location(module_id.to_path_buf(), id)
+                    .signature(s.clone());
+                self.functions.insert(key.clone());
+                declared = Some(key);
+
                 // initialise generics map
                 let mut generics: GenericsAssignment<'ast, T> = GGenericsAssignment::default();
 
@@ -1137,6 +1442,8 @@ impl<'ast, T: Field> Checker<'ast, T> {
                             .annotate(UBitwidth::B32),
                     );
 
+                    self.generics.insert(self.id_in_this_scope(generic.name()));
+
                     //we don't have to check for conflicts here, because this was done when checking the signature
                     self.insert_into_scope(generic.name(), Type::Uint(UBitwidth::B32), false);
                 }
@@ -1156,6 +1463,80 @@ impl<'ast, T: Field> Checker<'ast, T> {
                         });
                     }
 
+                    // derived nonces are private field elements of the entrypoint function
+                    if arg.derive_nonce {
+                        if state.main_id != module_id || id != "main" {
+                            errors.push(ErrorInner {
+                                pos: Some(pos),
+                                message:
+                                    "`#[derive_nonce]` is only allowed on arguments of the entrypoint function"
+                                        .into(),
+                            });
+                        } else if arg.is_private != Some(true)
+                            || *decl_ty != DeclarationType::FieldElement
+                        {
+                            errors.push(ErrorInner {
+                                pos: Some(pos),
+                                message: format!(
+                                    "`#[derive_nonce]` argument `{}` must be a private field element",
+                                    arg.id.value.id
+                                ),
+                            });
+                        }
+                    }
+
+                    // arguments bound at setup are private arguments of the entrypoint function
+                    if arg.bind_at_setup {
+                        if state.main_id != module_id || id != "main" {
+                            errors.push(ErrorInner {
+                                pos: Some(pos),
+                                message:
+                                    "`#[bind_at_setup]` is only allowed on arguments of the entrypoint function"
+                                        .into(),
+                            });
+                        } else if arg.derive_nonce {
+                            errors.push(ErrorInner {
+                                pos: Some(pos),
+                                message: format!(
+                                    "Argument `{}` cannot be both derived with `#[derive_nonce]` and bound with `#[bind_at_setup]`",
+                                    arg.id.value.id
+                                ),
+                            });
+                        } else if arg.is_private != Some(true) {
+                            errors.push(ErrorInner {
+                                pos: Some(pos),
+                                message: format!(
+                                    "`#[bind_at_setup]` argument `{}` must be private",
+                                    arg.id.value.id
+                                ),
+                            });
+                        }
+                    }
+
+                    // bounded field elements are arguments of the entrypoint function, whose bound leaves room for
+                    // the comparisons of field elements
+                    if let Some(bound) = arg.bound {
+                        let max_bound = T::get_required_bits() - 2;
+
+                        if state.main_id != module_id || id != "main" {
+                            errors.push(ErrorInner {
+                                pos: Some(pos),
+                                message: format!(
+                                    "`field<{}>` is only allowed for arguments of the entrypoint function and members of structs",
+                                    bound
+                                ),
+                            });
+                        } else if bound == 0 || bound as usize > max_bound {
+                            errors.push(ErrorInner {
+                                pos: Some(pos),
+                                message: format!(
+                                    "Expected the bound of argument `{}` to be between 1 and {} bits, found {}",
+                                    arg.id.value.id, max_bound, bound
+                                ),
+                            });
+                        }
+                    }
+
                     let decl_v = DeclarationVariable::new(
                         self.id_in_this_scope(arg.id.value.id),
                         decl_ty.clone(),
@@ -1177,16 +1558,35 @@ impl<'ast, T: Field> Checker<'ast, T> {
                     match self.scope.insert(id, info) {
                         false => {}
                         true => {
+                            let message = match (generic_spans.get(id), argument_spans.get(id)) {
+                                (Some(generic_pos), _) => format!(
+                                    "Argument `{}` shadows the generic parameter `{}`\n\tgeneric parameter declared at {}",
+                                    id, id, generic_pos.0
+                                ),
+                                (None, Some(first_pos)) => format!(
+                                    "Duplicate name in function definition: `{}` was previously declared as an argument\n\tfirst declared at {}",
+                                    id, first_pos.0
+                                ),
+                                (None, None) => format!(
+                                    "Duplicate name in function definition: `{}` was previously declared as a constant",
+                                    id
+                                ),
+                            };
+
                             errors.push(ErrorInner {
                                 pos: Some(pos),
-                                message: format!("Duplicate name in function definition: `{}` was previously declared as an argument, a generic parameter or a constant", arg.id.value.id)
+                                message,
                             });
                         }
                     };
+                    argument_spans.entry(id).or_insert(pos);
 
                     arguments_checked.push(DeclarationParameter {
                         id: decl_v,
                         private: arg.is_private.unwrap_or(false),
+                        derive_nonce: arg.derive_nonce,
+                        bind_at_setup: arg.bind_at_setup,
+                        bound: arg.bound,
                     });
                 }
 
@@ -1194,6 +1594,7 @@ impl<'ast, T: Field> Checker<'ast, T> {
 
                 for stat in funct.statements.into_iter() {
                     let pos = Some(stat.pos());
+                    let span_log = self.span_log(stat.pos(), module_id);
 
                     if let Statement::Return(..) = stat.value {
                         if found_return {
@@ -1208,6 +1609,7 @@ impl<'ast, T: Field> Checker<'ast, T> {
 
                     match self.check_statement(stat, module_id, &state.types) {
                         Ok(statement) => {
+                            statements_checked.extend(span_log);
                             statements_checked.push(statement);
                         }
                         Err(e) => {
@@ -1239,6 +1641,10 @@ impl<'ast, T: Field> Checker<'ast, T> {
         self.exit_scope();
 
         if !errors.is_empty() {
+            // only functions which check are kept declared
+            if let Some(key) = declared {
+                self.functions.remove(&key);
+            }
             return Err(errors);
         }
 
@@ -1259,40 +1665,13 @@ impl<'ast, T: Field> Checker<'ast, T> {
     ) -> Result<DeclarationSignature<'ast, T>, Vec<ErrorInner>> {
         let mut errors = vec![];
         let mut inputs = vec![];
-        let mut generics = vec![];
-
-        let mut generics_map = BTreeMap::new();
 
-        for (index, g) in signature.generics.iter().enumerate() {
-            if state
-                .constants
-                .get(module_id)
-                .and_then(|m| m.get(g.value))
-                .is_some()
-            {
-                errors.push(ErrorInner {
-                    pos: Some(g.pos()),
-                    message: format!(
-                        "Generic parameter {p} conflicts with constant symbol {p}",
-                        p = g.value
-                    ),
-                });
-            } else {
-                match generics_map.insert(g.value, index).is_none() {
-                    true => {
-                        generics.push(Some(DeclarationConstant::Generic(
-                            GenericIdentifier::with_name(g.value).with_index(index),
-                        )));
-                    }
-                    false => {
-                        errors.push(ErrorInner {
-                            pos: Some(g.pos()),
-                            message: format!("Generic parameter {} is already declared", g.value),
-                        });
-                    }
-                }
-            }
-        }
+        let (declared, generics_map) =
+            Self::check_generic_parameters(&signature.generics, module_id, state, &mut errors);
+        let generics: Vec<_> = declared
+            .into_iter()
+            .map(|g| Some(DeclarationConstant::Generic(g)))
+            .collect();
 
         for t in signature.inputs {
             match self.check_declaration_type(
@@ -1355,6 +1734,14 @@ impl<'ast, T: Field> Checker<'ast, T> {
             UnresolvedType::FieldElement => Ok(Type::FieldElement),
             UnresolvedType::Boolean => Ok(Type::Boolean),
             UnresolvedType::Uint(bitwidth) => Ok(Type::uint(bitwidth)),
+            UnresolvedType::Signed(bitwidth) => Ok(Type::Uint(UBitwidth::signed(bitwidth))),
+            UnresolvedType::BoundedField(bound) => Err(ErrorInner {
+                pos: Some(pos),
+                message: format!(
+                    "`field<{}>` is only allowed for arguments of the entrypoint function and members of structs",
+                    bound
+                ),
+            }),
             UnresolvedType::Array(t, size) => {
                 let size = self.check_expression(size, module_id, types)?;
 
@@ -1517,6 +1904,7 @@ impl<'ast, T: Field> Checker<'ast, T> {
                         }
                     }
                     (None, Some(index)) => Ok(DeclarationConstant::Generic(GenericIdentifier::with_name(name).with_index(*index))),
+                    (None, None) if name == FIELD_SIZE_IN_BITS => Ok(DeclarationConstant::from(field_size_in_bits::<T>())),
                     _ => Err(ErrorInner {
                         pos: Some(pos),
                         message: format!("Undeclared symbol `{}`", name)
@@ -1548,6 +1936,16 @@ impl<'ast, T: Field> Checker<'ast, T> {
             UnresolvedType::FieldElement => Ok(DeclarationType::FieldElement),
             UnresolvedType::Boolean => Ok(DeclarationType::Boolean),
             UnresolvedType::Uint(bitwidth) => Ok(DeclarationType::uint(bitwidth)),
+            UnresolvedType::Signed(bitwidth) => {
+                Ok(DeclarationType::Uint(UBitwidth::signed(bitwidth)))
+            }
+            UnresolvedType::BoundedField(bound) => Err(ErrorInner {
+                pos: Some(pos),
+                message: format!(
+                    "`field<{}>` is only allowed for arguments of the entrypoint function and members of structs",
+                    bound
+                ),
+            }),
             UnresolvedType::Array(t, size) => {
                 let checked_size = self.check_generic_expression(
                     size.clone(),
@@ -1751,14 +2149,83 @@ impl<'ast, T: Field> Checker<'ast, T> {
 
         let var = self.check_for_variable(var, module_id, types)?;
 
-        let checked_statements = statements
-            .into_iter()
-            .map(|s| self.check_statement(s, module_id, types))
-            .collect::<Result<Vec<_>, _>>()?;
+        let mut checked_statements = vec![];
+        for s in statements {
+            checked_statements.extend(self.span_log(s.pos(), module_id));
+            checked_statements.push(self.check_statement(s, module_id, types)?);
+        }
 
         Ok(TypedStatement::For(var, from, to, checked_statements))
     }
 
+    // integer expressions used as integers of a given type must fit in its range when their value is known at compile
+    // time. Negated literals are accepted for signed types, so that the smallest value of the type can be written
+    fn check_int_range(
+        e: &TypedExpression<'ast, T>,
+        bitwidth: UBitwidth,
+        pos: (Position, Position),
+    ) -> Result<(), ErrorInner> {
+        let value = match e {
+            TypedExpression::Int(IntExpression::Neg(inner)) if bitwidth.is_signed() => {
+                inner.try_evaluate().map(|v| (e, v, true))
+            }
+            TypedExpression::Int(inner) => inner.try_evaluate().map(|v| (e, v, false)),
+            _ => None,
+        };
+
+        let one = || BigUint::from(1u32);
+
+        // the magnitude of the smallest and the largest value of the type
+        let (min, max) = match bitwidth.is_signed() {
+            true => (
+                one() << (bitwidth.to_usize() - 1),
+                (one() << (bitwidth.to_usize() - 1)) - one(),
+            ),
+            false => (BigUint::from(0u32), (one() << bitwidth.to_usize()) - one()),
+        };
+
+        let (e, value) = match value {
+            Some((e, value, true)) if value > min => (e, format!("-{}", value)),
+            Some((e, value, false)) if value > max => (e, value.to_string()),
+            _ => return Ok(()),
+        };
+
+        let min = match bitwidth.is_signed() {
+            true => format!("-{}", min),
+            false => min.to_string(),
+        };
+
+        Err(ErrorInner {
+            pos: Some(pos),
+            message: match e {
+                TypedExpression::Int(IntExpression::Value(_)) if !bitwidth.is_signed() => format!(
+                    "Literal `{}` does not fit in type `{}`, expected a value between {} and {} (use `{}w{}` to truncate it explicitly)",
+                    value,
+                    Type::<T>::Uint(bitwidth),
+                    min,
+                    max,
+                    value,
+                    bitwidth.to_usize()
+                ),
+                TypedExpression::Int(IntExpression::Value(_)) => format!(
+                    "Literal `{}` does not fit in type `{}`, expected a value between {} and {}",
+                    value,
+                    Type::<T>::Uint(bitwidth),
+                    min,
+                    max,
+                ),
+                e => format!(
+                    "Expression `{}` evaluates to {}, which does not fit in type `{}`, expected a value between {} and {}",
+                    e,
+                    value,
+                    Type::<T>::Uint(bitwidth),
+                    min,
+                    max
+                ),
+            },
+        })
+    }
+
     // the assignee is already checked to be defined and mutable
     fn check_rhs(
         &mut self,
@@ -1857,7 +2324,84 @@ impl<'ast, T: Field> Checker<'ast, T> {
                     SourceMetadata::new(module_id.display().to_string(), pos.0),
                 )])
             }
+            AssemblyStatement::UnsafeWitness(assignee, plugin, arguments) => {
+                let assignee = self.check_assignee(assignee, module_id, types)?;
+
+                if !is_field_based(&assignee.get_type()) {
+                    return Err(ErrorInner {
+                        pos: Some(pos),
+                        message: format!(
+                            "Expected the outputs of plugin `{}` to be of type field or an array of field, found {}",
+                            plugin,
+                            assignee.get_type()
+                        ),
+                    });
+                }
+
+                let arguments = arguments
+                    .into_iter()
+                    .map(|a| {
+                        let a = self.check_expression(a, module_id, types)?;
+                        match FieldElementExpression::try_from_typed(a) {
+                            Ok(a) => Ok(a.into()),
+                            Err(a) if is_field_based(&a.get_type()) => Ok(a),
+                            Err(a) => Err(ErrorInner {
+                                pos: Some(pos),
+                                message: format!(
+                                    "Expected the arguments of plugin `{}` to be of type field or an array of field, found {}",
+                                    plugin,
+                                    a.get_type()
+                                ),
+                            }),
+                        }
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                Ok(vec![TypedAssemblyStatement::UnsafeWitness(
+                    assignee, plugin, arguments,
+                )])
+            }
+        }
+    }
+
+    // check that the outputs of each plugin are used in a later constraint of the same asm block, as the compiler
+    // cannot check them otherwise
+    fn check_unsafe_witnesses(
+        statements: &[TypedAssemblyStatement<'ast, T>],
+        positions: &[(usize, (Position, Position))],
+    ) -> Result<(), ErrorInner> {
+        for (index, pos) in positions {
+            if let TypedAssemblyStatement::UnsafeWitness(assignee, plugin, _) = &statements[*index]
+            {
+                let mut finder = IdentifierFinder {
+                    id: &assignee_base(assignee).id,
+                    found: false,
+                };
+
+                // a constraint whose sides are the same holds for any value, so it does not check the outputs
+                for s in &statements[index + 1..] {
+                    match s {
+                        TypedAssemblyStatement::Constraint(lhs, rhs, _) if lhs != rhs => {
+                            finder.fold_field_expression(lhs.clone());
+                            finder.fold_field_expression(rhs.clone());
+                        }
+                        _ => {}
+                    }
+                }
+
+                if !finder.found {
+                    return Err(ErrorInner {
+                        pos: Some(*pos),
+                        message: format!(
+                            "The outputs of plugin `{}` assigned to `{}` must be checked by a constraint in the same asm block",
+                            plugin, assignee
+                        ),
+                    });
+                }
+            }
         }
+
+        Ok(())
     }
 
     fn check_statement(
@@ -1871,12 +2415,19 @@ impl<'ast, T: Field> Checker<'ast, T> {
         match stat.value {
             Statement::Assembly(statements) => {
                 let mut checked_statements = vec![];
+                // the plugin calls, by index in the checked statements
+                let mut unsafe_witnesses = vec![];
                 for s in statements {
+                    if let AssemblyStatement::UnsafeWitness(..) = s.value {
+                        unsafe_witnesses.push((checked_statements.len(), s.pos()));
+                    }
                     checked_statements.extend(
                         self.check_assembly_statement(s, module_id, types)
                             .map_err(|e| vec![e])?,
                     );
                 }
+                Self::check_unsafe_witnesses(&checked_statements, &unsafe_witnesses)
+                    .map_err(|e| vec![e])?;
                 Ok(TypedStatement::Assembly(checked_statements))
             }
             Statement::Log(l, expressions) => {
@@ -1899,6 +2450,28 @@ impl<'ast, T: Field> Checker<'ast, T> {
                     }
                 }
 
+                // `{:x}` is only defined for unsigned integers and arrays of them
+                fn is_hex_formattable<T>(ty: &Type<'_, T>) -> bool {
+                    match ty {
+                        Type::Uint(_) => true,
+                        Type::Array(array_type) => is_hex_formattable(&array_type.ty),
+                        _ => false,
+                    }
+                }
+
+                for (index, e) in expressions.iter().enumerate() {
+                    if l.spec(index) == FormatSpec::Hex && !is_hex_formattable(&e.get_type()) {
+                        errors.push(ErrorInner {
+                            pos: Some(pos),
+                            message: format!(
+                                "Cannot format expression `{}` of type {} as hexadecimal, expected an unsigned integer",
+                                e,
+                                e.get_type()
+                            ),
+                        });
+                    }
+                }
+
                 if expressions.len() != l.len() {
                     errors.push(ErrorInner {
                         pos: Some(pos),
@@ -2015,8 +2588,11 @@ impl<'ast, T: Field> Checker<'ast, T> {
                     Type::Boolean => {
                         BooleanExpression::try_from_typed(checked_expr).map(TypedExpression::from)
                     }
-                    Type::Uint(bitwidth) => UExpression::try_from_typed(checked_expr, &bitwidth)
-                        .map(TypedExpression::from),
+                    Type::Uint(bitwidth) => {
+                        Self::check_int_range(&checked_expr, bitwidth, pos).map_err(|e| vec![e])?;
+                        UExpression::try_from_typed(checked_expr, &bitwidth)
+                            .map(TypedExpression::from)
+                    }
                     Type::Array(ref array_ty) => {
                         ArrayExpression::try_from_typed(checked_expr, array_ty)
                             .map(TypedExpression::from)
@@ -2062,8 +2638,11 @@ impl<'ast, T: Field> Checker<'ast, T> {
                     Type::Boolean => {
                         BooleanExpression::try_from_typed(checked_expr).map(TypedExpression::from)
                     }
-                    Type::Uint(bitwidth) => UExpression::try_from_typed(checked_expr, &bitwidth)
-                        .map(TypedExpression::from),
+                    Type::Uint(bitwidth) => {
+                        Self::check_int_range(&checked_expr, bitwidth, pos).map_err(|e| vec![e])?;
+                        UExpression::try_from_typed(checked_expr, &bitwidth)
+                            .map(TypedExpression::from)
+                    }
                     Type::Array(ref array_ty) => {
                         ArrayExpression::try_from_typed(checked_expr, array_ty)
                             .map(TypedExpression::from)
@@ -2091,19 +2670,38 @@ impl<'ast, T: Field> Checker<'ast, T> {
                 .map(|e| TypedStatement::Definition(assignee, e.into()))
                 .map_err(|e| vec![e])
             }
-            Statement::Assertion(e, message) => {
+            Statement::Assertion(e, message, critical) => {
+                // the compared value, which failures on the equality of arrays, structs and tuples refer to
+                let compared = match &e.value {
+                    Expression::Eq(box left, _) => Some(left.to_string()),
+                    _ => None,
+                };
+
                 let e = self
                     .check_expression(e, module_id, types)
                     .map_err(|e| vec![e])?;
 
                 match e {
-                    TypedExpression::Boolean(e) => Ok(TypedStatement::Assertion(
-                        e,
-                        RuntimeError::SourceAssertion(
-                            SourceMetadata::new(module_id.display().to_string(), pos.0)
-                                .message(message),
-                        ),
-                    )),
+                    TypedExpression::Boolean(e) => {
+                        let element = compared.filter(|_| {
+                            matches!(
+                                e,
+                                BooleanExpression::ArrayEq(_)
+                                    | BooleanExpression::StructEq(_)
+                                    | BooleanExpression::TupleEq(_)
+                            )
+                        });
+
+                        Ok(TypedStatement::Assertion(
+                            e,
+                            RuntimeError::SourceAssertion(
+                                SourceMetadata::new(module_id.display().to_string(), pos.0)
+                                    .message(message)
+                                    .critical(critical)
+                                    .element(element),
+                            ),
+                        ))
+                    }
                     e => Err(ErrorInner {
                         pos: Some(pos),
                         message: format!(
@@ -2115,14 +2713,32 @@ impl<'ast, T: Field> Checker<'ast, T> {
                 }
                 .map_err(|e| vec![e])
             }
-            Statement::For(var, from, to, statements) => {
+            Statement::For(var, from, to, statements, label) => {
                 self.enter_scope();
 
                 let res = self.check_for_loop(var, (from, to), statements, pos, module_id, types);
 
                 self.exit_scope();
 
-                res
+                // the body of a labeled loop is marked with the label and the index, so that the constraints of each
+                // iteration can be told apart once the loop is unrolled
+                res.map(|s| match (s, label) {
+                    (TypedStatement::For(var, from, to, statements), Some(label)) => {
+                        let index =
+                            UExpression::identifier(var.id.clone()).annotate(UBitwidth::B32);
+
+                        TypedStatement::For(
+                            var,
+                            from,
+                            to,
+                            std::iter::once(TypedStatement::PushIterationLog(label, index))
+                                .chain(statements)
+                                .chain(std::iter::once(TypedStatement::PopIterationLog))
+                                .collect(),
+                        )
+                    }
+                    (s, _) => s,
+                })
             }
         }
     }
@@ -2306,6 +2922,15 @@ impl<'ast, T: Field> Checker<'ast, T> {
             }),
         }?;
 
+        // casts are available in every module without being imported
+        if let Some(embed) = FlatEmbed::cast(fun_id) {
+            let key = DeclarationFunctionKey::with_location(module_id.to_path_buf(), fun_id)
+                .signature(embed.typed_signature());
+            self.functions.insert(key.clone());
+            self.embeds.insert(key, embed);
+            self.casts.insert(fun_id, embed);
+        }
+
         // check the generic arguments, if any
         let generics_checked: Option<Vec<Option<UExpression<'ast, T>>>> = generics
             .map(|generics| {
@@ -2358,8 +2983,23 @@ impl<'ast, T: Field> Checker<'ast, T> {
 
                 let f = functions.pop().unwrap();
 
+                // the depth of a Merkle path sets the number of hashes, so it has to be known at compile time
+                if let Some(FlatEmbed::MerkleVerifyPoseidon) = self.embeds.get(&f) {
+                    self.check_merkle_depth(&generics_checked, pos)?;
+                }
+                #[cfg(feature = "bellman")]
+                if let Some(FlatEmbed::MerkleVerifySha256) = self.embeds.get(&f) {
+                    self.check_merkle_depth(&generics_checked, pos)?;
+                }
+
                 let signature = f.signature;
 
+                for (a, t) in arguments_checked.iter().zip(signature.inputs.iter()) {
+                    if let DeclarationType::Uint(bitwidth) = t {
+                        Self::check_int_range(a, *bitwidth, pos)?;
+                    }
+                }
+
                 let arguments_checked = arguments_checked.into_iter().zip(signature.inputs.iter()).map(|(a, t)| TypedExpression::align_to_type(a, t)).collect::<Result<Vec<_>, _>>().map_err(|e| ErrorInner {
                     pos: Some(pos),
                     message: format!("Expected function call argument to be of type `{}`, found `{}` of type `{}`", e.1, e.0, e.0.get_type())
@@ -2384,38 +3024,40 @@ impl<'ast, T: Field> Checker<'ast, T> {
                     signature: signature.clone(),
                 };
 
+                let span = Some(SourceMetadata::new(module_id.display().to_string(), pos.0));
+
                 match output_type {
                     Type::Int => unreachable!(),
-                    Type::FieldElement => Ok(FieldElementExpression::function_call(
+                    Type::FieldElement => Ok(FieldElementExpression::FunctionCall(FunctionCallExpression::new(
                         function_key,
                         generics_checked,
                         arguments_checked,
-                    ).into()),
-                    Type::Boolean => Ok(BooleanExpression::function_call(
+                    ).span(span)).into()),
+                    Type::Boolean => Ok(BooleanExpression::FunctionCall(FunctionCallExpression::new(
                         function_key,
                         generics_checked,
                         arguments_checked,
-                    ).into()),
-                    Type::Uint(bitwidth) => Ok(UExpression::function_call(
+                    ).span(span)).into()),
+                    Type::Uint(bitwidth) => Ok(UExpressionInner::FunctionCall(FunctionCallExpression::new(
                         function_key,
                         generics_checked,
                         arguments_checked,
-                    ).annotate(bitwidth).into()),
-                    Type::Struct(struct_ty) => Ok(StructExpression::function_call(
+                    ).span(span)).annotate(bitwidth).into()),
+                    Type::Struct(struct_ty) => Ok(StructExpressionInner::FunctionCall(FunctionCallExpression::new(
                         function_key,
                         generics_checked,
                         arguments_checked,
-                    ).annotate(struct_ty).into()),
-                    Type::Array(array_ty) => Ok(ArrayExpression::function_call(
+                    ).span(span)).annotate(struct_ty).into()),
+                    Type::Array(array_ty) => Ok(ArrayExpressionInner::FunctionCall(FunctionCallExpression::new(
                         function_key,
                         generics_checked,
                         arguments_checked,
-                    ).annotate(*array_ty.ty, *array_ty.size).into()),
-                    Type::Tuple(tuple_ty) => Ok(TupleExpression::function_call(
+                    ).span(span)).annotate(*array_ty.ty, *array_ty.size).into()),
+                    Type::Tuple(tuple_ty) => Ok(TupleExpressionInner::FunctionCall(FunctionCallExpression::new(
                         function_key,
                         generics_checked,
                         arguments_checked,
-                    ).annotate(tuple_ty).into()),
+                    ).span(span)).annotate(tuple_ty).into()),
                 }
             }
             0 => Err(ErrorInner {
@@ -2468,13 +3110,15 @@ impl<'ast, T: Field> Checker<'ast, T> {
                             Type::Int => unreachable!(),
                         }
                     }
-                    None => Err(ErrorInner {
+                    // intrinsics can be shadowed like any other symbol
+                    None => intrinsic(name).ok_or_else(|| ErrorInner {
                         pos: Some(pos),
                         message: format!("Identifier \"{}\" is undefined", name),
                     }),
                 }
             }
             Expression::Add(box e1, box e2) => {
+                let operands = (Operand::of(&e1), Operand::of(&e2));
                 let e1_checked = self.check_expression(e1, module_id, types)?;
                 let e2_checked = self.check_expression(e2, module_id, types)?;
 
@@ -2483,10 +3127,7 @@ impl<'ast, T: Field> Checker<'ast, T> {
                 let (e1_checked, e2_checked) = TypedExpression::align_without_integers(
                     e1_checked, e2_checked,
                 )
-                .map_err(|(e1, e2)| ErrorInner {
-                    pos: Some(pos),
-                    message: format!("Cannot apply `+` to {}, {}", e1.get_type(), e2.get_type()),
-                })?;
+                .map_err(|(e1, e2)| binary_operator_error("+", pos, &operands, &e1, &e2))?;
 
                 match (e1_checked, e2_checked) {
                     (Int(e1), Int(e2)) => Ok(IntExpression::Add(box e1, box e2).into()),
@@ -2498,18 +3139,11 @@ impl<'ast, T: Field> Checker<'ast, T> {
                     {
                         Ok((e1 + e2).into())
                     }
-                    (t1, t2) => Err(ErrorInner {
-                        pos: Some(pos),
-
-                        message: format!(
-                            "Cannot apply `+` to {}, {}",
-                            t1.get_type(),
-                            t2.get_type()
-                        ),
-                    }),
+                    (t1, t2) => Err(binary_operator_error("+", pos, &operands, &t1, &t2)),
                 }
             }
             Expression::Sub(box e1, box e2) => {
+                let operands = (Operand::of(&e1), Operand::of(&e2));
                 let e1_checked = self.check_expression(e1, module_id, types)?;
                 let e2_checked = self.check_expression(e2, module_id, types)?;
 
@@ -2518,10 +3152,7 @@ impl<'ast, T: Field> Checker<'ast, T> {
                 let (e1_checked, e2_checked) = TypedExpression::align_without_integers(
                     e1_checked, e2_checked,
                 )
-                .map_err(|(e1, e2)| ErrorInner {
-                    pos: Some(pos),
-                    message: format!("Cannot apply `-` to {}, {}", e1.get_type(), e2.get_type()),
-                })?;
+                .map_err(|(e1, e2)| binary_operator_error("-", pos, &operands, &e1, &e2))?;
 
                 match (e1_checked, e2_checked) {
                     (Int(e1), Int(e2)) => Ok(IntExpression::Sub(box e1, box e2).into()),
@@ -2529,18 +3160,11 @@ impl<'ast, T: Field> Checker<'ast, T> {
                         Ok(FieldElementExpression::Sub(box e1, box e2).into())
                     }
                     (Uint(e1), Uint(e2)) if e1.get_type() == e2.get_type() => Ok((e1 - e2).into()),
-                    (t1, t2) => Err(ErrorInner {
-                        pos: Some(pos),
-
-                        message: format!(
-                            "Expected only field elements, found {}, {}",
-                            t1.get_type(),
-                            t2.get_type()
-                        ),
-                    }),
+                    (t1, t2) => Err(binary_operator_error("-", pos, &operands, &t1, &t2)),
                 }
             }
             Expression::Mult(box e1, box e2) => {
+                let operands = (Operand::of(&e1), Operand::of(&e2));
                 let e1_checked = self.check_expression(e1, module_id, types)?;
                 let e2_checked = self.check_expression(e2, module_id, types)?;
 
@@ -2549,10 +3173,7 @@ impl<'ast, T: Field> Checker<'ast, T> {
                 let (e1_checked, e2_checked) = TypedExpression::align_without_integers(
                     e1_checked, e2_checked,
                 )
-                .map_err(|(e1, e2)| ErrorInner {
-                    pos: Some(pos),
-                    message: format!("Cannot apply `*` to {}, {}", e1.get_type(), e2.get_type()),
-                })?;
+                .map_err(|(e1, e2)| binary_operator_error("*", pos, &operands, &e1, &e2))?;
 
                 match (e1_checked, e2_checked) {
                     (Int(e1), Int(e2)) => Ok(IntExpression::Mult(box e1, box e2).into()),
@@ -2564,18 +3185,11 @@ impl<'ast, T: Field> Checker<'ast, T> {
                     {
                         Ok((e1 * e2).into())
                     }
-                    (t1, t2) => Err(ErrorInner {
-                        pos: Some(pos),
-
-                        message: format!(
-                            "Cannot apply `*` to {}, {}",
-                            t1.get_type(),
-                            t2.get_type()
-                        ),
-                    }),
+                    (t1, t2) => Err(binary_operator_error("*", pos, &operands, &t1, &t2)),
                 }
             }
             Expression::Div(box e1, box e2) => {
+                let operands = (Operand::of(&e1), Operand::of(&e2));
                 let e1_checked = self.check_expression(e1, module_id, types)?;
                 let e2_checked = self.check_expression(e2, module_id, types)?;
 
@@ -2584,59 +3198,41 @@ impl<'ast, T: Field> Checker<'ast, T> {
                 let (e1_checked, e2_checked) = TypedExpression::align_without_integers(
                     e1_checked, e2_checked,
                 )
-                .map_err(|(e1, e2)| ErrorInner {
-                    pos: Some(pos),
-                    message: format!("Cannot apply `/` to {}, {}", e1.get_type(), e2.get_type()),
-                })?;
+                .map_err(|(e1, e2)| binary_operator_error("/", pos, &operands, &e1, &e2))?;
 
                 match (e1_checked, e2_checked) {
                     (Int(e1), Int(e2)) => Ok(IntExpression::Div(box e1, box e2).into()),
-                    (FieldElement(e1), FieldElement(e2)) => {
-                        Ok(FieldElementExpression::Div(box e1, box e2).into())
-                    }
+                    (FieldElement(e1), FieldElement(e2)) => Ok(FieldElementExpression::Div(
+                        box e1,
+                        box e2,
+                        Some(SourceMetadata::new(module_id.display().to_string(), pos.0)),
+                    )
+                    .into()),
                     (TypedExpression::Uint(e1), TypedExpression::Uint(e2))
-                        if e1.get_type() == e2.get_type() =>
+                        if e1.get_type() == e2.get_type() && !e1.bitwidth().is_signed() =>
                     {
                         Ok((e1 / e2).into())
                     }
-                    (t1, t2) => Err(ErrorInner {
-                        pos: Some(pos),
-
-                        message: format!(
-                            "Cannot apply `/` to {}, {}",
-                            t1.get_type(),
-                            t2.get_type()
-                        ),
-                    }),
+                    (t1, t2) => Err(binary_operator_error("/", pos, &operands, &t1, &t2)),
                 }
             }
             Expression::Rem(box e1, box e2) => {
+                let operands = (Operand::of(&e1), Operand::of(&e2));
                 let e1_checked = self.check_expression(e1, module_id, types)?;
                 let e2_checked = self.check_expression(e2, module_id, types)?;
 
                 let (e1_checked, e2_checked) = TypedExpression::align_without_integers(
                     e1_checked, e2_checked,
                 )
-                .map_err(|(e1, e2)| ErrorInner {
-                    pos: Some(pos),
-                    message: format!("Cannot apply `%` to {}, {}", e1.get_type(), e2.get_type()),
-                })?;
+                .map_err(|(e1, e2)| binary_operator_error("%", pos, &operands, &e1, &e2))?;
 
                 match (e1_checked, e2_checked) {
                     (TypedExpression::Uint(e1), TypedExpression::Uint(e2))
-                        if e1.get_type() == e2.get_type() =>
+                        if e1.get_type() == e2.get_type() && !e1.bitwidth().is_signed() =>
                     {
                         Ok((e1 % e2).into())
                     }
-                    (t1, t2) => Err(ErrorInner {
-                        pos: Some(pos),
-
-                        message: format!(
-                            "Cannot apply `%` to {}, {}",
-                            t1.get_type(),
-                            t2.get_type()
-                        ),
-                    }),
+                    (t1, t2) => Err(binary_operator_error("%", pos, &operands, &t1, &t2)),
                 }
             }
             Expression::Pow(box e1, box e2) => {
@@ -2792,6 +3388,18 @@ impl<'ast, T: Field> Checker<'ast, T> {
                 })?,
             )
             .into()),
+            Expression::OutOfRangeUintConstant(n, bitwidth) => Err(ErrorInner {
+                pos: Some(pos),
+                message: format!(
+                    "Literal `{}u{}` does not fit in type `u{}`, expected a value between 0 and {} (use `{}w{}` to truncate it explicitly)",
+                    n,
+                    bitwidth,
+                    bitwidth,
+                    (BigUint::from(1u32) << bitwidth) - BigUint::from(1u32),
+                    n,
+                    bitwidth
+                ),
+            }),
             Expression::U8Constant(n) => Ok(UExpressionInner::Value(n.into()).annotate(8).into()),
             Expression::U16Constant(n) => Ok(UExpressionInner::Value(n.into()).annotate(16).into()),
             Expression::U32Constant(n) => Ok(UExpressionInner::Value(n.into()).annotate(32).into()),
@@ -2924,6 +3532,11 @@ impl<'ast, T: Field> Checker<'ast, T> {
                 })?;
 
                 match (e1_checked, e2_checked) {
+                    // integers which were not given a type, such as `FIELD_MODULUS`, can be compared to literals
+                    (
+                        TypedExpression::Int(IntExpression::Value(v1)),
+                        TypedExpression::Int(IntExpression::Value(v2)),
+                    ) => Ok(BooleanExpression::Value(v1 == v2).into()),
                     (TypedExpression::FieldElement(e1), TypedExpression::FieldElement(e2)) => {
                         Ok(BooleanExpression::FieldEq(EqExpression::new(e1, e2)).into())
                     }
@@ -3465,6 +4078,7 @@ impl<'ast, T: Field> Checker<'ast, T> {
                             Ok(StructMember {
                                 id: m.id.clone(),
                                 ty: box v.get_type().clone(),
+                                bound: m.bound,
                             })
                         }
                     })
@@ -3580,7 +4194,9 @@ impl<'ast, T: Field> Checker<'ast, T> {
                     TypedExpression::Int(e1) => {
                         Ok(IntExpression::RightShift(box e1, box e2).into())
                     }
-                    TypedExpression::Uint(e1) => Ok(UExpression::right_shift(e1, e2).into()),
+                    TypedExpression::Uint(e1) if !e1.bitwidth().is_signed() => {
+                        Ok(UExpression::right_shift(e1, e2).into())
+                    }
                     TypedExpression::FieldElement(e1) => {
                         Ok(FieldElementExpression::RightShift(box e1, box e2).into())
                     }
@@ -3725,6 +4341,41 @@ impl<'ast, T: Field> Checker<'ast, T> {
         self.scope.insert(id, info)
     }
 
+    // check that the explicit depth of a Merkle path, if any, only depends on literals, constants and generic parameters
+    fn check_merkle_depth(
+        &self,
+        generics: &Option<Vec<Option<UExpression<'ast, T>>>>,
+        pos: (Position, Position),
+    ) -> Result<(), ErrorInner> {
+        match generics.iter().flatten().flatten().next() {
+            Some(depth) if !self.is_compile_time(depth) => Err(ErrorInner {
+                pos: Some(pos),
+                message: format!(
+                    "Expected the depth of the Merkle path to be a compile-time constant, found `{}`, try a literal, a constant or inferring it from the path",
+                    depth
+                ),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn is_compile_time(&self, e: &UExpression<'ast, T>) -> bool {
+        match &e.inner {
+            UExpressionInner::Value(_) => true,
+            UExpressionInner::Identifier(i) => {
+                matches!(i.id.id, CoreIdentifier::Constant(_)) || self.generics.contains(&i.id.id)
+            }
+            UExpressionInner::Add(left, right)
+            | UExpressionInner::Sub(left, right)
+            | UExpressionInner::Mult(left, right)
+            | UExpressionInner::Div(left, right)
+            | UExpressionInner::Rem(left, right) => {
+                self.is_compile_time(left) && self.is_compile_time(right)
+            }
+            _ => false,
+        }
+    }
+
     fn find_functions(
         &self,
         query: &FunctionQuery<'ast, T>,
@@ -3741,6 +4392,63 @@ impl<'ast, T: Field> Checker<'ast, T> {
     }
 }
 
+// whether values of type `ty` are made of field elements only
+fn is_field_based<T>(ty: &Type<T>) -> bool {
+    match ty {
+        Type::FieldElement => true,
+        Type::Array(array_type) => is_field_based(&array_type.ty),
+        _ => false,
+    }
+}
+
+// the variable an assignee writes to
+fn assignee_base<'a, 'ast, T>(a: &'a TypedAssignee<'ast, T>) -> &'a Variable<'ast, T> {
+    match a {
+        TypedAssignee::Identifier(v) => v,
+        TypedAssignee::Select(a, _)
+        | TypedAssignee::Member(a, _)
+        | TypedAssignee::Element(a, _) => assignee_base(a),
+    }
+}
+
+/// Finds whether the expressions it folds refer to an identifier, ignoring the products by the literal zero where it
+/// has no effect
+struct IdentifierFinder<'a, 'ast> {
+    id: &'a zokrates_ast::typed::Identifier<'ast>,
+    found: bool,
+}
+
+impl<'a, 'ast, T: Field> Folder<'ast, T> for IdentifierFinder<'a, 'ast> {
+    fn fold_name(
+        &mut self,
+        n: zokrates_ast::typed::Identifier<'ast>,
+    ) -> zokrates_ast::typed::Identifier<'ast> {
+        self.found |= n == *self.id;
+        n
+    }
+
+    fn fold_field_expression(
+        &mut self,
+        e: FieldElementExpression<'ast, T>,
+    ) -> FieldElementExpression<'ast, T> {
+        let is_zero = |e: &FieldElementExpression<'ast, T>| match e {
+            FieldElementExpression::Number(n) => *n == T::zero(),
+            _ => false,
+        };
+
+        // `0 * e` and `e * 0` do not depend on `e`
+        let vanishes = match &e {
+            FieldElementExpression::Mult(left, right) => is_zero(left) || is_zero(right),
+            _ => false,
+        };
+
+        match vanishes {
+            true => e,
+            false => zokrates_ast::typed::folder::fold_field_expression(self, e),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -4015,6 +4723,55 @@ mod tests {
             );
         }
 
+        #[test]
+        fn private_symbol_import() {
+            // foo.zok
+            // def _helper() {
+            //   return;
+            // }
+
+            // bar.zok
+            // from "./foo.zok" import _helper as helper;
+
+            // `_helper` can be declared in `foo`, but not imported
+
+            let foo: Module = Module {
+                symbols: vec![SymbolDeclaration {
+                    id: "_helper",
+                    symbol: Symbol::Here(SymbolDefinition::Function(function0())),
+                }
+                .mock()],
+            };
+
+            let bar: Module = Module {
+                symbols: vec![SymbolDeclaration {
+                    id: "helper",
+                    symbol: Symbol::There(SymbolImport::with_id_in_module("_helper", "foo").mock()),
+                }
+                .mock()],
+            };
+
+            let mut state = State::<Bn128Field>::new(
+                vec![("foo".into(), foo), ("bar".into(), bar)]
+                    .into_iter()
+                    .collect(),
+                (*MODULE_ID).clone(),
+            );
+
+            assert_eq!(
+                Checker::default().check_module(&OwnedTypedModuleId::from("foo"), &mut state),
+                Ok(())
+            );
+            assert_eq!(
+                Checker::default()
+                    .check_module(&OwnedTypedModuleId::from("bar"), &mut state)
+                    .unwrap_err()[0]
+                    .inner
+                    .message,
+                "Cannot import private symbol `_helper` from module `foo`"
+            );
+        }
+
         #[test]
         fn duplicate_function_declaration() {
             // def foo() {
@@ -4481,7 +5238,10 @@ mod tests {
         Checker {
             scope,
             functions,
+            embeds: HashMap::new(),
+            generics: HashSet::new(),
             return_type: None,
+            span_logs: false,
         }
     }
 
@@ -4784,6 +5544,7 @@ mod tests {
                 Expression::IntConstant(0usize.into()).mock(),
                 Expression::IntConstant(10usize.into()).mock(),
                 vec![],
+                None,
             )
             .mock(),
             Statement::Return(Some(Expression::Identifier("i").mock())).mock(),
@@ -4808,6 +5569,9 @@ mod tests {
                 message: "Identifier \"i\" is undefined".into()
             }])
         );
+
+        // the function is not declared once its check failed
+        assert!(checker.functions.is_empty());
     }
 
     #[test]
@@ -4832,6 +5596,7 @@ mod tests {
                 Expression::IntConstant(0usize.into()).mock(),
                 Expression::IntConstant(10usize.into()).mock(),
                 for_statements,
+                None,
             )
             .mock(),
             Statement::Return(None).mock(),
@@ -5163,6 +5928,7 @@ mod tests {
                 )
                 .mock(),
                 None,
+                false,
             )
             .mock(),
             Statement::Return(None).mock(),
@@ -5254,7 +6020,7 @@ mod tests {
                 .check_function("main", f, &*MODULE_ID, &state)
                 .unwrap_err()[0]
                 .message,
-            "Duplicate name in function definition: `a` was previously declared as an argument, a generic parameter or a constant"
+            "Duplicate name in function definition: `a` was previously declared as an argument\n\tfirst declared at 42:42"
         );
     }
 
@@ -5445,6 +6211,7 @@ mod tests {
                         )
                         .mock(),
                     ],
+                    None,
                 )
                 .mock(),
                 Statement::Assignment(
@@ -5653,7 +6420,7 @@ mod tests {
                         )
                         .unwrap_err()[0]
                         .message,
-                    "Duplicate key foo in struct definition"
+                    "Duplicate key foo in struct definition\n\tfirst declared at 42:42"
                 );
             }
 
@@ -0,0 +1,431 @@
+//! Module containing the built-in observers of the constraints emitted while compiling, see `compile_observed`.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use std::ops::RangeInclusive;
+use zokrates_ast::ir::Statement;
+use zokrates_field::Field;
+
+pub use zokrates_codegen::{ConstraintSink, Iteration, Origin};
+
+/// The maximum number of rows of the breakdown of a labeled loop, consecutive iterations being grouped if needed
+const MAX_BUCKETS: usize = 16;
+
+/// Counts the constraints emitted by each source function and each iteration of labeled loops, before optimization
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ConstraintCounter {
+    pub total: usize,
+    /// The number of constraints emitted by the body of each function, excluding the functions it calls
+    pub functions: BTreeMap<String, usize>,
+    /// The number of constraints emitted by each iteration of each labeled loop, including the functions it calls
+    /// and the loops it contains. Loops are keyed by the function they are declared in, then by label
+    pub loops: BTreeMap<String, BTreeMap<String, BTreeMap<u32, usize>>>,
+}
+
+impl<T> ConstraintSink<T> for ConstraintCounter {
+    fn on_constraint(&mut self, _: &Statement<T>, provenance: &Origin) {
+        self.total += 1;
+        *self
+            .functions
+            .entry(provenance.function.clone())
+            .or_default() += 1;
+        for iteration in &provenance.iterations {
+            *self
+                .loops
+                .entry(iteration.function.clone())
+                .or_default()
+                .entry(iteration.label.clone())
+                .or_default()
+                .entry(iteration.index)
+                .or_default() += 1;
+        }
+    }
+}
+
+impl ConstraintCounter {
+    /// The number of constraints of the loop labeled `label` in `function`, in at most `MAX_BUCKETS` ranges of
+    /// consecutive iterations. Ranges of iterations which emit no constraint are left out
+    pub fn buckets(&self, function: &str, label: &str) -> Vec<(RangeInclusive<u32>, usize)> {
+        let iterations = match self.loops.get(function).and_then(|loops| loops.get(label)) {
+            Some(iterations) => iterations,
+            None => return vec![],
+        };
+
+        let first = *iterations.keys().next().unwrap();
+        let last = *iterations.keys().last().unwrap();
+        let size = ((last - first) as usize / MAX_BUCKETS + 1) as u32;
+
+        let mut buckets: Vec<(RangeInclusive<u32>, usize)> = vec![];
+        for (index, count) in iterations {
+            let start = first + (index - first) / size * size;
+            match buckets.last_mut() {
+                Some((range, total)) if *range.start() == start => *total += count,
+                _ => buckets.push((start..=(start + size - 1).min(last), *count)),
+            }
+        }
+        buckets
+    }
+}
+
+impl fmt::Display for ConstraintCounter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self
+            .functions
+            .keys()
+            .map(|function| function.len())
+            .chain(std::iter::once("function".len()))
+            .max()
+            .unwrap();
+
+        writeln!(f, "{:<width$}  constraints", "function", width = width)?;
+
+        for (function, count) in &self.functions {
+            writeln!(f, "{:<width$}  {}", function, count, width = width)?;
+        }
+
+        writeln!(f, "{:<width$}  {}", "total", self.total, width = width)?;
+
+        for (function, label) in self
+            .loops
+            .iter()
+            .flat_map(|(function, loops)| loops.keys().map(move |label| (function, label)))
+        {
+            let buckets = self.buckets(function, label);
+            let width = buckets
+                .iter()
+                .map(|(range, _)| format_range(range).len())
+                .chain(std::iter::once("iterations".len()))
+                .max()
+                .unwrap();
+
+            writeln!(f)?;
+            writeln!(f, "'{} in {}", label, function)?;
+            writeln!(f, "{:<width$}  constraints", "iterations", width = width)?;
+            for (range, count) in &buckets {
+                writeln!(
+                    f,
+                    "{:<width$}  {}",
+                    format_range(range),
+                    count,
+                    width = width
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn format_range(range: &RangeInclusive<u32>) -> String {
+    match range.start() == range.end() {
+        true => range.start().to_string(),
+        false => format!("{}..={}", range.start(), range.end()),
+    }
+}
+
+/// Writes the emitted constraints to a CSV table with the columns `index`, `function`, `source` and `constraint`
+///
+/// A sink cannot fail, so the first error is kept and returned by `finish`, after which nothing is written anymore.
+pub struct CsvSink<W: io::Write> {
+    writer: csv::Writer<W>,
+    index: usize,
+    error: Option<csv::Error>,
+}
+
+impl<W: io::Write> CsvSink<W> {
+    pub fn new(writer: W) -> Self {
+        let mut writer = csv::Writer::from_writer(writer);
+        let error = writer
+            .write_record(["index", "function", "source", "constraint"])
+            .err();
+
+        CsvSink {
+            writer,
+            index: 0,
+            error,
+        }
+    }
+
+    /// Flush the table, returning the first error which occurred while writing it
+    pub fn finish(mut self) -> Result<(), csv::Error> {
+        match self.error {
+            Some(e) => Err(e),
+            None => self.writer.flush().map_err(Into::into),
+        }
+    }
+}
+
+impl<T: Field, W: io::Write> ConstraintSink<T> for CsvSink<W> {
+    fn on_constraint(&mut self, constraint: &Statement<T>, provenance: &Origin) {
+        if self.error.is_some() {
+            return;
+        }
+
+        self.error = self
+            .writer
+            .write_record([
+                self.index.to_string(),
+                provenance.function.clone(),
+                provenance
+                    .span
+                    .as_ref()
+                    .map(|span| span.to_string())
+                    .unwrap_or_default(),
+                constraint.to_string(),
+            ])
+            .err();
+        self.index += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::{compile, compile_observed, Observers};
+    use std::io;
+    use typed_arena::Arena;
+    use zokrates_common::pipeline::Pipeline;
+    use zokrates_common::{CompileConfig, Resolver};
+    use zokrates_field::Bn128Field;
+
+    #[derive(Default)]
+    struct RecordingSink<'ast>(Vec<(Statement<'ast, Bn128Field>, Origin)>);
+
+    impl<'ast> ConstraintSink<Bn128Field> for RecordingSink<'ast> {
+        fn on_constraint(&mut self, constraint: &Statement<Bn128Field>, provenance: &Origin) {
+            // the statements of the program do not borrow from the source
+            let constraint = match constraint {
+                Statement::Constraint(quad, lin, error) => {
+                    Statement::Constraint(quad.clone(), lin.clone(), error.clone())
+                }
+                _ => unreachable!(),
+            };
+            self.0.push((constraint, provenance.clone()));
+        }
+    }
+
+    const SOURCE: &str = r#"
+        def square(field x) -> field {
+            return x * x;
+        }
+
+        def main(private field a, u8 b) -> field {
+            assert(square(a) == 4);
+            return a + (b == 2 ? 1 : 0);
+        }
+    "#;
+
+    fn compile_source<'ast>(
+        config: CompileConfig,
+        arena: &'ast Arena<String>,
+        sink: &mut dyn ConstraintSink<Bn128Field>,
+    ) -> Vec<Statement<'ast, Bn128Field>> {
+        let (with_sink, _) = compile_observed::<Bn128Field, io::Error>(
+            SOURCE.to_string(),
+            "main".into(),
+            None::<&dyn Resolver<io::Error>>,
+            config.clone(),
+            arena,
+            Observers {
+                sink: Some(sink),
+                ..Observers::default()
+            },
+        )
+        .unwrap();
+
+        let without_sink = compile::<Bn128Field, io::Error>(
+            SOURCE.to_string(),
+            "main".into(),
+            None::<&dyn Resolver<io::Error>>,
+            config,
+            arena,
+        )
+        .unwrap()
+        .collect();
+
+        // observing the constraints does not change them
+        assert_eq!(with_sink.prog, without_sink.prog);
+
+        with_sink.prog.statements
+    }
+
+    fn constraints<'ast>(
+        statements: Vec<Statement<'ast, Bn128Field>>,
+    ) -> Vec<Statement<'ast, Bn128Field>> {
+        statements
+            .into_iter()
+            .filter(|s| matches!(s, Statement::Constraint(..)))
+            .collect()
+    }
+
+    #[test]
+    fn sees_every_constraint() {
+        // without optimization passes on the ir, the program has exactly the emitted constraints
+        let config =
+            CompileConfig::default().pipeline(Some("panics,uint".parse::<Pipeline>().unwrap()));
+        let arena = Arena::new();
+        let mut sink = RecordingSink::default();
+
+        let statements = compile_source(config, &arena, &mut sink);
+
+        assert_eq!(
+            sink.0.iter().map(|(s, _)| s.clone()).collect::<Vec<_>>(),
+            constraints(statements)
+        );
+
+        // the constraints of the assertion carry its location
+        assert!(sink
+            .0
+            .iter()
+            .any(|(_, origin)| origin.function == "main/main" && origin.span.is_some()));
+
+        // with the default passes, the optimized constraints are rewritten from the emitted ones, and some are removed
+        let arena = Arena::new();
+        let mut counter = ConstraintCounter::default();
+        let statements = compile_source(CompileConfig::default(), &arena, &mut counter);
+
+        assert!(counter.total >= constraints(statements).len());
+        assert_eq!(counter.total, counter.functions.values().sum::<usize>());
+    }
+
+    fn count(source: &str) -> ConstraintCounter {
+        let arena = Arena::new();
+        let mut counter = ConstraintCounter::default();
+
+        compile_observed::<Bn128Field, io::Error>(
+            source.to_string(),
+            "main".into(),
+            None::<&dyn Resolver<io::Error>>,
+            CompileConfig::default(),
+            &arena,
+            Observers {
+                sink: Some(&mut counter),
+                ..Observers::default()
+            },
+        )
+        .unwrap();
+
+        counter
+    }
+
+    #[test]
+    fn count_per_iteration() {
+        // only the first two iterations multiply
+        let counter = count(
+            r#"
+            def main(private field[8] a, private field b) -> field {
+                field mut acc = b;
+                for u32 i in 0..8 as 'rounds: {
+                    acc = i < 2 ? acc * a[i] : acc + a[i];
+                }
+                for u32 i in 0..8 {
+                    acc = acc * a[i];
+                }
+                return acc;
+            }
+        "#,
+        );
+
+        let rounds = &counter.loops["main/main"]["rounds"];
+        let count = |i: u32| rounds.get(&i).copied().unwrap_or(0);
+
+        assert!(count(0) > 0);
+        assert_eq!(count(0), count(1));
+        assert!((2..8).all(|i| count(i) < count(0)));
+
+        // the unlabeled loop is not broken down
+        assert_eq!(counter.loops["main/main"].len(), 1);
+        assert!(rounds.values().sum::<usize>() < counter.total);
+
+        assert!(counter
+            .to_string()
+            .contains("\n'rounds in main/main\niterations  constraints\n0 "));
+    }
+
+    #[test]
+    fn nested_loops_and_buckets() {
+        let counter = count(
+            r#"
+            def main(private field a) -> field {
+                field mut acc = a;
+                for u32 i in 0..64 as 'outer: {
+                    for u32 j in 0..2 as 'inner: {
+                        acc = acc * a;
+                    }
+                }
+                return acc;
+            }
+        "#,
+        );
+
+        // the iterations of the outer loop count the constraints of the inner loop
+        assert_eq!(counter.loops["main/main"]["outer"].len(), 64);
+        assert_eq!(counter.loops["main/main"]["inner"].len(), 2);
+        assert_eq!(
+            counter.loops["main/main"]["outer"].values().sum::<usize>(),
+            counter.loops["main/main"]["inner"].values().sum::<usize>()
+        );
+
+        let buckets = counter.buckets("main/main", "outer");
+        assert_eq!(buckets.len(), 16);
+        assert_eq!(buckets[0].0, 0..=3);
+        assert_eq!(buckets[15].0, 60..=63);
+        assert!(buckets.iter().all(|(_, count)| *count == buckets[0].1));
+
+        assert_eq!(counter.buckets("main/main", "inner").len(), 2);
+        assert_eq!(counter.buckets("main/main", "missing"), vec![]);
+    }
+
+    #[test]
+    fn same_label_in_different_functions() {
+        let counter = count(
+            r#"
+            def square(field a) -> field {
+                field mut acc = a;
+                for u32 i in 0..2 as 'rounds: {
+                    acc = acc * acc;
+                }
+                return acc;
+            }
+
+            def main(private field a) -> field {
+                field mut acc = a;
+                for u32 i in 0..3 as 'rounds: {
+                    acc = square(acc);
+                }
+                return acc;
+            }
+        "#,
+        );
+
+        // the loops are not merged, and the loop of `main` counts the constraints of the loop of `square`
+        assert_eq!(counter.loops["main/main"]["rounds"].len(), 3);
+        assert_eq!(counter.loops["main/square"]["rounds"].len(), 2);
+        assert_eq!(
+            counter.loops["main/main"]["rounds"].values().sum::<usize>(),
+            counter.loops["main/square"]["rounds"]
+                .values()
+                .sum::<usize>()
+        );
+        assert!(counter.to_string().contains("\n'rounds in main/square\n"));
+    }
+
+    #[test]
+    fn dump_csv() {
+        let arena = Arena::new();
+        let mut counter = ConstraintCounter::default();
+        let mut sink = (&mut counter, CsvSink::new(vec![]));
+
+        compile_source(CompileConfig::default(), &arena, &mut sink);
+
+        let (counter, csv) = sink;
+        let csv = String::from_utf8(csv.writer.into_inner().unwrap()).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+
+        assert_eq!(lines[0], "index,function,source,constraint");
+        assert_eq!(lines.len(), counter.total + 1);
+        assert!(lines[1].starts_with("0,main/main,"));
+    }
+}
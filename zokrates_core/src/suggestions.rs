@@ -0,0 +1,227 @@
+//! Suggestions attached to type errors, pointing at a change which would fix them.
+//!
+//! Suggestions are computed from what is known about the operands of an operation before they are checked (their
+//! span and whether they are literals), and from their types once they are checked.
+
+use num_bigint::BigUint;
+use std::fmt;
+use zokrates_ast::typed::types::GType;
+use zokrates_ast::untyped::{Expression, ExpressionNode, NodeValue, Position};
+
+/// An operand of an operation, as seen before it is checked
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operand {
+    pub pos: (Position, Position),
+    /// The value of the operand if it is a numeric literal
+    pub literal: Option<BigUint>,
+}
+
+impl Operand {
+    pub fn of(e: &ExpressionNode) -> Self {
+        let literal = match &e.value {
+            Expression::IntConstant(v) | Expression::FieldConstant(v) => Some(v.clone()),
+            Expression::U8Constant(v) => Some((*v).into()),
+            Expression::U16Constant(v) => Some((*v).into()),
+            Expression::U32Constant(v) => Some((*v).into()),
+            Expression::U64Constant(v) => Some((*v).into()),
+            _ => None,
+        };
+
+        Operand {
+            pos: e.pos(),
+            literal,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Suggestion {
+    /// Write a literal with another suffix
+    Literal { replacement: String },
+    /// Convert an expression with a function of the standard library
+    Cast {
+        function: String,
+        expression: String,
+    },
+}
+
+impl fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Suggestion::Literal { replacement } => {
+                write!(f, "write the literal as `{}`", replacement)
+            }
+            Suggestion::Cast {
+                function,
+                expression,
+            } => write!(
+                f,
+                "convert it with `{}({})`, after `import \"utils/casts/{}\";`",
+                function, expression, function
+            ),
+        }
+    }
+}
+
+/// Suggest how to turn `operand`, displayed as `expression`, from type `from` into type `to`, if any
+///
+/// Literals get a suffix matching the expected type when their value fits in it, other expressions are converted with
+/// the casts of the standard library.
+pub fn suggest_conversion<S>(
+    operand: &Operand,
+    expression: &str,
+    from: &GType<S>,
+    to: &GType<S>,
+) -> Option<Suggestion>
+where
+    GType<S>: fmt::Display,
+{
+    let literal = operand.literal.as_ref().and_then(|value| match to {
+        GType::FieldElement => Some(format!("{}f", value)),
+        GType::Uint(bitwidth) if !bitwidth.is_signed() && value.bits() <= bitwidth.to_usize() => {
+            Some(format!("{}{}", value, to))
+        }
+        _ => None,
+    });
+
+    if let Some(replacement) = literal {
+        return Some(Suggestion::Literal { replacement });
+    }
+
+    let function = match (from, to) {
+        (GType::Uint(_), GType::FieldElement) => format!("{}_to_field", from),
+        (GType::FieldElement, GType::Uint(bitwidth)) if !bitwidth.is_signed() => {
+            format!("field_to_{}", to)
+        }
+        _ => return None,
+    };
+
+    Some(Suggestion::Cast {
+        function,
+        expression: expression.to_string(),
+    })
+}
+
+/// Which operand of a binary operation a suggestion applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Side::Left => write!(f, "left"),
+            Side::Right => write!(f, "right"),
+        }
+    }
+}
+
+/// Suggest how to make the operands of a binary operation mixing a field element and an unsigned integer agree
+///
+/// A literal operand is rewritten to the type of the other one. Otherwise, the integer is converted to a field
+/// element, which always succeeds.
+pub fn suggest_for_operands<S>(
+    left: (&Operand, &str, &GType<S>),
+    right: (&Operand, &str, &GType<S>),
+) -> Option<(Side, Suggestion)>
+where
+    GType<S>: fmt::Display,
+{
+    let is_field_and_uint = matches!(
+        (left.2, right.2),
+        (GType::FieldElement, GType::Uint(_)) | (GType::Uint(_), GType::FieldElement)
+    );
+
+    if !is_field_and_uint {
+        return None;
+    }
+
+    let literal = |(operand, expression, from): (&Operand, &str, &GType<S>), to| {
+        operand
+            .literal
+            .as_ref()
+            .and_then(|_| suggest_conversion(operand, expression, from, to))
+            .filter(|s| matches!(s, Suggestion::Literal { .. }))
+    };
+
+    if let Some(s) = literal(right, left.2) {
+        return Some((Side::Right, s));
+    }
+    if let Some(s) = literal(left, right.2) {
+        return Some((Side::Left, s));
+    }
+
+    // convert the integer
+    let (side, (operand, expression, from), to) = match left.2 {
+        GType::Uint(_) => (Side::Left, left, right.2),
+        _ => (Side::Right, right, left.2),
+    };
+
+    suggest_conversion(
+        &Operand {
+            literal: None,
+            ..operand.clone()
+        },
+        expression,
+        from,
+        to,
+    )
+    .map(|s| (side, s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_ast::typed::types::{ConcreteType, UBitwidth};
+
+    fn operand(literal: Option<u32>) -> Operand {
+        Operand {
+            pos: (Position::mock(), Position::mock()),
+            literal: literal.map(BigUint::from),
+        }
+    }
+
+    #[test]
+    fn literal_suffix() {
+        assert_eq!(
+            suggest_conversion(
+                &operand(Some(42)),
+                "42f",
+                &ConcreteType::FieldElement,
+                &ConcreteType::Uint(UBitwidth::B8)
+            ),
+            Some(Suggestion::Literal {
+                replacement: "42u8".into()
+            })
+        );
+    }
+
+    #[test]
+    fn literal_too_large() {
+        assert_eq!(
+            suggest_conversion(
+                &operand(Some(256)),
+                "256f",
+                &ConcreteType::FieldElement,
+                &ConcreteType::Uint(UBitwidth::B8)
+            ),
+            Some(Suggestion::Cast {
+                function: "field_to_u8".into(),
+                expression: "256f".into()
+            })
+        );
+    }
+
+    #[test]
+    fn no_suggestion_for_other_types() {
+        assert_eq!(
+            suggest_for_operands(
+                (&operand(None), "a", &ConcreteType::Boolean),
+                (&operand(Some(1)), "1f", &ConcreteType::FieldElement)
+            ),
+            None
+        );
+    }
+}
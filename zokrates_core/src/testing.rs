@@ -0,0 +1,239 @@
+//! Module exposing the lowering of single typed expressions to constraints, so that gadgets can be unit tested without
+//! compiling a whole program.
+//!
+//! Only available with the `testing` feature.
+
+use zokrates_analysis::testing::lower;
+use zokrates_ast::typed::types::{ConcreteType, DeclarationSignature, DeclarationType};
+use zokrates_ast::typed::{
+    DeclarationParameter, DeclarationVariable, Typed, TypedExpression, TypedFunction,
+    TypedStatement,
+};
+use zokrates_ast::zir::ZirStatement;
+use zokrates_field::Field;
+
+pub use zokrates_codegen::testing::FlattenedExpression;
+
+/// Lowers `e` alone to constraints
+///
+/// `e` must be a field element, a boolean or an integer, and can only refer to the variables of `context`, which are
+/// mapped to `_0`, `_1`, ... in order. Like in a compiled program, `e` is lowered to zir, its integers are optimized,
+/// and integer results are reduced to their bitwidth. No constraint is added on the variables of the context.
+///
+/// # Panics
+///
+/// If `e` is not a scalar or cannot be lowered without the other passes of the compiler, for example because it
+/// contains a function call.
+pub fn flatten_expression<'ast, T: Field>(
+    e: TypedExpression<'ast, T>,
+    context: &[DeclarationVariable<'ast, T>],
+) -> FlattenedExpression<'ast, T> {
+    let output = DeclarationType::from(ConcreteType::try_from(e.get_type()).unwrap());
+
+    // wrap `e` in a function so that it can go through the lowering passes
+    let f = TypedFunction {
+        arguments: context
+            .iter()
+            .cloned()
+            .map(DeclarationParameter::from)
+            .collect(),
+        statements: vec![TypedStatement::Return(e)],
+        signature: DeclarationSignature::new()
+            .inputs(context.iter().map(|v| v.get_type()).collect())
+            .output(output),
+    };
+
+    let f = lower(f).unwrap();
+
+    let context: Vec<_> = f.arguments.into_iter().map(|p| p.id).collect();
+    let e = match f.statements.into_iter().last() {
+        Some(ZirStatement::Return(mut expressions)) if expressions.len() == 1 => {
+            expressions.pop().unwrap()
+        }
+        _ => panic!("only scalar expressions can be flattened"),
+    };
+
+    zokrates_codegen::testing::flatten_expression(e, &context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_ast::common::{RuntimeError, Variable};
+    use zokrates_ast::ir::{Directive, LinComb, QuadComb, Solver, Statement};
+    use zokrates_ast::typed::{
+        BooleanExpression, FieldElementExpression, Id, UBitwidth, UExpression,
+    };
+    use zokrates_ast::zir::{self, ZirExpression};
+    use zokrates_field::Bn128Field;
+
+    #[test]
+    fn u8_addition() {
+        // a + b, reduced to 8 bits
+        let flattened = flatten_expression::<Bn128Field>(
+            (UExpression::identifier("a".into()).annotate(UBitwidth::B8)
+                + UExpression::identifier("b".into()).annotate(UBitwidth::B8))
+            .into(),
+            &[
+                DeclarationVariable::uint("a", UBitwidth::B8),
+                DeclarationVariable::uint("b", UBitwidth::B8),
+            ],
+        );
+
+        let sum = LinComb::from(Variable::new(0)) + Variable::new(1).into();
+        // the sum holds on 9 bits, `_2` being the most significant one
+        let bits: Vec<_> = (2..11).map(Variable::new).collect();
+
+        let expected: Vec<_> = std::iter::once(Statement::Directive(Directive {
+            inputs: vec![sum.clone().into()],
+            outputs: bits.clone(),
+            solver: Solver::Bits(9),
+        }))
+        .chain(bits.iter().map(|bit| {
+            Statement::Constraint(
+                QuadComb::from_linear_combinations((*bit).into(), (*bit).into()),
+                (*bit).into(),
+                Some(RuntimeError::Bitness),
+            )
+        }))
+        .chain(std::iter::once(Statement::Constraint(
+            LinComb(
+                bits.iter()
+                    .rev()
+                    .enumerate()
+                    .map(|(index, bit)| (*bit, Bn128Field::from(2).pow(index)))
+                    .collect(),
+            )
+            .into(),
+            sum,
+            Some(RuntimeError::Sum),
+        )))
+        .collect();
+
+        assert_eq!(
+            flattened.variables,
+            vec![Variable::new(0), Variable::new(1)]
+        );
+        assert_eq!(flattened.statements, expected);
+        // the most significant bit is dropped
+        assert_eq!(
+            flattened.output,
+            LinComb(
+                bits[1..]
+                    .iter()
+                    .enumerate()
+                    .map(|(index, bit)| (*bit, Bn128Field::from(2).pow(7 - index)))
+                    .collect()
+            )
+        );
+    }
+
+    #[test]
+    fn boolean_and() {
+        let flattened = flatten_expression::<Bn128Field>(
+            BooleanExpression::And(
+                box BooleanExpression::identifier("a".into()),
+                box BooleanExpression::identifier("b".into()),
+            )
+            .into(),
+            &[
+                DeclarationVariable::boolean("a"),
+                DeclarationVariable::boolean("b"),
+            ],
+        );
+
+        assert_eq!(
+            flattened.statements,
+            vec![Statement::definition(
+                Variable::new(2),
+                QuadComb::from_linear_combinations(
+                    Variable::new(0).into(),
+                    Variable::new(1).into()
+                )
+            )]
+        );
+        assert_eq!(flattened.output, Variable::new(2).into());
+    }
+
+    // deep enough to overflow the stack of a test thread if the passes recursed on it without growing the stack
+    const DEPTH: usize = 100_000;
+
+    #[test]
+    fn long_field_sum() {
+        // a + a + ... + a
+        let a = || FieldElementExpression::identifier("a".into());
+        let sum = (1..DEPTH).fold(a(), |sum, _| sum + a());
+
+        let flattened = flatten_expression::<Bn128Field>(
+            sum.into(),
+            &[DeclarationVariable::field_element("a")],
+        );
+
+        assert!(flattened.statements.is_empty());
+        assert_eq!(flattened.output.0.len(), DEPTH);
+        assert!(flattened
+            .output
+            .0
+            .iter()
+            .all(|term| *term == (Variable::new(0), Bn128Field::from(1))));
+    }
+
+    #[test]
+    fn long_uint_sum() {
+        // a + a + ... + a, on 32 bits
+        let a = || UExpression::identifier("a".into()).annotate(UBitwidth::B32);
+        let long_sum = || (1..DEPTH).fold(a(), |sum, _| sum + a());
+
+        let variable = DeclarationVariable::uint("a", UBitwidth::B32);
+        let f = TypedFunction {
+            arguments: vec![variable.clone().into()],
+            statements: vec![TypedStatement::Return(long_sum().into())],
+            signature: DeclarationSignature::new()
+                .inputs(vec![variable.get_type()])
+                .output(variable.get_type()),
+        };
+
+        let sum = match lower::<Bn128Field>(f).unwrap().statements.pop() {
+            Some(ZirStatement::Return(mut expressions)) => match expressions.pop() {
+                Some(ZirExpression::Uint(e)) => e,
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+
+        // the sum fits in the field, so it is only reduced once it is returned
+        let metadata = sum.metadata.clone().unwrap();
+        assert!(metadata.should_reduce.is_true());
+        assert_eq!(
+            metadata.max,
+            Bn128Field::from(DEPTH) * Bn128Field::from(u32::MAX)
+        );
+
+        let mut depth = 1;
+        let mut e = &sum;
+        while let zir::UExpressionInner::Add(left, _) = &e.inner {
+            assert!(left.metadata.as_ref().unwrap().should_reduce.is_false());
+            e = left;
+            depth += 1;
+        }
+        assert_eq!(depth, DEPTH);
+
+        // the chain can be cloned, compared, displayed and dropped like any expression
+        assert_eq!(sum.clone(), sum);
+        assert_eq!(sum.to_string().matches('+').count(), DEPTH - 1);
+        drop(sum);
+
+        // once flattened, the sum is only decomposed once, and its 32 lowest bits are returned
+        let flattened = flatten_expression::<Bn128Field>(long_sum().into(), &[variable]);
+
+        assert_eq!(
+            flattened
+                .statements
+                .iter()
+                .filter(|s| matches!(s, Statement::Directive(_)))
+                .count(),
+            1
+        );
+        assert_eq!(flattened.output.0.len(), 32);
+    }
+}
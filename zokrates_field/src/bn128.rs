@@ -266,6 +266,21 @@ mod tests {
             assert_eq!(fp, FieldPrime::try_from_dec_str(&bv).unwrap());
         }
 
+        #[test]
+        fn dec_string_out_of_range() {
+            let max = FieldPrime::max_value().to_dec_string();
+            assert_eq!(
+                FieldPrime::try_from_dec_str(&max).unwrap(),
+                FieldPrime::max_value()
+            );
+
+            // the modulus and larger values are rejected rather than reduced
+            let modulus = (FieldPrime::max_value().to_biguint() + 1u32).to_string();
+            assert!(FieldPrime::try_from_dec_str(&modulus).is_err());
+            let larger = (FieldPrime::max_value().to_biguint() * 2u32).to_string();
+            assert!(FieldPrime::try_from_dec_str(&larger).is_err());
+        }
+
         #[test]
         fn compact_representation() {
             let one = FieldPrime::from(1);
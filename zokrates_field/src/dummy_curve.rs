@@ -195,6 +195,21 @@ impl Field for FieldPrime {
         unimplemented!()
     }
 
+    fn to_bytes_le(&self) -> Vec<u8> {
+        vec![self.v]
+    }
+
+    fn from_bytes_le(bytes: &[u8]) -> Result<Self, crate::FieldError> {
+        match bytes {
+            [v] if *v < _PRIME => Ok(FieldPrime { v: *v }),
+            [_] => Err(crate::FieldError::NonCanonical),
+            _ => Err(crate::FieldError::InvalidLength {
+                expected: 1,
+                found: bytes.len(),
+            }),
+        }
+    }
+
     fn to_dec_string(&self) -> String {
         unimplemented!()
     }
@@ -0,0 +1,516 @@
+//! The 64-bit prime field of modulus `2**64 - 2**32 + 1`, known as Goldilocks
+//!
+//! No curve is associated with this field: programs can be compiled and executed on it, but not used with the proving
+//! schemes based on pairings.
+
+use crate::{Field, FieldError, FieldParseError, Pow};
+use num_bigint::BigUint;
+use num_traits::{CheckedDiv, One, ToPrimitive, Zero};
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::{From, TryFrom};
+use std::fmt;
+use std::ops::{Add, Div, Mul, Sub};
+
+const MODULUS: u64 = 0xffff_ffff_0000_0001;
+
+#[derive(Default, PartialEq, PartialOrd, Clone, Eq, Ord, Hash)]
+pub struct FieldPrime {
+    v: u64,
+}
+
+impl FieldPrime {
+    fn reduce(v: u128) -> Self {
+        FieldPrime {
+            v: (v % MODULUS as u128) as u64,
+        }
+    }
+
+    // the exponent is a `u64` rather than a `usize`, which is too small for `MODULUS - 2` on 32-bit targets
+    fn pow_u64(self, mut exp: u64) -> Self {
+        let mut res = FieldPrime::one();
+        let mut base = self;
+
+        while exp > 0 {
+            if exp & 1 == 1 {
+                res = res * &base;
+            }
+            base = base.clone() * &base;
+            exp >>= 1;
+        }
+
+        res
+    }
+}
+
+impl fmt::Display for FieldPrime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.v)
+    }
+}
+
+impl fmt::Debug for FieldPrime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.v)
+    }
+}
+
+impl From<u128> for FieldPrime {
+    fn from(num: u128) -> Self {
+        Self::reduce(num)
+    }
+}
+
+impl From<u64> for FieldPrime {
+    fn from(num: u64) -> Self {
+        Self::reduce(num as u128)
+    }
+}
+
+impl From<u32> for FieldPrime {
+    fn from(num: u32) -> Self {
+        FieldPrime { v: num as u64 }
+    }
+}
+
+impl From<u16> for FieldPrime {
+    fn from(num: u16) -> Self {
+        FieldPrime { v: num as u64 }
+    }
+}
+
+impl From<u8> for FieldPrime {
+    fn from(num: u8) -> Self {
+        FieldPrime { v: num as u64 }
+    }
+}
+
+impl From<usize> for FieldPrime {
+    fn from(num: usize) -> Self {
+        Self::reduce(num as u128)
+    }
+}
+
+impl From<bool> for FieldPrime {
+    fn from(b: bool) -> Self {
+        FieldPrime { v: b as u64 }
+    }
+}
+
+impl From<i32> for FieldPrime {
+    fn from(num: i32) -> Self {
+        if num < 0 {
+            FieldPrime::zero() - FieldPrime::from(num.unsigned_abs())
+        } else {
+            FieldPrime::from(num as u32)
+        }
+    }
+}
+
+impl TryFrom<BigUint> for FieldPrime {
+    type Error = ();
+
+    fn try_from(value: BigUint) -> Result<Self, ()> {
+        match value.to_u64() {
+            Some(v) if v < MODULUS => Ok(FieldPrime { v }),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Zero for FieldPrime {
+    fn zero() -> FieldPrime {
+        FieldPrime { v: 0 }
+    }
+    fn is_zero(&self) -> bool {
+        self.v == 0
+    }
+}
+
+impl One for FieldPrime {
+    fn one() -> FieldPrime {
+        FieldPrime { v: 1 }
+    }
+}
+
+impl Add<FieldPrime> for FieldPrime {
+    type Output = FieldPrime;
+
+    fn add(self, other: FieldPrime) -> FieldPrime {
+        self + &other
+    }
+}
+
+impl<'a> Add<&'a FieldPrime> for FieldPrime {
+    type Output = FieldPrime;
+
+    fn add(self, other: &FieldPrime) -> FieldPrime {
+        Self::reduce(self.v as u128 + other.v as u128)
+    }
+}
+
+impl Sub<FieldPrime> for FieldPrime {
+    type Output = FieldPrime;
+
+    fn sub(self, other: FieldPrime) -> FieldPrime {
+        self - &other
+    }
+}
+
+impl<'a> Sub<&'a FieldPrime> for FieldPrime {
+    type Output = FieldPrime;
+
+    fn sub(self, other: &FieldPrime) -> FieldPrime {
+        Self::reduce(self.v as u128 + MODULUS as u128 - other.v as u128)
+    }
+}
+
+impl Mul<FieldPrime> for FieldPrime {
+    type Output = FieldPrime;
+
+    fn mul(self, other: FieldPrime) -> FieldPrime {
+        self * &other
+    }
+}
+
+impl<'a> Mul<&'a FieldPrime> for FieldPrime {
+    type Output = FieldPrime;
+
+    fn mul(self, other: &FieldPrime) -> FieldPrime {
+        Self::reduce(self.v as u128 * other.v as u128)
+    }
+}
+
+impl CheckedDiv for FieldPrime {
+    fn checked_div(&self, other: &FieldPrime) -> Option<FieldPrime> {
+        Some(self.clone() * other.inverse_mul()?)
+    }
+}
+
+impl Div<FieldPrime> for FieldPrime {
+    type Output = FieldPrime;
+
+    fn div(self, other: FieldPrime) -> FieldPrime {
+        self.checked_div(&other).unwrap()
+    }
+}
+
+impl<'a> Div<&'a FieldPrime> for FieldPrime {
+    type Output = FieldPrime;
+
+    fn div(self, other: &FieldPrime) -> FieldPrime {
+        self.checked_div(other).unwrap()
+    }
+}
+
+impl Pow<usize> for FieldPrime {
+    type Output = FieldPrime;
+
+    fn pow(self, exp: usize) -> FieldPrime {
+        self.pow_u64(exp as u64)
+    }
+}
+
+impl num_traits::CheckedAdd for FieldPrime {
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        let bound = Self::max_unique_value();
+
+        assert!(self <= &bound);
+        assert!(other <= &bound);
+
+        // we only go up to 2**(bitwidth - 1) because after that we lose uniqueness of bit decomposition
+        match self.v + other.v {
+            v if v > bound.v => None,
+            v => Some(FieldPrime { v }),
+        }
+    }
+}
+
+impl num_traits::CheckedMul for FieldPrime {
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        let bound = Self::max_unique_value();
+
+        assert!(self <= &bound);
+        assert!(other <= &bound);
+
+        // we only go up to 2**(bitwidth - 1) because after that we lose uniqueness of bit decomposition
+        match self.v as u128 * other.v as u128 {
+            v if v > bound.v as u128 => None,
+            v => Some(FieldPrime { v: v as u64 }),
+        }
+    }
+}
+
+impl Field for FieldPrime {
+    fn to_byte_vector(&self) -> Vec<u8> {
+        self.v.to_le_bytes().to_vec()
+    }
+
+    fn from_byte_vector(bytes: Vec<u8>) -> Self {
+        let v = BigUint::from_bytes_le(&bytes) % MODULUS;
+        FieldPrime {
+            v: v.to_u64().unwrap(),
+        }
+    }
+
+    fn to_bytes_le(&self) -> Vec<u8> {
+        self.to_byte_vector()
+    }
+
+    fn from_bytes_le(bytes: &[u8]) -> Result<Self, FieldError> {
+        let bytes = <[u8; 8]>::try_from(bytes).map_err(|_| FieldError::InvalidLength {
+            expected: 8,
+            found: bytes.len(),
+        })?;
+
+        match u64::from_le_bytes(bytes) {
+            v if v < MODULUS => Ok(FieldPrime { v }),
+            _ => Err(FieldError::NonCanonical),
+        }
+    }
+
+    fn to_dec_string(&self) -> String {
+        self.to_string()
+    }
+
+    fn inverse_mul(&self) -> Option<Self> {
+        match self.is_zero() {
+            true => None,
+            false => Some(self.clone().pow_u64(MODULUS - 2)),
+        }
+    }
+
+    fn min_value() -> Self {
+        FieldPrime::zero()
+    }
+
+    fn max_value() -> Self {
+        FieldPrime { v: MODULUS - 1 }
+    }
+
+    fn max_unique_value() -> Self {
+        FieldPrime { v: (1 << 63) - 1 }
+    }
+
+    fn to_bits_be(&self) -> Vec<bool> {
+        (0..64).rev().map(|i| self.v & (1 << i) != 0).collect()
+    }
+
+    fn get_required_bits() -> usize {
+        64
+    }
+
+    fn try_from_dec_str(s: &str) -> Result<Self, FieldParseError> {
+        Self::try_from_str(s, 10)
+    }
+
+    fn try_from_str(s: &str, radix: u32) -> Result<Self, FieldParseError> {
+        let x = BigUint::parse_bytes(s.as_bytes(), radix).ok_or(FieldParseError)?;
+        FieldPrime::try_from(x).map_err(|_| FieldParseError)
+    }
+
+    fn to_compact_dec_string(&self) -> String {
+        //values up to (p-1)/2 included are represented as positive, values between (p+1)/2 and p-1 as represented as negative by subtracting p
+        if self.v <= (MODULUS - 1) / 2 {
+            self.to_string()
+        } else {
+            format!("(-{})", MODULUS - self.v)
+        }
+    }
+
+    fn id() -> [u8; 4] {
+        use sha2::{Digest, Sha256};
+        let hash = Sha256::digest(&MODULUS.to_le_bytes());
+        let mut res = [0u8; 4];
+        res.copy_from_slice(&hash[..4]);
+        res
+    }
+
+    fn name() -> &'static str {
+        "goldilocks"
+    }
+
+    fn bits(&self) -> u32 {
+        std::cmp::max(64 - self.v.leading_zeros(), 1)
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        BigUint::from(self.v)
+    }
+}
+
+impl Serialize for FieldPrime {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.to_bytes_le())
+    }
+}
+
+struct FieldVisitor;
+
+impl<'de> Visitor<'de> for FieldVisitor {
+    type Value = FieldPrime;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a goldilocks field element")
+    }
+
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        FieldPrime::from_bytes_le(value).map_err(|e| E::custom(e.to_string()))
+    }
+
+    fn visit_byte_buf<E>(self, value: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_bytes(&value[..])
+    }
+
+    fn visit_seq<A>(self, value: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut value = value;
+        let mut elements = vec![];
+        while let Some(v) = value.next_element()? {
+            elements.push(v);
+        }
+
+        self.visit_bytes(&elements[..])
+    }
+}
+
+impl<'de> Deserialize<'de> for FieldPrime {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_bytes(FieldVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_traits::{CheckedAdd, CheckedMul};
+
+    impl<'a> From<&'a str> for FieldPrime {
+        fn from(s: &'a str) -> FieldPrime {
+            FieldPrime::try_from_dec_str(s).unwrap()
+        }
+    }
+
+    #[test]
+    fn required_bits() {
+        assert_eq!(FieldPrime::get_required_bits(), 64);
+        assert_eq!(FieldPrime::max_value().to_bits_be().len(), 64);
+    }
+
+    #[test]
+    fn max_value() {
+        assert_eq!(
+            FieldPrime::max_value(),
+            FieldPrime::from("18446744069414584320")
+        );
+        assert_eq!(
+            FieldPrime::max_value() + FieldPrime::one(),
+            FieldPrime::zero()
+        );
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(
+            FieldPrime::from("65484493"),
+            FieldPrime::from("65416358") + FieldPrime::from("68135")
+        );
+        assert_eq!(
+            FieldPrime::from("18446744069349236098"),
+            FieldPrime::from("68135") - FieldPrime::from("65416358")
+        );
+        assert_eq!(
+            FieldPrime::from("13472"),
+            FieldPrime::from("32") * FieldPrime::from("421")
+        );
+        assert_eq!(
+            FieldPrime::from(-9),
+            FieldPrime::from(-54) / FieldPrime::from(12) * FieldPrime::from(2)
+        );
+        assert_eq!(
+            FieldPrime::from(54).pow(12),
+            FieldPrime::try_from(BigUint::from(54u32).pow(12) % MODULUS).unwrap()
+        );
+        assert_eq!(FieldPrime::from(2).pow(64), FieldPrime::from("4294967295"));
+        assert_eq!(
+            FieldPrime::from(7).inverse_mul().unwrap() * FieldPrime::from(7),
+            FieldPrime::one()
+        );
+        assert_eq!(FieldPrime::zero().inverse_mul(), None);
+        assert_eq!(
+            FieldPrime::max_value().inverse_mul(),
+            Some(FieldPrime::max_value())
+        );
+        assert_eq!(
+            FieldPrime::from(2).inverse_mul().unwrap().to_biguint(),
+            (BigUint::from(MODULUS) + 1u32) / 2u32
+        );
+    }
+
+    #[test]
+    fn checked_operations() {
+        // the largest value with a unique 64 bit decomposition is 2**63 - 1
+        let bound = FieldPrime::max_unique_value();
+        assert_eq!(bound.bits(), 63);
+        assert_eq!(bound.checked_add(&FieldPrime::one()), None);
+        assert_eq!(
+            FieldPrime::from(2)
+                .pow(31)
+                .checked_mul(&FieldPrime::from(2).pow(31)),
+            Some(FieldPrime::from(2).pow(62))
+        );
+        assert_eq!(
+            FieldPrime::from(2)
+                .pow(32)
+                .checked_mul(&FieldPrime::from(2).pow(31)),
+            None
+        );
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(
+            FieldPrime::try_from(FieldPrime::max_value().to_biguint()),
+            Ok(FieldPrime::max_value())
+        );
+        assert_eq!(
+            FieldPrime::try_from(FieldPrime::max_value().to_biguint() + 1u32),
+            Err(())
+        );
+        assert_eq!(FieldPrime::from(u64::MAX), FieldPrime::from("4294967294"));
+        assert_eq!(FieldPrime::max_value().to_compact_dec_string(), "(-1)");
+
+        let fp = FieldPrime::from("101");
+        assert_eq!(fp, FieldPrime::from_byte_vector(fp.to_byte_vector()));
+
+        // inputs longer than the encoding are reduced
+        let mut bytes = FieldPrime::max_value().to_byte_vector();
+        bytes.extend([1, 0, 0]);
+        assert_eq!(
+            FieldPrime::from_byte_vector(bytes.clone()),
+            FieldPrime::try_from(BigUint::from_bytes_le(&bytes) % MODULUS).unwrap()
+        );
+
+        let serialized = serde_json::to_string(&fp).unwrap();
+        assert_eq!(fp, serde_json::from_str(&serialized).unwrap());
+    }
+
+    #[test]
+    fn distinct_id() {
+        assert_ne!(FieldPrime::id(), crate::Bn128Field::id());
+    }
+}
@@ -49,6 +49,29 @@ impl fmt::Debug for FieldParseError {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldError {
+    /// The encoding does not have the canonical length of the field
+    InvalidLength { expected: usize, found: usize },
+    /// The encoded value is not smaller than the modulus
+    NonCanonical,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FieldError::InvalidLength { expected, found } => write!(
+                f,
+                "Expected a field element encoded on {} bytes, found {} bytes",
+                expected, found
+            ),
+            FieldError::NonCanonical => {
+                write!(f, "Encoded value is not smaller than the field modulus")
+            }
+        }
+    }
+}
+
 pub enum G2Type {
     Fq,
     Fq2,
@@ -99,6 +122,11 @@ pub trait Field:
     fn to_byte_vector(&self) -> Vec<u8>;
     /// Returns an element of this `Field` from a little-endian byte vector
     fn from_byte_vector(_: Vec<u8>) -> Self;
+    /// Returns the canonical little-endian encoding of this element, on `(get_required_bits() + 7) / 8` bytes
+    fn to_bytes_le(&self) -> Vec<u8>;
+    /// Decodes an element from its canonical little-endian encoding, rejecting encodings of the wrong
+    /// length and values which are not smaller than the modulus
+    fn from_bytes_le(bytes: &[u8]) -> Result<Self, FieldError>;
     /// Returns this `Field`'s contents as decimal string
     fn to_dec_string(&self) -> String;
     /// Returns the multiplicative inverse, i.e.: self * self.inverse_mul() = Self::one()
@@ -133,8 +161,8 @@ pub trait Field:
 #[macro_use]
 mod prime_field {
     macro_rules! prime_field {
-        ($name:expr, $v:ty, $g2_ty:expr) => {
-            use crate::{Field, FieldParseError, Pow};
+        ($name:expr, fr = $fr:ty, $g2_ty:expr) => {
+            use crate::{Field, FieldError, FieldParseError, Pow};
             use ark_ff::{Field as ArkField, PrimeField};
             use num_bigint::BigUint;
             use num_traits::{CheckedDiv, One, Zero};
@@ -146,7 +174,7 @@ mod prime_field {
             use std::fmt::{Debug, Display};
             use std::ops::{Add, Div, Mul, Sub};
 
-            type Fr = <$v as ark_ec::PairingEngine>::Fr;
+            type Fr = $fr;
 
             #[derive(PartialEq, PartialOrd, Clone, Eq, Ord, Hash)]
             pub struct FieldPrime {
@@ -194,6 +222,36 @@ mod prime_field {
                     }
                 }
 
+                fn to_bytes_le(&self) -> Vec<u8> {
+                    use ark_ff::BigInteger;
+                    let mut bytes = self.v.into_repr().to_bytes_le();
+                    // the representation may have more limbs than needed, the extra bytes are zero
+                    bytes.truncate((Self::get_required_bits() + 7) / 8);
+                    bytes
+                }
+
+                fn from_bytes_le(bytes: &[u8]) -> Result<Self, FieldError> {
+                    use ark_ff::FromBytes;
+
+                    let expected = (Self::get_required_bits() + 7) / 8;
+                    if bytes.len() != expected {
+                        return Err(FieldError::InvalidLength {
+                            expected,
+                            found: bytes.len(),
+                        });
+                    }
+
+                    let mut padded = bytes.to_vec();
+                    padded.resize(std::mem::size_of::<<Fr as PrimeField>::BigInt>(), 0);
+
+                    let repr = <Fr as PrimeField>::BigInt::read(&padded[..])
+                        .map_err(|_| FieldError::NonCanonical)?;
+
+                    Fr::from_repr(repr)
+                        .map(|v| FieldPrime { v })
+                        .ok_or(FieldError::NonCanonical)
+                }
+
                 fn to_dec_string(&self) -> String {
                     self.to_string()
                 }
@@ -221,11 +279,8 @@ mod prime_field {
                     <Fr as PrimeField>::Params::MODULUS_BITS as usize
                 }
                 fn try_from_dec_str(s: &str) -> Result<Self, FieldParseError> {
-                    use std::str::FromStr;
-
-                    Ok(FieldPrime {
-                        v: Fr::from_str(s).map_err(|_| FieldParseError)?,
-                    })
+                    // parsing to `Fr` directly would reduce values which do not fit modulo the field size
+                    Self::try_from_str(s, 10)
                 }
                 fn try_from_str(s: &str, radix: u32) -> Result<Self, FieldParseError> {
                     let x = BigUint::parse_bytes(s.as_bytes(), radix).ok_or(FieldParseError)?;
@@ -572,6 +627,9 @@ mod prime_field {
                 }
             }
         };
+        ($name:expr, $v:ty, $g2_ty:expr) => {
+            prime_field!($name, fr = <$v as ark_ec::PairingEngine>::Fr, $g2_ty);
+        };
     }
 
     #[cfg(feature = "bellman")]
@@ -633,9 +691,102 @@ pub mod bls12_381;
 pub mod bn128;
 pub mod bw6_761;
 pub mod dummy_curve;
+pub mod goldilocks;
+pub mod pallas;
+pub mod vesta;
 
 pub use bls12_377::FieldPrime as Bls12_377Field;
 pub use bls12_381::FieldPrime as Bls12_381Field;
 pub use bn128::FieldPrime as Bn128Field;
 pub use bw6_761::FieldPrime as Bw6_761Field;
 pub use dummy_curve::FieldPrime as DummyCurveField;
+pub use goldilocks::FieldPrime as GoldilocksField;
+pub use pallas::FieldPrime as PallasField;
+pub use vesta::FieldPrime as VestaField;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    fn canonical_encoding<T: Field>() {
+        let length = (T::get_required_bits() + 7) / 8;
+        let encode = |v: &BigUint| {
+            let mut bytes = v.to_bytes_le();
+            bytes.resize(length, 0);
+            bytes
+        };
+
+        let mut rng = rand::thread_rng();
+
+        let random = (0..100).map(|_| {
+            (0..length / 8 + 1).fold(T::zero(), |acc, _| {
+                acc * T::from(1u128 << 64) + T::from(rng.gen::<u64>())
+            })
+        });
+
+        for v in vec![T::zero(), T::one(), T::max_value()]
+            .into_iter()
+            .chain(random)
+        {
+            let bytes = v.to_bytes_le();
+            assert_eq!(bytes, encode(&v.to_biguint()));
+            assert_eq!(T::from_bytes_le(&bytes), Ok(v));
+        }
+
+        let modulus = T::max_value().to_biguint() + 1u32;
+        assert_eq!(
+            T::from_bytes_le(&encode(&modulus)),
+            Err(FieldError::NonCanonical)
+        );
+        assert_eq!(
+            T::from_bytes_le(&encode(&(modulus + 1u32))),
+            Err(FieldError::NonCanonical)
+        );
+        assert_eq!(
+            T::from_bytes_le(&vec![0; length + 1]),
+            Err(FieldError::InvalidLength {
+                expected: length,
+                found: length + 1
+            })
+        );
+    }
+
+    #[test]
+    fn canonical_encoding_bn128() {
+        canonical_encoding::<Bn128Field>();
+        assert_eq!(Bn128Field::one().to_bytes_le().len(), 32);
+    }
+
+    #[test]
+    fn canonical_encoding_bls12_381() {
+        canonical_encoding::<Bls12_381Field>();
+    }
+
+    #[test]
+    fn canonical_encoding_bls12_377() {
+        canonical_encoding::<Bls12_377Field>();
+    }
+
+    #[test]
+    fn canonical_encoding_bw6_761() {
+        canonical_encoding::<Bw6_761Field>();
+        assert_eq!(Bw6_761Field::one().to_bytes_le().len(), 48);
+    }
+
+    #[test]
+    fn canonical_encoding_pallas() {
+        canonical_encoding::<PallasField>();
+    }
+
+    #[test]
+    fn canonical_encoding_vesta() {
+        canonical_encoding::<VestaField>();
+    }
+
+    #[test]
+    fn canonical_encoding_goldilocks() {
+        canonical_encoding::<GoldilocksField>();
+        assert_eq!(GoldilocksField::one().to_bytes_le().len(), 8);
+    }
+}
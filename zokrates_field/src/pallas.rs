@@ -0,0 +1,107 @@
+use crate::G2Type;
+
+prime_field!("pallas", fr = ark_pallas::Fr, G2Type::Fq);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_traits::{CheckedAdd, CheckedMul};
+
+    impl<'a> From<&'a str> for FieldPrime {
+        fn from(s: &'a str) -> FieldPrime {
+            FieldPrime::try_from_dec_str(s).unwrap()
+        }
+    }
+
+    #[test]
+    fn required_bits() {
+        assert_eq!(FieldPrime::get_required_bits(), 255);
+        assert_eq!(FieldPrime::max_value().to_bits_be().len(), 255);
+    }
+
+    #[test]
+    fn max_value() {
+        assert_eq!(
+            FieldPrime::max_value(),
+            FieldPrime::from(
+                "28948022309329048855892746252171976963363056481941647379679742748393362948096"
+            )
+        );
+        assert_eq!(
+            FieldPrime::max_value() + FieldPrime::one(),
+            FieldPrime::zero()
+        );
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(
+            FieldPrime::from("65484493"),
+            FieldPrime::from("65416358") + FieldPrime::from("68135")
+        );
+        assert_eq!(
+            FieldPrime::from(
+                "28948022309329048855892746252171976963363056481941647379679742748393297599874"
+            ),
+            FieldPrime::from("68135") - FieldPrime::from("65416358")
+        );
+        assert_eq!(
+            FieldPrime::from("13472"),
+            FieldPrime::from("32") * FieldPrime::from("421")
+        );
+        assert_eq!(
+            FieldPrime::from(
+                "14474011154664524427946373126085988481681528240970823689839871374196681474044"
+            ),
+            FieldPrime::from(-54) / FieldPrime::from(12)
+        );
+        assert_eq!(
+            FieldPrime::from("614787626176508399616"),
+            FieldPrime::from("54").pow(12)
+        );
+    }
+
+    #[test]
+    fn checked_operations() {
+        // the largest value with a unique 255 bit decomposition is 2**254 - 1
+        let bound = FieldPrime::max_unique_value();
+        assert_eq!(bound.bits(), 254);
+        assert_eq!(bound.checked_add(&FieldPrime::one()), None);
+        assert_eq!(
+            FieldPrime::from(2)
+                .pow(126)
+                .checked_mul(&FieldPrime::from(2).pow(127)),
+            Some(FieldPrime::from(2).pow(253))
+        );
+        assert_eq!(
+            FieldPrime::from(2)
+                .pow(127)
+                .checked_mul(&FieldPrime::from(2).pow(127)),
+            None
+        );
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(
+            FieldPrime::try_from(FieldPrime::max_value().to_biguint()),
+            Ok(FieldPrime::max_value())
+        );
+        assert_eq!(
+            FieldPrime::try_from(FieldPrime::max_value().to_biguint() + 1u32),
+            Err(())
+        );
+
+        let fp = FieldPrime::from("101");
+        assert_eq!(fp, FieldPrime::from_byte_vector(fp.to_byte_vector()));
+
+        let serialized = serde_json::to_string(&fp).unwrap();
+        assert_eq!(fp, serde_json::from_str(&serialized).unwrap());
+    }
+
+    #[test]
+    fn distinct_id() {
+        assert_ne!(FieldPrime::id(), crate::Bn128Field::id());
+        assert_ne!(crate::PallasField::id(), crate::VestaField::id());
+    }
+}
@@ -0,0 +1,235 @@
+use crate::trace::{ExecutionObserver, ExecutionStep};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use zokrates_ast::common::SourceMetadata;
+use zokrates_ast::ir::ProgramHash;
+
+/// How often a constraint originating from a source assertion was checked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConstraintCoverage {
+    /// The source location of the assertion
+    pub span: SourceMetadata,
+    /// The number of runs which checked the constraint
+    pub evaluated: usize,
+    /// The number of runs in which the check could have failed
+    pub exercised: usize,
+}
+
+/// The coverage of a source line, aggregated over the constraints of its assertions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LineCoverage {
+    /// The number of constraints originating from the line
+    pub constraints: usize,
+    /// The largest number of runs which checked one of these constraints
+    pub evaluated: usize,
+    /// The largest number of runs in which one of these constraints could have failed
+    pub exercised: usize,
+}
+
+/// The coverage of the assertions of a program, aggregated over several runs
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Coverage {
+    /// The hash of the constraint system of the program, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<ProgramHash>,
+    /// The number of runs
+    pub runs: usize,
+    /// The coverage of each constraint, by index of the constraint in the program
+    pub constraints: BTreeMap<usize, ConstraintCoverage>,
+}
+
+impl Coverage {
+    /// Add the runs of `other` to this coverage. Both must be collected on the same program
+    pub fn merge(&mut self, other: Coverage) -> Result<(), String> {
+        match (self.hash, other.hash) {
+            (Some(left), Some(right)) if left != right => {
+                return Err(format!(
+                    "Cannot merge coverage of different programs: {} and {}",
+                    left, right
+                ))
+            }
+            (None, hash) => self.hash = hash,
+            _ => {}
+        }
+
+        self.runs += other.runs;
+
+        for (index, constraint) in other.constraints {
+            self.constraints
+                .entry(index)
+                .and_modify(|c| {
+                    c.evaluated += constraint.evaluated;
+                    c.exercised += constraint.exercised;
+                })
+                .or_insert(constraint);
+        }
+
+        Ok(())
+    }
+
+    /// The coverage of each source line, by file and line
+    pub fn lines(&self) -> BTreeMap<(String, usize), LineCoverage> {
+        let mut lines: BTreeMap<_, LineCoverage> = BTreeMap::new();
+
+        for constraint in self.constraints.values() {
+            let line = lines
+                .entry((constraint.span.file.clone(), constraint.span.position.line))
+                .or_default();
+            line.constraints += 1;
+            line.evaluated = line.evaluated.max(constraint.evaluated);
+            line.exercised = line.exercised.max(constraint.exercised);
+        }
+
+        lines
+    }
+}
+
+/// An observer which collects the coverage of the source assertions over a single run
+pub struct CoverageCollector {
+    coverage: Coverage,
+}
+
+impl CoverageCollector {
+    pub fn new(hash: Option<ProgramHash>) -> Self {
+        CoverageCollector {
+            coverage: Coverage {
+                hash,
+                runs: 1,
+                constraints: BTreeMap::new(),
+            },
+        }
+    }
+
+    pub fn into_coverage(self) -> Coverage {
+        self.coverage
+    }
+}
+
+impl<T> ExecutionObserver<T> for CoverageCollector {
+    fn on_step(&mut self, step: &ExecutionStep<T>) -> io::Result<()> {
+        if let (Some(span), Some(exercised)) = (&step.span, step.exercised) {
+            let constraint = self
+                .coverage
+                .constraints
+                .entry(step.index)
+                .or_insert_with(|| ConstraintCoverage {
+                    span: span.clone(),
+                    evaluated: 0,
+                    exercised: 0,
+                });
+            constraint.evaluated += 1;
+            constraint.exercised += exercised as usize;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Interpreter;
+    use zokrates_ast::ir::{
+        Directive, LinComb, Parameter, Prog, QuadComb, RuntimeError, Solver, Statement, Variable,
+    };
+    use zokrates_ast::untyped::Position;
+    use zokrates_field::Bn128Field;
+
+    // def main(private c, private x)
+    //     # _2 = Or(1 - c, x)
+    //     (1 - c) * x == (1 - c) + x - _2 (branch isolation)
+    //     _2 == 1 (assertion at main.zok:3:5)
+    //     x == x (assertion at main.zok:5:5)
+    fn program() -> Prog<'static, Bn128Field> {
+        let c = Variable::new(0);
+        let x = Variable::new(1);
+        let o = Variable::new(2);
+
+        let not_c = LinComb::one() - LinComb::from(c);
+        let assertion = |line| {
+            Some(RuntimeError::SourceAssertion(SourceMetadata::new(
+                "main.zok".into(),
+                Position { line, col: 5 },
+            )))
+        };
+
+        Prog::new(
+            vec![Parameter::private(c), Parameter::private(x)],
+            vec![
+                Statement::Directive(Directive {
+                    inputs: vec![not_c.clone().into(), LinComb::from(x).into()],
+                    outputs: vec![o],
+                    solver: Solver::Or,
+                }),
+                Statement::Constraint(
+                    QuadComb::from_linear_combinations(not_c.clone(), x.into()),
+                    not_c + LinComb::from(x) - LinComb::from(o),
+                    Some(RuntimeError::BranchIsolation),
+                ),
+                Statement::Constraint(LinComb::one().into(), o.into(), assertion(3)),
+                Statement::Constraint(
+                    QuadComb::from_linear_combinations(x.into(), LinComb::one()),
+                    x.into(),
+                    assertion(5),
+                ),
+            ],
+            0,
+        )
+    }
+
+    fn run(inputs: &[u32]) -> Coverage {
+        let mut collector = CoverageCollector::new(None);
+
+        Interpreter::default()
+            .execute_with_observer(
+                program(),
+                &inputs
+                    .iter()
+                    .map(|i| Bn128Field::from(*i))
+                    .collect::<Vec<_>>(),
+                &mut io::sink(),
+                &mut collector,
+            )
+            .unwrap();
+
+        collector.into_coverage()
+    }
+
+    #[test]
+    fn branch_not_taken() {
+        let coverage = run(&[0, 5]);
+
+        assert_eq!(coverage.runs, 1);
+        assert_eq!(coverage.constraints.len(), 2);
+        assert_eq!(coverage.constraints[&2].evaluated, 1);
+        assert_eq!(coverage.constraints[&2].exercised, 0);
+        assert_eq!(coverage.constraints[&3].exercised, 1);
+    }
+
+    #[test]
+    fn merge() {
+        let mut coverage = run(&[0, 5]);
+        coverage.merge(run(&[1, 1])).unwrap();
+
+        assert_eq!(coverage.runs, 2);
+        assert_eq!(coverage.constraints[&2].evaluated, 2);
+        assert_eq!(coverage.constraints[&2].exercised, 1);
+
+        let lines = coverage.lines();
+        assert_eq!(
+            lines[&("main.zok".to_string(), 3)],
+            LineCoverage {
+                constraints: 1,
+                evaluated: 2,
+                exercised: 1
+            }
+        );
+
+        // coverage of another program cannot be merged
+        let mut other = run(&[1, 1]);
+        other.hash = Some(ProgramHash([1; 32]));
+        coverage.hash = Some(ProgramHash([0; 32]));
+        assert!(coverage.merge(other).is_err());
+    }
+}
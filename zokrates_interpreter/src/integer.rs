@@ -0,0 +1,122 @@
+use num_bigint::{BigInt, Sign};
+use std::fmt;
+use zokrates_ast::common::SourceMetadata;
+use zokrates_ast::ir::{LinComb, QuadComb, RuntimeError, Witness};
+use zokrates_field::Field;
+
+/// A constraint which holds modulo the field prime but not over the integers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModularConstraint {
+    /// The index of the constraint in the program
+    pub index: usize,
+    /// The source location of the constraint, if known
+    pub span: Option<SourceMetadata>,
+    /// The value of the quadratic side of the constraint over the integers
+    pub lhs: BigInt,
+    /// The value of the linear side of the constraint over the integers
+    pub rhs: BigInt,
+    /// The number of times the field prime separates both sides, so that `lhs - rhs == multiple * p`
+    pub multiple: BigInt,
+}
+
+/// The result of evaluating the constraints of a program over the integers, see `Interpreter::execute_integer`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct IntegerRunReport {
+    /// The number of constraints evaluated
+    pub constraints: usize,
+    /// The constraints which only hold modulo the field prime, in the order of the program
+    pub modular: Vec<ModularConstraint>,
+}
+
+impl IntegerRunReport {
+    pub fn is_empty(&self) -> bool {
+        self.modular.is_empty()
+    }
+}
+
+impl fmt::Display for IntegerRunReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} of {} constraints only hold modulo p",
+            self.modular.len(),
+            self.constraints
+        )?;
+
+        for c in &self.modular {
+            write!(f, "#{}", c.index)?;
+            if let Some(span) = &c.span {
+                write!(f, " ({})", span)?;
+            }
+            writeln!(
+                f,
+                ": lhs - rhs = {} * p, |lhs - rhs| has {} bits",
+                c.multiple,
+                (&c.lhs - &c.rhs).bits()
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The evaluation of the constraints over the integers, as they are executed
+pub(crate) struct IntegerCheck {
+    modulus: BigInt,
+    pub report: IntegerRunReport,
+}
+
+impl IntegerCheck {
+    pub fn new<T: Field>() -> Self {
+        IntegerCheck {
+            modulus: BigInt::from_biguint(Sign::Plus, T::max_value().to_biguint()) + 1,
+            report: IntegerRunReport::default(),
+        }
+    }
+
+    /// Evaluate the constraint at `index` over the integers, with the values of `witness`, which must satisfy it
+    /// modulo the field prime
+    pub fn check<T: Field>(
+        &mut self,
+        index: usize,
+        quad: &QuadComb<T>,
+        lin: &LinComb<T>,
+        error: &Option<RuntimeError>,
+        witness: &Witness<T>,
+    ) {
+        self.report.constraints += 1;
+
+        let lhs = self.evaluate(witness, &quad.left) * self.evaluate(witness, &quad.right);
+        let rhs = self.evaluate(witness, lin);
+
+        if lhs != rhs {
+            self.report.modular.push(ModularConstraint {
+                index,
+                span: match error {
+                    Some(RuntimeError::SourceAssertion(metadata))
+                    | Some(RuntimeError::SourceAssemblyConstraint(metadata))
+                    | Some(RuntimeError::DivisionByZero(metadata)) => Some(metadata.clone()),
+                    _ => None,
+                },
+                multiple: (&lhs - &rhs) / &self.modulus,
+                lhs,
+                rhs,
+            });
+        }
+    }
+
+    // the values of the variables are read as unsigned, as both field elements and integers are, signed integers
+    // being encoded in two's complement. The coefficients are read in `(-p/2, p/2]`, so that the coefficient `-1` of
+    // a subtraction keeps its sign
+    fn evaluate<T: Field>(&self, w: &Witness<T>, l: &LinComb<T>) -> BigInt {
+        l.0.iter().fold(BigInt::from(0), |acc, (var, mult)| {
+            let value = BigInt::from_biguint(Sign::Plus, w.0[var].to_biguint());
+            let mult = BigInt::from_biguint(Sign::Plus, mult.to_biguint());
+            let mult = match &mult * 2 > self.modulus {
+                true => mult - &self.modulus,
+                false => mult,
+            };
+            acc + value * mult
+        })
+    }
+}
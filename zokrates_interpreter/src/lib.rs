@@ -1,28 +1,120 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt;
-use zokrates_abi::{Decode, Value};
+use std::time::{Duration, Instant};
+use zokrates_ast::common::{FormatSpec, SourceMetadata};
 use zokrates_ast::ir::{
     LinComb, ProgIterator, QuadComb, RuntimeError, Solver, Statement, Variable, Witness,
 };
+use zokrates_ast::typed::ConcreteType;
 use zokrates_ast::zir;
 use zokrates_field::Field;
 
+pub use coverage::{ConstraintCoverage, Coverage, CoverageCollector, LineCoverage};
+pub use integer::{IntegerRunReport, ModularConstraint};
+pub use plugin::PluginRegistry;
+pub use redact::{Redaction, REDACTION_SALT_SIZE};
+pub use trace::{ExecutionObserver, ExecutionStep, TraceWriter};
+
+use integer::IntegerCheck;
+
+mod coverage;
+mod integer;
+mod parallel;
+mod plugin;
+mod redact;
+mod trace;
+
 pub type ExecutionResult<T> = Result<Witness<T>, Error>;
 
+/// The number of statements executed between two checks of the overall timeout
+const TIMEOUT_CHECK_INTERVAL: usize = 1024;
+
+/// The number of statements read at once when the directives are solved ahead of the execution
+const PARALLEL_WINDOW: usize = 1 << 16;
+
+/// The limit a solver is interrupted by
+#[derive(Clone, Copy)]
+enum Limit {
+    DirectiveBudget,
+    Timeout,
+}
+
 #[derive(Default)]
 pub struct Interpreter {
     /// Whether we should try to give out-of-range bit decompositions when the input is not a single summand.
     /// Used to do targeted testing of `<` flattening, making sure the bit decomposition we base the result on is unique.
     should_try_out_of_range: bool,
+    /// The time a single directive may take to run its solver
+    directive_budget: Option<Duration>,
+    /// The time the whole execution may take
+    timeout: Option<Duration>,
+    /// The solvers of the `unsafe_witness` directives
+    plugins: PluginRegistry,
+    /// The values to hide in the logs
+    redaction: Option<Redaction>,
+    /// Whether the directives are solved ahead of the execution, in parallel when they do not depend on each other
+    parallel_directives: bool,
 }
 
 impl Interpreter {
     pub fn try_out_of_range() -> Interpreter {
         Interpreter {
             should_try_out_of_range: true,
+            ..Default::default()
         }
     }
+
+    /// Abort the execution when the solver of a directive runs for longer than `budget`. The solvers of zir functions
+    /// are interrupted between two statements, and plugins run on their own thread which is left running
+    pub fn with_directive_budget(mut self, budget: Duration) -> Self {
+        self.directive_budget = Some(budget);
+        self
+    }
+
+    /// Abort the execution when it runs for longer than `timeout`. The elapsed time is checked every
+    /// `TIMEOUT_CHECK_INTERVAL` statements, and while solving directives as with `with_directive_budget`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Compute the outputs of the `unsafe_witness` directives named `name` with `solve`, which takes `inputs` values
+    /// and returns `outputs` values
+    pub fn with_plugin<T: Field>(
+        mut self,
+        name: &str,
+        inputs: usize,
+        outputs: usize,
+        solve: impl Fn(&[T]) -> Vec<T> + Send + Sync + 'static,
+    ) -> Self {
+        self.plugins.register(name, inputs, outputs, solve);
+        self
+    }
+
+    /// Resolve the `unsafe_witness` directives against `plugins`
+    pub fn with_plugins(mut self, plugins: PluginRegistry) -> Self {
+        self.plugins = plugins;
+        self
+    }
+
+    /// Log the values derived from private arguments as hashes, according to `redaction`, and mark the executed
+    /// statements writing such values
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = Some(redaction);
+        self
+    }
+
+    /// Solve the directives ahead of the execution, level by level in the graph of the dependencies between
+    /// statements. With the `multicore` feature, the directives of a level are solved in parallel. The constraints
+    /// are still checked in order, and the witness is the same as the one of a serial execution
+    ///
+    /// The program is read `PARALLEL_WINDOW` statements at a time, the directives of a window being solved before it is
+    /// executed. The directives are solved in order when a directive budget is set.
+    pub fn with_parallel_directives(mut self) -> Self {
+        self.parallel_directives = true;
+        self
+    }
 }
 
 impl Interpreter {
@@ -44,6 +136,163 @@ impl Interpreter {
         program: ProgIterator<'ast, T, I>,
         inputs: &[T],
         log_stream: &mut W,
+    ) -> ExecutionResult<T> {
+        self.execute_inner(
+            program,
+            inputs,
+            log_stream,
+            None,
+            &BTreeMap::new(),
+            None,
+            None,
+        )
+    }
+
+    /// Execute the program, using `hints` as the values of the variables they define instead of running the
+    /// directives which compute them. Hints can only be given for outputs of directives, and the constraints which
+    /// depend on them are still checked
+    pub fn execute_with_hints<'ast, T: Field, I: IntoIterator<Item = Statement<'ast, T>>>(
+        &self,
+        program: ProgIterator<'ast, T, I>,
+        inputs: &[T],
+        hints: BTreeMap<Variable, T>,
+    ) -> ExecutionResult<T> {
+        self.execute_with_hints_and_log_stream(program, inputs, hints, &mut std::io::sink())
+    }
+
+    pub fn execute_with_hints_and_log_stream<
+        'ast,
+        W: std::io::Write,
+        T: Field,
+        I: IntoIterator<Item = Statement<'ast, T>>,
+    >(
+        &self,
+        program: ProgIterator<'ast, T, I>,
+        inputs: &[T],
+        hints: BTreeMap<Variable, T>,
+        log_stream: &mut W,
+    ) -> ExecutionResult<T> {
+        self.execute_inner(program, inputs, log_stream, None, &hints, None, None)
+    }
+
+    /// Execute the program, completing `partial`, a witness merged from the witnesses of some of its sub-programs
+    /// with `Witness::merge`. Only the variables missing from `partial` are solved: the values of `partial` for
+    /// outputs of directives are used as hints, and the constraints defining its other values are checked rather than
+    /// evaluated. The values of the inputs must match `inputs`
+    pub fn execute_with_partial_witness<
+        'ast,
+        T: Field,
+        I: IntoIterator<Item = Statement<'ast, T>>,
+    >(
+        &self,
+        program: ProgIterator<'ast, T, I>,
+        inputs: &[T],
+        partial: &Witness<T>,
+    ) -> ExecutionResult<T> {
+        let program = program.collect();
+
+        let hints = program
+            .statements
+            .iter()
+            .filter_map(|s| match s {
+                Statement::Directive(d) => Some(d.outputs.iter()),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|o| partial.0.get(o).map(|v| (*o, v.clone())))
+            .collect();
+
+        let witness = self.execute_inner(
+            program,
+            inputs,
+            &mut std::io::sink(),
+            None,
+            &hints,
+            None,
+            Some(partial),
+        )?;
+
+        let mismatched: Vec<_> = partial
+            .0
+            .iter()
+            .filter(|(v, value)| witness.0.get(v) != Some(value))
+            .map(|(v, _)| *v)
+            .collect();
+
+        match mismatched.is_empty() {
+            true => Ok(witness),
+            false => Err(Error::PartialWitness {
+                variables: mismatched,
+            }),
+        }
+    }
+
+    /// Execute the program, reporting every executed statement to `observer`
+    pub fn execute_with_observer<
+        'ast,
+        W: std::io::Write,
+        T: Field,
+        I: IntoIterator<Item = Statement<'ast, T>>,
+    >(
+        &self,
+        program: ProgIterator<'ast, T, I>,
+        inputs: &[T],
+        log_stream: &mut W,
+        observer: &mut dyn ExecutionObserver<T>,
+    ) -> ExecutionResult<T> {
+        self.execute_inner(
+            program,
+            inputs,
+            log_stream,
+            Some(observer),
+            &BTreeMap::new(),
+            None,
+            None,
+        )
+    }
+
+    /// Execute the program, evaluating its constraints over the integers rather than modulo the field prime as they
+    /// are executed, and report the constraints which only hold thanks to the modular reduction
+    ///
+    /// The values of the variables are read as unsigned integers in `[0, p)`, as field elements and integers of any
+    /// signedness are encoded, while the coefficients are read in `(-p/2, p/2]`, so that the coefficient `-1` of a
+    /// subtraction stays negative. This is meant to debug overflows: a flagged constraint relies on wrapping around
+    /// the field prime.
+    pub fn execute_integer<'ast, T: Field, I: IntoIterator<Item = Statement<'ast, T>>>(
+        &self,
+        program: ProgIterator<'ast, T, I>,
+        inputs: &[T],
+    ) -> Result<IntegerRunReport, Error> {
+        let mut check = IntegerCheck::new::<T>();
+
+        self.execute_inner(
+            program,
+            inputs,
+            &mut std::io::sink(),
+            None,
+            &BTreeMap::new(),
+            Some(&mut check),
+            None,
+        )?;
+
+        Ok(check.report)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn execute_inner<
+        'ast,
+        W: std::io::Write,
+        T: Field,
+        I: IntoIterator<Item = Statement<'ast, T>>,
+    >(
+        &self,
+        program: ProgIterator<'ast, T, I>,
+        inputs: &[T],
+        log_stream: &mut W,
+        mut observer: Option<&mut dyn ExecutionObserver<T>>,
+        hints: &BTreeMap<Variable, T>,
+        mut integer: Option<&mut IntegerCheck>,
+        partial: Option<&Witness<T>>,
     ) -> ExecutionResult<T> {
         self.check_inputs(&program, inputs)?;
         let mut witness = Witness::default();
@@ -53,58 +302,243 @@ impl Interpreter {
             witness.insert(arg.id, value.clone());
         }
 
-        for statement in program.statements.into_iter() {
-            match statement {
+        // the hints which were not used by a directive
+        let mut unused_hints: BTreeSet<Variable> = hints.keys().copied().collect();
+        // the variables whose value depends on a hint
+        let mut hinted = BTreeSet::new();
+
+        // the known values of a partial witness are not solved again, the inputs taking precedence over them
+        for (variable, value) in partial.iter().flat_map(|partial| partial.0.iter()) {
+            if !witness.0.contains_key(variable) {
+                witness.insert(*variable, value.clone());
+                hinted.insert(*variable);
+            }
+        }
+        // the constraints which are not satisfied because of a hint
+        let mut inconsistent = vec![];
+        // the variables whose value is forced by a branch which is not taken, only tracked for the observer
+        let mut vacuous = BTreeSet::new();
+        // the clock is only read when a limit is set, as it is not available on every target
+        let start = self.timeout.map(|_| Instant::now());
+
+        // the written values are only copied, and the exercised constraints only tracked, for the observer, if any
+        let observed = observer.is_some();
+
+        let mut statements = program.statements.into_iter().enumerate();
+
+        // the directives are solved ahead of the execution one window of statements at a time, so that the program is
+        // not held in memory. The budget of a directive is measured around its solver, so the directives are then
+        // solved in order
+        let parallel = self.parallel_directives && self.directive_budget.is_none();
+        let mut window = vec![].into_iter();
+        // the outputs of the solvers of the directives of the window solved ahead of the execution, by statement index
+        let mut precomputed = HashMap::new();
+
+        // the private values are followed as the statements are executed
+        let mut redaction = self.redaction.clone();
+
+        loop {
+            let (index, statement) = match window.next() {
+                Some(next) => next,
+                None if parallel => {
+                    let next: Vec<_> = statements.by_ref().take(PARALLEL_WINDOW).collect();
+                    if next.is_empty() {
+                        break;
+                    }
+                    precomputed = parallel::solve_directives(self, &next, &witness, hints, start);
+                    window = next.into_iter();
+                    continue;
+                }
+                None => match statements.next() {
+                    Some(next) => next,
+                    None => break,
+                },
+            };
+
+            if let (Some(start), Some(timeout)) = (start, self.timeout) {
+                if index % TIMEOUT_CHECK_INTERVAL == TIMEOUT_CHECK_INTERVAL - 1
+                    && start.elapsed() > timeout
+                {
+                    return Err(Error::Timeout {
+                        timeout,
+                        statement: index,
+                    });
+                }
+            }
+
+            let private = match redaction.as_mut() {
+                Some(redaction) => redaction.observe(&statement),
+                None => false,
+            };
+
+            let (span, writes, exercised) = match statement {
                 Statement::Block(..) => unreachable!(),
                 Statement::Constraint(quad, lin, error) => match lin.is_assignee(&witness) {
                     true => {
                         let val = evaluate_quad(&witness, &quad).unwrap();
-                        witness.insert(lin.0.get(0).unwrap().0, val);
+                        let variable = lin.0.get(0).unwrap().0;
+                        let writes = match observed {
+                            true => vec![(variable, val.clone())],
+                            false => vec![],
+                        };
+                        witness.insert(variable, val);
+                        if let Some(integer) = integer.as_mut() {
+                            integer.check(index, &quad, &lin, &error, &witness);
+                        }
+                        if depends_on(&hinted, [&quad.left, &quad.right, &lin]) {
+                            hinted.insert(variable);
+                        }
+                        if observed && depends_on(&vacuous, [&quad.left, &quad.right, &lin]) {
+                            vacuous.insert(variable);
+                        }
+                        (error, writes, None)
                     }
                     false => {
                         let lhs_value = evaluate_quad(&witness, &quad).unwrap();
                         let rhs_value = evaluate_lin(&witness, &lin).unwrap();
+
+                        // a branch isolation constraint enforces `o == !c || y` where `!c` is the left factor:
+                        // when the branch is not taken, `o` holds whatever the assertions of the branch
+                        if observed
+                            && matches!(error, Some(RuntimeError::BranchIsolation))
+                            && evaluate_lin(&witness, &quad.left).unwrap() != T::zero()
+                        {
+                            let guard: BTreeSet<_> = quad
+                                .left
+                                .0
+                                .iter()
+                                .chain(quad.right.0.iter())
+                                .map(|(v, _)| *v)
+                                .collect();
+                            vacuous.extend(
+                                lin.0.iter().map(|(v, _)| *v).filter(|v| !guard.contains(v)),
+                            );
+                        }
+
+                        // the check could fail if it is not constant and not guarded by a branch which is not taken
+                        let exercised = observed.then(|| {
+                            !depends_on(&vacuous, [&quad.left, &quad.right, &lin])
+                                && [&quad.left, &quad.right, &lin]
+                                    .iter()
+                                    .any(|l| l.0.iter().any(|(v, _)| *v != Variable::one()))
+                        });
+
                         if lhs_value != rhs_value {
-                            return Err(Error::UnsatisfiedConstraint { error });
+                            if !depends_on(&hinted, [&quad.left, &quad.right, &lin]) {
+                                return Err(Error::UnsatisfiedConstraint { error });
+                            }
+                            inconsistent.push(format!(
+                                "#{}: {}",
+                                index,
+                                Statement::Constraint(quad, lin, error.clone())
+                            ));
+                        } else if let Some(integer) = integer.as_mut() {
+                            integer.check(index, &quad, &lin, &error, &witness);
                         }
+                        (error, vec![], exercised)
                     }
                 },
                 Statement::Directive(ref d) => {
-                    let mut inputs: Vec<_> = d
+                    let is_hinted = |o: &Variable| hints.contains_key(o);
+
+                    // the solver is only run if some of its outputs are not hinted
+                    let res = if d.outputs.iter().all(is_hinted) {
+                        d.outputs.iter().map(|o| hints[o].clone()).collect()
+                    } else {
+                        let deadline = self.deadline(start);
+
+                        let res = match precomputed.remove(&index) {
+                            Some(res) => Some(res),
+                            None => {
+                                let inputs = d
+                                    .inputs
+                                    .iter()
+                                    .map(|i| evaluate_quad(&witness, i).unwrap())
+                                    .collect();
+
+                                self.solve(&d.solver, inputs, deadline.map(|(at, _)| at))?
+                            }
+                        };
+
+                        let res = match (res, deadline) {
+                            (Some(res), _) => res,
+                            (None, Some((_, Limit::DirectiveBudget))) => {
+                                return Err(Error::DirectiveBudget {
+                                    solver: d.solver.kind().to_string(),
+                                    outputs: d.outputs.clone(),
+                                    span: directive_span(
+                                        &d.outputs,
+                                        statements.by_ref().map(|(_, s)| s),
+                                    ),
+                                    budget: self.directive_budget.unwrap(),
+                                });
+                            }
+                            (None, Some((_, Limit::Timeout))) => {
+                                return Err(Error::Timeout {
+                                    timeout: self.timeout.unwrap(),
+                                    statement: index,
+                                });
+                            }
+                            (None, None) => unreachable!(),
+                        };
+
+                        d.outputs
+                            .iter()
+                            .zip(res)
+                            .map(|(o, v)| hints.get(o).cloned().unwrap_or(v))
+                            .collect::<Vec<_>>()
+                    };
+
+                    let depends_on_hints = d
                         .inputs
                         .iter()
-                        .map(|i| evaluate_quad(&witness, i).unwrap())
-                        .collect();
-
-                    let res = match (&d.solver, self.should_try_out_of_range) {
-                        (Solver::Bits(bitwidth), true) if *bitwidth >= T::get_required_bits() => {
-                            Ok(Self::try_solve_with_out_of_range_bits(
-                                *bitwidth,
-                                inputs.pop().unwrap(),
-                            ))
+                        .any(|i| depends_on(&hinted, [&i.left, &i.right]));
+                    let depends_on_vacuous = observed
+                        && d.inputs
+                            .iter()
+                            .any(|i| depends_on(&vacuous, [&i.left, &i.right]));
+
+                    for o in &d.outputs {
+                        if depends_on_hints || is_hinted(o) {
+                            hinted.insert(*o);
+                        }
+                        if depends_on_vacuous {
+                            vacuous.insert(*o);
                         }
-                        _ => Self::execute_solver(&d.solver, &inputs),
+                        unused_hints.remove(o);
                     }
-                    .map_err(Error::Solver)?;
 
-                    for (i, o) in d.outputs.iter().enumerate() {
-                        witness.insert(*o, res[i].clone());
+                    let writes = match observed {
+                        true => d.outputs.iter().copied().zip(res.iter().cloned()).collect(),
+                        false => vec![],
+                    };
+
+                    for (o, v) in d.outputs.iter().zip(res) {
+                        witness.insert(*o, v);
                     }
+
+                    (None, writes, None)
                 }
                 Statement::Log(l, expressions) => {
-                    let mut parts = l.parts.into_iter();
+                    let mut parts = l.parts.iter();
 
                     write!(log_stream, "{}", parts.next().unwrap())
                         .map_err(|_| Error::LogStream)?;
 
-                    for ((t, e), part) in expressions.into_iter().zip(parts) {
+                    for (index, ((t, e), part)) in expressions.into_iter().zip(parts).enumerate() {
                         let values: Vec<_> = e
                             .iter()
                             .map(|e| evaluate_lin(&witness, e).unwrap())
                             .collect();
 
-                        write!(log_stream, "{}", Value::decode(values, t).into_serde_json())
-                            .map_err(|_| Error::LogStream)?;
+                        let value = match &redaction {
+                            Some(redaction) if redaction.any_private(&e) => {
+                                redaction.redact(&values)
+                            }
+                            _ => render(&values, &t, l.spec(index)),
+                        };
+
+                        write!(log_stream, "{}", value).map_err(|_| Error::LogStream)?;
 
                         write!(log_stream, "{}", part).map_err(|_| Error::LogStream)?;
                     }
@@ -112,13 +546,80 @@ impl Interpreter {
                     writeln!(log_stream).map_err(|_| Error::LogStream)?;
 
                     log_stream.flush().map_err(|_| Error::LogStream)?;
+
+                    (None, vec![], None)
                 }
+            };
+
+            if let Some(observer) = observer.as_mut() {
+                observer
+                    .on_step(&ExecutionStep {
+                        index,
+                        span: match span {
+                            Some(RuntimeError::SourceAssertion(metadata))
+                            | Some(RuntimeError::SourceAssemblyConstraint(metadata))
+                            | Some(RuntimeError::DivisionByZero(metadata)) => Some(metadata),
+                            _ => None,
+                        },
+                        writes,
+                        private,
+                        exercised,
+                    })
+                    .map_err(|_| Error::TraceStream)?;
             }
         }
 
+        if !unused_hints.is_empty() {
+            return Err(Error::InvalidHints {
+                variables: unused_hints.into_iter().collect(),
+            });
+        }
+
+        if !inconsistent.is_empty() {
+            return Err(Error::InconsistentHints {
+                constraints: inconsistent,
+            });
+        }
+
         Ok(witness)
     }
 
+    /// The instant at which a solver started now is interrupted, with the limit it reaches then: the earliest of the
+    /// end of the budget of a directive and of the timeout of the execution started at `start`
+    fn deadline(&self, start: Option<Instant>) -> Option<(Instant, Limit)> {
+        let budget = self
+            .directive_budget
+            .and_then(|budget| Instant::now().checked_add(budget))
+            .map(|at| (at, Limit::DirectiveBudget));
+        let timeout = start
+            .zip(self.timeout)
+            .and_then(|(start, timeout)| start.checked_add(timeout))
+            .map(|at| (at, Limit::Timeout));
+
+        budget.into_iter().chain(timeout).min_by_key(|(at, _)| *at)
+    }
+
+    /// Run the solver of a directive on the values of its inputs, returning `None` if it did not complete by `deadline`
+    fn solve<T: Field>(
+        &self,
+        solver: &Solver<T>,
+        mut inputs: Vec<T>,
+        deadline: Option<Instant>,
+    ) -> Result<Option<Vec<T>>, Error> {
+        let res = match (solver, self.should_try_out_of_range) {
+            (Solver::Bits(bitwidth), true) if *bitwidth >= T::get_required_bits() => Some(
+                Self::try_solve_with_out_of_range_bits(*bitwidth, inputs.pop().unwrap()),
+            ),
+            (Solver::Plugin(name, _, outputs), _) => {
+                self.plugins.solve(name, inputs, *outputs, deadline)?
+            }
+            _ => Self::execute_solver_until(solver, &inputs, deadline).map_err(Error::Solver)?,
+        };
+
+        // the solvers which cannot be interrupted are checked once they complete
+        Ok(res.filter(|_| deadline.map_or(true, |deadline| Instant::now() <= deadline)))
+    }
+
     fn try_solve_with_out_of_range_bits<T: Field>(bit_width: usize, input: T) -> Vec<T> {
         use num::traits::Pow;
         use num_bigint::BigUint;
@@ -165,6 +666,16 @@ impl Interpreter {
         solver: &Solver<'ast, T>,
         inputs: &[T],
     ) -> Result<Vec<T>, String> {
+        Self::execute_solver_until(solver, inputs, None).map(|res| res.unwrap())
+    }
+
+    /// Run `solver` on `inputs`, returning `None` if it is interrupted at `deadline`. Only the solvers of zir functions
+    /// are interrupted, between two of their statements
+    fn execute_solver_until<'ast, T: Field>(
+        solver: &Solver<'ast, T>,
+        inputs: &[T],
+        deadline: Option<Instant>,
+    ) -> Result<Option<Vec<T>>, String> {
         let (expected_input_count, expected_output_count) = solver.get_signature();
         assert_eq!(inputs.len(), expected_input_count);
 
@@ -214,14 +725,21 @@ impl Interpreter {
 
                 let mut propagator = zokrates_analysis::ZirPropagator::with_constants(constants);
 
-                let folded_function = propagator
-                    .fold_function(func.clone())
-                    .map_err(|e| e.to_string())?;
+                let mut statements = vec![];
+                for statement in func.statements.iter().cloned() {
+                    if deadline.map_or(false, |deadline| Instant::now() > deadline) {
+                        return Ok(None);
+                    }
 
-                assert_eq!(folded_function.statements.len(), 1);
-                if let zokrates_ast::zir::ZirStatement::Return(v) =
-                    folded_function.statements[0].clone()
-                {
+                    statements.extend(
+                        propagator
+                            .fold_statement(statement)
+                            .map_err(|e| e.to_string())?,
+                    );
+                }
+
+                assert_eq!(statements.len(), 1);
+                if let zokrates_ast::zir::ZirStatement::Return(v) = statements.pop().unwrap() {
                     v.into_iter()
                         .map(|v| match v {
                             zokrates_ast::zir::ZirExpression::FieldElement(
@@ -296,6 +814,27 @@ impl Interpreter {
                 let r = n - d * &q;
                 vec![T::try_from(q).unwrap(), T::try_from(r).unwrap()]
             }
+            Solver::Plugin(name, ..) => {
+                return Err(format!(
+                    "Plugin `{}` can only be run by an interpreter it is registered with",
+                    name
+                ))
+            }
+            Solver::Sample(_, index) => {
+                use num_bigint::BigUint;
+                use sha2::{Digest, Sha256};
+
+                // a deterministic function of the inputs, so that the same inputs always give the same witness
+                let mut hasher = Sha256::new();
+                hasher.update((*index as u64).to_le_bytes());
+                for input in inputs {
+                    hasher.update(input.to_byte_vector());
+                }
+
+                let modulus = T::max_value().to_biguint() + BigUint::from(1u32);
+                let value = BigUint::from_bytes_le(&hasher.finalize()) % modulus;
+                vec![T::try_from(value).unwrap()]
+            }
             #[cfg(feature = "bellman")]
             Solver::Sha256Round => {
                 use pairing_ce::bn256::Bn256;
@@ -318,7 +857,7 @@ impl Interpreter {
                         use pairing_ce::ff::{PrimeField, PrimeFieldRepr};
                         let mut res: Vec<u8> = vec![];
                         x.into_repr().write_le(&mut res).unwrap();
-                        T::from_byte_vector(res)
+                        T::from_bytes_le(&res).unwrap()
                     })
                     .collect()
             }
@@ -338,19 +877,140 @@ impl Interpreter {
 
         assert_eq!(res.len(), expected_output_count);
 
-        Ok(res)
+        Ok(Some(res))
     }
 }
 
+/// The location of a directive, which is the one of the first of the following constraints which reads its outputs
+fn directive_span<'ast, T: Field>(
+    outputs: &[Variable],
+    statements: impl Iterator<Item = Statement<'ast, T>>,
+) -> Option<SourceMetadata> {
+    use zokrates_ast::common::AssertionError;
+
+    statements
+        .filter_map(|statement| match statement {
+            Statement::Constraint(quad, lin, Some(error))
+                if [&quad.left, &quad.right, &lin]
+                    .iter()
+                    .any(|l| l.0.iter().any(|(v, _)| outputs.contains(v))) =>
+            {
+                Some(error)
+            }
+            _ => None,
+        })
+        .find_map(|error| match error {
+            RuntimeError::SourceAssemblyConstraint(metadata)
+            | RuntimeError::DivisionByZero(metadata) => Some(metadata),
+            error => error.source_assertion().cloned(),
+        })
+}
+
 #[derive(Debug)]
 pub struct EvaluationError;
 
 #[derive(PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum Error {
-    UnsatisfiedConstraint { error: Option<RuntimeError> },
+    UnsatisfiedConstraint {
+        error: Option<RuntimeError>,
+    },
     Solver(String),
-    WrongInputCount { expected: usize, received: usize },
+    WrongInputCount {
+        expected: usize,
+        received: usize,
+    },
     LogStream,
+    TraceStream,
+    InvalidHints {
+        variables: Vec<Variable>,
+    },
+    InconsistentHints {
+        constraints: Vec<String>,
+    },
+    PartialWitness {
+        variables: Vec<Variable>,
+    },
+    DirectiveBudget {
+        solver: String,
+        outputs: Vec<Variable>,
+        span: Option<SourceMetadata>,
+        budget: Duration,
+    },
+    Timeout {
+        timeout: Duration,
+        statement: usize,
+    },
+    MissingPlugin {
+        name: String,
+    },
+    PluginSignature {
+        name: String,
+        registered: (usize, usize),
+        declared: (usize, usize),
+    },
+}
+
+/// Render the raw values of a logged expression of type `ty`
+///
+/// Integers are reduced to their bitwidth before being displayed, in decimal or, for `{:x}`, in hexadecimal. Signed
+/// integers are displayed with their sign in decimal, and as their two's complement representation in hexadecimal.
+/// Field elements keep their quoted decimal representation.
+fn render<T: Field>(values: &[T], ty: &ConcreteType, spec: FormatSpec) -> String {
+    match ty {
+        ConcreteType::Int => unreachable!(),
+        ConcreteType::FieldElement => format!("\"{}\"", values[0].to_dec_string()),
+        ConcreteType::Boolean => format!("{}", values[0] == T::from(1)),
+        ConcreteType::Uint(bitwidth) => {
+            let signed = bitwidth.is_signed();
+            let bitwidth = bitwidth.to_usize();
+            let modulus = num_bigint::BigUint::from(1u8) << bitwidth;
+            let value = values[0].to_biguint() % &modulus;
+            match spec {
+                FormatSpec::Display if signed && value >= &modulus >> 1 => {
+                    format!("-{}", (modulus - value).to_str_radix(10))
+                }
+                FormatSpec::Display => value.to_str_radix(10),
+                FormatSpec::Hex => format!(
+                    "0x{:0>width$}",
+                    value.to_str_radix(16),
+                    width = bitwidth / 4
+                ),
+            }
+        }
+        ConcreteType::Array(array_type) => {
+            let size = array_type.ty.get_primitive_count();
+            let elements: Vec<_> = (0..*array_type.size as usize)
+                .map(|i| render(&values[i * size..(i + 1) * size], &array_type.ty, spec))
+                .collect();
+            format!("[{}]", elements.join(","))
+        }
+        ConcreteType::Tuple(tuple_type) => {
+            let elements: Vec<_> = split(values, tuple_type.elements.iter())
+                .map(|(c, ty)| render(c, ty, spec))
+                .collect();
+            format!("[{}]", elements.join(","))
+        }
+        ConcreteType::Struct(struct_type) => {
+            let members: Vec<_> = split(values, struct_type.members.iter().map(|m| &*m.ty))
+                .zip(struct_type.members.iter())
+                .map(|((c, ty), m)| format!("\"{}\":{}", m.id, render(c, ty, spec)))
+                .collect();
+            format!("{{{}}}", members.join(","))
+        }
+    }
+}
+
+/// Split `values` into consecutive chunks, one for each of `types`
+fn split<'a, T>(
+    values: &'a [T],
+    types: impl Iterator<Item = &'a ConcreteType>,
+) -> impl Iterator<Item = (&'a [T], &'a ConcreteType)> {
+    types.scan(0, move |state, ty| {
+        let new_state = *state + ty.get_primitive_count();
+        let res = (&values[*state..new_state], ty);
+        *state = new_state;
+        Some(res)
+    })
 }
 
 fn evaluate_lin<T: Field>(w: &Witness<T>, l: &LinComb<T>) -> Result<T, EvaluationError> {
@@ -364,6 +1024,16 @@ fn evaluate_lin<T: Field>(w: &Witness<T>, l: &LinComb<T>) -> Result<T, Evaluatio
         .map(|v| v.iter().fold(T::from(0), |acc, t| acc + t)) // return the sum
 }
 
+/// Whether any of the linear combinations `l` reads any of `variables`
+fn depends_on<'a, T: 'a>(
+    variables: &BTreeSet<Variable>,
+    l: impl IntoIterator<Item = &'a LinComb<T>>,
+) -> bool {
+    !variables.is_empty()
+        && l.into_iter()
+            .any(|l| l.0.iter().any(|(v, _)| variables.contains(v)))
+}
+
 pub fn evaluate_quad<T: Field>(w: &Witness<T>, q: &QuadComb<T>) -> Result<T, EvaluationError> {
     let left = evaluate_lin(w, &q.left)?;
     let right = evaluate_lin(w, &q.right)?;
@@ -401,6 +1071,73 @@ impl fmt::Display for Error {
                 if received == 1 { "" } else { "s" }
             ),
             Error::LogStream => write!(f, "Error writing a log to the log stream"),
+            Error::TraceStream => write!(f, "Error writing a step to the execution trace"),
+            Error::InvalidHints { ref variables } => write!(
+                f,
+                "Hints can only be given for variables computed by directives, found hints for {}",
+                variables
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Error::InconsistentHints { ref constraints } => {
+                write!(f, "The hints do not satisfy the following constraints:")?;
+                for c in constraints {
+                    write!(f, "\n{}", c)?;
+                }
+                Ok(())
+            }
+            Error::PartialWitness { ref variables } => write!(
+                f,
+                "The partial witness does not match the execution for {}",
+                variables
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Error::DirectiveBudget {
+                ref solver,
+                ref outputs,
+                ref span,
+                budget,
+            } => {
+                write!(
+                    f,
+                    "Directive `{}` computing {} exceeded its budget of {:?}",
+                    solver,
+                    outputs
+                        .iter()
+                        .map(|v| v.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    budget
+                )?;
+                match span {
+                    Some(span) => write!(f, " at {}:{}", span.file, span.position),
+                    None => Ok(()),
+                }
+            }
+            Error::Timeout { timeout, statement } => write!(
+                f,
+                "Execution exceeded the timeout of {:?} at statement #{}",
+                timeout, statement
+            ),
+            Error::MissingPlugin { ref name } => write!(
+                f,
+                "No plugin registered under the name `{}` for this curve",
+                name
+            ),
+            Error::PluginSignature {
+                ref name,
+                registered,
+                declared,
+            } => write!(
+                f,
+                "Plugin `{}` takes {} inputs and returns {} outputs, but the program declares {} inputs and {} outputs",
+                name, registered.0, registered.1, declared.0, declared.1
+            ),
         }
     }
 }
@@ -457,6 +1194,475 @@ mod tests {
         }
     }
 
+    mod integer {
+        use super::*;
+        use zokrates_ast::ir::{Directive, Parameter};
+
+        // the inverse of the input, which wraps around the field prime unless the input is 1 or -1
+        // # _1 = 1 / _0
+        // _1 * _0 == 1
+        // ~out_0 = _1
+        fn inverse() -> ProgIterator<'static, Bn128Field, Vec<Statement<'static, Bn128Field>>> {
+            ProgIterator::new(
+                vec![Parameter::private(Variable::new(0))],
+                vec![
+                    Statement::Directive(Directive {
+                        inputs: vec![
+                            LinComb::summand(1, Variable::one()).into(),
+                            Variable::new(0).into(),
+                        ],
+                        outputs: vec![Variable::new(1)],
+                        solver: Solver::Div,
+                    }),
+                    Statement::Constraint(
+                        QuadComb::from_linear_combinations(
+                            Variable::new(1).into(),
+                            Variable::new(0).into(),
+                        ),
+                        LinComb::summand(1, Variable::one()),
+                        Some(RuntimeError::SourceAssertion(Default::default())),
+                    ),
+                    Statement::constraint(Variable::new(1), Variable::public(0)),
+                ],
+                1,
+            )
+        }
+
+        #[test]
+        fn wraparound() {
+            let report = Interpreter::default()
+                .execute_integer(inverse(), &[Bn128Field::from(2)])
+                .unwrap();
+
+            assert_eq!(report.constraints, 2);
+            assert_eq!(report.modular.len(), 1);
+
+            // the inverse of 2 is (p + 1) / 2, so that 2 * (p + 1) / 2 == 1 + p
+            let flagged = &report.modular[0];
+            assert_eq!(flagged.index, 1);
+            assert!(flagged.span.is_some());
+            assert_eq!(flagged.rhs, num_bigint::BigInt::from(1));
+            assert_eq!(flagged.multiple, num_bigint::BigInt::from(1));
+            assert!(report
+                .to_string()
+                .starts_with("1 of 2 constraints only hold modulo p\n#1"));
+        }
+
+        #[test]
+        fn no_wraparound() {
+            let report = Interpreter::default()
+                .execute_integer(inverse(), &[Bn128Field::from(1)])
+                .unwrap();
+
+            assert_eq!(report.constraints, 2);
+            assert!(report.is_empty());
+        }
+
+        #[test]
+        fn large_values() {
+            // values above p / 2 are large unsigned values rather than negative ones: the inverse of p - 1 is p - 1,
+            // and (p - 1) * (p - 1) == 1 + (p - 2) * p
+            let report = Interpreter::default()
+                .execute_integer(inverse(), &[Bn128Field::from(-1)])
+                .unwrap();
+
+            let modulus = num_bigint::BigInt::from_biguint(
+                num_bigint::Sign::Plus,
+                Bn128Field::max_value().to_biguint(),
+            ) + 1;
+
+            assert_eq!(report.modular.len(), 1);
+            assert_eq!(report.modular[0].multiple, modulus - 2);
+
+            // the coefficient of a subtraction stays negative: `_0 - 1 == _1` holds over the integers
+            let program: ProgIterator<Bn128Field, _> = ProgIterator::new(
+                vec![Parameter::private(Variable::new(0))],
+                vec![
+                    Statement::constraint(
+                        LinComb::summand(1, Variable::new(0))
+                            - LinComb::summand(1, Variable::one()),
+                        Variable::new(1),
+                    ),
+                    Statement::constraint(Variable::new(1), Variable::public(0)),
+                ],
+                1,
+            );
+
+            let report = Interpreter::default()
+                .execute_integer(program, &[Bn128Field::from(42)])
+                .unwrap();
+
+            assert_eq!(report.constraints, 2);
+            assert!(report.is_empty());
+        }
+
+        #[test]
+        fn unsatisfied() {
+            // the constraints must hold modulo p in the first place
+            let res = Interpreter::default().execute_integer(inverse(), &[Bn128Field::from(0)]);
+
+            assert!(res.is_err());
+        }
+    }
+
+    mod hints {
+        use super::*;
+        use zokrates_ast::ir::{Directive, Parameter, Substitution};
+
+        // a square root computed outside of the program: the directive, which halves its input, cannot compute it
+        // # _1 = _0 / 2
+        // _1 * _1 == _0
+        // ~out_0 = _1
+        fn program() -> ProgIterator<'static, Bn128Field, Vec<Statement<'static, Bn128Field>>> {
+            ProgIterator::new(
+                vec![Parameter::private(Variable::new(0))],
+                vec![
+                    Statement::Directive(Directive {
+                        inputs: vec![
+                            Variable::new(0).into(),
+                            LinComb::summand(2, Variable::one()).into(),
+                        ],
+                        outputs: vec![Variable::new(1)],
+                        solver: Solver::Div,
+                    }),
+                    Statement::Constraint(
+                        QuadComb::from_linear_combinations(
+                            Variable::new(1).into(),
+                            Variable::new(1).into(),
+                        ),
+                        Variable::new(0).into(),
+                        Some(RuntimeError::SourceAssertion(Default::default())),
+                    ),
+                    Statement::constraint(Variable::new(1), Variable::public(0)),
+                ],
+                1,
+            )
+        }
+
+        #[test]
+        fn consistent() {
+            let witness = Interpreter::default()
+                .execute_with_hints(
+                    program(),
+                    &[Bn128Field::from(16)],
+                    vec![(Variable::new(1), Bn128Field::from(4))]
+                        .into_iter()
+                        .collect(),
+                )
+                .unwrap();
+
+            assert_eq!(witness.return_values(), vec![Bn128Field::from(4)]);
+        }
+
+        #[test]
+        fn inconsistent() {
+            let res = Interpreter::default().execute_with_hints(
+                program(),
+                &[Bn128Field::from(16)],
+                vec![(Variable::new(1), Bn128Field::from(5))]
+                    .into_iter()
+                    .collect(),
+            );
+
+            match res {
+                Err(Error::InconsistentHints { constraints }) => {
+                    assert_eq!(constraints.len(), 1);
+                    assert!(constraints[0].starts_with("#1: "));
+                }
+                _ => panic!("expected the hint to be rejected"),
+            }
+        }
+
+        #[test]
+        fn not_a_directive_output() {
+            let res = Interpreter::default().execute_with_hints(
+                program(),
+                &[Bn128Field::from(16)],
+                vec![
+                    (Variable::new(1), Bn128Field::from(4)),
+                    (Variable::public(0), Bn128Field::from(4)),
+                ]
+                .into_iter()
+                .collect(),
+            );
+
+            assert_eq!(
+                res.unwrap_err(),
+                Error::InvalidHints {
+                    variables: vec![Variable::public(0)]
+                }
+            );
+        }
+
+        // the witness of `program` merged from the witnesses of two sub-programs sharing the square root `_1`
+        fn partial() -> Witness<Bn128Field> {
+            let renaming = |pairs: &[(usize, usize)]| {
+                Substitution(
+                    pairs
+                        .iter()
+                        .map(|(from, to)| (Variable::new(*from), Variable::new(*to)))
+                        .collect(),
+                )
+            };
+
+            // `_0` and `_1` of the first part are the input and the square root
+            let square = Witness(
+                vec![
+                    (Variable::new(0), Bn128Field::from(16)),
+                    (Variable::new(1), Bn128Field::from(4)),
+                ]
+                .into_iter()
+                .collect(),
+            );
+            // the second part reads the square root as `_0`
+            let copy = Witness(
+                vec![(Variable::new(0), Bn128Field::from(4))]
+                    .into_iter()
+                    .collect(),
+            );
+
+            Witness::merge(
+                &program(),
+                vec![
+                    (renaming(&[(0, 0), (1, 1)]), square),
+                    (renaming(&[(0, 1)]), copy),
+                ],
+            )
+            .unwrap()
+        }
+
+        #[test]
+        fn partial_witness() {
+            let witness = Interpreter::default()
+                .execute_with_partial_witness(program(), &[Bn128Field::from(16)], &partial())
+                .unwrap();
+
+            assert_eq!(witness.return_values(), vec![Bn128Field::from(4)]);
+        }
+
+        #[test]
+        fn known_values_are_checked() {
+            // the output is known, so its definition is checked rather than evaluated
+            let mut partial = partial();
+            partial.insert(Variable::public(0), Bn128Field::from(5));
+
+            let res = Interpreter::default().execute_with_partial_witness(
+                program(),
+                &[Bn128Field::from(16)],
+                &partial,
+            );
+
+            assert!(matches!(res, Err(Error::InconsistentHints { .. })));
+        }
+
+        #[test]
+        fn inconsistent_partial_witness() {
+            // the partial witness is wrong about the input
+            let mut partial = partial();
+            partial.insert(Variable::new(0), Bn128Field::from(9));
+
+            let res = Interpreter::default().execute_with_partial_witness(
+                program(),
+                &[Bn128Field::from(16)],
+                &partial,
+            );
+
+            assert_eq!(
+                res.unwrap_err(),
+                Error::PartialWitness {
+                    variables: vec![Variable::new(0)]
+                }
+            );
+        }
+    }
+
+    mod limits {
+        use super::*;
+        use zokrates_ast::ir::{Directive, Parameter};
+        use zokrates_ast::untyped::Position;
+        use zokrates_ast::zir::{
+            FieldElementExpression, IdentifierExpression, ZirExpression, ZirFunction, ZirStatement,
+        };
+
+        // a solver which squares its input `count` times, slow enough to exceed any budget
+        fn slow_solver(count: usize) -> Solver<'static, Bn128Field> {
+            let x = zir::Variable::field_element("x");
+            let identifier = || {
+                Box::new(FieldElementExpression::Identifier(
+                    IdentifierExpression::new("x".into()),
+                ))
+            };
+
+            Solver::Zir(ZirFunction {
+                arguments: vec![zir::Parameter::private(x.clone())],
+                statements: (0..count)
+                    .map(|_| {
+                        ZirStatement::Definition(
+                            x.clone(),
+                            FieldElementExpression::Mult(identifier(), identifier()).into(),
+                        )
+                    })
+                    .chain(std::iter::once(ZirStatement::Return(vec![
+                        ZirExpression::FieldElement(*identifier()),
+                    ])))
+                    .collect(),
+                signature: zir::types::Signature {
+                    inputs: vec![zir::Type::FieldElement],
+                    outputs: vec![zir::Type::FieldElement],
+                },
+            })
+        }
+
+        // # _1 = slow(_0)
+        // _1 == _1
+        // ~out_0 = _1
+        fn program(
+            solver: Solver<'static, Bn128Field>,
+        ) -> ProgIterator<'static, Bn128Field, Vec<Statement<'static, Bn128Field>>> {
+            ProgIterator::new(
+                vec![Parameter::private(Variable::new(0))],
+                vec![
+                    Statement::Directive(Directive {
+                        inputs: vec![Variable::new(0).into()],
+                        outputs: vec![Variable::new(1)],
+                        solver,
+                    }),
+                    Statement::constraint(Variable::new(1), Variable::new(1)),
+                    Statement::constraint(Variable::new(1), Variable::public(0)),
+                ],
+                1,
+            )
+        }
+
+        #[test]
+        fn directive_over_budget() {
+            let res = Interpreter::default()
+                .with_directive_budget(Duration::ZERO)
+                .execute(program(slow_solver(10000)), &[Bn128Field::from(3)]);
+
+            assert_eq!(
+                res.unwrap_err(),
+                Error::DirectiveBudget {
+                    solver: "Zir".to_string(),
+                    outputs: vec![Variable::new(1)],
+                    span: None,
+                    budget: Duration::ZERO
+                }
+            );
+        }
+
+        #[test]
+        fn within_limits() {
+            let witness = Interpreter::default()
+                .with_directive_budget(Duration::from_secs(60))
+                .with_timeout(Duration::from_secs(60))
+                .execute(program(slow_solver(2)), &[Bn128Field::from(3)])
+                .unwrap();
+
+            assert_eq!(witness.return_values(), vec![Bn128Field::from(81)]);
+        }
+
+        #[test]
+        fn timeout() {
+            // enough statements for the timeout to be checked
+            let statements = (0..TIMEOUT_CHECK_INTERVAL)
+                .map(|_| Statement::constraint(Variable::new(0), Variable::new(0)))
+                .chain(std::iter::once(Statement::constraint(
+                    Variable::new(0),
+                    Variable::public(0),
+                )))
+                .collect();
+
+            let res = Interpreter::default().with_timeout(Duration::ZERO).execute(
+                ProgIterator::new(vec![Parameter::private(Variable::new(0))], statements, 1),
+                &[Bn128Field::from(3)],
+            );
+
+            assert_eq!(
+                res.unwrap_err(),
+                Error::Timeout {
+                    timeout: Duration::ZERO,
+                    statement: TIMEOUT_CHECK_INTERVAL - 1
+                }
+            );
+        }
+
+        // # _1 = Plugin("sleep")(_0)
+        // _1 == _1 // main.zok:3:5
+        // ~out_0 = _1
+        fn sleeping_plugin() -> (Interpreter, Vec<Statement<'static, Bn128Field>>) {
+            let interpreter =
+                Interpreter::default().with_plugin("sleep", 1, 1, |inputs: &[Bn128Field]| {
+                    std::thread::sleep(Duration::from_secs(60));
+                    inputs.to_vec()
+                });
+
+            let span = SourceMetadata::new("main.zok".into(), Position { line: 3, col: 5 });
+
+            let statements = vec![
+                Statement::Directive(Directive {
+                    inputs: vec![Variable::new(0).into()],
+                    outputs: vec![Variable::new(1)],
+                    solver: Solver::Plugin("sleep".into(), 1, 1),
+                }),
+                Statement::Constraint(
+                    Variable::new(1).into(),
+                    Variable::new(1).into(),
+                    Some(RuntimeError::SourceAssemblyConstraint(span)),
+                ),
+                Statement::constraint(Variable::new(1), Variable::public(0)),
+            ];
+
+            (interpreter, statements)
+        }
+
+        #[test]
+        fn interrupt_directive() {
+            let (interpreter, statements) = sleeping_plugin();
+
+            let started = Instant::now();
+            let res = interpreter
+                .with_directive_budget(Duration::from_millis(10))
+                .execute(
+                    ProgIterator::new(vec![Parameter::private(Variable::new(0))], statements, 1),
+                    &[Bn128Field::from(3)],
+                );
+
+            // the plugin is interrupted instead of being waited for
+            assert!(started.elapsed() < Duration::from_secs(30));
+            assert_eq!(
+                res.unwrap_err(),
+                Error::DirectiveBudget {
+                    solver: "Plugin".to_string(),
+                    outputs: vec![Variable::new(1)],
+                    span: Some(SourceMetadata::new(
+                        "main.zok".into(),
+                        Position { line: 3, col: 5 }
+                    )),
+                    budget: Duration::from_millis(10)
+                }
+            );
+        }
+
+        #[test]
+        fn timeout_in_directive() {
+            let (interpreter, statements) = sleeping_plugin();
+
+            let res = interpreter.with_timeout(Duration::from_millis(10)).execute(
+                ProgIterator::new(vec![Parameter::private(Variable::new(0))], statements, 1),
+                &[Bn128Field::from(3)],
+            );
+
+            assert_eq!(
+                res.unwrap_err(),
+                Error::Timeout {
+                    timeout: Duration::from_millis(10),
+                    statement: 0
+                }
+            );
+        }
+    }
+
     #[test]
     fn bits_of_one() {
         let inputs = vec![Bn128Field::from(1)];
@@ -494,4 +1700,60 @@ mod tests {
 
         assert_eq!(res, expected);
     }
+
+    #[test]
+    fn log_by_type() {
+        use zokrates_ast::common::FormatString;
+        use zokrates_ast::ir::Parameter;
+        use zokrates_ast::typed::{types::ConcreteArrayType, UBitwidth};
+
+        let arguments: Vec<_> = (0..5)
+            .map(|i| Parameter::private(Variable::new(i)))
+            .collect();
+
+        let lin = |i| vec![LinComb::from(Variable::new(i))];
+
+        let program: ProgIterator<Bn128Field, _> = ProgIterator::new(
+            arguments,
+            vec![Statement::Log(
+                FormatString::from("x = {}, hex = {:x}, b = {}, f = {}, a = {:x}"),
+                vec![
+                    (ConcreteType::Uint(UBitwidth::B32), lin(0)),
+                    (ConcreteType::Uint(UBitwidth::B32), lin(0)),
+                    (ConcreteType::Boolean, lin(1)),
+                    (ConcreteType::FieldElement, lin(2)),
+                    (
+                        ConcreteType::Array(ConcreteArrayType::new(
+                            ConcreteType::Uint(UBitwidth::B8),
+                            2u32,
+                        )),
+                        lin(3).into_iter().chain(lin(4)).collect(),
+                    ),
+                ],
+            )],
+            0,
+        );
+
+        let mut log = vec![];
+
+        Interpreter::default()
+            .execute_with_log_stream(
+                program,
+                &[
+                    // the result of an addition, which was not reduced to 32 bits
+                    Bn128Field::from(4294967301u64),
+                    Bn128Field::from(1),
+                    Bn128Field::from(42),
+                    Bn128Field::from(255),
+                    Bn128Field::from(16),
+                ],
+                &mut log,
+            )
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(log).unwrap(),
+            "x = 5, hex = 0x00000005, b = true, f = \"42\", a = [0xff,0x10]\n"
+        );
+    }
 }
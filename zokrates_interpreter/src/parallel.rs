@@ -0,0 +1,323 @@
+//! Solve the directives of a window of statements ahead of its execution, in parallel when they do not depend on
+//! each other.
+//!
+//! The statements which define variables, the directives and the constraints which assign a variable, are sorted
+//! into levels: a statement is one level above the highest statement defining a variable it reads. The statements
+//! of a level only read values of lower levels, so they can be evaluated in any order, and their values are merged
+//! into the witness in the order of the program once the whole level is evaluated. This way the values do not
+//! depend on the scheduling of the threads.
+//!
+//! Nothing is reported from here: a directive which fails, or which is not reached before the timeout, is left to the
+//! execution, which solves it again in order and reports the error with its context.
+
+use crate::{evaluate_quad, Interpreter};
+#[cfg(feature = "multicore")]
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Instant;
+use zokrates_ast::ir::{Statement, Variable, Witness};
+use zokrates_field::Field;
+
+/// The values defined by a statement, and the outputs of its solver if it is a directive
+type Evaluation<T> = (Vec<(Variable, T)>, Option<Vec<T>>);
+
+/// Sort the statements which define variables into levels, each level holding statement indices in increasing order
+fn levels<T: Field>(statements: &[(usize, Statement<T>)], witness: &Witness<T>) -> Vec<Vec<usize>> {
+    // the level of the statement defining each variable, the variables of the witness being defined before any
+    let mut defined: HashMap<Variable, Option<usize>> =
+        witness.0.keys().map(|v| (*v, None)).collect();
+    let mut levels: Vec<Vec<usize>> = vec![];
+
+    for (index, statement) in statements {
+        let (reads, writes) = match statement {
+            Statement::Constraint(quad, lin, _)
+                if lin.0.len() == 1
+                    && lin.0[0].1 == T::from(1)
+                    && !defined.contains_key(&lin.0[0].0) =>
+            {
+                (vec![quad], vec![lin.0[0].0])
+            }
+            Statement::Directive(d) => (d.inputs.iter().collect(), d.outputs.clone()),
+            _ => continue,
+        };
+
+        // a variable defined twice could be read at a level where it holds either value, so the following
+        // directives are left to the execution
+        if writes.iter().any(|v| defined.contains_key(v)) {
+            break;
+        }
+
+        let level = reads
+            .iter()
+            .flat_map(|q| q.left.0.iter().chain(q.right.0.iter()))
+            .filter_map(|(v, _)| defined.get(v).copied().flatten())
+            .max()
+            .map_or(0, |l| l + 1);
+
+        if levels.len() <= level {
+            levels.resize(level + 1, vec![]);
+        }
+        levels[level].push(*index);
+
+        defined.extend(writes.into_iter().map(|v| (v, Some(level))));
+    }
+
+    levels
+}
+
+/// Evaluate a statement which defines variables, returning `None` if it cannot be evaluated before `deadline`
+fn evaluate<T: Field>(
+    interpreter: &Interpreter,
+    statement: &Statement<T>,
+    witness: &Witness<T>,
+    hints: &BTreeMap<Variable, T>,
+    deadline: Option<Instant>,
+) -> Option<Evaluation<T>> {
+    if deadline.map_or(false, |deadline| Instant::now() > deadline) {
+        return None;
+    }
+
+    match statement {
+        Statement::Constraint(quad, lin, _) => {
+            let value = evaluate_quad(witness, quad).ok()?;
+            Some((vec![(lin.0[0].0, value)], None))
+        }
+        Statement::Directive(d) if d.outputs.iter().all(|o| hints.contains_key(o)) => Some((
+            d.outputs.iter().map(|o| (*o, hints[o].clone())).collect(),
+            None,
+        )),
+        Statement::Directive(d) => {
+            let inputs = d
+                .inputs
+                .iter()
+                .map(|i| evaluate_quad(witness, i).ok())
+                .collect::<Option<_>>()?;
+
+            let res = interpreter.solve(&d.solver, inputs, deadline).ok()??;
+
+            let writes = d
+                .outputs
+                .iter()
+                .zip(res.iter())
+                .map(|(o, v)| (*o, hints.get(o).unwrap_or(v).clone()))
+                .collect();
+
+            Some((writes, Some(res)))
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Solve the directives of `statements`, given with their index in the program, starting from `witness`, returning
+/// the outputs of their solvers by statement index. The directives which cannot be solved, or which are not reached
+/// before the timeout, are left out
+pub(crate) fn solve_directives<T: Field>(
+    interpreter: &Interpreter,
+    statements: &[(usize, Statement<T>)],
+    witness: &Witness<T>,
+    hints: &BTreeMap<Variable, T>,
+    start: Option<Instant>,
+) -> HashMap<usize, Vec<T>> {
+    let mut solved = HashMap::new();
+
+    let mut witness = witness.clone();
+
+    let deadline = start
+        .zip(interpreter.timeout)
+        .and_then(|(start, timeout)| start.checked_add(timeout));
+
+    // the indices in the levels are those of the program, the window starting at the first one
+    let offset = match statements.first() {
+        Some((offset, _)) => *offset,
+        None => return solved,
+    };
+
+    for level in levels(statements, &witness) {
+        let run = |index: &usize| {
+            evaluate(
+                interpreter,
+                &statements[*index - offset].1,
+                &witness,
+                hints,
+                deadline,
+            )
+            .map(|e| (*index, e))
+        };
+
+        #[cfg(feature = "multicore")]
+        let evaluations: Vec<_> = level.par_iter().filter_map(run).collect();
+        #[cfg(not(feature = "multicore"))]
+        let evaluations: Vec<_> = level.iter().filter_map(run).collect();
+
+        for (index, (writes, res)) in evaluations {
+            witness.0.extend(writes);
+            if let Some(res) = res {
+                solved.insert(index, res);
+            }
+        }
+    }
+
+    solved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_ast::ir::{Directive, LinComb, Parameter, Prog, QuadComb, Solver};
+    use zokrates_field::Bn128Field;
+
+    // def main(x, y) -> (~out_0, ~out_1)
+    //     # _2, _3 = Bits(2)(x)
+    //     # _4, _5 = Bits(2)(y)
+    //     _6 = _2 * _4
+    //     # _7 = Xor(_6, _3)
+    //     ~out_0 = _7
+    //     ~out_1 = _5
+    fn program() -> Prog<'static, Bn128Field> {
+        let x = Variable::new(0);
+        let y = Variable::new(1);
+        let v = Variable::new;
+
+        Prog::new(
+            vec![Parameter::private(x), Parameter::public(y)],
+            vec![
+                Statement::Directive(Directive {
+                    inputs: vec![x.into()],
+                    outputs: vec![v(2), v(3)],
+                    solver: Solver::Bits(2),
+                }),
+                Statement::Directive(Directive {
+                    inputs: vec![y.into()],
+                    outputs: vec![v(4), v(5)],
+                    solver: Solver::Bits(2),
+                }),
+                Statement::definition(
+                    v(6),
+                    QuadComb::from_linear_combinations(v(2).into(), v(4).into()),
+                ),
+                Statement::Directive(Directive {
+                    inputs: vec![v(6).into(), v(3).into()],
+                    outputs: vec![v(7)],
+                    solver: Solver::Xor,
+                }),
+                Statement::definition(Variable::public(0), LinComb::from(v(7))),
+                Statement::definition(Variable::public(1), LinComb::from(v(5))),
+            ],
+            2,
+        )
+    }
+
+    fn witness(program: &Prog<Bn128Field>, inputs: &[u32]) -> Witness<Bn128Field> {
+        let mut witness = Witness::default();
+        witness.insert(Variable::one(), Bn128Field::from(1));
+        for (p, i) in program.arguments.iter().zip(inputs) {
+            witness.insert(p.id, Bn128Field::from(*i));
+        }
+        witness
+    }
+
+    // the statements of `program`, as a window starting at `offset`
+    fn window(program: &Prog<Bn128Field>, offset: usize) -> Vec<(usize, Statement<Bn128Field>)> {
+        program
+            .statements
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(index, s)| (offset + index, s))
+            .collect()
+    }
+
+    #[test]
+    fn sort_into_levels() {
+        let program = program();
+
+        assert_eq!(
+            levels(&window(&program, 0), &witness(&program, &[2, 3])),
+            vec![vec![0, 1], vec![2, 5], vec![3], vec![4]]
+        );
+    }
+
+    #[test]
+    fn same_witness() {
+        let program = program();
+        let interpreter = Interpreter::default();
+
+        let solved = solve_directives(
+            &interpreter,
+            &window(&program, 0),
+            &witness(&program, &[2, 3]),
+            &BTreeMap::new(),
+            None,
+        );
+        assert_eq!(solved.len(), 3);
+
+        for inputs in [[0, 0], [2, 3], [3, 1]] {
+            let inputs: Vec<_> = inputs.iter().map(|i| Bn128Field::from(*i)).collect();
+            assert_eq!(
+                Interpreter::default()
+                    .execute(program.clone(), &inputs)
+                    .unwrap(),
+                Interpreter::default()
+                    .with_parallel_directives()
+                    .execute(program.clone(), &inputs)
+                    .unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn redefinition() {
+        let mut program = program();
+        // _2 is defined again, so the directives from there are not solved ahead
+        program.statements.insert(
+            1,
+            Statement::Directive(Directive {
+                inputs: vec![Variable::new(1).into()],
+                outputs: vec![Variable::new(2)],
+                solver: Solver::Bits(1),
+            }),
+        );
+
+        assert_eq!(
+            levels(&window(&program, 0), &witness(&program, &[2, 3])),
+            vec![vec![0]]
+        );
+    }
+
+    #[test]
+    fn window_offset() {
+        let program = program();
+
+        // the indices of the solved directives are those of the program
+        let solved = solve_directives(
+            &Interpreter::default(),
+            &window(&program, 10),
+            &witness(&program, &[2, 3]),
+            &BTreeMap::new(),
+            None,
+        );
+        assert_eq!(
+            solved
+                .keys()
+                .copied()
+                .collect::<std::collections::BTreeSet<_>>(),
+            [10, 11, 13].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn timeout() {
+        let program = program();
+        let interpreter = Interpreter::default().with_timeout(std::time::Duration::ZERO);
+
+        // past the timeout, no statement is evaluated
+        let solved = solve_directives(
+            &interpreter,
+            &window(&program, 0),
+            &witness(&program, &[2, 3]),
+            &BTreeMap::new(),
+            Instant::now().checked_sub(std::time::Duration::from_secs(1)),
+        );
+        assert!(solved.is_empty());
+    }
+}
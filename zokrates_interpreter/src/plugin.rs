@@ -0,0 +1,103 @@
+use crate::Error;
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::Instant;
+use zokrates_field::Field;
+
+type Solve<T> = Arc<dyn Fn(&[T]) -> Vec<T> + Send + Sync>;
+
+/// A solver registered under a name, for programs over the field `T`
+struct Plugin<T> {
+    inputs: usize,
+    outputs: usize,
+    solve: Solve<T>,
+}
+
+/// The solvers the `unsafe_witness` directives of a program are resolved against
+///
+/// A plugin is registered for a single field. Looking it up for another field fails as if it was missing.
+#[derive(Default)]
+pub struct PluginRegistry {
+    plugins: HashMap<String, Box<dyn Any + Send + Sync>>,
+}
+
+impl PluginRegistry {
+    /// Register `solve` under `name`, replacing any plugin registered under the same name
+    pub fn register<T: Field>(
+        &mut self,
+        name: &str,
+        inputs: usize,
+        outputs: usize,
+        solve: impl Fn(&[T]) -> Vec<T> + Send + Sync + 'static,
+    ) {
+        self.plugins.insert(
+            name.to_string(),
+            Box::new(Plugin {
+                inputs,
+                outputs,
+                solve: Arc::new(solve),
+            }),
+        );
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.plugins.contains_key(name)
+    }
+
+    /// Run the plugin `name` on `inputs`, checking that it was registered with the signature the program declares.
+    /// Returns `None` if the plugin is still running at `deadline`, in which case it is left running on its own thread
+    pub(crate) fn solve<T: Field>(
+        &self,
+        name: &str,
+        inputs: Vec<T>,
+        outputs: usize,
+        deadline: Option<Instant>,
+    ) -> Result<Option<Vec<T>>, Error> {
+        let plugin = self
+            .plugins
+            .get(name)
+            .and_then(|p| p.downcast_ref::<Plugin<T>>())
+            .ok_or_else(|| Error::MissingPlugin {
+                name: name.to_string(),
+            })?;
+
+        if (plugin.inputs, plugin.outputs) != (inputs.len(), outputs) {
+            return Err(Error::PluginSignature {
+                name: name.to_string(),
+                registered: (plugin.inputs, plugin.outputs),
+                declared: (inputs.len(), outputs),
+            });
+        }
+
+        let res = match deadline {
+            None => (plugin.solve)(&inputs),
+            Some(deadline) => {
+                let solve = plugin.solve.clone();
+                let (sender, receiver) = mpsc::channel();
+                std::thread::spawn(move || sender.send(solve(&inputs)));
+
+                match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(res) => res,
+                    Err(RecvTimeoutError::Timeout) => return Ok(None),
+                    Err(RecvTimeoutError::Disconnected) => {
+                        return Err(Error::Solver(format!("Plugin `{}` panicked", name)))
+                    }
+                }
+            }
+        };
+
+        if res.len() != outputs {
+            return Err(Error::Solver(format!(
+                "Plugin `{}` returned {} value{} instead of {}",
+                name,
+                res.len(),
+                if res.len() == 1 { "" } else { "s" },
+                outputs
+            )));
+        }
+
+        Ok(Some(res))
+    }
+}
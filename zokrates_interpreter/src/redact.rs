@@ -0,0 +1,221 @@
+//! Hide the values derived from the private arguments of a program in its logs and traces, so that debug output
+//! can be shared without leaking the secrets of the prover.
+//!
+//! The variables derived from the private arguments are followed as the statements are executed, so that the program
+//! is never held in memory. This over-approximates them: a value constrained together with a private one before it is
+//! logged is redacted too. A redacted value is replaced with a hash of the value salted for the run, so that equal
+//! values can still be matched within a run, but not across runs.
+
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use zokrates_ast::ir::{LinComb, Parameter, Statement, Variable};
+use zokrates_field::Field;
+
+/// The size of the salt of the hashes, in bytes
+pub const REDACTION_SALT_SIZE: usize = 32;
+
+/// The number of bytes of the hash shown instead of a value
+const REDACTED_HASH_SIZE: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct Redaction {
+    salt: [u8; REDACTION_SALT_SIZE],
+    /// The variables derived from the private arguments in the statements observed so far
+    private: BTreeSet<Variable>,
+}
+
+impl Redaction {
+    /// Redact the variables derived from the private `arguments` of a program, hashing their values with `salt`
+    pub fn new(arguments: &[Parameter], salt: [u8; REDACTION_SALT_SIZE]) -> Self {
+        Redaction {
+            salt,
+            private: arguments
+                .iter()
+                .filter(|p| p.private)
+                .map(|p| p.id)
+                .collect(),
+        }
+    }
+
+    /// Follow the private values through `statement`, which is about to be executed, and return whether the
+    /// variables it writes are derived from private arguments
+    pub fn observe<T>(&mut self, statement: &Statement<T>) -> bool {
+        // the variables of a constraint are all tainted together, as in the taint analysis
+        let (inputs, outputs) = match statement {
+            Statement::Constraint(quad, lin, _) => {
+                let variables = variables([&quad.left, &quad.right, lin]);
+                (variables.clone(), variables)
+            }
+            Statement::Directive(d) => (
+                variables(d.inputs.iter().flat_map(|i| [&i.left, &i.right])),
+                d.outputs.clone(),
+            ),
+            Statement::Log(..) | Statement::Block(..) => return false,
+        };
+
+        let private = inputs.iter().any(|v| self.is_private(v));
+
+        if private {
+            self.private.extend(outputs);
+        }
+
+        private
+    }
+
+    pub fn is_private(&self, v: &Variable) -> bool {
+        self.private.contains(v)
+    }
+
+    /// Whether any of the variables of `expressions` is derived from a private argument
+    pub fn any_private<T>(&self, expressions: &[LinComb<T>]) -> bool {
+        expressions
+            .iter()
+            .flat_map(|e| e.0.iter())
+            .any(|(v, _)| self.is_private(v))
+    }
+
+    /// The text shown instead of `values`, for example `<redacted:3f2a09c1d4e5b687>`
+    pub fn redact<T: Field>(&self, values: &[T]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt);
+        for value in values {
+            hasher.update(value.to_byte_vector());
+        }
+
+        let hash: String = hasher.finalize()[..REDACTED_HASH_SIZE]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+
+        format!("<redacted:{}>", hash)
+    }
+}
+
+/// The variables of `expressions`, except `~one`
+fn variables<'a, T: 'a>(expressions: impl IntoIterator<Item = &'a LinComb<T>>) -> Vec<Variable> {
+    expressions
+        .into_iter()
+        .flat_map(|e| e.0.iter().map(|(v, _)| *v))
+        .filter(|v| *v != Variable::one())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Interpreter, TraceWriter};
+    use zokrates_ast::common::FormatString;
+    use zokrates_ast::ir::{Parameter, QuadComb, Statement};
+    use zokrates_ast::typed::ConcreteType;
+    use zokrates_field::Bn128Field;
+
+    // def main(private x, y) -> ~out_0
+    //     _2 = x * x
+    //     log("x = {}, y = {}, square = {}", x, y, _2)
+    //     ~out_0 = y
+    fn program() -> Prog<'static, Bn128Field> {
+        let x = Variable::new(0);
+        let y = Variable::new(1);
+        let square = Variable::new(2);
+
+        Prog::new(
+            vec![Parameter::private(x), Parameter::public(y)],
+            vec![
+                Statement::definition(
+                    square,
+                    QuadComb::from_linear_combinations(x.into(), x.into()),
+                ),
+                Statement::Log(
+                    FormatString::from("x = {}, y = {}, square = {}"),
+                    vec![
+                        (ConcreteType::FieldElement, vec![x.into()]),
+                        (ConcreteType::FieldElement, vec![y.into()]),
+                        (ConcreteType::FieldElement, vec![square.into()]),
+                    ],
+                ),
+                Statement::definition(Variable::public(0), y),
+            ],
+            1,
+        )
+    }
+
+    fn log(redaction: Redaction) -> String {
+        let mut log = vec![];
+
+        Interpreter::default()
+            .with_redaction(redaction)
+            .execute_with_log_stream(
+                program(),
+                &[Bn128Field::from(3), Bn128Field::from(4)],
+                &mut log,
+            )
+            .unwrap();
+
+        String::from_utf8(log).unwrap()
+    }
+
+    #[test]
+    fn redact_private_values() {
+        let redaction = Redaction::new(&program().arguments, [0; REDACTION_SALT_SIZE]);
+
+        let mut observed = redaction.clone();
+        for statement in &program().statements {
+            observed.observe(statement);
+        }
+        assert!(observed.is_private(&Variable::new(2)));
+        assert!(!observed.is_private(&Variable::new(1)));
+
+        assert_eq!(
+            log(redaction.clone()),
+            format!(
+                "x = {}, y = \"4\", square = {}\n",
+                redaction.redact(&[Bn128Field::from(3)]),
+                redaction.redact(&[Bn128Field::from(9)])
+            )
+        );
+    }
+
+    #[test]
+    fn redact_trace() {
+        let redaction = Redaction::new(&program().arguments, [0; REDACTION_SALT_SIZE]);
+        let mut writer = TraceWriter::new(vec![]).redact(redaction.clone());
+
+        Interpreter::default()
+            .with_redaction(redaction.clone())
+            .execute_with_observer(
+                program(),
+                &[Bn128Field::from(3), Bn128Field::from(4)],
+                &mut std::io::sink(),
+                &mut writer,
+            )
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            format!(
+                "{{\"step\":0,\"writes\":[[\"_2\",\"{}\"]]}}\n{{\"step\":1,\"writes\":[]}}\n{{\"step\":2,\"writes\":[[\"~out_0\",\"4\"]]}}\n",
+                redaction.redact(&[Bn128Field::from(9)])
+            )
+        );
+    }
+
+    #[test]
+    fn salted_hash() {
+        let first = Redaction::new(&program().arguments, [1; REDACTION_SALT_SIZE]);
+        let second = Redaction::new(&program().arguments, [2; REDACTION_SALT_SIZE]);
+
+        // stable within a run
+        assert_eq!(log(first.clone()), log(first.clone()));
+        assert_eq!(
+            first.redact(&[Bn128Field::from(3)]),
+            first.redact(&[Bn128Field::from(3)])
+        );
+        assert_ne!(
+            first.redact(&[Bn128Field::from(3)]),
+            first.redact(&[Bn128Field::from(4)])
+        );
+
+        // different across runs
+        assert_ne!(log(first), log(second));
+    }
+}
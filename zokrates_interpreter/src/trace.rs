@@ -0,0 +1,215 @@
+use crate::Redaction;
+use serde::Serialize;
+use std::io;
+use zokrates_ast::common::SourceMetadata;
+use zokrates_ast::ir::Variable;
+use zokrates_field::Field;
+
+/// A single executed statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionStep<T> {
+    /// The index of the statement in the program
+    pub index: usize,
+    /// The source location of the statement, if known
+    pub span: Option<SourceMetadata>,
+    /// The variables written by the statement, in order, with their values
+    pub writes: Vec<(Variable, T)>,
+    /// Whether the values written are derived from private arguments, only tracked when the interpreter redacts them
+    pub private: bool,
+    /// For a constraint which is checked rather than used to compute a variable, whether the check could have
+    /// failed: constant checks and checks guarded by a branch which is not taken always hold
+    pub exercised: Option<bool>,
+}
+
+/// Receives every statement executed by the interpreter
+pub trait ExecutionObserver<T> {
+    fn on_step(&mut self, step: &ExecutionStep<T>) -> io::Result<()>;
+}
+
+impl<T, F: FnMut(&ExecutionStep<T>) -> io::Result<()>> ExecutionObserver<T> for F {
+    fn on_step(&mut self, step: &ExecutionStep<T>) -> io::Result<()> {
+        self(step)
+    }
+}
+
+/// An observer which writes one JSON object per executed statement, for example
+/// `{"step":3,"span":"main.zok:2:5","writes":[["_2","42"]]}`
+///
+/// When a filter is set, only the writes to the variables it contains are kept, and steps without
+/// any such write are skipped. When a redaction is set, the values of the steps marked as private by the interpreter,
+/// which must be given the same redaction, are written as hashes.
+pub struct TraceWriter<W> {
+    writer: W,
+    filter: Option<Vec<String>>,
+    redaction: Option<Redaction>,
+}
+
+impl<W: io::Write> TraceWriter<W> {
+    pub fn new(writer: W) -> Self {
+        TraceWriter {
+            writer,
+            filter: None,
+            redaction: None,
+        }
+    }
+
+    pub fn filter<S: Into<String>, I: IntoIterator<Item = S>>(mut self, variables: I) -> Self {
+        self.filter = Some(variables.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn redact(mut self, redaction: Redaction) -> Self {
+        self.redaction = Some(redaction);
+        self
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[derive(Serialize)]
+struct TraceLine {
+    step: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    span: Option<String>,
+    writes: Vec<(String, String)>,
+}
+
+impl<T: Field, W: io::Write> ExecutionObserver<T> for TraceWriter<W> {
+    fn on_step(&mut self, step: &ExecutionStep<T>) -> io::Result<()> {
+        let writes: Vec<_> = step
+            .writes
+            .iter()
+            .map(|(variable, value)| {
+                let value = match &self.redaction {
+                    Some(redaction) if step.private => {
+                        redaction.redact(std::slice::from_ref(value))
+                    }
+                    _ => value.to_dec_string(),
+                };
+                (variable.to_string(), value)
+            })
+            .filter(|(name, _)| match &self.filter {
+                Some(filter) => filter.contains(name),
+                None => true,
+            })
+            .collect();
+
+        if self.filter.is_some() && writes.is_empty() {
+            return Ok(());
+        }
+
+        let line = TraceLine {
+            step: step.index,
+            span: step
+                .span
+                .as_ref()
+                .map(|span| format!("{}:{}", span.file, span.position)),
+            writes,
+        };
+
+        serde_json::to_writer(&mut self.writer, &line)?;
+        writeln!(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Interpreter;
+    use zokrates_ast::ir::{Parameter, Prog, QuadComb, RuntimeError, Statement};
+    use zokrates_ast::untyped::Position;
+    use zokrates_field::Bn128Field;
+
+    // def main(private x) -> ~out_0
+    //     _1 = x * x
+    //     _1 == x * x (assertion at main.zok:3:5)
+    //     ~out_0 = _1
+    fn program() -> Prog<'static, Bn128Field> {
+        let x = Variable::new(0);
+        let square = Variable::new(1);
+        let out = Variable::public(0);
+
+        Prog::new(
+            vec![Parameter::private(x)],
+            vec![
+                Statement::definition(
+                    square,
+                    QuadComb::from_linear_combinations(x.into(), x.into()),
+                ),
+                Statement::Constraint(
+                    QuadComb::from_linear_combinations(x.into(), x.into()),
+                    square.into(),
+                    Some(RuntimeError::SourceAssertion(SourceMetadata::new(
+                        "main.zok".into(),
+                        Position { line: 3, col: 5 },
+                    ))),
+                ),
+                Statement::definition(out, square),
+            ],
+            1,
+        )
+    }
+
+    fn trace(writer: TraceWriter<Vec<u8>>) -> Vec<String> {
+        let mut writer = writer;
+        Interpreter::default()
+            .execute_with_observer(
+                program(),
+                &[Bn128Field::from(3)],
+                &mut io::sink(),
+                &mut writer,
+            )
+            .unwrap();
+
+        String::from_utf8(writer.into_inner())
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn writes_in_order() {
+        assert_eq!(
+            trace(TraceWriter::new(vec![])),
+            vec![
+                r#"{"step":0,"writes":[["_1","9"]]}"#,
+                r#"{"step":1,"span":"main.zok:3:5","writes":[]}"#,
+                r#"{"step":2,"writes":[["~out_0","9"]]}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn filter() {
+        assert_eq!(
+            trace(TraceWriter::new(vec![]).filter(["~out_0"])),
+            vec![r#"{"step":2,"writes":[["~out_0","9"]]}"#]
+        );
+    }
+
+    #[test]
+    fn callback() {
+        let mut steps = vec![];
+
+        Interpreter::default()
+            .execute_with_observer(
+                program(),
+                &[Bn128Field::from(3)],
+                &mut io::sink(),
+                &mut |step: &ExecutionStep<Bn128Field>| -> io::Result<()> {
+                    steps.push(step.clone());
+                    Ok(())
+                },
+            )
+            .unwrap();
+
+        assert_eq!(steps.len(), 3);
+        assert_eq!(
+            steps[0].writes,
+            vec![(Variable::new(1), Bn128Field::from(9))]
+        );
+    }
+}
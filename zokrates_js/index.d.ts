@@ -1,9 +1,16 @@
 declare module "zokrates-js" {
   export type Backend = "ark" | "bellman";
-  export type Curve = "bn128" | "bls12_381" | "bls12_377" | "bw6_761";
+  export type Curve =
+    | "bn128"
+    | "bls12_381"
+    | "bls12_377"
+    | "bw6_761"
+    | "pallas"
+    | "vesta";
   export type Scheme = "g16" | "gm17" | "marlin";
 
   export type VerificationKey = object;
+  export type Entropy = Uint8Array | string;
   export type ProvingKey = Uint8Array;
 
   export type ResolveCallback = (
@@ -14,6 +21,10 @@ declare module "zokrates-js" {
   export interface CompileConfig {
     isolate_branches?: boolean;
     debug?: boolean;
+    reduction_policy?: "greedy" | "minimize_decompositions";
+    div_by_zero?: "error" | "define_zero";
+    max_constraints?: number;
+    pipeline?: string;
   }
 
   export interface CompileOptions {
@@ -34,6 +45,23 @@ declare module "zokrates-js" {
     location: string;
   }
 
+  export interface SourcePosition {
+    line: number;
+    col: number;
+  }
+
+  export interface Diagnostic {
+    code: "parser" | "import" | "macro" | "semantic" | "io" | "analysis";
+    message: string;
+    file: string;
+    start?: SourcePosition;
+    end?: SourcePosition;
+  }
+
+  export interface CompileError extends Error {
+    diagnostics: Diagnostic[];
+  }
+
   export type LogCallback = (log: string) => void;
 
   export interface ComputeOptions {
@@ -49,9 +77,16 @@ declare module "zokrates-js" {
     };
   }
 
+  export interface AbiConstant {
+    name: string;
+    type: string;
+    value: any;
+  }
+
   export interface Abi {
     inputs: Array<any>;
     outputs: Array<any>;
+    constants?: Array<AbiConstant>;
   }
 
   export interface CompilationArtifacts {
@@ -85,14 +120,14 @@ declare module "zokrates-js" {
       args: any[],
       options?: ComputeOptions
     ): ComputationResult;
-    setup(program: Uint8Array, entropy?: string): SetupKeypair;
-    universalSetup(size: number, entropy?: string): Uint8Array;
+    setup(program: Uint8Array, entropy?: Entropy): SetupKeypair;
+    universalSetup(size: number, entropy?: Entropy): Uint8Array;
     setupWithSrs(srs: Uint8Array, program: Uint8Array): SetupKeypair;
     generateProof(
       program: Uint8Array,
       witness: string,
       provingKey: Uint8Array,
-      entropy?: string
+      entropy?: Entropy
     ): Proof;
     verify(verificationKey: VerificationKey, proof: Proof): boolean;
     exportSolidityVerifier(verificationKey: VerificationKey): string;
@@ -17,13 +17,26 @@ const initialize = async () => {
 
       config = { snarkjs, ...config };
 
-      const ptr = wasmExports.compile(
-        source,
-        location,
-        resolveCallback,
-        config,
-        curve
-      );
+      let ptr;
+      try {
+        ptr = wasmExports.compile(
+          source,
+          location,
+          resolveCallback,
+          config,
+          curve
+        );
+      } catch (diagnostics) {
+        if (!Array.isArray(diagnostics)) throw diagnostics;
+        const message = diagnostics
+          .map((d) =>
+            d.start
+              ? `${d.file}:${d.start.line}:${d.start.col}: ${d.message}`
+              : `${d.file}: ${d.message}`
+          )
+          .join("\n");
+        throw Object.assign(new Error(message), { diagnostics });
+      }
       const result = Object.assign(
         {
           program: ptr.program(),
@@ -0,0 +1,33 @@
+use serde::Serialize;
+use wasm_bindgen::JsValue;
+use zokrates_ast::untyped::Position;
+use zokrates_core::compile::{CompileError, CompileErrors};
+
+/// A compilation error in a shape web IDEs can use to underline the offending code
+#[derive(Serialize)]
+pub struct Diagnostic {
+    code: &'static str,
+    message: String,
+    file: String,
+    start: Option<Position>,
+    end: Option<Position>,
+}
+
+impl From<&CompileError> for Diagnostic {
+    fn from(e: &CompileError) -> Self {
+        let pos = e.value().pos();
+        Diagnostic {
+            code: e.value().code(),
+            message: e.value().message(),
+            file: e.file().display().to_string(),
+            start: pos.map(|p| p.0),
+            end: pos.map(|p| p.1),
+        }
+    }
+}
+
+/// Convert compilation errors to a JS array of diagnostic objects
+pub fn to_js_diagnostics(errors: &CompileErrors) -> JsValue {
+    let diagnostics: Vec<Diagnostic> = errors.0.iter().map(Diagnostic::from).collect();
+    serde_wasm_bindgen::to_value(&diagnostics).unwrap_or_else(|e| JsValue::from_str(&e.to_string()))
+}
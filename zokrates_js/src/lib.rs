@@ -1,8 +1,10 @@
+mod diagnostic;
 mod util;
 
 #[macro_use]
 extern crate lazy_static;
 
+use crate::diagnostic::to_js_diagnostics;
 use crate::util::normalize_path;
 use rand_0_8::{rngs::StdRng, SeedableRng};
 use serde::{Deserialize, Serialize};
@@ -12,6 +14,7 @@ use std::io::{Cursor, Write};
 use std::path::{Component, PathBuf};
 use typed_arena::Arena;
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
 use zokrates_abi::{parse_strict, Decode, Encode, Inputs};
 use zokrates_ark::Ark;
 use zokrates_ast::ir;
@@ -19,16 +22,18 @@ use zokrates_ast::ir::ProgEnum;
 use zokrates_ast::typed::abi::Abi;
 use zokrates_ast::typed::types::{ConcreteSignature, ConcreteType, GTupleType};
 use zokrates_bellman::Bellman;
-use zokrates_circom::{write_r1cs, write_witness};
+use zokrates_circom::{public_wires, write_r1cs, write_witness};
 use zokrates_common::helpers::{BackendParameter, CurveParameter, SchemeParameter};
 use zokrates_common::{CompileConfig, Resolver};
-use zokrates_core::compile::{compile as core_compile, CompilationArtifacts, CompileError};
+use zokrates_core::compile::{compile as core_compile, CompilationArtifacts};
 use zokrates_core::imports::Error;
-use zokrates_field::{Bls12_377Field, Bls12_381Field, Bn128Field, Bw6_761Field, Field};
+use zokrates_field::{
+    Bls12_377Field, Bls12_381Field, Bn128Field, Bw6_761Field, Field, PallasField, VestaField,
+};
 use zokrates_proof_systems::groth16::G16;
-use zokrates_proof_systems::rng::get_rng_from_entropy;
+use zokrates_proof_systems::rng::{get_rng_from_entropy, get_rng_from_seed};
 use zokrates_proof_systems::{
-    Backend, Marlin, NonUniversalBackend, NonUniversalScheme, Proof, Scheme,
+    read_proving_key_for, Backend, Marlin, NonUniversalBackend, NonUniversalScheme, Proof, Scheme,
     SolidityCompatibleField, SolidityCompatibleScheme, TaggedKeypair, TaggedProof,
     UniversalBackend, UniversalScheme, GM17,
 };
@@ -220,6 +225,27 @@ impl<'a> Write for LogWriter<'a> {
     }
 }
 
+/// Build the rng used by the backend from the entropy passed by the caller, which can either be
+/// a `Uint8Array` of random bytes, a string, or undefined in which case we fall back to `getrandom`
+fn get_rng(entropy: JsValue) -> Result<StdRng, JsValue> {
+    if entropy.is_null() || entropy.is_undefined() {
+        return Ok(StdRng::from_entropy());
+    }
+
+    if let Some(s) = entropy.as_string() {
+        return Ok(get_rng_from_entropy(&s));
+    }
+
+    if entropy.is_instance_of::<js_sys::Uint8Array>() {
+        let seed = js_sys::Uint8Array::from(entropy).to_vec();
+        return get_rng_from_seed(&seed).map_err(|e| JsValue::from_str(&e));
+    }
+
+    Err(JsValue::from_str(
+        "Invalid entropy: expected a `Uint8Array` or a string",
+    ))
+}
+
 mod internal {
     use super::*;
     use rand_0_8::{CryptoRng, RngCore};
@@ -238,8 +264,6 @@ mod internal {
             .unwrap_or(false);
         let config: CompileConfig = serde_json::from_value(config).unwrap_or_default();
 
-        let fmt_error = |e: &CompileError| format!("{}:{}", e.file().display(), e.value());
-
         let arena = Arena::new();
         let artifacts: CompilationArtifacts<T, _> = core_compile(
             source.as_string().unwrap(),
@@ -248,15 +272,7 @@ mod internal {
             config,
             &arena,
         )
-        .map_err(|ce| {
-            JsValue::from_str(
-                &ce.0
-                    .iter()
-                    .map(|e| fmt_error(e))
-                    .collect::<Vec<_>>()
-                    .join("\n"),
-            )
-        })?;
+        .map_err(|ce| to_js_diagnostics(&ce))?;
 
         let abi = artifacts.abi().clone();
 
@@ -293,17 +309,17 @@ mod internal {
             .map(|v| *v == serde_json::Value::Bool(true))
             .unwrap_or(false);
 
-        let (inputs, signature) = if abi.is_object() {
+        let (inputs, signature, abi) = if abi.is_object() {
             let abi: Abi = abi.into_serde().map_err(|err| {
                 JsValue::from_str(&format!("Could not deserialize `abi`: {}", err))
             })?;
 
             let signature = abi.signature();
             let inputs = parse_strict(&input, signature.inputs.clone())
-                .map(Inputs::Abi)
+                .map(Inputs::<T>::Abi)
                 .map_err(|err| JsValue::from_str(&err.to_string()))?;
 
-            (inputs, signature)
+            (abi.commit(inputs.encode()), signature, Some(abi))
         } else {
             let signature = ConcreteSignature::new()
                 .inputs(vec![ConcreteType::FieldElement; program.arguments.len()])
@@ -312,28 +328,32 @@ mod internal {
                 )));
 
             let inputs = parse_strict(&input, signature.inputs.clone())
-                .map(Inputs::Abi)
+                .map(Inputs::<T>::Abi)
                 .map_err(|err| JsValue::from_str(&err.to_string()))?;
 
-            (inputs, signature)
+            (inputs.encode(), signature, None)
         };
 
         let interpreter = zokrates_interpreter::Interpreter::default();
 
-        let public_inputs = program.public_inputs();
+        let public_wires = public_wires(&program);
 
         let mut writer = LogWriter::new(log_callback);
         let witness = interpreter
-            .execute_with_log_stream(program, &inputs.encode(), &mut writer)
+            .execute_with_log_stream(program, &inputs, &mut writer)
             .map_err(|err| JsValue::from_str(&format!("Execution failed: {}", err)))?;
 
+        let return_values = match &abi {
+            Some(abi) => abi.arrange_outputs(witness.return_values()),
+            None => witness.return_values(),
+        };
+
         let return_values: serde_json::Value =
-            zokrates_abi::Value::decode(witness.return_values(), *signature.output)
-                .into_serde_json();
+            zokrates_abi::Value::decode(return_values, *signature.output).into_serde_json();
 
         let snarkjs_witness = with_snarkjs_witness.then(|| {
             let mut buffer = Cursor::new(vec![]);
-            write_witness(&mut buffer, witness.clone(), public_inputs).unwrap();
+            write_witness(&mut buffer, witness.clone(), public_wires).unwrap();
             buffer.into_inner()
         });
 
@@ -394,7 +414,10 @@ mod internal {
         let ir_witness: ir::Witness<T> = ir::Witness::read(str_witness.as_bytes())
             .map_err(|err| JsValue::from_str(&format!("Could not read witness: {}", err)))?;
 
-        let proof = B::generate_proof(prog, ir_witness, pk.to_vec(), rng);
+        // proving keys written by the cli start with the hash of their constraint system
+        let pk = read_proving_key_for(pk, &prog.hash()).map_err(|e| JsValue::from_str(&e))?;
+
+        let proof = B::generate_proof(prog, ir_witness, pk, rng);
         Ok(JsValue::from_serde(&TaggedProof::<T, S>::new(proof.proof, proof.inputs)).unwrap())
     }
 
@@ -472,6 +495,12 @@ pub fn compile(
         CurveParameter::Bw6_761 => {
             internal::compile::<Bw6_761Field>(source, location, resolve_callback, config)
         }
+        CurveParameter::Pallas => {
+            internal::compile::<PallasField>(source, location, resolve_callback, config)
+        }
+        CurveParameter::Vesta => {
+            internal::compile::<VestaField>(source, location, resolve_callback, config)
+        }
     }
 }
 
@@ -491,6 +520,8 @@ pub fn compute_witness(
         ProgEnum::Bls12_381Program(p) => internal::compute::<_>(p, abi, args, config, log_callback),
         ProgEnum::Bls12_377Program(p) => internal::compute::<_>(p, abi, args, config, log_callback),
         ProgEnum::Bw6_761Program(p) => internal::compute::<_>(p, abi, args, config, log_callback),
+        ProgEnum::PallasProgram(p) => internal::compute::<_>(p, abi, args, config, log_callback),
+        ProgEnum::VestaProgram(p) => internal::compute::<_>(p, abi, args, config, log_callback),
     }
 }
 
@@ -549,10 +580,7 @@ pub fn setup(program: &[u8], entropy: JsValue, options: JsValue) -> Result<JsVal
         .map_err(|err| JsValue::from_str(&err))?
         .collect();
 
-    let mut rng = entropy
-        .as_string()
-        .map(|s| get_rng_from_entropy(&s))
-        .unwrap_or_else(StdRng::from_entropy);
+    let mut rng = get_rng(entropy)?;
 
     match (backend, scheme) {
         (BackendParameter::Bellman, SchemeParameter::G16) => match prog {
@@ -577,6 +605,7 @@ pub fn setup(program: &[u8], entropy: JsValue, options: JsValue) -> Result<JsVal
             ProgEnum::Bw6_761Program(p) => {
                 Ok(internal::setup_non_universal::<_, G16, Ark, _>(p, &mut rng))
             }
+            _ => Err(JsValue::from_str("Not supported")),
         },
         (BackendParameter::Ark, SchemeParameter::GM17) => match prog {
             ProgEnum::Bn128Program(p) => Ok(internal::setup_non_universal::<_, GM17, Ark, _>(
@@ -591,6 +620,7 @@ pub fn setup(program: &[u8], entropy: JsValue, options: JsValue) -> Result<JsVal
             ProgEnum::Bw6_761Program(p) => Ok(internal::setup_non_universal::<_, GM17, Ark, _>(
                 p, &mut rng,
             )),
+            _ => Err(JsValue::from_str("Not supported")),
         },
         _ => Err(JsValue::from_str("Unsupported options")),
     }
@@ -617,6 +647,7 @@ pub fn setup_with_srs(srs: &[u8], program: &[u8], options: JsValue) -> Result<Js
             ProgEnum::Bls12_381Program(p) => internal::setup_universal::<_, _, Marlin, Ark>(srs, p),
             ProgEnum::Bls12_377Program(p) => internal::setup_universal::<_, _, Marlin, Ark>(srs, p),
             ProgEnum::Bw6_761Program(p) => internal::setup_universal::<_, _, Marlin, Ark>(srs, p),
+            _ => Err(JsValue::from_str("Not supported")),
         },
         _ => Err(JsValue::from_str("Given scheme is not universal")),
     }
@@ -627,10 +658,7 @@ pub fn universal_setup(curve: JsValue, size: u32, entropy: JsValue) -> Result<Ve
     let curve = CurveParameter::try_from(curve.as_string().unwrap().as_str())
         .map_err(|e| JsValue::from_str(&e))?;
 
-    let mut rng = entropy
-        .as_string()
-        .map(|s| get_rng_from_entropy(&s))
-        .unwrap_or_else(StdRng::from_entropy);
+    let mut rng = get_rng(entropy)?;
 
     match curve {
         CurveParameter::Bn128 => Ok(internal::universal_setup_of_size::<
@@ -657,6 +685,7 @@ pub fn universal_setup(curve: JsValue, size: u32, entropy: JsValue) -> Result<Ve
             Ark,
             _,
         >(size, &mut rng)),
+        _ => Err(JsValue::from_str("Not supported")),
     }
 }
 
@@ -688,10 +717,7 @@ pub fn generate_proof(
         .map_err(|err| JsValue::from_str(&err))?
         .collect();
 
-    let mut rng = entropy
-        .as_string()
-        .map(|s| get_rng_from_entropy(&s))
-        .unwrap_or_else(StdRng::from_entropy);
+    let mut rng = get_rng(entropy)?;
 
     match (backend, scheme) {
         (BackendParameter::Bellman, SchemeParameter::G16) => match prog {
@@ -716,6 +742,7 @@ pub fn generate_proof(
             ProgEnum::Bw6_761Program(p) => {
                 internal::generate_proof::<_, G16, Ark, _>(p, witness, pk, &mut rng)
             }
+            _ => Err(JsValue::from_str("Not supported")),
         },
         (BackendParameter::Ark, SchemeParameter::GM17) => match prog {
             ProgEnum::Bn128Program(p) => {
@@ -730,6 +757,7 @@ pub fn generate_proof(
             ProgEnum::Bw6_761Program(p) => {
                 internal::generate_proof::<_, GM17, Ark, _>(p, witness, pk, &mut rng)
             }
+            _ => Err(JsValue::from_str("Not supported")),
         },
         (BackendParameter::Ark, SchemeParameter::MARLIN) => match prog {
             ProgEnum::Bn128Program(p) => {
@@ -744,6 +772,7 @@ pub fn generate_proof(
             ProgEnum::Bw6_761Program(p) => {
                 internal::generate_proof::<_, Marlin, Ark, _>(p, witness, pk, &mut rng)
             }
+            _ => Err(JsValue::from_str("Not supported")),
         },
         _ => Err(JsValue::from_str("Unsupported options")),
     }
@@ -816,18 +845,21 @@ pub fn verify(vk: JsValue, proof: JsValue, options: JsValue) -> Result<JsValue,
             CurveParameter::Bls12_381 => internal::verify::<Bls12_381Field, G16, Ark>(vk, proof),
             CurveParameter::Bls12_377 => internal::verify::<Bls12_377Field, G16, Ark>(vk, proof),
             CurveParameter::Bw6_761 => internal::verify::<Bw6_761Field, G16, Ark>(vk, proof),
+            _ => Err(JsValue::from_str("Not supported")),
         },
         (BackendParameter::Ark, SchemeParameter::GM17) => match curve {
             CurveParameter::Bn128 => internal::verify::<Bn128Field, GM17, Ark>(vk, proof),
             CurveParameter::Bls12_381 => internal::verify::<Bls12_381Field, GM17, Ark>(vk, proof),
             CurveParameter::Bls12_377 => internal::verify::<Bls12_377Field, GM17, Ark>(vk, proof),
             CurveParameter::Bw6_761 => internal::verify::<Bw6_761Field, GM17, Ark>(vk, proof),
+            _ => Err(JsValue::from_str("Not supported")),
         },
         (BackendParameter::Ark, SchemeParameter::MARLIN) => match curve {
             CurveParameter::Bn128 => internal::verify::<Bn128Field, Marlin, Ark>(vk, proof),
             CurveParameter::Bls12_381 => internal::verify::<Bls12_381Field, Marlin, Ark>(vk, proof),
             CurveParameter::Bls12_377 => internal::verify::<Bls12_377Field, Marlin, Ark>(vk, proof),
             CurveParameter::Bw6_761 => internal::verify::<Bw6_761Field, Marlin, Ark>(vk, proof),
+            _ => Err(JsValue::from_str("Not supported")),
         },
         _ => Err(JsValue::from_str("Unsupported options")),
     }
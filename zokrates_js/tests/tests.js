@@ -55,10 +55,35 @@ describe("tests", () => {
       assert.ok(artifacts.snarkjs.program !== undefined);
     });
 
+    it("should export public constants", () => {
+      const artifacts = zokratesProvider.compile(
+        "pub const u32 DEPTH = 3;\nconst field HIDDEN = 1;\ndef main() -> field { return HIDDEN; }"
+      );
+      assert.deepEqual(artifacts.abi.constants, [
+        { name: "DEPTH", type: "u32", value: "0x00000003" },
+      ]);
+    });
+
     it("should throw on invalid code", () => {
       assert.throws(() => zokratesProvider.compile(":-)"));
     });
 
+    it("should throw structured diagnostics", () => {
+      const code = "def main() {\n    field a = true;\n    return;\n}";
+      assert.throws(
+        () => zokratesProvider.compile(code),
+        (err) => {
+          assert.equal(err.diagnostics.length, 1);
+          const [diagnostic] = err.diagnostics;
+          assert.equal(diagnostic.code, "semantic");
+          assert.equal(diagnostic.file, "main.zok");
+          assert.deepEqual(diagnostic.start, { line: 2, col: 5 });
+          assert.deepEqual(diagnostic.end, { line: 2, col: 19 });
+          return true;
+        }
+      );
+    });
+
     it("should resolve stdlib module", () => {
       const code = `import "utils/pack/bool/unpack_unchecked";\ndef main() {}`;
       zokratesProvider.compile(code);
@@ -266,6 +291,42 @@ describe("tests", () => {
       assert.deepEqual(proof, proof2);
     });
 
+    it("generate proof with user-provided seed", () => {
+      const seed = new Uint8Array(32).fill(42);
+      proof = provider.generateProof(
+        artifacts.program,
+        computationResult.witness,
+        keypair.pk,
+        seed
+      );
+
+      // second call with the same seed should return the same proof
+      let proof2 = provider.generateProof(
+        artifacts.program,
+        computationResult.witness,
+        keypair.pk,
+        seed
+      );
+      assert.deepEqual(proof, proof2);
+    });
+
+    it("should reject short seeds", () => {
+      const seed = new Uint8Array(31).fill(42);
+      assert.throws(() =>
+        provider.generateProof(
+          artifacts.program,
+          computationResult.witness,
+          keypair.pk,
+          seed
+        )
+      );
+      if (options.scheme === "marlin") {
+        assert.throws(() => provider.universalSetup(4, seed));
+      } else {
+        assert.throws(() => provider.setup(artifacts.program, seed));
+      }
+    });
+
     if (options.scheme === "g16" && options.curve == "bn128") {
       it("generate snarkjs proof", () => {
         // write witness to fs
@@ -325,6 +325,31 @@ mod tests {
             };
         }
 
+        #[test]
+        fn parse_private_identifier() {
+            for input in ["_helper", "__depth", "_0"] {
+                let parse = ZoKratesParser::parse(Rule::identifier, input);
+                assert_eq!(parse.unwrap().as_str(), input);
+            }
+
+            // a single underscore is left to inferred generics
+            let parse = ZoKratesParser::parse(Rule::identifier, "_");
+            assert!(parse.is_err());
+
+            let parse = ZoKratesParser::parse(Rule::constant_generics_value, "_");
+            assert_eq!(
+                parse
+                    .unwrap()
+                    .next()
+                    .unwrap()
+                    .into_inner()
+                    .next()
+                    .unwrap()
+                    .as_rule(),
+                Rule::underscore
+            );
+        }
+
         #[test]
         fn parse_for_loop() {
             let input = "for u32 i in 0..3 { c = c + a[i]; }";
@@ -332,5 +357,80 @@ mod tests {
             let parse = ZoKratesParser::parse(Rule::iteration_statement, input);
             assert!(parse.is_ok());
         }
+
+        #[test]
+        fn parse_labeled_for_loop() {
+            let input = "for u32 i in 0..64 as 'rounds: { c = c + a[i]; }";
+
+            let parse = ZoKratesParser::parse(Rule::iteration_statement, input);
+            assert!(parse.is_ok());
+
+            // the label is a single token
+            let input = "for u32 i in 0..64 as ' rounds: { c = c + a[i]; }";
+
+            let parse = ZoKratesParser::parse(Rule::iteration_statement, input);
+            assert!(parse.is_err());
+        }
+
+        #[test]
+        fn parse_derive_nonce_parameter() {
+            let input = "#[derive_nonce] private field nonce";
+
+            let parse = ZoKratesParser::parse(Rule::parameter, input);
+            assert!(parse.is_ok());
+        }
+
+        #[test]
+        fn parse_bind_at_setup_parameter() {
+            let input = "#[bind_at_setup] private field threshold";
+
+            let parse = ZoKratesParser::parse(Rule::parameter, input);
+            assert!(parse.is_ok());
+        }
+
+        #[test]
+        fn parse_parameter_attributes_in_any_order() {
+            for input in [
+                "#[derive_nonce] #[bind_at_setup] private field nonce",
+                "#[bind_at_setup] #[derive_nonce] private field nonce",
+            ] {
+                let parse = ZoKratesParser::parse(Rule::parameter, input);
+                assert!(parse.is_ok());
+            }
+        }
+
+        #[test]
+        fn parse_bounded_field_parameter() {
+            let input = "private field<128>[2] balances";
+
+            let parse = ZoKratesParser::parse(Rule::parameter, input);
+            assert!(parse.is_ok());
+
+            // the bound is a decimal number
+            let input = "private field<N> balance";
+
+            let parse = ZoKratesParser::parse(Rule::parameter, input);
+            assert!(parse.is_err());
+        }
+
+        #[test]
+        fn parse_cast() {
+            for input in ["u32(x)", "u8::unchecked(x + 1)", "u64(x)[0]"] {
+                let parse = ZoKratesParser::parse(Rule::expression, input);
+                assert!(parse.is_ok());
+            }
+
+            // a cast is only called, and the name of its type is not an expression
+            let parse = ZoKratesParser::parse(Rule::expression, "u32[0]");
+            assert!(parse.is_err());
+        }
+
+        #[test]
+        fn parse_critical_assertion() {
+            let input = "#[critical] assert(a == b, \"message\")";
+
+            let parse = ZoKratesParser::parse(Rule::assertion_statement, input);
+            assert!(parse.is_ok());
+        }
     }
 }
@@ -1,5 +1,5 @@
 use from_pest::FromPest;
-use pest::error::Error as PestError;
+use pest::error::{Error as PestError, LineColLocation};
 use pest::iterators::Pairs;
 use std::fmt;
 use zokrates_parser::parse;
@@ -7,20 +7,23 @@ use zokrates_parser::Rule;
 #[macro_use]
 extern crate lazy_static;
 
+mod recovery;
+
 pub use ast::{
     Access, Arguments, ArrayAccess, ArrayInitializerExpression, ArrayType, AssemblyStatement,
     AssemblyStatementInner, AssertionStatement, Assignee, AssigneeAccess, AssignmentOperator,
     BasicOrStructOrTupleType, BasicType, BinaryExpression, BinaryOperator, CallAccess,
-    ConstantDefinition, ConstantGenericValue, DecimalLiteralExpression, DecimalNumber,
-    DecimalSuffix, DefinitionStatement, ExplicitGenerics, Expression, FieldType, File,
-    FromExpression, FunctionDefinition, HexLiteralExpression, HexNumberExpression,
-    IdentifierExpression, IdentifierOrDecimal, IfElseExpression, ImportDirective, ImportSymbol,
-    InlineArrayExpression, InlineStructExpression, InlineStructMember, InlineTupleExpression,
-    IterationStatement, LiteralExpression, LogStatement, Parameter, PostfixExpression, Range,
-    RangeOrExpression, ReturnStatement, Span, Spread, SpreadOrExpression, Statement,
-    StructDefinition, StructField, SymbolDeclaration, TernaryExpression, ToExpression, Type,
-    TypeDefinition, TypedIdentifier, TypedIdentifierOrAssignee, UnaryExpression, UnaryOperator,
-    Underscore, Visibility,
+    ConstantDefinition, ConstantGenericValue, CriticalAttribute, DecimalLiteralExpression,
+    DecimalNumber, DecimalSuffix, DefinitionStatement, DeriveNonceAttribute, ExplicitGenerics,
+    Expression, FieldBound, FieldType, File, FromExpression, FunctionDefinition,
+    HexLiteralExpression, HexNumberExpression, IdentifierExpression, IdentifierOrDecimal,
+    IfElseExpression, ImportDirective, ImportSymbol, InlineArrayExpression, InlineStructExpression,
+    InlineStructMember, InlineTupleExpression, IterationStatement, LiteralExpression, LogStatement,
+    LoopLabel, Parameter, ParameterAttribute, PostfixExpression, PubAttribute, Range,
+    RangeOrExpression, ReturnElement, ReturnStatement, ReturnTuple, Span, Spread,
+    SpreadOrExpression, Statement, StructDefinition, StructField, SymbolDeclaration,
+    TernaryExpression, ToExpression, Type, TypeDefinition, TypedIdentifier,
+    TypedIdentifierOrAssignee, UnaryExpression, UnaryOperator, Underscore, Visibility,
 };
 
 mod ast {
@@ -178,6 +181,7 @@ mod ast {
         pub generics: Vec<IdentifierExpression<'ast>>,
         pub parameters: Vec<Parameter<'ast>>,
         pub return_type: Option<Type<'ast>>,
+        pub return_tuple: Option<ReturnTuple<'ast>>,
         pub statements: Vec<Statement<'ast>>,
         #[pest_ast(outer())]
         pub span: Span<'ast>,
@@ -186,12 +190,17 @@ mod ast {
     #[derive(Debug, FromPest, PartialEq, Clone)]
     #[pest_ast(rule(Rule::const_definition))]
     pub struct ConstantDefinition<'ast> {
+        pub public: Option<PubAttribute>,
         pub id: TypedIdentifier<'ast>,
         pub expression: Expression<'ast>,
         #[pest_ast(outer())]
         pub span: Span<'ast>,
     }
 
+    #[derive(Debug, FromPest, PartialEq, Eq, Clone)]
+    #[pest_ast(rule(Rule::pub_attribute))]
+    pub struct PubAttribute {}
+
     #[derive(Debug, FromPest, PartialEq, Clone)]
     #[pest_ast(rule(Rule::type_definition))]
     pub struct TypeDefinition<'ast> {
@@ -254,11 +263,23 @@ mod ast {
         U16(U16Type<'ast>),
         U32(U32Type<'ast>),
         U64(U64Type<'ast>),
+        I8(I8Type<'ast>),
+        I16(I16Type<'ast>),
+        I32(I32Type<'ast>),
     }
 
     #[derive(Debug, FromPest, PartialEq, Eq, Clone)]
     #[pest_ast(rule(Rule::ty_field))]
     pub struct FieldType<'ast> {
+        pub bound: Option<FieldBound<'ast>>,
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
+    #[derive(Debug, FromPest, PartialEq, Eq, Clone)]
+    #[pest_ast(rule(Rule::field_bound))]
+    pub struct FieldBound<'ast> {
+        pub value: DecimalNumber<'ast>,
         #[pest_ast(outer())]
         pub span: Span<'ast>,
     }
@@ -315,6 +336,27 @@ mod ast {
         pub span: Span<'ast>,
     }
 
+    #[derive(Debug, FromPest, PartialEq, Eq, Clone)]
+    #[pest_ast(rule(Rule::ty_i8))]
+    pub struct I8Type<'ast> {
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
+    #[derive(Debug, FromPest, PartialEq, Eq, Clone)]
+    #[pest_ast(rule(Rule::ty_i16))]
+    pub struct I16Type<'ast> {
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
+    #[derive(Debug, FromPest, PartialEq, Eq, Clone)]
+    #[pest_ast(rule(Rule::ty_i32))]
+    pub struct I32Type<'ast> {
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
     #[derive(Debug, FromPest, PartialEq, Eq, Clone)]
     #[pest_ast(rule(Rule::ty_struct))]
     pub struct StructType<'ast> {
@@ -332,9 +374,28 @@ mod ast {
         pub span: Span<'ast>,
     }
 
+    /// The return values of a function when some of them have a visibility
+    #[derive(Debug, FromPest, PartialEq, Clone)]
+    #[pest_ast(rule(Rule::ty_return_tuple))]
+    pub struct ReturnTuple<'ast> {
+        pub elements: Vec<ReturnElement<'ast>>,
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
+    #[derive(Debug, FromPest, PartialEq, Clone)]
+    #[pest_ast(rule(Rule::return_element))]
+    pub struct ReturnElement<'ast> {
+        pub visibility: Option<Visibility>,
+        pub ty: Type<'ast>,
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
     #[derive(Debug, FromPest, PartialEq, Clone)]
     #[pest_ast(rule(Rule::parameter))]
     pub struct Parameter<'ast> {
+        pub attributes: Vec<ParameterAttribute>,
         pub visibility: Option<Visibility>,
         pub ty: Type<'ast>,
         pub mutable: Option<Mutable>,
@@ -408,23 +469,52 @@ mod ast {
     #[derive(Debug, FromPest, PartialEq, Clone)]
     #[pest_ast(rule(Rule::assertion_statement))]
     pub struct AssertionStatement<'ast> {
+        pub critical: Option<CriticalAttribute>,
         pub expression: Expression<'ast>,
         pub message: Option<QString<'ast>>,
         #[pest_ast(outer())]
         pub span: Span<'ast>,
     }
 
+    #[derive(Debug, FromPest, PartialEq, Eq, Clone)]
+    #[pest_ast(rule(Rule::critical_attribute))]
+    pub struct CriticalAttribute {}
+
+    #[derive(Debug, FromPest, PartialEq, Eq, Clone)]
+    #[pest_ast(rule(Rule::parameter_attribute))]
+    pub enum ParameterAttribute {
+        DeriveNonce(DeriveNonceAttribute),
+        BindAtSetup(BindAtSetupAttribute),
+    }
+
+    #[derive(Debug, FromPest, PartialEq, Eq, Clone)]
+    #[pest_ast(rule(Rule::derive_nonce_attribute))]
+    pub struct DeriveNonceAttribute {}
+
+    #[derive(Debug, FromPest, PartialEq, Eq, Clone)]
+    #[pest_ast(rule(Rule::bind_at_setup_attribute))]
+    pub struct BindAtSetupAttribute {}
+
     #[derive(Debug, FromPest, PartialEq, Clone)]
     #[pest_ast(rule(Rule::iteration_statement))]
     pub struct IterationStatement<'ast> {
         pub index: TypedIdentifier<'ast>,
         pub from: Expression<'ast>,
         pub to: Expression<'ast>,
+        pub label: Option<LoopLabel<'ast>>,
         pub statements: Vec<Statement<'ast>>,
         #[pest_ast(outer())]
         pub span: Span<'ast>,
     }
 
+    #[derive(Debug, FromPest, PartialEq, Clone)]
+    #[pest_ast(rule(Rule::loop_label))]
+    pub struct LoopLabel<'ast> {
+        pub id: IdentifierExpression<'ast>,
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
     #[derive(Debug, FromPest, PartialEq, Clone)]
     #[pest_ast(rule(Rule::return_statement))]
     pub struct ReturnStatement<'ast> {
@@ -467,9 +557,20 @@ mod ast {
         pub span: Span<'ast>,
     }
 
+    #[derive(Debug, FromPest, PartialEq, Clone)]
+    #[pest_ast(rule(Rule::asm_unsafe_witness))]
+    pub struct AssemblyUnsafeWitness<'ast> {
+        pub assignee: Assignee<'ast>,
+        pub plugin: QString<'ast>,
+        pub arguments: Vec<Expression<'ast>>,
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
     #[derive(Debug, FromPest, PartialEq, Clone)]
     #[pest_ast(rule(Rule::asm_statement_inner))]
     pub enum AssemblyStatementInner<'ast> {
+        UnsafeWitness(AssemblyUnsafeWitness<'ast>),
         Assignment(AssemblyAssignment<'ast>),
         Constraint(AssemblyConstraint<'ast>),
     }
@@ -526,12 +627,23 @@ mod ast {
         Expression(Expression<'ast>),
         InlineStruct(InlineStructExpression<'ast>),
         IfElse(IfElseExpression<'ast>),
+        Cast(CastFunction<'ast>),
         Primary(PrimaryExpression<'ast>),
         InlineArray(InlineArrayExpression<'ast>),
         InlineTuple(InlineTupleExpression<'ast>),
         ArrayInitializer(ArrayInitializerExpression<'ast>),
     }
 
+    /// The name of a conversion to an unsigned integer, such as `u32` or `u32::unchecked`
+    #[derive(Debug, FromPest, PartialEq, Eq, Clone)]
+    #[pest_ast(rule(Rule::cast_function))]
+    pub struct CastFunction<'ast> {
+        #[pest_ast(outer(with(span_into_str)))]
+        pub value: String,
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
     #[derive(Debug, FromPest, PartialEq, Clone)]
     #[pest_ast(rule(Rule::postfixed_term))]
     pub struct PostfixedTerm<'ast> {
@@ -643,6 +755,11 @@ mod ast {
             match t {
                 Term::Expression(e) => e,
                 Term::IfElse(e) => Expression::IfElse(e),
+                // a cast is called like a function named after it
+                Term::Cast(e) => Expression::Identifier(IdentifierExpression {
+                    value: e.value,
+                    span: e.span,
+                }),
                 Term::Primary(e) => e.into(),
                 Term::InlineArray(e) => Expression::InlineArray(e),
                 Term::InlineTuple(e) => Expression::InlineTuple(e),
@@ -1013,6 +1130,7 @@ mod ast {
         U32(U32Suffix<'ast>),
         U64(U64Suffix<'ast>),
         Field(FieldSuffix<'ast>),
+        Wrapping(WrappingSuffix<'ast>),
     }
 
     #[derive(Debug, FromPest, PartialEq, Eq, Clone)]
@@ -1050,6 +1168,13 @@ mod ast {
         pub span: Span<'ast>,
     }
 
+    #[derive(Debug, FromPest, PartialEq, Eq, Clone)]
+    #[pest_ast(rule(Rule::decimal_suffix_wrapping))]
+    pub struct WrappingSuffix<'ast> {
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
     #[derive(Debug, FromPest, PartialEq, Eq, Clone)]
     #[pest_ast(rule(Rule::decimal_number))]
     pub struct DecimalNumber<'ast> {
@@ -1167,6 +1292,16 @@ impl<'ast> From<Pairs<'ast, Rule>> for Prog<'ast> {
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub struct Error(PestError<Rule>);
 
+impl Error {
+    /// Returns the start and end `(line, column)` pairs of the error, both 1-indexed
+    pub fn line_col(&self) -> ((usize, usize), (usize, usize)) {
+        match self.0.line_col {
+            LineColLocation::Pos(pos) => (pos, pos),
+            LineColLocation::Span(start, end) => (start, end),
+        }
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -1178,6 +1313,17 @@ pub fn generate_ast(input: &str) -> Result<ast::File, Error> {
     Ok(Prog::from(parse_tree).0)
 }
 
+/// Generate the AST of `input`, or return up to `max_errors` syntax errors, in the order they appear in the source
+pub fn generate_ast_with_recovery(input: &str, max_errors: usize) -> Result<ast::File, Vec<Error>> {
+    match parse(input) {
+        Ok(parse_tree) => Ok(Prog::from(parse_tree).0),
+        Err(e) => Err(recovery::collect_errors(input, e, max_errors)
+            .into_iter()
+            .map(Error)
+            .collect()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::ast::*;
@@ -1255,8 +1401,10 @@ mod tests {
                         },
                         parameters: vec![],
                         return_type: Some(Type::Basic(BasicType::Field(FieldType {
+                            bound: None,
                             span: Span::new(source, 46, 51).unwrap()
                         }))),
+                        return_tuple: None,
                         statements: vec![Statement::Return(ReturnStatement {
                             expression: Some(Expression::add(
                                 Expression::Literal(LiteralExpression::DecimalLiteral(
@@ -1323,8 +1471,10 @@ mod tests {
                         },
                         parameters: vec![],
                         return_type: Some(Type::Basic(BasicType::Field(FieldType {
+                            bound: None,
                             span: Span::new(source, 46, 51).unwrap()
                         }))),
+                        return_tuple: None,
                         statements: vec![Statement::Return(ReturnStatement {
                             expression: Some(Expression::add(
                                 Expression::Literal(LiteralExpression::DecimalLiteral(
@@ -1415,8 +1565,10 @@ mod tests {
                         },
                         parameters: vec![],
                         return_type: Some(Type::Basic(BasicType::Field(FieldType {
+                            bound: None,
                             span: Span::new(source, 46, 51).unwrap()
                         }))),
+                        return_tuple: None,
                         statements: vec![Statement::Return(ReturnStatement {
                             expression: Some(Expression::ternary(
                                 Box::new(Expression::Literal(LiteralExpression::DecimalLiteral(
@@ -1475,8 +1627,10 @@ mod tests {
                     },
                     parameters: vec![],
                     return_type: Some(Type::Basic(BasicType::Field(FieldType {
+                        bound: None,
                         span: Span::new(source, 14, 19).unwrap()
                     }))),
+                    return_tuple: None,
                     statements: vec![Statement::Return(ReturnStatement {
                         expression: Some(Expression::Literal(LiteralExpression::DecimalLiteral(
                             DecimalLiteralExpression {
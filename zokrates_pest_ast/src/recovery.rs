@@ -0,0 +1,240 @@
+//! Recovery from syntax errors, so that several of them can be reported at once.
+//!
+//! When parsing fails, the statement containing the error is blanked out and the source is parsed again. If this does
+//! not get the parser past the error, the whole top-level item containing it (a function, a struct, a constant...) is
+//! blanked out instead. Blanking replaces characters with spaces and keeps line breaks, so that the positions of later
+//! errors are the same as in the original source.
+
+use pest::error::{Error as PestError, InputLocation};
+use zokrates_parser::{parse, Rule};
+
+/// The keywords starting a top-level item when found at the beginning of a line
+const ITEM_KEYWORDS: [&str; 8] = [
+    "def", "struct", "const", "pub", "type", "import", "from", "#pragma",
+];
+
+/// Collect up to `max_errors` syntax errors of `input`, starting with `first`, the error found by a normal parse
+pub fn collect_errors(
+    input: &str,
+    first: PestError<Rule>,
+    max_errors: usize,
+) -> Vec<PestError<Rule>> {
+    let mut source = input.to_string();
+    let mut error = first;
+    let mut errors = vec![];
+
+    loop {
+        let pos = offset(&error);
+        errors.push(anchor(error, input));
+
+        if errors.len() >= max_errors {
+            break;
+        }
+
+        // try to skip the statement first, then the item
+        let (start, end) = statement_bounds(&source, pos);
+        blank(&mut source, start, end);
+
+        let next = match parse(&source) {
+            Ok(_) => break,
+            Err(e) if offset(&e) > end => e,
+            Err(_) => {
+                // items are found in the original input, as their keywords may have been blanked already
+                let (start, end) = item_bounds(input, pos);
+                blank(&mut source, start, end);
+
+                match parse(&source) {
+                    Ok(_) => break,
+                    Err(e) if offset(&e) >= end.max(pos + 1) => e,
+                    // we cannot make progress, give up
+                    Err(_) => break,
+                }
+            }
+        };
+
+        error = next;
+    }
+
+    errors
+}
+
+fn offset(error: &PestError<Rule>) -> usize {
+    match error.location {
+        InputLocation::Pos(pos) => pos,
+        InputLocation::Span((start, _)) => start,
+    }
+}
+
+/// Rebuild `error`, found in a partially blanked source, so that it displays the line of the original `input`
+fn anchor(error: PestError<Rule>, input: &str) -> PestError<Rule> {
+    match error.location {
+        InputLocation::Pos(pos) => {
+            PestError::new_from_pos(error.variant, pest::Position::new(input, pos).unwrap())
+        }
+        InputLocation::Span((start, end)) => {
+            PestError::new_from_span(error.variant, pest::Span::new(input, start, end).unwrap())
+        }
+    }
+}
+
+/// The bounds of the statement containing `pos`: from the end of the previous statement or block delimiter, to the end
+/// of the statement or the next block delimiter
+fn statement_bounds(source: &str, pos: usize) -> (usize, usize) {
+    let mut start = 0;
+
+    for (i, c) in delimiters(source) {
+        if i < pos {
+            start = i + 1;
+        } else {
+            return (start, if c == ';' { i + 1 } else { i });
+        }
+    }
+
+    (start, source.len())
+}
+
+/// The statement and block delimiters of `source` with their offsets, skipping string literals and comments
+fn delimiters(source: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut chars = source.char_indices().peekable();
+
+    std::iter::from_fn(move || {
+        while let Some((i, c)) = chars.next() {
+            match c {
+                ';' | '{' | '}' => return Some((i, c)),
+                '"' => {
+                    chars.find(|(_, c)| *c == '"');
+                }
+                '/' => match chars.peek() {
+                    Some((_, '/')) => {
+                        chars.find(|(_, c)| *c == '\n');
+                    }
+                    Some((_, '*')) => {
+                        chars.next();
+                        let mut star = false;
+                        chars.find(|(_, c)| {
+                            let end = star && *c == '/';
+                            star = *c == '*';
+                            end
+                        });
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+        None
+    })
+}
+
+/// The bounds of the top-level item containing `pos`: from the beginning of its first line, to the beginning of the
+/// next item
+fn item_bounds(source: &str, pos: usize) -> (usize, usize) {
+    let item_starts = line_starts(source).filter(|start| {
+        let line = source[*start..].trim_start_matches(|c| c == ' ' || c == '\t');
+        ITEM_KEYWORDS.iter().any(|keyword| {
+            line.starts_with(keyword)
+                && !line[keyword.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_')
+        })
+    });
+
+    let mut start = 0;
+    let mut end = source.len();
+
+    for item_start in item_starts {
+        if item_start <= pos {
+            start = item_start;
+        } else {
+            end = item_start;
+            break;
+        }
+    }
+
+    (start, end)
+}
+
+fn line_starts(source: &str) -> impl Iterator<Item = usize> + '_ {
+    std::iter::once(0).chain(source.match_indices('\n').map(|(i, _)| i + 1))
+}
+
+/// Replace the characters of `source` between `start` and `end` with spaces, keeping line breaks and byte offsets
+fn blank(source: &mut String, start: usize, end: usize) {
+    let blanked: String = source[start..end]
+        .chars()
+        .map(|c| match c {
+            '\n' | '\r' => c.to_string(),
+            // as many spaces as the character has bytes, so that later offsets do not move
+            _ => " ".repeat(c.len_utf8()),
+        })
+        .collect();
+
+    source.replace_range(start..end, &blanked);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(source: &str) -> Vec<(usize, usize)> {
+        collect_errors(source, parse(source).unwrap_err(), 10)
+            .into_iter()
+            .map(|e| match e.line_col {
+                pest::error::LineColLocation::Pos(pos) => pos,
+                pest::error::LineColLocation::Span(start, _) => start,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn skip_statements() {
+        let source = "def main() {\n    field a = 1 +;\n    field b = ;\n    return;\n}\n";
+        assert_eq!(errors(source), vec![(2, 18), (3, 15)]);
+    }
+
+    #[test]
+    fn skip_items() {
+        let source =
+            "def foo( {\n    return;\n}\n\ndef main() {\n    field a = ;\n    return;\n}\n";
+        assert_eq!(errors(source), vec![(1, 10), (6, 15)]);
+    }
+
+    #[test]
+    fn max_errors() {
+        let source =
+            "def main() {\n    field a = ;\n    field b = ;\n    field c = ;\n    return;\n}\n";
+        assert_eq!(
+            collect_errors(source, parse(source).unwrap_err(), 2).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn blank_keeps_positions() {
+        let mut source = "a;\nb;\nc".to_string();
+        blank(&mut source, 0, 5);
+        assert_eq!(source, "  \n  \nc");
+    }
+
+    #[test]
+    fn blank_keeps_offsets() {
+        let mut source = "é;\n€;\nc".to_string();
+        blank(&mut source, 0, 8);
+        assert_eq!(source, "   \n    \nc");
+        assert_eq!(source.len(), "é;\n€;\nc".len());
+    }
+
+    #[test]
+    fn skip_items_with_non_ascii() {
+        let source = "def foo( {\n    // héllo wörld\n    return;\n}\n\ndef main() {\n    field a = ;\n    return;\n}\n";
+        assert_eq!(errors(source), vec![(1, 10), (7, 15)]);
+    }
+
+    #[test]
+    fn statement_bounds_skip_strings_and_comments() {
+        let source = "a; assert(x, \"}; {\"); /* ; } */ b // ;\n c;";
+        let pos = source.find('x').unwrap();
+        assert_eq!(statement_bounds(source, pos), (2, 21));
+
+        let pos = source.find('b').unwrap();
+        assert_eq!(statement_bounds(source, pos), (21, source.len()));
+    }
+}
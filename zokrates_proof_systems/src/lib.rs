@@ -1,13 +1,26 @@
 pub mod rng;
 pub mod to_token;
 
+mod proving_key;
 mod scheme;
 mod solidity;
+mod solidity_abi;
+mod solidity_gas;
 mod tagged;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod verify;
 
+pub use self::proving_key::{read_proving_key, read_proving_key_for, write_proving_key};
 pub use self::scheme::*;
 pub use self::solidity::*;
+pub use self::solidity_abi::{add_abi_wrapper, NAMED_VERIFY_FUNCTION};
+pub use self::solidity_gas::*;
 pub use tagged::{TaggedKeypair, TaggedProof, TaggedVerificationKey};
+pub use verify::{
+    verify_bytes, verify_bytes_with, BytesVerifier, Element, PointError, PointValidation,
+    VerifiableScheme, VerifyError,
+};
 
 use zokrates_ast::ir;
 
@@ -39,6 +52,15 @@ impl<T: Field, S: Scheme<T>> Proof<T, S> {
     pub fn new(proof: S::ProofPoints, inputs: Vec<String>) -> Self {
         Proof { proof, inputs }
     }
+
+    /// Re-randomize this proof using the curve arithmetic of the backend `B`, see `RerandomizationBackend`
+    pub fn rerandomize<B: RerandomizationBackend<T, S>, R: RngCore + CryptoRng>(
+        &self,
+        vk: &S::VerificationKey,
+        rng: &mut R,
+    ) -> Self {
+        B::rerandomize(vk, self, rng)
+    }
 }
 
 pub type Fr = String;
@@ -96,15 +118,45 @@ impl ToString for G2AffineFq2 {
 }
 
 pub trait Backend<T: Field, S: Scheme<T>> {
+    /// The deserialized proving key, which can be reused to generate several proofs
+    type ProvingKey: Send + Sync;
+
+    fn read_proving_key(proving_key: &[u8]) -> Result<Self::ProvingKey, String>;
+
     fn generate_proof<'a, I: IntoIterator<Item = ir::Statement<'a, T>>, R: RngCore + CryptoRng>(
         program: ir::ProgIterator<'a, T, I>,
         witness: ir::Witness<T>,
         proving_key: Vec<u8>,
         rng: &mut R,
+    ) -> Proof<T, S> {
+        let proving_key = Self::read_proving_key(&proving_key).unwrap();
+        Self::generate_proof_with_key(program, witness, &proving_key, rng)
+    }
+
+    fn generate_proof_with_key<
+        'a,
+        I: IntoIterator<Item = ir::Statement<'a, T>>,
+        R: RngCore + CryptoRng,
+    >(
+        program: ir::ProgIterator<'a, T, I>,
+        witness: ir::Witness<T>,
+        proving_key: &Self::ProvingKey,
+        rng: &mut R,
     ) -> Proof<T, S>;
 
     fn verify(vk: S::VerificationKey, proof: Proof<T, S>) -> bool;
 }
+
+/// A backend able to turn a proof into another valid proof of the same statement, which cannot be linked to the
+/// original one
+pub trait RerandomizationBackend<T: Field, S: Scheme<T>>: Backend<T, S> {
+    fn rerandomize<R: RngCore + CryptoRng>(
+        vk: &S::VerificationKey,
+        proof: &Proof<T, S>,
+        rng: &mut R,
+    ) -> Proof<T, S>;
+}
+
 pub trait NonUniversalBackend<T: Field, S: NonUniversalScheme<T>>: Backend<T, S> {
     fn setup<'a, I: IntoIterator<Item = ir::Statement<'a, T>>, R: RngCore + CryptoRng>(
         program: ir::ProgIterator<'a, T, I>,
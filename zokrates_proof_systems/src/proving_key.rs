@@ -0,0 +1,91 @@
+use std::io::{self, Read, Write};
+use zokrates_ast::ir::ProgramHash;
+
+const PROVING_KEY_MAGIC: &[u8; 4] = &[0x5a, 0x4f, 0x4b, 0x50];
+const PROVING_KEY_VERSION_1: &[u8; 4] = &[0, 0, 0, 1];
+
+/// Write a proving key, preceded by the hash of the constraint system it was generated for
+pub fn write_proving_key<W: Write>(mut w: W, pk: &[u8], hash: &ProgramHash) -> io::Result<()> {
+    w.write_all(PROVING_KEY_MAGIC)?;
+    w.write_all(PROVING_KEY_VERSION_1)?;
+    w.write_all(&hash.0)?;
+    w.write_all(pk)
+}
+
+/// Read a proving key and the hash of its constraint system.
+/// Proving keys written without a header, for example by older versions, have no hash
+pub fn read_proving_key<R: Read>(mut r: R) -> io::Result<(Vec<u8>, Option<ProgramHash>)> {
+    let mut pk = vec![];
+    r.read_to_end(&mut pk)?;
+
+    let header_length = PROVING_KEY_MAGIC.len() + PROVING_KEY_VERSION_1.len() + 32;
+
+    if pk.len() >= header_length
+        && &pk[..4] == PROVING_KEY_MAGIC
+        && &pk[4..8] == PROVING_KEY_VERSION_1
+    {
+        let mut hash = [0; 32];
+        hash.copy_from_slice(&pk[8..header_length]);
+        Ok((pk.split_off(header_length), Some(ProgramHash(hash))))
+    } else {
+        Ok((pk, None))
+    }
+}
+
+/// Read a proving key for the program with constraint system hash `expected`, failing if the key was generated for
+/// another one. Proving keys without a header are accepted as is
+pub fn read_proving_key_for<R: Read>(r: R, expected: &ProgramHash) -> Result<Vec<u8>, String> {
+    let (pk, hash) =
+        read_proving_key(r).map_err(|e| format!("Could not read proving key: {}", e))?;
+
+    match hash {
+        Some(hash) if hash != *expected => Err(format!(
+            "Constraint system hash mismatch: the proving key has hash {} but the program has hash {}",
+            hash, expected
+        )),
+        _ => Ok(pk),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let hash = ProgramHash([42; 32]);
+
+        let mut buffer = vec![];
+        write_proving_key(&mut buffer, &[1, 2, 3], &hash).unwrap();
+
+        assert_eq!(
+            read_proving_key(&buffer[..]).unwrap(),
+            (vec![1, 2, 3], Some(hash))
+        );
+    }
+
+    #[test]
+    fn legacy() {
+        assert_eq!(
+            read_proving_key(&[1, 2, 3][..]).unwrap(),
+            (vec![1, 2, 3], None)
+        );
+    }
+
+    #[test]
+    fn for_program() {
+        let hash = ProgramHash([42; 32]);
+
+        let mut buffer = vec![];
+        write_proving_key(&mut buffer, &[1, 2, 3], &hash).unwrap();
+
+        assert_eq!(read_proving_key_for(&buffer[..], &hash), Ok(vec![1, 2, 3]));
+        assert!(read_proving_key_for(&buffer[..], &ProgramHash([0; 32]))
+            .unwrap_err()
+            .starts_with("Constraint system hash mismatch"));
+        assert_eq!(
+            read_proving_key_for(&[1, 2, 3][..], &hash),
+            Ok(vec![1, 2, 3])
+        );
+    }
+}
@@ -2,10 +2,33 @@ use blake2::{Blake2b, Digest};
 use byteorder::ReadBytesExt;
 use rand_0_8::{rngs::StdRng, SeedableRng};
 
+/// The minimum number of bytes accepted by `get_rng_from_seed`
+pub const MIN_SEED_LENGTH: usize = 32;
+
+/// Build an rng which only depends on `entropy`, so that the proofs generated with it can be compared byte for byte.
+/// Anyone knowing the entropy can recover the randomness of a proof, and with it information on the witness.
 pub fn get_rng_from_entropy(entropy: &str) -> StdRng {
+    rng_from_bytes(entropy.as_bytes())
+}
+
+/// Build an rng from caller-provided random bytes, for example obtained from `crypto.getRandomValues`
+/// in a browser. Seeds shorter than `MIN_SEED_LENGTH` bytes are rejected.
+pub fn get_rng_from_seed(seed: &[u8]) -> Result<StdRng, String> {
+    if seed.len() < MIN_SEED_LENGTH {
+        return Err(format!(
+            "Entropy should be at least {} bytes long, found {}",
+            MIN_SEED_LENGTH,
+            seed.len()
+        ));
+    }
+
+    Ok(rng_from_bytes(seed))
+}
+
+fn rng_from_bytes(bytes: &[u8]) -> StdRng {
     let h = {
         let mut h = Blake2b::default();
-        h.input(&entropy.as_bytes());
+        h.input(bytes);
         h.result()
     };
 
@@ -18,3 +41,23 @@ pub fn get_rng_from_entropy(entropy: &str) -> StdRng {
 
     StdRng::from_seed(seed)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_0_8::RngCore;
+
+    #[test]
+    fn same_seed_same_rng() {
+        let seed = [42u8; MIN_SEED_LENGTH];
+        assert_eq!(
+            get_rng_from_seed(&seed).unwrap().next_u64(),
+            get_rng_from_seed(&seed).unwrap().next_u64()
+        );
+    }
+
+    #[test]
+    fn short_seed() {
+        assert!(get_rng_from_seed(&[42u8; MIN_SEED_LENGTH - 1]).is_err());
+    }
+}
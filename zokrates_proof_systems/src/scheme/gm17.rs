@@ -1,6 +1,9 @@
 use crate::scheme::{NonUniversalScheme, Scheme};
 use crate::solidity::{solidity_pairing_lib, SOLIDITY_G2_ADDITION_LIB};
-use crate::{G1Affine, G2Affine, SolidityCompatibleField, SolidityCompatibleScheme};
+use crate::{
+    Element, G1Affine, G2Affine, SolidityCompatibleField, SolidityCompatibleScheme,
+    VerifiableScheme, VerifierOperations,
+};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use zokrates_field::Field;
@@ -35,6 +38,38 @@ impl<T: Field> Scheme<T> for GM17 {
     type ProofPoints = ProofPoints<G1Affine, G2Affine>;
 }
 
+impl<T: Field> VerifiableScheme<T> for GM17 {
+    fn input_count(vk: &Self::VerificationKey) -> usize {
+        vk.query.len().saturating_sub(1)
+    }
+
+    fn verification_key_elements(vk: &Self::VerificationKey) -> Vec<(String, Element)> {
+        vec![
+            ("h".to_string(), Element::G2(&vk.h)),
+            ("g_alpha".to_string(), Element::G1(&vk.g_alpha)),
+            ("h_beta".to_string(), Element::G2(&vk.h_beta)),
+            ("g_gamma".to_string(), Element::G1(&vk.g_gamma)),
+            ("h_gamma".to_string(), Element::G2(&vk.h_gamma)),
+        ]
+        .into_iter()
+        .chain(
+            vk.query
+                .iter()
+                .enumerate()
+                .map(|(i, p)| (format!("query[{}]", i), Element::G1(p))),
+        )
+        .collect()
+    }
+
+    fn proof_elements(proof: &Self::ProofPoints) -> Vec<(String, Element)> {
+        vec![
+            ("a".to_string(), Element::G1(&proof.a)),
+            ("b".to_string(), Element::G2(&proof.b)),
+            ("c".to_string(), Element::G1(&proof.c)),
+        ]
+    }
+}
+
 impl<T: SolidityCompatibleField> SolidityCompatibleScheme<T> for GM17 {
     type Proof = Self::ProofPoints;
 
@@ -134,6 +169,21 @@ impl<T: SolidityCompatibleField> SolidityCompatibleScheme<T> for GM17 {
             SOLIDITY_G2_ADDITION_LIB, solidity_pairing_lib, template_text
         )
     }
+
+    fn verifier_operations(vk: &<GM17 as Scheme<T>>::VerificationKey) -> VerifierOperations {
+        let inputs = vk.query.len().saturating_sub(1);
+
+        VerifierOperations {
+            public_inputs: inputs,
+            // one addition and one multiplication per input, the addition of `query[0]` and of `g_alpha` to `a`
+            g1_additions: inputs + 2,
+            g1_multiplications: inputs,
+            pairing_checks: 2,
+            pairings: 6,
+            // the proof points are followed by the inputs, all of them static
+            calldata_bytes: 4 + 32 * (8 + inputs),
+        }
+    }
 }
 
 const CONTRACT_TEMPLATE: &str = r#"
@@ -1,6 +1,9 @@
 use crate::scheme::{NonUniversalScheme, Scheme};
 use crate::solidity::solidity_pairing_lib;
-use crate::{G1Affine, G2Affine, MpcScheme, SolidityCompatibleField, SolidityCompatibleScheme};
+use crate::{
+    Element, G1Affine, G2Affine, MpcScheme, SolidityCompatibleField, SolidityCompatibleScheme,
+    VerifiableScheme, VerifierOperations,
+};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use zokrates_field::Field;
@@ -15,7 +18,7 @@ pub struct ProofPoints<G1, G2> {
     pub c: G1,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct VerificationKey<G1, G2> {
     pub alpha: G1,
     pub beta: G2,
@@ -34,6 +37,37 @@ impl<T: Field> Scheme<T> for G16 {
 impl<T: Field> NonUniversalScheme<T> for G16 {}
 impl<T: Field> MpcScheme<T> for G16 {}
 
+impl<T: Field> VerifiableScheme<T> for G16 {
+    fn input_count(vk: &Self::VerificationKey) -> usize {
+        vk.gamma_abc.len().saturating_sub(1)
+    }
+
+    fn verification_key_elements(vk: &Self::VerificationKey) -> Vec<(String, Element)> {
+        vec![
+            ("alpha".to_string(), Element::G1(&vk.alpha)),
+            ("beta".to_string(), Element::G2(&vk.beta)),
+            ("gamma".to_string(), Element::G2(&vk.gamma)),
+            ("delta".to_string(), Element::G2(&vk.delta)),
+        ]
+        .into_iter()
+        .chain(
+            vk.gamma_abc
+                .iter()
+                .enumerate()
+                .map(|(i, p)| (format!("gamma_abc[{}]", i), Element::G1(p))),
+        )
+        .collect()
+    }
+
+    fn proof_elements(proof: &Self::ProofPoints) -> Vec<(String, Element)> {
+        vec![
+            ("a".to_string(), Element::G1(&proof.a)),
+            ("b".to_string(), Element::G2(&proof.b)),
+            ("c".to_string(), Element::G1(&proof.c)),
+        ]
+    }
+}
+
 impl<T: SolidityCompatibleField> SolidityCompatibleScheme<T> for G16 {
     type Proof = Self::ProofPoints;
 
@@ -128,6 +162,21 @@ impl<T: SolidityCompatibleField> SolidityCompatibleScheme<T> for G16 {
 
         format!("{}{}", solidity_pairing_lib_sans_bn256g2, template_text)
     }
+
+    fn verifier_operations(vk: &<G16 as Scheme<T>>::VerificationKey) -> VerifierOperations {
+        let inputs = vk.gamma_abc.len().saturating_sub(1);
+
+        VerifierOperations {
+            public_inputs: inputs,
+            // one addition and one multiplication per input, and the addition of `gamma_abc[0]`
+            g1_additions: inputs + 1,
+            g1_multiplications: inputs,
+            pairing_checks: 1,
+            pairings: 4,
+            // the proof points are followed by the inputs, all of them static
+            calldata_bytes: 4 + 32 * (8 + inputs),
+        }
+    }
 }
 
 const CONTRACT_TEMPLATE: &str = r#"
@@ -1,6 +1,6 @@
 use crate::scheme::{Scheme, UniversalScheme};
 use crate::solidity::{solidity_pairing_lib, SolidityCompatibleField, SolidityCompatibleScheme};
-use crate::{Fr, G1Affine, G2Affine};
+use crate::{Element, Fr, G1Affine, G2Affine, VerifiableScheme, VerifierOperations};
 use serde::{Deserialize, Serialize};
 use zokrates_field::Field;
 
@@ -87,6 +87,84 @@ impl<T: Field> Scheme<T> for Marlin {
 
 impl<T: Field> UniversalScheme<T> for Marlin {}
 
+// the elements of a list of commitments, each of them possibly shifted
+fn commitment_elements<'a>(
+    name: &str,
+    commitments: &'a [(G1Affine, Option<G1Affine>)],
+) -> Vec<(String, Element<'a>)> {
+    let mut res = vec![];
+    for (i, (c, shifted)) in commitments.iter().enumerate() {
+        res.push((format!("{}[{}]", name, i), Element::G1(c)));
+        if let Some(shifted) = shifted {
+            res.push((format!("{}[{}].shifted", name, i), Element::G1(shifted)));
+        }
+    }
+    res
+}
+
+impl<T: Field> VerifiableScheme<T> for Marlin {
+    fn input_count(vk: &Self::VerificationKey) -> usize {
+        vk.num_public_inputs
+    }
+
+    fn verification_key_elements(vk: &Self::VerificationKey) -> Vec<(String, Element)> {
+        vec![
+            (
+                "x_root_of_unity".to_string(),
+                Element::Fr(&vk.x_root_of_unity),
+            ),
+            ("vk.g".to_string(), Element::G1(&vk.vk.g)),
+            ("vk.gamma_g".to_string(), Element::G1(&vk.vk.gamma_g)),
+            ("vk.h".to_string(), Element::G2(&vk.vk.h)),
+            ("vk.beta_h".to_string(), Element::G2(&vk.vk.beta_h)),
+        ]
+        .into_iter()
+        .chain(commitment_elements("index_comms", &vk.index_comms))
+        .chain(
+            vk.degree_bounds_and_shift_powers
+                .iter()
+                .flatten()
+                .map(|(bound, p)| {
+                    (
+                        format!("degree_bounds_and_shift_powers[{}]", bound),
+                        Element::G1(p),
+                    )
+                }),
+        )
+        .collect()
+    }
+
+    fn proof_elements(proof: &Self::ProofPoints) -> Vec<(String, Element)> {
+        proof
+            .commitments
+            .iter()
+            .enumerate()
+            .flat_map(|(i, c)| commitment_elements(&format!("commitments[{}]", i), c))
+            .chain(
+                proof
+                    .evaluations
+                    .iter()
+                    .enumerate()
+                    .map(|(i, e)| (format!("evaluations[{}]", i), Element::Fr(e))),
+            )
+            .chain(vec![
+                (
+                    "pc_lc_opening_1".to_string(),
+                    Element::G1(&proof.pc_lc_opening_1),
+                ),
+                (
+                    "pc_lc_opening_1_degree".to_string(),
+                    Element::Fr(&proof.pc_lc_opening_1_degree),
+                ),
+                (
+                    "pc_lc_opening_2".to_string(),
+                    Element::G1(&proof.pc_lc_opening_2),
+                ),
+            ])
+            .collect()
+    }
+}
+
 impl<T: SolidityCompatibleField> SolidityCompatibleScheme<T> for Marlin {
     type Proof = SolidityProof<Fr, G1Affine>;
 
@@ -242,6 +320,22 @@ impl<T: SolidityCompatibleField> SolidityCompatibleScheme<T> for Marlin {
 
         format!("{}{}", solidity_pairing_lib, src)
     }
+
+    fn verifier_operations(vk: &<Marlin as Scheme<T>>::VerificationKey) -> VerifierOperations {
+        let inputs = vk.num_public_inputs;
+
+        // the commitments of the proof are combined in a fixed number of steps, whatever the program
+        VerifierOperations {
+            public_inputs: inputs,
+            g1_additions: 23,
+            g1_multiplications: 24,
+            pairing_checks: 1,
+            pairings: 2,
+            // the offset of the proof and the inputs, followed by the proof: 13 words of static members and the
+            // offsets of its arrays, then 4, 3 and 2 commitments and 5 evaluations, each array preceded by its length
+            calldata_bytes: 4 + 32 * (1 + inputs) + 32 * (13 + 9 + 7 + 5 + 6),
+        }
+    }
 }
 
 const CONTRACT_TEMPLATE: &str = r#"
@@ -1,4 +1,4 @@
-use crate::Scheme;
+use crate::{Scheme, VerifierOperations};
 use serde::{de::DeserializeOwned, Serialize};
 use zokrates_field::{Bn128Field, Field};
 
@@ -8,6 +8,9 @@ pub trait SolidityCompatibleScheme<T: SolidityCompatibleField>: Scheme<T> {
     type Proof: From<Self::ProofPoints> + Serialize + DeserializeOwned + Clone;
 
     fn export_solidity_verifier(vk: Self::VerificationKey) -> String;
+
+    /// The operations performed by `verifyTx` in the verifier exported for `vk`
+    fn verifier_operations(vk: &Self::VerificationKey) -> VerifierOperations;
 }
 
 pub const SOLIDITY_G2_ADDITION_LIB: &str = r#"// SPDX-License-Identifier: LGPL-3.0-only
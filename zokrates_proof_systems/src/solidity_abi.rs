@@ -0,0 +1,503 @@
+use zokrates_ast::typed::abi::Abi;
+use zokrates_ast::typed::ConcreteType;
+
+/// The name of the function added to the verifier, which takes the public values by name
+pub const NAMED_VERIFY_FUNCTION: &str = "verifyTxNamed";
+
+// identifiers which are already used in the verifier contracts, or are reserved in Solidity
+const RESERVED: &[&str] = &[
+    "Pairing",
+    "G1Point",
+    "G2Point",
+    "Proof",
+    "VerifyingKey",
+    "VerifierKey",
+    "Inputs",
+    "Outputs",
+    "address",
+    "bool",
+    "bytes",
+    "calldata",
+    "contract",
+    "emit",
+    "event",
+    "external",
+    "function",
+    "input",
+    "inputs",
+    "int",
+    "internal",
+    "mapping",
+    "memory",
+    "outputs",
+    "payable",
+    "proof",
+    "pure",
+    "returns",
+    "storage",
+    "string",
+    "uint",
+    "view",
+];
+
+/// Adds a function to an exported verifier which takes the public inputs and the output of the program as named,
+/// typed values and packs them into the field elements expected by `verifyTx`, in the order of the ABI encoder
+/// rearranged by the `public_layout` of the verification key, if any.
+///
+/// The verifier is returned unchanged if the program has no public values.
+pub fn add_abi_wrapper(
+    verifier: String,
+    abi: &Abi,
+    public_layout: &[usize],
+) -> Result<String, String> {
+    let wrapper = match render_wrapper(abi, public_layout)? {
+        Some(wrapper) => wrapper,
+        None => return Ok(verifier),
+    };
+
+    // the verifier contract is the last item of the file
+    let end = verifier
+        .rfind('}')
+        .ok_or_else(|| "Could not find the end of the verifier contract".to_string())?;
+
+    Ok(format!(
+        "{}{}{}",
+        &verifier[..end],
+        wrapper,
+        &verifier[end..]
+    ))
+}
+
+fn render_wrapper(abi: &Abi, public_layout: &[usize]) -> Result<Option<String>, String> {
+    let mut builder = WrapperBuilder::default();
+
+    let inputs: Vec<_> = abi
+        .public_inputs()
+        .into_iter()
+        .map(|i| (identifier(&i.name), i.ty))
+        .collect();
+
+    // a tuple output is flattened into one member per public element
+    let outputs: Vec<_> = match &abi.output {
+        ConcreteType::Tuple(t) if t.elements.is_empty() => vec![],
+        ConcreteType::Tuple(t) => t
+            .elements
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !abi.private_outputs.contains(i))
+            .map(|(i, ty)| (format!("out_{}", i), ty.clone()))
+            .collect(),
+        ty => vec![("out".to_string(), ty.clone())],
+    };
+
+    let mut parameters = vec!["Proof memory proof".to_string()];
+    let mut declarations = vec![];
+
+    for (struct_name, parameter, members) in [
+        ("Inputs", "inputs", inputs),
+        ("Outputs", "outputs", outputs),
+    ] {
+        if members.is_empty() {
+            continue;
+        }
+
+        let mut fields = vec![];
+        for (name, ty) in &members {
+            fields.push(format!("{} {};", builder.solidity_type(ty)?, name));
+            builder.pack(ty, format!("{}.{}", parameter, name))?;
+        }
+
+        declarations.push(render_struct(struct_name, &fields));
+        parameters.push(format!("{} memory {}", struct_name, parameter));
+    }
+
+    if builder.packed.is_empty() {
+        return Ok(None);
+    }
+
+    let packed = match public_layout.is_empty() {
+        true => builder.packed.clone(),
+        false => {
+            if public_layout.len() != builder.packed.len()
+                || public_layout.iter().any(|i| *i >= builder.packed.len())
+            {
+                return Err(
+                    "The public layout of the verification key does not match its ABI".to_string(),
+                );
+            }
+            public_layout
+                .iter()
+                .map(|i| builder.packed[*i].clone())
+                .collect()
+        }
+    };
+
+    let mut wrapper: String = builder
+        .structs
+        .iter()
+        .map(|(_, _, declaration)| declaration.as_str())
+        .chain(declarations.iter().map(|d| d.as_str()))
+        .collect();
+
+    wrapper.push_str(&format!(
+        "    function {}(\n            {}\n        ) public view returns (bool r) {{\n",
+        NAMED_VERIFY_FUNCTION,
+        parameters.join(", ")
+    ));
+    wrapper.push_str(&format!("        uint[{}] memory input;\n", packed.len()));
+    for (i, e) in packed.iter().enumerate() {
+        wrapper.push_str(&format!("        input[{}] = {};\n", i, e));
+    }
+    wrapper.push_str("        return verifyTx(proof, input);\n    }\n");
+
+    Ok(Some(wrapper))
+}
+
+fn render_struct(name: &str, fields: &[String]) -> String {
+    format!(
+        "    struct {} {{\n{}    }}\n",
+        name,
+        fields
+            .iter()
+            .map(|f| format!("        {}\n", f))
+            .collect::<String>()
+    )
+}
+
+// avoid clashes with the identifiers used in the contract
+fn identifier(name: &str) -> String {
+    if RESERVED.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_string()
+    }
+}
+
+#[derive(Default)]
+struct WrapperBuilder {
+    /// The struct declarations required by the public values, with the type they were generated for and their name
+    structs: Vec<(ConcreteType, String, String)>,
+    /// The expressions packing the public values into field elements, in order
+    packed: Vec<String>,
+}
+
+impl WrapperBuilder {
+    fn solidity_type(&mut self, ty: &ConcreteType) -> Result<String, String> {
+        match ty {
+            ConcreteType::FieldElement => Ok("uint256".to_string()),
+            ConcreteType::Boolean => Ok("bool".to_string()),
+            ConcreteType::Uint(bitwidth) if bitwidth.is_signed() => {
+                Ok(format!("int{}", bitwidth.to_usize()))
+            }
+            ConcreteType::Uint(bitwidth) => Ok(format!("uint{}", bitwidth.to_usize())),
+            ConcreteType::Array(array_type) => Ok(format!(
+                "{}[{}]",
+                self.solidity_type(&array_type.ty)?,
+                array_type.size
+            )),
+            ConcreteType::Struct(struct_type) => {
+                let members = struct_type
+                    .members
+                    .iter()
+                    .map(|m| (identifier(&m.id), *m.ty.clone()))
+                    .collect();
+                self.declare(ty, &struct_type.canonical_location.name, members)
+            }
+            ConcreteType::Tuple(tuple_type) => {
+                if tuple_type.elements.is_empty() {
+                    return Err("Empty tuples cannot be passed to the verifier".to_string());
+                }
+                let members = tuple_type
+                    .elements
+                    .iter()
+                    .enumerate()
+                    .map(|(i, ty)| (format!("_{}", i), ty.clone()))
+                    .collect();
+                self.declare(ty, "Tuple", members)
+            }
+            ConcreteType::Int => Err("Type `integer` cannot be passed to the verifier".to_string()),
+        }
+    }
+
+    // declare a struct for `ty` unless it was declared already, returning its name
+    fn declare(
+        &mut self,
+        ty: &ConcreteType,
+        name: &str,
+        members: Vec<(String, ConcreteType)>,
+    ) -> Result<String, String> {
+        if let Some((_, name, _)) = self.structs.iter().find(|(t, ..)| t == ty) {
+            return Ok(name.clone());
+        }
+
+        let fields = members
+            .iter()
+            .map(|(name, ty)| Ok(format!("{} {};", self.solidity_type(ty)?, name)))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        // different instances of a generic struct, or structs from different modules, can share a name
+        let name = (0..)
+            .map(|i| match i {
+                0 => identifier(name),
+                i => format!("{}_{}", name, i),
+            })
+            .find(|candidate| {
+                !RESERVED.contains(&candidate.as_str())
+                    && self.structs.iter().all(|(_, n, _)| n != candidate)
+            })
+            .unwrap();
+
+        let declaration = render_struct(&name, &fields);
+        self.structs.push((ty.clone(), name.clone(), declaration));
+        Ok(name)
+    }
+
+    fn pack(&mut self, ty: &ConcreteType, path: String) -> Result<(), String> {
+        match ty {
+            ConcreteType::FieldElement => self.packed.push(path),
+            ConcreteType::Boolean => self
+                .packed
+                .push(format!("{} ? uint256(1) : uint256(0)", path)),
+            // the circuit takes signed integers in two's complement, which is what a same width cast yields
+            ConcreteType::Uint(bitwidth) if bitwidth.is_signed() => {
+                self.packed
+                    .push(format!("uint256(uint{}({}))", bitwidth.to_usize(), path))
+            }
+            ConcreteType::Uint(_) => self.packed.push(format!("uint256({})", path)),
+            ConcreteType::Array(array_type) => {
+                for i in 0..*array_type.size {
+                    self.pack(&array_type.ty, format!("{}[{}]", path, i))?;
+                }
+            }
+            ConcreteType::Struct(struct_type) => {
+                for member in &struct_type.members {
+                    self.pack(&member.ty, format!("{}.{}", path, identifier(&member.id)))?;
+                }
+            }
+            ConcreteType::Tuple(tuple_type) => {
+                for (i, ty) in tuple_type.elements.iter().enumerate() {
+                    self.pack(ty, format!("{}._{}", path, i))?;
+                }
+            }
+            ConcreteType::Int => {
+                return Err("Type `integer` cannot be passed to the verifier".to_string())
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_abi::Encode;
+    use zokrates_ast::typed::abi::AbiInput;
+    use zokrates_ast::typed::types::{
+        ConcreteStructMember, ConcreteStructType, GTupleType, UBitwidth,
+    };
+    use zokrates_field::{Bn128Field, Field};
+
+    const VERIFIER: &str = "contract Verifier {\n}\n";
+
+    #[test]
+    fn named_u32_input_and_bool_output() {
+        let abi = Abi {
+            inputs: vec![
+                AbiInput {
+                    name: "a".into(),
+                    public: true,
+                    ty: ConcreteType::Uint(UBitwidth::B32),
+                },
+                AbiInput {
+                    name: "b".into(),
+                    public: false,
+                    ty: ConcreteType::FieldElement,
+                },
+            ],
+            output: ConcreteType::Boolean,
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
+        };
+
+        assert_eq!(
+            add_abi_wrapper(VERIFIER.into(), &abi, &[]).unwrap(),
+            "contract Verifier {
+    struct Inputs {
+        uint32 a;
+    }
+    struct Outputs {
+        bool out;
+    }
+    function verifyTxNamed(
+            Proof memory proof, Inputs memory inputs, Outputs memory outputs
+        ) public view returns (bool r) {
+        uint[2] memory input;
+        input[0] = uint256(inputs.a);
+        input[1] = outputs.out ? uint256(1) : uint256(0);
+        return verifyTx(proof, input);
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn public_layout() {
+        let abi = Abi {
+            inputs: vec![AbiInput {
+                name: "a".into(),
+                public: true,
+                ty: ConcreteType::Uint(UBitwidth::B32),
+            }],
+            output: ConcreteType::Boolean,
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
+        };
+
+        let wrapper = add_abi_wrapper(VERIFIER.into(), &abi, &[1, 0]).unwrap();
+        assert!(wrapper.contains(
+            "        input[0] = outputs.out ? uint256(1) : uint256(0);\n        input[1] = uint256(inputs.a);\n"
+        ));
+
+        assert!(add_abi_wrapper(VERIFIER.into(), &abi, &[0]).is_err());
+        assert!(add_abi_wrapper(VERIFIER.into(), &abi, &[0, 2]).is_err());
+    }
+
+    #[test]
+    fn no_public_values() {
+        let abi = Abi {
+            inputs: vec![AbiInput {
+                name: "a".into(),
+                public: false,
+                ty: ConcreteType::FieldElement,
+            }],
+            output: ConcreteType::Tuple(GTupleType::new(vec![])),
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
+        };
+
+        assert_eq!(
+            add_abi_wrapper(VERIFIER.into(), &abi, &[]).unwrap(),
+            VERIFIER
+        );
+    }
+
+    #[test]
+    fn packing_order_matches_abi_encoder() {
+        let point = ConcreteType::Struct(ConcreteStructType::new(
+            "main".into(),
+            "Point".into(),
+            vec![],
+            vec![
+                ConcreteStructMember::new("b".into(), ConcreteType::Boolean),
+                ConcreteStructMember::new(
+                    "x".into(),
+                    ConcreteType::array((ConcreteType::Uint(UBitwidth::B8), 2u32)),
+                ),
+            ],
+        ));
+
+        let abi = Abi {
+            inputs: vec![
+                AbiInput {
+                    name: "p".into(),
+                    public: true,
+                    ty: point.clone(),
+                },
+                AbiInput {
+                    name: "secret".into(),
+                    public: false,
+                    ty: ConcreteType::FieldElement,
+                },
+                AbiInput {
+                    name: "a".into(),
+                    public: true,
+                    ty: ConcreteType::Uint(UBitwidth::B32),
+                },
+            ],
+            output: ConcreteType::Tuple(GTupleType::new(vec![
+                ConcreteType::FieldElement,
+                ConcreteType::Boolean,
+            ])),
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
+        };
+
+        let values: serde_json::Value = serde_json::from_str(
+            r#"{
+                "inputs": { "p": { "b": true, "x": ["0x03", "0x04"] }, "a": "0x0000002a" },
+                "outputs": { "out_0": "0x07", "out_1": false }
+            }"#,
+        )
+        .unwrap();
+
+        // what the ABI encoder produces for the public inputs, followed by the outputs
+        let expected: Vec<Bn128Field> = zokrates_abi::parse_strict::<Bn128Field>(
+            r#"[{ "b": true, "x": ["0x03", "0x04"] }, "0x0000002a", "0x07", false]"#,
+            vec![
+                point,
+                ConcreteType::Uint(UBitwidth::B32),
+                ConcreteType::FieldElement,
+                ConcreteType::Boolean,
+            ],
+        )
+        .unwrap()
+        .encode();
+
+        // evaluate the packing expressions of the wrapper against the same values
+        let wrapper = add_abi_wrapper(VERIFIER.into(), &abi, &[]).unwrap();
+        let packed: Vec<Bn128Field> = wrapper
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("input["))
+            .map(|assignment| {
+                let expression = assignment.split(" = ").nth(1).unwrap();
+                let path = expression
+                    .trim_end_matches(';')
+                    .trim_end_matches(" ? uint256(1) : uint256(0)")
+                    .trim_start_matches("uint256(")
+                    .trim_end_matches(')');
+
+                let value = path
+                    .split(|c| c == '.' || c == '[' || c == ']')
+                    .filter(|segment| !segment.is_empty())
+                    .fold(&values, |value, segment| match segment.parse::<usize>() {
+                        Ok(index) => &value[index],
+                        Err(_) => &value[segment],
+                    });
+
+                match value {
+                    serde_json::Value::Bool(b) => Bn128Field::from(*b as u32),
+                    serde_json::Value::String(s) => {
+                        Bn128Field::try_from_str(s.trim_start_matches("0x"), 16).unwrap()
+                    }
+                    v => panic!("unexpected value {}", v),
+                }
+            })
+            .collect();
+
+        assert_eq!(packed, expected);
+        assert!(
+            wrapper.contains("    struct Point {\n        bool b;\n        uint8[2] x;\n    }\n")
+        );
+    }
+}
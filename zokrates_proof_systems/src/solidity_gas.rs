@@ -0,0 +1,271 @@
+//! Static estimation of the gas spent by an exported verifier contract
+//!
+//! The estimate only covers the calls to the elliptic curve precompiles of `alt_bn128` and the calldata of
+//! `verifyTx`, with the costs defined in EIP-1108 and EIP-2028. The base cost of the transaction and the execution of
+//! the Solidity code around the precompiles (loading the key, field arithmetic, the G2 addition of GM17) are not
+//! included.
+
+use crate::{SolidityCompatibleField, SolidityCompatibleScheme};
+use serde::Serialize;
+use std::fmt;
+use zokrates_ast::typed::abi::Abi;
+
+/// The cost of a call to the `ecAdd` precompile (EIP-1108)
+pub const EC_ADD_GAS: u64 = 150;
+/// The cost of a call to the `ecMul` precompile (EIP-1108)
+pub const EC_MUL_GAS: u64 = 6000;
+/// The base cost of a call to the `ecPairing` precompile (EIP-1108)
+pub const PAIRING_BASE_GAS: u64 = 45000;
+/// The cost of each pair checked by the `ecPairing` precompile (EIP-1108)
+pub const PAIRING_PER_PAIR_GAS: u64 = 34000;
+/// The cost of a non-zero byte of calldata (EIP-2028). All bytes are assumed to be non-zero
+pub const CALLDATA_BYTE_GAS: u64 = 16;
+/// The base cost of a call to the `sha256` precompile
+pub const SHA256_BASE_GAS: u64 = 60;
+/// The cost of each 32-byte word hashed by the `sha256` precompile
+pub const SHA256_PER_WORD_GAS: u64 = 12;
+
+/// The operations performed by the `verifyTx` function of an exported verifier
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VerifierOperations {
+    /// The number of public inputs taken by the verifier
+    pub public_inputs: usize,
+    /// The number of calls to `ecAdd`
+    pub g1_additions: usize,
+    /// The number of calls to `ecMul`
+    pub g1_multiplications: usize,
+    /// The number of calls to `ecPairing`
+    pub pairing_checks: usize,
+    /// The total number of pairs checked by the calls to `ecPairing`
+    pub pairings: usize,
+    /// The size of the ABI-encoded call to `verifyTx`
+    pub calldata_bytes: usize,
+}
+
+/// The cost of recomputing a commitment to the public inputs on chain. The verifier only checks the proof against the
+/// commitment, so this cost is paid by the caller when it hashes the committed values itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommitmentCost {
+    /// The number of field elements the commitment is computed from
+    pub committed_values: usize,
+    /// The gas spent hashing the committed values with the `sha256` precompile
+    pub hashing_gas: u64,
+    /// The size of the committed values in calldata
+    pub calldata_bytes: usize,
+}
+
+/// An estimate of the gas spent verifying a proof with an exported verifier
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VerifierCostReport {
+    pub scheme: String,
+    pub operations: VerifierOperations,
+    /// The cost of recomputing the commitment in the caller, if the public inputs are hashed. It is not included in
+    /// the gas of the verifier
+    pub commitment: Option<CommitmentCost>,
+    pub precompile_gas: u64,
+    pub calldata_gas: u64,
+    pub total_gas: u64,
+}
+
+impl VerifierCostReport {
+    /// Estimate the cost of the verifier exported for `vk`. When the public inputs of the program are hashed into a
+    /// commitment, as recorded in `abi`, the verifier only takes the commitment, and the cost of hashing the committed
+    /// values in the caller is reported separately.
+    pub fn new<T: SolidityCompatibleField, S: SolidityCompatibleScheme<T>>(
+        vk: &S::VerificationKey,
+        abi: Option<&Abi>,
+    ) -> Self {
+        let operations = S::verifier_operations(vk);
+
+        let commitment = abi.and_then(|abi| {
+            abi.commitment.as_ref().map(|commitment| {
+                let committed_values: usize = abi
+                    .inputs
+                    .iter()
+                    .filter(|i| commitment.inputs.contains(&i.name))
+                    .map(|i| i.ty.get_primitive_count())
+                    .sum();
+
+                CommitmentCost {
+                    committed_values,
+                    hashing_gas: SHA256_BASE_GAS + SHA256_PER_WORD_GAS * committed_values as u64,
+                    calldata_bytes: 32 * committed_values,
+                }
+            })
+        });
+
+        let precompile_gas = EC_ADD_GAS * operations.g1_additions as u64
+            + EC_MUL_GAS * operations.g1_multiplications as u64
+            + PAIRING_BASE_GAS * operations.pairing_checks as u64
+            + PAIRING_PER_PAIR_GAS * operations.pairings as u64;
+
+        let calldata_gas = CALLDATA_BYTE_GAS * operations.calldata_bytes as u64;
+
+        VerifierCostReport {
+            scheme: S::NAME.to_string(),
+            operations,
+            commitment,
+            precompile_gas,
+            calldata_gas,
+            total_gas: precompile_gas + calldata_gas,
+        }
+    }
+}
+
+impl fmt::Display for VerifierCostReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Estimated verification cost ({}):", self.scheme)?;
+        writeln!(f, "  public inputs: {}", self.operations.public_inputs)?;
+        writeln!(
+            f,
+            "  pairings: {} in {} check(s)",
+            self.operations.pairings, self.operations.pairing_checks
+        )?;
+        writeln!(
+            f,
+            "  G1 multiplications: {}",
+            self.operations.g1_multiplications
+        )?;
+        writeln!(f, "  G1 additions: {}", self.operations.g1_additions)?;
+        writeln!(f, "  calldata: {} bytes", self.operations.calldata_bytes)?;
+        if let Some(commitment) = &self.commitment {
+            writeln!(
+                f,
+                "  commitment (paid by the caller, not included below): {} hashed value(s), {} gas and {} bytes of calldata",
+                commitment.committed_values, commitment.hashing_gas, commitment.calldata_bytes
+            )?;
+        }
+        writeln!(f, "  precompile gas: {}", self.precompile_gas)?;
+        writeln!(f, "  calldata gas: {}", self.calldata_gas)?;
+        write!(f, "  total gas: {}", self.total_gas)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{G1Affine, G2Affine, G2AffineFq2, Scheme, G16, GM17};
+    use zokrates_ast::typed::abi::{AbiCommitment, AbiInput};
+    use zokrates_ast::typed::types::{ConcreteArrayType, ConcreteType, GTupleType};
+    use zokrates_field::Bn128Field;
+
+    fn g1() -> G1Affine {
+        G1Affine("0x0".into(), "0x0".into())
+    }
+
+    fn g2() -> G2Affine {
+        G2Affine::Fq2(G2AffineFq2(
+            ("0x0".into(), "0x0".into()),
+            ("0x0".into(), "0x0".into()),
+        ))
+    }
+
+    fn g16_vk(inputs: usize) -> <G16 as Scheme<Bn128Field>>::VerificationKey {
+        crate::groth16::VerificationKey {
+            alpha: g1(),
+            beta: g2(),
+            gamma: g2(),
+            delta: g2(),
+            gamma_abc: vec![g1(); inputs + 1],
+        }
+    }
+
+    #[test]
+    fn g16() {
+        let report = VerifierCostReport::new::<Bn128Field, G16>(&g16_vk(2), None);
+
+        assert_eq!(
+            report.operations,
+            VerifierOperations {
+                public_inputs: 2,
+                g1_additions: 3,
+                g1_multiplications: 2,
+                pairing_checks: 1,
+                pairings: 4,
+                calldata_bytes: 324,
+            }
+        );
+        assert_eq!(report.precompile_gas, 193450);
+        assert_eq!(report.calldata_gas, 5184);
+        assert_eq!(report.total_gas, 198634);
+    }
+
+    #[test]
+    fn g16_without_inputs() {
+        let vk = crate::groth16::VerificationKey {
+            gamma_abc: vec![],
+            ..g16_vk(0)
+        };
+
+        // a malformed key without `gamma_abc[0]` is counted as taking no input
+        let report = VerifierCostReport::new::<Bn128Field, G16>(&vk, None);
+        assert_eq!(report.operations.public_inputs, 0);
+    }
+
+    #[test]
+    fn gm17() {
+        let vk = crate::gm17::VerificationKey {
+            h: g2(),
+            g_alpha: g1(),
+            h_beta: g2(),
+            g_gamma: g1(),
+            h_gamma: g2(),
+            query: vec![g1(); 3],
+        };
+
+        let report = VerifierCostReport::new::<Bn128Field, GM17>(&vk, None);
+
+        assert_eq!(report.operations.g1_additions, 4);
+        assert_eq!(report.operations.pairings, 6);
+        assert_eq!(report.precompile_gas, 306600);
+        assert_eq!(report.total_gas, 311784);
+    }
+
+    #[test]
+    fn commitment() {
+        // def main(field[3] a, private field b) with `a` hashed into a single public input
+        let abi = Abi {
+            inputs: vec![
+                AbiInput {
+                    name: "a".into(),
+                    public: true,
+                    ty: ConcreteType::Array(ConcreteArrayType::new(
+                        ConcreteType::FieldElement,
+                        3u32,
+                    )),
+                },
+                AbiInput {
+                    name: "b".into(),
+                    public: false,
+                    ty: ConcreteType::FieldElement,
+                },
+            ],
+            output: ConcreteType::Tuple(GTupleType::new(vec![])),
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: Some(AbiCommitment {
+                name: "public_commitment".into(),
+                inputs: vec!["a".into()],
+            }),
+            constants: vec![],
+        };
+
+        let report = VerifierCostReport::new::<Bn128Field, G16>(&g16_vk(1), Some(&abi));
+
+        assert_eq!(
+            report.commitment,
+            Some(CommitmentCost {
+                committed_values: 3,
+                hashing_gas: 96,
+                calldata_bytes: 96,
+            })
+        );
+        // the verifier takes the commitment as its only input, and does not hash anything
+        assert_eq!(report.precompile_gas, 187300);
+        assert_eq!(report.calldata_gas, 4672);
+        assert_eq!(report.total_gas, 191972);
+    }
+}
@@ -1,4 +1,6 @@
 use serde::Serialize;
+use zokrates_ast::ir::ProgramHash;
+use zokrates_ast::typed::abi::Abi;
 use zokrates_field::Field;
 
 use super::{Fr, Scheme, SetupKeypair};
@@ -9,6 +11,18 @@ pub struct TaggedVerificationKey<T: Field, S: Scheme<T>> {
     curve: String,
     #[serde(flatten)]
     vk: S::VerificationKey,
+    /// The names and types of the public inputs and of the output of the program
+    #[serde(skip_serializing_if = "Option::is_none")]
+    abi: Option<Abi>,
+    /// The hash of the constraint system the key was generated for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hash: Option<ProgramHash>,
+    /// The order of the public inputs, see `zokrates_ast::ir::PublicLayout`. Empty for the default order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    public_layout: Vec<usize>,
+    /// The values of the inputs marked `#[bind_at_setup]`, which are fixed by this key
+    #[serde(skip_serializing_if = "serde_json::Map::is_empty")]
+    bindings: serde_json::Map<String, serde_json::Value>,
 }
 
 #[derive(Serialize)]
@@ -17,6 +31,12 @@ pub struct TaggedProof<T: Field, S: Scheme<T>> {
     curve: String,
     pub proof: S::ProofPoints,
     pub inputs: Vec<Fr>,
+    /// The hash of the constraint system the proof was generated for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<ProgramHash>,
+    /// The order of the public inputs, see `zokrates_ast::ir::PublicLayout`. Empty for the default order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub public_layout: Vec<usize>,
 }
 
 #[derive(Serialize)]
@@ -32,6 +52,19 @@ impl<T: Field, S: Scheme<T>> TaggedProof<T, S> {
             curve: T::name().to_string(),
             proof,
             inputs,
+            hash: None,
+            public_layout: vec![],
+        }
+    }
+
+    pub fn with_hash(self, hash: Option<ProgramHash>) -> Self {
+        TaggedProof { hash, ..self }
+    }
+
+    pub fn with_public_layout(self, public_layout: Vec<usize>) -> Self {
+        TaggedProof {
+            public_layout,
+            ..self
         }
     }
 }
@@ -42,8 +75,48 @@ impl<T: Field, S: Scheme<T>> TaggedVerificationKey<T, S> {
             scheme: S::NAME.to_string(),
             curve: T::name().to_string(),
             vk,
+            abi: None,
+            hash: None,
+            public_layout: vec![],
+            bindings: serde_json::Map::new(),
+        }
+    }
+
+    /// Attaches the public part of the ABI of the program, dropping its private inputs and outputs
+    pub fn with_abi(self, abi: Abi) -> Self {
+        TaggedVerificationKey {
+            abi: Some(Abi {
+                inputs: abi.public_inputs(),
+                output: abi.public_output(),
+                private_outputs: vec![],
+                no_outputs: false,
+                derived_nonces: vec![],
+                bound_at_setup: vec![],
+                bounds: Default::default(),
+                commitment: None,
+                constants: vec![],
+            }),
+            ..self
         }
     }
+
+    pub fn with_hash(self, hash: ProgramHash) -> Self {
+        TaggedVerificationKey {
+            hash: Some(hash),
+            ..self
+        }
+    }
+
+    pub fn with_public_layout(self, public_layout: Vec<usize>) -> Self {
+        TaggedVerificationKey {
+            public_layout,
+            ..self
+        }
+    }
+
+    pub fn with_bindings(self, bindings: serde_json::Map<String, serde_json::Value>) -> Self {
+        TaggedVerificationKey { bindings, ..self }
+    }
 }
 
 impl<T: Field, S: Scheme<T>> TaggedKeypair<T, S> {
@@ -0,0 +1,29 @@
+//! Module exposing the checks shared by the tests of the backends.
+//!
+//! Only available with the `testing` feature.
+
+use crate::rng::get_rng_from_entropy;
+use crate::{Backend, Scheme};
+use zokrates_ast::ir::{Prog, Witness};
+use zokrates_field::Field;
+
+/// Check that the proofs generated by the backend `B` only depend on the entropy they are generated from: the same
+/// entropy yields byte-identical proofs, and different entropies yield different proofs
+pub fn check_deterministic_proofs<T: Field, S: Scheme<T>, B: Backend<T, S>>(
+    program: Prog<T>,
+    witness: Witness<T>,
+    proving_key: Vec<u8>,
+) {
+    let prove = |entropy: &str| {
+        let proof = B::generate_proof(
+            program.clone(),
+            witness.clone(),
+            proving_key.clone(),
+            &mut get_rng_from_entropy(entropy),
+        );
+        serde_json::to_string(&proof).unwrap()
+    };
+
+    assert_eq!(prove("42"), prove("42"));
+    assert_ne!(prove("42"), prove("43"));
+}
@@ -0,0 +1,291 @@
+use crate::{Backend, Fr, G1Affine, G2Affine, Proof, Scheme};
+use std::fmt;
+use zokrates_field::Field;
+
+/// An element of a verification key or of a proof
+pub enum Element<'a> {
+    G1(&'a G1Affine),
+    G2(&'a G2Affine),
+    Fr(&'a Fr),
+}
+
+/// A scheme whose verification keys and proofs can be checked before a proof is verified
+pub trait VerifiableScheme<T: Field>: Scheme<T> {
+    /// The number of public inputs expected by `vk`
+    fn input_count(vk: &Self::VerificationKey) -> usize;
+
+    /// The elements of `vk`, each with a name to report it by
+    fn verification_key_elements(vk: &Self::VerificationKey) -> Vec<(String, Element)>;
+
+    /// The elements of `proof`, each with a name to report it by
+    fn proof_elements(proof: &Self::ProofPoints) -> Vec<(String, Element)>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointError {
+    /// A coordinate is not the canonical encoding of an element of the base field
+    NonCanonical,
+    /// The coordinates do not describe a point of the prime order subgroup of the curve
+    NotOnCurve,
+}
+
+/// A backend able to check the points of a verification key or of a proof using the arithmetic of the curve
+pub trait PointValidation<T: Field> {
+    fn check_g1(point: &G1Affine) -> Result<(), PointError>;
+    fn check_g2(point: &G2Affine) -> Result<(), PointError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The verification key or the proof could not be deserialized
+    Malformed {
+        what: &'static str,
+        reason: String,
+    },
+    /// The verification key or the proof was generated for another scheme
+    SchemeMismatch {
+        what: &'static str,
+        expected: String,
+        found: String,
+    },
+    /// The verification key or the proof was generated for another curve
+    CurveMismatch {
+        what: &'static str,
+        expected: String,
+        found: String,
+    },
+    NonCanonicalPoint {
+        name: String,
+    },
+    NotOnCurve {
+        name: String,
+    },
+    /// A scalar of the verification key or of the proof is not the canonical encoding of a field element
+    NonCanonicalScalar {
+        name: String,
+    },
+    /// A public input is not the canonical encoding of a field element
+    NonCanonicalInput {
+        index: usize,
+    },
+    InputCount {
+        expected: usize,
+        found: usize,
+    },
+    /// The backend does not support the scheme on the curve
+    Unsupported {
+        scheme: String,
+        curve: String,
+    },
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VerifyError::Malformed { what, reason } => {
+                write!(f, "Could not deserialize {}: {}", what, reason)
+            }
+            VerifyError::SchemeMismatch {
+                what,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Expected the scheme of the {} to be {}, found {}",
+                what, expected, found
+            ),
+            VerifyError::CurveMismatch {
+                what,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Expected the curve of the {} to be {}, found {}",
+                what, expected, found
+            ),
+            VerifyError::NonCanonicalPoint { name } => write!(
+                f,
+                "Point `{}` has a coordinate which is not a canonical field element",
+                name
+            ),
+            VerifyError::NotOnCurve { name } => write!(
+                f,
+                "Point `{}` is not in the prime order subgroup of the curve",
+                name
+            ),
+            VerifyError::NonCanonicalScalar { name } => {
+                write!(f, "Scalar `{}` is not a canonical field element", name)
+            }
+            VerifyError::NonCanonicalInput { index } => {
+                write!(f, "Public input {} is not a canonical field element", index)
+            }
+            VerifyError::InputCount { expected, found } => write!(
+                f,
+                "Expected {} public input{}, found {}",
+                expected,
+                if *expected == 1 { "" } else { "s" },
+                found
+            ),
+            VerifyError::Unsupported { scheme, curve } => write!(
+                f,
+                "The scheme {} is not supported on the curve {} by this backend",
+                scheme, curve
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+// parse a field element in the hexadecimal format of the proofs, rejecting values which are not reduced
+fn parse_scalar<T: Field>(value: &str) -> Option<T> {
+    T::try_from_str(value.strip_prefix("0x")?, 16).ok()
+}
+
+// check the scheme and the curve a verification key or a proof is tagged with
+fn check_tags<T: Field, S: Scheme<T>>(
+    value: &serde_json::Value,
+    what: &'static str,
+) -> Result<(), VerifyError> {
+    let tag = |key: &str| {
+        value
+            .get(key)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| VerifyError::Malformed {
+                what,
+                reason: format!("`{}` should be a string", key),
+            })
+    };
+
+    let scheme = tag("scheme")?;
+    if scheme != S::NAME {
+        return Err(VerifyError::SchemeMismatch {
+            what,
+            expected: S::NAME.to_string(),
+            found: scheme.to_string(),
+        });
+    }
+
+    let curve = tag("curve")?;
+    if curve != T::name() {
+        return Err(VerifyError::CurveMismatch {
+            what,
+            expected: T::name().to_string(),
+            found: curve.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+fn check_elements<T: Field, B: PointValidation<T>>(
+    elements: Vec<(String, Element)>,
+) -> Result<(), VerifyError> {
+    for (name, element) in elements {
+        let res = match element {
+            Element::G1(p) => B::check_g1(p),
+            Element::G2(p) => B::check_g2(p),
+            Element::Fr(s) => match parse_scalar::<T>(s) {
+                Some(_) => Ok(()),
+                None => return Err(VerifyError::NonCanonicalScalar { name }),
+            },
+        };
+
+        match res {
+            Ok(()) => {}
+            Err(PointError::NonCanonical) => return Err(VerifyError::NonCanonicalPoint { name }),
+            Err(PointError::NotOnCurve) => return Err(VerifyError::NotOnCurve { name }),
+        }
+    }
+
+    Ok(())
+}
+
+/// A backend verifying serialized proofs for the schemes and curves it supports, which are picked at runtime
+pub trait BytesVerifier {
+    /// Verify a proof with the scheme `scheme` on the curve `curve`, see `verify_bytes`
+    fn verify_bytes(
+        scheme: &str,
+        curve: &str,
+        vk: &[u8],
+        proof: &[u8],
+        public_inputs: &[Fr],
+    ) -> Result<bool, VerifyError>;
+}
+
+/// Verify a proof against a verification key, both in the JSON format written by the CLI, for the public inputs
+/// `public_inputs` given in hexadecimal.
+///
+/// The scheme and the curve are named like in the `scheme` and `curve` fields of the verification key and the proof,
+/// for example `g16` and `bn128`, so that they can be read from the artifacts. The public inputs stored in the proof
+/// are ignored. Every point is checked to be on the curve before the pairing check, so that malformed keys and proofs
+/// are reported as errors rather than accepted or rejected arbitrarily. Nothing is read from or written to the
+/// filesystem.
+pub fn verify_bytes<B: BytesVerifier>(
+    scheme: &str,
+    curve: &str,
+    vk: &[u8],
+    proof: &[u8],
+    public_inputs: &[Fr],
+) -> Result<bool, VerifyError> {
+    B::verify_bytes(scheme, curve, vk, proof, public_inputs)
+}
+
+/// Like `verify_bytes`, for a scheme and a curve known at compile time
+pub fn verify_bytes_with<
+    T: Field,
+    S: VerifiableScheme<T>,
+    B: Backend<T, S> + PointValidation<T>,
+>(
+    vk: &[u8],
+    proof: &[u8],
+    public_inputs: &[Fr],
+) -> Result<bool, VerifyError> {
+    let vk: serde_json::Value =
+        serde_json::from_slice(vk).map_err(|why| VerifyError::Malformed {
+            what: "verification key",
+            reason: why.to_string(),
+        })?;
+    let proof: serde_json::Value =
+        serde_json::from_slice(proof).map_err(|why| VerifyError::Malformed {
+            what: "proof",
+            reason: why.to_string(),
+        })?;
+
+    check_tags::<T, S>(&vk, "verification key")?;
+    check_tags::<T, S>(&proof, "proof")?;
+
+    let vk: S::VerificationKey =
+        serde_json::from_value(vk).map_err(|why| VerifyError::Malformed {
+            what: "verification key",
+            reason: why.to_string(),
+        })?;
+    let proof: Proof<T, S> =
+        serde_json::from_value(proof).map_err(|why| VerifyError::Malformed {
+            what: "proof",
+            reason: why.to_string(),
+        })?;
+
+    check_elements::<T, B>(S::verification_key_elements(&vk))?;
+    check_elements::<T, B>(S::proof_elements(&proof.proof))?;
+
+    if let Some(index) = public_inputs
+        .iter()
+        .position(|i| parse_scalar::<T>(i).is_none())
+    {
+        return Err(VerifyError::NonCanonicalInput { index });
+    }
+
+    let expected = S::input_count(&vk);
+    if public_inputs.len() != expected {
+        return Err(VerifyError::InputCount {
+            expected,
+            found: public_inputs.len(),
+        });
+    }
+
+    Ok(B::verify(
+        vk,
+        Proof::new(proof.proof, public_inputs.to_vec()),
+    ))
+}
@@ -1,17 +1,23 @@
 #[macro_use]
 extern crate serde_derive;
 
+pub mod prop;
+
 use std::fs::File;
 use std::io::BufReader;
 use std::path::{Path, PathBuf};
 
+use zokrates_ast::ir::{text::TextDisplay, Prog};
 use zokrates_ast::typed::types::GTupleType;
 use zokrates_ast::typed::ConcreteSignature;
 use zokrates_ast::typed::ConcreteType;
 use zokrates_common::CompileConfig;
 use zokrates_core::compile::compile;
 
-use zokrates_field::{Bls12_377Field, Bls12_381Field, Bn128Field, Bw6_761Field, Field};
+use zokrates_field::{
+    Bls12_377Field, Bls12_381Field, Bn128Field, Bw6_761Field, Field, GoldilocksField, PallasField,
+    VestaField,
+};
 use zokrates_fs_resolver::FileSystemResolver;
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -20,6 +26,9 @@ enum Curve {
     Bls12_381,
     Bls12_377,
     Bw6_761,
+    Pallas,
+    Vesta,
+    Goldilocks,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -28,6 +37,8 @@ struct Tests {
     pub curves: Option<Vec<Curve>>,
     pub max_constraint_count: Option<usize>,
     pub config: Option<CompileConfig>,
+    /// The warnings expected from the compilation, in order, each given by the start of its message
+    pub diagnostics: Option<Vec<String>>,
     pub abi: Option<bool>,
     pub tests: Vec<Test>,
 }
@@ -104,6 +115,9 @@ pub fn test_inner(test_path: &str) {
                     Curve::Bls12_381 => compile_and_run::<Bls12_381Field>(t.clone()),
                     Curve::Bls12_377 => compile_and_run::<Bls12_377Field>(t.clone()),
                     Curve::Bw6_761 => compile_and_run::<Bw6_761Field>(t.clone()),
+                    Curve::Pallas => compile_and_run::<PallasField>(t.clone()),
+                    Curve::Vesta => compile_and_run::<VestaField>(t.clone()),
+                    Curve::Goldilocks => compile_and_run::<GoldilocksField>(t.clone()),
                 }
             }
         })
@@ -112,6 +126,43 @@ pub fn test_inner(test_path: &str) {
         .unwrap();
 }
 
+/// Compare `program` with the golden file at `path`, if there is one. When `ZOKRATES_BLESS` is set, the golden file
+/// is overwritten instead: create an empty file to start tracking a program
+fn check_golden<T: Field>(path: &Path, program: &Prog<T>) {
+    if !path.exists() {
+        return;
+    }
+
+    let text = TextDisplay(program).to_string();
+
+    if std::env::var_os("ZOKRATES_BLESS").is_some() {
+        std::fs::write(path, text).unwrap();
+        return;
+    }
+
+    let golden = std::fs::read_to_string(path).unwrap();
+    let expected = Prog::<T>::from_text(&golden)
+        .unwrap_or_else(|e| panic!("Could not parse {}:{}", path.display(), e));
+    let expected = TextDisplay(&expected).to_string();
+
+    if let Some((line, (expected, found))) = expected
+        .lines()
+        .chain(std::iter::repeat(""))
+        .zip(text.lines().chain(std::iter::repeat("")))
+        .take(expected.lines().count().max(text.lines().count()))
+        .enumerate()
+        .find(|(_, (expected, found))| expected != found)
+    {
+        panic!(
+            "The compiled program differs from {} at line {}\nexpected: {}\nfound:    {}\nRun with `ZOKRATES_BLESS=1` to update it",
+            path.display(),
+            line + 1,
+            expected,
+            found
+        );
+    }
+}
+
 fn compile_and_run<T: Field>(t: Tests) {
     let entry_point = t.entry_point.unwrap();
 
@@ -133,6 +184,23 @@ fn compile_and_run<T: Field>(t: Tests) {
     )
     .unwrap();
 
+    if let Some(expected) = &t.diagnostics {
+        let found: Vec<_> = artifacts
+            .diagnostics()
+            .iter()
+            .map(|d| d.message.as_str())
+            .collect();
+
+        assert!(
+            found.len() == expected.len()
+                && found.iter().zip(expected).all(|(f, e)| f.starts_with(e)),
+            "{} expected the diagnostics {:?} but found {:?}",
+            entry_point.display(),
+            expected,
+            found
+        );
+    }
+
     let (bin, abi) = artifacts.into_inner();
     // here we do want the program in memory because we want to run many tests on it
     let bin = bin.collect();
@@ -150,7 +218,14 @@ fn compile_and_run<T: Field>(t: Tests) {
         );
     };
 
+    check_golden(
+        &entry_point.with_extension(format!("{}.ir", T::name())),
+        &bin,
+    );
+
     let interpreter = zokrates_interpreter::Interpreter::default();
+    let parallel_interpreter =
+        zokrates_interpreter::Interpreter::default().with_parallel_directives();
     let with_abi = t.abi.unwrap_or(true);
 
     for test in t.tests.into_iter() {
@@ -180,6 +255,14 @@ fn compile_and_run<T: Field>(t: Tests) {
 
         let output = interpreter.execute(bin.clone(), &input);
 
+        // solving the directives ahead of the execution gives the same witness
+        assert_eq!(
+            output,
+            parallel_interpreter.execute(bin.clone(), &input),
+            "{} gives a different witness when its directives are solved in parallel",
+            entry_point.display()
+        );
+
         use zokrates_abi::Decode;
 
         let output: Result<Output, zokrates_interpreter::Error> = output.map(|witness| Output {
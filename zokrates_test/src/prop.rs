@@ -0,0 +1,523 @@
+//! Property-based testing of compiled programs against a reference implementation.
+//!
+//! Inputs are drawn at random according to the ABI of the program, the program is run with the
+//! interpreter and its outputs are compared to those of the reference. When a case fails, its
+//! inputs are shrunk to a smaller failing case before being reported.
+
+use std::fmt;
+use std::path::Path;
+
+use rand_0_8::rngs::StdRng;
+use rand_0_8::{Rng, SeedableRng};
+use zokrates_abi::{Decode, Encode, Value};
+use zokrates_ast::ir::Statement;
+use zokrates_ast::typed::types::UBitwidth;
+use zokrates_ast::typed::ConcreteType;
+use zokrates_common::CompileConfig;
+use zokrates_core::compile::{compile, CompilationArtifacts};
+use zokrates_field::Field;
+use zokrates_fs_resolver::FileSystemResolver;
+use zokrates_interpreter::Interpreter;
+
+/// How random inputs are generated and failing cases shrunk
+#[derive(Debug, Clone)]
+pub struct Strategy {
+    seed: u64,
+    max_shrinks: usize,
+}
+
+impl Default for Strategy {
+    fn default() -> Self {
+        Strategy {
+            seed: 0,
+            max_shrinks: 1000,
+        }
+    }
+}
+
+impl Strategy {
+    pub fn with_seed(seed: u64) -> Self {
+        Strategy {
+            seed,
+            ..Self::default()
+        }
+    }
+
+    pub fn max_shrinks(mut self, max_shrinks: usize) -> Self {
+        self.max_shrinks = max_shrinks;
+        self
+    }
+}
+
+/// A minimal failing case
+#[derive(Debug)]
+pub struct Failure<T> {
+    /// The index of the first failing case
+    pub case: usize,
+    /// The seed of the strategy, to reproduce the failure
+    pub seed: u64,
+    /// The number of successful shrinking steps
+    pub shrinks: usize,
+    pub inputs: Vec<Value<T>>,
+    pub expected: Vec<Value<T>>,
+    pub actual: Result<Vec<Value<T>>, zokrates_interpreter::Error>,
+}
+
+fn display_values<T: Field>(values: &[Value<T>]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl<T: Field> fmt::Display for Failure<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Property failed on case {} (seed {}, shrunk {} times)",
+            self.case, self.seed, self.shrinks
+        )?;
+        writeln!(f, "Input: ({})", display_values(&self.inputs))?;
+        writeln!(f, "Expected: ({})", display_values(&self.expected))?;
+        match &self.actual {
+            Ok(values) => write!(f, "Found: ({})", display_values(values)),
+            Err(e) => write!(f, "Found error: {}", e),
+        }
+    }
+}
+
+/// Check that `reference` agrees with the program for `cases` random inputs
+///
+/// The reference receives one value per argument of `main` and returns one value per element of
+/// its return tuple, or a single value if `main` does not return a tuple.
+pub fn prop_check<'ast, T, I, R>(
+    artifacts: CompilationArtifacts<'ast, T, I>,
+    strategy: Strategy,
+    reference: R,
+    cases: usize,
+) -> Result<(), Failure<T>>
+where
+    T: Field,
+    I: IntoIterator<Item = Statement<'ast, T>>,
+    R: Fn(&[Value<T>]) -> Vec<Value<T>>,
+{
+    let (program, abi) = artifacts.into_inner();
+    let program = program.collect();
+    let signature = abi.signature();
+
+    let interpreter = Interpreter::default();
+
+    let run = |inputs: &[Value<T>]| -> Result<Vec<Value<T>>, zokrates_interpreter::Error> {
+        let encoded: Vec<T> = inputs.iter().cloned().flat_map(|v| v.encode()).collect();
+        interpreter
+            .execute(program.clone(), &encoded)
+            .map(|witness| {
+                match Value::decode(witness.return_values(), *signature.output.clone()) {
+                    Value::Tuple(elements) => elements,
+                    value => vec![value],
+                }
+            })
+    };
+
+    let check = |inputs: &[Value<T>]| -> Option<(Vec<Value<T>>, _)> {
+        let expected = reference(inputs);
+        let actual = run(inputs);
+        match &actual {
+            Ok(values) if *values == expected => None,
+            _ => Some((expected, actual)),
+        }
+    };
+
+    let mut rng = StdRng::seed_from_u64(strategy.seed);
+
+    for case in 0..cases {
+        let inputs: Vec<_> = signature
+            .inputs
+            .iter()
+            .map(|ty| arbitrary(&mut rng, ty))
+            .collect();
+
+        if let Some(mismatch) = check(&inputs) {
+            let mut inputs = inputs;
+            let (mut expected, mut actual) = mismatch;
+            let mut shrinks = 0;
+
+            // greedily move to the first smaller input which still fails, until none does
+            'shrink: while shrinks < strategy.max_shrinks {
+                for (index, ty) in signature.inputs.iter().enumerate() {
+                    for candidate in shrink(&inputs[index], ty) {
+                        let mut smaller = inputs.clone();
+                        smaller[index] = candidate;
+
+                        if let Some(mismatch) = check(&smaller) {
+                            inputs = smaller;
+                            expected = mismatch.0;
+                            actual = mismatch.1;
+                            shrinks += 1;
+                            continue 'shrink;
+                        }
+                    }
+                }
+                break;
+            }
+
+            return Err(Failure {
+                case,
+                seed: strategy.seed,
+                shrinks,
+                inputs,
+                expected,
+                actual,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Compile the program at `path` and check it with `prop_check`
+///
+/// Imports from the standard library are resolved relative to `../zokrates_stdlib/stdlib`, as in
+/// the other tests of this repository.
+pub fn check_file<T, R>(
+    path: &str,
+    strategy: Strategy,
+    reference: R,
+    cases: usize,
+) -> Result<(), Failure<T>>
+where
+    T: Field,
+    R: Fn(&[Value<T>]) -> Vec<Value<T>>,
+{
+    let code = std::fs::read_to_string(path).unwrap();
+
+    let stdlib = std::fs::canonicalize("../zokrates_stdlib/stdlib").unwrap();
+    let resolver = FileSystemResolver::with_stdlib_root(stdlib.to_str().unwrap());
+
+    let arena = typed_arena::Arena::new();
+
+    let artifacts = compile::<T, _>(
+        code,
+        Path::new(path).to_path_buf(),
+        Some(&resolver),
+        CompileConfig::default(),
+        &arena,
+    )
+    .unwrap();
+
+    prop_check(artifacts, strategy, reference, cases)
+}
+
+/// Define a `#[test]` checking the program at a path against a reference function
+///
+/// ```ignore
+/// prop_test!(add, Bn128Field, "tests/prop/add.zok", |inputs| { ... });
+/// prop_test!(add, Bn128Field, "tests/prop/add.zok", |inputs| { ... }, 500);
+/// ```
+#[macro_export]
+macro_rules! prop_test {
+    ($name:ident, $field:ty, $path:expr, $reference:expr) => {
+        $crate::prop_test!($name, $field, $path, $reference, 100);
+    };
+    ($name:ident, $field:ty, $path:expr, $reference:expr, $cases:expr) => {
+        #[test]
+        fn $name() {
+            if let Err(failure) = $crate::prop::check_file::<$field, _>(
+                $path,
+                $crate::prop::Strategy::default(),
+                $reference,
+                $cases,
+            ) {
+                panic!("{}", failure);
+            }
+        }
+    };
+}
+
+/// The value every primitive of `ty` shrinks towards
+fn default_value<T: Field>(ty: &ConcreteType) -> Value<T> {
+    match ty {
+        ConcreteType::Int => unreachable!(),
+        ConcreteType::FieldElement => Value::Field(T::zero()),
+        ConcreteType::Boolean => Value::Boolean(false),
+        ConcreteType::Uint(UBitwidth::B8) => Value::U8(0),
+        ConcreteType::Uint(UBitwidth::B16) => Value::U16(0),
+        ConcreteType::Uint(UBitwidth::B32) => Value::U32(0),
+        ConcreteType::Uint(UBitwidth::B64) => Value::U64(0),
+        ConcreteType::Uint(UBitwidth::I8) => Value::I8(0),
+        ConcreteType::Uint(UBitwidth::I16) => Value::I16(0),
+        ConcreteType::Uint(UBitwidth::I32) => Value::I32(0),
+        ConcreteType::Array(array_type) => Value::Array(vec![
+            default_value(&array_type.ty);
+            *array_type.size as usize
+        ]),
+        ConcreteType::Struct(struct_type) => Value::Struct(
+            struct_type
+                .members
+                .iter()
+                .map(|member| (member.id.clone(), default_value(&member.ty)))
+                .collect(),
+        ),
+        ConcreteType::Tuple(tuple_type) => {
+            Value::Tuple(tuple_type.elements.iter().map(default_value).collect())
+        }
+    }
+}
+
+/// Draw a uniform integer, or one of `0`, `1` and `max` one time out of eight to hit edge cases
+fn arbitrary_integer<R: Rng>(rng: &mut R, max: u64) -> u64 {
+    if rng.gen_ratio(1, 8) {
+        [0, 1, max][rng.gen_range(0..3)]
+    } else {
+        rng.gen_range(0..=max)
+    }
+}
+
+fn arbitrary_field<T: Field, R: Rng>(rng: &mut R) -> T {
+    if rng.gen_ratio(1, 8) {
+        return [T::zero(), T::one(), T::max_value()][rng.gen_range(0..3)].clone();
+    }
+
+    // accumulate more bits than the modulus has, so that the reduction is close to uniform
+    let limbs = T::get_required_bits() / 64 + 2;
+    (0..limbs).fold(T::zero(), |acc, _| {
+        acc * T::from(1u128 << 64) + T::from(rng.gen::<u64>())
+    })
+}
+
+fn arbitrary<T: Field, R: Rng>(rng: &mut R, ty: &ConcreteType) -> Value<T> {
+    match ty {
+        ConcreteType::Int => unreachable!(),
+        ConcreteType::FieldElement => Value::Field(arbitrary_field(rng)),
+        ConcreteType::Boolean => Value::Boolean(rng.gen()),
+        ConcreteType::Uint(UBitwidth::B8) => {
+            Value::U8(arbitrary_integer(rng, u8::MAX as u64) as u8)
+        }
+        ConcreteType::Uint(UBitwidth::B16) => {
+            Value::U16(arbitrary_integer(rng, u16::MAX as u64) as u16)
+        }
+        ConcreteType::Uint(UBitwidth::B32) => {
+            Value::U32(arbitrary_integer(rng, u32::MAX as u64) as u32)
+        }
+        ConcreteType::Uint(UBitwidth::B64) => Value::U64(arbitrary_integer(rng, u64::MAX)),
+        // signed integers are drawn by their two's complement representation
+        ConcreteType::Uint(UBitwidth::I8) => {
+            Value::I8(arbitrary_integer(rng, u8::MAX as u64) as u8 as i8)
+        }
+        ConcreteType::Uint(UBitwidth::I16) => {
+            Value::I16(arbitrary_integer(rng, u16::MAX as u64) as u16 as i16)
+        }
+        ConcreteType::Uint(UBitwidth::I32) => {
+            Value::I32(arbitrary_integer(rng, u32::MAX as u64) as u32 as i32)
+        }
+        ConcreteType::Array(array_type) => Value::Array(
+            (0..*array_type.size)
+                .map(|_| arbitrary(rng, &array_type.ty))
+                .collect(),
+        ),
+        ConcreteType::Struct(struct_type) => Value::Struct(
+            struct_type
+                .members
+                .iter()
+                .map(|member| (member.id.clone(), arbitrary(rng, &member.ty)))
+                .collect(),
+        ),
+        ConcreteType::Tuple(tuple_type) => Value::Tuple(
+            tuple_type
+                .elements
+                .iter()
+                .map(|ty| arbitrary(rng, ty))
+                .collect(),
+        ),
+    }
+}
+
+/// Candidates towards zero for an integer: `v - v`, `v - v / 2`, `v - v / 4`, ..., `v - 1`
+fn shrink_integer(v: u64) -> Vec<u64> {
+    let mut candidates = vec![];
+    let mut delta = v;
+    while delta > 0 {
+        candidates.push(v - delta);
+        delta /= 2;
+    }
+    candidates
+}
+
+/// Candidates towards zero for a signed integer, keeping its sign
+fn shrink_signed(v: i64) -> Vec<i64> {
+    shrink_integer(v.unsigned_abs())
+        .into_iter()
+        .map(|m| m as i64 * v.signum())
+        .collect()
+}
+
+fn shrink_field<T: Field>(v: &T) -> Vec<T> {
+    let v = v.to_biguint();
+    let mut candidates = vec![];
+    let mut delta = v.clone();
+    while delta > 0u32.into() {
+        candidates.push(T::try_from(v.clone() - &delta).unwrap());
+        delta /= 2u32;
+    }
+    candidates
+}
+
+/// Replace the element at `index` of `elements` by each of `candidates`
+fn replace_each<T: Clone>(elements: &[T], index: usize, candidates: Vec<T>) -> Vec<Vec<T>> {
+    candidates
+        .into_iter()
+        .map(|candidate| {
+            let mut elements = elements.to_vec();
+            elements[index] = candidate;
+            elements
+        })
+        .collect()
+}
+
+/// Strictly smaller values of type `ty`, most aggressive first
+fn shrink<T: Field>(value: &Value<T>, ty: &ConcreteType) -> Vec<Value<T>> {
+    if *value == default_value(ty) {
+        return vec![];
+    }
+
+    match (value, ty) {
+        (Value::Field(v), _) => shrink_field(v).into_iter().map(Value::Field).collect(),
+        (Value::Boolean(_), _) => vec![Value::Boolean(false)],
+        (Value::U8(v), _) => shrink_integer(*v as u64)
+            .into_iter()
+            .map(|v| Value::U8(v as u8))
+            .collect(),
+        (Value::U16(v), _) => shrink_integer(*v as u64)
+            .into_iter()
+            .map(|v| Value::U16(v as u16))
+            .collect(),
+        (Value::U32(v), _) => shrink_integer(*v as u64)
+            .into_iter()
+            .map(|v| Value::U32(v as u32))
+            .collect(),
+        (Value::U64(v), _) => shrink_integer(*v).into_iter().map(Value::U64).collect(),
+        (Value::I8(v), _) => shrink_signed(*v as i64)
+            .into_iter()
+            .map(|v| Value::I8(v as i8))
+            .collect(),
+        (Value::I16(v), _) => shrink_signed(*v as i64)
+            .into_iter()
+            .map(|v| Value::I16(v as i16))
+            .collect(),
+        (Value::I32(v), _) => shrink_signed(*v as i64)
+            .into_iter()
+            .map(|v| Value::I32(v as i32))
+            .collect(),
+        (Value::Array(elements), ConcreteType::Array(array_type)) => {
+            let default = default_value(&array_type.ty);
+
+            // try resetting the whole array, then each element, then shrinking each element
+            let mut candidates: Vec<_> = std::iter::once(default_value(ty))
+                .chain(elements.iter().enumerate().flat_map(|(index, element)| {
+                    let candidates = if *element == default {
+                        vec![]
+                    } else {
+                        std::iter::once(default.clone())
+                            .chain(
+                                shrink(element, &array_type.ty)
+                                    .into_iter()
+                                    .filter(|c| *c != default),
+                            )
+                            .collect()
+                    };
+                    replace_each(elements, index, candidates)
+                        .into_iter()
+                        .map(Value::Array)
+                }))
+                .collect();
+            candidates.dedup();
+            candidates
+        }
+        (Value::Struct(members), ConcreteType::Struct(struct_type)) => members
+            .iter()
+            .zip(&struct_type.members)
+            .enumerate()
+            .flat_map(|(index, ((id, member), member_type))| {
+                let candidates = shrink(member, &member_type.ty)
+                    .into_iter()
+                    .map(|c| (id.clone(), c))
+                    .collect();
+                replace_each(members, index, candidates)
+                    .into_iter()
+                    .map(Value::Struct)
+            })
+            .collect(),
+        (Value::Tuple(elements), ConcreteType::Tuple(tuple_type)) => elements
+            .iter()
+            .zip(&tuple_type.elements)
+            .enumerate()
+            .flat_map(|(index, (element, ty))| {
+                replace_each(elements, index, shrink(element, ty))
+                    .into_iter()
+                    .map(Value::Tuple)
+            })
+            .collect(),
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_ast::typed::types::ConcreteArrayType;
+    use zokrates_field::Bn128Field;
+
+    #[test]
+    fn arbitrary_respects_types() {
+        let ty = ConcreteType::Array(ConcreteArrayType::new(
+            ConcreteType::Uint(UBitwidth::B8),
+            3u32,
+        ));
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for _ in 0..100 {
+            match arbitrary::<Bn128Field, _>(&mut rng, &ty) {
+                Value::Array(elements) => {
+                    assert_eq!(elements.len(), 3);
+                    assert!(elements.iter().all(|e| matches!(e, Value::U8(_))));
+                }
+                _ => panic!("expected an array"),
+            }
+        }
+    }
+
+    #[test]
+    fn shrink_integers() {
+        assert_eq!(
+            shrink::<Bn128Field>(&Value::U32(10), &ConcreteType::Uint(UBitwidth::B32)),
+            vec![Value::U32(0), Value::U32(5), Value::U32(8), Value::U32(9)]
+        );
+        assert_eq!(
+            shrink::<Bn128Field>(&Value::U32(1), &ConcreteType::Uint(UBitwidth::B32)),
+            vec![Value::U32(0)]
+        );
+        assert!(
+            shrink::<Bn128Field>(&Value::U32(0), &ConcreteType::Uint(UBitwidth::B32)).is_empty()
+        );
+    }
+
+    #[test]
+    fn shrink_arrays_towards_defaults() {
+        let ty = ConcreteType::Array(ConcreteArrayType::new(
+            ConcreteType::Uint(UBitwidth::B8),
+            2u32,
+        ));
+
+        assert_eq!(
+            shrink::<Bn128Field>(&Value::Array(vec![Value::U8(4), Value::U8(1)]), &ty),
+            vec![
+                Value::Array(vec![Value::U8(0), Value::U8(0)]),
+                Value::Array(vec![Value::U8(0), Value::U8(1)]),
+                Value::Array(vec![Value::U8(2), Value::U8(1)]),
+                Value::Array(vec![Value::U8(3), Value::U8(1)]),
+                Value::Array(vec![Value::U8(4), Value::U8(0)]),
+            ]
+        );
+    }
+}
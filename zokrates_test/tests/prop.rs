@@ -0,0 +1,41 @@
+use zokrates_abi::Value;
+use zokrates_field::Bn128Field;
+use zokrates_test::prop::{check_file, Strategy};
+use zokrates_test::prop_test;
+
+fn wrapping_add(inputs: &[Value<Bn128Field>]) -> Vec<Value<Bn128Field>> {
+    match inputs {
+        [Value::U32(a), Value::U32(b)] => vec![Value::U32(a.wrapping_add(*b))],
+        _ => unreachable!(),
+    }
+}
+
+prop_test!(
+    add_u32,
+    Bn128Field,
+    "./tests/prop/add_u32.zok",
+    wrapping_add
+);
+
+#[test]
+fn shrinks_failing_case() {
+    // a wrong reference which saturates instead of wrapping
+    let failure = check_file::<Bn128Field, _>(
+        "./tests/prop/add_u32.zok",
+        Strategy::with_seed(1),
+        |inputs| match inputs {
+            [Value::U32(a), Value::U32(b)] => vec![Value::U32(a.saturating_add(*b))],
+            _ => unreachable!(),
+        },
+        1000,
+    )
+    .unwrap_err();
+
+    // the inputs cannot shrink further once their sum is the smallest one which overflows
+    match &failure.inputs[..] {
+        [Value::U32(a), Value::U32(b)] => {
+            assert_eq!(*a as u64 + *b as u64, 1 << 32);
+        }
+        _ => unreachable!(),
+    }
+}
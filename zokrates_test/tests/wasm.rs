@@ -19,6 +19,8 @@ fn generate_proof() {
     let program: Prog<Bn128Field> = Prog {
         arguments: vec![Parameter::public(Variable::new(0))],
         return_count: 1,
+        public_layout: vec![],
+        variable_ordering: None,
         statements: vec![Statement::constraint(Variable::new(0), Variable::new(0))],
     };
 
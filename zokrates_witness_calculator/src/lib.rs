@@ -0,0 +1,307 @@
+//! A standalone witness calculator for compiled programs.
+//!
+//! The crate compiled to `wasm32-unknown-unknown` is a template, which `zokrates export-witness-calculator` ships
+//! embedded in the binary. It exports a module made of the template and of a custom section named
+//! `zokrates_program`, which holds the length of the compiled program as a little-endian `u32`, the program, then its
+//! ABI specification. The loader written next to the module passes that section to the template, so that it computes
+//! witnesses without the compiler. The template exports:
+//!
+//! - `alloc_inputs(len) -> ptr`, which allocates `len` bytes for the arguments of the other functions
+//! - `load_program(ptr, len) -> result`, which takes ownership of the content of the `zokrates_program` section
+//! - `compute_witness(ptr, len) -> result`, which takes ownership of the inputs, given as ABI-encoded JSON, and
+//! computes the witness of the loaded program
+//! - `free_result(result)`, which releases a result buffer
+//!
+//! A result buffer is made of a little-endian `u32` status (`0` on success), a little-endian `u32` length, then as many
+//! bytes of either the output (the witness for `compute_witness`, nothing for `load_program`) or an error message.
+
+use std::sync::Mutex;
+use zokrates_abi::{parse_strict, Encode, Inputs};
+use zokrates_ast::ir::{self, ProgEnum};
+use zokrates_ast::typed::abi::Abi;
+use zokrates_field::Field;
+use zokrates_interpreter::Interpreter;
+
+/// The name of the custom section holding the program in an exported module
+pub const PROGRAM_SECTION: &str = "zokrates_program";
+
+// the content of the `zokrates_program` section, once loaded
+static PROGRAM: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+
+const RESULT_HEADER_SIZE: usize = 8;
+
+/// Compute the witness of `program` for the ABI-encoded `inputs`, returning it in the format of `zokrates
+/// compute-witness`
+pub fn compute_witness(program: &[u8], abi: &[u8], inputs: &str) -> Result<Vec<u8>, String> {
+    let abi: Abi = serde_json::from_slice(abi)
+        .map_err(|e| format!("Could not deserialize the ABI specification: {}", e))?;
+
+    match ProgEnum::deserialize(program)?.collect() {
+        ProgEnum::Bn128Program(p) => compute(p, &abi, inputs),
+        ProgEnum::Bls12_381Program(p) => compute(p, &abi, inputs),
+        ProgEnum::Bls12_377Program(p) => compute(p, &abi, inputs),
+        ProgEnum::Bw6_761Program(p) => compute(p, &abi, inputs),
+        ProgEnum::PallasProgram(p) => compute(p, &abi, inputs),
+        ProgEnum::VestaProgram(p) => compute(p, &abi, inputs),
+    }
+}
+
+/// Split the content of a `zokrates_program` section into the compiled program and its ABI specification
+pub fn split_section(section: &[u8]) -> Result<(&[u8], &[u8]), String> {
+    let malformed = || "Malformed program section".to_string();
+
+    let len = section.get(..4).ok_or_else(malformed)?;
+    let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+    let rest = &section[4..];
+
+    match len <= rest.len() {
+        true => Ok(rest.split_at(len)),
+        false => Err(malformed()),
+    }
+}
+
+/// Load the content of the `zokrates_program` section of the module, checking that it can be deserialized
+pub fn load_section(section: Vec<u8>) -> Result<(), String> {
+    let (program, abi) = split_section(&section)?;
+
+    ProgEnum::deserialize(program)?;
+    serde_json::from_slice::<Abi>(abi)
+        .map_err(|e| format!("Could not deserialize the ABI specification: {}", e))?;
+
+    *PROGRAM.lock().unwrap() = Some(section);
+
+    Ok(())
+}
+
+/// Compute the witness of the loaded program for the ABI-encoded `inputs`
+pub fn compute_loaded_witness(inputs: &str) -> Result<Vec<u8>, String> {
+    let section = PROGRAM.lock().unwrap();
+    let section = section
+        .as_ref()
+        .ok_or_else(|| "No program was loaded in this witness calculator".to_string())?;
+
+    let (program, abi) = split_section(section)?;
+
+    compute_witness(program, abi, inputs)
+}
+
+fn compute<T: Field>(program: ir::Prog<T>, abi: &Abi, inputs: &str) -> Result<Vec<u8>, String> {
+    let arguments = parse_strict(inputs, abi.signature().inputs)
+        .map(Inputs::Abi)
+        .map_err(|e| format!("Could not parse argument: {}", e))?;
+
+    // the commitment to hashed public inputs is computed from the values given by the user
+    let arguments = abi.commit(arguments.encode());
+
+    let witness = Interpreter::default()
+        .execute(program, &arguments)
+        .map_err(|e| format!("Execution failed: {}", e))?;
+
+    let mut buffer = vec![];
+    witness
+        .write(&mut buffer)
+        .map_err(|e| format!("Could not write witness: {}", e))?;
+
+    Ok(buffer)
+}
+
+// wrap the outcome of a call in a result buffer, see the crate documentation for its layout
+fn into_result(outcome: Result<Vec<u8>, String>) -> *mut u8 {
+    let (status, data) = match outcome {
+        Ok(data) => (0u32, data),
+        Err(message) => (1u32, message.into_bytes()),
+    };
+
+    let mut result = Vec::with_capacity(RESULT_HEADER_SIZE + data.len());
+    result.extend(status.to_le_bytes());
+    result.extend((data.len() as u32).to_le_bytes());
+    result.extend(data);
+
+    Box::into_raw(result.into_boxed_slice()) as *mut u8
+}
+
+/// Allocate `len` bytes for the arguments passed to `load_program` and `compute_witness`
+#[no_mangle]
+pub extern "C" fn alloc_inputs(len: usize) -> *mut u8 {
+    let mut buffer = std::mem::ManuallyDrop::new(Vec::<u8>::with_capacity(len));
+    buffer.as_mut_ptr()
+}
+
+/// Load the content of the `zokrates_program` section, see the crate documentation for the layout of the result
+///
+/// # Safety
+///
+/// `section` must have been returned by `alloc_inputs(len)`, and `len` bytes must have been written to it. The
+/// section is released by this function.
+#[no_mangle]
+pub unsafe extern "C" fn load_program(section: *mut u8, len: usize) -> *mut u8 {
+    let section = Vec::from_raw_parts(section, len, len);
+
+    into_result(load_section(section).map(|_| vec![]))
+}
+
+/// Compute the witness of the loaded program, see the crate documentation for the layout of the result
+///
+/// # Safety
+///
+/// `inputs` must have been returned by `alloc_inputs(len)`, and `len` bytes must have been written to it. The inputs
+/// are released by this function.
+#[export_name = "compute_witness"]
+pub unsafe extern "C" fn compute_witness_export(inputs: *mut u8, len: usize) -> *mut u8 {
+    let inputs = Vec::from_raw_parts(inputs, len, len);
+
+    into_result(
+        std::str::from_utf8(&inputs)
+            .map_err(|e| format!("Inputs are not valid UTF-8: {}", e))
+            .and_then(compute_loaded_witness),
+    )
+}
+
+/// Release a result returned by `load_program` or `compute_witness`
+///
+/// # Safety
+///
+/// `result` must have been returned by `load_program` or `compute_witness` and must not have been released already
+#[no_mangle]
+pub unsafe extern "C" fn free_result(result: *mut u8) {
+    let mut len = [0; 4];
+    len.copy_from_slice(std::slice::from_raw_parts(result.add(4), 4));
+    let len = RESULT_HEADER_SIZE + u32::from_le_bytes(len) as usize;
+
+    drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+        result, len,
+    )));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use zokrates_ast::ir::{Parameter, Prog, QuadComb, Statement, Variable};
+    use zokrates_ast::typed::abi::AbiInput;
+    use zokrates_ast::typed::types::ConcreteType;
+    use zokrates_field::Bn128Field;
+
+    // def main(private field x) -> field { return x * x; }
+    fn program() -> Prog<'static, Bn128Field> {
+        let x = Variable::new(0);
+
+        Prog::new(
+            vec![Parameter::private(x)],
+            vec![Statement::constraint(
+                QuadComb::from_linear_combinations(x.into(), x.into()),
+                Variable::public(0),
+            )],
+            1,
+        )
+    }
+
+    fn abi() -> Vec<u8> {
+        serde_json::to_vec(&Abi {
+            inputs: vec![AbiInput {
+                name: "x".into(),
+                public: false,
+                ty: ConcreteType::FieldElement,
+            }],
+            output: ConcreteType::FieldElement,
+            private_outputs: vec![],
+            no_outputs: false,
+            derived_nonces: vec![],
+            bound_at_setup: vec![],
+            bounds: Default::default(),
+            commitment: None,
+            constants: vec![],
+        })
+        .unwrap()
+    }
+
+    fn serialized() -> Vec<u8> {
+        let mut serialized = Cursor::new(vec![]);
+        program().serialize(&mut serialized).unwrap();
+        serialized.into_inner()
+    }
+
+    #[test]
+    fn same_witness_as_interpreter() {
+        let witness = compute_witness(&serialized(), &abi(), r#"["3"]"#).unwrap();
+
+        let mut expected = vec![];
+        Interpreter::default()
+            .execute(program(), &[Bn128Field::from(3u32)])
+            .unwrap()
+            .write(&mut expected)
+            .unwrap();
+
+        assert_eq!(witness, expected);
+    }
+
+    #[test]
+    fn invalid_inputs() {
+        assert!(compute_witness(&serialized(), &abi(), r#"["3", "4"]"#).is_err());
+    }
+
+    // the content of the `zokrates_program` section exported for `program`
+    fn section() -> Vec<u8> {
+        let program = serialized();
+        let mut section = (program.len() as u32).to_le_bytes().to_vec();
+        section.extend(program);
+        section.extend(abi());
+        section
+    }
+
+    // pass `bytes` to an export like the loader does, and read the status and the data of the result
+    unsafe fn call(
+        export: unsafe extern "C" fn(*mut u8, usize) -> *mut u8,
+        bytes: &[u8],
+    ) -> (u32, Vec<u8>) {
+        let ptr = alloc_inputs(bytes.len());
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len());
+
+        let result = export(ptr, bytes.len());
+        let header = std::slice::from_raw_parts(result, RESULT_HEADER_SIZE);
+        let status = u32::from_le_bytes(header[..4].try_into().unwrap());
+        let len = u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+        let data = std::slice::from_raw_parts(result.add(RESULT_HEADER_SIZE), len).to_vec();
+
+        free_result(result);
+
+        (status, data)
+    }
+
+    #[test]
+    fn split() {
+        let section = section();
+        let (program, abi) = split_section(&section).unwrap();
+        assert_eq!(program, serialized());
+        assert_eq!(abi, self::abi());
+
+        // the length of the program exceeds the section
+        assert!(split_section(&section[..10]).is_err());
+        assert!(split_section(&[1, 0]).is_err());
+    }
+
+    // the only test loading a program, as the loaded program is shared by the whole module
+    #[test]
+    fn result_layout() {
+        unsafe {
+            assert_eq!(
+                call(compute_witness_export, br#"["3"]"#),
+                (
+                    1,
+                    b"No program was loaded in this witness calculator".to_vec()
+                )
+            );
+
+            assert_eq!(call(load_program, &[1, 2, 3]).0, 1);
+            assert_eq!(call(load_program, &section()), (0, vec![]));
+
+            assert_eq!(
+                call(compute_witness_export, br#"["3"]"#),
+                (
+                    0,
+                    compute_witness(&serialized(), &abi(), r#"["3"]"#).unwrap()
+                )
+            );
+        }
+    }
+}